@@ -23,9 +23,79 @@ pub enum ApiError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("Config error: {0}")]
+    Config(String),
+
     #[error("Core error: {0}")]
     Core(#[from] cc_core::Error),
 }
 
+/// 受信 Webhook のエラー型
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("Webhook not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+}
+
+/// アップロードファイルのエラー型
+#[derive(Error, Debug)]
+pub enum FileError {
+    #[error("File not found: {0}")]
+    NotFound(String),
+
+    #[error("File too large: {size} bytes (max {max})")]
+    TooLarge { size: usize, max: usize },
+
+    #[error("Unsupported file type: {0}")]
+    UnsupportedType(String),
+
+    #[error("Transcription failed: {0}")]
+    Transcription(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// API キーのエラー型
+#[derive(Error, Debug)]
+pub enum KeyError {
+    #[error("API key not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// OIDC 認証のエラー型
+#[derive(Error, Debug)]
+pub enum OidcError {
+    #[error("OIDC discovery failed: {0}")]
+    Discovery(String),
+
+    #[error("JWKS error: {0}")]
+    Jwks(String),
+
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+
+    #[error("Missing claim: {0}")]
+    MissingClaim(String),
+}
+
 /// Result 型エイリアス
 pub type Result<T> = std::result::Result<T, ApiError>;
@@ -0,0 +1,358 @@
+//! Uploaded files
+//!
+//! `POST /api/files`（multipart の `file` フィールド）でファイルを保存し、ファイル ID を返します。
+//! チャットリクエストの `file_ids` に ID を指定すると、ファイルの種類に応じて
+//! メッセージに添付されます。
+//!
+//! - 画像（PNG / JPEG / GIF / WebP）: 画像としてモデルに渡します
+//! - PDF: ドキュメントブロックとしてモデルに渡します
+//! - 音声: Whisper（cc-voice）で文字起こしした内容をプロンプトに含めます
+//! - テキスト形式のファイル: 内容をプロンプトに含めます
+//!
+//! ファイルは `<dir>/<id>`、メタデータは `<dir>/<id>.json` に保存されます。
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+use utoipa::ToSchema;
+
+use cc_core::{DocumentSource, ImageSource};
+use cc_voice::WhisperClient;
+
+use crate::error::FileError;
+
+/// Default upload limit (Whisper accepts up to 25MB)
+pub const DEFAULT_MAX_FILE_BYTES: usize = 25 * 1024 * 1024;
+
+/// Claude accepts images up to 5MB
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Prefix of file IDs
+const ID_PREFIX: &str = "file_";
+
+/// Image media types the model accepts
+const IMAGE_MEDIA_TYPES: &[&str] = &[
+    ImageSource::MEDIA_TYPE_PNG,
+    ImageSource::MEDIA_TYPE_JPEG,
+    ImageSource::MEDIA_TYPE_GIF,
+    ImageSource::MEDIA_TYPE_WEBP,
+];
+
+/// How an uploaded file is passed to the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    Image,
+    Pdf,
+    Audio,
+    Text,
+}
+
+impl FileKind {
+    /// Detect the kind from the file name and media type
+    ///
+    /// Returns the kind and the media type to store (derived from the
+    /// extension when the upload has none), or None for unsupported files.
+    pub fn detect(name: &str, media_type: Option<&str>) -> Option<(Self, String)> {
+        let media_type = media_type
+            .map(|m| m.split(';').next().unwrap_or_default().trim().to_ascii_lowercase())
+            .filter(|m| !m.is_empty() && m != "application/octet-stream")
+            .or_else(|| media_type_from_extension(name).map(str::to_string));
+
+        if let Some(ref media_type) = media_type {
+            if IMAGE_MEDIA_TYPES.contains(&media_type.as_str()) {
+                return Some((Self::Image, media_type.clone()));
+            }
+            if media_type == DocumentSource::MEDIA_TYPE_PDF {
+                return Some((Self::Pdf, media_type.clone()));
+            }
+            if media_type.starts_with("audio/") {
+                return Some((Self::Audio, media_type.clone()));
+            }
+        }
+        if cc_core::document::is_text_document(name, media_type.as_deref()) {
+            return Some((Self::Text, media_type.unwrap_or_else(|| "text/plain".to_string())));
+        }
+        None
+    }
+}
+
+/// Media type of a file from its extension
+fn media_type_from_extension(name: &str) -> Option<&'static str> {
+    let (_, extension) = name.rsplit_once('.')?;
+    let media_type = match extension.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "flac" => "audio/flac",
+        "weba" => "audio/webm",
+        _ => return None,
+    };
+    Some(media_type)
+}
+
+/// Metadata of an uploaded file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FileInfo {
+    pub id: String,
+    pub name: String,
+    pub media_type: String,
+    pub kind: FileKind,
+    /// Size in bytes
+    pub size: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Uploaded files resolved for a chat message
+#[derive(Debug, Default)]
+pub struct Attachments {
+    /// Documents and transcripts to append to the message text
+    pub prompts: Vec<String>,
+    pub images: Vec<ImageSource>,
+    pub documents: Vec<DocumentSource>,
+}
+
+impl Attachments {
+    /// The message text followed by the attached text
+    pub fn text(&self, message: &str) -> String {
+        std::iter::once(message.trim())
+            .chain(self.prompts.iter().map(String::as_str))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Uploaded file storage
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+    max_bytes: usize,
+}
+
+impl FileStore {
+    /// Default storage directory
+    pub const DEFAULT_DIR: &'static str = "data/files";
+
+    /// Open (and create) the storage directory
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, FileError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            max_bytes: DEFAULT_MAX_FILE_BYTES,
+        })
+    }
+
+    /// Set the upload limit in bytes
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Upload limit in bytes
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Check the size and type of an upload
+    pub fn validate(&self, name: &str, media_type: Option<&str>, bytes: &[u8]) -> Result<(FileKind, String), FileError> {
+        if bytes.len() > self.max_bytes {
+            return Err(FileError::TooLarge {
+                size: bytes.len(),
+                max: self.max_bytes,
+            });
+        }
+        let Some((kind, media_type)) = FileKind::detect(name, media_type) else {
+            return Err(FileError::UnsupportedType(
+                media_type.map(str::to_string).unwrap_or_else(|| name.to_string()),
+            ));
+        };
+        match kind {
+            FileKind::Image if bytes.len() > MAX_IMAGE_BYTES => Err(FileError::TooLarge {
+                size: bytes.len(),
+                max: MAX_IMAGE_BYTES,
+            }),
+            FileKind::Text if std::str::from_utf8(bytes).is_err() => {
+                Err(FileError::UnsupportedType(format!("{} is not UTF-8 text", name)))
+            }
+            _ => Ok((kind, media_type)),
+        }
+    }
+
+    /// Store a file, returning its metadata
+    pub async fn store(&self, name: &str, media_type: Option<&str>, bytes: &[u8]) -> Result<FileInfo, FileError> {
+        let (kind, media_type) = self.validate(name, media_type, bytes)?;
+        let info = FileInfo {
+            id: format!("{}{}", ID_PREFIX, uuid::Uuid::new_v4().simple()),
+            name: name.to_string(),
+            media_type,
+            kind,
+            size: bytes.len(),
+            created_at: Utc::now(),
+        };
+
+        tokio::fs::write(self.dir.join(&info.id), bytes).await?;
+        tokio::fs::write(self.meta_path(&info.id), serde_json::to_vec(&info)?).await?;
+        info!("Stored file {} ({}, {} bytes)", info.id, info.media_type, info.size);
+        Ok(info)
+    }
+
+    /// Metadata of a file
+    pub async fn info(&self, id: &str) -> Result<FileInfo, FileError> {
+        if !is_valid_id(id) {
+            return Err(FileError::NotFound(id.to_string()));
+        }
+        match tokio::fs::read(self.meta_path(id)).await {
+            Ok(meta) => Ok(serde_json::from_slice(&meta)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(FileError::NotFound(id.to_string())),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Metadata and contents of a file
+    pub async fn read(&self, id: &str) -> Result<(FileInfo, Vec<u8>), FileError> {
+        let info = self.info(id).await?;
+        let bytes = tokio::fs::read(self.dir.join(id)).await?;
+        Ok((info, bytes))
+    }
+
+    /// Delete a file
+    pub async fn delete(&self, id: &str) -> Result<(), FileError> {
+        self.info(id).await?;
+        tokio::fs::remove_file(self.dir.join(id)).await?;
+        tokio::fs::remove_file(self.meta_path(id)).await?;
+        info!("Deleted file {}", id);
+        Ok(())
+    }
+
+    /// Resolve files for a chat message
+    ///
+    /// Audio files need a transcriber.
+    pub async fn attachments(
+        &self,
+        ids: &[String],
+        transcriber: Option<&WhisperClient>,
+    ) -> Result<Attachments, FileError> {
+        let mut attachments = Attachments::default();
+        for id in ids {
+            let (info, bytes) = self.read(id).await?;
+            debug!("Attaching {} ({:?})", info.id, info.kind);
+            match info.kind {
+                FileKind::Image => attachments.images.push(ImageSource::from_bytes(&info.media_type, &bytes)),
+                FileKind::Pdf => attachments.documents.push(DocumentSource::pdf(&bytes)),
+                FileKind::Audio => {
+                    let Some(transcriber) = transcriber else {
+                        return Err(FileError::UnsupportedType(
+                            "audio (transcription is not configured)".to_string(),
+                        ));
+                    };
+                    let transcript = transcriber
+                        .transcribe_text(&bytes, &info.name)
+                        .await
+                        .map_err(|e| FileError::Transcription(e.to_string()))?;
+                    attachments
+                        .prompts
+                        .push(format!("[Audio: {}]\n{}", info.name, transcript.trim()));
+                }
+                FileKind::Text => {
+                    let document = cc_core::extract_text(&info.name, Some(&info.media_type), &bytes)
+                        .ok_or_else(|| FileError::UnsupportedType(info.media_type.clone()))?;
+                    attachments.prompts.push(document.to_prompt());
+                }
+            }
+        }
+        Ok(attachments)
+    }
+
+    fn meta_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+/// Whether `id` looks like an ID issued by [`FileStore::store`] (no path
+/// separators or dots)
+fn is_valid_id(id: &str) -> bool {
+    id.strip_prefix(ID_PREFIX)
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_kind() {
+        assert_eq!(FileKind::detect("photo.JPG", None), Some((FileKind::Image, "image/jpeg".to_string())));
+        assert_eq!(
+            FileKind::detect("scan", Some("application/pdf")),
+            Some((FileKind::Pdf, "application/pdf".to_string()))
+        );
+        assert_eq!(
+            FileKind::detect("memo.m4a", Some("application/octet-stream")),
+            Some((FileKind::Audio, "audio/mp4".to_string()))
+        );
+        assert_eq!(
+            FileKind::detect("notes.md", None),
+            Some((FileKind::Text, "text/plain".to_string()))
+        );
+        assert_eq!(FileKind::detect("photo.bmp", Some("image/bmp")), None);
+        assert_eq!(FileKind::detect("archive.zip", None), None);
+    }
+
+    #[tokio::test]
+    async fn test_store_and_attach() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::open(dir.path()).unwrap().with_max_bytes(1024);
+
+        let notes = store.store("notes.txt", Some("text/plain"), b"meeting at 10").await.unwrap();
+        let image = store.store("dot.png", None, &[0x89, b'P', b'N', b'G']).await.unwrap();
+        let pdf = store.store("report.pdf", None, b"%PDF-1.7").await.unwrap();
+        assert_eq!(store.info(&notes.id).await.unwrap(), notes);
+
+        let attachments = store
+            .attachments(&[notes.id.clone(), image.id.clone(), pdf.id.clone()], None)
+            .await
+            .unwrap();
+        assert_eq!(attachments.images.len(), 1);
+        assert_eq!(attachments.documents.len(), 1);
+        assert!(attachments.text("Summarize").starts_with("Summarize\n\n[File: notes.txt]"));
+
+        let audio = store.store("memo.mp3", None, b"ID3").await.unwrap();
+        assert!(matches!(
+            store.attachments(&[audio.id], None).await,
+            Err(FileError::UnsupportedType(_))
+        ));
+
+        store.delete(&notes.id).await.unwrap();
+        assert!(matches!(store.info(&notes.id).await, Err(FileError::NotFound(_))));
+        assert!(matches!(store.info("../etc/passwd").await, Err(FileError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::open(dir.path()).unwrap().with_max_bytes(8);
+
+        assert!(matches!(
+            store.store("big.txt", None, b"123456789").await,
+            Err(FileError::TooLarge { size: 9, max: 8 })
+        ));
+        assert!(matches!(
+            store.store("tool.exe", None, b"MZ").await,
+            Err(FileError::UnsupportedType(_))
+        ));
+        assert!(matches!(
+            store.store("data.txt", None, &[0xff, 0xfe]).await,
+            Err(FileError::UnsupportedType(_))
+        ));
+    }
+}
@@ -3,15 +3,27 @@
 //! Request handlers for Claude API and session management.
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Json,
+    body::Bytes,
+    extract::{Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+use utoipa::{IntoParams, ToSchema};
 
+use cc_core::agents::{AgentHealth, CALLBACK_URL_KEY, QueuedTask, SubAgentTask, TaskId, TaskPriority, TaskQueue, TaskStatus};
 use cc_core::llm::{Message, MessageContent, MessagesRequest};
 use cc_core::session::Session;
+use cc_core::audit::{AuditEventType, AuditLevel};
+use cc_core::{PolicyDecision, PromptContext, PurgeReport};
+use cc_schedule::{RunRecord, ScheduleError, ScheduleTask, ScheduledTaskInfo, SchedulerHandle};
+use cc_workflow::{WorkflowEngine, WorkflowRun};
+use crate::error::{FileError, HookError, KeyError};
+use crate::files::{FileInfo, FileKind, FileStore};
+use crate::health::{readiness, Readiness, ReadinessReport};
+use crate::hooks::HookAccepted;
+use crate::keys::{ApiKeyInfo, ApiKeyStore, NewApiKey, STATIC_KEY_ID};
 use crate::server::AppState;
 
 // ============================================================================
@@ -19,7 +31,7 @@ use crate::server::AppState;
 // ============================================================================
 
 /// Chat request payload
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ChatRequest {
     /// User message
     pub message: String,
@@ -27,24 +39,48 @@ pub struct ChatRequest {
     pub session_id: Option<String>,
     /// System prompt override
     pub system: Option<String>,
+    /// Persona to use (stored on the session for subsequent requests)
+    pub persona: Option<String>,
     /// Max tokens
     #[serde(default = "default_max_tokens")]
     pub max_tokens: u64,
+    /// Uploaded files to attach (IDs from `POST /api/files`)
+    #[serde(default)]
+    pub file_ids: Vec<String>,
+    /// Your ID for the end user; with the memorizer enabled, facts about
+    /// them are remembered and used in later requests. Issued keys only see
+    /// their own users (OIDC callers are always their own user).
+    pub user: Option<String>,
 }
 
 fn default_max_tokens() -> u64 {
     4096
 }
 
+/// API user the memorizer remembers facts about
+///
+/// The shared key (and requests without authentication) may name any user.
+/// Users named by issued keys are kept under the key ID so that one key
+/// cannot read or change the memories of another's users, and OIDC callers
+/// are always the authenticated user.
+fn memory_user(caller: Option<&ApiKeyInfo>, user: Option<&str>) -> Option<String> {
+    match caller {
+        None => user.map(str::to_string),
+        Some(key) if key.id == STATIC_KEY_ID => user.map(str::to_string),
+        Some(key) if key.id.starts_with(crate::oidc::USER_ID_PREFIX) => Some(key.id.clone()),
+        Some(key) => user.map(|user| format!("{}:{}", key.id, user)),
+    }
+}
+
 /// Token usage information
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TokenUsage {
     pub input_tokens: u64,
     pub output_tokens: u64,
 }
 
 /// Chat response payload
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ChatResponse {
     /// Claude's response text
     pub response: String,
@@ -52,10 +88,12 @@ pub struct ChatResponse {
     pub session_id: String,
     /// Token usage
     pub tokens_used: Option<TokenUsage>,
+    /// Persona used for this response
+    pub persona: Option<String>,
 }
 
 /// Session info response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SessionInfoResponse {
     pub session_id: String,
     pub message_count: usize,
@@ -63,21 +101,21 @@ pub struct SessionInfoResponse {
 }
 
 /// Memory request payload
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct MemoryRequest {
     pub key: String,
     pub value: String,
 }
 
 /// Memory response payload
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MemoryResponse {
     pub success: bool,
     pub message: String,
 }
 
 /// Generic API error response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
 }
@@ -87,33 +125,173 @@ pub struct ErrorResponse {
 // ============================================================================
 
 /// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "The gateway is running", body = String),
+    ),
+    security(()),
+)]
 pub async fn health() -> &'static str {
     "OK"
 }
 
+/// Liveness probe response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LivenessResponse {
+    pub status: &'static str,
+    pub version: &'static str,
+}
+
+/// Liveness probe - the process is up and serving requests
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses(
+        (status = 200, description = "The gateway is alive", body = LivenessResponse),
+    ),
+    security(()),
+)]
+pub async fn healthz() -> Json<LivenessResponse> {
+    Json(LivenessResponse {
+        status: "ok",
+        version: env!("CARGO_PKG_VERSION"),
+    })
+}
+
+/// Readiness probe - checks the database, LLM API, MCP servers and channels
+///
+/// Returns 503 when a critical dependency (database, LLM API) fails. Other
+/// failures are reported as `degraded` with 200.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready or degraded", body = ReadinessReport),
+        (status = 503, description = "A critical dependency is failing", body = ReadinessReport),
+    ),
+    security(()),
+)]
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<ReadinessReport>) {
+    let report = readiness(&state.health_checks).await;
+    let status = if report.status == Readiness::NotReady {
+        warn!("Readiness check failed: {:?}", report.checks);
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(report))
+}
+
 /// Chat endpoint - send message to Claude
+#[utoipa::path(
+    post,
+    path = "/api/chat",
+    tag = "chat",
+    request_body = ChatRequest,
+    responses(
+        (status = 200, description = "Claude's response", body = ChatResponse),
+        (status = 400, description = "Unknown persona or invalid file", body = ErrorResponse),
+        (status = 500, description = "Claude API error", body = ErrorResponse),
+    ),
+)]
 pub async fn chat(
     State(state): State<AppState>,
+    caller: Option<Extension<ApiKeyInfo>>,
     Json(req): Json<ChatRequest>,
 ) -> Result<Json<ChatResponse>, (StatusCode, Json<ErrorResponse>)> {
     debug!("Chat request: {:?}", req);
 
+    let has_session = req.session_id.is_some();
     let session_id = req.session_id.unwrap_or_else(|| {
         uuid::Uuid::new_v4().to_string()
     });
 
+    // Resolve persona: request > stored session selection > "api" channel binding > default
+    let selected_persona = match req.persona {
+        Some(name) => {
+            if state.personas.get(&name).is_none() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Unknown persona: {}", name),
+                    }),
+                ));
+            }
+            if let Err(e) = state
+                .session_manager
+                .set_persona(&session_id, Some(name.clone()))
+                .await
+            {
+                error!("Failed to store persona for session {}: {}", session_id, e);
+            }
+            Some(name)
+        }
+        None if has_session => state
+            .session_manager
+            .get_persona(&session_id)
+            .await
+            .unwrap_or_default(),
+        None => None,
+    };
+    let persona = state
+        .personas
+        .resolve(selected_persona.as_deref(), None, Some("api"));
+
     // Get the model from client
     let model = state.claude_client.model().to_string();
 
+    let message = user_message(&state, &req.message, &req.file_ids).await?;
+
+    // Fall back to the "channels/api" or "default" prompt template
+    let system = req.system.clone().or_else(|| {
+        let context = PromptContext::new()
+            .channel("api")
+            .var("session_id", &session_id);
+        state
+            .prompts
+            .as_ref()?
+            .render_first(&["channels/api", "default"], &context)
+    });
+
     // Build the messages request
-    let messages_request = MessagesRequest {
+    let mut messages_request = MessagesRequest {
         model,
         max_tokens: req.max_tokens,
-        system: req.system,
-        messages: vec![Message::user(&req.message)],
+        system,
+        messages: vec![message],
         tools: None,
         thinking: None,
+        tool_choice: None,
+        temperature: None,
+    };
+
+    // An explicit system prompt in the request wins over the persona's
+    if let Some(persona) = persona {
+        persona.apply(&mut messages_request);
+        if let Some(system) = req.system {
+            messages_request.system = Some(system);
+        }
+    }
+
+    // Facts remembered about the user from earlier requests
+    let user = memory_user(caller.as_ref().map(|Extension(key)| key), req.user.as_deref());
+    let identity = match (&state.memorizer, &user) {
+        (Some(memorizer), Some(user)) => Some(memorizer.identity("api", user)),
+        _ => None,
     };
+    if let (Some(memorizer), Some(identity)) = (&state.memorizer, &identity) {
+        memorizer.inject(&mut messages_request, identity, &req.message).await;
+    }
+
+    // Passages of the knowledge base relevant to the message
+    if let Some(context) = &state.context {
+        cc_core::inject_context(context.as_ref(), &mut messages_request, &req.message).await;
+    }
 
     // Call Claude API
     match state.claude_client.messages(messages_request).await {
@@ -138,10 +316,15 @@ pub async fn chat(
 
             info!("Chat response: {} tokens", response_text.len());
 
+            if let (Some(memorizer), Some(identity)) = (&state.memorizer, identity) {
+                memorizer.learn(identity, req.message, response_text.clone());
+            }
+
             Ok(Json(ChatResponse {
                 response: response_text,
                 session_id,
                 tokens_used,
+                persona: persona.map(|p| p.name.clone()),
             }))
         }
         Err(e) => {
@@ -156,7 +339,147 @@ pub async fn chat(
     }
 }
 
+/// The user message of a chat request, with its uploaded files
+///
+/// Documents and images come before the text, as Claude recommends.
+async fn user_message(
+    state: &AppState,
+    text: &str,
+    file_ids: &[String],
+) -> Result<Message, FileApiError> {
+    if file_ids.is_empty() {
+        return Ok(Message::user(text));
+    }
+
+    let attachments = file_store(state)?
+        .attachments(file_ids, state.transcriber.as_deref())
+        .await
+        .map_err(file_error)?;
+    let mut content: Vec<MessageContent> = attachments
+        .documents
+        .iter()
+        .cloned()
+        .map(|source| MessageContent::Document { source })
+        .collect();
+    content.extend(
+        attachments
+            .images
+            .iter()
+            .cloned()
+            .map(|source| MessageContent::Image { source }),
+    );
+    content.push(MessageContent::Text {
+        text: attachments.text(text),
+    });
+
+    Ok(Message {
+        role: "user".to_string(),
+        content,
+    })
+}
+
+// ============================================================================
+// Token counting API
+// ============================================================================
+
+/// Token count request payload
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CountTokensRequest {
+    /// Single user message (shorthand for `messages`)
+    pub message: Option<String>,
+    /// Full conversation to count
+    #[serde(default)]
+    #[schema(value_type = Vec<Object>)]
+    pub messages: Vec<Message>,
+    /// System prompt
+    pub system: Option<String>,
+    /// Include the registered tool definitions in the count
+    #[serde(default)]
+    pub include_tools: bool,
+}
+
+/// Token count response payload
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CountTokensResponse {
+    pub input_tokens: u64,
+    /// True when the count is a local estimate
+    pub estimated: bool,
+}
+
+/// Count input tokens for a prospective request
+#[utoipa::path(
+    post,
+    path = "/api/tokens/count",
+    tag = "chat",
+    request_body = CountTokensRequest,
+    responses(
+        (status = 200, description = "Input token count", body = CountTokensResponse),
+        (status = 400, description = "Neither message nor messages given", body = ErrorResponse),
+        (status = 500, description = "Token counting failed", body = ErrorResponse),
+    ),
+)]
+pub async fn count_tokens(
+    State(state): State<AppState>,
+    Json(req): Json<CountTokensRequest>,
+) -> Result<Json<CountTokensResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut messages = req.messages;
+    if let Some(message) = req.message {
+        messages.push(Message::user(message));
+    }
+
+    if messages.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Either message or messages is required".to_string(),
+            }),
+        ));
+    }
+
+    let tools = if req.include_tools {
+        Some(state.tool_manager.definitions())
+    } else {
+        None
+    };
+
+    let request = MessagesRequest {
+        model: state.claude_client.model().to_string(),
+        max_tokens: default_max_tokens(),
+        system: req.system,
+        messages,
+        tools,
+        thinking: None,
+        tool_choice: None,
+        temperature: None,
+    };
+
+    match state.claude_client.count_tokens(&request).await {
+        Ok(count) => Ok(Json(CountTokensResponse {
+            input_tokens: count.input_tokens,
+            estimated: count.estimated,
+        })),
+        Err(e) => {
+            error!("Token counting failed: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Token counting failed: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
 /// Get session info
+#[utoipa::path(
+    get,
+    path = "/api/session/{session_id}",
+    tag = "sessions",
+    params(("session_id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Session info", body = SessionInfoResponse),
+    ),
+)]
 pub async fn session_info(
     Path(session_id): Path<String>,
 ) -> Result<Json<SessionInfoResponse>, (StatusCode, Json<ErrorResponse>)> {
@@ -171,6 +494,15 @@ pub async fn session_info(
 }
 
 /// Clear session
+#[utoipa::path(
+    delete,
+    path = "/api/session/{session_id}",
+    tag = "sessions",
+    params(("session_id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 204, description = "Session cleared"),
+    ),
+)]
 pub async fn clear_session(
     Path(session_id): Path<String>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
@@ -179,6 +511,15 @@ pub async fn clear_session(
 }
 
 /// Memory endpoint - save/recall memory
+#[utoipa::path(
+    post,
+    path = "/api/memory",
+    tag = "chat",
+    request_body = MemoryRequest,
+    responses(
+        (status = 200, description = "Memory saved", body = MemoryResponse),
+    ),
+)]
 pub async fn memory(
     Json(req): Json<MemoryRequest>,
 ) -> Result<Json<MemoryResponse>, (StatusCode, Json<ErrorResponse>)> {
@@ -196,35 +537,73 @@ pub async fn memory(
 // ============================================================================
 
 /// Create session request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateSessionRequest {
     /// Channel ID for the session
     pub channel_id: String,
 }
 
 /// Session detail response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SessionDetailResponse {
     pub id: String,
+    /// Pass as `session_id` to `/api/chat` to continue the session
     pub channel_id: String,
+    pub title: Option<String>,
+    pub persona: Option<String>,
     pub message_count: usize,
     pub created_at: String,
     pub updated_at: String,
 }
 
 /// Sessions list response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SessionsListResponse {
     pub sessions: Vec<SessionDetailResponse>,
+    /// Number of stored sessions (regardless of `limit` / `offset`)
     pub total: usize,
 }
 
+/// Sessions list query
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SessionsListQuery {
+    /// Maximum number of sessions to return (default: 50)
+    pub limit: Option<usize>,
+    /// Number of sessions to skip
+    pub offset: Option<usize>,
+}
+
+/// Session transcript response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionTranscriptResponse {
+    pub id: String,
+    #[schema(value_type = Vec<Object>)]
+    pub messages: Vec<Message>,
+}
+
+/// Rename session request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RenameSessionRequest {
+    /// New title (null removes it)
+    pub title: Option<String>,
+}
+
+/// Fork session request
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ForkSessionRequest {
+    /// Keep only the first N messages (default: all)
+    pub message_count: Option<usize>,
+}
+
 impl From<Session> for SessionDetailResponse {
     fn from(session: Session) -> Self {
         let message_count = session.message_count();
         Self {
             id: session.id.clone(),
             channel_id: session.channel_id.clone(),
+            title: session.title.clone(),
+            persona: session.persona.clone(),
             message_count,
             created_at: session.created_at.to_rfc3339(),
             updated_at: session.updated_at.to_rfc3339(),
@@ -232,101 +611,219 @@ impl From<Session> for SessionDetailResponse {
     }
 }
 
+type SessionApiError = (StatusCode, Json<ErrorResponse>);
+
+fn session_error(e: cc_core::Error) -> SessionApiError {
+    let status = match &e {
+        cc_core::Error::SessionNotFound(_) => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    if status == StatusCode::INTERNAL_SERVER_ERROR {
+        error!("Session error: {}", e);
+    }
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
+
+fn session_not_found(id: &str) -> SessionApiError {
+    session_error(cc_core::Error::SessionNotFound(id.to_string()))
+}
+
 /// Create a new session
+#[utoipa::path(
+    post,
+    path = "/api/sessions",
+    tag = "sessions",
+    request_body = CreateSessionRequest,
+    responses(
+        (status = 201, description = "Session created", body = SessionDetailResponse),
+    ),
+)]
 pub async fn create_session(
     State(state): State<AppState>,
     Json(req): Json<CreateSessionRequest>,
-) -> Result<Json<SessionDetailResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(StatusCode, Json<SessionDetailResponse>), SessionApiError> {
     debug!("Create session request: channel_id={}", req.channel_id);
 
-    match state.session_manager.get_or_create(&req.channel_id).await {
-        Ok(session) => {
-            info!("Created session: {} for channel: {}", session.id, req.channel_id);
-            Ok(Json(SessionDetailResponse::from(session)))
-        }
-        Err(e) => {
-            error!("Failed to create session: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: format!("Failed to create session: {}", e),
-                }),
-            ))
-        }
-    }
+    let session = state
+        .session_manager
+        .get_or_create(&req.channel_id)
+        .await
+        .map_err(session_error)?;
+    info!("Created session: {} for channel: {}", session.id, req.channel_id);
+    Ok((StatusCode::CREATED, Json(SessionDetailResponse::from(session))))
 }
 
 /// Get session by ID
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{id}",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Session", body = SessionDetailResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
 pub async fn get_session(
     State(state): State<AppState>,
     Path(session_id): Path<String>,
-) -> Result<Json<SessionDetailResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<SessionDetailResponse>, SessionApiError> {
     debug!("Get session request: id={}", session_id);
 
-    // SessionManager の公開メソッドを使用
-    match state.session_manager.get_cached_session(&session_id).await {
-        Some(session) => Ok(Json(SessionDetailResponse::from(session))),
-        None => Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Session not found: {}", session_id),
-            }),
-        )),
-    }
+    state
+        .session_manager
+        .get_session(&session_id)
+        .await
+        .map_err(session_error)?
+        .map(|session| Json(SessionDetailResponse::from(session)))
+        .ok_or_else(|| session_not_found(&session_id))
+}
+
+/// Get the messages of a session
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{id}/messages",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Session transcript", body = SessionTranscriptResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+pub async fn session_messages(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<SessionTranscriptResponse>, SessionApiError> {
+    debug!("Session transcript request: id={}", session_id);
+
+    let session = state
+        .session_manager
+        .get_session(&session_id)
+        .await
+        .map_err(session_error)?
+        .ok_or_else(|| session_not_found(&session_id))?;
+    Ok(Json(SessionTranscriptResponse {
+        id: session.id,
+        messages: session.messages,
+    }))
+}
+
+/// Rename a session
+#[utoipa::path(
+    patch,
+    path = "/api/sessions/{id}",
+    tag = "sessions",
+    request_body = RenameSessionRequest,
+    params(("id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Renamed session", body = SessionDetailResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+pub async fn rename_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(req): Json<RenameSessionRequest>,
+) -> Result<Json<SessionDetailResponse>, SessionApiError> {
+    let title = req.title.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    info!("Rename session request: id={} title={:?}", session_id, title);
+
+    let session = state
+        .session_manager
+        .rename_session(&session_id, title)
+        .await
+        .map_err(session_error)?;
+    Ok(Json(SessionDetailResponse::from(session)))
+}
+
+/// Fork a session into a new one
+#[utoipa::path(
+    post,
+    path = "/api/sessions/{id}/fork",
+    tag = "sessions",
+    request_body = Option<ForkSessionRequest>,
+    params(("id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 201, description = "Forked session", body = SessionDetailResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
+pub async fn fork_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    req: Option<Json<ForkSessionRequest>>,
+) -> Result<(StatusCode, Json<SessionDetailResponse>), SessionApiError> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    info!("Fork session request: id={} message_count={:?}", session_id, req.message_count);
+
+    let fork = state
+        .session_manager
+        .fork_session(&session_id, req.message_count)
+        .await
+        .map_err(session_error)?;
+    Ok((StatusCode::CREATED, Json(SessionDetailResponse::from(fork))))
 }
 
 /// Delete session by ID
+#[utoipa::path(
+    delete,
+    path = "/api/sessions/{id}",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 204, description = "Session deleted"),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+    ),
+)]
 pub async fn delete_session(
     State(state): State<AppState>,
     Path(session_id): Path<String>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<StatusCode, SessionApiError> {
     debug!("Delete session request: id={}", session_id);
 
-    // SessionManager の公開メソッドを使用
-    match state.session_manager.remove_from_cache(&session_id).await {
-        Some(channel_id) => {
-            // ストアからも削除
-            match state.session_manager.delete_session(&channel_id).await {
-                Ok(()) => {
-                    info!("Deleted session: {}", session_id);
-                    Ok(StatusCode::NO_CONTENT)
-                }
-                Err(e) => {
-                    error!("Failed to delete session from store: {}", e);
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ErrorResponse {
-                            error: format!("Failed to delete session: {}", e),
-                        }),
-                    ))
-                }
-            }
-        }
-        None => Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Session not found: {}", session_id),
-            }),
-        )),
+    if state
+        .session_manager
+        .delete_session_by_id(&session_id)
+        .await
+        .map_err(session_error)?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(session_not_found(&session_id))
     }
 }
 
-/// List all sessions
+/// List sessions, most recently updated first
+#[utoipa::path(
+    get,
+    path = "/api/sessions",
+    tag = "sessions",
+    params(SessionsListQuery),
+    responses(
+        (status = 200, description = "Sessions", body = SessionsListResponse),
+    ),
+)]
 pub async fn list_sessions(
     State(state): State<AppState>,
-) -> Json<SessionsListResponse> {
+    Query(query): Query<SessionsListQuery>,
+) -> Result<Json<SessionsListResponse>, SessionApiError> {
     debug!("List sessions request");
 
-    let sessions = state.session_manager.list_cached_sessions().await;
-    let session_responses: Vec<SessionDetailResponse> = sessions
-        .into_iter()
-        .map(SessionDetailResponse::from)
-        .collect();
+    let sessions = state
+        .session_manager
+        .list_sessions(query.limit.unwrap_or(50), query.offset.unwrap_or(0))
+        .await
+        .map_err(session_error)?;
+    let total = state
+        .session_manager
+        .count_sessions()
+        .await
+        .map_err(session_error)?;
 
-    Json(SessionsListResponse {
-        total: session_responses.len(),
-        sessions: session_responses,
-    })
+    Ok(Json(SessionsListResponse {
+        sessions: sessions.into_iter().map(SessionDetailResponse::from).collect(),
+        total,
+    }))
 }
 
 // ============================================================================
@@ -334,49 +831,90 @@ pub async fn list_sessions(
 // ============================================================================
 
 /// Tool definition response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ToolInfo {
     pub name: String,
     pub description: String,
+    #[schema(value_type = Object)]
     pub input_schema: serde_json::Value,
+    /// Tool policy decision: `allow`, `require_approval` or `deny`
+    #[schema(value_type = String)]
+    pub policy: &'static str,
 }
 
 /// Tools list response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ToolsListResponse {
     pub tools: Vec<ToolInfo>,
     pub total: usize,
 }
 
 /// Execute tool request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ExecuteToolRequest {
+    #[serde(default = "empty_tool_input")]
+    #[schema(value_type = Object)]
     pub input: serde_json::Value,
+    /// Only return what the call would do (`preview`) without running it
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Run a tool that needs approval; the caller confirms it (after a dry run)
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+fn empty_tool_input() -> serde_json::Value {
+    serde_json::json!({})
 }
 
 /// Tool execution response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ToolExecutionResponse {
     pub success: bool,
     pub result: Option<String>,
     pub error: Option<String>,
+    /// What the call would do (dry runs of tools that can tell)
+    pub preview: Option<String>,
+}
+
+fn policy_name(decision: PolicyDecision) -> &'static str {
+    match decision {
+        PolicyDecision::Allow => "allow",
+        PolicyDecision::RequireApproval => "require_approval",
+        PolicyDecision::Deny => "deny",
+    }
 }
 
 /// List all available tools
+#[utoipa::path(
+    get,
+    path = "/api/tools",
+    tag = "tools",
+    responses(
+        (status = 200, description = "Tools with their policy decision", body = ToolsListResponse),
+    ),
+)]
 pub async fn list_tools(
     State(state): State<AppState>,
 ) -> Json<ToolsListResponse> {
     debug!("List tools request");
 
     let definitions = state.tool_manager.definitions();
-    let tools: Vec<ToolInfo> = definitions
+    let mut tools: Vec<ToolInfo> = definitions
         .into_iter()
         .map(|d| ToolInfo {
+            policy: policy_name(
+                state
+                    .config
+                    .tool_policy
+                    .decide_call(&d.name, state.tool_manager.is_read_only(&d.name)),
+            ),
             name: d.name,
             description: d.description,
             input_schema: d.input_schema,
         })
         .collect();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
 
     Json(ToolsListResponse {
         total: tools.len(),
@@ -385,60 +923,1353 @@ pub async fn list_tools(
 }
 
 /// Execute a tool by name
+///
+/// Only available to authenticated requests. Tools that the tool policy
+/// denies are refused. `dry_run` returns the call's preview without running
+/// it; tools that need approval only run with `confirm`, which the caller
+/// sets after checking the preview.
+#[utoipa::path(
+    post,
+    path = "/api/tools/{name}/execute",
+    tag = "tools",
+    request_body = ExecuteToolRequest,
+    params(("name" = String, Path, description = "Tool name")),
+    responses(
+        (status = 200, description = "Tool result", body = ToolExecutionResponse),
+        (status = 403, description = "No API key, or refused by the tool policy", body = ErrorResponse),
+        (status = 404, description = "Tool not found", body = ErrorResponse),
+    ),
+)]
 pub async fn execute_tool(
     State(state): State<AppState>,
     Path(tool_name): Path<String>,
+    key: Option<Extension<ApiKeyInfo>>,
     Json(req): Json<ExecuteToolRequest>,
-) -> Json<ToolExecutionResponse> {
+) -> Result<Json<ToolExecutionResponse>, (StatusCode, Json<ErrorResponse>)> {
     debug!("Execute tool request: tool={}", tool_name);
 
-    match state.tool_manager.execute(&tool_name, req.input).await {
+    let refuse = |status: StatusCode, error: String| {
+        warn!("Refused tool execution: {}", error);
+        Err((status, Json(ErrorResponse { error })))
+    };
+
+    let Some(Extension(key)) = key else {
+        return refuse(
+            StatusCode::FORBIDDEN,
+            "Direct tool execution requires an API key".to_string(),
+        );
+    };
+    if !state.tool_manager.contains(&tool_name) {
+        return refuse(StatusCode::NOT_FOUND, format!("Unknown tool: {}", tool_name));
+    }
+    let read_only = state.tool_manager.is_read_only(&tool_name);
+    let decision = state.config.tool_policy.decide_call(&tool_name, read_only);
+    if decision == PolicyDecision::Deny {
+        return refuse(
+            StatusCode::FORBIDDEN,
+            format!("Tool {} is not allowed by the tool policy", tool_name),
+        );
+    }
+    if req.dry_run {
+        let preview = state.tool_manager.preview(&tool_name, &req.input).await;
+        return Ok(Json(ToolExecutionResponse {
+            success: true,
+            result: None,
+            error: None,
+            preview,
+        }));
+    }
+    if decision == PolicyDecision::RequireApproval && !req.confirm {
+        return refuse(
+            StatusCode::FORBIDDEN,
+            format!(
+                "Tool {} requires approval: check the call with \"dry_run\": true and repeat it with \"confirm\": true",
+                tool_name
+            ),
+        );
+    }
+
+    state.audit(
+        AuditEventType::ToolExecuted,
+        AuditLevel::Info,
+        Some(&key.id),
+        None,
+        "execute_tool",
+        if decision == PolicyDecision::RequireApproval {
+            format!("Tool {} confirmed and executed through the API", tool_name)
+        } else {
+            format!("Tool {} executed through the API", tool_name)
+        },
+    );
+    let response = match state.tool_manager.execute_approved(&tool_name, req.input).await {
+        Ok(result) if result.is_error => {
+            warn!("Tool {} returned an error: {}", tool_name, result.output);
+            ToolExecutionResponse {
+                success: false,
+                result: None,
+                error: Some(result.output),
+                preview: None,
+            }
+        }
         Ok(result) => {
             info!("Tool executed successfully: {}", tool_name);
-            Json(ToolExecutionResponse {
+            ToolExecutionResponse {
                 success: true,
                 result: Some(result.output),
                 error: None,
-            })
+                preview: None,
+            }
         }
         Err(e) => {
             error!("Tool execution failed: {}", e);
-            Json(ToolExecutionResponse {
+            ToolExecutionResponse {
                 success: false,
                 result: None,
                 error: Some(format!("Tool execution failed: {}", e)),
-            })
+                preview: None,
+            }
         }
-    }
+    };
+    Ok(Json(response))
 }
 
 // ============================================================================
-// Schedules API (Stub)
+// Personas API
 // ============================================================================
 
-/// Schedule information
-#[derive(Debug, Serialize)]
-pub struct ScheduleInfo {
-    pub id: String,
+/// Persona information
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PersonaInfo {
     pub name: String,
-    pub cron_expression: String,
-    pub enabled: bool,
+    pub description: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub allowed_tools: Option<Vec<String>>,
 }
 
+/// Personas list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PersonasListResponse {
+    pub personas: Vec<PersonaInfo>,
+    pub default: Option<String>,
+    pub total: usize,
+}
+
+/// List configured personas
+#[utoipa::path(
+    get,
+    path = "/api/personas",
+    tag = "personas",
+    responses(
+        (status = 200, description = "Personas", body = PersonasListResponse),
+    ),
+)]
+pub async fn list_personas(
+    State(state): State<AppState>,
+) -> Json<PersonasListResponse> {
+    debug!("List personas request");
+
+    let personas: Vec<PersonaInfo> = state
+        .personas
+        .list()
+        .iter()
+        .map(|p| PersonaInfo {
+            name: p.name.clone(),
+            description: p.description.clone(),
+            model: p.model.clone(),
+            temperature: p.temperature,
+            allowed_tools: p.allowed_tools.clone(),
+        })
+        .collect();
+
+    Json(PersonasListResponse {
+        total: personas.len(),
+        default: state.personas.default_name().map(str::to_string),
+        personas,
+    })
+}
+
+// ============================================================================
+// Schedules API
+// ============================================================================
+
 /// Schedules list response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SchedulesListResponse {
-    pub schedules: Vec<ScheduleInfo>,
+    #[schema(value_type = Vec<Object>)]
+    pub schedules: Vec<ScheduledTaskInfo>,
     pub total: usize,
+    /// Whether the scheduler is running
+    pub running: bool,
 }
 
-/// List all schedules (stub implementation)
-pub async fn list_schedules() -> Json<SchedulesListResponse> {
-    debug!("List schedules request (stub)");
+/// Schedule operation response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduleActionResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+/// Schedule run history response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduleRunsResponse {
+    #[schema(value_type = Vec<Object>)]
+    pub runs: Vec<RunRecord>,
+    pub total: usize,
+    /// Current number of consecutive failures
+    pub consecutive_failures: u32,
+}
+
+/// Schedule run history query
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ScheduleRunsQuery {
+    /// Maximum number of runs to return (default: 20)
+    pub limit: Option<usize>,
+}
+
+type ScheduleApiError = (StatusCode, Json<ErrorResponse>);
+
+fn scheduler_handle(state: &AppState) -> Result<&SchedulerHandle, ScheduleApiError> {
+    state.scheduler.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "Scheduler is not running".to_string(),
+            }),
+        )
+    })
+}
+
+fn schedule_error(e: ScheduleError) -> ScheduleApiError {
+    let status = match &e {
+        ScheduleError::TaskNotFound(_) => StatusCode::NOT_FOUND,
+        ScheduleError::TaskExists(_) => StatusCode::CONFLICT,
+        ScheduleError::CronParse(_) | ScheduleError::CronError(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
+
+fn schedule_ok(message: String) -> Json<ScheduleActionResponse> {
+    Json(ScheduleActionResponse {
+        success: true,
+        message,
+    })
+}
+
+/// List all schedules with their next run time
+#[utoipa::path(
+    get,
+    path = "/api/schedules",
+    tag = "schedules",
+    responses(
+        (status = 200, description = "Schedules", body = SchedulesListResponse),
+    ),
+)]
+pub async fn list_schedules(State(state): State<AppState>) -> Json<SchedulesListResponse> {
+    debug!("List schedules request");
+
+    let schedules = state
+        .scheduler
+        .as_ref()
+        .map(|s| s.list_with_next_run())
+        .unwrap_or_default();
 
-    // スケジューラーは未実装のため空リストを返す
     Json(SchedulesListResponse {
-        schedules: Vec::new(),
-        total: 0,
+        total: schedules.len(),
+        running: state.scheduler.is_some(),
+        schedules,
     })
 }
+
+/// Add a new schedule
+#[utoipa::path(
+    post,
+    path = "/api/schedules",
+    tag = "schedules",
+    request_body = Object,
+    responses(
+        (status = 201, description = "Schedule created", body = ScheduleActionResponse),
+        (status = 400, description = "Invalid cron expression", body = ErrorResponse),
+        (status = 409, description = "Schedule already exists", body = ErrorResponse),
+        (status = 503, description = "Scheduler is not running", body = ErrorResponse),
+    ),
+)]
+pub async fn create_schedule(
+    State(state): State<AppState>,
+    Json(task): Json<ScheduleTask>,
+) -> Result<(StatusCode, Json<ScheduleActionResponse>), ScheduleApiError> {
+    info!("Create schedule request: {}", task.name);
+
+    let name = task.name.clone();
+    scheduler_handle(&state)?
+        .add_task(task)
+        .map_err(schedule_error)?;
+
+    Ok((StatusCode::CREATED, schedule_ok(format!("Schedule '{}' created", name))))
+}
+
+/// Remove a schedule
+#[utoipa::path(
+    delete,
+    path = "/api/schedules/{name}",
+    tag = "schedules",
+    params(("name" = String, Path, description = "Schedule name")),
+    responses(
+        (status = 200, description = "Schedule deleted", body = ScheduleActionResponse),
+        (status = 404, description = "Schedule not found", body = ErrorResponse),
+        (status = 503, description = "Scheduler is not running", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_schedule(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ScheduleActionResponse>, ScheduleApiError> {
+    info!("Delete schedule request: {}", name);
+
+    scheduler_handle(&state)?
+        .remove_task(&name)
+        .map_err(schedule_error)?;
+
+    Ok(schedule_ok(format!("Schedule '{}' deleted", name)))
+}
+
+/// Pause a schedule
+#[utoipa::path(
+    post,
+    path = "/api/schedules/{name}/pause",
+    tag = "schedules",
+    params(("name" = String, Path, description = "Schedule name")),
+    responses(
+        (status = 200, description = "Schedule paused", body = ScheduleActionResponse),
+        (status = 404, description = "Schedule not found", body = ErrorResponse),
+        (status = 503, description = "Scheduler is not running", body = ErrorResponse),
+    ),
+)]
+pub async fn pause_schedule(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ScheduleActionResponse>, ScheduleApiError> {
+    scheduler_handle(&state)?
+        .pause(&name)
+        .map_err(schedule_error)?;
+
+    Ok(schedule_ok(format!("Schedule '{}' paused", name)))
+}
+
+/// Resume a paused schedule
+#[utoipa::path(
+    post,
+    path = "/api/schedules/{name}/resume",
+    tag = "schedules",
+    params(("name" = String, Path, description = "Schedule name")),
+    responses(
+        (status = 200, description = "Schedule resumed", body = ScheduleActionResponse),
+        (status = 404, description = "Schedule not found", body = ErrorResponse),
+        (status = 503, description = "Scheduler is not running", body = ErrorResponse),
+    ),
+)]
+pub async fn resume_schedule(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ScheduleActionResponse>, ScheduleApiError> {
+    scheduler_handle(&state)?
+        .resume(&name)
+        .map_err(schedule_error)?;
+
+    Ok(schedule_ok(format!("Schedule '{}' resumed", name)))
+}
+
+/// Run a schedule immediately (in the background)
+#[utoipa::path(
+    post,
+    path = "/api/schedules/{name}/run",
+    tag = "schedules",
+    params(("name" = String, Path, description = "Schedule name")),
+    responses(
+        (status = 202, description = "Schedule triggered", body = ScheduleActionResponse),
+        (status = 404, description = "Schedule not found", body = ErrorResponse),
+        (status = 503, description = "Scheduler is not running", body = ErrorResponse),
+    ),
+)]
+pub async fn run_schedule(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<(StatusCode, Json<ScheduleActionResponse>), ScheduleApiError> {
+    scheduler_handle(&state)?
+        .trigger_now(&name)
+        .map_err(schedule_error)?;
+
+    Ok((StatusCode::ACCEPTED, schedule_ok(format!("Schedule '{}' triggered", name))))
+}
+
+/// Get the run history of a schedule (newest first)
+#[utoipa::path(
+    get,
+    path = "/api/schedules/{name}/runs",
+    tag = "schedules",
+    params(("name" = String, Path, description = "Schedule name"), ScheduleRunsQuery),
+    responses(
+        (status = 200, description = "Run history", body = ScheduleRunsResponse),
+        (status = 503, description = "Scheduler or run history is disabled", body = ErrorResponse),
+    ),
+)]
+pub async fn schedule_runs(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<ScheduleRunsQuery>,
+) -> Result<Json<ScheduleRunsResponse>, ScheduleApiError> {
+    debug!("Schedule runs request: {}", name);
+
+    let history = scheduler_handle(&state)?.history().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "Run history is disabled".to_string(),
+            }),
+        )
+    })?;
+
+    let runs = history
+        .recent(Some(&name), query.limit.unwrap_or(20))
+        .map_err(schedule_error)?;
+    let consecutive_failures = history
+        .consecutive_failures(&name)
+        .map_err(schedule_error)?;
+
+    Ok(Json(ScheduleRunsResponse {
+        total: runs.len(),
+        runs,
+        consecutive_failures,
+    }))
+}
+
+// ============================================================================
+// Workflows API
+// ============================================================================
+
+/// Workflow summary
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkflowSummary {
+    pub name: String,
+    pub description: String,
+    /// Input variables with their default values
+    #[schema(value_type = Object)]
+    pub inputs: serde_json::Map<String, serde_json::Value>,
+    /// Number of top-level steps
+    pub steps: usize,
+}
+
+/// Workflows list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkflowsListResponse {
+    pub workflows: Vec<WorkflowSummary>,
+    pub total: usize,
+}
+
+/// Workflow run request
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct WorkflowRunRequest {
+    /// Input variables (override the workflow defaults)
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub inputs: serde_json::Map<String, serde_json::Value>,
+}
+
+fn workflow_engine(state: &AppState) -> Result<&WorkflowEngine, (StatusCode, Json<ErrorResponse>)> {
+    state.workflows.as_deref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "Workflows are not enabled".to_string(),
+            }),
+        )
+    })
+}
+
+/// List available workflows
+#[utoipa::path(
+    get,
+    path = "/api/workflows",
+    tag = "workflows",
+    responses(
+        (status = 200, description = "Workflows", body = WorkflowsListResponse),
+    ),
+)]
+pub async fn list_workflows(State(state): State<AppState>) -> Json<WorkflowsListResponse> {
+    debug!("List workflows request");
+
+    let workflows: Vec<WorkflowSummary> = state
+        .workflows
+        .as_ref()
+        .map(|engine| {
+            engine
+                .registry()
+                .list()
+                .into_iter()
+                .map(|w| WorkflowSummary {
+                    name: w.name.clone(),
+                    description: w.description.clone(),
+                    inputs: w.inputs.clone(),
+                    steps: w.steps.len(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Json(WorkflowsListResponse {
+        total: workflows.len(),
+        workflows,
+    })
+}
+
+/// Run a workflow and wait for the result
+#[utoipa::path(
+    post,
+    path = "/api/workflows/{name}/run",
+    tag = "workflows",
+    request_body = Option<WorkflowRunRequest>,
+    params(("name" = String, Path, description = "Workflow name")),
+    responses(
+        (status = 200, description = "Finished workflow run", body = Object),
+        (status = 404, description = "Workflow not found", body = ErrorResponse),
+        (status = 503, description = "Workflows are not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn run_workflow(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    request: Option<Json<WorkflowRunRequest>>,
+) -> Result<Json<WorkflowRun>, (StatusCode, Json<ErrorResponse>)> {
+    info!("Run workflow request: {}", name);
+
+    let Json(request) = request.unwrap_or_default();
+    let run = workflow_engine(&state)?
+        .run_named(&name, request.inputs)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse { error: e.to_string() }),
+            )
+        })?;
+
+    Ok(Json(run))
+}
+
+// ============================================================================
+// Sub-agents API
+// ============================================================================
+
+/// Sub-agent with its live statistics
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AgentInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub model: Option<String>,
+    pub capabilities: Vec<String>,
+    #[schema(value_type = String)]
+    pub health: AgentHealth,
+    pub tasks_completed: u64,
+    pub tasks_failed: u64,
+    pub failure_rate: f64,
+    pub average_latency_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_failure_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Sub-agents list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AgentsListResponse {
+    pub agents: Vec<AgentInfo>,
+    pub total: usize,
+}
+
+/// List sub-agents with their metrics and health
+#[utoipa::path(
+    get,
+    path = "/api/agents",
+    tag = "agents",
+    responses(
+        (status = 200, description = "Sub-agents", body = AgentsListResponse),
+    ),
+)]
+pub async fn list_agents(State(state): State<AppState>) -> Json<AgentsListResponse> {
+    debug!("List agents request");
+
+    let mut agents: Vec<AgentInfo> = state
+        .sub_agents
+        .as_ref()
+        .map(|manager| {
+            let metrics = manager.metrics();
+            manager
+                .all_agents()
+                .iter()
+                .map(|agent| {
+                    let stats = metrics.get(agent.id());
+                    AgentInfo {
+                        id: agent.id().as_str().to_string(),
+                        name: agent.name().to_string(),
+                        description: agent.description().to_string(),
+                        model: agent.model().map(str::to_string),
+                        capabilities: agent.capabilities().into_iter().map(|c| c.name).collect(),
+                        health: metrics.health(agent.id()),
+                        tasks_completed: stats.tasks_completed,
+                        tasks_failed: stats.tasks_failed,
+                        failure_rate: stats.failure_rate(),
+                        average_latency_ms: stats.average_latency_ms(),
+                        input_tokens: stats.input_tokens,
+                        output_tokens: stats.output_tokens,
+                        consecutive_failures: stats.consecutive_failures,
+                        last_error: stats.last_error,
+                        last_failure_at: stats.last_failure_at,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    agents.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Json(AgentsListResponse {
+        total: agents.len(),
+        agents,
+    })
+}
+
+/// Queued task request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EnqueueTaskRequest {
+    pub instruction: String,
+    /// Agent name (the best matching agent if omitted)
+    pub agent: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub priority: TaskPriority,
+    /// Earliest start time (immediately if omitted)
+    pub run_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub timeout_secs: Option<u64>,
+    pub max_iterations: Option<usize>,
+    #[serde(default)]
+    pub metadata: std::collections::HashMap<String, String>,
+}
+
+/// Queued tasks list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QueuedTasksListResponse {
+    #[schema(value_type = Vec<Object>)]
+    pub tasks: Vec<QueuedTask>,
+    pub total: usize,
+}
+
+/// Queued tasks list query
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QueuedTasksQuery {
+    /// Only tasks with this status
+    #[param(value_type = Option<String>)]
+    pub status: Option<TaskStatus>,
+    /// Maximum number of tasks to return (default: 50)
+    pub limit: Option<usize>,
+}
+
+type QueueApiError = (StatusCode, Json<ErrorResponse>);
+
+fn task_queue(state: &AppState) -> Result<&TaskQueue, QueueApiError> {
+    state.task_queue.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "Task queue is not enabled".to_string(),
+            }),
+        )
+    })
+}
+
+fn queue_error(e: cc_core::Error) -> QueueApiError {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse { error: e.to_string() }),
+    )
+}
+
+fn task_not_found(id: &str) -> QueueApiError {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse {
+            error: format!("Task not found: {}", id),
+        }),
+    )
+}
+
+/// Queue a task for background execution
+#[utoipa::path(
+    post,
+    path = "/api/agents/tasks",
+    tag = "agents",
+    request_body = EnqueueTaskRequest,
+    responses(
+        (status = 202, description = "Queued task", body = Object),
+        (status = 400, description = "Empty instruction or reserved metadata key", body = ErrorResponse),
+        (status = 503, description = "Task queue is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn enqueue_agent_task(
+    State(state): State<AppState>,
+    Json(request): Json<EnqueueTaskRequest>,
+) -> Result<(StatusCode, Json<QueuedTask>), QueueApiError> {
+    info!("Enqueue agent task request");
+
+    let queued = enqueue(&state, request, None).await?;
+    Ok((StatusCode::ACCEPTED, Json(queued)))
+}
+
+/// Validate a task request and add it to the queue
+async fn enqueue(
+    state: &AppState,
+    request: EnqueueTaskRequest,
+    callback_url: Option<String>,
+) -> Result<QueuedTask, QueueApiError> {
+    let queue = task_queue(state)?;
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+    if request.instruction.trim().is_empty() {
+        return Err(bad_request("instruction must not be empty".to_string()));
+    }
+    // The callback URL is only taken from the jobs API, where it is validated
+    if request.metadata.contains_key(CALLBACK_URL_KEY) {
+        return Err(bad_request(format!(
+            "metadata key '{}' is reserved; use callback_url of /api/jobs",
+            CALLBACK_URL_KEY
+        )));
+    }
+    if let Some(url) = &callback_url {
+        // Only public hosts (or those in TASK_CALLBACK_ALLOWED_HOSTS) get callbacks
+        if let Err(e) = queue.check_callback_url(url).await {
+            return Err(bad_request(e.to_string()));
+        }
+        if !queue.signs_callbacks() {
+            return Err(bad_request(
+                "callback_url requires TASK_CALLBACK_SECRET to be set on the server".to_string(),
+            ));
+        }
+    }
+
+    let mut builder = SubAgentTask::builder(request.instruction).priority(request.priority);
+    if let Some(secs) = request.timeout_secs {
+        builder = builder.timeout(secs);
+    }
+    if let Some(iterations) = request.max_iterations {
+        builder = builder.max_iterations(iterations);
+    }
+    for (key, value) in request.metadata {
+        builder = builder.metadata(key, value);
+    }
+    if let Some(url) = callback_url {
+        builder = builder.metadata(CALLBACK_URL_KEY, url);
+    }
+    queue
+        .enqueue(&builder.build(), request.agent, request.run_at)
+        .map_err(queue_error)
+}
+
+/// List queued tasks (newest first)
+#[utoipa::path(
+    get,
+    path = "/api/agents/tasks",
+    tag = "agents",
+    params(QueuedTasksQuery),
+    responses(
+        (status = 200, description = "Queued tasks", body = QueuedTasksListResponse),
+        (status = 503, description = "Task queue is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn list_agent_tasks(
+    State(state): State<AppState>,
+    Query(query): Query<QueuedTasksQuery>,
+) -> Result<Json<QueuedTasksListResponse>, QueueApiError> {
+    debug!("List agent tasks request");
+
+    let tasks = task_queue(&state)?
+        .list(query.status, query.limit.unwrap_or(50))
+        .map_err(queue_error)?;
+
+    Ok(Json(QueuedTasksListResponse {
+        total: tasks.len(),
+        tasks,
+    }))
+}
+
+/// Get the status and result of a queued task
+#[utoipa::path(
+    get,
+    path = "/api/agents/tasks/{id}",
+    tag = "agents",
+    params(("id" = String, Path, description = "Task ID")),
+    responses(
+        (status = 200, description = "Queued task", body = Object),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 503, description = "Task queue is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn get_agent_task(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<QueuedTask>, QueueApiError> {
+    task_queue(&state)?
+        .get(&TaskId::new(id.as_str()))
+        .map_err(queue_error)?
+        .map(Json)
+        .ok_or_else(|| task_not_found(&id))
+}
+
+/// Cancel a queued or running task
+#[utoipa::path(
+    delete,
+    path = "/api/agents/tasks/{id}",
+    tag = "agents",
+    params(("id" = String, Path, description = "Task ID")),
+    responses(
+        (status = 204, description = "Task cancelled"),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 503, description = "Task queue is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn cancel_agent_task(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, QueueApiError> {
+    info!("Cancel agent task request: {}", id);
+
+    let cancelled = task_queue(&state)?
+        .cancel(&TaskId::new(id.as_str()))
+        .map_err(queue_error)?;
+    if cancelled {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(task_not_found(&id))
+    }
+}
+
+// ============================================================================
+// Jobs API
+// ============================================================================
+
+/// Job request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateJobRequest {
+    #[serde(flatten)]
+    pub task: EnqueueTaskRequest,
+    /// URL that receives the finished job as a signed JSON POST
+    pub callback_url: Option<String>,
+}
+
+/// Job status and result
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobResponse {
+    pub id: String,
+    #[schema(value_type = String)]
+    pub status: TaskStatus,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<QueuedTask> for JobResponse {
+    fn from(task: QueuedTask) -> Self {
+        let (output, error) = match task.result {
+            Some(result) => (Some(result.output).filter(|o| !o.is_empty()), result.error),
+            None => (None, None),
+        };
+        Self {
+            id: task.id.0,
+            status: task.status,
+            created_at: task.created_at,
+            started_at: task.started_at,
+            finished_at: task.finished_at,
+            output,
+            error,
+        }
+    }
+}
+
+/// Start a long-running agent job and return its ID right away
+///
+/// Poll `GET /api/jobs/{id}` or pass `callback_url` to be notified when the
+/// job finishes. Callbacks carry an `X-Signature-256: sha256=<hex>` HMAC of
+/// the body made with `TASK_CALLBACK_SECRET`.
+#[utoipa::path(
+    post,
+    path = "/api/jobs",
+    tag = "jobs",
+    request_body = CreateJobRequest,
+    responses(
+        (status = 202, description = "Job accepted", body = JobResponse),
+        (status = 400, description = "Empty instruction or invalid callback_url", body = ErrorResponse),
+        (status = 503, description = "Task queue is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn create_job(
+    State(state): State<AppState>,
+    Json(request): Json<CreateJobRequest>,
+) -> Result<(StatusCode, Json<JobResponse>), QueueApiError> {
+    info!("Create job request");
+
+    let queued = enqueue(&state, request.task, request.callback_url).await?;
+    Ok((StatusCode::ACCEPTED, Json(JobResponse::from(queued))))
+}
+
+/// Get the status and result of a job
+#[utoipa::path(
+    get,
+    path = "/api/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job status and result", body = JobResponse),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 503, description = "Task queue is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn get_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<JobResponse>, QueueApiError> {
+    task_queue(&state)?
+        .get(&TaskId::new(id.as_str()))
+        .map_err(queue_error)?
+        .map(|task| Json(JobResponse::from(task)))
+        .ok_or_else(|| task_not_found(&id))
+}
+
+// ============================================================================
+// API keys
+// ============================================================================
+
+/// Issued key with its secret
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiKeyResponse {
+    #[serde(flatten)]
+    pub key: ApiKeyInfo,
+    /// Bearer token (only returned once)
+    pub secret: String,
+}
+
+/// API keys list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeysListResponse {
+    pub keys: Vec<ApiKeyInfo>,
+    pub total: usize,
+}
+
+type KeyApiError = (StatusCode, Json<ErrorResponse>);
+
+fn key_store(state: &AppState) -> Result<&ApiKeyStore, KeyApiError> {
+    state.api_keys.as_deref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "API key store is not enabled".to_string(),
+            }),
+        )
+    })
+}
+
+fn key_error(e: KeyError) -> KeyApiError {
+    let status = match &e {
+        KeyError::NotFound(_) => StatusCode::NOT_FOUND,
+        KeyError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    if status == StatusCode::INTERNAL_SERVER_ERROR {
+        error!("API key error: {}", e);
+    }
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
+
+/// List issued API keys
+#[utoipa::path(
+    get,
+    path = "/api/keys",
+    tag = "keys",
+    responses(
+        (status = 200, description = "Issued keys", body = ApiKeysListResponse),
+        (status = 503, description = "API key store is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+) -> Result<Json<ApiKeysListResponse>, KeyApiError> {
+    let keys = key_store(&state)?.list().map_err(key_error)?;
+    Ok(Json(ApiKeysListResponse {
+        total: keys.len(),
+        keys,
+    }))
+}
+
+/// Issue an API key
+#[utoipa::path(
+    post,
+    path = "/api/keys",
+    tag = "keys",
+    request_body = NewApiKey,
+    responses(
+        (status = 201, description = "Issued key with its secret", body = CreatedApiKeyResponse),
+        (status = 400, description = "Invalid key request", body = ErrorResponse),
+        (status = 503, description = "API key store is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiKeyInfo>>,
+    Json(req): Json<NewApiKey>,
+) -> Result<(StatusCode, Json<CreatedApiKeyResponse>), KeyApiError> {
+    let (key, secret) = key_store(&state)?.create(req).map_err(key_error)?;
+    state.audit(
+        AuditEventType::ApiKeyCreated,
+        AuditLevel::Info,
+        Some(&key.id),
+        None,
+        "create",
+        format!(
+            "API key {} ({}) created by {}",
+            key.id,
+            key.name,
+            caller.as_ref().map_or("unauthenticated request", |Extension(c)| c.id.as_str())
+        ),
+    );
+    Ok((StatusCode::CREATED, Json(CreatedApiKeyResponse { key, secret })))
+}
+
+/// Revoke an API key
+#[utoipa::path(
+    delete,
+    path = "/api/keys/{id}",
+    tag = "keys",
+    params(("id" = String, Path, description = "Key ID")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 404, description = "Key not found", body = ErrorResponse),
+        (status = 503, description = "API key store is not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    caller: Option<Extension<ApiKeyInfo>>,
+) -> Result<StatusCode, KeyApiError> {
+    if !key_store(&state)?.revoke(&id).map_err(key_error)? {
+        return Err(key_error(KeyError::NotFound(id)));
+    }
+    state.audit(
+        AuditEventType::ApiKeyRevoked,
+        AuditLevel::Info,
+        Some(&id),
+        None,
+        "revoke",
+        format!(
+            "API key {} revoked by {}",
+            id,
+            caller.as_ref().map_or("unauthenticated request", |Extension(c)| c.id.as_str())
+        ),
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Identities
+// ============================================================================
+
+/// What was deleted by a purge
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PurgeResponse {
+    /// Purged identity
+    pub identity: String,
+    /// Deleted sessions
+    pub sessions: usize,
+    /// Deleted memories
+    pub memories: usize,
+    /// Removed audit log entries
+    pub audit_entries: usize,
+}
+
+impl From<PurgeReport> for PurgeResponse {
+    fn from(report: PurgeReport) -> Self {
+        Self {
+            identity: report.identity,
+            sessions: report.sessions,
+            memories: report.memories,
+            audit_entries: report.audit_entries,
+        }
+    }
+}
+
+/// Delete all data of an identity: its sessions, memories and audit log
+/// entries. The purge itself is recorded in the audit log.
+#[utoipa::path(
+    delete,
+    path = "/api/identities/{identity}",
+    tag = "identities",
+    params(("identity" = String, Path, description = "Channel user (`discord:123`, `api:alice`) or linked identity")),
+    responses(
+        (status = 200, description = "Data deleted", body = PurgeResponse),
+    ),
+)]
+pub async fn purge_identity(
+    State(state): State<AppState>,
+    Path(identity): Path<String>,
+    caller: Option<Extension<ApiKeyInfo>>,
+) -> Result<Json<PurgeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let requested_by = match &caller {
+        Some(Extension(key)) => format!("API key {}", key.id),
+        None => "unauthenticated request".to_string(),
+    };
+    let report = cc_core::purge_identity(
+        &identity,
+        &state.config.memory,
+        &state.session_manager,
+        state.memory.as_deref(),
+        state.audit.as_deref(),
+        &requested_by,
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to purge {}: {}", identity, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e.to_string() }),
+        )
+    })?;
+    Ok(Json(report.into()))
+}
+
+// ============================================================================
+// Inbound webhooks
+// ============================================================================
+
+/// Receive a webhook and trigger its task in the background
+#[utoipa::path(
+    post,
+    path = "/hooks/{name}",
+    tag = "hooks",
+    request_body = Object,
+    params(("name" = String, Path, description = "Webhook name")),
+    responses(
+        (status = 202, description = "Task triggered", body = HookAccepted),
+        (status = 200, description = "Event filtered out", body = HookAccepted),
+        (status = 401, description = "Invalid signature", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+    ),
+    security(()),
+)]
+pub async fn receive_hook(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<HookAccepted>), (StatusCode, Json<ErrorResponse>)> {
+    let Some(hooks) = state.hooks.as_ref() else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Webhook not found: {}", name),
+            }),
+        ));
+    };
+
+    match hooks.accept(&name, &headers, &body) {
+        Ok(accepted) => {
+            let status = if accepted.triggered {
+                StatusCode::ACCEPTED
+            } else {
+                StatusCode::OK
+            };
+            Ok((status, Json(accepted)))
+        }
+        Err(e) => {
+            let status = match e {
+                HookError::NotFound(_) => StatusCode::NOT_FOUND,
+                HookError::InvalidSignature(_) => StatusCode::UNAUTHORIZED,
+            };
+            warn!("Webhook '{}' rejected: {}", name, e);
+            Err((status, Json(ErrorResponse { error: e.to_string() })))
+        }
+    }
+}
+
+// ============================================================================
+// Uploaded files
+// ============================================================================
+
+type FileApiError = (StatusCode, Json<ErrorResponse>);
+
+fn file_store(state: &AppState) -> Result<&FileStore, FileApiError> {
+    state.files.as_deref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "File uploads are not enabled".to_string(),
+            }),
+        )
+    })
+}
+
+fn file_error(e: FileError) -> FileApiError {
+    let status = match &e {
+        FileError::NotFound(_) => StatusCode::NOT_FOUND,
+        FileError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        FileError::UnsupportedType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        FileError::Transcription(_) => StatusCode::BAD_GATEWAY,
+        FileError::Io(_) | FileError::Json(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
+
+fn bad_upload(error: impl std::fmt::Display) -> FileApiError {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse {
+            error: format!("Invalid upload: {}", error),
+        }),
+    )
+}
+
+/// Upload a file (multipart field `file`)
+#[utoipa::path(
+    post,
+    path = "/api/files",
+    tag = "files",
+    request_body(content = Object, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Stored file", body = FileInfo),
+        (status = 400, description = "Invalid upload", body = ErrorResponse),
+        (status = 413, description = "File too large", body = ErrorResponse),
+        (status = 415, description = "Unsupported file type", body = ErrorResponse),
+        (status = 503, description = "File uploads are not enabled", body = ErrorResponse),
+    ),
+)]
+pub async fn upload_file(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<FileInfo>), FileApiError> {
+    let store = file_store(&state)?;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(bad_upload)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let name = field.file_name().unwrap_or("upload").to_string();
+        let media_type = field.content_type().map(str::to_string);
+
+        // Stop reading as soon as the limit is exceeded
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(bad_upload)? {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() > store.max_bytes() {
+                return Err(file_error(FileError::TooLarge {
+                    size: bytes.len(),
+                    max: store.max_bytes(),
+                }));
+            }
+        }
+
+        let (kind, _) = store
+            .validate(&name, media_type.as_deref(), &bytes)
+            .map_err(file_error)?;
+        if kind == FileKind::Audio && state.transcriber.is_none() {
+            return Err(file_error(FileError::UnsupportedType(
+                "audio (transcription is not configured)".to_string(),
+            )));
+        }
+
+        let info = store
+            .store(&name, media_type.as_deref(), &bytes)
+            .await
+            .map_err(file_error)?;
+        return Ok((StatusCode::CREATED, Json(info)));
+    }
+
+    Err(bad_upload("missing field 'file'"))
+}
+
+/// Get the metadata of an uploaded file
+#[utoipa::path(
+    get,
+    path = "/api/files/{id}",
+    tag = "files",
+    params(("id" = String, Path, description = "File ID")),
+    responses(
+        (status = 200, description = "File metadata", body = FileInfo),
+        (status = 404, description = "File not found", body = ErrorResponse),
+    ),
+)]
+pub async fn get_file(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<FileInfo>, FileApiError> {
+    file_store(&state)?.info(&id).await.map(Json).map_err(file_error)
+}
+
+/// Delete an uploaded file
+#[utoipa::path(
+    delete,
+    path = "/api/files/{id}",
+    tag = "files",
+    params(("id" = String, Path, description = "File ID")),
+    responses(
+        (status = 204, description = "File deleted"),
+        (status = 404, description = "File not found", body = ErrorResponse),
+    ),
+)]
+pub async fn delete_file(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, FileApiError> {
+    file_store(&state)?.delete(&id).await.map_err(file_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cc_core::config::MemoryConfig;
+    use cc_core::{Memory, MemoryStore};
+
+    fn key(id: &str) -> ApiKeyInfo {
+        ApiKeyInfo {
+            id: id.to_string(),
+            ..ApiKeyInfo::static_key()
+        }
+    }
+
+    #[test]
+    fn test_memory_user_is_scoped_to_the_key() {
+        let config = MemoryConfig::default();
+        let (first, second) = (key("key_one"), key("key_two"));
+        let identity = |caller: &ApiKeyInfo| {
+            config.identity("api", &memory_user(Some(caller), Some("alice")).unwrap())
+        };
+        assert_ne!(identity(&first), identity(&second));
+
+        // Facts remembered through one key are not seen through the other
+        let store = MemoryStore::in_memory().unwrap();
+        store.save(&Memory::new("Lives in Osaka").in_namespace(identity(&first))).unwrap();
+        assert_eq!(store.list_namespace(&identity(&first), 10).unwrap().len(), 1);
+        assert!(store.list_namespace(&identity(&second), 10).unwrap().is_empty());
+
+        // The shared key and OIDC users keep their own meaning
+        assert_eq!(memory_user(Some(&ApiKeyInfo::static_key()), Some("alice")).as_deref(), Some("alice"));
+        assert_eq!(memory_user(None, Some("alice")).as_deref(), Some("alice"));
+        assert_eq!(memory_user(Some(&key("oidc:u-42")), Some("alice")).as_deref(), Some("oidc:u-42"));
+        assert_eq!(memory_user(Some(&first), None), None);
+    }
+}
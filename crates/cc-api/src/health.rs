@@ -0,0 +1,253 @@
+//! ヘルスチェック
+//!
+//! `/healthz` はプロセスの生存確認、`/readyz` はデータベース・LLM API・
+//! MCP サーバー・チャネルなどの依存先を確認し、結果を JSON で返します。
+//! オーケストレーター（Kubernetes など）のプローブから利用する想定です。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::task::{AbortHandle, JoinSet};
+use utoipa::ToSchema;
+
+use cc_core::llm::{Message, MessagesRequest};
+use cc_core::{ClaudeClient, LlmProvider, SessionManager};
+
+/// How long a single check may take before it counts as failing
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A dependency checked by `/readyz`
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// Name shown in the report (e.g. `database`, `channel:discord`)
+    fn name(&self) -> &str;
+
+    /// Whether a failure makes the gateway not ready (otherwise: degraded)
+    fn critical(&self) -> bool {
+        false
+    }
+
+    /// Run the check, returning an optional detail or the failure reason
+    async fn check(&self) -> Result<Option<String>, String>;
+}
+
+/// Result of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Failing,
+}
+
+/// Overall readiness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Readiness {
+    /// All checks pass
+    Ready,
+    /// Only non-critical checks fail
+    Degraded,
+    /// A critical check fails
+    NotReady,
+}
+
+/// Outcome of one dependency check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub critical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub latency_ms: u64,
+}
+
+/// `/readyz` report
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReadinessReport {
+    pub status: Readiness,
+    pub checks: Vec<CheckResult>,
+}
+
+/// Run all checks concurrently
+pub async fn readiness(checks: &[Arc<dyn HealthCheck>]) -> ReadinessReport {
+    let mut tasks = JoinSet::new();
+    for (index, check) in checks.iter().enumerate() {
+        let check = Arc::clone(check);
+        tasks.spawn(async move {
+            let started = Instant::now();
+            let outcome = tokio::time::timeout(CHECK_TIMEOUT, check.check())
+                .await
+                .unwrap_or_else(|_| Err(format!("timed out after {:?}", CHECK_TIMEOUT)));
+            let (status, detail) = match outcome {
+                Ok(detail) => (CheckStatus::Ok, detail),
+                Err(reason) => (CheckStatus::Failing, Some(reason)),
+            };
+            let result = CheckResult {
+                name: check.name().to_string(),
+                status,
+                critical: check.critical(),
+                detail,
+                latency_ms: started.elapsed().as_millis() as u64,
+            };
+            (index, result)
+        });
+    }
+
+    let mut results = tasks.join_all().await;
+    results.sort_by_key(|(index, _)| *index);
+    let checks: Vec<CheckResult> = results.into_iter().map(|(_, result)| result).collect();
+
+    let failing = |critical: bool| {
+        checks
+            .iter()
+            .any(|c| c.status == CheckStatus::Failing && c.critical == critical)
+    };
+    let status = if failing(true) {
+        Readiness::NotReady
+    } else if failing(false) {
+        Readiness::Degraded
+    } else {
+        Readiness::Ready
+    };
+    ReadinessReport { status, checks }
+}
+
+/// Session database connectivity
+pub struct DatabaseCheck(pub Arc<SessionManager>);
+
+#[async_trait]
+impl HealthCheck for DatabaseCheck {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    fn critical(&self) -> bool {
+        true
+    }
+
+    async fn check(&self) -> Result<Option<String>, String> {
+        let sessions = self.0.count_sessions().await.map_err(|e| e.to_string())?;
+        Ok(Some(format!("{} sessions", sessions)))
+    }
+}
+
+/// LLM API reachability (a token count request, which is free)
+pub struct LlmCheck(pub Arc<ClaudeClient>);
+
+#[async_trait]
+impl HealthCheck for LlmCheck {
+    fn name(&self) -> &str {
+        "llm"
+    }
+
+    fn critical(&self) -> bool {
+        true
+    }
+
+    async fn check(&self) -> Result<Option<String>, String> {
+        let provider = self.0.provider();
+        if *provider != LlmProvider::Claude {
+            return Ok(Some(format!("not checked for the {:?} provider", provider)));
+        }
+
+        let request = MessagesRequest {
+            model: self.0.model().to_string(),
+            max_tokens: 1,
+            system: None,
+            messages: vec![Message::user("ping")],
+            tools: None,
+            thinking: None,
+            tool_choice: None,
+            temperature: None,
+        };
+        // The client falls back to a local estimate when the API fails
+        match self.0.count_tokens(&request).await {
+            Ok(count) if !count.estimated => Ok(None),
+            Ok(_) => Err("token count request failed".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// A background task (such as a channel) that should keep running
+pub struct TaskCheck {
+    name: String,
+    handle: AbortHandle,
+}
+
+impl TaskCheck {
+    pub fn new(name: impl Into<String>, handle: AbortHandle) -> Self {
+        Self {
+            name: name.into(),
+            handle,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for TaskCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> Result<Option<String>, String> {
+        if self.handle.is_finished() {
+            Err("stopped".to_string())
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed {
+        critical: bool,
+        ok: bool,
+    }
+
+    #[async_trait]
+    impl HealthCheck for Fixed {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn critical(&self) -> bool {
+            self.critical
+        }
+
+        async fn check(&self) -> Result<Option<String>, String> {
+            if self.ok { Ok(None) } else { Err("down".to_string()) }
+        }
+    }
+
+    fn check(critical: bool, ok: bool) -> Arc<dyn HealthCheck> {
+        Arc::new(Fixed { critical, ok })
+    }
+
+    #[tokio::test]
+    async fn test_readiness_status() {
+        let report = readiness(&[check(true, true), check(false, true)]).await;
+        assert_eq!(report.status, Readiness::Ready);
+        assert_eq!(report.checks.len(), 2);
+
+        let report = readiness(&[check(true, true), check(false, false)]).await;
+        assert_eq!(report.status, Readiness::Degraded);
+        assert_eq!(report.checks[1].detail.as_deref(), Some("down"));
+
+        let report = readiness(&[check(true, false), check(false, true)]).await;
+        assert_eq!(report.status, Readiness::NotReady);
+
+        let task = tokio::spawn(async {});
+        let handle = task.abort_handle();
+        task.await.unwrap();
+        let report = readiness(&[Arc::new(TaskCheck::new("channel:test", handle))]).await;
+        assert_eq!(report.checks[0].status, CheckStatus::Failing);
+        assert_eq!(report.status, Readiness::Degraded);
+    }
+}
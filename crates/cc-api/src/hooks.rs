@@ -0,0 +1,584 @@
+//! Inbound webhooks
+//!
+//! `POST /hooks/{name}` で外部サービス（GitHub / Stripe / Grafana など）のイベントを受け取り、
+//! HMAC 署名を検証したうえでワークフローまたはエージェントタスクを実行します。
+//! フックは hooks.toml で定義します。
+//!
+//! ```toml
+//! [[hooks]]
+//! name = "github"
+//! provider = "github"                 # github | stripe | generic
+//! secret = "${GITHUB_WEBHOOK_SECRET}"
+//! events = ["pull_request"]
+//! prompt = "PR {{payload.pull_request.html_url}} をレビューして要点をまとめて"
+//! tools = ["web_fetch"]
+//!
+//! [[hooks.outputs]]
+//! type = "discord"
+//! channel_id = "123456789012345678"
+//! ```
+//!
+//! テンプレートとワークフローの入力には `payload`（JSON ボディ）、`headers`、
+//! `event`、`hook` が渡されます。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use cc_core::webhook::verify_hmac_sha256_hex;
+use cc_core::{Config, ToolManager};
+use cc_schedule::{OutputDispatcher, OutputTarget, ScheduleResult};
+use cc_workflow::{Step, StepKind, Workflow, WorkflowEngine, WorkflowRun};
+
+use crate::error::{ApiError, HookError, Result};
+
+/// Maximum age of a Stripe signature timestamp (seconds)
+const STRIPE_TOLERANCE_SECS: u64 = 300;
+
+/// Headers never exposed to templates
+const HIDDEN_HEADERS: &[&str] = &["authorization", "cookie", "proxy-authorization"];
+
+/// hooks.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+}
+
+impl HooksConfig {
+    /// Default configuration file
+    pub const DEFAULT_PATH: &'static str = "hooks.toml";
+
+    /// Load from a TOML file (`${VAR}` is expanded from the environment)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        toml::from_str(&Config::expand_env_vars(&content))
+            .map_err(|e| ApiError::Config(format!("hooks: {}", e)))
+    }
+}
+
+/// Signature scheme of the sending service
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookProvider {
+    /// `X-Hub-Signature-256: sha256=<hex>` and `X-GitHub-Event`
+    Github,
+    /// `Stripe-Signature: t=<ts>,v1=<hex>`; the event is the payload's `type`
+    Stripe,
+    /// HMAC-SHA256 of the body as hex (optionally `sha256=` prefixed) in `signature_header`
+    #[default]
+    Generic,
+}
+
+/// A single inbound webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookConfig {
+    /// Hook name (`/hooks/{name}`)
+    pub name: String,
+
+    #[serde(default)]
+    pub provider: HookProvider,
+
+    /// Shared secret for the HMAC signature
+    #[serde(default, skip_serializing)]
+    pub secret: Option<String>,
+
+    /// Signature header (generic default: `X-Signature-256`)
+    #[serde(default)]
+    pub signature_header: Option<String>,
+
+    /// Accept requests without a secret configured (testing only)
+    #[serde(default)]
+    pub allow_unsigned: bool,
+
+    /// Only trigger for these events (all events if empty)
+    #[serde(default)]
+    pub events: Vec<String>,
+
+    /// Workflow to run
+    #[serde(default)]
+    pub workflow: Option<String>,
+
+    /// Agent instruction template (used when `workflow` is not set)
+    #[serde(default)]
+    pub prompt: Option<String>,
+
+    /// Tools available to the agent (read-only tools if empty)
+    #[serde(default)]
+    pub tools: Vec<String>,
+
+    /// Where to deliver the result
+    #[serde(default)]
+    pub outputs: Vec<OutputTarget>,
+
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Result of accepting a webhook request
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct HookAccepted {
+    pub hook: String,
+    pub event: Option<String>,
+    /// False if the event was filtered out
+    pub triggered: bool,
+}
+
+/// Verifies inbound webhooks and runs their tasks
+pub struct WebhookHandler {
+    hooks: HashMap<String, HookConfig>,
+    workflows: Arc<WorkflowEngine>,
+    output: OutputDispatcher,
+}
+
+impl WebhookHandler {
+    /// Create a handler for the enabled hooks
+    pub fn new(config: HooksConfig, workflows: Arc<WorkflowEngine>) -> Self {
+        let hooks = config
+            .hooks
+            .into_iter()
+            .filter(|hook| hook.enabled)
+            .map(|hook| (hook.name.clone(), hook))
+            .collect();
+        Self {
+            hooks,
+            workflows,
+            output: OutputDispatcher::new(),
+        }
+    }
+
+    /// Set the result delivery credentials
+    pub fn with_output_dispatcher(mut self, output: OutputDispatcher) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Number of enabled hooks
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Verify a request and start its task in the background
+    pub fn accept(
+        self: &Arc<Self>,
+        name: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> std::result::Result<HookAccepted, HookError> {
+        let hook = self
+            .hooks
+            .get(name)
+            .ok_or_else(|| HookError::NotFound(name.to_string()))?;
+        verify_signature(hook, headers, body, Utc::now().timestamp())?;
+
+        let payload = serde_json::from_slice(body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()));
+        let event = event_name(hook, headers, &payload);
+        let triggered = should_trigger(hook, event.as_deref());
+        info!(hook = %name, event = ?event, triggered, "Webhook received");
+
+        if triggered {
+            let inputs = hook_inputs(hook, headers, payload, event.as_deref());
+            let handler = Arc::clone(self);
+            let hook = hook.clone();
+            tokio::spawn(async move { handler.run(&hook, inputs).await });
+        }
+
+        Ok(HookAccepted {
+            hook: name.to_string(),
+            event,
+            triggered,
+        })
+    }
+
+    /// Run the hook's workflow or agent task and deliver the result
+    async fn run(&self, hook: &HookConfig, inputs: Map<String, Value>) -> Option<WorkflowRun> {
+        let run = match (&hook.workflow, &hook.prompt) {
+            (Some(workflow), _) => match self.workflows.run_named(workflow, inputs).await {
+                Ok(run) => run,
+                Err(e) => {
+                    warn!(hook = %hook.name, "Webhook workflow failed: {}", e);
+                    return None;
+                }
+            },
+            (None, Some(prompt)) => {
+                let workflow = agent_workflow(hook, prompt, self.workflows.tool_manager());
+                self.workflows.run(&workflow, inputs).await
+            }
+            (None, None) => {
+                warn!(hook = %hook.name, "Webhook has neither workflow nor prompt");
+                return None;
+            }
+        };
+
+        if !hook.outputs.is_empty() {
+            let result = ScheduleResult {
+                task_name: format!("Webhook: {}", hook.name),
+                executed_at: Utc::now(),
+                response: run
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| run.output.clone()),
+                success: run.success,
+                duration_ms: run.duration_ms,
+                input_tokens: run.input_tokens,
+                output_tokens: run.output_tokens,
+            };
+            self.output.dispatch(&hook.outputs, &result).await;
+        }
+        Some(run)
+    }
+}
+
+/// Single step running the hook's prompt
+///
+/// Payloads come from outside, so without a `tools` list the agent only
+/// gets the read-only tools; with no tools at all the prompt is answered
+/// without an agent.
+fn agent_workflow(hook: &HookConfig, prompt: &str, tool_manager: &ToolManager) -> Workflow {
+    let tools = if hook.tools.is_empty() {
+        let mut names: Vec<String> = tool_manager
+            .tool_names()
+            .into_iter()
+            .filter(|name| tool_manager.is_read_only(name))
+            .map(str::to_string)
+            .collect();
+        names.sort_unstable();
+        names
+    } else {
+        hook.tools.clone()
+    };
+    let kind = if tools.is_empty() {
+        StepKind::Prompt {
+            prompt: prompt.to_string(),
+            system: None,
+            model: None,
+            max_tokens: None,
+            temperature: None,
+        }
+    } else {
+        StepKind::Agent {
+            instruction: prompt.to_string(),
+            agent: None,
+            tools,
+            max_iterations: None,
+        }
+    };
+    Workflow {
+        name: format!("hook:{}", hook.name),
+        description: String::new(),
+        inputs: Map::new(),
+        steps: vec![Step {
+            id: "agent".to_string(),
+            continue_on_error: false,
+            kind,
+        }],
+        output: None,
+    }
+}
+
+/// Check the HMAC signature of a request
+fn verify_signature(
+    hook: &HookConfig,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+) -> std::result::Result<(), HookError> {
+    let Some(secret) = hook.secret.as_deref().filter(|s| !s.is_empty()) else {
+        return if hook.allow_unsigned {
+            Ok(())
+        } else {
+            Err(HookError::InvalidSignature("no secret configured".to_string()))
+        };
+    };
+
+    let header = signature_header(hook);
+    let signature = headers
+        .get(header)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| HookError::InvalidSignature(format!("missing {} header", header)))?;
+
+    let valid = match hook.provider {
+        HookProvider::Github | HookProvider::Generic => {
+            let hex_sig = signature.trim().trim_start_matches("sha256=");
+            verify_hmac_sha256_hex(secret, &[body], hex_sig)
+        }
+        HookProvider::Stripe => {
+            let mut timestamp = None;
+            let mut candidates = Vec::new();
+            for part in signature.split(',') {
+                match part.trim().split_once('=') {
+                    Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+                    Some(("v1", sig)) => candidates.push(sig),
+                    _ => {}
+                }
+            }
+            let timestamp = timestamp
+                .ok_or_else(|| HookError::InvalidSignature("missing timestamp".to_string()))?;
+            // `t` comes from the request; abs_diff cannot overflow on extreme values
+            if now.abs_diff(timestamp) > STRIPE_TOLERANCE_SECS {
+                return Err(HookError::InvalidSignature("timestamp out of tolerance".to_string()));
+            }
+            let prefix = format!("{}.", timestamp);
+            candidates
+                .iter()
+                .any(|sig| verify_hmac_sha256_hex(secret, &[prefix.as_bytes(), body], sig))
+        }
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(HookError::InvalidSignature("signature mismatch".to_string()))
+    }
+}
+
+fn signature_header(hook: &HookConfig) -> &str {
+    match (&hook.signature_header, hook.provider) {
+        (Some(header), _) => header,
+        (None, HookProvider::Github) => "x-hub-signature-256",
+        (None, HookProvider::Stripe) => "stripe-signature",
+        (None, HookProvider::Generic) => "x-signature-256",
+    }
+}
+
+/// Event name reported by the sender
+fn event_name(hook: &HookConfig, headers: &HeaderMap, payload: &Value) -> Option<String> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    match hook.provider {
+        HookProvider::Github => header("x-github-event"),
+        HookProvider::Stripe => payload["type"].as_str().map(str::to_string),
+        HookProvider::Generic => header("x-event-type")
+            .or_else(|| payload["event"].as_str().map(str::to_string))
+            .or_else(|| payload["type"].as_str().map(str::to_string)),
+    }
+}
+
+fn should_trigger(hook: &HookConfig, event: Option<&str>) -> bool {
+    // GitHub sends `ping` when the webhook is created
+    if hook.provider == HookProvider::Github && event == Some("ping") {
+        return false;
+    }
+    hook.events.is_empty() || event.is_some_and(|e| hook.events.iter().any(|allowed| allowed == e))
+}
+
+/// Template / workflow inputs for a request
+fn hook_inputs(
+    hook: &HookConfig,
+    headers: &HeaderMap,
+    payload: Value,
+    event: Option<&str>,
+) -> Map<String, Value> {
+    let signature = signature_header(hook).to_lowercase();
+    let headers: Map<String, Value> = headers
+        .iter()
+        .filter(|(name, _)| {
+            let name = name.as_str();
+            name != signature && !HIDDEN_HEADERS.contains(&name)
+        })
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|v| (name.as_str().to_string(), Value::String(v.to_string())))
+        })
+        .collect();
+
+    Map::from_iter([
+        ("payload".to_string(), payload),
+        ("headers".to_string(), Value::Object(headers)),
+        ("event".to_string(), event.map(Value::from).unwrap_or(Value::Null)),
+        ("hook".to_string(), Value::String(hook.name.clone())),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmac::{Hmac, Mac};
+    use serde_json::json;
+    use sha2::Sha256;
+
+    fn hook(provider: HookProvider) -> HookConfig {
+        HookConfig {
+            name: "test".to_string(),
+            provider,
+            secret: Some("s3cret".to_string()),
+            signature_header: None,
+            allow_unsigned: false,
+            events: Vec::new(),
+            workflow: None,
+            prompt: None,
+            tools: Vec::new(),
+            outputs: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    fn sign(parts: &[&[u8]]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+        for part in parts {
+            mac.update(part);
+        }
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_verify_github() {
+        let body = br#"{"action":"opened"}"#;
+        let hook = hook(HookProvider::Github);
+        let good = headers(&[("x-hub-signature-256", format!("sha256={}", sign(&[body])))]);
+        let bad = headers(&[("x-hub-signature-256", format!("sha256={}", sign(&[b"other"])))]);
+
+        assert!(verify_signature(&hook, &good, body, 0).is_ok());
+        assert!(verify_signature(&hook, &bad, body, 0).is_err());
+        assert!(verify_signature(&hook, &HeaderMap::new(), body, 0).is_err());
+    }
+
+    #[test]
+    fn test_verify_stripe() {
+        let body = br#"{"type":"invoice.paid"}"#;
+        let hook = hook(HookProvider::Stripe);
+        let now = 1_700_000_000;
+        let signature = sign(&[format!("{}.", now).as_bytes(), body]);
+        let header = headers(&[("stripe-signature", format!("t={},v1={}", now, signature))]);
+
+        assert!(verify_signature(&hook, &header, body, now + 10).is_ok());
+        assert!(verify_signature(&hook, &header, body, now + 3600).is_err());
+
+        // Extreme timestamps are rejected instead of overflowing
+        for t in [i64::MIN, i64::MAX] {
+            let header = headers(&[("stripe-signature", format!("t={},v1={}", t, signature))]);
+            assert!(verify_signature(&hook, &header, body, now).is_err());
+            assert!(verify_signature(&hook, &header, body, -now).is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_generic_and_unsigned() {
+        let body = b"alert";
+        let mut hook = hook(HookProvider::Generic);
+        hook.signature_header = Some("x-grafana-alerting-signature".to_string());
+        let header = headers(&[("x-grafana-alerting-signature", sign(&[body]))]);
+        assert!(verify_signature(&hook, &header, body, 0).is_ok());
+
+        hook.secret = None;
+        assert!(verify_signature(&hook, &HeaderMap::new(), body, 0).is_err());
+        hook.allow_unsigned = true;
+        assert!(verify_signature(&hook, &HeaderMap::new(), body, 0).is_ok());
+    }
+
+    #[test]
+    fn test_event_filter_and_inputs() {
+        let mut hook = hook(HookProvider::Github);
+        hook.events = vec!["pull_request".to_string()];
+        let headers = headers(&[
+            ("x-github-event", "pull_request".to_string()),
+            ("x-hub-signature-256", "sha256=00".to_string()),
+            ("authorization", "Bearer x".to_string()),
+        ]);
+        let payload = json!({"action": "opened"});
+
+        let event = event_name(&hook, &headers, &payload);
+        assert_eq!(event.as_deref(), Some("pull_request"));
+        assert!(should_trigger(&hook, event.as_deref()));
+        assert!(!should_trigger(&hook, Some("push")));
+        assert!(!should_trigger(&hook, Some("ping")));
+
+        let inputs = hook_inputs(&hook, &headers, payload, event.as_deref());
+        assert_eq!(inputs["payload"]["action"], "opened");
+        assert_eq!(inputs["headers"]["x-github-event"], "pull_request");
+        assert!(inputs["headers"].get("x-hub-signature-256").is_none());
+        assert!(inputs["headers"].get("authorization").is_none());
+    }
+
+    #[test]
+    fn test_agent_tools_default_to_read_only() {
+        struct Dummy(&'static str, bool);
+
+        #[async_trait::async_trait]
+        impl cc_core::Tool for Dummy {
+            fn name(&self) -> &str {
+                self.0
+            }
+            fn description(&self) -> &str {
+                ""
+            }
+            fn input_schema(&self) -> Value {
+                json!({"type": "object"})
+            }
+            fn is_read_only(&self) -> bool {
+                self.1
+            }
+            async fn execute(&self, _input: Value) -> cc_core::Result<cc_core::ToolResult> {
+                Ok(cc_core::ToolResult::success(""))
+            }
+        }
+
+        let tools_of = |workflow: Workflow| match &workflow.steps[0].kind {
+            StepKind::Agent { tools, .. } => Some(tools.clone()),
+            _ => None,
+        };
+        let mut hook = hook(HookProvider::Github);
+        let mut manager = ToolManager::new();
+        assert_eq!(tools_of(agent_workflow(&hook, "hi", &manager)), None);
+
+        manager.register(Arc::new(Dummy("bash", false)));
+        manager.register(Arc::new(Dummy("read", true)));
+        assert_eq!(tools_of(agent_workflow(&hook, "hi", &manager)), Some(vec!["read".to_string()]));
+
+        hook.tools = vec!["bash".to_string()];
+        assert_eq!(tools_of(agent_workflow(&hook, "hi", &manager)), Some(vec!["bash".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config: HooksConfig = toml::from_str(
+            r#"
+[[hooks]]
+name = "stripe"
+provider = "stripe"
+secret = "whsec"
+workflow = "billing"
+
+[[hooks.outputs]]
+type = "slack"
+channel = "C1"
+"#,
+        )
+        .unwrap();
+        let hook = &config.hooks[0];
+        assert_eq!(hook.provider, HookProvider::Stripe);
+        assert_eq!(hook.workflow.as_deref(), Some("billing"));
+        assert_eq!(hook.outputs.len(), 1);
+        assert!(hook.enabled);
+    }
+}
@@ -0,0 +1,389 @@
+//! API keys
+//!
+//! 共有キー（`API_KEY` / `api.key`）に加えて、SQLite に保存した複数の API キーで
+//! 認証できます。キーごとにスコープ・有効期限・1 分あたりのリクエスト上限を設定でき、
+//! `/api/keys`（admin スコープ）で発行・一覧・失効を行います。
+//!
+//! キー本体は発行時に一度だけ返し、データベースには SHA-256 ハッシュのみを保存します。
+//!
+//! スコープは `chat` < `tools` < `admin` の順に上位のものが下位を含みます。
+//!
+//! - `chat`: チャット、ファイル、メモリ、ペルソナ
+//! - `tools`: ツール、ワークフロー、サブエージェント、ジョブ、スケジュール
+//! - `admin`: API キーの管理、セッションの一覧・閲覧・変更（`/api/sessions` は
+//!   すべてのチャネルの会話を扱うため）
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use cc_core::migrate::{migrate, Migration};
+use sha2::{Digest, Sha256};
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::error::KeyError;
+
+/// Schema versions of the `api_keys` table
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create api_keys",
+    "CREATE TABLE IF NOT EXISTS api_keys (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        secret_hash TEXT NOT NULL UNIQUE,
+        scopes TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        expires_at TEXT,
+        rate_limit_per_minute INTEGER,
+        last_used_at TEXT,
+        revoked INTEGER NOT NULL DEFAULT 0
+    )",
+)];
+
+/// Prefix of key IDs
+const ID_PREFIX: &str = "key_";
+
+/// Prefix of key secrets
+const SECRET_PREFIX: &str = "ccg_";
+
+/// ID of the shared key from `API_KEY` / `api.key`
+pub const STATIC_KEY_ID: &str = "static";
+
+/// What an API key may access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// Chat, files, memory and personas
+    Chat,
+    /// Tools, workflows, sub-agents, jobs and schedules (includes `chat`)
+    Tools,
+    /// API key and session management (includes everything)
+    Admin,
+}
+
+impl ApiScope {
+    /// Scope needed to call `path`
+    pub fn required_for(path: &str) -> Self {
+        const TOOLS: &[&str] = &["/api/tools", "/api/workflows", "/api/agents", "/api/jobs", "/api/schedules"];
+
+        let under = |prefix: &str| path == prefix || path.starts_with(&format!("{}/", prefix));
+        // Sessions are not owned by keys; they hold every channel's conversations
+        if under("/api/keys") || under("/api/identities") || under("/api/sessions") {
+            Self::Admin
+        } else if TOOLS.iter().any(|p| under(p)) {
+            Self::Tools
+        } else {
+            Self::Chat
+        }
+    }
+}
+
+/// An API key (without its secret)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Requests per minute (None = no per-key limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_minute: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked: bool,
+}
+
+impl ApiKeyInfo {
+    /// The shared key from `API_KEY` / `api.key` (admin, never expires)
+    pub fn static_key() -> Self {
+        Self {
+            id: STATIC_KEY_ID.to_string(),
+            name: "API_KEY".to_string(),
+            scopes: vec![ApiScope::Admin],
+            created_at: DateTime::<Utc>::UNIX_EPOCH,
+            expires_at: None,
+            rate_limit_per_minute: None,
+            last_used_at: None,
+            revoked: false,
+        }
+    }
+
+    /// Whether the key grants `scope`
+    pub fn allows(&self, scope: ApiScope) -> bool {
+        self.scopes.iter().any(|granted| *granted >= scope)
+    }
+
+    /// Whether the key has expired at `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// A key to issue
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct NewApiKey {
+    pub name: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+fn default_scopes() -> Vec<ApiScope> {
+    vec![ApiScope::Chat]
+}
+
+/// SQLite-backed API key store
+pub struct ApiKeyStore {
+    conn: Mutex<Connection>,
+}
+
+impl ApiKeyStore {
+    /// Default database path
+    pub const DEFAULT_PATH: &'static str = "data/api_keys.db";
+
+    /// Open the database file (creating its parent directory)
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, KeyError> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Create an in-memory store (for testing)
+    pub fn in_memory() -> Result<Self, KeyError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, KeyError> {
+        migrate(&conn, "api_keys", MIGRATIONS)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Issue a key, returning it with its secret (shown only once)
+    pub fn create(&self, key: NewApiKey) -> Result<(ApiKeyInfo, String), KeyError> {
+        let name = key.name.trim().to_string();
+        if name.is_empty() {
+            return Err(KeyError::InvalidRequest("name must not be empty".to_string()));
+        }
+        if key.scopes.is_empty() {
+            return Err(KeyError::InvalidRequest("scopes must not be empty".to_string()));
+        }
+        if key.rate_limit_per_minute == Some(0) {
+            return Err(KeyError::InvalidRequest(
+                "rate_limit_per_minute must be positive".to_string(),
+            ));
+        }
+
+        let info = ApiKeyInfo {
+            id: format!("{}{}", ID_PREFIX, uuid::Uuid::new_v4().simple()),
+            name,
+            scopes: key.scopes,
+            created_at: Utc::now(),
+            expires_at: key.expires_at,
+            rate_limit_per_minute: key.rate_limit_per_minute,
+            last_used_at: None,
+            revoked: false,
+        };
+        let secret = format!(
+            "{}{}{}",
+            SECRET_PREFIX,
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+
+        self.lock().execute(
+            "INSERT INTO api_keys
+                (id, name, secret_hash, scopes, created_at, expires_at, rate_limit_per_minute)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                info.id,
+                info.name,
+                hash_secret(&secret),
+                serde_json::to_string(&info.scopes)?,
+                timestamp(info.created_at),
+                info.expires_at.map(timestamp),
+                info.rate_limit_per_minute,
+            ],
+        )?;
+        info!("Created API key {} ({})", info.id, info.name);
+        Ok((info, secret))
+    }
+
+    /// All keys, newest first
+    pub fn list(&self) -> Result<Vec<ApiKeyInfo>, KeyError> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM api_keys ORDER BY created_at DESC",
+            COLUMNS
+        ))?;
+        let keys = stmt
+            .query_map([], row_to_key)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(keys)
+    }
+
+    /// Revoke a key
+    ///
+    /// Returns `false` if the key does not exist or is already revoked.
+    pub fn revoke(&self, id: &str) -> Result<bool, KeyError> {
+        let updated = self.lock().execute(
+            "UPDATE api_keys SET revoked = 1 WHERE id = ?1 AND revoked = 0",
+            params![id],
+        )?;
+        if updated > 0 {
+            info!("Revoked API key {}", id);
+        }
+        Ok(updated > 0)
+    }
+
+    /// Whether any usable (not revoked) key exists
+    pub fn has_active_keys(&self) -> Result<bool, KeyError> {
+        let count: i64 = self.lock().query_row(
+            "SELECT COUNT(*) FROM api_keys WHERE revoked = 0",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Look up the key with `secret`, recording its use
+    ///
+    /// Revoked and expired keys are not returned.
+    pub fn authenticate(&self, secret: &str) -> Result<Option<ApiKeyInfo>, KeyError> {
+        let now = Utc::now();
+        let conn = self.lock();
+        let key = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM api_keys WHERE secret_hash = ?1 AND revoked = 0",
+                    COLUMNS
+                ),
+                params![hash_secret(secret)],
+                row_to_key,
+            )
+            .optional()?
+            .filter(|key| !key.is_expired(now));
+        if let Some(key) = &key {
+            conn.execute(
+                "UPDATE api_keys SET last_used_at = ?1 WHERE id = ?2",
+                params![timestamp(now), key.id],
+            )?;
+        }
+        Ok(key)
+    }
+}
+
+const COLUMNS: &str =
+    "id, name, scopes, created_at, expires_at, rate_limit_per_minute, last_used_at, revoked";
+
+fn row_to_key(row: &rusqlite::Row<'_>) -> rusqlite::Result<ApiKeyInfo> {
+    let scopes: String = row.get(2)?;
+    Ok(ApiKeyInfo {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        scopes: serde_json::from_str(&scopes).map_err(|_| rusqlite::Error::InvalidQuery)?,
+        created_at: parse_timestamp(&row.get::<_, String>(3)?)?,
+        expires_at: row
+            .get::<_, Option<String>>(4)?
+            .map(|s| parse_timestamp(&s))
+            .transpose()?,
+        rate_limit_per_minute: row.get(5)?,
+        last_used_at: row
+            .get::<_, Option<String>>(6)?
+            .map(|s| parse_timestamp(&s))
+            .transpose()?,
+        revoked: row.get(7)?,
+    })
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(value: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| rusqlite::Error::InvalidQuery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_key(name: &str, scopes: Vec<ApiScope>) -> NewApiKey {
+        NewApiKey {
+            name: name.to_string(),
+            scopes,
+            expires_at: None,
+            rate_limit_per_minute: None,
+        }
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(ApiScope::required_for("/api/chat"), ApiScope::Chat);
+        assert_eq!(ApiScope::required_for("/api/sessions"), ApiScope::Admin);
+        assert_eq!(ApiScope::required_for("/api/sessions/abc/messages"), ApiScope::Admin);
+        assert_eq!(ApiScope::required_for("/api/tools/bash/execute"), ApiScope::Tools);
+        assert_eq!(ApiScope::required_for("/api/jobs"), ApiScope::Tools);
+        assert_eq!(ApiScope::required_for("/api/keys/key_1"), ApiScope::Admin);
+        assert_eq!(ApiScope::required_for("/api/identities/discord:1"), ApiScope::Admin);
+        assert_eq!(ApiScope::required_for("/api/keysmith"), ApiScope::Chat);
+
+        let tools = new_key("ci", vec![ApiScope::Tools]);
+        let store = ApiKeyStore::in_memory().unwrap();
+        let (info, _) = store.create(tools).unwrap();
+        assert!(info.allows(ApiScope::Chat));
+        assert!(info.allows(ApiScope::Tools));
+        assert!(!info.allows(ApiScope::Admin));
+        assert!(ApiKeyInfo::static_key().allows(ApiScope::Admin));
+    }
+
+    #[test]
+    fn test_create_authenticate_revoke() {
+        let store = ApiKeyStore::in_memory().unwrap();
+        assert!(!store.has_active_keys().unwrap());
+
+        let (info, secret) = store.create(new_key("dashboard", vec![ApiScope::Chat])).unwrap();
+        assert!(secret.starts_with(SECRET_PREFIX));
+        assert!(store.has_active_keys().unwrap());
+
+        let found = store.authenticate(&secret).unwrap().unwrap();
+        assert_eq!(found.id, info.id);
+        assert!(store.authenticate("ccg_wrong").unwrap().is_none());
+        assert!(store.list().unwrap()[0].last_used_at.is_some());
+
+        assert!(store.revoke(&info.id).unwrap());
+        assert!(!store.revoke(&info.id).unwrap());
+        assert!(store.authenticate(&secret).unwrap().is_none());
+        assert!(!store.has_active_keys().unwrap());
+    }
+
+    #[test]
+    fn test_expired_key_and_validation() {
+        let store = ApiKeyStore::in_memory().unwrap();
+        let mut key = new_key("old", vec![ApiScope::Chat]);
+        key.expires_at = Some(Utc::now() - chrono::Duration::minutes(1));
+        let (_, secret) = store.create(key).unwrap();
+        assert!(store.authenticate(&secret).unwrap().is_none());
+
+        assert!(store.create(new_key(" ", vec![ApiScope::Chat])).is_err());
+        assert!(store.create(new_key("none", vec![])).is_err());
+    }
+}
@@ -4,10 +4,22 @@
 //! Built with axum for async HTTP handling.
 
 pub mod error;
+pub mod files;
 pub mod handlers;
+pub mod health;
+pub mod hooks;
+pub mod keys;
 pub mod middleware;
+pub mod oidc;
+pub mod openapi;
 pub mod routes;
 pub mod server;
 
-pub use error::{ApiError, Result};
-pub use server::start_server;
+pub use error::{ApiError, FileError, HookError, KeyError, OidcError, Result};
+pub use files::{FileInfo, FileKind, FileStore};
+pub use health::{HealthCheck, ReadinessReport, TaskCheck};
+pub use hooks::{HookConfig, HookProvider, HooksConfig, WebhookHandler};
+pub use keys::{ApiKeyInfo, ApiKeyStore, ApiScope, NewApiKey};
+pub use oidc::{OidcConfig, OidcVerifier};
+pub use openapi::ApiDoc;
+pub use server::{start_server, ApiServices};
@@ -3,18 +3,32 @@
 //! Provides API key authentication for protected endpoints.
 
 use axum::{
-    extract::Request,
+    extract::{Request, State},
     http::{header, StatusCode},
     middleware::Next,
     response::Response,
 };
+use tracing::{error, warn};
+
+use cc_core::audit::{AuditEventType, AuditLevel};
+use cc_core::webhook::tokens_match;
+
+use crate::keys::{ApiKeyInfo, ApiScope};
+use crate::oidc::OidcVerifier;
+use crate::server::AppState;
 
 /// Authentication extractor
 pub struct Authenticated;
 
 /// API key authentication middleware
+///
+/// Accepts the shared key (`API_KEY` / `api.key`), keys from the key store
+/// and JWTs from the OIDC provider, checks the scope the route needs, and
+/// adds the [`ApiKeyInfo`] to the request extensions. Requests are allowed
+/// without a key while neither kind of key is configured.
 pub async fn auth_middleware(
-    request: Request,
+    State(state): State<AppState>,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
     // Get API key from header
@@ -22,41 +36,106 @@ pub async fn auth_middleware(
         .headers()
         .get(header::AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
-        .and_then(|value| {
-            value
-                .strip_prefix("Bearer ")
-                .map(|s| s.to_string())
-        });
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|s| s.to_string());
+    let client_ip = request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let path = request.uri().path().to_string();
+
+    let static_key = state
+        .config
+        .api_key
+        .clone()
+        .or_else(|| std::env::var("API_KEY").ok());
+    let store_has_keys = match state.api_keys.as_ref().map(|store| store.has_active_keys()) {
+        Some(Ok(has_keys)) => has_keys,
+        Some(Err(e)) => {
+            error!("Failed to read API keys: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        None => false,
+    };
 
     // If no API key is configured, allow all requests
     // This is useful for development/testing
     // In production, you should always configure an API key
-    let expected_key = std::env::var("API_KEY").ok();
+    if static_key.is_none() && !store_has_keys && state.oidc.is_none() {
+        return Ok(next.run(request).await);
+    }
 
-    match (api_key, expected_key) {
-        (Some(key), Some(expected)) if key == expected => {
-            // Valid API key
-            Ok(next.run(request).await)
-        }
-        (None, Some(_)) => {
-            // API key required but not provided
-            Err(StatusCode::UNAUTHORIZED)
-        }
-        (Some(_), Some(_)) => {
-            // Invalid API key
-            Err(StatusCode::UNAUTHORIZED)
+    let key = match api_key {
+        Some(provided)
+            if static_key
+                .as_deref()
+                .is_some_and(|expected| tokens_match(expected, &provided)) =>
+        {
+            Some(ApiKeyInfo::static_key())
         }
-        (_, None) => {
-            // No API key configured, allow request
-            Ok(next.run(request).await)
+        Some(provided) if state.oidc.is_some() && OidcVerifier::is_jwt(&provided) => {
+            let oidc = state.oidc.as_ref().expect("checked above");
+            match oidc.verify(&provided).await {
+                Ok(user) => Some(user),
+                Err(e) => {
+                    warn!("Rejected JWT: {}", e);
+                    None
+                }
+            }
         }
+        Some(provided) => match state.api_keys.as_ref().map(|store| store.authenticate(&provided)) {
+            Some(Ok(key)) => key,
+            Some(Err(e)) => {
+                error!("Failed to authenticate API key: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+            None => None,
+        },
+        None => None,
+    };
+    let Some(key) = key else {
+        state.audit(
+            AuditEventType::AuthenticationFailure,
+            AuditLevel::Warning,
+            None,
+            client_ip.as_deref(),
+            &path,
+            "Missing or invalid API key",
+        );
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    let required = ApiScope::required_for(&path);
+    if !key.allows(required) {
+        warn!("API key {} lacks the {:?} scope for {}", key.id, required, path);
+        state.audit(
+            AuditEventType::AccessDenied,
+            AuditLevel::Warning,
+            Some(&key.id),
+            client_ip.as_deref(),
+            &path,
+            format!("Missing scope {:?}", required),
+        );
+        return Err(StatusCode::FORBIDDEN);
     }
+
+    state.audit(
+        AuditEventType::ApiKeyUsed,
+        AuditLevel::Info,
+        Some(&key.id),
+        client_ip.as_deref(),
+        &path,
+        format!("{} {}", request.method(), path),
+    );
+    request.extensions_mut().insert(key);
+    Ok(next.run(request).await)
 }
 
 /// Simple API key validation (for use in handlers)
 pub fn validate_api_key(provided: Option<&str>, expected: Option<&str>) -> bool {
     match (provided, expected) {
-        (Some(p), Some(e)) => p == e,
+        (Some(p), Some(e)) => tokens_match(e, p),
         (_, None) => true, // No key configured, allow
         (None, Some(_)) => false, // Key required but not provided
     }
@@ -77,5 +156,7 @@ mod tests {
         assert!(!validate_api_key(None, Some("secret")));
         assert!(!validate_api_key(Some("wrong"), Some("secret")));
         assert!(validate_api_key(Some("secret"), Some("secret")));
+        assert!(!validate_api_key(Some("secreT"), Some("secret")));
+        assert!(!validate_api_key(Some("secret2"), Some("secret")));
     }
 }
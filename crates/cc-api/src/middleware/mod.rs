@@ -1,6 +1,7 @@
 //! Middleware modules
 //!
-//! Contains authentication and rate limiting middleware.
+//! Contains authentication, rate limiting and timeout middleware.
 
 pub mod auth;
 pub mod rate_limit;
+pub mod timeout;
@@ -1,19 +1,56 @@
 //! Rate limiting middleware
 //!
 //! Provides request rate limiting to prevent API abuse.
+//!
+//! Each limit is a token bucket holding up to `max_requests` tokens that
+//! refills over `window`, so clients can burst up to the limit and then
+//! continue at the steady rate. Buckets live in memory, or in Redis when
+//! several gateway instances share their limits.
 
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
+use cc_core::audit::{AuditEventType, AuditLevel};
+use cc_core::RateLimitSettings;
 use tokio::sync::RwLock;
-use tracing::warn;
+use tracing::{info, warn};
+
+use crate::keys::ApiKeyInfo;
+use crate::server::AppState;
+
+/// Prefix of the Redis keys holding buckets
+const REDIS_PREFIX: &str = "cc-gateway:ratelimit:";
+
+/// Token bucket update (KEYS[1]: bucket, ARGV: capacity, window in ms)
+///
+/// Returns the milliseconds to wait before the next request, 0 if allowed.
+const REDIS_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local rate = capacity / tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(bucket[1]) or capacity
+local updated = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - updated) * rate)
+local wait = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+else
+  wait = math.ceil((1 - tokens) / rate)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+redis.call('PEXPIRE', KEYS[1], tonumber(ARGV[2]) + 1000)
+return wait
+"#;
 
 /// Rate limiter configuration
 #[derive(Clone)]
@@ -33,18 +70,28 @@ impl Default for RateLimitConfig {
     }
 }
 
-/// Client rate limit state
+/// Token bucket state
 #[derive(Clone)]
-struct ClientState {
-    request_count: u32,
-    window_start: Instant,
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
 }
 
-/// In-memory rate limiter
+/// Where buckets are kept
+#[derive(Clone)]
+enum Backend {
+    Memory(Arc<RwLock<HashMap<String, Bucket>>>),
+    Redis {
+        connection: redis::aio::MultiplexedConnection,
+        script: Arc<redis::Script>,
+    },
+}
+
+/// Token bucket rate limiter
 #[derive(Clone)]
 pub struct RateLimiter {
     config: RateLimitConfig,
-    clients: Arc<RwLock<HashMap<String, ClientState>>>,
+    backend: Backend,
 }
 
 impl RateLimiter {
@@ -57,44 +104,109 @@ impl RateLimiter {
     pub fn with_config(config: RateLimitConfig) -> Self {
         Self {
             config,
-            clients: Arc::new(RwLock::new(HashMap::new())),
+            backend: Backend::Memory(Arc::new(RwLock::new(HashMap::new()))),
+        }
+    }
+
+    /// Create a rate limiter whose buckets are shared through Redis
+    pub async fn redis(url: &str, config: RateLimitConfig) -> redis::RedisResult<Self> {
+        let connection = redis::Client::open(url)?
+            .get_multiplexed_async_connection()
+            .await?;
+        Ok(Self {
+            config,
+            backend: Backend::Redis {
+                connection,
+                script: Arc::new(redis::Script::new(REDIS_SCRIPT)),
+            },
+        })
+    }
+
+    /// Create the limiter described by `[api.rate_limit]`
+    ///
+    /// Falls back to in-memory buckets when Redis is unreachable.
+    pub async fn from_settings(settings: &RateLimitSettings) -> Self {
+        let Some(url) = &settings.redis_url else {
+            return Self::new();
+        };
+        match Self::redis(url, RateLimitConfig::default()).await {
+            Ok(limiter) => {
+                info!("API rate limits shared through Redis");
+                limiter
+            }
+            Err(e) => {
+                warn!("Redis rate limiting unavailable, using in-memory limits: {}", e);
+                Self::new()
+            }
         }
     }
 
     /// Check if a client is allowed to make a request
     pub async fn check(&self, client_id: &str) -> bool {
-        let mut clients = self.clients.write().await;
-        let now = Instant::now();
+        self.acquire(client_id, self.config.max_requests).await.is_ok()
+    }
 
-        let state = clients.entry(client_id.to_string()).or_insert(ClientState {
-            request_count: 0,
-            window_start: now,
-        });
+    /// Take a token from `bucket`, which holds up to `max_requests` tokens
+    /// per window
+    ///
+    /// Returns how long to wait when the bucket is empty.
+    pub async fn acquire(&self, bucket: &str, max_requests: u32) -> Result<(), Duration> {
+        let max_requests = max_requests.max(1);
+        match &self.backend {
+            Backend::Memory(buckets) => {
+                let mut buckets = buckets.write().await;
+                let now = Instant::now();
+                let capacity = f64::from(max_requests);
+                let rate = capacity / self.config.window.as_secs_f64();
 
-        // Reset window if expired
-        if now.duration_since(state.window_start) > self.config.window {
-            state.request_count = 0;
-            state.window_start = now;
-        }
+                let state = buckets.entry(bucket.to_string()).or_insert(Bucket {
+                    tokens: capacity,
+                    updated: now,
+                });
+                let elapsed = now.duration_since(state.updated).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * rate).min(capacity);
+                state.updated = now;
 
-        // Check limit
-        if state.request_count >= self.config.max_requests {
-            warn!("Rate limit exceeded for client: {}", client_id);
-            return false;
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    Ok(())
+                } else {
+                    warn!("Rate limit exceeded for client: {}", bucket);
+                    Err(Duration::from_secs_f64((1.0 - state.tokens) / rate))
+                }
+            }
+            Backend::Redis { connection, script } => {
+                let wait: redis::RedisResult<u64> = script
+                    .key(format!("{}{}", REDIS_PREFIX, bucket))
+                    .arg(max_requests)
+                    .arg(self.config.window.as_millis() as u64)
+                    .invoke_async(&mut connection.clone())
+                    .await;
+                match wait {
+                    Ok(0) => Ok(()),
+                    Ok(ms) => {
+                        warn!("Rate limit exceeded for client: {}", bucket);
+                        Err(Duration::from_millis(ms))
+                    }
+                    Err(e) => {
+                        // Don't take the API down with Redis
+                        warn!("Rate limit check failed, allowing request: {}", e);
+                        Ok(())
+                    }
+                }
+            }
         }
-
-        state.request_count += 1;
-        true
     }
 
-    /// Cleanup expired entries (should be called periodically)
+    /// Cleanup full buckets (should be called periodically)
     pub async fn cleanup(&self) {
-        let mut clients = self.clients.write().await;
-        let now = Instant::now();
+        if let Backend::Memory(buckets) = &self.backend {
+            let mut buckets = buckets.write().await;
+            let now = Instant::now();
 
-        clients.retain(|_, state| {
-            now.duration_since(state.window_start) <= self.config.window
-        });
+            // Redis expires its buckets itself
+            buckets.retain(|_, state| now.duration_since(state.updated) <= self.config.window);
+        }
     }
 }
 
@@ -104,30 +216,95 @@ impl Default for RateLimiter {
     }
 }
 
+/// Buckets a request draws from, with their per-minute limits
+///
+/// The client IP and the API key each get a bucket for the global limit
+/// and one for the matching route's limit. A key's own limit replaces the
+/// global per-key limit.
+pub fn applicable_limits(
+    settings: &RateLimitSettings,
+    path: &str,
+    client_ip: Option<&str>,
+    key: Option<&ApiKeyInfo>,
+) -> Vec<(String, u32)> {
+    let route = settings.route(path);
+    let mut limits = Vec::new();
+
+    if let Some(ip) = client_ip {
+        if let Some(limit) = settings.per_ip {
+            limits.push((format!("ip:*:{}", ip), limit));
+        }
+        if let Some(route) = route {
+            if let Some(limit) = route.per_ip {
+                limits.push((format!("ip:{}:{}", route.path, ip), limit));
+            }
+        }
+    }
+    if let Some(key) = key {
+        if let Some(limit) = key.rate_limit_per_minute.or(settings.per_key) {
+            limits.push((format!("key:*:{}", key.id), limit));
+        }
+        if let Some(route) = route {
+            if let Some(limit) = route.per_key {
+                limits.push((format!("key:{}:{}", route.path, key.id), limit));
+            }
+        }
+    }
+    limits
+}
+
 /// Rate limiting middleware
+///
+/// Runs after authentication so that the request's API key is known.
+/// Rejected requests get `429 Too Many Requests` with `Retry-After`.
 pub async fn rate_limit_middleware(
-    limiter: Arc<RateLimiter>,
+    State(state): State<AppState>,
     request: Request,
     next: Next,
-) -> Result<Response, StatusCode> {
+) -> Response {
     // Use client IP or API key as identifier
-    let client_id = request
+    let client_ip = request
         .headers()
         .get("x-forwarded-for")
         .and_then(|v| v.to_str().ok())
-        .unwrap_or("unknown")
-        .to_string();
+        .and_then(|v| v.split(',').next())
+        .map(|v| v.trim().to_string())
+        .or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        });
+    let key = request.extensions().get::<ApiKeyInfo>();
+    let path = request.uri().path();
 
-    if !limiter.check(&client_id).await {
-        return Err(StatusCode::TOO_MANY_REQUESTS);
+    let limits = applicable_limits(&state.config.api.rate_limit, path, client_ip.as_deref(), key);
+    for (bucket, limit) in limits {
+        if let Err(wait) = state.rate_limiter.acquire(&bucket, limit).await {
+            state.audit(
+                AuditEventType::RateLimitExceeded,
+                AuditLevel::Warning,
+                key.map(|k| k.id.as_str()),
+                client_ip.as_deref(),
+                path,
+                format!("More than {} requests per minute ({})", limit, bucket),
+            );
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+            )
+                .into_response();
+        }
     }
 
-    Ok(next.run(request).await)
+    next.run(request).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cc_core::RouteRateLimit;
 
     #[tokio::test]
     async fn test_rate_limiter_allows_within_limit() {
@@ -162,4 +339,50 @@ mod tests {
         assert!(limiter.check("client2").await);
         assert!(limiter.check("client2").await);
     }
+
+    #[tokio::test]
+    async fn test_rate_limiter_refills_and_reports_wait() {
+        let limiter = RateLimiter::with_config(RateLimitConfig {
+            max_requests: 60,
+            window: Duration::from_millis(600),
+        });
+
+        assert!(limiter.acquire("key_1", 1).await.is_ok());
+        let wait = limiter.acquire("key_1", 1).await.unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_millis(600));
+        assert!(limiter.acquire("key_2", 2).await.is_ok());
+
+        tokio::time::sleep(wait + Duration::from_millis(20)).await;
+        assert!(limiter.acquire("key_1", 1).await.is_ok());
+    }
+
+    #[test]
+    fn test_applicable_limits() {
+        let settings = RateLimitSettings {
+            per_ip: Some(100),
+            per_key: Some(50),
+            redis_url: None,
+            routes: vec![RouteRateLimit {
+                path: "/api/chat".to_string(),
+                per_ip: None,
+                per_key: Some(10),
+            }],
+        };
+        let mut key = ApiKeyInfo::static_key();
+
+        let limits = applicable_limits(&settings, "/api/chat", Some("10.0.0.1"), Some(&key));
+        assert_eq!(
+            limits,
+            vec![
+                ("ip:*:10.0.0.1".to_string(), 100),
+                ("key:*:static".to_string(), 50),
+                ("key:/api/chat:static".to_string(), 10),
+            ]
+        );
+
+        key.rate_limit_per_minute = Some(5);
+        let limits = applicable_limits(&settings, "/api/tools", None, Some(&key));
+        assert_eq!(limits, vec![("key:*:static".to_string(), 5)]);
+        assert!(applicable_limits(&RateLimitSettings::default(), "/api/chat", Some("ip"), None).is_empty());
+    }
 }
@@ -0,0 +1,44 @@
+//! Request timeout middleware
+//!
+//! Answers requests that take longer than the route's timeout with
+//! `504 Gateway Timeout`.
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::warn;
+
+use crate::handlers::ErrorResponse;
+use crate::server::AppState;
+
+/// Request timeout middleware
+///
+/// The timeout (`api.request_timeout_secs`, or the matching
+/// `[[api.timeouts]]` entry) covers producing the response headers, so
+/// streamed bodies are not cut off.
+pub async fn timeout_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let timeout = state.config.api.timeout_for(&path);
+
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!("Request to {} timed out after {:?}", path, timeout);
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(ErrorResponse {
+                    error: format!("Request timed out after {} seconds", timeout.as_secs()),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,423 @@
+//! OIDC / JWT authentication
+//!
+//! 静的な API キーの代わりに、OIDC プロバイダーが発行した JWT（`Authorization: Bearer <JWT>`）
+//! で認証できます。署名鍵は発行者の JWKS から取得してキャッシュし、未知の `kid` を
+//! 受け取ったときにも取り直します（鍵のローテーション対応）。
+//! 署名アルゴリズムはトークンのヘッダーではなく、鍵の `alg`（ない場合は設定）で決まります。
+//!
+//! 検証に通ったトークンはゲートウェイのユーザー（`oidc:<sub>`）に対応付けられ、
+//! API キーと同じようにスコープ・リクエスト上限・監査ログに使われます。
+//! スコープはトークンの `scope` クレーム（空白区切りまたは配列）の `chat` / `tools` /
+//! `admin` から決まります。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::error::OidcError;
+use crate::keys::{ApiKeyInfo, ApiScope};
+
+/// Prefix of the key IDs of OIDC users
+pub const USER_ID_PREFIX: &str = "oidc:";
+
+/// Minimum time between JWKS refreshes triggered by unknown key IDs
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// OIDC provider settings
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Issuer URL (`iss` claim and discovery base)
+    pub issuer: String,
+    /// Expected `aud` claim (not checked if None)
+    pub audience: Option<String>,
+    /// JWKS URL (discovered from the issuer if None)
+    pub jwks_url: Option<String>,
+    /// Claim identifying the user
+    pub user_claim: String,
+    /// Claim listing the granted scopes
+    pub scope_claim: String,
+    /// Accepted signing algorithms for keys that do not name their `alg`
+    pub algorithms: Vec<Algorithm>,
+    /// Scopes of tokens without a known scope
+    pub default_scopes: Vec<ApiScope>,
+    /// Requests per minute per user (None = no limit)
+    pub rate_limit_per_minute: Option<u32>,
+    /// How long fetched signing keys are used
+    pub jwks_cache: Duration,
+}
+
+impl OidcConfig {
+    /// Create a config for `issuer` with default claims
+    pub fn new(issuer: impl Into<String>) -> Self {
+        Self {
+            issuer: issuer.into(),
+            audience: None,
+            jwks_url: None,
+            user_claim: "sub".to_string(),
+            scope_claim: "scope".to_string(),
+            algorithms: vec![Algorithm::RS256],
+            default_scopes: vec![ApiScope::Chat],
+            rate_limit_per_minute: None,
+            jwks_cache: Duration::from_secs(3600),
+        }
+    }
+
+    /// Load from `API_OIDC_*` environment variables
+    ///
+    /// Returns None unless `API_OIDC_ISSUER` is set.
+    pub fn from_env() -> Option<Self> {
+        let issuer = std::env::var("API_OIDC_ISSUER").ok().filter(|s| !s.is_empty())?;
+        let mut config = Self::new(issuer);
+        config.audience = std::env::var("API_OIDC_AUDIENCE").ok();
+        config.jwks_url = std::env::var("API_OIDC_JWKS_URL").ok();
+        if let Ok(claim) = std::env::var("API_OIDC_USER_CLAIM") {
+            config.user_claim = claim;
+        }
+        if let Ok(claim) = std::env::var("API_OIDC_SCOPE_CLAIM") {
+            config.scope_claim = claim;
+        }
+        if let Ok(algorithms) = std::env::var("API_OIDC_ALGORITHMS") {
+            let algorithms: Vec<Algorithm> = algorithms
+                .split(',')
+                .filter_map(|alg| alg.trim().parse().ok())
+                .collect();
+            if !algorithms.is_empty() {
+                config.algorithms = algorithms;
+            }
+        }
+        config.rate_limit_per_minute = std::env::var("API_OIDC_RATE_LIMIT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|limit| *limit > 0);
+        if let Some(secs) = std::env::var("API_OIDC_JWKS_CACHE_SECS").ok().and_then(|v| v.parse().ok()) {
+            config.jwks_cache = Duration::from_secs(secs);
+        }
+        Some(config)
+    }
+}
+
+/// Signing keys with the time they were fetched
+struct CachedJwks {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+/// Discovery document (only the field we need)
+#[derive(Deserialize)]
+struct Discovery {
+    jwks_uri: String,
+}
+
+/// Validates JWTs from an OIDC provider
+pub struct OidcVerifier {
+    config: OidcConfig,
+    http: reqwest::Client,
+    jwks: RwLock<Option<Arc<CachedJwks>>>,
+}
+
+impl OidcVerifier {
+    /// Create a verifier (keys are fetched on first use)
+    pub fn new(config: OidcConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    /// Create a verifier with fixed signing keys (for testing)
+    #[cfg(test)]
+    fn with_keys(config: OidcConfig, keys: JwkSet) -> Self {
+        let verifier = Self::new(config);
+        *verifier.jwks.try_write().unwrap() = Some(Arc::new(CachedJwks {
+            keys,
+            fetched_at: Instant::now(),
+        }));
+        verifier
+    }
+
+    /// Whether `token` looks like a JWT rather than an API key
+    pub fn is_jwt(token: &str) -> bool {
+        token.split('.').count() == 3 && token.starts_with("ey")
+    }
+
+    /// Validate `token` and map it to a gateway user
+    pub async fn verify(&self, token: &str) -> Result<ApiKeyInfo, OidcError> {
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|e| OidcError::InvalidToken(e.to_string()))?;
+        let kid = header.kid.as_deref();
+        let jwk = match find_key(&self.cached_keys(false).await?.keys, kid) {
+            Some(jwk) => jwk,
+            // Unknown key: the provider may have rotated its keys
+            None => find_key(&self.cached_keys(true).await?.keys, kid)
+                .ok_or_else(|| OidcError::InvalidToken(format!("Unknown signing key: {:?}", kid)))?,
+        };
+        let key = DecodingKey::from_jwk(&jwk).map_err(|e| OidcError::Jwks(e.to_string()))?;
+
+        // The key (or the config) decides the algorithm, never the token itself
+        let algorithms = match jwk.common.key_algorithm {
+            Some(alg) => vec![alg
+                .to_string()
+                .parse::<Algorithm>()
+                .map_err(|_| OidcError::Jwks(format!("Signing key has a non-signing alg: {}", alg)))?],
+            None => self.config.algorithms.clone(),
+        };
+        if !algorithms.contains(&header.alg) {
+            return Err(OidcError::InvalidToken(format!(
+                "Token algorithm {:?} is not allowed for its signing key",
+                header.alg
+            )));
+        }
+        let mut validation = Validation::new(header.alg);
+        validation.algorithms = algorithms;
+        validation.set_issuer(&[&self.config.issuer]);
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims = jsonwebtoken::decode::<serde_json::Map<String, Value>>(token, &key, &validation)
+            .map_err(|e| OidcError::InvalidToken(e.to_string()))?
+            .claims;
+        self.user(&claims)
+    }
+
+    /// Map validated claims to a gateway user
+    fn user(&self, claims: &serde_json::Map<String, Value>) -> Result<ApiKeyInfo, OidcError> {
+        let user = claims
+            .get(&self.config.user_claim)
+            .and_then(Value::as_str)
+            .ok_or_else(|| OidcError::MissingClaim(self.config.user_claim.clone()))?;
+        let name = ["email", "preferred_username", "name"]
+            .iter()
+            .find_map(|claim| claims.get(*claim).and_then(Value::as_str))
+            .unwrap_or(user);
+
+        let mut scopes: Vec<ApiScope> = match claims.get(&self.config.scope_claim) {
+            Some(Value::String(scopes)) => scopes.split_whitespace().filter_map(parse_scope).collect(),
+            Some(Value::Array(scopes)) => scopes.iter().filter_map(Value::as_str).filter_map(parse_scope).collect(),
+            _ => Vec::new(),
+        };
+        if scopes.is_empty() {
+            scopes = self.config.default_scopes.clone();
+        }
+        let timestamp = |claim: &str| {
+            claims
+                .get(claim)
+                .and_then(Value::as_i64)
+                .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+        };
+
+        Ok(ApiKeyInfo {
+            id: format!("{}{}", USER_ID_PREFIX, user),
+            name: name.to_string(),
+            scopes,
+            created_at: timestamp("iat").unwrap_or_else(Utc::now),
+            expires_at: timestamp("exp"),
+            rate_limit_per_minute: self.config.rate_limit_per_minute,
+            last_used_at: None,
+            revoked: false,
+        })
+    }
+
+    /// Current signing keys, fetching them when stale (or when `refresh`
+    /// is set and the last fetch is not too recent)
+    async fn cached_keys(&self, refresh: bool) -> Result<Arc<CachedJwks>, OidcError> {
+        if let Some(cached) = self.jwks.read().await.as_ref() {
+            let age = cached.fetched_at.elapsed();
+            let max_age = if refresh { MIN_REFRESH_INTERVAL } else { self.config.jwks_cache };
+            if age < max_age {
+                return Ok(Arc::clone(cached));
+            }
+        }
+
+        let mut slot = self.jwks.write().await;
+        // Another request may have refreshed the keys meanwhile
+        if let Some(cached) = slot.as_ref() {
+            if cached.fetched_at.elapsed() < MIN_REFRESH_INTERVAL {
+                return Ok(Arc::clone(cached));
+            }
+        }
+        let cached = Arc::new(CachedJwks {
+            keys: self.fetch_keys().await?,
+            fetched_at: Instant::now(),
+        });
+        *slot = Some(Arc::clone(&cached));
+        Ok(cached)
+    }
+
+    async fn fetch_keys(&self) -> Result<JwkSet, OidcError> {
+        let jwks_url = match &self.config.jwks_url {
+            Some(url) => url.clone(),
+            None => {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.config.issuer.trim_end_matches('/')
+                );
+                self.get_json::<Discovery>(&url)
+                    .await
+                    .map_err(OidcError::Discovery)?
+                    .jwks_uri
+            }
+        };
+        let keys: JwkSet = self.get_json(&jwks_url).await.map_err(OidcError::Jwks)?;
+        info!("Fetched {} OIDC signing key(s) from {}", keys.keys.len(), jwks_url);
+        Ok(keys)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, String> {
+        debug!("Fetching {}", url);
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("{}: {}", url, e))?
+            .json()
+            .await
+            .map_err(|e| format!("{}: {}", url, e))
+    }
+}
+
+/// Scope from a scope claim entry (unknown scopes are ignored)
+fn parse_scope(scope: &str) -> Option<ApiScope> {
+    serde_json::from_value(Value::String(scope.to_string())).ok()
+}
+
+/// Look up a signing key by key ID (the only key when the token has none)
+fn find_key(keys: &JwkSet, kid: Option<&str>) -> Option<Jwk> {
+    match kid {
+        Some(kid) => keys.find(kid).cloned(),
+        None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"test-secret-with-enough-length!!";
+
+    fn keys() -> JwkSet {
+        serde_json::from_value(json!({
+            "keys": [{
+                "kty": "oct",
+                "kid": "k1",
+                "alg": "HS256",
+                "k": URL_SAFE_NO_PAD.encode(SECRET),
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn token(kid: &str, claims: Value) -> String {
+        signed(Algorithm::HS256, kid, claims)
+    }
+
+    fn signed(alg: Algorithm, kid: &str, claims: Value) -> String {
+        let mut header = Header::new(alg);
+        header.kid = Some(kid.to_string());
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn verifier() -> OidcVerifier {
+        let mut config = OidcConfig::new("https://idp.example.com");
+        config.audience = Some("cc-gateway".to_string());
+        config.rate_limit_per_minute = Some(30);
+        OidcVerifier::with_keys(config, keys())
+    }
+
+    #[tokio::test]
+    async fn test_verify_maps_claims() {
+        let exp = Utc::now().timestamp() + 600;
+        let jwt = token(
+            "k1",
+            json!({
+                "iss": "https://idp.example.com",
+                "aud": "cc-gateway",
+                "sub": "u-42",
+                "email": "ada@example.com",
+                "scope": "openid tools",
+                "exp": exp,
+            }),
+        );
+        assert!(OidcVerifier::is_jwt(&jwt));
+        assert!(!OidcVerifier::is_jwt("ccg_0123"));
+
+        let user = verifier().verify(&jwt).await.unwrap();
+        assert_eq!(user.id, "oidc:u-42");
+        assert_eq!(user.name, "ada@example.com");
+        assert_eq!(user.scopes, vec![ApiScope::Tools]);
+        assert_eq!(user.rate_limit_per_minute, Some(30));
+        assert_eq!(user.expires_at.map(|t| t.timestamp()), Some(exp));
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_bad_tokens() {
+        let exp = Utc::now().timestamp() + 600;
+        let claims = |iss: &str, aud: &str| json!({"iss": iss, "aud": aud, "sub": "u", "exp": exp});
+        let verifier = verifier();
+
+        let other_issuer = token("k1", claims("https://evil.example.com", "cc-gateway"));
+        assert!(matches!(verifier.verify(&other_issuer).await, Err(OidcError::InvalidToken(_))));
+
+        let other_audience = token("k1", claims("https://idp.example.com", "other"));
+        assert!(verifier.verify(&other_audience).await.is_err());
+
+        let expired = token(
+            "k1",
+            json!({"iss": "https://idp.example.com", "aud": "cc-gateway", "sub": "u", "exp": 1}),
+        );
+        assert!(verifier.verify(&expired).await.is_err());
+
+        let no_sub = token(
+            "k1",
+            json!({"iss": "https://idp.example.com", "aud": "cc-gateway", "exp": exp}),
+        );
+        assert!(matches!(verifier.verify(&no_sub).await, Err(OidcError::MissingClaim(_))));
+
+        // Default scopes when the token grants none we know
+        let plain = token("k1", claims("https://idp.example.com", "cc-gateway"));
+        assert_eq!(verifier.verify(&plain).await.unwrap().scopes, vec![ApiScope::Chat]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_uses_key_algorithm() {
+        let claims = json!({
+            "iss": "https://idp.example.com",
+            "aud": "cc-gateway",
+            "sub": "u",
+            "exp": Utc::now().timestamp() + 600,
+        });
+
+        // The key says HS256, so an HS384 token made with the same secret is refused
+        let other_alg = signed(Algorithm::HS384, "k1", claims.clone());
+        assert!(matches!(verifier().verify(&other_alg).await, Err(OidcError::InvalidToken(_))));
+
+        // Keys without `alg` only accept the configured algorithms (RS256 by default)
+        let mut unnamed = keys();
+        unnamed.keys[0].common.key_algorithm = None;
+        let mut config = OidcConfig::new("https://idp.example.com");
+        config.audience = Some("cc-gateway".to_string());
+        let jwt = token("k1", claims);
+        let strict = OidcVerifier::with_keys(config.clone(), unnamed.clone());
+        assert!(matches!(strict.verify(&jwt).await, Err(OidcError::InvalidToken(_))));
+
+        config.algorithms = vec![Algorithm::HS256];
+        let configured = OidcVerifier::with_keys(config, unnamed);
+        assert!(configured.verify(&jwt).await.is_ok());
+    }
+}
@@ -0,0 +1,119 @@
+//! OpenAPI 仕様
+//!
+//! ハンドラーの `#[utoipa::path]` 注釈から OpenAPI 3 の仕様を生成し、
+//! `/openapi.json` と Swagger UI（`/docs`）で公開します。
+
+use axum::Router;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::handlers;
+use crate::server::AppState;
+
+/// OpenAPI document of the HTTP API
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "cc-gateway API"),
+    paths(
+        handlers::health,
+        handlers::healthz,
+        handlers::readyz,
+        handlers::chat,
+        handlers::count_tokens,
+        handlers::memory,
+        handlers::session_info,
+        handlers::clear_session,
+        handlers::list_sessions,
+        handlers::create_session,
+        handlers::get_session,
+        handlers::rename_session,
+        handlers::delete_session,
+        handlers::session_messages,
+        handlers::fork_session,
+        handlers::list_tools,
+        handlers::execute_tool,
+        handlers::list_personas,
+        handlers::list_schedules,
+        handlers::create_schedule,
+        handlers::delete_schedule,
+        handlers::pause_schedule,
+        handlers::resume_schedule,
+        handlers::run_schedule,
+        handlers::schedule_runs,
+        handlers::list_workflows,
+        handlers::run_workflow,
+        handlers::list_agents,
+        handlers::list_agent_tasks,
+        handlers::enqueue_agent_task,
+        handlers::get_agent_task,
+        handlers::cancel_agent_task,
+        handlers::create_job,
+        handlers::get_job,
+        handlers::upload_file,
+        handlers::get_file,
+        handlers::delete_file,
+        handlers::list_api_keys,
+        handlers::create_api_key,
+        handlers::revoke_api_key,
+        handlers::purge_identity,
+        handlers::receive_hook,
+    ),
+    modifiers(&BearerAuth),
+    security(("bearer" = [])),
+    tags(
+        (name = "chat", description = "Chat, token counting and memory"),
+        (name = "sessions", description = "Conversation sessions"),
+        (name = "tools", description = "Direct tool execution"),
+        (name = "personas", description = "Configured personas"),
+        (name = "schedules", description = "Scheduled tasks"),
+        (name = "workflows", description = "Workflows"),
+        (name = "agents", description = "Sub-agents and the task queue"),
+        (name = "jobs", description = "Long-running agent jobs"),
+        (name = "files", description = "Uploaded files"),
+        (name = "keys", description = "API key management (admin scope)"),
+        (name = "identities", description = "Deleting a user's data (admin scope)"),
+        (name = "hooks", description = "Inbound webhooks (HMAC signed)"),
+        (name = "health", description = "Health checks and orchestrator probes"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the bearer token (API key or OIDC JWT) scheme
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// `/openapi.json` and the Swagger UI at `/docs`
+pub fn openapi_routes() -> Router<AppState> {
+    SwaggerUi::new("/docs")
+        .url("/openapi.json", ApiDoc::openapi())
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_covers_routes() {
+        let spec = ApiDoc::openapi();
+        for path in ["/api/chat", "/api/sessions/{id}", "/api/jobs/{id}", "/api/keys", "/hooks/{name}"] {
+            assert!(spec.paths.paths.contains_key(path), "missing {}", path);
+        }
+        let json = serde_json::to_value(&spec).unwrap();
+        assert!(json["components"]["schemas"]["ChatRequest"].is_object());
+        assert!(json["components"]["securitySchemes"]["bearer"].is_object());
+
+        // Documented paths use the same syntax as the router, which rejects invalid ones
+        let _ = crate::routes::routes();
+    }
+}
@@ -3,19 +3,39 @@
 //! Defines all HTTP API endpoints.
 
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{delete, get, post},
     Router,
 };
 
 use crate::handlers::{
-    chat, clear_session, health, memory, session_info,
+    chat, clear_session, count_tokens, health, healthz, memory, readyz, session_info,
     // Session management
-    delete_session, get_session, list_sessions,
+    create_session, delete_session, fork_session, get_session, list_sessions, rename_session,
+    session_messages,
     // Tools
-    list_tools,
+    execute_tool, list_tools,
+    // Personas
+    list_personas,
     // Schedules
-    list_schedules,
+    create_schedule, delete_schedule, list_schedules, pause_schedule, resume_schedule,
+    run_schedule, schedule_runs,
+    // Workflows
+    list_workflows, run_workflow,
+    // Sub-agents
+    cancel_agent_task, enqueue_agent_task, get_agent_task, list_agent_tasks, list_agents,
+    // Jobs
+    create_job, get_job,
+    // Inbound webhooks
+    receive_hook,
+    // Uploaded files
+    delete_file, get_file, upload_file,
+    // API keys
+    create_api_key, list_api_keys, revoke_api_key,
+    // Identities
+    purge_identity,
 };
+use crate::openapi::openapi_routes;
 use crate::server::AppState;
 
 /// Create the API router (unprotected routes only)
@@ -23,6 +43,13 @@ pub fn public_routes() -> Router<AppState> {
     Router::new()
         // Health check - no authentication required
         .route("/health", get(health))
+        // Orchestrator probes (liveness / dependency checks)
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        // Inbound webhooks - authenticated by their HMAC signature
+        .route("/hooks/{name}", post(receive_hook))
+        // OpenAPI spec (/openapi.json) and Swagger UI (/docs)
+        .merge(openapi_routes())
 }
 
 /// Create the protected API router (requires authentication)
@@ -30,19 +57,50 @@ pub fn protected_routes() -> Router<AppState> {
     Router::new()
         // Chat endpoint
         .route("/api/chat", post(chat))
+        // Token counting
+        .route("/api/tokens/count", post(count_tokens))
         // Session management (legacy endpoints)
-        .route("/api/session/:session_id", get(session_info))
-        .route("/api/session/:session_id", delete(clear_session))
+        .route("/api/session/{session_id}", get(session_info).delete(clear_session))
         // Memory endpoint
         .route("/api/memory", post(memory))
-        // Session management API (GET/DELETE only for now - POST has axum 0.8 compatibility issues)
-        .route("/api/sessions", get(list_sessions))
-        .route("/api/sessions/:id", get(get_session))
-        .route("/api/sessions/:id", delete(delete_session))
-        // Tools API (GET only for now)
+        // Session management API
+        .route("/api/sessions", get(list_sessions).post(create_session))
+        .route(
+            "/api/sessions/{id}",
+            get(get_session).patch(rename_session).delete(delete_session),
+        )
+        .route("/api/sessions/{id}/messages", get(session_messages))
+        .route("/api/sessions/{id}/fork", post(fork_session))
+        // Tools API
         .route("/api/tools", get(list_tools))
+        .route("/api/tools/{name}/execute", post(execute_tool))
+        // Personas API
+        .route("/api/personas", get(list_personas))
         // Schedules API
-        .route("/api/schedules", get(list_schedules))
+        .route("/api/schedules", get(list_schedules).post(create_schedule))
+        .route("/api/schedules/{name}", delete(delete_schedule))
+        .route("/api/schedules/{name}/pause", post(pause_schedule))
+        .route("/api/schedules/{name}/resume", post(resume_schedule))
+        .route("/api/schedules/{name}/run", post(run_schedule))
+        .route("/api/schedules/{name}/runs", get(schedule_runs))
+        // Workflows API
+        .route("/api/workflows", get(list_workflows))
+        .route("/api/workflows/{name}/run", post(run_workflow))
+        // Sub-agents API
+        .route("/api/agents", get(list_agents))
+        .route("/api/agents/tasks", get(list_agent_tasks).post(enqueue_agent_task))
+        .route("/api/agents/tasks/{id}", get(get_agent_task).delete(cancel_agent_task))
+        // Jobs API (long-running agent tasks on the task queue)
+        .route("/api/jobs", post(create_job))
+        .route("/api/jobs/{id}", get(get_job).delete(cancel_agent_task))
+        // Uploaded files (the store enforces its own size limit)
+        .route("/api/files", post(upload_file).layer(DefaultBodyLimit::disable()))
+        .route("/api/files/{id}", get(get_file).delete(delete_file))
+        // API keys (admin scope)
+        .route("/api/keys", get(list_api_keys).post(create_api_key))
+        .route("/api/keys/{id}", delete(revoke_api_key))
+        // Data of a user (admin scope)
+        .route("/api/identities/{identity}", delete(purge_identity))
 }
 
 /// Create the full API router (for backward compatibility without auth)
@@ -4,17 +4,33 @@
 
 use crate::error::Result;
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
     Router,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::info;
+use tracing::{info, warn};
 
-use cc_core::{ClaudeClient, Config, SessionManager, ToolManager};
+use cc_core::audit::{AuditEventType, AuditLevel, AuditLogger};
+use cc_core::{
+    CancellationToken, ClaudeClient, Config, ContextProvider, Memorizer, MemoryBackend, PersonaRegistry, PromptLibrary, SessionManager, SubAgentManager,
+    TaskQueue, ToolManager,
+};
+use cc_schedule::SchedulerHandle;
+use cc_voice::WhisperClient;
+use cc_workflow::WorkflowEngine;
 
+use crate::files::FileStore;
+use crate::health::{DatabaseCheck, HealthCheck, LlmCheck};
+use crate::hooks::WebhookHandler;
+use crate::keys::ApiKeyStore;
+use crate::oidc::OidcVerifier;
 use crate::middleware::auth::auth_middleware;
+use crate::middleware::rate_limit::{rate_limit_middleware, RateLimiter};
+use crate::middleware::timeout::timeout_middleware;
 use crate::routes::{protected_routes, public_routes};
 
 /// 共有アプリケーション状態
@@ -24,61 +40,218 @@ pub struct AppState {
     pub claude_client: Arc<ClaudeClient>,
     pub session_manager: Arc<SessionManager>,
     pub tool_manager: Arc<ToolManager>,
+    /// System prompt templates (optional)
+    pub prompts: Option<Arc<PromptLibrary>>,
+    /// Configured personas
+    pub personas: Arc<PersonaRegistry>,
+    /// Running scheduler (None when disabled)
+    pub scheduler: Option<SchedulerHandle>,
+    /// Workflow engine (None when disabled)
+    pub workflows: Option<Arc<WorkflowEngine>>,
+    /// Inbound webhooks (None when no hooks are configured)
+    pub hooks: Option<Arc<WebhookHandler>>,
+    /// Sub-agents (None when not exposed)
+    pub sub_agents: Option<Arc<SubAgentManager>>,
+    /// Persistent task queue (None when disabled)
+    pub task_queue: Option<TaskQueue>,
+    /// Uploaded files (None when uploads are disabled)
+    pub files: Option<Arc<FileStore>>,
+    /// Transcribes uploaded audio (None: audio uploads are rejected)
+    pub transcriber: Option<Arc<WhisperClient>>,
+    /// Issued API keys (None: only the shared key is accepted)
+    pub api_keys: Option<Arc<ApiKeyStore>>,
+    /// Validates JWTs from an OIDC provider (None: JWTs are not accepted)
+    pub oidc: Option<Arc<OidcVerifier>>,
+    /// Request limits per client IP and API key
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Audit log of authentication and key events
+    pub audit: Option<Arc<AuditLogger>>,
+    /// Remembers facts about the users named in chat requests (None = disabled)
+    pub memorizer: Option<Arc<Memorizer>>,
+    /// Long-term memories, deleted with their identity (None = not purged)
+    pub memory: Option<Arc<dyn MemoryBackend>>,
+    /// Finds documents relevant to chat messages (None = disabled)
+    pub context: Option<Arc<dyn ContextProvider>>,
+    /// Dependencies checked by `/readyz`
+    pub health_checks: Vec<Arc<dyn HealthCheck>>,
+}
+
+impl AppState {
+    /// Write an API event to the audit log (when one is configured)
+    pub(crate) fn audit(
+        &self,
+        event_type: AuditEventType,
+        level: AuditLevel,
+        key_id: Option<&str>,
+        client_ip: Option<&str>,
+        action: &str,
+        message: impl Into<String>,
+    ) {
+        let Some(logger) = &self.audit else {
+            return;
+        };
+        let mut entry = logger
+            .builder()
+            .event_type(event_type)
+            .level(level)
+            .message(message)
+            .gateway("api");
+        entry = match key_id {
+            Some(id) => entry.target_with_id("api_key", id, action),
+            None => entry.target("api_key", action),
+        };
+        if let Some(ip) = client_ip {
+            entry = entry.ip(ip);
+        }
+        if let Err(e) = logger.log(&entry.build()) {
+            warn!("Failed to write audit log: {}", e);
+        }
+    }
+}
+
+/// Optional services exposed through the API
+#[derive(Clone, Default)]
+pub struct ApiServices {
+    /// System prompt templates
+    pub prompts: Option<Arc<PromptLibrary>>,
+    /// Running scheduler
+    pub scheduler: Option<SchedulerHandle>,
+    /// Workflow engine
+    pub workflows: Option<Arc<WorkflowEngine>>,
+    /// Inbound webhooks
+    pub hooks: Option<Arc<WebhookHandler>>,
+    /// Sub-agents (listed with their metrics)
+    pub sub_agents: Option<Arc<SubAgentManager>>,
+    /// Persistent task queue for delegated work
+    pub task_queue: Option<TaskQueue>,
+    /// Uploaded files
+    pub files: Option<Arc<FileStore>>,
+    /// Transcribes uploaded audio
+    pub transcriber: Option<Arc<WhisperClient>>,
+    /// Issued API keys
+    pub api_keys: Option<Arc<ApiKeyStore>>,
+    /// OIDC authentication
+    pub oidc: Option<Arc<OidcVerifier>>,
+    /// Audit log of authentication and key events
+    pub audit: Option<Arc<AuditLogger>>,
+    /// Memorizer for chat requests that name a user
+    pub memorizer: Option<Arc<Memorizer>>,
+    /// Long-term memories (purged with `/api/identities`)
+    pub memory: Option<Arc<dyn MemoryBackend>>,
+    /// Knowledge base passages added to chat requests
+    pub context: Option<Arc<dyn ContextProvider>>,
+    /// Additional `/readyz` checks (MCP servers, channels, ...)
+    pub health_checks: Vec<Arc<dyn HealthCheck>>,
 }
 
 /// Start the HTTP API server
+///
+/// When `shutdown` is cancelled the server stops accepting connections and
+/// waits up to `api.shutdown_timeout_secs` for in-flight requests.
 pub async fn start_server(
     port: u16,
     config: Config,
     claude_client: ClaudeClient,
     session_manager: SessionManager,
     tool_manager: Arc<ToolManager>,
+    services: ApiServices,
+    shutdown: CancellationToken,
 ) -> Result<()> {
+    let claude_client = Arc::new(claude_client);
+    let session_manager = Arc::new(session_manager);
+    let mut health_checks: Vec<Arc<dyn HealthCheck>> = vec![
+        Arc::new(DatabaseCheck(Arc::clone(&session_manager))),
+        Arc::new(LlmCheck(Arc::clone(&claude_client))),
+    ];
+    health_checks.extend(services.health_checks);
+
     let state = AppState {
         config: config.clone(),
-        claude_client: Arc::new(claude_client),
-        session_manager: Arc::new(session_manager),
+        claude_client,
+        session_manager,
         tool_manager,
+        prompts: services.prompts,
+        personas: Arc::new(PersonaRegistry::from_config(&config.personas)),
+        scheduler: services.scheduler,
+        workflows: services.workflows,
+        hooks: services.hooks,
+        sub_agents: services.sub_agents,
+        task_queue: services.task_queue,
+        files: services.files,
+        transcriber: services.transcriber,
+        api_keys: services.api_keys,
+        oidc: services.oidc,
+        rate_limiter: Arc::new(RateLimiter::from_settings(&config.api.rate_limit).await),
+        audit: services.audit,
+        memorizer: services.memorizer,
+        memory: services.memory,
+        context: services.context,
+        health_checks,
     };
 
     // Check if API key is configured
-    let api_key_configured = std::env::var("API_KEY").is_ok() || config.api_key.is_some();
+    let api_key_configured = std::env::var("API_KEY").is_ok()
+        || config.api_key.is_some()
+        || state.oidc.is_some()
+        || state
+            .api_keys
+            .as_ref()
+            .is_some_and(|store| store.has_active_keys().unwrap_or(false));
 
     if api_key_configured {
         info!("API authentication enabled");
     } else {
-        info!("API authentication disabled (no API_KEY configured)");
+        info!("API authentication disabled (no API_KEY configured and no keys issued)");
     }
 
+    let state_limiter = Arc::clone(&state.rate_limiter);
+
     // Build CORS layer with restricted origins
     let cors_layer = build_cors_layer(&config);
 
-    // Build the app router
-    let app = if api_key_configured {
-        // With authentication
-        Router::new()
-            .merge(public_routes())
-            .merge(
-                protected_routes()
-                    .layer(middleware::from_fn(auth_middleware))
-            )
-            .layer(cors_layer)
-            .with_state(state)
-    } else {
-        // Without authentication (development mode)
-        Router::new()
-            .merge(public_routes())
-            .merge(protected_routes())
-            .layer(cors_layer)
-            .with_state(state)
-    };
+    // Build the app router (the middleware lets requests through while no key exists)
+    let app = Router::new()
+        .merge(public_routes())
+        .merge(
+            protected_routes()
+                .layer(middleware::from_fn_with_state(state.clone(), rate_limit_middleware))
+                .layer(middleware::from_fn_with_state(state.clone(), auth_middleware))
+        )
+        .layer(middleware::from_fn_with_state(state.clone(), timeout_middleware))
+        .layer(DefaultBodyLimit::max(config.api.max_body_size))
+        .layer(cors_layer)
+        .with_state(state);
+
+    // Drop idle in-memory rate limit buckets
+    let limiter = Arc::clone(&state_limiter);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(300));
+        loop {
+            interval.tick().await;
+            limiter.cleanup().await;
+        }
+    });
 
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     info!("HTTP API listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned());
+
+    // Stop waiting for slow connections once the drain timeout has passed
+    let drain_timeout = Duration::from_secs(config.api.shutdown_timeout_secs);
+    tokio::select! {
+        result = server.into_future() => result?,
+        _ = async {
+            shutdown.cancelled().await;
+            tokio::time::sleep(drain_timeout).await;
+        } => {
+            warn!("HTTP API connections still open after {:?}, closing them", drain_timeout);
+        }
+    }
 
+    info!("HTTP API stopped");
     Ok(())
 }
 
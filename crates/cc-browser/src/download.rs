@@ -0,0 +1,349 @@
+//! Download tracking
+//!
+//! Follows downloads of a browser through CDP `downloadWillBegin` /
+//! `downloadProgress` events and moves finished files to their suggested
+//! file names inside the download directory.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use headless_chrome::protocol::cdp::types::Event;
+use headless_chrome::protocol::cdp::{Browser as BrowserDomain, Page};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Maximum number of remembered downloads (the oldest are dropped)
+const MAX_ENTRIES: usize = 200;
+
+/// State of a download
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    InProgress,
+    Completed,
+    Canceled,
+}
+
+/// A download started by the browser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadEntry {
+    pub guid: String,
+    pub url: String,
+    pub suggested_filename: String,
+    pub state: DownloadState,
+    pub received_bytes: u64,
+    /// 0 if the size is unknown
+    pub total_bytes: u64,
+    pub started_at: DateTime<Utc>,
+    /// Location of the finished file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Directory Chrome writes the file to (as `<guid>`)
+    #[serde(skip)]
+    dir: PathBuf,
+    /// Order in which downloads began
+    #[serde(skip)]
+    seq: u64,
+}
+
+impl DownloadEntry {
+    pub fn is_finished(&self) -> bool {
+        self.state != DownloadState::InProgress
+    }
+}
+
+struct TrackerState {
+    dir: PathBuf,
+    entries: VecDeque<DownloadEntry>,
+    next_seq: u64,
+}
+
+/// Tracks the downloads of a browser session
+///
+/// Clones share the same state, so one clone can be handed to tab event
+/// listeners while another waits for downloads to finish.
+#[derive(Clone)]
+pub struct DownloadTracker {
+    inner: Arc<(Mutex<TrackerState>, Condvar)>,
+}
+
+impl DownloadTracker {
+    /// Create a tracker for downloads saved to `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let state = TrackerState {
+            dir: dir.into(),
+            entries: VecDeque::new(),
+            next_seq: 0,
+        };
+        Self {
+            inner: Arc::new((Mutex::new(state), Condvar::new())),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TrackerState> {
+        self.inner.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Directory new downloads are saved to
+    pub fn dir(&self) -> PathBuf {
+        self.lock().dir.clone()
+    }
+
+    /// Change the directory for downloads that begin from now on
+    pub fn set_dir(&self, dir: impl Into<PathBuf>) {
+        self.lock().dir = dir.into();
+    }
+
+    /// Update the downloads from a CDP event (other events are ignored)
+    ///
+    /// Both the Browser and the (deprecated) Page domain events are handled;
+    /// duplicates for the same download are ignored.
+    pub fn handle_event(&self, event: &Event) {
+        match event {
+            Event::BrowserDownloadWillBegin(event) => {
+                let params = &event.params;
+                self.on_begin(&params.guid, &params.url, &params.suggested_filename);
+            }
+            Event::PageDownloadWillBegin(event) => {
+                let params = &event.params;
+                self.on_begin(&params.guid, &params.url, &params.suggested_filename);
+            }
+            Event::BrowserDownloadProgress(event) => {
+                let params = &event.params;
+                let state = match params.state {
+                    BrowserDomain::DownloadProgressEventStateOption::InProgress => {
+                        DownloadState::InProgress
+                    }
+                    BrowserDomain::DownloadProgressEventStateOption::Completed => {
+                        DownloadState::Completed
+                    }
+                    BrowserDomain::DownloadProgressEventStateOption::Canceled => {
+                        DownloadState::Canceled
+                    }
+                };
+                self.on_progress(&params.guid, params.received_bytes, params.total_bytes, state);
+            }
+            Event::PageDownloadProgress(event) => {
+                let params = &event.params;
+                let state = match params.state {
+                    Page::DownloadProgressEventStateOption::InProgress => DownloadState::InProgress,
+                    Page::DownloadProgressEventStateOption::Completed => DownloadState::Completed,
+                    Page::DownloadProgressEventStateOption::Canceled => DownloadState::Canceled,
+                };
+                self.on_progress(&params.guid, params.received_bytes, params.total_bytes, state);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_begin(&self, guid: &str, url: &str, suggested_filename: &str) {
+        let mut state = self.lock();
+        if state.entries.iter().any(|e| e.guid == guid) {
+            return;
+        }
+        if state.entries.len() >= MAX_ENTRIES {
+            state.entries.pop_front();
+        }
+
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        let dir = state.dir.clone();
+        state.entries.push_back(DownloadEntry {
+            guid: guid.to_string(),
+            url: url.to_string(),
+            suggested_filename: suggested_filename.to_string(),
+            state: DownloadState::InProgress,
+            received_bytes: 0,
+            total_bytes: 0,
+            started_at: Utc::now(),
+            path: None,
+            dir,
+            seq,
+        });
+        info!("Download started: {} ({})", suggested_filename, url);
+        self.inner.1.notify_all();
+    }
+
+    fn on_progress(&self, guid: &str, received: f64, total: f64, new_state: DownloadState) {
+        let mut state = self.lock();
+        let Some(entry) = state
+            .entries
+            .iter_mut()
+            .find(|e| e.guid == guid && !e.is_finished())
+        else {
+            return;
+        };
+
+        entry.received_bytes = received as u64;
+        entry.total_bytes = total as u64;
+        entry.state = new_state;
+
+        match new_state {
+            DownloadState::InProgress => return,
+            DownloadState::Completed => {
+                let path = finish_file(entry);
+                info!("Download completed: {}", path.display());
+                entry.path = Some(path);
+            }
+            DownloadState::Canceled => info!("Download canceled: {}", entry.url),
+        }
+        self.inner.1.notify_all();
+    }
+
+    /// All remembered downloads, oldest first
+    pub fn entries(&self) -> Vec<DownloadEntry> {
+        self.lock().entries.iter().cloned().collect()
+    }
+
+    /// Marker for downloads that begin after this call
+    pub fn mark(&self) -> u64 {
+        self.lock().next_seq
+    }
+
+    /// Marker that also covers downloads still in progress
+    pub fn pending_mark(&self) -> u64 {
+        let state = self.lock();
+        state
+            .entries
+            .iter()
+            .find(|e| !e.is_finished())
+            .map_or(state.next_seq, |e| e.seq)
+    }
+
+    /// Wait until the downloads that began since `mark` have finished
+    ///
+    /// Returns once at least one download finished and none are in progress,
+    /// or on timeout with the downloads finished so far (possibly none).
+    pub fn wait(&self, mark: u64, timeout: Duration) -> Vec<DownloadEntry> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        loop {
+            let since: Vec<&DownloadEntry> =
+                state.entries.iter().filter(|e| e.seq >= mark).collect();
+            let done = !since.is_empty() && since.iter().all(|e| e.is_finished());
+
+            let now = Instant::now();
+            if done || now >= deadline {
+                return since
+                    .into_iter()
+                    .filter(|e| e.is_finished())
+                    .cloned()
+                    .collect();
+            }
+
+            state = self
+                .inner
+                .1
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+}
+
+/// Move a completed download from `<dir>/<guid>` to its suggested file name
+///
+/// Falls back to the GUID path if the file cannot be renamed.
+fn finish_file(entry: &DownloadEntry) -> PathBuf {
+    let source = entry.dir.join(&entry.guid);
+    let target = unique_path(&entry.dir, &file_name(&entry.suggested_filename, &entry.guid));
+    match std::fs::rename(&source, &target) {
+        Ok(()) => target,
+        Err(e) => {
+            warn!("Failed to rename download {}: {}", source.display(), e);
+            source
+        }
+    }
+}
+
+/// The last path component of a suggested file name (never empty)
+fn file_name(suggested: &str, fallback: &str) -> String {
+    Path::new(suggested)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(fallback)
+        .to_string()
+}
+
+/// `dir/name`, or `dir/stem (n).ext` if that file already exists
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let path = Path::new(name);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+    let ext = path.extension().and_then(|e| e.to_str());
+    (1..)
+        .map(|n| match ext {
+            Some(ext) => dir.join(format!("{} ({}).{}", stem, n, ext)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completed_download_is_renamed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("report.pdf"), b"old").unwrap();
+
+        let tracker = DownloadTracker::new(dir.path());
+        let mark = tracker.mark();
+        tracker.on_begin("g1", "https://example.com/report.pdf", "report.pdf");
+        std::fs::write(dir.path().join("g1"), b"new").unwrap();
+        tracker.on_progress("g1", 3.0, 3.0, DownloadState::InProgress);
+        tracker.on_progress("g1", 3.0, 3.0, DownloadState::Completed);
+        // A duplicate event from the other CDP domain is ignored
+        tracker.on_progress("g1", 3.0, 3.0, DownloadState::Completed);
+
+        let done = tracker.wait(mark, Duration::from_millis(10));
+        assert_eq!(done.len(), 1);
+        let path = done[0].path.clone().unwrap();
+        assert_eq!(path, dir.path().join("report (1).pdf"));
+        assert_eq!(std::fs::read(path).unwrap(), b"new");
+        assert_eq!(done[0].received_bytes, 3);
+    }
+
+    #[test]
+    fn test_wait_for_pending_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = DownloadTracker::new(dir.path());
+        tracker.on_begin("g1", "https://example.com/a.zip", "../a.zip");
+        let mark = tracker.pending_mark();
+
+        let events = tracker.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            events.on_progress("g1", 0.0, 0.0, DownloadState::Canceled);
+        });
+
+        let done = tracker.wait(mark, Duration::from_secs(5));
+        handle.join().unwrap();
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].state, DownloadState::Canceled);
+        assert!(done[0].path.is_none());
+    }
+
+    #[test]
+    fn test_wait_times_out_without_downloads() {
+        let tracker = DownloadTracker::new("/tmp");
+        assert!(tracker.wait(tracker.mark(), Duration::from_millis(20)).is_empty());
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("../../etc/passwd", "g"), "passwd");
+        assert_eq!(file_name("", "g"), "g");
+        assert_eq!(file_name("data.csv", "g"), "data.csv");
+    }
+}
@@ -20,6 +20,9 @@ pub enum BrowserError {
     #[error("Screenshot failed: {0}")]
     Screenshot(String),
 
+    #[error("PDF generation failed: {0}")]
+    Pdf(String),
+
     #[error("Extraction failed: {0}")]
     Extraction(String),
 
@@ -38,11 +41,20 @@ pub enum BrowserError {
     #[error("Cookie error: {0}")]
     Cookie(String),
 
+    #[error("Storage error: {0}")]
+    Storage(String),
+
     #[error("Download error: {0}")]
     Download(String),
 
+    #[error("Network error: {0}")]
+    Network(String),
+
     #[error("Frame error: {0}")]
     Frame(String),
+
+    #[error("Not supported: {0}")]
+    Unsupported(String),
 }
 
 /// Result type alias
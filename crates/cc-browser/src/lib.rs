@@ -1,14 +1,25 @@
 //! cc-browser: Browser automation tools for cc-gateway
 //!
-//! This crate provides browser automation capabilities using headless Chrome.
+//! This crate provides browser automation capabilities using headless Chrome,
+//! or any browser behind a W3C WebDriver server.
 //!
 //! ## Features
 //!
 //! - Headless Chrome automation via headless_chrome crate
-//! - Screenshot capture (full page or viewport)
+//! - Optional WebDriver backend (chromedriver, geckodriver, Selenium) with
+//!   real iframe switching, shadow DOM selectors (`host >>> inner`) and
+//!   device emulation
+//! - Screenshot capture (full page, viewport or a single element)
+//! - PDF export via Chrome's print-to-PDF
 //! - Form input and element interaction
+//! - Multiple tabs (tools act on the selected tab)
 //! - Text extraction and JavaScript execution
-//! - Session management with configurable timeouts
+//! - Accessibility-style page outlines with element refs
+//! - Network request recording, URL blocking and HAR export
+//! - Downloads saved to a configured directory with their file paths reported
+//! - Persistent named profiles, CDP cookies (incl. HttpOnly) and web storage access
+//! - One browser per conversation with a concurrency cap, idle eviction and
+//!   crash relaunch
 //!
 //! ## Usage
 //!
@@ -28,13 +39,28 @@
 //! manager.register(Arc::new(BrowserNavigateTool::new(browser_manager)));
 //! ```
 
+pub mod download;
 pub mod error;
+pub mod network;
+pub mod pool;
 pub mod session;
+pub mod snapshot;
 pub mod tools;
+pub mod webdriver;
 
+pub use download::{DownloadEntry, DownloadState, DownloadTracker};
 pub use error::{BrowserError, Result};
-pub use session::{BrowserConfig, BrowserConfigBuilder, BrowserSession};
+pub use network::{NetworkEntry, NetworkRecorder};
+pub use pool::BrowserPool;
+pub use session::{
+    BrowserBackend, BrowserConfig, BrowserConfigBuilder, BrowserSession, CookieOptions,
+    PdfOptions, StorageKind, TabInfo,
+};
+pub use snapshot::PageSnapshot;
 pub use tools::{
     BrowserClickTool, BrowserEvaluateTool, BrowserExtractTool, BrowserManager,
-    BrowserNavigateTool, BrowserScreenshotTool, BrowserTypeTool, BrowserWaitTool,
+    BrowserNavigateTool, BrowserNetworkTool, BrowserPdfTool, BrowserScreenshotTool,
+    BrowserSnapshotTool, BrowserStorageTool, BrowserTabTool, BrowserTypeTool, BrowserWaitTool,
+    register_browser_tools, register_browser_tools_with_config,
 };
+pub use webdriver::WebDriverSession;
@@ -0,0 +1,399 @@
+//! Network request recording
+//!
+//! Records requests and responses of a tab from CDP Network domain events
+//! and exports them as HAR.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use headless_chrome::protocol::cdp::types::Event;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// Maximum number of recorded requests (the oldest are dropped)
+const MAX_ENTRIES: usize = 1000;
+
+/// A recorded request and its response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkEntry {
+    pub request_id: String,
+    pub url: String,
+    pub method: String,
+    /// Document, Script, XHR, Fetch, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    /// When the request was sent
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Time until the response finished loading
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<f64>,
+    /// Bytes received over the network
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoded_data_length: Option<f64>,
+    /// Network error (including blocked requests)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub request_headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub response_headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<String>,
+    /// Monotonic CDP timestamp of the request (seconds)
+    #[serde(skip)]
+    timestamp: f64,
+}
+
+/// Records the network traffic of a tab
+///
+/// Clones share the same recording, so one clone can be handed to the tab's
+/// event listener while another is read from tools.
+#[derive(Clone, Default)]
+pub struct NetworkRecorder {
+    entries: Arc<Mutex<VecDeque<NetworkEntry>>>,
+}
+
+impl NetworkRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<NetworkEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Update the recording from a CDP event (other events are ignored)
+    pub fn handle_event(&self, event: &Event) {
+        match event {
+            Event::NetworkRequestWillBeSent(event) => {
+                let params = &event.params;
+                self.on_request(
+                    &params.request_id,
+                    &params.request.url,
+                    &params.request.method,
+                    params.Type.as_ref().and_then(enum_name),
+                    params.wall_time,
+                    params.timestamp,
+                    headers(&params.request.headers.0),
+                    params.request.post_data.clone(),
+                );
+            }
+            Event::NetworkResponseReceived(event) => {
+                let params = &event.params;
+                let response = &params.response;
+                self.on_response(
+                    &params.request_id,
+                    response.status,
+                    &response.status_text,
+                    &response.mime_type,
+                    headers(&response.headers.0),
+                );
+            }
+            Event::NetworkLoadingFinished(event) => {
+                let params = &event.params;
+                self.on_finished(
+                    &params.request_id,
+                    params.timestamp,
+                    Some(params.encoded_data_length),
+                    None,
+                );
+            }
+            Event::NetworkLoadingFailed(event) => {
+                let params = &event.params;
+                let error = match &params.blocked_reason {
+                    Some(reason) => format!(
+                        "{} (blocked: {})",
+                        params.error_text,
+                        enum_name(reason).unwrap_or_default()
+                    ),
+                    None => params.error_text.clone(),
+                };
+                self.on_finished(&params.request_id, params.timestamp, None, Some(error));
+            }
+            _ => {}
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_request(
+        &self,
+        request_id: &str,
+        url: &str,
+        method: &str,
+        resource_type: Option<String>,
+        wall_time: f64,
+        timestamp: f64,
+        request_headers: BTreeMap<String, String>,
+        post_data: Option<String>,
+    ) {
+        let started_at = DateTime::from_timestamp_millis((wall_time * 1000.0) as i64)
+            .unwrap_or_else(Utc::now);
+        let mut entries = self.lock();
+        // Redirects reuse the request ID; keep each hop as its own entry
+        if entries.len() >= MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(NetworkEntry {
+            request_id: request_id.to_string(),
+            url: url.to_string(),
+            method: method.to_string(),
+            resource_type,
+            started_at,
+            status: None,
+            status_text: None,
+            mime_type: None,
+            duration_ms: None,
+            encoded_data_length: None,
+            error: None,
+            request_headers,
+            response_headers: BTreeMap::new(),
+            post_data,
+            timestamp,
+        });
+    }
+
+    fn on_response(
+        &self,
+        request_id: &str,
+        status: u32,
+        status_text: &str,
+        mime_type: &str,
+        response_headers: BTreeMap<String, String>,
+    ) {
+        if let Some(entry) = self.latest(&mut self.lock(), request_id) {
+            entry.status = Some(status);
+            entry.status_text = Some(status_text.to_string());
+            entry.mime_type = Some(mime_type.to_string());
+            entry.response_headers = response_headers;
+        }
+    }
+
+    fn on_finished(
+        &self,
+        request_id: &str,
+        timestamp: f64,
+        encoded_data_length: Option<f64>,
+        error: Option<String>,
+    ) {
+        if let Some(entry) = self.latest(&mut self.lock(), request_id) {
+            entry.duration_ms = Some(((timestamp - entry.timestamp) * 1000.0).max(0.0));
+            entry.encoded_data_length = encoded_data_length;
+            entry.error = error;
+        }
+    }
+
+    fn latest<'a>(
+        &self,
+        entries: &'a mut VecDeque<NetworkEntry>,
+        request_id: &str,
+    ) -> Option<&'a mut NetworkEntry> {
+        entries.iter_mut().rev().find(|e| e.request_id == request_id)
+    }
+
+    /// Recorded requests in the order they were sent
+    ///
+    /// `url_filter` keeps only requests whose URL contains it.
+    pub fn entries(&self, url_filter: Option<&str>) -> Vec<NetworkEntry> {
+        self.lock()
+            .iter()
+            .filter(|e| url_filter.is_none_or(|f| e.url.contains(f)))
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+}
+
+/// Build a HAR 1.2 log from recorded entries
+///
+/// `bodies` maps request IDs to response bodies to embed.
+pub fn to_har(entries: &[NetworkEntry], bodies: &HashMap<String, String>) -> Value {
+    let har_entries: Vec<Value> = entries
+        .iter()
+        .map(|entry| {
+            let mut content = json!({
+                "size": entry.encoded_data_length.unwrap_or(-1.0),
+                "mimeType": entry.mime_type.clone().unwrap_or_default(),
+            });
+            if let Some(body) = bodies.get(&entry.request_id) {
+                content["text"] = json!(body);
+            }
+            let mut request = json!({
+                "method": entry.method,
+                "url": entry.url,
+                "httpVersion": "",
+                "cookies": [],
+                "headers": har_headers(&entry.request_headers),
+                "queryString": [],
+                "headersSize": -1,
+                "bodySize": entry.post_data.as_ref().map_or(0, |d| d.len() as i64),
+            });
+            if let Some(post_data) = &entry.post_data {
+                request["postData"] = json!({
+                    "mimeType": entry
+                        .request_headers
+                        .iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+                        .map(|(_, v)| v.as_str())
+                        .unwrap_or_default(),
+                    "text": post_data,
+                });
+            }
+            let duration = entry.duration_ms.unwrap_or(-1.0);
+
+            let mut har_entry = json!({
+                "startedDateTime": entry.started_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+                "time": duration.max(0.0),
+                "request": request,
+                "response": {
+                    "status": entry.status.unwrap_or(0),
+                    "statusText": entry.status_text.clone().unwrap_or_default(),
+                    "httpVersion": "",
+                    "cookies": [],
+                    "headers": har_headers(&entry.response_headers),
+                    "content": content,
+                    "redirectURL": entry
+                        .response_headers
+                        .iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case("location"))
+                        .map(|(_, v)| v.as_str())
+                        .unwrap_or_default(),
+                    "headersSize": -1,
+                    "bodySize": entry.encoded_data_length.unwrap_or(-1.0),
+                },
+                "cache": {},
+                "timings": { "send": 0, "wait": duration, "receive": 0 },
+            });
+            if let Some(error) = &entry.error {
+                har_entry["_error"] = json!(error);
+            }
+            har_entry
+        })
+        .collect();
+
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "cc-browser", "version": env!("CARGO_PKG_VERSION") },
+            "pages": [],
+            "entries": har_entries,
+        }
+    })
+}
+
+fn har_headers(headers: &BTreeMap<String, String>) -> Vec<Value> {
+    headers
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+fn headers(value: &Option<Value>) -> BTreeMap<String, String> {
+    value
+        .as_ref()
+        .and_then(Value::as_object)
+        .map(|map| {
+            map.iter()
+                .map(|(k, v)| (k.clone(), v.as_str().map_or_else(|| v.to_string(), str::to_string)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Protocol name of a CDP enum value (e.g. "XHR")
+fn enum_name<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(recorder: &NetworkRecorder, id: &str, url: &str) {
+        let mut headers = BTreeMap::new();
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        recorder.on_request(
+            id,
+            url,
+            "POST",
+            Some("Fetch".to_string()),
+            1_700_000_000.0,
+            10.0,
+            headers,
+            Some("{}".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_recorder_tracks_request_lifecycle() {
+        let recorder = NetworkRecorder::new();
+        record(&recorder, "1", "https://example.com/api/items");
+        record(&recorder, "2", "https://cdn.example.com/app.js");
+        recorder.on_response("1", 200, "OK", "application/json", BTreeMap::new());
+        recorder.on_finished("1", 10.25, Some(512.0), None);
+        recorder.on_finished("2", 11.0, None, Some("net::ERR_BLOCKED_BY_CLIENT".into()));
+
+        let entries = recorder.entries(None);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].status, Some(200));
+        assert_eq!(entries[0].duration_ms, Some(250.0));
+        assert_eq!(entries[0].encoded_data_length, Some(512.0));
+        assert!(entries[1].error.as_deref().unwrap().contains("BLOCKED"));
+
+        let api = recorder.entries(Some("/api/"));
+        assert_eq!(api.len(), 1);
+        assert_eq!(api[0].request_id, "1");
+
+        recorder.clear();
+        assert!(recorder.is_empty());
+    }
+
+    #[test]
+    fn test_recorder_drops_oldest() {
+        let recorder = NetworkRecorder::new();
+        for i in 0..MAX_ENTRIES + 5 {
+            record(&recorder, &i.to_string(), "https://example.com");
+        }
+        assert_eq!(recorder.len(), MAX_ENTRIES);
+        assert_eq!(recorder.entries(None)[0].request_id, "5");
+    }
+
+    #[test]
+    fn test_har_export() {
+        let recorder = NetworkRecorder::new();
+        record(&recorder, "1", "https://example.com/api");
+        recorder.on_response("1", 201, "Created", "application/json", BTreeMap::new());
+        recorder.on_finished("1", 10.1, Some(42.0), None);
+
+        let mut bodies = HashMap::new();
+        bodies.insert("1".to_string(), "{\"ok\":true}".to_string());
+        let har = to_har(&recorder.entries(None), &bodies);
+
+        assert_eq!(har["log"]["version"], "1.2");
+        let entry = &har["log"]["entries"][0];
+        assert_eq!(entry["request"]["method"], "POST");
+        assert_eq!(entry["request"]["postData"]["mimeType"], "application/json");
+        assert_eq!(entry["response"]["status"], 201);
+        assert_eq!(entry["response"]["content"]["text"], "{\"ok\":true}");
+        assert!(entry["startedDateTime"].as_str().unwrap().starts_with("2023-11-14T"));
+    }
+}
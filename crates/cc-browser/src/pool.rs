@@ -0,0 +1,289 @@
+//! Browser session pool
+//!
+//! Keeps one browser per conversation (the [`ToolOrigin`] of the tool call),
+//! caps the number of concurrent browsers, closes browsers that were idle
+//! for too long and relaunches browsers that crashed.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cc_core::ToolOrigin;
+use tracing::{info, warn};
+
+use crate::error::{BrowserError, Result};
+use crate::session::{BrowserConfig, BrowserSession};
+
+/// Pool key for tool calls outside a conversation, and for all calls when a
+/// persistent profile is configured (Chrome locks its user data dir)
+pub const DEFAULT_POOL_KEY: &str = "default";
+
+/// A pooled browser, launched on first use
+///
+/// The async mutex is the browser's operation queue: it is fair, so calls
+/// from one conversation run one at a time in arrival order.
+struct Slot {
+    session: Arc<tokio::sync::Mutex<Option<BrowserSession>>>,
+    last_used: Mutex<Instant>,
+}
+
+impl Slot {
+    fn new() -> Self {
+        Self {
+            session: Arc::new(tokio::sync::Mutex::new(None)),
+            last_used: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed()
+    }
+
+    /// Not used by a tool call right now
+    fn is_idle(&self) -> bool {
+        self.session.try_lock().is_ok()
+    }
+}
+
+/// Browser sessions keyed by conversation
+pub struct BrowserPool {
+    config: BrowserConfig,
+    slots: Mutex<HashMap<String, Arc<Slot>>>,
+}
+
+impl BrowserPool {
+    pub fn new(config: BrowserConfig) -> Self {
+        Self {
+            config,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &BrowserConfig {
+        &self.config
+    }
+
+    fn slots(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Slot>>> {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Pool key of the current tool call (`platform:channel`)
+    pub fn current_key(&self) -> String {
+        if !matches!(self.config.resolve_user_data_dir(), Ok(None)) {
+            return DEFAULT_POOL_KEY.to_string();
+        }
+        ToolOrigin::current()
+            .map(|origin| format!("{}:{}", origin.platform, origin.channel_id))
+            .unwrap_or_else(|| DEFAULT_POOL_KEY.to_string())
+    }
+
+    /// Get the slot for `key`, making room by evicting the least recently
+    /// used idle browser when the pool is full
+    fn slot(&self, key: &str) -> Result<Arc<Slot>> {
+        let mut slots = self.slots();
+        if let Some(slot) = slots.get(key) {
+            return Ok(slot.clone());
+        }
+
+        if slots.len() >= self.config.max_sessions.max(1) {
+            let victim = slots
+                .iter()
+                .filter(|(_, slot)| slot.is_idle())
+                .max_by_key(|(_, slot)| slot.idle_for())
+                .map(|(key, _)| key.clone());
+            match victim {
+                Some(victim) => {
+                    slots.remove(&victim);
+                    info!("Closed browser session '{}' to make room for '{}'", victim, key);
+                }
+                None => {
+                    return Err(BrowserError::Initialization(format!(
+                        "All {} browser sessions are busy",
+                        slots.len()
+                    )));
+                }
+            }
+        }
+
+        let slot = Arc::new(Slot::new());
+        slots.insert(key.to_string(), slot.clone());
+        Ok(slot)
+    }
+
+    /// Run `f` with the browser of the current conversation
+    ///
+    /// Browser calls block, so `f` runs on the blocking thread pool and never
+    /// stalls the async runtime. The browser is launched on first use and
+    /// relaunched if it crashed. Calls from other conversations are not
+    /// blocked while `f` runs.
+    pub async fn execute<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&BrowserSession) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.evict_idle();
+
+        let key = self.current_key();
+        let slot = self.slot(&key)?;
+        // The guard moves into the blocking task, so the queue stays blocked
+        // until the operation finished even if the caller gave up waiting
+        let guard = slot.session.clone().lock_owned().await;
+        slot.touch();
+
+        let config = self.config.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut guard = guard;
+            if guard.as_ref().is_some_and(|session| !session.is_alive()) {
+                warn!("Browser session '{}' is not responding; relaunching", key);
+                *guard = None;
+            }
+            if guard.is_none() {
+                info!("Creating new browser session for '{}'", key);
+                *guard = Some(BrowserSession::with_config(config)?);
+            }
+
+            let session = guard.as_ref().ok_or_else(|| {
+                BrowserError::Initialization("Failed to get browser session".to_string())
+            })?;
+            let result = f(session);
+            if result.is_err() && !session.is_alive() {
+                warn!("Browser session '{}' crashed; it will be relaunched", key);
+                *guard = None;
+            }
+            result
+        })
+        .await
+        .map_err(|e| BrowserError::Interaction(format!("Browser operation aborted: {}", e)))?;
+
+        slot.touch();
+        result
+    }
+
+    /// Close browsers that have not been used for the idle timeout
+    ///
+    /// Returns the number of closed sessions.
+    pub fn evict_idle(&self) -> usize {
+        let timeout = Duration::from_secs(self.config.idle_timeout);
+        let mut slots = self.slots();
+        let before = slots.len();
+        slots.retain(|key, slot| {
+            let expired = slot.idle_for() >= timeout && slot.is_idle();
+            if expired {
+                info!("Closing idle browser session '{}'", key);
+            }
+            !expired
+        });
+        before - slots.len()
+    }
+
+    /// Close the browser of one conversation
+    pub async fn close(&self, key: &str) -> bool {
+        let slot = self.slots().remove(key);
+        match slot {
+            Some(slot) => slot.session.lock().await.take().is_some(),
+            None => false,
+        }
+    }
+
+    /// Close all browsers
+    pub async fn close_all(&self) {
+        let slots: Vec<(String, Arc<Slot>)> = self.slots().drain().collect();
+        for (key, slot) in slots {
+            if slot.session.lock().await.take().is_some() {
+                info!("Browser session '{}' closed", key);
+            }
+        }
+    }
+
+    /// Keys of the pooled sessions
+    pub fn keys(&self) -> Vec<String> {
+        self.slots().keys().cloned().collect()
+    }
+
+    /// Number of running browsers (busy ones count as running)
+    pub fn active_sessions(&self) -> usize {
+        self.slots()
+            .values()
+            .filter(|slot| match slot.session.try_lock() {
+                Ok(session) => session.is_some(),
+                Err(_) => true,
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(max_sessions: usize, idle_timeout: u64) -> BrowserPool {
+        BrowserPool::new(
+            BrowserConfig::builder()
+                .max_sessions(max_sessions)
+                .idle_timeout(idle_timeout)
+                .build(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_key_follows_tool_origin() {
+        let pool = pool(4, 600);
+        assert_eq!(pool.current_key(), DEFAULT_POOL_KEY);
+
+        let key = ToolOrigin::new("discord", "42")
+            .scope(async { pool.current_key() })
+            .await;
+        assert_eq!(key, "discord:42");
+
+        let shared = BrowserPool::new(BrowserConfig::builder().profile("work").build());
+        let key = ToolOrigin::new("discord", "42")
+            .scope(async { shared.current_key() })
+            .await;
+        assert_eq!(key, DEFAULT_POOL_KEY);
+    }
+
+    #[tokio::test]
+    async fn test_full_pool_evicts_least_recently_used() {
+        let pool = pool(2, 600);
+        pool.slot("a").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        pool.slot("b").unwrap().touch();
+
+        pool.slot("c").unwrap();
+        let mut keys = pool.keys();
+        keys.sort();
+        assert_eq!(keys, vec!["b", "c"]);
+
+        // Busy sessions are never evicted
+        let b = pool.slot("b").unwrap();
+        let c = pool.slot("c").unwrap();
+        let _b = b.session.lock().await;
+        let _c = c.session.lock().await;
+        assert!(matches!(
+            pool.slot("d"),
+            Err(BrowserError::Initialization(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_evict_idle() {
+        let pool = pool(4, 0);
+        pool.slot("a").unwrap();
+        let busy = pool.slot("b").unwrap();
+        let guard = busy.session.lock().await;
+
+        assert_eq!(pool.evict_idle(), 1);
+        assert_eq!(pool.keys(), vec!["b"]);
+        assert_eq!(pool.active_sessions(), 1);
+
+        drop(guard);
+        assert_eq!(pool.active_sessions(), 0);
+    }
+}
@@ -2,18 +2,51 @@
 //!
 //! Provides a managed browser instance with automatic lifecycle handling.
 
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
 
-use headless_chrome::{Browser, LaunchOptionsBuilder, Tab, protocol::cdp::Page};
+use headless_chrome::browser::tab::{EventListener, ModifierKey};
+use headless_chrome::protocol::cdp::types::Event;
+use headless_chrome::protocol::cdp::{Browser as BrowserDomain, Input, Network, Page};
+use headless_chrome::{Browser, LaunchOptionsBuilder, Tab, types::PrintToPdfOptions};
 use tracing::{debug, info, warn};
 
+use crate::download::{DownloadEntry, DownloadTracker};
 use crate::error::{BrowserError, Result};
+use crate::network::{NetworkEntry, NetworkRecorder, to_har};
+use crate::snapshot::{PageSnapshot, SNAPSHOT_JS};
+use crate::webdriver::WebDriverSession;
+
+/// Default base directory for named browser profiles
+pub const DEFAULT_PROFILES_DIR: &str = "data/browser-profiles";
+
+/// Default directory for downloaded files
+pub const DEFAULT_DOWNLOAD_DIR: &str = "data/downloads";
+
+/// Backend that drives the browser
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BrowserBackend {
+    /// Chrome launched by the session and controlled over CDP
+    #[default]
+    Chrome,
+    /// A browser behind a W3C WebDriver server (chromedriver, geckodriver,
+    /// Selenium); supports real frame switching, shadow DOM selectors and
+    /// device emulation, but not the CDP-only network and download features
+    WebDriver {
+        /// Server URL, e.g. `http://localhost:9515`
+        url: String,
+        /// `browserName` capability (`chrome`, `firefox`, ...)
+        browser: String,
+    },
+}
 
 /// Browser session configuration
 #[derive(Debug, Clone)]
 pub struct BrowserConfig {
+    /// Backend that drives the browser
+    pub backend: BrowserBackend,
     /// Whether to run in headless mode
     pub headless: bool,
     /// Window width in pixels
@@ -28,11 +61,29 @@ pub struct BrowserConfig {
     pub enable_gpu: bool,
     /// Custom user agent
     pub user_agent: Option<String>,
+    /// Chrome user data directory (takes precedence over `profile`)
+    ///
+    /// Without a directory or profile, Chrome runs with a temporary profile that
+    /// is removed when the session closes.
+    pub user_data_dir: Option<PathBuf>,
+    /// Named profile stored under `profiles_dir`, so logins persist across restarts
+    pub profile: Option<String>,
+    /// Base directory for named profiles
+    pub profiles_dir: PathBuf,
+    /// Directory downloads are saved to
+    pub download_dir: PathBuf,
+    /// Maximum number of concurrent browsers (one per conversation)
+    pub max_sessions: usize,
+    /// Seconds after which an unused browser is closed
+    pub idle_timeout: u64,
+    /// Device to emulate, e.g. `iPhone 14 Pro` (WebDriver backend with chromedriver)
+    pub device: Option<String>,
 }
 
 impl Default for BrowserConfig {
     fn default() -> Self {
         Self {
+            backend: BrowserBackend::Chrome,
             headless: true,
             width: 1920,
             height: 1080,
@@ -40,6 +91,13 @@ impl Default for BrowserConfig {
             element_timeout: 10,
             enable_gpu: false,
             user_agent: None,
+            user_data_dir: None,
+            profile: None,
+            profiles_dir: PathBuf::from(DEFAULT_PROFILES_DIR),
+            download_dir: PathBuf::from(DEFAULT_DOWNLOAD_DIR),
+            max_sessions: 4,
+            idle_timeout: 600,
+            device: None,
         }
     }
 }
@@ -65,6 +123,30 @@ impl BrowserConfig {
             ..Default::default()
         }
     }
+
+    /// Resolve the user data directory Chrome should use
+    ///
+    /// Returns `None` for a temporary profile. Profile names are limited to
+    /// letters, digits, `-` and `_` so they cannot escape `profiles_dir`.
+    pub fn resolve_user_data_dir(&self) -> Result<Option<PathBuf>> {
+        if let Some(ref dir) = self.user_data_dir {
+            return Ok(Some(dir.clone()));
+        }
+        let Some(ref name) = self.profile else {
+            return Ok(None);
+        };
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(BrowserError::InvalidInput(format!(
+                "Invalid profile name '{}'",
+                name
+            )));
+        }
+        Ok(Some(self.profiles_dir.join(name)))
+    }
 }
 
 /// Builder for BrowserConfig
@@ -74,6 +156,20 @@ pub struct BrowserConfigBuilder {
 }
 
 impl BrowserConfigBuilder {
+    pub fn backend(mut self, backend: BrowserBackend) -> Self {
+        self.config.backend = backend;
+        self
+    }
+
+    /// Use the WebDriver server at `url` with the given browser
+    pub fn webdriver(mut self, url: impl Into<String>, browser: impl Into<String>) -> Self {
+        self.config.backend = BrowserBackend::WebDriver {
+            url: url.into(),
+            browser: browser.into(),
+        };
+        self
+    }
+
     pub fn headless(mut self, headless: bool) -> Self {
         self.config.headless = headless;
         self
@@ -105,15 +201,152 @@ impl BrowserConfigBuilder {
         self
     }
 
+    pub fn user_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.user_data_dir = Some(dir.into());
+        self
+    }
+
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.config.profile = Some(name.into());
+        self
+    }
+
+    pub fn profiles_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.profiles_dir = dir.into();
+        self
+    }
+
+    pub fn download_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.download_dir = dir.into();
+        self
+    }
+
+    pub fn max_sessions(mut self, max: usize) -> Self {
+        self.config.max_sessions = max;
+        self
+    }
+
+    pub fn idle_timeout(mut self, seconds: u64) -> Self {
+        self.config.idle_timeout = seconds;
+        self
+    }
+
+    pub fn device(mut self, name: impl Into<String>) -> Self {
+        self.config.device = Some(name.into());
+        self
+    }
+
     pub fn build(self) -> BrowserConfig {
         self.config
     }
 }
 
+/// Clears an input, textarea or contenteditable element
+///
+/// Uses the native value setter so frameworks that track the value
+/// (React) notice the change.
+const CLEAR_VALUE_JS: &str = r#"
+function() {
+    if (this.isContentEditable) {
+        this.textContent = '';
+    } else {
+        const proto = this instanceof HTMLTextAreaElement
+            ? HTMLTextAreaElement.prototype
+            : HTMLInputElement.prototype;
+        Object.getOwnPropertyDescriptor(proto, 'value').set.call(this, '');
+    }
+    this.dispatchEvent(new Event('input', { bubbles: true }));
+}
+"#;
+
+/// Selects the options matching the given values or labels and returns the
+/// selected values as a JSON string (undefined for non-select elements)
+const SELECT_OPTION_JS: &str = r#"
+function(values) {
+    if (!(this instanceof HTMLSelectElement)) return undefined;
+    const wanted = values.map(String);
+    let matched = false;
+    for (const option of this.options) {
+        const hit = wanted.includes(option.value) || wanted.includes(option.label.trim());
+        if (hit) matched = true;
+        if (this.multiple) {
+            option.selected = hit;
+        } else if (hit && !option.selected) {
+            option.selected = true;
+        }
+    }
+    if (matched) {
+        this.dispatchEvent(new Event('input', { bubbles: true }));
+        this.dispatchEvent(new Event('change', { bubbles: true }));
+    }
+    return JSON.stringify(matched ? Array.from(this.selectedOptions).map(o => o.value) : []);
+}
+"#;
+
+/// Split a key combination like `Control+Shift+T` into the key and its modifiers
+pub fn parse_key_combo(combo: &str) -> Result<(String, Vec<ModifierKey>)> {
+    let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+    let (key, modifier_names) = match parts.split_last() {
+        Some((key, modifiers)) if !key.is_empty() => (*key, modifiers),
+        _ => {
+            return Err(BrowserError::InvalidInput(format!(
+                "Invalid key combination '{}'",
+                combo
+            )));
+        }
+    };
+
+    let mut modifiers = Vec::with_capacity(modifier_names.len());
+    for name in modifier_names {
+        modifiers.push(match name.to_lowercase().as_str() {
+            "ctrl" | "control" => ModifierKey::Ctrl,
+            "alt" | "option" => ModifierKey::Alt,
+            "shift" => ModifierKey::Shift,
+            "meta" | "cmd" | "command" | "super" => ModifierKey::Meta,
+            _ => {
+                return Err(BrowserError::InvalidInput(format!(
+                    "Unknown modifier '{}' in '{}'",
+                    name, combo
+                )));
+            }
+        });
+    }
+
+    // Letters are defined in lower case; Shift is sent as a modifier
+    let key = if key.len() == 1 && key.chars().all(|c| c.is_ascii_alphabetic()) {
+        key.to_ascii_lowercase()
+    } else {
+        key.to_string()
+    };
+    Ok((key, modifiers))
+}
+
+/// Connection to the browser
+enum Driver {
+    Chrome(Browser),
+    WebDriver(WebDriverSession),
+}
+
 /// Managed browser session
 pub struct BrowserSession {
-    browser: Browser,
+    driver: Driver,
     config: BrowserConfig,
+    /// Target ID of the selected tab (the first tab if unset)
+    selected_tab: Mutex<Option<String>>,
+    /// Network recording (kept after stopping so it can still be read)
+    network: Mutex<NetworkCapture>,
+    /// Downloads of all tabs
+    downloads: DownloadTracker,
+    /// Target IDs of tabs whose download events are tracked
+    download_tabs: Mutex<HashSet<String>>,
+}
+
+/// Network recording state of a session
+#[derive(Default)]
+struct NetworkCapture {
+    recorder: NetworkRecorder,
+    /// Tab being recorded and its event listener while recording
+    listener: Option<(String, Weak<dyn EventListener<Event> + Send + Sync>)>,
 }
 
 impl BrowserSession {
@@ -124,9 +357,36 @@ impl BrowserSession {
 
     /// Create a new browser session with custom configuration
     pub fn with_config(config: BrowserConfig) -> Result<Self> {
+        info!("Creating browser session (headless: {})", config.headless);
+
+        let driver = match config.backend {
+            BrowserBackend::Chrome => Driver::Chrome(Self::launch_chrome(&config)?),
+            BrowserBackend::WebDriver {
+                ref url,
+                ref browser,
+            } => Driver::WebDriver(WebDriverSession::connect(url, browser, &config)?),
+        };
+
+        info!("Browser session created successfully");
+
+        let downloads = DownloadTracker::new(absolute_dir(&config.download_dir)?);
+
+        Ok(Self {
+            driver,
+            config,
+            selected_tab: Mutex::new(None),
+            network: Mutex::new(NetworkCapture::default()),
+            downloads,
+            download_tabs: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn launch_chrome(config: &BrowserConfig) -> Result<Browser> {
         use std::ffi::OsStr;
 
-        info!("Creating browser session (headless: {})", config.headless);
+        if let Some(ref device) = config.device {
+            warn!("Device emulation ({}) needs the WebDriver backend; ignored", device);
+        }
 
         let mut args: Vec<String> = vec![
             format!("--window-size={},{}", config.width, config.height),
@@ -146,42 +406,103 @@ impl BrowserSession {
 
         let os_args: Vec<&OsStr> = args.iter().map(OsStr::new).collect();
 
+        let user_data_dir = config.resolve_user_data_dir()?;
+        if let Some(ref dir) = user_data_dir {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                BrowserError::Initialization(format!(
+                    "Failed to create user data dir {}: {}",
+                    dir.display(),
+                    e
+                ))
+            })?;
+            info!("Using browser profile at {}", dir.display());
+        }
+
         let launch_options = LaunchOptionsBuilder::default()
             .headless(config.headless)
             .args(os_args)
+            .user_data_dir(user_data_dir)
             .build()
             .map_err(|e| {
                 BrowserError::Initialization(format!("Failed to build launch options: {}", e))
             })?;
 
-        let browser = Browser::new(launch_options).map_err(|e| {
+        Browser::new(launch_options).map_err(|e| {
             BrowserError::Initialization(format!("Failed to launch browser: {}", e))
-        })?;
+        })
+    }
 
-        info!("Browser session created successfully");
+    /// The CDP browser (operations that only the Chrome backend supports)
+    fn browser(&self) -> Result<&Browser> {
+        match self.driver {
+            Driver::Chrome(ref browser) => Ok(browser),
+            Driver::WebDriver(_) => Err(BrowserError::Unsupported(
+                "this operation needs the Chrome (CDP) backend".to_string(),
+            )),
+        }
+    }
 
-        Ok(Self { browser, config })
+    fn webdriver(&self) -> Option<&WebDriverSession> {
+        match self.driver {
+            Driver::WebDriver(ref session) => Some(session),
+            Driver::Chrome(_) => None,
+        }
+    }
+
+    /// Whether the browser still answers
+    pub fn is_alive(&self) -> bool {
+        match self.driver {
+            Driver::Chrome(ref browser) => browser.get_version().is_ok(),
+            Driver::WebDriver(ref session) => session.is_alive(),
+        }
     }
 
     /// Get the active tab
+    ///
+    /// This is the tab selected with [`switch_tab`](Self::switch_tab) or
+    /// [`open_tab`](Self::open_tab), falling back to the first tab once it is closed.
+    /// Not available with the WebDriver backend.
     pub fn active_tab(&self) -> Result<Arc<Tab>> {
-        let tabs = self.browser.get_tabs();
+        let tabs = self.browser()?.get_tabs();
         let tabs_guard = tabs
             .lock()
             .map_err(|e| BrowserError::TabError(format!("Failed to lock tabs: {}", e)))?;
 
-        tabs_guard
-            .first()
+        let selected = self.selected_tab_id();
+        let tab = selected
+            .and_then(|id| tabs_guard.iter().find(|t| *t.get_target_id() == id))
+            .or_else(|| tabs_guard.first())
             .cloned()
-            .ok_or_else(|| BrowserError::TabError("No active tab available".to_string()))
+            .ok_or_else(|| BrowserError::TabError("No active tab available".to_string()))?;
+        drop(tabs_guard);
+
+        // Downloads are denied in headless Chrome until a tab allows them
+        if let Err(e) = self.track_downloads(&tab) {
+            warn!("Downloads are not tracked on tab {}: {}", tab.get_target_id(), e);
+        }
+        Ok(tab)
+    }
+
+    fn selected_tab_id(&self) -> Option<String> {
+        self.selected_tab
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn select_tab(&self, id: Option<String>) {
+        *self.selected_tab.lock().unwrap_or_else(|e| e.into_inner()) = id;
     }
 
     /// Navigate to a URL
     pub fn navigate(&self, url: &str) -> Result<String> {
-        let tab = self.active_tab()?;
-
         info!("Navigating to: {}", url);
 
+        if let Some(wd) = self.webdriver() {
+            return wd.navigate(url);
+        }
+        let tab = self.active_tab()?;
+
         tab.navigate_to(url).map_err(|e| {
             BrowserError::Navigation(format!("Failed to navigate to {}: {}", url, e))
         })?;
@@ -199,33 +520,117 @@ impl BrowserSession {
     }
 
     /// Take a screenshot
-    pub fn screenshot(&self, _full_page: bool) -> Result<Vec<u8>> {
+    ///
+    /// `full_page` captures the whole scrollable page instead of the viewport.
+    pub fn screenshot(&self, full_page: bool) -> Result<Vec<u8>> {
+        debug!("Taking screenshot (full_page: {})", full_page);
+
+        if let Some(wd) = self.webdriver() {
+            if full_page {
+                warn!("Full page screenshots need the Chrome backend; capturing the viewport");
+            }
+            return wd.screenshot();
+        }
         let tab = self.active_tab()?;
 
-        debug!("Taking screenshot");
+        let clip = if full_page {
+            let metrics = tab.call_method(Page::GetLayoutMetrics(None)).map_err(|e| {
+                BrowserError::Screenshot(format!("Failed to get page size: {}", e))
+            })?;
+            Some(Page::Viewport {
+                x: 0.0,
+                y: 0.0,
+                width: metrics.css_content_size.width,
+                height: metrics.css_content_size.height,
+                scale: 1.0,
+            })
+        } else {
+            None
+        };
+
+        let data = tab
+            .call_method(Page::CaptureScreenshot {
+                format: Some(Page::CaptureScreenshotFormatOption::Png),
+                quality: None,
+                clip,
+                from_surface: Some(true),
+                capture_beyond_viewport: Some(full_page),
+                optimize_for_speed: None,
+            })
+            .map_err(|e| {
+                BrowserError::Screenshot(format!("Failed to capture screenshot: {}", e))
+            })?
+            .data;
+        let screenshot = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, data)
+            .map_err(|e| BrowserError::Screenshot(format!("Invalid screenshot data: {}", e)))?;
+
+        info!("Screenshot captured: {} bytes", screenshot.len());
+
+        Ok(screenshot)
+    }
+
+    /// Take a screenshot of a single element (its bounding box)
+    pub fn screenshot_element(&self, selector: &str) -> Result<Vec<u8>> {
+        debug!("Taking screenshot of element: {}", selector);
+
+        if let Some(wd) = self.webdriver() {
+            return wd.element_screenshot(selector);
+        }
+        let tab = self.active_tab()?;
 
         let screenshot = tab
-            .capture_screenshot(
-                Page::CaptureScreenshotFormatOption::Png,
-                Some(100),
-                None,
-                true,
+            .wait_for_element_with_custom_timeout(
+                selector,
+                Duration::from_secs(self.config.element_timeout),
             )
             .map_err(|e| {
-                BrowserError::Screenshot(format!("Failed to capture screenshot: {}", e))
+                BrowserError::ElementNotFound(format!("Element '{}' not found: {}", selector, e))
+            })?
+            .capture_screenshot(Page::CaptureScreenshotFormatOption::Png)
+            .map_err(|e| {
+                BrowserError::Screenshot(format!("Failed to capture '{}': {}", selector, e))
             })?;
 
-        info!("Screenshot captured: {} bytes", screenshot.len());
+        info!("Element screenshot captured: {} bytes", screenshot.len());
 
         Ok(screenshot)
     }
 
-    /// Click an element
-    pub fn click(&self, selector: &str) -> Result<()> {
+    /// Print the current page to PDF
+    pub fn print_pdf(&self, options: &PdfOptions) -> Result<Vec<u8>> {
+        debug!("Printing page to PDF");
+
+        if let Some(wd) = self.webdriver() {
+            return wd.print_pdf(options);
+        }
         let tab = self.active_tab()?;
 
+        let pdf = tab
+            .print_to_pdf(Some(PrintToPdfOptions {
+                landscape: Some(options.landscape),
+                print_background: Some(options.print_background),
+                scale: options.scale,
+                paper_width: options.paper_width,
+                paper_height: options.paper_height,
+                page_ranges: options.page_ranges.clone(),
+                ..Default::default()
+            }))
+            .map_err(|e| BrowserError::Pdf(format!("Failed to print to PDF: {}", e)))?;
+
+        info!("PDF generated: {} bytes", pdf.len());
+
+        Ok(pdf)
+    }
+
+    /// Click an element
+    pub fn click(&self, selector: &str) -> Result<()> {
         info!("Clicking element: {}", selector);
 
+        if let Some(wd) = self.webdriver() {
+            return wd.click(selector);
+        }
+        let tab = self.active_tab()?;
+
         tab.wait_for_element_with_custom_timeout(
             selector,
             Duration::from_secs(self.config.element_timeout),
@@ -242,11 +647,18 @@ impl BrowserSession {
     }
 
     /// Type text into an element
-    pub fn type_text(&self, selector: &str, text: &str) -> Result<()> {
+    ///
+    /// Text is inserted with CDP `Input.insertText`, which goes through the
+    /// browser's native input pipeline: frameworks such as React see the
+    /// change, and non-ASCII text (IME languages, emoji) is inserted as is.
+    pub fn type_text(&self, selector: &str, text: &str, clear_first: bool) -> Result<()> {
+        info!("Typing into element: {} ({} chars)", selector, text.chars().count());
+
+        if let Some(wd) = self.webdriver() {
+            return wd.type_text(selector, text, clear_first);
+        }
         let tab = self.active_tab()?;
 
-        info!("Typing into element: {} ({} chars)", selector, text.len());
-
         let element = tab
             .wait_for_element_with_custom_timeout(
                 selector,
@@ -256,29 +668,184 @@ impl BrowserSession {
                 BrowserError::ElementNotFound(format!("Element '{}' not found: {}", selector, e))
             })?;
 
-        // Use send_character method or type into the element
-        element.click().map_err(|e| {
+        element.focus().map_err(|e| {
             BrowserError::Interaction(format!("Failed to focus '{}': {}", selector, e))
         })?;
 
-        // Type character by character
-        for c in text.chars() {
-            tab.press_key(&c.to_string()).map_err(|e| {
-                BrowserError::Interaction(format!("Failed to type character: {}", e))
+        if clear_first {
+            element.call_js_fn(CLEAR_VALUE_JS, vec![], false).map_err(|e| {
+                BrowserError::Interaction(format!("Failed to clear '{}': {}", selector, e))
             })?;
         }
 
+        if !text.is_empty() {
+            tab.call_method(Input::InsertText {
+                text: text.to_string(),
+            })
+            .map_err(|e| BrowserError::Interaction(format!("Failed to type text: {}", e)))?;
+        }
+
         info!("Typed text into element: {}", selector);
 
         Ok(())
     }
 
-    /// Extract text content from an element
-    pub fn extract_text(&self, selector: &str) -> Result<String> {
+    /// Press a key combination such as `Enter`, `Control+A` or `Shift+Tab`
+    ///
+    /// With a selector the element is focused first; otherwise the keys go to
+    /// the focused element.
+    pub fn press_keys(&self, selector: Option<&str>, combo: &str) -> Result<()> {
+        if let Some(wd) = self.webdriver() {
+            debug!("Pressing keys: {}", combo);
+            return wd.press_keys(selector, combo);
+        }
+        let tab = self.active_tab()?;
+        let (key, modifiers) = parse_key_combo(combo)?;
+
+        if let Some(selector) = selector {
+            tab.wait_for_element_with_custom_timeout(
+                selector,
+                Duration::from_secs(self.config.element_timeout),
+            )
+            .map_err(|e| {
+                BrowserError::ElementNotFound(format!("Element '{}' not found: {}", selector, e))
+            })?
+            .focus()
+            .map_err(|e| {
+                BrowserError::Interaction(format!("Failed to focus '{}': {}", selector, e))
+            })?;
+        }
+
+        debug!("Pressing keys: {}", combo);
+
+        let modifiers = (!modifiers.is_empty()).then_some(modifiers.as_slice());
+        tab.press_key_with_modifiers(&key, modifiers).map_err(|e| {
+            BrowserError::Interaction(format!("Failed to press '{}': {}", combo, e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Select options of a `<select>` element by value or visible label
+    ///
+    /// Returns the values that are selected afterwards.
+    pub fn select_option(&self, selector: &str, values: &[String]) -> Result<Vec<String>> {
+        info!("Selecting {:?} in: {}", values, selector);
+
+        let result = match self.webdriver() {
+            Some(wd) => {
+                wd.call_on_element(selector, SELECT_OPTION_JS, vec![serde_json::json!(values)])?
+            }
+            None => self
+                .active_tab()?
+                .wait_for_element_with_custom_timeout(
+                    selector,
+                    Duration::from_secs(self.config.element_timeout),
+                )
+                .map_err(|e| {
+                    BrowserError::ElementNotFound(format!(
+                        "Element '{}' not found: {}",
+                        selector, e
+                    ))
+                })?
+                .call_js_fn(SELECT_OPTION_JS, vec![serde_json::json!(values)], false)
+                .map_err(|e| {
+                    BrowserError::Interaction(format!("Failed to select in '{}': {}", selector, e))
+                })?
+                .value
+                .unwrap_or_default(),
+        };
+
+        let selected = result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| {
+                BrowserError::InvalidInput(format!("'{}' is not a <select> element", selector))
+            })?;
+        let selected: Vec<String> = serde_json::from_str(&selected)
+            .map_err(|e| BrowserError::Interaction(format!("Invalid selection result: {}", e)))?;
+
+        if selected.is_empty() && !values.is_empty() {
+            return Err(BrowserError::InvalidInput(format!(
+                "No option matching {:?} in '{}'",
+                values, selector
+            )));
+        }
+        Ok(selected)
+    }
+
+    /// Check, uncheck or toggle a checkbox / radio button
+    ///
+    /// `checked: None` toggles. The element is clicked (only when its state
+    /// has to change), so the page's own click handlers run. Returns the new state.
+    pub fn set_checked(&self, selector: &str, checked: Option<bool>) -> Result<bool> {
+        if let Some(wd) = self.webdriver() {
+            let is_checked = || {
+                wd.call_on_element(selector, "function() { return this.checked; }", vec![])?
+                    .as_bool()
+                    .ok_or_else(|| {
+                        BrowserError::InvalidInput(format!(
+                            "'{}' is not a checkbox or radio button",
+                            selector
+                        ))
+                    })
+            };
+            if checked != Some(is_checked()?) {
+                wd.click(selector)?;
+            }
+            let state = is_checked()?;
+            info!("Set '{}' checked: {}", selector, state);
+            return Ok(state);
+        }
         let tab = self.active_tab()?;
 
+        let element = tab
+            .wait_for_element_with_custom_timeout(
+                selector,
+                Duration::from_secs(self.config.element_timeout),
+            )
+            .map_err(|e| {
+                BrowserError::ElementNotFound(format!("Element '{}' not found: {}", selector, e))
+            })?;
+
+        let is_checked = |element: &headless_chrome::browser::tab::element::Element<'_>| {
+            element
+                .call_js_fn("function() { return this.checked; }", vec![], false)
+                .map_err(|e| {
+                    BrowserError::Interaction(format!("Failed to read '{}': {}", selector, e))
+                })?
+                .value
+                .and_then(|v| v.as_bool())
+                .ok_or_else(|| {
+                    BrowserError::InvalidInput(format!(
+                        "'{}' is not a checkbox or radio button",
+                        selector
+                    ))
+                })
+        };
+
+        let current = is_checked(&element)?;
+        if checked != Some(current) {
+            element.click().map_err(|e| {
+                BrowserError::Interaction(format!("Failed to click '{}': {}", selector, e))
+            })?;
+        }
+        let state = is_checked(&element)?;
+
+        info!("Set '{}' checked: {}", selector, state);
+
+        Ok(state)
+    }
+
+    /// Extract text content from an element
+    pub fn extract_text(&self, selector: &str) -> Result<String> {
         debug!("Extracting text from: {}", selector);
 
+        if let Some(wd) = self.webdriver() {
+            return wd.text(selector);
+        }
+        let tab = self.active_tab()?;
+
         let element = tab
             .wait_for_element_with_custom_timeout(
                 selector,
@@ -297,29 +864,81 @@ impl BrowserSession {
         Ok(text)
     }
 
+    /// Outline the page (or the element matching `root`) for the model
+    ///
+    /// Interactive elements are tagged with refs usable as selectors
+    /// (see [`PageSnapshot::ref_selector`]); refs from an earlier snapshot are
+    /// replaced.
+    pub fn snapshot(
+        &self,
+        root: Option<&str>,
+        interactive_only: bool,
+        max_lines: usize,
+    ) -> Result<PageSnapshot> {
+        let root = root.map_or_else(|| "null".to_string(), js_string);
+        let script = format!("{}({}, {}, {})", SNAPSHOT_JS, root, interactive_only, max_lines);
+        let json = self.eval(&script, |e| {
+            BrowserError::Extraction(format!("Failed to snapshot page: {}", e))
+        })?;
+
+        let snapshot = PageSnapshot::from_json(json.as_str().unwrap_or("{}"))?;
+        debug!(
+            "Snapshot of {}: {} lines, {} refs",
+            snapshot.url,
+            snapshot.outline.lines().count(),
+            snapshot.refs
+        );
+        Ok(snapshot)
+    }
+
+    /// Evaluate a script in the page (the current frame with WebDriver)
+    ///
+    /// `error` builds the error for a failed evaluation.
+    fn eval(
+        &self,
+        script: &str,
+        error: impl Fn(String) -> BrowserError,
+    ) -> Result<serde_json::Value> {
+        if let Some(wd) = self.webdriver() {
+            return wd.evaluate(script).map_err(|e| error(e.to_string()));
+        }
+        let result = self
+            .active_tab()?
+            .evaluate(script, false)
+            .map_err(|e| error(e.to_string()))?;
+        Ok(result.value.unwrap_or(serde_json::Value::Null))
+    }
+
     /// Execute JavaScript
     pub fn evaluate_js(&self, script: &str) -> Result<serde_json::Value> {
-        let tab = self.active_tab()?;
-
         debug!(
             "Executing JavaScript: {}...",
             &script[..std::cmp::min(50, script.len())]
         );
 
-        let result = tab.evaluate(script, false).map_err(|e| {
+        self.eval(script, |e| {
             BrowserError::Interaction(format!("JavaScript execution failed: {}", e))
-        })?;
-
-        Ok(result.value.unwrap_or(serde_json::Value::Null))
+        })
     }
 
     /// Wait for an element to appear
     pub fn wait_for(&self, selector: &str, timeout_secs: Option<u64>) -> Result<()> {
-        let tab = self.active_tab()?;
         let timeout = Duration::from_secs(timeout_secs.unwrap_or(self.config.element_timeout));
 
         debug!("Waiting for element: {} (timeout: {:?})", selector, timeout);
 
+        if let Some(wd) = self.webdriver() {
+            return match wd.find(selector, Some(timeout)) {
+                Ok(_) => Ok(()),
+                Err(BrowserError::ElementNotFound(e)) => Err(BrowserError::Timeout(format!(
+                    "Element '{}' not found within timeout: {}",
+                    selector, e
+                ))),
+                Err(e) => Err(e),
+            };
+        }
+        let tab = self.active_tab()?;
+
         tab.wait_for_element_with_custom_timeout(selector, timeout)
             .map_err(|e| {
                 BrowserError::Timeout(format!(
@@ -331,9 +950,12 @@ impl BrowserSession {
         Ok(())
     }
 
-    /// Get all tabs
+    /// Get all tabs (empty with the WebDriver backend)
     pub fn tabs(&self) -> Vec<Arc<Tab>> {
-        let tabs = self.browser.get_tabs();
+        let Ok(browser) = self.browser() else {
+            return vec![];
+        };
+        let tabs = browser.get_tabs();
         match tabs.lock() {
             Ok(guard) => guard.clone(),
             Err(_) => vec![],
@@ -343,7 +965,7 @@ impl BrowserSession {
     /// Create a new tab
     pub fn new_tab(&self) -> Result<Arc<Tab>> {
         let tab = self
-            .browser
+            .browser()?
             .new_tab()
             .map_err(|e| BrowserError::TabError(format!("Failed to create new tab: {}", e)))?;
 
@@ -352,13 +974,157 @@ impl BrowserSession {
         Ok(tab)
     }
 
+    /// List open tabs
+    pub fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+        if let Some(wd) = self.webdriver() {
+            // Only the current window can be read without switching to the others
+            let active = wd.window_handle()?;
+            return wd
+                .window_handles()?
+                .into_iter()
+                .enumerate()
+                .map(|(index, id)| {
+                    let current = id == active;
+                    Ok(TabInfo {
+                        index,
+                        url: if current { wd.url()? } else { String::new() },
+                        title: if current { wd.title()? } else { String::new() },
+                        id,
+                        active: current,
+                    })
+                })
+                .collect();
+        }
+        let active = self.active_tab()?.get_target_id().clone();
+        Ok(self
+            .tabs()
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| TabInfo {
+                index,
+                id: tab.get_target_id().clone(),
+                url: tab.get_url(),
+                title: tab.get_title().unwrap_or_default(),
+                active: *tab.get_target_id() == active,
+            })
+            .collect())
+    }
+
+    /// Open a new tab (optionally navigating to `url`) and select it
+    pub fn open_tab(&self, url: Option<&str>) -> Result<TabInfo> {
+        if let Some(wd) = self.webdriver() {
+            let id = wd.new_window()?;
+            wd.switch_window(&id)?;
+            let title = match url {
+                Some(url) => self.navigate(url)?,
+                None => String::new(),
+            };
+            return Ok(TabInfo {
+                index: wd.window_handles()?.len().saturating_sub(1),
+                id,
+                url: wd.url()?,
+                title,
+                active: true,
+            });
+        }
+        let tab = self.new_tab()?;
+        let id = tab.get_target_id().clone();
+        self.select_tab(Some(id.clone()));
+
+        let title = match url {
+            Some(url) => self.navigate(url)?,
+            None => String::new(),
+        };
+
+        Ok(TabInfo {
+            index: self.tabs().len().saturating_sub(1),
+            id,
+            url: tab.get_url(),
+            title,
+            active: true,
+        })
+    }
+
+    /// Select the tab that the other operations act on
+    ///
+    /// `tab` is a target ID or an index from [`list_tabs`](Self::list_tabs).
+    pub fn switch_tab(&self, tab: &str) -> Result<TabInfo> {
+        if let Some(wd) = self.webdriver() {
+            let handles = wd.window_handles()?;
+            let (index, id) = handles
+                .iter()
+                .position(|h| h == tab)
+                .or_else(|| tab.parse::<usize>().ok().filter(|i| *i < handles.len()))
+                .map(|i| (i, handles[i].clone()))
+                .ok_or_else(|| BrowserError::TabError(format!("Tab '{}' not found", tab)))?;
+            wd.switch_window(&id)?;
+            info!("Switched to tab: {}", id);
+            return Ok(TabInfo {
+                index,
+                id,
+                url: wd.url()?,
+                title: wd.title()?,
+                active: true,
+            });
+        }
+        let tabs = self.tabs();
+        let (index, found) = tabs
+            .iter()
+            .enumerate()
+            .find(|(_, t)| t.get_target_id() == tab)
+            .or_else(|| {
+                tab.parse::<usize>()
+                    .ok()
+                    .and_then(|i| tabs.get(i).map(|t| (i, t)))
+            })
+            .ok_or_else(|| BrowserError::TabError(format!("Tab '{}' not found", tab)))?;
+
+        found
+            .activate()
+            .map_err(|e| BrowserError::TabError(format!("Failed to activate tab: {}", e)))?;
+        self.select_tab(Some(found.get_target_id().clone()));
+
+        info!("Switched to tab: {}", found.get_target_id());
+
+        Ok(TabInfo {
+            index,
+            id: found.get_target_id().clone(),
+            url: found.get_url(),
+            title: found.get_title().unwrap_or_default(),
+            active: true,
+        })
+    }
+
     /// Close a tab by target ID
+    ///
+    /// Closing the selected tab selects the first remaining tab.
     pub fn close_tab(&self, tab_id: &str) -> Result<()> {
+        if let Some(wd) = self.webdriver() {
+            if !wd.window_handles()?.iter().any(|h| h == tab_id) {
+                return Err(BrowserError::TabError(format!("Tab '{}' not found", tab_id)));
+            }
+            let current = wd.window_handle()?;
+            wd.switch_window(tab_id)?;
+            wd.close_window()?;
+            // WebDriver leaves no window selected after closing the current one
+            let next = match wd.window_handles()?.into_iter().find(|h| *h == current) {
+                Some(handle) => Some(handle),
+                None => wd.window_handles()?.into_iter().next(),
+            };
+            if let Some(next) = next {
+                wd.switch_window(&next)?;
+            }
+            info!("Closed tab: {}", tab_id);
+            return Ok(());
+        }
         let tabs = self.tabs();
         for tab in tabs {
             if tab.get_target_id() == tab_id {
                 tab.close(true)
                     .map_err(|e| BrowserError::TabError(format!("Failed to close tab: {}", e)))?;
+                if self.selected_tab_id().as_deref() == Some(tab_id) {
+                    self.select_tab(None);
+                }
                 info!("Closed tab: {}", tab_id);
                 return Ok(());
             }
@@ -371,8 +1137,133 @@ impl BrowserSession {
         )))
     }
 
+    // ==================== Network ====================
+
+    fn network(&self) -> std::sync::MutexGuard<'_, NetworkCapture> {
+        self.network.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn enable_network(tab: &Tab) -> Result<()> {
+        tab.call_method(Network::Enable {
+            max_total_buffer_size: None,
+            max_resource_buffer_size: None,
+            max_post_data_size: None,
+            report_direct_socket_traffic: None,
+            enable_durable_messages: None,
+        })
+        .map_err(|e| BrowserError::Network(format!("Failed to enable network domain: {}", e)))?;
+        Ok(())
+    }
+
+    /// Start recording the requests of the active tab
+    ///
+    /// Clears the previous recording. Recording follows the tab that was
+    /// active when it started.
+    pub fn start_network_capture(&self) -> Result<()> {
+        self.stop_network_capture()?;
+
+        let tab = self.active_tab()?;
+        Self::enable_network(&tab)?;
+
+        let mut network = self.network();
+        network.recorder.clear();
+        let recorder = network.recorder.clone();
+        let listener = tab
+            .add_event_listener(Arc::new(move |event: &Event| recorder.handle_event(event)))
+            .map_err(|e| BrowserError::Network(format!("Failed to listen to events: {}", e)))?;
+        network.listener = Some((tab.get_target_id().clone(), listener));
+
+        info!("Started network capture on tab {}", tab.get_target_id());
+        Ok(())
+    }
+
+    /// Stop recording (the recorded requests are kept)
+    pub fn stop_network_capture(&self) -> Result<()> {
+        let Some((tab_id, listener)) = self.network().listener.take() else {
+            return Ok(());
+        };
+        // The tab may already be closed
+        if let Some(tab) = self.tabs().into_iter().find(|t| *t.get_target_id() == tab_id) {
+            tab.remove_event_listener(&listener).map_err(|e| {
+                BrowserError::Network(format!("Failed to remove event listener: {}", e))
+            })?;
+        }
+        info!("Stopped network capture");
+        Ok(())
+    }
+
+    /// Whether requests are being recorded
+    pub fn is_capturing_network(&self) -> bool {
+        self.network().listener.is_some()
+    }
+
+    /// Recorded requests (optionally only URLs containing `url_filter`)
+    pub fn network_requests(&self, url_filter: Option<&str>) -> Vec<NetworkEntry> {
+        self.network().recorder.entries(url_filter)
+    }
+
+    /// Forget the recorded requests
+    pub fn clear_network_requests(&self) {
+        self.network().recorder.clear();
+    }
+
+    /// Body of a recorded response
+    ///
+    /// Returns the body and whether it is base64 encoded (binary content).
+    /// Chrome only keeps bodies of the current page, so fetch them before
+    /// navigating away.
+    pub fn response_body(&self, request_id: &str) -> Result<(String, bool)> {
+        let tab = self.active_tab()?;
+        let body = tab
+            .call_method(Network::GetResponseBody {
+                request_id: request_id.to_string(),
+            })
+            .map_err(|e| {
+                BrowserError::Network(format!("No body for request '{}': {}", request_id, e))
+            })?;
+        Ok((body.body, body.base_64_encoded))
+    }
+
+    /// Block requests whose URL matches one of `patterns` (`*` is a wildcard)
+    ///
+    /// An empty list removes the blocking.
+    pub fn block_urls(&self, patterns: &[String]) -> Result<()> {
+        let tab = self.active_tab()?;
+        Self::enable_network(&tab)?;
+        tab.call_method(Network::SetBlockedURLs {
+            urls: patterns.to_vec(),
+        })
+        .map_err(|e| BrowserError::Network(format!("Failed to block URLs: {}", e)))?;
+
+        info!("Blocked URL patterns: {:?}", patterns);
+        Ok(())
+    }
+
+    /// Export the recorded requests as HAR 1.2
+    ///
+    /// With `include_bodies`, text response bodies that are still available are embedded.
+    pub fn export_har(&self, include_bodies: bool) -> Result<serde_json::Value> {
+        let entries = self.network_requests(None);
+        let mut bodies = HashMap::new();
+        if include_bodies {
+            for entry in entries.iter().filter(|e| e.status.is_some()) {
+                match self.response_body(&entry.request_id) {
+                    Ok((body, false)) => {
+                        bodies.insert(entry.request_id.clone(), body);
+                    }
+                    Ok((_, true)) => {}
+                    Err(e) => debug!("Skipping body of {}: {}", entry.url, e),
+                }
+            }
+        }
+        Ok(to_har(&entries, &bodies))
+    }
+
     /// Get page HTML source
     pub fn page_source(&self) -> Result<String> {
+        if let Some(wd) = self.webdriver() {
+            return wd.source();
+        }
         let tab = self.active_tab()?;
 
         let source = tab
@@ -391,8 +1282,6 @@ impl BrowserSession {
 
     /// Get all frame information from the page
     pub fn get_frames(&self) -> Result<Vec<FrameInfo>> {
-        let tab = self.active_tab()?;
-
         let frames_script = r#"
             (function() {
                 var result = [];
@@ -415,11 +1304,11 @@ impl BrowserSession {
             })()
         "#;
 
-        let result = tab.evaluate(frames_script, false).map_err(|e| {
+        let result = self.eval(frames_script, |e| {
             BrowserError::Frame(format!("Failed to get frames: {}", e))
         })?;
 
-        let frames: Vec<FrameInfo> = serde_json::from_value(result.value.unwrap_or_default())
+        let frames: Vec<FrameInfo> = serde_json::from_value(result)
             .map_err(|e| BrowserError::Frame(format!("Failed to parse frames: {}", e)))?;
 
         debug!("Found {} frames", frames.len());
@@ -427,7 +1316,15 @@ impl BrowserSession {
     }
 
     /// Switch to a frame by index or name
+    ///
+    /// With the WebDriver backend the following operations act inside the
+    /// frame; `parent` and `main` switch back out.
     pub fn switch_to_frame(&self, frame_identifier: &str) -> Result<()> {
+        if let Some(wd) = self.webdriver() {
+            wd.switch_frame(frame_identifier)?;
+            info!("Switched to frame: {}", frame_identifier);
+            return Ok(());
+        }
         let tab = self.active_tab()?;
 
         let script = format!(
@@ -464,8 +1361,6 @@ impl BrowserSession {
 
     /// Get content from a specific iframe
     pub fn get_iframe_content(&self, selector: &str) -> Result<String> {
-        let tab = self.active_tab()?;
-
         let script = format!(
             r#"
             (function() {{
@@ -479,11 +1374,11 @@ impl BrowserSession {
             selector
         );
 
-        let result = tab.evaluate(&script, false).map_err(|e| {
+        let result = self.eval(&script, |e| {
             BrowserError::Frame(format!("Failed to get iframe content: {}", e))
         })?;
 
-        let content = result.value.unwrap_or_default().as_str().unwrap_or("").to_string();
+        let content = result.as_str().unwrap_or("").to_string();
         debug!("Got iframe content for {}: {} bytes", selector, content.len());
         Ok(content)
     }
@@ -492,6 +1387,9 @@ impl BrowserSession {
 
     /// Get all cookies for the current domain
     pub fn get_cookies(&self) -> Result<Vec<CookieInfo>> {
+        if let Some(wd) = self.webdriver() {
+            return wd.cookies();
+        }
         let tab = self.active_tab()?;
 
         let cookies = tab.get_cookies().map_err(|e| {
@@ -516,44 +1414,76 @@ impl BrowserSession {
         Ok(cookie_infos)
     }
 
-    /// Set a cookie
-    pub fn set_cookie(&self, name: &str, value: &str, domain: Option<&str>, path: Option<&str>) -> Result<()> {
-        let tab = self.active_tab()?;
-
-        let domain_clause = domain.map(|d| format!(r#", domain: "{}""#, d)).unwrap_or_default();
-        let path_clause = path.map(|p| format!(r#", path: "{}""#, p)).unwrap_or_default();
+    /// Set a cookie through CDP
+    ///
+    /// Unlike `document.cookie` this can set HttpOnly and Secure cookies. Without
+    /// a domain, the cookie is scoped to the current page URL.
+    pub fn set_cookie(&self, name: &str, value: &str, options: &CookieOptions) -> Result<()> {
+        let same_site = match options.same_site.as_deref().map(str::to_lowercase).as_deref() {
+            None => None,
+            Some("strict") => Some(Network::CookieSameSite::Strict),
+            Some("lax") => Some(Network::CookieSameSite::Lax),
+            Some("none") => Some(Network::CookieSameSite::None),
+            Some(other) => {
+                return Err(BrowserError::InvalidInput(format!(
+                    "Invalid same_site '{}'. Use: strict, lax, or none",
+                    other
+                )));
+            }
+        };
 
-        let script = format!(
-            r#"
-            (function() {{
-                document.cookie = "{}={}"{}{};
-            }})()
-            "#,
-            name, value, domain_clause, path_clause
-        );
+        if let Some(wd) = self.webdriver() {
+            wd.add_cookie(name, value, options)?;
+            info!("Set cookie: {}", name);
+            return Ok(());
+        }
 
-        tab.evaluate(&script, false).map_err(|e| {
+        let cookie = Network::CookieParam {
+            name: name.to_string(),
+            value: value.to_string(),
+            url: None,
+            domain: options.domain.clone(),
+            path: options.path.clone(),
+            secure: options.secure,
+            http_only: options.http_only,
+            same_site,
+            expires: options.expires,
+            priority: None,
+            same_party: None,
+            source_scheme: None,
+            source_port: None,
+            partition_key: None,
+        };
+
+        self.active_tab()?.set_cookies(vec![cookie]).map_err(|e| {
             BrowserError::Cookie(format!("Failed to set cookie: {}", e))
         })?;
 
-        info!("Set cookie: {}={}", name, value);
+        info!("Set cookie: {}", name);
         Ok(())
     }
 
     /// Delete a cookie by name
-    pub fn delete_cookie(&self, name: &str) -> Result<()> {
+    ///
+    /// Without a domain, the cookie matching the current page URL is deleted.
+    pub fn delete_cookie(&self, name: &str, domain: Option<&str>, path: Option<&str>) -> Result<()> {
+        if let Some(wd) = self.webdriver() {
+            // WebDriver only deletes cookies visible to the current page
+            wd.delete_cookie(name)?;
+            info!("Deleted cookie: {}", name);
+            return Ok(());
+        }
         let tab = self.active_tab()?;
 
-        let script = format!(
-            r#"
-            (function() {{
-                document.cookie = "{}=; expires=Thu, 01 Jan 1970 00:00:00 UTC; path=/";
-            }})()
-            "#,
-            name
-        );
+        let cookie = Network::DeleteCookies {
+            name: name.to_string(),
+            url: None,
+            domain: domain.map(str::to_string),
+            path: path.map(str::to_string),
+            partition_key: None,
+        };
 
-        tab.evaluate(&script, false).map_err(|e| {
+        tab.delete_cookies(vec![cookie]).map_err(|e| {
             BrowserError::Cookie(format!("Failed to delete cookie: {}", e))
         })?;
 
@@ -561,113 +1491,168 @@ impl BrowserSession {
         Ok(())
     }
 
-    /// Clear all cookies
+    /// Clear all browser cookies, including HttpOnly ones
     pub fn clear_cookies(&self) -> Result<()> {
-        let script = r#"
-            (function() {
-                var cookies = document.cookie.split(";");
-                for (var i = 0; i < cookies.length; i++) {
-                    var cookie = cookies[i];
-                    var eqPos = cookie.indexOf("=");
-                    var name = eqPos > -1 ? cookie.substr(0, eqPos) : cookie;
-                    document.cookie = name + "=; expires=Thu, 01 Jan 1970 00:00:00 UTC; path=/";
-                }
-            })()
-        "#;
-
+        if let Some(wd) = self.webdriver() {
+            wd.delete_all_cookies()?;
+            info!("Cleared all cookies");
+            return Ok(());
+        }
         let tab = self.active_tab()?;
-        tab.evaluate(script, false).map_err(|e| {
-            BrowserError::Cookie(format!("Failed to clear cookies: {}", e))
-        })?;
+        tab.call_method(Network::ClearBrowserCookies(None))
+            .map_err(|e| BrowserError::Cookie(format!("Failed to clear cookies: {}", e)))?;
 
         info!("Cleared all cookies");
         Ok(())
     }
 
-    // ==================== Download Handling ====================
-
-    /// Set download behavior and directory
-    pub fn set_download_path(&self, path: &str) -> Result<()> {
-        let tab = self.active_tab()?;
+    // ==================== Web Storage ====================
 
+    /// Evaluate `body` against the selected storage of the current page
+    ///
+    /// `body` sees the storage object as `s`; a string result is returned as is.
+    fn eval_storage(&self, kind: StorageKind, body: &str) -> Result<serde_json::Value> {
         let script = format!(
-            r#"
-            (function() {{
-                window.downloadPath = "{}";
-            }})()
-            "#,
-            path
+            "(function() {{ const s = window.{}; {} }})()",
+            kind.js_name(),
+            body
         );
 
-        tab.evaluate(&script, false).map_err(|e| {
-            BrowserError::Download(format!("Failed to set download path: {}", e))
-        })?;
+        self.eval(&script, |e| {
+            BrowserError::Storage(format!("{} access failed: {}", kind.js_name(), e))
+        })
+    }
+
+    /// Get a storage value (`None` if the key is not set)
+    pub fn storage_get(&self, kind: StorageKind, key: &str) -> Result<Option<String>> {
+        let body = format!("return s.getItem({});", js_string(key));
+        Ok(self
+            .eval_storage(kind, &body)?
+            .as_str()
+            .map(str::to_string))
+    }
 
-        info!("Set download path: {}", path);
+    /// Set a storage value
+    pub fn storage_set(&self, kind: StorageKind, key: &str, value: &str) -> Result<()> {
+        let body = format!("s.setItem({}, {});", js_string(key), js_string(value));
+        self.eval_storage(kind, &body)?;
+        debug!("Set {} item: {}", kind.js_name(), key);
         Ok(())
     }
 
-    /// Trigger a file download by clicking a link
-    pub fn download_by_selector(&self, selector: &str, download_dir: &str) -> Result<String> {
-        let tab = self.active_tab()?;
+    /// Remove a storage value
+    pub fn storage_remove(&self, kind: StorageKind, key: &str) -> Result<()> {
+        let body = format!("s.removeItem({});", js_string(key));
+        self.eval_storage(kind, &body)?;
+        debug!("Removed {} item: {}", kind.js_name(), key);
+        Ok(())
+    }
 
-        // First, set up the download handler via CDP
-        let download_script = format!(
-            r#"
-            (function() {{
-                var element = document.querySelector('{}');
-                if (!element) return null;
+    /// Remove all values from the storage
+    pub fn storage_clear(&self, kind: StorageKind) -> Result<()> {
+        self.eval_storage(kind, "s.clear();")?;
+        info!("Cleared {}", kind.js_name());
+        Ok(())
+    }
 
-                var href = element.href || element.download;
-                if (href) return href;
+    /// List all key/value pairs of the storage
+    pub fn storage_list(&self, kind: StorageKind) -> Result<HashMap<String, String>> {
+        let json = self.eval_storage(kind, "return JSON.stringify(Object.assign({}, s));")?;
+        let json = json.as_str().unwrap_or("{}");
+        serde_json::from_str(json)
+            .map_err(|e| BrowserError::Storage(format!("Invalid storage contents: {}", e)))
+    }
 
-                // If it's a button or other element, click it
-                element.click();
-                return 'clicked';
-            }})()
-            "#,
-            selector
-        );
+    // ==================== Download Handling ====================
 
-        let result = tab.evaluate(&download_script, false).map_err(|e| {
-            BrowserError::Download(format!("Failed to initiate download: {}", e))
-        })?;
+    /// Let Chrome save downloads of `tab` and follow their progress
+    ///
+    /// Done once per tab; Chrome names the files by GUID and the tracker
+    /// renames them when they complete.
+    fn track_downloads(&self, tab: &Arc<Tab>) -> Result<()> {
+        let mut tracked = self.download_tabs.lock().unwrap_or_else(|e| e.into_inner());
+        if tracked.contains(tab.get_target_id()) {
+            return Ok(());
+        }
 
-        let _download_info = result.value.unwrap_or_default();
+        Self::allow_downloads(tab, &self.downloads.dir())?;
+        let tracker = self.downloads.clone();
+        tab.add_event_listener(Arc::new(move |event: &Event| tracker.handle_event(event)))
+            .map_err(|e| BrowserError::Download(format!("Failed to listen to events: {}", e)))?;
 
-        // Create a unique filename based on timestamp
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| BrowserError::Download(e.to_string()))?
-            .as_millis();
+        tracked.insert(tab.get_target_id().clone());
+        Ok(())
+    }
 
-        let filename = format!("{}/download_{}", download_dir, timestamp);
+    fn allow_downloads(tab: &Tab, dir: &Path) -> Result<()> {
+        tab.call_method(BrowserDomain::SetDownloadBehavior {
+            behavior: BrowserDomain::SetDownloadBehaviorBehaviorOption::AllowAndName,
+            browser_context_id: None,
+            download_path: Some(dir.to_string_lossy().into_owned()),
+            events_enabled: Some(true),
+        })
+        .map_err(|e| BrowserError::Download(format!("Failed to set download behavior: {}", e)))?;
+        Ok(())
+    }
 
-        info!("Download initiated for selector: {} -> {}", selector, filename);
-        Ok(filename)
+    /// Directory downloads are saved to
+    pub fn download_dir(&self) -> PathBuf {
+        self.downloads.dir()
     }
 
-    /// Wait for a download to complete
-    pub fn wait_for_download(&self, timeout_secs: u64) -> Result<Vec<PathBuf>> {
-        // This is a simplified implementation - in practice, you'd monitor the download directory
-        let timeout = Duration::from_secs(timeout_secs);
-        let start = std::time::Instant::now();
+    /// Save downloads that begin from now on to `path`
+    pub fn set_download_path(&self, path: &str) -> Result<()> {
+        let dir = absolute_dir(Path::new(path))?;
+        let tab = self.active_tab()?;
+        Self::allow_downloads(&tab, &dir)?;
+        self.downloads.set_dir(&dir);
 
-        // For headless Chrome, downloads need to be configured via LaunchOptions
-        // This is a placeholder that returns empty - actual download monitoring
-        // would require the download folder to be configured at browser launch time
+        info!("Set download path: {}", dir.display());
+        Ok(())
+    }
+
+    /// Click the element matching `selector` and wait for the downloads it starts
+    pub fn download_by_selector(&self, selector: &str, timeout_secs: u64) -> Result<Vec<DownloadEntry>> {
+        self.active_tab()?;
+        let mark = self.downloads.mark();
+        self.click(selector)?;
+
+        let finished = self.downloads.wait(mark, Duration::from_secs(timeout_secs));
+        if finished.is_empty() {
+            return Err(BrowserError::Timeout(format!(
+                "No download finished within {}s after clicking {}",
+                timeout_secs, selector
+            )));
+        }
+        Ok(finished)
+    }
+
+    /// Wait for downloads in progress (or the next one to begin) to finish
+    pub fn wait_for_download(&self, timeout_secs: u64) -> Result<Vec<DownloadEntry>> {
+        self.active_tab()?;
         debug!("Waiting for download (timeout: {}s)", timeout_secs);
 
-        while start.elapsed() < timeout {
-            // In a full implementation, you'd check the download directory here
-            std::thread::sleep(Duration::from_millis(500));
+        let mark = self.downloads.pending_mark();
+        let finished = self.downloads.wait(mark, Duration::from_secs(timeout_secs));
+        if finished.is_empty() {
+            return Err(BrowserError::Timeout(format!(
+                "No download finished within {}s",
+                timeout_secs
+            )));
         }
+        Ok(finished)
+    }
 
-        Ok(vec![])
+    /// Downloads of this session, oldest first
+    pub fn downloads(&self) -> Vec<DownloadEntry> {
+        self.downloads.entries()
     }
 
     /// Get current URL
     pub fn get_current_url(&self) -> Result<String> {
+        if let Some(wd) = self.webdriver() {
+            return wd.url();
+        }
         let tab = self.active_tab()?;
 
         let url = tab.get_url();
@@ -677,6 +1662,11 @@ impl BrowserSession {
 
     /// Go back in history
     pub fn back(&self) -> Result<()> {
+        if let Some(wd) = self.webdriver() {
+            wd.back()?;
+            info!("Navigated back");
+            return Ok(());
+        }
         let tab = self.active_tab()?;
 
         let script = r#"
@@ -693,6 +1683,11 @@ impl BrowserSession {
 
     /// Go forward in history
     pub fn forward(&self) -> Result<()> {
+        if let Some(wd) = self.webdriver() {
+            wd.forward()?;
+            info!("Navigated forward");
+            return Ok(());
+        }
         let tab = self.active_tab()?;
 
         let script = r#"
@@ -709,6 +1704,11 @@ impl BrowserSession {
 
     /// Refresh the page
     pub fn refresh(&self) -> Result<()> {
+        if let Some(wd) = self.webdriver() {
+            wd.refresh()?;
+            info!("Page refreshed");
+            return Ok(());
+        }
         let tab = self.active_tab()?;
 
         tab.reload(true, None).map_err(|e| {
@@ -720,6 +1720,77 @@ impl BrowserSession {
     }
 }
 
+/// Options for [`BrowserSession::print_pdf`]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PdfOptions {
+    /// Landscape orientation
+    #[serde(default)]
+    pub landscape: bool,
+    /// Print background graphics
+    #[serde(default = "default_print_background")]
+    pub print_background: bool,
+    /// Scale of the page rendering (Chrome's default: 1.0)
+    pub scale: Option<f64>,
+    /// Paper width in inches
+    pub paper_width: Option<f64>,
+    /// Paper height in inches
+    pub paper_height: Option<f64>,
+    /// Pages to print, e.g. "1-5, 8"
+    pub page_ranges: Option<String>,
+}
+
+fn default_print_background() -> bool {
+    true
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            landscape: false,
+            print_background: default_print_background(),
+            scale: None,
+            paper_width: None,
+            paper_height: None,
+            page_ranges: None,
+        }
+    }
+}
+
+impl PdfOptions {
+    /// Set the paper size from a format name (letter, legal, tabloid, a3, a4, a5)
+    pub fn with_paper_format(mut self, format: &str) -> Result<Self> {
+        let (width, height) = match format.to_lowercase().as_str() {
+            "letter" => (8.5, 11.0),
+            "legal" => (8.5, 14.0),
+            "tabloid" => (11.0, 17.0),
+            "a3" => (11.69, 16.54),
+            "a4" => (8.27, 11.69),
+            "a5" => (5.83, 8.27),
+            _ => {
+                return Err(BrowserError::InvalidInput(format!(
+                    "Unknown paper format '{}'",
+                    format
+                )));
+            }
+        };
+        self.paper_width = Some(width);
+        self.paper_height = Some(height);
+        Ok(self)
+    }
+}
+
+/// Tab information
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TabInfo {
+    pub index: usize,
+    /// Target ID (used to switch or close the tab)
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    /// Whether browser tools currently act on this tab
+    pub active: bool,
+}
+
 /// Frame information
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FrameInfo {
@@ -742,6 +1813,59 @@ pub struct CookieInfo {
     pub expires: Option<f64>,
 }
 
+/// Options for [`BrowserSession::set_cookie`]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct CookieOptions {
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    pub secure: Option<bool>,
+    pub http_only: Option<bool>,
+    /// `strict`, `lax` or `none`
+    pub same_site: Option<String>,
+    /// Expiry as seconds since the Unix epoch (session cookie if unset)
+    pub expires: Option<f64>,
+}
+
+/// Web storage area of a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+    Local,
+    Session,
+}
+
+impl StorageKind {
+    /// Parse `local` / `session`
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "local" | "localStorage" => Ok(Self::Local),
+            "session" | "sessionStorage" => Ok(Self::Session),
+            _ => Err(BrowserError::InvalidInput(format!(
+                "Invalid storage '{}'. Use: local or session",
+                name
+            ))),
+        }
+    }
+
+    fn js_name(self) -> &'static str {
+        match self {
+            Self::Local => "localStorage",
+            Self::Session => "sessionStorage",
+        }
+    }
+}
+
+/// Create `dir` and return its absolute path (Chrome needs one for downloads)
+fn absolute_dir(dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .and_then(|_| dir.canonicalize())
+        .map_err(|e| BrowserError::Download(format!("Invalid download dir {}: {}", dir.display(), e)))
+}
+
+/// Quote a string as a JavaScript string literal
+fn js_string(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
 impl Drop for BrowserSession {
     fn drop(&mut self) {
         info!("Closing browser session");
@@ -757,6 +1881,7 @@ mod tests {
     fn test_browser_config_default() {
         let config = BrowserConfig::default();
         assert!(config.headless);
+        assert_eq!(config.backend, BrowserBackend::Chrome);
         assert_eq!(config.width, 1920);
         assert_eq!(config.height, 1080);
     }
@@ -775,6 +1900,17 @@ mod tests {
         assert_eq!(config.height, 720);
         assert_eq!(config.navigation_timeout, 60);
         assert_eq!(config.user_agent, Some("Custom Agent".to_string()));
+
+        let config = BrowserConfig::builder()
+            .webdriver("http://localhost:4444", "firefox")
+            .build();
+        assert_eq!(
+            config.backend,
+            BrowserBackend::WebDriver {
+                url: "http://localhost:4444".to_string(),
+                browser: "firefox".to_string(),
+            }
+        );
     }
 
     #[test]
@@ -785,4 +1921,75 @@ mod tests {
         let visible = BrowserConfig::visible();
         assert!(!visible.headless);
     }
+
+    #[test]
+    fn test_profile_user_data_dir() {
+        assert_eq!(BrowserConfig::default().resolve_user_data_dir().unwrap(), None);
+
+        let config = BrowserConfig::builder()
+            .profiles_dir("/tmp/profiles")
+            .profile("work")
+            .build();
+        assert_eq!(
+            config.resolve_user_data_dir().unwrap(),
+            Some(PathBuf::from("/tmp/profiles/work"))
+        );
+
+        let config = BrowserConfig::builder()
+            .profile("work")
+            .user_data_dir("/opt/chrome-data")
+            .build();
+        assert_eq!(
+            config.resolve_user_data_dir().unwrap(),
+            Some(PathBuf::from("/opt/chrome-data"))
+        );
+
+        let config = BrowserConfig::builder().profile("../etc").build();
+        assert!(matches!(
+            config.resolve_user_data_dir(),
+            Err(BrowserError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_storage_kind_and_js_string() {
+        assert_eq!(StorageKind::parse("local").unwrap(), StorageKind::Local);
+        assert_eq!(StorageKind::parse("sessionStorage").unwrap(), StorageKind::Session);
+        assert!(StorageKind::parse("cookies").is_err());
+        assert_eq!(js_string(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+
+    #[test]
+    fn test_parse_key_combo() {
+        let (key, modifiers) = parse_key_combo("Control+A").unwrap();
+        assert_eq!(key, "a");
+        assert!(matches!(modifiers.as_slice(), [ModifierKey::Ctrl]));
+
+        let (key, modifiers) = parse_key_combo("cmd + shift + Enter").unwrap();
+        assert_eq!(key, "Enter");
+        assert!(matches!(
+            modifiers.as_slice(),
+            [ModifierKey::Meta, ModifierKey::Shift]
+        ));
+
+        let (key, modifiers) = parse_key_combo("Tab").unwrap();
+        assert_eq!(key, "Tab");
+        assert!(modifiers.is_empty());
+
+        assert!(parse_key_combo("Ctrl+").is_err());
+        assert!(parse_key_combo("Hyper+A").is_err());
+    }
+
+    #[test]
+    fn test_pdf_paper_format() {
+        let options = PdfOptions::default().with_paper_format("A4").unwrap();
+        assert_eq!(options.paper_width, Some(8.27));
+        assert_eq!(options.paper_height, Some(11.69));
+        assert!(options.print_background);
+
+        assert!(matches!(
+            PdfOptions::default().with_paper_format("napkin"),
+            Err(BrowserError::InvalidInput(_))
+        ));
+    }
 }
@@ -0,0 +1,268 @@
+//! Page snapshots
+//!
+//! Builds a compact outline of the page (roles, accessible names and states)
+//! similar to Playwright's aria snapshots. Interactive elements get a ref
+//! that doubles as a CSS selector, so the model can act on the page without
+//! reading the HTML or a screenshot.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{BrowserError, Result};
+
+/// Attribute holding the ref of an interactive element
+pub const REF_ATTRIBUTE: &str = "data-cc-ref";
+
+/// Default maximum number of outline lines
+pub const DEFAULT_MAX_LINES: usize = 500;
+
+/// Walks the DOM (including open shadow roots) and returns the outline as JSON
+///
+/// Arguments: root selector (or null), interactive only, maximum lines.
+pub(crate) const SNAPSHOT_JS: &str = r#"
+(function(rootSelector, interactiveOnly, maxLines) {
+    const REF = 'data-cc-ref';
+    const root = rootSelector ? document.querySelector(rootSelector) : document.body;
+    if (!root) return JSON.stringify({ error: 'Element not found: ' + rootSelector });
+    document.querySelectorAll('[' + REF + ']').forEach(e => e.removeAttribute(REF));
+
+    const SKIP = new Set(['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'svg', 'HEAD', 'META', 'LINK']);
+    const INPUT_ROLES = {
+        button: 'button', submit: 'button', reset: 'button', image: 'button',
+        checkbox: 'checkbox', radio: 'radio', range: 'slider', number: 'spinbutton',
+        search: 'searchbox', file: 'button'
+    };
+    const TAG_ROLES = {
+        BUTTON: 'button', TEXTAREA: 'textbox', NAV: 'navigation', MAIN: 'main',
+        HEADER: 'banner', FOOTER: 'contentinfo', ASIDE: 'complementary', FORM: 'form',
+        UL: 'list', OL: 'list', LI: 'listitem', TABLE: 'table', TR: 'row',
+        TH: 'columnheader', TD: 'cell', IMG: 'img', DIALOG: 'dialog', OPTION: 'option',
+        SUMMARY: 'button', IFRAME: 'iframe', H1: 'heading', H2: 'heading', H3: 'heading',
+        H4: 'heading', H5: 'heading', H6: 'heading'
+    };
+    const INTERACTIVE_ROLES = new Set([
+        'button', 'link', 'checkbox', 'radio', 'tab', 'menuitem', 'option', 'switch',
+        'textbox', 'searchbox', 'combobox', 'listbox', 'slider', 'spinbutton'
+    ]);
+    // Roles whose name comes from their content; their children are not listed
+    const LEAF_ROLES = new Set([
+        'button', 'link', 'heading', 'option', 'textbox', 'searchbox', 'checkbox',
+        'radio', 'img', 'combobox', 'listbox', 'slider', 'spinbutton', 'switch',
+        'tab', 'menuitem'
+    ]);
+
+    const lines = [];
+    let refs = 0;
+    let truncated = false;
+
+    const clean = (text, max) => {
+        const t = (text || '').replace(/\s+/g, ' ').trim();
+        return t.length > max ? t.slice(0, max - 1) + '…' : t;
+    };
+
+    function role(el) {
+        const explicit = el.getAttribute('role');
+        if (explicit) return explicit.split(' ')[0];
+        const tag = el.tagName;
+        if (tag === 'A') return el.hasAttribute('href') ? 'link' : null;
+        if (tag === 'INPUT') {
+            const type = (el.getAttribute('type') || 'text').toLowerCase();
+            if (type === 'hidden') return null;
+            return INPUT_ROLES[type] || 'textbox';
+        }
+        if (tag === 'SELECT') return el.multiple || el.size > 1 ? 'listbox' : 'combobox';
+        if (tag === 'SECTION' && (el.hasAttribute('aria-label') || el.hasAttribute('aria-labelledby'))) {
+            return 'region';
+        }
+        return TAG_ROLES[tag] || null;
+    }
+
+    function hidden(el) {
+        if (el.hidden || el.getAttribute('aria-hidden') === 'true') return true;
+        const style = getComputedStyle(el);
+        return style.display === 'none' || style.visibility === 'hidden';
+    }
+
+    function interactive(el, r) {
+        if (INTERACTIVE_ROLES.has(r)) return true;
+        if (el.isContentEditable && !(el.parentElement && el.parentElement.isContentEditable)) return true;
+        if (el.hasAttribute('onclick')) return true;
+        const tabindex = el.getAttribute('tabindex');
+        return tabindex !== null && Number(tabindex) >= 0 && r !== null;
+    }
+
+    function name(el, r) {
+        const label = el.getAttribute('aria-label');
+        if (label) return clean(label, 80);
+        const labelledBy = el.getAttribute('aria-labelledby');
+        if (labelledBy) {
+            const text = labelledBy.split(' ')
+                .map(id => document.getElementById(id))
+                .filter(Boolean)
+                .map(e => e.textContent)
+                .join(' ');
+            if (text.trim()) return clean(text, 80);
+        }
+        if (el.labels && el.labels.length) {
+            return clean(Array.from(el.labels).map(l => l.textContent).join(' '), 80);
+        }
+        if (el.tagName === 'IMG') return clean(el.getAttribute('alt'), 80);
+        if (el.tagName === 'INPUT' && ['button', 'submit', 'reset'].includes(el.type)) {
+            return clean(el.value, 80);
+        }
+        if (LEAF_ROLES.has(r) && !['textbox', 'searchbox', 'combobox', 'listbox'].includes(r)) {
+            const text = clean(el.innerText || el.textContent, 80);
+            if (text) return text;
+        }
+        return clean(el.getAttribute('title') || el.getAttribute('placeholder'), 80);
+    }
+
+    function describe(el, r) {
+        let line = r;
+        const n = name(el, r);
+        if (n) line += ' ' + JSON.stringify(n);
+        const attrs = [];
+        if (r === 'heading') attrs.push('level=' + (Number(el.tagName[1]) || el.getAttribute('aria-level') || 2));
+        if (el.checked || el.getAttribute('aria-checked') === 'true') attrs.push('checked');
+        if (el.disabled || el.getAttribute('aria-disabled') === 'true') attrs.push('disabled');
+        if (el.getAttribute('aria-expanded')) attrs.push('expanded=' + el.getAttribute('aria-expanded'));
+        if (el.required) attrs.push('required');
+        if (interactive(el, r)) {
+            refs += 1;
+            const ref = 'e' + refs;
+            el.setAttribute(REF, ref);
+            attrs.push('ref=' + ref);
+        }
+        if (attrs.length) line += ' [' + attrs.join(', ') + ']';
+
+        if (r === 'link' && el.getAttribute('href')) {
+            line += ' -> ' + clean(el.getAttribute('href'), 100);
+        } else if ((r === 'textbox' || r === 'searchbox' || r === 'spinbutton') && el.type !== 'password' && el.value) {
+            line += ': ' + JSON.stringify(clean(el.value, 80));
+        } else if (el.tagName === 'SELECT') {
+            const selected = Array.from(el.selectedOptions).map(o => clean(o.label, 40));
+            if (selected.length) line += ': ' + JSON.stringify(selected.join(', '));
+        }
+        return line;
+    }
+
+    function push(depth, text) {
+        if (lines.length >= maxLines) {
+            truncated = true;
+            return false;
+        }
+        lines.push('  '.repeat(depth) + '- ' + text);
+        return true;
+    }
+
+    function walk(node, depth) {
+        for (const child of node.childNodes) {
+            if (truncated) return;
+            if (child.nodeType === Node.TEXT_NODE) {
+                if (interactiveOnly) continue;
+                const text = clean(child.textContent, 200);
+                if (text) push(depth, 'text: ' + JSON.stringify(text));
+                continue;
+            }
+            if (child.nodeType !== Node.ELEMENT_NODE) continue;
+            const el = child;
+            if (SKIP.has(el.tagName) || hidden(el)) continue;
+
+            const r = role(el);
+            const isInteractive = interactive(el, r);
+            const listed = interactiveOnly ? isInteractive : (r !== null || isInteractive);
+            let childDepth = depth;
+            if (listed) {
+                if (!push(depth, describe(el, r || 'generic'))) return;
+                childDepth = depth + 1;
+                if (LEAF_ROLES.has(r) || r === 'iframe') continue;
+            }
+            if (el.shadowRoot) walk(el.shadowRoot, childDepth);
+            walk(el, childDepth);
+        }
+    }
+
+    walk(root, 0);
+    return JSON.stringify({ url: location.href, title: document.title, lines, refs, truncated });
+})
+"#;
+
+/// Outline of a page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSnapshot {
+    pub url: String,
+    pub title: String,
+    /// One indented `- role "name" [attributes]` line per element
+    pub outline: String,
+    /// Number of interactive elements with a ref
+    pub refs: usize,
+    /// Whether the outline was cut at the line limit
+    pub truncated: bool,
+}
+
+#[derive(Deserialize)]
+struct RawSnapshot {
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    lines: Vec<String>,
+    #[serde(default)]
+    refs: usize,
+    #[serde(default)]
+    truncated: bool,
+}
+
+impl PageSnapshot {
+    /// Parse the JSON returned by the snapshot script
+    pub(crate) fn from_json(json: &str) -> Result<Self> {
+        let raw: RawSnapshot = serde_json::from_str(json)
+            .map_err(|e| BrowserError::Extraction(format!("Invalid snapshot: {}", e)))?;
+        if let Some(error) = raw.error {
+            return Err(BrowserError::ElementNotFound(error));
+        }
+        Ok(Self {
+            url: raw.url,
+            title: raw.title,
+            outline: raw.lines.join("\n"),
+            refs: raw.refs,
+            truncated: raw.truncated,
+        })
+    }
+
+    /// CSS selector of the element with the given ref (e.g. `e3`)
+    pub fn ref_selector(reference: &str) -> String {
+        format!("[{}=\"{}\"]", REF_ATTRIBUTE, reference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_from_json() {
+        let json = r#"{
+            "url": "https://example.com/",
+            "title": "Example",
+            "lines": ["- heading \"Example\" [level=1]", "- link \"More\" [ref=e1] -> /more"],
+            "refs": 1,
+            "truncated": false
+        }"#;
+        let snapshot = PageSnapshot::from_json(json).unwrap();
+        assert_eq!(snapshot.title, "Example");
+        assert_eq!(snapshot.refs, 1);
+        assert_eq!(snapshot.outline.lines().count(), 2);
+        assert!(snapshot.outline.contains("[ref=e1]"));
+
+        let missing = PageSnapshot::from_json(r##"{"error": "Element not found: #app"}"##);
+        assert!(matches!(missing, Err(BrowserError::ElementNotFound(_))));
+    }
+
+    #[test]
+    fn test_ref_selector() {
+        assert_eq!(PageSnapshot::ref_selector("e3"), r#"[data-cc-ref="e3"]"#);
+    }
+}
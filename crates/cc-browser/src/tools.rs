@@ -5,20 +5,25 @@
 use async_trait::async_trait;
 use serde_json::{Value, json};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
 use tracing::{debug, info};
 
+use cc_core::llm::ImageSource;
 use cc_core::{Tool, ToolResult};
 
+use crate::download::DownloadEntry;
 use crate::error::{BrowserError, Result};
-use crate::session::{BrowserConfig, BrowserSession};
+use crate::pool::BrowserPool;
+use crate::session::{BrowserConfig, BrowserSession, CookieOptions, PdfOptions, StorageKind};
+use crate::snapshot::DEFAULT_MAX_LINES;
 
 /// Shared browser session manager
 ///
-/// Maintains a single browser instance that can be reused across tool calls.
+/// Hands tool calls the browser of their conversation from a [`BrowserPool`].
+/// Clones share the pool.
+#[derive(Clone)]
 pub struct BrowserManager {
-    session: Arc<Mutex<Option<BrowserSession>>>,
-    config: BrowserConfig,
+    pool: Arc<BrowserPool>,
 }
 
 impl BrowserManager {
@@ -30,20 +35,13 @@ impl BrowserManager {
     /// Create a new browser manager with custom configuration
     pub fn with_config(config: BrowserConfig) -> Self {
         Self {
-            session: Arc::new(Mutex::new(None)),
-            config,
+            pool: Arc::new(BrowserPool::new(config)),
         }
     }
 
     /// Get or create a browser session
     pub async fn get_session(&self) -> Result<BrowserSession> {
-        let mut session_guard = self.session.lock().await;
-
-        if session_guard.is_none() {
-            info!("Creating new browser session");
-            let new_session = BrowserSession::with_config(self.config.clone())?;
-            *session_guard = Some(new_session);
-        }
+        self.execute_with_session(|_| Ok(())).await?;
 
         // Clone is not directly possible for BrowserSession, so we return a reference
         // In practice, we use the session directly through the guard
@@ -52,37 +50,51 @@ impl BrowserManager {
         ))
     }
 
-    /// Execute an operation with the browser session
+    /// Execute an operation with the browser session of the current conversation
+    ///
+    /// The operation runs on a blocking thread, queued behind earlier
+    /// operations of the same conversation.
     pub async fn execute_with_session<F, T>(&self, f: F) -> Result<T>
     where
-        F: FnOnce(&BrowserSession) -> Result<T>,
+        F: FnOnce(&BrowserSession) -> Result<T> + Send + 'static,
+        T: Send + 'static,
     {
-        let mut session_guard = self.session.lock().await;
-
-        if session_guard.is_none() {
-            info!("Creating new browser session");
-            let new_session = BrowserSession::with_config(self.config.clone())?;
-            *session_guard = Some(new_session);
-        }
-
-        let session = session_guard.as_ref().ok_or_else(|| {
-            BrowserError::Initialization("Failed to get browser session".to_string())
-        })?;
-
-        f(session)
+        self.pool.execute(f).await
     }
 
-    /// Close the browser session
+    /// Close all browser sessions
     pub async fn close(&self) {
-        let mut session_guard = self.session.lock().await;
-        if session_guard.take().is_some() {
-            info!("Browser session closed");
-        }
+        self.pool.close_all().await;
     }
 
     /// Check if a session is active
     pub async fn is_active(&self) -> bool {
-        self.session.lock().await.is_some()
+        self.pool.active_sessions() > 0
+    }
+
+    /// The session pool
+    pub fn pool(&self) -> &BrowserPool {
+        &self.pool
+    }
+
+    /// Periodically close browsers that exceeded the idle timeout
+    ///
+    /// The task stops once every clone of this manager is dropped.
+    pub fn start_idle_eviction(&self) -> tokio::task::JoinHandle<()> {
+        let pool = Arc::downgrade(&self.pool);
+        let period = Duration::from_secs((self.pool.config().idle_timeout / 2).clamp(1, 60));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(pool) = pool.upgrade() else { break };
+                let closed = pool.evict_idle();
+                if closed > 0 {
+                    debug!("Closed {} idle browser sessions", closed);
+                }
+            }
+        })
     }
 }
 
@@ -92,15 +104,6 @@ impl Default for BrowserManager {
     }
 }
 
-impl Clone for BrowserManager {
-    fn clone(&self) -> Self {
-        Self {
-            session: self.session.clone(),
-            config: self.config.clone(),
-        }
-    }
-}
-
 /// Browser navigate tool
 pub struct BrowserNavigateTool {
     manager: BrowserManager,
@@ -144,14 +147,15 @@ impl Tool for BrowserNavigateTool {
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
         let url = input["url"]
             .as_str()
-            .ok_or_else(|| cc_core::Error::ToolExecution("Missing url parameter".to_string()))?;
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing url parameter".to_string()))?
+            .to_string();
 
         debug!("browser_navigate: {}", url);
 
         let result = self
             .manager
-            .execute_with_session(|session| {
-                let title = session.navigate(url)?;
+            .execute_with_session(move |session| {
+                let title = session.navigate(&url)?;
                 Ok(json!({
                     "url": url,
                     "title": title,
@@ -189,7 +193,7 @@ impl Tool for BrowserClickTool {
     }
 
     fn description(&self) -> &str {
-        "Click an element in the browser using CSS selector"
+        "Click an element, check/uncheck/toggle a checkbox, or choose options of a <select> element"
     }
 
     fn input_schema(&self) -> Value {
@@ -198,7 +202,16 @@ impl Tool for BrowserClickTool {
             "properties": {
                 "selector": {
                     "type": "string",
-                    "description": "CSS selector for the element to click"
+                    "description": "CSS selector for the element"
+                },
+                "action": {
+                    "type": "string",
+                    "description": "Action: 'click' (default), 'check', 'uncheck', 'toggle', or 'select'"
+                },
+                "values": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Option values or labels to select (for select)"
                 },
                 "wait": {
                     "type": "boolean",
@@ -211,24 +224,84 @@ impl Tool for BrowserClickTool {
     }
 
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
-        let selector = input["selector"].as_str().ok_or_else(|| {
-            cc_core::Error::ToolExecution("Missing selector parameter".to_string())
-        })?;
+        let selector = input["selector"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing selector parameter".to_string()))?
+            .to_string();
+        let action = input["action"].as_str().unwrap_or("click").to_string();
 
-        debug!("browser_click: {}", selector);
+        debug!("browser_click: {} ({})", selector, action);
 
-        let result = self
-            .manager
-            .execute_with_session(|session| {
-                session.click(selector)?;
-                Ok(json!({
-                    "selector": selector,
-                    "action": "clicked",
-                    "status": "success"
-                }))
-            })
-            .await
-            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+        let result = match action.as_str() {
+            "click" => {
+                self.manager
+                    .execute_with_session(move |session| {
+                        session.click(&selector)?;
+                        Ok(json!({
+                            "selector": selector,
+                            "action": "clicked",
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
+
+            "check" | "uncheck" | "toggle" => {
+                let checked = match action.as_str() {
+                    "check" => Some(true),
+                    "uncheck" => Some(false),
+                    _ => None,
+                };
+                self.manager
+                    .execute_with_session(move |session| {
+                        let checked = session.set_checked(&selector, checked)?;
+                        Ok(json!({
+                            "selector": selector,
+                            "action": action,
+                            "checked": checked,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
+
+            "select" => {
+                let values: Vec<String> = match &input["values"] {
+                    Value::Array(values) => values
+                        .iter()
+                        .filter_map(|v| match v {
+                            Value::String(s) => Some(s.clone()),
+                            Value::Null => None,
+                            other => Some(other.to_string()),
+                        })
+                        .collect(),
+                    Value::String(value) => vec![value.clone()],
+                    _ => {
+                        return Err(cc_core::Error::ToolExecution(
+                            "Missing values parameter".to_string(),
+                        ))
+                    }
+                };
+                self.manager
+                    .execute_with_session(move |session| {
+                        let selected = session.select_option(&selector, &values)?;
+                        Ok(json!({
+                            "selector": selector,
+                            "action": "select",
+                            "selected": selected,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
+
+            _ => {
+                return Ok(ToolResult::error(
+                    "Invalid action. Use: click, check, uncheck, toggle, or select",
+                ))
+            }
+        }
+        .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
 
         Ok(ToolResult::success(
             serde_json::to_string(&result).unwrap_or_default(),
@@ -258,7 +331,7 @@ impl Tool for BrowserTypeTool {
     }
 
     fn description(&self) -> &str {
-        "Type text into an input field in the browser"
+        "Type text into an input field in the browser and/or press a key combination (e.g. Enter, Control+A)"
     }
 
     fn input_schema(&self) -> Value {
@@ -267,7 +340,7 @@ impl Tool for BrowserTypeTool {
             "properties": {
                 "selector": {
                     "type": "string",
-                    "description": "CSS selector for the input field"
+                    "description": "CSS selector for the input field (optional for keys: defaults to the focused element)"
                 },
                 "text": {
                     "type": "string",
@@ -277,29 +350,47 @@ impl Tool for BrowserTypeTool {
                     "type": "boolean",
                     "description": "Clear the field before typing (default: true)",
                     "default": true
+                },
+                "keys": {
+                    "type": "string",
+                    "description": "Key combination to press after typing, e.g. 'Enter', 'Tab', 'Control+A', 'Shift+ArrowDown'"
                 }
-            },
-            "required": ["selector", "text"]
+            }
         })
     }
 
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
-        let selector = input["selector"].as_str().ok_or_else(|| {
-            cc_core::Error::ToolExecution("Missing selector parameter".to_string())
-        })?;
-        let text = input["text"]
-            .as_str()
-            .ok_or_else(|| cc_core::Error::ToolExecution("Missing text parameter".to_string()))?;
+        let selector = input["selector"].as_str().map(str::to_string);
+        let text = input["text"].as_str().map(str::to_string);
+        let clear_first = input["clear_first"].as_bool().unwrap_or(true);
+        let keys = input["keys"].as_str().map(str::to_string);
+
+        if text.is_none() && keys.is_none() {
+            return Ok(ToolResult::error("Specify text and/or keys"));
+        }
+        if text.is_some() && selector.is_none() {
+            return Err(cc_core::Error::ToolExecution(
+                "Missing selector parameter".to_string(),
+            ));
+        }
 
-        debug!("browser_type: {} -> {}", selector, text);
+        debug!("browser_type: {:?} -> {:?} (keys: {:?})", selector, text, keys);
 
         let result = self
             .manager
-            .execute_with_session(|session| {
-                session.type_text(selector, text)?;
+            .execute_with_session(move |session| {
+                if let (Some(selector), Some(text)) = (&selector, &text) {
+                    session.type_text(selector, text, clear_first)?;
+                }
+                if let Some(keys) = &keys {
+                    // Typing already focused the element
+                    let focus = if text.is_some() { None } else { selector.as_deref() };
+                    session.press_keys(focus, keys)?;
+                }
                 Ok(json!({
                     "selector": selector,
                     "text": text,
+                    "keys": keys,
                     "action": "typed",
                     "status": "success"
                 }))
@@ -335,7 +426,7 @@ impl Tool for BrowserScreenshotTool {
     }
 
     fn description(&self) -> &str {
-        "Take a screenshot of the current page and return as base64"
+        "Take a screenshot of the current page (or a single element); the image is returned for you to look at"
     }
 
     fn input_schema(&self) -> Value {
@@ -357,30 +448,140 @@ impl Tool for BrowserScreenshotTool {
 
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
         let full_page = input["full_page"].as_bool().unwrap_or(false);
-        let _selector = input["selector"].as_str(); // Element screenshot not yet supported
+        let selector = input["selector"].as_str().map(str::to_string);
 
-        debug!("browser_screenshot: full_page={}", full_page);
+        debug!("browser_screenshot: full_page={}, selector={:?}", full_page, selector);
 
-        let result = self
+        let (result, screenshot_data) = self
             .manager
-            .execute_with_session(|session| {
-                let screenshot_data = session.screenshot(full_page)?;
-                let base64_data = base64::Engine::encode(
-                    &base64::engine::general_purpose::STANDARD,
-                    &screenshot_data,
-                );
+            .execute_with_session(move |session| {
+                let screenshot_data = match &selector {
+                    Some(selector) => session.screenshot_element(selector)?,
+                    None => session.screenshot(full_page)?,
+                };
 
-                Ok(json!({
-                    "image": base64_data,
+                let result = json!({
                     "format": "png",
                     "size_bytes": screenshot_data.len(),
-                    "full_page": full_page,
+                    "full_page": full_page && selector.is_none(),
+                    "selector": selector,
                     "status": "success"
-                }))
+                });
+                Ok((result, screenshot_data))
             })
             .await
             .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
 
+        // Sent as an image block so the model sees the page instead of base64 text
+        Ok(ToolResult::success(serde_json::to_string(&result).unwrap_or_default())
+            .with_image(ImageSource::png(&screenshot_data)))
+    }
+}
+
+/// Browser PDF export tool
+pub struct BrowserPdfTool {
+    manager: BrowserManager,
+}
+
+impl BrowserPdfTool {
+    pub fn new(manager: BrowserManager) -> Self {
+        Self { manager }
+    }
+
+    pub fn with_defaults() -> Self {
+        Self::new(BrowserManager::new())
+    }
+}
+
+#[async_trait]
+impl Tool for BrowserPdfTool {
+    fn name(&self) -> &str {
+        "browser_pdf"
+    }
+
+    fn description(&self) -> &str {
+        "Print the current page to PDF and save it to a file (or return it as base64)"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File to write the PDF to (optional, returns base64 if omitted)"
+                },
+                "paper": {
+                    "type": "string",
+                    "description": "Paper format: 'letter', 'legal', 'tabloid', 'a3', 'a4' or 'a5' (default: letter)"
+                },
+                "landscape": {
+                    "type": "boolean",
+                    "description": "Landscape orientation (default: false)",
+                    "default": false
+                },
+                "print_background": {
+                    "type": "boolean",
+                    "description": "Print background graphics (default: true)",
+                    "default": true
+                },
+                "scale": {
+                    "type": "number",
+                    "description": "Scale of the page rendering, 0.1 - 2 (default: 1)"
+                },
+                "page_ranges": {
+                    "type": "string",
+                    "description": "Pages to print, e.g. '1-5, 8' (default: all)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let path = input["path"].as_str();
+
+        let mut options = PdfOptions {
+            landscape: input["landscape"].as_bool().unwrap_or(false),
+            print_background: input["print_background"].as_bool().unwrap_or(true),
+            scale: input["scale"].as_f64(),
+            page_ranges: input["page_ranges"].as_str().map(str::to_string),
+            ..Default::default()
+        };
+        if let Some(paper) = input["paper"].as_str() {
+            options = match options.with_paper_format(paper) {
+                Ok(options) => options,
+                Err(e) => return Ok(ToolResult::error(e.to_string())),
+            };
+        }
+
+        debug!("browser_pdf: path={:?}", path);
+
+        let pdf = self
+            .manager
+            .execute_with_session(move |session| session.print_pdf(&options))
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        let result = match path {
+            Some(path) => {
+                if let Some(parent) = std::path::Path::new(path).parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, &pdf).await?;
+                info!("Saved PDF to {} ({} bytes)", path, pdf.len());
+                json!({
+                    "path": path,
+                    "size_bytes": pdf.len(),
+                    "status": "success"
+                })
+            }
+            None => json!({
+                "pdf": base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &pdf),
+                "size_bytes": pdf.len(),
+                "status": "success"
+            }),
+        };
+
         Ok(ToolResult::success(
             serde_json::to_string(&result).unwrap_or_default(),
         ))
@@ -430,14 +631,14 @@ impl Tool for BrowserExtractTool {
     }
 
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
-        let selector = input["selector"].as_str().unwrap_or("body");
+        let selector = input["selector"].as_str().unwrap_or("body").to_string();
         let include_html = input["include_html"].as_bool().unwrap_or(false);
 
         debug!("browser_extract: selector={}", selector);
 
         let result = self
             .manager
-            .execute_with_session(|session| {
+            .execute_with_session(move |session| {
                 if include_html {
                     let html = session.page_source()?;
                     Ok(json!({
@@ -447,7 +648,7 @@ impl Tool for BrowserExtractTool {
                         "status": "success"
                     }))
                 } else {
-                    let text = session.extract_text(selector)?;
+                    let text = session.extract_text(&selector)?;
                     Ok(json!({
                         "selector": selector,
                         "text": text,
@@ -465,6 +666,85 @@ impl Tool for BrowserExtractTool {
     }
 }
 
+/// Browser snapshot tool (accessibility-style page outline)
+pub struct BrowserSnapshotTool {
+    manager: BrowserManager,
+}
+
+impl BrowserSnapshotTool {
+    pub fn new(manager: BrowserManager) -> Self {
+        Self { manager }
+    }
+
+    pub fn with_defaults() -> Self {
+        Self::new(BrowserManager::new())
+    }
+}
+
+#[async_trait]
+impl Tool for BrowserSnapshotTool {
+    fn name(&self) -> &str {
+        "browser_snapshot"
+    }
+
+    fn description(&self) -> &str {
+        "Get a compact outline of the current page: roles, names and states of elements. \
+         Interactive elements get a ref like [ref=e3]; use the selector [data-cc-ref=\"e3\"] \
+         with browser_click or browser_type. Refs change with every snapshot."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "selector": {
+                    "type": "string",
+                    "description": "CSS selector of the element to outline (optional, defaults to the whole page)"
+                },
+                "interactive_only": {
+                    "type": "boolean",
+                    "description": "Only list interactive elements (default: false)",
+                    "default": false
+                },
+                "max_lines": {
+                    "type": "integer",
+                    "description": "Maximum number of outline lines (default: 500)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let selector = input["selector"].as_str().map(str::to_string);
+        let interactive_only = input["interactive_only"].as_bool().unwrap_or(false);
+        let max_lines = input["max_lines"]
+            .as_u64()
+            .map_or(DEFAULT_MAX_LINES, |n| n as usize);
+
+        debug!("browser_snapshot: selector={:?}", selector);
+
+        let snapshot = self
+            .manager
+            .execute_with_session(move |session| {
+                session.snapshot(selector.as_deref(), interactive_only, max_lines)
+            })
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        let mut output = format!(
+            "Page: {} ({})\nRefs: {}\n\n{}",
+            snapshot.title, snapshot.url, snapshot.refs, snapshot.outline
+        );
+        if snapshot.truncated {
+            output.push_str(&format!(
+                "\n\n[truncated at {} lines; pass a selector or interactive_only to narrow it]",
+                max_lines
+            ));
+        }
+        Ok(ToolResult::success(output))
+    }
+}
+
 /// Browser evaluate tool (JavaScript execution)
 pub struct BrowserEvaluateTool {
     manager: BrowserManager,
@@ -506,14 +786,15 @@ impl Tool for BrowserEvaluateTool {
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
         let script = input["script"]
             .as_str()
-            .ok_or_else(|| cc_core::Error::ToolExecution("Missing script parameter".to_string()))?;
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing script parameter".to_string()))?
+            .to_string();
 
         debug!("browser_evaluate: {} bytes", script.len());
 
         let result = self
             .manager
-            .execute_with_session(|session| {
-                let value = session.evaluate_js(script)?;
+            .execute_with_session(move |session| {
+                let value = session.evaluate_js(&script)?;
                 Ok(json!({
                     "result": value,
                     "status": "success"
@@ -572,17 +853,18 @@ impl Tool for BrowserWaitTool {
     }
 
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
-        let selector = input["selector"].as_str().ok_or_else(|| {
-            cc_core::Error::ToolExecution("Missing selector parameter".to_string())
-        })?;
+        let selector = input["selector"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing selector parameter".to_string()))?
+            .to_string();
         let timeout = input["timeout"].as_u64();
 
         debug!("browser_wait: {} (timeout: {:?})", selector, timeout);
 
         let result = self
             .manager
-            .execute_with_session(|session| {
-                session.wait_for(selector, timeout)?;
+            .execute_with_session(move |session| {
+                session.wait_for(&selector, timeout)?;
                 Ok(json!({
                     "selector": selector,
                     "action": "waited",
@@ -633,7 +915,7 @@ impl Tool for BrowserFramesTool {
     async fn execute(&self, _input: Value) -> cc_core::Result<ToolResult> {
         let result = self
             .manager
-            .execute_with_session(|session| {
+            .execute_with_session(move |session| {
                 let frames = session.get_frames()?;
                 Ok(json!({
                     "frames": frames,
@@ -689,16 +971,17 @@ impl Tool for BrowserSwitchFrameTool {
     }
 
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
-        let frame = input["frame"].as_str().ok_or_else(|| {
-            cc_core::Error::ToolExecution("Missing frame parameter".to_string())
-        })?;
+        let frame = input["frame"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing frame parameter".to_string()))?
+            .to_string();
 
         debug!("browser_switch_frame: {}", frame);
 
         let result = self
             .manager
-            .execute_with_session(|session| {
-                session.switch_to_frame(frame)?;
+            .execute_with_session(move |session| {
+                session.switch_to_frame(&frame)?;
                 Ok(json!({
                     "frame": frame,
                     "action": "switched",
@@ -757,11 +1040,27 @@ impl Tool for BrowserCookiesTool {
                 },
                 "domain": {
                     "type": "string",
-                    "description": "Cookie domain (for set, optional)"
+                    "description": "Cookie domain (for set/delete, optional)"
                 },
                 "path": {
                     "type": "string",
-                    "description": "Cookie path (for set, optional)"
+                    "description": "Cookie path (for set/delete, optional)"
+                },
+                "secure": {
+                    "type": "boolean",
+                    "description": "Secure flag (for set, optional)"
+                },
+                "http_only": {
+                    "type": "boolean",
+                    "description": "HttpOnly flag (for set, optional)"
+                },
+                "same_site": {
+                    "type": "string",
+                    "description": "SameSite: 'strict', 'lax', or 'none' (for set, optional)"
+                },
+                "expires": {
+                    "type": "number",
+                    "description": "Expiry in seconds since the Unix epoch (for set, optional; session cookie if omitted)"
                 }
             },
             "required": ["action"]
@@ -776,7 +1075,7 @@ impl Tool for BrowserCookiesTool {
         let result = match action {
             "get" => self
                 .manager
-                .execute_with_session(|session| {
+                .execute_with_session(move |session| {
                     let cookies = session.get_cookies()?;
                     Ok(json!({
                         "cookies": cookies,
@@ -794,12 +1093,13 @@ impl Tool for BrowserCookiesTool {
                 let value = input["value"]
                     .as_str()
                     .ok_or_else(|| cc_core::Error::ToolExecution("Missing value parameter".to_string()))?;
-                let domain = input["domain"].as_str();
-                let path = input["path"].as_str();
+                let (name, value) = (name.to_string(), value.to_string());
+                let options: CookieOptions = serde_json::from_value(input.clone())
+                    .map_err(|e| cc_core::Error::ToolExecution(format!("Invalid cookie options: {}", e)))?;
 
                 self.manager
-                    .execute_with_session(|session| {
-                        session.set_cookie(name, value, domain, path)?;
+                    .execute_with_session(move |session| {
+                        session.set_cookie(&name, &value, &options)?;
                         Ok(json!({
                             "action": "set",
                             "name": name,
@@ -811,13 +1111,17 @@ impl Tool for BrowserCookiesTool {
             }
 
             "delete" => {
-                let name = input["name"].as_str().ok_or_else(|| {
-                    cc_core::Error::ToolExecution("Missing name parameter".to_string())
-                })?;
+                let name = input["name"]
+                    .as_str()
+                    .ok_or_else(|| cc_core::Error::ToolExecution("Missing name parameter".to_string()))?
+                    .to_string();
+
+                let domain = input["domain"].as_str().map(str::to_string);
+                let path = input["path"].as_str().map(str::to_string);
 
                 self.manager
-                    .execute_with_session(|session| {
-                        session.delete_cookie(name)?;
+                    .execute_with_session(move |session| {
+                        session.delete_cookie(&name, domain.as_deref(), path.as_deref())?;
                         Ok(json!({
                             "action": "delete",
                             "name": name,
@@ -830,7 +1134,7 @@ impl Tool for BrowserCookiesTool {
 
             "clear" => self
                 .manager
-                .execute_with_session(|session| {
+                .execute_with_session(move |session| {
                     session.clear_cookies()?;
                     Ok(json!({
                         "action": "clear",
@@ -851,12 +1155,12 @@ impl Tool for BrowserCookiesTool {
     }
 }
 
-/// Browser download tool
-pub struct BrowserDownloadTool {
+/// Browser web storage tool
+pub struct BrowserStorageTool {
     manager: BrowserManager,
 }
 
-impl BrowserDownloadTool {
+impl BrowserStorageTool {
     pub fn new(manager: BrowserManager) -> Self {
         Self { manager }
     }
@@ -867,13 +1171,13 @@ impl BrowserDownloadTool {
 }
 
 #[async_trait]
-impl Tool for BrowserDownloadTool {
+impl Tool for BrowserStorageTool {
     fn name(&self) -> &str {
-        "browser_download"
+        "browser_storage"
     }
 
     fn description(&self) -> &str {
-        "Manage downloads or trigger download from a link"
+        "Get, set, remove, list, or clear localStorage/sessionStorage values of the current page"
     }
 
     fn input_schema(&self) -> Value {
@@ -882,19 +1186,19 @@ impl Tool for BrowserDownloadTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "description": "Action: 'set_path', 'download', or 'wait'"
+                    "description": "Action: 'get', 'set', 'remove', 'list', or 'clear'"
                 },
-                "path": {
+                "storage": {
                     "type": "string",
-                    "description": "Download directory path"
+                    "description": "Storage area: 'local' (default) or 'session'"
                 },
-                "selector": {
+                "key": {
                     "type": "string",
-                    "description": "CSS selector for download link (for download action)"
+                    "description": "Item key (for get/set/remove)"
                 },
-                "timeout": {
-                    "type": "integer",
-                    "description": "Timeout in seconds (for wait action)"
+                "value": {
+                    "type": "string",
+                    "description": "Item value (for set)"
                 }
             },
             "required": ["action"]
@@ -905,67 +1209,96 @@ impl Tool for BrowserDownloadTool {
         let action = input["action"].as_str().ok_or_else(|| {
             cc_core::Error::ToolExecution("Missing action parameter".to_string())
         })?;
+        let kind = match StorageKind::parse(input["storage"].as_str().unwrap_or("local")) {
+            Ok(kind) => kind,
+            Err(e) => return Ok(ToolResult::error(e.to_string())),
+        };
+        let key = input["key"].as_str();
+        let require_key = || {
+            key.map(str::to_string)
+                .ok_or_else(|| cc_core::Error::ToolExecution("Missing key parameter".to_string()))
+        };
 
         let result = match action {
-            "set_path" => {
-                let path = input["path"].as_str().ok_or_else(|| {
-                    cc_core::Error::ToolExecution("Missing path parameter".to_string())
-                })?;
-
+            "get" => {
+                let key = require_key()?;
                 self.manager
-                    .execute_with_session(|session| {
-                        session.set_download_path(path)?;
+                    .execute_with_session(move |session| {
+                        let value = session.storage_get(kind, &key)?;
                         Ok(json!({
-                            "action": "set_path",
-                            "path": path,
+                            "key": key,
+                            "value": value,
+                            "found": value.is_some(),
                             "status": "success"
                         }))
                     })
                     .await
-                    .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?
             }
 
-            "download" => {
-                let selector = input["selector"].as_str().ok_or_else(|| {
-                    cc_core::Error::ToolExecution("Missing selector parameter".to_string())
-                })?;
-                let path = input["path"].as_str().unwrap_or("/tmp");
+            "set" => {
+                let key = require_key()?;
+                let value = input["value"]
+                    .as_str()
+                    .ok_or_else(|| cc_core::Error::ToolExecution("Missing value parameter".to_string()))?
+                    .to_string();
+                self.manager
+                    .execute_with_session(move |session| {
+                        session.storage_set(kind, &key, &value)?;
+                        Ok(json!({
+                            "action": "set",
+                            "key": key,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
 
+            "remove" => {
+                let key = require_key()?;
                 self.manager
-                    .execute_with_session(|session| {
-                        let filename = session.download_by_selector(selector, path)?;
+                    .execute_with_session(move |session| {
+                        session.storage_remove(kind, &key)?;
                         Ok(json!({
-                            "action": "download",
-                            "filename": filename,
+                            "action": "remove",
+                            "key": key,
                             "status": "success"
                         }))
                     })
                     .await
-                    .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?
             }
 
-            "wait" => {
-                let timeout = input["timeout"].as_u64().unwrap_or(30);
+            "list" => {
+                self.manager
+                    .execute_with_session(move |session| {
+                        let items = session.storage_list(kind)?;
+                        Ok(json!({
+                            "count": items.len(),
+                            "items": items,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
 
+            "clear" => {
                 self.manager
-                    .execute_with_session(|session| {
-                        let files = session.wait_for_download(timeout)?;
+                    .execute_with_session(move |session| {
+                        session.storage_clear(kind)?;
                         Ok(json!({
-                            "action": "wait",
-                            "files": files,
+                            "action": "clear",
                             "status": "success"
                         }))
                     })
                     .await
-                    .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?
             }
 
             _ => {
                 return Ok(ToolResult::error(
-                    "Invalid action. Use: set_path, download, or wait",
-                ))
+                    "Invalid action. Use: get, set, remove, list, or clear",
+                ));
             }
-        };
+        }
+        .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
 
         Ok(ToolResult::success(
             serde_json::to_string(&result).unwrap_or_default(),
@@ -973,12 +1306,12 @@ impl Tool for BrowserDownloadTool {
     }
 }
 
-/// Browser navigation tools (back, forward, refresh, url)
-pub struct BrowserNavigationTool {
+/// Browser download tool
+pub struct BrowserDownloadTool {
     manager: BrowserManager,
 }
 
-impl BrowserNavigationTool {
+impl BrowserDownloadTool {
     pub fn new(manager: BrowserManager) -> Self {
         Self { manager }
     }
@@ -989,13 +1322,13 @@ impl BrowserNavigationTool {
 }
 
 #[async_trait]
-impl Tool for BrowserNavigationTool {
+impl Tool for BrowserDownloadTool {
     fn name(&self) -> &str {
-        "browser_navigation"
+        "browser_download"
     }
 
     fn description(&self) -> &str {
-        "Navigate back, forward, refresh, or get current URL"
+        "Download files: click a download link and get the saved file paths, wait for downloads, list them, or change the download directory"
     }
 
     fn input_schema(&self) -> Value {
@@ -1004,7 +1337,497 @@ impl Tool for BrowserNavigationTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "description": "Action: 'back', 'forward', 'refresh', or 'url'"
+                    "description": "Action: 'download' (click selector and wait), 'wait', 'list', or 'set_path'"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Download directory (for set_path, or download into it)"
+                },
+                "selector": {
+                    "type": "string",
+                    "description": "CSS selector of the link or button that starts the download (for download action)"
+                },
+                "timeout": {
+                    "type": "integer",
+                    "description": "Seconds to wait for the download to finish (default: 30)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let action = input["action"].as_str().ok_or_else(|| {
+            cc_core::Error::ToolExecution("Missing action parameter".to_string())
+        })?;
+        let timeout = input["timeout"].as_u64().unwrap_or(30);
+
+        let result = match action {
+            "set_path" => {
+                let path = input["path"]
+                    .as_str()
+                    .ok_or_else(|| cc_core::Error::ToolExecution("Missing path parameter".to_string()))?
+                    .to_string();
+
+                self.manager
+                    .execute_with_session(move |session| {
+                        session.set_download_path(&path)?;
+                        Ok(json!({
+                            "action": "set_path",
+                            "path": session.download_dir(),
+                            "status": "success"
+                        }))
+                    })
+                    .await
+                    .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?
+            }
+
+            "download" => {
+                let selector = input["selector"]
+                    .as_str()
+                    .ok_or_else(|| cc_core::Error::ToolExecution("Missing selector parameter".to_string()))?
+                    .to_string();
+                let path = input["path"].as_str().map(str::to_string);
+
+                self.manager
+                    .execute_with_session(move |session| {
+                        if let Some(path) = &path {
+                            session.set_download_path(path)?;
+                        }
+                        let downloads = session.download_by_selector(&selector, timeout)?;
+                        Ok(json!({
+                            "action": "download",
+                            "files": downloaded_files(&downloads),
+                            "downloads": downloads,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+                    .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?
+            }
+
+            "wait" => self
+                .manager
+                .execute_with_session(move |session| {
+                    let downloads = session.wait_for_download(timeout)?;
+                    Ok(json!({
+                        "action": "wait",
+                        "files": downloaded_files(&downloads),
+                        "downloads": downloads,
+                        "status": "success"
+                    }))
+                })
+                .await
+                .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?,
+
+            "list" => self
+                .manager
+                .execute_with_session(move |session| {
+                    let downloads = session.downloads();
+                    Ok(json!({
+                        "action": "list",
+                        "download_dir": session.download_dir(),
+                        "count": downloads.len(),
+                        "downloads": downloads,
+                        "status": "success"
+                    }))
+                })
+                .await
+                .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?,
+
+            _ => {
+                return Ok(ToolResult::error(
+                    "Invalid action. Use: download, wait, list, or set_path",
+                ))
+            }
+        };
+
+        Ok(ToolResult::success(
+            serde_json::to_string(&result).unwrap_or_default(),
+        ))
+    }
+}
+
+/// Paths of the completed downloads
+fn downloaded_files(downloads: &[DownloadEntry]) -> Vec<&std::path::Path> {
+    downloads.iter().filter_map(|d| d.path.as_deref()).collect()
+}
+
+/// Browser tab management tool
+pub struct BrowserTabTool {
+    manager: BrowserManager,
+}
+
+impl BrowserTabTool {
+    pub fn new(manager: BrowserManager) -> Self {
+        Self { manager }
+    }
+
+    pub fn with_defaults() -> Self {
+        Self::new(BrowserManager::new())
+    }
+}
+
+#[async_trait]
+impl Tool for BrowserTabTool {
+    fn name(&self) -> &str {
+        "browser_tab"
+    }
+
+    fn description(&self) -> &str {
+        "Open, list, switch, or close browser tabs. Other browser tools act on the selected tab"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "description": "Action: 'open', 'list', 'switch', or 'close'"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "URL to load in the new tab (for open, optional)"
+                },
+                "tab": {
+                    "type": "string",
+                    "description": "Tab ID or index from 'list' (for switch/close; close defaults to the selected tab)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let action = input["action"].as_str().ok_or_else(|| {
+            cc_core::Error::ToolExecution("Missing action parameter".to_string())
+        })?;
+        let tab = input["tab"].as_str().map(str::to_string);
+
+        debug!("browser_tab: {} {:?}", action, tab);
+
+        let result = match action {
+            "open" => {
+                let url = input["url"].as_str().map(str::to_string);
+                self.manager
+                    .execute_with_session(move |session| {
+                        let tab = session.open_tab(url.as_deref())?;
+                        Ok(json!({ "action": "open", "tab": tab, "status": "success" }))
+                    })
+                    .await
+            }
+
+            "list" => {
+                self.manager
+                    .execute_with_session(move |session| {
+                        let tabs = session.list_tabs()?;
+                        Ok(json!({
+                            "action": "list",
+                            "count": tabs.len(),
+                            "tabs": tabs,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
+
+            "switch" => {
+                let tab = tab.ok_or_else(|| {
+                    cc_core::Error::ToolExecution("Missing tab parameter".to_string())
+                })?;
+                self.manager
+                    .execute_with_session(move |session| {
+                        let tab = session.switch_tab(&tab)?;
+                        Ok(json!({ "action": "switch", "tab": tab, "status": "success" }))
+                    })
+                    .await
+            }
+
+            "close" => {
+                self.manager
+                    .execute_with_session(move |session| {
+                        let tabs = session.list_tabs()?;
+                        let target = tabs
+                            .iter()
+                            .find(|t| match &tab {
+                                Some(tab) => t.id == *tab || t.index.to_string() == *tab,
+                                None => t.active,
+                            })
+                            .ok_or_else(|| {
+                                BrowserError::TabError(format!(
+                                    "Tab '{}' not found",
+                                    tab.unwrap_or_default()
+                                ))
+                            })?;
+                        session.close_tab(&target.id)?;
+                        Ok(json!({ "action": "close", "id": target.id, "status": "success" }))
+                    })
+                    .await
+            }
+
+            _ => {
+                return Ok(ToolResult::error(
+                    "Invalid action. Use: open, list, switch, or close",
+                ))
+            }
+        }
+        .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(
+            serde_json::to_string(&result).unwrap_or_default(),
+        ))
+    }
+}
+
+/// Browser network tool (request recording, URL blocking, HAR export)
+pub struct BrowserNetworkTool {
+    manager: BrowserManager,
+}
+
+impl BrowserNetworkTool {
+    pub fn new(manager: BrowserManager) -> Self {
+        Self { manager }
+    }
+
+    pub fn with_defaults() -> Self {
+        Self::new(BrowserManager::new())
+    }
+}
+
+/// Maximum requests returned by `list` unless a limit is given
+const DEFAULT_NETWORK_LIST_LIMIT: usize = 100;
+
+#[async_trait]
+impl Tool for BrowserNetworkTool {
+    fn name(&self) -> &str {
+        "browser_network"
+    }
+
+    fn description(&self) -> &str {
+        "Record network requests of the current tab (URL, status, timing, bodies), block URL patterns, or export a HAR file"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "description": "Action: 'start', 'stop', 'list', 'body', 'block', 'clear', or 'har'"
+                },
+                "filter": {
+                    "type": "string",
+                    "description": "Only requests whose URL contains this text (for list)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of requests, newest first (for list, default: 100)"
+                },
+                "request_id": {
+                    "type": "string",
+                    "description": "Request ID from 'list' (for body)"
+                },
+                "patterns": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "URL patterns to block, '*' is a wildcard (for block; empty list unblocks)"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "File to write the HAR to (for har, optional; returns it inline if omitted)"
+                },
+                "include_bodies": {
+                    "type": "boolean",
+                    "description": "Embed text response bodies in the HAR (default: false)",
+                    "default": false
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let action = input["action"].as_str().ok_or_else(|| {
+            cc_core::Error::ToolExecution("Missing action parameter".to_string())
+        })?;
+
+        debug!("browser_network: {}", action);
+
+        let result = match action {
+            "start" => {
+                self.manager
+                    .execute_with_session(move |session| {
+                        session.start_network_capture()?;
+                        Ok(json!({ "action": "start", "status": "success" }))
+                    })
+                    .await
+            }
+
+            "stop" => {
+                self.manager
+                    .execute_with_session(move |session| {
+                        session.stop_network_capture()?;
+                        Ok(json!({
+                            "action": "stop",
+                            "recorded": session.network_requests(None).len(),
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
+
+            "list" => {
+                let filter = input["filter"].as_str().map(str::to_string);
+                let limit = input["limit"]
+                    .as_u64()
+                    .map_or(DEFAULT_NETWORK_LIST_LIMIT, |l| l as usize);
+                self.manager
+                    .execute_with_session(move |session| {
+                        let mut requests = session.network_requests(filter.as_deref());
+                        let total = requests.len();
+                        requests.reverse();
+                        requests.truncate(limit);
+                        Ok(json!({
+                            "action": "list",
+                            "capturing": session.is_capturing_network(),
+                            "total": total,
+                            "requests": requests,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
+
+            "body" => {
+                let request_id = input["request_id"]
+                    .as_str()
+                    .ok_or_else(|| {
+                        cc_core::Error::ToolExecution("Missing request_id parameter".to_string())
+                    })?
+                    .to_string();
+                self.manager
+                    .execute_with_session(move |session| {
+                        let (body, base64_encoded) = session.response_body(&request_id)?;
+                        Ok(json!({
+                            "action": "body",
+                            "request_id": request_id,
+                            "body": body,
+                            "base64_encoded": base64_encoded,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
+
+            "block" => {
+                let patterns: Vec<String> = input["patterns"]
+                    .as_array()
+                    .map(|p| {
+                        p.iter()
+                            .filter_map(|v| v.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                self.manager
+                    .execute_with_session(move |session| {
+                        session.block_urls(&patterns)?;
+                        Ok(json!({
+                            "action": "block",
+                            "patterns": patterns,
+                            "status": "success"
+                        }))
+                    })
+                    .await
+            }
+
+            "clear" => {
+                self.manager
+                    .execute_with_session(move |session| {
+                        session.clear_network_requests();
+                        Ok(json!({ "action": "clear", "status": "success" }))
+                    })
+                    .await
+            }
+
+            "har" => {
+                let include_bodies = input["include_bodies"].as_bool().unwrap_or(false);
+                let har = self
+                    .manager
+                    .execute_with_session(move |session| session.export_har(include_bodies))
+                    .await
+                    .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+                let entries = har["log"]["entries"].as_array().map_or(0, Vec::len);
+
+                match input["path"].as_str() {
+                    Some(path) => {
+                        if let Some(parent) = std::path::Path::new(path).parent() {
+                            tokio::fs::create_dir_all(parent).await?;
+                        }
+                        tokio::fs::write(path, serde_json::to_vec_pretty(&har)?).await?;
+                        info!("Saved HAR to {} ({} entries)", path, entries);
+                        Ok(json!({
+                            "action": "har",
+                            "path": path,
+                            "entries": entries,
+                            "status": "success"
+                        }))
+                    }
+                    None => Ok(json!({
+                        "action": "har",
+                        "har": har,
+                        "entries": entries,
+                        "status": "success"
+                    })),
+                }
+            }
+
+            _ => {
+                return Ok(ToolResult::error(
+                    "Invalid action. Use: start, stop, list, body, block, clear, or har",
+                ))
+            }
+        }
+        .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(
+            serde_json::to_string(&result).unwrap_or_default(),
+        ))
+    }
+}
+
+/// Browser navigation tools (back, forward, refresh, url)
+pub struct BrowserNavigationTool {
+    manager: BrowserManager,
+}
+
+impl BrowserNavigationTool {
+    pub fn new(manager: BrowserManager) -> Self {
+        Self { manager }
+    }
+
+    pub fn with_defaults() -> Self {
+        Self::new(BrowserManager::new())
+    }
+}
+
+#[async_trait]
+impl Tool for BrowserNavigationTool {
+    fn name(&self) -> &str {
+        "browser_navigation"
+    }
+
+    fn description(&self) -> &str {
+        "Navigate back, forward, refresh, or get current URL"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "description": "Action: 'back', 'forward', 'refresh', or 'url'"
                 }
             },
             "required": ["action"]
@@ -1019,7 +1842,7 @@ impl Tool for BrowserNavigationTool {
         let result = match action {
             "back" => self
                 .manager
-                .execute_with_session(|session| {
+                .execute_with_session(move |session| {
                     session.back()?;
                     Ok(json!({ "action": "back", "status": "success" }))
                 })
@@ -1028,7 +1851,7 @@ impl Tool for BrowserNavigationTool {
 
             "forward" => self
                 .manager
-                .execute_with_session(|session| {
+                .execute_with_session(move |session| {
                     session.forward()?;
                     Ok(json!({ "action": "forward", "status": "success" }))
                 })
@@ -1037,7 +1860,7 @@ impl Tool for BrowserNavigationTool {
 
             "refresh" => self
                 .manager
-                .execute_with_session(|session| {
+                .execute_with_session(move |session| {
                     session.refresh()?;
                     Ok(json!({ "action": "refresh", "status": "success" }))
                 })
@@ -1046,7 +1869,7 @@ impl Tool for BrowserNavigationTool {
 
             "url" => self
                 .manager
-                .execute_with_session(|session| {
+                .execute_with_session(move |session| {
                     let url = session.get_current_url()?;
                     Ok(json!({ "action": "url", "url": url, "status": "success" }))
                 })
@@ -1068,7 +1891,20 @@ impl Tool for BrowserNavigationTool {
 
 /// Register browser tools with a tool manager using a shared browser session
 pub fn register_browser_tools(manager: &mut cc_core::ToolManager) {
-    let browser_manager = BrowserManager::new();
+    register_browser_tools_with_config(manager, BrowserConfig::default());
+}
+
+/// Register all browser tools sharing a browser launched with `config`
+///
+/// Use [`BrowserConfig::profile`] to keep logins across gateway restarts.
+pub fn register_browser_tools_with_config(
+    manager: &mut cc_core::ToolManager,
+    config: BrowserConfig,
+) {
+    let browser_manager = BrowserManager::with_config(config);
+    if tokio::runtime::Handle::try_current().is_ok() {
+        browser_manager.start_idle_eviction();
+    }
 
     manager.register(Arc::new(BrowserNavigateTool::new(browser_manager.clone())));
     manager.register(Arc::new(BrowserClickTool::new(browser_manager.clone())));
@@ -1076,7 +1912,9 @@ pub fn register_browser_tools(manager: &mut cc_core::ToolManager) {
     manager.register(Arc::new(BrowserScreenshotTool::new(
         browser_manager.clone(),
     )));
+    manager.register(Arc::new(BrowserPdfTool::new(browser_manager.clone())));
     manager.register(Arc::new(BrowserExtractTool::new(browser_manager.clone())));
+    manager.register(Arc::new(BrowserSnapshotTool::new(browser_manager.clone())));
     manager.register(Arc::new(BrowserEvaluateTool::new(browser_manager.clone())));
     manager.register(Arc::new(BrowserWaitTool::new(browser_manager.clone())));
     manager.register(Arc::new(BrowserFramesTool::new(browser_manager.clone())));
@@ -1084,10 +1922,13 @@ pub fn register_browser_tools(manager: &mut cc_core::ToolManager) {
         browser_manager.clone(),
     )));
     manager.register(Arc::new(BrowserCookiesTool::new(browser_manager.clone())));
+    manager.register(Arc::new(BrowserStorageTool::new(browser_manager.clone())));
     manager.register(Arc::new(BrowserDownloadTool::new(browser_manager.clone())));
+    manager.register(Arc::new(BrowserTabTool::new(browser_manager.clone())));
+    manager.register(Arc::new(BrowserNetworkTool::new(browser_manager.clone())));
     manager.register(Arc::new(BrowserNavigationTool::new(browser_manager)));
 
-    info!("Registered 12 browser automation tools");
+    info!("Registered 17 browser automation tools");
 }
 
 #[cfg(test)]
@@ -1119,6 +1960,20 @@ mod tests {
             BrowserEvaluateTool::new(manager.clone()).name(),
             "browser_evaluate"
         );
+        assert_eq!(BrowserPdfTool::new(manager.clone()).name(), "browser_pdf");
+        assert_eq!(BrowserTabTool::new(manager.clone()).name(), "browser_tab");
+        assert_eq!(
+            BrowserNetworkTool::new(manager.clone()).name(),
+            "browser_network"
+        );
+        assert_eq!(
+            BrowserSnapshotTool::new(manager.clone()).name(),
+            "browser_snapshot"
+        );
+        assert_eq!(
+            BrowserStorageTool::new(manager.clone()).name(),
+            "browser_storage"
+        );
         assert_eq!(BrowserWaitTool::new(manager).name(), "browser_wait");
     }
 
@@ -0,0 +1,675 @@
+//! WebDriver backend
+//!
+//! Drives a browser through a W3C WebDriver server (chromedriver,
+//! geckodriver, Selenium). Unlike the CDP backend it switches into iframes
+//! for real, finds elements inside shadow roots (`host >>> inner` selectors)
+//! and can emulate mobile devices through chromedriver.
+
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use headless_chrome::browser::tab::ModifierKey;
+use reqwest::Method;
+use serde_json::{Value, json};
+use tokio::runtime::Runtime;
+use tracing::{debug, info, warn};
+
+use crate::error::{BrowserError, Result};
+use crate::session::{BrowserConfig, CookieInfo, CookieOptions, PdfOptions, parse_key_combo};
+
+/// Key of an element reference in WebDriver JSON
+const ELEMENT_KEY: &str = "element-6066-11e4-a07c-4a5d4d5b5b5b";
+
+/// Key of a shadow root reference in WebDriver JSON
+const SHADOW_KEY: &str = "shadow-6066-11e4-a07c-4a5d4d5b5b5b";
+
+/// Separator for selectors that pierce shadow roots (`my-app >>> button`)
+pub const SHADOW_SEPARATOR: &str = ">>>";
+
+/// Interval between element lookups while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runtime for WebDriver HTTP calls
+///
+/// Session methods are synchronous like the CDP backend, so requests are
+/// driven on a dedicated runtime that lives for the whole process.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("cc-browser-webdriver")
+            .enable_all()
+            .build()
+            .expect("Failed to build WebDriver runtime")
+    })
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
+
+/// A session on a WebDriver server
+pub struct WebDriverSession {
+    client: reqwest::Client,
+    /// `<server>/session/<id>`
+    base: String,
+    element_timeout: Duration,
+}
+
+impl WebDriverSession {
+    /// Start a new session on the WebDriver server at `server_url`
+    pub fn connect(server_url: &str, browser: &str, config: &BrowserConfig) -> Result<Self> {
+        let server = server_url.trim_end_matches('/').to_string();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.navigation_timeout.max(30) + 30))
+            .build()
+            .map_err(|e| BrowserError::Initialization(format!("Failed to build HTTP client: {}", e)))?;
+
+        let body = json!({ "capabilities": { "alwaysMatch": capabilities(browser, config)? } });
+        let value = request(&client, Method::POST, &format!("{}/session", server), Some(body))
+            .map_err(|e| {
+                BrowserError::Initialization(format!(
+                    "Failed to start WebDriver session at {}: {}",
+                    server, e
+                ))
+            })?;
+        let session_id = value["sessionId"].as_str().ok_or_else(|| {
+            BrowserError::Initialization("WebDriver server returned no session ID".to_string())
+        })?;
+
+        info!("WebDriver session {} started at {}", session_id, server);
+
+        let session = Self {
+            client,
+            base: format!("{}/session/{}", server, session_id),
+            element_timeout: Duration::from_secs(config.element_timeout),
+        };
+        session.command(
+            Method::POST,
+            "/timeouts",
+            Some(json!({ "pageLoad": config.navigation_timeout * 1000 })),
+        )?;
+        Ok(session)
+    }
+
+    /// Send a command to this session and return its `value`
+    fn command(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let url = format!("{}{}", self.base, path);
+        request(&self.client, method, &url, body)
+    }
+
+    /// Whether the browser still answers
+    pub fn is_alive(&self) -> bool {
+        self.command(Method::GET, "/url", None).is_ok()
+    }
+
+    // ==================== Elements ====================
+
+    /// Find an element, waiting up to `timeout` for it to appear
+    ///
+    /// `host >>> inner` looks up `inner` inside the shadow root of `host`.
+    pub fn find(&self, selector: &str, timeout: Option<Duration>) -> Result<String> {
+        let deadline = Instant::now() + timeout.unwrap_or(self.element_timeout);
+        loop {
+            if let Some(element) = self.find_once(selector)? {
+                return Ok(element);
+            }
+            if Instant::now() >= deadline {
+                return Err(BrowserError::ElementNotFound(format!(
+                    "Element '{}' not found",
+                    selector
+                )));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn find_once(&self, selector: &str) -> Result<Option<String>> {
+        let mut scope = String::new();
+        let mut element = None;
+        for (i, part) in selector.split(SHADOW_SEPARATOR).map(str::trim).enumerate() {
+            if i > 0 {
+                let host: &String = match &element {
+                    Some(host) => host,
+                    None => return Ok(None),
+                };
+                let shadow = match self.command(Method::GET, &format!("/element/{}/shadow", host), None) {
+                    Ok(shadow) => shadow,
+                    Err(BrowserError::ElementNotFound(_)) => return Ok(None),
+                    Err(e) => return Err(e),
+                };
+                let shadow_id = shadow[SHADOW_KEY].as_str().unwrap_or_default();
+                scope = format!("/shadow/{}", shadow_id);
+            }
+
+            let found = self.command(
+                Method::POST,
+                &format!("{}/element", scope),
+                Some(json!({ "using": "css selector", "value": part })),
+            );
+            element = match found {
+                Ok(value) => value[ELEMENT_KEY].as_str().map(str::to_string),
+                Err(BrowserError::ElementNotFound(_)) => return Ok(None),
+                Err(e) => return Err(e),
+            };
+        }
+        Ok(element)
+    }
+
+    /// Run `function` (written as `function(...) { ... this ... }`) on an element
+    pub fn call_on_element(&self, selector: &str, function: &str, args: Vec<Value>) -> Result<Value> {
+        let element = self.find(selector, None)?;
+        let mut script_args = vec![element_ref(&element)];
+        script_args.extend(args);
+        self.execute(
+            &format!(
+                "return ({}).apply(arguments[0], Array.prototype.slice.call(arguments, 1));",
+                function
+            ),
+            script_args,
+        )
+    }
+
+    pub fn click(&self, selector: &str) -> Result<()> {
+        let element = self.find(selector, None)?;
+        self.command(Method::POST, &format!("/element/{}/click", element), Some(json!({})))?;
+        Ok(())
+    }
+
+    pub fn type_text(&self, selector: &str, text: &str, clear_first: bool) -> Result<()> {
+        let element = self.find(selector, None)?;
+        if clear_first {
+            self.command(Method::POST, &format!("/element/{}/clear", element), Some(json!({})))?;
+        }
+        if !text.is_empty() {
+            self.command(
+                Method::POST,
+                &format!("/element/{}/value", element),
+                Some(json!({ "text": text })),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Press a key combination (see [`parse_key_combo`])
+    pub fn press_keys(&self, selector: Option<&str>, combo: &str) -> Result<()> {
+        if let Some(selector) = selector {
+            self.call_on_element(selector, "function() { this.focus(); }", vec![])?;
+        }
+
+        let (key, modifiers) = parse_key_combo(combo)?;
+        let key = key_code(&key).ok_or_else(|| {
+            BrowserError::InvalidInput(format!("Unknown key '{}' in '{}'", key, combo))
+        })?;
+        let modifiers: Vec<&str> = modifiers.iter().map(modifier_code).collect();
+
+        let mut actions = Vec::new();
+        for modifier in &modifiers {
+            actions.push(json!({ "type": "keyDown", "value": modifier }));
+        }
+        actions.push(json!({ "type": "keyDown", "value": key }));
+        actions.push(json!({ "type": "keyUp", "value": key }));
+        for modifier in modifiers.iter().rev() {
+            actions.push(json!({ "type": "keyUp", "value": modifier }));
+        }
+
+        self.command(
+            Method::POST,
+            "/actions",
+            Some(json!({
+                "actions": [{ "type": "key", "id": "keyboard", "actions": actions }]
+            })),
+        )?;
+        self.command(Method::DELETE, "/actions", None)?;
+        Ok(())
+    }
+
+    pub fn text(&self, selector: &str) -> Result<String> {
+        let element = self.find(selector, None)?;
+        let value = self.command(Method::GET, &format!("/element/{}/text", element), None)?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    // ==================== Page ====================
+
+    /// Navigate and return the page title
+    pub fn navigate(&self, url: &str) -> Result<String> {
+        self.command(Method::POST, "/url", Some(json!({ "url": url })))
+            .map_err(|e| BrowserError::Navigation(format!("Failed to navigate to {}: {}", url, e)))?;
+        self.title()
+    }
+
+    pub fn title(&self) -> Result<String> {
+        let value = self.command(Method::GET, "/title", None)?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    pub fn url(&self) -> Result<String> {
+        let value = self.command(Method::GET, "/url", None)?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    pub fn source(&self) -> Result<String> {
+        let value = self.command(Method::GET, "/source", None)?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    pub fn back(&self) -> Result<()> {
+        self.command(Method::POST, "/back", Some(json!({})))?;
+        Ok(())
+    }
+
+    pub fn forward(&self) -> Result<()> {
+        self.command(Method::POST, "/forward", Some(json!({})))?;
+        Ok(())
+    }
+
+    pub fn refresh(&self) -> Result<()> {
+        self.command(Method::POST, "/refresh", Some(json!({})))?;
+        Ok(())
+    }
+
+    /// Run a script body (use `return` for a result) with `arguments`
+    pub fn execute(&self, script: &str, args: Vec<Value>) -> Result<Value> {
+        self.command(
+            Method::POST,
+            "/execute/sync",
+            Some(json!({ "script": script, "args": args })),
+        )
+    }
+
+    /// Evaluate a JavaScript expression (or statements) like CDP `Runtime.evaluate`
+    pub fn evaluate(&self, expression: &str) -> Result<Value> {
+        self.execute("return eval(arguments[0]);", vec![json!(expression)])
+    }
+
+    /// PNG screenshot of the viewport
+    pub fn screenshot(&self) -> Result<Vec<u8>> {
+        let value = self.command(Method::GET, "/screenshot", None)?;
+        decode_base64(&value, "screenshot")
+    }
+
+    /// PNG screenshot of one element
+    pub fn element_screenshot(&self, selector: &str) -> Result<Vec<u8>> {
+        let element = self.find(selector, None)?;
+        let value = self.command(Method::GET, &format!("/element/{}/screenshot", element), None)?;
+        decode_base64(&value, "screenshot")
+    }
+
+    pub fn print_pdf(&self, options: &PdfOptions) -> Result<Vec<u8>> {
+        let value = self
+            .command(Method::POST, "/print", Some(print_parameters(options)))
+            .map_err(|e| BrowserError::Pdf(format!("Failed to print to PDF: {}", e)))?;
+        decode_base64(&value, "PDF")
+    }
+
+    // ==================== Windows and frames ====================
+
+    pub fn window_handles(&self) -> Result<Vec<String>> {
+        let value = self.command(Method::GET, "/window/handles", None)?;
+        Ok(value
+            .as_array()
+            .map(|handles| {
+                handles
+                    .iter()
+                    .filter_map(|h| h.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    pub fn window_handle(&self) -> Result<String> {
+        let value = self.command(Method::GET, "/window", None)?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Open a new tab and return its handle (without switching to it)
+    pub fn new_window(&self) -> Result<String> {
+        let value = self.command(Method::POST, "/window/new", Some(json!({ "type": "tab" })))?;
+        value["handle"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| BrowserError::TabError("No handle for the new tab".to_string()))
+    }
+
+    pub fn switch_window(&self, handle: &str) -> Result<()> {
+        self.command(Method::POST, "/window", Some(json!({ "handle": handle })))?;
+        Ok(())
+    }
+
+    /// Close the current tab
+    pub fn close_window(&self) -> Result<()> {
+        self.command(Method::DELETE, "/window", None)?;
+        Ok(())
+    }
+
+    /// Switch into a frame by index, name or id
+    ///
+    /// `parent` goes one level up, `main` / `top` back to the page.
+    pub fn switch_frame(&self, frame: &str) -> Result<()> {
+        match frame {
+            "parent" => {
+                self.command(Method::POST, "/frame/parent", Some(json!({})))?;
+            }
+            "main" | "top" | "default" => {
+                self.command(Method::POST, "/frame", Some(json!({ "id": null })))?;
+            }
+            _ => {
+                let id = match frame.parse::<u64>() {
+                    Ok(index) => json!(index),
+                    Err(_) => {
+                        let quoted = Value::String(frame.to_string()).to_string();
+                        let selector = format!(
+                            "iframe[name={q}], iframe[id={q}], frame[name={q}], frame[id={q}]",
+                            q = quoted
+                        );
+                        element_ref(&self.find(&selector, None)?)
+                    }
+                };
+                self.command(Method::POST, "/frame", Some(json!({ "id": id })))
+                    .map_err(|e| BrowserError::Frame(format!("Frame '{}' not found: {}", frame, e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    // ==================== Cookies ====================
+
+    pub fn cookies(&self) -> Result<Vec<CookieInfo>> {
+        let value = self.command(Method::GET, "/cookie", None)?;
+        Ok(value
+            .as_array()
+            .map(|cookies| cookies.iter().map(cookie_info).collect())
+            .unwrap_or_default())
+    }
+
+    pub fn add_cookie(&self, name: &str, value: &str, options: &CookieOptions) -> Result<()> {
+        let mut cookie = json!({ "name": name, "value": value });
+        if let Some(ref domain) = options.domain {
+            cookie["domain"] = json!(domain);
+        }
+        if let Some(ref path) = options.path {
+            cookie["path"] = json!(path);
+        }
+        if let Some(secure) = options.secure {
+            cookie["secure"] = json!(secure);
+        }
+        if let Some(http_only) = options.http_only {
+            cookie["httpOnly"] = json!(http_only);
+        }
+        if let Some(ref same_site) = options.same_site {
+            let mut chars = same_site.chars();
+            let capitalized: String = chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + &chars.as_str().to_lowercase())
+                .unwrap_or_default();
+            cookie["sameSite"] = json!(capitalized);
+        }
+        if let Some(expires) = options.expires {
+            cookie["expiry"] = json!(expires as u64);
+        }
+        self.command(Method::POST, "/cookie", Some(json!({ "cookie": cookie })))
+            .map_err(|e| BrowserError::Cookie(format!("Failed to set cookie: {}", e)))?;
+        Ok(())
+    }
+
+    pub fn delete_cookie(&self, name: &str) -> Result<()> {
+        self.command(Method::DELETE, &format!("/cookie/{}", urlencode(name)), None)
+            .map_err(|e| BrowserError::Cookie(format!("Failed to delete cookie: {}", e)))?;
+        Ok(())
+    }
+
+    pub fn delete_all_cookies(&self) -> Result<()> {
+        self.command(Method::DELETE, "/cookie", None)
+            .map_err(|e| BrowserError::Cookie(format!("Failed to clear cookies: {}", e)))?;
+        Ok(())
+    }
+}
+
+impl Drop for WebDriverSession {
+    fn drop(&mut self) {
+        // Ending the session closes the browser; don't block while dropping
+        let request = self.client.delete(&self.base).send();
+        let base = self.base.clone();
+        runtime().spawn(async move {
+            if let Err(e) = request.await {
+                warn!("Failed to end WebDriver session {}: {}", base, e);
+            }
+        });
+    }
+}
+
+/// Send a WebDriver request and return the `value` of the response
+fn request(client: &reqwest::Client, method: Method, url: &str, body: Option<Value>) -> Result<Value> {
+    debug!("WebDriver {} {}", method, url);
+    let mut builder = client.request(method, url);
+    if let Some(body) = body {
+        builder = builder.json(&body);
+    }
+
+    let response: Value = block_on(async {
+        let response = builder.send().await?;
+        response.json::<Value>().await
+    })
+    .map_err(|e| BrowserError::Interaction(format!("WebDriver request failed: {}", e)))?;
+
+    let value = response.get("value").cloned().unwrap_or(Value::Null);
+    match value.get("error").and_then(Value::as_str) {
+        Some(code) => Err(map_error(code, value["message"].as_str().unwrap_or_default())),
+        None => Ok(value),
+    }
+}
+
+/// Map a WebDriver error code to a [`BrowserError`]
+fn map_error(code: &str, message: &str) -> BrowserError {
+    let message = format!("{}: {}", code, message);
+    match code {
+        "no such element" | "stale element reference" | "no such shadow root"
+        | "detached shadow root" => BrowserError::ElementNotFound(message),
+        "no such frame" => BrowserError::Frame(message),
+        "no such window" => BrowserError::TabError(message),
+        "timeout" | "script timeout" => BrowserError::Timeout(message),
+        "invalid argument" | "invalid selector" => BrowserError::InvalidInput(message),
+        "session not created" | "invalid session id" => BrowserError::Initialization(message),
+        "javascript error" => BrowserError::Interaction(message),
+        "unable to set cookie" | "no such cookie" => BrowserError::Cookie(message),
+        _ => BrowserError::Interaction(message),
+    }
+}
+
+/// Capabilities for a new session
+fn capabilities(browser: &str, config: &BrowserConfig) -> Result<Value> {
+    let mut chrome_args = vec![
+        format!("--window-size={},{}", config.width, config.height),
+        "--no-sandbox".to_string(),
+        "--disable-dev-shm-usage".to_string(),
+    ];
+    let mut firefox_args = vec![
+        format!("-width={}", config.width),
+        format!("-height={}", config.height),
+    ];
+    if config.headless {
+        chrome_args.push("--headless=new".to_string());
+        firefox_args.push("-headless".to_string());
+    }
+    if !config.enable_gpu {
+        chrome_args.push("--disable-gpu".to_string());
+    }
+    if let Some(ref ua) = config.user_agent {
+        chrome_args.push(format!("--user-agent={}", ua));
+    }
+    if let Some(dir) = config.resolve_user_data_dir()? {
+        chrome_args.push(format!("--user-data-dir={}", dir.display()));
+        firefox_args.extend(["-profile".to_string(), dir.display().to_string()]);
+    }
+
+    let mut chrome_options = json!({ "args": chrome_args });
+    if let Some(ref device) = config.device {
+        chrome_options["mobileEmulation"] = json!({ "deviceName": device });
+    }
+
+    Ok(json!({
+        "browserName": browser,
+        "goog:chromeOptions": chrome_options,
+        "moz:firefoxOptions": { "args": firefox_args },
+    }))
+}
+
+/// Parameters of the WebDriver Print command (sizes in centimeters)
+fn print_parameters(options: &PdfOptions) -> Value {
+    let mut params = json!({
+        "orientation": if options.landscape { "landscape" } else { "portrait" },
+        "background": options.print_background,
+    });
+    if let Some(scale) = options.scale {
+        params["scale"] = json!(scale);
+    }
+    if let (Some(width), Some(height)) = (options.paper_width, options.paper_height) {
+        params["page"] = json!({ "width": width * 2.54, "height": height * 2.54 });
+    }
+    if let Some(ref ranges) = options.page_ranges {
+        let ranges: Vec<&str> = ranges
+            .split(',')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .collect();
+        params["pageRanges"] = json!(ranges);
+    }
+    params
+}
+
+fn element_ref(element: &str) -> Value {
+    json!({ ELEMENT_KEY: element })
+}
+
+fn decode_base64(value: &Value, what: &str) -> Result<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(value.as_str().unwrap_or_default())
+        .map_err(|e| BrowserError::Interaction(format!("Invalid {} data: {}", what, e)))
+}
+
+fn cookie_info(cookie: &Value) -> CookieInfo {
+    let text = |key: &str| cookie[key].as_str().unwrap_or_default().to_string();
+    CookieInfo {
+        name: text("name"),
+        value: text("value"),
+        domain: text("domain"),
+        path: text("path"),
+        secure: cookie["secure"].as_bool().unwrap_or(false),
+        http_only: cookie["httpOnly"].as_bool().unwrap_or(false),
+        same_site: cookie["sameSite"].as_str().map(str::to_string),
+        expires: cookie["expiry"].as_f64(),
+    }
+}
+
+/// Percent-encode a path segment
+fn urlencode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn modifier_code(modifier: &ModifierKey) -> &'static str {
+    match modifier {
+        ModifierKey::Ctrl => "\u{E009}",
+        ModifierKey::Shift => "\u{E008}",
+        ModifierKey::Alt => "\u{E00A}",
+        ModifierKey::Meta => "\u{E03D}",
+    }
+}
+
+/// WebDriver code point of a key name (single characters are sent as is)
+fn key_code(key: &str) -> Option<String> {
+    if key.chars().count() == 1 {
+        return Some(key.to_string());
+    }
+    let code = match key {
+        "Enter" => '\u{E007}',
+        "Tab" => '\u{E004}',
+        "Backspace" => '\u{E003}',
+        "Escape" => '\u{E00C}',
+        "Space" => ' ',
+        "Delete" => '\u{E017}',
+        "Insert" => '\u{E016}',
+        "Home" => '\u{E011}',
+        "End" => '\u{E010}',
+        "PageUp" => '\u{E00E}',
+        "PageDown" => '\u{E00F}',
+        "ArrowLeft" => '\u{E012}',
+        "ArrowUp" => '\u{E013}',
+        "ArrowRight" => '\u{E014}',
+        "ArrowDown" => '\u{E015}',
+        _ => {
+            let n: u32 = key.strip_prefix('F')?.parse().ok()?;
+            if !(1..=12).contains(&n) {
+                return None;
+            }
+            char::from_u32(0xE031 + n - 1)?
+        }
+    };
+    Some(code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_codes() {
+        assert_eq!(key_code("a").as_deref(), Some("a"));
+        assert_eq!(key_code("Enter").as_deref(), Some("\u{E007}"));
+        assert_eq!(key_code("F5").as_deref(), Some("\u{E035}"));
+        assert_eq!(key_code("F13"), None);
+        assert_eq!(key_code("Hyper"), None);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let config = BrowserConfig::builder()
+            .window_size(800, 600)
+            .device("Pixel 7")
+            .build();
+        let caps = capabilities("chrome", &config).unwrap();
+        assert_eq!(caps["browserName"], "chrome");
+        let args = caps["goog:chromeOptions"]["args"].as_array().unwrap();
+        assert!(args.contains(&json!("--headless=new")));
+        assert!(args.contains(&json!("--window-size=800,600")));
+        assert_eq!(
+            caps["goog:chromeOptions"]["mobileEmulation"]["deviceName"],
+            "Pixel 7"
+        );
+    }
+
+    #[test]
+    fn test_print_parameters() {
+        let options = PdfOptions {
+            landscape: true,
+            page_ranges: Some("1-2, 5".to_string()),
+            ..PdfOptions::default().with_paper_format("letter").unwrap()
+        };
+        let params = print_parameters(&options);
+        assert_eq!(params["orientation"], "landscape");
+        assert_eq!(params["page"]["width"], 8.5 * 2.54);
+        assert_eq!(params["pageRanges"], json!(["1-2", "5"]));
+    }
+
+    #[test]
+    fn test_error_mapping() {
+        assert!(matches!(
+            map_error("no such element", "#missing"),
+            BrowserError::ElementNotFound(_)
+        ));
+        assert!(matches!(map_error("no such frame", ""), BrowserError::Frame(_)));
+        assert!(matches!(map_error("unknown error", ""), BrowserError::Interaction(_)));
+        assert_eq!(urlencode("a b/c"), "a%20b%2Fc");
+    }
+}
@@ -1,11 +1,13 @@
 //! CalDAV client implementation
 
 use crate::error::{CalendarError, Result};
-use crate::models::{CalendarConfig, CalendarEvent};
-use chrono::{DateTime, Utc};
+use crate::models::{CalendarConfig, CalendarEvent, ParticipationStatus};
+use crate::recurrence::{expand_events, to_utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
 use quick_xml::events::Event;
 use quick_xml::Reader;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use tracing::{debug, error, info};
 
 /// CalDAV client for calendar operations
@@ -39,6 +41,9 @@ impl CalendarClient {
     }
 
     /// Get calendar events within a date range
+    ///
+    /// Recurring events are expanded into one event per occurrence in the
+    /// range (honoring EXDATE and overridden occurrences).
     pub async fn get_events(
         &self,
         start: DateTime<Utc>,
@@ -92,7 +97,7 @@ impl CalendarClient {
         }
 
         let text = response.text().await.map_err(|e| CalendarError::HttpError(e.to_string()))?;
-        let events = self.parse_calendar_response(&text)?;
+        let events = expand_events(self.parse_calendar_response(&text)?, start, end);
 
         info!("Fetched {} events", events.len());
         Ok(events)
@@ -104,13 +109,14 @@ impl CalendarClient {
         let url = format!("{}/{}", self.base_url, calendar_path);
 
         let uid = event.uid.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-        let ical = self.event_to_ical(&event, &uid);
+        let ical = event_to_ical(&event, &uid, None);
+        let url = format!("{}/{}.ics", url, uid);
 
         debug!("Creating event: {}", event.summary);
 
         let response = self
             .client
-            .request(reqwest::Method::from_bytes(b"PUT").unwrap(), format!("{}/{}.ics", url, uid))
+            .request(reqwest::Method::from_bytes(b"PUT").unwrap(), &url)
             .basic_auth(&self.config.username, Some(&self.config.password))
             .header("Content-Type", "text/calendar; charset=utf-8")
             .header("If-None-Match", "*")
@@ -132,20 +138,124 @@ impl CalendarClient {
         info!("Created event: {}", uid);
 
         let mut created_event = event;
+        created_event.etag = etag_header(&response);
+        created_event.href = Some(url);
         created_event.uid = Some(uid);
         Ok(created_event)
     }
 
+    /// Update an existing calendar event
+    ///
+    /// The event must come from [`get_events`](Self::get_events) or
+    /// [`create_event`](Self::create_event) (or at least carry its UID). When
+    /// it has an ETag the update only succeeds if the server copy is
+    /// unchanged; otherwise [`CalendarError::Conflict`] is returned and the
+    /// event should be fetched again. Updating an occurrence of a recurring
+    /// event rewrites the whole series, so pass the series itself (its
+    /// `recurrence_id` must be unset).
+    pub async fn update_event(&self, event: CalendarEvent) -> Result<CalendarEvent> {
+        let uid = event
+            .uid
+            .clone()
+            .ok_or_else(|| CalendarError::UpdateError("Event has no UID".to_string()))?;
+        if event.recurrence_id.is_some() {
+            return Err(CalendarError::UpdateError(format!(
+                "{} is a single occurrence; update the recurring event instead",
+                uid
+            )));
+        }
+        let url = self.event_url(&event, &uid);
+        let ical = event_to_ical(&event, &uid, None);
+
+        debug!("Updating event: {}", uid);
+
+        let response = self
+            .client
+            .request(reqwest::Method::from_bytes(b"PUT").unwrap(), &url)
+            .basic_auth(&self.config.username, Some(&self.config.password))
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .header("If-Match", event.etag.as_deref().unwrap_or("*"))
+            .body(ical)
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+
+        match response.status() {
+            status if status.is_success() => {}
+            StatusCode::PRECONDITION_FAILED => {
+                return Err(CalendarError::Conflict(format!(
+                    "{} was modified on the server",
+                    uid
+                )));
+            }
+            StatusCode::NOT_FOUND => return Err(CalendarError::EventNotFound(uid)),
+            status => {
+                let error_text = response.text().await.unwrap_or_default();
+                error!("Update event failed: {} - {}", status, error_text);
+                return Err(CalendarError::UpdateError(format!(
+                    "Failed to update event: {} - {}",
+                    status, error_text
+                )));
+            }
+        }
+
+        info!("Updated event: {}", uid);
+
+        let mut updated_event = event;
+        // Servers that change the data on store may omit the ETag
+        updated_event.etag = etag_header(&response);
+        updated_event.href = Some(url);
+        Ok(updated_event)
+    }
+
+    /// Get a single event (the recurring series, not its occurrences)
+    pub async fn get_event(&self, uid: &str) -> Result<CalendarEvent> {
+        let url = format!("{}/{}/{}.ics", self.base_url, self.calendar_path(), uid);
+
+        debug!("Fetching event: {}", uid);
+
+        let response = self
+            .client
+            .get(&url)
+            .basic_auth(&self.config.username, Some(&self.config.password))
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(CalendarError::EventNotFound(uid.to_string()));
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(CalendarError::CaldavError(format!(
+                "Request failed: {} - {}",
+                status, error_text
+            )));
+        }
+
+        let etag = etag_header(&response);
+        let text = response.text().await.map_err(|e| CalendarError::HttpError(e.to_string()))?;
+        let mut events = parse_icalendar(&text);
+        let index = events
+            .iter()
+            .position(|e| e.recurrence_id.is_none())
+            .ok_or_else(|| CalendarError::EventNotFound(uid.to_string()))?;
+        let mut event = events.swap_remove(index);
+        event.etag = etag;
+        event.href = Some(url);
+        Ok(event)
+    }
+
     /// Delete a calendar event
     pub async fn delete_event(&self, uid: &str) -> Result<()> {
-        let calendar_path = self.calendar_path();
-        let url = format!("{}/{}.ics", self.base_url, calendar_path);
+        let url = format!("{}/{}/{}.ics", self.base_url, self.calendar_path(), uid);
 
         debug!("Deleting event: {}", uid);
 
         let response = self
             .client
-            .request(reqwest::Method::from_bytes(b"DELETE").unwrap(), format!("{}/{}", url, uid))
+            .request(reqwest::Method::from_bytes(b"DELETE").unwrap(), &url)
             .basic_auth(&self.config.username, Some(&self.config.password))
             .header("If-Match", "*")
             .send()
@@ -209,29 +319,64 @@ impl CalendarClient {
             .unwrap_or_else(|| "calendars".to_string())
     }
 
+    /// URL of an event's calendar object (its server href, or `{uid}.ics`)
+    fn event_url(&self, event: &CalendarEvent, uid: &str) -> String {
+        match event.href {
+            Some(ref href) if href.starts_with("http://") || href.starts_with("https://") => href.clone(),
+            Some(ref href) => reqwest::Url::parse(&self.base_url)
+                .and_then(|base| base.join(href))
+                .map(|url| url.to_string())
+                .unwrap_or_else(|_| format!("{}{}", self.base_url, href)),
+            None => format!("{}/{}/{}.ics", self.base_url, self.calendar_path(), uid),
+        }
+    }
+
     fn parse_calendar_response(&self, response: &str) -> Result<Vec<CalendarEvent>> {
         let mut events = Vec::new();
         let mut reader = Reader::from_str(response);
         reader.config_mut().trim_text(true);
 
         let mut buf = Vec::new();
-        let mut in_calendar_data = false;
-        let mut current_calendar_data = String::new();
+        let mut current: Option<&'static str> = None;
+        let mut href = String::new();
+        let mut etag = String::new();
+        let mut calendar_data = String::new();
 
         loop {
             match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) if e.name().as_ref() == b"calendar-data" => {
-                    in_calendar_data = true;
-                    current_calendar_data.clear();
-                }
-                Ok(Event::End(ref e)) if e.name().as_ref() == b"calendar-data" => {
-                    in_calendar_data = false;
-                    if let Ok(event) = self.parse_icalendar(&current_calendar_data) {
-                        events.push(event);
+                Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                    b"response" => {
+                        href.clear();
+                        etag.clear();
+                        calendar_data.clear();
+                    }
+                    b"href" => current = Some("href"),
+                    b"getetag" => current = Some("getetag"),
+                    b"calendar-data" => current = Some("calendar-data"),
+                    _ => {}
+                },
+                Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                    b"response" => {
+                        for mut event in parse_icalendar(&calendar_data) {
+                            event.href = Some(href.trim().to_string()).filter(|h| !h.is_empty());
+                            event.etag = Some(etag.trim().to_string()).filter(|e| !e.is_empty());
+                            events.push(event);
+                        }
+                    }
+                    b"href" | b"getetag" | b"calendar-data" => current = None,
+                    _ => {}
+                },
+                Ok(Event::Text(ref e)) => {
+                    let text = e.unescape().unwrap_or_default();
+                    match current {
+                        Some("href") => href.push_str(&text),
+                        Some("getetag") => etag.push_str(&text),
+                        Some("calendar-data") => calendar_data.push_str(&text),
+                        _ => {}
                     }
                 }
-                Ok(Event::Text(ref e)) if in_calendar_data => {
-                    current_calendar_data.push_str(&e.unescape().unwrap_or_default());
+                Ok(Event::CData(ref e)) if current == Some("calendar-data") => {
+                    calendar_data.push_str(&String::from_utf8_lossy(e));
                 }
                 Ok(Event::Eof) => break,
                 Err(e) => {
@@ -281,124 +426,465 @@ impl CalendarClient {
 
         Ok(calendars)
     }
+}
 
-    fn parse_icalendar(&self, ical: &str) -> Result<CalendarEvent> {
-        let mut summary = String::new();
-        let mut description = None;
-        let mut start = Utc::now();
-        let mut end = Utc::now();
-        let mut location = None;
-        let mut uid = None;
-        let mut attendees = Vec::new();
-        let mut all_day = false;
-
-        for line in ical.lines() {
-            let line = line.trim();
-            if let Some(val) = line.strip_prefix("SUMMARY:") {
-                summary = val.to_string();
-            } else if let Some(val) = line.strip_prefix("DESCRIPTION:") {
-                description = Some(val.to_string());
-            } else if line.starts_with("DTSTART") {
-                if let Some(dt) = self.parse_ical_date(line) {
-                    start = dt;
+/// Parse every VEVENT of an iCalendar object (the series and any
+/// overridden occurrences)
+pub(crate) fn parse_icalendar(ical: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<CalendarEvent> = None;
+    let mut has_start = false;
+    let mut has_end = false;
+    let mut duration = None;
+    // Depth of components nested in the VEVENT (VALARM)
+    let mut nested = 0;
+
+    for line in unfold(ical) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => {
+                current = Some(CalendarEvent::default());
+                has_start = false;
+                has_end = false;
+                duration = None;
+                nested = 0;
+                continue;
+            }
+            ("END", "VEVENT") => {
+                if let Some(mut event) = current.take() {
+                    if !has_end {
+                        event.end = event.start
+                            + duration.unwrap_or(if event.all_day {
+                                Duration::days(1)
+                            } else {
+                                Duration::zero()
+                            });
+                    }
+                    if has_start {
+                        events.push(event);
+                    }
                 }
-            } else if line.starts_with("DTEND") {
-                if let Some(dt) = self.parse_ical_date(line) {
-                    end = dt;
+                continue;
+            }
+            ("BEGIN", _) => nested += 1,
+            ("END", _) => nested -= 1,
+            _ => {}
+        }
+        let Some(ref mut event) = current else {
+            continue;
+        };
+        if nested > 0 {
+            continue;
+        }
+
+        let tzid = params.iter().find(|(k, _)| k == "TZID").map(|(_, v)| v.as_str());
+        let is_date = params.iter().any(|(k, v)| k == "VALUE" && v == "DATE");
+        match name.as_str() {
+            "UID" => event.uid = Some(value.to_string()),
+            "SUMMARY" => event.summary = unescape_text(value),
+            "DESCRIPTION" => event.description = Some(unescape_text(value)),
+            "LOCATION" => event.location = Some(unescape_text(value)),
+            "DTSTART" => {
+                if let Some(dt) = parse_ical_date(value, tzid) {
+                    event.start = dt;
+                    has_start = true;
+                    event.all_day = is_date || !value.contains('T');
+                    event.timezone = tzid.map(str::to_string);
+                }
+            }
+            "DTEND" => {
+                if let Some(dt) = parse_ical_date(value, tzid) {
+                    event.end = dt;
+                    has_end = true;
                 }
-            } else if let Some(val) = line.strip_prefix("LOCATION:") {
-                location = Some(val.to_string());
-            } else if let Some(val) = line.strip_prefix("UID:") {
-                uid = Some(val.to_string());
-            } else if let Some(email) = line.strip_prefix("ATTENDEE") {
-                if let Some(pos) = email.find(':') {
-                    attendees.push(email[pos + 1..].to_string());
+            }
+            "DURATION" => duration = parse_duration(value),
+            "RRULE" => event.rrule = Some(value.to_string()),
+            "EXDATE" => event
+                .exdates
+                .extend(value.split(',').filter_map(|v| parse_ical_date(v, tzid))),
+            "RECURRENCE-ID" => event.recurrence_id = parse_ical_date(value, tzid),
+            "ORGANIZER" => event.organizer = Some(strip_mailto(value)),
+            "ATTENDEE" => {
+                let attendee = strip_mailto(value);
+                let status = params
+                    .iter()
+                    .find(|(k, _)| k == "PARTSTAT")
+                    .and_then(|(_, v)| ParticipationStatus::parse(v))
+                    .filter(|s| *s != ParticipationStatus::NeedsAction);
+                if let Some(status) = status {
+                    event.attendee_status.insert(attendee.clone(), status);
                 }
-            } else if line.contains("VALUE=DATE") {
-                all_day = true;
+                event.attendees.push(attendee);
             }
+            "SEQUENCE" => event.sequence = value.trim().parse().unwrap_or(0),
+            "LAST-MODIFIED" => event.modified = parse_ical_date(value, None),
+            _ => {}
         }
+    }
 
-        Ok(CalendarEvent {
-            uid,
-            summary,
-            description,
-            start,
-            end,
-            location,
-            organizer: None,
-            attendees,
-            all_day,
-            rrule: None,
-            modified: None,
-        })
+    events
+}
+
+/// Serialize an event as an iCalendar object
+///
+/// `method` is the iTIP method for scheduling messages; objects stored on
+/// the server carry none (RFC 4791 section 4.1).
+pub(crate) fn event_to_ical(event: &CalendarEvent, uid: &str, method: Option<&str>) -> String {
+    let mut ical = String::new();
+
+    ical.push_str("BEGIN:VCALENDAR\r\n");
+    ical.push_str("VERSION:2.0\r\n");
+    ical.push_str("PRODID:-//cc-gateway//calendar//EN\r\n");
+    ical.push_str("CALSCALE:GREGORIAN\r\n");
+    if let Some(method) = method {
+        ical.push_str(&format!("METHOD:{}\r\n", method));
     }
+    ical.push_str("BEGIN:VEVENT\r\n");
 
-    fn parse_ical_date(&self, line: &str) -> Option<DateTime<Utc>> {
-        let colon_pos = line.find(':')?;
-        let date_str = &line[colon_pos + 1..];
+    ical.push_str(&format!("UID:{}\r\n", uid));
+    ical.push_str(&format!("DTSTAMP:{}\r\n", Utc::now().format("%Y%m%dT%H%M%SZ")));
+    ical.push_str(&format!("SEQUENCE:{}\r\n", event.sequence));
+    if method == Some("CANCEL") {
+        ical.push_str("STATUS:CANCELLED\r\n");
+    }
 
-        if date_str.contains('T') {
-            // Date-time format: YYYYMMDDTHHMMSSZ
-            DateTime::parse_from_str(date_str, "%Y%m%dT%H%M%SZ")
-                .ok()
-                .map(|dt| dt.with_timezone(&Utc))
-        } else {
-            // Date only format: YYYYMMDD
-            chrono::NaiveDate::parse_from_str(date_str, "%Y%m%d")
-                .ok()
-                .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc())
-        }
+    let tz = event
+        .timezone
+        .as_deref()
+        .and_then(|tz| tz.parse::<Tz>().ok())
+        .filter(|_| !event.all_day);
+    let format_date = |name: &str, dt: &DateTime<Utc>| match tz {
+        _ if event.all_day => format!("{};VALUE=DATE:{}", name, dt.format("%Y%m%d")),
+        Some(tz) => format!(
+            "{};TZID={}:{}",
+            name,
+            tz.name(),
+            dt.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+        ),
+        None => format!("{}:{}", name, dt.format("%Y%m%dT%H%M%SZ")),
+    };
+
+    ical.push_str(&format!("{}\r\n", format_date("DTSTART", &event.start)));
+    ical.push_str(&format!("{}\r\n", format_date("DTEND", &event.end)));
+
+    ical.push_str(&format!("SUMMARY:{}\r\n", escape_text(&event.summary)));
+
+    if let Some(ref desc) = event.description {
+        ical.push_str(&format!("DESCRIPTION:{}\r\n", escape_text(desc)));
     }
 
-    fn event_to_ical(&self, event: &CalendarEvent, uid: &str) -> String {
-        let mut ical = String::new();
+    if let Some(ref loc) = event.location {
+        ical.push_str(&format!("LOCATION:{}\r\n", escape_text(loc)));
+    }
 
-        ical.push_str("BEGIN:VCALENDAR\r\n");
-        ical.push_str("VERSION:2.0\r\n");
-        ical.push_str("PRODID:-//cc-gateway//calendar//EN\r\n");
-        ical.push_str("CALSCALE:GREGORIAN\r\n");
-        ical.push_str("METHOD:PUBLISH\r\n");
-        ical.push_str("BEGIN:VEVENT\r\n");
+    if let Some(ref organizer) = event.organizer {
+        ical.push_str(&format!("ORGANIZER:mailto:{}\r\n", organizer));
+    }
 
-        ical.push_str(&format!("UID:{}\r\n", uid));
-        ical.push_str(&format!("DTSTAMP:{}\r\n", Utc::now().format("%Y%m%dT%H%M%SZ")));
+    for attendee in &event.attendees {
+        let status = event.status_of(attendee);
+        let rsvp = if status == ParticipationStatus::NeedsAction { ";RSVP=TRUE" } else { "" };
+        ical.push_str(&format!(
+            "ATTENDEE;ROLE=REQ-PARTICIPANT;PARTSTAT={}{}:mailto:{}\r\n",
+            status.as_str(),
+            rsvp,
+            attendee
+        ));
+    }
 
-        let start_str = if event.all_day {
-            event.start.format("%Y%m%d").to_string()
-        } else {
-            event.start.format("%Y%m%dT%H%M%SZ").to_string()
-        };
-        let end_str = if event.all_day {
-            event.end.format("%Y%m%d").to_string()
-        } else {
-            event.end.format("%Y%m%dT%H%M%SZ").to_string()
-        };
+    if let Some(ref rrule) = event.rrule {
+        ical.push_str(&format!("RRULE:{}\r\n", rrule));
+    }
+
+    for exdate in &event.exdates {
+        ical.push_str(&format!("{}\r\n", format_date("EXDATE", exdate)));
+    }
+
+    ical.push_str("END:VEVENT\r\n");
+    ical.push_str("END:VCALENDAR\r\n");
 
-        ical.push_str(&format!("DTSTART:{}\r\n", start_str));
-        ical.push_str(&format!("DTEND:{}\r\n", end_str));
+    ical
+}
 
-        ical.push_str(&format!("SUMMARY:{}\r\n", event.summary));
+fn etag_header(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Join folded content lines (continuations start with a space or tab)
+fn unfold(ical: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ical.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push(line.trim_start().to_string()),
+        }
+    }
+    lines
+}
 
-        if let Some(ref desc) = event.description {
-            ical.push_str(&format!("DESCRIPTION:{}\r\n", desc));
+/// Property parameters as (upper-cased name, value) pairs
+type Params = Vec<(String, String)>;
+
+/// Split `NAME;PARAM=value:VALUE` into the upper-cased name, the parameters
+/// and the value
+pub(crate) fn split_property(line: &str) -> Option<(String, Params, &str)> {
+    // The value starts at the first colon outside a quoted parameter
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
         }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, value))
+}
 
-        if let Some(ref loc) = event.location {
-            ical.push_str(&format!("LOCATION:{}\r\n", loc));
+fn unescape_text(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
         }
+        match chars.next() {
+            Some('n') | Some('N') => output.push('\n'),
+            Some(other) => output.push(other),
+            None => output.push('\\'),
+        }
+    }
+    output
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
 
-        for attendee in &event.attendees {
-            ical.push_str(&format!("ATTENDEE:mailto:{}\r\n", attendee));
+fn strip_mailto(value: &str) -> String {
+    let value = value.trim();
+    value
+        .get(..7)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("mailto:"))
+        .map_or(value, |_| &value[7..])
+        .to_string()
+}
+
+/// Parse a DATE or DATE-TIME value (UTC, floating or in `tzid`)
+pub(crate) fn parse_ical_date(value: &str, tzid: Option<&str>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|dt| dt.and_utc());
+    }
+    let local = if value.contains('T') {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?
+    } else {
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?
+    };
+    // Unknown (e.g. Windows) TZIDs and floating times are treated as UTC
+    Some(to_utc(local, tzid.and_then(|tz| tz.parse::<Tz>().ok())))
+}
+
+/// Parse a DURATION value such as `PT1H30M`, `P1D` or `P2W`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim_start_matches('+')),
+    };
+    let value = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
         }
+    }
+    Some(if negative { -total } else { total })
+}
 
-        if let Some(ref rrule) = event.rrule {
-            ical.push_str(&format!("RRULE:{}\r\n", rrule));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> CalendarClient {
+        CalendarClient {
+            client: Client::new(),
+            config: CalendarConfig::new("https://dav.example.com/dav/", "user", "pass")
+                .with_calendar_id("calendars/user/work"),
+            base_url: "https://dav.example.com/dav".to_string(),
         }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response>
+    <d:href>/dav/calendars/user/work/standup.ics</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:getetag>"etag-1"</d:getetag>
+        <cal:calendar-data>BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:standup
+SUMMARY:Standup\, daily
+DTSTART;TZID=Europe/Berlin:20240325T090000
+DURATION:PT15M
+RRULE:FREQ=DAILY;COUNT=5
+EXDATE;TZID=Europe/Berlin:20240326T090000
+ATTENDEE;CN=Ann;ROLE=REQ-PARTICIPANT:mailto:ann@exa
+ mple.com
+BEGIN:VALARM
+TRIGGER:-PT5M
+DESCRIPTION:Reminder
+END:VALARM
+END:VEVENT
+BEGIN:VEVENT
+UID:standup
+RECURRENCE-ID;TZID=Europe/Berlin:20240328T090000
+SUMMARY:Late standup
+DTSTART;TZID=Europe/Berlin:20240328T110000
+DTEND;TZID=Europe/Berlin:20240328T111500
+END:VEVENT
+END:VCALENDAR
+</cal:calendar-data>
+      </d:prop>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+    #[test]
+    fn test_parse_calendar_response() {
+        let events = client().parse_calendar_response(REPORT).unwrap();
+        assert_eq!(events.len(), 2);
+
+        let series = &events[0];
+        assert_eq!(series.summary, "Standup, daily");
+        assert_eq!(series.etag.as_deref(), Some("\"etag-1\""));
+        assert_eq!(series.href.as_deref(), Some("/dav/calendars/user/work/standup.ics"));
+        assert_eq!(series.timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(series.start, utc("2024-03-25T08:00:00Z"));
+        assert_eq!(series.end, utc("2024-03-25T08:15:00Z"));
+        assert_eq!(series.exdates, vec![utc("2024-03-26T08:00:00Z")]);
+        assert_eq!(series.attendees, vec!["ann@example.com"]);
+        assert_eq!(series.description, None);
+
+        assert_eq!(events[1].recurrence_id, Some(utc("2024-03-28T08:00:00Z")));
+    }
+
+    #[test]
+    fn test_expand_parsed_series() {
+        let events = client().parse_calendar_response(REPORT).unwrap();
+        let expanded = expand_events(events, utc("2024-03-25T00:00:00Z"), utc("2024-04-05T00:00:00Z"));
+        let starts: Vec<_> = expanded.iter().map(|e| (e.start, e.summary.as_str())).collect();
+        // Berlin switches to summer time on March 31st
+        assert_eq!(
+            starts,
+            vec![
+                (utc("2024-03-25T08:00:00Z"), "Standup, daily"),
+                (utc("2024-03-27T08:00:00Z"), "Standup, daily"),
+                (utc("2024-03-28T10:00:00Z"), "Late standup"),
+                (utc("2024-03-29T08:00:00Z"), "Standup, daily"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_to_ical_round_trip() {
+        let mut event = CalendarEvent::new(
+            "Review; notes",
+            utc("2024-07-01T07:00:00Z"),
+            utc("2024-07-01T08:00:00Z"),
+        )
+        .with_rrule("FREQ=WEEKLY;BYDAY=MO")
+        .with_timezone("Europe/Berlin");
+        event.exdates = vec![utc("2024-07-08T07:00:00Z")];
+        event.attendees = vec!["ann@example.com".to_string(), "bob@example.com".to_string()];
+        event.set_status("ann@example.com", ParticipationStatus::Accepted);
+        event.sequence = 2;
+
+        let ical = event_to_ical(&event, "review", None);
+        assert!(!ical.contains("METHOD:"));
+        assert!(ical.contains("ATTENDEE;ROLE=REQ-PARTICIPANT;PARTSTAT=ACCEPTED:mailto:ann@example.com\r\n"));
+        assert!(ical.contains("PARTSTAT=NEEDS-ACTION;RSVP=TRUE:mailto:bob@example.com\r\n"));
+        assert!(ical.contains("DTSTART;TZID=Europe/Berlin:20240701T090000\r\n"));
+        assert!(ical.contains("EXDATE;TZID=Europe/Berlin:20240708T090000\r\n"));
+        assert!(ical.contains("SUMMARY:Review\\; notes\r\n"));
+
+        let parsed = parse_icalendar(&ical);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].summary, event.summary);
+        assert_eq!(parsed[0].start, event.start);
+        assert_eq!(parsed[0].exdates, event.exdates);
+        assert_eq!(parsed[0].rrule, event.rrule);
+        assert_eq!(parsed[0].sequence, 2);
+        assert_eq!(parsed[0].status_of("ANN@example.com"), ParticipationStatus::Accepted);
+        assert_eq!(parsed[0].status_of("bob@example.com"), ParticipationStatus::NeedsAction);
+    }
 
-        ical.push_str("END:VEVENT\r\n");
-        ical.push_str("END:VCALENDAR\r\n");
+    #[test]
+    fn test_event_url() {
+        let client = client();
+        let mut event = CalendarEvent::default();
+        assert_eq!(
+            client.event_url(&event, "abc"),
+            "https://dav.example.com/dav/calendars/user/work/abc.ics"
+        );
+        event.href = Some("/dav/calendars/user/work/other.ics".to_string());
+        assert_eq!(
+            client.event_url(&event, "abc"),
+            "https://dav.example.com/dav/calendars/user/work/other.ics"
+        );
+    }
 
-        ical
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("-P1DT2H"), Some(-Duration::hours(26)));
+        assert_eq!(parse_duration("1H"), None);
     }
 }
@@ -35,6 +35,12 @@ pub enum CalendarError {
     #[error("Update error: {0}")]
     UpdateError(String),
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Scheduling error: {0}")]
+    Scheduling(String),
+
     #[error("Parse error: {0}")]
     ParseError(String),
 
@@ -0,0 +1,560 @@
+//! Google Calendar API backend
+//!
+//! Recurring events are expanded by the server (`singleEvents=true`); each
+//! occurrence has its own instance ID, so single occurrences can be updated
+//! and deleted directly.
+
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, NaiveDate, SecondsFormat, Utc};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::client::{parse_ical_date, split_property};
+use crate::error::{CalendarError, Result};
+use crate::models::{CalendarEvent, ParticipationStatus};
+use crate::oauth::OAuthCredentials;
+use crate::provider::{CalendarProvider, api_error};
+
+const GOOGLE_API_URL: &str = "https://www.googleapis.com/calendar/v3";
+
+/// Events requested per page
+const PAGE_SIZE: &str = "250";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleEvent {
+    #[serde(default, skip_serializing)]
+    id: Option<String>,
+    #[serde(default, skip_serializing)]
+    etag: Option<String>,
+    #[serde(default, skip_serializing)]
+    status: Option<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    #[serde(default)]
+    start: Option<EventTime>,
+    #[serde(default)]
+    end: Option<EventTime>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attendees: Vec<Person>,
+    #[serde(default, skip_serializing)]
+    organizer: Option<Person>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    recurrence: Vec<String>,
+    #[serde(default, skip_serializing)]
+    original_start_time: Option<EventTime>,
+    #[serde(default, skip_serializing)]
+    sequence: u32,
+    #[serde(default, skip_serializing)]
+    updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventTime {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date_time: Option<DateTime<FixedOffset>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_zone: Option<String>,
+}
+
+impl EventTime {
+    fn to_utc(&self) -> Option<DateTime<Utc>> {
+        match (self.date_time, self.date) {
+            (Some(dt), _) => Some(dt.with_timezone(&Utc)),
+            (None, Some(date)) => date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc()),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Person {
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    response_status: Option<String>,
+}
+
+impl Person {
+    fn status(&self) -> Option<ParticipationStatus> {
+        match self.response_status.as_deref()? {
+            "accepted" => Some(ParticipationStatus::Accepted),
+            "declined" => Some(ParticipationStatus::Declined),
+            "tentative" => Some(ParticipationStatus::Tentative),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventList {
+    #[serde(default)]
+    items: Vec<GoogleEvent>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarList {
+    #[serde(default)]
+    items: Vec<CalendarListEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarListEntry {
+    id: String,
+}
+
+impl GoogleEvent {
+    fn from_event(event: &CalendarEvent) -> Self {
+        let time = |dt: &DateTime<Utc>| {
+            if event.all_day {
+                EventTime {
+                    date: Some(dt.date_naive()),
+                    ..Default::default()
+                }
+            } else {
+                EventTime {
+                    date_time: Some(dt.fixed_offset()),
+                    // Required for recurring events
+                    time_zone: event
+                        .timezone
+                        .clone()
+                        .or_else(|| event.rrule.as_ref().map(|_| "UTC".to_string())),
+                    ..Default::default()
+                }
+            }
+        };
+
+        let mut recurrence = Vec::new();
+        if let Some(ref rrule) = event.rrule {
+            recurrence.push(format!("RRULE:{}", rrule.trim_start_matches("RRULE:")));
+            recurrence.extend(
+                event
+                    .exdates
+                    .iter()
+                    .map(|dt| format!("EXDATE:{}", dt.format("%Y%m%dT%H%M%SZ"))),
+            );
+        }
+
+        Self {
+            summary: Some(event.summary.clone()),
+            description: event.description.clone(),
+            location: event.location.clone(),
+            start: Some(time(&event.start)),
+            end: Some(time(&event.end)),
+            attendees: event
+                .attendees
+                .iter()
+                .map(|email| Person {
+                    email: Some(email.clone()),
+                    response_status: match event.status_of(email) {
+                        ParticipationStatus::Accepted => Some("accepted".to_string()),
+                        ParticipationStatus::Declined => Some("declined".to_string()),
+                        ParticipationStatus::Tentative => Some("tentative".to_string()),
+                        _ => None,
+                    },
+                })
+                .collect(),
+            recurrence,
+            ..Default::default()
+        }
+    }
+
+    fn into_event(self) -> Option<CalendarEvent> {
+        let start = self.start.as_ref()?;
+        let start_utc = start.to_utc()?;
+        let end = self
+            .end
+            .as_ref()
+            .and_then(EventTime::to_utc)
+            .unwrap_or(start_utc);
+        Some(CalendarEvent {
+            uid: self.id,
+            summary: self.summary.unwrap_or_default(),
+            description: self.description,
+            start: start_utc,
+            end,
+            location: self.location,
+            organizer: self.organizer.and_then(|p| p.email),
+            attendee_status: self
+                .attendees
+                .iter()
+                .filter_map(|p| Some((p.email.clone()?, p.status()?)))
+                .collect(),
+            attendees: self.attendees.into_iter().filter_map(|p| p.email).collect(),
+            sequence: self.sequence,
+            all_day: start.date.is_some(),
+            rrule: self
+                .recurrence
+                .iter()
+                .find_map(|line| line.strip_prefix("RRULE:"))
+                .map(str::to_string),
+            modified: self.updated,
+            timezone: start.time_zone.clone(),
+            exdates: self.recurrence.iter().flat_map(|line| exdates(line)).collect(),
+            recurrence_id: self.original_start_time.as_ref().and_then(EventTime::to_utc),
+            etag: self.etag,
+            href: None,
+        })
+    }
+}
+
+/// Dates of an `EXDATE` recurrence line (empty for other lines)
+fn exdates(line: &str) -> Vec<DateTime<Utc>> {
+    match split_property(line) {
+        Some((name, params, value)) if name == "EXDATE" => {
+            let tzid = params.iter().find(|(k, _)| k == "TZID").map(|(_, v)| v.as_str());
+            value
+                .split(',')
+                .filter_map(|v| parse_ical_date(v, tzid))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Google Calendar API client
+pub struct GoogleCalendarClient {
+    http: Client,
+    credentials: OAuthCredentials,
+    calendar_id: String,
+    base_url: String,
+}
+
+impl GoogleCalendarClient {
+    /// Create a client for the account's primary calendar
+    pub fn new(credentials: OAuthCredentials) -> Self {
+        Self {
+            http: Client::new(),
+            credentials,
+            calendar_id: "primary".to_string(),
+            base_url: GOOGLE_API_URL.to_string(),
+        }
+    }
+
+    /// Use another calendar (an ID from [`list_calendars`](CalendarProvider::list_calendars))
+    pub fn with_calendar_id(mut self, calendar_id: impl Into<String>) -> Self {
+        self.calendar_id = calendar_id.into();
+        self
+    }
+
+    /// Override the API base URL
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// URL of the calendar's events collection, or of one event
+    fn events_url(&self, event_id: Option<&str>) -> Result<Url> {
+        let mut url = Url::parse(&self.base_url)
+            .map_err(|e| CalendarError::Configuration(format!("Invalid base URL: {}", e)))?;
+        {
+            let mut segments = url
+                .path_segments_mut()
+                .map_err(|_| CalendarError::Configuration("Invalid base URL".to_string()))?;
+            segments.pop_if_empty();
+            segments.extend(["calendars", self.calendar_id.as_str(), "events"]);
+            if let Some(id) = event_id {
+                segments.push(id);
+            }
+        }
+        Ok(url)
+    }
+
+    async fn request(&self, method: reqwest::Method, url: Url) -> Result<reqwest::RequestBuilder> {
+        let token = self.credentials.access_token().await?;
+        Ok(self.http.request(method, url).bearer_auth(token))
+    }
+}
+
+#[async_trait]
+impl CalendarProvider for GoogleCalendarClient {
+    fn name(&self) -> &str {
+        "google"
+    }
+
+    async fn list_calendars(&self) -> Result<Vec<String>> {
+        let url = Url::parse(&format!("{}/users/me/calendarList", self.base_url))
+            .map_err(|e| CalendarError::Configuration(e.to_string()))?;
+        let response = self
+            .request(reqwest::Method::GET, url)
+            .await?
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, "list calendars").await);
+        }
+        let list: CalendarList = response
+            .json()
+            .await
+            .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+        Ok(list.items.into_iter().map(|c| c.id).collect())
+    }
+
+    async fn get_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<CalendarEvent>> {
+        let mut events = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut url = self.events_url(None)?;
+            url.query_pairs_mut()
+                .append_pair("timeMin", &start.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .append_pair("timeMax", &end.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .append_pair("singleEvents", "true")
+                .append_pair("orderBy", "startTime")
+                .append_pair("maxResults", PAGE_SIZE);
+            if let Some(ref token) = page_token {
+                url.query_pairs_mut().append_pair("pageToken", token);
+            }
+            debug!("Fetching events from: {}", url);
+
+            let response = self
+                .request(reqwest::Method::GET, url)
+                .await?
+                .send()
+                .await
+                .map_err(|e| CalendarError::Connection(e.to_string()))?;
+            if !response.status().is_success() {
+                return Err(api_error(response, "list events").await);
+            }
+            let page: EventList = response
+                .json()
+                .await
+                .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+
+            events.extend(
+                page.items
+                    .into_iter()
+                    .filter(|e| e.status.as_deref() != Some("cancelled"))
+                    .filter_map(GoogleEvent::into_event),
+            );
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        info!("Fetched {} events", events.len());
+        Ok(events)
+    }
+
+    async fn get_event(&self, uid: &str) -> Result<CalendarEvent> {
+        let response = self
+            .request(reqwest::Method::GET, self.events_url(Some(uid))?)
+            .await?
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, uid).await);
+        }
+        let event: GoogleEvent = response
+            .json()
+            .await
+            .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+        event
+            .into_event()
+            .ok_or_else(|| CalendarError::EventNotFound(uid.to_string()))
+    }
+
+    async fn create_event(&self, event: CalendarEvent) -> Result<CalendarEvent> {
+        debug!("Creating event: {}", event.summary);
+        let response = self
+            .request(reqwest::Method::POST, self.events_url(None)?)
+            .await?
+            .json(&GoogleEvent::from_event(&event))
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, "create event").await);
+        }
+        let created: GoogleEvent = response
+            .json()
+            .await
+            .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+        let created = created
+            .into_event()
+            .ok_or_else(|| CalendarError::CreateError("Server returned an event without a start".to_string()))?;
+        info!("Created event: {:?}", created.uid);
+        Ok(created)
+    }
+
+    async fn update_event(&self, event: CalendarEvent) -> Result<CalendarEvent> {
+        let id = event
+            .uid
+            .clone()
+            .ok_or_else(|| CalendarError::UpdateError("Event has no UID".to_string()))?;
+        debug!("Updating event: {}", id);
+
+        let mut request = self
+            .request(reqwest::Method::PUT, self.events_url(Some(&id))?)
+            .await?
+            .json(&GoogleEvent::from_event(&event));
+        if let Some(ref etag) = event.etag {
+            request = request.header("If-Match", etag);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, &id).await);
+        }
+        let updated: GoogleEvent = response
+            .json()
+            .await
+            .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+        info!("Updated event: {}", id);
+        updated
+            .into_event()
+            .ok_or_else(|| CalendarError::UpdateError("Server returned an event without a start".to_string()))
+    }
+
+    async fn delete_event(&self, uid: &str) -> Result<()> {
+        debug!("Deleting event: {}", uid);
+        let response = self
+            .request(reqwest::Method::DELETE, self.events_url(Some(uid))?)
+            .await?
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, uid).await);
+        }
+        info!("Deleted event: {}", uid);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{bearer_token, body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    async fn client(server: &MockServer) -> GoogleCalendarClient {
+        GoogleCalendarClient::new(OAuthCredentials::access_token_only("token"))
+            .with_calendar_id("team@group.calendar.google.com")
+            .with_base_url(server.uri())
+    }
+
+    #[tokio::test]
+    async fn test_get_events_pages() {
+        let server = MockServer::start().await;
+        let events_path = "/calendars/team@group.calendar.google.com/events";
+        Mock::given(method("GET"))
+            .and(path(events_path))
+            .and(bearer_token("token"))
+            .and(query_param("singleEvents", "true"))
+            .and(query_param("pageToken", "next"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "items": [{
+                    "id": "holiday",
+                    "etag": "\"2\"",
+                    "summary": "Holiday",
+                    "start": {"date": "2024-03-02"},
+                    "end": {"date": "2024-03-03"}
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(events_path))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "items": [
+                    {
+                        "id": "standup_20240301T080000Z",
+                        "etag": "\"1\"",
+                        "summary": "Standup",
+                        "start": {"dateTime": "2024-03-01T09:00:00+01:00", "timeZone": "Europe/Berlin"},
+                        "end": {"dateTime": "2024-03-01T09:15:00+01:00", "timeZone": "Europe/Berlin"},
+                        "originalStartTime": {"dateTime": "2024-03-01T09:00:00+01:00"},
+                        "attendees": [{"email": "ann@example.com"}],
+                        "organizer": {"email": "bob@example.com"}
+                    },
+                    {"id": "gone", "status": "cancelled"}
+                ],
+                "nextPageToken": "next"
+            })))
+            .mount(&server)
+            .await;
+
+        let events = client(&server)
+            .await
+            .get_events(utc("2024-03-01T00:00:00Z"), utc("2024-03-08T00:00:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid.as_deref(), Some("standup_20240301T080000Z"));
+        assert_eq!(events[0].start, utc("2024-03-01T08:00:00Z"));
+        assert_eq!(events[0].recurrence_id, Some(utc("2024-03-01T08:00:00Z")));
+        assert_eq!(events[0].timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(events[0].attendees, vec!["ann@example.com"]);
+        assert_eq!(events[0].organizer.as_deref(), Some("bob@example.com"));
+        assert!(events[1].all_day);
+        assert_eq!(events[1].end, utc("2024-03-03T00:00:00Z"));
+    }
+
+    #[tokio::test]
+    async fn test_create_recurring_event() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({
+                "summary": "Review",
+                "start": {"dateTime": "2024-07-01T07:00:00Z", "timeZone": "UTC"},
+                "recurrence": ["RRULE:FREQ=WEEKLY"]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "new-id",
+                "etag": "\"1\"",
+                "summary": "Review",
+                "start": {"dateTime": "2024-07-01T07:00:00Z", "timeZone": "UTC"},
+                "end": {"dateTime": "2024-07-01T08:00:00Z", "timeZone": "UTC"},
+                "recurrence": ["RRULE:FREQ=WEEKLY", "EXDATE;TZID=Europe/Berlin:20240708T090000"]
+            })))
+            .mount(&server)
+            .await;
+
+        let event = CalendarEvent::new("Review", utc("2024-07-01T07:00:00Z"), utc("2024-07-01T08:00:00Z"))
+            .with_rrule("FREQ=WEEKLY");
+        let created = client(&server).await.create_event(event).await.unwrap();
+        assert_eq!(created.uid.as_deref(), Some("new-id"));
+        assert_eq!(created.rrule.as_deref(), Some("FREQ=WEEKLY"));
+        assert_eq!(created.exdates, vec![utc("2024-07-08T07:00:00Z")]);
+    }
+
+    #[tokio::test]
+    async fn test_update_conflict() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/calendars/team@group.calendar.google.com/events/ev1"))
+            .and(header("If-Match", "\"old\""))
+            .respond_with(ResponseTemplate::new(412))
+            .mount(&server)
+            .await;
+
+        let mut event = CalendarEvent::new("Moved", utc("2024-07-01T07:00:00Z"), utc("2024-07-01T08:00:00Z"));
+        event.uid = Some("ev1".to_string());
+        event.etag = Some("\"old\"".to_string());
+        let err = client(&server).await.update_event(event).await.unwrap_err();
+        assert!(matches!(err, CalendarError::Conflict(_)));
+    }
+}
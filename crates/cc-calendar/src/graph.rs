@@ -0,0 +1,674 @@
+//! Microsoft Graph (Outlook / Microsoft 365) calendar backend
+//!
+//! Events are read through `calendarView`, which expands recurring events on
+//! the server. Graph describes recurrence with a structured pattern instead of
+//! an RRULE, so rules are converted on create/update; rules Graph cannot
+//! express (e.g. BYSETPOS or several BYDAY ordinals) are rejected.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc, Weekday};
+use chrono_tz::Tz;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::{debug, info, warn};
+
+use crate::error::{CalendarError, Result};
+use crate::models::{CalendarEvent, ParticipationStatus};
+use crate::oauth::OAuthCredentials;
+use crate::provider::{CalendarProvider, api_error};
+use crate::recurrence::{Frequency, RecurrenceRule};
+
+const GRAPH_API_URL: &str = "https://graph.microsoft.com/v1.0";
+
+/// Return times in UTC and plain-text bodies
+const PREFER: &str = "outlook.timezone=\"UTC\", outlook.body-content-type=\"text\"";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphEvent {
+    #[serde(default, skip_serializing)]
+    id: Option<String>,
+    #[serde(default, rename = "@odata.etag", skip_serializing)]
+    etag: Option<String>,
+    #[serde(default)]
+    subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<ItemBody>,
+    #[serde(default)]
+    start: Option<DateTimeTimeZone>,
+    #[serde(default)]
+    end: Option<DateTimeTimeZone>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<Location>,
+    #[serde(default, skip_serializing)]
+    organizer: Option<Recipient>,
+    #[serde(default)]
+    attendees: Vec<Recipient>,
+    #[serde(default)]
+    is_all_day: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recurrence: Option<Value>,
+    #[serde(default, skip_serializing)]
+    original_start: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing)]
+    original_start_time_zone: Option<String>,
+    #[serde(default, skip_serializing)]
+    last_modified_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing)]
+    is_cancelled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemBody {
+    content_type: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DateTimeTimeZone {
+    date_time: String,
+    time_zone: String,
+}
+
+impl DateTimeTimeZone {
+    fn new(dt: &DateTime<Utc>, tz: Option<Tz>) -> Self {
+        match tz {
+            Some(tz) => Self {
+                date_time: dt.with_timezone(&tz).format("%Y-%m-%dT%H:%M:%S").to_string(),
+                time_zone: tz.name().to_string(),
+            },
+            None => Self {
+                date_time: dt.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                time_zone: "UTC".to_string(),
+            },
+        }
+    }
+
+    fn to_utc(&self) -> Option<DateTime<Utc>> {
+        let local = NaiveDateTime::parse_from_str(&self.date_time, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+        // Windows zone names are not IANA names; times are requested in UTC anyway
+        Some(crate::recurrence::to_utc(local, self.time_zone.parse::<Tz>().ok()))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Recipient {
+    email_address: EmailAddress,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    #[serde(default, skip_serializing)]
+    status: Option<ResponseStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponseStatus {
+    #[serde(default)]
+    response: Option<String>,
+}
+
+impl Recipient {
+    fn status(&self) -> Option<ParticipationStatus> {
+        match self.status.as_ref()?.response.as_deref()? {
+            "accepted" => Some(ParticipationStatus::Accepted),
+            "declined" => Some(ParticipationStatus::Declined),
+            "tentativelyAccepted" => Some(ParticipationStatus::Tentative),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EmailAddress {
+    #[serde(default)]
+    address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(default = "Vec::new")]
+    value: Vec<T>,
+    #[serde(default, rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphCalendar {
+    id: String,
+}
+
+impl GraphEvent {
+    fn from_event(event: &CalendarEvent) -> Result<Self> {
+        let tz = event
+            .timezone
+            .as_deref()
+            .and_then(|tz| tz.parse::<Tz>().ok())
+            .filter(|_| !event.all_day);
+
+        // Single occurrences keep the series' recurrence
+        let recurrence = match event.rrule {
+            Some(ref rrule) if event.recurrence_id.is_none() => {
+                if !event.exdates.is_empty() {
+                    warn!("Graph cannot exclude dates on create/update; delete those occurrences instead");
+                }
+                Some(recurrence_to_graph(rrule, event, tz)?)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            subject: Some(event.summary.clone()),
+            body: event.description.as_ref().map(|content| ItemBody {
+                content_type: "text".to_string(),
+                content: content.clone(),
+            }),
+            start: Some(DateTimeTimeZone::new(&event.start, tz)),
+            end: Some(DateTimeTimeZone::new(&event.end, tz)),
+            location: event.location.as_ref().map(|name| Location {
+                display_name: Some(name.clone()),
+            }),
+            attendees: event
+                .attendees
+                .iter()
+                .map(|address| Recipient {
+                    email_address: EmailAddress {
+                        address: Some(address.clone()),
+                    },
+                    kind: Some("required".to_string()),
+                    status: None,
+                })
+                .collect(),
+            is_all_day: event.all_day,
+            recurrence,
+            ..Default::default()
+        })
+    }
+
+    fn into_event(self) -> Option<CalendarEvent> {
+        let start = self.start.as_ref()?.to_utc()?;
+        let end = self
+            .end
+            .as_ref()
+            .and_then(DateTimeTimeZone::to_utc)
+            .unwrap_or(start);
+        Some(CalendarEvent {
+            uid: self.id,
+            summary: self.subject.unwrap_or_default(),
+            description: self
+                .body
+                .map(|b| b.content)
+                .filter(|c| !c.trim().is_empty()),
+            start,
+            end,
+            location: self
+                .location
+                .and_then(|l| l.display_name)
+                .filter(|l| !l.is_empty()),
+            organizer: self.organizer.and_then(|r| r.email_address.address),
+            attendee_status: self
+                .attendees
+                .iter()
+                .filter_map(|r| Some((r.email_address.address.clone()?, r.status()?)))
+                .collect(),
+            attendees: self
+                .attendees
+                .into_iter()
+                .filter_map(|r| r.email_address.address)
+                .collect(),
+            sequence: 0,
+            all_day: self.is_all_day,
+            rrule: None,
+            modified: self.last_modified_date_time,
+            timezone: self.original_start_time_zone.filter(|tz| tz.parse::<Tz>().is_ok()),
+            exdates: Vec::new(),
+            recurrence_id: self.original_start,
+            etag: self.etag,
+            href: None,
+        })
+    }
+}
+
+/// Convert an RRULE to a Graph `patternedRecurrence`
+fn recurrence_to_graph(rrule: &str, event: &CalendarEvent, tz: Option<Tz>) -> Result<Value> {
+    let rule = RecurrenceRule::parse(rrule)?;
+    let unsupported = || CalendarError::ParseError(format!("Microsoft Graph cannot represent RRULE {}", rrule));
+    if !rule.by_set_pos.is_empty() || rule.by_month.len() > 1 || rule.by_month_day.len() > 1 {
+        return Err(unsupported());
+    }
+
+    let local_start = match tz {
+        Some(tz) => event.start.with_timezone(&tz).date_naive(),
+        None => event.start.date_naive(),
+    };
+    let days: Vec<&str> = if rule.by_day.is_empty() {
+        vec![weekday_name(local_start.weekday())]
+    } else {
+        rule.by_day.iter().map(|(_, wd)| weekday_name(*wd)).collect()
+    };
+    let ordinals: Vec<i32> = rule.by_day.iter().filter_map(|(n, _)| *n).collect();
+    let month = rule.by_month.first().copied().unwrap_or(local_start.month());
+    let day_of_month = match rule.by_month_day.first() {
+        Some(&day) if day > 0 => day as u32,
+        Some(_) => return Err(unsupported()),
+        None => local_start.day(),
+    };
+
+    let pattern = match rule.frequency {
+        Frequency::Daily if rule.by_day.is_empty() => json!({"type": "daily"}),
+        Frequency::Daily | Frequency::Weekly => json!({
+            "type": "weekly",
+            "daysOfWeek": days,
+            "firstDayOfWeek": weekday_name(rule.week_start),
+        }),
+        Frequency::Monthly | Frequency::Yearly if !rule.by_day.is_empty() => {
+            // All BYDAY entries must share one ordinal (e.g. -1MO,-1FR)
+            let index = match ordinals.as_slice() {
+                [first, rest @ ..] if ordinals.len() == rule.by_day.len() && rest.iter().all(|n| n == first) => {
+                    week_index(*first).ok_or_else(unsupported)?
+                }
+                _ => return Err(unsupported()),
+            };
+            if rule.frequency == Frequency::Monthly {
+                json!({"type": "relativeMonthly", "daysOfWeek": days, "index": index})
+            } else {
+                json!({"type": "relativeYearly", "daysOfWeek": days, "index": index, "month": month})
+            }
+        }
+        Frequency::Monthly => json!({"type": "absoluteMonthly", "dayOfMonth": day_of_month}),
+        Frequency::Yearly => json!({"type": "absoluteYearly", "dayOfMonth": day_of_month, "month": month}),
+    };
+    let mut pattern = pattern;
+    pattern["interval"] = json!(rule.interval);
+
+    let start_date = local_start.format("%Y-%m-%d").to_string();
+    let time_zone = tz.map_or("UTC", |tz| tz.name());
+    let range = match (rule.count, rule.until) {
+        (Some(count), _) => json!({
+            "type": "numbered",
+            "startDate": start_date,
+            "numberOfOccurrences": count,
+            "recurrenceTimeZone": time_zone,
+        }),
+        (None, Some(until)) => {
+            let end_date = match tz {
+                Some(tz) => until.with_timezone(&tz).date_naive(),
+                None => until.date_naive(),
+            };
+            json!({
+                "type": "endDate",
+                "startDate": start_date,
+                "endDate": end_date.format("%Y-%m-%d").to_string(),
+                "recurrenceTimeZone": time_zone,
+            })
+        }
+        (None, None) => json!({
+            "type": "noEnd",
+            "startDate": start_date,
+            "recurrenceTimeZone": time_zone,
+        }),
+    };
+    Ok(json!({"pattern": pattern, "range": range}))
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    }
+}
+
+fn week_index(ordinal: i32) -> Option<&'static str> {
+    Some(match ordinal {
+        1 => "first",
+        2 => "second",
+        3 => "third",
+        4 => "fourth",
+        -1 => "last",
+        _ => return None,
+    })
+}
+
+/// Microsoft Graph calendar client
+pub struct GraphCalendarClient {
+    http: Client,
+    credentials: OAuthCredentials,
+    calendar_id: Option<String>,
+    base_url: String,
+}
+
+impl GraphCalendarClient {
+    /// Create a client for the user's default calendar
+    pub fn new(credentials: OAuthCredentials) -> Self {
+        Self {
+            http: Client::new(),
+            credentials,
+            calendar_id: None,
+            base_url: GRAPH_API_URL.to_string(),
+        }
+    }
+
+    /// Use another calendar (an ID from [`list_calendars`](CalendarProvider::list_calendars))
+    pub fn with_calendar_id(mut self, calendar_id: impl Into<String>) -> Self {
+        self.calendar_id = Some(calendar_id.into());
+        self
+    }
+
+    /// Override the API base URL
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    fn calendar_url(&self) -> String {
+        match self.calendar_id {
+            Some(ref id) => format!("{}/me/calendars/{}", self.base_url, id),
+            None => format!("{}/me/calendar", self.base_url),
+        }
+    }
+
+    async fn request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder> {
+        let token = self.credentials.access_token().await?;
+        Ok(self
+            .http
+            .request(method, url)
+            .bearer_auth(token)
+            .header("Prefer", PREFER))
+    }
+
+    async fn send_event(
+        &self,
+        request: reqwest::RequestBuilder,
+        event: &CalendarEvent,
+        context: &str,
+    ) -> Result<CalendarEvent> {
+        let response = request
+            .json(&GraphEvent::from_event(event)?)
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, context).await);
+        }
+        let stored: GraphEvent = response
+            .json()
+            .await
+            .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+        stored
+            .into_event()
+            .ok_or_else(|| CalendarError::ParseError("Server returned an event without a start".to_string()))
+    }
+}
+
+#[async_trait]
+impl CalendarProvider for GraphCalendarClient {
+    fn name(&self) -> &str {
+        "microsoft"
+    }
+
+    async fn list_calendars(&self) -> Result<Vec<String>> {
+        let url = format!("{}/me/calendars", self.base_url);
+        let response = self
+            .request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, "list calendars").await);
+        }
+        let page: Page<GraphCalendar> = response
+            .json()
+            .await
+            .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+        Ok(page.value.into_iter().map(|c| c.id).collect())
+    }
+
+    async fn get_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<CalendarEvent>> {
+        let mut url = reqwest::Url::parse(&format!("{}/calendarView", self.calendar_url()))
+            .map_err(|e| CalendarError::Configuration(e.to_string()))?;
+        url.query_pairs_mut()
+            .append_pair("startDateTime", &start.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+            .append_pair("endDateTime", &end.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+            .append_pair("$top", "100");
+        let mut next = Some(url.to_string());
+        let mut events = Vec::new();
+
+        while let Some(url) = next.take() {
+            debug!("Fetching events from: {}", url);
+            let response = self
+                .request(reqwest::Method::GET, &url)
+                .await?
+                .send()
+                .await
+                .map_err(|e| CalendarError::Connection(e.to_string()))?;
+            if !response.status().is_success() {
+                return Err(api_error(response, "list events").await);
+            }
+            let page: Page<GraphEvent> = response
+                .json()
+                .await
+                .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+            events.extend(
+                page.value
+                    .into_iter()
+                    .filter(|e| !e.is_cancelled)
+                    .filter_map(GraphEvent::into_event),
+            );
+            next = page.next_link;
+        }
+
+        events.sort_by_key(|e| e.start);
+        info!("Fetched {} events", events.len());
+        Ok(events)
+    }
+
+    async fn get_event(&self, uid: &str) -> Result<CalendarEvent> {
+        let url = format!("{}/me/events/{}", self.base_url, uid);
+        let response = self
+            .request(reqwest::Method::GET, &url)
+            .await?
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, uid).await);
+        }
+        let event: GraphEvent = response
+            .json()
+            .await
+            .map_err(|e| CalendarError::ParseError(e.to_string()))?;
+        event
+            .into_event()
+            .ok_or_else(|| CalendarError::EventNotFound(uid.to_string()))
+    }
+
+    async fn create_event(&self, event: CalendarEvent) -> Result<CalendarEvent> {
+        debug!("Creating event: {}", event.summary);
+        let url = format!("{}/events", self.calendar_url());
+        let request = self.request(reqwest::Method::POST, &url).await?;
+        let created = self
+            .send_event(request, &event, "create event")
+            .await
+            .map_err(|e| match e {
+                CalendarError::HttpError(message) => CalendarError::CreateError(message),
+                other => other,
+            })?;
+        info!("Created event: {:?}", created.uid);
+        Ok(created)
+    }
+
+    async fn update_event(&self, event: CalendarEvent) -> Result<CalendarEvent> {
+        let id = event
+            .uid
+            .clone()
+            .ok_or_else(|| CalendarError::UpdateError("Event has no UID".to_string()))?;
+        debug!("Updating event: {}", id);
+
+        let url = format!("{}/me/events/{}", self.base_url, id);
+        let mut request = self.request(reqwest::Method::PATCH, &url).await?;
+        if let Some(ref etag) = event.etag {
+            request = request.header("If-Match", etag);
+        }
+        let updated = self.send_event(request, &event, &id).await?;
+        info!("Updated event: {}", id);
+        Ok(updated)
+    }
+
+    async fn delete_event(&self, uid: &str) -> Result<()> {
+        debug!("Deleting event: {}", uid);
+        let url = format!("{}/me/events/{}", self.base_url, uid);
+        let response = self
+            .request(reqwest::Method::DELETE, &url)
+            .await?
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(api_error(response, uid).await);
+        }
+        info!("Deleted event: {}", uid);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, header_exists, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn client(server: &MockServer) -> GraphCalendarClient {
+        GraphCalendarClient::new(OAuthCredentials::access_token_only("token")).with_base_url(server.uri())
+    }
+
+    #[tokio::test]
+    async fn test_calendar_view_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/me/calendar/calendarView"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "value": [{
+                    "id": "b",
+                    "subject": "Lunch",
+                    "start": {"dateTime": "2024-03-01T11:00:00.0000000", "timeZone": "UTC"},
+                    "end": {"dateTime": "2024-03-01T12:00:00.0000000", "timeZone": "UTC"}
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/me/calendar/calendarView"))
+            .and(header_exists("Prefer"))
+            .and(query_param("startDateTime", "2024-03-01T00:00:00Z"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "value": [{
+                    "id": "a",
+                    "@odata.etag": "W/\"1\"",
+                    "subject": "Standup",
+                    "body": {"contentType": "text", "content": "Daily sync"},
+                    "start": {"dateTime": "2024-03-01T08:00:00.0000000", "timeZone": "UTC"},
+                    "end": {"dateTime": "2024-03-01T08:15:00.0000000", "timeZone": "UTC"},
+                    "location": {"displayName": "Room 1"},
+                    "organizer": {"emailAddress": {"address": "bob@example.com"}},
+                    "attendees": [{"emailAddress": {"address": "ann@example.com"}, "type": "required"}],
+                    "originalStart": "2024-03-01T08:00:00Z",
+                    "originalStartTimeZone": "Europe/Berlin"
+                }],
+                "@odata.nextLink": format!("{}/me/calendar/calendarView?page=2", server.uri())
+            })))
+            .mount(&server)
+            .await;
+
+        let events = client(&server)
+            .get_events(utc("2024-03-01T00:00:00Z"), utc("2024-03-02T00:00:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].summary, "Standup");
+        assert_eq!(events[0].etag.as_deref(), Some("W/\"1\""));
+        assert_eq!(events[0].description.as_deref(), Some("Daily sync"));
+        assert_eq!(events[0].location.as_deref(), Some("Room 1"));
+        assert_eq!(events[0].attendees, vec!["ann@example.com"]);
+        assert_eq!(events[0].recurrence_id, Some(utc("2024-03-01T08:00:00Z")));
+        assert_eq!(events[0].timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(events[1].start, utc("2024-03-01T11:00:00Z"));
+    }
+
+    #[tokio::test]
+    async fn test_create_recurring_event() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/me/calendars/work/events"))
+            .and(body_partial_json(json!({
+                "subject": "Review",
+                "start": {"dateTime": "2024-07-01T09:00:00", "timeZone": "Europe/Berlin"},
+                "recurrence": {
+                    "pattern": {"type": "relativeMonthly", "daysOfWeek": ["monday"], "index": "first", "interval": 1},
+                    "range": {"type": "numbered", "startDate": "2024-07-01", "numberOfOccurrences": 6}
+                }
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": "new-id",
+                "subject": "Review",
+                "start": {"dateTime": "2024-07-01T07:00:00.0000000", "timeZone": "UTC"},
+                "end": {"dateTime": "2024-07-01T08:00:00.0000000", "timeZone": "UTC"}
+            })))
+            .mount(&server)
+            .await;
+
+        let event = CalendarEvent::new("Review", utc("2024-07-01T07:00:00Z"), utc("2024-07-01T08:00:00Z"))
+            .with_rrule("FREQ=MONTHLY;BYDAY=1MO;COUNT=6")
+            .with_timezone("Europe/Berlin");
+        let created = client(&server)
+            .with_calendar_id("work")
+            .create_event(event)
+            .await
+            .unwrap();
+        assert_eq!(created.uid.as_deref(), Some("new-id"));
+    }
+
+    #[test]
+    fn test_unsupported_recurrence() {
+        let event = CalendarEvent::default();
+        assert!(recurrence_to_graph("FREQ=MONTHLY;BYDAY=MO,TU;BYSETPOS=-1", &event, None).is_err());
+        assert!(recurrence_to_graph("FREQ=MONTHLY;BYDAY=1MO,-1FR", &event, None).is_err());
+        assert!(recurrence_to_graph("FREQ=WEEKLY;BYDAY=MO,FR", &event, None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_update_conflict() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(path("/me/events/ev1"))
+            .and(header("If-Match", "W/\"old\""))
+            .respond_with(ResponseTemplate::new(412))
+            .mount(&server)
+            .await;
+
+        let mut event = CalendarEvent::new("Moved", utc("2024-07-01T07:00:00Z"), utc("2024-07-01T08:00:00Z"));
+        event.uid = Some("ev1".to_string());
+        event.etag = Some("W/\"old\"".to_string());
+        let err = client(&server).update_event(event).await.unwrap_err();
+        assert!(matches!(err, CalendarError::Conflict(_)));
+    }
+}
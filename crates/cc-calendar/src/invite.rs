@@ -0,0 +1,248 @@
+//! Meeting invitations by email (iMIP, RFC 6047)
+//!
+//! [`InviteMailer`] keeps the organizer's copy of an event in a
+//! [`CalendarProvider`] and mails the iTIP messages to the attendees.
+//! Attendee replies are read back from incoming mail and recorded on the
+//! event.
+//!
+//! Microsoft 365 mails attendees of events with attendees by itself; use the
+//! mailer with CalDAV or Google calendars.
+
+use std::sync::Arc;
+
+use cc_email::{EmailSender, OutgoingEmail, ParsedEmail};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use tracing::info;
+
+use crate::error::{CalendarError, Result};
+use crate::itip::{self, ItipMessage, ItipMethod};
+use crate::models::{CalendarEvent, ParticipationStatus};
+use crate::provider::CalendarProvider;
+
+/// Result of processing an attendee's reply
+#[derive(Debug, Clone)]
+pub struct ReplyOutcome {
+    /// The event after recording the reply
+    pub event: CalendarEvent,
+    /// Attendees whose status changed
+    pub changes: Vec<(String, ParticipationStatus)>,
+}
+
+/// Sends invitations for events and records the replies
+pub struct InviteMailer {
+    provider: Arc<dyn CalendarProvider>,
+    sender: EmailSender,
+}
+
+impl InviteMailer {
+    pub fn new(provider: Arc<dyn CalendarProvider>, sender: EmailSender) -> Self {
+        Self { provider, sender }
+    }
+
+    /// Organizer address of the invitations (the sender address)
+    pub fn organizer(&self) -> &str {
+        self.sender.from_address()
+    }
+
+    /// Create `event` with the sender as organizer and invite its attendees
+    pub async fn invite(&self, mut event: CalendarEvent) -> Result<CalendarEvent> {
+        event.organizer = Some(self.organizer().to_string());
+        event.attendee_status.clear();
+        event.sequence = 0;
+        // Check before creating the event so a bad invitation leaves nothing behind
+        invitation_email(&with_placeholder_uid(&event), ItipMethod::Request)?;
+
+        let mut created = self.provider.create_event(event).await?;
+        created.organizer.get_or_insert_with(|| self.organizer().to_string());
+        self.send(&created, ItipMethod::Request).await?;
+        info!("Invited {} attendees to {}", created.attendees.len(), created.summary);
+        Ok(created)
+    }
+
+    /// Save changes to an invited event and send the attendees an update
+    pub async fn update(&self, mut event: CalendarEvent) -> Result<CalendarEvent> {
+        event.sequence += 1;
+        let mut updated = self.provider.update_event(event).await?;
+        updated.organizer.get_or_insert_with(|| self.organizer().to_string());
+        self.send(&updated, ItipMethod::Request).await?;
+        Ok(updated)
+    }
+
+    /// The organizer's copy of the event `uid`
+    pub async fn event(&self, uid: &str) -> Result<CalendarEvent> {
+        self.provider.get_event(uid).await
+    }
+
+    /// Tell the attendees the event is cancelled and delete it
+    pub async fn cancel(&self, uid: &str) -> Result<CalendarEvent> {
+        let mut event = self.provider.get_event(uid).await?;
+        event.sequence += 1;
+        event.organizer.get_or_insert_with(|| self.organizer().to_string());
+        self.send(&event, ItipMethod::Cancel).await?;
+        self.provider.delete_event(uid).await?;
+        info!("Cancelled {}", event.summary);
+        Ok(event)
+    }
+
+    /// Record the response in an attendee's reply email on the event
+    ///
+    /// Only the sender's own response is accepted.
+    pub async fn process_reply(&self, email: &ParsedEmail) -> Result<ReplyOutcome> {
+        let ics = email
+            .calendar
+            .as_deref()
+            .ok_or_else(|| CalendarError::Scheduling("Email has no calendar reply".to_string()))?;
+        let reply = ItipMessage::parse(ics)?;
+
+        let mut event = self.provider.get_event(reply.uid()).await?;
+        let organizer = event.organizer.as_deref().unwrap_or(self.organizer());
+        if !organizer.eq_ignore_ascii_case(self.organizer()) {
+            return Err(CalendarError::Scheduling(format!(
+                "Event {} is organized by {}",
+                reply.uid(),
+                organizer
+            )));
+        }
+
+        let changes = itip::apply_reply(&mut event, &reply, email.from.as_deref())?;
+        if !changes.is_empty() {
+            event = self.provider.update_event(event).await?;
+            for (attendee, status) in &changes {
+                info!("{} answered {} for {}", attendee, status.as_str(), event.summary);
+            }
+        }
+        Ok(ReplyOutcome { event, changes })
+    }
+
+    async fn send(&self, event: &CalendarEvent, method: ItipMethod) -> Result<()> {
+        let email = invitation_email(event, method)?;
+        self.sender
+            .send_message(&email)
+            .await
+            .map_err(|e| CalendarError::Scheduling(format!("Failed to send {}: {}", method.as_str(), e)))?;
+        Ok(())
+    }
+}
+
+/// The email carrying an invitation, update or cancellation of `event`
+pub fn invitation_email(event: &CalendarEvent, method: ItipMethod) -> Result<OutgoingEmail> {
+    let ics = match method {
+        ItipMethod::Request => itip::request(event)?,
+        ItipMethod::Cancel => itip::cancel(event)?,
+        ItipMethod::Reply => {
+            return Err(CalendarError::Scheduling(
+                "Replies are sent by attendees, not the organizer".to_string(),
+            ));
+        }
+    };
+    let to: Vec<String> = event
+        .attendees
+        .iter()
+        .filter(|a| !event.organizer.as_deref().is_some_and(|o| o.eq_ignore_ascii_case(a)))
+        .cloned()
+        .collect();
+    if to.is_empty() {
+        return Err(CalendarError::Scheduling("Event has no attendees to invite".to_string()));
+    }
+
+    let subject = match method {
+        ItipMethod::Cancel => format!("Cancelled: {}", event.summary),
+        _ if event.sequence > 0 => format!("Updated invitation: {}", event.summary),
+        _ => format!("Invitation: {}", event.summary),
+    };
+    let mut body = vec![event.summary.clone(), format!("When: {}", when(event))];
+    if let Some(ref location) = event.location {
+        body.push(format!("Where: {}", location));
+    }
+    if let Some(ref organizer) = event.organizer {
+        body.push(format!("Organizer: {}", organizer));
+    }
+    if let Some(ref description) = event.description {
+        body.push(String::new());
+        body.push(description.clone());
+    }
+
+    Ok(OutgoingEmail {
+        to,
+        subject,
+        body: body.join("\n"),
+        ..Default::default()
+    }
+    .calendar(method.as_str(), ics))
+}
+
+/// Start and end in the event's time zone
+fn when(event: &CalendarEvent) -> String {
+    if event.all_day {
+        let last_day = event.end - chrono::Duration::days(1);
+        return if last_day.date_naive() > event.start.date_naive() {
+            format!("{} - {} (all day)", event.start.format("%Y-%m-%d"), last_day.format("%Y-%m-%d"))
+        } else {
+            format!("{} (all day)", event.start.format("%Y-%m-%d"))
+        };
+    }
+    let tz = event.timezone.as_deref().and_then(|tz| tz.parse::<Tz>().ok());
+    let format = |dt: &DateTime<Utc>, pattern: &str| match tz {
+        Some(tz) => dt.with_timezone(&tz).format(pattern).to_string(),
+        None => dt.format(pattern).to_string(),
+    };
+    let end_pattern = if event.start.date_naive() == event.end.date_naive() { "%H:%M" } else { "%Y-%m-%d %H:%M" };
+    format!(
+        "{} - {} ({})",
+        format(&event.start, "%Y-%m-%d %H:%M"),
+        format(&event.end, end_pattern),
+        tz.map_or("UTC", |tz| tz.name())
+    )
+}
+
+/// A copy with a UID, for validating an invitation before the event exists
+fn with_placeholder_uid(event: &CalendarEvent) -> CalendarEvent {
+    let mut event = event.clone();
+    event.uid.get_or_insert_with(|| "new".to_string());
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meeting() -> CalendarEvent {
+        let start = DateTime::parse_from_rfc3339("2024-05-02T13:00:00Z").unwrap().with_timezone(&Utc);
+        let mut event = CalendarEvent::new("Planning", start, start + chrono::Duration::minutes(30))
+            .with_location("Room 4")
+            .with_timezone("Europe/Berlin");
+        event.uid = Some("plan-1".to_string());
+        event.organizer = Some("bot@example.com".to_string());
+        event.attendees = vec!["ann@example.com".to_string(), "bot@example.com".to_string()];
+        event
+    }
+
+    #[test]
+    fn test_invitation_email() {
+        let email = invitation_email(&meeting(), ItipMethod::Request).unwrap();
+        assert_eq!(email.to, vec!["ann@example.com"]);
+        assert_eq!(email.subject, "Invitation: Planning");
+        assert!(email.body.contains("When: 2024-05-02 15:00 - 15:30 (Europe/Berlin)"));
+        assert!(email.body.contains("Where: Room 4"));
+        let calendar = email.calendar.as_ref().unwrap();
+        assert_eq!(calendar.method, "REQUEST");
+        assert!(calendar.data.contains("UID:plan-1\r\n"));
+
+        // The attendee's mail client sees the invitation
+        let raw = email.to_rfc5322("bot@example.com", None, "<i@example.com>");
+        let received = ParsedEmail::parse(raw.as_bytes());
+        let message = ItipMessage::parse(received.calendar.as_deref().unwrap()).unwrap();
+        assert_eq!(message.method, ItipMethod::Request);
+
+        let mut updated = meeting();
+        updated.sequence = 1;
+        let email = invitation_email(&updated, ItipMethod::Cancel).unwrap();
+        assert_eq!(email.subject, "Cancelled: Planning");
+        assert!(email.calendar.unwrap().data.contains("STATUS:CANCELLED"));
+
+        let mut alone = meeting();
+        alone.attendees = vec!["bot@example.com".to_string()];
+        assert!(invitation_email(&alone, ItipMethod::Request).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! iTIP scheduling messages (RFC 5546)
+//!
+//! Builds and reads the iCalendar objects exchanged when inviting attendees:
+//! `REQUEST` (an invitation or an update), `REPLY` (an attendee's answer) and
+//! `CANCEL`. See [`crate::invite`] for sending them by email.
+
+use crate::client::{event_to_ical, parse_icalendar, split_property};
+use crate::error::{CalendarError, Result};
+use crate::models::{CalendarEvent, ParticipationStatus};
+
+/// iTIP method of a scheduling message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItipMethod {
+    Request,
+    Reply,
+    Cancel,
+}
+
+impl ItipMethod {
+    /// iCalendar METHOD value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Request => "REQUEST",
+            Self::Reply => "REPLY",
+            Self::Cancel => "CANCEL",
+        }
+    }
+
+    /// Parse a METHOD value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "REQUEST" => Some(Self::Request),
+            "REPLY" => Some(Self::Reply),
+            "CANCEL" => Some(Self::Cancel),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed scheduling message
+#[derive(Debug, Clone)]
+pub struct ItipMessage {
+    pub method: ItipMethod,
+    /// The event and any overridden occurrences it carries
+    pub events: Vec<CalendarEvent>,
+}
+
+impl ItipMessage {
+    /// Parse an iCalendar object with a METHOD
+    pub fn parse(ics: &str) -> Result<Self> {
+        let method = ics
+            .lines()
+            .filter_map(|line| split_property(line.trim_end_matches('\r')))
+            .find(|(name, _, _)| name == "METHOD")
+            .map(|(_, _, value)| value.to_string())
+            .ok_or_else(|| CalendarError::Scheduling("Calendar object has no METHOD".to_string()))?;
+        let method = ItipMethod::parse(&method)
+            .ok_or_else(|| CalendarError::Scheduling(format!("Unsupported iTIP method: {}", method)))?;
+
+        let events: Vec<CalendarEvent> = parse_icalendar(ics)
+            .into_iter()
+            .filter(|e| e.uid.is_some())
+            .collect();
+        if events.is_empty() {
+            return Err(CalendarError::Scheduling("Calendar object has no event with a UID".to_string()));
+        }
+        Ok(Self { method, events })
+    }
+
+    /// UID of the event the message is about
+    pub fn uid(&self) -> &str {
+        self.events[0].uid.as_deref().unwrap_or_default()
+    }
+
+    /// The series (or single event), without overridden occurrences
+    pub fn event(&self) -> Option<&CalendarEvent> {
+        self.events.iter().find(|e| e.recurrence_id.is_none())
+    }
+}
+
+/// An invitation (or updated invitation) for every attendee of `event`
+pub fn request(event: &CalendarEvent) -> Result<String> {
+    let uid = scheduling_uid(event)?;
+    Ok(event_to_ical(event, uid, Some(ItipMethod::Request.as_str())))
+}
+
+/// A cancellation of `event`
+pub fn cancel(event: &CalendarEvent) -> Result<String> {
+    let uid = scheduling_uid(event)?;
+    Ok(event_to_ical(event, uid, Some(ItipMethod::Cancel.as_str())))
+}
+
+/// `attendee`'s answer to an invitation
+pub fn reply(event: &CalendarEvent, attendee: &str, status: ParticipationStatus) -> Result<String> {
+    let uid = scheduling_uid(event)?;
+    let mut reply = event.clone();
+    reply.attendees.retain(|a| a.eq_ignore_ascii_case(attendee));
+    if !reply.set_status(attendee, status) {
+        return Err(CalendarError::Scheduling(format!("{} is not invited to {}", attendee, uid)));
+    }
+    Ok(event_to_ical(&reply, uid, Some(ItipMethod::Reply.as_str())))
+}
+
+/// Record the responses of a `REPLY` on the organizer's copy of the event
+///
+/// With `sender` set, only that attendee's response is accepted (replies
+/// must come from the attendee they answer for). Returns the attendees whose
+/// status changed.
+pub fn apply_reply(
+    event: &mut CalendarEvent,
+    reply: &ItipMessage,
+    sender: Option<&str>,
+) -> Result<Vec<(String, ParticipationStatus)>> {
+    if reply.method != ItipMethod::Reply {
+        return Err(CalendarError::Scheduling(format!(
+            "Expected a REPLY, got {}",
+            reply.method.as_str()
+        )));
+    }
+    if event.uid.as_deref() != Some(reply.uid()) {
+        return Err(CalendarError::Scheduling(format!(
+            "Reply is for event {}, not {}",
+            reply.uid(),
+            event.uid.as_deref().unwrap_or_default()
+        )));
+    }
+    let answer = reply.event().ok_or_else(|| {
+        CalendarError::Scheduling("Replies to single occurrences are not supported".to_string())
+    })?;
+    if answer.sequence < event.sequence {
+        return Err(CalendarError::Scheduling(format!(
+            "Reply answers revision {} of the event, which is now at revision {}",
+            answer.sequence, event.sequence
+        )));
+    }
+
+    let mut changed = Vec::new();
+    for attendee in &answer.attendees {
+        if sender.is_some_and(|s| !s.eq_ignore_ascii_case(attendee)) {
+            continue;
+        }
+        let status = answer.status_of(attendee);
+        if event.status_of(attendee) != status && event.set_status(attendee, status) {
+            changed.push((attendee.clone(), status));
+        }
+    }
+    let invited = answer
+        .attendees
+        .iter()
+        .any(|a| event.attendees.iter().any(|b| b.eq_ignore_ascii_case(a)));
+    if !invited {
+        return Err(CalendarError::Scheduling(format!(
+            "Reply from {} does not answer for an invited attendee",
+            answer.attendees.join(", ")
+        )));
+    }
+    Ok(changed)
+}
+
+fn scheduling_uid(event: &CalendarEvent) -> Result<&str> {
+    if event.organizer.is_none() {
+        return Err(CalendarError::Scheduling("Event has no organizer".to_string()));
+    }
+    event
+        .uid
+        .as_deref()
+        .ok_or_else(|| CalendarError::Scheduling("Event has no UID".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn meeting() -> CalendarEvent {
+        let mut event = CalendarEvent::new("Planning", utc("2024-05-02T13:00:00Z"), utc("2024-05-02T14:00:00Z"));
+        event.uid = Some("plan-1".to_string());
+        event.organizer = Some("bot@example.com".to_string());
+        event.attendees = vec!["ann@example.com".to_string(), "bob@example.com".to_string()];
+        event.sequence = 1;
+        event
+    }
+
+    #[test]
+    fn test_request_and_cancel() {
+        let ics = request(&meeting()).unwrap();
+        assert!(ics.contains("METHOD:REQUEST\r\n"));
+        assert!(ics.contains("ORGANIZER:mailto:bot@example.com\r\n"));
+        assert!(ics.contains("SEQUENCE:1\r\n"));
+        assert!(ics.contains("PARTSTAT=NEEDS-ACTION;RSVP=TRUE:mailto:ann@example.com\r\n"));
+        let parsed = ItipMessage::parse(&ics).unwrap();
+        assert_eq!(parsed.method, ItipMethod::Request);
+        assert_eq!(parsed.uid(), "plan-1");
+
+        let ics = cancel(&meeting()).unwrap();
+        assert!(ics.contains("METHOD:CANCEL\r\n"));
+        assert!(ics.contains("STATUS:CANCELLED\r\n"));
+
+        let mut no_organizer = meeting();
+        no_organizer.organizer = None;
+        assert!(request(&no_organizer).is_err());
+    }
+
+    #[test]
+    fn test_apply_reply() {
+        let ics = reply(&meeting(), "ANN@example.com", ParticipationStatus::Accepted).unwrap();
+        assert!(ics.contains("METHOD:REPLY\r\n"));
+        assert!(!ics.contains("bob@example.com"));
+        let message = ItipMessage::parse(&ics).unwrap();
+
+        let mut event = meeting();
+        let changed = apply_reply(&mut event, &message, Some("ann@example.com")).unwrap();
+        assert_eq!(changed, vec![("ann@example.com".to_string(), ParticipationStatus::Accepted)]);
+        assert_eq!(event.status_of("ann@example.com"), ParticipationStatus::Accepted);
+        assert_eq!(event.status_of("bob@example.com"), ParticipationStatus::NeedsAction);
+
+        // Applying the same reply again changes nothing
+        assert!(apply_reply(&mut event, &message, None).unwrap().is_empty());
+
+        // Someone else can't answer for Ann
+        let mut event = meeting();
+        assert!(apply_reply(&mut event, &message, Some("mallory@example.com")).unwrap().is_empty());
+        assert_eq!(event.status_of("ann@example.com"), ParticipationStatus::NeedsAction);
+    }
+
+    #[test]
+    fn test_reject_stale_or_foreign_replies() {
+        let message = ItipMessage::parse(&reply(&meeting(), "bob@example.com", ParticipationStatus::Declined).unwrap()).unwrap();
+
+        let mut updated = meeting();
+        updated.sequence = 2;
+        assert!(apply_reply(&mut updated, &message, None).is_err());
+
+        let mut other = meeting();
+        other.uid = Some("other".to_string());
+        assert!(apply_reply(&mut other, &message, None).is_err());
+
+        let mut uninvited = meeting();
+        uninvited.attendees = vec!["ann@example.com".to_string()];
+        assert!(apply_reply(&mut uninvited, &message, None).is_err());
+
+        let request = ItipMessage::parse(&request(&meeting()).unwrap()).unwrap();
+        assert!(apply_reply(&mut meeting(), &request, None).is_err());
+        assert!(ItipMessage::parse("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n").is_err());
+    }
+}
@@ -4,9 +4,13 @@
 //!
 //! ## Features
 //!
-//! - CalDAV client for calendar access
-//! - Event creation, retrieval, and deletion
-//! - Support for multiple calendar providers
+//! - CalDAV, Google Calendar and Microsoft Graph backends behind the
+//!   [`CalendarProvider`] trait
+//! - Event creation, retrieval, ETag-aware updates, and deletion
+//! - Expansion of recurring events (RRULE / EXDATE) into occurrences
+//! - Meeting invitations by email (iTIP / iMIP) with attendee replies
+//!   recorded on the event
+//! - Agent tools (`calendar_list_events`, `calendar_create_event`, ...)
 //!
 //! ## Usage
 //!
@@ -41,13 +45,33 @@
 
 pub mod client;
 pub mod error;
+pub mod google;
+pub mod graph;
+pub mod invite;
+pub mod itip;
 pub mod models;
+pub mod oauth;
+pub mod provider;
+pub mod recurrence;
+pub mod tools;
 
 pub use client::CalendarClient;
 pub use error::{CalendarError, Result};
-pub use models::{CalendarConfig, CalendarEvent};
+pub use google::GoogleCalendarClient;
+pub use graph::GraphCalendarClient;
+pub use invite::{InviteMailer, ReplyOutcome};
+pub use itip::{ItipMessage, ItipMethod};
+pub use models::{CalendarConfig, CalendarEvent, ParticipationStatus};
+pub use oauth::OAuthCredentials;
+pub use provider::{CalendarProvider, provider_from_env};
+pub use recurrence::{RecurrenceRule, expand_event, expand_events};
+pub use tools::{
+    CalendarCancelInviteTool, CalendarCreateEventTool, CalendarDeleteEventTool, CalendarListEventsTool,
+    CalendarProcessReplyTool, CalendarSendInviteTool, CalendarUpdateEventTool, register_calendar_tools,
+    register_invite_tools,
+};
 
 /// Re-export models for easy use
 pub mod prelude {
-    pub use super::{CalendarClient, CalendarConfig, CalendarEvent};
+    pub use super::{CalendarClient, CalendarConfig, CalendarEvent, CalendarProvider};
 }
@@ -1,5 +1,7 @@
 //! Data models for calendar integration
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -38,7 +40,7 @@ impl CalendarConfig {
 /// Calendar event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalendarEvent {
-    /// Event unique identifier (ETag)
+    /// Event unique identifier (iCalendar UID)
     #[serde(default)]
     pub uid: Option<String>,
     /// Event summary/title
@@ -59,6 +61,13 @@ pub struct CalendarEvent {
     /// Event attendees
     #[serde(default)]
     pub attendees: Vec<String>,
+    /// Attendee responses by address (attendees without an entry have not
+    /// responded)
+    #[serde(default)]
+    pub attendee_status: BTreeMap<String, ParticipationStatus>,
+    /// Revision number of the scheduling data (iCalendar SEQUENCE)
+    #[serde(default)]
+    pub sequence: u32,
     /// All-day event flag
     #[serde(default)]
     pub all_day: bool,
@@ -68,6 +77,21 @@ pub struct CalendarEvent {
     /// Last modification time
     #[serde(default)]
     pub modified: Option<DateTime<Utc>>,
+    /// Time zone of the start/end times (IANA name from TZID)
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Excluded occurrence start times (EXDATE)
+    #[serde(default)]
+    pub exdates: Vec<DateTime<Utc>>,
+    /// Original start of this occurrence (RECURRENCE-ID)
+    #[serde(default)]
+    pub recurrence_id: Option<DateTime<Utc>>,
+    /// Server ETag of the calendar object, used for conditional updates
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// Server path of the calendar object
+    #[serde(default)]
+    pub href: Option<String>,
 }
 
 impl Default for CalendarEvent {
@@ -82,9 +106,16 @@ impl Default for CalendarEvent {
             location: None,
             organizer: None,
             attendees: Vec::new(),
+            attendee_status: BTreeMap::new(),
+            sequence: 0,
             all_day: false,
             rrule: None,
             modified: None,
+            timezone: None,
+            exdates: Vec::new(),
+            recurrence_id: None,
+            etag: None,
+            href: None,
         }
     }
 }
@@ -111,4 +142,76 @@ impl CalendarEvent {
         self.location = Some(location.into());
         self
     }
+
+    /// Set the recurrence rule (e.g. `FREQ=WEEKLY;BYDAY=MO`)
+    pub fn with_rrule(mut self, rrule: impl Into<String>) -> Self {
+        self.rrule = Some(rrule.into());
+        self
+    }
+
+    /// Set the time zone recurrences are evaluated in
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Whether this event repeats
+    pub fn is_recurring(&self) -> bool {
+        self.rrule.is_some()
+    }
+
+    /// Response of an attendee (case-insensitive address match)
+    pub fn status_of(&self, attendee: &str) -> ParticipationStatus {
+        self.attendee_status
+            .iter()
+            .find(|(address, _)| address.eq_ignore_ascii_case(attendee))
+            .map_or(ParticipationStatus::NeedsAction, |(_, status)| *status)
+    }
+
+    /// Record an attendee's response; returns `false` if `attendee` is not
+    /// invited
+    pub fn set_status(&mut self, attendee: &str, status: ParticipationStatus) -> bool {
+        let Some(address) = self.attendees.iter().find(|a| a.eq_ignore_ascii_case(attendee)) else {
+            return false;
+        };
+        self.attendee_status.insert(address.clone(), status);
+        true
+    }
+}
+
+/// An attendee's response to an invitation (iCalendar PARTSTAT)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipationStatus {
+    #[default]
+    NeedsAction,
+    Accepted,
+    Declined,
+    Tentative,
+    Delegated,
+}
+
+impl ParticipationStatus {
+    /// iCalendar PARTSTAT value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NeedsAction => "NEEDS-ACTION",
+            Self::Accepted => "ACCEPTED",
+            Self::Declined => "DECLINED",
+            Self::Tentative => "TENTATIVE",
+            Self::Delegated => "DELEGATED",
+        }
+    }
+
+    /// Parse a PARTSTAT value (also accepts `needs_action` style names)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().replace('_', "-").as_str() {
+            "NEEDS-ACTION" => Some(Self::NeedsAction),
+            "ACCEPTED" => Some(Self::Accepted),
+            "DECLINED" => Some(Self::Declined),
+            "TENTATIVE" => Some(Self::Tentative),
+            "DELEGATED" => Some(Self::Delegated),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,222 @@
+//! OAuth2 access tokens for the Google Calendar and Microsoft Graph backends
+//!
+//! Tokens are refreshed from a long-lived refresh token (obtained once with
+//! the provider's consent flow) and cached in memory until shortly before
+//! they expire.
+
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::error::{CalendarError, Result};
+
+/// Refresh tokens this long before they expire
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// Google Calendar scope
+pub const GOOGLE_CALENDAR_SCOPE: &str = "https://www.googleapis.com/auth/calendar";
+/// Microsoft Graph calendar scope
+pub const GRAPH_CALENDAR_SCOPE: &str = "https://graph.microsoft.com/Calendars.ReadWrite offline_access";
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    expires_in: Option<i64>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// OAuth2 refresh-token credentials
+pub struct OAuthCredentials {
+    http: Client,
+    token_url: String,
+    client_id: String,
+    client_secret: Option<String>,
+    refresh_token: Option<String>,
+    scope: Option<String>,
+    token: Mutex<CachedToken>,
+}
+
+impl std::fmt::Debug for OAuthCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuthCredentials")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OAuthCredentials {
+    /// Credentials for any OAuth2 token endpoint
+    pub fn new(
+        token_url: impl Into<String>,
+        client_id: impl Into<String>,
+        refresh_token: impl Into<String>,
+    ) -> Self {
+        Self {
+            http: Client::new(),
+            token_url: token_url.into(),
+            client_id: client_id.into(),
+            client_secret: None,
+            refresh_token: Some(refresh_token.into()),
+            scope: None,
+            token: Mutex::new(CachedToken::default()),
+        }
+    }
+
+    /// Credentials for a Google account
+    pub fn google(client_id: impl Into<String>, refresh_token: impl Into<String>) -> Self {
+        Self::new("https://oauth2.googleapis.com/token", client_id, refresh_token)
+            .with_scope(GOOGLE_CALENDAR_SCOPE)
+    }
+
+    /// Credentials for a Microsoft 365 / Outlook.com account (`tenant` is a
+    /// tenant ID, `common` or `consumers`)
+    pub fn microsoft(
+        tenant: &str,
+        client_id: impl Into<String>,
+        refresh_token: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            format!("https://login.microsoftonline.com/{}/oauth2/v2.0/token", tenant),
+            client_id,
+            refresh_token,
+        )
+        .with_scope(GRAPH_CALENDAR_SCOPE)
+    }
+
+    /// A fixed access token that is never refreshed (for tests and
+    /// short-lived scripts)
+    pub fn access_token_only(access_token: impl Into<String>) -> Self {
+        Self {
+            http: Client::new(),
+            token_url: String::new(),
+            client_id: String::new(),
+            client_secret: None,
+            refresh_token: None,
+            scope: None,
+            token: Mutex::new(CachedToken {
+                access_token: access_token.into(),
+                expires_at: None,
+            }),
+        }
+    }
+
+    /// Set the client secret (confidential clients)
+    pub fn with_client_secret(mut self, secret: impl Into<String>) -> Self {
+        self.client_secret = Some(secret.into());
+        self
+    }
+
+    /// Set the requested scope
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    /// A valid access token, refreshed if needed
+    pub async fn access_token(&self) -> Result<String> {
+        let mut token = self.token.lock().await;
+        let expiring = token.access_token.is_empty()
+            || token
+                .expires_at
+                .is_some_and(|at| at - Duration::seconds(EXPIRY_MARGIN_SECS) <= Utc::now());
+        if !expiring {
+            return Ok(token.access_token.clone());
+        }
+        let Some(ref refresh_token) = self.refresh_token else {
+            return Err(CalendarError::Authentication(
+                "Access token expired and no refresh token is configured".to_string(),
+            ));
+        };
+
+        debug!("Refreshing calendar access token");
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("client_id", self.client_id.as_str()),
+            ("refresh_token", refresh_token.as_str()),
+        ];
+        if let Some(ref secret) = self.client_secret {
+            form.push(("client_secret", secret));
+        }
+        if let Some(ref scope) = self.scope {
+            form.push(("scope", scope));
+        }
+
+        let response: TokenResponse = self
+            .http
+            .post(&self.token_url)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| CalendarError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| CalendarError::Authentication(e.to_string()))?;
+
+        let Some(access_token) = response.access_token else {
+            return Err(CalendarError::Authentication(format!(
+                "Token refresh failed: {}",
+                response
+                    .error_description
+                    .or(response.error)
+                    .unwrap_or_else(|| "no access token returned".to_string())
+            )));
+        };
+        *token = CachedToken {
+            access_token: access_token.clone(),
+            expires_at: response.expires_in.map(|s| Utc::now() + Duration::seconds(s)),
+        };
+        Ok(access_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_refresh_and_cache() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .and(body_string_contains("refresh_token=refresh"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "fresh",
+                "expires_in": 3600
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let credentials = OAuthCredentials::new(format!("{}/token", server.uri()), "client", "refresh");
+        assert_eq!(credentials.access_token().await.unwrap(), "fresh");
+        assert_eq!(credentials.access_token().await.unwrap(), "fresh");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "invalid_grant",
+                "error_description": "Token has been revoked"
+            })))
+            .mount(&server)
+            .await;
+
+        let credentials = OAuthCredentials::new(format!("{}/token", server.uri()), "client", "refresh");
+        let err = credentials.access_token().await.unwrap_err();
+        assert!(err.to_string().contains("Token has been revoked"));
+    }
+}
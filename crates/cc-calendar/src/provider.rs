@@ -0,0 +1,165 @@
+//! Calendar backends behind a common interface
+//!
+//! CalDAV ([`CalendarClient`]), Google Calendar ([`GoogleCalendarClient`])
+//! and Microsoft Graph ([`GraphCalendarClient`]) all read and write the same
+//! [`CalendarEvent`] model, so callers don't care which server hosts the
+//! calendar.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+
+use crate::client::CalendarClient;
+use crate::error::{CalendarError, Result};
+use crate::google::GoogleCalendarClient;
+use crate::graph::GraphCalendarClient;
+use crate::models::{CalendarConfig, CalendarEvent};
+use crate::oauth::OAuthCredentials;
+
+/// A calendar backend
+#[async_trait]
+pub trait CalendarProvider: Send + Sync {
+    /// Backend name (`caldav`, `google`, `microsoft`)
+    fn name(&self) -> &str;
+
+    /// Calendar identifiers available to the account
+    async fn list_calendars(&self) -> Result<Vec<String>>;
+
+    /// Events overlapping `[start, end)`, with recurring events expanded into
+    /// occurrences
+    async fn get_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<CalendarEvent>>;
+
+    /// A single event by UID
+    async fn get_event(&self, uid: &str) -> Result<CalendarEvent>;
+
+    /// Create an event and return it with its server identifiers
+    async fn create_event(&self, event: CalendarEvent) -> Result<CalendarEvent>;
+
+    /// Update an event; fails with [`CalendarError::Conflict`] when its ETag
+    /// no longer matches the server copy
+    async fn update_event(&self, event: CalendarEvent) -> Result<CalendarEvent>;
+
+    /// Delete an event by UID
+    async fn delete_event(&self, uid: &str) -> Result<()>;
+}
+
+#[async_trait]
+impl CalendarProvider for CalendarClient {
+    fn name(&self) -> &str {
+        "caldav"
+    }
+
+    async fn list_calendars(&self) -> Result<Vec<String>> {
+        CalendarClient::list_calendars(self).await
+    }
+
+    async fn get_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<CalendarEvent>> {
+        CalendarClient::get_events(self, start, end).await
+    }
+
+    async fn get_event(&self, uid: &str) -> Result<CalendarEvent> {
+        CalendarClient::get_event(self, uid).await
+    }
+
+    async fn create_event(&self, event: CalendarEvent) -> Result<CalendarEvent> {
+        CalendarClient::create_event(self, event).await
+    }
+
+    async fn update_event(&self, event: CalendarEvent) -> Result<CalendarEvent> {
+        CalendarClient::update_event(self, event).await
+    }
+
+    async fn delete_event(&self, uid: &str) -> Result<()> {
+        CalendarClient::delete_event(self, uid).await
+    }
+}
+
+/// Build the provider configured in the environment
+///
+/// `CALENDAR_PROVIDER` selects the backend (default `caldav`):
+///
+/// - `caldav`: `CALDAV_URL`, `CALDAV_USERNAME`, `CALDAV_PASSWORD`
+/// - `google` / `microsoft`: `CALENDAR_OAUTH_CLIENT_ID`,
+///   `CALENDAR_OAUTH_REFRESH_TOKEN`, optional `CALENDAR_OAUTH_CLIENT_SECRET`
+///   and (Microsoft) `CALENDAR_OAUTH_TENANT`
+///
+/// `CALENDAR_ID` picks the calendar (defaults to the primary calendar).
+/// Returns `Ok(None)` when the selected backend is not configured.
+pub async fn provider_from_env() -> Result<Option<Arc<dyn CalendarProvider>>> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let calendar_id = var("CALENDAR_ID");
+    let provider = var("CALENDAR_PROVIDER").unwrap_or_else(|| "caldav".to_string());
+
+    let oauth = |build: fn(String, String) -> OAuthCredentials| {
+        let (client_id, refresh_token) = (
+            var("CALENDAR_OAUTH_CLIENT_ID")?,
+            var("CALENDAR_OAUTH_REFRESH_TOKEN")?,
+        );
+        let credentials = build(client_id, refresh_token);
+        Some(match var("CALENDAR_OAUTH_CLIENT_SECRET") {
+            Some(secret) => credentials.with_client_secret(secret),
+            None => credentials,
+        })
+    };
+
+    let provider: Arc<dyn CalendarProvider> = match provider.to_ascii_lowercase().as_str() {
+        "caldav" => {
+            let (Some(url), Some(username), Some(password)) =
+                (var("CALDAV_URL"), var("CALDAV_USERNAME"), var("CALDAV_PASSWORD"))
+            else {
+                return Ok(None);
+            };
+            let mut config = CalendarConfig::new(url, username, password);
+            config.calendar_id = calendar_id;
+            Arc::new(CalendarClient::new(config).await?)
+        }
+        "google" => {
+            let Some(credentials) = oauth(OAuthCredentials::google) else {
+                return Ok(None);
+            };
+            let client = GoogleCalendarClient::new(credentials);
+            Arc::new(match calendar_id {
+                Some(id) => client.with_calendar_id(id),
+                None => client,
+            })
+        }
+        "microsoft" | "outlook" | "graph" => {
+            let Some(credentials) = oauth(|id, token| {
+                let tenant = std::env::var("CALENDAR_OAUTH_TENANT").unwrap_or_else(|_| "common".to_string());
+                OAuthCredentials::microsoft(&tenant, id, token)
+            }) else {
+                return Ok(None);
+            };
+            let client = GraphCalendarClient::new(credentials);
+            Arc::new(match calendar_id {
+                Some(id) => client.with_calendar_id(id),
+                None => client,
+            })
+        }
+        other => {
+            return Err(CalendarError::Configuration(format!(
+                "Unknown CALENDAR_PROVIDER: {} (expected caldav, google or microsoft)",
+                other
+            )));
+        }
+    };
+    Ok(Some(provider))
+}
+
+/// Map an unsuccessful REST API response to an error
+pub(crate) async fn api_error(response: reqwest::Response, context: &str) -> CalendarError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    match status {
+        StatusCode::PRECONDITION_FAILED => {
+            CalendarError::Conflict(format!("{}: event was modified on the server", context))
+        }
+        StatusCode::NOT_FOUND | StatusCode::GONE => CalendarError::EventNotFound(context.to_string()),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            CalendarError::Authentication(format!("{}: {} - {}", context, status, body))
+        }
+        _ => CalendarError::HttpError(format!("{}: {} - {}", context, status, body)),
+    }
+}
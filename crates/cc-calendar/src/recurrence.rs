@@ -0,0 +1,637 @@
+//! Recurring event expansion (RFC 5545 RRULE / EXDATE / RECURRENCE-ID)
+//!
+//! Rules are evaluated in the event's own time zone (`TZID`) so that a weekly
+//! 09:00 meeting stays at 09:00 across daylight saving changes. Supported
+//! parts: FREQ (DAILY, WEEKLY, MONTHLY, YEARLY), INTERVAL, COUNT, UNTIL,
+//! BYDAY, BYMONTHDAY, BYMONTH, BYSETPOS and WKST.
+
+use std::collections::{HashSet, VecDeque};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use tracing::warn;
+
+use crate::error::{CalendarError, Result};
+use crate::models::CalendarEvent;
+
+/// Periods without any occurrence after which expansion gives up
+/// (guards against rules that can never match, e.g. BYMONTHDAY=31;BYMONTH=2)
+const MAX_EMPTY_PERIODS: u32 = 1000;
+
+/// Recurrence frequency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A parsed `RRULE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<DateTime<Utc>>,
+    /// BYDAY entries: an optional ordinal (`-1` = last) and the weekday
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+}
+
+impl RecurrenceRule {
+    /// Parse an RRULE value such as `FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10`
+    pub fn parse(rule: &str) -> Result<Self> {
+        let rule = rule.trim().trim_start_matches("RRULE:");
+        let invalid = |part: &str| CalendarError::ParseError(format!("Invalid RRULE part: {}", part));
+
+        let mut frequency = None;
+        let mut parsed = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+        };
+
+        for part in rule.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| invalid(part))?;
+            let numbers = || -> Result<Vec<i32>> {
+                value
+                    .split(',')
+                    .map(|n| n.trim().parse().map_err(|_| invalid(part)))
+                    .collect()
+            };
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => {
+                            return Err(CalendarError::ParseError(format!(
+                                "Unsupported RRULE frequency: {}",
+                                other
+                            )));
+                        }
+                    })
+                }
+                "INTERVAL" => {
+                    parsed.interval = value.parse().map_err(|_| invalid(part))?;
+                    if parsed.interval == 0 {
+                        return Err(invalid(part));
+                    }
+                }
+                "COUNT" => parsed.count = Some(value.parse().map_err(|_| invalid(part))?),
+                "UNTIL" => parsed.until = Some(parse_until(value).ok_or_else(|| invalid(part))?),
+                "BYDAY" => {
+                    parsed.by_day = value
+                        .split(',')
+                        .map(|day| parse_by_day(day.trim()).ok_or_else(|| invalid(part)))
+                        .collect::<Result<_>>()?
+                }
+                "BYMONTHDAY" => parsed.by_month_day = numbers()?,
+                "BYMONTH" => {
+                    parsed.by_month = numbers()?
+                        .into_iter()
+                        .map(|m| u32::try_from(m).ok().filter(|m| (1..=12).contains(m)))
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| invalid(part))?
+                }
+                "BYSETPOS" => parsed.by_set_pos = numbers()?,
+                "WKST" => parsed.week_start = parse_weekday(value).ok_or_else(|| invalid(part))?,
+                // BYHOUR, BYMINUTE, ... keep the time of DTSTART
+                other => warn!("Ignoring unsupported RRULE part: {}", other),
+            }
+        }
+
+        parsed.frequency = frequency.ok_or_else(|| {
+            CalendarError::ParseError(format!("RRULE without FREQ: {}", rule))
+        })?;
+        Ok(parsed)
+    }
+
+    /// Occurrence start times (local wall-clock time) from `dtstart` on,
+    /// including `dtstart` itself; UNTIL is left to the caller
+    pub fn local_occurrences(&self, dtstart: NaiveDateTime) -> impl Iterator<Item = NaiveDateTime> + '_ {
+        Occurrences {
+            rule: self,
+            dtstart,
+            period: 0,
+            empty_periods: 0,
+            emitted: 0,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Candidate dates of the `period`-th period after DTSTART
+    fn period_dates(&self, dtstart: NaiveDate, period: u32) -> Vec<NaiveDate> {
+        let step = i64::from(period) * i64::from(self.interval);
+        let mut dates = match self.frequency {
+            Frequency::Daily => {
+                let date = dtstart + Duration::days(step);
+                let weekdays_match = self.by_day.is_empty()
+                    || self.by_day.iter().any(|(_, wd)| *wd == date.weekday());
+                let month_day_match = self.by_month_day.is_empty()
+                    || self.by_month_day.iter().any(|d| resolve_month_day(date, *d) == Some(date));
+                if weekdays_match && month_day_match {
+                    vec![date]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let offset = (7 + dtstart.weekday().num_days_from_monday()
+                    - self.week_start.num_days_from_monday())
+                    % 7;
+                let week = dtstart - Duration::days(i64::from(offset)) + Duration::weeks(step);
+                (0..7)
+                    .map(|d| week + Duration::days(d))
+                    .filter(|date| {
+                        if self.by_day.is_empty() {
+                            date.weekday() == dtstart.weekday()
+                        } else {
+                            self.by_day.iter().any(|(_, wd)| *wd == date.weekday())
+                        }
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = dtstart.year() * 12 + dtstart.month0() as i32 + step as i32;
+                let (year, month) = (months.div_euclid(12), months.rem_euclid(12) as u32 + 1);
+                self.month_dates(year, month, dtstart)
+            }
+            Frequency::Yearly => {
+                let year = dtstart.year() + step as i32;
+                if self.by_month.is_empty() && self.by_month_day.is_empty() && !self.by_day.is_empty() {
+                    // e.g. BYDAY=20MO: weekdays counted within the year
+                    let first = NaiveDate::from_ymd_opt(year, 1, 1);
+                    let last = NaiveDate::from_ymd_opt(year, 12, 31);
+                    match (first, last) {
+                        (Some(first), Some(last)) => weekdays_in(first, last, &self.by_day),
+                        _ => Vec::new(),
+                    }
+                } else if self.by_month.is_empty() && self.by_month_day.is_empty() {
+                    NaiveDate::from_ymd_opt(year, dtstart.month(), dtstart.day())
+                        .into_iter()
+                        .collect()
+                } else {
+                    let months: Vec<u32> = if self.by_month.is_empty() {
+                        (1..=12).collect()
+                    } else {
+                        self.by_month.clone()
+                    };
+                    months
+                        .into_iter()
+                        .flat_map(|month| self.month_dates(year, month, dtstart))
+                        .collect()
+                }
+            }
+        };
+
+        if !self.by_month.is_empty() {
+            dates.retain(|d| self.by_month.contains(&d.month()));
+        }
+        dates.sort_unstable();
+        dates.dedup();
+
+        if !self.by_set_pos.is_empty() {
+            let len = dates.len() as i32;
+            dates = self
+                .by_set_pos
+                .iter()
+                .filter_map(|&pos| {
+                    let index = if pos > 0 { pos - 1 } else { len + pos };
+                    (0..len).contains(&index).then(|| dates[index as usize])
+                })
+                .collect();
+            dates.sort_unstable();
+            dates.dedup();
+        }
+        dates
+    }
+
+    /// Candidate dates within one month
+    fn month_dates(&self, year: i32, month: u32, dtstart: NaiveDate) -> Vec<NaiveDate> {
+        let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+            return Vec::new();
+        };
+        let last = last_day_of_month(first);
+
+        if !self.by_month_day.is_empty() {
+            return self
+                .by_month_day
+                .iter()
+                .filter_map(|&day| resolve_month_day(first, day))
+                .filter(|date| {
+                    self.by_day.is_empty() || self.by_day.iter().any(|(_, wd)| *wd == date.weekday())
+                })
+                .collect();
+        }
+        if !self.by_day.is_empty() {
+            return weekdays_in(first, last, &self.by_day);
+        }
+        // Months without the day (e.g. the 31st) are skipped
+        NaiveDate::from_ymd_opt(year, month, dtstart.day())
+            .into_iter()
+            .collect()
+    }
+}
+
+struct Occurrences<'a> {
+    rule: &'a RecurrenceRule,
+    dtstart: NaiveDateTime,
+    period: u32,
+    empty_periods: u32,
+    emitted: u32,
+    buffer: VecDeque<NaiveDateTime>,
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<NaiveDateTime> {
+        loop {
+            if self.rule.count.is_some_and(|count| self.emitted >= count) {
+                return None;
+            }
+            if let Some(next) = self.buffer.pop_front() {
+                self.emitted += 1;
+                return Some(next);
+            }
+            if self.empty_periods >= MAX_EMPTY_PERIODS {
+                return None;
+            }
+
+            let time = self.dtstart.time();
+            let first = self.period == 0;
+            self.buffer.extend(
+                self.rule
+                    .period_dates(self.dtstart.date(), self.period)
+                    .into_iter()
+                    .map(|date| date.and_time(time))
+                    .filter(|start| *start > self.dtstart),
+            );
+            // DTSTART is always the first occurrence
+            if first {
+                self.buffer.push_front(self.dtstart);
+            }
+            self.period = self.period.checked_add(1)?;
+            if self.buffer.is_empty() {
+                self.empty_periods += 1;
+            } else {
+                self.empty_periods = 0;
+            }
+        }
+    }
+}
+
+/// Occurrences of a (possibly recurring) event overlapping `[range_start, range_end)`
+///
+/// Each occurrence keeps the event's UID and has `recurrence_id` set to its
+/// original start. Non-recurring events are returned as-is when they overlap.
+pub fn expand_event(
+    event: &CalendarEvent,
+    range_start: DateTime<Utc>,
+    range_end: DateTime<Utc>,
+) -> Vec<CalendarEvent> {
+    let overlaps = |start: DateTime<Utc>, end: DateTime<Utc>| {
+        start < range_end && (end > range_start || (end == start && start >= range_start))
+    };
+
+    let Some(ref rrule) = event.rrule else {
+        return if overlaps(event.start, event.end) {
+            vec![event.clone()]
+        } else {
+            Vec::new()
+        };
+    };
+    let rule = match RecurrenceRule::parse(rrule) {
+        Ok(rule) => rule,
+        Err(e) => {
+            warn!("Cannot expand {:?}: {}", event.uid, e);
+            return vec![event.clone()];
+        }
+    };
+
+    let tz = event
+        .timezone
+        .as_deref()
+        .and_then(|tz| tz.parse::<Tz>().ok());
+    let local_start = match tz {
+        Some(tz) => event.start.with_timezone(&tz).naive_local(),
+        None => event.start.naive_utc(),
+    };
+    let duration = event.end - event.start;
+    let excluded: HashSet<DateTime<Utc>> = event.exdates.iter().copied().collect();
+
+    let mut occurrences = Vec::new();
+    for local in rule.local_occurrences(local_start) {
+        let start = to_utc(local, tz);
+        if rule.until.is_some_and(|until| start > until) || start >= range_end {
+            break;
+        }
+        if excluded.contains(&start) || !overlaps(start, start + duration) {
+            continue;
+        }
+        let mut occurrence = event.clone();
+        occurrence.start = start;
+        occurrence.end = start + duration;
+        occurrence.recurrence_id = Some(start);
+        occurrence.exdates = Vec::new();
+        occurrences.push(occurrence);
+    }
+    occurrences
+}
+
+/// Expand all events, replacing occurrences that have their own override
+/// (a VEVENT with the same UID and a `RECURRENCE-ID`)
+pub fn expand_events(
+    events: Vec<CalendarEvent>,
+    range_start: DateTime<Utc>,
+    range_end: DateTime<Utc>,
+) -> Vec<CalendarEvent> {
+    let (overrides, masters): (Vec<_>, Vec<_>) = events
+        .into_iter()
+        .partition(|e| e.recurrence_id.is_some() && e.rrule.is_none());
+    let overridden: HashSet<(Option<String>, Option<DateTime<Utc>>)> = overrides
+        .iter()
+        .map(|e| (e.uid.clone(), e.recurrence_id))
+        .collect();
+
+    let mut expanded: Vec<CalendarEvent> = masters
+        .iter()
+        .flat_map(|event| expand_event(event, range_start, range_end))
+        .filter(|e| {
+            e.rrule.is_none() || !overridden.contains(&(e.uid.clone(), e.recurrence_id))
+        })
+        .collect();
+    expanded.extend(
+        overrides
+            .into_iter()
+            .filter(|e| e.start < range_end && e.end > range_start),
+    );
+    expanded.sort_by_key(|e| e.start);
+    expanded
+}
+
+/// Convert a local wall-clock time to UTC
+///
+/// Times skipped by a DST change move forward by the gap; repeated times use
+/// the first occurrence.
+pub(crate) fn to_utc(local: NaiveDateTime, tz: Option<Tz>) -> DateTime<Utc> {
+    let Some(tz) = tz else {
+        return local.and_utc();
+    };
+    (0..4)
+        .find_map(|hours| {
+            tz.from_local_datetime(&(local + Duration::hours(hours)))
+                .earliest()
+        })
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+fn parse_until(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S") {
+        return Some(dt.and_utc());
+    }
+    // A date UNTIL includes the whole day
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(23, 59, 59))
+        .map(|dt| dt.and_utc())
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    Some(match value.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Parse a BYDAY entry like `MO`, `2TU` or `-1FR`
+fn parse_by_day(value: &str) -> Option<(Option<i32>, Weekday)> {
+    if value.len() < 2 {
+        return None;
+    }
+    let (ordinal, day) = value.split_at(value.len() - 2);
+    let weekday = parse_weekday(day)?;
+    if ordinal.is_empty() {
+        return Some((None, weekday));
+    }
+    let ordinal: i32 = ordinal.trim_start_matches('+').parse().ok()?;
+    (ordinal != 0).then_some((Some(ordinal), weekday))
+}
+
+fn last_day_of_month(first: NaiveDate) -> NaiveDate {
+    let next = if first.month() == 12 {
+        NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+    };
+    next.map_or(first, |next| next - Duration::days(1))
+}
+
+/// The date for a BYMONTHDAY value (negative counts from the month's end)
+fn resolve_month_day(date_in_month: NaiveDate, day: i32) -> Option<NaiveDate> {
+    let first = date_in_month.with_day(1)?;
+    let last = last_day_of_month(first);
+    let day = if day > 0 {
+        day
+    } else {
+        last.day() as i32 + day + 1
+    };
+    u32::try_from(day)
+        .ok()
+        .filter(|d| *d >= 1 && *d <= last.day())
+        .and_then(|d| first.with_day(d))
+}
+
+/// Dates between `first` and `last` matching BYDAY entries (ordinals count
+/// within the span)
+fn weekdays_in(first: NaiveDate, last: NaiveDate, by_day: &[(Option<i32>, Weekday)]) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    for &(ordinal, weekday) in by_day {
+        let matching: Vec<NaiveDate> = first
+            .iter_days()
+            .take_while(|d| *d <= last)
+            .filter(|d| d.weekday() == weekday)
+            .collect();
+        match ordinal {
+            None => dates.extend(matching),
+            Some(n) => {
+                let len = matching.len() as i32;
+                let index = if n > 0 { n - 1 } else { len + n };
+                if (0..len).contains(&index) {
+                    dates.push(matching[index as usize]);
+                }
+            }
+        }
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn event(start: &str, rrule: &str) -> CalendarEvent {
+        let start = utc(start);
+        CalendarEvent {
+            uid: Some("ev".to_string()),
+            start,
+            end: start + Duration::hours(1),
+            rrule: Some(rrule.to_string()),
+            ..CalendarEvent::new("Recurring", start, start + Duration::hours(1))
+        }
+    }
+
+    fn starts(events: &[CalendarEvent]) -> Vec<String> {
+        events.iter().map(|e| e.start.format("%Y-%m-%d %H:%M").to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR,2MO;COUNT=5;WKST=SU").unwrap();
+        assert_eq!(rule.frequency, Frequency::Monthly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.count, Some(5));
+        assert_eq!(rule.by_day, vec![(Some(-1), Weekday::Fri), (Some(2), Weekday::Mon)]);
+        assert_eq!(rule.week_start, Weekday::Sun);
+
+        assert!(RecurrenceRule::parse("FREQ=SECONDLY").is_err());
+        assert!(RecurrenceRule::parse("COUNT=3").is_err());
+        assert!(RecurrenceRule::parse("FREQ=DAILY;INTERVAL=0").is_err());
+    }
+
+    #[test]
+    fn test_weekly_with_exdate_and_range() {
+        let mut ev = event("2024-01-01T09:00:00Z", "FREQ=WEEKLY;BYDAY=MO,WE");
+        ev.exdates = vec![utc("2024-01-08T09:00:00Z")];
+
+        let occurrences = expand_event(&ev, utc("2024-01-02T00:00:00Z"), utc("2024-01-16T00:00:00Z"));
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2024-01-03 09:00", "2024-01-10 09:00", "2024-01-15 09:00"]
+        );
+        assert_eq!(occurrences[0].recurrence_id, Some(utc("2024-01-03T09:00:00Z")));
+        assert_eq!(occurrences[0].end, utc("2024-01-03T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_count_and_until() {
+        let ev = event("2024-01-30T12:00:00Z", "FREQ=DAILY;COUNT=3");
+        let occurrences = expand_event(&ev, utc("2024-01-01T00:00:00Z"), utc("2025-01-01T00:00:00Z"));
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2024-01-30 12:00", "2024-01-31 12:00", "2024-02-01 12:00"]
+        );
+
+        let ev = event("2024-01-30T12:00:00Z", "FREQ=DAILY;INTERVAL=2;UNTIL=20240203T120000Z");
+        let occurrences = expand_event(&ev, utc("2024-01-01T00:00:00Z"), utc("2025-01-01T00:00:00Z"));
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2024-01-30 12:00", "2024-02-01 12:00", "2024-02-03 12:00"]
+        );
+    }
+
+    #[test]
+    fn test_monthly_rules() {
+        // Months without a 31st are skipped
+        let ev = event("2024-01-31T08:00:00Z", "FREQ=MONTHLY;COUNT=3");
+        let occurrences = expand_event(&ev, utc("2024-01-01T00:00:00Z"), utc("2025-01-01T00:00:00Z"));
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2024-01-31 08:00", "2024-03-31 08:00", "2024-05-31 08:00"]
+        );
+
+        // Last Friday of the month
+        let ev = event("2024-01-26T08:00:00Z", "FREQ=MONTHLY;BYDAY=-1FR");
+        let occurrences = expand_event(&ev, utc("2024-01-01T00:00:00Z"), utc("2024-04-01T00:00:00Z"));
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2024-01-26 08:00", "2024-02-23 08:00", "2024-03-29 08:00"]
+        );
+
+        // Last weekday of the month
+        let ev = event("2024-01-31T08:00:00Z", "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1");
+        let occurrences = expand_event(&ev, utc("2024-01-01T00:00:00Z"), utc("2024-07-01T00:00:00Z"));
+        assert_eq!(
+            starts(&occurrences),
+            vec![
+                "2024-01-31 08:00",
+                "2024-02-29 08:00",
+                "2024-03-29 08:00",
+                "2024-04-30 08:00",
+                "2024-05-31 08:00",
+                "2024-06-28 08:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yearly_rules() {
+        let ev = event("2024-02-29T00:00:00Z", "FREQ=YEARLY;COUNT=2");
+        let occurrences = expand_event(&ev, utc("2024-01-01T00:00:00Z"), utc("2033-01-01T00:00:00Z"));
+        assert_eq!(starts(&occurrences), vec!["2024-02-29 00:00", "2028-02-29 00:00"]);
+
+        // US Thanksgiving: fourth Thursday of November
+        let ev = event("2024-11-28T17:00:00Z", "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH");
+        let occurrences = expand_event(&ev, utc("2025-01-01T00:00:00Z"), utc("2027-01-01T00:00:00Z"));
+        assert_eq!(starts(&occurrences), vec!["2025-11-27 17:00", "2026-11-26 17:00"]);
+    }
+
+    #[test]
+    fn test_time_zone_keeps_wall_clock_time() {
+        // 09:00 in New York is 14:00 UTC in winter and 13:00 UTC in summer
+        let mut ev = event("2024-03-04T14:00:00Z", "FREQ=WEEKLY");
+        ev.timezone = Some("America/New_York".to_string());
+        let occurrences = expand_event(&ev, utc("2024-03-01T00:00:00Z"), utc("2024-03-20T00:00:00Z"));
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2024-03-04 14:00", "2024-03-11 13:00", "2024-03-18 13:00"]
+        );
+    }
+
+    #[test]
+    fn test_overrides_replace_occurrences() {
+        let master = event("2024-01-01T09:00:00Z", "FREQ=DAILY;COUNT=3");
+        let moved = CalendarEvent {
+            uid: Some("ev".to_string()),
+            recurrence_id: Some(utc("2024-01-02T09:00:00Z")),
+            ..CalendarEvent::new("Moved", utc("2024-01-02T15:00:00Z"), utc("2024-01-02T16:00:00Z"))
+        };
+        let single = CalendarEvent::new("Single", utc("2024-01-01T12:00:00Z"), utc("2024-01-01T13:00:00Z"));
+
+        let expanded = expand_events(
+            vec![master, moved, single],
+            utc("2024-01-01T00:00:00Z"),
+            utc("2024-02-01T00:00:00Z"),
+        );
+        assert_eq!(
+            starts(&expanded),
+            vec!["2024-01-01 09:00", "2024-01-01 12:00", "2024-01-02 15:00", "2024-01-03 09:00"]
+        );
+        assert_eq!(expanded[2].summary, "Moved");
+    }
+}
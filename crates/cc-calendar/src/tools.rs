@@ -0,0 +1,988 @@
+//! Calendar tools for cc-gateway
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use serde_json::{json, Value};
+
+use cc_core::{Tool, ToolResult};
+use cc_email::{EmailReceiver, ImapConfig, ParsedEmail};
+
+use crate::invite::InviteMailer;
+use crate::models::CalendarEvent;
+use crate::provider::CalendarProvider;
+use crate::recurrence::{RecurrenceRule, to_utc};
+
+/// Days listed when no end is given
+const DEFAULT_LIST_DAYS: i64 = 7;
+
+/// Longest range listed by `days`
+const MAX_LIST_DAYS: i64 = 366;
+
+/// Event length when neither end nor duration is given
+const DEFAULT_DURATION_MINUTES: i64 = 60;
+
+/// Longest event length given as `duration_minutes` (31 days)
+const MAX_DURATION_MINUTES: i64 = 31 * 24 * 60;
+
+/// Calendar event listing tool
+pub struct CalendarListEventsTool {
+    provider: Arc<dyn CalendarProvider>,
+}
+
+impl CalendarListEventsTool {
+    pub fn new(provider: Arc<dyn CalendarProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarListEventsTool {
+    fn name(&self) -> &str {
+        "calendar_list_events"
+    }
+
+    fn description(&self) -> &str {
+        "List calendar events in a time range (recurring events are listed once per occurrence)"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "start": {
+                    "type": "string",
+                    "description": "Range start (RFC 3339 or YYYY-MM-DD, default: now)"
+                },
+                "end": {
+                    "type": "string",
+                    "description": "Range end (RFC 3339 or YYYY-MM-DD, default: start + days)"
+                },
+                "days": {
+                    "type": "integer",
+                    "description": "Length of the range in days when end is omitted (default: 7)",
+                    "default": 7,
+                    "minimum": 1,
+                    "maximum": MAX_LIST_DAYS
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Only events whose title, description or location contain this text"
+                }
+            }
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let start = parse_time(&input["start"], None)?.map_or_else(Utc::now, |(t, _)| t);
+        let end = match parse_time(&input["end"], None)? {
+            Some((end, _)) => end,
+            None => {
+                let days = input["days"].as_i64().unwrap_or(DEFAULT_LIST_DAYS).max(1);
+                match Duration::try_days(days).and_then(|days| start.checked_add_signed(days)) {
+                    Some(end) => end,
+                    None => return Ok(ToolResult::error("'days' is out of range")),
+                }
+            }
+        };
+        if end <= start {
+            return Ok(ToolResult::error("'end' must be after 'start'"));
+        }
+
+        let mut events = self
+            .provider
+            .get_events(start, end)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+        if let Some(query) = input["query"].as_str() {
+            let query = query.to_lowercase();
+            events.retain(|e| {
+                [Some(&e.summary), e.description.as_ref(), e.location.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .any(|text| text.to_lowercase().contains(&query))
+            });
+        }
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "start": start.to_rfc3339(),
+            "end": end.to_rfc3339(),
+            "count": events.len(),
+            "events": events.iter().map(event_to_json).collect::<Vec<_>>()
+        })).unwrap_or_default()))
+    }
+}
+
+/// Calendar event creation tool
+pub struct CalendarCreateEventTool {
+    provider: Arc<dyn CalendarProvider>,
+}
+
+impl CalendarCreateEventTool {
+    pub fn new(provider: Arc<dyn CalendarProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarCreateEventTool {
+    fn name(&self) -> &str {
+        "calendar_create_event"
+    }
+
+    fn description(&self) -> &str {
+        "Create a calendar event, optionally recurring (RRULE) and with attendees"
+    }
+
+    fn input_schema(&self) -> Value {
+        new_event_schema()
+    }
+
+    async fn preview(&self, input: &Value) -> Option<String> {
+        Some(match new_event(input) {
+            Ok(event) => format!("Create event\n{}", describe_event(&event)),
+            Err(e) => format!("The call would fail: {}", e),
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let event = new_event(&input)?;
+        let created = self
+            .provider
+            .create_event(event)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "created",
+            "event": event_to_json(&created)
+        })).unwrap_or_default()))
+    }
+}
+
+/// Calendar event update tool
+pub struct CalendarUpdateEventTool {
+    provider: Arc<dyn CalendarProvider>,
+}
+
+impl CalendarUpdateEventTool {
+    pub fn new(provider: Arc<dyn CalendarProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarUpdateEventTool {
+    fn name(&self) -> &str {
+        "calendar_update_event"
+    }
+
+    fn description(&self) -> &str {
+        "Change an existing calendar event; only the given fields are modified"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "uid": {
+                    "type": "string",
+                    "description": "Event UID from calendar_list_events"
+                },
+                "summary": {
+                    "type": "string",
+                    "description": "New title"
+                },
+                "start": {
+                    "type": "string",
+                    "description": "New start time (the event keeps its length unless end is given)"
+                },
+                "end": {
+                    "type": "string",
+                    "description": "New end time"
+                },
+                "timezone": {
+                    "type": "string",
+                    "description": "IANA time zone for local times"
+                },
+                "description": {
+                    "type": "string",
+                    "description": "New description"
+                },
+                "location": {
+                    "type": "string",
+                    "description": "New location"
+                },
+                "attendees": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Replacement attendee list"
+                },
+                "rrule": {
+                    "type": "string",
+                    "description": "New recurrence rule (empty string removes the recurrence)"
+                }
+            },
+            "required": ["uid"]
+        })
+    }
+
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let uid = input["uid"].as_str()?;
+        let before = match self.provider.get_event(uid).await {
+            Ok(event) => event,
+            Err(e) => return Some(format!("The call would fail: {}", e)),
+        };
+        let mut after = before.clone();
+        if let Err(e) = apply_update(&mut after, input) {
+            return Some(format!("The call would fail: {}", e));
+        }
+        Some(format!(
+            "Update event\nBefore:\n{}\nAfter:\n{}",
+            describe_event(&before),
+            describe_event(&after)
+        ))
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let uid = input["uid"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'uid' parameter".to_string()))?;
+        let mut event = self
+            .provider
+            .get_event(uid)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        apply_update(&mut event, &input)?;
+        if event.end < event.start {
+            return Ok(ToolResult::error("'end' must not be before 'start'"));
+        }
+
+        let updated = self
+            .provider
+            .update_event(event)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "updated",
+            "event": event_to_json(&updated)
+        })).unwrap_or_default()))
+    }
+}
+
+/// Calendar event deletion tool
+pub struct CalendarDeleteEventTool {
+    provider: Arc<dyn CalendarProvider>,
+}
+
+impl CalendarDeleteEventTool {
+    pub fn new(provider: Arc<dyn CalendarProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarDeleteEventTool {
+    fn name(&self) -> &str {
+        "calendar_delete_event"
+    }
+
+    fn description(&self) -> &str {
+        "Delete a calendar event (for a recurring event, the whole series)"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "uid": {
+                    "type": "string",
+                    "description": "Event UID from calendar_list_events"
+                }
+            },
+            "required": ["uid"]
+        })
+    }
+
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let uid = input["uid"].as_str()?;
+        Some(match self.provider.get_event(uid).await {
+            Ok(event) if event.is_recurring() => {
+                format!("Delete the whole series\n{}", describe_event(&event))
+            }
+            Ok(event) => format!("Delete event\n{}", describe_event(&event)),
+            Err(e) => format!("The call would fail: {}", e),
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let uid = input["uid"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'uid' parameter".to_string()))?;
+        self.provider
+            .delete_event(uid)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "deleted",
+            "uid": uid
+        })).unwrap_or_default()))
+    }
+}
+
+/// Meeting invitation tool
+pub struct CalendarSendInviteTool {
+    mailer: Arc<InviteMailer>,
+}
+
+impl CalendarSendInviteTool {
+    pub fn new(mailer: Arc<InviteMailer>) -> Self {
+        Self { mailer }
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarSendInviteTool {
+    fn name(&self) -> &str {
+        "calendar_send_invite"
+    }
+
+    fn description(&self) -> &str {
+        "Create a meeting and email an invitation to every attendee; their replies can be recorded with calendar_process_reply"
+    }
+
+    fn input_schema(&self) -> Value {
+        let mut schema = new_event_schema();
+        schema["required"] = json!(["summary", "start", "attendees"]);
+        schema
+    }
+
+    async fn preview(&self, input: &Value) -> Option<String> {
+        Some(match new_event(input) {
+            Ok(event) => format!(
+                "Create event and email an invitation from {} to every attendee\n{}",
+                self.mailer.organizer(),
+                describe_event(&event)
+            ),
+            Err(e) => format!("The call would fail: {}", e),
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let event = new_event(&input)?;
+        if event.attendees.is_empty() {
+            return Ok(ToolResult::error("'attendees' must list at least one address"));
+        }
+        let created = self
+            .mailer
+            .invite(event)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "invited",
+            "event": event_to_json(&created)
+        })).unwrap_or_default()))
+    }
+}
+
+/// Meeting cancellation tool
+pub struct CalendarCancelInviteTool {
+    mailer: Arc<InviteMailer>,
+}
+
+impl CalendarCancelInviteTool {
+    pub fn new(mailer: Arc<InviteMailer>) -> Self {
+        Self { mailer }
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarCancelInviteTool {
+    fn name(&self) -> &str {
+        "calendar_cancel_invite"
+    }
+
+    fn description(&self) -> &str {
+        "Cancel a meeting: email a cancellation to the attendees and delete the event"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "uid": {
+                    "type": "string",
+                    "description": "Event UID from calendar_send_invite or calendar_list_events"
+                }
+            },
+            "required": ["uid"]
+        })
+    }
+
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let uid = input["uid"].as_str()?;
+        Some(match self.mailer.event(uid).await {
+            Ok(event) => format!(
+                "Email a cancellation to {} and delete the event\n{}",
+                event.attendees.join(", "),
+                describe_event(&event)
+            ),
+            Err(e) => format!("The call would fail: {}", e),
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let uid = input["uid"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'uid' parameter".to_string()))?;
+        let event = self
+            .mailer
+            .cancel(uid)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "cancelled",
+            "uid": uid,
+            "notified": event.attendees
+        })).unwrap_or_default()))
+    }
+}
+
+/// Meeting reply processing tool
+pub struct CalendarProcessReplyTool {
+    mailer: Arc<InviteMailer>,
+    imap: ImapConfig,
+}
+
+impl CalendarProcessReplyTool {
+    pub fn new(mailer: Arc<InviteMailer>, imap: ImapConfig) -> Self {
+        Self { mailer, imap }
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarProcessReplyTool {
+    fn name(&self) -> &str {
+        "calendar_process_reply"
+    }
+
+    fn description(&self) -> &str {
+        "Record an attendee's accept/decline reply email (from email_list) on the meeting"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "uid": {
+                    "type": "integer",
+                    "description": "Email UID of the reply"
+                },
+                "folder": {
+                    "type": "string",
+                    "description": "Folder containing the reply (default: INBOX)",
+                    "default": "INBOX"
+                }
+            },
+            "required": ["uid"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let uid = input["uid"]
+            .as_u64()
+            .and_then(|uid| u32::try_from(uid).ok())
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'uid' parameter".to_string()))?;
+        let folder = input["folder"].as_str().unwrap_or("INBOX");
+
+        let raw = EmailReceiver::new(self.imap.clone())
+            .get_email(folder, uid)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+        let outcome = self
+            .mailer
+            .process_reply(&ParsedEmail::parse(raw.as_bytes()))
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": if outcome.changes.is_empty() { "unchanged" } else { "updated" },
+            "changes": outcome
+                .changes
+                .iter()
+                .map(|(attendee, status)| json!({"attendee": attendee, "status": status}))
+                .collect::<Vec<_>>(),
+            "event": event_to_json(&outcome.event)
+        })).unwrap_or_default()))
+    }
+}
+
+/// Register all calendar tools backed by `provider`
+pub fn register_calendar_tools(manager: &mut cc_core::ToolManager, provider: Arc<dyn CalendarProvider>) {
+    manager.register(Arc::new(CalendarListEventsTool::new(Arc::clone(&provider))));
+    manager.register(Arc::new(CalendarCreateEventTool::new(Arc::clone(&provider))));
+    manager.register(Arc::new(CalendarUpdateEventTool::new(Arc::clone(&provider))));
+    manager.register(Arc::new(CalendarDeleteEventTool::new(provider)));
+}
+
+/// Register the meeting invitation tools; replies can only be processed
+/// with an IMAP account to read them from
+pub fn register_invite_tools(
+    manager: &mut cc_core::ToolManager,
+    mailer: Arc<InviteMailer>,
+    imap: Option<ImapConfig>,
+) {
+    manager.register(Arc::new(CalendarSendInviteTool::new(Arc::clone(&mailer))));
+    if let Some(imap) = imap {
+        manager.register(Arc::new(CalendarProcessReplyTool::new(Arc::clone(&mailer), imap)));
+    }
+    manager.register(Arc::new(CalendarCancelInviteTool::new(mailer)));
+}
+
+/// Input schema of a new event (calendar_create_event, calendar_send_invite)
+fn new_event_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "summary": {
+                "type": "string",
+                "description": "Event title"
+            },
+            "start": {
+                "type": "string",
+                "description": "Start time (RFC 3339, or local YYYY-MM-DDTHH:MM in 'timezone'); a YYYY-MM-DD date creates an all-day event"
+            },
+            "end": {
+                "type": "string",
+                "description": "End time (default: start + duration_minutes)"
+            },
+            "duration_minutes": {
+                "type": "integer",
+                "description": "Length of the event when end is omitted (default: 60)",
+                "default": 60,
+                "minimum": 1,
+                "maximum": MAX_DURATION_MINUTES
+            },
+            "timezone": {
+                "type": "string",
+                "description": "IANA time zone for local times and recurrences (e.g. Europe/Berlin)"
+            },
+            "description": {
+                "type": "string",
+                "description": "Event description"
+            },
+            "location": {
+                "type": "string",
+                "description": "Event location"
+            },
+            "attendees": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Attendee email addresses"
+            },
+            "rrule": {
+                "type": "string",
+                "description": "Recurrence rule, e.g. FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10"
+            }
+        },
+        "required": ["summary", "start"]
+    })
+}
+
+/// A new event from the input of [`new_event_schema`]
+fn new_event(input: &Value) -> cc_core::Result<CalendarEvent> {
+    let summary = input["summary"]
+        .as_str()
+        .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'summary' parameter".to_string()))?;
+    let timezone = parse_timezone(&input["timezone"])?;
+    let (start, all_day) = parse_time(&input["start"], timezone)?
+        .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'start' parameter".to_string()))?;
+    let end = match parse_time(&input["end"], timezone)? {
+        Some((end, _)) => end,
+        None if all_day => start + Duration::days(1),
+        None => {
+            let minutes = input["duration_minutes"].as_i64().unwrap_or(DEFAULT_DURATION_MINUTES);
+            if minutes < 1 {
+                return Err(cc_core::Error::ToolExecution(
+                    "'duration_minutes' must be at least 1".to_string(),
+                ));
+            }
+            Duration::try_minutes(minutes)
+                .and_then(|duration| start.checked_add_signed(duration))
+                .ok_or_else(|| cc_core::Error::ToolExecution("'duration_minutes' is out of range".to_string()))?
+        }
+    };
+    if end < start {
+        return Err(cc_core::Error::ToolExecution("'end' must not be before 'start'".to_string()));
+    }
+
+    let mut event = CalendarEvent::new(summary, start, end);
+    event.all_day = all_day;
+    event.timezone = timezone.map(|tz| tz.name().to_string());
+    apply_fields(&mut event, input)?;
+    Ok(event)
+}
+
+fn event_to_json(event: &CalendarEvent) -> Value {
+    json!({
+        "uid": event.uid,
+        "summary": event.summary,
+        "start": event.start.to_rfc3339(),
+        "end": event.end.to_rfc3339(),
+        "all_day": event.all_day,
+        "timezone": event.timezone,
+        "description": event.description,
+        "location": event.location,
+        "organizer": event.organizer,
+        "attendees": event.attendees,
+        "attendee_status": event.attendee_status,
+        "rrule": event.rrule,
+        "recurrence_id": event.recurrence_id.map(|t| t.to_rfc3339())
+    })
+}
+
+/// The event as a few lines for previews
+fn describe_event(event: &CalendarEvent) -> String {
+    let mut lines = vec![format!("  {}", event.summary)];
+    let when = if event.all_day {
+        format!("  {} (all day)", event.start.format("%Y-%m-%d"))
+    } else {
+        format!("  {} – {}", event.start.to_rfc3339(), event.end.to_rfc3339())
+    };
+    lines.push(match &event.timezone {
+        Some(tz) => format!("{} [{}]", when, tz),
+        None => when,
+    });
+    if let Some(location) = &event.location {
+        lines.push(format!("  Location: {}", location));
+    }
+    if !event.attendees.is_empty() {
+        lines.push(format!("  Attendees: {}", event.attendees.join(", ")));
+    }
+    if let Some(rrule) = &event.rrule {
+        lines.push(format!("  Repeats: {}", rrule));
+    }
+    if let Some(description) = &event.description {
+        lines.push(format!("  {}", description));
+    }
+    lines.join("\n")
+}
+
+/// Apply the input of calendar_update_event to `event`
+///
+/// Local times are in the given time zone, else the event's; a new start
+/// keeps the event's length unless an end is given.
+fn apply_update(event: &mut CalendarEvent, input: &Value) -> cc_core::Result<()> {
+    let timezone = match parse_timezone(&input["timezone"])? {
+        Some(tz) => {
+            event.timezone = Some(tz.name().to_string());
+            Some(tz)
+        }
+        None => event.timezone.as_deref().and_then(|tz| tz.parse().ok()),
+    };
+    if let Some(summary) = input["summary"].as_str() {
+        event.summary = summary.to_string();
+    }
+    let length = event.end - event.start;
+    if let Some((start, all_day)) = parse_time(&input["start"], timezone)? {
+        event.start = start;
+        event.end = start + length;
+        event.all_day = all_day;
+    }
+    if let Some((end, _)) = parse_time(&input["end"], timezone)? {
+        event.end = end;
+    }
+    apply_fields(event, input)
+}
+
+/// Description, location, attendees and recurrence from the tool input
+fn apply_fields(event: &mut CalendarEvent, input: &Value) -> cc_core::Result<()> {
+    if let Some(description) = input["description"].as_str() {
+        event.description = Some(description.to_string());
+    }
+    if let Some(location) = input["location"].as_str() {
+        event.location = Some(location.to_string());
+    }
+    if let Some(attendees) = input["attendees"].as_array() {
+        event.attendees = attendees
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+    }
+    match input["rrule"].as_str() {
+        Some("") => event.rrule = None,
+        Some(rrule) => {
+            RecurrenceRule::parse(rrule).map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+            event.rrule = Some(rrule.trim_start_matches("RRULE:").to_string());
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+fn parse_timezone(value: &Value) -> cc_core::Result<Option<Tz>> {
+    value
+        .as_str()
+        .map(|tz| {
+            tz.parse::<Tz>().map_err(|_| {
+                cc_core::Error::ToolExecution(format!("Unknown time zone '{}' (use an IANA name)", tz))
+            })
+        })
+        .transpose()
+}
+
+/// A time parameter and whether it was a plain date
+///
+/// Accepts RFC 3339, local `YYYY-MM-DDTHH:MM[:SS]` (in `tz`, default UTC) and
+/// `YYYY-MM-DD`.
+fn parse_time(value: &Value, tz: Option<Tz>) -> cc_core::Result<Option<(DateTime<Utc>, bool)>> {
+    let Some(text) = value.as_str() else {
+        return Ok(None);
+    };
+    let text = text.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Ok(Some((dt.with_timezone(&Utc), false)));
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(local) = NaiveDateTime::parse_from_str(text, format) {
+            return Ok(Some((to_utc(local, tz), false)));
+        }
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| Some((midnight.and_utc(), true)))
+        .ok_or_else(|| {
+            cc_core::Error::ToolExecution(format!(
+                "Invalid time '{}' (use RFC 3339 or YYYY-MM-DD)",
+                text
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{CalendarError, Result};
+    use std::sync::Mutex;
+
+    /// In-memory provider
+    #[derive(Default)]
+    struct MemoryProvider {
+        events: Mutex<Vec<CalendarEvent>>,
+    }
+
+    #[async_trait]
+    impl CalendarProvider for MemoryProvider {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        async fn list_calendars(&self) -> Result<Vec<String>> {
+            Ok(vec!["default".to_string()])
+        }
+
+        async fn get_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<CalendarEvent>> {
+            let events = self.events.lock().unwrap().clone();
+            Ok(crate::recurrence::expand_events(events, start, end))
+        }
+
+        async fn get_event(&self, uid: &str) -> Result<CalendarEvent> {
+            self.events
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|e| e.uid.as_deref() == Some(uid))
+                .cloned()
+                .ok_or_else(|| CalendarError::EventNotFound(uid.to_string()))
+        }
+
+        async fn create_event(&self, mut event: CalendarEvent) -> Result<CalendarEvent> {
+            let mut events = self.events.lock().unwrap();
+            event.uid = Some(format!("ev{}", events.len() + 1));
+            events.push(event.clone());
+            Ok(event)
+        }
+
+        async fn update_event(&self, event: CalendarEvent) -> Result<CalendarEvent> {
+            let mut events = self.events.lock().unwrap();
+            let slot = events
+                .iter_mut()
+                .find(|e| e.uid == event.uid)
+                .ok_or_else(|| CalendarError::EventNotFound(format!("{:?}", event.uid)))?;
+            *slot = event.clone();
+            Ok(event)
+        }
+
+        async fn delete_event(&self, uid: &str) -> Result<()> {
+            self.events.lock().unwrap().retain(|e| e.uid.as_deref() != Some(uid));
+            Ok(())
+        }
+    }
+
+    fn output(result: ToolResult) -> Value {
+        assert!(!result.is_error, "{}", result.output);
+        serde_json::from_str(&result.output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_list_update_delete() {
+        let provider: Arc<dyn CalendarProvider> = Arc::new(MemoryProvider::default());
+        let created = output(
+            CalendarCreateEventTool::new(Arc::clone(&provider))
+                .execute(json!({
+                    "summary": "Standup",
+                    "start": "2024-03-25T09:00",
+                    "timezone": "Europe/Berlin",
+                    "duration_minutes": 15,
+                    "rrule": "FREQ=DAILY;COUNT=3"
+                }))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(created["event"]["start"], "2024-03-25T08:00:00+00:00");
+        assert_eq!(created["event"]["end"], "2024-03-25T08:15:00+00:00");
+
+        let list = CalendarListEventsTool::new(Arc::clone(&provider));
+        let listed = output(
+            list.execute(json!({"start": "2024-03-25", "days": 7, "query": "stand"}))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(listed["count"], 3);
+
+        let updated = output(
+            CalendarUpdateEventTool::new(Arc::clone(&provider))
+                .execute(json!({"uid": "ev1", "start": "2024-03-25T10:00", "location": "Room 2"}))
+                .await
+                .unwrap(),
+        );
+        // Local times use the event's own time zone and the length is kept
+        assert_eq!(updated["event"]["start"], "2024-03-25T09:00:00+00:00");
+        assert_eq!(updated["event"]["end"], "2024-03-25T09:15:00+00:00");
+        assert_eq!(updated["event"]["location"], "Room 2");
+
+        CalendarDeleteEventTool::new(Arc::clone(&provider))
+            .execute(json!({"uid": "ev1"}))
+            .await
+            .unwrap();
+        let listed = output(list.execute(json!({"start": "2024-03-25"})).await.unwrap());
+        assert_eq!(listed["count"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_previews() {
+        let provider: Arc<dyn CalendarProvider> = Arc::new(MemoryProvider::default());
+        let input = json!({"summary": "Review", "start": "2024-03-25T09:00:00Z", "location": "Room 1"});
+        let create = CalendarCreateEventTool::new(Arc::clone(&provider));
+        let preview = create.preview(&input).await.unwrap();
+        assert_eq!(
+            preview,
+            "Create event\n  Review\n  2024-03-25T09:00:00+00:00 – 2024-03-25T10:00:00+00:00\n  Location: Room 1"
+        );
+        create.execute(input).await.unwrap();
+
+        let update = CalendarUpdateEventTool::new(Arc::clone(&provider));
+        let preview = update.preview(&json!({"uid": "ev1", "location": "Room 2"})).await.unwrap();
+        assert!(preview.contains("Before:\n  Review\n"));
+        assert!(preview.ends_with("Location: Room 2"));
+        // Nothing changes until the call runs
+        assert_eq!(provider.get_event("ev1").await.unwrap().location.as_deref(), Some("Room 1"));
+
+        let delete = CalendarDeleteEventTool::new(Arc::clone(&provider));
+        assert!(delete.preview(&json!({"uid": "ev1"})).await.unwrap().starts_with("Delete event\n  Review"));
+        assert!(delete.preview(&json!({"uid": "ev9"})).await.unwrap().starts_with("The call would fail"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_input() {
+        let list = CalendarListEventsTool::new(Arc::new(MemoryProvider::default()));
+        let huge = list.execute(json!({"days": 1_000_000_000_000i64})).await.unwrap();
+        assert!(huge.is_error);
+        assert!(huge.output.contains("out of range"));
+
+        let tool = CalendarCreateEventTool::new(Arc::new(MemoryProvider::default()));
+        assert!(tool.execute(json!({"summary": "x", "start": "tomorrow"})).await.is_err());
+        for minutes in [0, -30, i64::MAX] {
+            assert!(tool
+                .execute(json!({"summary": "x", "start": "2024-01-01T10:00:00Z", "duration_minutes": minutes}))
+                .await
+                .is_err());
+        }
+        assert!(tool
+            .execute(json!({"summary": "x", "start": "2024-01-01", "rrule": "FREQ=SOMETIMES"}))
+            .await
+            .is_err());
+        assert!(tool
+            .execute(json!({"summary": "x", "start": "2024-01-01T10:00", "timezone": "Mars/Base"}))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_all_day_start() {
+        let (start, all_day) = parse_time(&json!("2024-05-01"), None).unwrap().unwrap();
+        assert!(all_day);
+        assert_eq!(start.to_rfc3339(), "2024-05-01T00:00:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn test_process_reply() {
+        use crate::itip;
+        use crate::models::ParticipationStatus;
+        use cc_email::send::EmailConfig;
+
+        let provider: Arc<dyn CalendarProvider> = Arc::new(MemoryProvider::default());
+        let mut event = new_event(&json!({
+            "summary": "Planning",
+            "start": "2024-05-02T13:00:00Z",
+            "attendees": ["ann@example.com", "bob@example.com"]
+        }))
+        .unwrap();
+        event.organizer = Some("bot@example.com".to_string());
+        let event = provider.create_event(event).await.unwrap();
+
+        let sender = cc_email::EmailSender::new(EmailConfig {
+            smtp_host: "smtp.example.com".to_string(),
+            smtp_port: 587,
+            smtp_user: "bot@example.com".to_string(),
+            smtp_pass: String::new(),
+            from_address: "bot@example.com".to_string(),
+            from_name: None,
+            oauth: None,
+            allow_insecure_auth: false,
+        })
+        .unwrap();
+        let mailer = InviteMailer::new(Arc::clone(&provider), sender);
+
+        // Ann's mail client answers the invitation
+        let ics = itip::reply(&event, "ann@example.com", ParticipationStatus::Accepted).unwrap();
+        let raw = cc_email::OutgoingEmail::new("bot@example.com", "Accepted: Planning", "Ann has accepted")
+            .calendar("REPLY", ics)
+            .to_rfc5322("ann@example.com", Some("Ann"), "<r@example.com>");
+        let outcome = mailer.process_reply(&ParsedEmail::parse(raw.as_bytes())).await.unwrap();
+        assert_eq!(outcome.changes, vec![("ann@example.com".to_string(), ParticipationStatus::Accepted)]);
+
+        let stored = provider.get_event("ev1").await.unwrap();
+        assert_eq!(stored.status_of("ann@example.com"), ParticipationStatus::Accepted);
+        assert_eq!(stored.status_of("bob@example.com"), ParticipationStatus::NeedsAction);
+        assert_eq!(event_to_json(&stored)["attendee_status"]["ann@example.com"], "accepted");
+
+        // A plain email is not a reply
+        let plain = cc_email::OutgoingEmail::new("bot@example.com", "Re: Planning", "See you there")
+            .to_rfc5322("ann@example.com", None, "<p@example.com>");
+        assert!(mailer.process_reply(&ParsedEmail::parse(plain.as_bytes())).await.is_err());
+    }
+}
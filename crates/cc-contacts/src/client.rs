@@ -1,7 +1,10 @@
 //! CardDAV client implementation
 
 use crate::error::{ContactsError, Result};
-use crate::models::{Contact, ContactsConfig};
+use crate::models::{Contact, ContactPage, ContactQuery, ContactsConfig};
+use crate::sync::{SyncState, SyncSummary};
+use crate::vcard;
+use quick_xml::escape::escape;
 use quick_xml::events::Event;
 use quick_xml::Reader;
 use reqwest::Client;
@@ -38,27 +41,168 @@ impl ContactsClient {
     }
 
     /// Get all contacts from the addressbook
+    ///
+    /// Prefer [`search_contacts`](Self::search_contacts) or
+    /// [`sync`](Self::sync) for large address books.
     pub async fn get_contacts(&self) -> Result<Vec<Contact>> {
-        let addressbook_path = self.addressbook_path();
-        let url = format!("{}/{}", self.base_url, addressbook_path);
+        let (contacts, _) = self.query(&ContactQuery::default()).await?;
+        info!("Fetched {} contacts", contacts.len());
+        Ok(contacts)
+    }
 
-        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
-<D:addressbook-query xmlns:D="DAV:">
+    /// Search contacts on the server and return one page of the matches,
+    /// sorted by name
+    ///
+    /// The text is matched (case-insensitive, substring) against the name,
+    /// nickname, email addresses, phone numbers and organization.
+    pub async fn search_contacts(&self, query: &ContactQuery) -> Result<ContactPage> {
+        let (mut contacts, truncated) = self.query(query).await?;
+        contacts.sort_by_cached_key(|c| c.full_name.to_lowercase());
+
+        let total = contacts.len();
+        let contacts: Vec<Contact> = contacts
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+        let has_more = query.offset + contacts.len() < total;
+        debug!("Search matched {} contacts (truncated: {})", total, truncated);
+
+        Ok(ContactPage {
+            contacts,
+            offset: query.offset,
+            total,
+            has_more,
+            truncated,
+        })
+    }
+
+    /// Bring `state` up to date with the server
+    ///
+    /// Uses WebDAV sync-collection (RFC 6578): only cards changed since the
+    /// stored sync token are downloaded. An expired or missing token falls
+    /// back to a full sync.
+    pub async fn sync(&self, state: &mut SyncState) -> Result<SyncSummary> {
+        let full = state.sync_token.is_none();
+        let changes = match self.sync_collection(state.sync_token.as_deref()).await {
+            Err(ContactsError::SyncTokenExpired(_)) if !full => {
+                info!("Sync token expired, running a full sync");
+                state.sync_token = None;
+                state.contacts.clear();
+                return Box::pin(self.sync(state)).await;
+            }
+            other => other?,
+        };
+
+        let collection = self.addressbook_url();
+        let mut changed_hrefs = Vec::new();
+        let mut listed = std::collections::HashSet::new();
+        let mut deleted = 0;
+        for response in changes.responses {
+            if same_path(&response.href, &collection) {
+                continue;
+            }
+            if response.is_not_found() {
+                if state.contacts.remove(&response.href).is_some() {
+                    deleted += 1;
+                }
+                continue;
+            }
+            listed.insert(response.href.clone());
+            if state
+                .contacts
+                .get(&response.href)
+                .is_none_or(|c| c.etag.is_none() || c.etag != response.etag)
+            {
+                changed_hrefs.push(response.href);
+            }
+        }
+
+        let mut changed = 0;
+        for chunk in changed_hrefs.chunks(MULTIGET_BATCH) {
+            for contact in self.multiget(chunk).await? {
+                if let Some(href) = contact.href.clone() {
+                    state.contacts.insert(href, contact);
+                    changed += 1;
+                }
+            }
+        }
+        if full {
+            // A full sync lists every card; anything else was deleted meanwhile
+            let before = state.contacts.len();
+            state.contacts.retain(|href, _| listed.contains(href));
+            deleted += before - state.contacts.len();
+        }
+        state.sync_token = changes.sync_token;
+
+        info!("Synced contacts: {} changed, {} deleted", changed, deleted);
+        Ok(SyncSummary { changed, deleted, full })
+    }
+
+    /// Run an addressbook-query and return the contacts and whether the
+    /// server truncated the result
+    async fn query(&self, query: &ContactQuery) -> Result<(Vec<Contact>, bool)> {
+        let url = self.addressbook_url();
+        debug!("Querying contacts from: {}", url);
+
+        let text = self.report(&url, "1", &query.to_xml()).await?;
+        let multistatus = parse_multistatus(&text)?;
+        let truncated = multistatus.is_truncated();
+        Ok((self.contacts_from(multistatus), truncated))
+    }
+
+    async fn sync_collection(&self, sync_token: Option<&str>) -> Result<Multistatus> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<D:sync-collection xmlns:D="DAV:">
+    <D:sync-token>{}</D:sync-token>
+    <D:sync-level>1</D:sync-level>
     <D:prop>
         <D:getetag/>
-        <D:address-data/>
     </D:prop>
-</D:addressbook-query>"#;
+</D:sync-collection>"#,
+            escape(sync_token.unwrap_or_default())
+        );
+
+        match self.report(&self.addressbook_url(), "0", &body).await {
+            // RFC 6578: an invalid token fails the DAV:valid-sync-token precondition
+            Err(ContactsError::CarddavError(message)) if message.contains("valid-sync-token") => {
+                Err(ContactsError::SyncTokenExpired(message))
+            }
+            Err(e) => Err(e),
+            Ok(text) => parse_multistatus(&text),
+        }
+    }
 
-        debug!("Fetching contacts from: {}", url);
+    /// Fetch cards by href (addressbook-multiget)
+    async fn multiget(&self, hrefs: &[String]) -> Result<Vec<Contact>> {
+        let hrefs: String = hrefs
+            .iter()
+            .map(|href| format!("    <D:href>{}</D:href>\n", escape(href)))
+            .collect();
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<C:addressbook-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav">
+    <D:prop>
+        <D:getetag/>
+        <C:address-data/>
+    </D:prop>
+{}</C:addressbook-multiget>"#,
+            hrefs
+        );
+        let text = self.report(&self.addressbook_url(), "1", &body).await?;
+        Ok(self.contacts_from(parse_multistatus(&text)?))
+    }
 
+    /// Send a REPORT request and return the response body
+    async fn report(&self, url: &str, depth: &str, body: &str) -> Result<String> {
         let response = self
             .client
-            .request(reqwest::Method::from_bytes(b"REPORT").unwrap(), &url)
+            .request(reqwest::Method::from_bytes(b"REPORT").unwrap(), url)
             .basic_auth(&self.config.username, Some(&self.config.password))
             .header("Content-Type", "application/xml; charset=utf-8")
-            .header("Depth", "1")
-            .body(body)
+            .header("Depth", depth)
+            .body(body.to_string())
             .send()
             .await
             .map_err(|e| ContactsError::Connection(e.to_string()))?;
@@ -73,11 +217,22 @@ impl ContactsClient {
             )));
         }
 
-        let text = response.text().await.map_err(|e| ContactsError::HttpError(e.to_string()))?;
-        let contacts = self.parse_contacts_response(&text)?;
+        response.text().await.map_err(|e| ContactsError::HttpError(e.to_string()))
+    }
 
-        info!("Fetched {} contacts", contacts.len());
-        Ok(contacts)
+    /// Parse the cards of a multistatus response
+    fn contacts_from(&self, multistatus: Multistatus) -> Vec<Contact> {
+        multistatus
+            .responses
+            .into_iter()
+            .filter_map(|response| {
+                let vcard = response.address_data.filter(|d| !d.trim().is_empty())?;
+                let mut contact = vcard::parse_contact(&vcard).ok()?;
+                contact.href = Some(response.href);
+                contact.etag = response.etag;
+                Some(contact)
+            })
+            .collect()
     }
 
     /// Add a new contact
@@ -86,7 +241,7 @@ impl ContactsClient {
         let url = format!("{}/{}", self.base_url, addressbook_path);
 
         let uid = contact.uid.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-        let vcard = self.contact_to_vcard(&contact, &uid);
+        let vcard = vcard::contact_to_vcard(&contact, &uid);
 
         debug!("Adding contact: {}", contact.full_name);
 
@@ -123,17 +278,13 @@ impl ContactsClient {
 
     /// Delete a contact
     pub async fn delete_contact(&self, uid: &str) -> Result<()> {
-        let addressbook_path = self.addressbook_path();
-        let url = format!("{}/{}.vcf", self.base_url, addressbook_path);
+        let url = self.contact_url(uid);
 
         debug!("Deleting contact: {}", uid);
 
         let response = self
             .client
-            .request(
-                reqwest::Method::from_bytes(b"DELETE").unwrap(),
-                format!("{}/{}", url, uid),
-            )
+            .request(reqwest::Method::from_bytes(b"DELETE").unwrap(), &url)
             .basic_auth(&self.config.username, Some(&self.config.password))
             .header("If-Match", "*")
             .send()
@@ -160,27 +311,32 @@ impl ContactsClient {
             ContactsError::UpdateError("Contact UID is required for update".to_string())
         })?;
 
-        let addressbook_path = self.addressbook_path();
-        let url = format!("{}/{}.vcf", self.base_url, addressbook_path);
+        let url = match contact.href {
+            Some(ref href) => self.resolve_href(href),
+            None => self.contact_url(uid),
+        };
 
-        let vcard = self.contact_to_vcard(&contact, uid);
+        let vcard = vcard::contact_to_vcard(&contact, uid);
 
         debug!("Updating contact: {}", uid);
 
         let response = self
             .client
-            .request(
-                reqwest::Method::from_bytes(b"PUT").unwrap(),
-                format!("{}/{}", url, uid),
-            )
+            .request(reqwest::Method::from_bytes(b"PUT").unwrap(), &url)
             .basic_auth(&self.config.username, Some(&self.config.password))
             .header("Content-Type", "text/vcard; charset=utf-8")
-            .header("If-Match", "*")
+            .header("If-Match", contact.etag.as_deref().unwrap_or("*"))
             .body(vcard)
             .send()
             .await
             .map_err(|e| ContactsError::Connection(e.to_string()))?;
 
+        if response.status() == reqwest::StatusCode::PRECONDITION_FAILED {
+            return Err(ContactsError::UpdateError(format!(
+                "{} was modified on the server",
+                uid
+            )));
+        }
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
@@ -192,7 +348,15 @@ impl ContactsClient {
         }
 
         info!("Updated contact: {}", uid);
-        Ok(contact)
+
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let mut updated_contact = contact;
+        updated_contact.etag = etag;
+        Ok(updated_contact)
     }
 
     /// Get list of available addressbooks
@@ -237,42 +401,20 @@ impl ContactsClient {
             .unwrap_or_else(|| "contacts".to_string())
     }
 
-    fn parse_contacts_response(&self, response: &str) -> Result<Vec<Contact>> {
-        let mut contacts = Vec::new();
-        let mut reader = Reader::from_str(response);
-        reader.config_mut().trim_text(true);
-
-        let mut buf = Vec::new();
-        let mut in_address_data = false;
-        let mut current_vcard = String::new();
+    fn addressbook_url(&self) -> String {
+        format!("{}/{}", self.base_url, self.addressbook_path())
+    }
 
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) if e.name().as_ref() == b"address-data" => {
-                    in_address_data = true;
-                    current_vcard.clear();
-                }
-                Ok(Event::End(ref e)) if e.name().as_ref() == b"address-data" => {
-                    in_address_data = false;
-                    if !current_vcard.trim().is_empty() {
-                        if let Ok(contact) = self.parse_vcard(&current_vcard) {
-                            contacts.push(contact);
-                        }
-                    }
-                }
-                Ok(Event::Text(ref e)) if in_address_data => {
-                    current_vcard.push_str(&e.unescape().unwrap_or_default());
-                }
-                Ok(Event::Eof) => break,
-                Err(e) => {
-                    return Err(ContactsError::XmlParseError(e.to_string()));
-                }
-                _ => {}
-            }
-            buf.clear();
-        }
+    fn contact_url(&self, uid: &str) -> String {
+        format!("{}/{}.vcf", self.addressbook_url(), uid)
+    }
 
-        Ok(contacts)
+    /// Absolute URL of a server href
+    fn resolve_href(&self, href: &str) -> String {
+        reqwest::Url::parse(&self.base_url)
+            .and_then(|base| base.join(href))
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| format!("{}{}", self.base_url, href))
     }
 
     fn parse_addressbooks_response(&self, response: &str) -> Result<Vec<String>> {
@@ -286,11 +428,11 @@ impl ContactsClient {
 
         loop {
             match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) if e.name().as_ref() == b"href" => {
+                Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"href" => {
                     in_href = true;
                     current_href.clear();
                 }
-                Ok(Event::End(ref e)) if e.name().as_ref() == b"href" => {
+                Ok(Event::End(ref e)) if e.local_name().as_ref() == b"href" => {
                     in_href = false;
                     let path = current_href.trim().to_string();
                     if path.contains("contacts") || path.ends_with("/") {
@@ -311,149 +453,339 @@ impl ContactsClient {
 
         Ok(addressbooks)
     }
+}
 
-    fn parse_vcard(&self, vcard: &str) -> Result<Contact> {
-        let mut contact = Contact::default();
+/// Cards fetched per addressbook-multiget request
+const MULTIGET_BATCH: usize = 100;
+
+/// One `<D:response>` of a multistatus body
+#[derive(Debug, Default)]
+struct DavResponse {
+    href: String,
+    etag: Option<String>,
+    /// Status of the response itself (not of a propstat)
+    status: Option<String>,
+    address_data: Option<String>,
+}
 
-        for line in vcard.lines() {
-            let line = line.trim();
-            if line.starts_with("FN:") || line.starts_with("FN;") {
-                if let Some(val) = extract_vcard_value(line) {
-                    contact.full_name = val;
-                }
-            } else if line.starts_with("N:") || line.starts_with("N;") {
-                if let Some(parts) = extract_vcard_values(line) {
-                    if parts.len() >= 2 {
-                        contact.last_name = Some(parts[0].clone());
-                        contact.first_name = Some(parts[1].clone());
-                        if contact.full_name.is_empty() {
-                            contact.full_name = format!("{} {}", parts[1], parts[0]).trim().to_string();
-                        }
-                    }
-                }
-            } else if line.starts_with("EMAIL") {
-                if let Some(email) = extract_vcard_value(line) {
-                    if !contact.emails.contains(&email) {
-                        contact.emails.push(email.clone());
-                    }
-                    if contact.email.is_none() {
-                        contact.email = Some(email);
-                    }
-                }
-            } else if line.starts_with("TEL") {
-                if let Some(phone) = extract_vcard_value(line) {
-                    if !contact.phones.contains(&phone) {
-                        contact.phones.push(phone.clone());
-                    }
-                    if contact.phone.is_none() {
-                        contact.phone = Some(phone);
-                    }
-                }
-            } else if line.starts_with("ORG:") || line.starts_with("ORG;") {
-                if let Some(org) = extract_vcard_value(line) {
-                    contact.organization = Some(org);
-                }
-            } else if line.starts_with("TITLE:") || line.starts_with("TITLE;") {
-                if let Some(title) = extract_vcard_value(line) {
-                    contact.title = Some(title);
-                }
-            } else if line.starts_with("NOTE:") || line.starts_with("NOTE;") {
-                if let Some(note) = extract_vcard_value(line) {
-                    contact.note = Some(note);
-                }
-            } else if line.starts_with("URL:") || line.starts_with("URL;") {
-                if let Some(url) = extract_vcard_value(line) {
-                    contact.url = Some(url);
-                }
-            } else if line.starts_with("BDAY:") || line.starts_with("BDAY;") {
-                if let Some(bday) = extract_vcard_value(line) {
-                    contact.birthday = Some(bday);
+impl DavResponse {
+    fn is_not_found(&self) -> bool {
+        self.status.as_deref().is_some_and(|s| s.contains(" 404"))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Multistatus {
+    responses: Vec<DavResponse>,
+    sync_token: Option<String>,
+}
+
+impl Multistatus {
+    /// Whether the server cut the result short (507 on the collection)
+    fn is_truncated(&self) -> bool {
+        self.responses
+            .iter()
+            .any(|r| r.status.as_deref().is_some_and(|s| s.contains(" 507")))
+    }
+}
+
+fn parse_multistatus(xml: &str) -> Result<Multistatus> {
+    let mut multistatus = Multistatus::default();
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut current: Option<DavResponse> = None;
+    let mut in_propstat = false;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"response" => current = Some(DavResponse::default()),
+                    b"propstat" => in_propstat = true,
+                    _ => {}
                 }
-            } else if line.starts_with("UID:") || line.starts_with("UID;") {
-                if let Some(uid) = extract_vcard_value(line) {
-                    contact.uid = Some(uid);
+                text.clear();
+            }
+            Ok(Event::Text(ref e)) => text.push_str(&e.unescape().unwrap_or_default()),
+            Ok(Event::CData(ref e)) => text.push_str(&String::from_utf8_lossy(e)),
+            Ok(Event::End(ref e)) => {
+                let value = text.trim().to_string();
+                match (e.local_name().as_ref(), current.as_mut()) {
+                    (b"response", _) => multistatus.responses.extend(current.take()),
+                    (b"propstat", _) => in_propstat = false,
+                    (b"href", Some(response)) if response.href.is_empty() => response.href = value,
+                    (b"getetag", Some(response)) => response.etag = Some(value).filter(|v| !v.is_empty()),
+                    (b"address-data", Some(response)) => response.address_data = Some(text.clone()),
+                    (b"status", Some(response)) if !in_propstat => response.status = Some(value),
+                    (b"sync-token", None) => multistatus.sync_token = Some(value).filter(|v| !v.is_empty()),
+                    _ => {}
                 }
+                text.clear();
             }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(ContactsError::XmlParseError(e.to_string())),
+            _ => {}
         }
-
-        Ok(contact)
+        buf.clear();
     }
 
-    fn contact_to_vcard(&self, contact: &Contact, uid: &str) -> String {
-        let mut vcard = String::new();
-
-        vcard.push_str("BEGIN:VCARD\r\n");
-        vcard.push_str("VERSION:3.0\r\n");
-        vcard.push_str(&format!("UID:{}\r\n", uid));
-        vcard.push_str(&format!("FN:{}\r\n", contact.full_name));
-
-        if let (Some(first), Some(last)) = (&contact.first_name, &contact.last_name) {
-            vcard.push_str(&format!("N:{};{};;;\r\n", last, first));
-        } else if !contact.full_name.is_empty() {
-            let parts: Vec<&str> = contact.full_name.split_whitespace().collect();
-            if parts.len() >= 2 {
-                let last = parts.last().unwrap();
-                let first = parts[..parts.len() - 1].join(" ");
-                vcard.push_str(&format!("N:{};{};;;\r\n", last, first));
-            }
-        }
+    Ok(multistatus)
+}
 
-        for email in &contact.emails {
-            vcard.push_str(&format!("EMAIL:{}\r\n", email));
-        }
+/// Whether an href and a URL point at the same path
+fn same_path(href: &str, url: &str) -> bool {
+    let path = |s: &str| {
+        reqwest::Url::parse(s)
+            .map(|u| u.path().to_string())
+            .unwrap_or_else(|_| s.to_string())
+            .trim_end_matches('/')
+            .to_string()
+    };
+    path(href) == path(url)
+}
 
-        for phone in &contact.phones {
-            vcard.push_str(&format!("TEL:{}\r\n", phone));
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
-        if let Some(ref org) = contact.organization {
-            vcard.push_str(&format!("ORG:{}\r\n", org));
-        }
+    async fn client(server: &MockServer) -> ContactsClient {
+        ContactsClient::new(ContactsConfig::new(server.uri(), "user", "secret"))
+            .await
+            .unwrap()
+    }
 
-        if let Some(ref title) = contact.title {
-            vcard.push_str(&format!("TITLE:{}\r\n", title));
-        }
+    fn vcard(uid: &str, name: &str, email: &str) -> String {
+        format!(
+            "BEGIN:VCARD\r\nVERSION:3.0\r\nUID:{}\r\nFN:{}\r\nEMAIL:{}\r\nEND:VCARD\r\n",
+            uid, name, email
+        )
+    }
 
-        if let Some(ref note) = contact.note {
-            vcard.push_str(&format!("NOTE:{}\r\n", note));
-        }
+    fn card_response(uid: &str, etag: &str, name: &str) -> String {
+        format!(
+            r#"<d:response>
+    <d:href>/contacts/{uid}.vcf</d:href>
+    <d:propstat>
+        <d:prop>
+            <d:getetag>"{etag}"</d:getetag>
+            <card:address-data><![CDATA[{card}]]></card:address-data>
+        </d:prop>
+        <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+</d:response>"#,
+            uid = uid,
+            etag = etag,
+            card = vcard(uid, name, &format!("{}@example.com", uid))
+        )
+    }
 
-        if let Some(ref url) = contact.url {
-            vcard.push_str(&format!("URL:{}\r\n", url));
-        }
+    fn multistatus(responses: &str, sync_token: Option<&str>) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:card="urn:ietf:params:xml:ns:carddav">
+{}
+{}
+</d:multistatus>"#,
+            responses,
+            sync_token.map(|t| format!("<d:sync-token>{}</d:sync-token>", t)).unwrap_or_default()
+        )
+    }
 
-        if let Some(ref bday) = contact.birthday {
-            vcard.push_str(&format!("BDAY:{}\r\n", bday));
-        }
+    fn listing(uid: &str, etag: &str) -> String {
+        format!(
+            r#"<d:response>
+    <d:href>/contacts/{}.vcf</d:href>
+    <d:propstat>
+        <d:prop><d:getetag>"{}"</d:getetag></d:prop>
+        <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+</d:response>"#,
+            uid, etag
+        )
+    }
 
-        vcard.push_str("END:VCARD\r\n");
+    fn deletion(uid: &str) -> String {
+        format!(
+            "<d:response><d:href>/contacts/{}.vcf</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>",
+            uid
+        )
+    }
 
-        vcard
+    #[test]
+    fn test_parse_multistatus() {
+        let xml = multistatus(
+            &format!(
+                "{}{}<d:response><d:href>/contacts/</d:href><d:status>HTTP/1.1 507 Insufficient Storage</d:status></d:response>",
+                card_response("ann", "1", "Ann Smith"),
+                deletion("bob")
+            ),
+            Some("http://example.com/sync/2"),
+        );
+        let parsed = parse_multistatus(&xml).unwrap();
+        assert_eq!(parsed.responses.len(), 3);
+        assert_eq!(parsed.responses[0].href, "/contacts/ann.vcf");
+        assert_eq!(parsed.responses[0].etag.as_deref(), Some("\"1\""));
+        assert!(parsed.responses[0].address_data.as_deref().unwrap().contains("FN:Ann Smith"));
+        assert!(!parsed.responses[0].is_not_found());
+        assert!(parsed.responses[1].is_not_found());
+        assert!(parsed.is_truncated());
+        assert_eq!(parsed.sync_token.as_deref(), Some("http://example.com/sync/2"));
     }
-}
 
-/// Extract value from vCard property line
-fn extract_vcard_value(line: &str) -> Option<String> {
-    if let Some(colon_pos) = line.find(':') {
-        let value = line[colon_pos + 1..].trim().to_string();
-        if !value.is_empty() {
-            return Some(value);
-        }
+    #[tokio::test]
+    async fn test_search_contacts_pages_sorted_matches() {
+        let server = MockServer::start().await;
+        let body = multistatus(
+            &[
+                card_response("carol", "1", "Carol White"),
+                card_response("ann", "2", "Ann Smith"),
+                card_response("bob", "3", "Bob Jones"),
+            ]
+            .concat(),
+            None,
+        );
+        Mock::given(method("REPORT"))
+            .and(path("/contacts"))
+            .and(header("Depth", "1"))
+            .and(body_string_contains(r#"<C:prop-filter name="EMAIL">"#))
+            .and(body_string_contains(r#"match-type="contains">example</C:text-match>"#))
+            .respond_with(ResponseTemplate::new(207).set_body_string(body))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = client(&server).await;
+
+        let first = client
+            .search_contacts(&ContactQuery::new("example").with_page(0, 2))
+            .await
+            .unwrap();
+        assert_eq!(first.total, 3);
+        assert!(first.has_more);
+        assert!(!first.truncated);
+        let names: Vec<&str> = first.contacts.iter().map(|c| c.full_name.as_str()).collect();
+        assert_eq!(names, ["Ann Smith", "Bob Jones"]);
+        assert_eq!(first.contacts[0].href.as_deref(), Some("/contacts/ann.vcf"));
+        assert_eq!(first.contacts[0].etag.as_deref(), Some("\"2\""));
+
+        let second = client
+            .search_contacts(&ContactQuery::new("example").with_page(2, 2))
+            .await
+            .unwrap();
+        assert_eq!(second.contacts.len(), 1);
+        assert_eq!(second.contacts[0].full_name, "Carol White");
+        assert!(!second.has_more);
     }
-    None
-}
 
-/// Extract multiple values from vCard property (semicolon-separated)
-fn extract_vcard_values(line: &str) -> Option<Vec<String>> {
-    if let Some(colon_pos) = line.find(':') {
-        let values: Vec<String> = line[colon_pos + 1..]
-            .split(';')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-        if !values.is_empty() {
-            return Some(values);
-        }
+    #[tokio::test]
+    async fn test_sync_downloads_only_changes() {
+        let server = MockServer::start().await;
+        let client = client(&server).await;
+        let mut state = SyncState::default();
+
+        // Initial sync lists everything
+        Mock::given(method("REPORT"))
+            .and(body_string_contains("<D:sync-token></D:sync-token>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(multistatus(
+                &[listing("ann", "1"), listing("bob", "1")].concat(),
+                Some("token-1"),
+            )))
+            .mount(&server)
+            .await;
+        Mock::given(method("REPORT"))
+            .and(body_string_contains("addressbook-multiget"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(multistatus(
+                &[card_response("ann", "1", "Ann Smith"), card_response("bob", "1", "Bob Jones")].concat(),
+                None,
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let summary = client.sync(&mut state).await.unwrap();
+        assert_eq!(summary, SyncSummary { changed: 2, deleted: 0, full: true });
+        assert_eq!(state.sync_token.as_deref(), Some("token-1"));
+        assert_eq!(state.contacts.len(), 2);
+        server.verify().await;
+        server.reset().await;
+
+        // Incremental sync: Ann changed, Bob was deleted, Carol is unchanged
+        // (already known with the same ETag) and must not be refetched
+        let mut carol = Contact::new("Carol White");
+        carol.href = Some("/contacts/carol.vcf".to_string());
+        carol.etag = Some("\"7\"".to_string());
+        state.contacts.insert("/contacts/carol.vcf".to_string(), carol);
+
+        Mock::given(method("REPORT"))
+            .and(body_string_contains("<D:sync-token>token-1</D:sync-token>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(multistatus(
+                &[listing("ann", "2"), deletion("bob"), listing("carol", "7")].concat(),
+                Some("token-2"),
+            )))
+            .mount(&server)
+            .await;
+        Mock::given(method("REPORT"))
+            .and(body_string_contains("<D:href>/contacts/ann.vcf</D:href>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(multistatus(
+                &card_response("ann", "2", "Ann Smith-Jones"),
+                None,
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let summary = client.sync(&mut state).await.unwrap();
+        assert_eq!(summary, SyncSummary { changed: 1, deleted: 1, full: false });
+        assert_eq!(state.sync_token.as_deref(), Some("token-2"));
+        assert_eq!(state.contacts.len(), 2);
+        assert_eq!(state.contacts["/contacts/ann.vcf"].full_name, "Ann Smith-Jones");
+        assert_eq!(state.search("white").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_expired_token_runs_full_sync() {
+        let server = MockServer::start().await;
+        let client = client(&server).await;
+
+        let mut state = SyncState {
+            sync_token: Some("stale".to_string()),
+            ..Default::default()
+        };
+        let mut gone = Contact::new("Gone");
+        gone.href = Some("/contacts/gone.vcf".to_string());
+        state.contacts.insert("/contacts/gone.vcf".to_string(), gone);
+
+        Mock::given(method("REPORT"))
+            .and(body_string_contains("<D:sync-token>stale</D:sync-token>"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"<d:error xmlns:d="DAV:"><d:valid-sync-token/></d:error>"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("REPORT"))
+            .and(body_string_contains("<D:sync-token></D:sync-token>"))
+            .respond_with(
+                ResponseTemplate::new(207).set_body_string(multistatus(&listing("ann", "1"), Some("fresh"))),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("REPORT"))
+            .and(body_string_contains("addressbook-multiget"))
+            .respond_with(
+                ResponseTemplate::new(207).set_body_string(multistatus(&card_response("ann", "1", "Ann Smith"), None)),
+            )
+            .mount(&server)
+            .await;
+
+        let summary = client.sync(&mut state).await.unwrap();
+        assert!(summary.full);
+        assert_eq!(summary.changed, 1);
+        assert_eq!(state.sync_token.as_deref(), Some("fresh"));
+        let names: Vec<&str> = state.contacts().map(|c| c.full_name.as_str()).collect();
+        assert_eq!(names, ["Ann Smith"]);
     }
-    None
 }
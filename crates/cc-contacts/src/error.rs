@@ -35,6 +35,12 @@ pub enum ContactsError {
     #[error("Update error: {0}")]
     UpdateError(String),
 
+    #[error("Sync token expired: {0}")]
+    SyncTokenExpired(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
     #[error("Parse error: {0}")]
     ParseError(String),
 
@@ -6,6 +6,11 @@
 //!
 //! - CardDAV client for contact access
 //! - Contact creation, retrieval, and deletion
+//! - vCard 3.0/4.0 parsing and serialization (folding, escaping, groups,
+//!   typed emails/phones, multiple addresses, embedded photos)
+//! - Server-side search with paging
+//! - Incremental sync (WebDAV sync-collection) with a persisted sync token
+//! - Agent tools (`contact_search`, `contact_add`, ...)
 //! - Support for multiple contact providers
 //!
 //! ## Usage
@@ -37,10 +42,17 @@
 pub mod client;
 pub mod error;
 pub mod models;
+pub mod sync;
+pub mod tools;
+pub mod vcard;
 
 pub use client::ContactsClient;
 pub use error::{ContactsError, Result};
-pub use models::{Contact, ContactsConfig};
+pub use models::{Contact, ContactPage, ContactQuery, ContactsConfig};
+pub use sync::{SyncState, SyncSummary};
+pub use tools::{
+    ContactAddTool, ContactDeleteTool, ContactSearchTool, ContactUpdateTool, register_contacts_tools,
+};
 
 /// Re-export models for easy use
 pub mod prelude {
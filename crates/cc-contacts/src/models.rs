@@ -1,7 +1,16 @@
 //! Data models for contacts integration
 
+use std::collections::BTreeMap;
+
+use quick_xml::escape::escape;
 use serde::{Deserialize, Serialize};
 
+/// Upper bound on server-side search results
+const MAX_SEARCH_RESULTS: usize = 1000;
+
+/// vCard properties a text search looks at
+const SEARCH_PROPERTIES: [&str; 5] = ["FN", "NICKNAME", "EMAIL", "TEL", "ORG"];
+
 /// Contacts configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ContactsConfig {
@@ -32,6 +41,19 @@ impl ContactsConfig {
         self.addressbook_id = Some(addressbook_id.into());
         self
     }
+
+    /// Load from `CARDDAV_URL`, `CARDDAV_USERNAME`, `CARDDAV_PASSWORD` and
+    /// optional `CARDDAV_ADDRESSBOOK`; `None` if not configured
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let mut config = Self::new(
+            var("CARDDAV_URL")?,
+            var("CARDDAV_USERNAME")?,
+            var("CARDDAV_PASSWORD")?,
+        );
+        config.addressbook_id = var("CARDDAV_ADDRESSBOOK");
+        Some(config)
+    }
 }
 
 /// Contact information
@@ -55,12 +77,18 @@ pub struct Contact {
     /// Primary email (convenience field)
     #[serde(default)]
     pub email: Option<String>,
+    /// vCard `TYPE`s (`home`, `work`, ...) by email address
+    #[serde(default)]
+    pub email_types: BTreeMap<String, Vec<String>>,
     /// Phone numbers
     #[serde(default)]
     pub phones: Vec<String>,
     /// Primary phone (convenience field)
     #[serde(default)]
     pub phone: Option<String>,
+    /// vCard `TYPE`s (`cell`, `work`, ...) by phone number
+    #[serde(default)]
+    pub phone_types: BTreeMap<String, Vec<String>>,
     /// Postal addresses
     #[serde(default)]
     pub addresses: Vec<PostalAddress>,
@@ -79,7 +107,7 @@ pub struct Contact {
     /// URL/Website
     #[serde(default)]
     pub url: Option<String>,
-    /// Photo URL
+    /// Photo URL, or a `data:` URI for embedded photos
     #[serde(default)]
     pub photo_url: Option<String>,
     /// Birthday
@@ -88,6 +116,19 @@ pub struct Contact {
     /// Anniversary
     #[serde(default)]
     pub anniversary: Option<String>,
+    /// vCard version the contact was read from (`3.0` or `4.0`)
+    #[serde(default)]
+    pub vcard_version: Option<String>,
+    /// vCard properties without a dedicated field, as unfolded content
+    /// lines, written back unchanged
+    #[serde(default)]
+    pub other_properties: Vec<String>,
+    /// Server ETag of the vCard, used for conditional updates
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// Server path of the vCard
+    #[serde(default)]
+    pub href: Option<String>,
 }
 
 impl Contact {
@@ -130,11 +171,43 @@ impl Contact {
         self.note = Some(note.into());
         self
     }
+
+    /// Whether the name, an email address, a phone number or the
+    /// organization contains `query` (case-insensitive; phone numbers are
+    /// compared by digits)
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        let text_match = [Some(&self.full_name), self.organization.as_ref()]
+            .into_iter()
+            .flatten()
+            .chain(&self.emails)
+            .chain(&self.email)
+            .any(|text| text.to_lowercase().contains(&query));
+        let digits: String = query.chars().filter(char::is_ascii_digit).collect();
+        text_match
+            || (digits.len() >= 3
+                && self.phones.iter().chain(&self.phone).any(|phone| {
+                    phone
+                        .chars()
+                        .filter(char::is_ascii_digit)
+                        .collect::<String>()
+                        .contains(&digits)
+                }))
+    }
 }
 
 /// Postal address
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PostalAddress {
+    /// Post office box
+    #[serde(default)]
+    pub po_box: Option<String>,
+    /// Extended address (apartment, suite)
+    #[serde(default)]
+    pub extended: Option<String>,
     /// Street address
     #[serde(default)]
     pub street: Option<String>,
@@ -182,3 +255,82 @@ impl PostalAddress {
         self
     }
 }
+
+/// A contact search
+#[derive(Debug, Clone, Default)]
+pub struct ContactQuery {
+    /// Text to look for (all contacts when `None`)
+    pub text: Option<String>,
+    /// Matches to skip
+    pub offset: usize,
+    /// Maximum number of matches to return
+    pub limit: Option<usize>,
+}
+
+impl ContactQuery {
+    /// Search for contacts containing `text`
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()).filter(|t: &String| !t.trim().is_empty()),
+            ..Default::default()
+        }
+    }
+
+    /// Return `limit` matches starting at `offset`
+    pub fn with_page(mut self, offset: usize, limit: usize) -> Self {
+        self.offset = offset;
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The addressbook-query REPORT body (RFC 6352)
+    pub(crate) fn to_xml(&self) -> String {
+        let filter = match self.text {
+            Some(ref text) => {
+                let text = escape(text.trim());
+                let prop_filters: String = SEARCH_PROPERTIES
+                    .iter()
+                    .map(|name| {
+                        format!(
+                            r#"        <C:prop-filter name="{}">
+            <C:text-match collation="i;unicode-casemap" match-type="contains">{}</C:text-match>
+        </C:prop-filter>
+"#,
+                            name, text
+                        )
+                    })
+                    .collect();
+                format!("    <C:filter test=\"anyof\">\n{}    </C:filter>", prop_filters)
+            }
+            None => "    <C:filter/>".to_string(),
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<C:addressbook-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav">
+    <D:prop>
+        <D:getetag/>
+        <C:address-data/>
+    </D:prop>
+{}
+    <C:limit>
+        <C:nresults>{}</C:nresults>
+    </C:limit>
+</C:addressbook-query>"#,
+            filter, MAX_SEARCH_RESULTS
+        )
+    }
+}
+
+/// One page of search results
+#[derive(Debug, Clone, Default)]
+pub struct ContactPage {
+    pub contacts: Vec<Contact>,
+    /// Offset of the first contact in this page
+    pub offset: usize,
+    /// Number of matches across all pages
+    pub total: usize,
+    /// Whether more matches follow this page
+    pub has_more: bool,
+    /// Whether the server stopped before returning every match
+    pub truncated: bool,
+}
@@ -0,0 +1,95 @@
+//! Incremental address book synchronization state
+//!
+//! [`SyncState`] holds the last WebDAV sync token and the contacts seen so
+//! far. Persist it between runs so [`ContactsClient::sync`] only downloads
+//! cards that changed since the previous sync.
+//!
+//! [`ContactsClient::sync`]: crate::ContactsClient::sync
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ContactsError, Result};
+use crate::models::Contact;
+
+/// Sync token and local copy of the address book
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    /// Token returned by the last sync (`None` before the first sync)
+    #[serde(default)]
+    pub sync_token: Option<String>,
+    /// Contacts by server href
+    #[serde(default)]
+    pub contacts: BTreeMap<String, Contact>,
+}
+
+impl SyncState {
+    /// Load the state from a JSON file (empty state if the file is missing)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| ContactsError::Storage(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(ContactsError::Storage(format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    /// Save the state as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| ContactsError::Storage(e.to_string()))?;
+        std::fs::write(path, json)
+            .map_err(|e| ContactsError::Storage(format!("{}: {}", path.display(), e)))
+    }
+
+    /// All synced contacts
+    pub fn contacts(&self) -> impl Iterator<Item = &Contact> {
+        self.contacts.values()
+    }
+
+    /// Synced contacts matching `query` (see [`Contact::matches`])
+    pub fn search<'a>(&'a self, query: &'a str) -> impl Iterator<Item = &'a Contact> + 'a {
+        self.contacts.values().filter(move |c| c.matches(query))
+    }
+}
+
+/// Outcome of a sync
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    /// Cards added or modified
+    pub changed: usize,
+    /// Cards removed
+    pub deleted: usize,
+    /// Whether all cards were listed (first sync or expired token)
+    pub full: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() {
+        let path = std::env::temp_dir().join(format!("cc-contacts-sync-{}.json", std::process::id()));
+        assert!(SyncState::load(&path).unwrap().sync_token.is_none());
+
+        let mut state = SyncState {
+            sync_token: Some("token-1".to_string()),
+            ..Default::default()
+        };
+        let mut contact = Contact::new("Ann Smith").with_email("ann@example.com");
+        contact.etag = Some("\"1\"".to_string());
+        state.contacts.insert("/contacts/ann.vcf".to_string(), contact);
+        state.save(&path).unwrap();
+
+        let loaded = SyncState::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.sync_token.as_deref(), Some("token-1"));
+        assert_eq!(loaded.contacts["/contacts/ann.vcf"].etag.as_deref(), Some("\"1\""));
+        assert_eq!(loaded.search("ann@").count(), 1);
+    }
+}
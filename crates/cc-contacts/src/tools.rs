@@ -0,0 +1,386 @@
+//! Contacts tools for cc-gateway
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use cc_core::{Tool, ToolResult};
+
+use crate::client::ContactsClient;
+use crate::models::{Contact, ContactQuery};
+
+/// Contacts returned by a search unless a limit is given
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Contact search tool
+pub struct ContactSearchTool {
+    client: Arc<ContactsClient>,
+}
+
+impl ContactSearchTool {
+    pub fn new(client: Arc<ContactsClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for ContactSearchTool {
+    fn name(&self) -> &str {
+        "contact_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the address book by name, email, phone number or organization"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Text to look for (empty lists all contacts)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of contacts to return (default: 20)",
+                    "default": 20
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Matches to skip, for the next page (default: 0)",
+                    "default": 0
+                }
+            }
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let query = input["query"].as_str().unwrap_or_default();
+        let limit = input["limit"]
+            .as_u64()
+            .map_or(DEFAULT_SEARCH_LIMIT, |l| l as usize);
+        let offset = input["offset"].as_u64().unwrap_or(0) as usize;
+
+        let page = self
+            .client
+            .search_contacts(&ContactQuery::new(query).with_page(offset, limit))
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "query": query,
+            "total": page.total,
+            "offset": page.offset,
+            "has_more": page.has_more,
+            "contacts": page.contacts.iter().map(contact_to_json).collect::<Vec<_>>()
+        })).unwrap_or_default()))
+    }
+}
+
+/// Contact creation tool
+pub struct ContactAddTool {
+    client: Arc<ContactsClient>,
+}
+
+impl ContactAddTool {
+    pub fn new(client: Arc<ContactsClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for ContactAddTool {
+    fn name(&self) -> &str {
+        "contact_add"
+    }
+
+    fn description(&self) -> &str {
+        "Add a contact to the address book"
+    }
+
+    fn input_schema(&self) -> Value {
+        contact_schema(&["full_name"])
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let full_name = input["full_name"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'full_name' parameter".to_string()))?;
+        let mut contact = Contact::new(full_name);
+        apply_fields(&mut contact, &input);
+
+        let created = self
+            .client
+            .add_contact(contact)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "added",
+            "contact": contact_to_json(&created)
+        })).unwrap_or_default()))
+    }
+}
+
+/// Contact update tool
+pub struct ContactUpdateTool {
+    client: Arc<ContactsClient>,
+}
+
+impl ContactUpdateTool {
+    pub fn new(client: Arc<ContactsClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for ContactUpdateTool {
+    fn name(&self) -> &str {
+        "contact_update"
+    }
+
+    fn description(&self) -> &str {
+        "Change a contact; only the given fields are modified (emails and phones replace the existing lists)"
+    }
+
+    fn input_schema(&self) -> Value {
+        let mut schema = contact_schema(&["uid"]);
+        schema["properties"]["uid"] = json!({
+            "type": "string",
+            "description": "Contact UID from contact_search"
+        });
+        schema
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let uid = input["uid"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'uid' parameter".to_string()))?;
+        let mut contact = self
+            .client
+            .get_contacts()
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?
+            .into_iter()
+            .find(|c| c.uid.as_deref() == Some(uid))
+            .ok_or_else(|| cc_core::Error::ToolExecution(format!("Contact not found: {}", uid)))?;
+
+        if let Some(full_name) = input["full_name"].as_str() {
+            contact.full_name = full_name.to_string();
+        }
+        apply_fields(&mut contact, &input);
+
+        let updated = self
+            .client
+            .update_contact(contact)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "updated",
+            "contact": contact_to_json(&updated)
+        })).unwrap_or_default()))
+    }
+}
+
+/// Contact deletion tool
+pub struct ContactDeleteTool {
+    client: Arc<ContactsClient>,
+}
+
+impl ContactDeleteTool {
+    pub fn new(client: Arc<ContactsClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Tool for ContactDeleteTool {
+    fn name(&self) -> &str {
+        "contact_delete"
+    }
+
+    fn description(&self) -> &str {
+        "Delete a contact from the address book"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "uid": {
+                    "type": "string",
+                    "description": "Contact UID from contact_search"
+                }
+            },
+            "required": ["uid"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let uid = input["uid"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'uid' parameter".to_string()))?;
+        self.client
+            .delete_contact(uid)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "deleted",
+            "uid": uid
+        })).unwrap_or_default()))
+    }
+}
+
+/// Register all contacts tools backed by `client`
+pub fn register_contacts_tools(manager: &mut cc_core::ToolManager, client: Arc<ContactsClient>) {
+    manager.register(Arc::new(ContactSearchTool::new(Arc::clone(&client))));
+    manager.register(Arc::new(ContactAddTool::new(Arc::clone(&client))));
+    manager.register(Arc::new(ContactUpdateTool::new(Arc::clone(&client))));
+    manager.register(Arc::new(ContactDeleteTool::new(client)));
+}
+
+/// Input schema shared by the add and update tools
+fn contact_schema(required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "full_name": {
+                "type": "string",
+                "description": "Display name"
+            },
+            "first_name": {
+                "type": "string",
+                "description": "Given name"
+            },
+            "last_name": {
+                "type": "string",
+                "description": "Family name"
+            },
+            "emails": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Email addresses (the first is the primary one)"
+            },
+            "phones": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Phone numbers (the first is the primary one)"
+            },
+            "organization": {
+                "type": "string",
+                "description": "Company or organization"
+            },
+            "title": {
+                "type": "string",
+                "description": "Job title"
+            },
+            "birthday": {
+                "type": "string",
+                "description": "Birthday (YYYY-MM-DD)"
+            },
+            "note": {
+                "type": "string",
+                "description": "Free-form note"
+            }
+        },
+        "required": required
+    })
+}
+
+/// Optional contact fields from the tool input
+fn apply_fields(contact: &mut Contact, input: &Value) {
+    let text = |key: &str| input[key].as_str().map(str::to_string);
+    let list = |key: &str| {
+        input[key].as_array().map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    if let Some(first_name) = text("first_name") {
+        contact.first_name = Some(first_name);
+    }
+    if let Some(last_name) = text("last_name") {
+        contact.last_name = Some(last_name);
+    }
+    if let Some(emails) = list("emails") {
+        contact.email = emails.first().cloned();
+        contact.emails = emails;
+    }
+    if let Some(phones) = list("phones") {
+        contact.phone = phones.first().cloned();
+        contact.phones = phones;
+    }
+    if let Some(organization) = text("organization") {
+        contact.organization = Some(organization);
+    }
+    if let Some(title) = text("title") {
+        contact.title = Some(title);
+    }
+    if let Some(birthday) = text("birthday") {
+        contact.birthday = Some(birthday);
+    }
+    if let Some(note) = text("note") {
+        contact.note = Some(note);
+    }
+}
+
+fn contact_to_json(contact: &Contact) -> Value {
+    json!({
+        "uid": contact.uid,
+        "full_name": contact.full_name,
+        "emails": contact.emails,
+        "phones": contact.phones,
+        "organization": contact.organization,
+        "title": contact.title,
+        "birthday": contact.birthday,
+        "note": contact.note
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_fields() {
+        let mut contact = Contact::new("Ann Smith").with_email("old@example.com");
+        apply_fields(
+            &mut contact,
+            &json!({
+                "emails": ["ann@example.com", "ann@work.example"],
+                "phones": ["+49 30 123456"],
+                "organization": "Example GmbH"
+            }),
+        );
+        assert_eq!(contact.email.as_deref(), Some("ann@example.com"));
+        assert_eq!(contact.emails.len(), 2);
+        assert_eq!(contact.phone.as_deref(), Some("+49 30 123456"));
+        assert_eq!(contact.organization.as_deref(), Some("Example GmbH"));
+    }
+
+    #[test]
+    fn test_contact_matches() {
+        let contact = Contact::new("Ann Smith")
+            .with_email("ann@example.com")
+            .with_phone("+49 (30) 123-456")
+            .with_organization("Example GmbH");
+        assert!(contact.matches("smith"));
+        assert!(contact.matches("EXAMPLE.COM"));
+        assert!(contact.matches("30123456"));
+        assert!(contact.matches("gmbh"));
+        assert!(!contact.matches("bob"));
+        assert!(contact.matches(""));
+    }
+}
@@ -0,0 +1,758 @@
+//! vCard 3.0 (RFC 2426) and 4.0 (RFC 6350) parsing and serialization
+//!
+//! Handles line folding, value escaping, property groups (`item1.EMAIL`),
+//! quoted parameters and inline photos. Properties without a [`Contact`]
+//! field are kept in [`Contact::other_properties`] so updates don't drop
+//! them.
+
+use std::fmt;
+
+use crate::error::{ContactsError, Result};
+use crate::models::{Contact, PostalAddress};
+
+/// Maximum line length in octets before folding (RFC 6350 section 3.2)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Properties rewritten on every serialization and never kept verbatim
+const GENERATED_PROPERTIES: [&str; 5] = ["BEGIN", "END", "VERSION", "PRODID", "REV"];
+
+/// A single content line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    /// Group prefix (`item1` in `item1.EMAIL`)
+    pub group: Option<String>,
+    /// Upper-case property name
+    pub name: String,
+    /// Parameters with their (unquoted) values
+    pub params: Vec<(String, Vec<String>)>,
+    /// Raw value, still escaped
+    pub value: String,
+}
+
+impl Property {
+    /// Create a property with an already escaped value
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            group: None,
+            name: name.into().to_ascii_uppercase(),
+            params: Vec::new(),
+            value: value.into(),
+        }
+    }
+
+    /// Create a property with a text value
+    pub fn text(name: impl Into<String>, text: &str) -> Self {
+        Self::new(name, escape_text(text))
+    }
+
+    /// Create a property with a structured value (`ADR`, `N`, `ORG`)
+    pub fn structured(name: impl Into<String>, components: &[&str]) -> Self {
+        let value = components
+            .iter()
+            .map(|c| escape_text(c))
+            .collect::<Vec<_>>()
+            .join(";");
+        Self::new(name, value)
+    }
+
+    /// Add a parameter
+    pub fn with_param(mut self, name: impl Into<String>, values: Vec<String>) -> Self {
+        if !values.is_empty() {
+            self.params.push((name.into().to_ascii_uppercase(), values));
+        }
+        self
+    }
+
+    /// Parse an unfolded content line
+    pub fn parse(line: &str) -> Option<Self> {
+        let name_end = line.find([';', ':'])?;
+        let (group, name) = match line[..name_end].rsplit_once('.') {
+            Some((group, name)) => (Some(group.to_string()), name),
+            None => (None, &line[..name_end]),
+        };
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        let mut rest = &line[name_end..];
+        while let Some(tail) = rest.strip_prefix(';') {
+            let (param, remaining) = split_unquoted(tail, &[';', ':']);
+            let (param_name, values) = match param.split_once('=') {
+                Some((n, v)) => (n.to_ascii_uppercase(), split_param_values(v)),
+                // vCard 2.1/3.0 shorthand: `TEL;CELL:` means `TYPE=CELL`
+                None => ("TYPE".to_string(), vec![param.to_string()]),
+            };
+            params.push((param_name, values));
+            rest = remaining;
+        }
+
+        Some(Self {
+            group,
+            name: name.to_ascii_uppercase(),
+            params,
+            value: rest.strip_prefix(':')?.to_string(),
+        })
+    }
+
+    /// Serialize to a single (unfolded) content line
+    pub fn to_line(&self) -> String {
+        let mut line = String::new();
+        if let Some(ref group) = self.group {
+            line.push_str(group);
+            line.push('.');
+        }
+        line.push_str(&self.name);
+        for (name, values) in &self.params {
+            line.push(';');
+            line.push_str(name);
+            line.push('=');
+            let values: Vec<String> = values.iter().map(|v| quote_param(v)).collect();
+            line.push_str(&values.join(","));
+        }
+        line.push(':');
+        line.push_str(&self.value);
+        line
+    }
+
+    /// Values of a parameter (case-insensitive name)
+    pub fn param(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.params
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .flat_map(|(_, values)| values.iter().map(String::as_str))
+    }
+
+    /// Lower-case `TYPE` values, without `pref`
+    pub fn types(&self) -> Vec<String> {
+        // Servers also send `TYPE="work,voice"` as one quoted value
+        self.param("TYPE")
+            .flat_map(|t| t.split(','))
+            .map(|t| t.trim().to_ascii_lowercase())
+            .filter(|t| !t.is_empty() && t != "pref")
+            .collect()
+    }
+
+    /// Whether the property is marked preferred (`TYPE=pref` or `PREF=`)
+    pub fn is_preferred(&self) -> bool {
+        self.param("TYPE")
+            .flat_map(|t| t.split(','))
+            .any(|t| t.trim().eq_ignore_ascii_case("pref")) || self.param("PREF").next().is_some()
+    }
+
+    /// Unescaped text value
+    pub fn text_value(&self) -> String {
+        unescape_text(&self.value)
+    }
+
+    /// Unescaped components of a structured value
+    pub fn components(&self) -> Vec<String> {
+        split_escaped(&self.value, ';')
+            .into_iter()
+            .map(|c| unescape_text(&c))
+            .collect()
+    }
+}
+
+/// A parsed vCard
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VCard {
+    /// `VERSION` value (`3.0` or `4.0`)
+    pub version: Option<String>,
+    /// All properties except `BEGIN`, `END` and `VERSION`, in order
+    pub properties: Vec<Property>,
+}
+
+impl VCard {
+    /// Parse the first vCard in `text`
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut card = Self::default();
+        let mut in_card = false;
+        for line in unfold(text) {
+            let Some(property) = Property::parse(&line) else {
+                continue;
+            };
+            match property.name.as_str() {
+                "BEGIN" if property.value.eq_ignore_ascii_case("VCARD") => in_card = true,
+                "END" if property.value.eq_ignore_ascii_case("VCARD") && in_card => return Ok(card),
+                "VERSION" if in_card => card.version = Some(property.value.trim().to_string()),
+                _ if in_card => card.properties.push(property),
+                _ => {}
+            }
+        }
+        if in_card {
+            // Tolerate a missing END:VCARD
+            Ok(card)
+        } else {
+            Err(ContactsError::ParseError("No BEGIN:VCARD found".to_string()))
+        }
+    }
+
+    /// First property named `name`
+    pub fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Serializes with CRLF line endings and folded lines
+impl fmt::Display for VCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BEGIN:VCARD\r\n")?;
+        f.write_str(&fold(&format!("VERSION:{}", self.version.as_deref().unwrap_or("3.0"))))?;
+        for property in &self.properties {
+            f.write_str(&fold(&property.to_line()))?;
+        }
+        f.write_str("END:VCARD\r\n")
+    }
+}
+
+/// Parse a vCard into a [`Contact`]
+pub fn parse_contact(text: &str) -> Result<Contact> {
+    let card = VCard::parse(text)?;
+    let mut contact = Contact {
+        vcard_version: card.version.clone(),
+        ..Default::default()
+    };
+
+    // Apple-style labels (`item1.X-ABLabel:Work`) become types of the
+    // grouped property they describe
+    let label = |group: &Option<String>| {
+        let group = group.as_deref()?;
+        card.properties
+            .iter()
+            .find(|p| p.name == "X-ABLABEL" && p.group.as_deref() == Some(group))
+            .map(|p| {
+                p.text_value()
+                    .trim_start_matches("_$!<")
+                    .trim_end_matches(">!$_")
+                    .to_lowercase()
+            })
+    };
+    let mapped_groups: Vec<&str> = card
+        .properties
+        .iter()
+        .filter(|p| matches!(p.name.as_str(), "EMAIL" | "TEL" | "ADR"))
+        .filter_map(|p| p.group.as_deref())
+        .collect();
+
+    let mut preferred_email = None;
+    let mut preferred_phone = None;
+    for property in &card.properties {
+        match property.name.as_str() {
+            "FN" if contact.full_name.is_empty() => contact.full_name = property.text_value(),
+            "N" if contact.first_name.is_none() && contact.last_name.is_none() => {
+                let parts = property.components();
+                let part = |i: usize| parts.get(i).filter(|p| !p.is_empty()).cloned();
+                contact.last_name = part(0);
+                contact.first_name = part(1);
+            }
+            "EMAIL" => {
+                let email = property.text_value();
+                if email.is_empty() || contact.emails.contains(&email) {
+                    continue;
+                }
+                let mut types: Vec<String> = property.types().into_iter().filter(|t| t != "internet").collect();
+                types.extend(label(&property.group));
+                if !types.is_empty() {
+                    contact.email_types.insert(email.clone(), types);
+                }
+                if property.is_preferred() && preferred_email.is_none() {
+                    preferred_email = Some(email.clone());
+                }
+                contact.emails.push(email);
+            }
+            "TEL" => {
+                let value = property.text_value();
+                let phone = value.strip_prefix("tel:").unwrap_or(&value).to_string();
+                if phone.is_empty() || contact.phones.contains(&phone) {
+                    continue;
+                }
+                let mut types = property.types();
+                types.extend(label(&property.group));
+                if !types.is_empty() {
+                    contact.phone_types.insert(phone.clone(), types);
+                }
+                if property.is_preferred() && preferred_phone.is_none() {
+                    preferred_phone = Some(phone.clone());
+                }
+                contact.phones.push(phone);
+            }
+            "ADR" => {
+                let parts = property.components();
+                let part = |i: usize| parts.get(i).filter(|p| !p.is_empty()).cloned();
+                contact.addresses.push(PostalAddress {
+                    po_box: part(0),
+                    extended: part(1),
+                    street: part(2),
+                    city: part(3),
+                    region: part(4),
+                    postal_code: part(5),
+                    country: part(6),
+                    address_type: property.types().into_iter().next().or_else(|| label(&property.group)),
+                    is_preferred: property.is_preferred(),
+                });
+            }
+            "ORG" if contact.organization.is_none() => {
+                let mut parts = property.components().into_iter().filter(|p| !p.is_empty());
+                contact.organization = parts.next();
+                contact.department = parts.next();
+            }
+            "TITLE" if contact.title.is_none() => contact.title = Some(property.text_value()),
+            "NOTE" if contact.note.is_none() => contact.note = Some(property.text_value()),
+            "URL" if contact.url.is_none() => contact.url = Some(property.text_value()),
+            "BDAY" if contact.birthday.is_none() => contact.birthday = Some(property.value.clone()),
+            "ANNIVERSARY" | "X-ANNIVERSARY" if contact.anniversary.is_none() => {
+                contact.anniversary = Some(property.value.clone())
+            }
+            "PHOTO" if contact.photo_url.is_none() => contact.photo_url = Some(photo_uri(property)),
+            "UID" if contact.uid.is_none() => contact.uid = Some(property.text_value()),
+            "X-ABLABEL" if property.group.as_deref().is_some_and(|g| mapped_groups.contains(&g)) => {}
+            name if GENERATED_PROPERTIES.contains(&name) => {}
+            _ => contact.other_properties.push(property.to_line()),
+        }
+    }
+
+    contact.email = preferred_email.or_else(|| contact.emails.first().cloned());
+    contact.phone = preferred_phone.or_else(|| contact.phones.first().cloned());
+    if contact.full_name.is_empty() {
+        contact.full_name = [contact.first_name.as_deref(), contact.last_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    Ok(contact)
+}
+
+/// Serialize a [`Contact`] as a vCard with the given UID
+///
+/// Uses the version the contact was read with (3.0 for new contacts).
+pub fn contact_to_vcard(contact: &Contact, uid: &str) -> String {
+    let v4 = contact.vcard_version.as_deref() == Some("4.0");
+    let mut card = VCard {
+        version: Some(if v4 { "4.0" } else { "3.0" }.to_string()),
+        properties: Vec::new(),
+    };
+    let props = &mut card.properties;
+    let pref = |preferred: bool, types: Vec<String>| -> Vec<(String, Vec<String>)> {
+        let mut params = Vec::new();
+        let mut types = types;
+        if preferred && !v4 {
+            types.push("pref".to_string());
+        }
+        if !types.is_empty() {
+            params.push(("TYPE".to_string(), types));
+        }
+        if preferred && v4 {
+            params.push(("PREF".to_string(), vec!["1".to_string()]));
+        }
+        params
+    };
+
+    props.push(Property::text("UID", uid));
+    props.push(Property::text("FN", &contact.full_name));
+
+    let (last, first) = match (&contact.first_name, &contact.last_name) {
+        (None, None) => match contact.full_name.rsplit_once(' ') {
+            Some((first, last)) => (last.trim().to_string(), first.trim().to_string()),
+            None => (contact.full_name.clone(), String::new()),
+        },
+        (first, last) => (
+            last.clone().unwrap_or_default(),
+            first.clone().unwrap_or_default(),
+        ),
+    };
+    props.push(Property::structured("N", &[&last, &first, "", "", ""]));
+
+    let primary_email = contact.email.as_ref().or(contact.emails.first());
+    let emails = contact.emails.iter().chain(contact.email.iter().filter(|e| !contact.emails.contains(e)));
+    for email in emails {
+        let preferred = contact.emails.len() > 1 && Some(email) == primary_email;
+        let mut types = contact.email_types.get(email).cloned().unwrap_or_default();
+        if !v4 && types.is_empty() {
+            types.push("internet".to_string());
+        }
+        let mut property = Property::text("EMAIL", email);
+        property.params = pref(preferred, types);
+        props.push(property);
+    }
+
+    let primary_phone = contact.phone.as_ref().or(contact.phones.first());
+    let phones = contact.phones.iter().chain(contact.phone.iter().filter(|p| !contact.phones.contains(p)));
+    for phone in phones {
+        let preferred = contact.phones.len() > 1 && Some(phone) == primary_phone;
+        let types = contact.phone_types.get(phone).cloned().unwrap_or_default();
+        let mut property = Property::text("TEL", phone);
+        property.params = pref(preferred, types);
+        props.push(property);
+    }
+
+    for address in &contact.addresses {
+        let part = |p: &Option<String>| p.clone().unwrap_or_default();
+        let components = [
+            part(&address.po_box),
+            part(&address.extended),
+            part(&address.street),
+            part(&address.city),
+            part(&address.region),
+            part(&address.postal_code),
+            part(&address.country),
+        ];
+        let components: Vec<&str> = components.iter().map(String::as_str).collect();
+        let mut property = Property::structured("ADR", &components);
+        property.params = pref(address.is_preferred, address.address_type.iter().cloned().collect());
+        props.push(property);
+    }
+
+    if contact.organization.is_some() || contact.department.is_some() {
+        let organization = contact.organization.as_deref().unwrap_or_default();
+        let components: Vec<&str> = std::iter::once(organization)
+            .chain(contact.department.as_deref())
+            .collect();
+        props.push(Property::structured("ORG", &components));
+    }
+    if let Some(ref title) = contact.title {
+        props.push(Property::text("TITLE", title));
+    }
+    if let Some(ref note) = contact.note {
+        props.push(Property::text("NOTE", note));
+    }
+    if let Some(ref url) = contact.url {
+        props.push(Property::new("URL", url.clone()));
+    }
+    if let Some(ref birthday) = contact.birthday {
+        props.push(Property::new("BDAY", birthday.clone()));
+    }
+    if let Some(ref anniversary) = contact.anniversary {
+        props.push(Property::new(if v4 { "ANNIVERSARY" } else { "X-ANNIVERSARY" }, anniversary.clone()));
+    }
+    if let Some(ref photo) = contact.photo_url {
+        props.push(photo_property(photo, v4));
+    }
+    props.extend(contact.other_properties.iter().filter_map(|line| Property::parse(line)));
+
+    card.to_string()
+}
+
+/// Photo as a URI; inline vCard 3.0 photos become `data:` URIs
+fn photo_uri(property: &Property) -> String {
+    let inline = property
+        .param("ENCODING")
+        .any(|e| e.eq_ignore_ascii_case("b") || e.eq_ignore_ascii_case("base64"));
+    if !inline {
+        return property.value.clone();
+    }
+    let media_type = property
+        .param("TYPE")
+        .next()
+        .map(|t| match t.to_ascii_lowercase() {
+            t if t.contains('/') => t,
+            t => format!("image/{}", t),
+        })
+        .unwrap_or_else(|| "image/jpeg".to_string());
+    let data: String = property.value.chars().filter(|c| !c.is_whitespace()).collect();
+    format!("data:{};base64,{}", media_type, data)
+}
+
+/// `PHOTO` property for a URI; vCard 3.0 embeds `data:` URIs as base64
+fn photo_property(uri: &str, v4: bool) -> Property {
+    if v4 {
+        return Property::new("PHOTO", uri);
+    }
+    let inline = uri
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"));
+    match inline {
+        Some((media_type, data)) => {
+            let subtype = media_type.rsplit('/').next().unwrap_or("jpeg").to_ascii_uppercase();
+            Property::new("PHOTO", data)
+                .with_param("ENCODING", vec!["b".to_string()])
+                .with_param("TYPE", vec![subtype])
+        }
+        None => Property::new("PHOTO", uri).with_param("VALUE", vec!["uri".to_string()]),
+    }
+}
+
+/// Join folded lines (a line break followed by a space or tab continues the
+/// previous line)
+pub fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Fold a content line at 75 octets without splitting UTF-8 characters;
+/// the result ends with CRLF
+pub fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3 + 2);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// Escape a text value
+pub fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            ',' => out.push_str("\\,"),
+            ';' => out.push_str("\\;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Unescape a text value
+pub fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Split on `separator` unless it is escaped with a backslash
+fn split_escaped(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for c in value.chars() {
+        let current = parts.last_mut().expect("parts is never empty");
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == separator {
+            parts.push(String::new());
+        } else {
+            current.push(c);
+        }
+    }
+    if escaped {
+        parts.last_mut().expect("parts is never empty").push('\\');
+    }
+    parts
+}
+
+/// Split `text` at the first of `stops` outside double quotes
+fn split_unquoted<'a>(text: &'a str, stops: &[char]) -> (&'a str, &'a str) {
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            c if !quoted && stops.contains(&c) => return (&text[..i], &text[i..]),
+            _ => {}
+        }
+    }
+    (text, "")
+}
+
+/// Comma-separated parameter values, with quotes removed
+fn split_param_values(value: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut rest = value;
+    loop {
+        let (item, remaining) = split_unquoted(rest, &[',']);
+        values.push(item.trim_matches('"').to_string());
+        match remaining.strip_prefix(',') {
+            Some(remaining) => rest = remaining,
+            None => return values,
+        }
+    }
+}
+
+/// Quote a parameter value if it contains a separator
+fn quote_param(value: &str) -> String {
+    if value.contains([':', ';', ',']) {
+        format!("\"{}\"", value.replace('"', "'"))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfold_and_escape() {
+        let card = "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Ann\r\n  Smith\r\nNOTE:Line one\\nLine two\\, with comma\\; and semi\r\n\tcolon\r\nEND:VCARD\r\n";
+        let contact = parse_contact(card).unwrap();
+        assert_eq!(contact.full_name, "Ann Smith");
+        assert_eq!(
+            contact.note.as_deref(),
+            Some("Line one\nLine two, with comma; and semicolon")
+        );
+        assert!(parse_contact("FN:No card").is_err());
+    }
+
+    #[test]
+    fn test_fold_respects_utf8() {
+        let line = format!("NOTE:{}", "ü".repeat(60));
+        let folded = fold(&line);
+        for physical in folded.split("\r\n").filter(|l| !l.is_empty()) {
+            assert!(physical.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(unfold(&folded), vec![line]);
+    }
+
+    #[test]
+    fn test_property_params_and_groups() {
+        let property = Property::parse(r#"item1.ADR;TYPE="home,postal";LABEL="1 Main St; Springfield":;;1 Main St;Springfield;;12345;USA"#).unwrap();
+        assert_eq!(property.group.as_deref(), Some("item1"));
+        assert_eq!(property.name, "ADR");
+        assert_eq!(property.types(), ["home", "postal"]);
+        assert_eq!(property.param("LABEL").next(), Some("1 Main St; Springfield"));
+        assert_eq!(property.components()[2], "1 Main St");
+
+        let shorthand = Property::parse("TEL;CELL;PREF:+1 555 0100").unwrap();
+        assert_eq!(shorthand.types(), ["cell"]);
+        assert!(shorthand.is_preferred());
+    }
+
+    #[test]
+    fn test_parse_vcard3() {
+        let card = "BEGIN:VCARD\r
+VERSION:3.0\r
+UID:ann-1\r
+FN:Ann Smith\r
+N:Smith;Ann;;Dr.;\r
+item1.EMAIL;type=INTERNET;type=WORK:ann@work.example\r
+item1.X-ABLabel:_$!<Other>!$_\r
+EMAIL;TYPE=INTERNET,HOME,pref:ann@example.com\r
+TEL;TYPE=CELL:+1 555 0100\r
+TEL;TYPE=WORK,VOICE:+1 555 0199\r
+ADR;TYPE=HOME:;;1 Main St;Springfield;IL;12345;USA\r
+ADR;TYPE=WORK;TYPE=pref:;Suite 5;2 Office Rd\\, Floor 3;Shelbyville;;;USA\r
+ORG:Example Inc.;Research\r
+NICKNAME:Annie\r
+X-SOCIALPROFILE;TYPE=mastodon:https://example.social/@ann\r
+PRODID:-//Example//EN\r
+END:VCARD\r
+";
+        let contact = parse_contact(card).unwrap();
+        assert_eq!(contact.vcard_version.as_deref(), Some("3.0"));
+        assert_eq!(contact.uid.as_deref(), Some("ann-1"));
+        assert_eq!(contact.first_name.as_deref(), Some("Ann"));
+        assert_eq!(contact.last_name.as_deref(), Some("Smith"));
+        assert_eq!(contact.emails, ["ann@work.example", "ann@example.com"]);
+        assert_eq!(contact.email.as_deref(), Some("ann@example.com"));
+        assert_eq!(contact.email_types["ann@work.example"], ["work", "other"]);
+        assert_eq!(contact.email_types["ann@example.com"], ["home"]);
+        assert_eq!(contact.phones, ["+1 555 0100", "+1 555 0199"]);
+        assert_eq!(contact.phone_types["+1 555 0199"], ["work", "voice"]);
+        assert_eq!(contact.addresses.len(), 2);
+        assert_eq!(contact.addresses[0].city.as_deref(), Some("Springfield"));
+        assert_eq!(contact.addresses[0].address_type.as_deref(), Some("home"));
+        assert_eq!(contact.addresses[1].street.as_deref(), Some("2 Office Rd, Floor 3"));
+        assert_eq!(contact.addresses[1].extended.as_deref(), Some("Suite 5"));
+        assert!(contact.addresses[1].is_preferred);
+        assert_eq!(contact.organization.as_deref(), Some("Example Inc."));
+        assert_eq!(contact.department.as_deref(), Some("Research"));
+        assert_eq!(contact.other_properties.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_vcard4() {
+        let card = "BEGIN:VCARD\nVERSION:4.0\nUID:urn:uuid:4fbe8971-0bc3-424c-9c26-36c3e1eff6b1\nFN:Bob Jones\nTEL;VALUE=uri;TYPE=\"voice,cell\";PREF=1:tel:+1-555-555-5555\nEMAIL;TYPE=work:bob@example.com\nANNIVERSARY:20090808\nPHOTO:https://example.com/bob.jpg\nEND:VCARD\n";
+        let contact = parse_contact(card).unwrap();
+        assert_eq!(contact.uid.as_deref(), Some("urn:uuid:4fbe8971-0bc3-424c-9c26-36c3e1eff6b1"));
+        assert_eq!(contact.phone.as_deref(), Some("+1-555-555-5555"));
+        assert_eq!(contact.phone_types["+1-555-555-5555"], ["voice", "cell"]);
+        assert_eq!(contact.anniversary.as_deref(), Some("20090808"));
+        assert_eq!(contact.photo_url.as_deref(), Some("https://example.com/bob.jpg"));
+        // No N property: the name parts stay empty but FN is kept
+        assert_eq!(contact.full_name, "Bob Jones");
+        assert!(contact.first_name.is_none());
+    }
+
+    #[test]
+    fn test_photo_round_trip() {
+        let data = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+        let card = format!(
+            "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Pic\r\nPHOTO;ENCODING=b;TYPE=PNG:{}\r\n {}\r\nEND:VCARD\r\n",
+            &data[..40],
+            &data[40..]
+        );
+        let contact = parse_contact(&card).unwrap();
+        assert_eq!(contact.photo_url, Some(format!("data:image/png;base64,{}", data)));
+
+        let written = contact_to_vcard(&contact, "pic-1");
+        assert!(written.contains("PHOTO;ENCODING=b;TYPE=PNG:"));
+        assert_eq!(parse_contact(&written).unwrap().photo_url, contact.photo_url);
+
+        let mut v4 = contact.clone();
+        v4.vcard_version = Some("4.0".to_string());
+        let written = contact_to_vcard(&v4, "pic-1");
+        assert!(written.contains("VERSION:4.0"));
+        assert!(written.contains("PHOTO:data:image/png;base64,"));
+        assert_eq!(parse_contact(&written).unwrap().photo_url, contact.photo_url);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut contact = Contact::new("Ann Smith")
+            .with_email("ann@example.com")
+            .with_email("ann@work.example")
+            .with_phone("+1 555 0100")
+            .with_organization("Example; Inc.")
+            .with_note("Met at the conference,\nfollow up in May");
+        contact.email_types.insert("ann@work.example".to_string(), vec!["work".to_string()]);
+        contact.phone_types.insert("+1 555 0100".to_string(), vec!["cell".to_string()]);
+        contact.addresses.push(
+            PostalAddress::new()
+                .with_street("1 Main St")
+                .with_city("Springfield")
+                .with_country("USA"),
+        );
+        contact.other_properties.push("NICKNAME:Annie".to_string());
+
+        let written = contact_to_vcard(&contact, "ann-1");
+        assert!(written.starts_with("BEGIN:VCARD\r\nVERSION:3.0\r\n"));
+        assert!(written.contains("N:Smith;Ann;;;\r\n"));
+        assert!(written.contains("ORG:Example\\; Inc.\r\n"));
+        assert!(written.contains("EMAIL;TYPE=work,pref:ann@work.example\r\n"));
+
+        let parsed = parse_contact(&written).unwrap();
+        assert_eq!(parsed.uid.as_deref(), Some("ann-1"));
+        assert_eq!(parsed.full_name, contact.full_name);
+        assert_eq!(parsed.emails, contact.emails);
+        assert_eq!(parsed.email, contact.email);
+        assert_eq!(parsed.email_types["ann@work.example"], ["work"]);
+        assert_eq!(parsed.phone_types, contact.phone_types);
+        assert_eq!(parsed.organization, contact.organization);
+        assert_eq!(parsed.note, contact.note);
+        assert_eq!(parsed.addresses[0].street.as_deref(), Some("1 Main St"));
+        assert_eq!(parsed.addresses[0].country.as_deref(), Some("USA"));
+        assert_eq!(parsed.other_properties, ["NICKNAME:Annie"]);
+    }
+}
@@ -0,0 +1,279 @@
+//! Declarative sub-agent configuration
+//!
+//! agents.toml / agents.yaml に宣言したサブエージェントを起動時に
+//! [`SubAgentManager`] へ登録します。
+//!
+//! ```toml
+//! [[agents]]
+//! name = "code_reviewer"
+//! description = "Reviews code for issues"
+//! system_prompt = "You are a meticulous code reviewer."
+//! model = "claude-sonnet-4-20250514"
+//! keywords = ["review", "レビュー"]
+//! tools = ["read", "glob", "grep"]
+//!
+//! [[agents.capabilities]]
+//! name = "security"
+//! description = "Security audit"
+//! keywords = ["vulnerability", "脆弱性"]
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::default::DefaultSubAgent;
+use super::manager::SubAgentManager;
+use super::types::{AgentCapability, SubAgent};
+use crate::config::Config;
+use crate::tool::ToolManager;
+use crate::{Error, Result};
+
+/// agents.toml / agents.yaml
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentsConfig {
+    #[serde(default)]
+    pub agents: Vec<AgentDefinition>,
+}
+
+/// A sub-agent declared in the configuration file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentDefinition {
+    /// Unique agent name
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+
+    /// Model override (the gateway's model if omitted)
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Routing keywords (shorthand for a capability named after the agent)
+    #[serde(default)]
+    pub keywords: Vec<String>,
+
+    #[serde(default)]
+    pub capabilities: Vec<AgentCapability>,
+
+    /// Tool allowlist (all tools if empty)
+    #[serde(default)]
+    pub tools: Vec<String>,
+
+    /// Use this agent for tasks without a better match
+    #[serde(default)]
+    pub default: bool,
+
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl AgentDefinition {
+    /// Capabilities including the one implied by `keywords`
+    pub fn all_capabilities(&self) -> Vec<AgentCapability> {
+        let mut capabilities = self.capabilities.clone();
+        if !self.keywords.is_empty() {
+            capabilities.push(
+                AgentCapability::new(&self.name, &self.description)
+                    .with_keywords(self.keywords.clone()),
+            );
+        }
+        capabilities
+    }
+
+    /// Build the sub-agent
+    pub fn build(&self, config: &Config, tool_manager: Arc<ToolManager>) -> Result<DefaultSubAgent> {
+        let mut builder = DefaultSubAgent::builder(&self.name, config, tool_manager)
+            .description(&self.description)
+            .capabilities(self.all_capabilities())
+            .tools(self.tools.clone());
+        if let Some(prompt) = &self.system_prompt {
+            builder = builder.system_prompt(prompt);
+        }
+        if let Some(model) = &self.model {
+            builder = builder.model(model);
+        }
+        builder.build()
+    }
+}
+
+impl AgentsConfig {
+    /// Default configuration files (searched in order)
+    pub const DEFAULT_PATHS: [&'static str; 3] = ["agents.toml", "agents.yaml", "agents.yml"];
+
+    /// Load from a TOML or YAML file (by extension; `${VAR}` is expanded)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::Config(format!("Failed to read agents file {}: {}", path.display(), e))
+        })?;
+        let content = Config::expand_env_vars(&content);
+
+        let config: Self = match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+            "yaml" | "yml" => serde_yaml::from_str(&content)
+                .map_err(|e| Error::Config(format!("Failed to parse YAML agents file: {}", e)))?,
+            "toml" => toml::from_str(&content)
+                .map_err(|e| Error::Config(format!("Failed to parse TOML agents file: {}", e)))?,
+            other => {
+                return Err(Error::Config(format!(
+                    "Unsupported agents file format: {}",
+                    other
+                )));
+            }
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that agent names are present and unique
+    pub fn validate(&self) -> Result<()> {
+        let mut names = std::collections::HashSet::new();
+        for agent in &self.agents {
+            if agent.name.trim().is_empty() {
+                return Err(Error::Config("agent name must not be empty".to_string()));
+            }
+            if !names.insert(agent.name.as_str()) {
+                return Err(Error::Config(format!("duplicate agent name: {}", agent.name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the enabled agents and register them
+    ///
+    /// Returns the number of registered agents.
+    pub fn register_all(
+        &self,
+        manager: &mut SubAgentManager,
+        config: &Config,
+        tool_manager: &Arc<ToolManager>,
+    ) -> Result<usize> {
+        let mut count = 0;
+        for definition in self.agents.iter().filter(|a| a.enabled) {
+            let agent = Arc::new(definition.build(config, Arc::clone(tool_manager))?);
+            let id = agent.id().clone();
+            manager.register(agent);
+            if definition.default {
+                manager.set_default(id);
+            }
+            info!(agent = %definition.name, tools = ?definition.tools, "Sub-agent loaded from config");
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::types::SubAgentTask;
+
+    fn test_config() -> Config {
+        Config {
+            api: crate::config::ApiConfig::default(),
+            llm: crate::config::LlmConfig {
+                provider: crate::config::LlmProvider::Claude,
+                api_key: "test-key".to_string(),
+                model: "claude-sonnet-4-20250514".to_string(),
+                base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
+            },
+            claude_api_key: "test-key".to_string(),
+            claude_model: "claude-sonnet-4-20250514".to_string(),
+            discord_token: None,
+            admin_user_ids: vec![],
+            api_key: None,
+            mcp: crate::config::McpConfig::default(),
+            memory: crate::config::MemoryConfig::default(),
+            scheduler: crate::config::SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_toml_and_register() {
+        let agents: AgentsConfig = toml::from_str(
+            r#"
+[[agents]]
+name = "reviewer"
+description = "Reviews code"
+system_prompt = "You review code."
+model = "claude-haiku"
+keywords = ["review"]
+tools = ["read"]
+
+[[agents.capabilities]]
+name = "security"
+description = "Security audit"
+keywords = ["vulnerability"]
+
+[[agents]]
+name = "writer"
+default = true
+
+[[agents]]
+name = "disabled"
+enabled = false
+"#,
+        )
+        .unwrap();
+        agents.validate().unwrap();
+
+        let mut manager = SubAgentManager::new();
+        let count = agents
+            .register_all(&mut manager, &test_config(), &Arc::new(ToolManager::new()))
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let reviewer = manager.get_by_name("reviewer").unwrap();
+        assert_eq!(reviewer.model(), Some("claude-haiku"));
+        assert_eq!(reviewer.capabilities().len(), 2);
+        assert!(reviewer.can_handle(&SubAgentTask::new("Please review this")));
+        assert!(reviewer.can_handle(&SubAgentTask::new("Find a vulnerability")));
+        assert_eq!(manager.get_default().unwrap().name(), "writer");
+        assert!(manager.get_by_name("disabled").is_none());
+    }
+
+    #[test]
+    fn test_parse_yaml() {
+        let agents: AgentsConfig = serde_yaml::from_str(
+            r#"
+agents:
+  - name: translator
+    system_prompt: Translate to English.
+    keywords: [translate, 翻訳]
+"#,
+        )
+        .unwrap();
+        let translator = &agents.agents[0];
+        assert!(translator.enabled);
+        assert_eq!(translator.all_capabilities()[0].keywords.len(), 2);
+    }
+
+    #[test]
+    fn test_duplicate_names_rejected() {
+        let agents = AgentsConfig {
+            agents: vec![
+                serde_yaml::from_str("name: a").unwrap(),
+                serde_yaml::from_str("name: a").unwrap(),
+            ],
+        };
+        assert!(agents.validate().is_err());
+    }
+}
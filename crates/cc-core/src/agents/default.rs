@@ -8,14 +8,13 @@ use std::sync::Arc;
 use std::time::Instant;
 use tracing::{debug, info, warn};
 
-use super::types::{
-    AgentCapability, SubAgent, SubAgentId, SubAgentResult, SubAgentTask, TaskStatus,
-    ToolCallRecord,
-};
+use super::events::{SubAgentEvent, SubAgentEventKind};
+use super::tool::DELEGATE_TASK_TOOL;
+use super::types::{AgentCapability, SubAgent, SubAgentId, SubAgentResult, SubAgentTask, TaskStatus};
 use crate::config::Config;
-use crate::llm::{ClaudeClient, Message, MessagesRequest};
-use crate::tool::ToolManager;
-use crate::Result;
+use crate::llm::{AgentEngine, AgentHooks, AgentRun, ClaudeClient, Message, MessagesRequest, ToolCall, Usage};
+use crate::tool::{ToolManager, ToolResult};
+use crate::{Error, Result};
 
 /// Default sub-agent implementation using ClaudeClient
 pub struct DefaultSubAgent {
@@ -25,6 +24,8 @@ pub struct DefaultSubAgent {
     capabilities: Vec<AgentCapability>,
     system_prompt: Option<String>,
     model_override: Option<String>,
+    /// Tool allowlist (all tools if empty)
+    allowed_tools: Vec<String>,
     client: ClaudeClient,
     tool_manager: Arc<ToolManager>,
 }
@@ -46,6 +47,7 @@ impl DefaultSubAgent {
             capabilities: vec![],
             system_prompt: None,
             model_override: None,
+            allowed_tools: vec![],
             client,
             tool_manager,
         })
@@ -75,6 +77,20 @@ impl DefaultSubAgent {
         self.model_override = Some(model.into());
     }
 
+    /// Restrict the tools this agent may use (all tools if empty)
+    pub fn set_allowed_tools(&mut self, tools: Vec<String>) {
+        self.allowed_tools = tools;
+    }
+
+    /// Tool allowlist (empty means all tools)
+    pub fn allowed_tools(&self) -> &[String] {
+        &self.allowed_tools
+    }
+
+    fn is_tool_allowed(&self, name: &str) -> bool {
+        self.allowed_tools.is_empty() || self.allowed_tools.iter().any(|t| t == name)
+    }
+
     /// Get the model to use
     fn get_model(&self) -> String {
         self.model_override
@@ -82,135 +98,93 @@ impl DefaultSubAgent {
             .unwrap_or_else(|| self.client.model().to_string())
     }
 
-    /// Execute with ClaudeClient agent loop
-    async fn execute_agent_loop(
-        &self,
-        task: SubAgentTask,
-    ) -> std::result::Result<(String, usize, u64, u64, Vec<ToolCallRecord>), String> {
-        let model = self.get_model();
-        let system = self.system_prompt.clone();
-        let tools = if task.available_tools.is_empty() {
+    /// Execute with the agent engine
+    async fn execute_agent_loop(&self, task: &SubAgentTask) -> Result<AgentRun> {
+        let tools: Vec<_> = if task.available_tools.is_empty() {
             self.tool_manager.definitions()
         } else {
             task.available_tools.clone()
-        };
+        }
+        .into_iter()
+        // Sub-agents never delegate further (prevents unbounded recursion)
+        .filter(|t| t.name != DELEGATE_TASK_TOOL && self.is_tool_allowed(&t.name))
+        .collect();
 
         let mut messages = task.context.clone();
         messages.push(Message::user(&task.instruction));
 
-        let mut iterations = 0;
-        let mut total_input = 0u64;
-        let mut total_output = 0u64;
-        let mut tool_calls = Vec::new();
-        let tool_manager = self.tool_manager.clone();
+        let mut request = MessagesRequest {
+            model: self.get_model(),
+            max_tokens: task.max_tokens,
+            system: self.system_prompt.clone(),
+            messages,
+            tools: if tools.is_empty() { None } else { Some(tools) },
+            thinking: None,
+            tool_choice: None,
+            temperature: None,
+        };
 
-        loop {
-            iterations += 1;
-            if iterations > task.max_iterations {
-                return Err("Max iterations reached".to_string());
-            }
+        // Dropping the loop on cancellation aborts the in-flight LLM call or tool
+        AgentEngine::new(&self.client)
+            .with_tools(&self.tool_manager)
+            .with_max_iterations(task.max_iterations)
+            .with_cancellation(task.cancel.clone())
+            .run(&mut request, &SubAgentHooks { agent: self, task })
+            .await
+    }
+}
 
-            let request = MessagesRequest {
-                model: model.clone(),
-                max_tokens: task.max_tokens,
-                system: system.clone(),
-                messages: messages.clone(),
-                tools: if tools.is_empty() {
-                    None
-                } else {
-                    Some(tools.clone())
-                },
-                thinking: None,
-            };
-
-            let response = self
-                .client
-                .messages(request)
-                .await
-                .map_err(|e| e.to_string())?;
+/// Reports the progress of a task as events and enforces the tool allowlist
+struct SubAgentHooks<'a> {
+    agent: &'a DefaultSubAgent,
+    task: &'a SubAgentTask,
+}
 
-            // Track token usage
-            if let Some(usage) = &response.usage {
-                total_input += usage.input_tokens;
-                total_output += usage.output_tokens;
-            }
+#[async_trait]
+impl AgentHooks for SubAgentHooks<'_> {
+    async fn on_iteration(&self, iteration: usize) {
+        let started = SubAgentEventKind::IterationStarted { iteration };
+        self.task.emit(&self.agent.id, &self.agent.name, started);
+    }
 
-            match response.stop_reason.as_str() {
-                "end_turn" | "stop_sequence" | "stop" => {
-                    let text = response
-                        .content
-                        .iter()
-                        .filter_map(|c| {
-                            if let crate::llm::MessageContent::Text { text } = c {
-                                Some(text.clone())
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    return Ok((text, iterations, total_input, total_output, tool_calls));
-                }
-                "tool_use" | "tool_calls" => {
-                    let uses: Vec<_> = response
-                        .content
-                        .iter()
-                        .filter_map(|c| {
-                            if let crate::llm::MessageContent::ToolUse { id, name, input } = c {
-                                Some((id.clone(), name.clone(), input.clone()))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-
-                    if uses.is_empty() {
-                        warn!("tool_use stop_reason but no tool_uses found");
-                        continue;
-                    }
-
-                    let mut tool_results = Vec::new();
-                    for (id, name, input) in &uses {
-                        debug!("SubAgent executing tool: {} with input: {:?}", name, input);
-
-                        let result = tool_manager
-                            .execute(name, input.clone())
-                            .await
-                            .unwrap_or_else(|e| crate::tool::ToolResult::error(e.to_string()));
-
-                        tool_calls.push(ToolCallRecord {
-                            id: id.clone(),
-                            name: name.clone(),
-                            input: input.clone(),
-                            output: result.output.clone(),
-                            is_error: result.is_error,
-                        });
-
-                        tool_results.push(crate::llm::MessageContent::ToolResult {
-                            tool_use_id: id.clone(),
-                            content: result.output,
-                            is_error: result.is_error,
-                        });
-                    }
-
-                    // Add assistant message
-                    messages.push(Message {
-                        role: "assistant".to_string(),
-                        content: response.content,
-                    });
-
-                    // Add tool results
-                    messages.push(Message {
-                        role: "user".to_string(),
-                        content: tool_results,
-                    });
-                }
-                other => {
-                    return Err(format!("Unknown stop_reason: {}", other));
-                }
-            }
-        }
+    async fn on_usage(&self, usage: &Usage) {
+        self.task.emit(
+            &self.agent.id,
+            &self.agent.name,
+            SubAgentEventKind::TokensUsed {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+            },
+        );
+    }
+
+    async fn on_tool_call(&self, call: &ToolCall, tools: &ToolManager) -> ToolResult {
+        debug!("SubAgent executing tool: {} with input: {:?}", call.name, call.input);
+
+        // Only the tools the task offers (all when it offers none) and the agent allows
+        let offered = self.task.available_tools.is_empty()
+            || self.task.available_tools.iter().any(|t| t.name == call.name);
+        let result = if call.name != DELEGATE_TASK_TOOL && offered && self.agent.is_tool_allowed(&call.name) {
+            tools
+                .execute(&call.name, call.input.clone())
+                .await
+                .unwrap_or_else(|e| ToolResult::error(e.to_string()))
+        } else {
+            ToolResult::error(format!(
+                "Tool '{}' is not allowed for agent '{}'",
+                call.name, self.agent.name
+            ))
+        };
+
+        self.task.emit(
+            &self.agent.id,
+            &self.agent.name,
+            SubAgentEventKind::ToolCalled {
+                tool: call.name.clone(),
+                is_error: result.is_error,
+            },
+        );
+        result
     }
 }
 
@@ -261,31 +235,60 @@ impl SubAgent for DefaultSubAgent {
             task_id.as_str()
         );
 
-        match self.execute_agent_loop(task).await {
-            Ok((output, iterations, input_tokens, output_tokens, tool_calls)) => {
+        task.emit(
+            &self.id,
+            &self.name,
+            SubAgentEventKind::TaskStarted {
+                instruction: task.instruction.clone(),
+            },
+        );
+        let events = task.events.clone();
+        let finished = |status: TaskStatus, error: Option<String>| {
+            if let Some(events) = &events {
+                let kind = SubAgentEventKind::TaskFinished { status, error };
+                let event = SubAgentEvent::new(task_id.clone(), self.id.clone(), &self.name, kind);
+                let _ = events.send(event);
+            }
+        };
+
+        match self.execute_agent_loop(&task).await {
+            Ok(run) => {
                 let execution_time_ms = start_time.elapsed().as_millis() as u64;
 
                 info!(
                     "SubAgent '{}' completed task in {}ms, {} iterations",
-                    self.name, execution_time_ms, iterations
+                    self.name, execution_time_ms, run.iterations
                 );
+                finished(TaskStatus::Completed, None);
 
                 Ok(SubAgentResult {
                     task_id,
                     agent_id: self.id.clone(),
-                    output,
+                    output: run.text,
                     success: true,
                     error: None,
-                    iterations,
-                    input_tokens,
-                    output_tokens,
+                    iterations: run.iterations,
+                    input_tokens: run.usage.input_tokens,
+                    output_tokens: run.usage.output_tokens,
                     execution_time_ms,
                     status: TaskStatus::Completed,
-                    tool_calls,
+                    tool_calls: run.tool_calls,
                 })
             }
+            Err(Error::Cancelled) => {
+                info!("SubAgent '{}' cancelled task {}", self.name, task_id.as_str());
+                finished(TaskStatus::Cancelled, Some("Task cancelled".to_string()));
+                Ok(SubAgentResult::failure(
+                    task_id,
+                    self.id.clone(),
+                    "Task cancelled",
+                    TaskStatus::Cancelled,
+                ))
+            }
             Err(e) => {
+                let e = e.to_string();
                 warn!("SubAgent '{}' failed: {}", self.name, e);
+                finished(TaskStatus::Failed, Some(e.clone()));
 
                 Ok(SubAgentResult::failure(
                     task_id,
@@ -305,6 +308,7 @@ pub struct DefaultSubAgentBuilder {
     capabilities: Vec<AgentCapability>,
     system_prompt: Option<String>,
     model_override: Option<String>,
+    allowed_tools: Vec<String>,
     config: Config,
     tool_manager: Arc<ToolManager>,
 }
@@ -321,6 +325,7 @@ impl DefaultSubAgentBuilder {
             capabilities: vec![],
             system_prompt: None,
             model_override: None,
+            allowed_tools: vec![],
             config: config.clone(),
             tool_manager,
         }
@@ -351,6 +356,12 @@ impl DefaultSubAgentBuilder {
         self
     }
 
+    /// Restrict the tools the agent may use (all tools if empty)
+    pub fn tools(mut self, tools: Vec<String>) -> Self {
+        self.allowed_tools = tools;
+        self
+    }
+
     pub fn build(self) -> Result<DefaultSubAgent> {
         let client = ClaudeClient::new(&self.config)?;
 
@@ -361,6 +372,7 @@ impl DefaultSubAgentBuilder {
             capabilities: self.capabilities,
             system_prompt: self.system_prompt,
             model_override: self.model_override,
+            allowed_tools: self.allowed_tools,
             client,
             tool_manager: self.tool_manager,
         })
@@ -379,6 +391,9 @@ mod tests {
                 api_key: "test-key".to_string(),
                 model: "claude-sonnet-4-20250514".to_string(),
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -388,6 +403,12 @@ mod tests {
             mcp: crate::config::McpConfig::default(),
             memory: crate::config::MemoryConfig::default(),
             scheduler: crate::config::SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -3,27 +3,43 @@
 //! Implements:
 //! - TaskDelegator: Splits and delegates tasks to sub-agents
 //! - ResultAggregator: Combines results from multiple sub-agents
-//! - ParallelExecutor: Executes tasks in parallel with concurrency control
+//!   (concatenation or LLM synthesis)
+//! - ParallelExecutor: Executes tasks in parallel with concurrency control,
+//!   per-task timeouts and cooperative cancellation
 
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use tokio::sync::{Mutex, Semaphore};
 use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, warn};
 
+use super::events::{SubAgentEvent, SubAgentEventKind};
 use super::manager::SubAgentManager;
-use super::types::{SubAgentId, SubAgentResult, SubAgentTask, TaskId, TaskPriority};
-use crate::llm::{Message, ToolDefinition};
+use super::metrics::AgentMetricsTracker;
+use super::types::{
+    SubAgent, SubAgentId, SubAgentResult, SubAgentTask, TaskId, TaskPriority, TaskStatus,
+};
+use crate::llm::{ClaudeClient, Message, MessageContent, MessagesRequestBuilder, ToolDefinition};
 use crate::Result;
 
+/// Default system prompt for [`AggregationStrategy::LlmSynthesize`]
+pub const DEFAULT_SYNTHESIS_PROMPT: &str = "You combine the outputs of several sub-agents that \
+worked on parts of the same task into one coherent answer. Merge overlapping points, keep every \
+important detail, point out contradictions you cannot resolve, and answer in the language of \
+the original task. Do not mention the sub-agents.";
+
+/// Maximum tokens for the synthesized answer
+const SYNTHESIS_MAX_TOKENS: u64 = 4096;
+
 /// Configuration for task delegation
 #[derive(Debug, Clone)]
 pub struct DelegationConfig {
     /// Maximum concurrent sub-agent executions
     pub max_concurrency: usize,
-    /// Default timeout for delegated tasks
+    /// Timeout for delegated tasks that do not set `timeout_secs`
     pub default_timeout_secs: u64,
     /// Maximum iterations for sub-agent loops
     pub default_max_iterations: usize,
@@ -52,12 +68,23 @@ impl Default for DelegationConfig {
 pub struct TaskDelegator {
     manager: Arc<Mutex<SubAgentManager>>,
     config: DelegationConfig,
+    cancel: CancellationToken,
 }
 
 impl TaskDelegator {
     /// Create a new task delegator
     pub fn new(manager: Arc<Mutex<SubAgentManager>>, config: DelegationConfig) -> Self {
-        Self { manager, config }
+        Self {
+            manager,
+            config,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Stop delegated tasks when `cancel` fires (e.g. Ctrl+C or client disconnect)
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
     }
 
     /// Create with default configuration
@@ -67,8 +94,8 @@ impl TaskDelegator {
 
     /// Delegate a single task to the best available agent
     pub async fn delegate(&self, task: SubAgentTask) -> Result<SubAgentResult> {
-        let manager = self.manager.lock().await;
-        manager.execute_with_best_agent(task).await
+        let timeout = task_timeout(&self.config, &task);
+        run_best(&self.manager, task, &self.cancel, timeout).await
     }
 
     /// Delegate a task to a specific agent
@@ -77,8 +104,15 @@ impl TaskDelegator {
         agent_id: SubAgentId,
         task: SubAgentTask,
     ) -> Result<SubAgentResult> {
-        let manager = self.manager.lock().await;
-        manager.execute_with_agent(&agent_id, task).await
+        let (agent, metrics) = {
+            let manager = self.manager.lock().await;
+            let agent = manager.get(&agent_id).ok_or_else(|| {
+                crate::Error::Other(format!("Agent not found: {}", agent_id.as_str()))
+            })?;
+            (agent, manager.metrics())
+        };
+        let timeout = task_timeout(&self.config, &task);
+        run_task(agent, task, &self.cancel, timeout, &metrics).await
     }
 
     /// Delegate multiple tasks in parallel
@@ -86,7 +120,8 @@ impl TaskDelegator {
         &self,
         tasks: Vec<SubAgentTask>,
     ) -> Result<Vec<SubAgentResult>> {
-        let executor = ParallelExecutor::new(self.manager.clone(), self.config.clone());
+        let executor = ParallelExecutor::new(self.manager.clone(), self.config.clone())
+            .with_cancellation(self.cancel.clone());
         executor.execute_all(tasks).await
     }
 
@@ -122,6 +157,8 @@ impl TaskDelegator {
                     max_tokens: task.max_tokens / parts as u64,
                     timeout_secs: task.timeout_secs,
                     metadata: task.metadata.clone(),
+                    cancel: task.cancel.child_token(),
+                    events: task.events.clone(),
                 }
             })
             .collect()
@@ -241,6 +278,10 @@ impl AggregatedResult {
 pub struct ResultAggregator {
     /// Strategy for combining results
     strategy: AggregationStrategy,
+    /// LLM client for [`AggregationStrategy::LlmSynthesize`]
+    client: Option<ClaudeClient>,
+    /// System prompt for synthesis
+    synthesis_prompt: String,
 }
 
 /// Strategy for aggregating results
@@ -254,12 +295,43 @@ pub enum AggregationStrategy {
     WithSummary,
     /// Custom aggregation logic
     Custom,
+    /// Have the LLM synthesize the successful outputs into one answer
+    ///
+    /// Requires [`ResultAggregator::with_client`] and [`ResultAggregator::synthesize`];
+    /// falls back to `SuccessOnly` otherwise.
+    LlmSynthesize,
 }
 
 impl ResultAggregator {
     /// Create a new result aggregator
     pub fn new(strategy: AggregationStrategy) -> Self {
-        Self { strategy }
+        Self {
+            strategy,
+            client: None,
+            synthesis_prompt: DEFAULT_SYNTHESIS_PROMPT.to_string(),
+        }
+    }
+
+    /// Create an aggregator that synthesizes results with the LLM
+    pub fn llm_synthesize(client: ClaudeClient) -> Self {
+        Self::new(AggregationStrategy::LlmSynthesize).with_client(client)
+    }
+
+    /// Set the LLM client used for synthesis
+    pub fn with_client(mut self, client: ClaudeClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Override the synthesis system prompt
+    pub fn with_synthesis_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.synthesis_prompt = prompt.into();
+        self
+    }
+
+    /// Get the strategy
+    pub fn strategy(&self) -> AggregationStrategy {
+        self.strategy
     }
 
     /// Create with default strategy
@@ -274,7 +346,55 @@ impl ResultAggregator {
             AggregationStrategy::SuccessOnly => self.success_only(results),
             AggregationStrategy::WithSummary => self.with_summary(results),
             AggregationStrategy::Custom => self.custom(results),
+            // Synthesis needs an async LLM call; see `synthesize`
+            AggregationStrategy::LlmSynthesize => self.success_only(results),
+        }
+    }
+
+    /// Aggregate results for a task, using the LLM when the strategy is `LlmSynthesize`
+    ///
+    /// Other strategies behave like [`aggregate`](Self::aggregate). If no client is
+    /// set or the LLM call fails, the concatenated successful outputs are returned.
+    pub async fn synthesize(&self, task: &str, results: Vec<SubAgentResult>) -> AggregatedResult {
+        let mut aggregated = self.aggregate(results);
+        if self.strategy != AggregationStrategy::LlmSynthesize || aggregated.successful_count < 2 {
+            return aggregated;
+        }
+        let Some(client) = &self.client else {
+            warn!("LlmSynthesize requested without an LLM client; concatenating results");
+            return aggregated;
+        };
+
+        let request = MessagesRequestBuilder::new(client.model().to_string())
+            .system(&self.synthesis_prompt)
+            .max_tokens(SYNTHESIS_MAX_TOKENS)
+            .user(synthesis_input(task, &aggregated.results))
+            .build();
+
+        match client.messages(request).await {
+            Ok(response) => {
+                let text = response
+                    .content
+                    .iter()
+                    .filter_map(|c| match c {
+                        MessageContent::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if let Some(usage) = &response.usage {
+                    aggregated.total_input_tokens += usage.input_tokens;
+                    aggregated.total_output_tokens += usage.output_tokens;
+                }
+                if text.trim().is_empty() {
+                    warn!("LLM synthesis returned no text; concatenating results");
+                } else {
+                    aggregated.combined_output = text;
+                }
+            }
+            Err(e) => warn!("LLM synthesis failed, concatenating results: {}", e),
         }
+        aggregated
     }
 
     fn concatenate(&self, results: Vec<SubAgentResult>) -> AggregatedResult {
@@ -329,6 +449,16 @@ impl ResultAggregator {
     }
 }
 
+/// User message listing the original task and each sub-agent output
+fn synthesis_input(task: &str, results: &[SubAgentResult]) -> String {
+    let mut input = format!("## Original task\n{}\n", task);
+    for (index, result) in results.iter().filter(|r| r.success).enumerate() {
+        input.push_str(&format!("\n## Result {}\n{}\n", index + 1, result.output.trim()));
+    }
+    input.push_str("\nCombine these results into a single answer to the original task.");
+    input
+}
+
 impl Default for ResultAggregator {
     fn default() -> Self {
         Self::with_defaults()
@@ -339,33 +469,51 @@ impl Default for ResultAggregator {
 pub struct ParallelExecutor {
     manager: Arc<Mutex<SubAgentManager>>,
     config: DelegationConfig,
+    cancel: CancellationToken,
 }
 
 impl ParallelExecutor {
     /// Create a new parallel executor
     pub fn new(manager: Arc<Mutex<SubAgentManager>>, config: DelegationConfig) -> Self {
-        Self { manager, config }
+        Self {
+            manager,
+            config,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Stop all in-flight tasks when `cancel` fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
     }
 
     /// Execute multiple tasks in parallel
+    ///
+    /// Tasks that time out or are cancelled yield failed results with
+    /// `TaskStatus::Timeout` / `TaskStatus::Cancelled`. With `fail_fast`, the
+    /// first error cancels the remaining tasks.
     pub async fn execute_all(&self, tasks: Vec<SubAgentTask>) -> Result<Vec<SubAgentResult>> {
         let semaphore = Arc::new(Semaphore::new(self.config.max_concurrency));
         let results = Arc::new(Mutex::new(Vec::new()));
+        // Cancelling the run never cancels the caller's token
+        let run_cancel = self.cancel.child_token();
         let mut join_set = JoinSet::new();
 
         let start_time = Instant::now();
 
         for task in tasks {
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
+            let Some(permit) = acquire(&semaphore, &run_cancel).await else {
+                break;
+            };
             let manager = self.manager.clone();
             let results = results.clone();
             let fail_fast = self.config.fail_fast;
+            let run_cancel = run_cancel.clone();
+            let timeout = task_timeout(&self.config, &task);
 
             join_set.spawn(async move {
-                let result = {
-                    let m = manager.lock().await;
-                    m.execute_with_best_agent(task).await
-                };
+                let result = run_best(&manager, task, &run_cancel, timeout).await;
 
                 drop(permit);
 
@@ -377,6 +525,7 @@ impl ParallelExecutor {
                     Err(e) => {
                         if fail_fast {
                             error!("Task failed, aborting due to fail_fast: {}", e);
+                            run_cancel.cancel();
                             return Err(e);
                         }
                         warn!("Task failed: {}", e);
@@ -388,11 +537,15 @@ impl ParallelExecutor {
         }
 
         // Wait for all tasks to complete
+        let mut first_error = None;
         while let Some(res) = join_set.join_next().await {
             if let Ok(Err(e)) = res {
-                if self.config.fail_fast {
-                    return Err(e);
-                }
+                first_error.get_or_insert(e);
+            }
+        }
+        if self.config.fail_fast {
+            if let Some(e) = first_error {
+                return Err(e);
             }
         }
 
@@ -410,18 +563,20 @@ impl ParallelExecutor {
     pub async fn execute_ordered(&self, tasks: Vec<SubAgentTask>) -> Result<Vec<SubAgentResult>> {
         let semaphore = Arc::new(Semaphore::new(self.config.max_concurrency));
         let results = Arc::new(Mutex::new(vec![None; tasks.len()]));
+        let run_cancel = self.cancel.child_token();
         let mut join_set = JoinSet::new();
 
         for (index, task) in tasks.into_iter().enumerate() {
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
+            let Some(permit) = acquire(&semaphore, &run_cancel).await else {
+                break;
+            };
             let manager = self.manager.clone();
             let results = results.clone();
+            let run_cancel = run_cancel.clone();
+            let timeout = task_timeout(&self.config, &task);
 
             join_set.spawn(async move {
-                let result = {
-                    let m = manager.lock().await;
-                    m.execute_with_best_agent(task).await
-                };
+                let result = run_best(&manager, task, &run_cancel, timeout).await;
 
                 drop(permit);
 
@@ -456,10 +611,15 @@ impl ParallelExecutor {
             tasks.drain(..).map(|t| (t, 0)).collect();
 
         while let Some((task, attempts)) = retry_queue.pop_front() {
+            if self.cancel.is_cancelled() {
+                break;
+            }
             let result = self.execute_all(vec![task.clone()]).await?;
 
             for r in result {
-                if r.success || attempts >= max_retries {
+                // Cancelled and timed-out tasks are not retried
+                let retryable = r.status == TaskStatus::Failed;
+                if r.success || !retryable || attempts >= max_retries {
                     all_results.push(r);
                 } else {
                     warn!(
@@ -477,6 +637,100 @@ impl ParallelExecutor {
     }
 }
 
+/// Timeout of a task (`default_timeout_secs` if the task sets none)
+pub(super) fn task_timeout(config: &DelegationConfig, task: &SubAgentTask) -> Duration {
+    let secs = if task.timeout_secs > 0 {
+        task.timeout_secs
+    } else {
+        config.default_timeout_secs
+    };
+    Duration::from_secs(secs)
+}
+
+/// Wait for a permit unless the run is cancelled first
+async fn acquire(
+    semaphore: &Arc<Semaphore>,
+    cancel: &CancellationToken,
+) -> Option<tokio::sync::OwnedSemaphorePermit> {
+    tokio::select! {
+        _ = cancel.cancelled() => None,
+        permit = semaphore.clone().acquire_owned() => permit.ok(),
+    }
+}
+
+/// Run a task on the best agent
+///
+/// The manager lock is only held while selecting the agent, so tasks really
+/// run in parallel.
+async fn run_best(
+    manager: &Mutex<SubAgentManager>,
+    task: SubAgentTask,
+    cancel: &CancellationToken,
+    timeout: Duration,
+) -> Result<SubAgentResult> {
+    let (agent, metrics) = {
+        let manager = manager.lock().await;
+        let agent = manager
+            .find_best_agent(&task)
+            .ok_or_else(|| crate::Error::Other("No agent available for task".to_string()))?;
+        (agent, manager.metrics())
+    };
+    run_task(agent, task, cancel, timeout, &metrics).await
+}
+
+/// Run a task until it finishes, times out, or either token is cancelled
+///
+/// Stopping drops the agent's future (aborting in-flight LLM requests and
+/// tool executions) and cancels the task's own token for work it spawned.
+/// The outcome is recorded in `metrics`.
+pub(super) async fn run_task(
+    agent: Arc<dyn SubAgent>,
+    task: SubAgentTask,
+    cancel: &CancellationToken,
+    timeout: Duration,
+    metrics: &AgentMetricsTracker,
+) -> Result<SubAgentResult> {
+    let started = Instant::now();
+    let task_id = task.id.clone();
+    let task_cancel = task.cancel.clone();
+    let events = task.events.clone();
+
+    let status = tokio::select! {
+        _ = cancel.cancelled() => TaskStatus::Cancelled,
+        _ = task_cancel.cancelled() => TaskStatus::Cancelled,
+        result = tokio::time::timeout(timeout, agent.execute(task)) => match result {
+            Ok(result) => {
+                if let Ok(result) = &result {
+                    metrics.record(result, started.elapsed());
+                }
+                return result;
+            }
+            Err(_) => TaskStatus::Timeout,
+        },
+    };
+
+    task_cancel.cancel();
+    let error = match status {
+        TaskStatus::Timeout => format!("Task timed out after {:?}", timeout),
+        _ => "Task cancelled".to_string(),
+    };
+    warn!("Task {} stopped: {}", task_id.as_str(), error);
+    if let Some(events) = events {
+        let _ = events.send(SubAgentEvent::new(
+            task_id.clone(),
+            agent.id().clone(),
+            agent.name(),
+            SubAgentEventKind::TaskFinished {
+                status,
+                error: Some(error.clone()),
+            },
+        ));
+    }
+    let result = SubAgentResult::failure(task_id, agent.id().clone(), error, status);
+    metrics.record(&result, started.elapsed());
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -596,4 +850,120 @@ mod tests {
         assert!(subtasks[1].instruction.contains("performance"));
         assert!(subtasks[2].instruction.contains("readability"));
     }
+
+    #[test]
+    fn test_synthesis_input() {
+        let task_id = TaskId::new("test");
+        let agent_id = SubAgentId::new("agent");
+        let results = vec![
+            SubAgentResult::success(task_id.clone(), agent_id.clone(), "Fast", 1, 10, 5, 100),
+            SubAgentResult::failure(task_id.clone(), agent_id.clone(), "Error", TaskStatus::Failed),
+            SubAgentResult::success(task_id.clone(), agent_id.clone(), "Secure", 1, 10, 5, 100),
+        ];
+
+        let input = synthesis_input("Review the design", &results);
+        assert!(input.starts_with("## Original task\nReview the design"));
+        assert!(input.contains("## Result 1\nFast"));
+        assert!(input.contains("## Result 2\nSecure"));
+        assert!(!input.contains("Error"));
+    }
+
+    #[tokio::test]
+    async fn test_llm_synthesize_without_client_falls_back() {
+        let aggregator = ResultAggregator::new(AggregationStrategy::LlmSynthesize);
+        let task_id = TaskId::new("test");
+        let agent_id = SubAgentId::new("agent");
+        let results = vec![
+            SubAgentResult::success(task_id.clone(), agent_id.clone(), "A", 1, 10, 5, 100),
+            SubAgentResult::success(task_id.clone(), agent_id.clone(), "B", 1, 10, 5, 100),
+        ];
+
+        let aggregated = aggregator.synthesize("task", results).await;
+        assert_eq!(aggregated.combined_output, "A\n\n---\n\nB");
+        assert_eq!(aggregated.successful_count, 2);
+    }
+
+    /// Sleeps far longer than any test waits
+    struct SlowAgent {
+        id: SubAgentId,
+    }
+
+    #[async_trait::async_trait]
+    impl SubAgent for SlowAgent {
+        fn id(&self) -> &SubAgentId {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn description(&self) -> &str {
+            "Never finishes in time"
+        }
+
+        fn capabilities(&self) -> Vec<crate::agents::AgentCapability> {
+            vec![]
+        }
+
+        fn can_handle(&self, _task: &SubAgentTask) -> bool {
+            true
+        }
+
+        async fn execute(&self, task: SubAgentTask) -> Result<SubAgentResult> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(SubAgentResult::success(task.id, self.id.clone(), "done", 1, 0, 0, 0))
+        }
+    }
+
+    fn slow_manager() -> Arc<Mutex<SubAgentManager>> {
+        let mut manager = SubAgentManager::new();
+        manager.register(Arc::new(SlowAgent {
+            id: SubAgentId::new("slow"),
+        }));
+        Arc::new(Mutex::new(manager))
+    }
+
+    #[tokio::test]
+    async fn test_parallel_task_timeout() {
+        let executor = ParallelExecutor::new(slow_manager(), DelegationConfig::default());
+        let (events, mut progress) = crate::agents::event_channel();
+        let task = SubAgentTask::new("wait").with_timeout(1).with_events(events);
+
+        let started = Instant::now();
+        let results = executor.execute_all(vec![task]).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(results[0].status, TaskStatus::Timeout);
+
+        let event = progress.recv().await.unwrap();
+        assert!(matches!(
+            event.kind,
+            SubAgentEventKind::TaskFinished { status: TaskStatus::Timeout, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delegation_cancelled() {
+        let cancel = CancellationToken::new();
+        let delegator = TaskDelegator::with_defaults(slow_manager()).with_cancellation(cancel.clone());
+
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+        let task = SubAgentTask::new("wait");
+        let task_cancel = task.cancel.clone();
+        let results = delegator
+            .delegate_parallel(vec![task, SubAgentTask::new("wait")])
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.status == TaskStatus::Cancelled));
+        assert!(task_cancel.is_cancelled());
+
+        // Tasks delegated after cancellation stop immediately
+        let result = delegator.delegate(SubAgentTask::new("wait")).await.unwrap();
+        assert_eq!(result.status, TaskStatus::Cancelled);
+    }
 }
@@ -0,0 +1,166 @@
+//! Sub-Agent progress events
+//!
+//! サブエージェントの実行状況 (イテレーション開始・ツール呼び出し・トークン使用量)
+//! をイベントとして送信します。タスクに [`SubAgentEventSender`] を持たせると
+//! エージェントループから直接イベントが届き、[`SubAgentEventHub`] を使うと
+//! cc-ws / cc-dashboard など複数の購読者に配信できます。
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+
+use super::types::{SubAgentId, TaskId, TaskStatus};
+
+/// Sending half of a progress event stream
+pub type SubAgentEventSender = mpsc::UnboundedSender<SubAgentEvent>;
+
+/// Receiving half of a progress event stream
+pub type SubAgentEventReceiver = mpsc::UnboundedReceiver<SubAgentEvent>;
+
+/// Create a progress event stream
+pub fn event_channel() -> (SubAgentEventSender, SubAgentEventReceiver) {
+    mpsc::unbounded_channel()
+}
+
+/// Progress of a sub-agent task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubAgentEvent {
+    pub task_id: TaskId,
+    pub agent_id: SubAgentId,
+    /// Agent name
+    pub agent: String,
+    #[serde(flatten)]
+    pub kind: SubAgentEventKind,
+    /// Unix timestamp (milliseconds)
+    pub timestamp: i64,
+}
+
+/// What happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SubAgentEventKind {
+    /// The agent picked up the task
+    TaskStarted { instruction: String },
+    /// An agent loop iteration (LLM call) started
+    IterationStarted { iteration: usize },
+    /// A tool was executed
+    ToolCalled { tool: String, is_error: bool },
+    /// Tokens used by one LLM call
+    TokensUsed { input_tokens: u64, output_tokens: u64 },
+    /// The task finished (successfully or not)
+    TaskFinished {
+        status: TaskStatus,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+impl SubAgentEvent {
+    pub fn new(
+        task_id: TaskId,
+        agent_id: SubAgentId,
+        agent: impl Into<String>,
+        kind: SubAgentEventKind,
+    ) -> Self {
+        Self {
+            task_id,
+            agent_id,
+            agent: agent.into(),
+            kind,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// Fans progress events out to any number of subscribers
+///
+/// Give tasks a [`sender`](Self::sender) and let the WebSocket server or the
+/// dashboard [`subscribe`](Self::subscribe). Events are dropped while nobody
+/// is subscribed; slow subscribers lose the oldest events.
+#[derive(Clone)]
+pub struct SubAgentEventHub {
+    tx: broadcast::Sender<SubAgentEvent>,
+}
+
+impl Default for SubAgentEventHub {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl SubAgentEventHub {
+    /// Create a hub buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self { tx }
+    }
+
+    /// Sender for tasks; events are forwarded to the subscribers
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn sender(&self) -> SubAgentEventSender {
+        let (tx, mut rx) = event_channel();
+        let hub = self.tx.clone();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                // No subscribers is not an error
+                let _ = hub.send(event);
+            }
+        });
+        tx
+    }
+
+    /// Publish an event directly
+    pub fn publish(&self, event: SubAgentEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SubAgentEvent> {
+        self.tx.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: SubAgentEventKind) -> SubAgentEvent {
+        SubAgentEvent::new(TaskId::new("t1"), SubAgentId::new("a1"), "coder", kind)
+    }
+
+    #[test]
+    fn test_event_json() {
+        let json = serde_json::to_value(event(SubAgentEventKind::ToolCalled {
+            tool: "bash".to_string(),
+            is_error: false,
+        }))
+        .unwrap();
+        assert_eq!(json["event"], "tool_called");
+        assert_eq!(json["tool"], "bash");
+        assert_eq!(json["task_id"], "t1");
+        assert_eq!(json["agent"], "coder");
+
+        let parsed: SubAgentEvent = serde_json::from_value(json).unwrap();
+        assert!(matches!(parsed.kind, SubAgentEventKind::ToolCalled { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_hub_fans_out() {
+        let hub = SubAgentEventHub::default();
+        let mut first = hub.subscribe();
+        let mut second = hub.subscribe();
+
+        let tx = hub.sender();
+        tx.send(event(SubAgentEventKind::IterationStarted { iteration: 1 }))
+            .unwrap();
+
+        for rx in [&mut first, &mut second] {
+            let received = rx.recv().await.unwrap();
+            assert_eq!(received.kind, SubAgentEventKind::IterationStarted { iteration: 1 });
+        }
+        assert_eq!(hub.subscriber_count(), 2);
+    }
+}
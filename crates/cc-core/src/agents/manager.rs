@@ -2,11 +2,14 @@
 //!
 //! Manages registration, lookup, and selection of sub-agents.
 //! Implements capability-based routing for task delegation.
+//! Agents with recent consecutive failures are avoided (see [`AgentMetricsTracker`]).
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, info, warn};
 
+use super::metrics::{AgentMetricsTracker, HealthPolicy};
 use super::types::{AgentCapability, SubAgent, SubAgentId, SubAgentTask, SubAgentResult};
 use crate::Result;
 
@@ -18,6 +21,8 @@ pub struct SubAgentManager {
     name_to_id: HashMap<String, SubAgentId>,
     /// Default agent ID for tasks without specific routing
     default_agent_id: Option<SubAgentId>,
+    /// Live execution statistics
+    metrics: Arc<AgentMetricsTracker>,
 }
 
 impl SubAgentManager {
@@ -27,9 +32,20 @@ impl SubAgentManager {
             agents: HashMap::new(),
             name_to_id: HashMap::new(),
             default_agent_id: None,
+            metrics: Arc::new(AgentMetricsTracker::default()),
         }
     }
 
+    /// Set when agents are considered unhealthy (resets the statistics)
+    pub fn set_health_policy(&mut self, policy: HealthPolicy) {
+        self.metrics = Arc::new(AgentMetricsTracker::new(policy));
+    }
+
+    /// Live statistics of the registered agents
+    pub fn metrics(&self) -> Arc<AgentMetricsTracker> {
+        Arc::clone(&self.metrics)
+    }
+
     /// Register a sub-agent
     pub fn register(&mut self, agent: Arc<dyn SubAgent>) {
         let id = agent.id().clone();
@@ -94,13 +110,33 @@ impl SubAgentManager {
     }
 
     /// Find the best agent for a task based on capabilities
+    ///
+    /// Unhealthy agents are only chosen when no healthy agent can handle the task.
     pub fn find_best_agent(&self, task: &SubAgentTask) -> Option<Arc<dyn SubAgent>> {
+        self.find_best_matching(task, true)
+            .or_else(|| {
+                self.get_default()
+                    .filter(|a| self.metrics.is_available(a.id()))
+            })
+            .or_else(|| self.find_best_matching(task, false))
+            .or_else(|| self.get_default())
+    }
+
+    fn find_best_matching(
+        &self,
+        task: &SubAgentTask,
+        healthy_only: bool,
+    ) -> Option<Arc<dyn SubAgent>> {
         let mut best_match: Option<(Arc<dyn SubAgent>, usize)> = None;
 
         for agent in self.agents.values() {
             if !agent.can_handle(task) {
                 continue;
             }
+            if healthy_only && !self.metrics.is_available(agent.id()) {
+                debug!("Skipping unhealthy agent: {}", agent.name());
+                continue;
+            }
 
             // Count matching capabilities
             let match_score = agent
@@ -119,8 +155,7 @@ impl SubAgentManager {
             }
         }
 
-        // Return best match or default agent
-        best_match.map(|(a, _)| a).or_else(|| self.get_default())
+        best_match.map(|(a, _)| a)
     }
 
     /// Get all registered agents
@@ -176,7 +211,7 @@ impl SubAgentManager {
             agent.name()
         );
 
-        agent.execute(task).await
+        self.execute_tracked(&agent, task).await
     }
 
     /// Execute a task with a specific agent
@@ -189,7 +224,19 @@ impl SubAgentManager {
             .get(agent_id)
             .ok_or_else(|| crate::Error::Other(format!("Agent not found: {}", agent_id.as_str())))?;
 
-        agent.execute(task).await
+        self.execute_tracked(&agent, task).await
+    }
+
+    /// Execute a task with `agent` and record its statistics
+    pub async fn execute_tracked(
+        &self,
+        agent: &Arc<dyn SubAgent>,
+        task: SubAgentTask,
+    ) -> Result<SubAgentResult> {
+        let started = Instant::now();
+        let result = agent.execute(task).await?;
+        self.metrics.record(&result, started.elapsed());
+        Ok(result)
     }
 }
 
@@ -343,4 +390,34 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().success);
     }
+
+    #[tokio::test]
+    async fn test_manager_records_metrics_and_avoids_unhealthy() {
+        let mut manager = SubAgentManager::new();
+        let cap = AgentCapability::new("code", "Code").with_keywords(vec!["code".into()]);
+        manager.register(Arc::new(MockAgent::new("generalist", vec![])));
+        manager.register(Arc::new(MockAgent::new("coder", vec![cap])));
+
+        manager
+            .execute_with_agent(&SubAgentId::new("coder"), SubAgentTask::new("code"))
+            .await
+            .unwrap();
+        let metrics = manager.metrics();
+        assert_eq!(metrics.get(&SubAgentId::new("coder")).tasks_completed, 1);
+
+        let task = SubAgentTask::new("Write some code");
+        assert_eq!(manager.find_best_agent(&task).unwrap().name(), "coder");
+
+        for _ in 0..metrics.policy().failure_threshold {
+            let failed = SubAgentResult::failure(
+                TaskId::new("t"),
+                SubAgentId::new("coder"),
+                "boom",
+                crate::agents::TaskStatus::Failed,
+            );
+            metrics.record(&failed, std::time::Duration::ZERO);
+        }
+        // Falls back to the healthy default agent
+        assert_eq!(manager.find_best_agent(&task).unwrap().name(), "generalist");
+    }
 }
@@ -0,0 +1,214 @@
+//! Sub-Agent live metrics and health
+//!
+//! エージェントごとの実行統計 (完了数・失敗率・平均レイテンシ・トークン数) を記録し、
+//! 直近で失敗が続いているエージェントを委譲先から外すために使います。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::types::{SubAgentId, SubAgentResult, TaskStatus};
+
+/// When an agent is considered unhealthy
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HealthPolicy {
+    /// Consecutive failures after which the agent is avoided
+    pub failure_threshold: u32,
+    /// How long an unhealthy agent is avoided after its last failure
+    pub cooldown_secs: u64,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown_secs: 300,
+        }
+    }
+}
+
+/// Health of an agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentHealth {
+    Healthy,
+    /// The last task failed
+    Degraded,
+    /// Too many recent consecutive failures; avoided in delegation
+    Unhealthy,
+}
+
+/// Execution statistics of one agent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentMetrics {
+    pub tasks_completed: u64,
+    pub tasks_failed: u64,
+    pub consecutive_failures: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Sum of task latencies (for the average)
+    pub total_latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure_at: Option<DateTime<Utc>>,
+}
+
+impl AgentMetrics {
+    /// Finished tasks (cancelled tasks are not counted)
+    pub fn total_tasks(&self) -> u64 {
+        self.tasks_completed + self.tasks_failed
+    }
+
+    /// Fraction of finished tasks that failed (0.0 without tasks)
+    pub fn failure_rate(&self) -> f64 {
+        match self.total_tasks() {
+            0 => 0.0,
+            total => self.tasks_failed as f64 / total as f64,
+        }
+    }
+
+    /// Average task latency in milliseconds (0 without tasks)
+    pub fn average_latency_ms(&self) -> u64 {
+        self.total_latency_ms.checked_div(self.total_tasks()).unwrap_or(0)
+    }
+
+    /// Health under `policy` at `now`
+    pub fn health(&self, policy: &HealthPolicy, now: DateTime<Utc>) -> AgentHealth {
+        if self.consecutive_failures == 0 {
+            return AgentHealth::Healthy;
+        }
+        let recent = self.last_failure_at.is_some_and(|at| {
+            (now - at).num_seconds() < policy.cooldown_secs as i64
+        });
+        if recent && self.consecutive_failures >= policy.failure_threshold {
+            AgentHealth::Unhealthy
+        } else {
+            AgentHealth::Degraded
+        }
+    }
+
+    fn record(&mut self, result: &SubAgentResult, latency: Duration, now: DateTime<Utc>) {
+        self.input_tokens += result.input_tokens;
+        self.output_tokens += result.output_tokens;
+        self.total_latency_ms += latency.as_millis() as u64;
+        if result.success {
+            self.tasks_completed += 1;
+            self.consecutive_failures = 0;
+        } else {
+            self.tasks_failed += 1;
+            self.consecutive_failures += 1;
+            self.last_error = result.error.clone();
+            self.last_failure_at = Some(now);
+        }
+    }
+}
+
+/// Thread-safe per-agent metrics shared by the manager and executors
+#[derive(Debug, Default)]
+pub struct AgentMetricsTracker {
+    metrics: Mutex<HashMap<SubAgentId, AgentMetrics>>,
+    policy: HealthPolicy,
+}
+
+impl AgentMetricsTracker {
+    pub fn new(policy: HealthPolicy) -> Self {
+        Self {
+            metrics: Mutex::new(HashMap::new()),
+            policy,
+        }
+    }
+
+    pub fn policy(&self) -> &HealthPolicy {
+        &self.policy
+    }
+
+    /// Record a finished task
+    ///
+    /// Cancelled tasks are ignored; they say nothing about the agent.
+    pub fn record(&self, result: &SubAgentResult, latency: Duration) {
+        if result.status == TaskStatus::Cancelled {
+            return;
+        }
+        let mut metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        metrics
+            .entry(result.agent_id.clone())
+            .or_default()
+            .record(result, latency, Utc::now());
+    }
+
+    /// Statistics of an agent (empty if it has not run yet)
+    pub fn get(&self, id: &SubAgentId) -> AgentMetrics {
+        let metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        metrics.get(id).cloned().unwrap_or_default()
+    }
+
+    pub fn health(&self, id: &SubAgentId) -> AgentHealth {
+        self.get(id).health(&self.policy, Utc::now())
+    }
+
+    pub fn is_available(&self, id: &SubAgentId) -> bool {
+        self.health(id) != AgentHealth::Unhealthy
+    }
+
+    /// Forget the statistics of an agent
+    pub fn reset(&self, id: &SubAgentId) {
+        let mut metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        metrics.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::types::TaskId;
+
+    fn failure(status: TaskStatus) -> SubAgentResult {
+        SubAgentResult::failure(TaskId::new("t"), SubAgentId::new("a"), "boom", status)
+    }
+
+    #[test]
+    fn test_metrics_aggregate() {
+        let tracker = AgentMetricsTracker::default();
+        let id = SubAgentId::new("a");
+        let ok = SubAgentResult::success(TaskId::new("t"), id.clone(), "ok", 1, 100, 20, 0);
+
+        tracker.record(&ok, Duration::from_millis(300));
+        tracker.record(&failure(TaskStatus::Timeout), Duration::from_millis(100));
+        tracker.record(&failure(TaskStatus::Cancelled), Duration::from_millis(999));
+
+        let metrics = tracker.get(&id);
+        assert_eq!(metrics.total_tasks(), 2);
+        assert_eq!(metrics.failure_rate(), 0.5);
+        assert_eq!(metrics.average_latency_ms(), 200);
+        assert_eq!(metrics.input_tokens, 100);
+        assert_eq!(metrics.last_error.as_deref(), Some("boom"));
+        assert_eq!(tracker.health(&id), AgentHealth::Degraded);
+    }
+
+    #[test]
+    fn test_unhealthy_after_consecutive_failures() {
+        let policy = HealthPolicy {
+            failure_threshold: 2,
+            cooldown_secs: 60,
+        };
+        let tracker = AgentMetricsTracker::new(policy);
+        let id = SubAgentId::new("a");
+
+        tracker.record(&failure(TaskStatus::Failed), Duration::ZERO);
+        assert!(tracker.is_available(&id));
+        tracker.record(&failure(TaskStatus::Failed), Duration::ZERO);
+        assert_eq!(tracker.health(&id), AgentHealth::Unhealthy);
+        assert!(!tracker.is_available(&id));
+
+        // The cooldown expires
+        let later = Utc::now() + chrono::Duration::seconds(61);
+        assert_eq!(tracker.get(&id).health(&policy, later), AgentHealth::Degraded);
+
+        tracker.reset(&id);
+        assert_eq!(tracker.health(&id), AgentHealth::Healthy);
+    }
+}
@@ -60,20 +60,56 @@
 //! let result = delegator.delegate(task).await?;
 //! println!("Result: {}", result.output);
 //! ```
+//!
+//! The primary agent can delegate on its own through the `delegate_task` tool
+//! ([`DelegateTaskTool`]).
+//!
+//! For multi-step goals, [`Orchestrator`] plans a task DAG with the main model,
+//! runs the tasks on sub-agents in dependency order and reviews the result.
+//!
+//! Attach a [`SubAgentEventSender`] to a task (or use a [`SubAgentEventHub`])
+//! to follow iterations, tool calls and token usage live.
+//!
+//! [`SubAgentManager`] keeps per-agent statistics ([`AgentMetricsTracker`]) and
+//! avoids agents that failed repeatedly in the recent past.
+//!
+//! [`TaskQueue`] stores tasks in SQLite and runs them in the background, so
+//! delegated work survives restarts and can be looked up by [`TaskId`].
+//!
+//! Agents can also be declared in agents.toml / agents.yaml and registered
+//! with [`AgentsConfig::register_all`].
 
+pub mod config;
 pub mod default;
 pub mod delegation;
+pub mod events;
 pub mod manager;
+pub mod metrics;
+pub mod orchestrator;
+pub mod queue;
+pub mod tool;
 pub mod types;
 
 // Re-exports
+pub use config::{AgentDefinition, AgentsConfig};
 pub use default::DefaultSubAgent;
 pub use delegation::{
     AggregatedResult, AggregationStrategy, DelegationConfig, ParallelExecutor, ResultAggregator,
     TaskDelegator,
 };
+pub use events::{
+    SubAgentEvent, SubAgentEventHub, SubAgentEventKind, SubAgentEventReceiver,
+    SubAgentEventSender, event_channel,
+};
 pub use manager::SubAgentManager;
+pub use metrics::{AgentHealth, AgentMetrics, AgentMetricsTracker, HealthPolicy};
+pub use orchestrator::{
+    OrchestrationResult, Orchestrator, OrchestratorConfig, PlannedTask, ReviewOutcome, TaskPlan,
+};
+pub use queue::{CALLBACK_SIGNATURE_HEADER, CALLBACK_URL_KEY, QueuedTask, TaskQueue};
+pub use tool::{DELEGATE_TASK_TOOL, DelegateTaskTool, DelegationContext};
 pub use types::{
     AgentCapability, SubAgent, SubAgentId, SubAgentResult, SubAgentTask, SubAgentTaskBuilder,
     TaskId, TaskPriority, TaskStatus, ToolCallRecord,
 };
+pub use tokio_util::sync::CancellationToken;
@@ -0,0 +1,606 @@
+//! Hierarchical Orchestration (planner → workers → reviewer)
+//!
+//! The main model decomposes a goal into a task DAG, the tasks are delegated
+//! to sub-agents in dependency order (independent tasks run in parallel), the
+//! outputs are synthesized and a reviewer pass checks the final answer.
+//!
+//! ```rust,ignore
+//! let orchestrator = Orchestrator::new(client, Arc::new(manager));
+//! let result = orchestrator.run("Compare the three candidate databases").await?;
+//! println!("{}", result.output);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use super::delegation::{AggregationStrategy, ResultAggregator};
+use super::events::SubAgentEventSender;
+use super::manager::SubAgentManager;
+use super::types::{SubAgentId, SubAgentResult, SubAgentTask, TaskId, TaskStatus};
+use crate::llm::{ClaudeClient, MessageContent, MessagesRequestBuilder, repair_json};
+use crate::{Error, Result};
+
+const PLANNER_PROMPT: &str = "You are a planner that splits a goal into tasks for specialist \
+sub-agents. Respond with JSON only, in the form \
+{\"tasks\": [{\"id\": \"short_id\", \"instruction\": \"...\", \"agent\": \"agent name or null\", \
+\"depends_on\": [\"id\"]}]}. Each instruction must be self-contained. Use depends_on only when a \
+task needs another task's result; independent tasks run in parallel. Prefer few tasks.";
+
+const REVIEWER_PROMPT: &str = "You review an answer produced by a team of agents against the \
+original goal. Respond with JSON only, in the form {\"approved\": true|false, \"feedback\": \
+\"...\", \"revised_answer\": \"... or null\"}. Set approved to false if the answer is incomplete or \
+wrong, and provide a corrected revised_answer when you can.";
+
+/// Maximum tokens for planner and reviewer calls
+const ORCHESTRATOR_MAX_TOKENS: u64 = 4096;
+
+/// A task in the plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedTask {
+    /// Task identifier referenced by `depends_on`
+    pub id: String,
+    /// Instruction for the sub-agent
+    pub instruction: String,
+    /// Sub-agent name (best match if omitted or unknown)
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// Tasks whose results this task needs
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+/// Task DAG produced by the planner
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskPlan {
+    pub tasks: Vec<PlannedTask>,
+}
+
+impl TaskPlan {
+    /// Check ids, dependencies and cycles
+    pub fn validate(&self) -> Result<()> {
+        if self.tasks.is_empty() {
+            return Err(Error::Other("plan has no tasks".to_string()));
+        }
+        let mut ids = HashSet::new();
+        for task in &self.tasks {
+            if !ids.insert(task.id.as_str()) {
+                return Err(Error::Other(format!("duplicate task id: {}", task.id)));
+            }
+        }
+        for task in &self.tasks {
+            if let Some(missing) = task.depends_on.iter().find(|d| !ids.contains(d.as_str())) {
+                return Err(Error::Other(format!(
+                    "task {} depends on unknown task {}",
+                    task.id, missing
+                )));
+            }
+        }
+        self.layers().map(|_| ())
+    }
+
+    /// Group tasks into layers; each layer only depends on earlier layers
+    pub fn layers(&self) -> Result<Vec<Vec<&PlannedTask>>> {
+        let mut done: HashSet<&str> = HashSet::new();
+        let mut remaining: Vec<&PlannedTask> = self.tasks.iter().collect();
+        let mut layers = Vec::new();
+
+        while !remaining.is_empty() {
+            let (ready, blocked): (Vec<_>, Vec<_>) = remaining
+                .into_iter()
+                .partition(|t| t.depends_on.iter().all(|d| done.contains(d.as_str())));
+            if ready.is_empty() {
+                return Err(Error::Other("plan contains a dependency cycle".to_string()));
+            }
+            done.extend(ready.iter().map(|t| t.id.as_str()));
+            layers.push(ready);
+            remaining = blocked;
+        }
+        Ok(layers)
+    }
+
+    /// Tasks no other task depends on (their outputs form the answer)
+    pub fn sinks(&self) -> Vec<&PlannedTask> {
+        let needed: HashSet<&str> = self
+            .tasks
+            .iter()
+            .flat_map(|t| t.depends_on.iter().map(String::as_str))
+            .collect();
+        self.tasks
+            .iter()
+            .filter(|t| !needed.contains(t.id.as_str()))
+            .collect()
+    }
+}
+
+/// Reviewer verdict
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReviewOutcome {
+    pub approved: bool,
+    #[serde(default)]
+    pub feedback: String,
+    #[serde(default)]
+    pub revised_answer: Option<String>,
+}
+
+/// Configuration for the orchestrator
+#[derive(Debug, Clone)]
+pub struct OrchestratorConfig {
+    /// Maximum number of planned tasks
+    pub max_tasks: usize,
+    /// Maximum concurrent sub-agent executions
+    pub max_concurrency: usize,
+    /// Run the reviewer pass
+    pub review: bool,
+    /// Maximum iterations for each sub-agent task
+    pub max_iterations: usize,
+}
+
+impl Default for OrchestratorConfig {
+    fn default() -> Self {
+        Self {
+            max_tasks: 8,
+            max_concurrency: 4,
+            review: true,
+            max_iterations: 10,
+        }
+    }
+}
+
+/// Result of an orchestrated run
+#[derive(Debug, Clone)]
+pub struct OrchestrationResult {
+    pub goal: String,
+    pub plan: TaskPlan,
+    /// Sub-agent results by planned task id
+    pub results: HashMap<String, SubAgentResult>,
+    /// Final answer (after review)
+    pub output: String,
+    /// Reviewer verdict (None if the review was skipped or failed)
+    pub review: Option<ReviewOutcome>,
+    /// All tasks succeeded and the reviewer did not reject the answer
+    pub success: bool,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub duration_ms: u64,
+}
+
+/// Planner → workers → reviewer orchestration over a [`SubAgentManager`]
+pub struct Orchestrator {
+    client: ClaudeClient,
+    manager: Arc<SubAgentManager>,
+    config: OrchestratorConfig,
+    cancel: CancellationToken,
+    events: Option<SubAgentEventSender>,
+}
+
+impl Orchestrator {
+    /// Create an orchestrator using `client` for planning, synthesis and review
+    pub fn new(client: ClaudeClient, manager: Arc<SubAgentManager>) -> Self {
+        Self {
+            client,
+            manager,
+            config: OrchestratorConfig::default(),
+            cancel: CancellationToken::new(),
+            events: None,
+        }
+    }
+
+    /// Set the configuration
+    pub fn with_config(mut self, config: OrchestratorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Stop the running tasks when `cancel` fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Send the workers' progress events to `events`
+    pub fn with_events(mut self, events: SubAgentEventSender) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Plan, execute and review a goal
+    pub async fn run(&self, goal: &str) -> Result<OrchestrationResult> {
+        let (plan, input_tokens, output_tokens) = self.plan(goal).await?;
+        let mut result = self.execute(goal, plan).await?;
+        result.input_tokens += input_tokens;
+        result.output_tokens += output_tokens;
+        Ok(result)
+    }
+
+    /// Ask the main model for a task plan
+    ///
+    /// Returns the plan and the tokens used.
+    pub async fn plan(&self, goal: &str) -> Result<(TaskPlan, u64, u64)> {
+        let agents = self
+            .manager
+            .all_agents()
+            .iter()
+            .map(|a| format!("- {}: {}", a.name(), a.description()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let prompt = format!(
+            "## Goal\n{}\n\n## Available agents\n{}\n\nPlan at most {} tasks.",
+            goal, agents, self.config.max_tasks
+        );
+
+        let (text, input_tokens, output_tokens) = self.ask(PLANNER_PROMPT, prompt).await?;
+        let plan = parse_plan(&text)?;
+        if plan.tasks.len() > self.config.max_tasks {
+            return Err(Error::Other(format!(
+                "plan has {} tasks (max {})",
+                plan.tasks.len(),
+                self.config.max_tasks
+            )));
+        }
+        info!("Orchestrator planned {} task(s)", plan.tasks.len());
+        Ok((plan, input_tokens, output_tokens))
+    }
+
+    /// Execute a plan layer by layer, then synthesize and review
+    pub async fn execute(&self, goal: &str, plan: TaskPlan) -> Result<OrchestrationResult> {
+        plan.validate()?;
+        let started = Instant::now();
+        let semaphore = Arc::new(Semaphore::new(self.config.max_concurrency.max(1)));
+        let mut results: HashMap<String, SubAgentResult> = HashMap::new();
+
+        for layer in plan.layers()? {
+            if self.cancel.is_cancelled() {
+                return Err(Error::Other("orchestration cancelled".to_string()));
+            }
+            let mut join_set = JoinSet::new();
+            for planned in layer {
+                // Skip tasks whose prerequisites failed
+                if let Some(failed) = planned
+                    .depends_on
+                    .iter()
+                    .find(|d| results.get(*d).is_none_or(|r| !r.success))
+                {
+                    results.insert(
+                        planned.id.clone(),
+                        SubAgentResult::failure(
+                            TaskId::new(&planned.id),
+                            SubAgentId::new("orchestrator"),
+                            format!("prerequisite task {} failed", failed),
+                            TaskStatus::Cancelled,
+                        ),
+                    );
+                    continue;
+                }
+
+                let mut task = SubAgentTask::new(task_instruction(planned, &results))
+                    .with_max_iterations(self.config.max_iterations)
+                    .with_cancellation(self.cancel.child_token());
+                task.events = self.events.clone();
+                let manager = Arc::clone(&self.manager);
+                let semaphore = Arc::clone(&semaphore);
+                let planned = planned.clone();
+                join_set.spawn(async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    debug!("Orchestrator running task {}", planned.id);
+                    let agent = planned.agent.as_deref().and_then(|name| manager.get_by_name(name));
+                    let result = match agent {
+                        Some(agent) => manager.execute_tracked(&agent, task).await,
+                        None => manager.execute_with_best_agent(task).await,
+                    };
+                    (planned.id, result)
+                });
+            }
+
+            while let Some(joined) = join_set.join_next().await {
+                let (id, result) =
+                    joined.map_err(|e| Error::Other(format!("task panicked: {}", e)))?;
+                let result = result.unwrap_or_else(|e| {
+                    SubAgentResult::failure(
+                        TaskId::new(&id),
+                        SubAgentId::new("orchestrator"),
+                        e.to_string(),
+                        TaskStatus::Failed,
+                    )
+                });
+                if !result.success {
+                    warn!("Orchestrator task {} failed: {:?}", id, result.error);
+                }
+                results.insert(id, result);
+            }
+        }
+
+        let mut input_tokens: u64 = results.values().map(|r| r.input_tokens).sum();
+        let mut output_tokens: u64 = results.values().map(|r| r.output_tokens).sum();
+        let all_succeeded = results.values().all(|r| r.success);
+
+        // Combine the outputs nothing else consumed
+        let final_results: Vec<SubAgentResult> = plan
+            .sinks()
+            .iter()
+            .filter_map(|t| results.get(&t.id).cloned())
+            .collect();
+        let aggregated = ResultAggregator::new(AggregationStrategy::LlmSynthesize)
+            .with_client(self.client.clone())
+            .synthesize(goal, final_results)
+            .await;
+        // The aggregate totals include the sink results already counted above
+        let sink_input: u64 = aggregated.results.iter().map(|r| r.input_tokens).sum();
+        let sink_output: u64 = aggregated.results.iter().map(|r| r.output_tokens).sum();
+        input_tokens += aggregated.total_input_tokens - sink_input;
+        output_tokens += aggregated.total_output_tokens - sink_output;
+        let mut output = aggregated.combined_output;
+
+        let review = if self.config.review && !output.is_empty() {
+            match self.review(goal, &output).await {
+                Ok((review, review_input, review_output)) => {
+                    input_tokens += review_input;
+                    output_tokens += review_output;
+                    Some(review)
+                }
+                Err(e) => {
+                    warn!("Orchestrator review failed: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(revised) = review
+            .as_ref()
+            .and_then(|r| r.revised_answer.as_ref())
+            .filter(|a| !a.trim().is_empty())
+        {
+            output = revised.clone();
+        }
+
+        let rejected = review
+            .as_ref()
+            .is_some_and(|r| !r.approved && r.revised_answer.is_none());
+        Ok(OrchestrationResult {
+            goal: goal.to_string(),
+            plan,
+            results,
+            output,
+            review,
+            success: all_succeeded && !rejected,
+            input_tokens,
+            output_tokens,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Reviewer pass over the combined answer
+    async fn review(&self, goal: &str, answer: &str) -> Result<(ReviewOutcome, u64, u64)> {
+        let prompt = format!("## Goal\n{}\n\n## Answer\n{}", goal, answer);
+        let (text, input_tokens, output_tokens) = self.ask(REVIEWER_PROMPT, prompt).await?;
+        let review = repair_json(&text)
+            .and_then(|v| serde_json::from_value(v).ok())
+            .ok_or_else(|| Error::Other("reviewer returned invalid JSON".to_string()))?;
+        Ok((review, input_tokens, output_tokens))
+    }
+
+    /// Single-turn call to the main model
+    async fn ask(&self, system: &str, prompt: String) -> Result<(String, u64, u64)> {
+        let request = MessagesRequestBuilder::new(self.client.model().to_string())
+            .system(system)
+            .max_tokens(ORCHESTRATOR_MAX_TOKENS)
+            .user(prompt)
+            .build();
+        let response = self.client.messages(request).await?;
+        let text = response
+            .content
+            .iter()
+            .filter_map(|c| match c {
+                MessageContent::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (input_tokens, output_tokens) = response
+            .usage
+            .map(|u| (u.input_tokens, u.output_tokens))
+            .unwrap_or_default();
+        Ok((text, input_tokens, output_tokens))
+    }
+}
+
+/// Parse the planner's JSON (a `{"tasks": [...]}` object or a bare array)
+fn parse_plan(text: &str) -> Result<TaskPlan> {
+    let value = repair_json(text)
+        .ok_or_else(|| Error::Other("planner returned invalid JSON".to_string()))?;
+    let plan = if value.is_array() {
+        serde_json::from_value(value).map(|tasks| TaskPlan { tasks })
+    } else {
+        serde_json::from_value(value)
+    }
+    .map_err(|e| Error::Other(format!("invalid plan: {}", e)))?;
+    plan.validate()?;
+    Ok(plan)
+}
+
+/// Instruction including the outputs of prerequisite tasks
+fn task_instruction(planned: &PlannedTask, results: &HashMap<String, SubAgentResult>) -> String {
+    if planned.depends_on.is_empty() {
+        return planned.instruction.clone();
+    }
+    let mut instruction = planned.instruction.clone();
+    instruction.push_str("\n\n## Results of prerequisite tasks");
+    for dep in &planned.depends_on {
+        if let Some(result) = results.get(dep) {
+            instruction.push_str(&format!("\n\n### {}\n{}", dep, result.output.trim()));
+        }
+    }
+    instruction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::types::{AgentCapability, SubAgent};
+    use async_trait::async_trait;
+
+    /// Echoes its instruction, or fails tasks containing "fail"
+    struct EchoAgent {
+        id: SubAgentId,
+    }
+
+    #[async_trait]
+    impl SubAgent for EchoAgent {
+        fn id(&self) -> &SubAgentId {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echoes the instruction"
+        }
+
+        fn capabilities(&self) -> Vec<AgentCapability> {
+            vec![]
+        }
+
+        fn can_handle(&self, _task: &SubAgentTask) -> bool {
+            true
+        }
+
+        async fn execute(&self, task: SubAgentTask) -> Result<SubAgentResult> {
+            if task.instruction.contains("fail") {
+                return Ok(SubAgentResult::failure(task.id, self.id.clone(), "boom", TaskStatus::Failed));
+            }
+            Ok(SubAgentResult::success(
+                task.id,
+                self.id.clone(),
+                format!("done: {}", task.instruction),
+                1,
+                10,
+                5,
+                1,
+            ))
+        }
+    }
+
+    fn orchestrator() -> Orchestrator {
+        let mut manager = SubAgentManager::new();
+        manager.register(Arc::new(EchoAgent {
+            id: SubAgentId::new("echo"),
+        }));
+        let config = crate::config::Config {
+            api: crate::config::ApiConfig::default(),
+            llm: crate::config::LlmConfig {
+                provider: crate::config::LlmProvider::Claude,
+                api_key: "test-key".to_string(),
+                model: "claude-sonnet-4-20250514".to_string(),
+                base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
+            },
+            claude_api_key: "test-key".to_string(),
+            claude_model: "claude-sonnet-4-20250514".to_string(),
+            discord_token: None,
+            admin_user_ids: vec![],
+            api_key: None,
+            mcp: crate::config::McpConfig::default(),
+            memory: crate::config::MemoryConfig::default(),
+            scheduler: crate::config::SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
+        };
+        Orchestrator::new(ClaudeClient::new(&config).unwrap(), Arc::new(manager)).with_config(
+            OrchestratorConfig {
+                review: false,
+                ..OrchestratorConfig::default()
+            },
+        )
+    }
+
+    fn task(id: &str, instruction: &str, depends_on: &[&str]) -> PlannedTask {
+        PlannedTask {
+            id: id.to_string(),
+            instruction: instruction.to_string(),
+            agent: None,
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_plan_layers_and_validation() {
+        let plan = TaskPlan {
+            tasks: vec![
+                task("a", "research A", &[]),
+                task("b", "research B", &[]),
+                task("c", "compare", &["a", "b"]),
+            ],
+        };
+        plan.validate().unwrap();
+        let layers = plan.layers().unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].len(), 2);
+        assert_eq!(layers[1][0].id, "c");
+        assert_eq!(plan.sinks().len(), 1);
+
+        let cycle = TaskPlan {
+            tasks: vec![task("a", "x", &["b"]), task("b", "y", &["a"])],
+        };
+        assert!(cycle.validate().is_err());
+        let unknown = TaskPlan {
+            tasks: vec![task("a", "x", &["missing"])],
+        };
+        assert!(unknown.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_plan() {
+        let plan = parse_plan(
+            r#"```json
+{"tasks": [{"id": "a", "instruction": "do it", "agent": null}]}
+```"#,
+        )
+        .unwrap();
+        assert_eq!(plan.tasks[0].id, "a");
+        assert!(plan.tasks[0].depends_on.is_empty());
+
+        let bare = parse_plan(r#"[{"id": "x", "instruction": "y"}]"#).unwrap();
+        assert_eq!(bare.tasks.len(), 1);
+        assert!(parse_plan("no plan here").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_passes_dependency_outputs() {
+        let plan = TaskPlan {
+            tasks: vec![task("a", "first", &[]), task("b", "second", &["a"])],
+        };
+        let result = orchestrator().execute("goal", plan).await.unwrap();
+
+        assert!(result.success);
+        assert!(result.results["b"].output.contains("### a\ndone: first"));
+        assert_eq!(result.output, result.results["b"].output);
+        assert_eq!(result.input_tokens, 20);
+    }
+
+    #[tokio::test]
+    async fn test_execute_skips_dependents_of_failed_tasks() {
+        let plan = TaskPlan {
+            tasks: vec![task("a", "fail here", &[]), task("b", "second", &["a"])],
+        };
+        let result = orchestrator().execute("goal", plan).await.unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.results["b"].status, TaskStatus::Cancelled);
+        assert!(result.output.is_empty());
+    }
+}
@@ -0,0 +1,888 @@
+//! Persistent task queue for delegated work
+//!
+//! API などから受け付けた [`SubAgentTask`] を SQLite に保存し、バックグラウンドで
+//! 優先度・開始予定時刻の順に実行します。再起動をまたいでも失われず、
+//! [`TaskId`] で状態と結果を照会できます。
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Notify, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use super::delegation::{DelegationConfig, run_task, task_timeout};
+use super::manager::SubAgentManager;
+use super::types::{SubAgentId, SubAgentResult, SubAgentTask, TaskId, TaskPriority, TaskStatus};
+use crate::migrate::{migrate, Migration};
+use crate::Result;
+
+/// Re-check interval when no task is due
+const IDLE_POLL: Duration = Duration::from_secs(3600);
+
+/// Task metadata key of the URL notified when the task finishes
+pub const CALLBACK_URL_KEY: &str = "callback_url";
+
+/// Header carrying the `sha256=<hex>` HMAC of a completion callback body
+pub const CALLBACK_SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Timeout of a completion callback request
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Schema versions of the `agent_tasks` table
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create agent_tasks",
+    "CREATE TABLE IF NOT EXISTS agent_tasks (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        id TEXT NOT NULL UNIQUE,
+        instruction TEXT NOT NULL,
+        agent TEXT,
+        priority INTEGER NOT NULL,
+        status TEXT NOT NULL,
+        run_at TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        started_at TEXT,
+        finished_at TEXT,
+        task TEXT NOT NULL,
+        result TEXT
+    );
+    CREATE INDEX IF NOT EXISTS idx_agent_tasks_due ON agent_tasks(status, run_at);",
+)];
+
+/// A task in the queue with its current state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTask {
+    pub id: TaskId,
+    pub instruction: String,
+    /// Agent name (the best matching agent if omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    pub priority: TaskPriority,
+    pub status: TaskStatus,
+    /// Earliest start time
+    pub run_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Result once the task has finished
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<SubAgentResult>,
+}
+
+/// SQLite-backed queue of delegated tasks
+///
+/// Clone it to share between the worker and the HTTP API.
+#[derive(Clone)]
+pub struct TaskQueue {
+    inner: Arc<QueueInner>,
+    /// Secret that signs completion callbacks (callbacks are not sent without it)
+    callback_secret: Option<Arc<str>>,
+    /// Callback hosts allowed even though they are not public
+    callback_hosts: Arc<[String]>,
+}
+
+struct QueueInner {
+    conn: Mutex<Connection>,
+    /// Cancellation tokens of running tasks
+    running: Mutex<HashMap<TaskId, CancellationToken>>,
+    /// Wakes the worker when tasks are added
+    wake: Notify,
+}
+
+impl TaskQueue {
+    /// Default database path
+    pub const DEFAULT_PATH: &'static str = "data/agent_tasks.db";
+
+    /// Open the database file (creating its parent directory)
+    ///
+    /// Tasks that were running when the process stopped are queued again.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Create an in-memory queue (for testing)
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        migrate(&conn, "agent_tasks", MIGRATIONS)?;
+        let recovered = conn.execute(
+            "UPDATE agent_tasks SET status = ?1, started_at = NULL WHERE status = ?2",
+            params![status_str(TaskStatus::Queued), status_str(TaskStatus::Running)],
+        )?;
+        if recovered > 0 {
+            info!("Re-queued {} interrupted task(s)", recovered);
+        }
+
+        Ok(Self {
+            inner: Arc::new(QueueInner {
+                conn: Mutex::new(conn),
+                running: Mutex::new(HashMap::new()),
+                wake: Notify::new(),
+            }),
+            callback_secret: None,
+            callback_hosts: Arc::from([]),
+        })
+    }
+
+    /// Allow callbacks to `hosts` (names or IP addresses as written in the
+    /// URL) even when they resolve to loopback or private addresses
+    ///
+    /// Set it before cloning the queue for the worker and the API.
+    pub fn with_callback_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.callback_hosts = hosts.into_iter().map(|h| h.into().to_ascii_lowercase()).collect();
+        self
+    }
+
+    /// Sign completion callbacks with `secret`
+    ///
+    /// Set it before cloning the queue for the worker and the API.
+    pub fn with_callback_secret(mut self, secret: impl Into<String>) -> Self {
+        self.callback_secret = Some(Arc::from(secret.into()));
+        self
+    }
+
+    /// Whether completion callbacks can be sent (a signing secret is set)
+    pub fn signs_callbacks(&self) -> bool {
+        self.callback_secret.is_some()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.inner.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn running(&self) -> std::sync::MutexGuard<'_, HashMap<TaskId, CancellationToken>> {
+        self.inner.running.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Add a task
+    ///
+    /// `agent` pins the task to a named agent; `run_at` delays its start.
+    pub fn enqueue(
+        &self,
+        task: &SubAgentTask,
+        agent: Option<String>,
+        run_at: Option<DateTime<Utc>>,
+    ) -> Result<QueuedTask> {
+        if let Some(url) = task.metadata.get(CALLBACK_URL_KEY) {
+            self.parse_callback_url(url)?;
+        }
+        let created_at = Utc::now();
+        let run_at = run_at.unwrap_or(created_at);
+        self.lock().execute(
+            "INSERT INTO agent_tasks
+                (id, instruction, agent, priority, status, run_at, created_at, task)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                task.id.as_str(),
+                task.instruction,
+                agent,
+                task.priority.weight(),
+                status_str(TaskStatus::Queued),
+                timestamp(run_at),
+                timestamp(created_at),
+                serde_json::to_string(task)?,
+            ],
+        )?;
+        debug!("Queued task {} (run at {})", task.id.as_str(), run_at);
+        self.inner.wake.notify_one();
+
+        Ok(QueuedTask {
+            id: task.id.clone(),
+            instruction: task.instruction.clone(),
+            agent,
+            priority: task.priority,
+            status: TaskStatus::Queued,
+            run_at,
+            created_at,
+            started_at: None,
+            finished_at: None,
+            result: None,
+        })
+    }
+
+    /// Look up a task
+    pub fn get(&self, id: &TaskId) -> Result<Option<QueuedTask>> {
+        let conn = self.lock();
+        let task = conn
+            .query_row(
+                &format!("SELECT {} FROM agent_tasks WHERE id = ?1", COLUMNS),
+                params![id.as_str()],
+                row_to_task,
+            )
+            .optional()?;
+        Ok(task)
+    }
+
+    /// Most recently added tasks (newest first), optionally filtered by status
+    pub fn list(&self, status: Option<TaskStatus>, limit: usize) -> Result<Vec<QueuedTask>> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM agent_tasks
+             WHERE ?1 IS NULL OR status = ?1
+             ORDER BY seq DESC
+             LIMIT ?2",
+            COLUMNS
+        ))?;
+        let tasks = stmt
+            .query_map(params![status.map(status_str), limit as i64], row_to_task)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(tasks)
+    }
+
+    /// Cancel a queued or running task
+    ///
+    /// Returns `false` if the task does not exist or has already finished.
+    pub fn cancel(&self, id: &TaskId) -> Result<bool> {
+        if let Some(token) = self.running().get(id) {
+            // The worker records the cancelled result
+            token.cancel();
+            return Ok(true);
+        }
+        let updated = self.lock().execute(
+            "UPDATE agent_tasks SET status = ?1, finished_at = ?2 WHERE id = ?3 AND status = ?4",
+            params![
+                status_str(TaskStatus::Cancelled),
+                timestamp(Utc::now()),
+                id.as_str(),
+                status_str(TaskStatus::Queued),
+            ],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Mark the next due task as running and return it
+    ///
+    /// Higher priorities run first, then earlier start times.
+    fn claim_next(&self, now: DateTime<Utc>) -> Result<Option<(SubAgentTask, Option<String>)>> {
+        let conn = self.lock();
+        let next = conn
+            .query_row(
+                "SELECT id, task, agent FROM agent_tasks
+                 WHERE status = ?1 AND run_at <= ?2
+                 ORDER BY priority DESC, run_at, seq
+                 LIMIT 1",
+                params![status_str(TaskStatus::Queued), timestamp(now)],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((id, task, agent)) = next else {
+            return Ok(None);
+        };
+
+        conn.execute(
+            "UPDATE agent_tasks SET status = ?1, started_at = ?2 WHERE id = ?3",
+            params![status_str(TaskStatus::Running), timestamp(now), id],
+        )?;
+        Ok(Some((serde_json::from_str(&task)?, agent)))
+    }
+
+    /// Store the result of a finished task
+    fn finish(&self, result: &SubAgentResult) -> Result<()> {
+        self.lock().execute(
+            "UPDATE agent_tasks SET status = ?1, finished_at = ?2, result = ?3 WHERE id = ?4",
+            params![
+                status_str(result.status),
+                timestamp(Utc::now()),
+                serde_json::to_string(result)?,
+                result.task_id.as_str(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Put a task interrupted by shutdown back into the queue
+    fn requeue(&self, id: &TaskId) -> Result<()> {
+        self.lock().execute(
+            "UPDATE agent_tasks SET status = ?1, started_at = NULL WHERE id = ?2",
+            params![status_str(TaskStatus::Queued), id.as_str()],
+        )?;
+        Ok(())
+    }
+
+    fn next_run_at(&self) -> Result<Option<DateTime<Utc>>> {
+        let run_at: Option<String> = self.lock().query_row(
+            "SELECT MIN(run_at) FROM agent_tasks WHERE status = ?1",
+            params![status_str(TaskStatus::Queued)],
+            |row| row.get(0),
+        )?;
+        Ok(run_at.and_then(|s| parse_timestamp(&s).ok()))
+    }
+
+    /// Start the worker running up to `max_concurrency` tasks at once
+    ///
+    /// When `shutdown` fires, running tasks are stopped and queued again.
+    pub fn start(
+        &self,
+        manager: Arc<SubAgentManager>,
+        max_concurrency: usize,
+        shutdown: CancellationToken,
+    ) -> JoinHandle<()> {
+        let queue = self.clone();
+        let semaphore = Arc::new(Semaphore::new(max_concurrency.max(1)));
+        tokio::spawn(async move {
+            info!("Task queue worker started");
+            loop {
+                let permit = tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    permit = semaphore.clone().acquire_owned() => match permit {
+                        Ok(permit) => permit,
+                        Err(_) => break,
+                    },
+                };
+
+                match queue.claim_next(Utc::now()) {
+                    Ok(Some((task, agent))) => {
+                        let queue = queue.clone();
+                        let manager = Arc::clone(&manager);
+                        let shutdown = shutdown.clone();
+                        tokio::spawn(async move {
+                            queue.run(&manager, task, agent, &shutdown).await;
+                            drop(permit);
+                        });
+                        continue;
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to fetch queued task: {}", e),
+                }
+                drop(permit);
+
+                let delay = match queue.next_run_at() {
+                    Ok(Some(run_at)) => (run_at - Utc::now()).to_std().unwrap_or(Duration::ZERO),
+                    Ok(None) => IDLE_POLL,
+                    Err(e) => {
+                        warn!("Failed to fetch queued task: {}", e);
+                        IDLE_POLL
+                    }
+                };
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = queue.inner.wake.notified() => {}
+                }
+            }
+            info!("Task queue worker stopped");
+        })
+    }
+
+    /// Run a claimed task and store its result
+    async fn run(
+        &self,
+        manager: &SubAgentManager,
+        mut task: SubAgentTask,
+        agent: Option<String>,
+        shutdown: &CancellationToken,
+    ) {
+        let id = task.id.clone();
+        let callback_url = task.metadata.get(CALLBACK_URL_KEY).cloned();
+        task.cancel = CancellationToken::new();
+        self.running().insert(id.clone(), task.cancel.clone());
+
+        let selected = match &agent {
+            Some(name) => manager.get_by_name(name),
+            None => manager.find_best_agent(&task),
+        };
+        let result = match selected {
+            Some(selected) => {
+                let timeout = task_timeout(&DelegationConfig::default(), &task);
+                info!("Running queued task {} on {}", id.as_str(), selected.name());
+                run_task(selected, task, shutdown, timeout, &manager.metrics()).await
+            }
+            None => Err(crate::Error::Other(match agent {
+                Some(name) => format!("Agent not found: {}", name),
+                None => "No agent available for task".to_string(),
+            })),
+        };
+        self.running().remove(&id);
+
+        let interrupted = shutdown.is_cancelled();
+        let stored = match result {
+            // Interrupted by shutdown: run it again after the restart
+            Ok(_) | Err(_) if interrupted => self.requeue(&id),
+            Ok(result) => self.finish(&result),
+            Err(e) => self.finish(&SubAgentResult::failure(
+                id.clone(),
+                SubAgentId::new("none"),
+                e.to_string(),
+                TaskStatus::Failed,
+            )),
+        };
+        if let Err(e) = stored {
+            warn!("Failed to store result of task {}: {}", id.as_str(), e);
+            return;
+        }
+
+        if let Some(url) = callback_url.filter(|_| !interrupted) {
+            self.notify_callback(&url, &id).await;
+        }
+    }
+
+    /// Parse a callback URL, refusing other schemes and non-public IP addresses
+    ///
+    /// Host names are only checked by [`check_callback_url`](Self::check_callback_url),
+    /// which resolves them.
+    fn parse_callback_url(&self, url: &str) -> Result<reqwest::Url> {
+        let invalid = |reason: &str| crate::Error::Other(format!("Invalid callback_url {}: {}", url, reason));
+        let parsed = reqwest::Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(invalid("must be an http(s) URL"));
+        }
+        let host = parsed.host_str().ok_or_else(|| invalid("no host"))?.to_ascii_lowercase();
+        if self.callback_hosts.contains(&host) {
+            return Ok(parsed);
+        }
+        let literal = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>();
+        if literal.is_ok_and(|ip| !is_public(ip)) {
+            return Err(invalid("not a public address"));
+        }
+        Ok(parsed)
+    }
+
+    /// Check a callback URL and resolve its host
+    ///
+    /// Refuses hosts that resolve to loopback, link-local, private or other
+    /// non-public addresses unless they are allowed with
+    /// [`with_callback_hosts`](Self::with_callback_hosts). Returns the
+    /// addresses to connect to, so a later lookup cannot point elsewhere.
+    pub async fn check_callback_url(&self, url: &str) -> Result<(reqwest::Url, Vec<SocketAddr>)> {
+        let parsed = self.parse_callback_url(url)?;
+        let host = parsed.host_str().unwrap_or_default().to_string();
+        let port = parsed.port_or_known_default().unwrap_or(80);
+        let lookup_host = host.trim_start_matches('[').trim_end_matches(']');
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((lookup_host, port))
+            .await
+            .map_err(|e| crate::Error::Other(format!("Invalid callback_url {}: {}", url, e)))?
+            .collect();
+        if addrs.is_empty() {
+            return Err(crate::Error::Other(format!("Invalid callback_url {}: host not found", url)));
+        }
+        let allowed = self.callback_hosts.contains(&host.to_ascii_lowercase());
+        if !allowed && addrs.iter().any(|addr| !is_public(addr.ip())) {
+            return Err(crate::Error::Other(format!(
+                "Invalid callback_url {}: {} is not a public address",
+                url, host
+            )));
+        }
+        Ok((parsed, addrs))
+    }
+
+    /// POST the finished task to its callback URL, signed with the callback secret
+    async fn notify_callback(&self, url: &str, id: &TaskId) {
+        let Some(secret) = &self.callback_secret else {
+            warn!("Skipped callback of task {}: no callback secret is set", id.as_str());
+            return;
+        };
+        let task = match self.get(id) {
+            Ok(Some(task)) => task,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to load task {} for its callback: {}", id.as_str(), e);
+                return;
+            }
+        };
+        let body = match serde_json::to_vec(&task) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize task {} for its callback: {}", id.as_str(), e);
+                return;
+            }
+        };
+        let signature = crate::webhook::sign_hmac_sha256_hex(secret, &body);
+        // Checked again here: the host may resolve differently than when queued
+        let (url, addrs) = match self.check_callback_url(url).await {
+            Ok(target) => target,
+            Err(e) => {
+                warn!("Skipped callback of task {}: {}", id.as_str(), e);
+                return;
+            }
+        };
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve_to_addrs(url.host_str().unwrap_or_default(), &addrs)
+            .build();
+        let client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Completion callback of task {} failed: {}", id.as_str(), e);
+                return;
+            }
+        };
+        let response = client
+            .post(url)
+            .timeout(CALLBACK_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(CALLBACK_SIGNATURE_HEADER, format!("sha256={}", signature))
+            .body(body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        match response {
+            Ok(_) => debug!("Sent completion callback of task {}", id.as_str()),
+            Err(e) => warn!("Completion callback of task {} failed: {}", id.as_str(), e),
+        }
+    }
+}
+
+/// Whether `ip` is a public internet address (not loopback, private,
+/// link-local, shared, documentation, multicast or unspecified)
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // 100.64.0.0/10 (carrier-grade NAT)
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // fc00::/7 (unique local) and fe80::/10 (link-local)
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+const COLUMNS: &str =
+    "id, instruction, agent, priority, status, run_at, created_at, started_at, finished_at, result";
+
+fn row_to_task(row: &rusqlite::Row<'_>) -> rusqlite::Result<QueuedTask> {
+    let status: String = row.get(4)?;
+    let result: Option<String> = row.get(9)?;
+    Ok(QueuedTask {
+        id: TaskId::new(row.get::<_, String>(0)?),
+        instruction: row.get(1)?,
+        agent: row.get(2)?,
+        priority: priority_from_weight(row.get(3)?),
+        status: parse_status(&status)?,
+        run_at: parse_timestamp(&row.get::<_, String>(5)?)?,
+        created_at: parse_timestamp(&row.get::<_, String>(6)?)?,
+        started_at: row
+            .get::<_, Option<String>>(7)?
+            .map(|s| parse_timestamp(&s))
+            .transpose()?,
+        finished_at: row
+            .get::<_, Option<String>>(8)?
+            .map(|s| parse_timestamp(&s))
+            .transpose()?,
+        result: result
+            .map(|s| serde_json::from_str(&s).map_err(|_| rusqlite::Error::InvalidQuery))
+            .transpose()?,
+    })
+}
+
+fn priority_from_weight(weight: u8) -> TaskPriority {
+    [
+        TaskPriority::Critical,
+        TaskPriority::High,
+        TaskPriority::Normal,
+        TaskPriority::Low,
+    ]
+    .into_iter()
+    .find(|p| p.weight() <= weight)
+    .unwrap_or(TaskPriority::Low)
+}
+
+fn status_str(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "pending",
+        TaskStatus::Queued => "queued",
+        TaskStatus::Running => "running",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Failed => "failed",
+        TaskStatus::Cancelled => "cancelled",
+        TaskStatus::Timeout => "timeout",
+    }
+}
+
+fn parse_status(value: &str) -> rusqlite::Result<TaskStatus> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| rusqlite::Error::InvalidQuery)
+}
+
+/// Fixed-width RFC 3339 so string comparison keeps the order
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(value: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| rusqlite::Error::InvalidQuery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::types::{AgentCapability, SubAgent};
+    use async_trait::async_trait;
+
+    struct EchoAgent {
+        id: SubAgentId,
+    }
+
+    #[async_trait]
+    impl SubAgent for EchoAgent {
+        fn id(&self) -> &SubAgentId {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echoes the instruction"
+        }
+
+        fn capabilities(&self) -> Vec<AgentCapability> {
+            vec![]
+        }
+
+        async fn execute(&self, task: SubAgentTask) -> Result<SubAgentResult> {
+            Ok(SubAgentResult::success(
+                task.id,
+                self.id.clone(),
+                task.instruction,
+                1,
+                0,
+                0,
+                0,
+            ))
+        }
+    }
+
+    #[test]
+    fn test_claim_order_and_schedule() {
+        let queue = TaskQueue::in_memory().unwrap();
+        let low = SubAgentTask::new("low").with_priority(TaskPriority::Low);
+        let high = SubAgentTask::new("high").with_priority(TaskPriority::High);
+        let later = SubAgentTask::new("later").with_priority(TaskPriority::Critical);
+        queue.enqueue(&low, None, None).unwrap();
+        queue.enqueue(&high, Some("echo".into()), None).unwrap();
+        queue
+            .enqueue(&later, None, Some(Utc::now() + chrono::Duration::hours(1)))
+            .unwrap();
+        // Tasks without a run time are due from when they were queued
+        let now = Utc::now();
+
+        let (first, agent) = queue.claim_next(now).unwrap().unwrap();
+        assert_eq!(first.id, high.id);
+        assert_eq!(agent.as_deref(), Some("echo"));
+        assert_eq!(queue.get(&high.id).unwrap().unwrap().status, TaskStatus::Running);
+
+        let (second, _) = queue.claim_next(now).unwrap().unwrap();
+        assert_eq!(second.id, low.id);
+        assert!(queue.claim_next(now).unwrap().is_none());
+        assert_eq!(queue.next_run_at().unwrap().map(|t| t > now), Some(true));
+
+        let queued = queue.list(Some(TaskStatus::Queued), 10).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].priority, TaskPriority::Critical);
+    }
+
+    #[test]
+    fn test_cancel_queued_task() {
+        let queue = TaskQueue::in_memory().unwrap();
+        let task = SubAgentTask::new("never");
+        queue.enqueue(&task, None, None).unwrap();
+
+        assert!(queue.cancel(&task.id).unwrap());
+        assert!(!queue.cancel(&task.id).unwrap());
+        assert!(!queue.cancel(&TaskId::new("missing")).unwrap());
+        assert_eq!(queue.get(&task.id).unwrap().unwrap().status, TaskStatus::Cancelled);
+        assert!(queue.claim_next(Utc::now()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_running_tasks_requeued_on_reopen() {
+        let path = std::env::temp_dir().join(format!("cc-agent-tasks-{}.db", std::process::id()));
+        let task = SubAgentTask::new("interrupted").with_timeout(30);
+        {
+            let queue = TaskQueue::open(&path).unwrap();
+            queue.enqueue(&task, None, None).unwrap();
+            queue.claim_next(Utc::now()).unwrap().unwrap();
+        }
+        let queue = TaskQueue::open(&path).unwrap();
+        let reopened = queue.get(&task.id).unwrap().unwrap();
+        let (claimed, _) = queue.claim_next(Utc::now()).unwrap().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(reopened.status, TaskStatus::Queued);
+        assert_eq!(claimed.timeout_secs, 30);
+    }
+
+    #[tokio::test]
+    async fn test_worker_runs_tasks() {
+        let mut manager = SubAgentManager::new();
+        manager.register(Arc::new(EchoAgent {
+            id: SubAgentId::new("echo"),
+        }));
+        let queue = TaskQueue::in_memory().unwrap();
+        let shutdown = CancellationToken::new();
+        let worker = queue.start(Arc::new(manager), 2, shutdown.clone());
+
+        let task = SubAgentTask::new("hello");
+        queue.enqueue(&task, Some("echo".into()), None).unwrap();
+        let missing = SubAgentTask::new("nobody");
+        queue.enqueue(&missing, Some("ghost".into()), None).unwrap();
+
+        let mut finished = None;
+        for _ in 0..100 {
+            let done = queue.list(None, 10).unwrap();
+            if done.iter().all(|t| t.finished_at.is_some()) {
+                finished = Some(done);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        shutdown.cancel();
+        worker.await.unwrap();
+
+        let finished = finished.expect("tasks did not finish");
+        let ok = finished.iter().find(|t| t.id == task.id).unwrap();
+        assert_eq!(ok.status, TaskStatus::Completed);
+        assert_eq!(ok.result.as_ref().unwrap().output, "hello");
+        let failed = finished.iter().find(|t| t.id == missing.id).unwrap();
+        assert_eq!(failed.status, TaskStatus::Failed);
+        assert!(failed.result.as_ref().unwrap().error.as_ref().unwrap().contains("ghost"));
+    }
+
+    #[tokio::test]
+    async fn test_worker_posts_callback() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/done", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // Read until the whole body announced by content-length has arrived
+            let complete = |request: &[u8]| {
+                let text = String::from_utf8_lossy(request);
+                text.split_once("\r\n\r\n").is_some_and(|(head, body)| {
+                    head.lines()
+                        .find_map(|line| line.strip_prefix("content-length: "))
+                        .and_then(|len| len.trim().parse::<usize>().ok())
+                        .is_some_and(|len| body.len() >= len)
+                })
+            };
+            while !complete(&request) {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let mut manager = SubAgentManager::new();
+        manager.register(Arc::new(EchoAgent {
+            id: SubAgentId::new("echo"),
+        }));
+        let queue = TaskQueue::in_memory()
+            .unwrap()
+            .with_callback_secret("s3cret")
+            .with_callback_hosts(["127.0.0.1"]);
+        let shutdown = CancellationToken::new();
+        let worker = queue.start(Arc::new(manager), 1, shutdown.clone());
+
+        let task = SubAgentTask::builder("hello")
+            .metadata(CALLBACK_URL_KEY, url)
+            .build();
+        queue.enqueue(&task, Some("echo".into()), None).unwrap();
+
+        let request = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("callback was not sent")
+            .unwrap();
+        shutdown.cancel();
+        worker.await.unwrap();
+
+        assert!(request.starts_with("POST /done"));
+        assert!(request.contains(task.id.as_str()));
+        assert!(request.contains("\"completed\""));
+
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+        let signature = head
+            .lines()
+            .find_map(|line| line.strip_prefix("x-signature-256: sha256="))
+            .expect("callback was not signed");
+        assert!(crate::webhook::verify_hmac_sha256_hex("s3cret", &[body.as_bytes()], signature));
+    }
+
+    #[tokio::test]
+    async fn test_callback_url_must_be_public() {
+        let queue = TaskQueue::in_memory().unwrap();
+        for url in [
+            "ftp://example.com/done",
+            "http://127.0.0.1/done",
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.0.0.5:8080/done",
+            "http://192.168.1.1/done",
+            "http://[::1]/done",
+            "http://[::ffff:127.0.0.1]/done",
+            "http://[fd00::1]/done",
+            "http://100.64.0.1/done",
+            "http://localhost:8080/done",
+        ] {
+            assert!(queue.check_callback_url(url).await.is_err(), "{}", url);
+        }
+        let (_, addrs) = queue.check_callback_url("https://93.184.216.34/done").await.unwrap();
+        assert_eq!(addrs, vec!["93.184.216.34:443".parse().unwrap()]);
+
+        // Every enqueue path refuses such URLs
+        let task = SubAgentTask::builder("hello")
+            .metadata(CALLBACK_URL_KEY, "http://169.254.169.254/")
+            .build();
+        assert!(queue.enqueue(&task, None, None).is_err());
+
+        // Allowed hosts may be internal
+        let queue = queue.with_callback_hosts(["localhost", "10.0.0.5"]);
+        assert!(queue.check_callback_url("http://localhost:8080/done").await.is_ok());
+        assert!(queue.check_callback_url("http://10.0.0.5:8080/done").await.is_ok());
+        assert!(queue.check_callback_url("http://192.168.1.1/done").await.is_err());
+    }
+}
@@ -0,0 +1,293 @@
+//! Sub-Agent Delegation Tool
+//!
+//! Lets the primary agent spawn sub-agent tasks mid-conversation via the
+//! `delegate_task` tool.
+
+use std::sync::{Arc, OnceLock};
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use tokio_util::sync::CancellationToken;
+
+use super::events::SubAgentEventSender;
+use super::manager::SubAgentManager;
+use super::types::{SubAgentResult, SubAgentTask};
+use crate::tool::{Tool, ToolManager, ToolResult};
+use crate::{Error, Result};
+
+/// Name of the delegation tool (never offered to the sub-agents themselves)
+pub const DELEGATE_TASK_TOOL: &str = "delegate_task";
+
+/// Maximum iterations a delegated task may request
+const MAX_DELEGATED_ITERATIONS: u64 = 30;
+
+/// Sub-agents and tools used by `delegate_task`
+///
+/// Sub-agents need the finished tool manager, so this is set after the
+/// tool has been registered.
+pub struct DelegationContext {
+    pub agents: Arc<SubAgentManager>,
+    pub tools: Arc<ToolManager>,
+    /// Delegated tasks are cancelled with this token (e.g. on shutdown)
+    pub cancel: CancellationToken,
+    /// Progress events of delegated tasks
+    pub events: Option<SubAgentEventSender>,
+}
+
+/// `delegate_task` tool
+pub struct DelegateTaskTool {
+    context: Arc<OnceLock<DelegationContext>>,
+}
+
+impl DelegateTaskTool {
+    /// Create the tool; set `context` once the sub-agents are built
+    pub fn new(context: Arc<OnceLock<DelegationContext>>) -> Self {
+        Self { context }
+    }
+}
+
+#[async_trait]
+impl Tool for DelegateTaskTool {
+    fn name(&self) -> &str {
+        DELEGATE_TASK_TOOL
+    }
+
+    // The sub-agent's own tool calls take the slots
+    fn is_limited(&self) -> bool {
+        false
+    }
+
+    fn description(&self) -> &str {
+        "Delegate a self-contained task to a sub-agent and get its result. Use it for focused \
+         work such as research, code review or analysis that benefits from a specialist or a \
+         fresh context. The sub-agent does not see this conversation, so include everything it \
+         needs in the instruction."
+    }
+
+    fn input_schema(&self) -> Value {
+        let agents = self
+            .context
+            .get()
+            .map(|ctx| {
+                let mut names: Vec<&str> = ctx.agents.agent_names();
+                names.sort_unstable();
+                names.join(", ")
+            })
+            .unwrap_or_default();
+        json!({
+            "type": "object",
+            "properties": {
+                "instruction": {
+                    "type": "string",
+                    "description": "Complete, self-contained instruction for the sub-agent"
+                },
+                "agent": {
+                    "type": "string",
+                    "description": format!("Sub-agent name (best match if omitted). Available: {}", agents)
+                },
+                "tools": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Tools the sub-agent may use (all of its tools if omitted)"
+                },
+                "max_iterations": {
+                    "type": "integer",
+                    "description": "Maximum agent loop iterations (default: 10)"
+                }
+            },
+            "required": ["instruction"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let instruction = input["instruction"]
+            .as_str()
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| Error::ToolExecution("Missing 'instruction' parameter".to_string()))?;
+
+        let Some(ctx) = self.context.get() else {
+            return Ok(ToolResult::error("Sub-agents are not available"));
+        };
+
+        // Offer the requested tools, never the delegation tool itself
+        let requested: Option<Vec<&str>> = input["tools"]
+            .as_array()
+            .map(|tools| tools.iter().filter_map(|t| t.as_str()).collect());
+        let tools = ctx
+            .tools
+            .definitions()
+            .into_iter()
+            .filter(|t| t.name != DELEGATE_TASK_TOOL)
+            .filter(|t| requested.as_ref().is_none_or(|names| names.contains(&t.name.as_str())))
+            .collect::<Vec<_>>();
+        if requested.is_some() && tools.is_empty() {
+            return Ok(ToolResult::error("None of the requested tools are available"));
+        }
+
+        let mut task = SubAgentTask::new(instruction)
+            .with_tools(tools)
+            .with_cancellation(ctx.cancel.child_token());
+        task.events = ctx.events.clone();
+        if let Some(iterations) = input["max_iterations"].as_u64() {
+            task = task.with_max_iterations(iterations.clamp(1, MAX_DELEGATED_ITERATIONS) as usize);
+        }
+
+        let result = match input["agent"].as_str().filter(|s| !s.is_empty()) {
+            Some(name) => {
+                let Some(agent) = ctx.agents.get_by_name(name) else {
+                    let mut names = ctx.agents.agent_names();
+                    names.sort_unstable();
+                    return Ok(ToolResult::error(format!(
+                        "Unknown agent '{}'. Available: {}",
+                        name,
+                        names.join(", ")
+                    )));
+                };
+                ctx.agents.execute_tracked(&agent, task).await
+            }
+            None => ctx.agents.execute_with_best_agent(task).await,
+        };
+
+        Ok(match result {
+            Ok(result) => tool_result(&result),
+            Err(e) => ToolResult::error(format!("Delegation failed: {}", e)),
+        })
+    }
+}
+
+/// Summarize a sub-agent result for the primary agent
+fn tool_result(result: &SubAgentResult) -> ToolResult {
+    let summary = json!({
+        "success": result.success,
+        "output": result.output,
+        "error": result.error,
+        "iterations": result.iterations,
+        "input_tokens": result.input_tokens,
+        "output_tokens": result.output_tokens,
+        "tool_calls": result.tool_calls.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+    });
+    let text = serde_json::to_string_pretty(&summary).unwrap_or_else(|_| result.output.clone());
+    if result.success {
+        ToolResult::success(text)
+    } else {
+        ToolResult::error(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::types::{AgentCapability, SubAgent, SubAgentId, TaskStatus};
+
+    /// Reports the tools it was offered
+    struct ToolListAgent {
+        id: SubAgentId,
+    }
+
+    #[async_trait]
+    impl SubAgent for ToolListAgent {
+        fn id(&self) -> &SubAgentId {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "lister"
+        }
+
+        fn description(&self) -> &str {
+            "Lists offered tools"
+        }
+
+        fn capabilities(&self) -> Vec<AgentCapability> {
+            vec![]
+        }
+
+        fn can_handle(&self, _task: &SubAgentTask) -> bool {
+            true
+        }
+
+        async fn execute(&self, task: SubAgentTask) -> Result<SubAgentResult> {
+            let names: Vec<_> = task.available_tools.iter().map(|t| t.name.clone()).collect();
+            if names.is_empty() {
+                return Ok(SubAgentResult::failure(task.id, self.id.clone(), "no tools", TaskStatus::Failed));
+            }
+            Ok(SubAgentResult::success(task.id, self.id.clone(), names.join(","), 1, 1, 1, 1))
+        }
+    }
+
+    /// Tool that does nothing
+    struct Noop(&'static str);
+
+    #[async_trait]
+    impl Tool for Noop {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "noop"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(&self, _input: Value) -> Result<ToolResult> {
+            Ok(ToolResult::success(""))
+        }
+    }
+
+    fn delegate_tool() -> DelegateTaskTool {
+        let context = Arc::new(OnceLock::new());
+        let mut tools = ToolManager::new();
+        tools.register(Arc::new(Noop("read")));
+        tools.register(Arc::new(Noop("bash")));
+        tools.register(Arc::new(DelegateTaskTool::new(Arc::clone(&context))));
+
+        let mut agents = SubAgentManager::new();
+        agents.register(Arc::new(ToolListAgent {
+            id: SubAgentId::new("lister"),
+        }));
+        context
+            .set(DelegationContext {
+                agents: Arc::new(agents),
+                tools: Arc::new(tools),
+                cancel: CancellationToken::new(),
+                events: None,
+            })
+            .ok();
+        DelegateTaskTool::new(context)
+    }
+
+    #[tokio::test]
+    async fn test_delegate_excludes_itself_and_filters_tools() {
+        let tool = delegate_tool();
+
+        let result = tool.execute(json!({"instruction": "list"})).await.unwrap();
+        assert!(!result.is_error);
+        assert!(!result.output.contains(DELEGATE_TASK_TOOL));
+
+        let result = tool
+            .execute(json!({"instruction": "list", "agent": "lister", "tools": ["read"]}))
+            .await
+            .unwrap();
+        let value: Value = serde_json::from_str(&result.output).unwrap();
+        assert_eq!(value["output"], "read");
+    }
+
+    #[tokio::test]
+    async fn test_delegate_errors() {
+        let tool = delegate_tool();
+        assert!(tool.execute(json!({})).await.is_err());
+
+        let unknown = tool
+            .execute(json!({"instruction": "x", "agent": "nobody"}))
+            .await
+            .unwrap();
+        assert!(unknown.is_error);
+        assert!(unknown.output.contains("lister"));
+
+        let unset = DelegateTaskTool::new(Arc::new(OnceLock::new()));
+        assert!(unset.execute(json!({"instruction": "x"})).await.unwrap().is_error);
+    }
+}
@@ -8,7 +8,9 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
 
+use super::events::{SubAgentEvent, SubAgentEventKind, SubAgentEventSender};
 use crate::llm::{Message, ToolDefinition};
 use crate::Result;
 
@@ -109,6 +111,12 @@ pub struct SubAgentTask {
     pub timeout_secs: u64,
     /// Metadata for task tracking
     pub metadata: HashMap<String, String>,
+    /// Cooperative cancellation; agents stop in-flight work when it fires
+    #[serde(skip)]
+    pub cancel: CancellationToken,
+    /// Progress events are sent here while the task runs
+    #[serde(skip)]
+    pub events: Option<SubAgentEventSender>,
 }
 
 impl SubAgentTask {
@@ -124,6 +132,8 @@ impl SubAgentTask {
             max_tokens: 4096,
             timeout_secs: 120,
             metadata: HashMap::new(),
+            cancel: CancellationToken::new(),
+            events: None,
         }
     }
 
@@ -161,6 +171,26 @@ impl SubAgentTask {
         self.max_iterations = iterations;
         self
     }
+
+    /// Set the cancellation token
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Send progress events to `events`
+    pub fn with_events(mut self, events: SubAgentEventSender) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Send a progress event (no-op without an event stream)
+    pub fn emit(&self, agent_id: &SubAgentId, agent: &str, kind: SubAgentEventKind) {
+        if let Some(events) = &self.events {
+            // The receiver may have gone away; progress is best-effort
+            let _ = events.send(SubAgentEvent::new(self.id.clone(), agent_id.clone(), agent, kind));
+        }
+    }
 }
 
 /// Builder for SubAgentTask
@@ -173,6 +203,8 @@ pub struct SubAgentTaskBuilder {
     max_tokens: u64,
     timeout_secs: u64,
     metadata: HashMap<String, String>,
+    cancel: CancellationToken,
+    events: Option<SubAgentEventSender>,
 }
 
 impl SubAgentTaskBuilder {
@@ -186,6 +218,8 @@ impl SubAgentTaskBuilder {
             max_tokens: 4096,
             timeout_secs: 120,
             metadata: HashMap::new(),
+            cancel: CancellationToken::new(),
+            events: None,
         }
     }
 
@@ -234,6 +268,16 @@ impl SubAgentTaskBuilder {
         self
     }
 
+    pub fn cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn events(mut self, events: SubAgentEventSender) -> Self {
+        self.events = Some(events);
+        self
+    }
+
     pub fn build(self) -> SubAgentTask {
         SubAgentTask {
             id: TaskId::default(),
@@ -245,6 +289,8 @@ impl SubAgentTaskBuilder {
             max_tokens: self.max_tokens,
             timeout_secs: self.timeout_secs,
             metadata: self.metadata,
+            cancel: self.cancel,
+            events: self.events,
         }
     }
 }
@@ -152,6 +152,55 @@ impl AuditLogger {
         Ok(())
     }
 
+    /// Remove the entries matching `predicate` from the log file and its
+    /// rotated copies, e.g. to erase a user's data. Lines that are not
+    /// entries are kept. Returns the number of entries removed.
+    pub fn remove_entries(&self, predicate: impl Fn(&AuditEntry) -> bool) -> AuditResult<usize> {
+        let Some(ref path) = self.config.log_file else {
+            return Ok(0);
+        };
+
+        // Hold the handle so no entry is written while the files are rewritten
+        let mut file_guard = self.log_file.lock().unwrap();
+        let mut size_guard = self.current_file_size.lock().unwrap();
+
+        let mut removed = 0;
+        let rotated = (1..=self.config.max_rotated_files).map(|i| format!("{}.{}", path, i));
+        for file in std::iter::once(path.clone()).chain(rotated) {
+            let contents = match fs::read_to_string(&file) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let mut kept = String::with_capacity(contents.len());
+            let mut removed_here = 0;
+            for line in contents.lines() {
+                match serde_json::from_str::<AuditEntry>(line) {
+                    Ok(entry) if predicate(&entry) => removed_here += 1,
+                    _ => {
+                        kept.push_str(line);
+                        kept.push('\n');
+                    }
+                }
+            }
+            if removed_here == 0 {
+                continue;
+            }
+            fs::write(&file, &kept)?;
+            removed += removed_here;
+
+            if file == *path {
+                *file_guard = Some(OpenOptions::new().create(true).append(true).open(path)?);
+                *size_guard = kept.len();
+            }
+        }
+
+        if removed > 0 {
+            info!("Removed {} entries from the audit log", removed);
+        }
+        Ok(removed)
+    }
+
     /// Create a builder for audit entries
     pub fn builder(&self) -> AuditEntryBuilder {
         AuditEntryBuilder::new()
@@ -311,4 +360,31 @@ mod tests {
         let contents = fs::read_to_string(&log_path).unwrap();
         assert!(contents.contains("Test message"));
     }
+
+    #[test]
+    fn test_remove_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("test.log");
+        let config = AuditConfig {
+            log_file: Some(log_path.to_str().unwrap().to_string()),
+            log_to_console: false,
+            ..Default::default()
+        };
+        let logger = AuditLogger::new(config).unwrap();
+        for message in ["keep", "drop", "drop"] {
+            logger
+                .log(&AuditEntry::new(AuditEventType::MessageSent, AuditLevel::Info, message))
+                .unwrap();
+        }
+        fs::write(format!("{}.1", log_path.display()), "not json\n").unwrap();
+
+        assert_eq!(logger.remove_entries(|entry| entry.message == "drop").unwrap(), 2);
+        logger
+            .log(&AuditEntry::new(AuditEventType::MessageSent, AuditLevel::Info, "after"))
+            .unwrap();
+        let contents = fs::read_to_string(&log_path).unwrap();
+        assert!(contents.contains("keep") && contents.contains("after"));
+        assert!(!contents.contains("drop"));
+        assert_eq!(fs::read_to_string(format!("{}.1", log_path.display())).unwrap(), "not json\n");
+    }
 }
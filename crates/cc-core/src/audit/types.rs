@@ -39,6 +39,12 @@ pub enum AuditEventType {
     MessageReceived,
     MessageFiltered,
     ToolExecuted,
+    ToolApprovalRequested,
+    ToolApproved,
+    ToolDenied,
+
+    // LLM events
+    LlmRequestRetried,
 
     // Configuration events
     ConfigChanged,
@@ -56,6 +62,8 @@ pub enum AuditEventType {
     UserCreated,
     UserDeleted,
     PermissionChanged,
+    /// All data of an identity was erased (never removed by a purge)
+    DataPurged,
 }
 
 /// Source of an audit event
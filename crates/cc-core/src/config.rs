@@ -8,8 +8,14 @@
 //! 設定ファイル内では `${VAR_NAME}` 形式で環境変数を展開できます。
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
+use crate::llm::RetryPolicy;
+use crate::notify::NotifyConfig;
+use crate::tool::{ToolLimits, ToolPolicy};
+use crate::persona::PersonasConfig;
+
 /// LLM Provider type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -20,6 +26,10 @@ pub enum LlmProvider {
     Claude,
     /// OpenAI-compatible API (GLM, etc.)
     OpenAi,
+    /// AWS Bedrock (Anthropic models, SigV4 signed)
+    Bedrock,
+    /// Google Vertex AI (Anthropic models)
+    Vertex,
 }
 
 
@@ -39,6 +49,18 @@ pub struct LlmConfig {
 
     /// Base URL (optional, for custom endpoints)
     pub base_url: Option<String>,
+
+    /// Cloud region (Bedrock: AWS region, Vertex: GCP location)
+    #[serde(default)]
+    pub region: Option<String>,
+
+    /// GCP project ID (Vertex only)
+    #[serde(default)]
+    pub project_id: Option<String>,
+
+    /// Retry policy for transient API failures
+    #[serde(default)]
+    pub retry: RetryPolicy,
 }
 
 impl Default for LlmConfig {
@@ -48,6 +70,9 @@ impl Default for LlmConfig {
             model: default_model(),
             provider: LlmProvider::Claude,
             base_url: None,
+            region: None,
+            project_id: None,
+            retry: RetryPolicy::default(),
         }
     }
 }
@@ -95,6 +120,30 @@ pub struct Config {
     #[serde(default)]
     pub scheduler: SchedulerConfig,
 
+    /// Persona definitions and bindings
+    #[serde(default)]
+    pub personas: PersonasConfig,
+
+    /// Notification targets and alert routes
+    #[serde(default)]
+    pub notify: NotifyConfig,
+
+    /// Which tools need approval or are refused
+    #[serde(default)]
+    pub tool_policy: ToolPolicy,
+
+    /// Timeouts and concurrency of tool calls
+    #[serde(default)]
+    pub tool_limits: ToolLimits,
+
+    /// Channels started in server mode
+    #[serde(default)]
+    pub channels: ChannelsConfig,
+
+    /// Knowledge base of user-provided documents
+    #[serde(default)]
+    pub knowledge: KnowledgeConfig,
+
     /// API key for HTTP API (shorthand for api.key)
     #[serde(skip_serializing)]
     pub api_key: Option<String>,
@@ -113,6 +162,26 @@ pub struct ApiConfig {
     /// If empty, defaults to localhost only
     #[serde(default)]
     pub allowed_origins: Option<Vec<String>>,
+
+    /// Request rate limits
+    #[serde(default)]
+    pub rate_limit: RateLimitSettings,
+
+    /// Maximum request body size in bytes (file uploads have their own limit)
+    #[serde(default = "default_max_body_size")]
+    pub max_body_size: usize,
+
+    /// Seconds before a request is answered with 504 Gateway Timeout
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+
+    /// Timeouts for path prefixes (the first matching route applies)
+    #[serde(default)]
+    pub timeouts: Vec<RouteTimeout>,
+
+    /// Seconds to wait for in-flight requests on shutdown
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
 }
 
 impl Default for ApiConfig {
@@ -121,6 +190,264 @@ impl Default for ApiConfig {
             key: None,
             port: default_api_port(),
             allowed_origins: None,
+            rate_limit: RateLimitSettings::default(),
+            max_body_size: default_max_body_size(),
+            request_timeout_secs: default_request_timeout_secs(),
+            timeouts: Vec::new(),
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Timeout of a request to `path`
+    pub fn timeout_for(&self, path: &str) -> std::time::Duration {
+        let secs = self
+            .timeouts
+            .iter()
+            .find(|route| {
+                let prefix = route.path.trim_end_matches('/');
+                path == prefix || path.starts_with(&format!("{}/", prefix))
+            })
+            .map_or(self.request_timeout_secs, |route| route.secs);
+        std::time::Duration::from_secs(secs)
+    }
+}
+
+/// Timeout of a path prefix (`[[api.timeouts]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteTimeout {
+    /// Path prefix (e.g. `/api/jobs`)
+    pub path: String,
+
+    /// Seconds before the request is answered with 504
+    pub secs: u64,
+}
+
+/// HTTP API rate limits (`[api.rate_limit]` in cc-gateway.toml)
+///
+/// Limits are requests per minute, enforced as token buckets that allow a
+/// burst of up to the limit. An API key's own limit replaces `per_key`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitSettings {
+    /// Requests per minute per client IP (None = no limit)
+    #[serde(default)]
+    pub per_ip: Option<u32>,
+
+    /// Requests per minute per API key / OIDC user (None = no limit)
+    #[serde(default)]
+    pub per_key: Option<u32>,
+
+    /// Redis URL to share the limits between instances (None = in-memory)
+    #[serde(default)]
+    pub redis_url: Option<String>,
+
+    /// Additional limits for path prefixes (the first matching route applies)
+    #[serde(default)]
+    pub routes: Vec<RouteRateLimit>,
+}
+
+/// Rate limits of a path prefix (`[[api.rate_limit.routes]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteRateLimit {
+    /// Path prefix (e.g. `/api/chat`)
+    pub path: String,
+
+    /// Requests per minute per client IP on this route
+    #[serde(default)]
+    pub per_ip: Option<u32>,
+
+    /// Requests per minute per API key on this route
+    #[serde(default)]
+    pub per_key: Option<u32>,
+}
+
+impl RateLimitSettings {
+    /// The route limits that apply to `path`
+    pub fn route(&self, path: &str) -> Option<&RouteRateLimit> {
+        self.routes.iter().find(|route| {
+            let prefix = route.path.trim_end_matches('/');
+            path == prefix || path.starts_with(&format!("{}/", prefix))
+        })
+    }
+}
+
+/// Channels started in server mode (`[channels]` in cc-gateway.toml)
+///
+/// Each enabled channel runs as its own service and is started again when it
+/// stops. Credentials come from the channel's environment variables.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelsConfig {
+    /// Discord bot (DISCORD_BOT_TOKEN)
+    pub discord: bool,
+    /// Email (IMAP IDLE + SMTP replies)
+    pub email: bool,
+    /// Telegram bot (TELEGRAM_BOT_TOKEN)
+    pub telegram: bool,
+    /// Slack app in Socket Mode (SLACK_BOT_TOKEN, SLACK_APP_TOKEN)
+    pub slack: bool,
+    /// WhatsApp webhook (Twilio or the Cloud API)
+    pub whatsapp: bool,
+    /// LINE webhook (LINE_CHANNEL_SECRET, LINE_CHANNEL_ACCESS_TOKEN)
+    pub line: bool,
+    /// Signal through signal-cli's REST API (SIGNAL_PHONE_NUMBER)
+    pub signal: bool,
+    /// iMessage (macOS, or a BlueBubbles server)
+    pub imessage: bool,
+    /// Facebook Messenger webhook
+    pub facebook: bool,
+    /// Instagram DM webhook
+    pub instagram: bool,
+    /// WebSocket server
+    pub ws: bool,
+    /// Web dashboard
+    pub dashboard: bool,
+    /// Ports of the webhook and web servers
+    pub ports: ChannelPorts,
+    /// Seconds to wait before starting a stopped service again
+    ///
+    /// Doubled after every consecutive failure, up to `max_restart_delay_secs`.
+    /// Also applies to the HTTP API server and the scheduler.
+    pub restart_delay_secs: u64,
+    /// Upper bound of the restart delay
+    pub max_restart_delay_secs: u64,
+}
+
+impl Default for ChannelsConfig {
+    fn default() -> Self {
+        Self {
+            // Discord stays on as before; it still needs a token
+            discord: true,
+            email: false,
+            telegram: false,
+            slack: false,
+            whatsapp: false,
+            line: false,
+            signal: false,
+            imessage: false,
+            facebook: false,
+            instagram: false,
+            ws: false,
+            dashboard: false,
+            ports: ChannelPorts::default(),
+            restart_delay_secs: 5,
+            max_restart_delay_secs: 300,
+        }
+    }
+}
+
+impl ChannelsConfig {
+    /// Names of all channels
+    pub const NAMES: &'static [&'static str] = &[
+        "discord", "email", "telegram", "slack", "whatsapp", "line", "signal", "imessage",
+        "facebook", "instagram", "ws", "dashboard",
+    ];
+
+    /// Whether the channel `name` is enabled
+    pub fn is_enabled(&self, name: &str) -> bool {
+        match name {
+            "discord" => self.discord,
+            "email" => self.email,
+            "telegram" => self.telegram,
+            "slack" => self.slack,
+            "whatsapp" => self.whatsapp,
+            "line" => self.line,
+            "signal" => self.signal,
+            "imessage" => self.imessage,
+            "facebook" => self.facebook,
+            "instagram" => self.instagram,
+            "ws" => self.ws,
+            "dashboard" => self.dashboard,
+            _ => false,
+        }
+    }
+
+    /// Enable or disable the channel `name` (false if there is no such channel)
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.flag_mut(name) {
+            Some(flag) => {
+                *flag = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Names of the enabled channels
+    pub fn enabled(&self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .copied()
+            .filter(|name| self.is_enabled(name))
+            .collect()
+    }
+
+    /// Enable exactly the channels in a comma-separated list (`CHANNELS`)
+    ///
+    /// Unknown names are returned so they can be reported.
+    pub fn enable_only(&mut self, list: &str) -> Vec<String> {
+        for name in Self::NAMES {
+            self.set_enabled(name, false);
+        }
+        list.split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty() && !self.set_enabled(name, true))
+            .collect()
+    }
+
+    /// Apply `CHANNELS` (the enabled channels) and `EMAIL_CHANNEL_ENABLED`
+    fn apply_env_overrides(&mut self) {
+        if let Ok(list) = std::env::var("CHANNELS") {
+            for name in self.enable_only(&list) {
+                tracing::warn!("Unknown channel in CHANNELS: {}", name);
+            }
+        }
+        if let Ok(enabled) = std::env::var("EMAIL_CHANNEL_ENABLED") {
+            self.email = enabled == "true" || enabled == "1";
+        }
+    }
+
+    fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "discord" => Some(&mut self.discord),
+            "email" => Some(&mut self.email),
+            "telegram" => Some(&mut self.telegram),
+            "slack" => Some(&mut self.slack),
+            "whatsapp" => Some(&mut self.whatsapp),
+            "line" => Some(&mut self.line),
+            "signal" => Some(&mut self.signal),
+            "imessage" => Some(&mut self.imessage),
+            "facebook" => Some(&mut self.facebook),
+            "instagram" => Some(&mut self.instagram),
+            "ws" => Some(&mut self.ws),
+            "dashboard" => Some(&mut self.dashboard),
+            _ => None,
+        }
+    }
+}
+
+/// Ports of the channels that listen for webhooks or clients (`[channels.ports]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelPorts {
+    pub whatsapp: u16,
+    pub line: u16,
+    pub facebook: u16,
+    pub instagram: u16,
+    pub ws: u16,
+    pub dashboard: u16,
+}
+
+impl Default for ChannelPorts {
+    fn default() -> Self {
+        Self {
+            whatsapp: 3010,
+            line: 3011,
+            facebook: 3012,
+            instagram: 3013,
+            ws: 3001,
+            dashboard: 3002,
         }
     }
 }
@@ -130,12 +457,151 @@ pub struct MemoryConfig {
     /// Path to SQLite database file
     #[serde(default = "default_db_path")]
     pub db_path: String,
+
+    /// Postgres connection URL (postgres://...); when set, sessions and
+    /// memories are stored in Postgres instead of `db_path`
+    #[serde(default)]
+    pub database_url: Option<String>,
+
+    /// Maximum number of Postgres connections per store
+    #[serde(default = "default_pool_size")]
+    pub pool_size: usize,
+
+    /// Automatic fact extraction (`[memory.memorizer]`)
+    #[serde(default)]
+    pub memorizer: MemorizerConfig,
+
+    /// Channel users that are the same person, by identity name
+    /// (`alice = ["discord:123", "telegram:456"]`)
+    #[serde(default)]
+    pub identities: HashMap<String, Vec<String>>,
+}
+
+impl MemoryConfig {
+    /// Identity of a channel user: the linked identity name, or
+    /// `<channel>:<user_id>` when the user is not linked
+    pub fn identity(&self, channel: &str, user_id: &str) -> String {
+        let key = format!("{}:{}", channel, user_id);
+        self.identities
+            .iter()
+            .find(|(_, members)| members.contains(&key))
+            .map(|(name, _)| name.clone())
+            .unwrap_or(key)
+    }
+
+    /// Memory namespace shared by everyone in a channel (`<channel>:channel:<channel_id>`)
+    pub fn channel_namespace(&self, channel: &str, channel_id: &str) -> String {
+        format!("{}:channel:{}", channel, channel_id)
+    }
+
+    /// Channel users that make up `identity`: its linked users, or the
+    /// identity itself when it is not a linked name
+    pub fn identity_members(&self, identity: &str) -> Vec<String> {
+        match self.identities.get(identity) {
+            Some(members) => members.clone(),
+            None => vec![identity.to_string()],
+        }
+    }
+}
+
+/// Settings of the memorizer, which remembers facts about users
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemorizerConfig {
+    /// Extract facts after each turn and add them to the system prompt
+    pub enabled: bool,
+    /// Model used for extraction (default: the configured model)
+    pub model: Option<String>,
+    /// Most facts added to the system prompt of a turn
+    pub max_facts: usize,
+    /// Days until an extracted fact expires (None = never)
+    pub ttl_days: Option<u64>,
+}
+
+impl Default for MemorizerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: None,
+            max_facts: 10,
+            ttl_days: None,
+        }
+    }
+}
+
+/// Knowledge base built from local documents and web pages (`[knowledge]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnowledgeConfig {
+    /// Index the sources and offer `knowledge_search` in server mode
+    pub enabled: bool,
+    /// SQLite database of the index
+    pub db_path: String,
+    /// Folders, files and `http(s)://` URLs to index
+    pub sources: Vec<String>,
+    /// Characters per chunk
+    pub chunk_size: usize,
+    /// Characters shared by consecutive chunks
+    pub chunk_overlap: usize,
+    /// Chunks added to the system prompt of a turn (0 = only `knowledge_search`)
+    pub top_k: usize,
+    /// Lowest similarity (0.0 - 1.0) of an added chunk
+    pub min_score: f32,
+    /// Seconds between re-indexing the sources (0 = only at startup)
+    pub reindex_interval_secs: u64,
+    /// How chunks are embedded
+    pub embedding: EmbeddingConfig,
+}
+
+impl Default for KnowledgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            db_path: "data/knowledge.db".to_string(),
+            sources: Vec::new(),
+            chunk_size: 1000,
+            chunk_overlap: 200,
+            top_k: 4,
+            min_score: 0.2,
+            reindex_interval_secs: 3600,
+            embedding: EmbeddingConfig::default(),
+        }
+    }
+}
+
+/// Embedding model of the knowledge base (`[knowledge.embedding]`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingConfig {
+    /// Who computes the embeddings
+    pub provider: EmbeddingProvider,
+    /// Model name (default: `text-embedding-3-small` for OpenAI)
+    pub model: Option<String>,
+    /// API key (default: `OPENAI_API_KEY`)
+    pub api_key: Option<String>,
+    /// OpenAI-compatible endpoint (default: `https://api.openai.com/v1`)
+    pub base_url: Option<String>,
+}
+
+/// Embedding providers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// Hashed word vectors computed locally (no API, lexical matching only)
+    #[default]
+    Local,
+    /// OpenAI `/embeddings` API or a compatible server (Ollama, LM Studio, ...)
+    OpenAi,
 }
 
 impl Default for MemoryConfig {
     fn default() -> Self {
         Self {
             db_path: default_db_path(),
+            database_url: None,
+            pool_size: default_pool_size(),
+            memorizer: MemorizerConfig::default(),
+            identities: HashMap::new(),
         }
     }
 }
@@ -167,6 +633,22 @@ fn default_api_port() -> u16 {
     3000
 }
 
+fn default_max_body_size() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_request_timeout_secs() -> u64 {
+    300
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+fn default_pool_size() -> usize {
+    16
+}
+
 fn default_db_path() -> String {
     "data/cc-gateway.db".to_string()
 }
@@ -176,7 +658,7 @@ impl Config {
     ///
     /// `${VAR_NAME}` 形式の文字列を環境変数の値に置換します。
     /// 環境変数が存在しない場合は文字列をそのまま返します。
-    fn expand_env_vars(value: &str) -> String {
+    pub fn expand_env_vars(value: &str) -> String {
         let mut result = String::new();
         let mut chars = value.chars().peekable();
 
@@ -285,6 +767,8 @@ impl Config {
         // provider 文字列から LlmProvider への変換
         let provider = match llm.provider.unwrap_or_default().to_lowercase().as_str() {
             "openai" | "glm" | "zai" | "minimax" => LlmProvider::OpenAi,
+            "bedrock" | "aws" => LlmProvider::Bedrock,
+            "vertex" | "vertexai" => LlmProvider::Vertex,
             _ => LlmProvider::Claude,
         };
 
@@ -293,6 +777,9 @@ impl Config {
             model: llm.model.unwrap_or_else(default_model),
             provider,
             base_url: llm.base_url,
+            region: llm.region,
+            project_id: llm.project_id,
+            retry: llm.retry.unwrap_or_default(),
         };
 
         // Discord 設定
@@ -309,12 +796,25 @@ impl Config {
             key: api.key.clone(),
             port: api.port.unwrap_or_else(default_api_port),
             allowed_origins: api.allowed_origins,
+            rate_limit: api.rate_limit.unwrap_or_default(),
+            max_body_size: api.max_body_size.unwrap_or_else(default_max_body_size),
+            request_timeout_secs: api
+                .request_timeout_secs
+                .unwrap_or_else(default_request_timeout_secs),
+            timeouts: api.timeouts.unwrap_or_default(),
+            shutdown_timeout_secs: api
+                .shutdown_timeout_secs
+                .unwrap_or_else(default_shutdown_timeout_secs),
         };
 
         // Memory 設定
         let memory = toml.memory.unwrap_or_default();
         let memory_config = MemoryConfig {
             db_path: memory.db_path.unwrap_or_else(default_db_path),
+            database_url: memory.database_url,
+            pool_size: memory.pool_size.unwrap_or_else(default_pool_size),
+            memorizer: memory.memorizer.unwrap_or_default(),
+            identities: memory.identities.unwrap_or_default(),
         };
 
         // MCP 設定
@@ -342,6 +842,12 @@ impl Config {
             memory: memory_config,
             mcp: mcp_config,
             scheduler: scheduler_config,
+            personas: toml.personas.unwrap_or_default(),
+            notify: toml.notify.unwrap_or_default(),
+            tool_policy: toml.tool_policy.unwrap_or_default(),
+            tool_limits: toml.tool_limits.unwrap_or_default(),
+            channels: toml.channels.unwrap_or_default(),
+            knowledge: toml.knowledge.unwrap_or_default(),
         })
     }
 
@@ -375,6 +881,8 @@ impl Config {
             if !provider.is_empty() {
                 self.llm.provider = match provider.to_lowercase().as_str() {
                     "openai" | "glm" | "zai" | "minimax" => LlmProvider::OpenAi,
+                    "bedrock" | "aws" => LlmProvider::Bedrock,
+                    "vertex" | "vertexai" => LlmProvider::Vertex,
                     _ => LlmProvider::Claude,
                 };
             }
@@ -387,6 +895,22 @@ impl Config {
             }
         }
 
+        if let Ok(region) = std::env::var("LLM_REGION") {
+            if !region.is_empty() {
+                self.llm.region = Some(region);
+            }
+        }
+        if let Ok(project_id) = std::env::var("LLM_PROJECT_ID") {
+            if !project_id.is_empty() {
+                self.llm.project_id = Some(project_id);
+            }
+        }
+        if let Ok(retries) = std::env::var("LLM_MAX_RETRIES") {
+            if let Ok(n) = retries.parse() {
+                self.llm.retry.max_retries = n;
+            }
+        }
+
         // Discord 設定の上書き
         if let Ok(token) = std::env::var("DISCORD_BOT_TOKEN") {
             self.discord_token = Some(token);
@@ -415,11 +939,35 @@ impl Config {
                     .collect()
             );
         }
+        if let Some(limit) = std::env::var("API_RATE_LIMIT_PER_IP").ok().and_then(|v| v.parse().ok()) {
+            self.api.rate_limit.per_ip = Some(limit);
+        }
+        if let Ok(url) = std::env::var("API_RATE_LIMIT_REDIS_URL") {
+            self.api.rate_limit.redis_url = Some(url);
+        }
+        if let Some(size) = std::env::var("API_MAX_BODY_SIZE").ok().and_then(|v| v.parse().ok()) {
+            self.api.max_body_size = size;
+        }
+        if let Some(secs) = std::env::var("API_REQUEST_TIMEOUT_SECS").ok().and_then(|v| v.parse().ok()) {
+            self.api.request_timeout_secs = secs;
+        }
 
         // Memory 設定の上書き
         if let Ok(path) = std::env::var("DB_PATH") {
             self.memory.db_path = path;
         }
+        if let Ok(url) = std::env::var("DATABASE_URL") {
+            self.memory.database_url = Some(url);
+        }
+        if let Some(size) = std::env::var("DB_POOL_SIZE").ok().and_then(|v| v.parse().ok()) {
+            self.memory.pool_size = size;
+        }
+        if let Ok(enabled) = std::env::var("MEMORIZER_ENABLED") {
+            self.memory.memorizer.enabled = enabled.to_lowercase() == "true";
+        }
+        if let Ok(enabled) = std::env::var("KNOWLEDGE_ENABLED") {
+            self.knowledge.enabled = enabled.to_lowercase() == "true";
+        }
 
         // MCP 設定の上書き
         if let Ok(path) = std::env::var("MCP_CONFIG_PATH") {
@@ -436,26 +984,42 @@ impl Config {
         if let Ok(path) = std::env::var("SCHEDULE_CONFIG_PATH") {
             self.scheduler.config_path = Some(path);
         }
+
+        // チャネル設定の上書き
+        self.channels.apply_env_overrides();
     }
 
     /// Load configuration from environment variables
     pub fn from_env() -> crate::Result<Self> {
+        // Determine provider
+        let provider = match std::env::var("LLM_PROVIDER").unwrap_or_default().to_lowercase().as_str() {
+            "openai" | "glm" | "zai" | "minimax" => LlmProvider::OpenAi,
+            "bedrock" | "aws" => LlmProvider::Bedrock,
+            "vertex" | "vertexai" => LlmProvider::Vertex,
+            _ => LlmProvider::Claude,
+        };
+
         // Get API key from either LLM_API_KEY or CLAUDE_API_KEY
-        let api_key = std::env::var("LLM_API_KEY")
+        // (Bedrock / Vertex authenticate with cloud credentials instead)
+        let api_key = match std::env::var("LLM_API_KEY")
             .or_else(|_| std::env::var("CLAUDE_API_KEY"))
-            .map_err(|_| Error::Config("LLM_API_KEY or CLAUDE_API_KEY not set".to_string()))?;
+        {
+            Ok(key) => key,
+            Err(_) if matches!(provider, LlmProvider::Bedrock | LlmProvider::Vertex) => {
+                String::new()
+            }
+            Err(_) => {
+                return Err(Error::Config(
+                    "LLM_API_KEY or CLAUDE_API_KEY not set".to_string(),
+                ))
+            }
+        };
 
         // Get model from either LLM_MODEL or CLAUDE_MODEL
         let model = std::env::var("LLM_MODEL")
             .or_else(|_| std::env::var("CLAUDE_MODEL"))
             .unwrap_or_else(|_| default_model());
 
-        // Determine provider
-        let provider = match std::env::var("LLM_PROVIDER").unwrap_or_default().to_lowercase().as_str() {
-            "openai" | "glm" | "zai" | "minimax" => LlmProvider::OpenAi,
-            _ => LlmProvider::Claude,
-        };
-
         // Get base URL (for custom endpoints like GLM Coding Plan)
         let base_url = std::env::var("LLM_BASE_URL").ok();
 
@@ -464,6 +1028,15 @@ impl Config {
             model: model.clone(),
             provider,
             base_url,
+            region: std::env::var("LLM_REGION").ok(),
+            project_id: std::env::var("LLM_PROJECT_ID").ok(),
+            retry: RetryPolicy {
+                max_retries: std::env::var("LLM_MAX_RETRIES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(RetryPolicy::default().max_retries),
+                ..RetryPolicy::default()
+            },
         };
 
         Ok(Config {
@@ -483,11 +1056,37 @@ impl Config {
                 allowed_origins: std::env::var("API_ALLOWED_ORIGINS")
                     .ok()
                     .map(|s| s.split(',').map(|s| s.trim().to_string()).collect()),
+                rate_limit: RateLimitSettings {
+                    per_ip: std::env::var("API_RATE_LIMIT_PER_IP").ok().and_then(|v| v.parse().ok()),
+                    redis_url: std::env::var("API_RATE_LIMIT_REDIS_URL").ok(),
+                    ..Default::default()
+                },
+                max_body_size: std::env::var("API_MAX_BODY_SIZE")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(default_max_body_size),
+                request_timeout_secs: std::env::var("API_REQUEST_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(default_request_timeout_secs),
+                ..Default::default()
             },
             api_key: std::env::var("API_KEY").ok(),
             memory: MemoryConfig {
                 db_path: std::env::var("DB_PATH")
                     .unwrap_or_else(|_| default_db_path()),
+                database_url: std::env::var("DATABASE_URL").ok(),
+                pool_size: std::env::var("DB_POOL_SIZE")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(default_pool_size),
+                memorizer: MemorizerConfig {
+                    enabled: std::env::var("MEMORIZER_ENABLED")
+                        .map(|v| v.to_lowercase() == "true")
+                        .unwrap_or(false),
+                    ..Default::default()
+                },
+                identities: HashMap::new(),
             },
             mcp: McpConfig {
                 config_path: std::env::var("MCP_CONFIG_PATH").ok(),
@@ -501,6 +1100,21 @@ impl Config {
                     .unwrap_or(true),
                 config_path: std::env::var("SCHEDULE_CONFIG_PATH").ok(),
             },
+            personas: PersonasConfig::default(),
+            notify: Default::default(),
+            tool_policy: ToolPolicy::default(),
+            tool_limits: ToolLimits::default(),
+            channels: {
+                let mut channels = ChannelsConfig::default();
+                channels.apply_env_overrides();
+                channels
+            },
+            knowledge: KnowledgeConfig {
+                enabled: std::env::var("KNOWLEDGE_ENABLED")
+                    .map(|v| v.to_lowercase() == "true")
+                    .unwrap_or(false),
+                ..Default::default()
+            },
         })
     }
 
@@ -531,6 +1145,18 @@ struct TomlConfig {
     mcp: Option<TomlMcpConfig>,
     /// スケジューラー設定
     scheduler: Option<TomlSchedulerConfig>,
+    /// ペルソナ設定
+    personas: Option<PersonasConfig>,
+    /// 通知設定
+    notify: Option<NotifyConfig>,
+    /// ツール実行ポリシー
+    tool_policy: Option<ToolPolicy>,
+    /// ツールのタイムアウトと同時実行数
+    tool_limits: Option<ToolLimits>,
+    /// チャネル設定
+    channels: Option<ChannelsConfig>,
+    /// ナレッジベース設定
+    knowledge: Option<KnowledgeConfig>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -547,6 +1173,15 @@ struct TomlLlmConfig {
     /// ベース URL (オプション)
     #[serde(default)]
     base_url: Option<String>,
+    /// リージョン (Bedrock / Vertex)
+    #[serde(default)]
+    region: Option<String>,
+    /// GCP プロジェクト ID (Vertex)
+    #[serde(default)]
+    project_id: Option<String>,
+    /// リトライ設定 ([llm.retry])
+    #[serde(default)]
+    retry: Option<RetryPolicy>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -568,6 +1203,21 @@ struct TomlApiConfig {
     /// 許可する CORS オリジン
     #[serde(default)]
     allowed_origins: Option<Vec<String>>,
+    /// リクエスト数の上限
+    #[serde(default)]
+    rate_limit: Option<RateLimitSettings>,
+    /// リクエストボディの最大サイズ（バイト）
+    #[serde(default)]
+    max_body_size: Option<usize>,
+    /// リクエストのタイムアウト（秒）
+    #[serde(default)]
+    request_timeout_secs: Option<u64>,
+    /// パスごとのタイムアウト
+    #[serde(default)]
+    timeouts: Option<Vec<RouteTimeout>>,
+    /// シャットダウン時に処理中のリクエストを待つ時間（秒）
+    #[serde(default)]
+    shutdown_timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -575,6 +1225,18 @@ struct TomlMemoryConfig {
     /// データベースパス
     #[serde(default)]
     db_path: Option<String>,
+    /// Postgres の接続 URL
+    #[serde(default)]
+    database_url: Option<String>,
+    /// Postgres の最大接続数
+    #[serde(default)]
+    pool_size: Option<usize>,
+    /// ファクト抽出の設定
+    #[serde(default)]
+    memorizer: Option<MemorizerConfig>,
+    /// 同一人物として扱うチャネルユーザー
+    #[serde(default)]
+    identities: Option<HashMap<String, Vec<String>>>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -662,6 +1324,19 @@ mod tests {
         assert!(config.config_path.is_none());
     }
 
+    #[test]
+    fn test_channels_config() {
+        let mut channels = ChannelsConfig::default();
+        assert_eq!(channels.enabled(), vec!["discord"]);
+        assert_eq!(channels.ports.ws, 3001);
+
+        let unknown = channels.enable_only("telegram, Slack,,fax");
+        assert_eq!(unknown, vec!["fax".to_string()]);
+        assert_eq!(channels.enabled(), vec!["telegram", "slack"]);
+        assert!(!channels.is_enabled("discord"));
+        assert!(!channels.set_enabled("fax", true));
+    }
+
     #[test]
     fn test_expand_env_vars() {
         // テスト用環境変数を設定
@@ -712,6 +1387,9 @@ mod tests {
                 model: "test_model".to_string(),
                 provider: LlmProvider::Claude,
                 base_url: Some("https://example.com".to_string()),
+                region: None,
+                project_id: None,
+                retry: RetryPolicy::default(),
             },
             claude_api_key: "test_key".to_string(),
             claude_model: "test_model".to_string(),
@@ -722,6 +1400,12 @@ mod tests {
             memory: MemoryConfig::default(),
             mcp: McpConfig::default(),
             scheduler: SchedulerConfig::default(),
+            personas: PersonasConfig::default(),
+            notify: Default::default(),
+            tool_policy: ToolPolicy::default(),
+            tool_limits: ToolLimits::default(),
+            channels: ChannelsConfig::default(),
+            knowledge: Default::default(),
         };
 
         let llm_config = config.llm_config();
@@ -747,9 +1431,31 @@ admin_user_ids = [123456, 789012]
 [api]
 port = 8080
 key = "api_key"
+max_body_size = 1048576
+request_timeout_secs = 60
+
+[[api.timeouts]]
+path = "/api/jobs"
+secs = 5
+
+[api.rate_limit]
+per_ip = 120
+
+[[api.rate_limit.routes]]
+path = "/api/chat"
+per_key = 30
 
 [memory]
 db_path = "/path/to/db"
+database_url = "postgres://cc:secret@db/cc_gateway"
+pool_size = 8
+
+[memory.memorizer]
+enabled = true
+ttl_days = 365
+
+[memory.identities]
+alice = ["discord:123", "telegram:456"]
 
 [mcp]
 enabled = false
@@ -758,6 +1464,22 @@ config_path = "/path/to/mcp.json"
 [scheduler]
 enabled = true
 config_path = "/path/to/schedule.toml"
+
+[channels]
+telegram = true
+restart_delay_secs = 10
+
+[channels.ports]
+line = 8443
+
+[knowledge]
+enabled = true
+sources = ["docs", "https://example.com/faq"]
+chunk_size = 800
+
+[knowledge.embedding]
+provider = "openai"
+model = "nomic-embed-text"
 "#;
 
         let toml_config: TomlConfig = toml::from_str(toml_content).unwrap();
@@ -778,10 +1500,37 @@ config_path = "/path/to/schedule.toml"
         let api = toml_config.api.unwrap();
         assert_eq!(api.port, Some(8080));
         assert_eq!(api.key, Some("api_key".to_string()));
+        let rate_limit = api.rate_limit.unwrap();
+        assert_eq!(rate_limit.per_ip, Some(120));
+        assert_eq!(rate_limit.route("/api/chat").and_then(|r| r.per_key), Some(30));
+        assert!(rate_limit.route("/api/chatter").is_none());
+        assert_eq!(api.max_body_size, Some(1048576));
+        assert_eq!(api.request_timeout_secs, Some(60));
+        let api_config = ApiConfig {
+            request_timeout_secs: 60,
+            timeouts: api.timeouts.unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(api_config.timeout_for("/api/jobs/123"), std::time::Duration::from_secs(5));
+        assert_eq!(api_config.timeout_for("/api/chat"), std::time::Duration::from_secs(60));
 
         // Memory 設定の検証
         let memory = toml_config.memory.unwrap();
         assert_eq!(memory.db_path, Some("/path/to/db".to_string()));
+        assert_eq!(memory.database_url.as_deref(), Some("postgres://cc:secret@db/cc_gateway"));
+        assert_eq!(memory.pool_size, Some(8));
+        let memorizer = memory.memorizer.unwrap();
+        assert!(memorizer.enabled);
+        assert_eq!(memorizer.max_facts, 10);
+        assert_eq!(memorizer.ttl_days, Some(365));
+        let memory_config = MemoryConfig {
+            identities: memory.identities.unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(memory_config.identity("telegram", "456"), "alice");
+        assert_eq!(memory_config.identity("slack", "U1"), "slack:U1");
+        assert_eq!(memory_config.identity_members("alice"), vec!["discord:123", "telegram:456"]);
+        assert_eq!(memory_config.identity_members("slack:U1"), vec!["slack:U1"]);
 
         // MCP 設定の検証
         let mcp = toml_config.mcp.unwrap();
@@ -792,5 +1541,23 @@ config_path = "/path/to/schedule.toml"
         let scheduler = toml_config.scheduler.unwrap();
         assert_eq!(scheduler.enabled, Some(true));
         assert_eq!(scheduler.config_path, Some("/path/to/schedule.toml".to_string()));
+
+        // チャネル設定の検証（省略した項目は既定値）
+        let channels = toml_config.channels.unwrap();
+        assert!(channels.discord);
+        assert!(channels.telegram);
+        assert_eq!(channels.restart_delay_secs, 10);
+        assert_eq!(channels.max_restart_delay_secs, 300);
+        assert_eq!(channels.ports.line, 8443);
+        assert_eq!(channels.ports.whatsapp, 3010);
+
+        // ナレッジベース設定の検証
+        let knowledge = toml_config.knowledge.unwrap();
+        assert!(knowledge.enabled);
+        assert_eq!(knowledge.sources, vec!["docs", "https://example.com/faq"]);
+        assert_eq!(knowledge.chunk_size, 800);
+        assert_eq!(knowledge.chunk_overlap, 200);
+        assert_eq!(knowledge.embedding.provider, EmbeddingProvider::OpenAi);
+        assert_eq!(knowledge.embedding.model.as_deref(), Some("nomic-embed-text"));
     }
 }
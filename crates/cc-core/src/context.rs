@@ -0,0 +1,63 @@
+//! Extra context for a turn
+//!
+//! A [`ContextProvider`] finds material relevant to the user's message, such
+//! as passages of a knowledge base, which channels add to the system prompt
+//! of the request before sending it.
+
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::llm::MessagesRequest;
+use crate::Result;
+
+/// Source of context relevant to a message
+#[async_trait]
+pub trait ContextProvider: Send + Sync {
+    /// Section for the system prompt relevant to `query` (None = nothing relevant)
+    async fn context(&self, query: &str) -> Result<Option<String>>;
+}
+
+/// Add the context `provider` finds for `query` to the request's system prompt
+///
+/// Failures are logged; the turn goes ahead without the context.
+pub async fn inject_context(provider: &dyn ContextProvider, request: &mut MessagesRequest, query: &str) {
+    let section = match provider.context(query).await {
+        Ok(Some(section)) => section,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to find context for the message: {}", e);
+            return;
+        }
+    };
+    request.system = Some(match request.system.take() {
+        Some(system) => format!("{}\n\n{}", system, section),
+        None => section,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MessagesRequestBuilder;
+
+    struct Fixed(Option<&'static str>);
+
+    #[async_trait]
+    impl ContextProvider for Fixed {
+        async fn context(&self, _query: &str) -> Result<Option<String>> {
+            Ok(self.0.map(str::to_string))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inject_context() {
+        let mut request = MessagesRequestBuilder::new("model".to_string()).build();
+        inject_context(&Fixed(None), &mut request, "hello").await;
+        assert!(request.system.is_none());
+
+        inject_context(&Fixed(Some("## Documents")), &mut request, "hello").await;
+        assert_eq!(request.system.as_deref(), Some("## Documents"));
+        inject_context(&Fixed(Some("## More")), &mut request, "hello").await;
+        assert_eq!(request.system.as_deref(), Some("## Documents\n\n## More"));
+    }
+}
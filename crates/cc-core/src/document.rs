@@ -0,0 +1,115 @@
+//! Document text extraction
+//!
+//! Turns files users send through a channel (notes, source code, CSV, JSON,
+//! ...) into text the model can read. Only text-based formats are
+//! supported; binary formats such as PDF or Office files are reported as
+//! unsupported so the channel can tell the user.
+
+/// Characters of a document passed to the model (the rest is cut off)
+pub const MAX_DOCUMENT_CHARS: usize = 50_000;
+
+/// File extensions read as text
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "rst", "csv", "tsv", "json", "jsonl", "yaml", "yml", "toml", "xml",
+    "html", "htm", "ini", "cfg", "conf", "log", "sql", "sh", "bash", "zsh", "py", "rs", "js", "ts",
+    "jsx", "tsx", "go", "java", "kt", "c", "h", "cpp", "hpp", "cs", "rb", "php", "swift", "css",
+    "scss", "ics", "vcf", "srt", "vtt", "tex",
+];
+
+/// Media types read as text besides `text/*`
+const TEXT_MEDIA_TYPES: &[&str] = &[
+    "application/json",
+    "application/xml",
+    "application/yaml",
+    "application/x-yaml",
+    "application/toml",
+    "application/javascript",
+    "application/x-sh",
+    "application/sql",
+];
+
+/// Text extracted from a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedDocument {
+    /// File name
+    pub name: String,
+    /// Text content
+    pub text: String,
+    /// Whether the text was cut at [`MAX_DOCUMENT_CHARS`]
+    pub truncated: bool,
+}
+
+impl ExtractedDocument {
+    /// The document as part of a user message
+    pub fn to_prompt(&self) -> String {
+        let mut prompt = format!("[File: {}]\n```\n{}\n```", self.name, self.text.replace("```", "`\u{200b}``"));
+        if self.truncated {
+            prompt.push_str("\n(truncated)");
+        }
+        prompt
+    }
+}
+
+/// Whether a file with `name` and `media_type` can be read as text
+pub fn is_text_document(name: &str, media_type: Option<&str>) -> bool {
+    if let Some(media_type) = media_type {
+        let media_type = media_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        if media_type.starts_with("text/") || TEXT_MEDIA_TYPES.contains(&media_type.as_str()) {
+            return true;
+        }
+    }
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| TEXT_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Extract the text of a document (None if the format is not supported)
+pub fn extract_text(name: &str, media_type: Option<&str>, bytes: &[u8]) -> Option<ExtractedDocument> {
+    if !is_text_document(name, media_type) {
+        return None;
+    }
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let text = std::str::from_utf8(bytes).ok()?;
+
+    let truncated = text.chars().count() > MAX_DOCUMENT_CHARS;
+    let text = if truncated {
+        text.chars().take(MAX_DOCUMENT_CHARS).collect()
+    } else {
+        text.to_string()
+    };
+    Some(ExtractedDocument {
+        name: name.to_string(),
+        text,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_text_document() {
+        assert!(is_text_document("notes.MD", None));
+        assert!(is_text_document("data", Some("text/csv; charset=utf-8")));
+        assert!(is_text_document("config", Some("application/json")));
+        assert!(!is_text_document("report.pdf", Some("application/pdf")));
+        assert!(!is_text_document("archive.zip", None));
+    }
+
+    #[test]
+    fn test_extract_text() {
+        let doc = extract_text("main.rs", None, b"\xEF\xBB\xBFfn main() {}").unwrap();
+        assert_eq!(doc.text, "fn main() {}");
+        assert!(!doc.truncated);
+        assert!(doc.to_prompt().starts_with("[File: main.rs]\n```\nfn main() {}"));
+
+        // Not UTF-8, or not a text format
+        assert!(extract_text("data.txt", None, &[0xff, 0xfe, 0x00]).is_none());
+        assert!(extract_text("report.pdf", None, b"%PDF-1.7").is_none());
+
+        let long = extract_text("long.txt", None, "あ".repeat(MAX_DOCUMENT_CHARS + 1).as_bytes()).unwrap();
+        assert!(long.truncated);
+        assert_eq!(long.text.chars().count(), MAX_DOCUMENT_CHARS);
+        assert!(long.to_prompt().ends_with("(truncated)"));
+    }
+}
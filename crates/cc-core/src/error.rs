@@ -17,6 +17,9 @@ pub enum Error {
     #[error("Database error: {0}")]
     Database(#[from] rusqlite::Error),
 
+    #[error("Storage error: {0}")]
+    Storage(String),
+
     #[error("Tool execution error: {0}")]
     ToolExecution(String),
 
@@ -32,9 +35,32 @@ pub enum Error {
     #[error("MCP error: {0}")]
     Mcp(String),
 
+    #[error("Notification error: {0}")]
+    Notify(String),
+
+    #[error("Cancelled")]
+    Cancelled,
+
+    #[error("Max iterations ({0}) reached")]
+    MaxIterations(usize),
+
     #[error("{0}")]
     Other(String),
 }
 
+#[cfg(feature = "postgres")]
+impl From<tokio_postgres::Error> for Error {
+    fn from(e: tokio_postgres::Error) -> Self {
+        Self::Storage(e.to_string())
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<deadpool_postgres::PoolError> for Error {
+    fn from(e: deadpool_postgres::PoolError) -> Self {
+        Self::Storage(e.to_string())
+    }
+}
+
 /// Result type alias for cc-core
 pub type Result<T> = std::result::Result<T, Error>;
@@ -6,31 +6,55 @@
 pub mod agents;
 pub mod audit;
 pub mod config;
+pub mod context;
+pub mod document;
 pub mod error;
 pub mod llm;
 pub mod memory;
+pub mod migrate;
+pub mod notify;
+pub mod persona;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod prompts;
+pub mod purge;
 pub mod session;
 pub mod skills;
 pub mod tool;
+pub mod webhook;
 
 pub use agents::{
-    AggregatedResult, AggregationStrategy, AgentCapability, DefaultSubAgent, DelegationConfig,
-    ParallelExecutor, ResultAggregator, SubAgent, SubAgentId, SubAgentManager, SubAgentResult,
-    SubAgentTask, SubAgentTaskBuilder, TaskDelegator, TaskId, TaskPriority, TaskStatus,
-    ToolCallRecord,
+    AgentCapability, AgentDefinition, AgentsConfig, AggregatedResult, AggregationStrategy,
+    CancellationToken, DefaultSubAgent, DelegateTaskTool, DelegationConfig, DelegationContext, OrchestrationResult,
+    Orchestrator, OrchestratorConfig, ParallelExecutor, ResultAggregator, SubAgent, SubAgentId,
+    SubAgentManager, SubAgentResult, SubAgentTask, SubAgentTaskBuilder, TaskDelegator, TaskId,
+    TaskPlan, TaskPriority, TaskQueue, TaskStatus, ToolCallRecord,
 };
 pub use audit::{
     AuditConfig, AuditEntry, AuditEntryBuilder, AuditError, AuditEventType, AuditLevel,
     AuditLogger, AuditResult, AuditSource, AuditTarget, CryptoError, CryptoResult, EncryptedData,
     EncryptionAlgorithm, EncryptionConfig, SimpleEncryptor,
 };
-pub use config::{ApiConfig, Config, LlmConfig, LlmProvider, McpConfig, MemoryConfig, SchedulerConfig};
+pub use config::{
+    ApiConfig, ChannelPorts, ChannelsConfig, Config, EmbeddingConfig, EmbeddingProvider, KnowledgeConfig, LlmConfig, LlmProvider, McpConfig, MemorizerConfig, MemoryConfig, RateLimitSettings, RouteRateLimit,
+    RouteTimeout, SchedulerConfig,
+};
+pub use context::{inject_context, ContextProvider};
+pub use document::{extract_text, ExtractedDocument};
 pub use error::{Error, Result};
 pub use llm::{
-    ClaudeClient, ImageSource, Message, MessageContent, MessagesRequest, MessagesRequestBuilder,
-    MessagesResponse, ThinkingConfig, ThinkingLevel, ToolDefinition, Usage,
+    AgentEngine, AgentHooks, AgentRun, ClaudeClient, DocumentSource, ImageSource, Message, MessageContent,
+    MessagesRequest, MessagesRequestBuilder, MessagesResponse, RetryPolicy, ThinkingConfig, ThinkingLevel, ToolChoice,
+    ToolDefinition, Usage,
 };
-pub use memory::{Memory, MemoryStore};
-pub use session::{Session, SessionManager, SessionStore};
+pub use memory::{Memorizer, Memory, MemoryBackend, MemoryStore};
+pub use notify::{Notification, NotificationLevel, Notifier, NotifyConfig};
+pub use persona::{Persona, PersonaRegistry, PersonasConfig};
+pub use prompts::{PromptContext, PromptLibrary, PromptTemplate};
+pub use purge::{purge_identity, PurgeReport};
+pub use session::{Session, SessionBackend, SessionManager, SessionStore};
 pub use skills::{Skill, SkillConfig, SkillLoader};
-pub use tool::{Tool, ToolManager, ToolResult};
+pub use tool::{
+    ApprovalOutcome, PolicyDecision, Tool, ToolApprover, ToolLimits, ToolManager, ToolOrigin, ToolPolicy, ToolResult,
+};
+pub use webhook::{SignatureError, WebhookVerifier};
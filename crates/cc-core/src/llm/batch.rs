@@ -0,0 +1,270 @@
+//! Message Batches API
+//!
+//! 大量のリクエストを非同期でまとめて処理する Anthropic Message Batches API
+//! のクライアントです。通常の Messages API より低コストで、結果は最大 24 時間
+//! 以内に返されます。
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::error::{Error, Result};
+
+use super::client::ClaudeClient;
+use super::types::{MessagesRequest, MessagesResponse};
+
+/// A single request inside a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequest {
+    /// Caller-chosen identifier used to match results
+    pub custom_id: String,
+    /// Regular Messages API parameters
+    pub params: MessagesRequest,
+}
+
+impl BatchRequest {
+    pub fn new(custom_id: impl Into<String>, params: MessagesRequest) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            params,
+        }
+    }
+}
+
+/// Per-status request counts for a batch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchRequestCounts {
+    #[serde(default)]
+    pub processing: u64,
+    #[serde(default)]
+    pub succeeded: u64,
+    #[serde(default)]
+    pub errored: u64,
+    #[serde(default)]
+    pub canceled: u64,
+    #[serde(default)]
+    pub expired: u64,
+}
+
+/// Batch processing status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    InProgress,
+    Canceling,
+    Ended,
+}
+
+/// Message batch metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageBatch {
+    pub id: String,
+    pub processing_status: BatchStatus,
+    #[serde(default)]
+    pub request_counts: BatchRequestCounts,
+    #[serde(default)]
+    pub results_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub ended_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl MessageBatch {
+    /// Whether processing has finished (results are available)
+    pub fn is_ended(&self) -> bool {
+        self.processing_status == BatchStatus::Ended
+    }
+}
+
+/// Outcome of a single batch request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchResultType {
+    Succeeded { message: MessagesResponse },
+    Errored { error: serde_json::Value },
+    Canceled,
+    Expired,
+}
+
+/// One line of the batch results file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub custom_id: String,
+    pub result: BatchResultType,
+}
+
+impl BatchResult {
+    /// Get the response message if the request succeeded
+    pub fn message(&self) -> Option<&MessagesResponse> {
+        match &self.result {
+            BatchResultType::Succeeded { message } => Some(message),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a JSONL results body
+pub fn parse_batch_results(body: &str) -> Result<Vec<BatchResult>> {
+    body.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(Error::from))
+        .collect()
+}
+
+impl ClaudeClient {
+    /// Submit a new message batch
+    pub async fn create_batch(&self, requests: Vec<BatchRequest>) -> Result<MessageBatch> {
+        self.ensure_anthropic_api("Message Batches API")?;
+
+        let payload = serde_json::json!({ "requests": requests });
+        let body = self
+            .send_with_retry("Claude Batches", || {
+                self.anthropic_request(Method::POST, "/messages/batches")
+                    .json(&payload)
+            })
+            .await?;
+
+        let batch: MessageBatch = serde_json::from_str(&body)?;
+        info!("Created message batch {} ({} requests)", batch.id, requests.len());
+        Ok(batch)
+    }
+
+    /// Fetch the current state of a batch
+    pub async fn get_batch(&self, batch_id: &str) -> Result<MessageBatch> {
+        self.ensure_anthropic_api("Message Batches API")?;
+
+        let path = format!("/messages/batches/{}", batch_id);
+        let body = self
+            .send_with_retry("Claude Batches", || {
+                self.anthropic_request(Method::GET, &path)
+            })
+            .await?;
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Request cancellation of a batch
+    pub async fn cancel_batch(&self, batch_id: &str) -> Result<MessageBatch> {
+        self.ensure_anthropic_api("Message Batches API")?;
+
+        let path = format!("/messages/batches/{}/cancel", batch_id);
+        let body = self
+            .send_with_retry("Claude Batches", || {
+                self.anthropic_request(Method::POST, &path)
+            })
+            .await?;
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Download results for an ended batch
+    pub async fn batch_results(&self, batch: &MessageBatch) -> Result<Vec<BatchResult>> {
+        self.ensure_anthropic_api("Message Batches API")?;
+
+        let path = match &batch.results_url {
+            Some(url) => url.clone(),
+            None => format!("/messages/batches/{}/results", batch.id),
+        };
+        let body = self
+            .send_with_retry("Claude Batches", || {
+                self.anthropic_request(Method::GET, &path)
+            })
+            .await?;
+
+        parse_batch_results(&body)
+    }
+
+    /// Poll a batch until it ends or `timeout` elapses
+    pub async fn wait_for_batch(
+        &self,
+        batch_id: &str,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<MessageBatch> {
+        let started = Instant::now();
+
+        loop {
+            let batch = self.get_batch(batch_id).await?;
+            if batch.is_ended() {
+                info!(
+                    "Message batch {} ended: {} succeeded, {} errored",
+                    batch.id, batch.request_counts.succeeded, batch.request_counts.errored
+                );
+                return Ok(batch);
+            }
+
+            if started.elapsed() + poll_interval > timeout {
+                return Err(Error::ClaudeApi(format!(
+                    "Timed out waiting for batch {} after {:?}",
+                    batch_id,
+                    started.elapsed()
+                )));
+            }
+
+            debug!(
+                "Batch {} still {:?} ({} processing)",
+                batch.id, batch.processing_status, batch.request_counts.processing
+            );
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_batch_deserialize() {
+        let json = r#"{
+            "id": "msgbatch_01",
+            "type": "message_batch",
+            "processing_status": "in_progress",
+            "request_counts": {"processing": 2, "succeeded": 0, "errored": 0, "canceled": 0, "expired": 0},
+            "results_url": null,
+            "created_at": "2024-09-24T18:37:24.100435Z",
+            "ended_at": null,
+            "expires_at": "2024-09-25T18:37:24.100435Z"
+        }"#;
+
+        let batch: MessageBatch = serde_json::from_str(json).unwrap();
+        assert_eq!(batch.id, "msgbatch_01");
+        assert_eq!(batch.processing_status, BatchStatus::InProgress);
+        assert_eq!(batch.request_counts.processing, 2);
+        assert!(!batch.is_ended());
+    }
+
+    #[test]
+    fn test_parse_batch_results() {
+        let body = concat!(
+            r#"{"custom_id":"a","result":{"type":"succeeded","message":{"id":"msg_1","type":"message","role":"assistant","content":[{"type":"text","text":"hi"}],"model":"claude","stop_reason":"end_turn","usage":{"input_tokens":1,"output_tokens":1}}}}"#,
+            "\n",
+            r#"{"custom_id":"b","result":{"type":"errored","error":{"type":"invalid_request_error"}}}"#,
+            "\n",
+            r#"{"custom_id":"c","result":{"type":"expired"}}"#,
+            "\n"
+        );
+
+        let results = parse_batch_results(body).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].custom_id, "a");
+        assert!(results[0].message().is_some());
+        assert!(matches!(results[1].result, BatchResultType::Errored { .. }));
+        assert!(matches!(results[2].result, BatchResultType::Expired));
+    }
+
+    #[test]
+    fn test_batch_request_serialize() {
+        let params = super::super::types::MessagesRequestBuilder::new("claude".to_string())
+            .user("hello")
+            .build();
+        let json = serde_json::to_value(BatchRequest::new("task-1", params)).unwrap();
+        assert_eq!(json["custom_id"], "task-1");
+        assert_eq!(json["params"]["model"], "claude");
+    }
+}
@@ -1,15 +1,29 @@
 //! LLM API HTTP Client
 //!
-//! Supports both Claude API and OpenAI-compatible APIs (GLM, etc.)
+//! Supports Claude API, OpenAI-compatible APIs (GLM, etc.),
+//! and Anthropic models hosted on AWS Bedrock / Google Vertex AI.
+
+use std::sync::Arc;
+use std::time::Instant;
 
 use reqwest::Client;
 use tracing::{debug, info, warn};
 
+use crate::audit::{AuditEntry, AuditEventType, AuditLevel, AuditLogger};
 use crate::config::{Config, LlmProvider};
 use crate::error::{Error, Result};
+use crate::notify::{AlertKind, Notification, Notifier};
 
+use super::retry::{RetryPolicy, low_rate_limits, parse_retry_after};
+use super::sigv4::{self, AwsCredentials, SigningRequest};
 use super::types::*;
 
+/// `anthropic_version` body field required by Bedrock
+const BEDROCK_ANTHROPIC_VERSION: &str = "bedrock-2023-05-31";
+
+/// `anthropic_version` body field required by Vertex AI
+const VERTEX_ANTHROPIC_VERSION: &str = "vertex-2023-10-16";
+
 /// LLM API client (supports Claude and OpenAI-compatible APIs)
 #[derive(Clone)]
 pub struct ClaudeClient {
@@ -18,6 +32,11 @@ pub struct ClaudeClient {
     model: String,
     base_url: String,
     provider: LlmProvider,
+    region: String,
+    project_id: Option<String>,
+    retry_policy: RetryPolicy,
+    audit_logger: Option<Arc<AuditLogger>>,
+    notifier: Option<Arc<Notifier>>,
 }
 
 impl ClaudeClient {
@@ -30,12 +49,41 @@ impl ClaudeClient {
 
         let llm_config = config.llm_config();
 
+        // Region: explicit config, then the provider's conventional env var
+        let region = match &llm_config.region {
+            Some(region) => region.clone(),
+            None => match llm_config.provider {
+                LlmProvider::Vertex => std::env::var("CLOUD_ML_REGION")
+                    .unwrap_or_else(|_| "us-east5".to_string()),
+                _ => std::env::var("AWS_REGION")
+                    .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|_| "us-east-1".to_string()),
+            },
+        };
+
+        let project_id = llm_config
+            .project_id
+            .clone()
+            .or_else(|| std::env::var("ANTHROPIC_VERTEX_PROJECT_ID").ok());
+
+        if llm_config.provider == LlmProvider::Vertex && project_id.is_none() {
+            return Err(Error::Config(
+                "Vertex provider requires llm.project_id (or LLM_PROJECT_ID)".to_string(),
+            ));
+        }
+
         // Determine base URL based on provider
         let base_url = match &llm_config.base_url {
             Some(url) => url.clone(),
             None => match llm_config.provider {
                 LlmProvider::Claude => "https://api.anthropic.com/v1".to_string(),
                 LlmProvider::OpenAi => "https://api.openai.com/v1".to_string(),
+                LlmProvider::Bedrock => {
+                    format!("https://bedrock-runtime.{}.amazonaws.com", region)
+                }
+                LlmProvider::Vertex => {
+                    format!("https://{}-aiplatform.googleapis.com/v1", region)
+                }
             },
         };
 
@@ -45,6 +93,11 @@ impl ClaudeClient {
             model: llm_config.model.clone(),
             base_url,
             provider: llm_config.provider.clone(),
+            region,
+            project_id,
+            retry_policy: llm_config.retry.clone(),
+            audit_logger: None,
+            notifier: None,
         })
     }
 
@@ -55,6 +108,29 @@ impl ClaudeClient {
         Ok(client)
     }
 
+    /// Override the retry policy
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Emit audit events (e.g. retries) to the given logger
+    pub fn with_audit_logger(mut self, logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(logger);
+        self
+    }
+
+    /// Send quota warnings and API error alerts through the notifier's routes
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Get the retry policy
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
     /// Send a message to the LLM API
     pub async fn messages(
         &self,
@@ -70,6 +146,8 @@ impl ClaudeClient {
         match self.provider {
             LlmProvider::Claude => self.send_claude_request(request).await,
             LlmProvider::OpenAi => self.send_openai_request(request).await,
+            LlmProvider::Bedrock => self.send_bedrock_request(request).await,
+            LlmProvider::Vertex => self.send_vertex_request(request).await,
         }
     }
 
@@ -82,32 +160,176 @@ impl ClaudeClient {
 
         debug!("Sending request to Claude API: {}", url);
 
-        let response = self
-            .client
-            .post(&url)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .map_err(Error::Http)?;
+        let body = self
+            .send_with_retry("Claude", || {
+                self.client
+                    .post(&url)
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", "2023-06-01")
+                    .header("content-type", "application/json")
+                    .json(&request)
+            })
+            .await?;
 
-        let status = response.status();
-        let body = response.text().await.map_err(Error::Http)?;
+        let parsed: MessagesResponse =
+            serde_json::from_str(&body).map_err(|e| {
+                Error::ClaudeApi(format!("Failed to parse response: {} - {}", e, body))
+            })?;
+
+        info!(
+            "Claude API response: stop_reason={:?}, tokens={}",
+            parsed.stop_reason,
+            parsed.usage.as_ref().map(|u| u.output_tokens).unwrap_or(0)
+        );
 
-        if !status.is_success() {
-            warn!("Claude API error: {} - {}", status, body);
-            return Err(Error::ClaudeApi(format!("{}: {}", status, body)));
+        Ok(parsed)
+    }
+
+    /// Send request to AWS Bedrock (InvokeModel, SigV4 signed)
+    async fn send_bedrock_request(
+        &self,
+        request: MessagesRequest,
+    ) -> Result<MessagesResponse> {
+        let credentials = AwsCredentials::from_env().ok_or_else(|| {
+            Error::Config(
+                "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set for Bedrock".to_string(),
+            )
+        })?;
+
+        let path = format!("/model/{}/invoke", sigv4::uri_encode(&request.model));
+        let url = format!("{}{}", self.base_url.trim_end_matches('/'), path);
+        let host = reqwest::Url::parse(&url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .ok_or_else(|| Error::Config(format!("Invalid Bedrock URL: {}", url)))?;
+
+        let body = Self::cloud_request_body(&request, BEDROCK_ANTHROPIC_VERSION)?;
+
+        debug!("Sending request to Bedrock: {}", url);
+
+        // Re-sign on every attempt so x-amz-date stays fresh across retries
+        let response_body = self
+            .send_with_retry("Bedrock", || {
+                let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+                let signed_headers = sigv4::sign(
+                    &SigningRequest {
+                        method: "POST",
+                        host: &host,
+                        path: &path,
+                        region: &self.region,
+                        service: "bedrock",
+                        payload: &body,
+                        headers: vec![(
+                            "content-type".to_string(),
+                            "application/json".to_string(),
+                        )],
+                    },
+                    &credentials,
+                    &amz_date,
+                );
+
+                let mut builder = self
+                    .client
+                    .post(&url)
+                    .header("content-type", "application/json");
+                for (name, value) in signed_headers {
+                    builder = builder.header(name, value);
+                }
+                builder.body(body.clone())
+            })
+            .await?;
+
+        Self::parse_anthropic_response("Bedrock", &response_body)
+    }
+
+    /// Send request to Google Vertex AI (rawPredict)
+    async fn send_vertex_request(
+        &self,
+        request: MessagesRequest,
+    ) -> Result<MessagesResponse> {
+        let project_id = self.project_id.as_deref().unwrap_or_default();
+        let url = format!(
+            "{}/projects/{}/locations/{}/publishers/anthropic/models/{}:rawPredict",
+            self.base_url.trim_end_matches('/'),
+            project_id,
+            self.region,
+            request.model
+        );
+
+        let body = Self::cloud_request_body(&request, VERTEX_ANTHROPIC_VERSION)?;
+        let token = self.vertex_access_token().await?;
+
+        debug!("Sending request to Vertex AI: {}", url);
+
+        let response_body = self
+            .send_with_retry("Vertex AI", || {
+                self.client
+                    .post(&url)
+                    .bearer_auth(&token)
+                    .header("content-type", "application/json")
+                    .body(body.clone())
+            })
+            .await?;
+
+        Self::parse_anthropic_response("Vertex AI", &response_body)
+    }
+
+    /// Build a Messages API body for Bedrock / Vertex
+    ///
+    /// Both platforms take the model from the URL and require
+    /// `anthropic_version` in the body instead of a header.
+    fn cloud_request_body(request: &MessagesRequest, anthropic_version: &str) -> Result<Vec<u8>> {
+        let mut body = serde_json::to_value(request)?;
+        if let Some(obj) = body.as_object_mut() {
+            obj.remove("model");
+            obj.insert(
+                "anthropic_version".to_string(),
+                serde_json::Value::String(anthropic_version.to_string()),
+            );
         }
+        Ok(serde_json::to_vec(&body)?)
+    }
 
+    /// Resolve an OAuth access token for Vertex AI
+    ///
+    /// Uses `llm.api_key` if set, otherwise `GOOGLE_ACCESS_TOKEN`,
+    /// falling back to `gcloud auth print-access-token`.
+    async fn vertex_access_token(&self) -> Result<String> {
+        if !self.api_key.is_empty() {
+            return Ok(self.api_key.clone());
+        }
+        if let Ok(token) = std::env::var("GOOGLE_ACCESS_TOKEN") {
+            if !token.is_empty() {
+                return Ok(token);
+            }
+        }
+
+        let output = tokio::process::Command::new("gcloud")
+            .args(["auth", "print-access-token"])
+            .output()
+            .await
+            .map_err(|e| Error::Config(format!("Failed to run gcloud for Vertex token: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(Error::Config(format!(
+                "gcloud auth print-access-token failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Parse an Anthropic-format response returned by a cloud provider
+    fn parse_anthropic_response(platform: &str, body: &str) -> Result<MessagesResponse> {
         let parsed: MessagesResponse =
-            serde_json::from_str(&body).map_err(|e| {
+            serde_json::from_str(body).map_err(|e| {
                 Error::ClaudeApi(format!("Failed to parse response: {} - {}", e, body))
             })?;
 
         info!(
-            "Claude API response: stop_reason={:?}, tokens={}",
+            "{} API response: stop_reason={:?}, tokens={}",
+            platform,
             parsed.stop_reason,
             parsed.usage.as_ref().map(|u| u.output_tokens).unwrap_or(0)
         );
@@ -127,23 +349,15 @@ impl ClaudeClient {
         // Convert to OpenAI format
         let openai_request = ChatCompletionRequest::from_claude_request(&request);
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("content-type", "application/json")
-            .json(&openai_request)
-            .send()
-            .await
-            .map_err(Error::Http)?;
-
-        let status = response.status();
-        let body = response.text().await.map_err(Error::Http)?;
-
-        if !status.is_success() {
-            warn!("OpenAI API error: {} - {}", status, body);
-            return Err(Error::ClaudeApi(format!("{}: {}", status, body)));
-        }
+        let body = self
+            .send_with_retry("OpenAI", || {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.api_key))
+                    .header("content-type", "application/json")
+                    .json(&openai_request)
+            })
+            .await?;
 
         // Parse OpenAI response
         let openai_response: ChatCompletionResponse =
@@ -163,181 +377,218 @@ impl ClaudeClient {
         Ok(parsed)
     }
 
-    /// Create a messages request builder
-    pub fn request_builder(&self) -> MessagesRequestBuilder {
-        MessagesRequestBuilder::new(self.model.clone())
+    /// Fail unless the client talks to the native Anthropic API
+    pub(super) fn ensure_anthropic_api(&self, feature: &str) -> Result<()> {
+        if self.provider != LlmProvider::Claude {
+            return Err(Error::Config(format!(
+                "{} is only supported with the Claude provider",
+                feature
+            )));
+        }
+        Ok(())
     }
 
-    /// Get the model name
-    pub fn model(&self) -> &str {
-        &self.model
+    /// Whether replies can be streamed (native Anthropic API only)
+    pub(super) fn supports_streaming(&self) -> bool {
+        self.provider == LlmProvider::Claude && !self.base_url.contains("minimax.io")
     }
 
-    /// Get the provider type
-    pub fn provider(&self) -> &LlmProvider {
-        &self.provider
+    /// Build a request against the native Anthropic API
+    ///
+    /// `path` is appended to the base URL unless it is already absolute
+    /// (e.g. a batch `results_url`).
+    pub(super) fn anthropic_request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> reqwest::RequestBuilder {
+        let url = if path.starts_with("http://") || path.starts_with("https://") {
+            path.to_string()
+        } else {
+            format!("{}{}", self.base_url.trim_end_matches('/'), path)
+        };
+
+        self.client
+            .request(method, url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
     }
 
-    /// Run the agent loop with tools
-    pub async fn run_agent_loop(
-        &self,
-        messages: Vec<Message>,
-        system: Option<String>,
-        tools: Vec<ToolDefinition>,
-        max_iterations: usize,
-        tool_executor: impl Fn(&str, &serde_json::Value) -> Result<ToolResult>,
-    ) -> Result<AgentLoopResult> {
-        let mut current_messages = messages;
-        let mut iterations = 0;
-        let mut total_tokens = TokenUsage::default();
+    /// Send a request, retrying transient failures per the retry policy
+    ///
+    /// `make_request` is called once per attempt. Returns the response body
+    /// on success; non-retryable errors and exhausted budgets surface as
+    /// `Error::ClaudeApi` / `Error::Http`.
+    pub(super) async fn send_with_retry<F>(&self, platform: &str, make_request: F) -> Result<String>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let policy = &self.retry_policy;
+        let started = Instant::now();
+        let budget = std::time::Duration::from_millis(policy.max_elapsed_ms);
+        let mut attempt: u32 = 0;
 
         loop {
-            iterations += 1;
-            if iterations > max_iterations {
-                return Ok(AgentLoopResult {
-                    final_response: "Max iterations reached".to_string(),
-                    iterations,
-                    total_tokens,
-                    tool_calls: vec![],
-                });
-            }
+            // Status / body of the failed attempt, for alerts
+            let last_status: Option<u16>;
+            let last_body: String;
+            let (reason, retry_after, final_error) = match make_request().send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    let headers = response.headers().clone();
+                    let body = response.text().await.map_err(Error::Http)?;
+
+                    if status.is_success() {
+                        self.check_quota(platform, &headers);
+                        return Ok(body);
+                    }
 
-            let request = MessagesRequest {
-                model: self.model.clone(),
-                max_tokens: 4096,
-                system: system.clone(),
-                messages: current_messages.clone(),
-                tools: Some(tools.clone()),
-                thinking: None,
+                    warn!("{} API error: {} - {}", platform, status, body);
+                    let error = Error::ClaudeApi(format!("{}: {}", status, body));
+                    if !RetryPolicy::is_retryable(status, &body) {
+                        self.alert_failure(platform, Some(status.as_u16()), &body);
+                        return Err(error);
+                    }
+                    last_status = Some(status.as_u16());
+                    last_body = body;
+                    (status.to_string(), parse_retry_after(&headers), error)
+                }
+                Err(e) if e.is_timeout() || e.is_connect() => {
+                    warn!("{} API request failed: {}", platform, e);
+                    last_status = None;
+                    last_body = e.to_string();
+                    (e.to_string(), None, Error::Http(e))
+                }
+                Err(e) => return Err(Error::Http(e)),
             };
 
-            let response = self.messages(request).await?;
-
-            // Accumulate token usage
-            if let Some(usage) = &response.usage {
-                total_tokens.input_tokens += usage.input_tokens;
-                total_tokens.output_tokens += usage.output_tokens;
+            attempt += 1;
+            if attempt > policy.max_retries {
+                self.alert_failure(platform, last_status, &last_body);
+                return Err(final_error);
             }
 
-            match response.stop_reason.as_str() {
-                "end_turn" | "stop_sequence" | "stop" => {
-                    // Extract text response
-                    let text = response
-                        .content
-                        .iter()
-                        .filter_map(|c| {
-                            if let MessageContent::Text { text } = c {
-                                Some(text.clone())
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    return Ok(AgentLoopResult {
-                        final_response: text,
-                        iterations,
-                        total_tokens,
-                        tool_calls: vec![],
-                    });
-                }
-                "tool_use" | "tool_calls" => {
-                    // Process tool uses
-                    let tool_uses: Vec<_> = response
-                        .content
-                        .iter()
-                        .filter_map(|c| {
-                            if let MessageContent::ToolUse { id, name, input } = c {
-                                Some((id.clone(), name.clone(), input.clone()))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-
-                    if tool_uses.is_empty() {
-                        warn!("tool_use stop_reason but no tool_uses found");
-                        continue;
-                    }
+            let delay = policy.backoff(attempt, retry_after);
+            if started.elapsed() + delay > budget {
+                warn!(
+                    "{} retry budget exhausted after {} attempts ({:?})",
+                    platform,
+                    attempt,
+                    started.elapsed()
+                );
+                self.alert_failure(platform, last_status, &last_body);
+                return Err(final_error);
+            }
 
-                    // Execute tools and collect results
-                    let mut tool_results = Vec::new();
-                    for (id, name, input) in &tool_uses {
-                        debug!("Executing tool: {} with input: {:?}", name, input);
-                        let result = tool_executor(name, input)?;
-                        tool_results.push(MessageContent::ToolResult {
-                            tool_use_id: id.clone(),
-                            content: result.output,
-                            is_error: result.is_error,
-                        });
-                    }
+            warn!(
+                "Retrying {} request (attempt {}/{}) in {:?}: {}",
+                platform, attempt, policy.max_retries, delay, reason
+            );
+            self.audit_retry(platform, attempt, &reason, delay);
 
-                    // Add assistant message with tool_use
-                    current_messages.push(Message {
-                        role: "assistant".to_string(),
-                        content: response.content.clone(),
-                    });
-
-                    // Add user message with tool_results
-                    current_messages.push(Message {
-                        role: "user".to_string(),
-                        content: tool_results,
-                    });
-                }
-                other => {
-                    warn!("Unknown stop_reason: {}", other);
-                    return Err(Error::ClaudeApi(format!(
-                        "Unknown stop_reason: {}",
-                        other
-                    )));
-                }
-            }
+            tokio::time::sleep(delay).await;
         }
     }
-}
 
-/// Result of agent loop execution
-#[derive(Debug)]
-pub struct AgentLoopResult {
-    pub final_response: String,
-    pub iterations: usize,
-    pub total_tokens: TokenUsage,
-    pub tool_calls: Vec<ToolCall>,
-}
-
-#[derive(Debug, Default)]
-pub struct TokenUsage {
-    pub input_tokens: u64,
-    pub output_tokens: u64,
-}
+    /// Warn when the remaining rate limit drops below the notifier's threshold
+    fn check_quota(&self, platform: &str, headers: &reqwest::header::HeaderMap) {
+        let Some(notifier) = self.notifier.as_ref().filter(|n| n.routes_alert(AlertKind::Quota))
+        else {
+            return;
+        };
+        let Some(usage) = low_rate_limits(headers, notifier.quota_threshold()) else {
+            return;
+        };
 
-#[derive(Debug, Clone)]
-pub struct ToolCall {
-    pub id: String,
-    pub name: String,
-    pub input: serde_json::Value,
-}
+        let notification = Notification::warning(
+            format!("{} rate limit running low", platform),
+            format!("model: {}\n{}", self.model, usage),
+        );
+        let notifier = Arc::clone(notifier);
+        tokio::spawn(async move {
+            notifier.alert(AlertKind::Quota, &notification).await;
+        });
+    }
 
-/// Tool execution result
-#[derive(Debug, Clone)]
-pub struct ToolResult {
-    pub output: String,
-    pub is_error: bool,
-}
+    /// Alert on a request that finally failed (after retries)
+    ///
+    /// Rate limits and exhausted credit go to the quota route; authentication
+    /// failures and unavailability go to the error route. Other client errors
+    /// (e.g. invalid requests) are not alerted.
+    fn alert_failure(&self, platform: &str, status: Option<u16>, body: &str) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
 
-impl ToolResult {
-    pub fn success(output: impl Into<String>) -> Self {
-        Self {
-            output: output.into(),
-            is_error: false,
+        let (kind, title) = match status {
+            Some(429) => (AlertKind::Quota, format!("{} rate limit exceeded", platform)),
+            Some(400) if body.contains("credit balance") => {
+                (AlertKind::Quota, format!("{} credit balance too low", platform))
+            }
+            Some(401 | 403) => (AlertKind::Error, format!("{} authentication failed", platform)),
+            Some(status) if status >= 500 => {
+                (AlertKind::Error, format!("{} API unavailable", platform))
+            }
+            None => (AlertKind::Error, format!("{} API unreachable", platform)),
+            Some(_) => return,
+        };
+        if !notifier.routes_alert(kind) {
+            return;
         }
+
+        let detail: String = body.chars().take(500).collect();
+        let notification = Notification::error(
+            title,
+            format!(
+                "model: {}\nstatus: {}\n{}",
+                self.model,
+                status.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string()),
+                detail
+            ),
+        );
+        let notifier = Arc::clone(notifier);
+        tokio::spawn(async move {
+            notifier.alert(kind, &notification).await;
+        });
     }
 
-    pub fn error(output: impl Into<String>) -> Self {
-        Self {
-            output: output.into(),
-            is_error: true,
+    /// Record a retry in the audit log, if one is attached
+    fn audit_retry(&self, platform: &str, attempt: u32, reason: &str, delay: std::time::Duration) {
+        let Some(logger) = &self.audit_logger else {
+            return;
+        };
+
+        let entry = AuditEntry::new(
+            AuditEventType::LlmRequestRetried,
+            AuditLevel::Warning,
+            format!("{} request retried: {}", platform, reason),
+        )
+        .with_metadata(serde_json::json!({
+            "platform": platform,
+            "model": self.model,
+            "attempt": attempt,
+            "delay_ms": delay.as_millis() as u64,
+            "reason": reason,
+        }));
+
+        if let Err(e) = logger.log(&entry) {
+            warn!("Failed to write retry audit entry: {}", e);
         }
     }
+
+    /// Create a messages request builder
+    pub fn request_builder(&self) -> MessagesRequestBuilder {
+        MessagesRequestBuilder::new(self.model.clone())
+    }
+
+    /// Get the model name
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Get the provider type
+    pub fn provider(&self) -> &LlmProvider {
+        &self.provider
+    }
 }
@@ -0,0 +1,488 @@
+//! Agent loop shared by the CLI, channels and sub-agents
+//!
+//! [`AgentEngine`] sends a request, runs the tools the model calls, sends the
+//! results back and repeats until the model answers without calling tools.
+//! Callers observe and steer a turn through [`AgentHooks`]: they show
+//! progress, apply the tool policy and approvals, or handle tools of their
+//! own in [`AgentHooks::on_tool_call`].
+//!
+//! Read-only tools called in the same response run concurrently (see
+//! [`crate::tool::parallel`]); their results are sent back in call order.
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::agents::ToolCallRecord;
+use crate::llm::{ClaudeClient, Message, MessageContent, MessagesRequest, MessagesResponse, Usage};
+use crate::tool::{execute_ordered, ToolManager, ToolResult, MAX_CONCURRENT_TOOLS};
+use crate::{CancellationToken, Error, Result};
+
+/// Model calls per turn unless set with [`AgentEngine::with_max_iterations`]
+pub const DEFAULT_MAX_ITERATIONS: usize = 10;
+
+/// A tool call requested by the model
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+}
+
+/// Tokens used by a turn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// A finished turn
+#[derive(Debug, Clone)]
+pub struct AgentRun {
+    /// The model's answer
+    pub text: String,
+    /// Model calls made
+    pub iterations: usize,
+    /// Tokens used by all model calls
+    pub usage: TokenUsage,
+    /// Tool calls in the order they were made
+    pub tool_calls: Vec<ToolCallRecord>,
+}
+
+/// Observes and steers a turn of the [`AgentEngine`]
+///
+/// Every method has a default, so callers implement only what they need.
+#[async_trait]
+pub trait AgentHooks: Send + Sync {
+    /// Before each model call (starting at 1)
+    async fn on_iteration(&self, _iteration: usize) {}
+
+    /// Answer text as it streams in (only with [`AgentEngine::streaming`])
+    fn on_text_delta(&self, _delta: &str) {}
+
+    /// Text the model wrote alongside tool calls, before they run
+    async fn on_text(&self, _text: &str) {}
+
+    /// Tokens used by one model call
+    async fn on_usage(&self, _usage: &Usage) {}
+
+    /// Run one tool call
+    ///
+    /// The default executes it with `tools`, which refuses calls its
+    /// [`ToolPolicy`](crate::ToolPolicy) denies or that need an approval.
+    /// Override to ask for approval (then run the call with
+    /// [`ToolManager::execute_approved`]), show progress or handle tools that
+    /// are not in `tools`.
+    /// Calls to read-only tools of one response are made concurrently.
+    async fn on_tool_call(&self, call: &ToolCall, tools: &ToolManager) -> ToolResult {
+        tools
+            .execute(&call.name, call.input.clone())
+            .await
+            .unwrap_or_else(|e| ToolResult::error(e.to_string()))
+    }
+}
+
+/// Hooks that only run the tools
+pub struct NoHooks;
+
+impl AgentHooks for NoHooks {}
+
+/// Runs the messages / tool_use loop of an agent turn
+pub struct AgentEngine<'a> {
+    client: &'a ClaudeClient,
+    tools: Option<&'a ToolManager>,
+    max_iterations: usize,
+    cancel: Option<CancellationToken>,
+    streaming: bool,
+}
+
+impl<'a> AgentEngine<'a> {
+    /// Engine answering with `client` (without tools until [`Self::with_tools`])
+    pub fn new(client: &'a ClaudeClient) -> Self {
+        Self {
+            client,
+            tools: None,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            cancel: None,
+            streaming: false,
+        }
+    }
+
+    /// Run the tools the model calls with `tools`
+    ///
+    /// Without tools, the text of the first response is the answer.
+    pub fn with_tools(mut self, tools: &'a ToolManager) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Give up with [`Error::MaxIterations`] after this many model calls
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Stop with [`Error::Cancelled`] when `cancel` fires, aborting the
+    /// in-flight model call or tools
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Stream answers, reporting text to [`AgentHooks::on_text_delta`]
+    pub fn streaming(mut self, streaming: bool) -> Self {
+        self.streaming = streaming;
+        self
+    }
+
+    /// Run `request` until the model answers without calling tools
+    ///
+    /// `request` carries the system prompt, history and tool definitions; its
+    /// messages are extended with the tool calls and results of the turn (the
+    /// answer itself is not added).
+    pub async fn run(&self, request: &mut MessagesRequest, hooks: &dyn AgentHooks) -> Result<AgentRun> {
+        let cancel = self.cancel.clone().unwrap_or_default();
+        tokio::select! {
+            _ = cancel.cancelled() => Err(Error::Cancelled),
+            run = self.run_loop(request, hooks) => run,
+        }
+    }
+
+    async fn run_loop(&self, request: &mut MessagesRequest, hooks: &dyn AgentHooks) -> Result<AgentRun> {
+        let mut usage = TokenUsage::default();
+        let mut tool_calls = Vec::new();
+
+        for iteration in 1..=self.max_iterations {
+            debug!("Agent iteration {}", iteration);
+            hooks.on_iteration(iteration).await;
+
+            let response = self.send(request.clone(), hooks).await?;
+            if let Some(response_usage) = &response.usage {
+                usage.input_tokens += response_usage.input_tokens;
+                usage.output_tokens += response_usage.output_tokens;
+                hooks.on_usage(response_usage).await;
+            }
+
+            let text = response_text(&response);
+            let calls = tool_calls_of(&response);
+            let tools = match self.tools {
+                Some(tools)
+                    if matches!(response.stop_reason.as_str(), "tool_use" | "tool_calls") && !calls.is_empty() =>
+                {
+                    tools
+                }
+                _ => {
+                    return Ok(AgentRun {
+                        text,
+                        iterations: iteration,
+                        usage,
+                        tool_calls,
+                    });
+                }
+            };
+
+            if !text.is_empty() {
+                hooks.on_text(&text).await;
+            }
+            let results = execute_ordered(
+                calls,
+                MAX_CONCURRENT_TOOLS,
+                |call| tools.is_read_only(&call.name),
+                |call| async move {
+                    debug!("Executing tool: {} with input: {:?}", call.name, call.input);
+                    let result = hooks.on_tool_call(&call, tools).await;
+                    (call, result)
+                },
+            )
+            .await;
+
+            let mut contents = Vec::with_capacity(results.len());
+            for (call, result) in results {
+                contents.push(MessageContent::ToolResult {
+                    tool_use_id: call.id.clone(),
+                    content: result.content(),
+                    is_error: result.is_error,
+                });
+                tool_calls.push(ToolCallRecord {
+                    id: call.id,
+                    name: call.name,
+                    input: call.input,
+                    output: result.output,
+                    is_error: result.is_error,
+                });
+            }
+
+            request.messages.push(Message {
+                role: "assistant".to_string(),
+                content: response.content,
+            });
+            request.messages.push(Message {
+                role: "user".to_string(),
+                content: contents,
+            });
+        }
+
+        Err(Error::MaxIterations(self.max_iterations))
+    }
+
+    async fn send(&self, request: MessagesRequest, hooks: &dyn AgentHooks) -> Result<MessagesResponse> {
+        if self.streaming {
+            self.client
+                .messages_stream(request, |delta| hooks.on_text_delta(delta))
+                .await
+        } else {
+            self.client.messages(request).await
+        }
+    }
+}
+
+/// Text blocks of a response, one per line
+pub fn response_text(response: &MessagesResponse) -> String {
+    response
+        .content
+        .iter()
+        .filter_map(|c| match c {
+            MessageContent::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Tool calls of a response, in order
+fn tool_calls_of(response: &MessagesResponse) -> Vec<ToolCall> {
+    response
+        .content
+        .iter()
+        .filter_map(|c| match c {
+            MessageContent::ToolUse { id, name, input } => Some(ToolCall {
+                id: id.clone(),
+                name: name.clone(),
+                input: input.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, LlmConfig};
+    use crate::tool::Tool;
+    use serde_json::{Value, json};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn client(base_url: String) -> ClaudeClient {
+        let config = Config {
+            llm: LlmConfig {
+                api_key: "test-key".to_string(),
+                ..Default::default()
+            },
+            claude_api_key: "test-key".to_string(),
+            claude_model: "test".to_string(),
+            discord_token: None,
+            admin_user_ids: vec![],
+            api: Default::default(),
+            api_key: None,
+            mcp: Default::default(),
+            memory: Default::default(),
+            scheduler: Default::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
+        };
+        ClaudeClient::with_base_url(&config, base_url).unwrap()
+    }
+
+    /// Serve `responses` (message bodies) one per request, returning the request bodies
+    async fn model_server(responses: Vec<Value>) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                        continue;
+                    };
+                    let length = head
+                        .to_ascii_lowercase()
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length:")?.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if n == 0 || body.len() >= length {
+                        requests.push(body.to_string());
+                        break;
+                    }
+                }
+                let body = response.to_string();
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (url, server)
+    }
+
+    fn response(stop_reason: &str, content: Value) -> Value {
+        json!({
+            "id": "msg",
+            "type": "message",
+            "role": "assistant",
+            "content": content,
+            "model": "test",
+            "stop_reason": stop_reason,
+            "usage": {"input_tokens": 10, "output_tokens": 5},
+        })
+    }
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the text"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object", "properties": {"text": {"type": "string"}}})
+        }
+
+        async fn execute(&self, input: Value) -> Result<ToolResult> {
+            Ok(ToolResult::success(input["text"].as_str().unwrap_or_default()))
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl AgentHooks for Recorder {
+        async fn on_iteration(&self, iteration: usize) {
+            self.events.lock().unwrap().push(format!("iteration {}", iteration));
+        }
+
+        async fn on_text(&self, text: &str) {
+            self.events.lock().unwrap().push(format!("text {}", text));
+        }
+
+        async fn on_tool_call(&self, call: &ToolCall, tools: &ToolManager) -> ToolResult {
+            self.events.lock().unwrap().push(format!("tool {}", call.name));
+            if call.name == "forbidden" {
+                return ToolResult::error("not allowed");
+            }
+            tools.execute(&call.name, call.input.clone()).await.unwrap()
+        }
+    }
+
+    fn tools() -> ToolManager {
+        let mut tools = ToolManager::new();
+        tools.register(Arc::new(EchoTool));
+        tools
+    }
+
+    fn request() -> MessagesRequest {
+        MessagesRequest {
+            model: "test".to_string(),
+            max_tokens: 100,
+            system: None,
+            messages: vec![Message::user("hi")],
+            tools: None,
+            thinking: None,
+            tool_choice: None,
+            temperature: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_with_tools() {
+        let (url, server) = model_server(vec![
+            response(
+                "tool_use",
+                json!([
+                    {"type": "text", "text": "Let me check."},
+                    {"type": "tool_use", "id": "t1", "name": "echo", "input": {"text": "pong"}},
+                    {"type": "tool_use", "id": "t2", "name": "forbidden", "input": {}},
+                ]),
+            ),
+            response("end_turn", json!([{"type": "text", "text": "Done: pong"}])),
+        ])
+        .await;
+        let client = client(url);
+        let tools = tools();
+        let hooks = Recorder::default();
+
+        let mut request = request();
+        let run = AgentEngine::new(&client)
+            .with_tools(&tools)
+            .run(&mut request, &hooks)
+            .await
+            .unwrap();
+
+        assert_eq!(run.text, "Done: pong");
+        assert_eq!(run.iterations, 2);
+        assert_eq!(run.usage, TokenUsage { input_tokens: 20, output_tokens: 10 });
+        assert_eq!(run.tool_calls.len(), 2);
+        assert_eq!(run.tool_calls[0].output, "pong");
+        assert!(run.tool_calls[1].is_error);
+        assert_eq!(
+            *hooks.events.lock().unwrap(),
+            vec!["iteration 1", "text Let me check.", "tool echo", "tool forbidden", "iteration 2"]
+        );
+
+        // The tool calls and results are part of the history, in call order
+        assert_eq!(request.messages.len(), 3);
+        let requests = server.await.unwrap();
+        let second: Value = serde_json::from_str(&requests[1]).unwrap();
+        let results = &second["messages"][2]["content"];
+        assert_eq!(results[0]["tool_use_id"], "t1");
+        assert_eq!(results[1]["tool_use_id"], "t2");
+    }
+
+    #[tokio::test]
+    async fn test_max_iterations_and_cancellation() {
+        let tool_use = response(
+            "tool_use",
+            json!([{"type": "tool_use", "id": "t1", "name": "echo", "input": {"text": "again"}}]),
+        );
+        let (url, server) = model_server(vec![tool_use.clone(), tool_use]).await;
+        let client = client(url);
+        let tools = tools();
+
+        let run = AgentEngine::new(&client)
+            .with_tools(&tools)
+            .with_max_iterations(2)
+            .run(&mut request(), &NoHooks)
+            .await;
+        assert!(matches!(run, Err(Error::MaxIterations(2))));
+        server.await.unwrap();
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let run = AgentEngine::new(&client)
+            .with_cancellation(cancel)
+            .run(&mut request(), &NoHooks)
+            .await;
+        assert!(matches!(run, Err(Error::Cancelled)));
+    }
+}
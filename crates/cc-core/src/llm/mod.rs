@@ -1,9 +1,27 @@
 //! LLM API client and types
 //!
-//! Supports both Claude API and OpenAI-compatible APIs (GLM, etc.)
+//! Supports Claude API, OpenAI-compatible APIs (GLM, etc.),
+//! AWS Bedrock and Google Vertex AI.
 
+mod batch;
 mod client;
+mod engine;
+mod retry;
+mod sigv4;
+mod stream;
+mod structured;
+mod tokens;
 mod types;
 
-pub use client::{AgentLoopResult, ClaudeClient, TokenUsage, ToolCall, ToolResult};
+pub use batch::{
+    BatchRequest, BatchRequestCounts, BatchResult, BatchResultType, BatchStatus, MessageBatch,
+    parse_batch_results,
+};
+pub use client::ClaudeClient;
+pub use engine::{
+    AgentEngine, AgentHooks, AgentRun, DEFAULT_MAX_ITERATIONS, NoHooks, TokenUsage, ToolCall, response_text,
+};
+pub use retry::RetryPolicy;
+pub use structured::repair_json;
+pub use tokens::{TokenCount, estimate_text_tokens, estimate_tokens};
 pub use types::*;
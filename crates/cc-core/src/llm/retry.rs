@@ -0,0 +1,228 @@
+//! Retry policy for transient LLM API failures
+//!
+//! 429 / 5xx / 529 (overloaded) を指数バックオフ + ジッターで再試行します。
+//! `retry-after` ヘッダーがある場合はその値を優先します。
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Retry policy configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Maximum number of retries (0 disables retrying)
+    pub max_retries: u32,
+    /// Backoff before the first retry, in milliseconds
+    pub initial_backoff_ms: u64,
+    /// Upper bound for a single backoff, in milliseconds
+    pub max_backoff_ms: u64,
+    /// Total time budget across all attempts, in milliseconds
+    pub max_elapsed_ms: u64,
+    /// Apply random jitter to computed backoffs
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 30_000,
+            max_elapsed_ms: 120_000,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a response status/body indicates a transient failure
+    pub fn is_retryable(status: StatusCode, body: &str) -> bool {
+        matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504 | 529)
+            || body.contains("overloaded_error")
+    }
+
+    /// Compute the delay before retry number `attempt` (1-based)
+    ///
+    /// A server-provided `retry_after` wins over the computed backoff,
+    /// but is still capped by `max_backoff_ms`.
+    pub fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let max = Duration::from_millis(self.max_backoff_ms);
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(max);
+        }
+
+        let exp = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(20));
+        let capped = exp.min(self.max_backoff_ms);
+
+        let millis = if self.jitter {
+            // Equal jitter: half fixed, half random
+            let half = capped / 2;
+            half + random_below(half + 1)
+        } else {
+            capped
+        };
+
+        Duration::from_millis(millis)
+    }
+}
+
+/// Parse `retry-after-ms` / `retry-after` (seconds) headers
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(ms) = headers
+        .get("retry-after-ms")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+    {
+        return Some(Duration::from_millis(ms.max(0.0) as u64));
+    }
+
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .map(|secs| Duration::from_millis((secs.max(0.0) * 1000.0) as u64))
+}
+
+/// Anthropic rate limits whose remaining fraction is below `threshold`
+///
+/// Reads the `anthropic-ratelimit-<kind>-limit` / `-remaining` / `-reset` headers
+/// and returns one line per low limit, or None if all are above the threshold.
+pub fn low_rate_limits(headers: &HeaderMap, threshold: f64) -> Option<String> {
+    let header = |name: String| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let lines: Vec<String> = ["requests", "tokens", "input-tokens", "output-tokens"]
+        .iter()
+        .filter_map(|kind| {
+            let limit: f64 = header(format!("anthropic-ratelimit-{}-limit", kind))?
+                .parse()
+                .ok()?;
+            let remaining: f64 = header(format!("anthropic-ratelimit-{}-remaining", kind))?
+                .parse()
+                .ok()?;
+            if limit <= 0.0 || remaining / limit >= threshold {
+                return None;
+            }
+            let reset = header(format!("anthropic-ratelimit-{}-reset", kind))
+                .map(|reset| format!(" (resets {})", reset))
+                .unwrap_or_default();
+            Some(format!("{}: {}/{} remaining{}", kind, remaining, limit, reset))
+        })
+        .collect();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+fn random_below(bound: u64) -> u64 {
+    if bound == 0 {
+        return 0;
+    }
+    (uuid::Uuid::new_v4().as_u128() % bound as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_jitter() -> RetryPolicy {
+        RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(RetryPolicy::is_retryable(StatusCode::TOO_MANY_REQUESTS, ""));
+        assert!(RetryPolicy::is_retryable(StatusCode::INTERNAL_SERVER_ERROR, ""));
+        assert!(RetryPolicy::is_retryable(StatusCode::from_u16(529).unwrap(), ""));
+        assert!(RetryPolicy::is_retryable(
+            StatusCode::OK,
+            r#"{"type":"error","error":{"type":"overloaded_error"}}"#
+        ));
+        assert!(!RetryPolicy::is_retryable(StatusCode::BAD_REQUEST, "invalid_request_error"));
+        assert!(!RetryPolicy::is_retryable(StatusCode::UNAUTHORIZED, ""));
+    }
+
+    #[test]
+    fn test_backoff_exponential_and_capped() {
+        let policy = no_jitter();
+        assert_eq!(policy.backoff(1, None), Duration::from_millis(1_000));
+        assert_eq!(policy.backoff(2, None), Duration::from_millis(2_000));
+        assert_eq!(policy.backoff(3, None), Duration::from_millis(4_000));
+        assert_eq!(policy.backoff(10, None), Duration::from_millis(30_000));
+    }
+
+    #[test]
+    fn test_backoff_jitter_range() {
+        let policy = RetryPolicy::default();
+        for _ in 0..50 {
+            let delay = policy.backoff(2, None);
+            assert!(delay >= Duration::from_millis(1_000));
+            assert!(delay <= Duration::from_millis(2_000));
+        }
+    }
+
+    #[test]
+    fn test_backoff_honors_retry_after() {
+        let policy = no_jitter();
+        assert_eq!(
+            policy.backoff(1, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            policy.backoff(1, Some(Duration::from_secs(600))),
+            Duration::from_millis(30_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert!(parse_retry_after(&headers).is_none());
+
+        headers.insert("retry-after", "3".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(3)));
+
+        headers.insert("retry-after-ms", "1500".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_retry_policy_partial_toml() {
+        let policy: RetryPolicy = toml::from_str("max_retries = 5").unwrap();
+        assert_eq!(policy.max_retries, 5);
+        assert_eq!(policy.initial_backoff_ms, 1_000);
+        assert!(policy.jitter);
+    }
+
+    #[test]
+    fn test_low_rate_limits() {
+        let mut headers = HeaderMap::new();
+        headers.insert("anthropic-ratelimit-requests-limit", "100".parse().unwrap());
+        headers.insert("anthropic-ratelimit-requests-remaining", "50".parse().unwrap());
+        headers.insert("anthropic-ratelimit-tokens-limit", "10000".parse().unwrap());
+        headers.insert("anthropic-ratelimit-tokens-remaining", "500".parse().unwrap());
+        headers.insert(
+            "anthropic-ratelimit-tokens-reset",
+            "2026-01-01T00:00:00Z".parse().unwrap(),
+        );
+
+        let low = low_rate_limits(&headers, 0.1).unwrap();
+        assert_eq!(low, "tokens: 500/10000 remaining (resets 2026-01-01T00:00:00Z)");
+        assert!(low_rate_limits(&headers, 0.01).is_none());
+        assert!(low_rate_limits(&HeaderMap::new(), 0.1).is_none());
+    }
+}
@@ -0,0 +1,218 @@
+//! AWS Signature Version 4 signing
+//!
+//! Bedrock Runtime へのリクエスト署名に使用する最小限の SigV4 実装です。
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// AWS credentials used for signing
+#[derive(Debug, Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Load credentials from the standard AWS environment variables
+    pub fn from_env() -> Option<Self> {
+        let access_key_id = std::env::var("AWS_ACCESS_KEY_ID").ok()?;
+        let secret_access_key = std::env::var("AWS_SECRET_ACCESS_KEY").ok()?;
+        let session_token = std::env::var("AWS_SESSION_TOKEN")
+            .ok()
+            .filter(|t| !t.is_empty());
+
+        Some(Self {
+            access_key_id,
+            secret_access_key,
+            session_token,
+        })
+    }
+}
+
+/// A request to be signed
+pub struct SigningRequest<'a> {
+    pub method: &'a str,
+    pub host: &'a str,
+    /// URI path, already percent-encoded once
+    pub path: &'a str,
+    pub region: &'a str,
+    pub service: &'a str,
+    pub payload: &'a [u8],
+    /// Extra headers to sign (lowercase names)
+    pub headers: Vec<(String, String)>,
+}
+
+/// Sign a request and return the headers to attach
+/// (`x-amz-date`, optional `x-amz-security-token`, `authorization`).
+pub fn sign(
+    request: &SigningRequest<'_>,
+    credentials: &AwsCredentials,
+    amz_date: &str,
+) -> Vec<(String, String)> {
+    let date_stamp = &amz_date[..8];
+
+    let mut headers: Vec<(String, String)> = request
+        .headers
+        .iter()
+        .map(|(k, v)| (k.to_lowercase(), v.trim().to_string()))
+        .collect();
+    headers.push(("host".to_string(), request.host.to_string()));
+    headers.push(("x-amz-date".to_string(), amz_date.to_string()));
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    headers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let payload_hash = hex::encode(Sha256::digest(request.payload));
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        request.method,
+        canonical_uri(request.path),
+        canonical_headers,
+        signed_headers,
+        payload_hash
+    );
+
+    let scope = format!(
+        "{}/{}/{}/aws4_request",
+        date_stamp, request.region, request.service
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let k_date = hmac(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date_stamp.as_bytes(),
+    );
+    let k_region = hmac(&k_date, request.region.as_bytes());
+    let k_service = hmac(&k_region, request.service.as_bytes());
+    let k_signing = hmac(&k_service, b"aws4_request");
+    let signature = hex::encode(hmac(&k_signing, string_to_sign.as_bytes()));
+
+    let mut out = vec![("x-amz-date".to_string(), amz_date.to_string())];
+    if let Some(token) = &credentials.session_token {
+        out.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    out.push((
+        "authorization".to_string(),
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    out
+}
+
+/// Percent-encode a single path segment per RFC 3986 (unreserved chars kept)
+pub fn uri_encode(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for b in segment.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Non-S3 services expect each path segment to be encoded a second time
+fn canonical_uri(path: &str) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/').map(uri_encode).collect::<Vec<_>>().join("/")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    #[test]
+    fn test_sign_get_vanilla() {
+        // AWS SigV4 test suite: get-vanilla
+        let request = SigningRequest {
+            method: "GET",
+            host: "example.amazonaws.com",
+            path: "/",
+            region: "us-east-1",
+            service: "service",
+            payload: b"",
+            headers: vec![],
+        };
+
+        let headers = sign(&request, &example_credentials(), "20150830T123600Z");
+        let auth = headers
+            .iter()
+            .find(|(k, _)| k == "authorization")
+            .map(|(_, v)| v.as_str())
+            .unwrap();
+
+        assert_eq!(
+            auth,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_sign_includes_session_token() {
+        let mut credentials = example_credentials();
+        credentials.session_token = Some("token".to_string());
+
+        let request = SigningRequest {
+            method: "POST",
+            host: "bedrock-runtime.us-east-1.amazonaws.com",
+            path: "/model/x/invoke",
+            region: "us-east-1",
+            service: "bedrock",
+            payload: b"{}",
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+        };
+
+        let headers = sign(&request, &credentials, "20240101T000000Z");
+        assert!(headers.iter().any(|(k, v)| k == "x-amz-security-token" && v == "token"));
+        let auth = &headers.last().unwrap().1;
+        assert!(auth.contains("SignedHeaders=content-type;host;x-amz-date;x-amz-security-token"));
+    }
+
+    #[test]
+    fn test_canonical_uri_double_encodes() {
+        let path = format!("/model/{}/invoke", uri_encode("anthropic.claude-v2:1"));
+        assert_eq!(path, "/model/anthropic.claude-v2%3A1/invoke");
+        assert_eq!(canonical_uri(&path), "/model/anthropic.claude-v2%253A1/invoke");
+    }
+}
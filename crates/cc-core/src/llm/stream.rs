@@ -0,0 +1,325 @@
+//! Streaming responses
+//!
+//! Messages API の Server-Sent Events を受け取り、テキストの差分を
+//! コールバックに渡しながら最終的な [`MessagesResponse`] を組み立てます。
+//! ストリーミングに対応していないプロバイダーでは通常のリクエストを送り、
+//! 応答全体を 1 つの差分として渡します。
+
+use futures::StreamExt;
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::error::{Error, Result};
+
+use super::client::ClaudeClient;
+use super::types::*;
+
+impl ClaudeClient {
+    /// Send a request and stream the reply
+    ///
+    /// `on_text` is called with each piece of answer text as it arrives.
+    /// Returns the complete response once the stream ends.
+    pub async fn messages_stream<F>(&self, request: MessagesRequest, mut on_text: F) -> Result<MessagesResponse>
+    where
+        F: FnMut(&str) + Send,
+    {
+        if !self.supports_streaming() {
+            let response = self.messages(request).await?;
+            for content in &response.content {
+                if let MessageContent::Text { text } = content {
+                    on_text(text);
+                }
+            }
+            return Ok(response);
+        }
+
+        let mut body = serde_json::to_value(&request)?;
+        if let Some(obj) = body.as_object_mut() {
+            obj.insert("stream".to_string(), Value::Bool(true));
+        }
+
+        debug!("Sending streaming request to Claude API");
+
+        let response = self
+            .anthropic_request(reqwest::Method::POST, "/messages")
+            .json(&body)
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.map_err(Error::Http)?;
+            warn!("Claude API streaming error: {} - {}", status, text);
+            return Err(Error::ClaudeApi(format!("{}: {}", status, text)));
+        }
+
+        let mut accumulator = StreamAccumulator::default();
+        let mut buffer = String::new();
+        let mut bytes = response.bytes_stream();
+        while let Some(chunk) = bytes.next().await {
+            let chunk = chunk.map_err(Error::Http)?;
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+            // Events are separated by a blank line
+            while let Some(end) = buffer.find("\n\n") {
+                let event: String = buffer.drain(..end + 2).collect();
+                if let Some(text) = accumulator.apply_event(&event)? {
+                    on_text(&text);
+                }
+            }
+        }
+
+        let parsed = accumulator.finish()?;
+        info!(
+            "Claude API streamed response: stop_reason={:?}, tokens={}",
+            parsed.stop_reason,
+            parsed.usage.as_ref().map(|u| u.output_tokens).unwrap_or(0)
+        );
+        Ok(parsed)
+    }
+}
+
+/// Content block being received
+enum PartialBlock {
+    Text(String),
+    ToolUse { id: String, name: String, json: String },
+    Thinking { thinking: String, signature: Option<String> },
+    RedactedThinking(String),
+}
+
+/// Builds a response from stream events
+#[derive(Default)]
+struct StreamAccumulator {
+    id: String,
+    model: String,
+    blocks: Vec<PartialBlock>,
+    stop_reason: Option<String>,
+    stop_sequence: Option<String>,
+    usage: Usage,
+    done: bool,
+}
+
+impl StreamAccumulator {
+    /// Apply one SSE event, returning new answer text if it carried any
+    fn apply_event(&mut self, event: &str) -> Result<Option<String>> {
+        let data: String = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim_start)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let data: Value = serde_json::from_str(&data)?;
+
+        match data["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                let message = &data["message"];
+                self.id = message["id"].as_str().unwrap_or_default().to_string();
+                self.model = message["model"].as_str().unwrap_or_default().to_string();
+                self.add_usage(&message["usage"]);
+            }
+            "content_block_start" => {
+                let block = &data["content_block"];
+                let block = match block["type"].as_str().unwrap_or_default() {
+                    "tool_use" => PartialBlock::ToolUse {
+                        id: block["id"].as_str().unwrap_or_default().to_string(),
+                        name: block["name"].as_str().unwrap_or_default().to_string(),
+                        json: String::new(),
+                    },
+                    "thinking" => PartialBlock::Thinking {
+                        thinking: block["thinking"].as_str().unwrap_or_default().to_string(),
+                        signature: None,
+                    },
+                    "redacted_thinking" => {
+                        PartialBlock::RedactedThinking(block["data"].as_str().unwrap_or_default().to_string())
+                    }
+                    _ => PartialBlock::Text(block["text"].as_str().unwrap_or_default().to_string()),
+                };
+                self.blocks.push(block);
+            }
+            "content_block_delta" => {
+                let delta = &data["delta"];
+                let index = data["index"].as_u64().unwrap_or_default() as usize;
+                let Some(block) = self.blocks.get_mut(index) else {
+                    return Ok(None);
+                };
+                match (block, delta["type"].as_str().unwrap_or_default()) {
+                    (PartialBlock::Text(text), "text_delta") => {
+                        let piece = delta["text"].as_str().unwrap_or_default();
+                        text.push_str(piece);
+                        return Ok(Some(piece.to_string()).filter(|p| !p.is_empty()));
+                    }
+                    (PartialBlock::ToolUse { json, .. }, "input_json_delta") => {
+                        json.push_str(delta["partial_json"].as_str().unwrap_or_default());
+                    }
+                    (PartialBlock::Thinking { thinking, .. }, "thinking_delta") => {
+                        thinking.push_str(delta["thinking"].as_str().unwrap_or_default());
+                    }
+                    (PartialBlock::Thinking { signature, .. }, "signature_delta") => {
+                        *signature = delta["signature"].as_str().map(str::to_string);
+                    }
+                    _ => {}
+                }
+            }
+            "message_delta" => {
+                let delta = &data["delta"];
+                if let Some(reason) = delta["stop_reason"].as_str() {
+                    self.stop_reason = Some(reason.to_string());
+                }
+                if let Some(sequence) = delta["stop_sequence"].as_str() {
+                    self.stop_sequence = Some(sequence.to_string());
+                }
+                self.add_usage(&data["usage"]);
+            }
+            "message_stop" => self.done = true,
+            "error" => {
+                return Err(Error::ClaudeApi(format!(
+                    "Stream error: {}",
+                    data["error"]["message"].as_str().unwrap_or("unknown error")
+                )));
+            }
+            // content_block_stop, ping
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// Add the token counts present in a usage object
+    fn add_usage(&mut self, usage: &Value) {
+        let count = |key: &str| usage[key].as_u64();
+        if let Some(tokens) = count("input_tokens") {
+            self.usage.input_tokens = tokens;
+        }
+        if let Some(tokens) = count("output_tokens") {
+            self.usage.output_tokens = tokens;
+        }
+        if let Some(tokens) = count("cache_read_input_tokens") {
+            self.usage.cache_read_tokens = tokens;
+        }
+        if let Some(tokens) = count("cache_creation_input_tokens") {
+            self.usage.cache_write_tokens = tokens;
+        }
+    }
+
+    /// The complete response
+    fn finish(self) -> Result<MessagesResponse> {
+        if !self.done {
+            return Err(Error::ClaudeApi("Stream ended before message_stop".to_string()));
+        }
+
+        let content = self
+            .blocks
+            .into_iter()
+            .map(|block| match block {
+                PartialBlock::Text(text) => MessageContent::Text { text },
+                PartialBlock::ToolUse { id, name, json } => MessageContent::ToolUse {
+                    id,
+                    name,
+                    input: if json.trim().is_empty() {
+                        Value::Object(Default::default())
+                    } else {
+                        serde_json::from_str(&json).unwrap_or(Value::Object(Default::default()))
+                    },
+                },
+                PartialBlock::Thinking { thinking, signature } => MessageContent::Thinking { thinking, signature },
+                PartialBlock::RedactedThinking(data) => MessageContent::RedactedThinking { data },
+            })
+            .collect();
+
+        Ok(MessagesResponse {
+            id: self.id,
+            response_type: "message".to_string(),
+            role: "assistant".to_string(),
+            content,
+            model: self.model,
+            stop_sequence: self.stop_sequence,
+            stop_reason: self.stop_reason.unwrap_or_else(|| "end_turn".to_string()),
+            usage: Some(self.usage),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, data: Value) -> String {
+        format!("event: {}\ndata: {}\n\n", name, data)
+    }
+
+    #[test]
+    fn test_accumulate_text_and_tool_use() {
+        let events = [
+            event(
+                "message_start",
+                serde_json::json!({"type": "message_start", "message": {"id": "msg_1", "model": "claude", "usage": {"input_tokens": 12, "output_tokens": 1}}}),
+            ),
+            event(
+                "content_block_start",
+                serde_json::json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            ),
+            event(
+                "content_block_delta",
+                serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hello"}}),
+            ),
+            event("ping", serde_json::json!({"type": "ping"})),
+            event(
+                "content_block_delta",
+                serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": " world"}}),
+            ),
+            event(
+                "content_block_start",
+                serde_json::json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "tu_1", "name": "search", "input": {}}}),
+            ),
+            event(
+                "content_block_delta",
+                serde_json::json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{\"q\": "}}),
+            ),
+            event(
+                "content_block_delta",
+                serde_json::json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "\"rust\"}"}}),
+            ),
+            event(
+                "message_delta",
+                serde_json::json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 30}}),
+            ),
+            event("message_stop", serde_json::json!({"type": "message_stop"})),
+        ];
+
+        let mut accumulator = StreamAccumulator::default();
+        let mut streamed = String::new();
+        for event in &events {
+            if let Some(text) = accumulator.apply_event(event).unwrap() {
+                streamed.push_str(&text);
+            }
+        }
+        assert_eq!(streamed, "Hello world");
+
+        let response = accumulator.finish().unwrap();
+        assert_eq!(response.id, "msg_1");
+        assert_eq!(response.stop_reason, "tool_use");
+        let usage = response.usage.unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (12, 30));
+        assert!(matches!(&response.content[0], MessageContent::Text { text } if text == "Hello world"));
+        assert!(matches!(
+            &response.content[1],
+            MessageContent::ToolUse { name, input, .. } if name == "search" && input["q"] == "rust"
+        ));
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        let mut accumulator = StreamAccumulator::default();
+        let error = event(
+            "error",
+            serde_json::json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}),
+        );
+        assert!(accumulator.apply_event(&error).is_err());
+
+        // A stream cut off before message_stop is incomplete
+        assert!(StreamAccumulator::default().finish().is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! Structured output (JSON mode) helper
+//!
+//! JSON スキーマを持つツールを強制的に呼び出させることで、
+//! 機械可読な出力を安定して取得します。パースに失敗した場合は
+//! エラー内容をモデルに返して再試行します。
+
+use serde::de::DeserializeOwned;
+use tracing::{debug, warn};
+
+use crate::error::{Error, Result};
+
+use super::client::ClaudeClient;
+use super::types::*;
+
+/// Name of the synthetic tool used to carry structured output
+pub const STRUCTURED_OUTPUT_TOOL: &str = "structured_output";
+
+/// Default number of attempts for structured requests
+pub const DEFAULT_STRUCTURED_ATTEMPTS: usize = 3;
+
+impl ClaudeClient {
+    /// Send a request and deserialize the reply into `T`
+    ///
+    /// `schema` is a JSON Schema describing `T`. The model is forced to call a
+    /// `structured_output` tool with that schema; if a provider ignores the
+    /// tool and replies with text, the JSON is extracted from the text instead.
+    pub async fn messages_structured<T: DeserializeOwned>(
+        &self,
+        request: MessagesRequest,
+        schema: serde_json::Value,
+    ) -> Result<T> {
+        self.messages_structured_with_attempts(request, schema, DEFAULT_STRUCTURED_ATTEMPTS)
+            .await
+    }
+
+    /// Same as [`messages_structured`](Self::messages_structured) with a custom attempt count
+    pub async fn messages_structured_with_attempts<T: DeserializeOwned>(
+        &self,
+        mut request: MessagesRequest,
+        schema: serde_json::Value,
+        max_attempts: usize,
+    ) -> Result<T> {
+        request.tools = Some(vec![ToolDefinition::new(
+            STRUCTURED_OUTPUT_TOOL,
+            "Return the final answer as structured data matching the input schema.",
+            schema,
+        )]);
+        request.tool_choice = Some(ToolChoice::Tool {
+            name: STRUCTURED_OUTPUT_TOOL.to_string(),
+        });
+        // Forced tool use is not compatible with extended thinking
+        request.thinking = None;
+
+        let mut last_error = String::new();
+
+        for attempt in 1..=max_attempts.max(1) {
+            let response = self.messages(request.clone()).await?;
+            let (tool_use_id, value) = extract_structured_value(&response.content);
+
+            let parsed = match value {
+                Some(value) => serde_json::from_value::<T>(value).map_err(|e| e.to_string()),
+                None => Err("response did not contain JSON output".to_string()),
+            };
+
+            match parsed {
+                Ok(output) => {
+                    debug!("Structured output parsed on attempt {}", attempt);
+                    return Ok(output);
+                }
+                Err(e) => {
+                    warn!("Structured output attempt {} failed: {}", attempt, e);
+                    last_error = e;
+                }
+            }
+
+            // Feed the error back so the model can correct itself
+            let feedback = format!(
+                "The output was invalid: {}. Respond again with output that matches the schema exactly.",
+                last_error
+            );
+            request.messages.push(Message {
+                role: "assistant".to_string(),
+                content: response.content.clone(),
+            });
+            request.messages.push(match tool_use_id {
+                Some(id) => Message {
+                    role: "user".to_string(),
+                    content: vec![MessageContent::ToolResult {
+                        tool_use_id: id,
+                        content: feedback.into(),
+                        is_error: true,
+                    }],
+                },
+                None => Message::user(feedback),
+            });
+        }
+
+        Err(Error::ClaudeApi(format!(
+            "Structured output failed after {} attempts: {}",
+            max_attempts.max(1),
+            last_error
+        )))
+    }
+}
+
+/// Pull the structured value out of a response
+///
+/// Prefers the `structured_output` tool input; falls back to JSON found in
+/// text blocks. Returns the tool_use id (if any) alongside the value.
+fn extract_structured_value(
+    content: &[MessageContent],
+) -> (Option<String>, Option<serde_json::Value>) {
+    for block in content {
+        if let MessageContent::ToolUse { id, name, input } = block {
+            if name == STRUCTURED_OUTPUT_TOOL {
+                return (Some(id.clone()), Some(input.clone()));
+            }
+        }
+    }
+
+    let text = content
+        .iter()
+        .filter_map(|c| match c {
+            MessageContent::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    (None, repair_json(&text))
+}
+
+/// Best-effort extraction of a JSON value from free-form model text
+///
+/// Handles code fences, leading/trailing prose, and trailing commas.
+pub fn repair_json(text: &str) -> Option<serde_json::Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    // Strip ```json ... ``` fences
+    let unfenced = if let Some(start) = trimmed.find("```") {
+        let after = &trimmed[start + 3..];
+        let after = after.strip_prefix("json").unwrap_or(after);
+        match after.find("```") {
+            Some(end) => &after[..end],
+            None => after,
+        }
+    } else {
+        trimmed
+    };
+
+    // Take the outermost object or array
+    let start = unfenced.find(['{', '['])?;
+    let close = if unfenced.as_bytes()[start] == b'{' { '}' } else { ']' };
+    let end = unfenced.rfind(close)?;
+    if end <= start {
+        return None;
+    }
+    let candidate = &unfenced[start..=end];
+
+    if let Ok(value) = serde_json::from_str(candidate) {
+        return Some(value);
+    }
+
+    // Remove trailing commas before closing brackets
+    let mut cleaned = String::with_capacity(candidate.len());
+    let chars: Vec<char> = candidate.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        cleaned.push(c);
+    }
+
+    serde_json::from_str(&cleaned).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_repair_json_plain() {
+        assert_eq!(repair_json(r#"{"a": 1}"#), Some(json!({"a": 1})));
+    }
+
+    #[test]
+    fn test_repair_json_code_fence_and_prose() {
+        let text = "Here you go:\n```json\n{\"items\": [1, 2]}\n```\nLet me know!";
+        assert_eq!(repair_json(text), Some(json!({"items": [1, 2]})));
+    }
+
+    #[test]
+    fn test_repair_json_trailing_comma() {
+        let text = r#"Result: {"a": [1, 2,], "b": "x",}"#;
+        assert_eq!(repair_json(text), Some(json!({"a": [1, 2], "b": "x"})));
+    }
+
+    #[test]
+    fn test_repair_json_none() {
+        assert_eq!(repair_json("no json here"), None);
+    }
+
+    #[test]
+    fn test_extract_prefers_tool_use() {
+        let content = vec![
+            MessageContent::Text {
+                text: r#"{"ignored": true}"#.to_string(),
+            },
+            MessageContent::ToolUse {
+                id: "toolu_1".to_string(),
+                name: STRUCTURED_OUTPUT_TOOL.to_string(),
+                input: json!({"answer": 42}),
+            },
+        ];
+
+        let (id, value) = extract_structured_value(&content);
+        assert_eq!(id.as_deref(), Some("toolu_1"));
+        assert_eq!(value, Some(json!({"answer": 42})));
+    }
+
+    #[test]
+    fn test_extract_falls_back_to_text() {
+        let content = vec![MessageContent::Text {
+            text: "```json\n{\"answer\": 42}\n```".to_string(),
+        }];
+
+        let (id, value) = extract_structured_value(&content);
+        assert!(id.is_none());
+        assert_eq!(value, Some(json!({"answer": 42})));
+    }
+}
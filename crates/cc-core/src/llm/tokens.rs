@@ -0,0 +1,171 @@
+//! Token counting
+//!
+//! Claude の count_tokens API を使って正確な入力トークン数を取得します。
+//! API が使えないプロバイダーやエラー時はローカルの概算値にフォールバックします。
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::error::Result;
+
+use super::client::ClaudeClient;
+use super::types::*;
+
+/// Result of a token count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenCount {
+    /// Number of input tokens the request would consume
+    pub input_tokens: u64,
+    /// True when the value is a local heuristic rather than an API count
+    pub estimated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountTokensResponse {
+    input_tokens: u64,
+}
+
+impl ClaudeClient {
+    /// Count input tokens for a request
+    ///
+    /// Uses `/messages/count_tokens` on the Claude provider and falls back to
+    /// [`estimate_tokens`] for other providers or when the API call fails.
+    pub async fn count_tokens(&self, request: &MessagesRequest) -> Result<TokenCount> {
+        if self.ensure_anthropic_api("Token counting").is_err() {
+            return Ok(estimate_tokens(request));
+        }
+
+        let mut body = serde_json::to_value(request)?;
+        if let Some(obj) = body.as_object_mut() {
+            // count_tokens does not accept max_tokens
+            obj.remove("max_tokens");
+        }
+
+        let response = self
+            .send_with_retry("Claude count_tokens", || {
+                self.anthropic_request(Method::POST, "/messages/count_tokens")
+                    .json(&body)
+            })
+            .await;
+
+        match response.and_then(|text| Ok(serde_json::from_str::<CountTokensResponse>(&text)?)) {
+            Ok(parsed) => {
+                debug!("count_tokens: {}", parsed.input_tokens);
+                Ok(TokenCount {
+                    input_tokens: parsed.input_tokens,
+                    estimated: false,
+                })
+            }
+            Err(e) => {
+                warn!("count_tokens failed, using estimate: {}", e);
+                Ok(estimate_tokens(request))
+            }
+        }
+    }
+}
+
+/// Estimate input tokens locally
+///
+/// Roughly 4 characters per token for ASCII text and ~1 token per
+/// non-ASCII character (CJK), plus fixed overhead for images and tools.
+pub fn estimate_tokens(request: &MessagesRequest) -> TokenCount {
+    let mut total: u64 = 0;
+
+    if let Some(system) = &request.system {
+        total += estimate_text_tokens(system);
+    }
+
+    for message in &request.messages {
+        // Per-message framing overhead
+        total += 4;
+        for block in &message.content {
+            total += estimate_block_tokens(block);
+        }
+    }
+
+    if let Some(tools) = &request.tools {
+        for tool in tools {
+            total += estimate_text_tokens(&tool.name)
+                + estimate_text_tokens(&tool.description)
+                + estimate_text_tokens(&tool.input_schema.to_string());
+        }
+    }
+
+    TokenCount {
+        input_tokens: total,
+        estimated: true,
+    }
+}
+
+fn estimate_block_tokens(block: &MessageContent) -> u64 {
+    match block {
+        MessageContent::Text { text } => estimate_text_tokens(text),
+        // Images are billed by size; ~1600 tokens is a typical upper bound
+        MessageContent::Image { .. } => 1600,
+        // PDFs cost ~1500-3000 tokens per page; assume ~50KB per page
+        MessageContent::Document { source } => {
+            (source.approximate_size() / 50_000 + 1) as u64 * 2000
+        }
+        MessageContent::ToolUse { name, input, .. } => {
+            estimate_text_tokens(name) + estimate_text_tokens(&input.to_string())
+        }
+        MessageContent::ToolResult { content, .. } => match content {
+            ToolResultContent::Text(text) => estimate_text_tokens(text),
+            ToolResultContent::Blocks(blocks) => blocks.iter().map(estimate_block_tokens).sum(),
+        },
+        MessageContent::Thinking { thinking, .. } => estimate_text_tokens(thinking),
+        MessageContent::RedactedThinking { data } => estimate_text_tokens(data),
+    }
+}
+
+/// Estimate tokens for a piece of text
+pub fn estimate_text_tokens(text: &str) -> u64 {
+    let (ascii, other) = text.chars().fold((0u64, 0u64), |(a, o), c| {
+        if c.is_ascii() { (a + 1, o) } else { (a, o + 1) }
+    });
+    ascii.div_ceil(4) + other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_text_tokens() {
+        assert_eq!(estimate_text_tokens(""), 0);
+        assert_eq!(estimate_text_tokens("abcd"), 1);
+        assert_eq!(estimate_text_tokens("abcde"), 2);
+        assert_eq!(estimate_text_tokens("こんにちは"), 5);
+    }
+
+    #[test]
+    fn test_estimate_tokens_request() {
+        let request = MessagesRequestBuilder::new("claude".to_string())
+            .system("abcdefgh")
+            .user("abcd")
+            .build();
+
+        let count = estimate_tokens(&request);
+        assert!(count.estimated);
+        // system (2) + message overhead (4) + text (1)
+        assert_eq!(count.input_tokens, 7);
+    }
+
+    #[test]
+    fn test_estimate_tokens_grows_with_tools() {
+        let base = MessagesRequestBuilder::new("claude".to_string())
+            .user("hello")
+            .build();
+        let with_tool = MessagesRequestBuilder::new("claude".to_string())
+            .user("hello")
+            .tool(ToolDefinition::new(
+                "read",
+                "Read a file",
+                serde_json::json!({"type": "object"}),
+            ))
+            .build();
+
+        assert!(estimate_tokens(&with_tool).input_tokens > estimate_tokens(&base).input_tokens);
+    }
+}
@@ -89,6 +89,8 @@ impl Message {
 pub enum MessageContent {
     Text { text: String },
     Image { source: ImageSource },
+    /// PDF document (read natively by Claude)
+    Document { source: DocumentSource },
     ToolUse {
         id: String,
         name: String,
@@ -96,7 +98,7 @@ pub enum MessageContent {
     },
     ToolResult {
         tool_use_id: String,
-        content: String,
+        content: ToolResultContent,
         #[serde(default)]
         is_error: bool,
     },
@@ -127,6 +129,54 @@ impl MessageContent {
     }
 }
 
+/// Content of a tool result: text, or text and image blocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolResultContent {
+    Text(String),
+    Blocks(Vec<MessageContent>),
+}
+
+impl ToolResultContent {
+    /// `text` followed by `images` (plain text when there are none)
+    pub fn with_images(text: impl Into<String>, images: Vec<ImageSource>) -> Self {
+        let text = text.into();
+        if images.is_empty() {
+            return Self::Text(text);
+        }
+        let mut blocks = vec![MessageContent::Text { text }];
+        blocks.extend(images.into_iter().map(|source| MessageContent::Image { source }));
+        Self::Blocks(blocks)
+    }
+
+    /// The text of the result, without images
+    pub fn text(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|b| match b {
+                    MessageContent::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+impl From<String> for ToolResultContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for ToolResultContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
 /// Image source for multimodal input
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageSource {
@@ -220,6 +270,34 @@ impl ImageSource {
     }
 }
 
+/// Document source for PDF input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentSource {
+    #[serde(rename = "type")]
+    pub source_type: String,
+    pub media_type: String,
+    pub data: String,
+}
+
+impl DocumentSource {
+    /// PDF media type
+    pub const MEDIA_TYPE_PDF: &'static str = "application/pdf";
+
+    /// Create a PDF document source from bytes (encodes to base64)
+    pub fn pdf(bytes: &[u8]) -> Self {
+        Self {
+            source_type: "base64".to_string(),
+            media_type: Self::MEDIA_TYPE_PDF.to_string(),
+            data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes),
+        }
+    }
+
+    /// Get approximate size in bytes (decoded)
+    pub fn approximate_size(&self) -> usize {
+        (self.data.len() * 3) / 4
+    }
+}
+
 /// Tool definition for Claude API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolDefinition {
@@ -242,6 +320,20 @@ impl ToolDefinition {
     }
 }
 
+/// How the model should use the provided tools
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// Model decides whether to use tools
+    Auto,
+    /// Model must use one of the tools
+    Any,
+    /// Model must use the named tool
+    Tool { name: String },
+    /// Model must not use tools
+    None,
+}
+
 /// Thinking configuration for Claude 3.7+ extended thinking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThinkingConfig {
@@ -350,6 +442,12 @@ pub struct MessagesRequest {
     /// Extended thinking configuration (Claude 3.7+)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thinking: Option<ThinkingConfig>,
+    /// Tool selection constraint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    /// Sampling temperature (0.0 - 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
 }
 
 /// Messages API response
@@ -480,6 +578,10 @@ pub struct ChatCompletionRequest {
     pub max_tokens: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<OpenAiTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
 }
 
 impl ChatCompletionRequest {
@@ -502,11 +604,24 @@ impl ChatCompletionRequest {
             t.iter().map(OpenAiTool::from).collect()
         });
 
+        // Convert tool choice
+        let tool_choice = req.tool_choice.as_ref().map(|choice| match choice {
+            ToolChoice::Auto => serde_json::json!("auto"),
+            ToolChoice::Any => serde_json::json!("required"),
+            ToolChoice::None => serde_json::json!("none"),
+            ToolChoice::Tool { name } => serde_json::json!({
+                "type": "function",
+                "function": { "name": name }
+            }),
+        });
+
         Self {
             model: req.model.clone(),
             messages,
             max_tokens: Some(req.max_tokens),
             tools,
+            tool_choice,
+            temperature: req.temperature,
         }
     }
 }
@@ -629,6 +744,8 @@ pub struct MessagesRequestBuilder {
     messages: Vec<Message>,
     tools: Vec<ToolDefinition>,
     thinking: Option<ThinkingConfig>,
+    tool_choice: Option<ToolChoice>,
+    temperature: Option<f32>,
 }
 
 impl MessagesRequestBuilder {
@@ -640,6 +757,8 @@ impl MessagesRequestBuilder {
             messages: vec![],
             tools: vec![],
             thinking: None,
+            tool_choice: None,
+            temperature: None,
         }
     }
 
@@ -701,6 +820,18 @@ impl MessagesRequestBuilder {
         self
     }
 
+    /// Constrain how the model uses tools
+    pub fn tool_choice(mut self, choice: ToolChoice) -> Self {
+        self.tool_choice = Some(choice);
+        self
+    }
+
+    /// Set the sampling temperature
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
     pub fn build(self) -> MessagesRequest {
         MessagesRequest {
             model: self.model,
@@ -713,6 +844,8 @@ impl MessagesRequestBuilder {
                 Some(self.tools)
             },
             thinking: self.thinking,
+            tool_choice: self.tool_choice,
+            temperature: self.temperature,
         }
     }
 }
@@ -964,6 +1097,66 @@ mod tests {
         assert!(json.contains(r#""budget_tokens":8192"#));
     }
 
+    #[test]
+    fn test_tool_choice_serialization() {
+        let choice = ToolChoice::Tool {
+            name: "structured_output".to_string(),
+        };
+        let json = serde_json::to_string(&choice).unwrap();
+        assert_eq!(json, r#"{"type":"tool","name":"structured_output"}"#);
+
+        let json = serde_json::to_string(&ToolChoice::Any).unwrap();
+        assert_eq!(json, r#"{"type":"any"}"#);
+    }
+
+    #[test]
+    fn test_tool_result_content() {
+        let text = MessageContent::ToolResult {
+            tool_use_id: "t1".to_string(),
+            content: "done".into(),
+            is_error: false,
+        };
+        let json = serde_json::to_value(&text).unwrap();
+        assert_eq!(json["content"], "done");
+
+        let content = ToolResultContent::with_images(
+            "{\"status\":\"success\"}",
+            vec![ImageSource::png(b"png")],
+        );
+        let json = serde_json::to_value(MessageContent::ToolResult {
+            tool_use_id: "t2".to_string(),
+            content,
+            is_error: false,
+        })
+        .unwrap();
+        assert_eq!(json["content"][0]["type"], "text");
+        assert_eq!(json["content"][1]["type"], "image");
+        assert_eq!(json["content"][1]["source"]["media_type"], "image/png");
+
+        // Both forms read back (e.g. from stored sessions)
+        let parsed: MessageContent = serde_json::from_value(json).unwrap();
+        let MessageContent::ToolResult { content, .. } = parsed else {
+            panic!("expected a tool result");
+        };
+        assert!(matches!(content, ToolResultContent::Blocks(ref blocks) if blocks.len() == 2));
+        assert_eq!(content.text(), "{\"status\":\"success\"}");
+    }
+
+    #[test]
+    fn test_tool_choice_to_openai() {
+        let request = MessagesRequestBuilder::new("glm-4.7".to_string())
+            .user("Hi")
+            .tool(ToolDefinition::new("f", "d", serde_json::json!({"type": "object"})))
+            .tool_choice(ToolChoice::Tool { name: "f".to_string() })
+            .build();
+
+        let openai = ChatCompletionRequest::from_claude_request(&request);
+        assert_eq!(
+            openai.tool_choice,
+            Some(serde_json::json!({"type": "function", "function": {"name": "f"}}))
+        );
+    }
+
     #[test]
     fn test_thinking_content_serialization() {
         let content = MessageContent::Thinking {
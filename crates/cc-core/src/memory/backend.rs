@@ -0,0 +1,89 @@
+//! Storage backends of memories
+//!
+//! The SQLite [`MemoryStore`] is used by default; with the `postgres` feature
+//! and `[memory].database_url` set, memories are kept in Postgres instead.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::MemoryConfig;
+use crate::memory::{Memory, MemoryStore};
+use crate::Result;
+
+/// Persistent storage of memories
+///
+/// Expired memories are never returned and are deleted by
+/// [`MemoryBackend::purge_expired`].
+#[async_trait]
+pub trait MemoryBackend: Send + Sync {
+    /// Insert or replace a memory
+    async fn save(&self, memory: &Memory) -> Result<()>;
+
+    /// Load a memory by ID
+    async fn load(&self, id: &str) -> Result<Option<Memory>>;
+
+    /// Search memories of every namespace by content, newest first
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<Memory>>;
+
+    /// Search the memories of one namespace by content, newest first
+    async fn search_namespace(&self, namespace: &str, query: &str, limit: usize) -> Result<Vec<Memory>>;
+
+    /// Delete a memory by ID
+    async fn delete(&self, id: &str) -> Result<()>;
+
+    /// List the newest memories whose metadata has `key` set to the string `value`
+    async fn list_by_metadata(&self, key: &str, value: &str, limit: usize) -> Result<Vec<Memory>>;
+
+    /// List the newest memories of a namespace
+    async fn list_namespace(&self, namespace: &str, limit: usize) -> Result<Vec<Memory>>;
+
+    /// List recent memories
+    async fn list_recent(&self, limit: usize) -> Result<Vec<Memory>>;
+
+    /// Delete every memory of a namespace, returning how many were deleted
+    async fn delete_namespace(&self, namespace: &str) -> Result<usize>;
+
+    /// Delete expired memories, returning how many were deleted
+    async fn purge_expired(&self) -> Result<usize>;
+
+    /// Count total memories
+    async fn count(&self) -> Result<usize>;
+
+    /// Clear all memories
+    async fn clear(&self) -> Result<()>;
+}
+
+/// Open the memory backend selected by `config`
+pub fn open_backend(config: &MemoryConfig) -> Result<Arc<dyn MemoryBackend>> {
+    match config.database_url.as_deref() {
+        None => Ok(Arc::new(MemoryStore::new(&config.db_path)?)),
+        #[cfg(feature = "postgres")]
+        Some(url) => Ok(Arc::new(super::PgMemoryStore::new(crate::postgres::create_pool(
+            url,
+            config.pool_size,
+        )?))),
+        #[cfg(not(feature = "postgres"))]
+        Some(_) => Err(crate::Error::Config(
+            "memory.database_url requires a build with the postgres feature".to_string(),
+        )),
+    }
+}
+
+/// Delete expired memories every `interval`
+pub fn spawn_expiry_sweep(store: Arc<dyn MemoryBackend>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match store.purge_expired().await {
+                Ok(0) => {}
+                Ok(n) => info!("Deleted {} expired memories", n),
+                Err(e) => warn!("Failed to delete expired memories: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,397 @@
+//! Automatic memory of facts about users
+//!
+//! After a conversation turn, the [`Memorizer`] asks the model in the
+//! background which durable facts and preferences the user revealed and
+//! stores the new ones in the namespace of the user's identity
+//! (`metadata.source = "memorizer"`). Before a turn,
+//! [`Memorizer::inject`] adds the stored facts most relevant to the message to
+//! the system prompt.
+//!
+//! Identities come from [`MemoryConfig::identity`], so users linked in
+//! `[memory.identities]` share their facts across channels.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, info, warn};
+
+use crate::config::MemoryConfig;
+use crate::llm::{ClaudeClient, Message, MessageContent, MessagesRequest};
+use crate::memory::{Memory, MemoryBackend};
+use crate::Result;
+
+/// `metadata.source` of memories written by the memorizer
+pub const MEMORIZER_SOURCE: &str = "memorizer";
+
+/// Facts of an identity considered when extracting and ranking
+const MAX_KNOWN_FACTS: usize = 200;
+
+/// Known facts shown to the model so it only returns new ones
+const MAX_FACTS_IN_PROMPT: usize = 50;
+
+const EXTRACTION_PROMPT: &str = "You extract durable facts about the user from one conversation turn: \
+who they are, their preferences, ongoing projects, people and things they care about. \
+Ignore one-off requests, the assistant's statements and anything not about the user. \
+Reply with only a JSON array of objects {\"fact\": string, \"kind\": \"fact\" | \"preference\", \"tags\": [string]}. \
+Write each fact as a short third-person statement in the user's language. \
+Leave out facts that are already known. Reply [] when there is nothing new.";
+
+/// A fact returned by the extraction prompt
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct Fact {
+    fact: String,
+    #[serde(default = "default_kind")]
+    kind: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+fn default_kind() -> String {
+    "fact".to_string()
+}
+
+/// Extracts facts about users and recalls them for later turns
+pub struct Memorizer {
+    client: Arc<ClaudeClient>,
+    store: Arc<dyn MemoryBackend>,
+    config: MemoryConfig,
+}
+
+impl Memorizer {
+    pub fn new(client: Arc<ClaudeClient>, store: Arc<dyn MemoryBackend>, config: &MemoryConfig) -> Self {
+        Self {
+            client,
+            store,
+            config: config.clone(),
+        }
+    }
+
+    /// Identity of a channel user (see [`MemoryConfig::identity`])
+    pub fn identity(&self, channel: &str, user_id: &str) -> String {
+        self.config.identity(channel, user_id)
+    }
+
+    /// Stored facts of `identity` most relevant to `query`, best first
+    ///
+    /// Facts sharing words with the query come first; the rest follow newest
+    /// first, so general preferences are included when nothing matches.
+    pub async fn recall(&self, identity: &str, query: &str) -> Result<Vec<Memory>> {
+        let facts = self.store.list_namespace(identity, MAX_KNOWN_FACTS).await?;
+        let query_terms = terms(query);
+        let mut scored: Vec<(usize, Memory)> = facts
+            .into_iter()
+            .map(|memory| (terms(&memory.content).intersection(&query_terms).count(), memory))
+            .collect();
+        // Stable sort keeps the newest-first order among equal scores
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        Ok(scored
+            .into_iter()
+            .take(self.config.memorizer.max_facts)
+            .map(|(_, memory)| memory)
+            .collect())
+    }
+
+    /// Add the facts of `identity` relevant to `query` to the request's system prompt
+    ///
+    /// Failures are logged; the turn goes ahead without the facts.
+    pub async fn inject(&self, request: &mut MessagesRequest, identity: &str, query: &str) {
+        let facts = match self.recall(identity, query).await {
+            Ok(facts) => facts,
+            Err(e) => {
+                warn!("Failed to recall memories of {}: {}", identity, e);
+                return;
+            }
+        };
+        if let Some(section) = facts_section(&facts) {
+            request.system = Some(match request.system.take() {
+                Some(system) => format!("{}\n\n{}", system, section),
+                None => section,
+            });
+        }
+    }
+
+    /// Extract facts from a finished turn in the background
+    pub fn learn(self: &Arc<Self>, identity: String, user: String, assistant: String) {
+        let memorizer = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(e) = memorizer.memorize(&identity, &user, &assistant).await {
+                warn!("Failed to extract memories of {}: {}", identity, e);
+            }
+        });
+    }
+
+    /// Extract facts from a turn and store the new ones, returning how many were stored
+    pub async fn memorize(&self, identity: &str, user: &str, assistant: &str) -> Result<usize> {
+        let known = self.store.list_namespace(identity, MAX_KNOWN_FACTS).await?;
+        let mut prompt = String::new();
+        if !known.is_empty() {
+            prompt.push_str("Already known:\n");
+            for memory in known.iter().take(MAX_FACTS_IN_PROMPT) {
+                prompt.push_str(&format!("- {}\n", memory.content));
+            }
+            prompt.push('\n');
+        }
+        prompt.push_str(&format!("User: {}\n\nAssistant: {}", user, assistant));
+
+        let mut request = self
+            .client
+            .request_builder()
+            .system(EXTRACTION_PROMPT)
+            .max_tokens(1024)
+            .message(Message::user(prompt))
+            .build();
+        if let Some(model) = &self.config.memorizer.model {
+            request.model = model.clone();
+        }
+        let response = self.client.messages(request).await?;
+        let text: String = response
+            .content
+            .iter()
+            .filter_map(|c| match c {
+                MessageContent::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let mut seen: HashSet<String> = known.iter().map(|m| normalize(&m.content)).collect();
+        let mut stored = 0;
+        for fact in parse_facts(&text) {
+            if !seen.insert(normalize(&fact.fact)) {
+                continue;
+            }
+            let mut memory = Memory::new(fact.fact.trim())
+                .in_namespace(identity)
+                .with_metadata(json!({
+                    "source": MEMORIZER_SOURCE,
+                    "kind": fact.kind,
+                    "tags": fact.tags,
+                }));
+            if let Some(days) = self.config.memorizer.ttl_days {
+                memory = memory.expires_in_days(days);
+            }
+            self.store.save(&memory).await?;
+            stored += 1;
+        }
+        if stored > 0 {
+            info!("Remembered {} new facts about {}", stored, identity);
+        } else {
+            debug!("No new facts about {}", identity);
+        }
+        Ok(stored)
+    }
+}
+
+/// System prompt section listing `facts` (None when there are none)
+fn facts_section(facts: &[Memory]) -> Option<String> {
+    if facts.is_empty() {
+        return None;
+    }
+    let mut section = String::from(
+        "## What you remember about the user\nUse these facts when they help; do not list them unprompted.\n",
+    );
+    for memory in facts {
+        section.push_str(&format!("- {}\n", memory.content));
+    }
+    Some(section.trim_end().to_string())
+}
+
+/// Facts in the model's reply, tolerating text or code fences around the array
+fn parse_facts(text: &str) -> Vec<Fact> {
+    let (start, end) = match (text.find('['), text.rfind(']')) {
+        (Some(start), Some(end)) if start < end => (start, end),
+        _ => return Vec::new(),
+    };
+    match serde_json::from_str::<Vec<Fact>>(&text[start..=end]) {
+        Ok(facts) => facts.into_iter().filter(|f| !f.fact.trim().is_empty()).collect(),
+        Err(e) => {
+            debug!("Ignoring unparsable memorizer reply: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Lowercase text with whitespace collapsed, for duplicate detection
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Words of `text` for relevance: alphanumeric words of two or more
+/// characters, and character pairs of text written without spaces (CJK)
+fn terms(text: &str) -> HashSet<String> {
+    let mut terms = HashSet::new();
+    for word in text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.iter().all(char::is_ascii) {
+            if chars.len() >= 2 {
+                terms.insert(word.to_string());
+            }
+        } else {
+            terms.extend(chars.windows(2).map(|pair| pair.iter().collect::<String>()));
+        }
+    }
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, LlmConfig};
+    use crate::llm::MessagesRequestBuilder;
+    use crate::memory::MemoryStore;
+    use std::collections::HashMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn config() -> Config {
+        Config {
+            llm: LlmConfig {
+                api_key: "test-key".to_string(),
+                ..Default::default()
+            },
+            claude_api_key: "test-key".to_string(),
+            claude_model: "claude-sonnet-4-20250514".to_string(),
+            discord_token: None,
+            admin_user_ids: vec![],
+            api: Default::default(),
+            api_key: None,
+            mcp: Default::default(),
+            memory: MemoryConfig {
+                identities: HashMap::from([(
+                    "alice".to_string(),
+                    vec!["discord:1".to_string(), "telegram:2".to_string()],
+                )]),
+                ..Default::default()
+            },
+            scheduler: Default::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
+        }
+    }
+
+    fn memorizer(base_url: Option<String>) -> (Memorizer, Arc<MemoryStore>) {
+        let config = config();
+        let store = Arc::new(MemoryStore::in_memory().unwrap());
+        let client = match base_url {
+            Some(url) => ClaudeClient::with_base_url(&config, url),
+            None => ClaudeClient::new(&config),
+        };
+        let client = Arc::new(client.unwrap());
+        let memorizer = Memorizer::new(client, store.clone(), &config.memory);
+        (memorizer, store)
+    }
+
+    fn fact(identity: &str, content: &str) -> Memory {
+        Memory::new(content)
+            .in_namespace(identity)
+            .with_metadata(json!({"source": MEMORIZER_SOURCE}))
+    }
+
+    #[test]
+    fn test_parse_facts() {
+        let reply = "Here you go:\n```json\n[{\"fact\": \"Alice lives in Osaka\", \"tags\": [\"home\"]}, {\"fact\": \" \"}]\n```";
+        let facts = parse_facts(reply);
+        assert_eq!(facts.len(), 1);
+        assert_eq!(facts[0].fact, "Alice lives in Osaka");
+        assert_eq!(facts[0].kind, "fact");
+        assert!(parse_facts("[]").is_empty());
+        assert!(parse_facts("nothing new").is_empty());
+    }
+
+    #[test]
+    fn test_identity_links_channels() {
+        let (memorizer, _) = memorizer(None);
+        assert_eq!(memorizer.identity("telegram", "2"), "alice");
+        assert_eq!(memorizer.identity("discord", "1"), "alice");
+        assert_eq!(memorizer.identity("discord", "3"), "discord:3");
+    }
+
+    #[tokio::test]
+    async fn test_recall_and_inject() {
+        let (memorizer, store) = memorizer(None);
+        store.save(&fact("alice", "Prefers answers in Rust")).unwrap();
+        store.save(&fact("alice", "東京に住んでいる")).unwrap();
+        store.save(&fact("bob", "Prefers Python")).unwrap();
+
+        let recalled = memorizer.recall("alice", "What's the weather in 東京?").await.unwrap();
+        assert_eq!(recalled.len(), 2);
+        assert_eq!(recalled[0].content, "東京に住んでいる");
+
+        let mut request = MessagesRequestBuilder::new("model".to_string()).system("Be helpful.").build();
+        memorizer.inject(&mut request, "alice", "hello").await;
+        let system = request.system.unwrap();
+        assert!(system.starts_with("Be helpful.\n\n## What you remember"));
+        assert!(system.contains("- Prefers answers in Rust"));
+        assert!(!system.contains("Python"));
+
+        let mut request = MessagesRequestBuilder::new("model".to_string()).build();
+        memorizer.inject(&mut request, "carol", "hello").await;
+        assert!(request.system.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_memorize_stores_new_facts() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("Assistant: Noted") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let reply = json!([
+                {"fact": "Alice lives in Osaka", "kind": "fact", "tags": ["home"]},
+                {"fact": "alice  prefers tea", "kind": "preference"},
+            ]);
+            let body = json!({
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "text", "text": reply.to_string()}],
+                "model": "test",
+                "stop_reason": "end_turn",
+            })
+            .to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let (memorizer, store) = memorizer(Some(url));
+        store.save(&fact("alice", "Alice prefers tea")).unwrap();
+        let stored = memorizer
+            .memorize("alice", "I moved to Osaka last month", "Noted")
+            .await
+            .unwrap();
+        let request = server.await.unwrap();
+
+        // The known fact is shown to the model and not stored twice
+        assert!(request.contains("Alice prefers tea"));
+        assert_eq!(stored, 1);
+        let facts = store.list_namespace("alice", 10).unwrap();
+        assert_eq!(facts.len(), 2);
+        let osaka = facts.iter().find(|m| m.content == "Alice lives in Osaka").unwrap();
+        assert_eq!(osaka.metadata["source"], MEMORIZER_SOURCE);
+        assert_eq!(osaka.metadata["tags"][0], "home");
+    }
+}
@@ -1,10 +1,21 @@
 //! Memory system for cc-core
 //!
 //! This module provides persistent storage for memories/conversations
-//! using SQLite as the backend with optional FTS5 full-text search.
+//! using SQLite as the backend with optional FTS5 full-text search, or
+//! Postgres (`postgres` feature).
 
+mod backend;
+mod memorizer;
+#[cfg(feature = "postgres")]
+mod postgres;
 mod store;
+mod tools;
 mod types;
 
+pub use backend::{open_backend, spawn_expiry_sweep, MemoryBackend};
+pub use memorizer::{Memorizer, MEMORIZER_SOURCE};
+#[cfg(feature = "postgres")]
+pub use postgres::PgMemoryStore;
 pub use store::MemoryStore;
+pub use tools::{register_memory_tools, MEMORY_TOOL_SOURCE};
 pub use types::Memory;
@@ -0,0 +1,290 @@
+//! Memory storage using Postgres (`postgres` feature)
+
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use serde_json::Value as JsonValue;
+use tokio::sync::OnceCell;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Row;
+use tracing::{debug, info};
+
+use crate::memory::{Memory, MemoryBackend};
+use crate::migrate::Migration;
+use crate::postgres;
+use crate::Result;
+
+/// Columns read by [`memory_from_row`]
+const COLUMNS: &str = "id, content, metadata, created_at, namespace, expires_at";
+
+/// Condition excluding expired memories
+const NOT_EXPIRED: &str = "(expires_at IS NULL OR expires_at > now())";
+
+/// Schema versions of the `memories` table
+const MIGRATIONS: &[Migration] = &[
+    Migration::sql(
+        1,
+        "create memories",
+        "CREATE TABLE IF NOT EXISTS memories (
+            id TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            metadata JSONB,
+            created_at TIMESTAMPTZ NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_memories_created_at ON memories(created_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_memories_content ON memories USING GIN (to_tsvector('simple', content));",
+    ),
+    Migration::sql(
+        2,
+        "add namespace and expiry",
+        "ALTER TABLE memories ADD COLUMN IF NOT EXISTS namespace TEXT;
+        ALTER TABLE memories ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+        UPDATE memories SET namespace = metadata ->> 'identity' WHERE metadata ->> 'identity' IS NOT NULL;
+        CREATE INDEX IF NOT EXISTS idx_memories_namespace ON memories(namespace, created_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_memories_expires_at ON memories(expires_at);",
+    ),
+];
+
+/// Postgres-based storage for memories
+///
+/// Search uses the full-text index and falls back to a substring match when
+/// it finds nothing (the `simple` configuration does not split text without
+/// spaces, such as Japanese, into words).
+pub struct PgMemoryStore {
+    pool: Pool,
+    schema: OnceCell<()>,
+}
+
+impl PgMemoryStore {
+    /// The schema is created or upgraded on first use
+    pub fn new(pool: Pool) -> Self {
+        Self {
+            pool,
+            schema: OnceCell::new(),
+        }
+    }
+
+    async fn client(&self) -> Result<Object> {
+        self.schema
+            .get_or_try_init(|| async {
+                postgres::migrate(&self.pool, "memories", MIGRATIONS).await.map(|_| ())
+            })
+            .await?;
+        Ok(self.pool.get().await?)
+    }
+
+    /// Memories matching `condition` (WHERE / ORDER BY / LIMIT clauses)
+    async fn query(&self, condition: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Memory>> {
+        let rows = self
+            .client()
+            .await?
+            .query(&format!("SELECT {} FROM memories {}", COLUMNS, condition), params)
+            .await?;
+        rows.iter().map(memory_from_row).collect()
+    }
+
+    async fn search_in(&self, namespace: Option<&str>, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        let params: [&(dyn ToSql + Sync); 3] = [&query, &(limit as i64), &namespace];
+        let mut memories = self
+            .query(
+                &format!(
+                    "WHERE to_tsvector('simple', content) @@ plainto_tsquery('simple', $1)
+                     AND ($3::TEXT IS NULL OR namespace = $3) AND {}
+                     ORDER BY created_at DESC
+                     LIMIT $2",
+                    NOT_EXPIRED
+                ),
+                &params,
+            )
+            .await?;
+        if memories.is_empty() {
+            memories = self
+                .query(
+                    &format!(
+                        "WHERE strpos(lower(content), lower($1)) > 0
+                         AND ($3::TEXT IS NULL OR namespace = $3) AND {}
+                         ORDER BY created_at DESC
+                         LIMIT $2",
+                        NOT_EXPIRED
+                    ),
+                    &params,
+                )
+                .await?;
+        }
+        debug!("Found {} memories matching query: {}", memories.len(), query);
+        Ok(memories)
+    }
+
+    async fn delete_where(&self, condition: &str, params: &[&(dyn ToSql + Sync)]) -> Result<usize> {
+        let deleted = self
+            .client()
+            .await?
+            .execute(&format!("DELETE FROM memories WHERE {}", condition), params)
+            .await?;
+        if deleted > 0 {
+            debug!("Deleted {} memories", deleted);
+        }
+        Ok(deleted as usize)
+    }
+}
+
+#[async_trait]
+impl MemoryBackend for PgMemoryStore {
+    async fn save(&self, memory: &Memory) -> Result<()> {
+        self.client()
+            .await?
+            .execute(
+                "INSERT INTO memories (id, content, metadata, created_at, namespace, expires_at)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (id) DO UPDATE SET
+                    content = EXCLUDED.content,
+                    metadata = EXCLUDED.metadata,
+                    created_at = EXCLUDED.created_at,
+                    namespace = EXCLUDED.namespace,
+                    expires_at = EXCLUDED.expires_at",
+                &[
+                    &memory.id,
+                    &memory.content,
+                    &memory.metadata,
+                    &memory.created_at,
+                    &memory.namespace,
+                    &memory.expires_at,
+                ],
+            )
+            .await?;
+        debug!("Saved memory with id: {}", memory.id);
+        Ok(())
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<Memory>> {
+        Ok(self
+            .query(&format!("WHERE id = $1 AND {}", NOT_EXPIRED), &[&id])
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.search_in(None, query, limit).await
+    }
+
+    async fn search_namespace(&self, namespace: &str, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.search_in(Some(namespace), query, limit).await
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        let rows_affected = self
+            .client()
+            .await?
+            .execute("DELETE FROM memories WHERE id = $1", &[&id])
+            .await?;
+        if rows_affected > 0 {
+            debug!("Deleted memory with id: {}", id);
+        }
+        Ok(())
+    }
+
+    async fn list_by_metadata(&self, key: &str, value: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.query(
+            &format!("WHERE metadata ->> $1 = $2 AND {} ORDER BY created_at DESC LIMIT $3", NOT_EXPIRED),
+            &[&key, &value, &(limit as i64)],
+        )
+        .await
+    }
+
+    async fn list_namespace(&self, namespace: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.query(
+            &format!("WHERE namespace = $1 AND {} ORDER BY created_at DESC LIMIT $2", NOT_EXPIRED),
+            &[&namespace, &(limit as i64)],
+        )
+        .await
+    }
+
+    async fn list_recent(&self, limit: usize) -> Result<Vec<Memory>> {
+        self.query(
+            &format!("WHERE {} ORDER BY created_at DESC LIMIT $1", NOT_EXPIRED),
+            &[&(limit as i64)],
+        )
+        .await
+    }
+
+    async fn delete_namespace(&self, namespace: &str) -> Result<usize> {
+        self.delete_where("namespace = $1", &[&namespace]).await
+    }
+
+    async fn purge_expired(&self) -> Result<usize> {
+        self.delete_where(&format!("NOT {}", NOT_EXPIRED), &[]).await
+    }
+
+    async fn count(&self) -> Result<usize> {
+        let row = self
+            .client()
+            .await?
+            .query_one(&format!("SELECT COUNT(*) FROM memories WHERE {}", NOT_EXPIRED), &[])
+            .await?;
+        Ok(row.get::<_, i64>(0) as usize)
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.client().await?.execute("DELETE FROM memories", &[]).await?;
+        info!("Cleared all memories");
+        Ok(())
+    }
+}
+
+/// Build a memory from a row of [`COLUMNS`]
+fn memory_from_row(row: &Row) -> Result<Memory> {
+    Ok(Memory {
+        id: row.try_get(0)?,
+        content: row.try_get(1)?,
+        metadata: row.try_get::<_, Option<JsonValue>>(2)?.unwrap_or(JsonValue::Null),
+        created_at: row.try_get(3)?,
+        namespace: row.try_get(4)?,
+        expires_at: row.try_get(5)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Runs against the database in CC_TEST_DATABASE_URL, skipped without it
+    #[tokio::test]
+    async fn test_roundtrip_and_search() {
+        let url = match std::env::var("CC_TEST_DATABASE_URL") {
+            Ok(url) => url,
+            Err(_) => return,
+        };
+        let store = PgMemoryStore::new(postgres::create_pool(&url, 2).unwrap());
+        let marker = uuid::Uuid::new_v4().simple().to_string();
+        let memory = Memory::new(format!("{} Rust is a systems programming language", marker))
+            .with_metadata(json!({"priority": "high"}));
+        let japanese = Memory::new(format!("{}ラーメンが好き", marker));
+        store.save(&memory).await.unwrap();
+        store.save(&japanese).await.unwrap();
+
+        let loaded = store.load(&memory.id).await.unwrap().unwrap();
+        assert_eq!(loaded.metadata["priority"], "high");
+        let tagged = store.list_by_metadata("priority", "high", 1000).await.unwrap();
+        assert!(tagged.iter().any(|m| m.id == memory.id));
+        assert_eq!(store.search(&format!("{} rust", marker), 10).await.unwrap().len(), 1);
+        assert_eq!(store.search("ラーメン", 100).await.unwrap().iter().filter(|m| m.id == japanese.id).count(), 1);
+
+        let namespace = format!("test:{}", marker);
+        let scoped = Memory::new("Likes green tea").in_namespace(namespace.as_str());
+        let expired = Memory::new("Old news")
+            .in_namespace(namespace.as_str())
+            .with_expiry(chrono::Utc::now() - chrono::Duration::minutes(1));
+        store.save(&scoped).await.unwrap();
+        store.save(&expired).await.unwrap();
+        assert_eq!(store.list_namespace(&namespace, 10).await.unwrap().len(), 1);
+        assert_eq!(store.search_namespace(&namespace, "tea", 10).await.unwrap().len(), 1);
+        assert!(store.load(&expired.id).await.unwrap().is_none());
+        assert!(store.purge_expired().await.unwrap() >= 1);
+        assert_eq!(store.delete_namespace(&namespace).await.unwrap(), 1);
+
+        store.delete(&memory.id).await.unwrap();
+        store.delete(&japanese.id).await.unwrap();
+        assert!(store.load(&memory.id).await.unwrap().is_none());
+    }
+}
@@ -1,15 +1,74 @@
 //! Memory storage implementation using SQLite
 
+use std::sync::{Mutex, MutexGuard};
+
+use async_trait::async_trait;
 use rusqlite::{Connection, params};
-use crate::memory::Memory;
+use crate::memory::{Memory, MemoryBackend};
+use crate::migrate::{self, Migration};
 use crate::Result;
 use serde_json::Value as JsonValue;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use tracing::{debug, info};
 
+/// Columns read by [`memory_from_row`]
+const COLUMNS: &str = "id, content, metadata, created_at, namespace, expires_at";
+
+/// Condition excluding expired memories (expiry times are stored as
+/// `YYYY-MM-DDTHH:MM:SSZ`, so they compare as text)
+const NOT_EXPIRED: &str = "(expires_at IS NULL OR expires_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))";
+
+/// Schema versions of the `memories` table
+const MIGRATIONS: &[Migration] = &[
+    Migration::sql(
+        1,
+        "create memories",
+        "CREATE TABLE IF NOT EXISTS memories (
+            id TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            metadata TEXT,
+            created_at TEXT NOT NULL
+        )",
+    ),
+    Migration::run(2, "create memories_fts", create_fts_table),
+    Migration::sql(
+        3,
+        "add namespace and expiry",
+        "ALTER TABLE memories ADD COLUMN namespace TEXT;
+        ALTER TABLE memories ADD COLUMN expires_at TEXT;
+        UPDATE memories SET namespace = json_extract(metadata, '$.identity')
+            WHERE json_extract(metadata, '$.identity') IS NOT NULL;
+        CREATE INDEX IF NOT EXISTS idx_memories_namespace ON memories(namespace, created_at);
+        CREATE INDEX IF NOT EXISTS idx_memories_expires_at ON memories(expires_at)",
+    ),
+];
+
+/// FTS5 index for full-text search
+///
+/// SQLite builds without FTS5 fall back to LIKE search, so a failure is not an error.
+fn create_fts_table(conn: &Connection) -> rusqlite::Result<()> {
+    let fts_result = conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(
+            id UNINDEXED,
+            content,
+            content='memories',
+            content_rowid=rowid
+        )",
+        [],
+    );
+    match fts_result {
+        Ok(_) => debug!("FTS5 full-text search enabled"),
+        Err(e) => debug!("FTS5 not available, falling back to LIKE search: {}", e),
+    }
+    Ok(())
+}
+
 /// SQLite-based storage for memories
+///
+/// Expired memories are never returned; [`MemoryStore::purge_expired`]
+/// deletes them.
 pub struct MemoryStore {
-    conn: Connection,
+    conn: Mutex<Connection>,
 }
 
 impl MemoryStore {
@@ -17,7 +76,7 @@ impl MemoryStore {
     pub fn new(db_path: &str) -> Result<Self> {
         debug!("Opening memory database at: {}", db_path);
         let conn = Connection::open(db_path)?;
-        let store = Self { conn };
+        let store = Self { conn: Mutex::new(conn) };
         store.init_tables()?;
         info!("MemoryStore initialized successfully");
         Ok(store)
@@ -26,59 +85,36 @@ impl MemoryStore {
     /// Create an in-memory MemoryStore (useful for testing)
     pub fn in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
-        let store = Self { conn };
+        let store = Self { conn: Mutex::new(conn) };
         store.init_tables()?;
         Ok(store)
     }
 
-    /// Initialize database tables
+    /// Create or upgrade the database tables
     fn init_tables(&self) -> Result<()> {
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS memories (
-                id TEXT PRIMARY KEY,
-                content TEXT NOT NULL,
-                metadata TEXT,
-                created_at TEXT NOT NULL
-            )",
-            [],
-        )?;
-
-        // Try to create FTS5 virtual table for full-text search
-        // This may fail if FTS5 is not compiled into SQLite
-        let fts_result = self.conn.execute(
-            "CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(
-                id UNINDEXED,
-                content,
-                content='memories',
-                content_rowid=rowid
-            )",
-            [],
-        );
-
-        match fts_result {
-            Ok(_) => debug!("FTS5 full-text search enabled"),
-            Err(e) => debug!("FTS5 not available, falling back to LIKE search: {}", e),
-        }
-
+        migrate::migrate(&self.conn(), "memories", MIGRATIONS)?;
         Ok(())
     }
 
     /// Save a memory to the store
     pub fn save(&self, memory: &Memory) -> Result<()> {
         let metadata_json = serde_json::to_string(&memory.metadata)?;
-        self.conn.execute(
-            "INSERT OR REPLACE INTO memories (id, content, metadata, created_at)
-             VALUES (?1, ?2, ?3, ?4)",
+        let conn = self.conn();
+        conn.execute(
+            "INSERT OR REPLACE INTO memories (id, content, metadata, created_at, namespace, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![
                 memory.id,
                 memory.content,
                 metadata_json,
                 memory.created_at.to_rfc3339(),
+                memory.namespace,
+                memory.expires_at.map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, true)),
             ],
         )?;
 
         // Also update FTS index if available
-        self.conn.execute(
+        conn.execute(
             "INSERT OR REPLACE INTO memories_fts (rowid, id, content)
              SELECT rowid, id, content FROM memories WHERE id = ?1",
             params![memory.id],
@@ -90,29 +126,13 @@ impl MemoryStore {
 
     /// Load a memory by ID
     pub fn load(&self, id: &str) -> Result<Option<Memory>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, metadata, created_at FROM memories WHERE id = ?1"
-        )?;
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM memories WHERE id = ?1 AND {}",
+            COLUMNS, NOT_EXPIRED
+        ))?;
 
-        let result = stmt.query_row(params![id], |row| {
-            let id: String = row.get(0)?;
-            let content: String = row.get(1)?;
-            let metadata_str: String = row.get(2)?;
-            let created_at_str: String = row.get(3)?;
-
-            let metadata: JsonValue = serde_json::from_str(&metadata_str)
-                .unwrap_or(JsonValue::Null);
-            let created_at: DateTime<Utc> = DateTime::parse_from_rfc3339(&created_at_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
-
-            Ok(Memory {
-                id,
-                content,
-                metadata,
-                created_at,
-            })
-        });
+        let result = stmt.query_row(params![id], memory_from_row);
 
         match result {
             Ok(memory) => Ok(Some(memory)),
@@ -123,92 +143,74 @@ impl MemoryStore {
 
     /// Search memories by content (using LIKE or FTS if available)
     pub fn search(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.search_in(None, query, limit)
+    }
+
+    /// Search the memories of one namespace by content
+    pub fn search_namespace(&self, namespace: &str, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.search_in(Some(namespace), query, limit)
+    }
+
+    fn search_in(&self, namespace: Option<&str>, query: &str, limit: usize) -> Result<Vec<Memory>> {
         // Try FTS first, fall back to LIKE search if FTS fails
-        let memories = match self.search_with_fts(query, limit) {
+        let memories = match self.search_with_fts(namespace, query, limit) {
             Ok(results) => results,
-            Err(_) => self.search_with_like(query, limit)?,
+            Err(_) => self.search_with_like(namespace, query, limit)?,
         };
         debug!("Found {} memories matching query: {}", memories.len(), query);
         Ok(memories)
     }
 
     /// Search using FTS5 (if available)
-    fn search_with_fts(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT m.id, m.content, m.metadata, m.created_at
+    fn search_with_fts(&self, namespace: Option<&str>, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT m.id, m.content, m.metadata, m.created_at, m.namespace, m.expires_at
              FROM memories m
              JOIN memories_fts fts ON m.id = fts.id
-             WHERE memories_fts MATCH ?1
+             WHERE memories_fts MATCH ?1 AND (?3 IS NULL OR m.namespace = ?3) AND {}
              ORDER BY m.created_at DESC
-             LIMIT ?2"
-        )?;
+             LIMIT ?2",
+            NOT_EXPIRED.replace("expires_at", "m.expires_at")
+        ))?;
 
-        let memories = stmt.query_map(params![query, limit as i32], |row| {
-            let id: String = row.get(0)?;
-            let content: String = row.get(1)?;
-            let metadata_str: String = row.get(2)?;
-            let created_at_str: String = row.get(3)?;
-
-            let metadata: JsonValue = serde_json::from_str(&metadata_str)
-                .unwrap_or(JsonValue::Null);
-            let created_at: DateTime<Utc> = DateTime::parse_from_rfc3339(&created_at_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
-
-            Ok(Memory {
-                id,
-                content,
-                metadata,
-                created_at,
-            })
-        })?.collect::<std::result::Result<Vec<_>, _>>()?;
+        let memories = stmt
+            .query_map(params![query, limit as i32, namespace], memory_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(memories)
     }
 
     /// Fallback search using LIKE
-    fn search_with_like(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, metadata, created_at FROM memories
-             WHERE content LIKE ?1
+    fn search_with_like(&self, namespace: Option<&str>, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM memories
+             WHERE content LIKE ?1 AND (?3 IS NULL OR namespace = ?3) AND {}
              ORDER BY created_at DESC
-             LIMIT ?2"
-        )?;
+             LIMIT ?2",
+            COLUMNS, NOT_EXPIRED
+        ))?;
 
         let pattern = format!("%{}%", query);
-        let memories = stmt.query_map(params![pattern, limit as i32], |row| {
-            let id: String = row.get(0)?;
-            let content: String = row.get(1)?;
-            let metadata_str: String = row.get(2)?;
-            let created_at_str: String = row.get(3)?;
-
-            let metadata: JsonValue = serde_json::from_str(&metadata_str)
-                .unwrap_or(JsonValue::Null);
-            let created_at: DateTime<Utc> = DateTime::parse_from_rfc3339(&created_at_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
-
-            Ok(Memory {
-                id,
-                content,
-                metadata,
-                created_at,
-            })
-        })?.collect::<std::result::Result<Vec<_>, _>>()?;
+        let memories = stmt
+            .query_map(params![pattern, limit as i32, namespace], memory_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(memories)
     }
 
     /// Delete a memory by ID
     pub fn delete(&self, id: &str) -> Result<()> {
+        let conn = self.conn();
         // Delete from FTS index first
-        self.conn.execute(
+        conn.execute(
             "DELETE FROM memories_fts WHERE id = ?1",
             params![id],
         ).ok();
 
         // Then delete from main table
-        let rows_affected = self.conn.execute(
+        let rows_affected = conn.execute(
             "DELETE FROM memories WHERE id = ?1",
             params![id],
         )?;
@@ -219,42 +221,46 @@ impl MemoryStore {
         Ok(())
     }
 
+    /// List the newest memories whose metadata has `key` set to the string `value`
+    pub fn list_by_metadata(&self, key: &str, value: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.query(
+            &format!("WHERE json_extract(metadata, '$.' || ?1) = ?2 AND {} ORDER BY created_at DESC LIMIT ?3", NOT_EXPIRED),
+            params![key, value, limit as i64],
+        )
+    }
+
+    /// List the newest memories of a namespace
+    pub fn list_namespace(&self, namespace: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.query(
+            &format!("WHERE namespace = ?1 AND {} ORDER BY created_at DESC LIMIT ?2", NOT_EXPIRED),
+            params![namespace, limit as i64],
+        )
+    }
+
     /// List recent memories
     pub fn list_recent(&self, limit: usize) -> Result<Vec<Memory>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, metadata, created_at FROM memories
-             ORDER BY created_at DESC
-             LIMIT ?1"
+        let memories = self.query(
+            &format!("WHERE {} ORDER BY created_at DESC LIMIT ?1", NOT_EXPIRED),
+            params![limit as i64],
         )?;
-
-        let memories = stmt.query_map(params![limit as i32], |row| {
-            let id: String = row.get(0)?;
-            let content: String = row.get(1)?;
-            let metadata_str: String = row.get(2)?;
-            let created_at_str: String = row.get(3)?;
-
-            let metadata: JsonValue = serde_json::from_str(&metadata_str)
-                .unwrap_or(JsonValue::Null);
-            let created_at: DateTime<Utc> = DateTime::parse_from_rfc3339(&created_at_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
-
-            Ok(Memory {
-                id,
-                content,
-                metadata,
-                created_at,
-            })
-        })?.collect::<std::result::Result<Vec<_>, _>>()?;
-
         debug!("Listed {} recent memories", memories.len());
         Ok(memories)
     }
 
+    /// Delete every memory of a namespace, returning how many were deleted
+    pub fn delete_namespace(&self, namespace: &str) -> Result<usize> {
+        self.delete_where("namespace = ?1", params![namespace])
+    }
+
+    /// Delete expired memories, returning how many were deleted
+    pub fn purge_expired(&self) -> Result<usize> {
+        self.delete_where(&format!("NOT {}", NOT_EXPIRED), params![])
+    }
+
     /// Count total memories
     pub fn count(&self) -> Result<usize> {
-        let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM memories",
+        let count: i64 = self.conn().query_row(
+            &format!("SELECT COUNT(*) FROM memories WHERE {}", NOT_EXPIRED),
             [],
             |row| row.get(0),
         )?;
@@ -263,11 +269,112 @@ impl MemoryStore {
 
     /// Clear all memories
     pub fn clear(&self) -> Result<()> {
-        self.conn.execute("DELETE FROM memories_fts", [])?;
-        self.conn.execute("DELETE FROM memories", [])?;
+        let conn = self.conn();
+        conn.execute("DELETE FROM memories_fts", [])?;
+        conn.execute("DELETE FROM memories", [])?;
         info!("Cleared all memories");
         Ok(())
     }
+
+    /// Memories matching `condition` (WHERE / ORDER BY / LIMIT clauses)
+    fn query(&self, condition: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<Memory>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM memories {}", COLUMNS, condition))?;
+        let memories = stmt
+            .query_map(params, memory_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(memories)
+    }
+
+    /// Delete the memories matching `condition` and their index entries
+    fn delete_where(&self, condition: &str, params: &[&dyn rusqlite::ToSql]) -> Result<usize> {
+        let conn = self.conn();
+        conn.execute(
+            &format!("DELETE FROM memories_fts WHERE id IN (SELECT id FROM memories WHERE {})", condition),
+            params,
+        ).ok();
+        let deleted = conn.execute(&format!("DELETE FROM memories WHERE {}", condition), params)?;
+        if deleted > 0 {
+            debug!("Deleted {} memories", deleted);
+        }
+        Ok(deleted)
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Build a memory from a row of [`COLUMNS`]
+fn memory_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Memory> {
+    let metadata_str: String = row.get(2)?;
+    let created_at_str: String = row.get(3)?;
+    let expires_at_str: Option<String> = row.get(5)?;
+
+    let metadata: JsonValue = serde_json::from_str(&metadata_str)
+        .unwrap_or(JsonValue::Null);
+    let parse = |s: &str| DateTime::parse_from_rfc3339(s).map(|dt| dt.with_timezone(&Utc));
+    let created_at: DateTime<Utc> = parse(&created_at_str).unwrap_or_else(|_| Utc::now());
+
+    Ok(Memory {
+        id: row.get(0)?,
+        content: row.get(1)?,
+        metadata,
+        created_at,
+        namespace: row.get(4)?,
+        expires_at: expires_at_str.and_then(|s| parse(&s).ok()),
+    })
+}
+
+#[async_trait]
+impl MemoryBackend for MemoryStore {
+    async fn save(&self, memory: &Memory) -> Result<()> {
+        MemoryStore::save(self, memory)
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<Memory>> {
+        MemoryStore::load(self, id)
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        MemoryStore::search(self, query, limit)
+    }
+
+    async fn search_namespace(&self, namespace: &str, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        MemoryStore::search_namespace(self, namespace, query, limit)
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        MemoryStore::delete(self, id)
+    }
+
+    async fn list_by_metadata(&self, key: &str, value: &str, limit: usize) -> Result<Vec<Memory>> {
+        MemoryStore::list_by_metadata(self, key, value, limit)
+    }
+
+    async fn list_namespace(&self, namespace: &str, limit: usize) -> Result<Vec<Memory>> {
+        MemoryStore::list_namespace(self, namespace, limit)
+    }
+
+    async fn list_recent(&self, limit: usize) -> Result<Vec<Memory>> {
+        MemoryStore::list_recent(self, limit)
+    }
+
+    async fn delete_namespace(&self, namespace: &str) -> Result<usize> {
+        MemoryStore::delete_namespace(self, namespace)
+    }
+
+    async fn purge_expired(&self) -> Result<usize> {
+        MemoryStore::purge_expired(self)
+    }
+
+    async fn count(&self) -> Result<usize> {
+        MemoryStore::count(self)
+    }
+
+    async fn clear(&self) -> Result<()> {
+        MemoryStore::clear(self)
+    }
 }
 
 #[cfg(test)]
@@ -326,6 +433,82 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_list_by_metadata() -> Result<()> {
+        let store = MemoryStore::in_memory()?;
+
+        store.save(&Memory::new("Likes tea").with_metadata(json!({"identity": "alice"})))?;
+        store.save(&Memory::new("Likes coffee").with_metadata(json!({"identity": "bob"})))?;
+        store.save(&Memory::new("No metadata"))?;
+
+        let alice = store.list_by_metadata("identity", "alice", 10)?;
+        assert_eq!(alice.len(), 1);
+        assert_eq!(alice[0].content, "Likes tea");
+        assert!(store.list_by_metadata("identity", "carol", 10)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_namespaces() -> Result<()> {
+        let store = MemoryStore::in_memory()?;
+
+        store.save(&Memory::new("Likes green tea").in_namespace("alice"))?;
+        store.save(&Memory::new("Likes black tea").in_namespace("bob"))?;
+        store.save(&Memory::new("Tea is served at three"))?;
+
+        assert_eq!(store.list_namespace("alice", 10)?.len(), 1);
+        let found = store.search_namespace("bob", "tea", 10)?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].namespace.as_deref(), Some("bob"));
+        assert_eq!(store.search("tea", 10)?.len(), 3);
+
+        assert_eq!(store.delete_namespace("alice")?, 1);
+        assert!(store.list_namespace("alice", 10)?.is_empty());
+        assert_eq!(store.count()?, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expiry() -> Result<()> {
+        let store = MemoryStore::in_memory()?;
+
+        let expired = Memory::new("Old news").with_expiry(Utc::now() - chrono::Duration::minutes(1));
+        let fresh = Memory::new("Fresh news").expires_in_days(1);
+        store.save(&expired)?;
+        store.save(&fresh)?;
+
+        assert!(store.load(&expired.id)?.is_none());
+        let loaded = store.load(&fresh.id)?.unwrap();
+        assert_eq!(
+            loaded.expires_at.map(|at| at.timestamp()),
+            fresh.expires_at.map(|at| at.timestamp())
+        );
+        assert_eq!(store.search("news", 10)?.len(), 1);
+        assert_eq!(store.list_recent(10)?.len(), 1);
+        assert_eq!(store.purge_expired()?, 1);
+        assert_eq!(store.purge_expired()?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrates_identity_to_namespace() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        migrate::migrate(&conn, "memories", &MIGRATIONS[..2])?;
+        conn.execute(
+            "INSERT INTO memories (id, content, metadata, created_at) VALUES ('m1', 'Likes tea', '{\"identity\":\"alice\"}', ?1)",
+            params![Utc::now().to_rfc3339()],
+        )?;
+        let store = MemoryStore { conn: Mutex::new(conn) };
+        store.init_tables()?;
+
+        assert_eq!(store.list_namespace("alice", 10)?.len(), 1);
+
+        Ok(())
+    }
+
     #[test]
     fn test_list_recent() -> Result<()> {
         let store = MemoryStore::in_memory()?;
@@ -0,0 +1,356 @@
+//! Memory tools (`memory_save`, `memory_search`, `memory_delete`)
+//!
+//! Let the agent manage long-term memory itself. When a call comes from a
+//! channel user (see [`ToolOrigin`]), memories are saved in the namespace of
+//! the user's identity (so the memorizer also recalls them) or of the channel,
+//! and the tools only see those two namespaces. Calls without a user (CLI,
+//! scheduled tasks) see every memory.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use crate::config::MemoryConfig;
+use crate::memory::{Memory, MemoryBackend};
+use crate::tool::{Tool, ToolManager, ToolOrigin, ToolResult};
+use crate::{Error, Result};
+
+/// `metadata.source` of memories saved with `memory_save`
+pub const MEMORY_TOOL_SOURCE: &str = "tool";
+
+/// Memories returned by a search unless a limit is given
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Store and identity settings shared by the memory tools
+struct MemoryTools {
+    store: Arc<dyn MemoryBackend>,
+    config: MemoryConfig,
+}
+
+/// Namespaces of the user a tool call comes from
+struct Caller {
+    /// The user's identity
+    user: String,
+    /// Shared by everyone in the channel
+    channel: String,
+}
+
+impl Caller {
+    fn owns(&self, memory: &Memory) -> bool {
+        memory.namespace.as_deref().is_some_and(|ns| ns == self.user || ns == self.channel)
+    }
+}
+
+impl MemoryTools {
+    /// Namespaces of the user the current tool call comes from, if any
+    fn caller(&self) -> Option<Caller> {
+        let origin = ToolOrigin::current()?;
+        let user_id = origin.user_id.as_deref()?;
+        Some(Caller {
+            user: self.config.identity(&origin.platform, user_id),
+            channel: self.config.channel_namespace(&origin.platform, &origin.channel_id),
+        })
+    }
+}
+
+/// Whether the caller may see `memory` (every memory without a caller)
+fn visible_to(memory: &Memory, caller: Option<&Caller>) -> bool {
+    caller.is_none_or(|caller| caller.owns(memory))
+}
+
+fn memory_to_json(memory: &Memory) -> Value {
+    json!({
+        "id": memory.id,
+        "content": memory.content,
+        "tags": memory.metadata.get("tags").cloned().unwrap_or_else(|| json!([])),
+        "namespace": memory.namespace,
+        "created_at": memory.created_at.to_rfc3339(),
+        "expires_at": memory.expires_at.map(|at| at.to_rfc3339()),
+    })
+}
+
+/// `memory_save` tool
+pub struct MemorySaveTool {
+    tools: Arc<MemoryTools>,
+}
+
+#[async_trait]
+impl Tool for MemorySaveTool {
+    fn name(&self) -> &str {
+        "memory_save"
+    }
+
+    fn description(&self) -> &str {
+        "Save something to long-term memory so it can be recalled in later conversations: \
+         facts about the user, their preferences, decisions or anything they ask you to remember. \
+         Write it as a short self-contained statement."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "What to remember"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional keywords for grouping"
+                },
+                "scope": {
+                    "type": "string",
+                    "enum": ["user", "channel"],
+                    "description": "Remember it for the user (default) or for everyone in this channel"
+                },
+                "expires_in_days": {
+                    "type": "integer",
+                    "description": "Forget it after this many days (default: never)"
+                }
+            },
+            "required": ["content"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let content = input["content"]
+            .as_str()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| Error::ToolExecution("Missing 'content' parameter".to_string()))?;
+        let tags: Vec<&str> = input["tags"]
+            .as_array()
+            .map(|tags| tags.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut memory = Memory::new(content)
+            .with_metadata(json!({ "source": MEMORY_TOOL_SOURCE, "tags": tags }));
+        if let Some(caller) = self.tools.caller() {
+            memory = match input["scope"].as_str() {
+                Some("channel") => memory.in_namespace(caller.channel),
+                _ => memory.in_namespace(caller.user),
+            };
+        }
+        if let Some(days) = input["expires_in_days"].as_u64() {
+            memory = memory.expires_in_days(days);
+        }
+        self.tools.store.save(&memory).await?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "saved",
+            "memory": memory_to_json(&memory)
+        })).unwrap_or_default()))
+    }
+}
+
+/// `memory_search` tool
+pub struct MemorySearchTool {
+    tools: Arc<MemoryTools>,
+}
+
+#[async_trait]
+impl Tool for MemorySearchTool {
+    fn name(&self) -> &str {
+        "memory_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search long-term memory for things saved in earlier conversations. \
+         An empty query lists the most recent memories."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Text to look for (empty lists recent memories)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of memories to return (default: 10)",
+                    "default": 10
+                }
+            }
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let query = input["query"].as_str().unwrap_or_default().trim();
+        let limit = input["limit"]
+            .as_u64()
+            .map_or(DEFAULT_SEARCH_LIMIT, |l| l as usize);
+
+        let store = &self.tools.store;
+        let mut matches = match (self.tools.caller(), query.is_empty()) {
+            (Some(caller), true) => {
+                let mut matches = store.list_namespace(&caller.user, limit).await?;
+                matches.extend(store.list_namespace(&caller.channel, limit).await?);
+                matches
+            }
+            (Some(caller), false) => {
+                let mut matches = store.search_namespace(&caller.user, query, limit).await?;
+                matches.extend(store.search_namespace(&caller.channel, query, limit).await?);
+                matches
+            }
+            (None, true) => store.list_recent(limit).await?,
+            (None, false) => store.search(query, limit).await?,
+        };
+        matches.sort_by_key(|m| std::cmp::Reverse(m.created_at));
+        let memories: Vec<Value> = matches.iter().take(limit).map(memory_to_json).collect();
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "query": query,
+            "count": memories.len(),
+            "memories": memories
+        })).unwrap_or_default()))
+    }
+}
+
+/// `memory_delete` tool
+pub struct MemoryDeleteTool {
+    tools: Arc<MemoryTools>,
+}
+
+#[async_trait]
+impl Tool for MemoryDeleteTool {
+    fn name(&self) -> &str {
+        "memory_delete"
+    }
+
+    fn description(&self) -> &str {
+        "Delete a memory by its ID (from memory_search), e.g. when the user asks you to forget \
+         something or it is no longer true"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "ID of the memory to delete"
+                }
+            },
+            "required": ["id"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let id = input["id"]
+            .as_str()
+            .ok_or_else(|| Error::ToolExecution("Missing 'id' parameter".to_string()))?;
+
+        // Other users' memories are reported as missing
+        let caller = self.tools.caller();
+        match self.tools.store.load(id).await? {
+            Some(memory) if visible_to(&memory, caller.as_ref()) => {}
+            _ => return Ok(ToolResult::error(format!("Memory not found: {}", id))),
+        }
+        self.tools.store.delete(id).await?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "deleted",
+            "id": id
+        })).unwrap_or_default()))
+    }
+}
+
+/// Register the memory tools backed by `store`
+///
+/// `config` links channel users to identities (`[memory.identities]`).
+pub fn register_memory_tools(manager: &mut ToolManager, store: Arc<dyn MemoryBackend>, config: &MemoryConfig) {
+    let tools = Arc::new(MemoryTools {
+        store,
+        config: config.clone(),
+    });
+    manager.register(Arc::new(MemorySaveTool { tools: Arc::clone(&tools) }));
+    manager.register(Arc::new(MemorySearchTool { tools: Arc::clone(&tools) }));
+    manager.register(Arc::new(MemoryDeleteTool { tools }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStore;
+
+    fn manager() -> ToolManager {
+        let mut manager = ToolManager::new();
+        let store = Arc::new(MemoryStore::in_memory().unwrap());
+        register_memory_tools(&mut manager, store, &MemoryConfig::default());
+        manager
+    }
+
+    async fn call(manager: &ToolManager, name: &str, input: Value) -> Value {
+        let result = manager.execute(name, input).await.unwrap();
+        assert!(!result.is_error, "{}", result.output);
+        serde_json::from_str(&result.output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_save_search_delete() {
+        let manager = manager();
+        let saved = call(&manager, "memory_save", json!({"content": "Prefers dark mode", "tags": ["ui"]})).await;
+        let id = saved["memory"]["id"].as_str().unwrap().to_string();
+        assert_eq!(saved["memory"]["tags"], json!(["ui"]));
+
+        let found = call(&manager, "memory_search", json!({"query": "dark mode"})).await;
+        assert_eq!(found["count"], 1);
+        assert_eq!(found["memories"][0]["id"], id.as_str());
+        let recent = call(&manager, "memory_search", json!({})).await;
+        assert_eq!(recent["count"], 1);
+
+        call(&manager, "memory_delete", json!({"id": id})).await;
+        let found = call(&manager, "memory_search", json!({"query": "dark mode"})).await;
+        assert_eq!(found["count"], 0);
+        assert!(manager.execute("memory_save", json!({"content": " "})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scoped_to_caller() {
+        let manager = manager();
+        let alice = ToolOrigin::new("discord", "c1").with_user("1");
+        let bob = ToolOrigin::new("discord", "c1").with_user("2");
+
+        let saved = alice
+            .clone()
+            .scope(call(&manager, "memory_save", json!({"content": "Alice likes tea"})))
+            .await;
+        let id = saved["memory"]["id"].as_str().unwrap().to_string();
+
+        let found = bob.clone().scope(call(&manager, "memory_search", json!({"query": "tea"}))).await;
+        assert_eq!(found["count"], 0);
+        let deleted = bob
+            .clone()
+            .scope(manager.execute("memory_delete", json!({"id": id})))
+            .await
+            .unwrap();
+        assert!(deleted.is_error);
+
+        let found = alice.clone().scope(call(&manager, "memory_search", json!({}))).await;
+        assert_eq!(found["count"], 1);
+        assert_eq!(found["memories"][0]["namespace"], "discord:1");
+
+        // Channel memories are shared with everyone in the channel
+        alice
+            .scope(call(&manager, "memory_save", json!({"content": "Standup is at ten", "scope": "channel", "expires_in_days": 7})))
+            .await;
+        let found = bob.clone().scope(call(&manager, "memory_search", json!({"query": "standup"}))).await;
+        assert_eq!(found["count"], 1);
+        assert_eq!(found["memories"][0]["namespace"], "discord:channel:c1");
+        assert!(found["memories"][0]["expires_at"].is_string());
+        let elsewhere = ToolOrigin::new("discord", "c2").with_user("2");
+        let found = elsewhere.scope(call(&manager, "memory_search", json!({"query": "standup"}))).await;
+        assert_eq!(found["count"], 0);
+        // Calls without a user see every memory
+        let found = call(&manager, "memory_search", json!({})).await;
+        assert_eq!(found["count"], 2);
+    }
+}
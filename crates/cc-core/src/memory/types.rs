@@ -1,9 +1,12 @@
 //! Memory type definitions for cc-core
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
+/// Longest accepted time to live (100 years)
+const MAX_TTL_DAYS: u64 = 36_500;
+
 /// A memory entry stored in the system
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
@@ -15,6 +18,13 @@ pub struct Memory {
     pub metadata: JsonValue,
     /// When the memory was created
     pub created_at: DateTime<Utc>,
+    /// Owner of the memory: a user identity or a channel namespace
+    /// (None = shared)
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// When the memory expires (None = never)
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl Memory {
@@ -25,6 +35,8 @@ impl Memory {
             content: content.into(),
             metadata: JsonValue::Null,
             created_at: Utc::now(),
+            namespace: None,
+            expires_at: None,
         }
     }
 
@@ -35,6 +47,8 @@ impl Memory {
             content: content.into(),
             metadata: JsonValue::Null,
             created_at: Utc::now(),
+            namespace: None,
+            expires_at: None,
         }
     }
 
@@ -49,6 +63,29 @@ impl Memory {
         self.created_at = created_at;
         self
     }
+
+    /// Put the memory in a namespace
+    pub fn in_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Expire the memory at `expires_at`
+    pub fn with_expiry(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Expire the memory `days` days from now
+    pub fn expires_in_days(self, days: u64) -> Self {
+        let expires_at = Utc::now() + Duration::days(days.min(MAX_TTL_DAYS) as i64);
+        self.with_expiry(expires_at)
+    }
+
+    /// Whether the memory has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| at <= Utc::now())
+    }
 }
 
 #[cfg(test)]
@@ -73,4 +110,14 @@ mod tests {
         assert_eq!(memory.metadata["source"], "test");
         assert_eq!(memory.metadata["importance"], 5);
     }
+
+    #[test]
+    fn test_memory_expiry() {
+        let memory = Memory::new("Test content").in_namespace("alice");
+        assert_eq!(memory.namespace.as_deref(), Some("alice"));
+        assert!(!memory.is_expired());
+        assert!(!memory.clone().expires_in_days(1).is_expired());
+        assert!(memory.clone().expires_in_days(u64::MAX).expires_at.is_some());
+        assert!(memory.with_expiry(Utc::now() - Duration::seconds(1)).is_expired());
+    }
 }
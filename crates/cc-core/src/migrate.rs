@@ -0,0 +1,207 @@
+//! Versioned schema migrations for the SQLite stores
+//!
+//! Every store keeps an ordered list of [`Migration`]s. [`migrate`] applies
+//! the ones a database has not recorded yet, each in its own transaction, and
+//! records them in `schema_migrations` under the store's name, so several
+//! stores can share one database file.
+//!
+//! The first migration of a store only creates what is missing, which brings
+//! databases created before migrations existed under version control without
+//! touching their data. Later changes are added as new versions; released
+//! migrations are never edited.
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use tracing::info;
+
+/// How a migration changes the schema
+#[derive(Clone, Copy)]
+pub enum Step {
+    /// SQL statements (run as a batch)
+    Sql(&'static str),
+    /// Code, for changes that depend on the current schema
+    Run(fn(&Connection) -> rusqlite::Result<()>),
+}
+
+/// One schema version of a store
+#[derive(Clone, Copy)]
+pub struct Migration {
+    /// Starts at 1 and increases by one
+    pub version: u32,
+    pub description: &'static str,
+    pub step: Step,
+}
+
+impl Migration {
+    pub const fn sql(version: u32, description: &'static str, sql: &'static str) -> Self {
+        Self {
+            version,
+            description,
+            step: Step::Sql(sql),
+        }
+    }
+
+    pub const fn run(
+        version: u32,
+        description: &'static str,
+        run: fn(&Connection) -> rusqlite::Result<()>,
+    ) -> Self {
+        Self {
+            version,
+            description,
+            step: Step::Run(run),
+        }
+    }
+}
+
+/// Apply the pending migrations of `store` and return its schema version
+///
+/// Fails without changes when the database was migrated by a newer build.
+pub fn migrate(conn: &Connection, store: &str, migrations: &[Migration]) -> rusqlite::Result<u32> {
+    debug_assert!(
+        migrations
+            .iter()
+            .enumerate()
+            .all(|(i, m)| m.version as usize == i + 1),
+        "migrations of {} must be numbered 1, 2, ...",
+        store
+    );
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            store TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL,
+            PRIMARY KEY (store, version)
+        )",
+    )?;
+
+    let current = version(conn, store)?;
+    let latest = migrations.last().map_or(0, |m| m.version);
+    if current > latest {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_ERROR),
+            Some(format!(
+                "{} schema version {} is newer than this build supports ({})",
+                store, current, latest
+            )),
+        ));
+    }
+
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        match migration.step {
+            Step::Sql(sql) => tx.execute_batch(sql)?,
+            Step::Run(run) => run(&tx)?,
+        }
+        tx.execute(
+            "INSERT INTO schema_migrations (store, version, description, applied_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![store, migration.version, migration.description, Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        info!(store, version = migration.version, "Applied migration: {}", migration.description);
+    }
+    Ok(current.max(latest))
+}
+
+/// Schema version of `store` (0 = no migration applied)
+pub fn version(conn: &Connection, store: &str) -> rusqlite::Result<u32> {
+    let tracked: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !tracked {
+        return Ok(0);
+    }
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations WHERE store = ?1",
+        params![store],
+        |row| row.get(0),
+    )
+}
+
+/// Add a column unless the table already has it
+///
+/// For tables that builds before migrations altered on their own.
+pub fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTES: &[Migration] = &[
+        Migration::sql(1, "create notes", "CREATE TABLE IF NOT EXISTS notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL)"),
+        Migration::run(2, "add notes.tag", |conn| add_column_if_missing(conn, "notes", "tag", "TEXT")),
+    ];
+
+    #[test]
+    fn test_migrate_applies_pending_versions() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(version(&conn, "notes").unwrap(), 0);
+        assert_eq!(migrate(&conn, "notes", &NOTES[..1]).unwrap(), 1);
+        conn.execute("INSERT INTO notes (body) VALUES ('kept')", []).unwrap();
+
+        assert_eq!(migrate(&conn, "notes", NOTES).unwrap(), 2);
+        assert_eq!(migrate(&conn, "notes", NOTES).unwrap(), 2);
+        conn.execute("UPDATE notes SET tag = 'x'", []).unwrap();
+        let body: String = conn.query_row("SELECT body FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(body, "kept");
+
+        let recorded: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations WHERE store = 'notes'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(recorded, 2);
+    }
+
+    #[test]
+    fn test_migrate_adopts_existing_schema() {
+        // A table created by a build before migrations, already altered
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL, tag TEXT)")
+            .unwrap();
+        assert_eq!(migrate(&conn, "notes", NOTES).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let broken = [
+            NOTES[0],
+            Migration::sql(2, "broken", "CREATE TABLE extra (id INTEGER); INSERT INTO missing VALUES (1)"),
+        ];
+        let conn = Connection::open_in_memory().unwrap();
+        assert!(migrate(&conn, "notes", &broken).is_err());
+        assert_eq!(version(&conn, "notes").unwrap(), 1);
+        let extra: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'extra'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(extra, 0);
+    }
+
+    #[test]
+    fn test_rejects_newer_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn, "notes", NOTES).unwrap();
+        let err = migrate(&conn, "notes", &NOTES[..1]).unwrap_err();
+        assert!(err.to_string().contains("newer than this build"));
+
+        // Stores in the same file are versioned separately
+        assert_eq!(migrate(&conn, "other", &NOTES[..1]).unwrap(), 1);
+    }
+}
@@ -0,0 +1,276 @@
+//! Notification channels
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+
+use super::types::{ChannelConfig, Notification};
+use crate::{Error, Result};
+
+/// Discord message limit
+const DISCORD_MAX_CHARS: usize = 2000;
+/// Telegram message limit
+const TELEGRAM_MAX_CHARS: usize = 4096;
+/// Slack text limit (recommended)
+const SLACK_MAX_CHARS: usize = 40000;
+/// Twilio concatenated SMS limit
+const SMS_MAX_CHARS: usize = 1600;
+
+/// A destination notifications can be pushed to
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    /// Short description for logs
+    fn describe(&self) -> String;
+
+    /// Deliver a notification
+    async fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Email delivery used by `email` channels
+///
+/// Implemented outside cc-core (e.g. by cc-email) so that SMTP support stays optional.
+#[async_trait]
+pub trait EmailTransport: Send + Sync {
+    async fn send_email(&self, to: &str, subject: &str, body: &str) -> Result<()>;
+}
+
+/// Twilio account used for `sms` channels
+#[derive(Debug, Clone)]
+pub struct TwilioCredentials {
+    pub account_sid: String,
+    pub auth_token: String,
+    pub from_number: String,
+}
+
+/// Credentials shared by the built-in channels
+#[derive(Clone, Default)]
+pub struct NotifierCredentials {
+    pub discord_token: Option<String>,
+    pub slack_token: Option<String>,
+    pub telegram_token: Option<String>,
+    pub twilio: Option<TwilioCredentials>,
+    pub email: Option<Arc<dyn EmailTransport>>,
+}
+
+impl NotifierCredentials {
+    /// Read bot tokens and Twilio credentials from the environment
+    ///
+    /// - `DISCORD_BOT_TOKEN`, `SLACK_BOT_TOKEN`, `TELEGRAM_BOT_TOKEN`
+    /// - `TWILIO_ACCOUNT_SID`, `TWILIO_AUTH_TOKEN`, `TWILIO_PHONE_NUMBER`
+    pub fn from_env() -> Self {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+        let twilio = match (
+            env("TWILIO_ACCOUNT_SID"),
+            env("TWILIO_AUTH_TOKEN"),
+            env("TWILIO_PHONE_NUMBER"),
+        ) {
+            (Some(account_sid), Some(auth_token), Some(from_number)) => Some(TwilioCredentials {
+                account_sid,
+                auth_token,
+                from_number,
+            }),
+            _ => None,
+        };
+
+        Self {
+            discord_token: env("DISCORD_BOT_TOKEN"),
+            slack_token: env("SLACK_BOT_TOKEN"),
+            telegram_token: env("TELEGRAM_BOT_TOKEN"),
+            twilio,
+            email: None,
+        }
+    }
+
+    /// Set the Discord bot token
+    pub fn with_discord_token(mut self, token: impl Into<String>) -> Self {
+        self.discord_token = Some(token.into());
+        self
+    }
+
+    /// Set the email transport
+    pub fn with_email(mut self, email: Arc<dyn EmailTransport>) -> Self {
+        self.email = Some(email);
+        self
+    }
+}
+
+/// Built-in channel created from a [`ChannelConfig`]
+pub struct ConfiguredChannel {
+    config: ChannelConfig,
+    credentials: NotifierCredentials,
+    http: Client,
+}
+
+impl ConfiguredChannel {
+    pub fn new(config: ChannelConfig, credentials: NotifierCredentials, http: Client) -> Self {
+        Self {
+            config,
+            credentials,
+            http,
+        }
+    }
+
+    async fn post_json(&self, url: &str, body: serde_json::Value, service: &str) -> Result<reqwest::Response> {
+        let response = self.http.post(url).json(&body).send().await?;
+        check_status(response, service).await
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for ConfiguredChannel {
+    fn describe(&self) -> String {
+        self.config.describe()
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let text = notification.to_text();
+        let creds = &self.credentials;
+
+        match &self.config {
+            ChannelConfig::DiscordWebhook { url } => {
+                self.post_json(
+                    url,
+                    json!({ "content": truncate(&text, DISCORD_MAX_CHARS) }),
+                    "Discord",
+                )
+                .await?;
+            }
+            ChannelConfig::Discord { channel_id } => {
+                let token = require(&creds.discord_token, "DISCORD_BOT_TOKEN")?;
+                let response = self
+                    .http
+                    .post(format!(
+                        "https://discord.com/api/v10/channels/{}/messages",
+                        channel_id
+                    ))
+                    .header("Authorization", format!("Bot {}", token))
+                    .json(&json!({ "content": truncate(&text, DISCORD_MAX_CHARS) }))
+                    .send()
+                    .await?;
+                check_status(response, "Discord").await?;
+            }
+            ChannelConfig::SlackWebhook { url } => {
+                self.post_json(url, json!({ "text": truncate(&text, SLACK_MAX_CHARS) }), "Slack")
+                    .await?;
+            }
+            ChannelConfig::Slack { channel } => {
+                let token = require(&creds.slack_token, "SLACK_BOT_TOKEN")?;
+                let response = self
+                    .http
+                    .post("https://slack.com/api/chat.postMessage")
+                    .bearer_auth(token)
+                    .json(&json!({ "channel": channel, "text": truncate(&text, SLACK_MAX_CHARS) }))
+                    .send()
+                    .await?;
+                let body: serde_json::Value = check_status(response, "Slack").await?.json().await?;
+                // Slack returns HTTP 200 with ok: false on errors
+                if body.get("ok").and_then(|v| v.as_bool()) != Some(true) {
+                    return Err(Error::Notify(format!(
+                        "Slack API error: {}",
+                        body.get("error").and_then(|v| v.as_str()).unwrap_or("unknown")
+                    )));
+                }
+            }
+            ChannelConfig::Telegram { chat_id } => {
+                let token = require(&creds.telegram_token, "TELEGRAM_BOT_TOKEN")?;
+                self.post_json(
+                    &format!("https://api.telegram.org/bot{}/sendMessage", token),
+                    json!({ "chat_id": chat_id, "text": truncate(&text, TELEGRAM_MAX_CHARS) }),
+                    "Telegram",
+                )
+                .await?;
+            }
+            ChannelConfig::Email { to, subject } => {
+                let email = creds
+                    .email
+                    .as_ref()
+                    .ok_or_else(|| Error::Notify("email transport is not configured".to_string()))?;
+                let subject = subject
+                    .clone()
+                    .unwrap_or_else(|| format!("[cc-gateway] {}", notification.title));
+                email.send_email(to, &subject, &text).await?;
+            }
+            ChannelConfig::Sms { to } => {
+                let twilio = creds.twilio.as_ref().ok_or_else(|| {
+                    Error::Notify("TWILIO_ACCOUNT_SID / TWILIO_AUTH_TOKEN / TWILIO_PHONE_NUMBER are not set".to_string())
+                })?;
+                let response = self
+                    .http
+                    .post(format!(
+                        "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+                        twilio.account_sid
+                    ))
+                    .basic_auth(&twilio.account_sid, Some(&twilio.auth_token))
+                    .form(&[
+                        ("To", to.as_str()),
+                        ("From", twilio.from_number.as_str()),
+                        ("Body", truncate(&text, SMS_MAX_CHARS)),
+                    ])
+                    .send()
+                    .await?;
+                check_status(response, "Twilio").await?;
+            }
+            ChannelConfig::Webhook { url, headers } => {
+                let mut request = self.http.post(url).json(notification);
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                check_status(request.send().await?, "Webhook").await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn require<'a>(value: &'a Option<String>, name: &str) -> Result<&'a str> {
+    value
+        .as_deref()
+        .ok_or_else(|| Error::Notify(format!("{} is not set", name)))
+}
+
+async fn check_status(response: reqwest::Response, service: &str) -> Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(Error::Notify(format!("{} returned {}: {}", service, status, body)))
+}
+
+/// Truncate to at most `max` characters
+fn truncate(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("こんにちは", 3), "こんに");
+        assert_eq!(truncate("abc", 10), "abc");
+    }
+
+    #[tokio::test]
+    async fn test_missing_credentials() {
+        let channel = ConfiguredChannel::new(
+            ChannelConfig::Sms {
+                to: "+10000000000".to_string(),
+            },
+            NotifierCredentials::default(),
+            Client::new(),
+        );
+        assert_eq!(channel.describe(), "sms:+10000000000");
+        assert!(matches!(
+            channel.send(&Notification::info("t", "b")).await,
+            Err(Error::Notify(_))
+        ));
+    }
+}
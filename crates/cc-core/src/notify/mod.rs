@@ -0,0 +1,18 @@
+//! Outbound notifications
+//!
+//! 論理的なターゲット名 (例: `ops`) に対して Discord Webhook / Slack / Telegram /
+//! メール / SMS (Twilio) / Webhook へ通知を送信します。
+//! スケジューラーの結果配信、LLM API のクォータ警告、エラー通知で使用します。
+
+mod channel;
+mod notifier;
+mod types;
+
+pub use channel::{
+    ConfiguredChannel, EmailTransport, NotificationChannel, NotifierCredentials,
+    TwilioCredentials,
+};
+pub use notifier::Notifier;
+pub use types::{
+    AlertKind, ChannelConfig, Notification, NotificationLevel, NotifyConfig, NotifyRoutes,
+};
@@ -0,0 +1,267 @@
+//! Notifier: delivers notifications to logical targets
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use tracing::{info, warn};
+
+use super::channel::{ConfiguredChannel, NotificationChannel, NotifierCredentials};
+use super::types::{AlertKind, Notification, NotifyConfig, NotifyRoutes};
+use crate::{Error, Result};
+
+/// Pushes notifications to channels grouped under logical target names
+///
+/// Share it as `Arc<Notifier>` between the scheduler, the LLM client and gateways.
+pub struct Notifier {
+    targets: HashMap<String, Vec<Arc<dyn NotificationChannel>>>,
+    routes: NotifyRoutes,
+    quota_threshold: f64,
+    cooldown: Duration,
+    /// Last delivery time of each alert (for the cooldown)
+    last_alerts: Mutex<HashMap<(AlertKind, String), Instant>>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::from_config(&NotifyConfig::default(), NotifierCredentials::default())
+    }
+}
+
+impl Notifier {
+    /// Create a notifier without targets
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a notifier with the built-in channels from `[notify]`
+    pub fn from_config(config: &NotifyConfig, credentials: NotifierCredentials) -> Self {
+        let http = Client::new();
+        let targets = config
+            .targets
+            .iter()
+            .map(|(name, channels)| {
+                let channels = channels
+                    .iter()
+                    .map(|channel| {
+                        Arc::new(ConfiguredChannel::new(
+                            channel.clone(),
+                            credentials.clone(),
+                            http.clone(),
+                        )) as Arc<dyn NotificationChannel>
+                    })
+                    .collect();
+                (name.clone(), channels)
+            })
+            .collect();
+
+        Self {
+            targets,
+            routes: config.routes.clone(),
+            quota_threshold: config.quota_threshold,
+            cooldown: Duration::from_secs(config.cooldown_secs),
+            last_alerts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Add a channel to a target
+    pub fn with_channel(mut self, target: impl Into<String>, channel: Arc<dyn NotificationChannel>) -> Self {
+        self.targets.entry(target.into()).or_default().push(channel);
+        self
+    }
+
+    /// Set the alert routes
+    pub fn with_routes(mut self, routes: NotifyRoutes) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// Configured target names (sorted)
+    pub fn targets(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.targets.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn has_target(&self, target: &str) -> bool {
+        self.targets.contains_key(target)
+    }
+
+    /// Remaining-quota fraction below which quota alerts are sent
+    pub fn quota_threshold(&self) -> f64 {
+        self.quota_threshold
+    }
+
+    /// Whether alerts of this kind are routed anywhere
+    pub fn routes_alert(&self, kind: AlertKind) -> bool {
+        self.routes.target(kind).is_some()
+    }
+
+    /// Send a notification to every channel of a target
+    ///
+    /// Failures of individual channels are logged; an error is returned only
+    /// if the target is unknown or every channel failed.
+    pub async fn notify(&self, target: &str, notification: &Notification) -> Result<usize> {
+        let channels = self
+            .targets
+            .get(target)
+            .ok_or_else(|| Error::Notify(format!("unknown notification target: {}", target)))?;
+
+        let mut sent = 0;
+        let mut last_error = None;
+        for channel in channels {
+            match channel.send(notification).await {
+                Ok(()) => {
+                    sent += 1;
+                    info!(target, channel = %channel.describe(), "Notification sent");
+                }
+                Err(e) => {
+                    warn!(target, channel = %channel.describe(), "Notification failed: {}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if sent == 0 => Err(e),
+            _ => Ok(sent),
+        }
+    }
+
+    /// Send an automatic alert to its routed target
+    ///
+    /// Identical alerts (same kind and title) are suppressed during the cooldown.
+    /// Returns true if the alert was delivered to at least one channel.
+    pub async fn alert(&self, kind: AlertKind, notification: &Notification) -> bool {
+        let Some(target) = self.routes.target(kind) else {
+            return false;
+        };
+
+        {
+            let mut last_alerts = self.last_alerts.lock().unwrap_or_else(|e| e.into_inner());
+            let key = (kind, notification.title.clone());
+            let now = Instant::now();
+            if last_alerts
+                .get(&key)
+                .is_some_and(|sent| now.duration_since(*sent) < self.cooldown)
+            {
+                return false;
+            }
+            last_alerts.insert(key, now);
+        }
+
+        match self.notify(target, notification).await {
+            Ok(sent) => sent > 0,
+            Err(e) => {
+                warn!(?kind, "Alert delivery failed: {}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Records notifications, or fails every send
+    struct Recorder {
+        sent: Mutex<Vec<Notification>>,
+        fail: bool,
+    }
+
+    impl Recorder {
+        fn new(fail: bool) -> Arc<Self> {
+            Arc::new(Self {
+                sent: Mutex::new(Vec::new()),
+                fail,
+            })
+        }
+
+        fn count(&self) -> usize {
+            self.sent.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait]
+    impl NotificationChannel for Recorder {
+        fn describe(&self) -> String {
+            "recorder".to_string()
+        }
+
+        async fn send(&self, notification: &Notification) -> Result<()> {
+            if self.fail {
+                return Err(Error::Notify("down".to_string()));
+            }
+            self.sent.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notify_target() {
+        let ok = Recorder::new(false);
+        let broken = Recorder::new(true);
+        let notifier = Notifier::new()
+            .with_channel("ops", ok.clone())
+            .with_channel("ops", broken.clone())
+            .with_channel("dead", broken);
+
+        assert_eq!(notifier.targets(), vec!["dead", "ops"]);
+        assert_eq!(notifier.notify("ops", &Notification::info("hi", "")).await.unwrap(), 1);
+        assert_eq!(ok.count(), 1);
+        assert!(notifier.notify("dead", &Notification::info("hi", "")).await.is_err());
+        assert!(notifier.notify("missing", &Notification::info("hi", "")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_alert_routes_and_cooldown() {
+        let ops = Recorder::new(false);
+        let notifier = Notifier::new()
+            .with_channel("ops", ops.clone())
+            .with_routes(NotifyRoutes {
+                quota: Some("ops".to_string()),
+                errors: None,
+            });
+
+        let warning = Notification::warning("Rate limit low", "tokens: 5%");
+        assert!(notifier.routes_alert(AlertKind::Quota));
+        assert!(notifier.alert(AlertKind::Quota, &warning).await);
+        // Same alert within the cooldown is suppressed
+        assert!(!notifier.alert(AlertKind::Quota, &warning).await);
+        // Unrouted alerts are dropped
+        assert!(!notifier.alert(AlertKind::Error, &Notification::error("x", "")).await);
+        assert_eq!(ops.count(), 1);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config: NotifyConfig = toml::from_str(
+            r#"
+quota_threshold = 0.2
+
+[routes]
+errors = "ops"
+
+[[targets.ops]]
+type = "discord_webhook"
+url = "https://discord.com/api/webhooks/1/x"
+
+[[targets.ops]]
+type = "sms"
+to = "+819012345678"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.targets["ops"].len(), 2);
+        assert_eq!(config.cooldown_secs, 900);
+
+        let notifier = Notifier::from_config(&config, NotifierCredentials::default());
+        assert!(notifier.has_target("ops"));
+        assert!(notifier.routes_alert(AlertKind::Error));
+        assert!(!notifier.routes_alert(AlertKind::Quota));
+        assert_eq!(notifier.quota_threshold(), 0.2);
+    }
+}
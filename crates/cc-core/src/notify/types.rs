@@ -0,0 +1,196 @@
+//! Notification types and configuration
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Severity of a notification
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationLevel {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl NotificationLevel {
+    fn icon(&self) -> &'static str {
+        match self {
+            Self::Info => "ℹ️",
+            Self::Warning => "⚠️",
+            Self::Error => "🚨",
+        }
+    }
+}
+
+/// A message pushed to notification channels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub level: NotificationLevel,
+}
+
+impl Notification {
+    pub fn new(level: NotificationLevel, title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+            level,
+        }
+    }
+
+    pub fn info(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self::new(NotificationLevel::Info, title, body)
+    }
+
+    pub fn warning(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self::new(NotificationLevel::Warning, title, body)
+    }
+
+    pub fn error(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self::new(NotificationLevel::Error, title, body)
+    }
+
+    /// Plain text rendering (`<icon> [title]` followed by the body)
+    pub fn to_text(&self) -> String {
+        if self.body.is_empty() {
+            format!("{} [{}]", self.level.icon(), self.title)
+        } else {
+            format!("{} [{}]\n{}", self.level.icon(), self.title, self.body)
+        }
+    }
+}
+
+/// Kinds of automatic alerts routed through `[notify.routes]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    /// LLM API rate limit / quota running low
+    Quota,
+    /// LLM API errors (authentication, overload, exhausted retries)
+    Error,
+}
+
+/// A notification channel
+///
+/// Credentials that are omitted fall back to the usual environment variables
+/// (`DISCORD_BOT_TOKEN`, `SLACK_BOT_TOKEN`, `TELEGRAM_BOT_TOKEN`,
+/// `TWILIO_ACCOUNT_SID` / `TWILIO_AUTH_TOKEN` / `TWILIO_PHONE_NUMBER`, `SMTP_*`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChannelConfig {
+    /// Discord incoming webhook URL
+    DiscordWebhook { url: String },
+    /// Discord channel via the bot
+    Discord { channel_id: String },
+    /// Slack incoming webhook URL
+    SlackWebhook { url: String },
+    /// Slack channel via the bot (`chat.postMessage`)
+    Slack { channel: String },
+    /// Telegram chat via the bot
+    Telegram { chat_id: String },
+    /// Email (requires an email transport)
+    Email {
+        to: String,
+        #[serde(default)]
+        subject: Option<String>,
+    },
+    /// SMS via Twilio
+    Sms { to: String },
+    /// JSON POST to an arbitrary URL
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+impl ChannelConfig {
+    /// Short description for logs
+    pub fn describe(&self) -> String {
+        match self {
+            Self::DiscordWebhook { .. } => "discord_webhook".to_string(),
+            Self::Discord { channel_id } => format!("discord:{}", channel_id),
+            Self::SlackWebhook { .. } => "slack_webhook".to_string(),
+            Self::Slack { channel } => format!("slack:{}", channel),
+            Self::Telegram { chat_id } => format!("telegram:{}", chat_id),
+            Self::Email { to, .. } => format!("email:{}", to),
+            Self::Sms { to } => format!("sms:{}", to),
+            Self::Webhook { url, .. } => format!("webhook:{}", url),
+        }
+    }
+}
+
+/// Which logical targets receive automatic alerts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotifyRoutes {
+    /// Target for LLM quota / rate-limit warnings
+    #[serde(default)]
+    pub quota: Option<String>,
+    /// Target for LLM API error alerts
+    #[serde(default)]
+    pub errors: Option<String>,
+}
+
+impl NotifyRoutes {
+    pub fn target(&self, kind: AlertKind) -> Option<&str> {
+        match kind {
+            AlertKind::Quota => self.quota.as_deref(),
+            AlertKind::Error => self.errors.as_deref(),
+        }
+    }
+}
+
+/// `[notify]` configuration
+///
+/// ```toml
+/// [notify.routes]
+/// quota = "ops"
+/// errors = "ops"
+///
+/// [[notify.targets.ops]]
+/// type = "discord_webhook"
+/// url = "https://discord.com/api/webhooks/..."
+///
+/// [[notify.targets.ops]]
+/// type = "sms"
+/// to = "+819012345678"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    /// Logical target name → channels
+    #[serde(default)]
+    pub targets: HashMap<String, Vec<ChannelConfig>>,
+
+    #[serde(default)]
+    pub routes: NotifyRoutes,
+
+    /// Warn when less than this fraction of the rate limit remains
+    #[serde(default = "default_quota_threshold")]
+    pub quota_threshold: f64,
+
+    /// Minimum interval between identical alerts (seconds)
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            targets: HashMap::new(),
+            routes: NotifyRoutes::default(),
+            quota_threshold: default_quota_threshold(),
+            cooldown_secs: default_cooldown_secs(),
+        }
+    }
+}
+
+fn default_quota_threshold() -> f64 {
+    0.1
+}
+
+fn default_cooldown_secs() -> u64 {
+    900
+}
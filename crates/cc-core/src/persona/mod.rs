@@ -0,0 +1,10 @@
+//! Persona / profile system
+//!
+//! ペルソナ (名前・システムプロンプト・ツール許可リスト・モデル・temperature)
+//! を定義し、チャンネル・ユーザー・セッションごとに切り替えられるようにします。
+
+mod registry;
+mod types;
+
+pub use registry::PersonaRegistry;
+pub use types::{Persona, PersonasConfig};
@@ -0,0 +1,214 @@
+//! Persona registry
+
+use std::collections::HashMap;
+
+use tracing::warn;
+
+use super::types::{Persona, PersonasConfig};
+
+/// Collection of configured personas with channel / user bindings
+#[derive(Debug, Clone, Default)]
+pub struct PersonaRegistry {
+    /// Personas in configuration order
+    personas: Vec<Persona>,
+    default: Option<String>,
+    channels: HashMap<String, String>,
+    users: HashMap<String, String>,
+}
+
+impl PersonaRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a registry from configuration
+    ///
+    /// Bindings that reference unknown personas are ignored with a warning.
+    pub fn from_config(config: &PersonasConfig) -> Self {
+        let mut registry = Self::new();
+        for persona in &config.profiles {
+            registry.add(persona.clone());
+        }
+
+        registry.default = config
+            .default
+            .clone()
+            .filter(|name| registry.check_binding("default", name));
+        registry.channels = config
+            .channels
+            .iter()
+            .filter(|(id, name)| registry.check_binding(&format!("channel {}", id), name))
+            .map(|(id, name)| (id.clone(), name.clone()))
+            .collect();
+        registry.users = config
+            .users
+            .iter()
+            .filter(|(id, name)| registry.check_binding(&format!("user {}", id), name))
+            .map(|(id, name)| (id.clone(), name.clone()))
+            .collect();
+
+        registry
+    }
+
+    fn check_binding(&self, target: &str, name: &str) -> bool {
+        let exists = self.get(name).is_some();
+        if !exists {
+            warn!("Unknown persona '{}' for {}", name, target);
+        }
+        exists
+    }
+
+    /// Add (or replace) a persona
+    pub fn add(&mut self, persona: Persona) {
+        match self.personas.iter_mut().find(|p| p.name == persona.name) {
+            Some(existing) => *existing = persona,
+            None => self.personas.push(persona),
+        }
+    }
+
+    /// Get a persona by name
+    pub fn get(&self, name: &str) -> Option<&Persona> {
+        self.personas.iter().find(|p| p.name == name)
+    }
+
+    /// All personas in configuration order
+    pub fn list(&self) -> &[Persona] {
+        &self.personas
+    }
+
+    /// Check if no personas are configured
+    pub fn is_empty(&self) -> bool {
+        self.personas.is_empty()
+    }
+
+    /// Name of the default persona
+    pub fn default_name(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// Resolve the effective persona
+    ///
+    /// Priority: session selection > user binding > channel binding > default.
+    pub fn resolve(
+        &self,
+        session: Option<&str>,
+        user_id: Option<&str>,
+        channel_id: Option<&str>,
+    ) -> Option<&Persona> {
+        self.resolve_with_default(session, user_id, channel_id, None)
+    }
+
+    /// Resolve the effective persona, with `default` taking precedence over
+    /// the configured default (e.g. a per-server default)
+    pub fn resolve_with_default(
+        &self,
+        session: Option<&str>,
+        user_id: Option<&str>,
+        channel_id: Option<&str>,
+        default: Option<&str>,
+    ) -> Option<&Persona> {
+        session
+            .and_then(|name| self.get(name))
+            .or_else(|| user_id.and_then(|id| self.users.get(id)).and_then(|n| self.get(n)))
+            .or_else(|| {
+                channel_id
+                    .and_then(|id| self.channels.get(id))
+                    .and_then(|n| self.get(n))
+            })
+            .or_else(|| default.and_then(|n| self.get(n)))
+            .or_else(|| self.default.as_deref().and_then(|n| self.get(n)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PersonasConfig {
+        PersonasConfig {
+            default: Some("assistant".to_string()),
+            channels: HashMap::from([("chan-1".to_string(), "coder".to_string())]),
+            users: HashMap::from([
+                ("user-1".to_string(), "teacher".to_string()),
+                ("user-2".to_string(), "missing".to_string()),
+            ]),
+            profiles: vec![
+                Persona::new("assistant"),
+                Persona::new("coder"),
+                Persona::new("teacher"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_resolve_priority() {
+        let registry = PersonaRegistry::from_config(&config());
+
+        let name = |p: Option<&Persona>| p.map(|p| p.name.clone());
+        assert_eq!(
+            name(registry.resolve(Some("coder"), Some("user-1"), None)),
+            Some("coder".to_string())
+        );
+        assert_eq!(
+            name(registry.resolve(None, Some("user-1"), Some("chan-1"))),
+            Some("teacher".to_string())
+        );
+        assert_eq!(
+            name(registry.resolve(None, Some("user-9"), Some("chan-1"))),
+            Some("coder".to_string())
+        );
+        assert_eq!(
+            name(registry.resolve(Some("unknown"), None, None)),
+            Some("assistant".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_with_default() {
+        let registry = PersonaRegistry::from_config(&config());
+        let name = |p: Option<&Persona>| p.map(|p| p.name.clone());
+        assert_eq!(
+            name(registry.resolve_with_default(None, None, None, Some("teacher"))),
+            Some("teacher".to_string())
+        );
+        // Bindings still win over the override
+        assert_eq!(
+            name(registry.resolve_with_default(None, None, Some("chan-1"), Some("teacher"))),
+            Some("coder".to_string())
+        );
+        assert_eq!(
+            name(registry.resolve_with_default(None, None, None, Some("missing"))),
+            Some("assistant".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unknown_bindings_are_dropped() {
+        let registry = PersonaRegistry::from_config(&config());
+        // user-2 points at a missing persona, so the default applies
+        assert_eq!(
+            registry.resolve(None, Some("user-2"), None).map(|p| p.name.as_str()),
+            Some("assistant")
+        );
+    }
+
+    #[test]
+    fn test_empty_registry() {
+        let registry = PersonaRegistry::new();
+        assert!(registry.is_empty());
+        assert!(registry.resolve(Some("coder"), None, None).is_none());
+    }
+
+    #[test]
+    fn test_add_replaces_existing() {
+        let mut registry = PersonaRegistry::new();
+        registry.add(Persona::new("coder"));
+        registry.add(Persona {
+            temperature: Some(0.1),
+            ..Persona::new("coder")
+        });
+        assert_eq!(registry.list().len(), 1);
+        assert_eq!(registry.get("coder").unwrap().temperature, Some(0.1));
+    }
+}
@@ -0,0 +1,184 @@
+//! Persona types
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::llm::{MessagesRequest, ToolDefinition};
+
+/// A named assistant profile
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Persona {
+    /// Unique persona name (used in `/persona <name>`)
+    pub name: String,
+
+    /// Short description shown in persona listings
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// System prompt (falls back to the caller's default when unset)
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+
+    /// Tool allowlist (`None` = all tools)
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+
+    /// Model override
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Sampling temperature override
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+impl Persona {
+    /// Create a persona with only a name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Check whether a tool may be used by this persona
+    pub fn allows_tool(&self, tool_name: &str) -> bool {
+        self.allowed_tools
+            .as_ref()
+            .is_none_or(|allowed| allowed.iter().any(|t| t == tool_name))
+    }
+
+    /// Drop tools that are not in the allowlist
+    pub fn filter_tools(&self, tools: Vec<ToolDefinition>) -> Vec<ToolDefinition> {
+        tools
+            .into_iter()
+            .filter(|tool| self.allows_tool(&tool.name))
+            .collect()
+    }
+
+    /// Apply this persona's overrides to a request
+    pub fn apply(&self, request: &mut MessagesRequest) {
+        if let Some(system) = &self.system_prompt {
+            request.system = Some(system.clone());
+        }
+        if let Some(model) = &self.model {
+            request.model = model.clone();
+        }
+        if let Some(temperature) = self.temperature {
+            request.temperature = Some(temperature);
+        }
+        if let Some(tools) = request.tools.take() {
+            let tools = self.filter_tools(tools);
+            request.tools = if tools.is_empty() { None } else { Some(tools) };
+        }
+    }
+}
+
+/// Persona configuration (`[personas]` in cc-gateway.toml)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersonasConfig {
+    /// Persona used when nothing else is selected
+    #[serde(default)]
+    pub default: Option<String>,
+
+    /// Channel ID → persona name
+    #[serde(default)]
+    pub channels: HashMap<String, String>,
+
+    /// User ID → persona name
+    #[serde(default)]
+    pub users: HashMap<String, String>,
+
+    /// Persona definitions (`[[personas.profiles]]`)
+    #[serde(default)]
+    pub profiles: Vec<Persona>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MessagesRequestBuilder;
+
+    fn tool(name: &str) -> ToolDefinition {
+        ToolDefinition::new(name, "test", serde_json::json!({"type": "object"}))
+    }
+
+    #[test]
+    fn test_allows_tool() {
+        let mut persona = Persona::new("coder");
+        assert!(persona.allows_tool("bash"));
+
+        persona.allowed_tools = Some(vec!["read".to_string()]);
+        assert!(persona.allows_tool("read"));
+        assert!(!persona.allows_tool("bash"));
+    }
+
+    #[test]
+    fn test_apply_overrides_request() {
+        let persona = Persona {
+            name: "coder".to_string(),
+            system_prompt: Some("You write code.".to_string()),
+            allowed_tools: Some(vec!["read".to_string()]),
+            model: Some("claude-opus".to_string()),
+            temperature: Some(0.2),
+            ..Default::default()
+        };
+
+        let mut request = MessagesRequestBuilder::new("claude-sonnet".to_string())
+            .system("default")
+            .user("hi")
+            .tool(tool("read"))
+            .tool(tool("bash"))
+            .build();
+        persona.apply(&mut request);
+
+        assert_eq!(request.system.as_deref(), Some("You write code."));
+        assert_eq!(request.model, "claude-opus");
+        assert_eq!(request.temperature, Some(0.2));
+        let tools = request.tools.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "read");
+    }
+
+    #[test]
+    fn test_apply_keeps_unset_fields() {
+        let persona = Persona::new("plain");
+        let mut request = MessagesRequestBuilder::new("claude-sonnet".to_string())
+            .system("default")
+            .user("hi")
+            .build();
+        persona.apply(&mut request);
+
+        assert_eq!(request.system.as_deref(), Some("default"));
+        assert_eq!(request.model, "claude-sonnet");
+        assert!(request.temperature.is_none());
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        let config: PersonasConfig = toml::from_str(
+            r#"
+            default = "assistant"
+
+            [channels]
+            "123" = "coder"
+
+            [[profiles]]
+            name = "assistant"
+
+            [[profiles]]
+            name = "coder"
+            system_prompt = "You write code."
+            allowed_tools = ["read", "write"]
+            temperature = 0.2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.default.as_deref(), Some("assistant"));
+        assert_eq!(config.channels.get("123").map(String::as_str), Some("coder"));
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.profiles[1].temperature, Some(0.2));
+    }
+}
@@ -0,0 +1,113 @@
+//! Postgres connection pool and schema migrations
+//!
+//! Used by the Postgres session and memory stores (`postgres` feature).
+//! Migrations reuse the [`Migration`] lists of [`crate::migrate`] (SQL steps
+//! only) and are recorded in the same `schema_migrations` layout. An advisory
+//! lock serializes them, so instances started at the same time against one
+//! database do not apply a migration twice.
+
+use deadpool_postgres::{Pool, PoolConfig, Runtime};
+use tokio_postgres::NoTls;
+use tracing::info;
+
+use crate::migrate::{Migration, Step};
+use crate::{Error, Result};
+
+/// Key of the advisory lock held while migrating
+const MIGRATION_LOCK: i64 = 0x6363_6777_6d69_6772;
+
+/// Create a pool of up to `size` connections to `url`
+///
+/// Connections are opened when first needed.
+pub fn create_pool(url: &str, size: usize) -> Result<Pool> {
+    let config = deadpool_postgres::Config {
+        url: Some(url.to_string()),
+        pool: Some(PoolConfig::new(size.max(1))),
+        ..Default::default()
+    };
+    config
+        .create_pool(Some(Runtime::Tokio1), NoTls)
+        .map_err(|e| Error::Config(format!("Invalid memory.database_url: {}", e)))
+}
+
+/// Apply the pending migrations of `store` and return its schema version
+pub async fn migrate(pool: &Pool, store: &str, migrations: &[Migration]) -> Result<u32> {
+    let latest = migrations.last().map_or(0, |m| m.version);
+    let mut client = pool.get().await?;
+    loop {
+        let tx = client.transaction().await?;
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK]).await?;
+        tx.batch_execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                store TEXT NOT NULL,
+                version INTEGER NOT NULL,
+                description TEXT NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL,
+                PRIMARY KEY (store, version)
+            )",
+        )
+        .await?;
+        let current: i32 = tx
+            .query_one(
+                "SELECT COALESCE(MAX(version), 0) FROM schema_migrations WHERE store = $1",
+                &[&store],
+            )
+            .await?
+            .get(0);
+        let current = current as u32;
+        if current > latest {
+            return Err(Error::Storage(format!(
+                "{} schema version {} is newer than this build supports ({})",
+                store, current, latest
+            )));
+        }
+        let migration = match migrations.iter().find(|m| m.version == current + 1) {
+            Some(migration) => migration,
+            None => return Ok(current),
+        };
+
+        match migration.step {
+            Step::Sql(sql) => tx.batch_execute(sql).await?,
+            Step::Run(_) => {
+                return Err(Error::Storage(format!(
+                    "Migration {} of {} is not SQL and cannot run on Postgres",
+                    migration.version, store
+                )));
+            }
+        }
+        tx.execute(
+            "INSERT INTO schema_migrations (store, version, description, applied_at)
+             VALUES ($1, $2, $3, now())",
+            &[&store, &(migration.version as i32), &migration.description],
+        )
+        .await?;
+        tx.commit().await?;
+        info!(store, version = migration.version, "Applied migration: {}", migration.description);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_pool_rejects_invalid_url() {
+        assert!(create_pool("postgres://localhost/cc_gateway", 4).is_ok());
+        assert!(create_pool("not a url", 4).is_err());
+    }
+
+    /// Runs against the database in CC_TEST_DATABASE_URL, skipped without it
+    #[tokio::test]
+    async fn test_migrate() {
+        let url = match std::env::var("CC_TEST_DATABASE_URL") {
+            Ok(url) => url,
+            Err(_) => return,
+        };
+        let pool = create_pool(&url, 2).unwrap();
+        let store = format!("test_{}", uuid::Uuid::new_v4().simple());
+        let migrations = [Migration::sql(1, "noop", "SELECT 1")];
+        assert_eq!(migrate(&pool, &store, &migrations).await.unwrap(), 1);
+        assert_eq!(migrate(&pool, &store, &migrations).await.unwrap(), 1);
+        assert!(migrate(&pool, &store, &[]).await.is_err());
+    }
+}
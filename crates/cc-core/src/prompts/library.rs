@@ -0,0 +1,310 @@
+//! Prompt library loaded from a directory
+//!
+//! `prompts/` 以下のテンプレートファイル (`.md`, `.txt`, `.hbs`) を読み込みます。
+//! テンプレート名はディレクトリからの相対パス (拡張子なし) です。
+//! 例: `prompts/channels/discord.md` → `channels/discord`
+//!
+//! ファイルの更新日時を監視し、変更があれば自動的に再読み込みします。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use chrono::Local;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::template::PromptTemplate;
+use crate::{Error, Result};
+
+/// Template file extensions
+const TEMPLATE_EXTENSIONS: &[&str] = &["md", "txt", "hbs"];
+
+/// Variables available to a template
+#[derive(Debug, Clone)]
+pub struct PromptContext {
+    vars: Map<String, Value>,
+}
+
+impl PromptContext {
+    /// Create a context pre-populated with `date`, `time`, `datetime` and `weekday`
+    pub fn new() -> Self {
+        let now = Local::now();
+        let mut vars = Map::new();
+        vars.insert("date".into(), now.format("%Y-%m-%d").to_string().into());
+        vars.insert("time".into(), now.format("%H:%M").to_string().into());
+        vars.insert("datetime".into(), now.format("%Y-%m-%d %H:%M:%S %Z").to_string().into());
+        vars.insert("weekday".into(), now.format("%A").to_string().into());
+        Self { vars }
+    }
+
+    /// Set the user's display name (`user_name`)
+    pub fn user_name(self, name: impl Into<String>) -> Self {
+        self.var("user_name", name.into())
+    }
+
+    /// Set the channel identifier (`channel`)
+    pub fn channel(self, channel: impl Into<String>) -> Self {
+        self.var("channel", channel.into())
+    }
+
+    /// Set the agent name (`agent`)
+    pub fn agent(self, agent: impl Into<String>) -> Self {
+        self.var("agent", agent.into())
+    }
+
+    /// Set memory snippets (`memories`, iterate with `{{#each memories}}`)
+    pub fn memories(self, memories: Vec<String>) -> Self {
+        self.var("memories", memories)
+    }
+
+    /// Set an arbitrary variable
+    pub fn var(mut self, key: impl Into<String>, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        self.vars.insert(key.into(), value);
+        self
+    }
+
+    /// Get the variables as a JSON object
+    pub fn to_value(&self) -> Value {
+        Value::Object(self.vars.clone())
+    }
+}
+
+impl Default for PromptContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LoadedTemplate {
+    template: PromptTemplate,
+    modified: Option<SystemTime>,
+}
+
+/// A directory of prompt templates with hot reload
+pub struct PromptLibrary {
+    dir: PathBuf,
+    templates: RwLock<HashMap<String, LoadedTemplate>>,
+}
+
+impl PromptLibrary {
+    /// Create a library and load all templates from `dir`
+    pub fn load(dir: impl Into<PathBuf>) -> Result<Self> {
+        let library = Self {
+            dir: dir.into(),
+            templates: RwLock::new(HashMap::new()),
+        };
+        let count = library.reload()?;
+        info!("Loaded {} prompt templates from {}", count, library.dir.display());
+        Ok(library)
+    }
+
+    /// Template directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Names of all loaded templates
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .templates
+            .read()
+            .map(|t| t.keys().cloned().collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Get a template by name
+    pub fn get(&self, name: &str) -> Option<PromptTemplate> {
+        self.templates
+            .read()
+            .ok()?
+            .get(name)
+            .map(|t| t.template.clone())
+    }
+
+    /// Render a template by name
+    pub fn render(&self, name: &str, context: &PromptContext) -> Option<String> {
+        self.get(name).map(|t| t.render(&context.to_value()))
+    }
+
+    /// Render the first template that exists among `candidates`
+    ///
+    /// e.g. `["agents/coder", "channels/discord", "default"]`
+    pub fn render_first(&self, candidates: &[&str], context: &PromptContext) -> Option<String> {
+        candidates
+            .iter()
+            .find_map(|name| self.render(name, context))
+    }
+
+    /// Rescan the directory, re-parsing new or modified files
+    ///
+    /// Returns the number of templates (re)loaded. Files that fail to parse
+    /// keep their previous version.
+    pub fn reload(&self) -> Result<usize> {
+        if !self.dir.exists() {
+            return Err(Error::Config(format!(
+                "Prompt directory not found: {}",
+                self.dir.display()
+            )));
+        }
+
+        let mut files = Vec::new();
+        collect_template_files(&self.dir, &mut files)?;
+
+        let mut templates = self
+            .templates
+            .write()
+            .map_err(|_| Error::Other("Prompt library lock poisoned".to_string()))?;
+
+        let mut seen = Vec::with_capacity(files.len());
+        let mut reloaded = 0;
+
+        for path in files {
+            let Some(name) = template_name(&self.dir, &path) else {
+                continue;
+            };
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+            seen.push(name.clone());
+
+            if let Some(existing) = templates.get(&name) {
+                if existing.modified.is_some() && existing.modified == modified {
+                    continue;
+                }
+            }
+
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(Error::from)
+                .and_then(|source| PromptTemplate::parse(&name, source));
+            match parsed {
+                Ok(template) => {
+                    debug!("Loaded prompt template: {}", name);
+                    templates.insert(name, LoadedTemplate { template, modified });
+                    reloaded += 1;
+                }
+                Err(e) => warn!("Failed to load prompt template {}: {}", path.display(), e),
+            }
+        }
+
+        templates.retain(|name, _| seen.contains(name));
+        Ok(reloaded)
+    }
+
+    /// Poll the directory for changes every `interval`
+    pub fn spawn_hot_reload(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let library = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match library.reload() {
+                    Ok(0) => {}
+                    Ok(n) => info!("Reloaded {} prompt templates", n),
+                    Err(e) => warn!("Prompt hot reload failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+fn collect_template_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_template_files(&path, out)?;
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| TEMPLATE_EXTENSIONS.contains(&e))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// `prompts/channels/discord.md` → `channels/discord`
+fn template_name(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?.with_extension("");
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_and_render() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("default.md"), "Hello {{user_name}}").unwrap();
+        std::fs::create_dir(dir.path().join("channels")).unwrap();
+        std::fs::write(
+            dir.path().join("channels/discord.md"),
+            "Discord #{{channel}}",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("ignored.json"), "{}").unwrap();
+
+        let library = PromptLibrary::load(dir.path()).unwrap();
+        assert_eq!(library.names(), vec!["channels/discord", "default"]);
+
+        let ctx = PromptContext::new().user_name("Alice").channel("general");
+        assert_eq!(library.render("default", &ctx).unwrap(), "Hello Alice");
+        assert_eq!(
+            library
+                .render_first(&["channels/slack", "channels/discord"], &ctx)
+                .unwrap(),
+            "Discord #general"
+        );
+        assert!(library.render("missing", &ctx).is_none());
+    }
+
+    #[test]
+    fn test_reload_picks_up_changes() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("default.md");
+        std::fs::write(&path, "v1").unwrap();
+
+        let library = PromptLibrary::load(dir.path()).unwrap();
+        assert_eq!(library.get("default").unwrap().source(), "v1");
+
+        std::fs::write(&path, "v2").unwrap();
+        // Force a different mtime regardless of filesystem resolution
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+
+        std::fs::write(dir.path().join("new.md"), "new").unwrap();
+        library.reload().unwrap();
+        assert_eq!(library.get("default").unwrap().source(), "v2");
+        assert!(library.get("new").is_some());
+
+        std::fs::remove_file(dir.path().join("new.md")).unwrap();
+        library.reload().unwrap();
+        assert!(library.get("new").is_none());
+    }
+
+    #[test]
+    fn test_context_builtins() {
+        let value = PromptContext::new().to_value();
+        assert!(value.get("date").is_some());
+        assert!(value.get("time").is_some());
+        assert!(value.get("weekday").is_some());
+    }
+
+    #[test]
+    fn test_missing_dir_is_error() {
+        assert!(PromptLibrary::load("/nonexistent/prompts").is_err());
+    }
+}
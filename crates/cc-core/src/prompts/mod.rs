@@ -0,0 +1,10 @@
+//! System prompt templates
+//!
+//! チャンネル・エージェントごとのシステムプロンプトをテンプレートから生成します。
+//! テンプレートは `prompts/` ディレクトリから読み込まれ、変更は自動的に反映されます。
+
+pub mod library;
+pub mod template;
+
+pub use library::{PromptContext, PromptLibrary};
+pub use template::PromptTemplate;
@@ -0,0 +1,308 @@
+//! Handlebars-style prompt templates
+//!
+//! サポートする構文:
+//! - `{{name}}` / `{{user.name}}` - 変数展開 (未定義の場合は空文字)
+//! - `{{#if name}}...{{else}}...{{/if}}` - 条件分岐 (空文字・空配列・false・null は偽)
+//! - `{{#each items}}...{{this}}...{{/each}}` - 配列の繰り返し
+//! - `{{! comment }}` - コメント
+
+use serde_json::Value;
+
+use crate::{Error, Result};
+
+/// A parsed prompt template
+#[derive(Debug, Clone)]
+pub struct PromptTemplate {
+    name: String,
+    source: String,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var(String),
+    If {
+        condition: String,
+        then_branch: Vec<Node>,
+        else_branch: Vec<Node>,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+    },
+}
+
+impl PromptTemplate {
+    /// Parse a template
+    pub fn parse(name: impl Into<String>, source: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        let source = source.into();
+        let tokens = tokenize(&source);
+        let mut pos = 0;
+        let nodes = parse_nodes(&tokens, &mut pos, &name, None)?;
+
+        Ok(Self {
+            name,
+            source,
+            nodes,
+        })
+    }
+
+    /// Template name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Raw template source
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Render with the given variables (a JSON object)
+    pub fn render(&self, vars: &Value) -> String {
+        let mut out = String::with_capacity(self.source.len());
+        render_nodes(&self.nodes, &[vars], &mut out);
+        out
+    }
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Text(&'a str),
+    Tag(&'a str),
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        match rest[start + 2..].find("}}") {
+            Some(end) => {
+                tokens.push(Token::Tag(rest[start + 2..start + 2 + end].trim()));
+                rest = &rest[start + 2 + end + 2..];
+            }
+            None => {
+                // Unterminated tag: treat the remainder as literal text
+                tokens.push(Token::Text(&rest[start..]));
+                rest = "";
+            }
+        }
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+
+    tokens
+}
+
+/// Parse until the closing tag for `block` (if any)
+fn parse_nodes(
+    tokens: &[Token<'_>],
+    pos: &mut usize,
+    template: &str,
+    block: Option<&str>,
+) -> Result<Vec<Node>> {
+    let mut nodes = Vec::new();
+
+    while *pos < tokens.len() {
+        let token = &tokens[*pos];
+        *pos += 1;
+
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.to_string())),
+            Token::Tag(tag) if tag.starts_with('!') => {}
+            Token::Tag(tag) if tag.starts_with('/') => {
+                let closing = tag[1..].trim();
+                return match block {
+                    Some(open) if open == closing => Ok(nodes),
+                    _ => Err(Error::Config(format!(
+                        "Template '{}': unexpected {{{{/{}}}}}",
+                        template, closing
+                    ))),
+                };
+            }
+            Token::Tag(tag) if *tag == "else" => {
+                // Handled by the enclosing #if
+                *pos -= 1;
+                return match block {
+                    Some("if") => Ok(nodes),
+                    _ => Err(Error::Config(format!(
+                        "Template '{}': {{{{else}}}} outside of {{{{#if}}}}",
+                        template
+                    ))),
+                };
+            }
+            Token::Tag(tag) if tag.starts_with("#if ") => {
+                let condition = tag[4..].trim().to_string();
+                let then_branch = parse_nodes(tokens, pos, template, Some("if"))?;
+                let else_branch = if matches!(tokens.get(*pos), Some(Token::Tag("else"))) {
+                    *pos += 1;
+                    parse_nodes(tokens, pos, template, Some("if"))?
+                } else {
+                    Vec::new()
+                };
+                nodes.push(Node::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                });
+            }
+            Token::Tag(tag) if tag.starts_with("#each ") => {
+                let path = tag[6..].trim().to_string();
+                let body = parse_nodes(tokens, pos, template, Some("each"))?;
+                nodes.push(Node::Each { path, body });
+            }
+            Token::Tag(tag) => nodes.push(Node::Var(tag.to_string())),
+        }
+    }
+
+    match block {
+        Some(open) => Err(Error::Config(format!(
+            "Template '{}': unclosed {{{{#{}}}}}",
+            template, open
+        ))),
+        None => Ok(nodes),
+    }
+}
+
+fn render_nodes(nodes: &[Node], scopes: &[&Value], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var(path) => {
+                if let Some(value) = lookup(scopes, path) {
+                    out.push_str(&value_to_string(value));
+                }
+            }
+            Node::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if lookup(scopes, condition).is_some_and(is_truthy) {
+                    render_nodes(then_branch, scopes, out);
+                } else {
+                    render_nodes(else_branch, scopes, out);
+                }
+            }
+            Node::Each { path, body } => {
+                if let Some(Value::Array(items)) = lookup(scopes, path) {
+                    for item in items {
+                        let mut inner = scopes.to_vec();
+                        inner.push(item);
+                        render_nodes(body, &inner, out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Resolve a dotted path, innermost scope first
+fn lookup<'a>(scopes: &[&'a Value], path: &str) -> Option<&'a Value> {
+    if path == "this" || path == "." {
+        return scopes.last().copied();
+    }
+
+    let path = path.strip_prefix("this.").unwrap_or(path);
+    scopes.iter().rev().find_map(|scope| {
+        path.split('.')
+            .try_fold(*scope, |value, key| value.get(key))
+    })
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_variables() {
+        let t = PromptTemplate::parse("t", "Hello {{user_name}} in #{{channel}}!").unwrap();
+        let out = t.render(&json!({"user_name": "Alice", "channel": "general"}));
+        assert_eq!(out, "Hello Alice in #general!");
+    }
+
+    #[test]
+    fn test_missing_variable_renders_empty() {
+        let t = PromptTemplate::parse("t", "[{{missing}}]").unwrap();
+        assert_eq!(t.render(&json!({})), "[]");
+    }
+
+    #[test]
+    fn test_dotted_path() {
+        let t = PromptTemplate::parse("t", "{{user.name}} ({{user.id}})").unwrap();
+        let out = t.render(&json!({"user": {"name": "Bob", "id": 42}}));
+        assert_eq!(out, "Bob (42)");
+    }
+
+    #[test]
+    fn test_if_else() {
+        let t = PromptTemplate::parse("t", "{{#if name}}Hi {{name}}{{else}}Hi there{{/if}}").unwrap();
+        assert_eq!(t.render(&json!({"name": "Carol"})), "Hi Carol");
+        assert_eq!(t.render(&json!({"name": ""})), "Hi there");
+        assert_eq!(t.render(&json!({})), "Hi there");
+    }
+
+    #[test]
+    fn test_each() {
+        let t = PromptTemplate::parse(
+            "t",
+            "Memories:{{#each memories}}\n- {{this}}{{/each}}",
+        )
+        .unwrap();
+        let out = t.render(&json!({"memories": ["likes tea", "lives in Tokyo"]}));
+        assert_eq!(out, "Memories:\n- likes tea\n- lives in Tokyo");
+    }
+
+    #[test]
+    fn test_each_with_objects_and_outer_scope() {
+        let t = PromptTemplate::parse(
+            "t",
+            "{{#each items}}{{key}}={{value}}@{{channel}};{{/each}}",
+        )
+        .unwrap();
+        let out = t.render(&json!({
+            "channel": "c1",
+            "items": [{"key": "a", "value": 1}, {"key": "b", "value": 2}]
+        }));
+        assert_eq!(out, "a=1@c1;b=2@c1;");
+    }
+
+    #[test]
+    fn test_comment_is_dropped() {
+        let t = PromptTemplate::parse("t", "a{{! note }}b").unwrap();
+        assert_eq!(t.render(&json!({})), "ab");
+    }
+
+    #[test]
+    fn test_unclosed_block_is_error() {
+        assert!(PromptTemplate::parse("t", "{{#if x}}open").is_err());
+        assert!(PromptTemplate::parse("t", "{{/each}}").is_err());
+        assert!(PromptTemplate::parse("t", "{{else}}").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Erase everything stored about an identity
+//!
+//! An identity is a channel user (`discord:123456`) or a name linking several
+//! of them (`[memory.identities]`). A purge deletes, for the identity and each
+//! linked user:
+//!
+//! - memories in their namespace
+//! - sessions of the channel named after them and its sub-channels
+//!   (`api:alice`, `api:alice:notes`)
+//! - audit entries about them (usage is computed from sessions, so it goes
+//!   with them)
+//!
+//! and then records the purge itself in the audit log.
+
+use serde::Serialize;
+use serde_json::{Value, json};
+use tracing::info;
+
+use crate::audit::{AuditEntry, AuditEventType, AuditLevel, AuditLogger};
+use crate::config::MemoryConfig;
+use crate::memory::MemoryBackend;
+use crate::session::SessionManager;
+use crate::{Error, Result};
+
+/// Metadata keys of audit entries that hold a channel user ID
+const AUDIT_USER_KEYS: &[&str] = &["user_id", "requester", "decided_by"];
+
+/// What a purge deleted
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PurgeReport {
+    /// Purged identity
+    pub identity: String,
+    /// Deleted sessions
+    pub sessions: usize,
+    /// Deleted memories
+    pub memories: usize,
+    /// Removed audit entries
+    pub audit_entries: usize,
+}
+
+/// Delete all data of `identity` and record the purge in `audit`
+///
+/// `requested_by` names who asked for it (an API key, `cli`) in the record.
+pub async fn purge_identity(
+    identity: &str,
+    config: &MemoryConfig,
+    sessions: &SessionManager,
+    memories: Option<&dyn MemoryBackend>,
+    audit: Option<&AuditLogger>,
+    requested_by: &str,
+) -> Result<PurgeReport> {
+    let mut owners = config.identity_members(identity);
+    if !owners.iter().any(|owner| owner == identity) {
+        owners.push(identity.to_string());
+    }
+
+    let mut report = PurgeReport {
+        identity: identity.to_string(),
+        ..Default::default()
+    };
+    for owner in &owners {
+        report.sessions += sessions.delete_sessions_of(owner).await?;
+        if let Some(memories) = memories {
+            report.memories += memories.delete_namespace(owner).await?;
+        }
+    }
+
+    if let Some(audit) = audit {
+        report.audit_entries = audit
+            .remove_entries(|entry| is_about(entry, &owners))
+            .map_err(|e| Error::Storage(format!("Failed to purge the audit log: {}", e)))?;
+
+        let entry = AuditEntry::new(
+            AuditEventType::DataPurged,
+            AuditLevel::Warning,
+            format!("Purged all data of {} (requested by {})", identity, requested_by),
+        )
+        .with_metadata(json!({
+            "identity": identity,
+            "members": owners,
+            "requested_by": requested_by,
+            "sessions": report.sessions,
+            "memories": report.memories,
+            "audit_entries": report.audit_entries,
+        }));
+        audit
+            .log(&entry)
+            .map_err(|e| Error::Storage(format!("Failed to record the purge: {}", e)))?;
+    }
+
+    info!(
+        "Purged {}: {} sessions, {} memories, {} audit entries",
+        identity, report.sessions, report.memories, report.audit_entries
+    );
+    Ok(report)
+}
+
+/// Whether an audit entry is about one of `owners` (purge records never are)
+fn is_about(entry: &AuditEntry, owners: &[String]) -> bool {
+    if entry.event_type == AuditEventType::DataPurged {
+        return false;
+    }
+    let Some(metadata) = &entry.metadata else {
+        return false;
+    };
+    if let Some(identity) = metadata.get("identity").and_then(Value::as_str) {
+        if owners.iter().any(|owner| owner == identity) {
+            return true;
+        }
+    }
+
+    let gateway = entry.source.as_ref().and_then(|source| source.gateway.as_deref());
+    owners.iter().any(|owner| {
+        let Some((platform, user_id)) = owner.split_once(':') else {
+            return false;
+        };
+        gateway == Some(platform)
+            && AUDIT_USER_KEYS.iter().any(|key| match metadata.get(*key) {
+                Some(Value::String(id)) => id == user_id,
+                Some(Value::Number(id)) => id.to_string() == user_id,
+                _ => false,
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditConfig, AuditSource};
+    use crate::memory::{Memory, MemoryStore};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn discord_entry(requester: &str) -> AuditEntry {
+        AuditEntry::new(AuditEventType::ToolApprovalRequested, AuditLevel::Warning, "Approval requested")
+            .with_source(AuditSource {
+                ip_address: None,
+                user_agent: None,
+                gateway: Some("discord".to_string()),
+                channel_id: Some("c1".to_string()),
+            })
+            .with_metadata(json!({ "tool": "bash", "requester": requester }))
+    }
+
+    #[tokio::test]
+    async fn test_purge_identity() {
+        let config = MemoryConfig {
+            identities: HashMap::from([(
+                "alice".to_string(),
+                vec!["discord:1".to_string(), "api:alice".to_string()],
+            )]),
+            ..Default::default()
+        };
+        let sessions = SessionManager::in_memory().unwrap();
+        sessions.get_or_create("api:alice").await.unwrap();
+        sessions.get_or_create("api:bob").await.unwrap();
+        let memories = MemoryStore::in_memory().unwrap();
+        memories.save(&Memory::new("Likes tea").in_namespace("alice")).unwrap();
+        memories.save(&Memory::new("Lives in Osaka").in_namespace("discord:1")).unwrap();
+        memories.save(&Memory::new("Likes coffee").in_namespace("discord:2")).unwrap();
+
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("audit.log");
+        let audit = AuditLogger::new(AuditConfig {
+            log_file: Some(log_path.to_str().unwrap().to_string()),
+            log_to_console: false,
+            ..Default::default()
+        })
+        .unwrap();
+        audit.log(&discord_entry("1")).unwrap();
+        audit.log(&discord_entry("2")).unwrap();
+
+        let report = purge_identity("alice", &config, &sessions, Some(&memories), Some(&audit), "cli")
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            PurgeReport {
+                identity: "alice".to_string(),
+                sessions: 1,
+                memories: 2,
+                audit_entries: 1,
+            }
+        );
+        assert_eq!(sessions.count_sessions().await.unwrap(), 1);
+        assert_eq!(memories.count().unwrap(), 1);
+
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("\"requester\":\"2\""));
+        assert!(!log.contains("\"requester\":\"1\""));
+        assert!(log.contains("data_purged"));
+
+        // The purge record survives a second purge
+        purge_identity("alice", &config, &sessions, Some(&memories), Some(&audit), "cli")
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap().matches("data_purged").count(), 2);
+    }
+}
@@ -0,0 +1,67 @@
+//! Storage backends of sessions
+//!
+//! [`SessionManager`](super::SessionManager) persists sessions through a
+//! [`SessionBackend`]: the SQLite [`SessionStore`] by default, or Postgres
+//! (`postgres` feature) when `[memory].database_url` is set, so several
+//! gateway instances can share one session database.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::MemoryConfig;
+use crate::session::{Session, SessionStore};
+use crate::Result;
+
+/// Persistent storage of sessions
+#[async_trait]
+pub trait SessionBackend: Send + Sync {
+    /// Insert or replace a session
+    async fn save(&self, session: &Session) -> Result<()>;
+
+    /// Load a session by ID
+    async fn load(&self, id: &str) -> Result<Option<Session>>;
+
+    /// Delete a session by ID
+    async fn delete(&self, id: &str) -> Result<()>;
+
+    /// List all sessions for a channel, most recently updated first
+    async fn list_by_channel(&self, channel_id: &str) -> Result<Vec<Session>>;
+
+    /// List sessions whose channel ID starts with `prefix`, most recently updated first
+    async fn list_by_channel_prefix(&self, prefix: &str) -> Result<Vec<Session>>;
+
+    /// Get the most recent session for a channel
+    async fn get_latest_by_channel(&self, channel_id: &str) -> Result<Option<Session>>;
+
+    /// Delete all sessions for a channel, returning how many were deleted
+    async fn delete_by_channel(&self, channel_id: &str) -> Result<usize>;
+
+    /// List sessions of all channels, most recently updated first
+    async fn list(&self, limit: usize, offset: usize) -> Result<Vec<Session>>;
+
+    /// Count sessions of all channels
+    async fn count(&self) -> Result<usize>;
+
+    /// Count sessions for a channel
+    async fn count_by_channel(&self, channel_id: &str) -> Result<usize>;
+}
+
+/// Open the session backend selected by `config`
+///
+/// Postgres connections are opened on first use, so this does not fail when
+/// the database is temporarily unreachable.
+pub fn open_backend(config: &MemoryConfig) -> Result<Arc<dyn SessionBackend>> {
+    match config.database_url.as_deref() {
+        None => Ok(Arc::new(SessionStore::new(&config.db_path)?)),
+        #[cfg(feature = "postgres")]
+        Some(url) => Ok(Arc::new(super::PgSessionStore::new(crate::postgres::create_pool(
+            url,
+            config.pool_size,
+        )?))),
+        #[cfg(not(feature = "postgres"))]
+        Some(_) => Err(crate::Error::Config(
+            "memory.database_url requires a build with the postgres feature".to_string(),
+        )),
+    }
+}
@@ -1,18 +1,19 @@
 //! Session management
 
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
-use crate::session::{Session, SessionStore};
+use crate::config::MemoryConfig;
+use crate::session::{Session, SessionBackend, SessionStore};
 use crate::llm::Message;
 use crate::{Error, Result};
 
 /// Session manager that handles session lifecycle
 pub struct SessionManager {
-    /// Persistent storage (wrapped in Mutex for thread safety)
-    store: Arc<Mutex<SessionStore>>,
+    /// Persistent storage
+    store: Arc<dyn SessionBackend>,
     /// In-memory cache for active sessions
     cache: Arc<RwLock<HashMap<String, Session>>>,
     /// Maximum messages per session (0 = unlimited)
@@ -22,32 +23,32 @@ pub struct SessionManager {
 impl SessionManager {
     /// Create a new session manager with a database path
     pub fn new(db_path: &str) -> Result<Self> {
-        let store = SessionStore::new(db_path)?;
-        Ok(Self {
-            store: Arc::new(Mutex::new(store)),
-            cache: Arc::new(RwLock::new(HashMap::new())),
-            max_messages: 0,
-        })
+        Self::with_options(db_path, 0)
     }
 
     /// Create a new session manager with custom settings
     pub fn with_options(db_path: &str, max_messages: usize) -> Result<Self> {
-        let store = SessionStore::new(db_path)?;
-        Ok(Self {
-            store: Arc::new(Mutex::new(store)),
+        Ok(Self::with_backend(Arc::new(SessionStore::new(db_path)?), max_messages))
+    }
+
+    /// Create a session manager on the backend selected by `[memory]`
+    /// (SQLite, or Postgres when `database_url` is set)
+    pub fn from_config(config: &MemoryConfig) -> Result<Self> {
+        Ok(Self::with_backend(super::open_backend(config)?, 0))
+    }
+
+    /// Create a session manager on a storage backend
+    pub fn with_backend(store: Arc<dyn SessionBackend>, max_messages: usize) -> Self {
+        Self {
+            store,
             cache: Arc::new(RwLock::new(HashMap::new())),
             max_messages,
-        })
+        }
     }
 
     /// Create an in-memory session manager (for testing)
     pub fn in_memory() -> Result<Self> {
-        let store = SessionStore::in_memory()?;
-        Ok(Self {
-            store: Arc::new(Mutex::new(store)),
-            cache: Arc::new(RwLock::new(HashMap::new())),
-            max_messages: 0,
-        })
+        Ok(Self::with_backend(Arc::new(SessionStore::in_memory()?), 0))
     }
 
     /// Get or create a session for a channel
@@ -62,10 +63,7 @@ impl SessionManager {
         }
 
         // Try to load from store
-        let session_from_store = {
-            let store = self.store.lock().unwrap();
-            store.get_latest_by_channel(channel_id)?
-        }; // MutexGuard dropped here before await
+        let session_from_store = self.store.get_latest_by_channel(channel_id).await?;
 
         if let Some(session) = session_from_store {
             debug!("Session loaded from store for channel: {}", channel_id);
@@ -78,10 +76,7 @@ impl SessionManager {
         // Create new session
         info!("Creating new session for channel: {}", channel_id);
         let session = Session::new(channel_id);
-        {
-            let store = self.store.lock().unwrap();
-            store.save(&session)?;
-        }
+        self.store.save(&session).await?;
 
         // Add to cache
         let mut cache = self.cache.write().await;
@@ -108,8 +103,7 @@ impl SessionManager {
         }
 
         // Persist to store
-        let store = self.store.lock().unwrap();
-        store.save(session)?;
+        self.store.save(session).await?;
 
         Ok(())
     }
@@ -125,14 +119,31 @@ impl SessionManager {
         Ok(session.messages.clone())
     }
 
+    /// Set the persona for a channel's session (None = reset to default)
+    pub async fn set_persona(&self, channel_id: &str, persona: Option<String>) -> Result<()> {
+        self.get_or_create(channel_id).await?;
+
+        let mut cache = self.cache.write().await;
+        if let Some(session) = cache.get_mut(channel_id) {
+            session.set_persona(persona);
+            self.store.save(session).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the persona selected for a channel's session
+    pub async fn get_persona(&self, channel_id: &str) -> Result<Option<String>> {
+        Ok(self.get_or_create(channel_id).await?.persona)
+    }
+
     /// Clear messages for a channel
     pub async fn clear_messages(&self, channel_id: &str) -> Result<()> {
         let mut cache = self.cache.write().await;
 
         if let Some(session) = cache.get_mut(channel_id) {
             session.clear_messages();
-            let store = self.store.lock().unwrap();
-            store.save(session)?;
+            self.store.save(session).await?;
             info!("Cleared messages for channel: {}", channel_id);
         }
 
@@ -148,15 +159,32 @@ impl SessionManager {
         }
 
         // Remove from store
-        {
-            let store = self.store.lock().unwrap();
-            store.delete_by_channel(channel_id)?;
-        }
+        self.store.delete_by_channel(channel_id).await?;
         info!("Deleted session for channel: {}", channel_id);
 
         Ok(())
     }
 
+    /// Delete every session of `owner`: the channel named `owner` and its
+    /// sub-channels (`<owner>:...`). Returns the number of sessions deleted.
+    pub async fn delete_sessions_of(&self, owner: &str) -> Result<usize> {
+        let prefix = format!("{}:", owner);
+        {
+            let mut cache = self.cache.write().await;
+            cache.retain(|channel_id, _| channel_id != owner && !channel_id.starts_with(&prefix));
+        }
+
+        let mut deleted = self.store.delete_by_channel(owner).await?;
+        for session in self.store.list_by_channel_prefix(&prefix).await? {
+            self.store.delete(&session.id).await?;
+            deleted += 1;
+        }
+        if deleted > 0 {
+            info!("Deleted {} sessions of {}", deleted, owner);
+        }
+        Ok(deleted)
+    }
+
     /// Get session count
     pub async fn session_count(&self) -> usize {
         let cache = self.cache.read().await;
@@ -166,9 +194,8 @@ impl SessionManager {
     /// Sync cache to persistent storage
     pub async fn sync(&self) -> Result<()> {
         let cache = self.cache.read().await;
-        let store = self.store.lock().unwrap();
         for session in cache.values() {
-            store.save(session)?;
+            self.store.save(session).await?;
         }
         debug!("Synced {} sessions to storage", cache.len());
         Ok(())
@@ -204,6 +231,72 @@ impl SessionManager {
         channel_id
     }
 
+    /// List sessions of all channels from storage, most recently updated first
+    pub async fn list_sessions(&self, limit: usize, offset: usize) -> Result<Vec<Session>> {
+        self.store.list(limit, offset).await
+    }
+
+    /// Count sessions of all channels in storage
+    pub async fn count_sessions(&self) -> Result<usize> {
+        self.store.count().await
+    }
+
+    /// Get a session by ID (cached or stored)
+    pub async fn get_session(&self, id: &str) -> Result<Option<Session>> {
+        if let Some(session) = self.get_cached_session(id).await {
+            return Ok(Some(session));
+        }
+        self.store.load(id).await
+    }
+
+    /// Rename a session by ID (None = remove the title)
+    pub async fn rename_session(&self, id: &str, title: Option<String>) -> Result<Session> {
+        self.update_session(id, |session| session.set_title(title)).await
+    }
+
+    /// Fork a session by ID, keeping its first `message_count` messages
+    /// (None = all)
+    pub async fn fork_session(&self, id: &str, message_count: Option<usize>) -> Result<Session> {
+        let session = self
+            .get_session(id)
+            .await?
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        let fork = session.fork(message_count);
+        self.store.save(&fork).await?;
+        info!("Forked session {} into {}", id, fork.id);
+        Ok(fork)
+    }
+
+    /// Delete a session by ID, returning whether it existed
+    pub async fn delete_session_by_id(&self, id: &str) -> Result<bool> {
+        let cached = self.remove_from_cache(id).await.is_some();
+        let stored = self.store.load(id).await?.is_some();
+        self.store.delete(id).await?;
+        if cached || stored {
+            info!("Deleted session: {}", id);
+        }
+        Ok(cached || stored)
+    }
+
+    /// Apply `update` to a session by ID and persist it
+    async fn update_session(&self, id: &str, update: impl FnOnce(&mut Session)) -> Result<Session> {
+        let mut cache = self.cache.write().await;
+        if let Some(session) = cache.values_mut().find(|s| s.id == id) {
+            update(session);
+            self.store.save(session).await?;
+            return Ok(session.clone());
+        }
+
+        let mut session = self
+            .store
+            .load(id)
+            .await?
+            .ok_or_else(|| Error::SessionNotFound(id.to_string()))?;
+        update(&mut session);
+        self.store.save(&session).await?;
+        Ok(session)
+    }
+
     /// Invalidate cache for a channel
     pub async fn invalidate_cache(&self, channel_id: &str) {
         let mut cache = self.cache.write().await;
@@ -250,6 +343,58 @@ mod tests {
         assert!(messages.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_set_persona() {
+        let manager = SessionManager::in_memory().unwrap();
+
+        manager.set_persona("channel-123", Some("coder".to_string())).await.unwrap();
+        assert_eq!(manager.get_persona("channel-123").await.unwrap().as_deref(), Some("coder"));
+
+        // Survives a cache invalidation (persisted to the store)
+        manager.invalidate_cache("channel-123").await;
+        assert_eq!(manager.get_persona("channel-123").await.unwrap().as_deref(), Some("coder"));
+
+        manager.set_persona("channel-123", None).await.unwrap();
+        assert!(manager.get_persona("channel-123").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rename_fork_and_delete() {
+        let manager = SessionManager::in_memory().unwrap();
+
+        let session = manager.get_or_create("channel-123").await.unwrap();
+        manager.add_message("channel-123", Message::user("Hello")).await.unwrap();
+        manager.add_message("channel-123", Message::assistant("Hi")).await.unwrap();
+
+        let renamed = manager.rename_session(&session.id, Some("Greeting".to_string())).await.unwrap();
+        assert_eq!(renamed.title.as_deref(), Some("Greeting"));
+
+        let fork = manager.fork_session(&session.id, Some(1)).await.unwrap();
+        assert_eq!(fork.messages.len(), 1);
+        // The fork is stored and can be continued as its own channel
+        assert_eq!(manager.get_session(&fork.id).await.unwrap().unwrap().title.as_deref(), Some("Greeting"));
+        assert_eq!(manager.get_or_create(&fork.channel_id).await.unwrap().id, fork.id);
+        assert_eq!(manager.count_sessions().await.unwrap(), 2);
+        assert_eq!(manager.list_sessions(10, 0).await.unwrap().len(), 2);
+
+        assert!(manager.delete_session_by_id(&session.id).await.unwrap());
+        assert!(!manager.delete_session_by_id(&session.id).await.unwrap());
+        assert!(manager.get_session(&session.id).await.unwrap().is_none());
+        assert!(manager.rename_session(&session.id, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_sessions_of() {
+        let manager = SessionManager::in_memory().unwrap();
+        manager.get_or_create("api:alice").await.unwrap();
+        manager.get_or_create("api:alice:notes").await.unwrap();
+        manager.get_or_create("api:alice2").await.unwrap();
+
+        assert_eq!(manager.delete_sessions_of("api:alice").await.unwrap(), 2);
+        assert_eq!(manager.count_sessions().await.unwrap(), 1);
+        assert_eq!(manager.session_count().await, 1);
+    }
+
     #[tokio::test]
     async fn test_message_limit() {
         let manager = SessionManager::with_options(":memory:", 3).unwrap();
@@ -2,10 +2,16 @@
 //!
 //! Provides session persistence and management for conversation history.
 
+mod backend;
 mod manager;
+#[cfg(feature = "postgres")]
+mod postgres;
 mod store;
 mod types;
 
+pub use backend::{open_backend, SessionBackend};
 pub use manager::SessionManager;
+#[cfg(feature = "postgres")]
+pub use postgres::PgSessionStore;
 pub use store::SessionStore;
 pub use types::Session;
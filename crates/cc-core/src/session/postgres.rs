@@ -0,0 +1,197 @@
+//! Session persistence using Postgres (`postgres` feature)
+
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use tokio::sync::OnceCell;
+use tokio_postgres::Row;
+
+use crate::migrate::Migration;
+use crate::postgres;
+use crate::session::{Session, SessionBackend};
+use crate::Result;
+
+/// Columns read by [`session_from_row`]
+const COLUMNS: &str = "id, channel_id, messages, created_at, updated_at, persona, title";
+
+/// Schema versions of the `sessions` table
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create sessions",
+    "CREATE TABLE IF NOT EXISTS sessions (
+        id TEXT PRIMARY KEY,
+        channel_id TEXT NOT NULL,
+        messages JSONB NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL,
+        persona TEXT,
+        title TEXT
+    );
+    CREATE INDEX IF NOT EXISTS idx_sessions_channel_id ON sessions(channel_id, updated_at DESC);
+    CREATE INDEX IF NOT EXISTS idx_sessions_updated_at ON sessions(updated_at DESC);",
+)];
+
+/// Postgres-based session store
+pub struct PgSessionStore {
+    pool: Pool,
+    schema: OnceCell<()>,
+}
+
+impl PgSessionStore {
+    /// The schema is created or upgraded on first use
+    pub fn new(pool: Pool) -> Self {
+        Self {
+            pool,
+            schema: OnceCell::new(),
+        }
+    }
+
+    async fn client(&self) -> Result<Object> {
+        self.schema
+            .get_or_try_init(|| async {
+                postgres::migrate(&self.pool, "sessions", MIGRATIONS).await.map(|_| ())
+            })
+            .await?;
+        Ok(self.pool.get().await?)
+    }
+
+    async fn query(&self, condition: &str, params: &[&(dyn tokio_postgres::types::ToSql + Sync)]) -> Result<Vec<Session>> {
+        let rows = self
+            .client()
+            .await?
+            .query(&format!("SELECT {} FROM sessions {}", COLUMNS, condition), params)
+            .await?;
+        rows.iter().map(session_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl SessionBackend for PgSessionStore {
+    async fn save(&self, session: &Session) -> Result<()> {
+        let messages = serde_json::to_value(&session.messages)?;
+        self.client()
+            .await?
+            .execute(
+                "INSERT INTO sessions (id, channel_id, messages, created_at, updated_at, persona, title)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (id) DO UPDATE SET
+                    channel_id = EXCLUDED.channel_id,
+                    messages = EXCLUDED.messages,
+                    updated_at = EXCLUDED.updated_at,
+                    persona = EXCLUDED.persona,
+                    title = EXCLUDED.title",
+                &[
+                    &session.id,
+                    &session.channel_id,
+                    &messages,
+                    &session.created_at,
+                    &session.updated_at,
+                    &session.persona,
+                    &session.title,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<Session>> {
+        Ok(self.query("WHERE id = $1", &[&id]).await?.into_iter().next())
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        self.client().await?.execute("DELETE FROM sessions WHERE id = $1", &[&id]).await?;
+        Ok(())
+    }
+
+    async fn list_by_channel(&self, channel_id: &str) -> Result<Vec<Session>> {
+        self.query("WHERE channel_id = $1 ORDER BY updated_at DESC", &[&channel_id]).await
+    }
+
+    async fn list_by_channel_prefix(&self, prefix: &str) -> Result<Vec<Session>> {
+        self.query("WHERE starts_with(channel_id, $1) ORDER BY updated_at DESC", &[&prefix]).await
+    }
+
+    async fn get_latest_by_channel(&self, channel_id: &str) -> Result<Option<Session>> {
+        Ok(self
+            .query("WHERE channel_id = $1 ORDER BY updated_at DESC LIMIT 1", &[&channel_id])
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn delete_by_channel(&self, channel_id: &str) -> Result<usize> {
+        let affected = self
+            .client()
+            .await?
+            .execute("DELETE FROM sessions WHERE channel_id = $1", &[&channel_id])
+            .await?;
+        Ok(affected as usize)
+    }
+
+    async fn list(&self, limit: usize, offset: usize) -> Result<Vec<Session>> {
+        self.query(
+            "ORDER BY updated_at DESC LIMIT $1 OFFSET $2",
+            &[&(limit as i64), &(offset as i64)],
+        )
+        .await
+    }
+
+    async fn count(&self) -> Result<usize> {
+        let row = self.client().await?.query_one("SELECT COUNT(*) FROM sessions", &[]).await?;
+        Ok(row.get::<_, i64>(0) as usize)
+    }
+
+    async fn count_by_channel(&self, channel_id: &str) -> Result<usize> {
+        let row = self
+            .client()
+            .await?
+            .query_one("SELECT COUNT(*) FROM sessions WHERE channel_id = $1", &[&channel_id])
+            .await?;
+        Ok(row.get::<_, i64>(0) as usize)
+    }
+}
+
+/// Build a session from a row of [`COLUMNS`]
+fn session_from_row(row: &Row) -> Result<Session> {
+    Ok(Session {
+        id: row.try_get(0)?,
+        channel_id: row.try_get(1)?,
+        messages: serde_json::from_value(row.try_get(2)?)?,
+        created_at: row.try_get(3)?,
+        updated_at: row.try_get(4)?,
+        persona: row.try_get(5)?,
+        title: row.try_get(6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::Message;
+
+    /// Runs against the database in CC_TEST_DATABASE_URL, skipped without it
+    #[tokio::test]
+    async fn test_roundtrip() {
+        let url = match std::env::var("CC_TEST_DATABASE_URL") {
+            Ok(url) => url,
+            Err(_) => return,
+        };
+        let store = PgSessionStore::new(postgres::create_pool(&url, 2).unwrap());
+        let channel = format!("test:{}", uuid::Uuid::new_v4());
+        let mut session = Session::new(channel.as_str());
+        session.add_message(Message::user("Hello"));
+        session.set_persona(Some("coder".to_string()));
+        store.save(&session).await.unwrap();
+        session.add_message(Message::assistant("Hi"));
+        store.save(&session).await.unwrap();
+
+        let loaded = store.get_latest_by_channel(&channel).await.unwrap().unwrap();
+        assert_eq!(loaded.id, session.id);
+        assert_eq!(loaded.messages.len(), 2);
+        assert_eq!(loaded.persona.as_deref(), Some("coder"));
+        assert_eq!(store.list_by_channel_prefix("test:").await.unwrap().iter().filter(|s| s.channel_id == channel).count(), 1);
+        assert_eq!(store.count_by_channel(&channel).await.unwrap(), 1);
+
+        assert_eq!(store.delete_by_channel(&channel).await.unwrap(), 1);
+        assert!(store.load(&session.id).await.unwrap().is_none());
+    }
+}
@@ -1,21 +1,51 @@
 //! Session persistence using SQLite
 
+use std::sync::{Mutex, MutexGuard};
+
+use async_trait::async_trait;
 use rusqlite::{Connection, params};
-use crate::session::Session;
+use crate::session::{Session, SessionBackend};
 use crate::llm::Message;
+use crate::migrate::{self, Migration};
 use crate::{Error, Result};
 use chrono::{DateTime, Utc};
 
+/// Columns read by [`session_from_row`]
+const COLUMNS: &str = "id, channel_id, messages, created_at, updated_at, persona, title";
+
+/// Schema versions of the `sessions` table
+const MIGRATIONS: &[Migration] = &[
+    Migration::sql(
+        1,
+        "create sessions",
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            channel_id TEXT NOT NULL,
+            messages TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            persona TEXT,
+            title TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_sessions_channel_id ON sessions(channel_id);",
+    ),
+    // Databases created before the persona and title columns existed
+    Migration::run(2, "add sessions.persona and sessions.title", |conn| {
+        migrate::add_column_if_missing(conn, "sessions", "persona", "TEXT")?;
+        migrate::add_column_if_missing(conn, "sessions", "title", "TEXT")
+    }),
+];
+
 /// SQLite-based session store
 pub struct SessionStore {
-    conn: Connection,
+    conn: Mutex<Connection>,
 }
 
 impl SessionStore {
     /// Create a new session store with the given database path
     pub fn new(db_path: &str) -> Result<Self> {
         let conn = Connection::open(db_path)?;
-        let store = Self { conn };
+        let store = Self { conn: Mutex::new(conn) };
         store.init_tables()?;
         Ok(store)
     }
@@ -23,45 +53,31 @@ impl SessionStore {
     /// Create an in-memory session store (for testing)
     pub fn in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
-        let store = Self { conn };
+        let store = Self { conn: Mutex::new(conn) };
         store.init_tables()?;
         Ok(store)
     }
 
-    /// Initialize database tables
+    /// Create or upgrade the database tables
     fn init_tables(&self) -> Result<()> {
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS sessions (
-                id TEXT PRIMARY KEY,
-                channel_id TEXT NOT NULL,
-                messages TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )",
-            [],
-        )?;
-
-        // Create index for channel_id queries
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_sessions_channel_id ON sessions(channel_id)",
-            [],
-        )?;
-
+        migrate::migrate(&self.conn(), "sessions", MIGRATIONS)?;
         Ok(())
     }
 
     /// Save a session to the database
     pub fn save(&self, session: &Session) -> Result<()> {
         let messages_json = serde_json::to_string(&session.messages)?;
-        self.conn.execute(
-            "INSERT OR REPLACE INTO sessions (id, channel_id, messages, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+        self.conn().execute(
+            "INSERT OR REPLACE INTO sessions (id, channel_id, messages, created_at, updated_at, persona, title)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 session.id,
                 session.channel_id,
                 messages_json,
                 session.created_at.to_rfc3339(),
                 session.updated_at.to_rfc3339(),
+                session.persona,
+                session.title,
             ],
         )?;
         Ok(())
@@ -69,34 +85,12 @@ impl SessionStore {
 
     /// Load a session by ID
     pub fn load(&self, id: &str) -> Result<Option<Session>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, channel_id, messages, created_at, updated_at FROM sessions WHERE id = ?1"
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            &format!("SELECT {} FROM sessions WHERE id = ?1", COLUMNS)
         )?;
 
-        let result = stmt.query_row(params![id], |row| {
-            let messages_json: String = row.get(2)?;
-            let messages: Vec<Message> = serde_json::from_str(&messages_json)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?;
-
-            let created_at_str: String = row.get(3)?;
-            let updated_at_str: String = row.get(4)?;
-
-            let created_at = DateTime::parse_from_rfc3339(&created_at_str)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?
-                .with_timezone(&Utc);
-
-            let updated_at = DateTime::parse_from_rfc3339(&updated_at_str)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?
-                .with_timezone(&Utc);
-
-            Ok(Session {
-                id: row.get(0)?,
-                channel_id: row.get(1)?,
-                messages,
-                created_at,
-                updated_at,
-            })
-        });
+        let result = stmt.query_row(params![id], session_from_row);
 
         match result {
             Ok(session) => Ok(Some(session)),
@@ -107,41 +101,18 @@ impl SessionStore {
 
     /// Delete a session by ID
     pub fn delete(&self, id: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+        self.conn().execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
         Ok(())
     }
 
     /// List all sessions for a channel
     pub fn list_by_channel(&self, channel_id: &str) -> Result<Vec<Session>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, channel_id, messages, created_at, updated_at FROM sessions
-             WHERE channel_id = ?1 ORDER BY updated_at DESC"
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            &format!("SELECT {} FROM sessions WHERE channel_id = ?1 ORDER BY updated_at DESC", COLUMNS)
         )?;
 
-        let sessions = stmt.query_map(params![channel_id], |row| {
-            let messages_json: String = row.get(2)?;
-            let messages: Vec<Message> = serde_json::from_str(&messages_json)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?;
-
-            let created_at_str: String = row.get(3)?;
-            let updated_at_str: String = row.get(4)?;
-
-            let created_at = DateTime::parse_from_rfc3339(&created_at_str)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?
-                .with_timezone(&Utc);
-
-            let updated_at = DateTime::parse_from_rfc3339(&updated_at_str)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?
-                .with_timezone(&Utc);
-
-            Ok(Session {
-                id: row.get(0)?,
-                channel_id: row.get(1)?,
-                messages,
-                created_at,
-                updated_at,
-            })
-        })?;
+        let sessions = stmt.query_map(params![channel_id], session_from_row)?;
 
         let mut result = Vec::new();
         for session in sessions {
@@ -150,37 +121,24 @@ impl SessionStore {
         Ok(result)
     }
 
+    /// List sessions whose channel ID starts with `prefix`, most recently updated first
+    pub fn list_by_channel_prefix(&self, prefix: &str) -> Result<Vec<Session>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            &format!("SELECT {} FROM sessions WHERE substr(channel_id, 1, ?2) = ?1 ORDER BY updated_at DESC", COLUMNS)
+        )?;
+        let sessions = stmt.query_map(params![prefix, prefix.chars().count() as i64], session_from_row)?;
+        Ok(sessions.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
     /// Get the most recent session for a channel
     pub fn get_latest_by_channel(&self, channel_id: &str) -> Result<Option<Session>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, channel_id, messages, created_at, updated_at FROM sessions
-             WHERE channel_id = ?1 ORDER BY updated_at DESC LIMIT 1"
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            &format!("SELECT {} FROM sessions WHERE channel_id = ?1 ORDER BY updated_at DESC LIMIT 1", COLUMNS)
         )?;
 
-        let result = stmt.query_row(params![channel_id], |row| {
-            let messages_json: String = row.get(2)?;
-            let messages: Vec<Message> = serde_json::from_str(&messages_json)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?;
-
-            let created_at_str: String = row.get(3)?;
-            let updated_at_str: String = row.get(4)?;
-
-            let created_at = DateTime::parse_from_rfc3339(&created_at_str)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?
-                .with_timezone(&Utc);
-
-            let updated_at = DateTime::parse_from_rfc3339(&updated_at_str)
-                .map_err(|_| rusqlite::Error::InvalidQuery)?
-                .with_timezone(&Utc);
-
-            Ok(Session {
-                id: row.get(0)?,
-                channel_id: row.get(1)?,
-                messages,
-                created_at,
-                updated_at,
-            })
-        });
+        let result = stmt.query_row(params![channel_id], session_from_row);
 
         match result {
             Ok(session) => Ok(Some(session)),
@@ -191,22 +149,113 @@ impl SessionStore {
 
     /// Delete all sessions for a channel
     pub fn delete_by_channel(&self, channel_id: &str) -> Result<usize> {
-        let affected = self.conn.execute(
+        let affected = self.conn().execute(
             "DELETE FROM sessions WHERE channel_id = ?1",
             params![channel_id],
         )?;
         Ok(affected)
     }
 
+    /// List sessions of all channels, most recently updated first
+    pub fn list(&self, limit: usize, offset: usize) -> Result<Vec<Session>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            &format!("SELECT {} FROM sessions ORDER BY updated_at DESC LIMIT ?1 OFFSET ?2", COLUMNS)
+        )?;
+        let sessions = stmt.query_map(params![limit as i64, offset as i64], session_from_row)?;
+        Ok(sessions.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Count sessions of all channels
+    pub fn count(&self) -> Result<usize> {
+        let count: i64 = self.conn().query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
     /// Count sessions for a channel
     pub fn count_by_channel(&self, channel_id: &str) -> Result<usize> {
-        let count: i64 = self.conn.query_row(
+        let count: i64 = self.conn().query_row(
             "SELECT COUNT(*) FROM sessions WHERE channel_id = ?1",
             params![channel_id],
             |row| row.get(0),
         )?;
         Ok(count as usize)
     }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl SessionBackend for SessionStore {
+    async fn save(&self, session: &Session) -> Result<()> {
+        SessionStore::save(self, session)
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<Session>> {
+        SessionStore::load(self, id)
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        SessionStore::delete(self, id)
+    }
+
+    async fn list_by_channel(&self, channel_id: &str) -> Result<Vec<Session>> {
+        SessionStore::list_by_channel(self, channel_id)
+    }
+
+    async fn list_by_channel_prefix(&self, prefix: &str) -> Result<Vec<Session>> {
+        SessionStore::list_by_channel_prefix(self, prefix)
+    }
+
+    async fn get_latest_by_channel(&self, channel_id: &str) -> Result<Option<Session>> {
+        SessionStore::get_latest_by_channel(self, channel_id)
+    }
+
+    async fn delete_by_channel(&self, channel_id: &str) -> Result<usize> {
+        SessionStore::delete_by_channel(self, channel_id)
+    }
+
+    async fn list(&self, limit: usize, offset: usize) -> Result<Vec<Session>> {
+        SessionStore::list(self, limit, offset)
+    }
+
+    async fn count(&self) -> Result<usize> {
+        SessionStore::count(self)
+    }
+
+    async fn count_by_channel(&self, channel_id: &str) -> Result<usize> {
+        SessionStore::count_by_channel(self, channel_id)
+    }
+}
+
+/// Build a session from a row of [`COLUMNS`]
+fn session_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Session> {
+    let messages_json: String = row.get(2)?;
+    let messages: Vec<Message> = serde_json::from_str(&messages_json)
+        .map_err(|_| rusqlite::Error::InvalidQuery)?;
+
+    let created_at_str: String = row.get(3)?;
+    let updated_at_str: String = row.get(4)?;
+
+    let created_at = DateTime::parse_from_rfc3339(&created_at_str)
+        .map_err(|_| rusqlite::Error::InvalidQuery)?
+        .with_timezone(&Utc);
+
+    let updated_at = DateTime::parse_from_rfc3339(&updated_at_str)
+        .map_err(|_| rusqlite::Error::InvalidQuery)?
+        .with_timezone(&Utc);
+
+    Ok(Session {
+        id: row.get(0)?,
+        channel_id: row.get(1)?,
+        messages,
+        persona: row.get(5)?,
+        title: row.get(6)?,
+        created_at,
+        updated_at,
+    })
 }
 
 #[cfg(test)]
@@ -235,6 +284,58 @@ mod tests {
         assert_eq!(loaded.messages.len(), 1);
     }
 
+    #[test]
+    fn test_list_by_channel_prefix() {
+        let store = SessionStore::in_memory().unwrap();
+        for channel in ["cli:a", "cli:b", "discord:1", "xcli:c"] {
+            store.save(&Session::new(channel)).unwrap();
+        }
+
+        let mut channels: Vec<String> = store
+            .list_by_channel_prefix("cli:")
+            .unwrap()
+            .into_iter()
+            .map(|s| s.channel_id)
+            .collect();
+        channels.sort();
+        assert_eq!(channels, vec!["cli:a", "cli:b"]);
+    }
+
+    #[test]
+    fn test_persona_roundtrip() {
+        let store = SessionStore::in_memory().unwrap();
+        let mut session = Session::new("channel-123");
+        session.set_persona(Some("coder".to_string()));
+
+        store.save(&session).unwrap();
+        let loaded = store.get_latest_by_channel("channel-123").unwrap().unwrap();
+        assert_eq!(loaded.persona.as_deref(), Some("coder"));
+    }
+
+    #[test]
+    fn test_migrates_legacy_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE sessions (
+                id TEXT PRIMARY KEY,
+                channel_id TEXT NOT NULL,
+                messages TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+        let store = SessionStore { conn: Mutex::new(conn) };
+        store.init_tables().unwrap();
+
+        let session = Session::new("channel-123");
+        store.save(&session).unwrap();
+        let loaded = store.load(&session.id).unwrap().unwrap();
+        assert!(loaded.persona.is_none());
+        assert!(loaded.title.is_none());
+    }
+
     #[test]
     fn test_delete() {
         let store = SessionStore::in_memory().unwrap();
@@ -261,5 +362,9 @@ mod tests {
 
         let sessions = store.list_by_channel("channel-123").unwrap();
         assert_eq!(sessions.len(), 2);
+
+        assert_eq!(store.count().unwrap(), 3);
+        assert_eq!(store.list(2, 0).unwrap().len(), 2);
+        assert_eq!(store.list(10, 2).unwrap().len(), 1);
     }
 }
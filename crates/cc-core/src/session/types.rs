@@ -13,6 +13,12 @@ pub struct Session {
     pub channel_id: String,
     /// Conversation messages
     pub messages: Vec<Message>,
+    /// Selected persona name (None = channel / user default)
+    #[serde(default)]
+    pub persona: Option<String>,
+    /// Display name (set by the user, e.g. through the API)
+    #[serde(default)]
+    pub title: Option<String>,
     /// Session creation timestamp
     pub created_at: DateTime<Utc>,
     /// Last update timestamp
@@ -27,6 +33,8 @@ impl Session {
             id: uuid::Uuid::new_v4().to_string(),
             channel_id: channel_id.into(),
             messages: Vec::new(),
+            persona: None,
+            title: None,
             created_at: now,
             updated_at: now,
         }
@@ -38,6 +46,33 @@ impl Session {
         self.updated_at = Utc::now();
     }
 
+    /// Select a persona for this session
+    pub fn set_persona(&mut self, persona: Option<String>) {
+        self.persona = persona;
+        self.updated_at = Utc::now();
+    }
+
+    /// Rename the session (None = remove the title)
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+        self.updated_at = Utc::now();
+    }
+
+    /// Copy of the session under a new ID, keeping the first
+    /// `message_count` messages (None = all)
+    ///
+    /// The fork is its own channel (its channel ID is its session ID), so
+    /// continuing it leaves the original untouched.
+    pub fn fork(&self, message_count: Option<usize>) -> Self {
+        let mut fork = Self::new("");
+        fork.channel_id = fork.id.clone();
+        let count = message_count.unwrap_or(self.messages.len()).min(self.messages.len());
+        fork.messages = self.messages[..count].to_vec();
+        fork.persona = self.persona.clone();
+        fork.title = self.title.clone();
+        fork
+    }
+
     /// Clear all messages in the session
     pub fn clear_messages(&mut self) {
         self.messages.clear();
@@ -73,4 +108,19 @@ mod tests {
         session.add_message(Message::user("Hello"));
         assert_eq!(session.messages.len(), 1);
     }
+
+    #[test]
+    fn test_fork() {
+        let mut session = Session::new("channel-123");
+        session.add_message(Message::user("Hello"));
+        session.add_message(Message::assistant("Hi"));
+        session.set_title(Some("Greeting".to_string()));
+
+        let fork = session.fork(Some(1));
+        assert_ne!(fork.id, session.id);
+        assert_eq!(fork.channel_id, fork.id);
+        assert_eq!(fork.messages.len(), 1);
+        assert_eq!(fork.title.as_deref(), Some("Greeting"));
+        assert_eq!(session.fork(Some(10)).messages.len(), 2);
+    }
 }
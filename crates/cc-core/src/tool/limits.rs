@@ -0,0 +1,97 @@
+//! Tool execution limits
+//!
+//! Bounds how long a tool call may run and how many calls run at once, so a
+//! hung `bash` command or browser page cannot stall an agent turn. A call
+//! that exceeds its timeout is dropped and the model gets an error result.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tool::policy::matches_pattern;
+
+/// Tool limits (`[tool_limits]` in cc-gateway.toml)
+///
+/// Names in `timeouts` ending in `*` match by prefix (`browser_*`); an exact
+/// name wins over a prefix, and a longer prefix over a shorter one. A
+/// timeout of 0 means no timeout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolLimits {
+    /// Seconds a call may run unless `timeouts` says otherwise
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Seconds per tool or tool prefix
+    #[serde(default)]
+    pub timeouts: BTreeMap<String, u64>,
+
+    /// Calls running at the same time across all turns (0 = unlimited)
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+}
+
+impl Default for ToolLimits {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_timeout_secs(),
+            timeouts: BTreeMap::new(),
+            max_concurrent: default_max_concurrent(),
+        }
+    }
+}
+
+/// As long as `bash` allows a command to run
+fn default_timeout_secs() -> u64 {
+    600
+}
+
+fn default_max_concurrent() -> usize {
+    8
+}
+
+impl ToolLimits {
+    /// How long a call to `tool` may run (`None` when unbounded)
+    pub fn timeout_for(&self, tool: &str) -> Option<Duration> {
+        let secs = match self.timeouts.get(tool) {
+            Some(secs) => *secs,
+            None => self
+                .timeouts
+                .iter()
+                .filter(|(pattern, _)| pattern.ends_with('*') && matches_pattern(pattern, tool))
+                .max_by_key(|(pattern, _)| pattern.len())
+                .map_or(self.timeout_secs, |(_, secs)| *secs),
+        };
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_for() {
+        let limits = ToolLimits {
+            timeout_secs: 60,
+            timeouts: BTreeMap::from([
+                ("browser_*".to_string(), 30),
+                ("browser_pdf".to_string(), 90),
+                ("browser_net*".to_string(), 0),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(limits.timeout_for("bash"), Some(Duration::from_secs(60)));
+        assert_eq!(limits.timeout_for("browser_click"), Some(Duration::from_secs(30)));
+        assert_eq!(limits.timeout_for("browser_pdf"), Some(Duration::from_secs(90)));
+        assert_eq!(limits.timeout_for("browser_network"), None);
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let limits: ToolLimits = toml::from_str("max_concurrent = 2\n[timeouts]\nweb_fetch = 20").unwrap();
+        assert_eq!(limits.timeout_secs, 600);
+        assert_eq!(limits.max_concurrent, 2);
+        assert_eq!(limits.timeout_for("web_fetch"), Some(Duration::from_secs(20)));
+    }
+}
@@ -3,33 +3,87 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use serde_json::Value as JsonValue;
+use jsonschema::Validator;
+use serde_json::{json, Value as JsonValue};
+use tokio::sync::Semaphore;
+use tracing::warn;
 
-use crate::tool::{Tool, ToolResult};
+use crate::tool::{PolicyDecision, Tool, ToolLimits, ToolPolicy, ToolResult};
 use crate::llm::ToolDefinition;
 use crate::Result;
 
 /// Manager for registered tools
 ///
-/// Handles tool registration, retrieval, and execution.
+/// Handles tool registration, retrieval, and execution. Inputs are checked
+/// against the tool's `input_schema` and calls are bound by the manager's
+/// [`ToolLimits`] and [`ToolPolicy`].
 pub struct ToolManager {
     /// Registered tools indexed by name
     tools: HashMap<String, Arc<dyn Tool>>,
+    /// Compiled input schemas (missing when a schema does not compile)
+    validators: HashMap<String, Validator>,
+    limits: ToolLimits,
+    /// Free execution slots (`None` when unlimited)
+    slots: Option<Semaphore>,
+    policy: ToolPolicy,
 }
 
 impl ToolManager {
-    /// Create a new empty tool manager
+    /// Create a new empty tool manager with the default limits
+    ///
+    /// Every tool runs until a policy is set with [`Self::set_policy`].
     pub fn new() -> Self {
+        let limits = ToolLimits::default();
         Self {
             tools: HashMap::new(),
+            validators: HashMap::new(),
+            slots: slots(&limits),
+            limits,
+            policy: ToolPolicy::allow_all(),
         }
     }
 
+    /// Replace the timeouts and concurrency limit of tool calls
+    pub fn set_limits(&mut self, limits: ToolLimits) {
+        self.slots = slots(&limits);
+        self.limits = limits;
+    }
+
+    /// Timeouts and concurrency limit of tool calls
+    pub fn limits(&self) -> &ToolLimits {
+        &self.limits
+    }
+
+    /// Replace the policy applied to every call
+    pub fn set_policy(&mut self, policy: ToolPolicy) {
+        self.policy = policy;
+    }
+
+    /// Policy applied to every call
+    pub fn policy(&self) -> &ToolPolicy {
+        &self.policy
+    }
+
+    /// What the policy says about a call to `name`
+    pub fn decide(&self, name: &str) -> PolicyDecision {
+        self.policy.decide_call(name, self.is_read_only(name))
+    }
+
     /// Register a tool
     ///
     /// If a tool with the same name already exists, it will be replaced.
     pub fn register(&mut self, tool: Arc<dyn Tool>) {
-        self.tools.insert(tool.name().to_string(), tool);
+        let name = tool.name().to_string();
+        match jsonschema::validator_for(&tool.input_schema()) {
+            Ok(validator) => {
+                self.validators.insert(name.clone(), validator);
+            }
+            Err(e) => {
+                warn!("Input schema of tool {} is invalid, its input is not validated: {}", name, e);
+                self.validators.remove(&name);
+            }
+        }
+        self.tools.insert(name, tool);
     }
 
     /// Get a tool by name
@@ -51,13 +105,99 @@ impl ToolManager {
     /// * `name` - The name of the tool to execute
     /// * `input` - The input parameters for the tool
     ///
+    /// Calls the policy denies, or that need an approval, return an error
+    /// result without running; callers that asked someone first use
+    /// [`Self::execute_approved`]. Input that does not match the tool's
+    /// schema is not passed to the tool; the error result lists what is
+    /// wrong so the model can retry. Waits for a free slot when
+    /// `max_concurrent` calls are running. A call that runs past its timeout
+    /// is dropped and returns an error result.
+    ///
     /// # Errors
     /// Returns an error if the tool is not found or execution fails
     pub async fn execute(&self, name: &str, input: JsonValue) -> Result<ToolResult> {
+        if self.contains(name) && self.decide(name) == PolicyDecision::RequireApproval {
+            warn!("Refused tool {}: it needs an approval", name);
+            return Ok(ToolResult::error(format!(
+                "Tool {} needs an approval, which was not given",
+                name
+            )));
+        }
+        self.execute_approved(name, input).await
+    }
+
+    /// Execute a call someone approved
+    ///
+    /// Like [`Self::execute`], but calls that need an approval run. Calls
+    /// the policy denies still return an error result.
+    ///
+    /// # Errors
+    /// Returns an error if the tool is not found or execution fails
+    pub async fn execute_approved(&self, name: &str, input: JsonValue) -> Result<ToolResult> {
+        if self.decide(name) == PolicyDecision::Deny {
+            warn!("Refused tool {}: denied by the tool policy", name);
+            return Ok(ToolResult::error(format!(
+                "Tool {} is not allowed by the tool policy",
+                name
+            )));
+        }
         let tool = self.get(name).ok_or_else(|| {
             crate::Error::ToolExecution(format!("Unknown tool: {}", name))
         })?;
-        tool.execute(input).await
+        if let Some(error) = self.validation_error(name, &input) {
+            return Ok(error);
+        }
+        if !tool.is_limited() {
+            return tool.execute(input).await;
+        }
+
+        // The semaphore is never closed, so acquiring only fails if it were
+        let _slot = match &self.slots {
+            Some(slots) => slots.acquire().await.ok(),
+            None => None,
+        };
+        let Some(timeout) = self.limits.timeout_for(name) else {
+            return tool.execute(input).await;
+        };
+        match tokio::time::timeout(timeout, tool.execute(input)).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!("Tool {} timed out after {}s", name, timeout.as_secs());
+                Ok(ToolResult::error(format!(
+                    "Tool {} timed out after {} seconds and was stopped",
+                    name,
+                    timeout.as_secs()
+                )))
+            }
+        }
+    }
+
+    /// Error result listing where `input` breaks the schema of tool `name`
+    fn validation_error(&self, name: &str, input: &JsonValue) -> Option<ToolResult> {
+        let validator = self.validators.get(name)?;
+        let errors: Vec<JsonValue> = validator
+            .iter_errors(input)
+            .map(|e| json!({"path": e.instance_path().as_str(), "message": e.to_string()}))
+            .collect();
+        if errors.is_empty() {
+            return None;
+        }
+        let error = json!({
+            "error": "invalid_input",
+            "message": format!("The input does not match the input schema of {}; fix it and call the tool again", name),
+            "errors": errors,
+        });
+        Some(ToolResult::error(serde_json::to_string(&error).unwrap_or_default()))
+    }
+
+    /// Preview of a call to `name` (see [`Tool::preview`])
+    pub async fn preview(&self, name: &str, input: &JsonValue) -> Option<String> {
+        self.get(name)?.preview(input).await
+    }
+
+    /// Whether `name` is a registered read-only tool (see [`Tool::is_read_only`])
+    pub fn is_read_only(&self, name: &str) -> bool {
+        self.tools.get(name).is_some_and(|t| t.is_read_only())
     }
 
     /// Check if a tool is registered
@@ -81,8 +221,145 @@ impl ToolManager {
     }
 }
 
+fn slots(limits: &ToolLimits) -> Option<Semaphore> {
+    (limits.max_concurrent > 0).then(|| Semaphore::new(limits.max_concurrent))
+}
+
 impl Default for ToolManager {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Sleeps for `input.ms` milliseconds, recording the most calls running at once
+    #[derive(Default)]
+    struct SleepTool {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Tool for SleepTool {
+        fn name(&self) -> &str {
+            "sleep"
+        }
+
+        fn description(&self) -> &str {
+            "Sleep"
+        }
+
+        fn input_schema(&self) -> JsonValue {
+            serde_json::json!({"type": "object"})
+        }
+
+        async fn execute(&self, input: JsonValue) -> Result<ToolResult> {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(input["ms"].as_u64().unwrap_or(0))).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(ToolResult::success("done"))
+        }
+    }
+
+    /// Reads a required `text` field without checking it
+    struct ShoutTool;
+
+    #[async_trait]
+    impl Tool for ShoutTool {
+        fn name(&self) -> &str {
+            "shout"
+        }
+
+        fn description(&self) -> &str {
+            "Shout the text"
+        }
+
+        fn input_schema(&self) -> JsonValue {
+            serde_json::json!({
+                "type": "object",
+                "properties": {"text": {"type": "string"}, "times": {"type": "integer", "minimum": 1}},
+                "required": ["text"]
+            })
+        }
+
+        async fn execute(&self, input: JsonValue) -> Result<ToolResult> {
+            Ok(ToolResult::success(input["text"].as_str().unwrap().to_uppercase()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_validates_input() {
+        let mut manager = ToolManager::new();
+        manager.register(Arc::new(ShoutTool));
+
+        let result = manager.execute("shout", serde_json::json!({"text": "hi"})).await.unwrap();
+        assert_eq!(result.output, "HI");
+
+        let result = manager
+            .execute("shout", serde_json::json!({"times": 0}))
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        let error: JsonValue = serde_json::from_str(&result.output).unwrap();
+        assert_eq!(error["error"], "invalid_input");
+        let errors = error["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().any(|e| e["path"] == "/times"));
+        assert!(errors.iter().any(|e| e["message"].as_str().unwrap().contains("\"text\"")));
+
+        let result = manager.execute("shout", serde_json::json!({"text": 5})).await.unwrap();
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_limits() {
+        let tool = Arc::new(SleepTool::default());
+        let mut manager = ToolManager::new();
+        manager.register(tool.clone());
+        manager.set_limits(ToolLimits {
+            timeout_secs: 0,
+            timeouts: BTreeMap::from([("sleep".to_string(), 1)]),
+            max_concurrent: 2,
+        });
+
+        let calls = (0..4).map(|_| manager.execute("sleep", serde_json::json!({"ms": 50})));
+        let results = futures::future::join_all(calls).await;
+        assert!(results.iter().all(|r| !r.as_ref().unwrap().is_error));
+        assert_eq!(tool.peak.load(Ordering::SeqCst), 2);
+
+        let result = manager.execute("sleep", serde_json::json!({"ms": 5000})).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("timed out after 1 seconds"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_applies_policy() {
+        let mut manager = ToolManager::new();
+        manager.register(Arc::new(ShoutTool));
+        manager.register(Arc::new(SleepTool::default()));
+        manager.set_policy(ToolPolicy {
+            require_approval: vec!["shout".to_string()],
+            deny: vec!["sleep".to_string()],
+            ..Default::default()
+        });
+
+        let input = serde_json::json!({"text": "hi"});
+        let result = manager.execute("shout", input.clone()).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("needs an approval"));
+        let result = manager.execute_approved("shout", input).await.unwrap();
+        assert_eq!(result.output, "HI");
+
+        let result = manager.execute_approved("sleep", serde_json::json!({})).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("not allowed by the tool policy"));
+    }
+}
@@ -4,9 +4,17 @@
 //! requested by Claude API.
 
 pub mod definition;
+pub mod limits;
 pub mod manager;
+pub mod origin;
+pub mod parallel;
+pub mod policy;
 pub mod traits;
 
 pub use definition::ToolDefinition;
+pub use limits::ToolLimits;
 pub use manager::ToolManager;
+pub use origin::ToolOrigin;
+pub use parallel::{execute_ordered, MAX_CONCURRENT_TOOLS};
+pub use policy::{ApprovalOutcome, PolicyDecision, ToolApprover, ToolPolicy};
 pub use traits::{Tool, ToolResult};
@@ -0,0 +1,67 @@
+//! Originating channel of a tool call
+//!
+//! Gateways run their agent loop inside [`ToolOrigin::scope`] so that tools
+//! (e.g. schedule or reminder creation) can deliver results back to the
+//! channel the request came from.
+
+use std::future::Future;
+
+tokio::task_local! {
+    static CURRENT_ORIGIN: ToolOrigin;
+}
+
+/// The channel a tool call originated from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolOrigin {
+    /// Platform name (`discord`, `telegram`, `slack`, ...)
+    pub platform: String,
+    /// Channel / chat ID on the platform
+    pub channel_id: String,
+    /// User who sent the request
+    pub user_id: Option<String>,
+}
+
+impl ToolOrigin {
+    /// Create a new origin
+    pub fn new(platform: impl Into<String>, channel_id: impl Into<String>) -> Self {
+        Self {
+            platform: platform.into(),
+            channel_id: channel_id.into(),
+            user_id: None,
+        }
+    }
+
+    /// Set the requesting user
+    pub fn with_user(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Run a future with this origin available to tools
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_ORIGIN.scope(self, future).await
+    }
+
+    /// The origin of the tool call currently being executed, if any
+    pub fn current() -> Option<Self> {
+        CURRENT_ORIGIN.try_with(Clone::clone).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scope() {
+        assert!(ToolOrigin::current().is_none());
+
+        let origin = ToolOrigin::new("discord", "123").with_user("42");
+        let seen = origin
+            .clone()
+            .scope(async { ToolOrigin::current() })
+            .await;
+        assert_eq!(seen, Some(origin));
+        assert!(ToolOrigin::current().is_none());
+    }
+}
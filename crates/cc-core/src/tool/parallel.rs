@@ -0,0 +1,108 @@
+//! Concurrent execution of the tool calls of one response
+//!
+//! When the model asks for several tools at once, consecutive calls to
+//! read-only tools ([`Tool::is_read_only`](crate::tool::Tool::is_read_only))
+//! run at the same time, at most [`MAX_CONCURRENT_TOOLS`] at once. Any other
+//! call runs alone, after the calls before it and before the calls after it,
+//! so a `write` followed by a `read` still sees the written file. Results are
+//! returned in the order of the calls, which is the order of their
+//! `tool_use_id`s in the response.
+//!
+//! The calls are polled on the current task rather than spawned, so the
+//! task-local [`ToolOrigin`](crate::tool::ToolOrigin) stays visible to them.
+
+use std::future::Future;
+
+use futures::stream::{self, StreamExt};
+
+/// Tool calls of one response run at the same time
+pub const MAX_CONCURRENT_TOOLS: usize = 4;
+
+/// Run `execute` on each call and return the outputs in call order
+///
+/// Runs of calls for which `concurrent` holds are executed together, at most
+/// `limit` at a time; the others run one by one.
+pub async fn execute_ordered<T, R, Fut>(
+    calls: Vec<T>,
+    limit: usize,
+    concurrent: impl Fn(&T) -> bool,
+    execute: impl Fn(T) -> Fut,
+) -> Vec<R>
+where
+    Fut: Future<Output = R>,
+{
+    let mut results = Vec::with_capacity(calls.len());
+    let mut batch = Vec::new();
+    for call in calls {
+        if concurrent(&call) {
+            batch.push(call);
+            continue;
+        }
+        run_batch(&mut batch, limit, &execute, &mut results).await;
+        results.push(execute(call).await);
+    }
+    run_batch(&mut batch, limit, &execute, &mut results).await;
+    results
+}
+
+async fn run_batch<T, R, Fut>(batch: &mut Vec<T>, limit: usize, execute: &impl Fn(T) -> Fut, results: &mut Vec<R>)
+where
+    Fut: Future<Output = R>,
+{
+    if batch.is_empty() {
+        return;
+    }
+    // `buffered` keeps the output order of its input
+    let outputs: Vec<R> = stream::iter(batch.drain(..))
+        .map(execute)
+        .buffered(limit.max(1))
+        .collect()
+        .await;
+    results.extend(outputs);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_execute_ordered() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let log = Mutex::new(Vec::new());
+
+        // (id, read-only, delay in ms)
+        let calls = vec![("a", true, 30), ("b", true, 10), ("c", true, 20), ("w", false, 5), ("d", true, 5)];
+        let results = execute_ordered(
+            calls,
+            2,
+            |call| call.1,
+            |(id, _, delay)| {
+                let (running, peak, log) = (&running, &peak, &log);
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    log.lock().unwrap().push(format!("start {}", id));
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    log.lock().unwrap().push(format!("end {}", id));
+                    id.to_uppercase()
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(results, vec!["A", "B", "C", "W", "D"]);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+        // The write starts after the reads before it end and ends before the read after it starts
+        let log = log.into_inner().unwrap();
+        let position = |entry: &str| log.iter().position(|e| e == entry).unwrap();
+        assert!(position("start b") < position("end a"));
+        assert!(position("start w") > position("end c"));
+        assert!(position("start d") > position("end w"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Tool execution policy
+//!
+//! Decides per tool whether a call runs, needs a person's approval first, or
+//! is refused. The [`ToolManager`](crate::ToolManager) applies its policy to
+//! every call, so agents, workflows and channels that cannot ask anyone never
+//! run a denied tool or one that needs approval. Channels that can ask
+//! someone (e.g. Discord with approval buttons) implement [`ToolApprover`];
+//! they show the call's [preview](crate::Tool::preview) so the person sees
+//! what would change, then run the call with
+//! [`ToolManager::execute_approved`](crate::ToolManager::execute_approved).
+//!
+//! In plan mode every tool that changes something needs approval, so nothing
+//! is written, run or sent without a confirmation.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// What to do with a tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// Run the tool
+    Allow,
+    /// Ask an approver before running the tool
+    RequireApproval,
+    /// Never run the tool
+    Deny,
+}
+
+/// Tool policy (`[tool_policy]` in cc-gateway.toml)
+///
+/// Names ending in `*` match by prefix (`browser_*`). `deny` wins over
+/// `require_approval`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolPolicy {
+    /// Tools that need approval before they run
+    #[serde(default = "default_require_approval")]
+    pub require_approval: Vec<String>,
+
+    /// Tools that are never run
+    #[serde(default)]
+    pub deny: Vec<String>,
+
+    /// Seconds to wait for an approval before refusing the call
+    #[serde(default = "default_approval_timeout_secs")]
+    pub approval_timeout_secs: u64,
+
+    /// Ask before every tool that is not read-only (plan mode)
+    #[serde(default)]
+    pub plan_mode: bool,
+}
+
+impl Default for ToolPolicy {
+    fn default() -> Self {
+        Self {
+            require_approval: default_require_approval(),
+            deny: Vec::new(),
+            approval_timeout_secs: default_approval_timeout_secs(),
+            plan_mode: false,
+        }
+    }
+}
+
+fn default_require_approval() -> Vec<String> {
+    ["bash", "write", "edit", "browser_*"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_approval_timeout_secs() -> u64 {
+    300
+}
+
+impl ToolPolicy {
+    /// A policy that runs every tool
+    pub fn allow_all() -> Self {
+        Self {
+            require_approval: Vec::new(),
+            ..Default::default()
+        }
+    }
+
+    /// Decide what to do with a call to `tool`
+    pub fn decide(&self, tool: &str) -> PolicyDecision {
+        if self.deny.iter().any(|p| matches_pattern(p, tool)) {
+            PolicyDecision::Deny
+        } else if self.require_approval.iter().any(|p| matches_pattern(p, tool)) {
+            PolicyDecision::RequireApproval
+        } else {
+            PolicyDecision::Allow
+        }
+    }
+
+    /// Decide what to do with a call to `tool`, taking plan mode into account
+    ///
+    /// `read_only` is [`Tool::is_read_only`](crate::Tool::is_read_only) of the tool.
+    pub fn decide_call(&self, tool: &str, read_only: bool) -> PolicyDecision {
+        match self.decide(tool) {
+            PolicyDecision::Allow if self.plan_mode && !read_only => PolicyDecision::RequireApproval,
+            decision => decision,
+        }
+    }
+}
+
+pub(crate) fn matches_pattern(pattern: &str, tool: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => tool.starts_with(prefix),
+        None => pattern == tool,
+    }
+}
+
+/// Answer to an approval request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalOutcome {
+    /// Approved by the given user
+    Approved { by: String },
+    /// Denied by the given user
+    Denied { by: String },
+    /// Nobody answered in time
+    TimedOut,
+}
+
+/// Asks a person whether a tool call may run
+#[async_trait]
+pub trait ToolApprover: Send + Sync {
+    /// Ask for approval of a call to `tool` with `input`
+    ///
+    /// `preview` is what the call would do (a diff, the command, a draft)
+    /// when the tool can tell; show it in place of the raw input.
+    async fn approve(&self, tool: &str, input: &JsonValue, preview: Option<&str>) -> ApprovalOutcome;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide() {
+        let policy = ToolPolicy::default();
+        assert_eq!(policy.decide("bash"), PolicyDecision::RequireApproval);
+        assert_eq!(policy.decide("browser_click"), PolicyDecision::RequireApproval);
+        assert_eq!(policy.decide("read"), PolicyDecision::Allow);
+        assert_eq!(policy.decide("bash_history"), PolicyDecision::Allow);
+
+        let policy = ToolPolicy {
+            deny: vec!["bash".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(policy.decide("bash"), PolicyDecision::Deny);
+        assert_eq!(ToolPolicy::allow_all().decide("bash"), PolicyDecision::Allow);
+    }
+
+    #[test]
+    fn test_decide_call_in_plan_mode() {
+        let policy = ToolPolicy {
+            deny: vec!["bash".to_string()],
+            plan_mode: true,
+            ..ToolPolicy::allow_all()
+        };
+        assert_eq!(policy.decide_call("email_send", false), PolicyDecision::RequireApproval);
+        assert_eq!(policy.decide_call("read", true), PolicyDecision::Allow);
+        assert_eq!(policy.decide_call("bash", false), PolicyDecision::Deny);
+
+        let policy = ToolPolicy::allow_all();
+        assert_eq!(policy.decide_call("email_send", false), PolicyDecision::Allow);
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        let policy: ToolPolicy = toml::from_str("deny = [\"browser_*\"]\napproval_timeout_secs = 60").unwrap();
+        assert_eq!(policy.require_approval, default_require_approval());
+        assert_eq!(policy.decide("browser_type"), PolicyDecision::Deny);
+        assert_eq!(policy.approval_timeout_secs, 60);
+    }
+}
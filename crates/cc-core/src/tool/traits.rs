@@ -6,6 +6,7 @@
 use async_trait::async_trait;
 use serde_json::Value as JsonValue;
 
+use crate::llm::{ImageSource, ToolResultContent};
 use crate::Result;
 
 /// Tool execution result
@@ -15,6 +16,8 @@ pub struct ToolResult {
     pub output: String,
     /// Whether the execution resulted in an error
     pub is_error: bool,
+    /// Images shown to the model after `output` (e.g. a screenshot)
+    pub images: Vec<ImageSource>,
 }
 
 impl ToolResult {
@@ -23,6 +26,7 @@ impl ToolResult {
         Self {
             output: output.into(),
             is_error: false,
+            images: Vec::new(),
         }
     }
 
@@ -31,8 +35,20 @@ impl ToolResult {
         Self {
             output: output.into(),
             is_error: true,
+            images: Vec::new(),
         }
     }
+
+    /// Attach an image the model sees as an image block rather than text
+    pub fn with_image(mut self, image: ImageSource) -> Self {
+        self.images.push(image);
+        self
+    }
+
+    /// Content of the `tool_result` block sent to the model
+    pub fn content(&self) -> ToolResultContent {
+        ToolResultContent::with_images(self.output.clone(), self.images.clone())
+    }
 }
 
 /// Tool trait for Claude API tool_use
@@ -50,6 +66,31 @@ pub trait Tool: Send + Sync + 'static {
     /// Get the JSON schema for the tool's input parameters
     fn input_schema(&self) -> JsonValue;
 
+    /// Whether the tool only reads (files, the web, stores) and changes nothing
+    ///
+    /// Calls to read-only tools requested in the same response run
+    /// concurrently; other tools run one at a time.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Whether calls are bound by the manager's [`ToolLimits`](crate::tool::ToolLimits)
+    ///
+    /// Tools that run other tools (such as `delegate_task`) return false:
+    /// holding a slot while their own calls wait for one could deadlock.
+    fn is_limited(&self) -> bool {
+        true
+    }
+
+    /// What a call with `input` would do, without doing it
+    ///
+    /// Shown when asking to confirm the call: a diff of the file, the command
+    /// to run, the draft to send. `None` when the tool has nothing better to
+    /// show than its input.
+    async fn preview(&self, _input: &JsonValue) -> Option<String> {
+        None
+    }
+
     /// Execute the tool with the given input
     ///
     /// # Arguments
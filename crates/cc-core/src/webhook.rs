@@ -0,0 +1,307 @@
+//! Webhook signature verification
+//!
+//! Checks that a webhook request really comes from the platform it claims
+//! to come from. Each platform signs requests with a shared secret:
+//!
+//! - Meta (Facebook, Instagram, WhatsApp Cloud API): `X-Hub-Signature-256`,
+//!   `sha256=` + hex HMAC-SHA256 of the body with the app secret
+//! - LINE: `X-Line-Signature`, base64 HMAC-SHA256 of the body with the
+//!   channel secret
+//! - Twilio: `X-Twilio-Signature`, base64 HMAC-SHA1 of the public webhook
+//!   URL followed by the sorted form parameters, with the auth token
+//! - Slack (HTTP Events API, slash commands): `X-Slack-Signature`, `v0=` +
+//!   hex HMAC-SHA256 of `v0:{timestamp}:{body}` with the signing secret; the
+//!   `X-Slack-Request-Timestamp` header must be recent
+//!
+//! Services with their own header layout (GitHub, Stripe, Grafana, ...)
+//! sign with a hex HMAC-SHA256; check those with [`verify_hmac_sha256_hex`].
+//!
+//! The channel webhook handlers check requests with [`WebhookVerifier`]. All
+//! comparisons are constant-time.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use hmac::{Hmac, Mac};
+use reqwest::header::HeaderMap;
+use sha1::Sha1;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+type HmacSha1 = Hmac<Sha1>;
+
+/// Maximum age of a Slack request timestamp (seconds)
+pub const SLACK_TOLERANCE_SECS: u64 = 300;
+
+/// Why a webhook request was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("Missing header: {0}")]
+    MissingHeader(&'static str),
+
+    #[error("Signature mismatch")]
+    Mismatch,
+
+    #[error("Request timestamp out of tolerance")]
+    StaleTimestamp,
+}
+
+/// Signature scheme and secret of a webhook endpoint
+#[derive(Debug, Clone)]
+pub enum WebhookVerifier {
+    /// Meta platforms (`X-Hub-Signature-256`)
+    Meta { app_secret: String },
+    /// LINE (`X-Line-Signature`)
+    Line { channel_secret: String },
+    /// Twilio (`X-Twilio-Signature`)
+    Twilio { auth_token: String },
+    /// Slack (`X-Slack-Signature`)
+    Slack { signing_secret: String },
+}
+
+impl WebhookVerifier {
+    /// Header carrying the signature
+    pub fn header(&self) -> &'static str {
+        match self {
+            Self::Meta { .. } => "x-hub-signature-256",
+            Self::Line { .. } => "x-line-signature",
+            Self::Twilio { .. } => "x-twilio-signature",
+            Self::Slack { .. } => "x-slack-signature",
+        }
+    }
+
+    /// Verify a request
+    ///
+    /// `url` is the public URL the platform posts to; only Twilio signs it
+    /// (pass the URL exactly as configured in the Twilio console).
+    pub fn verify(&self, headers: &HeaderMap, url: &str, body: &[u8]) -> Result<(), SignatureError> {
+        self.verify_at(headers, url, body, chrono::Utc::now().timestamp())
+    }
+
+    /// Verify a request at Unix time `now`
+    pub fn verify_at(
+        &self,
+        headers: &HeaderMap,
+        url: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), SignatureError> {
+        let signature = header_value(headers, self.header())?;
+        let valid = match self {
+            Self::Meta { app_secret } => verify_meta(app_secret, body, signature),
+            Self::Line { channel_secret } => verify_line(channel_secret, body, signature),
+            Self::Twilio { auth_token } => {
+                let params: Vec<(String, String)> = form_urlencoded::parse(body).into_owned().collect();
+                verify_twilio(auth_token, url, &params, signature)
+            }
+            Self::Slack { signing_secret } => {
+                let timestamp = header_value(headers, "x-slack-request-timestamp")?.trim();
+                // Replayed requests carry an old timestamp; abs_diff cannot overflow
+                match timestamp.parse::<i64>() {
+                    Ok(ts) if now.abs_diff(ts) <= SLACK_TOLERANCE_SECS => {}
+                    _ => return Err(SignatureError::StaleTimestamp),
+                }
+                verify_slack(signing_secret, timestamp, body, signature)
+            }
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(SignatureError::Mismatch)
+        }
+    }
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &'static str) -> Result<&'a str, SignatureError> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(SignatureError::MissingHeader(name))
+}
+
+/// Verify an `X-Hub-Signature-256` header (`sha256=<hex>`)
+pub fn verify_meta(app_secret: &str, body: &[u8], header: &str) -> bool {
+    header
+        .trim()
+        .strip_prefix("sha256=")
+        .is_some_and(|hex_sig| verify_hmac_sha256_hex(app_secret, &[body], hex_sig))
+}
+
+/// Hex HMAC-SHA256 of `body` with `secret` (the counterpart of [`verify_hmac_sha256_hex`])
+pub fn sign_hmac_sha256_hex(secret: &str, body: &[u8]) -> String {
+    hmac_sha256(secret, &[body])
+        .map(|mac| hex::encode(mac.finalize().into_bytes()))
+        .unwrap_or_default()
+}
+
+/// Verify a hex HMAC-SHA256 of `parts`, signed one after the other, with `secret`
+pub fn verify_hmac_sha256_hex(secret: &str, parts: &[&[u8]], hex_sig: &str) -> bool {
+    let Ok(expected) = hex::decode(hex_sig.trim()) else {
+        return false;
+    };
+    hmac_sha256(secret, parts).is_some_and(|mac| mac.verify_slice(&expected).is_ok())
+}
+
+/// Verify an `X-Line-Signature` header (base64)
+pub fn verify_line(channel_secret: &str, body: &[u8], header: &str) -> bool {
+    let Ok(expected) = STANDARD.decode(header.trim()) else {
+        return false;
+    };
+    hmac_sha256(channel_secret, &[body]).is_some_and(|mac| mac.verify_slice(&expected).is_ok())
+}
+
+/// Verify an `X-Twilio-Signature` header (base64)
+///
+/// Twilio signs the full webhook URL followed by each POST parameter name
+/// and value, sorted by name.
+pub fn verify_twilio(auth_token: &str, url: &str, params: &[(String, String)], header: &str) -> bool {
+    let Ok(expected) = STANDARD.decode(header.trim()) else {
+        return false;
+    };
+    let mut sorted: Vec<&(String, String)> = params.iter().collect();
+    sorted.sort();
+
+    let Ok(mut mac) = HmacSha1::new_from_slice(auth_token.as_bytes()) else {
+        return false;
+    };
+    mac.update(url.as_bytes());
+    for (key, value) in sorted {
+        mac.update(key.as_bytes());
+        mac.update(value.as_bytes());
+    }
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Verify an `X-Slack-Signature` header (`v0=<hex>`)
+///
+/// The caller checks that `timestamp` is recent.
+pub fn verify_slack(signing_secret: &str, timestamp: &str, body: &[u8], header: &str) -> bool {
+    let prefix = format!("v0:{}:", timestamp);
+    header
+        .trim()
+        .strip_prefix("v0=")
+        .is_some_and(|hex_sig| verify_hmac_sha256_hex(signing_secret, &[prefix.as_bytes(), body], hex_sig))
+}
+
+/// Compare a shared secret or token in time independent of where it differs
+pub fn tokens_match(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn hmac_sha256(secret: &str, parts: &[&[u8]]) -> Option<HmacSha256> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).ok()?;
+    for part in parts {
+        mac.update(part);
+    }
+    Some(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn sign_sha256(secret: &str, data: &[u8]) -> Vec<u8> {
+        hmac_sha256(secret, &[data]).unwrap().finalize().into_bytes().to_vec()
+    }
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_verify_meta() {
+        let body = br#"{"object":"page"}"#;
+        let header = format!("sha256={}", hex::encode(sign_sha256("app-secret", body)));
+        assert!(verify_meta("app-secret", body, &header));
+        assert!(!verify_meta("other", body, &header));
+        assert!(!verify_meta("app-secret", body, header.trim_start_matches("sha256=")));
+
+        let verifier = WebhookVerifier::Meta { app_secret: "app-secret".to_string() };
+        let signed = headers(&[("x-hub-signature-256", header)]);
+        assert!(verifier.verify(&signed, "", body).is_ok());
+        assert_eq!(verifier.verify(&signed, "", b"{}"), Err(SignatureError::Mismatch));
+        assert_eq!(
+            verifier.verify(&HeaderMap::new(), "", body),
+            Err(SignatureError::MissingHeader("x-hub-signature-256"))
+        );
+    }
+
+    #[test]
+    fn test_verify_hmac_sha256_hex() {
+        let signature = hex::encode(sign_sha256("s3cret", b"1700000000.{}"));
+        assert!(verify_hmac_sha256_hex("s3cret", &[b"1700000000.", b"{}"], &signature));
+        assert!(!verify_hmac_sha256_hex("s3cret", &[b"{}"], &signature));
+        assert!(!verify_hmac_sha256_hex("s3cret", &[b"{}"], "not hex"));
+    }
+
+    #[test]
+    fn test_verify_line() {
+        let body = br#"{"events":[]}"#;
+        let header = STANDARD.encode(sign_sha256("channel-secret", body));
+        assert!(verify_line("channel-secret", body, &header));
+        assert!(!verify_line("channel-secret", b"{}", &header));
+        assert!(!verify_line("channel-secret", body, "not base64!"));
+    }
+
+    #[test]
+    fn test_verify_twilio() {
+        // Example from Twilio's webhook security documentation
+        let url = "https://mycompany.com/myapp.php?foo=1&bar=2";
+        let body = b"CallSid=CA1234567890ABCDE&Caller=%2B12349013030&Digits=1234&From=%2B12349013030&To=%2B18005551212";
+        let verifier = WebhookVerifier::Twilio { auth_token: "12345".to_string() };
+        let signed = headers(&[("x-twilio-signature", "0/KCTR6DLpKmkAf8muzZqo1nDgQ=".to_string())]);
+        assert!(verifier.verify(&signed, url, body).is_ok());
+        assert_eq!(
+            verifier.verify(&signed, "https://mycompany.com/other", body),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_slack() {
+        let body = b"token=x&team_id=T1";
+        let ts = "1700000000";
+        let header = format!("v0={}", hex::encode(sign_sha256("signing-secret", format!("v0:{}:token=x&team_id=T1", ts).as_bytes())));
+        assert!(verify_slack("signing-secret", ts, body, &header));
+        assert!(!verify_slack("signing-secret", ts, b"token=y", &header));
+        assert!(!verify_slack("signing-secret", ts, body, header.trim_start_matches("v0=")));
+
+        let verifier = WebhookVerifier::Slack { signing_secret: "signing-secret".to_string() };
+        let signed = |ts: &str| {
+            headers(&[
+                ("x-slack-signature", header.clone()),
+                ("x-slack-request-timestamp", ts.to_string()),
+            ])
+        };
+        assert!(verifier.verify_at(&signed(ts), "", body, 1_700_000_100).is_ok());
+        assert_eq!(
+            verifier.verify_at(&signed(ts), "", body, 1_700_001_000),
+            Err(SignatureError::StaleTimestamp)
+        );
+        assert_eq!(
+            verifier.verify_at(&signed(ts), "", b"token=y", 1_700_000_100),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verifier.verify_at(&headers(&[("x-slack-signature", header.clone())]), "", body, 1_700_000_100),
+            Err(SignatureError::MissingHeader("x-slack-request-timestamp"))
+        );
+
+        // Extreme or malformed timestamps are rejected without overflowing
+        for ts in [i64::MIN.to_string(), i64::MAX.to_string(), "soon".to_string()] {
+            assert_eq!(
+                verifier.verify_at(&signed(&ts), "", body, 1_700_000_000),
+                Err(SignatureError::StaleTimestamp)
+            );
+        }
+    }
+}
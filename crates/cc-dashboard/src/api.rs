@@ -6,12 +6,20 @@ use async_trait::async_trait;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::{Html, IntoResponse, Json},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Json,
+    },
     routing::get,
     Router,
 };
+use cc_core::agents::SubAgentEventHub;
+use cc_core::{Session, SessionManager};
+use cc_schedule::{RunHistory, RunRecord};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
 use tower_http::cors::{Any, CorsLayer};
 
 /// Dashboard state shared across handlers
@@ -20,6 +28,12 @@ pub struct DashboardState {
     pub sessions: Arc<dyn SessionProvider + Send + Sync>,
     /// Usage data provider
     pub usage: Arc<dyn UsageProvider + Send + Sync>,
+    /// Scheduled run history provider (optional)
+    pub schedule_runs: Option<Arc<dyn ScheduleRunProvider + Send + Sync>>,
+    /// Live sub-agent progress events (optional)
+    pub agent_events: Option<SubAgentEventHub>,
+    /// Status of the gateway's services (optional)
+    pub services: Option<Arc<dyn ServiceProvider + Send + Sync>>,
 }
 
 impl Clone for DashboardState {
@@ -27,6 +41,9 @@ impl Clone for DashboardState {
         Self {
             sessions: self.sessions.clone(),
             usage: self.usage.clone(),
+            schedule_runs: self.schedule_runs.clone(),
+            agent_events: self.agent_events.clone(),
+            services: self.services.clone(),
         }
     }
 }
@@ -37,7 +54,34 @@ impl DashboardState {
         sessions: Arc<dyn SessionProvider + Send + Sync>,
         usage: Arc<dyn UsageProvider + Send + Sync>,
     ) -> Self {
-        Self { sessions, usage }
+        Self {
+            sessions,
+            usage,
+            schedule_runs: None,
+            agent_events: None,
+            services: None,
+        }
+    }
+
+    /// Set the scheduled run history provider
+    pub fn with_schedule_runs(
+        mut self,
+        provider: Arc<dyn ScheduleRunProvider + Send + Sync>,
+    ) -> Self {
+        self.schedule_runs = Some(provider);
+        self
+    }
+
+    /// Stream sub-agent progress events from the given hub
+    pub fn with_agent_events(mut self, hub: SubAgentEventHub) -> Self {
+        self.agent_events = Some(hub);
+        self
+    }
+
+    /// Set the service status provider
+    pub fn with_services(mut self, provider: Arc<dyn ServiceProvider + Send + Sync>) -> Self {
+        self.services = Some(provider);
+        self
     }
 }
 
@@ -61,6 +105,140 @@ pub trait UsageProvider: Send + Sync {
     async fn get_usage_range(&self, start: i64, end: i64) -> UsageStats;
 }
 
+/// Scheduled run history provider trait
+#[async_trait]
+pub trait ScheduleRunProvider: Send + Sync {
+    /// Get recent runs (newest first), optionally filtered by task name
+    async fn get_schedule_runs(&self, task: Option<&str>, limit: usize) -> Vec<RunRecord>;
+}
+
+/// Service status provider trait
+#[async_trait]
+pub trait ServiceProvider: Send + Sync {
+    /// Get the status of every supervised service
+    async fn get_services(&self) -> Vec<ServiceInfo>;
+}
+
+#[async_trait]
+impl ScheduleRunProvider for RunHistory {
+    async fn get_schedule_runs(&self, task: Option<&str>, limit: usize) -> Vec<RunRecord> {
+        self.recent(task, limit).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load schedule runs: {}", e);
+            Vec::new()
+        })
+    }
+}
+
+/// Sessions listed from a session manager
+const MAX_LISTED_SESSIONS: usize = 1000;
+
+/// Sessions without activity for this long are shown as idle
+const ACTIVE_SECS: i64 = 30 * 60;
+
+/// Sessions persisted by the gateway (HTTP API, WebSocket and CLI)
+///
+/// Token usage is not stored with sessions, so it is reported as zero.
+#[async_trait]
+impl SessionProvider for SessionManager {
+    async fn get_sessions(&self) -> Vec<SessionInfo> {
+        self.list_sessions(MAX_LISTED_SESSIONS, 0)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to list sessions: {}", e);
+                Vec::new()
+            })
+            .iter()
+            .map(session_info)
+            .collect()
+    }
+
+    async fn get_session(&self, id: &str) -> Option<SessionInfo> {
+        match SessionManager::get_session(self, id).await {
+            Ok(session) => session.as_ref().map(session_info),
+            Err(e) => {
+                tracing::warn!("Failed to load session {}: {}", id, e);
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl UsageProvider for SessionManager {
+    async fn get_usage(&self) -> UsageStats {
+        usage_stats(&self.get_sessions().await)
+    }
+
+    async fn get_usage_range(&self, start: i64, end: i64) -> UsageStats {
+        let sessions: Vec<SessionInfo> = self
+            .get_sessions()
+            .await
+            .into_iter()
+            .filter(|session| session.updated_at >= start && session.updated_at <= end)
+            .collect();
+        usage_stats(&sessions)
+    }
+}
+
+fn session_info(session: &Session) -> SessionInfo {
+    let updated_at = session.updated_at.timestamp();
+    let idle = chrono::Utc::now().timestamp() - updated_at > ACTIVE_SECS;
+    SessionInfo {
+        id: session.id.clone(),
+        // Channel IDs are prefixed with their channel ("cli:...", "ws:..."); API clients choose their own
+        channel: session
+            .channel_id
+            .split_once(':')
+            .map_or("api", |(channel, _)| channel)
+            .to_string(),
+        title: session.title.clone(),
+        message_count: session.messages.len(),
+        tokens: TokenUsage::default(),
+        created_at: session.created_at.timestamp(),
+        updated_at,
+        status: if idle { "idle" } else { "active" }.to_string(),
+    }
+}
+
+fn usage_stats(sessions: &[SessionInfo]) -> UsageStats {
+    let mut by_channel = std::collections::HashMap::new();
+    let mut daily: std::collections::BTreeMap<String, DailyStats> = std::collections::BTreeMap::new();
+    for session in sessions {
+        let channel = by_channel
+            .entry(session.channel.clone())
+            .or_insert_with(|| ChannelStats {
+                name: session.channel.clone(),
+                sessions: 0,
+                messages: 0,
+                tokens: TokenUsage::default(),
+            });
+        channel.sessions += 1;
+        channel.messages += session.message_count;
+
+        let date = chrono::DateTime::from_timestamp(session.updated_at, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%d")
+            .to_string();
+        daily
+            .entry(date.clone())
+            .or_insert_with(|| DailyStats {
+                date,
+                sessions: 0,
+                tokens: TokenUsage::default(),
+                cost: 0.0,
+            })
+            .sessions += 1;
+    }
+    UsageStats {
+        total_sessions: sessions.len(),
+        total_messages: sessions.iter().map(|session| session.message_count).sum(),
+        tokens: TokenUsage::default(),
+        estimated_cost: 0.0,
+        by_channel,
+        daily: daily.into_values().collect(),
+    }
+}
+
 /// Session information for display
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionInfo {
@@ -82,6 +260,23 @@ pub struct SessionInfo {
     pub status: String,
 }
 
+/// Status of a supervised service (channel, API server, scheduler)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceInfo {
+    /// Service name
+    pub name: String,
+    /// "running", "restarting" or "stopped"
+    pub status: String,
+    /// Number of restarts since the gateway started
+    pub restarts: u32,
+    /// Error (or panic message) of the last failed run
+    pub last_error: Option<String>,
+    /// Start of the current run (Unix timestamp)
+    pub started_at: Option<i64>,
+    /// Next restart while restarting (Unix timestamp)
+    pub restart_at: Option<i64>,
+}
+
 /// Token usage information
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TokenUsage {
@@ -154,6 +349,15 @@ pub struct SessionQuery {
     pub limit: Option<usize>,
 }
 
+/// Query parameters for scheduled run history
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleRunQuery {
+    /// Filter by task name
+    pub task: Option<String>,
+    /// Limit results (default: 50)
+    pub limit: Option<usize>,
+}
+
 /// Create the dashboard router
 pub fn create_router(state: DashboardState) -> Router {
     Router::new()
@@ -161,6 +365,9 @@ pub fn create_router(state: DashboardState) -> Router {
         .route("/api/sessions", get(list_sessions))
         .route("/api/sessions/{id}", get(get_session))
         .route("/api/usage", get(get_usage))
+        .route("/api/schedule-runs", get(list_schedule_runs))
+        .route("/api/agent-events", get(agent_events))
+        .route("/api/services", get(list_services))
         .route("/api/health", get(health_check))
         .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any))
         .with_state(Arc::new(state))
@@ -209,6 +416,53 @@ async fn get_usage(State(state): State<Arc<DashboardState>>) -> impl IntoRespons
     Json(stats)
 }
 
+/// List scheduled run history
+async fn list_schedule_runs(
+    State(state): State<Arc<DashboardState>>,
+    Query(query): Query<ScheduleRunQuery>,
+) -> impl IntoResponse {
+    let runs = match &state.schedule_runs {
+        Some(provider) => {
+            provider
+                .get_schedule_runs(query.task.as_deref(), query.limit.unwrap_or(50))
+                .await
+        }
+        None => Vec::new(),
+    };
+    Json(runs)
+}
+
+/// List supervised services
+async fn list_services(State(state): State<Arc<DashboardState>>) -> impl IntoResponse {
+    let services = match &state.services {
+        Some(provider) => provider.get_services().await,
+        None => Vec::new(),
+    };
+    Json(services)
+}
+
+/// Stream sub-agent progress events (Server-Sent Events)
+async fn agent_events(State(state): State<Arc<DashboardState>>) -> impl IntoResponse {
+    let Some(hub) = &state.agent_events else {
+        return (StatusCode::NOT_FOUND, "Agent events are not enabled").into_response();
+    };
+
+    let stream = futures::stream::unfold(hub.subscribe(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    let event = Event::default().event("agent").json_data(&event).ok()?;
+                    return Some((Ok::<_, Infallible>(event), rx));
+                }
+                // Slow clients skip events instead of blocking agents
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
 /// Health check endpoint
 async fn health_check() -> impl IntoResponse {
     Json(serde_json::json!({
@@ -272,6 +526,11 @@ const INDEX_HTML: &str = r#"<!DOCTYPE html>
         }
         .badge-active { background: #d4edda; color: #155724; }
         .badge-inactive { background: #f8d7da; color: #721c24; }
+        .badge-success { background: #d4edda; color: #155724; }
+        .badge-failure { background: #f8d7da; color: #721c24; }
+        .badge-running { background: #d4edda; color: #155724; }
+        .badge-restarting { background: #fff3cd; color: #856404; }
+        .badge-stopped { background: #f8d7da; color: #721c24; }
         .refresh-btn {
             background: #3498db;
             color: white;
@@ -311,6 +570,23 @@ const INDEX_HTML: &str = r#"<!DOCTYPE html>
             </div>
         </div>
 
+        <div class="sessions-table" style="margin-bottom: 20px;">
+            <h2>Services</h2>
+            <table>
+                <thead>
+                    <tr>
+                        <th>Service</th>
+                        <th>Status</th>
+                        <th>Restarts</th>
+                        <th>Since</th>
+                        <th>Last Error</th>
+                    </tr>
+                </thead>
+                <tbody id="services-body">
+                </tbody>
+            </table>
+        </div>
+
         <div class="sessions-table">
             <h2>Recent Sessions</h2>
             <table>
@@ -328,15 +604,70 @@ const INDEX_HTML: &str = r#"<!DOCTYPE html>
                 </tbody>
             </table>
         </div>
+
+        <div class="sessions-table" style="margin-top: 20px;">
+            <h2>Scheduled Runs</h2>
+            <table>
+                <thead>
+                    <tr>
+                        <th>Task</th>
+                        <th>Started</th>
+                        <th>Duration</th>
+                        <th>Tokens</th>
+                        <th>Status</th>
+                        <th>Output</th>
+                    </tr>
+                </thead>
+                <tbody id="runs-body">
+                </tbody>
+            </table>
+        </div>
+
+        <div class="sessions-table" style="margin-top: 20px;">
+            <h2>Agent Activity</h2>
+            <table>
+                <thead>
+                    <tr>
+                        <th>Time</th>
+                        <th>Agent</th>
+                        <th>Task</th>
+                        <th>Event</th>
+                    </tr>
+                </thead>
+                <tbody id="agent-events-body">
+                </tbody>
+            </table>
+        </div>
     </div>
     <script>
         async function loadData() {
             try {
-                const [usageRes, sessionsRes] = await Promise.all([
+                const [usageRes, sessionsRes, runsRes, servicesRes] = await Promise.all([
                     fetch('/api/usage'),
-                    fetch('/api/sessions?limit=20')
+                    fetch('/api/sessions?limit=20'),
+                    fetch('/api/schedule-runs?limit=20'),
+                    fetch('/api/services')
                 ]);
 
+                if (servicesRes.ok) {
+                    const services = await servicesRes.json();
+                    const tbody = document.getElementById('services-body');
+                    tbody.innerHTML = services.map(s => {
+                        const since = s.status === 'restarting' ? s.restart_at : s.started_at;
+                        const error = (s.last_error || '').substring(0, 80)
+                            .replace(/&/g, '&amp;').replace(/</g, '&lt;');
+                        return `
+                        <tr>
+                            <td>${s.name}</td>
+                            <td><span class="badge badge-${s.status}">${s.status}</span></td>
+                            <td>${s.restarts}</td>
+                            <td>${since ? new Date(since * 1000).toLocaleString() : '-'}</td>
+                            <td>${error}</td>
+                        </tr>
+                    `;
+                    }).join('');
+                }
+
                 if (usageRes.ok) {
                     const usage = await usageRes.json();
                     document.getElementById('total-sessions').textContent = usage.total_sessions;
@@ -361,11 +692,61 @@ const INDEX_HTML: &str = r#"<!DOCTYPE html>
                         </tr>
                     `).join('');
                 }
+
+                if (runsRes.ok) {
+                    const runs = await runsRes.json();
+                    const tbody = document.getElementById('runs-body');
+                    tbody.innerHTML = runs.map(r => {
+                        const status = r.success ? 'success' : 'failure';
+                        const excerpt = r.output_excerpt.substring(0, 80)
+                            .replace(/&/g, '&amp;').replace(/</g, '&lt;');
+                        return `
+                        <tr>
+                            <td>${r.task_name}</td>
+                            <td>${new Date(r.started_at).toLocaleString()}</td>
+                            <td>${(r.duration_ms / 1000).toFixed(1)}s</td>
+                            <td>${(r.input_tokens + r.output_tokens).toLocaleString()}</td>
+                            <td><span class="badge badge-${status}">${status}</span></td>
+                            <td>${excerpt}</td>
+                        </tr>
+                    `;
+                    }).join('');
+                }
             } catch (e) {
                 console.error('Failed to load data:', e);
             }
         }
 
+        function describeAgentEvent(e) {
+            switch (e.event) {
+                case 'task_started': return 'started';
+                case 'iteration_started': return `iteration ${e.iteration}`;
+                case 'tool_called': return `tool ${e.tool}${e.is_error ? ' (error)' : ''}`;
+                case 'tokens_used': return `${e.input_tokens + e.output_tokens} tokens`;
+                case 'task_finished': return e.status;
+                default: return e.event;
+            }
+        }
+
+        const agentEvents = new EventSource('/api/agent-events');
+        agentEvents.addEventListener('agent', msg => {
+            const e = JSON.parse(msg.data);
+            const row = document.createElement('tr');
+            [
+                new Date(e.timestamp).toLocaleTimeString(),
+                e.agent,
+                e.task_id.substring(0, 8),
+                describeAgentEvent(e),
+            ].forEach(text => {
+                const cell = document.createElement('td');
+                cell.textContent = text;
+                row.appendChild(cell);
+            });
+            const tbody = document.getElementById('agent-events-body');
+            tbody.prepend(row);
+            while (tbody.rows.length > 50) tbody.deleteRow(-1);
+        });
+
         loadData();
         setInterval(loadData, 30000);
     </script>
@@ -432,6 +813,88 @@ mod tests {
         assert_eq!(usage.total(), 175);
     }
 
+    #[tokio::test]
+    async fn test_session_manager_provider() {
+        let manager = SessionManager::in_memory().unwrap();
+        manager.get_or_create("cli:notes").await.unwrap();
+        manager.get_or_create("my-app").await.unwrap();
+        manager.add_message("cli:notes", cc_core::Message::user("hello")).await.unwrap();
+        manager.add_message("cli:notes", cc_core::Message::assistant("hi")).await.unwrap();
+        manager.add_message("my-app", cc_core::Message::user("ping")).await.unwrap();
+        manager.sync().await.unwrap();
+
+        let sessions = SessionProvider::get_sessions(&manager).await;
+        assert_eq!(sessions.len(), 2);
+        let notes = sessions.iter().find(|session| session.channel == "cli").unwrap();
+        assert_eq!(notes.message_count, 2);
+        assert_eq!(notes.status, "active");
+
+        let usage = manager.get_usage().await;
+        assert_eq!(usage.total_sessions, 2);
+        assert_eq!(usage.total_messages, 3);
+        assert_eq!(usage.by_channel["api"].messages, 1);
+        assert_eq!(usage.daily.len(), 1);
+        assert_eq!(manager.get_usage_range(0, 1).await.total_sessions, 0);
+    }
+
+    #[tokio::test]
+    async fn test_run_history_provider() {
+        let history = RunHistory::in_memory().unwrap();
+        history
+            .record(&cc_schedule::ScheduleResult {
+                task_name: "daily".to_string(),
+                executed_at: chrono::Utc::now(),
+                response: "done".to_string(),
+                success: true,
+                duration_ms: 10,
+                input_tokens: 1,
+                output_tokens: 2,
+            })
+            .unwrap();
+
+        let runs = history.get_schedule_runs(Some("daily"), 10).await;
+        assert_eq!(runs.len(), 1);
+        assert!(history.get_schedule_runs(Some("other"), 10).await.is_empty());
+    }
+
+    struct MockServiceProvider;
+
+    #[async_trait]
+    impl ServiceProvider for MockServiceProvider {
+        async fn get_services(&self) -> Vec<ServiceInfo> {
+            vec![ServiceInfo {
+                name: "discord".to_string(),
+                status: "restarting".to_string(),
+                restarts: 2,
+                last_error: Some("connection lost".to_string()),
+                started_at: None,
+                restart_at: Some(0),
+            }]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_services() {
+        let state = DashboardState::new(
+            Arc::new(MockSessionProvider),
+            Arc::new(MockUsageProvider),
+        );
+        let response = list_services(State(Arc::new(state))).await.into_response();
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+        assert_eq!(&body[..], b"[]");
+
+        let state = DashboardState::new(
+            Arc::new(MockSessionProvider),
+            Arc::new(MockUsageProvider),
+        )
+        .with_services(Arc::new(MockServiceProvider));
+        let response = list_services(State(Arc::new(state))).await.into_response();
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+        let services: Vec<ServiceInfo> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(services[0].name, "discord");
+        assert_eq!(services[0].restarts, 2);
+    }
+
     #[test]
     fn test_create_router() {
         let state = DashboardState::new(
@@ -440,4 +903,22 @@ mod tests {
         );
         let _router = create_router(state);
     }
+
+    #[tokio::test]
+    async fn test_agent_events_disabled() {
+        let state = DashboardState::new(
+            Arc::new(MockSessionProvider),
+            Arc::new(MockUsageProvider),
+        );
+        let response = agent_events(State(Arc::new(state))).await.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let state = DashboardState::new(
+            Arc::new(MockSessionProvider),
+            Arc::new(MockUsageProvider),
+        )
+        .with_agent_events(SubAgentEventHub::default());
+        let response = agent_events(State(Arc::new(state))).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
@@ -9,6 +9,8 @@
 //! - Token usage tracking
 //! - Cost estimation
 //! - Channel-based statistics
+//! - Scheduled task run history
+//! - Status of the gateway's services
 //! - RESTful API
 //!
 //! ## Usage
@@ -32,6 +34,6 @@ pub mod api;
 pub mod error;
 pub mod server;
 
-pub use api::{ChannelStats, DashboardState, DailyStats, SessionInfo, SessionProvider, TokenUsage, UsageProvider, UsageStats};
+pub use api::{ChannelStats, DashboardState, DailyStats, ScheduleRunProvider, ServiceInfo, ServiceProvider, SessionInfo, SessionProvider, TokenUsage, UsageProvider, UsageStats};
 pub use error::{DashboardError, Result};
 pub use server::{DashboardConfig, DashboardServer};
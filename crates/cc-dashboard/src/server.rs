@@ -4,9 +4,12 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::Router;
+use cc_core::agents::SubAgentEventHub;
 use tracing::info;
 
-use crate::api::{create_router, DashboardState, SessionProvider, UsageProvider};
+use crate::api::{
+    create_router, DashboardState, ScheduleRunProvider, ServiceProvider, SessionProvider, UsageProvider,
+};
 use crate::error::{DashboardError, Result};
 
 /// Dashboard server configuration
@@ -63,6 +66,27 @@ impl DashboardServer {
         }
     }
 
+    /// Show scheduled run history from the given provider
+    pub fn with_schedule_runs(
+        mut self,
+        provider: Arc<dyn ScheduleRunProvider + Send + Sync>,
+    ) -> Self {
+        self.state = self.state.with_schedule_runs(provider);
+        self
+    }
+
+    /// Stream sub-agent progress from the given hub
+    pub fn with_agent_events(mut self, hub: SubAgentEventHub) -> Self {
+        self.state = self.state.with_agent_events(hub);
+        self
+    }
+
+    /// Show the status of the gateway's services from the given provider
+    pub fn with_services(mut self, provider: Arc<dyn ServiceProvider + Send + Sync>) -> Self {
+        self.state = self.state.with_services(provider);
+        self
+    }
+
     /// Get the router
     pub fn router(&self) -> Router {
         create_router(self.state.clone())
@@ -0,0 +1,104 @@
+//! Agent loop for Discord turns
+//!
+//! Runs the model with the gateway's tools (when configured) and reports tool
+//! calls and intermediate text to a [`Progress`] as the turn goes on. Calls
+//! are checked against the tool policy; those needing approval wait for the
+//! [`ToolApprover`].
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use cc_core::llm::ToolCall;
+use cc_core::{
+    AgentEngine, AgentHooks, ApprovalOutcome, MessagesRequest, PolicyDecision, ToolApprover, ToolManager, ToolPolicy,
+    ToolResult,
+};
+
+use crate::commands::Data;
+use crate::progress::{Progress, ProgressTarget};
+
+/// Model calls per turn before giving up
+const MAX_ITERATIONS: usize = 10;
+
+/// Run `request` until the model answers without calling tools
+///
+/// `request` carries the system prompt, history and tools (with the persona
+/// applied); its messages are extended with the tool calls of the turn. Tool
+/// calls are checked against `policy` (the guild's, or the global one).
+pub(crate) async fn run_agent<T: ProgressTarget>(
+    data: &Data,
+    mut request: MessagesRequest,
+    policy: &ToolPolicy,
+    progress: &mut Progress<T>,
+    approver: &dyn ToolApprover,
+) -> cc_core::Result<String> {
+    let mut engine = AgentEngine::new(&data.claude_client).with_max_iterations(MAX_ITERATIONS);
+    if let Some(tools) = &data.tools {
+        engine = engine.with_tools(tools);
+    }
+    let hooks = DiscordHooks {
+        policy,
+        progress: Mutex::new(progress),
+        approver,
+    };
+    Ok(engine.run(&mut request, &hooks).await?.text)
+}
+
+/// Applies the tool policy and reports the turn to the [`Progress`]
+struct DiscordHooks<'a, T: ProgressTarget> {
+    policy: &'a ToolPolicy,
+    progress: Mutex<&'a mut Progress<T>>,
+    approver: &'a dyn ToolApprover,
+}
+
+#[async_trait]
+impl<T: ProgressTarget> AgentHooks for DiscordHooks<'_, T> {
+    async fn on_text(&self, text: &str) {
+        self.progress.lock().await.draft(text).await;
+    }
+
+    async fn on_tool_call(&self, call: &ToolCall, tools: &ToolManager) -> ToolResult {
+        run_tool(self.policy, tools, &call.name, call.input.clone(), &self.progress, self.approver).await
+    }
+}
+
+/// Run one tool call as `policy` allows
+async fn run_tool<T: ProgressTarget>(
+    policy: &ToolPolicy,
+    tools: &ToolManager,
+    name: &str,
+    input: serde_json::Value,
+    progress: &Mutex<&mut Progress<T>>,
+    approver: &dyn ToolApprover,
+) -> ToolResult {
+    match policy.decide_call(name, tools.is_read_only(name)) {
+        PolicyDecision::Allow => {}
+        PolicyDecision::Deny => {
+            progress.lock().await.tool_refused(name).await;
+            return ToolResult::error(format!("Tool {} is not allowed by the tool policy", name));
+        }
+        PolicyDecision::RequireApproval => {
+            progress.lock().await.awaiting_approval(name).await;
+            let preview = tools.preview(name, &input).await;
+            match approver.approve(name, &input, preview.as_deref()).await {
+                ApprovalOutcome::Approved { .. } => {}
+                ApprovalOutcome::Denied { .. } => {
+                    progress.lock().await.tool_refused(name).await;
+                    return ToolResult::error(format!("An admin denied running {}", name));
+                }
+                ApprovalOutcome::TimedOut => {
+                    progress.lock().await.tool_refused(name).await;
+                    return ToolResult::error(format!("Running {} was not approved in time", name));
+                }
+            }
+        }
+    }
+
+    progress.lock().await.tool_started(name).await;
+    let result = tools
+        .execute_approved(name, input)
+        .await
+        .unwrap_or_else(|e| ToolResult::error(e.to_string()));
+    progress.lock().await.tool_finished(name, result.is_error).await;
+    result
+}
@@ -0,0 +1,260 @@
+//! Tool approval buttons
+//!
+//! When the [`ToolPolicy`](cc_core::ToolPolicy) requires approval for a tool,
+//! the bot posts the call (its preview when the tool has one) with Approve /
+//! Deny buttons in the channel and waits for an admin to press one. Without
+//! `ADMIN_USER_IDS`, only server members who can manage the server approve.
+//! Decisions are written to the audit log.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use serde_json::Value as JsonValue;
+use tracing::{info, warn};
+
+use cc_core::audit::{AuditEntry, AuditEventType, AuditLevel, AuditLogger, AuditSource};
+use cc_core::{ApprovalOutcome, ToolApprover};
+
+const APPROVE_BUTTON: &str = "cc_tool_approve";
+const DENY_BUTTON: &str = "cc_tool_deny";
+
+/// Longest tool input shown in the prompt
+const MAX_INPUT_PREVIEW: usize = 1200;
+
+/// Asks the channel's admins with buttons
+pub struct ButtonApprover {
+    ctx: serenity::Context,
+    channel_id: serenity::ChannelId,
+    /// The user whose request led to the call
+    requester: String,
+    admin_user_ids: Vec<String>,
+    timeout: Duration,
+    audit: Option<Arc<AuditLogger>>,
+}
+
+impl ButtonApprover {
+    pub fn new(
+        ctx: serenity::Context,
+        channel_id: serenity::ChannelId,
+        requester: impl Into<String>,
+        admin_user_ids: Vec<String>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            ctx,
+            channel_id,
+            requester: requester.into(),
+            admin_user_ids,
+            timeout,
+            audit: None,
+        }
+    }
+
+    /// Record requests and decisions in an audit log
+    pub fn with_audit_logger(mut self, audit: Option<Arc<AuditLogger>>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    async fn ask(&self, tool: &str, input: &JsonValue, preview: Option<&str>) -> serenity::Result<ApprovalOutcome> {
+        let message = serenity::CreateMessage::new()
+            .content(approval_prompt(tool, input, preview, &self.requester))
+            .components(approval_buttons());
+        let mut message = self.channel_id.send_message(&self.ctx, message).await?;
+
+        let outcome = loop {
+            let Some(press) = message
+                .await_component_interaction(&self.ctx.shard)
+                .timeout(self.timeout)
+                .await
+            else {
+                break ApprovalOutcome::TimedOut;
+            };
+
+            let user_id = press.user.id.to_string();
+            let permissions = press.member.as_ref().and_then(|member| member.permissions);
+            if !is_approver(&self.admin_user_ids, &user_id, permissions) {
+                let reply = serenity::CreateInteractionResponseMessage::new()
+                    .content("管理者のみ承認できます。")
+                    .ephemeral(true);
+                if let Err(e) = press
+                    .create_response(&self.ctx, serenity::CreateInteractionResponse::Message(reply))
+                    .await
+                {
+                    warn!("Failed to answer approval press: {:?}", e);
+                }
+                continue;
+            }
+
+            let outcome = match press.data.custom_id.as_str() {
+                APPROVE_BUTTON => ApprovalOutcome::Approved { by: user_id },
+                DENY_BUTTON => ApprovalOutcome::Denied { by: user_id },
+                _ => continue,
+            };
+            let update = serenity::CreateInteractionResponseMessage::new()
+                .content(decided_prompt(tool, &outcome))
+                .components(Vec::new());
+            if let Err(e) = press
+                .create_response(&self.ctx, serenity::CreateInteractionResponse::UpdateMessage(update))
+                .await
+            {
+                warn!("Failed to update approval prompt: {:?}", e);
+            }
+            break outcome;
+        };
+
+        if outcome == ApprovalOutcome::TimedOut {
+            let edit = serenity::EditMessage::new()
+                .content(decided_prompt(tool, &outcome))
+                .components(Vec::new());
+            if let Err(e) = message.edit(&self.ctx, edit).await {
+                warn!("Failed to close approval prompt: {:?}", e);
+            }
+        }
+        Ok(outcome)
+    }
+
+    fn audit(&self, event_type: AuditEventType, level: AuditLevel, message: String, metadata: JsonValue) {
+        info!("{}", message);
+        let Some(ref audit) = self.audit else {
+            return;
+        };
+        let entry = AuditEntry::new(event_type, level, message)
+            .with_source(AuditSource {
+                ip_address: None,
+                user_agent: None,
+                gateway: Some("discord".to_string()),
+                channel_id: Some(self.channel_id.to_string()),
+            })
+            .with_metadata(metadata);
+        if let Err(e) = audit.log(&entry) {
+            warn!("Failed to write audit entry: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl ToolApprover for ButtonApprover {
+    async fn approve(&self, tool: &str, input: &JsonValue, preview: Option<&str>) -> ApprovalOutcome {
+        self.audit(
+            AuditEventType::ToolApprovalRequested,
+            AuditLevel::Warning,
+            format!("Approval requested for {} by {}", tool, self.requester),
+            serde_json::json!({ "tool": tool, "input": input, "requester": self.requester }),
+        );
+
+        let outcome = match self.ask(tool, input, preview).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                warn!("Failed to ask for approval of {}: {:?}", tool, e);
+                ApprovalOutcome::TimedOut
+            }
+        };
+
+        let (event_type, message, by) = match &outcome {
+            ApprovalOutcome::Approved { by } => {
+                (AuditEventType::ToolApproved, format!("{} approved by {}", tool, by), Some(by))
+            }
+            ApprovalOutcome::Denied { by } => {
+                (AuditEventType::ToolDenied, format!("{} denied by {}", tool, by), Some(by))
+            }
+            ApprovalOutcome::TimedOut => {
+                (AuditEventType::ToolDenied, format!("{} not approved in time", tool), None)
+            }
+        };
+        self.audit(
+            event_type,
+            AuditLevel::Warning,
+            message,
+            serde_json::json!({ "tool": tool, "requester": self.requester, "decided_by": by }),
+        );
+        outcome
+    }
+}
+
+/// Whether `user_id` may approve tool calls: a configured admin, or when no
+/// admins are set, a server member with Administrator or Manage Server
+fn is_approver(admin_user_ids: &[String], user_id: &str, permissions: Option<serenity::Permissions>) -> bool {
+    if !admin_user_ids.is_empty() {
+        return admin_user_ids.iter().any(|id| id == user_id);
+    }
+    permissions.is_some_and(|p| p.administrator() || p.manage_guild())
+}
+
+fn approval_buttons() -> Vec<serenity::CreateActionRow> {
+    vec![serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(APPROVE_BUTTON)
+            .label("承認")
+            .style(serenity::ButtonStyle::Success),
+        serenity::CreateButton::new(DENY_BUTTON)
+            .label("拒否")
+            .style(serenity::ButtonStyle::Danger),
+    ])]
+}
+
+/// The prompt asking to approve a call to `tool`, showing `preview` or else the input
+fn approval_prompt(tool: &str, input: &JsonValue, preview: Option<&str>, requester: &str) -> String {
+    let (mut input, language) = match preview {
+        Some(preview) => (preview.to_string(), ""),
+        None => (serde_json::to_string_pretty(input).unwrap_or_default(), "json"),
+    };
+    if input.len() > MAX_INPUT_PREVIEW {
+        let mut end = MAX_INPUT_PREVIEW;
+        while !input.is_char_boundary(end) {
+            end -= 1;
+        }
+        input.truncate(end);
+        input.push_str("\n…");
+    }
+    format!(
+        "⚠️ <@{}> のリクエストでツール `{}` を実行しようとしています。実行を承認しますか？\n```{}\n{}\n```",
+        requester,
+        tool,
+        language,
+        input.replace("```", "`\u{200b}``")
+    )
+}
+
+/// The prompt after a decision
+fn decided_prompt(tool: &str, outcome: &ApprovalOutcome) -> String {
+    match outcome {
+        ApprovalOutcome::Approved { by } => format!("✅ ツール `{}` の実行を <@{}> が承認しました。", tool, by),
+        ApprovalOutcome::Denied { by } => format!("🚫 ツール `{}` の実行を <@{}> が拒否しました。", tool, by),
+        ApprovalOutcome::TimedOut => format!("⌛ ツール `{}` の実行は承認されませんでした（タイムアウト）。", tool),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_approver() {
+        let admins = vec!["1".to_string()];
+        assert!(is_approver(&admins, "1", None));
+        assert!(!is_approver(&admins, "2", Some(serenity::Permissions::ADMINISTRATOR)));
+
+        // Without admins, ordinary members (and DMs) cannot approve
+        assert!(!is_approver(&[], "1", None));
+        assert!(!is_approver(&[], "1", Some(serenity::Permissions::SEND_MESSAGES)));
+        assert!(is_approver(&[], "1", Some(serenity::Permissions::MANAGE_GUILD)));
+        assert!(is_approver(&[], "1", Some(serenity::Permissions::ADMINISTRATOR)));
+    }
+
+    #[test]
+    fn test_approval_prompt() {
+        let prompt = approval_prompt("bash", &serde_json::json!({ "command": "ls ```" }), None, "42");
+        assert!(prompt.starts_with("⚠️ <@42> のリクエストでツール `bash`"));
+        assert!(prompt.contains("\"command\": \"ls `\u{200b}``\""));
+        assert!(prompt.ends_with("\n```"));
+
+        let long = approval_prompt("write", &serde_json::json!({ "content": "あ".repeat(1000) }), None, "42");
+        assert!(long.len() < MAX_INPUT_PREVIEW + 200);
+        assert!(long.contains("…\n```"));
+
+        let preview = approval_prompt("write", &serde_json::json!({}), Some("+ new line"), "42");
+        assert!(preview.ends_with("\n```\n+ new line\n```"));
+    }
+}
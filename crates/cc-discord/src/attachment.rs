@@ -0,0 +1,144 @@
+//! Image attachments
+//!
+//! Images attached to a mention, DM or `/ask` are downloaded and sent to the
+//! model along with the text, so users can ask about screenshots directly.
+//! Other files, and images the API would refuse, are skipped.
+
+use poise::serenity_prelude as serenity;
+use tracing::{debug, warn};
+
+use cc_core::{ImageSource, Message};
+
+/// Images sent per message (further ones are skipped)
+pub const MAX_IMAGES: usize = 5;
+
+/// Largest image the API accepts
+pub const MAX_IMAGE_BYTES: u32 = 5 * 1024 * 1024;
+
+/// Question used when only images were sent
+pub(crate) const DEFAULT_IMAGE_QUESTION: &str = "この画像について説明してください。";
+
+/// Images of a message, with the names of attachments that were skipped
+#[derive(Debug, Default)]
+pub struct Images {
+    pub images: Vec<ImageSource>,
+    pub skipped: Vec<String>,
+}
+
+impl Images {
+    /// Download the supported images among `attachments`
+    pub async fn download(attachments: &[serenity::Attachment]) -> Self {
+        let mut result = Self::default();
+        for attachment in attachments {
+            let Some(media_type) =
+                image_media_type(attachment.content_type.as_deref(), &attachment.filename)
+            else {
+                debug!("Skipping non-image attachment: {}", attachment.filename);
+                continue;
+            };
+            if result.images.len() >= MAX_IMAGES || attachment.size > MAX_IMAGE_BYTES {
+                result.skipped.push(attachment.filename.clone());
+                continue;
+            }
+            match attachment.download().await {
+                Ok(bytes) => result.images.push(ImageSource::from_bytes(media_type, &bytes)),
+                Err(e) => {
+                    warn!("Failed to download attachment {}: {:?}", attachment.filename, e);
+                    result.skipped.push(attachment.filename.clone());
+                }
+            }
+        }
+        result
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// The user message for `text` with the images
+    pub fn message(&self, text: &str) -> Message {
+        if self.images.is_empty() {
+            Message::user(text)
+        } else {
+            Message::user_with_images(text, self.images.clone())
+        }
+    }
+
+    /// `text` as kept in the history (images are not kept, only noted)
+    pub fn history_text(&self, text: &str) -> String {
+        if self.images.is_empty() {
+            text.to_string()
+        } else {
+            format!("{}\n[画像 {} 枚を添付]", text, self.images.len())
+        }
+    }
+
+    /// Note for the user about skipped attachments
+    pub fn skipped_note(&self) -> Option<String> {
+        if self.skipped.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "⚠️ 次の画像は送信できませんでした（{}枚まで、1枚 {}MB まで）: {}",
+            MAX_IMAGES,
+            MAX_IMAGE_BYTES / 1024 / 1024,
+            self.skipped.join(", ")
+        ))
+    }
+}
+
+/// The media type of an image the API accepts, from the content type or
+/// else the file extension
+fn image_media_type(content_type: Option<&str>, filename: &str) -> Option<&'static str> {
+    let supported = [
+        ImageSource::MEDIA_TYPE_PNG,
+        ImageSource::MEDIA_TYPE_JPEG,
+        ImageSource::MEDIA_TYPE_GIF,
+        ImageSource::MEDIA_TYPE_WEBP,
+    ];
+    if let Some(content_type) = content_type {
+        // Discord may add parameters ("image/png; charset=...")
+        let media_type = content_type.split(';').next().unwrap_or("").trim();
+        return supported.into_iter().find(|t| t.eq_ignore_ascii_case(media_type));
+    }
+    let extension = filename.rsplit_once('.')?.1.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some(ImageSource::MEDIA_TYPE_PNG),
+        "jpg" | "jpeg" => Some(ImageSource::MEDIA_TYPE_JPEG),
+        "gif" => Some(ImageSource::MEDIA_TYPE_GIF),
+        "webp" => Some(ImageSource::MEDIA_TYPE_WEBP),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_media_type() {
+        assert_eq!(image_media_type(Some("image/png"), "a.png"), Some("image/png"));
+        assert_eq!(image_media_type(Some("image/jpeg; x=y"), "a"), Some("image/jpeg"));
+        assert_eq!(image_media_type(Some("image/svg+xml"), "a.svg"), None);
+        assert_eq!(image_media_type(Some("application/pdf"), "a.png"), None);
+        assert_eq!(image_media_type(None, "Screenshot.JPG"), Some("image/jpeg"));
+        assert_eq!(image_media_type(None, "notes.txt"), None);
+        assert_eq!(image_media_type(None, "noextension"), None);
+    }
+
+    #[test]
+    fn test_images_message() {
+        let images = Images {
+            images: vec![ImageSource::png(b"png")],
+            skipped: vec!["big.png".to_string()],
+        };
+        assert_eq!(images.message("What is this?").content.len(), 2);
+        assert_eq!(images.history_text("What is this?"), "What is this?\n[画像 1 枚を添付]");
+        assert!(images.skipped_note().unwrap().ends_with("big.png"));
+
+        let none = Images::default();
+        assert_eq!(none.message("hi").content.len(), 1);
+        assert_eq!(none.history_text("hi"), "hi");
+        assert!(none.skipped_note().is_none());
+    }
+}
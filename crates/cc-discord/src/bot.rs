@@ -3,12 +3,14 @@
 use std::sync::Arc;
 use tracing::info;
 
-use cc_core::{ClaudeClient, Config};
+use cc_core::audit::AuditLogger;
+use cc_core::{ClaudeClient, Config, ContextProvider, Memorizer, PersonaRegistry, ToolManager};
 use poise::serenity_prelude as serenity;
 use serenity::FullEvent as Event;
 
 use crate::commands::{get_commands, Data};
 use crate::error::{DiscordError, Result};
+use crate::guild::GuildConfigStore;
 use crate::handler::handle_message;
 use crate::session::InMemorySessionStore;
 
@@ -17,8 +19,18 @@ pub struct DiscordBot {
     config: Config,
     claude_client: Arc<ClaudeClient>,
     session_store: Arc<InMemorySessionStore>,
+    personas: Arc<PersonaRegistry>,
+    thread_after: Option<usize>,
+    tools: Option<Arc<ToolManager>>,
+    audit: Option<Arc<AuditLogger>>,
+    guilds: Option<Arc<GuildConfigStore>>,
+    memorizer: Option<Arc<Memorizer>>,
+    context: Option<Arc<dyn ContextProvider>>,
 }
 
+/// Default number of exchanges in a channel before moving to a thread
+pub const DEFAULT_THREAD_AFTER: usize = 3;
+
 impl DiscordBot {
     /// Create a new Discord bot
     pub fn new(
@@ -26,10 +38,18 @@ impl DiscordBot {
         claude_client: ClaudeClient,
         session_store: Arc<InMemorySessionStore>,
     ) -> Result<Self> {
+        let personas = Arc::new(PersonaRegistry::from_config(&config.personas));
         Ok(Self {
             config,
             claude_client: Arc::new(claude_client),
             session_store,
+            personas,
+            thread_after: Some(DEFAULT_THREAD_AFTER),
+            tools: None,
+            audit: None,
+            guilds: None,
+            memorizer: None,
+            context: None,
         })
     }
 
@@ -45,13 +65,64 @@ impl DiscordBot {
             }
         });
 
+        let personas = Arc::new(PersonaRegistry::from_config(&config.personas));
         Self {
             config,
             claude_client,
             session_store,
+            personas,
+            thread_after: Some(DEFAULT_THREAD_AFTER),
+            tools: None,
+            audit: None,
+            guilds: None,
+            memorizer: None,
+            context: None,
         }
     }
 
+    /// Use a shared persona registry
+    pub fn with_personas(mut self, personas: Arc<PersonaRegistry>) -> Self {
+        self.personas = personas;
+        self
+    }
+
+    /// Move channel conversations into a thread after this many exchanges
+    /// (None = never start threads)
+    pub fn with_thread_after(mut self, thread_after: Option<usize>) -> Self {
+        self.thread_after = thread_after;
+        self
+    }
+
+    /// Let the agent call these tools, showing progress while it works
+    pub fn with_tools(mut self, tools: Arc<ToolManager>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Record tool approvals in an audit log
+    pub fn with_audit_logger(mut self, audit: Arc<AuditLogger>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Keep per-guild settings (changed with /config) in this store
+    pub fn with_guild_store(mut self, guilds: Arc<GuildConfigStore>) -> Self {
+        self.guilds = Some(guilds);
+        self
+    }
+
+    /// Remember facts about users and add them to later conversations
+    pub fn with_memorizer(mut self, memorizer: Arc<Memorizer>) -> Self {
+        self.memorizer = Some(memorizer);
+        self
+    }
+
+    /// Add documents relevant to each message (e.g. from the knowledge base)
+    pub fn with_context_provider(mut self, context: Arc<dyn ContextProvider>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
     /// Get the session store
     pub fn session_store(&self) -> Arc<InMemorySessionStore> {
         self.session_store.clone()
@@ -80,6 +151,14 @@ impl DiscordBot {
             claude_client: self.claude_client.clone(),
             session_store: self.session_store.clone(),
             admin_user_ids: self.config.admin_user_ids.clone(),
+            personas: self.personas.clone(),
+            thread_after: self.thread_after,
+            tools: self.tools.clone(),
+            tool_policy: self.config.tool_policy.clone(),
+            audit: self.audit.clone(),
+            guilds: self.guilds.clone(),
+            memorizer: self.memorizer.clone(),
+            context: self.context.clone(),
         };
 
         // Build poise framework
@@ -1,17 +1,41 @@
 //! /ask command - Ask Claude a question (poise implementation)
 
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
 use tracing::info;
 
-use cc_core::Message;
+use cc_core::{Message, ToolOrigin};
 
+use crate::agent::run_agent;
+use crate::attachment::Images;
 use crate::commands::Data;
 use crate::error::Result;
+use crate::handler::{build_request, start_thread};
+use crate::progress::{Progress, ProgressTarget, THINKING};
+use crate::response::{paginate, Response};
+
+/// The command's reply, edited to show progress
+struct ReplyTarget<'a> {
+    ctx: poise::Context<'a, Data, crate::error::DiscordError>,
+    handle: poise::ReplyHandle<'a>,
+}
+
+#[async_trait]
+impl ProgressTarget for ReplyTarget<'_> {
+    async fn show(&self, content: &str) -> Result<()> {
+        self.handle
+            .edit(self.ctx, poise::CreateReply::default().content(content))
+            .await?;
+        Ok(())
+    }
+}
 
 /// Ask Claude a question
 #[poise::command(slash_command, rename = "ask")]
 pub async fn ask(
     ctx: poise::Context<'_, Data, crate::error::DiscordError>,
     #[description = "The question to ask Claude"] question: String,
+    #[description = "An image to ask about (PNG, JPEG, GIF or WebP)"] image: Option<serenity::Attachment>,
     #[description = "Only visible to you (default: true)"]
     #[flag]
     ephemeral: bool,
@@ -36,63 +60,89 @@ pub async fn ask(
     // Get shared data
     let data = ctx.data();
 
+    // Servers can limit the bot to some channels (its threads stay open)
+    let guild = data.guild_settings(ctx.guild_id());
+    if !data.session_store.is_bot_thread(&session_key) && !guild.allows_channel(&session_key) {
+        ctx.say("このチャンネルではボットを利用できません。").await?;
+        return Ok(());
+    }
+
+    // An attached image goes to the model with the question
+    let images = Images::download(image.as_slice()).await;
+    if let Some(note) = images.skipped_note() {
+        ctx.say(note).await?;
+    }
+
     // Get existing session or create new one
     let session = data.session_store.get_or_create(&session_key);
 
     // Build message history
     let mut messages: Vec<Message> = session.messages.clone();
-    messages.push(Message::user(&question));
-
-    // Build request with conversation history
-    let mut request_builder = data
-        .claude_client
-        .request_builder()
-        .system("You are a helpful assistant. Respond in the same language as the user's question. Be concise and helpful.")
-        .max_tokens(2048);
-
-    // Add conversation history (limit to last 20 messages)
-    let history_start = messages.len().saturating_sub(20);
-    for message in messages.into_iter().skip(history_start) {
-        request_builder = request_builder.message(message);
-    }
-
-    let request = request_builder.build();
-
-    let response_text = match data.claude_client.messages(request).await {
-        Ok(response) => {
-            // Extract text from response
-            let text = response
-                .content
-                .iter()
-                .filter_map(|c| {
-                    if let cc_core::MessageContent::Text { text } = c {
-                        Some(text.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            // Update session with user message and assistant response
-            data.session_store
-                .add_message(&session_key, Message::user(&question));
-            data.session_store
-                .add_message(&session_key, Message::assistant(&text));
-
-            // Truncate if too long for Discord
-            if text.len() > 1900 {
-                format!("{}\n\n...(truncated)", &text[..1900])
-            } else {
-                text
-            }
-        }
+    messages.push(images.message(&question));
+
+    let user_id = ctx.author().id.to_string();
+    let mut request = build_request(
+        data,
+        "You are a helpful assistant. Respond in the same language as the user's question. Be concise and helpful.",
+        2048,
+        messages,
+        &guild,
+        data.persona(session.persona.as_deref(), &guild, &user_id, &session_key),
+    );
+    data.recall(&mut request, &user_id, &question).await;
+
+    // Show progress in the deferred reply while the agent works
+    let handle = ctx.say(THINKING).await?;
+    let mut progress = Progress::new(ReplyTarget { ctx, handle });
+    let policy = guild.tool_policy(&data.tool_policy);
+    let approver = data.approver(ctx.serenity_context(), ctx.channel_id(), &user_id, policy);
+    let origin = ToolOrigin::new("discord", session_key.clone()).with_user(user_id.clone());
+    let result = origin
+        .scope(run_agent(data, request, policy, &mut progress, &approver))
+        .await;
+    let ReplyTarget { handle, .. } = progress.into_target();
+
+    let text = match result {
+        Ok(text) => text,
         Err(e) => {
-            format!("エラーが発生しました: {}", e)
+            handle
+                .edit(ctx, poise::CreateReply::default().content(format!("エラーが発生しました: {}", e)))
+                .await?;
+            return Ok(());
         }
     };
 
-    ctx.say(response_text).await?;
+    // Update session with user message and assistant response
+    data.session_store
+        .add_message(&session_key, Message::user(images.history_text(&question)));
+    data.session_store
+        .add_message(&session_key, Message::assistant(&text));
+    data.remember(&user_id, &question, &text);
+
+    // Long answers are sent as pages or a file
+    let response = Response::new(&text);
+    handle.edit(ctx, response.reply()).await?;
+    let pages = match response {
+        Response::Pages(pages) => Some(pages),
+        _ => None,
+    };
+
+    // A visible answer in a server channel opens a thread for follow-ups,
+    // which then need no command
+    let in_thread = data.session_store.is_bot_thread(&session_key);
+    let open_thread =
+        !ephemeral && ctx.guild_id().is_some() && !in_thread && data.thread_after.is_some();
+    if pages.is_none() && !open_thread {
+        return Ok(());
+    }
+
+    let message = handle.message().await?.into_owned();
+    if open_thread {
+        start_thread(ctx.serenity_context(), &message, &question, data).await;
+    }
+    if let Some(pages) = pages {
+        paginate(ctx.serenity_context().clone(), message, pages);
+    }
 
     Ok(())
 }
@@ -0,0 +1,238 @@
+//! /config command - Per-guild settings (poise implementation)
+//!
+//! Server admins can override the model, default persona, the channels the
+//! bot answers in and the tool policy. Settings are stored per guild in the
+//! [`GuildConfigStore`](crate::guild::GuildConfigStore).
+
+use poise::serenity_prelude as serenity;
+use tracing::info;
+
+use cc_core::ToolPolicy;
+
+use crate::commands::Data;
+use crate::error::Result;
+use crate::guild::GuildSettings;
+
+type Context<'a> = poise::Context<'a, Data, crate::error::DiscordError>;
+
+/// Change this server's bot settings
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "config",
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands("show", "model", "persona", "channels", "tools"),
+    subcommand_required
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Only configured admins may change settings (everyone when none are set)
+async fn is_config_admin(ctx: Context<'_>) -> Result<bool> {
+    let admins = &ctx.data().admin_user_ids;
+    let user_id = ctx.author().id.to_string();
+    if admins.is_empty() || admins.contains(&user_id) {
+        return Ok(true);
+    }
+    ctx.send(
+        poise::CreateReply::default()
+            .content("このコマンドは管理者のみ使用できます。")
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(false)
+}
+
+/// Apply `change` to the settings of the current guild and show the result
+async fn update(ctx: Context<'_>, change: impl FnOnce(&mut GuildSettings)) -> Result<()> {
+    let data = ctx.data();
+    let (Some(guild_id), Some(guilds)) = (ctx.guild_id(), data.guilds.as_ref()) else {
+        ctx.say("サーバー設定の保存先が設定されていません。").await?;
+        return Ok(());
+    };
+    let settings = guilds.update(&guild_id.to_string(), change)?;
+    info!("Updated settings of guild {}: {:?}", guild_id, settings);
+    ctx.say(format!("設定を更新しました。\n{}", describe(&settings, data)))
+        .await?;
+    Ok(())
+}
+
+/// Show this server's settings
+#[poise::command(slash_command, guild_only, check = "is_config_admin")]
+async fn show(ctx: Context<'_>) -> Result<()> {
+    let settings = ctx.data().guild_settings(ctx.guild_id());
+    ctx.say(describe(&settings, ctx.data())).await?;
+    Ok(())
+}
+
+/// Set the model used in this server
+#[poise::command(slash_command, guild_only, check = "is_config_admin")]
+async fn model(
+    ctx: Context<'_>,
+    #[description = "Model name (omit to use the global setting)"] name: Option<String>,
+) -> Result<()> {
+    let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    update(ctx, |s| s.model = name).await
+}
+
+/// Set the default persona of this server
+#[poise::command(slash_command, guild_only, check = "is_config_admin")]
+async fn persona(
+    ctx: Context<'_>,
+    #[description = "Persona name (omit or \"default\" to use the global default)"]
+    name: Option<String>,
+) -> Result<()> {
+    let name = match name.as_deref().map(str::trim) {
+        None | Some("") | Some("default") => None,
+        Some(name) => match ctx.data().personas.get(name) {
+            Some(persona) => Some(persona.name.clone()),
+            None => {
+                ctx.say(format!(
+                    "ペルソナ `{}` は見つかりません。`/persona` で一覧を表示できます。",
+                    name
+                ))
+                .await?;
+                return Ok(());
+            }
+        },
+    };
+    update(ctx, |s| s.persona = name).await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+enum ChannelAction {
+    #[name = "add"]
+    Add,
+    #[name = "remove"]
+    Remove,
+    #[name = "clear"]
+    Clear,
+}
+
+/// Choose the channels the bot answers in (none = all)
+#[poise::command(slash_command, guild_only, check = "is_config_admin")]
+async fn channels(
+    ctx: Context<'_>,
+    #[description = "add / remove a channel, or clear to allow all"] action: ChannelAction,
+    #[description = "Channel (default: this channel)"] channel: Option<serenity::GuildChannel>,
+) -> Result<()> {
+    let channel_id = channel.map_or(ctx.channel_id(), |c| c.id).to_string();
+    update(ctx, |s| match action {
+        ChannelAction::Add => {
+            if !s.allowed_channels.contains(&channel_id) {
+                s.allowed_channels.push(channel_id);
+            }
+        }
+        ChannelAction::Remove => s.allowed_channels.retain(|c| *c != channel_id),
+        ChannelAction::Clear => s.allowed_channels.clear(),
+    })
+    .await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+enum ToolAction {
+    #[name = "approve"]
+    Approve,
+    #[name = "deny"]
+    Deny,
+    #[name = "allow"]
+    Allow,
+    #[name = "reset"]
+    Reset,
+}
+
+/// Change the tool policy of this server
+#[poise::command(slash_command, guild_only, check = "is_config_admin")]
+async fn tools(
+    ctx: Context<'_>,
+    #[description = "approve (needs approval) / deny / allow a tool, or reset to the global policy"]
+    action: ToolAction,
+    #[description = "Tool name (a trailing * matches by prefix)"] tool: Option<String>,
+) -> Result<()> {
+    let tool = tool.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if action != ToolAction::Reset && tool.is_none() {
+        ctx.say("ツール名を指定してください。").await?;
+        return Ok(());
+    }
+    let global = ctx.data().tool_policy.clone();
+    update(ctx, |s| match tool {
+        Some(tool) if action != ToolAction::Reset => {
+            let policy = s.tool_policy.get_or_insert(global);
+            apply_tool_action(policy, action, tool);
+        }
+        _ => s.tool_policy = None,
+    })
+    .await
+}
+
+/// Make `policy` treat `tool` as `action` says
+fn apply_tool_action(policy: &mut ToolPolicy, action: ToolAction, tool: String) {
+    policy.require_approval.retain(|t| *t != tool);
+    policy.deny.retain(|t| *t != tool);
+    match action {
+        ToolAction::Approve => policy.require_approval.push(tool),
+        ToolAction::Deny => policy.deny.push(tool),
+        ToolAction::Allow | ToolAction::Reset => {}
+    }
+}
+
+/// The settings as shown to admins
+fn describe(settings: &GuildSettings, data: &Data) -> String {
+    let list = |items: &[String]| {
+        if items.is_empty() {
+            "なし".to_string()
+        } else {
+            items.iter().map(|i| format!("`{}`", i)).collect::<Vec<_>>().join(", ")
+        }
+    };
+
+    let mut text = String::from("**サーバー設定:**\n");
+    text.push_str(&format!(
+        "- モデル: {}\n",
+        settings
+            .model
+            .as_ref()
+            .map_or_else(|| format!("`{}`（全体設定）", data.claude_client.model()), |m| format!("`{}`", m))
+    ));
+    text.push_str(&format!(
+        "- ペルソナ: {}\n",
+        settings.persona.as_ref().map_or("全体設定".to_string(), |p| format!("`{}`", p))
+    ));
+    if settings.allowed_channels.is_empty() {
+        text.push_str("- チャンネル: すべて\n");
+    } else {
+        let channels: Vec<_> = settings.allowed_channels.iter().map(|c| format!("<#{}>", c)).collect();
+        text.push_str(&format!("- チャンネル: {}\n", channels.join(", ")));
+    }
+    let policy = settings.tool_policy(&data.tool_policy);
+    let source = if settings.tool_policy.is_some() { "" } else { "（全体設定）" };
+    text.push_str(&format!(
+        "- ツール{}: 承認が必要 {} / 禁止 {}\n",
+        source,
+        list(&policy.require_approval),
+        list(&policy.deny)
+    ));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cc_core::PolicyDecision;
+
+    #[test]
+    fn test_apply_tool_action() {
+        let mut policy = ToolPolicy::default();
+        apply_tool_action(&mut policy, ToolAction::Deny, "bash".to_string());
+        assert_eq!(policy.decide("bash"), PolicyDecision::Deny);
+        assert!(!policy.require_approval.contains(&"bash".to_string()));
+
+        apply_tool_action(&mut policy, ToolAction::Approve, "web_fetch".to_string());
+        assert_eq!(policy.decide("web_fetch"), PolicyDecision::RequireApproval);
+
+        apply_tool_action(&mut policy, ToolAction::Allow, "bash".to_string());
+        assert_eq!(policy.decide("bash"), PolicyDecision::Allow);
+        assert!(policy.deny.is_empty());
+    }
+}
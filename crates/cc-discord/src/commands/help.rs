@@ -21,15 +21,22 @@ pub async fn help(
 
 3. **返信**: ボットのメッセージに返信すると会話が続きます
 
+4. **スレッド**: 会話が長くなるとスレッドが作成されます。スレッド内ではメンションなしで会話を続けられます
+
+5. **画像**: メッセージに画像（PNG / JPEG / GIF / WebP）を添付すると、その画像について質問できます
+
 **Slash Commands:**
 
-- `/ask <question>` - Claudeに質問する
-- `/clear` - 現在のチャンネルの会話履歴をクリアする
+- `/ask <question> [image]` - Claudeに質問する（画像を添付できます。公開の回答ではスレッドで続きを話せます）
+- `/clear` - 現在のチャンネル（スレッド）の会話履歴をクリアする
+- `/persona [name]` - ペルソナを切り替える（省略で一覧、`default` でリセット）
+- `/config` - サーバーごとのモデル・ペルソナ・利用チャンネル・ツール設定（サーバー管理権限が必要）
 - `/help` - このヘルプを表示
 
 **注意事項:**
 - 管理者のみ使用可能です（設定で制御）
-- メッセージは2000文字で制限されます
+- ツールを使う処理中は、実行中のツールと途中経過がメッセージに表示されます
+- 2000文字を超える回答はページ送り（◀ / ▶）で、非常に長い回答は .md ファイルで送られます
 "#;
 
     ctx.say(response).await?;
@@ -2,12 +2,20 @@
 
 mod ask;
 mod clear;
+mod config;
 mod help;
+mod persona;
 
 use std::sync::Arc;
+use std::time::Duration;
 
-use cc_core::ClaudeClient;
+use cc_core::audit::AuditLogger;
+use cc_core::{ClaudeClient, ContextProvider, Memorizer, MessagesRequest, Persona, PersonaRegistry, ToolManager, ToolPolicy};
+use poise::serenity_prelude as serenity;
+use tracing::warn;
 
+use crate::approval::ButtonApprover;
+use crate::guild::{GuildConfigStore, GuildSettings};
 use crate::session::InMemorySessionStore;
 
 /// User data stored and accessible in all command invocations
@@ -15,6 +23,89 @@ pub struct Data {
     pub claude_client: Arc<ClaudeClient>,
     pub session_store: Arc<InMemorySessionStore>,
     pub admin_user_ids: Vec<String>,
+    pub personas: Arc<PersonaRegistry>,
+    /// Tools the agent may call (None = answer without tools)
+    pub tools: Option<Arc<ToolManager>>,
+    /// Which tools need an admin's approval or are refused
+    pub tool_policy: ToolPolicy,
+    /// Audit log for tool approvals
+    pub audit: Option<Arc<AuditLogger>>,
+    /// Per-guild settings (None = global configuration only)
+    pub guilds: Option<Arc<GuildConfigStore>>,
+    /// Remembers facts about users across conversations (None = disabled)
+    pub memorizer: Option<Arc<Memorizer>>,
+    /// Finds documents relevant to a message (None = disabled)
+    pub context: Option<Arc<dyn ContextProvider>>,
+    /// Exchanges in a channel after which the conversation moves to a thread
+    /// (None = never start threads)
+    pub thread_after: Option<usize>,
+}
+
+impl Data {
+    /// Settings of `guild_id` (defaults outside guilds or without a store)
+    pub fn guild_settings(&self, guild_id: Option<serenity::GuildId>) -> GuildSettings {
+        let (Some(guild_id), Some(guilds)) = (guild_id, self.guilds.as_ref()) else {
+            return GuildSettings::default();
+        };
+        guilds.get(&guild_id.to_string()).unwrap_or_else(|e| {
+            warn!("Failed to load settings of guild {}: {}", guild_id, e);
+            GuildSettings::default()
+        })
+    }
+
+    /// Add what the memorizer remembers about `user_id` and the documents
+    /// relevant to `message` to a request
+    pub async fn recall(&self, request: &mut MessagesRequest, user_id: &str, message: &str) {
+        if let Some(memorizer) = &self.memorizer {
+            let identity = memorizer.identity("discord", user_id);
+            memorizer.inject(request, &identity, message).await;
+        }
+        if let Some(context) = &self.context {
+            cc_core::inject_context(context.as_ref(), request, message).await;
+        }
+    }
+
+    /// Let the memorizer learn from a finished turn of `user_id` in the background
+    pub fn remember(&self, user_id: &str, message: &str, answer: &str) {
+        if let Some(memorizer) = &self.memorizer {
+            let identity = memorizer.identity("discord", user_id);
+            memorizer.learn(identity, message.to_string(), answer.to_string());
+        }
+    }
+
+    /// The persona for a turn (session > user > channel > guild > default)
+    pub fn persona(
+        &self,
+        session_persona: Option<&str>,
+        guild: &GuildSettings,
+        user_id: &str,
+        channel_id: &str,
+    ) -> Option<&Persona> {
+        self.personas.resolve_with_default(
+            session_persona,
+            Some(user_id),
+            Some(channel_id),
+            guild.persona.as_deref(),
+        )
+    }
+
+    /// Approver asking the admins in `channel_id` about calls `requester` caused
+    pub fn approver(
+        &self,
+        ctx: &serenity::Context,
+        channel_id: serenity::ChannelId,
+        requester: &str,
+        policy: &ToolPolicy,
+    ) -> ButtonApprover {
+        ButtonApprover::new(
+            ctx.clone(),
+            channel_id,
+            requester,
+            self.admin_user_ids.clone(),
+            Duration::from_secs(policy.approval_timeout_secs),
+        )
+        .with_audit_logger(self.audit.clone())
+    }
 }
 
 /// Error type for commands
@@ -23,9 +114,11 @@ pub type Error = crate::error::DiscordError;
 /// Export commands for registration
 pub use ask::ask;
 pub use clear::clear;
+pub use config::config;
 pub use help::help;
+pub use persona::persona;
 
 /// Get all commands for registration
 pub fn get_commands() -> Vec<poise::Command<Data, Error>> {
-    vec![ask(), clear(), help()]
+    vec![ask(), clear(), config(), help(), persona()]
 }
@@ -0,0 +1,69 @@
+//! /persona command - Switch or list personas (poise implementation)
+
+use tracing::info;
+
+use crate::commands::Data;
+use crate::error::Result;
+
+/// Switch the persona for this channel, or list available personas
+#[poise::command(slash_command, rename = "persona")]
+pub async fn persona(
+    ctx: poise::Context<'_, Data, crate::error::DiscordError>,
+    #[description = "Persona name (omit to list, \"default\" to reset)"] name: Option<String>,
+) -> Result<()> {
+    let data = ctx.data();
+    let channel_id = ctx.channel_id().to_string();
+    let user_id = ctx.author().id.to_string();
+
+    if data.personas.is_empty() {
+        ctx.say("ペルソナが設定されていません。").await?;
+        return Ok(());
+    }
+
+    let response = match name.as_deref().map(str::trim) {
+        None | Some("") => {
+            let session = data.session_store.get_or_create(&channel_id);
+            let guild = data.guild_settings(ctx.guild_id());
+            let current = data
+                .persona(session.persona.as_deref(), &guild, &user_id, &channel_id)
+                .map(|p| p.name.clone());
+
+            let mut text = String::from("**ペルソナ一覧:**\n");
+            for persona in data.personas.list() {
+                let marker = if current.as_deref() == Some(persona.name.as_str()) {
+                    " ← 現在"
+                } else {
+                    ""
+                };
+                match &persona.description {
+                    Some(description) => {
+                        text.push_str(&format!("- `{}` - {}{}\n", persona.name, description, marker))
+                    }
+                    None => text.push_str(&format!("- `{}`{}\n", persona.name, marker)),
+                }
+            }
+            text
+        }
+        Some("default") | Some("reset") => {
+            data.session_store.set_persona(&channel_id, None);
+            info!("Reset persona for channel: {}", channel_id);
+            "ペルソナをデフォルトに戻しました。".to_string()
+        }
+        Some(name) => match data.personas.get(name) {
+            Some(persona) => {
+                data.session_store
+                    .set_persona(&channel_id, Some(persona.name.clone()));
+                info!("Switched persona for channel {} to {}", channel_id, persona.name);
+                format!("ペルソナを `{}` に切り替えました。", persona.name)
+            }
+            None => format!(
+                "ペルソナ `{}` は見つかりません。`/persona` で一覧を表示できます。",
+                name
+            ),
+        },
+    };
+
+    ctx.say(response).await?;
+
+    Ok(())
+}
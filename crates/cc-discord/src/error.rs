@@ -18,6 +18,15 @@ pub enum DiscordError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("Serenity error: {0}")]
     SerenityError(Box<serenity::Error>),
 }
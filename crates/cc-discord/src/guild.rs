@@ -0,0 +1,189 @@
+//! Per-guild settings
+//!
+//! Each Discord server can override the model, default persona, the channels
+//! the bot answers in and the tool policy with `/config`. Settings are kept
+//! in SQLite; servers without settings use the global configuration.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use cc_core::migrate::{migrate, Migration};
+use cc_core::ToolPolicy;
+
+use crate::error::Result;
+
+/// Schema versions of the `guild_settings` table
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create guild_settings",
+    "CREATE TABLE IF NOT EXISTS guild_settings (
+        guild_id TEXT PRIMARY KEY,
+        settings TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    )",
+)];
+
+/// Settings of one guild (unset fields use the global configuration)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GuildSettings {
+    /// Model for requests in this guild
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Persona used unless one is picked with /persona
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+    /// Channels the bot answers in (empty = all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_channels: Vec<String>,
+    /// Tool policy for this guild
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_policy: Option<ToolPolicy>,
+}
+
+impl GuildSettings {
+    /// Whether the bot answers in `channel_id`
+    pub fn allows_channel(&self, channel_id: &str) -> bool {
+        self.allowed_channels.is_empty() || self.allowed_channels.iter().any(|c| c == channel_id)
+    }
+
+    /// The tool policy, falling back to `global`
+    pub fn tool_policy<'a>(&'a self, global: &'a ToolPolicy) -> &'a ToolPolicy {
+        self.tool_policy.as_ref().unwrap_or(global)
+    }
+}
+
+/// SQLite store of guild settings
+pub struct GuildConfigStore {
+    conn: Mutex<Connection>,
+}
+
+impl GuildConfigStore {
+    /// Default database path
+    pub const DEFAULT_PATH: &'static str = "data/discord_guilds.db";
+
+    /// Open the database file (creating its parent directory)
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Create an in-memory store (for testing)
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        migrate(&conn, "guild_settings", MIGRATIONS)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Settings of `guild_id` (defaults if none are stored)
+    pub fn get(&self, guild_id: &str) -> Result<GuildSettings> {
+        let settings: Option<String> = self
+            .lock()
+            .query_row(
+                "SELECT settings FROM guild_settings WHERE guild_id = ?1",
+                params![guild_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match settings {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(GuildSettings::default()),
+        }
+    }
+
+    /// Store the settings of `guild_id`
+    pub fn save(&self, guild_id: &str, settings: &GuildSettings) -> Result<()> {
+        let json = serde_json::to_string(settings)?;
+        self.lock().execute(
+            "INSERT INTO guild_settings (guild_id, settings, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(guild_id) DO UPDATE SET settings = excluded.settings, updated_at = excluded.updated_at",
+            params![guild_id, json, now()],
+        )?;
+        Ok(())
+    }
+
+    /// Change the settings of `guild_id` and store them
+    pub fn update(&self, guild_id: &str, change: impl FnOnce(&mut GuildSettings)) -> Result<GuildSettings> {
+        let mut settings = self.get(guild_id)?;
+        change(&mut settings);
+        self.save(guild_id, &settings)?;
+        Ok(settings)
+    }
+
+    /// Forget the settings of `guild_id`
+    pub fn remove(&self, guild_id: &str) -> Result<bool> {
+        let removed = self
+            .lock()
+            .execute("DELETE FROM guild_settings WHERE guild_id = ?1", params![guild_id])?;
+        Ok(removed > 0)
+    }
+
+    /// When the settings of `guild_id` were last changed
+    pub fn updated_at(&self, guild_id: &str) -> Result<Option<DateTime<Utc>>> {
+        let updated_at: Option<String> = self
+            .lock()
+            .query_row(
+                "SELECT updated_at FROM guild_settings WHERE guild_id = ?1",
+                params![guild_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(updated_at
+            .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+            .map(|t| t.with_timezone(&Utc)))
+    }
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cc_core::PolicyDecision;
+
+    #[test]
+    fn test_store_round_trip() {
+        let store = GuildConfigStore::in_memory().unwrap();
+        assert_eq!(store.get("g1").unwrap(), GuildSettings::default());
+        assert!(store.updated_at("g1").unwrap().is_none());
+
+        let settings = store
+            .update("g1", |s| {
+                s.model = Some("claude-haiku".to_string());
+                s.allowed_channels.push("c1".to_string());
+                s.tool_policy = Some(ToolPolicy {
+                    deny: vec!["bash".to_string()],
+                    ..Default::default()
+                });
+            })
+            .unwrap();
+        assert_eq!(store.get("g1").unwrap(), settings);
+        assert!(store.updated_at("g1").unwrap().is_some());
+        assert_eq!(store.get("g2").unwrap(), GuildSettings::default());
+
+        assert!(settings.allows_channel("c1"));
+        assert!(!settings.allows_channel("c2"));
+        let global = ToolPolicy::allow_all();
+        assert_eq!(settings.tool_policy(&global).decide("bash"), PolicyDecision::Deny);
+        assert_eq!(GuildSettings::default().tool_policy(&global).decide("bash"), PolicyDecision::Allow);
+
+        assert!(store.remove("g1").unwrap());
+        assert_eq!(store.get("g1").unwrap(), GuildSettings::default());
+    }
+}
@@ -1,53 +1,95 @@
 //! Discord event handler implementation using poise Framework
+//!
+//! The bot answers mentions, replies to its messages and DMs, and every
+//! message in a thread it started. A conversation in a server channel moves
+//! into a new thread once it gets long, so follow-ups don't need a mention.
 
+use poise::serenity_prelude as serenity;
 use tracing::{debug, error, info, warn};
 
-use cc_core::Message;
+use cc_core::{Message, MessagesRequest, Persona, ToolOrigin};
+use serenity::Mentionable;
 
+use crate::agent::run_agent;
+use crate::attachment::{Images, DEFAULT_IMAGE_QUESTION};
 use crate::commands::Data;
 use crate::error::Result;
-
-/// Split a message into chunks at sentence boundaries
-fn split_message(text: &str, max_size: usize) -> Vec<String> {
-    if text.len() <= max_size {
-        return vec![text.to_string()];
+use crate::guild::GuildSettings;
+use crate::progress::{MessageTarget, Progress, ProgressTarget};
+use crate::response::{Response, MAX_MESSAGE_LEN};
+
+/// Discord's thread name length limit
+const MAX_THREAD_NAME_CHARS: usize = 100;
+
+/// Name for a thread started from `text`: its first line, shortened to fit
+pub(crate) fn thread_name(text: &str) -> String {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("");
+    if line.is_empty() {
+        return "Claude".to_string();
+    }
+    if line.chars().count() <= MAX_THREAD_NAME_CHARS {
+        return line.to_string();
     }
+    let mut name: String = line.chars().take(MAX_THREAD_NAME_CHARS - 1).collect();
+    name.push('…');
+    name
+}
 
-    let mut chunks = Vec::new();
-    let mut remaining = text;
+/// Whether a channel conversation should move into a thread
+///
+/// `exchanges` counts the question/answer pairs in the channel including the
+/// current one. Long answers always get a thread; otherwise one is started
+/// after `thread_after` exchanges (never when `None`).
+pub(crate) fn should_start_thread(exchanges: usize, reply_len: usize, thread_after: Option<usize>) -> bool {
+    match thread_after {
+        Some(after) => reply_len > MAX_MESSAGE_LEN || exchanges >= after,
+        None => false,
+    }
+}
 
-    while !remaining.is_empty() {
-        if remaining.len() <= max_size {
-            chunks.push(remaining.to_string());
-            break;
+/// Start a thread on `message` and move the channel's conversation into it
+///
+/// Returns `None` when the message is already in a thread or the thread
+/// could not be created (e.g. missing permissions).
+pub(crate) async fn start_thread(
+    ctx: &serenity::Context,
+    message: &serenity::Message,
+    name: &str,
+    data: &Data,
+) -> Option<serenity::ChannelId> {
+    match message.channel_id.to_channel(ctx).await {
+        Ok(serenity::Channel::Guild(channel)) if channel.thread_metadata.is_none() => {}
+        Ok(_) => return None,
+        Err(e) => {
+            warn!("Failed to look up channel {}: {:?}", message.channel_id, e);
+            return None;
         }
-
-        // Find a good break point
-        let search_end = max_size.min(remaining.len());
-        let chunk = &remaining[..search_end];
-
-        // Try to break at sentence end (。 ! ? \n)
-        let break_point = chunk
-            .rfind("。")
-            .or_else(|| chunk.rfind("!"))
-            .or_else(|| chunk.rfind("?"))
-            .or_else(|| chunk.rfind("\n\n"))
-            .or_else(|| chunk.rfind("\n"))
-            .or_else(|| chunk.rfind(" "))
-            .map(|i| i + 1)
-            .unwrap_or(max_size);
-
-        chunks.push(remaining[..break_point].to_string());
-        remaining = &remaining[break_point..];
     }
 
-    chunks
+    let builder = serenity::CreateThread::new(thread_name(name))
+        .auto_archive_duration(serenity::AutoArchiveDuration::OneDay);
+    match message
+        .channel_id
+        .create_thread_from_message(ctx, message.id, builder)
+        .await
+    {
+        Ok(thread) => {
+            data.session_store
+                .move_to_thread(&message.channel_id.to_string(), &thread.id.to_string());
+            info!("Moved conversation in {} to thread {}", message.channel_id, thread.id);
+            Some(thread.id)
+        }
+        Err(e) => {
+            warn!("Failed to create thread in {}: {:?}", message.channel_id, e);
+            None
+        }
+    }
 }
 
-/// Handle message events (for mentions, DMs, and replies)
+/// Handle message events (for mentions, DMs, replies and the bot's threads)
 pub async fn handle_message(
-    ctx: &poise::serenity_prelude::Context,
-    msg: &poise::serenity_prelude::Message,
+    ctx: &serenity::Context,
+    msg: &serenity::Message,
     data: &Data,
 ) -> Result<()> {
     // Ignore messages from bots
@@ -57,18 +99,28 @@ pub async fn handle_message(
 
     let bot_id = ctx.cache.current_user().id;
 
+    // Session key: the channel, or the thread the message was posted in
+    let session_key = msg.channel_id.to_string();
+
     // Check if message is a reply to the bot
     let is_reply_to_bot = msg
-        .message_reference
+        .referenced_message
         .as_ref()
-        .and_then(|r| r.message_id)
-        .is_some();
+        .is_some_and(|m| m.author.id == bot_id);
 
     let is_mention = msg.mentions.iter().any(|user| user.id == bot_id);
     let is_dm = msg.guild_id.is_none();
+    let in_bot_thread = data.session_store.is_bot_thread(&session_key);
 
-    // Only respond if mentioned, replied to, or in DM
-    if !is_mention && !is_reply_to_bot && !is_dm {
+    // Only respond if mentioned, replied to, in DM or in a thread the bot started
+    if !is_mention && !is_reply_to_bot && !is_dm && !in_bot_thread {
+        return Ok(());
+    }
+
+    // Servers can limit the bot to some channels (its threads stay open)
+    let guild = data.guild_settings(msg.guild_id);
+    if !in_bot_thread && !guild.allows_channel(&session_key) {
+        debug!("Ignoring message in channel not allowed for this guild: {}", session_key);
         return Ok(());
     }
 
@@ -83,114 +135,169 @@ pub async fn handle_message(
 
     // Clean the message (remove mentions)
     let content = msg.content.clone();
-    let clean_content = content
+    let mut clean_content = content
         .replace(&format!("<@{}>", bot_id), "")
         .replace(&format!("<@!{}>", bot_id), "")
         .trim()
         .to_string();
 
-    if clean_content.is_empty() {
-        if let Err(e) = msg.reply(&ctx.http, "はい、何かお手伝いしましょうか？").await {
+    // Attached images go to the model with the text
+    let images = Images::download(&msg.attachments).await;
+    if let Some(note) = images.skipped_note() {
+        if let Err(e) = msg.reply(&ctx.http, note).await {
             warn!("Failed to send reply: {:?}", e);
         }
-        return Ok(());
     }
 
-    // Show typing indicator
-    let _ = msg.channel_id.broadcast_typing(&ctx.http).await;
-
-    // Get session key (thread or channel)
-    let session_key = msg.channel_id.to_string();
+    if clean_content.is_empty() {
+        if images.is_empty() {
+            if let Err(e) = msg.reply(&ctx.http, "はい、何かお手伝いしましょうか？").await {
+                warn!("Failed to send reply: {:?}", e);
+            }
+            return Ok(());
+        }
+        clean_content = DEFAULT_IMAGE_QUESTION.to_string();
+    }
 
     // Get existing session or create new one
     let session = data.session_store.get_or_create(&session_key);
 
     // Build message history
     let mut messages: Vec<Message> = session.messages.clone();
-    messages.push(Message::user(&clean_content));
+    messages.push(images.message(&clean_content));
 
     info!(
-        "Processing message from {} in {}: {} (history: {} messages)",
+        "Processing message from {} in {}: {} (images: {}, history: {} messages)",
         msg.author.name,
         session_key,
         clean_content,
+        images.images.len(),
         messages.len() - 1
     );
 
-    // Build request with conversation history
+    let mut request = build_request(
+        data,
+        "You are a helpful assistant. Respond in the same language as the user's question. Keep track of the conversation context.",
+        4096,
+        messages,
+        &guild,
+        data.persona(session.persona.as_deref(), &guild, &user_id_str, &session_key),
+    );
+
+    data.recall(&mut request, &user_id_str, &clean_content).await;
+
+    // Long conversations in a server channel continue in a thread
+    let exchanges = session.message_count() / 2 + 1;
+    let can_thread = !is_dm && !in_bot_thread;
+    let thread = if can_thread && should_start_thread(exchanges, 0, data.thread_after) {
+        start_thread(ctx, msg, &clean_content, data).await
+    } else {
+        None
+    };
+    let session_key = thread.map_or(session_key, |id| id.to_string());
+
+    // Typing indicator and a placeholder that shows progress until the answer is ready
+    let channel_id = thread.unwrap_or(msg.channel_id);
+    let typing = channel_id.start_typing(&ctx.http);
+    let reply_to = if thread.is_some() { None } else { Some(msg) };
+    let target = MessageTarget::post(ctx.http.clone(), channel_id, reply_to).await?;
+    let mut progress = Progress::new(target);
+
+    let policy = guild.tool_policy(&data.tool_policy);
+    let approver = data.approver(ctx, channel_id, &user_id_str, policy);
+    let origin = ToolOrigin::new("discord", session_key.clone()).with_user(user_id_str.clone());
+    let result = origin
+        .scope(run_agent(data, request, policy, &mut progress, &approver))
+        .await;
+    typing.stop();
+    let target = progress.into_target();
+
+    let text = match result {
+        Ok(text) => text,
+        Err(e) => {
+            error!("Claude API error: {:?}", e);
+            target.show(&format!("エラーが発生しました: {}", e)).await?;
+            return Ok(());
+        }
+    };
+
+    // Update session with user message and assistant response
+    data.session_store
+        .add_message(&session_key, Message::user(images.history_text(&clean_content)));
+    data.session_store
+        .add_message(&session_key, Message::assistant(&text));
+    data.remember(&user_id_str, &clean_content, &text);
+
+    // An answer too long for one message also moves the conversation to a thread
+    if thread.is_none() && can_thread && should_start_thread(exchanges, text.len(), data.thread_after) {
+        if let Some(thread_id) = start_thread(ctx, msg, &clean_content, data).await {
+            Response::new(&text).send(ctx, thread_id, None).await?;
+            target.show(&format!("➡️ {} で続きます", thread_id.mention())).await?;
+            return Ok(());
+        }
+    }
+
+    // Replace the placeholder with the answer (long ones as pages or a file)
+    Response::new(&text).finish(ctx, &target).await
+}
+
+/// The request for a turn: system prompt, history, tools, the guild's model
+/// and the persona
+pub(crate) fn build_request(
+    data: &Data,
+    system: &str,
+    max_tokens: u64,
+    messages: Vec<Message>,
+    guild: &GuildSettings,
+    persona: Option<&Persona>,
+) -> MessagesRequest {
     let mut request_builder = data
         .claude_client
         .request_builder()
-        .system("You are a helpful assistant. Respond in the same language as the user's question. Keep track of the conversation context.")
-        .max_tokens(4096);
+        .system(system)
+        .max_tokens(max_tokens);
 
     // Add conversation history (limit to last 20 messages to avoid token limits)
     let history_start = messages.len().saturating_sub(20);
     for message in messages.into_iter().skip(history_start) {
         request_builder = request_builder.message(message);
     }
-
-    let request = request_builder.build();
-
-    match data.claude_client.messages(request).await {
-        Ok(response) => {
-            // Extract text from response
-            let text = response
-                .content
-                .iter()
-                .filter_map(|c| {
-                    if let cc_core::MessageContent::Text { text } = c {
-                        Some(text.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            // Update session with user message and assistant response
-            data.session_store
-                .add_message(&session_key, Message::user(&clean_content));
-            data.session_store
-                .add_message(&session_key, Message::assistant(&text));
-
-            // Send response (Discord has 2000 char limit)
-            if text.len() <= 2000 {
-                if let Err(e) = msg.reply(&ctx.http, &text).await {
-                    error!("Failed to send reply: {:?}", e);
-                }
-            } else {
-                // Split into chunks
-                let chunks = split_message(&text, 1900);
-                for (i, chunk) in chunks.iter().enumerate() {
-                    let content = if i == 0 {
-                        chunk.clone()
-                    } else {
-                        format!("(続き {})\n{}", i + 1, chunk)
-                    };
-
-                    if let Err(e) = msg.reply(&ctx.http, &content).await {
-                        error!("Failed to send reply chunk {}: {:?}", i, e);
-                        break;
-                    }
-
-                    // Small delay between chunks to avoid rate limiting
-                    if i < chunks.len() - 1 {
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    }
-                }
-            }
-        }
-        Err(e) => {
-            error!("Claude API error: {:?}", e);
-            if let Err(e) = msg
-                .reply(&ctx.http, &format!("エラーが発生しました: {}", e))
-                .await
-            {
-                error!("Failed to send error message: {:?}", e);
-            }
+    if let Some(ref tools) = data.tools {
+        for tool in tools.definitions() {
+            request_builder = request_builder.tool(tool);
         }
     }
 
-    Ok(())
+    let mut request = request_builder.build();
+    if let Some(ref model) = guild.model {
+        request.model = model.clone();
+    }
+
+    // A persona's own model wins over the guild's
+    if let Some(persona) = persona {
+        persona.apply(&mut request);
+    }
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_name() {
+        assert_eq!(thread_name("\n  Plan the release\nwith details"), "Plan the release");
+        assert_eq!(thread_name(""), "Claude");
+        let long = thread_name(&"a".repeat(150));
+        assert_eq!(long.chars().count(), MAX_THREAD_NAME_CHARS);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn test_should_start_thread() {
+        assert!(!should_start_thread(1, 100, Some(3)));
+        assert!(should_start_thread(3, 100, Some(3)));
+        assert!(should_start_thread(1, MAX_MESSAGE_LEN + 1, Some(3)));
+        assert!(!should_start_thread(10, MAX_MESSAGE_LEN + 1, None));
+    }
 }
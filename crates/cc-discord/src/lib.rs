@@ -3,14 +3,21 @@
 //! Discord Botを通じてClaude APIへのアクセスを提供します。
 //! poise framework + Serenity 0.12を使用してDiscord Gatewayに接続します。
 
+pub mod agent;
+pub mod approval;
+pub mod attachment;
 pub mod bot;
 pub mod commands;
 pub mod error;
+pub mod guild;
 pub mod handler;
+pub mod progress;
+pub mod response;
 pub mod session;
 
 pub use bot::DiscordBot;
 pub use error::{DiscordError, Result};
+pub use guild::{GuildConfigStore, GuildSettings};
 pub use session::InMemorySessionStore;
 
 // Re-export poise serenity prelude for convenience
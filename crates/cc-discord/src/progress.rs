@@ -0,0 +1,240 @@
+//! Live progress of a turn
+//!
+//! While the agent works, a placeholder message shows what it is doing
+//! (tool calls and any text written between them) and is edited as the turn
+//! goes on. Edits are throttled to stay under Discord's rate limits; the
+//! caller replaces the placeholder with the final answer.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use tracing::warn;
+
+use crate::error::Result;
+use crate::response::MAX_MESSAGE_LEN;
+
+/// Placeholder shown before anything happens
+pub(crate) const THINKING: &str = "💭 考え中…";
+
+/// Minimum time between two edits
+const EDIT_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// Status lines kept (older ones are dropped)
+const MAX_STATUS_LINES: usize = 10;
+
+/// Where progress is shown
+#[async_trait]
+pub trait ProgressTarget: Send + Sync {
+    /// Replace the shown progress with `content`
+    async fn show(&self, content: &str) -> Result<()>;
+}
+
+/// A message the bot posted in a channel
+pub struct MessageTarget {
+    http: Arc<serenity::Http>,
+    channel_id: serenity::ChannelId,
+    message_id: serenity::MessageId,
+}
+
+impl MessageTarget {
+    /// Post the placeholder in `channel` (as a reply to `reply_to`, if given)
+    pub async fn post(
+        http: Arc<serenity::Http>,
+        channel_id: serenity::ChannelId,
+        reply_to: Option<&serenity::Message>,
+    ) -> Result<Self> {
+        let mut builder = serenity::CreateMessage::new().content(THINKING);
+        if let Some(message) = reply_to {
+            builder = builder.reference_message(message);
+        }
+        let message = channel_id.send_message(&http, builder).await?;
+        Ok(Self {
+            http,
+            channel_id,
+            message_id: message.id,
+        })
+    }
+
+    pub fn channel_id(&self) -> serenity::ChannelId {
+        self.channel_id
+    }
+
+    pub fn message_id(&self) -> serenity::MessageId {
+        self.message_id
+    }
+
+    /// Apply `edit` to the placeholder
+    pub async fn edit(&self, edit: serenity::EditMessage) -> Result<serenity::Message> {
+        Ok(self
+            .channel_id
+            .edit_message(&self.http, self.message_id, edit)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl ProgressTarget for MessageTarget {
+    async fn show(&self, content: &str) -> Result<()> {
+        self.edit(serenity::EditMessage::new().content(content)).await?;
+        Ok(())
+    }
+}
+
+/// Progress of one turn, shown on a [`ProgressTarget`]
+pub struct Progress<T> {
+    target: T,
+    status: Vec<String>,
+    draft: String,
+    last_edit: Option<Instant>,
+}
+
+impl<T: ProgressTarget> Progress<T> {
+    pub fn new(target: T) -> Self {
+        Self {
+            target,
+            status: Vec::new(),
+            draft: String::new(),
+            last_edit: None,
+        }
+    }
+
+    /// The agent wants to call `tool`, which needs approval
+    pub async fn awaiting_approval(&mut self, tool: &str) {
+        self.push_status(format!("⏳ `{}` の承認待ち…", tool));
+        self.refresh(true).await;
+    }
+
+    /// The agent is calling `tool`
+    pub async fn tool_started(&mut self, tool: &str) {
+        let line = format!("🔧 `{}` を実行中…", tool);
+        match self.status.last_mut() {
+            Some(last) if *last == format!("⏳ `{}` の承認待ち…", tool) => *last = line,
+            _ => self.push_status(line),
+        }
+        self.refresh(false).await;
+    }
+
+    /// `tool` was not run
+    pub async fn tool_refused(&mut self, tool: &str) {
+        let line = format!("🚫 `{}`", tool);
+        match self.status.last_mut() {
+            Some(last) if *last == format!("⏳ `{}` の承認待ち…", tool) => *last = line,
+            _ => self.push_status(line),
+        }
+        self.refresh(true).await;
+    }
+
+    /// `tool` finished
+    pub async fn tool_finished(&mut self, tool: &str, is_error: bool) {
+        let mark = if is_error { "⚠️" } else { "✅" };
+        if let Some(line) = self.status.last_mut() {
+            *line = format!("{} `{}`", mark, tool);
+        }
+        self.refresh(false).await;
+    }
+
+    /// Text the agent wrote along with its tool calls
+    pub async fn draft(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+        self.draft = text.trim().to_string();
+        self.refresh(true).await;
+    }
+
+    fn push_status(&mut self, line: String) {
+        self.status.push(line);
+        if self.status.len() > MAX_STATUS_LINES {
+            self.status.remove(0);
+        }
+    }
+
+    /// The target, for showing the final answer
+    pub fn into_target(self) -> T {
+        self.target
+    }
+
+    async fn refresh(&mut self, force: bool) {
+        if !force && self.last_edit.is_some_and(|t| t.elapsed() < EDIT_INTERVAL) {
+            return;
+        }
+        self.last_edit = Some(Instant::now());
+        if let Err(e) = self.target.show(&render(&self.status, &self.draft)).await {
+            warn!("Failed to update progress: {:?}", e);
+        }
+    }
+}
+
+/// The progress message: the draft text, then the status lines
+pub(crate) fn render(status: &[String], draft: &str) -> String {
+    // Status gets at most half of the message; the oldest lines go first
+    let mut lines = status;
+    while lines.len() > 1 && lines.iter().map(|l| l.len() + 1).sum::<usize>() > MAX_MESSAGE_LEN / 2 {
+        lines = &lines[1..];
+    }
+    let status = lines.join("\n");
+    let room = MAX_MESSAGE_LEN.saturating_sub(status.len().max(THINKING.len()) + 8);
+
+    let mut text = String::new();
+    if !draft.is_empty() {
+        if draft.len() > room {
+            let mut end = room;
+            while !draft.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.push_str(&draft[..end]);
+            text.push('…');
+        } else {
+            text.push_str(draft);
+        }
+        text.push_str("\n\n");
+    }
+    text.push_str(if status.is_empty() { THINKING } else { &status });
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl ProgressTarget for Recorder {
+        async fn show(&self, content: &str) -> Result<()> {
+            self.0.lock().unwrap().push(content.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_progress_throttles_status_edits() {
+        let mut progress = Progress::new(Recorder::default());
+        progress.tool_started("web_search").await;
+        progress.tool_finished("web_search", false).await;
+        progress.tool_started("calendar_list_events").await;
+        // Draft text always goes out
+        progress.draft("Checking your calendar").await;
+
+        let shown = progress.into_target().0.into_inner().unwrap();
+        assert_eq!(shown.len(), 2);
+        assert_eq!(shown[0], "🔧 `web_search` を実行中…");
+        assert_eq!(
+            shown[1],
+            "Checking your calendar\n\n✅ `web_search`\n🔧 `calendar_list_events` を実行中…"
+        );
+    }
+
+    #[test]
+    fn test_render_fits_message() {
+        assert_eq!(render(&[], ""), THINKING);
+        let status = vec!["✅ `web_search`".to_string()];
+        let text = render(&status, &"長い".repeat(2000));
+        assert!(text.len() <= MAX_MESSAGE_LEN);
+        assert!(text.ends_with("…\n\n✅ `web_search`"));
+    }
+}
@@ -0,0 +1,370 @@
+//! Long response handling
+//!
+//! Discord messages are limited to 2000 characters. Answers that fit are sent
+//! as plain text; longer ones become pages of embeds with ◀ / ▶ buttons, and
+//! very long ones are attached as a Markdown file with a short preview.
+//! Pages are split at line and code block boundaries, and a code block that
+//! spans pages is closed and reopened so each page renders on its own.
+
+use std::time::Duration;
+
+use poise::serenity_prelude as serenity;
+use tracing::warn;
+
+use crate::error::Result;
+use crate::progress::MessageTarget;
+
+/// Discord's message length limit
+pub(crate) const MAX_MESSAGE_LEN: usize = 2000;
+
+/// Page size (embed descriptions are limited to 4096 characters)
+const PAGE_LEN: usize = 4000;
+
+/// More pages than this are sent as a file instead
+const MAX_PAGES: usize = 10;
+
+/// Length of the preview sent with a file
+const PREVIEW_LEN: usize = 1500;
+
+/// How long the page buttons keep working
+const PAGE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Embed accent color
+const EMBED_COLOR: u32 = 0xD97757;
+
+const PREV_BUTTON: &str = "cc_page_prev";
+const NEXT_BUTTON: &str = "cc_page_next";
+
+/// How a response is sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    /// A single plain message
+    Text(String),
+    /// Embed pages with navigation buttons
+    Pages(Vec<String>),
+    /// A preview message with the full text attached as `response.md`
+    File { preview: String, content: String },
+}
+
+impl Response {
+    /// Pick the layout for `text` by its length
+    pub fn new(text: &str) -> Self {
+        if text.len() <= MAX_MESSAGE_LEN {
+            return Self::Text(text.to_string());
+        }
+        let pages = chunk_response(text, PAGE_LEN);
+        if pages.len() <= MAX_PAGES {
+            return Self::Pages(pages);
+        }
+        let first = chunk_response(text, PREVIEW_LEN).swap_remove(0);
+        Self::File {
+            preview: format!("{}\n\n…(全文は添付ファイルを参照してください)", first),
+            content: text.to_string(),
+        }
+    }
+
+    /// The response as a slash command reply
+    pub fn reply(&self) -> poise::CreateReply {
+        match self {
+            Self::Text(text) => poise::CreateReply::default().content(text),
+            Self::Pages(pages) => poise::CreateReply::default()
+                .content("")
+                .embed(page_embed(pages, 0))
+                .components(page_buttons(0, pages.len(), false)),
+            Self::File { preview, content } => poise::CreateReply::default()
+                .content(preview)
+                .attachment(attachment(content)),
+        }
+    }
+
+    /// The response as a channel message
+    pub fn message(&self) -> serenity::CreateMessage {
+        match self {
+            Self::Text(text) => serenity::CreateMessage::new().content(text),
+            Self::Pages(pages) => serenity::CreateMessage::new()
+                .embed(page_embed(pages, 0))
+                .components(page_buttons(0, pages.len(), false)),
+            Self::File { preview, content } => serenity::CreateMessage::new()
+                .content(preview)
+                .add_file(attachment(content)),
+        }
+    }
+
+    /// Send the response to `channel` (as a reply to `reply_to`, if given)
+    pub async fn send(
+        self,
+        ctx: &serenity::Context,
+        channel: serenity::ChannelId,
+        reply_to: Option<&serenity::Message>,
+    ) -> Result<()> {
+        let mut builder = self.message();
+        if let Some(message) = reply_to {
+            builder = builder.reference_message(message);
+        }
+        let sent = channel.send_message(ctx, builder).await?;
+        if let Self::Pages(pages) = self {
+            paginate(ctx.clone(), sent, pages);
+        }
+        Ok(())
+    }
+
+    /// Show the response in place of a progress placeholder
+    pub async fn finish(self, ctx: &serenity::Context, target: &MessageTarget) -> Result<()> {
+        let edit = match &self {
+            Self::Text(text) => serenity::EditMessage::new().content(text),
+            Self::Pages(pages) => serenity::EditMessage::new()
+                .content("")
+                .embed(page_embed(pages, 0))
+                .components(page_buttons(0, pages.len(), false)),
+            Self::File { preview, content } => serenity::EditMessage::new()
+                .content(preview)
+                .new_attachment(attachment(content)),
+        };
+        let message = target.edit(edit).await?;
+        if let Self::Pages(pages) = self {
+            paginate(ctx.clone(), message, pages);
+        }
+        Ok(())
+    }
+}
+
+/// Embed showing page `index` of `pages`
+fn page_embed(pages: &[String], index: usize) -> serenity::CreateEmbed {
+    let mut embed = serenity::CreateEmbed::new()
+        .description(&pages[index])
+        .color(EMBED_COLOR);
+    if pages.len() > 1 {
+        embed = embed.footer(serenity::CreateEmbedFooter::new(format!(
+            "{} / {}",
+            index + 1,
+            pages.len()
+        )));
+    }
+    embed
+}
+
+/// ◀ / ▶ buttons for page `index` of `total`
+fn page_buttons(index: usize, total: usize, expired: bool) -> Vec<serenity::CreateActionRow> {
+    if total <= 1 {
+        return Vec::new();
+    }
+    vec![serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(PREV_BUTTON)
+            .label("◀")
+            .style(serenity::ButtonStyle::Secondary)
+            .disabled(expired || index == 0),
+        serenity::CreateButton::new(NEXT_BUTTON)
+            .label("▶")
+            .style(serenity::ButtonStyle::Secondary)
+            .disabled(expired || index + 1 == total),
+    ])]
+}
+
+fn attachment(content: &str) -> serenity::CreateAttachment {
+    serenity::CreateAttachment::bytes(content.as_bytes().to_vec(), "response.md")
+}
+
+/// Turn pages on button presses until the buttons time out
+pub(crate) fn paginate(ctx: serenity::Context, mut message: serenity::Message, pages: Vec<String>) {
+    if pages.len() <= 1 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut index: usize = 0;
+        while let Some(press) = message
+            .await_component_interaction(&ctx.shard)
+            .timeout(PAGE_TIMEOUT)
+            .await
+        {
+            index = match press.data.custom_id.as_str() {
+                PREV_BUTTON => index.saturating_sub(1),
+                NEXT_BUTTON => (index + 1).min(pages.len() - 1),
+                _ => index,
+            };
+            let update = serenity::CreateInteractionResponseMessage::new()
+                .embed(page_embed(&pages, index))
+                .components(page_buttons(index, pages.len(), false));
+            if let Err(e) = press
+                .create_response(&ctx.http, serenity::CreateInteractionResponse::UpdateMessage(update))
+                .await
+            {
+                warn!("Failed to turn page: {:?}", e);
+            }
+        }
+
+        // Leave the current page up with the buttons disabled
+        let edit = serenity::EditMessage::new().components(page_buttons(index, pages.len(), true));
+        if let Err(e) = message.edit(&ctx, edit).await {
+            warn!("Failed to disable page buttons: {:?}", e);
+        }
+    });
+}
+
+/// Split `text` into chunks of at most `max_size` bytes
+///
+/// Chunks end at line boundaries; a code block that would straddle a chunk
+/// boundary starts a new chunk if it fits in one, and otherwise is closed
+/// and reopened (with its language) across the chunks.
+pub(crate) fn chunk_response(text: &str, max_size: usize) -> Vec<String> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    // The opening fence of the code block we're in
+    let mut fence: Option<String> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let is_fence = line.trim_start().starts_with("```");
+
+        // Start a code block on a fresh chunk when it fits in one
+        if is_fence && fence.is_none() && !current.trim().is_empty() {
+            let block_len: usize = lines[i..]
+                .iter()
+                .enumerate()
+                .skip(1)
+                .find(|(_, l)| l.trim_start().starts_with("```"))
+                .map(|(end, _)| lines[i..=i + end].iter().map(|l| l.len()).sum())
+                .unwrap_or(usize::MAX);
+            if current.len() + block_len > max_size && block_len <= max_size {
+                push_chunk(&mut chunks, &mut current, None);
+            }
+        }
+
+        // Room for closing an open code block at the end of the chunk
+        let reserve = if fence.is_some() { 4 } else { 0 };
+        let limit = max_size.saturating_sub(reserve + 8).max(1);
+        for piece in split_message(line, limit) {
+            if !current.is_empty() && current.len() + piece.len() + reserve > max_size {
+                push_chunk(&mut chunks, &mut current, fence.as_deref());
+            }
+            current.push_str(&piece);
+        }
+
+        if is_fence {
+            fence = match fence {
+                Some(_) => None,
+                None => Some(line.trim().to_string()),
+            };
+        }
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current.trim_end().to_string());
+    }
+    if chunks.is_empty() {
+        chunks.push(String::new());
+    }
+    chunks
+}
+
+/// Finish the current chunk, closing and reopening an open code block
+fn push_chunk(chunks: &mut Vec<String>, current: &mut String, fence: Option<&str>) {
+    let mut chunk = current.trim_end().to_string();
+    current.clear();
+    if let Some(fence) = fence {
+        chunk.push_str("\n```");
+        current.push_str(fence);
+        current.push('\n');
+    }
+    if !chunk.trim().is_empty() {
+        chunks.push(chunk);
+    }
+}
+
+/// Split a message into chunks at sentence boundaries
+pub(crate) fn split_message(text: &str, max_size: usize) -> Vec<String> {
+    if text.len() <= max_size {
+        return vec![text.to_string()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut remaining = text;
+
+    while !remaining.is_empty() {
+        if remaining.len() <= max_size {
+            chunks.push(remaining.to_string());
+            break;
+        }
+
+        // Find a good break point
+        let mut search_end = max_size.min(remaining.len());
+        while !remaining.is_char_boundary(search_end) {
+            search_end -= 1;
+        }
+        let chunk = &remaining[..search_end];
+
+        // Try to break at sentence end (。 ! ? \n)
+        let break_point = chunk
+            .rfind("。")
+            .or_else(|| chunk.rfind("!"))
+            .or_else(|| chunk.rfind("?"))
+            .or_else(|| chunk.rfind("\n\n"))
+            .or_else(|| chunk.rfind("\n"))
+            .or_else(|| chunk.rfind(" "))
+            .map(|i| i + chunk[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(search_end);
+
+        chunks.push(remaining[..break_point].to_string());
+        remaining = &remaining[break_point..];
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_message_multibyte() {
+        let text = "あいうえお。".repeat(500);
+        let chunks = split_message(&text, 1900);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= 1900 && c.ends_with('。')));
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn test_response_layout() {
+        assert_eq!(Response::new("short"), Response::Text("short".to_string()));
+
+        let paragraph = format!("{}\n\n", "word ".repeat(100));
+        match Response::new(&paragraph.repeat(10)) {
+            Response::Pages(pages) => {
+                assert_eq!(pages.len(), 2);
+                assert!(pages.iter().all(|p| p.len() <= PAGE_LEN));
+            }
+            other => panic!("expected pages, got {:?}", other),
+        }
+
+        match Response::new(&paragraph.repeat(100)) {
+            Response::File { preview, content } => {
+                assert!(preview.len() < MAX_MESSAGE_LEN);
+                assert_eq!(content.len(), paragraph.len() * 100);
+            }
+            other => panic!("expected a file, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_chunk_keeps_code_blocks_whole() {
+        let block = "```rust\nfn main() {}\n```\n";
+        let text = format!("{}{}after\n", "intro line\n".repeat(5), block);
+        let chunks = chunk_response(&text, 70);
+        assert!(chunks.iter().all(|c| c.len() <= 70));
+        assert!(chunks.iter().any(|c| c.starts_with("```rust\nfn main() {}\n```")));
+    }
+
+    #[test]
+    fn test_chunk_reopens_long_code_block() {
+        let code: String = (0..40).map(|i| format!("let x{} = {};\n", i, i)).collect();
+        let text = format!("Here:\n```rust\n{}```\nDone.", code);
+        let chunks = chunk_response(&text, 200);
+        assert!(chunks.len() > 2);
+        for chunk in &chunks {
+            assert!(chunk.len() <= 200);
+            // Every chunk has balanced fences
+            assert_eq!(chunk.matches("```").count() % 2, 0, "{}", chunk);
+        }
+        assert!(chunks[1].starts_with("```rust\n"));
+        assert!(chunks.last().unwrap().ends_with("Done."));
+    }
+}
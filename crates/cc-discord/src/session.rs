@@ -1,11 +1,13 @@
 //! In-memory session management for Discord bot
 //!
-//! Thread-safe session storage using DashMap
+//! Thread-safe session storage using DashMap. Sessions are keyed by channel
+//! ID; a Discord thread is a channel of its own, so each thread keeps its own
+//! conversation.
 
 use std::sync::Arc;
 use std::time::Duration;
 
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use tokio::time::interval;
 use tracing::info;
 
@@ -15,6 +17,8 @@ use cc_core::{Message, Session};
 #[derive(Clone)]
 pub struct InMemorySessionStore {
     sessions: Arc<DashMap<String, Session>>,
+    /// Threads the bot started, where it answers without being mentioned
+    threads: Arc<DashSet<String>>,
     #[allow(dead_code)]
     max_sessions: usize,
     session_timeout_secs: u64,
@@ -25,6 +29,7 @@ impl InMemorySessionStore {
     pub fn new() -> Self {
         Self {
             sessions: Arc::new(DashMap::new()),
+            threads: Arc::new(DashSet::new()),
             max_sessions: 1000,
             session_timeout_secs: 3600, // 1 hour
         }
@@ -34,6 +39,7 @@ impl InMemorySessionStore {
     pub fn with_settings(max_sessions: usize, session_timeout_secs: u64) -> Self {
         Self {
             sessions: Arc::new(DashMap::new()),
+            threads: Arc::new(DashSet::new()),
             max_sessions,
             session_timeout_secs,
         }
@@ -67,6 +73,32 @@ impl InMemorySessionStore {
         }
     }
 
+    /// Set the persona for a channel (None = reset to default)
+    pub fn set_persona(&self, channel_id: &str, persona: Option<String>) {
+        self.sessions
+            .entry(channel_id.to_string())
+            .or_insert_with(|| Session::new(channel_id))
+            .set_persona(persona);
+    }
+
+    /// Move a channel's conversation into a thread the bot started
+    ///
+    /// The thread continues with the channel's history and persona; the
+    /// channel starts over.
+    pub fn move_to_thread(&self, channel_id: &str, thread_id: &str) {
+        let mut session = self
+            .remove(channel_id)
+            .unwrap_or_else(|| Session::new(thread_id));
+        session.channel_id = thread_id.to_string();
+        self.sessions.insert(thread_id.to_string(), session);
+        self.threads.insert(thread_id.to_string());
+    }
+
+    /// Check if the bot started this thread
+    pub fn is_bot_thread(&self, channel_id: &str) -> bool {
+        self.threads.contains(channel_id)
+    }
+
     /// Clear a session's messages
     pub fn clear(&self, channel_id: &str) -> bool {
         if let Some(mut session) = self.sessions.get_mut(channel_id) {
@@ -158,4 +190,31 @@ mod tests {
         let session = store.get("channel-123").unwrap();
         assert!(session.is_empty());
     }
+
+    #[test]
+    fn test_persona_survives_clear() {
+        let store = InMemorySessionStore::new();
+        store.set_persona("channel-123", Some("coder".to_string()));
+        store.add_message("channel-123", Message::user("Hello"));
+        store.clear("channel-123");
+
+        let session = store.get("channel-123").unwrap();
+        assert_eq!(session.persona.as_deref(), Some("coder"));
+    }
+
+    #[test]
+    fn test_move_to_thread() {
+        let store = InMemorySessionStore::new();
+        store.set_persona("channel-123", Some("coder".to_string()));
+        store.add_message("channel-123", Message::user("Hello"));
+        assert!(!store.is_bot_thread("thread-1"));
+
+        store.move_to_thread("channel-123", "thread-1");
+        assert!(store.is_bot_thread("thread-1"));
+        assert!(store.get("channel-123").is_none());
+
+        let thread = store.get("thread-1").unwrap();
+        assert_eq!(thread.message_count(), 1);
+        assert_eq!(thread.persona.as_deref(), Some("coder"));
+    }
 }
@@ -0,0 +1,519 @@
+//! Email as a chat channel
+//!
+//! Watches a mailbox with IMAP IDLE, runs each incoming email through the
+//! agent loop and answers in the same thread. Every email thread (identified
+//! by the root of its `References`) has its own conversation session.
+//!
+//! Anyone can put a message in a mailbox, so the channel is closed by
+//! default: only listed senders whose mail passed DMARC at the receiving
+//! server are answered, the agent only gets read-only tools unless others
+//! are listed, and every call is checked against the tool policy. There is
+//! nobody to ask for approval, so calls that need it are refused.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use tracing::{debug, error, info, warn};
+
+use async_trait::async_trait;
+use cc_core::llm::ToolCall;
+use cc_core::{
+    AgentEngine, AgentHooks, CancellationToken, ClaudeClient, Message, MessagesRequest, PolicyDecision,
+    Session, ToolManager, ToolOrigin, ToolPolicy, ToolResult,
+};
+
+use crate::error::{EmailError, Result};
+use crate::imap::{IdleEvent, ImapSession};
+use crate::message::{OutgoingEmail, ParsedEmail};
+use crate::receive::ImapConfig;
+use crate::send::EmailSender;
+
+/// Configuration for the email channel
+#[derive(Clone, Debug)]
+pub struct EmailChannelConfig {
+    /// Folder to watch
+    pub folder: String,
+    /// Allowed sender addresses or `@domain` suffixes. Empty = nobody.
+    pub allowed_senders: Vec<String>,
+    /// Only answer mail that passed DMARC according to the receiving
+    /// server's `Authentication-Results` header
+    pub require_authenticated: bool,
+    /// authserv-id of the receiving server; only its `Authentication-Results`
+    /// headers are trusted (none are while it is unset)
+    pub authserv_id: Option<String>,
+    /// Tools the agent may use. None = the read-only tools only.
+    pub tools: Option<Vec<String>>,
+    /// Policy every tool call is checked against
+    pub tool_policy: ToolPolicy,
+    /// System prompt for Claude
+    pub system_prompt: String,
+    /// Maximum tool-use iterations per email
+    pub max_iterations: usize,
+    /// How long one IDLE command waits before it is renewed
+    pub idle_timeout: Duration,
+    /// Poll interval for servers without IDLE
+    pub poll_interval: Duration,
+    /// Delay before reconnecting after an error
+    pub reconnect_delay: Duration,
+    /// Threads without activity for this long start a new session
+    pub session_timeout: Duration,
+    /// Number of history messages sent with each request
+    pub max_history: usize,
+}
+
+impl Default for EmailChannelConfig {
+    fn default() -> Self {
+        Self {
+            folder: "INBOX".to_string(),
+            allowed_senders: Vec::new(),
+            require_authenticated: true,
+            authserv_id: None,
+            tools: None,
+            tool_policy: ToolPolicy::default(),
+            system_prompt: "You are a helpful assistant replying to email. Write the reply body only, as plain text, without a subject line or placeholder signature. Respond in the same language as the sender.".to_string(),
+            max_iterations: 10,
+            // Servers drop idle connections after 30 minutes (RFC 2177)
+            idle_timeout: Duration::from_secs(25 * 60),
+            poll_interval: Duration::from_secs(60),
+            reconnect_delay: Duration::from_secs(30),
+            session_timeout: Duration::from_secs(7 * 24 * 3600),
+            max_history: 20,
+        }
+    }
+}
+
+impl EmailChannelConfig {
+    /// Read channel settings from the environment
+    ///
+    /// `EMAIL_FOLDER` (default INBOX), `EMAIL_ALLOWED_SENDERS` (comma
+    /// separated), `EMAIL_REQUIRE_AUTH` (default true), `EMAIL_AUTHSERV_ID`,
+    /// `EMAIL_TOOLS` (comma separated, default: read-only tools)
+    pub fn from_env() -> Self {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+        let mut config = Self::default();
+        if let Some(folder) = env("EMAIL_FOLDER") {
+            config.folder = folder;
+        }
+        if let Some(senders) = env("EMAIL_ALLOWED_SENDERS") {
+            config.allowed_senders = senders
+                .split(',')
+                .map(|s| s.trim().to_ascii_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Some(require) = env("EMAIL_REQUIRE_AUTH") {
+            config.require_authenticated = !matches!(require.to_ascii_lowercase().as_str(), "false" | "0" | "no");
+        }
+        config.authserv_id = env("EMAIL_AUTHSERV_ID").map(|id| id.trim().to_string());
+        if let Some(tools) = env("EMAIL_TOOLS") {
+            config.tools = Some(
+                tools
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+            );
+        }
+        config
+    }
+
+    /// Whether the agent may call `tool` (`read_only` as reported by the tool)
+    fn allows_tool(&self, tool: &str, read_only: bool) -> bool {
+        match &self.tools {
+            Some(tools) => tools.iter().any(|t| t == tool),
+            None => read_only,
+        }
+    }
+}
+
+/// Email channel: answers incoming mail with the agent
+pub struct EmailChannel {
+    imap: ImapConfig,
+    sender: EmailSender,
+    client: Arc<ClaudeClient>,
+    tools: Arc<ToolManager>,
+    config: EmailChannelConfig,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl EmailChannel {
+    /// Create a new email channel
+    pub fn new(
+        imap: ImapConfig,
+        sender: EmailSender,
+        client: Arc<ClaudeClient>,
+        tools: Arc<ToolManager>,
+    ) -> Self {
+        Self {
+            imap,
+            sender,
+            client,
+            tools,
+            config: EmailChannelConfig::default(),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the channel configuration
+    pub fn with_config(mut self, config: EmailChannelConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Watch the mailbox until `cancel` is triggered, reconnecting after errors
+    pub async fn run(&self, cancel: CancellationToken) {
+        if self.config.allowed_senders.is_empty() {
+            warn!("Email channel answers nobody (EMAIL_ALLOWED_SENDERS is empty)");
+        }
+        if !self.config.require_authenticated {
+            warn!("Email channel trusts the From header without DMARC (EMAIL_REQUIRE_AUTH=false)");
+        } else if self.config.authserv_id.is_none() {
+            warn!("Email channel answers nobody (EMAIL_AUTHSERV_ID is not set, so no mail passes DMARC)");
+        }
+        info!(
+            "Email channel watching {} on {}",
+            self.config.folder, self.imap.imap_host
+        );
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                result = self.watch() => {
+                    if let Err(e) = result {
+                        error!("Email channel error: {}", e);
+                    }
+                }
+            }
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(self.config.reconnect_delay) => {}
+            }
+        }
+        info!("Email channel stopped");
+    }
+
+    /// One connection: answer unseen mail, then wait for more
+    async fn watch(&self) -> Result<()> {
+        let mut imap = ImapSession::connect(&self.imap).await?;
+        imap.select(&self.config.folder).await?;
+        let idle = imap.has_capability("IDLE");
+        if !idle {
+            info!(
+                "IMAP server has no IDLE support, polling every {:?}",
+                self.config.poll_interval
+            );
+        }
+
+        loop {
+            for uid in imap.uid_search("UNSEEN").await? {
+                if let Err(e) = self.process(&mut imap, uid).await {
+                    // Connection problems end this session; other failures
+                    // leave the message unseen so it is retried later
+                    if matches!(e, EmailError::ImapConnection(_)) {
+                        return Err(e);
+                    }
+                    error!("Failed to answer email UID {}: {}", uid, e);
+                }
+            }
+
+            if idle {
+                if imap.idle(self.config.idle_timeout).await? == IdleEvent::NewMail {
+                    debug!("New mail in {}", self.config.folder);
+                }
+            } else {
+                tokio::time::sleep(self.config.poll_interval).await;
+                // Keep the connection alive and let the server report changes
+                imap.command("NOOP").await?;
+            }
+        }
+    }
+
+    /// Answer one message and mark it as seen
+    async fn process(&self, imap: &mut ImapSession, uid: u32) -> Result<()> {
+        let fetched = imap.uid_fetch(&uid.to_string(), "BODY.PEEK[]").await?;
+        let Some(message) = fetched.into_iter().find(|m| m.uid == uid) else {
+            return Ok(());
+        };
+        let email = ParsedEmail::parse(&message.raw);
+
+        if self.should_answer(&email) {
+            self.answer(&email).await?;
+        }
+        imap.add_flags(&uid.to_string(), "\\Seen").await
+    }
+
+    /// Whether an email gets a reply
+    fn should_answer(&self, email: &ParsedEmail) -> bool {
+        let Some(from) = email.from.as_deref() else {
+            return false;
+        };
+        if from.eq_ignore_ascii_case(self.sender.from_address()) {
+            return false;
+        }
+        if email.automated {
+            debug!("Ignoring automated email from {}", from);
+            return false;
+        }
+        if !is_sender_allowed(&self.config.allowed_senders, from) {
+            debug!("Ignoring email from unauthorized sender: {}", from);
+            return false;
+        }
+        let authenticated = self
+            .config
+            .authserv_id
+            .as_deref()
+            .is_some_and(|id| email.dmarc_passed(id));
+        if self.config.require_authenticated && !authenticated {
+            warn!("Ignoring email from {} that did not pass DMARC", from);
+            return false;
+        }
+        true
+    }
+
+    /// Run the agent on an email and send the reply
+    async fn answer(&self, email: &ParsedEmail) -> Result<()> {
+        let thread_id = email.thread_id();
+        let from = email.from.clone().unwrap_or_default();
+        info!("Processing email from {}: {}", from, email.subject);
+
+        let (history, is_new) = self.session_history(&thread_id);
+        let content = user_content(email, is_new);
+
+        let mut messages = history;
+        messages.push(Message::user(&content));
+        let start = messages.len().saturating_sub(self.config.max_history);
+        let messages = messages.split_off(start);
+
+        // Scheduled results and reminders created from this email go back to the sender
+        let origin = ToolOrigin::new("email", email.reply_address().unwrap_or(&from))
+            .with_user(from.clone());
+        let reply = origin
+            .scope(self.run_agent(messages))
+            .await
+            .map_err(|e| EmailError::SmtpSend(format!("Agent failed: {}", e)))?;
+        if reply.trim().is_empty() {
+            warn!("Agent produced an empty reply for {}", thread_id);
+            return Ok(());
+        }
+
+        let outgoing = OutgoingEmail::reply(email, reply.clone())
+            .ok_or_else(|| EmailError::InvalidAddress(from.clone()))?;
+        self.sender.send_message(&outgoing).await?;
+
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .entry(thread_id.clone())
+            .or_insert_with(|| Session::new(format!("email:{}", thread_id)));
+        session.add_message(Message::user(&content));
+        session.add_message(Message::assistant(&reply));
+        Ok(())
+    }
+
+    /// The stored history of a thread and whether the thread is new
+    ///
+    /// Sessions idle for longer than `session_timeout` are dropped.
+    fn session_history(&self, thread_id: &str) -> (Vec<Message>, bool) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let cutoff = Utc::now()
+            - chrono::Duration::from_std(self.config.session_timeout).unwrap_or(chrono::Duration::MAX);
+        sessions.retain(|_, session| session.updated_at > cutoff);
+        match sessions.get(thread_id) {
+            Some(session) => (session.messages.clone(), false),
+            None => (Vec::new(), true),
+        }
+    }
+
+    /// Agent loop with tool use
+    async fn run_agent(&self, messages: Vec<Message>) -> anyhow::Result<String> {
+        let tools: Vec<_> = self
+            .tools
+            .definitions()
+            .into_iter()
+            .filter(|tool| self.config.allows_tool(&tool.name, self.tools.is_read_only(&tool.name)))
+            .collect();
+        let mut request = MessagesRequest {
+            model: self.client.model().to_string(),
+            max_tokens: 4096,
+            system: Some(self.config.system_prompt.clone()),
+            messages,
+            tools: if tools.is_empty() { None } else { Some(tools) },
+            thinking: None,
+            tool_choice: None,
+            temperature: None,
+        };
+        let run = AgentEngine::new(&self.client)
+            .with_tools(&self.tools)
+            .with_max_iterations(self.config.max_iterations)
+            .run(&mut request, &EmailHooks { config: &self.config })
+            .await?;
+        Ok(run.text)
+    }
+}
+
+/// Restricts the agent to the channel's tools and the tool policy
+struct EmailHooks<'a> {
+    config: &'a EmailChannelConfig,
+}
+
+#[async_trait]
+impl AgentHooks for EmailHooks<'_> {
+    async fn on_tool_call(&self, call: &ToolCall, tools: &ToolManager) -> ToolResult {
+        let name = call.name.as_str();
+        let read_only = tools.is_read_only(name);
+        if !self.config.allows_tool(name, read_only) {
+            return ToolResult::error(format!("Tool {} is not available in the email channel", name));
+        }
+        match self.config.tool_policy.decide_call(name, read_only) {
+            PolicyDecision::Allow => tools
+                .execute(name, call.input.clone())
+                .await
+                .unwrap_or_else(|e| ToolResult::error(e.to_string())),
+            PolicyDecision::Deny => ToolResult::error(format!("Tool {} is not allowed by the tool policy", name)),
+            PolicyDecision::RequireApproval => ToolResult::error(format!(
+                "Tool {} needs approval, which the email channel cannot ask for",
+                name
+            )),
+        }
+    }
+}
+
+/// Check a sender against the allow list (full addresses or `@domain`)
+fn is_sender_allowed(allowed: &[String], sender: &str) -> bool {
+    let sender = sender.to_ascii_lowercase();
+    allowed.iter().any(|allowed| {
+        let allowed = allowed.to_ascii_lowercase();
+        if allowed.starts_with('@') {
+            sender.ends_with(&allowed)
+        } else {
+            sender == allowed
+        }
+    })
+}
+
+/// The user message for an email
+///
+/// The first email of a thread keeps its quoted text as context; later ones
+/// only carry what is new, since the earlier messages are in the session.
+fn user_content(email: &ParsedEmail, is_new: bool) -> String {
+    let sender = match (&email.from_name, &email.from) {
+        (Some(name), Some(address)) => format!("{} <{}>", name, address),
+        (None, Some(address)) => address.clone(),
+        _ => "unknown".to_string(),
+    };
+    let text = if is_new {
+        email.text.clone()
+    } else {
+        let new_text = email.new_text();
+        if new_text.is_empty() { email.text.clone() } else { new_text }
+    };
+    format!("From: {}\nSubject: {}\n\n{}", sender, email.subject, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_sender_allowed() {
+        let allowed = vec!["alice@example.com".to_string(), "@corp.example".to_string()];
+        assert!(is_sender_allowed(&allowed, "Alice@Example.com"));
+        assert!(is_sender_allowed(&allowed, "bob@corp.example"));
+        assert!(!is_sender_allowed(&allowed, "bob@example.com"));
+        assert!(!is_sender_allowed(&allowed, "bob@evilcorp.example"));
+        assert!(!is_sender_allowed(&[], "anyone@example.com"));
+    }
+
+    #[test]
+    fn test_allows_tool() {
+        let config = EmailChannelConfig::default();
+        assert!(config.allows_tool("read", true));
+        assert!(!config.allows_tool("bash", false));
+
+        let config = EmailChannelConfig {
+            tools: Some(vec!["calendar_create".to_string()]),
+            ..Default::default()
+        };
+        assert!(config.allows_tool("calendar_create", false));
+        assert!(!config.allows_tool("read", true));
+    }
+
+    struct Tool(&'static str, bool);
+
+    #[async_trait]
+    impl cc_core::Tool for Tool {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "test"
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn is_read_only(&self) -> bool {
+            self.1
+        }
+
+        async fn execute(&self, _input: serde_json::Value) -> cc_core::Result<ToolResult> {
+            Ok(ToolResult::success("ran"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_apply_tools_and_policy() {
+        let mut tools = ToolManager::new();
+        tools.register(Arc::new(Tool("read", true)));
+        tools.register(Arc::new(Tool("bash", false)));
+        tools.register(Arc::new(Tool("calendar_create", false)));
+        let call = |name: &str| ToolCall {
+            id: "1".to_string(),
+            name: name.to_string(),
+            input: serde_json::json!({}),
+        };
+
+        let config = EmailChannelConfig::default();
+        let hooks = EmailHooks { config: &config };
+        assert_eq!(hooks.on_tool_call(&call("read"), &tools).await.output, "ran");
+        let result = hooks.on_tool_call(&call("bash"), &tools).await;
+        assert!(result.is_error);
+        assert!(result.output.contains("not available"));
+
+        let config = EmailChannelConfig {
+            tools: Some(vec!["bash".to_string(), "calendar_create".to_string()]),
+            tool_policy: ToolPolicy {
+                plan_mode: true,
+                ..ToolPolicy::default()
+            },
+            ..Default::default()
+        };
+        let hooks = EmailHooks { config: &config };
+        let result = hooks.on_tool_call(&call("bash"), &tools).await;
+        assert!(result.output.contains("needs approval"));
+        let result = hooks.on_tool_call(&call("calendar_create"), &tools).await;
+        assert!(result.output.contains("needs approval"));
+    }
+
+    #[test]
+    fn test_user_content() {
+        let email = ParsedEmail {
+            from: Some("alice@example.com".to_string()),
+            from_name: Some("Alice".to_string()),
+            subject: "Re: Plans".to_string(),
+            text: "Yes\n\nOn Monday, Bot wrote:\n> Shall we?".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            user_content(&email, true),
+            "From: Alice <alice@example.com>\nSubject: Re: Plans\n\nYes\n\nOn Monday, Bot wrote:\n> Shall we?"
+        );
+        assert_eq!(
+            user_content(&email, false),
+            "From: Alice <alice@example.com>\nSubject: Re: Plans\n\nYes"
+        );
+    }
+}
@@ -17,6 +17,9 @@ pub enum EmailError {
     #[error("IMAP connection error: {0}")]
     ImapConnection(String),
 
+    #[error("IMAP command failed: {0}")]
+    ImapCommand(String),
+
     #[error("Email parsing error: {0}")]
     Parsing(String),
 
@@ -31,6 +34,12 @@ pub enum EmailError {
 
     #[error("Authentication failed: {0}")]
     AuthFailed(String),
+
+    #[error("Refusing to send credentials without TLS: {0}")]
+    InsecureAuth(String),
+
+    #[error("OAuth error: {0}")]
+    OAuth(String),
 }
 
 /// Result type alias
@@ -0,0 +1,638 @@
+//! Minimal IMAP4rev1 client
+//!
+//! Covers what the email channel and tools need: LOGIN, AUTHENTICATE XOAUTH2,
+//! LIST, SELECT, UID SEARCH, UID FETCH, UID STORE, UID MOVE and IDLE
+//! (RFC 2177).
+
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufStream};
+use tracing::{debug, warn};
+
+use crate::error::{EmailError, Result};
+use crate::oauth::xoauth2;
+use crate::receive::ImapConfig;
+use crate::transport::{MailStream, is_implicit_tls};
+
+/// An untagged server response
+///
+/// Literals (`{n}` followed by n bytes) stay as `{n}` in `line`; their bytes
+/// are kept in `literals` in order.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Response {
+    pub line: String,
+    pub literals: Vec<Vec<u8>>,
+}
+
+/// State of a selected mailbox
+#[derive(Debug, Clone, Default)]
+pub struct Mailbox {
+    /// Number of messages
+    pub exists: u32,
+    pub uid_validity: Option<u32>,
+    pub uid_next: Option<u32>,
+}
+
+/// A message fetched with `UID FETCH`
+#[derive(Debug, Clone)]
+pub struct FetchedMessage {
+    pub uid: u32,
+    pub flags: Vec<String>,
+    /// The fetched body section (the whole RFC 5322 message or its header)
+    pub raw: Vec<u8>,
+}
+
+/// A folder returned by `LIST`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Folder {
+    pub name: String,
+    /// Attributes such as `\HasChildren`, `\Sent` or `\Trash`
+    pub attributes: Vec<String>,
+    /// Hierarchy delimiter (e.g. `/` or `.`)
+    pub delimiter: Option<String>,
+}
+
+impl Folder {
+    /// Whether the folder cannot be selected (a pure container)
+    pub fn is_selectable(&self) -> bool {
+        !self
+            .attributes
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case("\\Noselect") || a.eq_ignore_ascii_case("\\NonExistent"))
+    }
+}
+
+/// Why [`ImapSession::idle`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleEvent {
+    /// The mailbox has new messages
+    NewMail,
+    /// Nothing happened before the timeout
+    Timeout,
+}
+
+/// An authenticated IMAP connection
+pub struct ImapSession {
+    stream: BufStream<MailStream>,
+    next_tag: u32,
+    capabilities: Vec<String>,
+}
+
+impl ImapSession {
+    /// Connect and log in
+    pub async fn connect(config: &ImapConfig) -> Result<Self> {
+        let connection_error =
+            |e: std::io::Error| EmailError::ImapConnection(format!("{}: {}", config.imap_host, e));
+
+        let stream = MailStream::connect(
+            &config.imap_host,
+            config.imap_port,
+            is_implicit_tls(config.imap_port),
+        )
+        .await
+        .map_err(connection_error)?;
+
+        let mut session = Self {
+            stream: BufStream::new(stream),
+            next_tag: 1,
+            capabilities: Vec::new(),
+        };
+
+        let greeting = session.read_response().await?;
+        if greeting.line.starts_with("* BYE") {
+            return Err(EmailError::ImapConnection(greeting.line));
+        }
+        session.refresh_capabilities().await?;
+
+        if !session.stream.get_ref().is_tls() {
+            if session.has_capability("STARTTLS") {
+                session.command("STARTTLS").await?;
+                let stream = session
+                    .stream
+                    .into_inner()
+                    .start_tls(&config.imap_host)
+                    .await
+                    .map_err(connection_error)?;
+                session = Self {
+                    stream: BufStream::new(stream),
+                    next_tag: session.next_tag,
+                    capabilities: Vec::new(),
+                };
+                session.refresh_capabilities().await?;
+            } else if config.allow_insecure_auth {
+                warn!("IMAP server {} does not offer TLS", config.imap_host);
+            } else {
+                // Without STARTTLS (or with it stripped on the way) the password would go in cleartext
+                return Err(EmailError::InsecureAuth(format!(
+                    "IMAP server {} does not offer STARTTLS (set IMAP_ALLOW_INSECURE_AUTH=true to log in anyway)",
+                    config.imap_host
+                )));
+            }
+        }
+
+        let login = match config.oauth {
+            Some(ref oauth) => {
+                let token = oauth.access_token().await?;
+                session.authenticate("XOAUTH2", &xoauth2(&config.imap_user, &token)).await
+            }
+            None => {
+                let login = format!("LOGIN {} {}", quote(&config.imap_user), quote(&config.imap_pass));
+                session.command(&login).await.map(|_| ())
+            }
+        };
+        login.map_err(|e| match e {
+            EmailError::ImapCommand(message) => EmailError::AuthFailed(message),
+            other => other,
+        })?;
+        // Servers may announce more capabilities once logged in
+        session.refresh_capabilities().await?;
+
+        debug!("Logged in to {} as {}", config.imap_host, config.imap_user);
+        Ok(session)
+    }
+
+    /// SASL authentication with a single client response
+    async fn authenticate(&mut self, mechanism: &str, response: &str) -> Result<()> {
+        let tag = self.send_command(&format!("AUTHENTICATE {}", mechanism)).await?;
+        let mut responded = false;
+        loop {
+            let reply = self.read_response().await?;
+            if reply.line.starts_with('+') {
+                // The first challenge asks for the response; a later one
+                // carries error details and is answered with an empty line
+                if responded {
+                    self.write_line("").await?;
+                } else {
+                    self.write_line(response).await?;
+                    responded = true;
+                }
+                continue;
+            }
+            match tagged_status(&reply.line, &tag) {
+                Some(status) if starts_with_ignore_case(status, "OK") => return Ok(()),
+                Some(status) => return Err(EmailError::ImapCommand(status.to_string())),
+                None => {}
+            }
+        }
+    }
+
+    async fn refresh_capabilities(&mut self) -> Result<()> {
+        let responses = self.command("CAPABILITY").await?;
+        self.capabilities = responses
+            .iter()
+            .filter_map(|r| r.line.strip_prefix("* CAPABILITY "))
+            .flat_map(|caps| caps.split_whitespace().map(str::to_ascii_uppercase))
+            .collect();
+        Ok(())
+    }
+
+    /// Whether the server announced `capability` (e.g. `IDLE`)
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(capability))
+    }
+
+    /// All folders
+    pub async fn list_folders(&mut self) -> Result<Vec<Folder>> {
+        let responses = self.command("LIST \"\" \"*\"").await?;
+        Ok(responses.iter().filter_map(parse_list).collect())
+    }
+
+    /// Select a folder
+    pub async fn select(&mut self, folder: &str) -> Result<Mailbox> {
+        let responses = self
+            .command(&format!("SELECT {}", quote(folder)))
+            .await
+            .map_err(|e| match e {
+                EmailError::ImapCommand(_) => EmailError::FolderNotFound(folder.to_string()),
+                other => other,
+            })?;
+
+        let mut mailbox = Mailbox::default();
+        for response in &responses {
+            let line = &response.line;
+            if let Some(count) = line
+                .strip_prefix("* ")
+                .and_then(|rest| rest.strip_suffix(" EXISTS"))
+            {
+                mailbox.exists = count.trim().parse().unwrap_or(0);
+            } else if let Some(value) = response_code(line, "UIDVALIDITY") {
+                mailbox.uid_validity = value.parse().ok();
+            } else if let Some(value) = response_code(line, "UIDNEXT") {
+                mailbox.uid_next = value.parse().ok();
+            }
+        }
+        Ok(mailbox)
+    }
+
+    /// UIDs of the messages matching a search `criteria` (e.g. `UNSEEN`)
+    pub async fn uid_search(&mut self, criteria: &str) -> Result<Vec<u32>> {
+        let responses = self.command(&format!("UID SEARCH {}", criteria)).await?;
+        let mut uids: Vec<u32> = responses
+            .iter()
+            .filter_map(|r| r.line.strip_prefix("* SEARCH"))
+            .flat_map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()))
+            .collect();
+        uids.sort_unstable();
+        Ok(uids)
+    }
+
+    /// Fetch the messages in `uid_set` (e.g. `42` or `1:5,9`)
+    ///
+    /// `section` is a body section such as `BODY.PEEK[]` (the whole message,
+    /// without setting `\Seen`) or `BODY.PEEK[HEADER]`.
+    pub async fn uid_fetch(&mut self, uid_set: &str, section: &str) -> Result<Vec<FetchedMessage>> {
+        let responses = self
+            .command(&format!("UID FETCH {} (UID FLAGS {})", uid_set, section))
+            .await?;
+        Ok(responses.iter().filter_map(parse_fetch).collect())
+    }
+
+    /// Add flags (e.g. `\Seen`) to the messages in `uid_set`
+    pub async fn add_flags(&mut self, uid_set: &str, flags: &str) -> Result<()> {
+        self.command(&format!("UID STORE {} +FLAGS.SILENT ({})", uid_set, flags))
+            .await?;
+        Ok(())
+    }
+
+    /// Remove flags from the messages in `uid_set`
+    pub async fn remove_flags(&mut self, uid_set: &str, flags: &str) -> Result<()> {
+        self.command(&format!("UID STORE {} -FLAGS.SILENT ({})", uid_set, flags))
+            .await?;
+        Ok(())
+    }
+
+    /// Move the messages in `uid_set` to another folder
+    ///
+    /// Uses MOVE (RFC 6851) when available, otherwise copies, marks the
+    /// originals `\Deleted` and expunges them.
+    pub async fn move_messages(&mut self, uid_set: &str, destination: &str) -> Result<()> {
+        let destination = quote(destination);
+        if self.has_capability("MOVE") {
+            self.command(&format!("UID MOVE {} {}", uid_set, destination))
+                .await?;
+            return Ok(());
+        }
+
+        self.command(&format!("UID COPY {} {}", uid_set, destination))
+            .await?;
+        self.add_flags(uid_set, "\\Deleted").await?;
+        if self.has_capability("UIDPLUS") {
+            self.command(&format!("UID EXPUNGE {}", uid_set)).await?;
+        } else {
+            // Also removes other messages already marked \Deleted
+            self.command("EXPUNGE").await?;
+        }
+        Ok(())
+    }
+
+    /// Wait for new messages in the selected folder
+    ///
+    /// Uses IDLE, so the server pushes changes; call again after
+    /// [`IdleEvent::Timeout`] (servers drop idle connections after 30 minutes).
+    pub async fn idle(&mut self, timeout: Duration) -> Result<IdleEvent> {
+        let tag = self.send_command("IDLE").await?;
+
+        // Wait for the continuation request
+        loop {
+            let response = self.read_response().await?;
+            if response.line.starts_with('+') {
+                break;
+            }
+            if let Some(status) = tagged_status(&response.line, &tag) {
+                return Err(EmailError::ImapCommand(status.to_string()));
+            }
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut event = IdleEvent::Timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match tokio::time::timeout(remaining, self.read_response()).await {
+                Ok(response) => {
+                    if response?.line.ends_with(" EXISTS") {
+                        event = IdleEvent::NewMail;
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+
+        self.write_line("DONE").await?;
+        self.read_until_tagged(&tag).await?;
+        Ok(event)
+    }
+
+    /// Log out (errors are ignored; the connection is closed either way)
+    pub async fn logout(mut self) {
+        if let Err(e) = self.command("LOGOUT").await {
+            debug!("IMAP logout failed: {}", e);
+        }
+    }
+
+    /// Run a command and return its untagged responses
+    pub(crate) async fn command(&mut self, command: &str) -> Result<Vec<Response>> {
+        let tag = self.send_command(command).await?;
+        self.read_until_tagged(&tag).await
+    }
+
+    async fn send_command(&mut self, command: &str) -> Result<String> {
+        let tag = format!("A{:04}", self.next_tag);
+        self.next_tag += 1;
+        let verb = command.split(' ').next().unwrap_or_default();
+        debug!("IMAP > {} {}", tag, if verb == "LOGIN" { "LOGIN ***" } else { command });
+        self.write_line(&format!("{} {}", tag, command)).await?;
+        Ok(tag)
+    }
+
+    async fn read_until_tagged(&mut self, tag: &str) -> Result<Vec<Response>> {
+        let mut untagged = Vec::new();
+        loop {
+            let response = self.read_response().await?;
+            match tagged_status(&response.line, tag) {
+                Some(status) if starts_with_ignore_case(status, "OK") => return Ok(untagged),
+                Some(status) => return Err(EmailError::ImapCommand(status.to_string())),
+                None => untagged.push(response),
+            }
+        }
+    }
+
+    async fn write_line(&mut self, line: &str) -> Result<()> {
+        let io_error = |e: std::io::Error| EmailError::ImapConnection(e.to_string());
+        self.stream
+            .write_all(format!("{}\r\n", line).as_bytes())
+            .await
+            .map_err(io_error)?;
+        self.stream.flush().await.map_err(io_error)
+    }
+
+    /// Read one response, including the literals it contains
+    async fn read_response(&mut self) -> Result<Response> {
+        let io_error = |e: std::io::Error| EmailError::ImapConnection(e.to_string());
+        let mut response = Response::default();
+        loop {
+            let mut buf = Vec::new();
+            let read = self.stream.read_until(b'\n', &mut buf).await.map_err(io_error)?;
+            if read == 0 {
+                return Err(EmailError::ImapConnection(
+                    "Connection closed by server".to_string(),
+                ));
+            }
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches(['\r', '\n']);
+            response.line.push_str(line);
+
+            let Some(size) = literal_size(line) else {
+                return Ok(response);
+            };
+            let mut literal = vec![0; size];
+            self.stream.read_exact(&mut literal).await.map_err(io_error)?;
+            response.literals.push(literal);
+        }
+    }
+}
+
+/// Quote a string for use as an IMAP astring
+pub(crate) fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The size of the literal announced at the end of `line` (`{123}` or `{123+}`)
+fn literal_size(line: &str) -> Option<usize> {
+    let inner = line.strip_suffix('}')?;
+    let start = inner.rfind('{')?;
+    inner[start + 1..].trim_end_matches('+').parse().ok()
+}
+
+/// Status text of the tagged response for `tag` (`OK ...`, `NO ...`, `BAD ...`)
+fn tagged_status<'a>(line: &'a str, tag: &str) -> Option<&'a str> {
+    line.strip_prefix(tag)?.strip_prefix(' ')
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.len() >= prefix.len() && text[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+/// Value of a response code such as `* OK [UIDVALIDITY 123] ...`
+fn response_code<'a>(line: &'a str, code: &str) -> Option<&'a str> {
+    let start = line.find(&format!("[{} ", code))? + code.len() + 2;
+    let end = line[start..].find(']')? + start;
+    Some(line[start..end].trim())
+}
+
+/// Parse a `* LIST (\\HasNoChildren) "/" "INBOX"` response
+fn parse_list(response: &Response) -> Option<Folder> {
+    let rest = response.line.strip_prefix("* LIST ")?;
+    let rest = rest.strip_prefix('(')?;
+    let (attributes, rest) = rest.split_once(')')?;
+    let rest = rest.trim_start();
+
+    let (delimiter, rest) = if let Some(quoted) = rest.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (Some(quoted[..end].replace('\\', "")), &quoted[end + 1..])
+    } else {
+        // NIL: flat hierarchy
+        (None, rest.get(3..)?)
+    };
+
+    let name = rest.trim();
+    let name = if let Some(literal) = response.literals.first().filter(|_| name.ends_with('}')) {
+        String::from_utf8_lossy(literal).into_owned()
+    } else if let Some(quoted) = name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+        quoted.replace("\\\"", "\"").replace("\\\\", "\\")
+    } else {
+        name.to_string()
+    };
+
+    Some(Folder {
+        name,
+        attributes: attributes.split_whitespace().map(str::to_string).collect(),
+        delimiter,
+    })
+}
+
+/// Parse a `* n FETCH (UID .. FLAGS (..) BODY[] {size})` response
+fn parse_fetch(response: &Response) -> Option<FetchedMessage> {
+    let line = &response.line;
+    if !line.starts_with("* ") || !line.contains(" FETCH (") {
+        return None;
+    }
+
+    let uid = line
+        .split_whitespace()
+        .skip_while(|word| !word.trim_start_matches('(').eq_ignore_ascii_case("UID"))
+        .nth(1)
+        .and_then(|uid| uid.trim_end_matches(')').parse().ok())?;
+
+    let flags = line
+        .find("FLAGS (")
+        .and_then(|start| {
+            let rest = &line[start + 7..];
+            rest.find(')').map(|end| &rest[..end])
+        })
+        .map(|flags| flags.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default();
+
+    Some(FetchedMessage {
+        uid,
+        flags,
+        raw: response.literals.first().cloned().unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Serve one IMAP connection, answering each tagged command from `script`
+    async fn fake_server(script: Vec<(&'static str, &'static str)>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (read, mut write) = socket.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"* OK fake IMAP ready\r\n").await.unwrap();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let (tag, command) = line.split_once(' ').unwrap_or((&line, ""));
+                let reply = script
+                    .iter()
+                    .find(|(prefix, _)| command.starts_with(prefix))
+                    .map(|(_, reply)| *reply)
+                    .unwrap_or("");
+                let reply = reply.replace("TAG", tag);
+                write.write_all(reply.as_bytes()).await.unwrap();
+                if command == "IDLE" {
+                    // Wait for DONE
+                    let _ = lines.next_line().await;
+                    write
+                        .write_all(format!("{} OK IDLE done\r\n", tag).as_bytes())
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+        port
+    }
+
+    fn config(port: u16) -> ImapConfig {
+        ImapConfig {
+            imap_host: "127.0.0.1".to_string(),
+            imap_port: port,
+            imap_user: "bot@example.com".to_string(),
+            imap_pass: "secret".to_string(),
+            oauth: None,
+            allow_insecure_auth: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_select_search_fetch_and_idle() {
+        let port = fake_server(vec![
+            ("CAPABILITY", "* CAPABILITY IMAP4rev1 IDLE\r\nTAG OK done\r\n"),
+            ("LOGIN", "TAG OK logged in\r\n"),
+            (
+                "SELECT",
+                "* 3 EXISTS\r\n* OK [UIDVALIDITY 7] ok\r\n* OK [UIDNEXT 12] ok\r\nTAG OK [READ-WRITE] done\r\n",
+            ),
+            ("UID SEARCH", "* SEARCH 11 9\r\nTAG OK done\r\n"),
+            (
+                "UID FETCH",
+                "* 3 FETCH (UID 11 FLAGS (\\Recent) BODY[] {9}\r\nSubject: )\r\nTAG OK done\r\n",
+            ),
+            ("UID STORE", "TAG OK done\r\n"),
+            ("IDLE", "+ idling\r\n* 4 EXISTS\r\n"),
+            ("LOGOUT", "* BYE\r\nTAG OK bye\r\n"),
+        ])
+        .await;
+
+        let mut session = ImapSession::connect(&config(port)).await.unwrap();
+        assert!(session.has_capability("idle"));
+
+        let mailbox = session.select("INBOX").await.unwrap();
+        assert_eq!(mailbox.exists, 3);
+        assert_eq!(mailbox.uid_validity, Some(7));
+        assert_eq!(mailbox.uid_next, Some(12));
+
+        assert_eq!(session.uid_search("UNSEEN").await.unwrap(), vec![9, 11]);
+
+        let messages = session.uid_fetch("11", "BODY.PEEK[]").await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].uid, 11);
+        assert_eq!(messages[0].flags, vec!["\\Recent"]);
+        assert_eq!(messages[0].raw, b"Subject: ");
+
+        session.add_flags("11", "\\Seen").await.unwrap();
+        let event = session.idle(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(event, IdleEvent::NewMail);
+        session.logout().await;
+    }
+
+    #[tokio::test]
+    async fn test_login_failure() {
+        let port = fake_server(vec![
+            ("CAPABILITY", "* CAPABILITY IMAP4rev1\r\nTAG OK done\r\n"),
+            ("LOGIN", "TAG NO [AUTHENTICATIONFAILED] invalid credentials\r\n"),
+        ])
+        .await;
+
+        let result = ImapSession::connect(&config(port)).await;
+        assert!(matches!(result, Err(EmailError::AuthFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_no_login_without_tls() {
+        let port = fake_server(vec![
+            ("CAPABILITY", "* CAPABILITY IMAP4rev1 IDLE\r\nTAG OK done\r\n"),
+            ("LOGOUT", "* BYE\r\nTAG OK bye\r\n"),
+        ])
+        .await;
+        let config = ImapConfig {
+            allow_insecure_auth: false,
+            ..config(port)
+        };
+
+        let result = ImapSession::connect(&config).await;
+        assert!(matches!(result, Err(EmailError::InsecureAuth(_))));
+    }
+
+    #[test]
+    fn test_response_helpers() {
+        assert_eq!(literal_size("* 1 FETCH (BODY[] {42}"), Some(42));
+        assert_eq!(literal_size("* 1 FETCH (BODY[] {42+}"), Some(42));
+        assert_eq!(literal_size("* OK done"), None);
+        assert_eq!(tagged_status("A0001 NO failed", "A0001"), Some("NO failed"));
+        assert_eq!(tagged_status("A00012 OK", "A0001"), None);
+        assert_eq!(quote(r#"pa"ss\"#), r#""pa\"ss\\""#);
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let folder = |line: &str| {
+            parse_list(&Response {
+                line: line.to_string(),
+                literals: Vec::new(),
+            })
+        };
+        assert_eq!(
+            folder(r#"* LIST (\HasNoChildren \Sent) "/" "[Gmail]/Sent Mail""#),
+            Some(Folder {
+                name: "[Gmail]/Sent Mail".to_string(),
+                attributes: vec!["\\HasNoChildren".to_string(), "\\Sent".to_string()],
+                delimiter: Some("/".to_string()),
+            })
+        );
+        let inbox = folder(r#"* LIST () "." INBOX"#).unwrap();
+        assert_eq!(inbox.name, "INBOX");
+        assert!(inbox.is_selectable());
+        let container = folder(r#"* LIST (\Noselect) NIL Archive"#).unwrap();
+        assert_eq!(container.delimiter, None);
+        assert_eq!(container.name, "Archive");
+        assert!(!container.is_selectable());
+        assert!(folder("* FLAGS (\\Seen)").is_none());
+    }
+}
@@ -1,13 +1,27 @@
 //! cc-email: Email tools for cc-gateway
 //!
-//! This crate provides email sending and receiving capabilities.
+//! This crate provides email sending and receiving capabilities, and an
+//! IMAP IDLE based channel that answers incoming mail with the agent.
 
+pub mod channel;
 pub mod error;
+pub mod imap;
+pub mod message;
+pub mod oauth;
 pub mod receive;
 pub mod send;
+mod smtp;
 pub mod tools;
+mod transport;
 
+pub use channel::{EmailChannel, EmailChannelConfig};
 pub use error::{EmailError, Result};
-pub use receive::EmailReceiver;
+pub use imap::{Folder, ImapSession};
+pub use message::{Attachment, CalendarPart, OutgoingEmail, ParsedEmail};
+pub use oauth::{OAuth2, OAuthProvider};
+pub use receive::{EmailReceiver, FlagAction, ImapConfig, SearchCriteria};
 pub use send::EmailSender;
-pub use tools::{EmailListTool, EmailReadTool, EmailSendTool};
+pub use tools::{
+    EmailFlagTool, EmailFoldersTool, EmailListTool, EmailMoveTool, EmailReadTool,
+    EmailSearchTool, EmailSendTool, register_email_tools,
+};
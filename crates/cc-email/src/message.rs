@@ -0,0 +1,1077 @@
+//! Email message parsing and composition
+//!
+//! Parses the parts of RFC 5322 / MIME messages the gateway needs (threading
+//! headers, sender, subject and a plain-text body) and builds outgoing
+//! messages with correct reply headers.
+
+use base64::Engine;
+use chrono::Utc;
+
+/// An incoming email
+#[derive(Debug, Clone, Default)]
+pub struct ParsedEmail {
+    /// `Message-ID` including the angle brackets
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    /// `References`, oldest first
+    pub references: Vec<String>,
+    /// Sender address
+    pub from: Option<String>,
+    /// Sender display name
+    pub from_name: Option<String>,
+    /// `Reply-To` address, if different from the sender
+    pub reply_to: Option<String>,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub date: Option<String>,
+    /// Plain-text body (HTML-only messages are converted)
+    pub text: String,
+    /// Sent by an auto-responder or mailing list (never answered)
+    pub automated: bool,
+    /// `Authentication-Results` values (RFC 8601), topmost first
+    pub authentication_results: Vec<String>,
+    /// iCalendar object of a meeting invitation or reply (iMIP, RFC 6047)
+    pub calendar: Option<String>,
+}
+
+impl ParsedEmail {
+    /// Parse a raw RFC 5322 message
+    pub fn parse(raw: &[u8]) -> Self {
+        let (headers, body) = split_message(raw);
+        let header = |name: &str| header_value(&headers, name).map(|v| decode_words(v.trim()));
+
+        let (from_name, from) = header("from")
+            .map(|v| parse_address(&v))
+            .unwrap_or((None, None));
+        let reply_to = header("reply-to")
+            .and_then(|v| parse_address(&v).1)
+            .filter(|addr| Some(addr) != from.as_ref());
+
+        let automated = header("auto-submitted").is_some_and(|v| !v.eq_ignore_ascii_case("no"))
+            || header("precedence").is_some_and(|v| {
+                matches!(v.to_ascii_lowercase().as_str(), "bulk" | "list" | "junk")
+            })
+            || header("list-id").is_some();
+        let authentication_results = headers
+            .iter()
+            .filter(|(name, _)| name == "authentication-results")
+            .map(|(_, value)| value.clone())
+            .collect();
+
+        Self {
+            message_id: header("message-id").and_then(|v| message_ids(&v).into_iter().next()),
+            in_reply_to: header("in-reply-to").and_then(|v| message_ids(&v).into_iter().next()),
+            references: header("references").map(|v| message_ids(&v)).unwrap_or_default(),
+            from,
+            from_name,
+            reply_to,
+            to: header("to")
+                .map(|v| split_addresses(&v).iter().filter_map(|a| parse_address(a).1).collect())
+                .unwrap_or_default(),
+            subject: header("subject").unwrap_or_default(),
+            date: header("date"),
+            text: body_text(&headers, body),
+            automated,
+            authentication_results,
+            calendar: calendar_part(&headers, body),
+        }
+    }
+
+    /// Whether the server `authserv_id` found that the `From` domain passed DMARC
+    ///
+    /// Only results added by that server count (RFC 8601 §5): the topmost
+    /// header with its authserv-id. Headers of other ids came with the
+    /// message or from elsewhere and can be forged.
+    pub fn dmarc_passed(&self, authserv_id: &str) -> bool {
+        let Some(from) = self.from.as_deref() else {
+            return false;
+        };
+        self.authentication_results
+            .iter()
+            .find(|results| authserv_id_of(results).eq_ignore_ascii_case(authserv_id))
+            .is_some_and(|results| dmarc_passed(results, from))
+    }
+
+    /// Identifier shared by all messages of a conversation
+    ///
+    /// The first message of the thread (the root of `References`), falling
+    /// back to this message's own ID.
+    pub fn thread_id(&self) -> String {
+        self.references
+            .first()
+            .or(self.in_reply_to.as_ref())
+            .or(self.message_id.as_ref())
+            .cloned()
+            .unwrap_or_else(|| {
+                format!(
+                    "{}|{}",
+                    self.from.as_deref().unwrap_or_default(),
+                    strip_reply_prefix(&self.subject)
+                )
+            })
+    }
+
+    /// Address replies go to
+    pub fn reply_address(&self) -> Option<&str> {
+        self.reply_to.as_deref().or(self.from.as_deref())
+    }
+
+    /// Subject for a reply (`Re: ` is added once)
+    pub fn reply_subject(&self) -> String {
+        format!("Re: {}", strip_reply_prefix(&self.subject))
+    }
+
+    /// `References` for a reply: this message's references plus its own ID
+    pub fn reply_references(&self) -> Vec<String> {
+        let mut references = self.references.clone();
+        if references.is_empty() {
+            references.extend(self.in_reply_to.clone());
+        }
+        references.extend(self.message_id.clone());
+        references
+    }
+
+    /// The body without quoted earlier messages
+    pub fn new_text(&self) -> String {
+        strip_quoted(&self.text)
+    }
+}
+
+/// An email to send
+#[derive(Debug, Clone, Default)]
+pub struct OutgoingEmail {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    /// Recipients that are not listed in the headers
+    pub bcc: Vec<String>,
+    pub reply_to: Option<String>,
+    pub subject: String,
+    /// Plain-text body
+    pub body: String,
+    /// HTML body; `body` is sent as its plain-text alternative
+    pub html_body: Option<String>,
+    pub attachments: Vec<Attachment>,
+    /// Meeting invitation or reply sent along with the text (iMIP)
+    pub calendar: Option<CalendarPart>,
+    /// `Message-ID` of the message being answered
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+}
+
+impl OutgoingEmail {
+    pub fn new(to: impl Into<String>, subject: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            to: vec![to.into()],
+            subject: subject.into(),
+            body: body.into(),
+            ..Default::default()
+        }
+    }
+
+    /// A reply to `original`, threaded with `In-Reply-To` / `References`
+    ///
+    /// Returns `None` if the original has no sender address.
+    pub fn reply(original: &ParsedEmail, body: impl Into<String>) -> Option<Self> {
+        Some(Self {
+            to: vec![original.reply_address()?.to_string()],
+            subject: original.reply_subject(),
+            body: body.into(),
+            in_reply_to: original.message_id.clone(),
+            references: original.reply_references(),
+            ..Default::default()
+        })
+    }
+
+    /// Add an HTML body (without a plain-text body one is derived from it)
+    pub fn html(mut self, html: impl Into<String>) -> Self {
+        self.html_body = Some(html.into());
+        self
+    }
+
+    pub fn cc(mut self, address: impl Into<String>) -> Self {
+        self.cc.push(address.into());
+        self
+    }
+
+    pub fn bcc(mut self, address: impl Into<String>) -> Self {
+        self.bcc.push(address.into());
+        self
+    }
+
+    pub fn reply_to(mut self, address: impl Into<String>) -> Self {
+        self.reply_to = Some(address.into());
+        self
+    }
+
+    pub fn attach(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Add an iCalendar object with its iTIP method (`REQUEST`, `REPLY`, `CANCEL`)
+    pub fn calendar(mut self, method: impl Into<String>, ics: impl Into<String>) -> Self {
+        self.calendar = Some(CalendarPart {
+            method: method.into(),
+            data: ics.into(),
+        });
+        self
+    }
+
+    /// Every envelope recipient (To, Cc and Bcc)
+    pub fn recipients(&self) -> Vec<String> {
+        self.to
+            .iter()
+            .chain(&self.cc)
+            .chain(&self.bcc)
+            .cloned()
+            .collect()
+    }
+
+    /// Render the message (CRLF line endings) with the given sender and `Message-ID`
+    ///
+    /// Bcc recipients are left out of the headers.
+    pub fn to_rfc5322(&self, from: &str, from_name: Option<&str>, message_id: &str) -> String {
+        let from = match from_name {
+            Some(name) if !name.is_empty() => format!("{} <{}>", encode_header(name), from),
+            _ => from.to_string(),
+        };
+        let mut headers = vec![
+            format!("From: {}", from),
+            format!("To: {}", self.to.join(", ")),
+        ];
+        if !self.cc.is_empty() {
+            headers.push(format!("Cc: {}", self.cc.join(", ")));
+        }
+        if let Some(ref reply_to) = self.reply_to {
+            headers.push(format!("Reply-To: {}", reply_to));
+        }
+        headers.push(format!("Subject: {}", encode_header(&self.subject)));
+        headers.push(format!("Date: {}", Utc::now().to_rfc2822()));
+        headers.push(format!("Message-ID: {}", message_id));
+        if let Some(ref in_reply_to) = self.in_reply_to {
+            headers.push(format!("In-Reply-To: {}", in_reply_to));
+        }
+        if !self.references.is_empty() {
+            headers.push(format!("References: {}", self.references.join("\r\n ")));
+        }
+        headers.push("MIME-Version: 1.0".to_string());
+
+        let body = self.mime_body();
+        headers.extend(body.headers);
+        format!("{}\r\n\r\n{}", headers.join("\r\n"), body.body)
+    }
+
+    /// The MIME tree: mixed(related(alternative(text, html, calendar), inline), attachments)
+    fn mime_body(&self) -> MimePart {
+        let text = match (&self.html_body, self.body.is_empty()) {
+            (Some(html), true) => html_to_text(html),
+            _ => self.body.clone(),
+        };
+        let mut body = MimePart::leaf("text/plain; charset=utf-8", Vec::new(), text.as_bytes());
+
+        let mut alternatives = Vec::new();
+        if let Some(ref html) = self.html_body {
+            alternatives.push(MimePart::leaf("text/html; charset=utf-8", Vec::new(), html.as_bytes()));
+        }
+        if let Some(ref calendar) = self.calendar {
+            let content_type = format!("text/calendar; method={}; charset=utf-8", calendar.method.to_ascii_uppercase());
+            alternatives.push(MimePart::leaf(&content_type, Vec::new(), calendar.data.as_bytes()));
+        }
+        if !alternatives.is_empty() {
+            alternatives.insert(0, body);
+            body = MimePart::multipart("alternative", alternatives);
+        }
+
+        let (inline, attached): (Vec<&Attachment>, Vec<&Attachment>) = self
+            .attachments
+            .iter()
+            .partition(|a| a.content_id.is_some() && self.html_body.is_some());
+        if !inline.is_empty() {
+            let mut parts = vec![body];
+            parts.extend(inline.into_iter().map(Attachment::to_part));
+            body = MimePart::multipart("related", parts);
+        }
+        if !attached.is_empty() {
+            let mut parts = vec![body];
+            parts.extend(attached.into_iter().map(Attachment::to_part));
+            body = MimePart::multipart("mixed", parts);
+        }
+        body
+    }
+}
+
+/// An iCalendar object carried in an email (iMIP)
+#[derive(Debug, Clone, Default)]
+pub struct CalendarPart {
+    /// iTIP method, also set as the `method` content type parameter
+    pub method: String,
+    /// The iCalendar text
+    pub data: String,
+}
+
+/// A file attached to an outgoing email
+#[derive(Debug, Clone, Default)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+    /// Content-ID of an inline image, referenced as `cid:<id>` in the HTML body
+    pub content_id: Option<String>,
+}
+
+impl Attachment {
+    pub fn new(
+        filename: impl Into<String>,
+        content_type: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            filename: filename.into(),
+            content_type: content_type.into(),
+            data: data.into(),
+            content_id: None,
+        }
+    }
+
+    /// Read a file, guessing its content type from the extension
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "attachment".to_string());
+        let content_type = content_type_for(&filename);
+        Ok(Self::new(filename, content_type, data))
+    }
+
+    /// Show inline in the HTML body as `cid:<content_id>`
+    pub fn inline(mut self, content_id: impl Into<String>) -> Self {
+        self.content_id = Some(content_id.into());
+        self
+    }
+
+    fn to_part(&self) -> MimePart {
+        let filename = encode_header(&self.filename).replace('"', "");
+        let mut headers = Vec::new();
+        match self.content_id {
+            Some(ref id) => {
+                headers.push(format!("Content-ID: <{}>", id.trim_matches(['<', '>'])));
+                headers.push(format!("Content-Disposition: inline; filename=\"{}\"", filename));
+            }
+            None => {
+                headers.push(format!("Content-Disposition: attachment; filename=\"{}\"", filename));
+            }
+        }
+        let content_type = format!("{}; name=\"{}\"", self.content_type, filename);
+        MimePart::leaf(&content_type, headers, &self.data)
+    }
+}
+
+/// Content type for a file name, by extension
+pub fn content_type_for(filename: &str) -> &'static str {
+    let extension = filename
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "md" => "text/markdown",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "ics" => "text/calendar",
+        "vcf" => "text/vcard",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A rendered MIME entity: its headers and its encoded body
+struct MimePart {
+    headers: Vec<String>,
+    body: String,
+}
+
+impl MimePart {
+    /// A single part, base64 encoded
+    fn leaf(content_type: &str, extra_headers: Vec<String>, data: &[u8]) -> Self {
+        let mut headers = vec![
+            format!("Content-Type: {}", content_type),
+            "Content-Transfer-Encoding: base64".to_string(),
+        ];
+        headers.extend(extra_headers);
+        Self {
+            headers,
+            body: base64_lines(data),
+        }
+    }
+
+    /// A `multipart/<subtype>` container
+    fn multipart(subtype: &str, parts: Vec<MimePart>) -> Self {
+        let boundary = format!("=_{}", uuid::Uuid::new_v4().simple());
+        let mut body = String::new();
+        for part in parts {
+            body.push_str(&format!(
+                "--{}\r\n{}\r\n\r\n{}\r\n",
+                boundary,
+                part.headers.join("\r\n"),
+                part.body
+            ));
+        }
+        body.push_str(&format!("--{}--", boundary));
+        Self {
+            headers: vec![format!(
+                "Content-Type: multipart/{}; boundary=\"{}\"",
+                subtype, boundary
+            )],
+            body,
+        }
+    }
+}
+
+/// Create a new `Message-ID` for a sender address
+pub fn new_message_id(from: &str) -> String {
+    let domain = from.rsplit_once('@').map_or("localhost", |(_, d)| d);
+    format!("<{}@{}>", uuid::Uuid::new_v4().simple(), domain)
+}
+
+/// Remove any number of `Re:` / `Fwd:` style prefixes
+fn strip_reply_prefix(subject: &str) -> &str {
+    let mut subject = subject.trim();
+    loop {
+        let lower = subject.to_ascii_lowercase();
+        let prefix = ["re:", "aw:", "sv:", "fwd:", "fw:"]
+            .iter()
+            .find(|p| lower.starts_with(*p));
+        match prefix {
+            Some(p) => subject = subject[p.len()..].trim_start(),
+            None => return subject,
+        }
+    }
+}
+
+// ==================== Headers ====================
+
+/// Unfolded headers with lowercase names, in message order
+type Headers = Vec<(String, String)>;
+
+/// Split a message into unfolded headers and the body
+fn split_message(raw: &[u8]) -> (Headers, &[u8]) {
+    let (head, body) = match find(raw, b"\r\n\r\n") {
+        Some(i) => (&raw[..i], &raw[i + 4..]),
+        None => match find(raw, b"\n\n") {
+            Some(i) => (&raw[..i], &raw[i + 2..]),
+            None => (raw, &raw[raw.len()..]),
+        },
+    };
+
+    let mut headers: Headers = Vec::new();
+    for line in String::from_utf8_lossy(head).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+/// The authserv-id of an `Authentication-Results` value (before the first `;`,
+/// without the optional version)
+fn authserv_id_of(results: &str) -> &str {
+    results
+        .split(';')
+        .next()
+        .and_then(|id| id.split_whitespace().next())
+        .unwrap_or_default()
+}
+
+/// Whether an `Authentication-Results` value (RFC 8601) reports a DMARC
+/// pass for the domain of `from`
+fn dmarc_passed(results: &str, from: &str) -> bool {
+    let domain = from.rsplit_once('@').map(|(_, d)| d).unwrap_or_default();
+    results.split(';').skip(1).any(|result| {
+        let mut words = result.split_whitespace().map(|w| w.to_ascii_lowercase());
+        if words.next().as_deref() != Some("dmarc=pass") {
+            return false;
+        }
+        // header.from names the domain that was checked
+        words
+            .find_map(|w| w.strip_prefix("header.from=").map(str::to_string))
+            .is_none_or(|checked| checked.eq_ignore_ascii_case(domain))
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Split a header value like `text/plain; charset="utf-8"` into the value
+/// and its lowercase-named parameters
+fn parse_params(value: &str) -> (String, Vec<(String, String)>) {
+    let mut parts = value.split(';');
+    let main = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().trim_matches('"').to_string()))
+        .collect();
+    (main, params)
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+/// All `<...>` message IDs in a header value
+fn message_ids(value: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        ids.push(rest[start..start + len + 1].to_string());
+        rest = &rest[start + len + 1..];
+    }
+    ids
+}
+
+/// Split an address list on commas outside quotes and angle brackets
+fn split_addresses(value: &str) -> Vec<String> {
+    let mut addresses = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut angle = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '<' if !quoted => angle = true,
+            '>' if !quoted => angle = false,
+            ',' if !quoted && !angle => {
+                addresses.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    addresses.push(current);
+    addresses
+        .into_iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+/// Split `Name <addr@example.com>` into the display name and the address
+fn parse_address(value: &str) -> (Option<String>, Option<String>) {
+    let value = value.trim();
+    match (value.rfind('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => {
+            let name = value[..start].trim().trim_matches('"').trim();
+            let address = value[start + 1..end].trim();
+            (
+                (!name.is_empty()).then(|| name.to_string()),
+                (!address.is_empty()).then(|| address.to_string()),
+            )
+        }
+        _ => (None, value.contains('@').then(|| value.to_string())),
+    }
+}
+
+/// Decode RFC 2047 encoded words (`=?utf-8?B?...?=`)
+pub(crate) fn decode_words(value: &str) -> String {
+    let mut output = String::new();
+    let mut rest = value;
+    let mut last_was_encoded = false;
+
+    while let Some(start) = rest.find("=?") {
+        let decoded = encoded_word(&rest[start..]);
+        let Some((text, len)) = decoded else {
+            output.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            last_was_encoded = false;
+            continue;
+        };
+        let between = &rest[..start];
+        // Whitespace between adjacent encoded words is not part of the text
+        if !(last_was_encoded && between.trim().is_empty()) {
+            output.push_str(between);
+        }
+        output.push_str(&text);
+        rest = &rest[start + len..];
+        last_was_encoded = true;
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Decode one encoded word at the start of `input`; returns the text and its length
+fn encoded_word(input: &str) -> Option<(String, usize)> {
+    let inner = input.strip_prefix("=?")?;
+    let (charset, rest) = inner.split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let end = rest.find("?=")?;
+    let text = &rest[..end];
+    let len = 2 + charset.len() + 1 + encoding.len() + 1 + end + 2;
+
+    let bytes = match encoding.to_ascii_uppercase().as_str() {
+        "B" => base64::engine::general_purpose::STANDARD
+            .decode(text.trim_end_matches('='))
+            .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(text.trim_end_matches('=')))
+            .ok()?,
+        "Q" => decode_quoted_printable(&text.replace('_', " ")),
+        _ => return None,
+    };
+    Some((decode_charset(&bytes, charset), len))
+}
+
+/// Encode a header value as RFC 2047 words if it is not plain ASCII
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    // Keep each encoded word within the 75 character limit
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in value.chars() {
+        if chunk.len() + c.len_utf8() > 45 {
+            words.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(c);
+    }
+    words.push(chunk);
+    words
+        .iter()
+        .map(|w| {
+            format!(
+                "=?utf-8?B?{}?=",
+                base64::engine::general_purpose::STANDARD.encode(w)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+// ==================== Body ====================
+
+/// Plain text of a (possibly multipart) body
+fn body_text(headers: &[(String, String)], body: &[u8]) -> String {
+    let (content_type, params) = header_value(headers, "content-type")
+        .map(parse_params)
+        .unwrap_or_else(|| ("text/plain".to_string(), Vec::new()));
+
+    if content_type.starts_with("multipart/") {
+        let Some(boundary) = param(&params, "boundary") else {
+            return String::new();
+        };
+        let parts: Vec<(Headers, &[u8])> = multipart_parts(body, boundary)
+            .into_iter()
+            .map(split_message)
+            .filter(|(headers, _)| {
+                !header_value(headers, "content-disposition")
+                    .is_some_and(|d| d.to_ascii_lowercase().starts_with("attachment"))
+            })
+            .collect();
+
+        let part_type = |headers: &[(String, String)]| {
+            header_value(headers, "content-type")
+                .map(|v| parse_params(v).0)
+                .unwrap_or_else(|| "text/plain".to_string())
+        };
+        // Prefer plain text, then nested multiparts, then HTML
+        let preferred = parts
+            .iter()
+            .find(|(h, _)| part_type(h) == "text/plain")
+            .or_else(|| parts.iter().find(|(h, _)| part_type(h).starts_with("multipart/")))
+            .or_else(|| parts.iter().find(|(h, _)| part_type(h) == "text/html"));
+        return preferred
+            .map(|(headers, body)| body_text(headers, body))
+            .unwrap_or_default();
+    }
+
+    if !content_type.starts_with("text/") {
+        return String::new();
+    }
+
+    let text = decode_text(headers, &params, body);
+    if content_type == "text/html" {
+        html_to_text(&text)
+    } else {
+        text.replace("\r\n", "\n").trim().to_string()
+    }
+}
+
+/// The first `text/calendar` part, inline or attached
+fn calendar_part(headers: &[(String, String)], body: &[u8]) -> Option<String> {
+    let (content_type, params) = header_value(headers, "content-type")
+        .map(parse_params)
+        .unwrap_or_else(|| ("text/plain".to_string(), Vec::new()));
+
+    if content_type.starts_with("multipart/") {
+        let boundary = param(&params, "boundary")?;
+        return multipart_parts(body, boundary)
+            .into_iter()
+            .map(split_message)
+            .find_map(|(headers, body)| calendar_part(&headers, body));
+    }
+    matches!(content_type.as_str(), "text/calendar" | "application/ics")
+        .then(|| decode_text(headers, &params, body))
+}
+
+/// A leaf part's body with its transfer encoding and charset decoded
+fn decode_text(headers: &[(String, String)], params: &[(String, String)], body: &[u8]) -> String {
+    let encoding = header_value(headers, "content-transfer-encoding")
+        .unwrap_or("7bit")
+        .to_ascii_lowercase();
+    let bytes = match encoding.as_str() {
+        "base64" => {
+            let compact: String = String::from_utf8_lossy(body)
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(compact.trim_end_matches('='))
+                .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(compact.trim_end_matches('=')))
+                .unwrap_or_default()
+        }
+        "quoted-printable" => decode_quoted_printable(&String::from_utf8_lossy(body)),
+        _ => body.to_vec(),
+    };
+    decode_charset(&bytes, param(params, "charset").unwrap_or("utf-8"))
+}
+
+/// The raw parts of a multipart body
+fn multipart_parts<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+    let mut parts = Vec::new();
+    let mut rest = body;
+    let mut started = false;
+
+    while let Some(pos) = find(rest, delimiter) {
+        if started {
+            // Drop the line break that belongs to the delimiter
+            let part = &rest[..pos];
+            let part = part.strip_suffix(b"\r\n").or_else(|| part.strip_suffix(b"\n")).unwrap_or(part);
+            parts.push(part);
+        }
+        started = true;
+        rest = &rest[pos + delimiter.len()..];
+        if rest.starts_with(b"--") {
+            break;
+        }
+        rest = rest
+            .strip_prefix(b"\r\n")
+            .or_else(|| rest.strip_prefix(b"\n"))
+            .unwrap_or(rest);
+    }
+    parts
+}
+
+fn decode_quoted_printable(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            // Soft line break
+            if bytes[i + 1..].starts_with(b"\r\n") {
+                i += 3;
+                continue;
+            }
+            if bytes[i + 1..].starts_with(b"\n") {
+                i += 2;
+                continue;
+            }
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            if let Some(byte) = hex {
+                output.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        output.push(bytes[i]);
+        i += 1;
+    }
+    output
+}
+
+/// Decode text in the given charset (UTF-8 and Latin-1; others are read as UTF-8)
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.to_ascii_lowercase().as_str() {
+        "iso-8859-1" | "latin1" | "windows-1252" => bytes.iter().map(|&b| b as char).collect(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Crude HTML to text conversion for HTML-only messages
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + len].trim().to_ascii_lowercase();
+        let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or_default();
+        rest = &rest[start + len + 1..];
+
+        if matches!(name, "script" | "style") && !tag.starts_with('/') {
+            // Skip the element's content
+            let close = format!("</{}", name);
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .map_or("", |end| &rest[end..]);
+            continue;
+        }
+        if matches!(name, "br" | "p" | "div" | "tr" | "li" | "h1" | "h2" | "h3" | "blockquote") {
+            text.push('\n');
+        }
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let mut output = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.is_empty() && (i == 0 || lines[i - 1].is_empty()) {
+            continue;
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output.trim().to_string()
+}
+
+/// Remove the quoted previous messages from a reply
+///
+/// Cuts at the attribution line (`On ... wrote:`), an
+/// `-----Original Message-----` separator or the first run of `>` lines.
+pub(crate) fn strip_quoted(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut end = lines.len();
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        let attribution = (trimmed.ends_with("wrote:") || trimmed.ends_with("書きました：") || trimmed.ends_with("書きました:"))
+            && lines[i + 1..]
+                .iter()
+                .find(|l| !l.trim().is_empty())
+                .is_none_or(|l| l.trim_start().starts_with('>'));
+        if attribution || trimmed.starts_with("-----Original Message-----") || trimmed.starts_with('>') {
+            end = i;
+            break;
+        }
+    }
+    lines[..end].join("\n").trim().to_string()
+}
+
+/// Base64 with 76 character lines
+fn base64_lines(bytes: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    encoded
+        .as_bytes()
+        .chunks(76)
+        .map(|line| String::from_utf8_lossy(line).into_owned())
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLY: &str = "From: =?utf-8?B?5bGx55Sw?= <yamada@example.com>\r\n\
+To: Bot <bot@example.com>, other@example.com\r\n\
+Subject: Re: =?utf-8?Q?Caf=C3=A9?= plans\r\n\
+Message-ID: <c@example.com>\r\n\
+In-Reply-To: <b@example.com>\r\n\
+References: <a@example.com>\r\n <b@example.com>\r\n\
+Content-Type: multipart/alternative; boundary=\"xyz\"\r\n\
+\r\n\
+--xyz\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Sounds good =E2=80=94 see you=\r\n there.\r\n\
+\r\n\
+On Mon, Bot <bot@example.com> wrote:\r\n\
+> Shall we meet?\r\n\
+--xyz\r\n\
+Content-Type: text/html\r\n\
+\r\n\
+<p>Sounds good</p>\r\n\
+--xyz--\r\n";
+
+    #[test]
+    fn test_parse_reply() {
+        let email = ParsedEmail::parse(REPLY.as_bytes());
+        assert_eq!(email.from.as_deref(), Some("yamada@example.com"));
+        assert_eq!(email.from_name.as_deref(), Some("山田"));
+        assert_eq!(email.to, vec!["bot@example.com", "other@example.com"]);
+        assert_eq!(email.subject, "Re: Café plans");
+        assert_eq!(email.references, vec!["<a@example.com>", "<b@example.com>"]);
+        assert_eq!(email.thread_id(), "<a@example.com>");
+        assert!(email.text.contains("> Shall we meet?"));
+        assert_eq!(email.new_text(), "Sounds good — see you there.");
+        assert!(!email.automated);
+    }
+
+    #[test]
+    fn test_reply_headers() {
+        let original = ParsedEmail::parse(REPLY.as_bytes());
+        let reply = OutgoingEmail::reply(&original, "返信です").unwrap();
+        assert_eq!(reply.to, vec!["yamada@example.com"]);
+        assert_eq!(reply.subject, "Re: Café plans");
+        assert_eq!(reply.in_reply_to.as_deref(), Some("<c@example.com>"));
+        assert_eq!(
+            reply.references,
+            vec!["<a@example.com>", "<b@example.com>", "<c@example.com>"]
+        );
+
+        let raw = reply.to_rfc5322("bot@example.com", Some("Bot"), "<d@example.com>");
+        let parsed = ParsedEmail::parse(raw.as_bytes());
+        assert_eq!(parsed.subject, "Re: Café plans");
+        assert_eq!(parsed.in_reply_to.as_deref(), Some("<c@example.com>"));
+        assert_eq!(parsed.thread_id(), "<a@example.com>");
+        assert_eq!(parsed.text, "返信です");
+    }
+
+    #[test]
+    fn test_compose_html_with_attachments() {
+        let email = OutgoingEmail::new("a@example.com", "Report", "See attached")
+            .html("<p>See <b>attached</b></p><img src=\"cid:logo\">")
+            .cc("c@example.com")
+            .bcc("hidden@example.com")
+            .reply_to("team@example.com")
+            .attach(Attachment::new("logo.png", "image/png", b"png".to_vec()).inline("logo"))
+            .attach(Attachment::new("レポート.pdf", content_type_for("レポート.pdf"), b"%PDF".to_vec()));
+        assert_eq!(
+            email.recipients(),
+            vec!["a@example.com", "c@example.com", "hidden@example.com"]
+        );
+
+        let raw = email.to_rfc5322("bot@example.com", None, "<m@example.com>");
+        assert!(raw.contains("Cc: c@example.com\r\n"));
+        assert!(raw.contains("Reply-To: team@example.com\r\n"));
+        assert!(!raw.contains("hidden@example.com"));
+        assert!(raw.contains("Content-Type: multipart/mixed;"));
+        assert!(raw.contains("Content-Type: multipart/related;"));
+        assert!(raw.contains("Content-Type: multipart/alternative;"));
+        assert!(raw.contains("Content-ID: <logo>"));
+        assert!(raw.contains("Content-Type: application/pdf; name=\"=?utf-8?B?"));
+        assert!(raw.contains("Content-Disposition: attachment;"));
+
+        let parsed = ParsedEmail::parse(raw.as_bytes());
+        assert_eq!(parsed.text, "See attached");
+        assert_eq!(parsed.reply_to.as_deref(), Some("team@example.com"));
+    }
+
+    #[test]
+    fn test_compose_html_only() {
+        let email = OutgoingEmail::new("a@example.com", "Hi", "").html("<p>Hello<br>there</p>");
+        let raw = email.to_rfc5322("bot@example.com", None, "<m@example.com>");
+        assert!(raw.contains("Content-Type: multipart/alternative;"));
+        assert!(!raw.contains("multipart/mixed"));
+        assert_eq!(ParsedEmail::parse(raw.as_bytes()).text, "Hello\nthere");
+    }
+
+    #[test]
+    fn test_calendar_part_round_trip() {
+        let ics = "BEGIN:VCALENDAR\r\nMETHOD:REQUEST\r\nBEGIN:VEVENT\r\nUID:1\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let email = OutgoingEmail::new("a@example.com", "Invitation: Sync", "You are invited").calendar("request", ics);
+        let raw = email.to_rfc5322("bot@example.com", None, "<m@example.com>");
+        assert!(raw.contains("Content-Type: text/calendar; method=REQUEST; charset=utf-8"));
+
+        let parsed = ParsedEmail::parse(raw.as_bytes());
+        assert_eq!(parsed.text, "You are invited");
+        assert_eq!(parsed.calendar.as_deref(), Some(ics));
+        assert!(ParsedEmail::parse(REPLY.as_bytes()).calendar.is_none());
+    }
+
+    #[test]
+    fn test_html_only_and_automated() {
+        let raw = b"From: noreply@example.com\r\n\
+Auto-Submitted: auto-replied\r\n\
+Content-Type: text/html; charset=iso-8859-1\r\n\
+\r\n\
+<html><style>p {}</style><p>Out of office &amp; away</p><br>Caf\xe9</html>";
+        let email = ParsedEmail::parse(raw);
+        assert!(email.automated);
+        assert_eq!(email.text, "Out of office & away\n\nCafé");
+        assert_eq!(email.thread_id(), "noreply@example.com|");
+    }
+
+    #[test]
+    fn test_authenticated() {
+        let raw = |results: &str| {
+            format!("Authentication-Results: {}
+Authentication-Results: mx.evil; dmarc=pass header.from=example.com
+From: a@example.com
+
+Hi", results)
+        };
+        let parse = |results: &str| ParsedEmail::parse(raw(results).as_bytes()).dmarc_passed("mx.example.net");
+        assert!(parse("mx.example.net; spf=pass smtp.mailfrom=example.com;
+ dmarc=pass (p=REJECT) header.from=example.com"));
+        assert!(parse("MX.example.net 1; dkim=pass; dmarc=pass"));
+        assert!(!parse("mx.example.net; dmarc=fail header.from=example.com"));
+        assert!(!parse("mx.example.net; dmarc=pass header.from=other.example"));
+        assert!(!parse("mx.example.net; none"));
+        assert!(!ParsedEmail::parse(REPLY.as_bytes()).dmarc_passed("mx.example.net"));
+
+        // A forged pass on top is ignored when the receiving server added no header
+        let forged = ParsedEmail::parse(raw("mx.forged.example; dmarc=pass header.from=example.com").as_bytes());
+        assert!(!forged.dmarc_passed("mx.example.net"));
+        // ... and also when it did, below the forged one
+        let forged = ParsedEmail::parse(
+            b"Authentication-Results: mx.evil; dmarc=pass header.from=example.com
+Authentication-Results: mx.example.net; dmarc=fail header.from=example.com
+From: a@example.com
+
+Hi",
+        );
+        assert!(!forged.dmarc_passed("mx.example.net"));
+        assert!(forged.dmarc_passed("mx.evil"));
+    }
+
+    #[test]
+    fn test_strip_reply_prefix_and_encode() {
+        assert_eq!(strip_reply_prefix("RE: Fwd: re: Hello"), "Hello");
+        assert_eq!(encode_header("Hello"), "Hello");
+        assert_eq!(decode_words(&encode_header("こんにちは世界、長い件名のテストです")), "こんにちは世界、長い件名のテストです");
+    }
+}
@@ -0,0 +1,484 @@
+//! OAuth2 for SMTP and IMAP (SASL XOAUTH2)
+//!
+//! Gmail and Microsoft 365 are retiring password logins. Tokens are obtained
+//! once with the device code flow (`cc-gateway email login`) or seeded with a
+//! refresh token, cached in a JSON file and refreshed before they expire.
+//!
+//! Google does not allow the Gmail scope in the device code flow; for Gmail,
+//! create the refresh token with another OAuth client and set
+//! `EMAIL_OAUTH_REFRESH_TOKEN`.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+use crate::error::{EmailError, Result};
+
+/// Refresh tokens this long before they expire
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// OAuth2 identity provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OAuthProvider {
+    /// Gmail / Google Workspace
+    Google,
+    /// Microsoft 365 / Outlook.com (`tenant` is a tenant ID, `common` or `consumers`)
+    Microsoft { tenant: String },
+    /// Any other provider
+    Custom {
+        token_url: String,
+        device_authorization_url: Option<String>,
+        scope: String,
+    },
+}
+
+impl OAuthProvider {
+    /// Parse a provider name (`google`, `microsoft`)
+    pub fn from_name(name: &str, tenant: Option<String>) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "google" | "gmail" => Some(Self::Google),
+            "microsoft" | "outlook" | "office365" | "m365" => Some(Self::Microsoft {
+                tenant: tenant.unwrap_or_else(|| "common".to_string()),
+            }),
+            _ => None,
+        }
+    }
+
+    fn token_url(&self) -> String {
+        match self {
+            Self::Google => "https://oauth2.googleapis.com/token".to_string(),
+            Self::Microsoft { tenant } => format!(
+                "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+                tenant
+            ),
+            Self::Custom { token_url, .. } => token_url.clone(),
+        }
+    }
+
+    fn device_authorization_url(&self) -> Option<String> {
+        match self {
+            // The Gmail scope is not available to the device flow
+            Self::Google => None,
+            Self::Microsoft { tenant } => Some(format!(
+                "https://login.microsoftonline.com/{}/oauth2/v2.0/devicecode",
+                tenant
+            )),
+            Self::Custom {
+                device_authorization_url,
+                ..
+            } => device_authorization_url.clone(),
+        }
+    }
+
+    fn scope(&self) -> String {
+        match self {
+            Self::Google => "https://mail.google.com/".to_string(),
+            Self::Microsoft { .. } => "https://outlook.office.com/IMAP.AccessAsUser.All https://outlook.office.com/SMTP.Send offline_access".to_string(),
+            Self::Custom { scope, .. } => scope.clone(),
+        }
+    }
+}
+
+/// A cached access token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthToken {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl OAuthToken {
+    /// Whether the access token must be refreshed before use
+    pub fn is_expiring(&self) -> bool {
+        self.access_token.is_empty()
+            || self.expires_at.is_some_and(|expires_at| {
+                expires_at - chrono::Duration::seconds(EXPIRY_MARGIN_SECS) <= Utc::now()
+            })
+    }
+}
+
+/// A pending device code authorization
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    /// Code the user enters at `verification_uri`
+    pub user_code: String,
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    pub expires_in: u64,
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+    /// Instructions for the user, if the provider sends them
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// OAuth2 token source for one mailbox
+pub struct OAuth2 {
+    provider: OAuthProvider,
+    client_id: String,
+    client_secret: Option<String>,
+    token_path: Option<PathBuf>,
+    token: Mutex<Option<OAuthToken>>,
+    http: reqwest::Client,
+}
+
+impl std::fmt::Debug for OAuth2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuth2")
+            .field("provider", &self.provider)
+            .field("client_id", &self.client_id)
+            .field("token_path", &self.token_path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OAuth2 {
+    /// Create a token source without any token yet
+    pub fn new(provider: OAuthProvider, client_id: impl Into<String>) -> Self {
+        Self {
+            provider,
+            client_id: client_id.into(),
+            client_secret: None,
+            token_path: None,
+            token: Mutex::new(None),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Set the client secret (needed by Google and confidential clients)
+    pub fn with_client_secret(mut self, secret: impl Into<String>) -> Self {
+        self.client_secret = Some(secret.into());
+        self
+    }
+
+    /// Cache tokens in a file, loading the cached token if there is one
+    pub fn with_token_path(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        if let Ok(json) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<OAuthToken>(&json) {
+                Ok(token) => self.token = Mutex::new(Some(token)),
+                Err(e) => debug!("Ignoring invalid token cache {}: {}", path.display(), e),
+            }
+        }
+        self.token_path = Some(path);
+        self
+    }
+
+    /// Use a refresh token when no cached token is available
+    pub fn with_refresh_token(mut self, refresh_token: impl Into<String>) -> Self {
+        let token = self.token.get_mut();
+        if token.is_none() {
+            *token = Some(OAuthToken {
+                access_token: String::new(),
+                refresh_token: Some(refresh_token.into()),
+                expires_at: None,
+            });
+        }
+        self
+    }
+
+    /// Read OAuth2 settings from the environment
+    ///
+    /// `EMAIL_OAUTH_PROVIDER` (google / microsoft) and `EMAIL_OAUTH_CLIENT_ID`
+    /// (required), `EMAIL_OAUTH_CLIENT_SECRET`, `EMAIL_OAUTH_TENANT` (default
+    /// common), `EMAIL_OAUTH_REFRESH_TOKEN`, `EMAIL_OAUTH_TOKEN_PATH`
+    /// (default email_oauth_token.json)
+    pub fn from_env() -> Option<Self> {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+        let provider = OAuthProvider::from_name(
+            &env("EMAIL_OAUTH_PROVIDER")?,
+            env("EMAIL_OAUTH_TENANT"),
+        )?;
+        let mut oauth = Self::new(provider, env("EMAIL_OAUTH_CLIENT_ID")?).with_token_path(
+            env("EMAIL_OAUTH_TOKEN_PATH").unwrap_or_else(|| "email_oauth_token.json".to_string()),
+        );
+        if let Some(secret) = env("EMAIL_OAUTH_CLIENT_SECRET") {
+            oauth = oauth.with_client_secret(secret);
+        }
+        if let Some(refresh_token) = env("EMAIL_OAUTH_REFRESH_TOKEN") {
+            oauth = oauth.with_refresh_token(refresh_token);
+        }
+        Some(oauth)
+    }
+
+    /// A valid access token, refreshed if it is about to expire
+    pub async fn access_token(&self) -> Result<String> {
+        let mut guard = self.token.lock().await;
+        let Some(token) = guard.as_ref() else {
+            return Err(EmailError::OAuth(
+                "No OAuth token; run `cc-gateway email login` or set EMAIL_OAUTH_REFRESH_TOKEN"
+                    .to_string(),
+            ));
+        };
+        if !token.is_expiring() {
+            return Ok(token.access_token.clone());
+        }
+
+        let refresh_token = token.refresh_token.clone().ok_or_else(|| {
+            EmailError::OAuth("Access token expired and no refresh token is available".to_string())
+        })?;
+        debug!("Refreshing OAuth access token");
+        let response = self
+            .token_request(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &refresh_token),
+            ])
+            .await?;
+        let mut token = token_from_response(response)?;
+        // Providers may omit the refresh token when it does not change
+        if token.refresh_token.is_none() {
+            token.refresh_token = Some(refresh_token);
+        }
+        self.save(&token);
+        let access_token = token.access_token.clone();
+        *guard = Some(token);
+        Ok(access_token)
+    }
+
+    /// Start the device code flow; show `user_code` and `verification_uri` to the user
+    pub async fn start_device_flow(&self) -> Result<DeviceAuthorization> {
+        let url = self.provider.device_authorization_url().ok_or_else(|| {
+            EmailError::OAuth(
+                "This provider does not support the device code flow for mail; set EMAIL_OAUTH_REFRESH_TOKEN instead"
+                    .to_string(),
+            )
+        })?;
+        let scope = self.provider.scope();
+        let response = self
+            .http
+            .post(&url)
+            .form(&[("client_id", self.client_id.as_str()), ("scope", scope.as_str())])
+            .send()
+            .await
+            .map_err(|e| EmailError::OAuth(e.to_string()))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| EmailError::OAuth(e.to_string()))?;
+        if !status.is_success() {
+            return Err(EmailError::OAuth(format!("Device authorization failed ({}): {}", status, body)));
+        }
+        serde_json::from_str(&body).map_err(|e| EmailError::OAuth(format!("Invalid device authorization: {}", e)))
+    }
+
+    /// Wait until the user has approved the device code, then store the token
+    pub async fn complete_device_flow(&self, authorization: &DeviceAuthorization) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(authorization.expires_in);
+        let mut interval = authorization.interval;
+
+        loop {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(EmailError::OAuth("Device code expired".to_string()));
+            }
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+
+            let response = self
+                .token_request(&[
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                    ("device_code", &authorization.device_code),
+                ])
+                .await?;
+            match response.error.as_deref() {
+                Some("authorization_pending") => continue,
+                Some("slow_down") => interval += 5,
+                _ => {
+                    let token = token_from_response(response)?;
+                    self.save(&token);
+                    *self.token.lock().await = Some(token);
+                    info!("OAuth authorization complete");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    async fn token_request(&self, params: &[(&str, &str)]) -> Result<TokenResponse> {
+        let mut form = vec![("client_id", self.client_id.as_str())];
+        if let Some(ref secret) = self.client_secret {
+            form.push(("client_secret", secret));
+        }
+        form.extend_from_slice(params);
+
+        let response = self
+            .http
+            .post(self.provider.token_url())
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| EmailError::OAuth(e.to_string()))?;
+        // Errors come back as JSON with an `error` field (and usually status 400)
+        response
+            .json()
+            .await
+            .map_err(|e| EmailError::OAuth(format!("Invalid token response: {}", e)))
+    }
+
+    /// Write the token to the cache file
+    fn save(&self, token: &OAuthToken) {
+        let Some(ref path) = self.token_path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(token)
+            .map_err(std::io::Error::other)
+            .and_then(|json| std::fs::write(path, json));
+        #[cfg(unix)]
+        let result = result.and_then(|_| {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to save OAuth token to {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn token_from_response(response: TokenResponse) -> Result<OAuthToken> {
+    if let Some(error) = response.error {
+        let description = response.error_description.unwrap_or_default();
+        return Err(EmailError::OAuth(format!("{} {}", error, description).trim().to_string()));
+    }
+    let access_token = response
+        .access_token
+        .ok_or_else(|| EmailError::OAuth("Token response has no access_token".to_string()))?;
+    Ok(OAuthToken {
+        access_token,
+        refresh_token: response.refresh_token,
+        expires_at: response
+            .expires_in
+            .map(|secs| Utc::now() + chrono::Duration::seconds(secs)),
+    })
+}
+
+/// The SASL XOAUTH2 initial response (base64)
+pub(crate) fn xoauth2(user: &str, access_token: &str) -> String {
+    base64::engine::general_purpose::STANDARD.encode(format!(
+        "user={}\x01auth=Bearer {}\x01\x01",
+        user, access_token
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn custom(server: &MockServer) -> OAuthProvider {
+        OAuthProvider::Custom {
+            token_url: format!("{}/token", server.uri()),
+            device_authorization_url: Some(format!("{}/device", server.uri())),
+            scope: "mail".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .and(body_string_contains("refresh_token=seed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "fresh",
+                "expires_in": 3600
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let path = std::env::temp_dir().join(format!("cc-email-oauth-{}.json", std::process::id()));
+        let oauth = OAuth2::new(custom(&server), "client")
+            .with_token_path(&path)
+            .with_refresh_token("seed");
+
+        assert_eq!(oauth.access_token().await.unwrap(), "fresh");
+        // Cached until it expires
+        assert_eq!(oauth.access_token().await.unwrap(), "fresh");
+
+        let cached: OAuthToken =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(cached.access_token, "fresh");
+        assert_eq!(cached.refresh_token.as_deref(), Some("seed"));
+    }
+
+    #[tokio::test]
+    async fn test_device_flow() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/device"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "device_code": "dev",
+                "user_code": "ABCD-EFGH",
+                "verification_uri": "https://example.com/device",
+                "expires_in": 60,
+                "interval": 0
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "authorization_pending"
+            })))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("device_code=dev"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "granted",
+                "refresh_token": "refresh",
+                "expires_in": 3600
+            })))
+            .mount(&server)
+            .await;
+
+        let oauth = OAuth2::new(custom(&server), "client");
+        assert!(oauth.access_token().await.is_err());
+
+        let authorization = oauth.start_device_flow().await.unwrap();
+        assert_eq!(authorization.user_code, "ABCD-EFGH");
+        oauth.complete_device_flow(&authorization).await.unwrap();
+        assert_eq!(oauth.access_token().await.unwrap(), "granted");
+    }
+
+    #[test]
+    fn test_xoauth2_and_providers() {
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(xoauth2("me@example.com", "tok"))
+            .unwrap();
+        assert_eq!(decoded, b"user=me@example.com\x01auth=Bearer tok\x01\x01");
+
+        assert_eq!(OAuthProvider::from_name("gmail", None), Some(OAuthProvider::Google));
+        assert!(OAuthProvider::Google.device_authorization_url().is_none());
+        let microsoft = OAuthProvider::from_name("microsoft", None).unwrap();
+        assert!(microsoft.token_url().contains("/common/"));
+        assert!(OAuthProvider::from_name("yahoo", None).is_none());
+    }
+}
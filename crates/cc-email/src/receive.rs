@@ -1,11 +1,14 @@
 //! Email receiving via IMAP
-//!
-//! Note: This is a simplified implementation stub.
-//! Full IMAP implementation requires careful handling of connections.
 
+use std::sync::Arc;
+
+use chrono::NaiveDate;
 use tracing::info;
 
 use crate::error::{EmailError, Result};
+use crate::imap::{Folder, ImapSession, quote};
+use crate::message::ParsedEmail;
+use crate::oauth::OAuth2;
 
 /// Email receiver configuration
 #[derive(Debug, Clone)]
@@ -14,6 +17,29 @@ pub struct ImapConfig {
     pub imap_port: u16,
     pub imap_user: String,
     pub imap_pass: String,
+    /// Authenticate with XOAUTH2 instead of the password
+    pub oauth: Option<Arc<OAuth2>>,
+    /// Log in even when the server offers no TLS (credentials go in cleartext)
+    pub allow_insecure_auth: bool,
+}
+
+impl ImapConfig {
+    /// Read IMAP settings from the environment
+    ///
+    /// `IMAP_HOST` (required), `IMAP_PORT` (default 993), `IMAP_USER`, `IMAP_PASS`,
+    /// `IMAP_ALLOW_INSECURE_AUTH`, and the `EMAIL_OAUTH_*` settings of [`OAuth2::from_env`]
+    pub fn from_env() -> Option<Self> {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+        env("IMAP_HOST").map(|imap_host| Self {
+            imap_host,
+            imap_port: env("IMAP_PORT").and_then(|p| p.parse().ok()).unwrap_or(993),
+            imap_user: env("IMAP_USER").unwrap_or_default(),
+            imap_pass: env("IMAP_PASS").unwrap_or_default(),
+            oauth: OAuth2::from_env().map(Arc::new),
+            allow_insecure_auth: env("IMAP_ALLOW_INSECURE_AUTH").is_some_and(|v| v == "true" || v == "1"),
+        })
+    }
 }
 
 /// Email message summary
@@ -26,6 +52,91 @@ pub struct EmailSummary {
     pub flags: Vec<String>,
 }
 
+/// Filters for [`EmailReceiver::search`]; all set filters must match
+#[derive(Debug, Clone, Default)]
+pub struct SearchCriteria {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub subject: Option<String>,
+    /// Text anywhere in the headers or body
+    pub text: Option<String>,
+    /// Received on or after this date
+    pub since: Option<NaiveDate>,
+    /// Received before this date
+    pub before: Option<NaiveDate>,
+    /// `Some(true)` for unread only, `Some(false)` for read only
+    pub unseen: Option<bool>,
+    /// `Some(true)` for flagged only, `Some(false)` for unflagged only
+    pub flagged: Option<bool>,
+}
+
+impl SearchCriteria {
+    /// The IMAP `SEARCH` arguments
+    pub fn to_imap(&self) -> String {
+        let mut keys = Vec::new();
+        let strings = [
+            ("FROM", &self.from),
+            ("TO", &self.to),
+            ("SUBJECT", &self.subject),
+            ("TEXT", &self.text),
+        ];
+        for (key, value) in strings {
+            if let Some(value) = value {
+                keys.push(format!("{} {}", key, quote(value)));
+            }
+        }
+        // IMAP dates look like 1-Feb-2024
+        if let Some(since) = self.since {
+            keys.push(format!("SINCE {}", since.format("%-d-%b-%Y")));
+        }
+        if let Some(before) = self.before {
+            keys.push(format!("BEFORE {}", before.format("%-d-%b-%Y")));
+        }
+        match self.unseen {
+            Some(true) => keys.push("UNSEEN".to_string()),
+            Some(false) => keys.push("SEEN".to_string()),
+            None => {}
+        }
+        match self.flagged {
+            Some(true) => keys.push("FLAGGED".to_string()),
+            Some(false) => keys.push("UNFLAGGED".to_string()),
+            None => {}
+        }
+
+        if keys.is_empty() {
+            return "ALL".to_string();
+        }
+        let keys = keys.join(" ");
+        if keys.is_ascii() {
+            keys
+        } else {
+            format!("CHARSET UTF-8 {}", keys)
+        }
+    }
+}
+
+/// A flag change for [`EmailReceiver::set_flags`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagAction {
+    MarkRead,
+    MarkUnread,
+    Flag,
+    Unflag,
+}
+
+impl FlagAction {
+    /// Parse `mark_read`, `mark_unread`, `flag` or `unflag`
+    pub fn parse(action: &str) -> Option<Self> {
+        match action {
+            "mark_read" | "read" => Some(Self::MarkRead),
+            "mark_unread" | "unread" => Some(Self::MarkUnread),
+            "flag" => Some(Self::Flag),
+            "unflag" => Some(Self::Unflag),
+            _ => None,
+        }
+    }
+}
+
 /// Email receiver
 pub struct EmailReceiver {
     config: ImapConfig,
@@ -37,33 +148,123 @@ impl EmailReceiver {
         Self { config }
     }
 
-    /// List emails (stub implementation)
+    /// List the most recent emails in a folder, newest first
     pub async fn list_emails(&self, folder: &str, limit: usize) -> Result<Vec<EmailSummary>> {
         info!(
             "Listing {} emails in folder: {} (IMAP: {})",
             limit, folder, self.config.imap_host
         );
 
-        // Stub implementation - returns empty list
-        // Full implementation would use async-imap or imap crate
-        Ok(Vec::new())
+        self.search(folder, &SearchCriteria::default(), limit).await
+    }
+
+    /// Search a folder, newest first
+    pub async fn search(
+        &self,
+        folder: &str,
+        criteria: &SearchCriteria,
+        limit: usize,
+    ) -> Result<Vec<EmailSummary>> {
+        let mut session = ImapSession::connect(&self.config).await?;
+        session.select(folder).await?;
+        let uids = session.uid_search(&criteria.to_imap()).await?;
+        let recent: Vec<String> = uids.iter().rev().take(limit).map(u32::to_string).collect();
+        if recent.is_empty() {
+            session.logout().await;
+            return Ok(Vec::new());
+        }
+
+        let mut messages = session
+            .uid_fetch(&recent.join(","), "BODY.PEEK[HEADER]")
+            .await?;
+        session.logout().await;
+        messages.sort_by_key(|m| std::cmp::Reverse(m.uid));
+
+        Ok(messages
+            .into_iter()
+            .map(|message| {
+                let header = ParsedEmail::parse(&message.raw);
+                EmailSummary {
+                    uid: message.uid,
+                    subject: Some(header.subject).filter(|s| !s.is_empty()),
+                    from: header.from,
+                    date: header.date,
+                    flags: message.flags,
+                }
+            })
+            .collect())
     }
 
-    /// Get email content by UID (stub implementation)
+    /// List all folders
+    pub async fn list_folders(&self) -> Result<Vec<Folder>> {
+        let mut session = ImapSession::connect(&self.config).await?;
+        let folders = session.list_folders().await?;
+        session.logout().await;
+        Ok(folders)
+    }
+
+    /// Move emails to another folder
+    pub async fn move_emails(&self, folder: &str, uids: &[u32], destination: &str) -> Result<()> {
+        info!(
+            "Moving {} emails from {} to {} (IMAP: {})",
+            uids.len(),
+            folder,
+            destination,
+            self.config.imap_host
+        );
+
+        let mut session = ImapSession::connect(&self.config).await?;
+        session.select(folder).await?;
+        let result = session.move_messages(&uid_set(uids), destination).await;
+        session.logout().await;
+        result.map_err(|e| match e {
+            EmailError::ImapCommand(message) if message.contains("TRYCREATE") => {
+                EmailError::FolderNotFound(destination.to_string())
+            }
+            other => other,
+        })
+    }
+
+    /// Mark emails as read / unread or flag / unflag them
+    pub async fn set_flags(&self, folder: &str, uids: &[u32], action: FlagAction) -> Result<()> {
+        let mut session = ImapSession::connect(&self.config).await?;
+        session.select(folder).await?;
+        let uid_set = uid_set(uids);
+        let result = match action {
+            FlagAction::MarkRead => session.add_flags(&uid_set, "\\Seen").await,
+            FlagAction::MarkUnread => session.remove_flags(&uid_set, "\\Seen").await,
+            FlagAction::Flag => session.add_flags(&uid_set, "\\Flagged").await,
+            FlagAction::Unflag => session.remove_flags(&uid_set, "\\Flagged").await,
+        };
+        session.logout().await;
+        result
+    }
+
+    /// Get the raw RFC 5322 message by UID
     pub async fn get_email(&self, folder: &str, uid: u32) -> Result<String> {
         info!(
             "Getting email UID {} from folder: {} (IMAP: {})",
             uid, folder, self.config.imap_host
         );
 
-        // Stub implementation
-        Err(EmailError::MessageNotFound(format!(
-            "UID {} - IMAP integration requires configuration",
-            uid
-        )))
+        let mut session = ImapSession::connect(&self.config).await?;
+        session.select(folder).await?;
+        let messages = session.uid_fetch(&uid.to_string(), "BODY.PEEK[]").await?;
+        session.logout().await;
+
+        messages
+            .into_iter()
+            .find(|m| m.uid == uid)
+            .map(|m| String::from_utf8_lossy(&m.raw).into_owned())
+            .ok_or_else(|| EmailError::MessageNotFound(format!("UID {}", uid)))
     }
 }
 
+/// A UID set such as `3,7,9`
+fn uid_set(uids: &[u32]) -> String {
+    uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,7 +276,33 @@ mod tests {
             imap_port: 993,
             imap_user: "user@example.com".to_string(),
             imap_pass: "password".to_string(),
+            oauth: None,
+            allow_insecure_auth: false,
         };
         assert_eq!(config.imap_host, "imap.example.com");
     }
+
+    #[test]
+    fn test_search_criteria() {
+        assert_eq!(SearchCriteria::default().to_imap(), "ALL");
+
+        let criteria = SearchCriteria {
+            from: Some("alice@example.com".to_string()),
+            subject: Some("invoice".to_string()),
+            since: NaiveDate::from_ymd_opt(2024, 2, 1),
+            unseen: Some(true),
+            flagged: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            criteria.to_imap(),
+            r#"FROM "alice@example.com" SUBJECT "invoice" SINCE 1-Feb-2024 UNSEEN UNFLAGGED"#
+        );
+
+        let japanese = SearchCriteria {
+            subject: Some("請求書".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(japanese.to_imap(), r#"CHARSET UTF-8 SUBJECT "請求書""#);
+    }
 }
@@ -1,11 +1,15 @@
 //! Email sending via SMTP
-//!
-//! Note: This is a simplified implementation stub.
-//! Full SMTP implementation requires careful configuration.
 
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cc_core::notify::EmailTransport;
 use tracing::info;
 
-use crate::error::Result;
+use crate::error::{EmailError, Result};
+use crate::message::{OutgoingEmail, new_message_id};
+use crate::oauth::OAuth2;
+use crate::smtp;
 
 /// Email sender configuration
 #[derive(Debug, Clone)]
@@ -16,6 +20,31 @@ pub struct EmailConfig {
     pub smtp_pass: String,
     pub from_address: String,
     pub from_name: Option<String>,
+    /// Authenticate with XOAUTH2 instead of the password
+    pub oauth: Option<Arc<OAuth2>>,
+    /// Log in even when the server offers no TLS (credentials go in cleartext)
+    pub allow_insecure_auth: bool,
+}
+
+impl EmailConfig {
+    /// Read SMTP settings from the environment
+    ///
+    /// `SMTP_HOST` (required), `SMTP_PORT` (default 587), `SMTP_USER`, `SMTP_PASS`, `SMTP_FROM`,
+    /// `SMTP_ALLOW_INSECURE_AUTH`, and the `EMAIL_OAUTH_*` settings of [`OAuth2::from_env`]
+    pub fn from_env() -> Option<Self> {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+        env("SMTP_HOST").map(|smtp_host| Self {
+            smtp_host,
+            smtp_port: env("SMTP_PORT").and_then(|p| p.parse().ok()).unwrap_or(587),
+            smtp_user: env("SMTP_USER").unwrap_or_default(),
+            smtp_pass: env("SMTP_PASS").unwrap_or_default(),
+            from_address: env("SMTP_FROM").unwrap_or_default(),
+            from_name: Some("cc-gateway".to_string()),
+            oauth: OAuth2::from_env().map(Arc::new),
+            allow_insecure_auth: env("SMTP_ALLOW_INSECURE_AUTH").is_some_and(|v| v == "true" || v == "1"),
+        })
+    }
 }
 
 /// Email sender
@@ -30,42 +59,81 @@ impl EmailSender {
         Ok(Self { config })
     }
 
-    /// Send an email (stub implementation)
-    pub async fn send(&self, to: &str, subject: &str, body: &str, _html: bool) -> Result<String> {
+    /// Sender address (`SMTP_FROM`, falling back to the SMTP user)
+    pub fn from_address(&self) -> &str {
+        if self.config.from_address.is_empty() {
+            &self.config.smtp_user
+        } else {
+            &self.config.from_address
+        }
+    }
+
+    /// Send an email
+    pub async fn send(&self, to: &str, subject: &str, body: &str, html: bool) -> Result<String> {
+        let email = if html {
+            OutgoingEmail::new(to, subject, "").html(body)
+        } else {
+            OutgoingEmail::new(to, subject, body)
+        };
+        let message_id = self.send_message(&email).await?;
+        Ok(format!("Email sent: to={}, subject={}, id={}", to, subject, message_id))
+    }
+
+    /// Send a message and return its `Message-ID`
+    pub async fn send_message(&self, email: &OutgoingEmail) -> Result<String> {
+        let from = self.from_address();
+        if !from.contains('@') {
+            return Err(EmailError::SmtpConfig(
+                "SMTP_FROM (or SMTP_USER) must be an email address".to_string(),
+            ));
+        }
+        let recipients = email.recipients();
+        if recipients.is_empty() {
+            return Err(EmailError::InvalidAddress("no recipients".to_string()));
+        }
+        let invalid = recipients
+            .iter()
+            .chain(&email.reply_to)
+            .find(|address| !is_valid_address(address));
+        if let Some(invalid) = invalid {
+            return Err(EmailError::InvalidAddress(invalid.clone()));
+        }
+
         info!(
             "Sending email to {} via {}:{}",
-            to, self.config.smtp_host, self.config.smtp_port
-        );
-
-        // Stub implementation
-        // Full implementation would use lettre to send
-        Ok(format!(
-            "Email queued: to={}, subject={}, length={} (SMTP: {}:{})",
-            to,
-            subject,
-            body.len(),
+            recipients.join(", "),
             self.config.smtp_host,
             self.config.smtp_port
-        ))
+        );
+        let message_id = new_message_id(from);
+        let data = email.to_rfc5322(from, self.config.from_name.as_deref(), &message_id);
+        smtp::send_mail(&self.config, from, &recipients, &data).await?;
+        Ok(message_id)
     }
 
-    /// Send a multipart email (stub implementation)
+    /// Send an HTML email with a plain-text alternative
     pub async fn send_multipart(
         &self,
         to: &str,
         subject: &str,
-        _text_body: &str,
-        _html_body: &str,
+        text_body: &str,
+        html_body: &str,
     ) -> Result<String> {
-        info!(
-            "Sending multipart email to {} via {}:{}",
-            to, self.config.smtp_host, self.config.smtp_port
-        );
+        let email = OutgoingEmail::new(to, subject, text_body).html(html_body);
+        let message_id = self.send_message(&email).await?;
+        Ok(format!("Email sent: to={}, subject={}, id={}", to, subject, message_id))
+    }
+}
 
-        Ok(format!(
-            "Multipart email queued: to={}, subject={} (SMTP: {}:{})",
-            to, subject, self.config.smtp_host, self.config.smtp_port
-        ))
+/// A bare address (`user@example.com`) without spaces or header separators
+fn is_valid_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !address.contains(|c: char| c.is_whitespace() || "<>,;\"".contains(c))
+        }
+        None => false,
     }
 }
 
@@ -77,6 +145,16 @@ impl Clone for EmailSender {
     }
 }
 
+#[async_trait]
+impl EmailTransport for EmailSender {
+    async fn send_email(&self, to: &str, subject: &str, body: &str) -> cc_core::Result<()> {
+        self.send(to, subject, body, false)
+            .await
+            .map(|_| ())
+            .map_err(|e| cc_core::Error::Notify(e.to_string()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,6 +168,8 @@ mod tests {
             smtp_pass: "password".to_string(),
             from_address: "noreply@example.com".to_string(),
             from_name: Some("Test".to_string()),
+            oauth: None,
+            allow_insecure_auth: false,
         };
         assert_eq!(config.smtp_host, "smtp.example.com");
     }
@@ -103,8 +183,20 @@ mod tests {
             smtp_pass: "password".to_string(),
             from_address: "noreply@example.com".to_string(),
             from_name: None,
+            oauth: None,
+            allow_insecure_auth: false,
         };
         let sender = EmailSender::new(config);
         assert!(sender.is_ok());
     }
+
+    #[test]
+    fn test_is_valid_address() {
+        assert!(is_valid_address("user@example.com"));
+        assert!(!is_valid_address("user"));
+        assert!(!is_valid_address("@example.com"));
+        assert!(!is_valid_address("user@localhost"));
+        assert!(!is_valid_address("a@example.com\r\nBcc: b@example.com"));
+        assert!(!is_valid_address("Name <user@example.com>"));
+    }
 }
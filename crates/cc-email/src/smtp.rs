@@ -0,0 +1,332 @@
+//! Minimal SMTP submission client
+//!
+//! EHLO, STARTTLS, AUTH PLAIN / LOGIN / XOAUTH2 and a single message per
+//! connection.
+
+use base64::Engine;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufStream};
+use tracing::{debug, warn};
+
+use crate::error::{EmailError, Result};
+use crate::oauth::xoauth2;
+use crate::send::EmailConfig;
+use crate::transport::{MailStream, is_implicit_tls};
+
+/// A server reply: the status code and the text of all its lines
+#[derive(Debug)]
+struct Reply {
+    code: u16,
+    lines: Vec<String>,
+}
+
+impl Reply {
+    fn is_positive(&self) -> bool {
+        (200..400).contains(&self.code)
+    }
+
+    fn text(&self) -> String {
+        format!("{} {}", self.code, self.lines.join(" "))
+    }
+}
+
+struct SmtpConnection {
+    stream: BufStream<MailStream>,
+}
+
+impl SmtpConnection {
+    async fn read_reply(&mut self) -> Result<Reply> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stream
+                .read_line(&mut line)
+                .await
+                .map_err(|e| EmailError::SmtpSend(e.to_string()))?;
+            if read == 0 {
+                return Err(EmailError::SmtpSend("Connection closed by server".to_string()));
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            let code = line
+                .get(..3)
+                .and_then(|c| c.parse().ok())
+                .ok_or_else(|| EmailError::SmtpSend(format!("Invalid reply: {}", line)))?;
+            lines.push(line.get(4..).unwrap_or_default().to_string());
+            // `250-...` continues, `250 ...` ends the reply
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(Reply { code, lines });
+            }
+        }
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<()> {
+        let io_error = |e: std::io::Error| EmailError::SmtpSend(e.to_string());
+        self.stream.write_all(data).await.map_err(io_error)?;
+        self.stream.flush().await.map_err(io_error)
+    }
+
+    /// Send a command and require a positive reply
+    async fn command(&mut self, command: &str) -> Result<Reply> {
+        let verb = command.split(' ').next().unwrap_or_default();
+        debug!("SMTP > {}", if verb == "AUTH" { "AUTH ***" } else { command });
+        self.write(format!("{}\r\n", command).as_bytes()).await?;
+        let reply = self.read_reply().await?;
+        if !reply.is_positive() {
+            return Err(EmailError::SmtpSend(format!("{}: {}", verb, reply.text())));
+        }
+        Ok(reply)
+    }
+
+    async fn ehlo(&mut self) -> Result<Vec<String>> {
+        let reply = self.command("EHLO cc-gateway").await?;
+        // The first line is the greeting, the rest are extensions
+        Ok(reply
+            .lines
+            .iter()
+            .skip(1)
+            .map(|l| l.to_ascii_uppercase())
+            .collect())
+    }
+}
+
+/// Deliver one message (`data` is the rendered RFC 5322 message)
+pub(crate) async fn send_mail(
+    config: &EmailConfig,
+    from: &str,
+    recipients: &[String],
+    data: &str,
+) -> Result<()> {
+    let connection_error =
+        |e: std::io::Error| EmailError::SmtpSend(format!("{}: {}", config.smtp_host, e));
+    let stream = MailStream::connect(
+        &config.smtp_host,
+        config.smtp_port,
+        is_implicit_tls(config.smtp_port),
+    )
+    .await
+    .map_err(connection_error)?;
+    let mut conn = SmtpConnection {
+        stream: BufStream::new(stream),
+    };
+
+    let greeting = conn.read_reply().await?;
+    if greeting.code != 220 {
+        return Err(EmailError::SmtpSend(greeting.text()));
+    }
+    let mut extensions = conn.ehlo().await?;
+
+    if !conn.stream.get_ref().is_tls() {
+        if extensions.iter().any(|e| e == "STARTTLS") {
+            conn.command("STARTTLS").await?;
+            let stream = conn
+                .stream
+                .into_inner()
+                .start_tls(&config.smtp_host)
+                .await
+                .map_err(connection_error)?;
+            conn = SmtpConnection {
+                stream: BufStream::new(stream),
+            };
+            extensions = conn.ehlo().await?;
+        } else if config.smtp_user.is_empty() || config.allow_insecure_auth {
+            warn!("SMTP server {} does not offer TLS", config.smtp_host);
+        } else {
+            // Without STARTTLS (or with it stripped on the way) the password would go in cleartext
+            return Err(EmailError::InsecureAuth(format!(
+                "SMTP server {} does not offer STARTTLS (set SMTP_ALLOW_INSECURE_AUTH=true to log in anyway)",
+                config.smtp_host
+            )));
+        }
+    }
+
+    if !config.smtp_user.is_empty() {
+        authenticate(&mut conn, config, &extensions).await?;
+    }
+
+    conn.command(&format!("MAIL FROM:<{}>", from)).await?;
+    for recipient in recipients {
+        conn.command(&format!("RCPT TO:<{}>", recipient))
+            .await
+            .map_err(|e| match e {
+                EmailError::SmtpSend(message) if message.contains(": 55") => {
+                    EmailError::InvalidAddress(format!("{} ({})", recipient, message))
+                }
+                other => other,
+            })?;
+    }
+    conn.command("DATA").await?;
+    conn.write(dot_stuff(data).as_bytes()).await?;
+    let reply = conn.read_reply().await?;
+    if !reply.is_positive() {
+        return Err(EmailError::SmtpSend(reply.text()));
+    }
+
+    if let Err(e) = conn.command("QUIT").await {
+        debug!("SMTP quit failed: {}", e);
+    }
+    Ok(())
+}
+
+async fn authenticate(
+    conn: &mut SmtpConnection,
+    config: &EmailConfig,
+    extensions: &[String],
+) -> Result<()> {
+    let encode = |value: &str| base64::engine::general_purpose::STANDARD.encode(value);
+    let mechanisms: Vec<&str> = extensions
+        .iter()
+        .filter_map(|e| e.strip_prefix("AUTH").map(str::trim))
+        .flat_map(|m| m.trim_start_matches('=').split_whitespace())
+        .collect();
+    let auth_error = |e: EmailError| match e {
+        EmailError::SmtpSend(message) => EmailError::AuthFailed(message),
+        other => other,
+    };
+
+    if let Some(ref oauth) = config.oauth {
+        let token = oauth.access_token().await?;
+        let reply = conn
+            .command(&format!("AUTH XOAUTH2 {}", xoauth2(&config.smtp_user, &token)))
+            .await
+            .map_err(auth_error)?;
+        if reply.code == 334 {
+            // A challenge carries the error details; answer it so the server ends the exchange
+            let details = base64::engine::general_purpose::STANDARD
+                .decode(reply.lines.join(""))
+                .map(|d| String::from_utf8_lossy(&d).into_owned())
+                .unwrap_or_else(|_| reply.text());
+            conn.write(b"\r\n").await?;
+            let _ = conn.read_reply().await;
+            return Err(EmailError::AuthFailed(details));
+        }
+        return Ok(());
+    }
+
+    if mechanisms.contains(&"LOGIN") && !mechanisms.contains(&"PLAIN") {
+        conn.command("AUTH LOGIN").await.map_err(auth_error)?;
+        conn.command(&encode(&config.smtp_user)).await.map_err(auth_error)?;
+        conn.command(&encode(&config.smtp_pass)).await.map_err(auth_error)?;
+    } else {
+        let credentials = format!("\0{}\0{}", config.smtp_user, config.smtp_pass);
+        conn.command(&format!("AUTH PLAIN {}", encode(&credentials)))
+            .await
+            .map_err(auth_error)?;
+    }
+    Ok(())
+}
+
+/// Normalize line endings to CRLF, escape leading dots and add the terminator
+fn dot_stuff(data: &str) -> String {
+    let mut output = String::with_capacity(data.len() + 8);
+    for line in data.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.starts_with('.') {
+            output.push('.');
+        }
+        output.push_str(line);
+        output.push_str("\r\n");
+    }
+    output.push_str(".\r\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn config(port: u16) -> EmailConfig {
+        EmailConfig {
+            smtp_host: "127.0.0.1".to_string(),
+            smtp_port: port,
+            smtp_user: "bot".to_string(),
+            smtp_pass: "secret".to_string(),
+            from_address: "bot@example.com".to_string(),
+            from_name: None,
+            oauth: None,
+            allow_insecure_auth: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_mail() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (read, mut write) = socket.into_split();
+            let mut lines = BufReader::new(read).lines();
+            let mut received = Vec::new();
+            write.write_all(b"220 fake ESMTP\r\n").await.unwrap();
+            while let Ok(Some(line)) = lines.next_line().await {
+                received.push(line.clone());
+                let reply: &[u8] = match line.as_str() {
+                    l if l.starts_with("EHLO") => b"250-fake\r\n250-AUTH PLAIN LOGIN\r\n250 8BITMIME\r\n",
+                    l if l.starts_with("AUTH") => b"235 ok\r\n",
+                    "DATA" => b"354 go ahead\r\n",
+                    "." => b"250 queued\r\n",
+                    "QUIT" => {
+                        write.write_all(b"221 bye\r\n").await.unwrap();
+                        break;
+                    }
+                    l if l.starts_with("MAIL") || l.starts_with("RCPT") => b"250 ok\r\n",
+                    _ => continue,
+                };
+                write.write_all(reply).await.unwrap();
+            }
+            received
+        });
+
+        send_mail(
+            &config(port),
+            "bot@example.com",
+            &["user@example.com".to_string()],
+            "Subject: hi\n\n.hidden\nbody",
+        )
+        .await
+        .unwrap();
+
+        let received = server.await.unwrap();
+        assert_eq!(received[1], "AUTH PLAIN AGJvdABzZWNyZXQ=");
+        assert!(received.contains(&"MAIL FROM:<bot@example.com>".to_string()));
+        assert!(received.contains(&"RCPT TO:<user@example.com>".to_string()));
+        assert!(received.contains(&"..hidden".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_no_auth_without_tls() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (read, mut write) = socket.into_split();
+            let mut lines = BufReader::new(read).lines();
+            let mut received = Vec::new();
+            write.write_all(b"220 fake ESMTP\r\n").await.unwrap();
+            while let Ok(Some(line)) = lines.next_line().await {
+                received.push(line.clone());
+                if line.starts_with("EHLO") {
+                    // No STARTTLS, as if it had been stripped on the way
+                    write.write_all(b"250-fake\r\n250 AUTH PLAIN LOGIN\r\n").await.unwrap();
+                }
+            }
+            received
+        });
+
+        let config = EmailConfig {
+            allow_insecure_auth: false,
+            ..config(port)
+        };
+        let result = send_mail(&config, "bot@example.com", &["user@example.com".to_string()], "hi").await;
+        assert!(matches!(result, Err(EmailError::InsecureAuth(_))));
+
+        let received = server.await.unwrap();
+        assert!(received.iter().all(|line| !line.starts_with("AUTH")));
+    }
+
+    #[test]
+    fn test_dot_stuff() {
+        assert_eq!(dot_stuff("a\r\n.b\nc"), "a\r\n..b\r\nc\r\n.\r\n");
+    }
+}
@@ -1,12 +1,16 @@
 //! Email tools for cc-gateway
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use chrono::NaiveDate;
 use serde_json::{json, Value};
 
 use cc_core::{Tool, ToolResult};
 
 use super::error::Result;
-use super::receive::{EmailReceiver, ImapConfig};
+use super::message::{Attachment, OutgoingEmail};
+use super::receive::{EmailReceiver, FlagAction, ImapConfig, SearchCriteria};
 use super::send::{EmailConfig, EmailSender};
 
 /// Email send tool
@@ -28,7 +32,7 @@ impl Tool for EmailSendTool {
     }
 
     fn description(&self) -> &str {
-        "Send an email via SMTP"
+        "Send an email via SMTP, optionally with HTML, CC/BCC and file attachments"
     }
 
     fn input_schema(&self) -> Value {
@@ -37,7 +41,21 @@ impl Tool for EmailSendTool {
             "properties": {
                 "to": {
                     "type": "string",
-                    "description": "Recipient email address"
+                    "description": "Recipient email address (comma-separated for several)"
+                },
+                "cc": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "CC recipients"
+                },
+                "bcc": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "BCC recipients (not shown to other recipients)"
+                },
+                "reply_to": {
+                    "type": "string",
+                    "description": "Address replies should go to"
                 },
                 "subject": {
                     "type": "string",
@@ -51,12 +69,66 @@ impl Tool for EmailSendTool {
                     "type": "boolean",
                     "description": "Whether body is HTML (default: false)",
                     "default": false
+                },
+                "html_body": {
+                    "type": "string",
+                    "description": "HTML version of the email; body is sent as its plain-text alternative"
+                },
+                "attachments": {
+                    "type": "array",
+                    "description": "Files to attach",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Path of the file to attach"
+                            },
+                            "filename": {
+                                "type": "string",
+                                "description": "File name shown to the recipient (default: the file's name)"
+                            },
+                            "content_type": {
+                                "type": "string",
+                                "description": "MIME type (default: guessed from the extension)"
+                            },
+                            "inline_id": {
+                                "type": "string",
+                                "description": "Show the image inline; reference it in html_body as <img src=\"cid:ID\">"
+                            }
+                        },
+                        "required": ["path"]
+                    }
                 }
             },
             "required": ["to", "subject", "body"]
         })
     }
 
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let mut draft = vec![format!("From: {}", self.sender.from_address())];
+        for (header, key) in [("To", "to"), ("Cc", "cc"), ("Bcc", "bcc")] {
+            let list = addresses(&input[key]);
+            if !list.is_empty() {
+                draft.push(format!("{}: {}", header, list.join(", ")));
+            }
+        }
+        if let Some(reply_to) = input["reply_to"].as_str() {
+            draft.push(format!("Reply-To: {}", reply_to));
+        }
+        draft.push(format!("Subject: {}", input["subject"].as_str()?));
+        if let Some(attachments) = input["attachments"].as_array() {
+            let paths: Vec<&str> = attachments.iter().filter_map(|a| a["path"].as_str()).collect();
+            draft.push(format!("Attachments: {}", paths.join(", ")));
+        }
+        draft.push(String::new());
+        draft.push(input["body"].as_str()?.to_string());
+        if input["html_body"].is_string() {
+            draft.push("(with an HTML version)".to_string());
+        }
+        Some(draft.join("\n"))
+    }
+
     async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
         let to = input["to"]
             .as_str()
@@ -69,21 +141,81 @@ impl Tool for EmailSendTool {
             .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'body' parameter".to_string()))?;
         let html = input["html"].as_bool().unwrap_or(false);
 
-        let result = self
+        let mut email = OutgoingEmail {
+            to: addresses(&Value::from(to)),
+            cc: addresses(&input["cc"]),
+            bcc: addresses(&input["bcc"]),
+            reply_to: input["reply_to"].as_str().map(str::to_string),
+            subject: subject.to_string(),
+            ..Default::default()
+        };
+        match input["html_body"].as_str() {
+            Some(html_body) => {
+                email.body = body.to_string();
+                email.html_body = Some(html_body.to_string());
+            }
+            None if html => email.html_body = Some(body.to_string()),
+            None => email.body = body.to_string(),
+        }
+        if let Some(attachments) = input["attachments"].as_array() {
+            for attachment in attachments {
+                email.attachments.push(load_attachment(attachment)?);
+            }
+        }
+
+        let message_id = self
             .sender
-            .send(to, subject, body, html)
+            .send_message(&email)
             .await
             .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
 
         Ok(ToolResult::success(serde_json::to_string(&json!({
             "status": "sent",
-            "to": to,
+            "to": email.to,
+            "cc": email.cc,
+            "bcc": email.bcc,
             "subject": subject,
-            "message": result
+            "attachments": email.attachments.iter().map(|a| &a.filename).collect::<Vec<_>>(),
+            "message_id": message_id
         })).unwrap_or_default()))
     }
 }
 
+/// Addresses from a string (comma separated) or an array of strings
+fn addresses(value: &Value) -> Vec<String> {
+    let values: Vec<&str> = match value {
+        Value::String(s) => s.split(',').collect(),
+        Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    values
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Read an attachment described by `{path, filename?, content_type?, inline_id?}`
+fn load_attachment(spec: &Value) -> cc_core::Result<Attachment> {
+    let path = spec["path"].as_str().ok_or_else(|| {
+        cc_core::Error::ToolExecution("Attachment is missing 'path'".to_string())
+    })?;
+    let mut attachment = Attachment::from_path(path).map_err(|e| {
+        cc_core::Error::ToolExecution(format!("Cannot read attachment {}: {}", path, e))
+    })?;
+    if let Some(filename) = spec["filename"].as_str() {
+        attachment.filename = filename.to_string();
+    }
+    if let Some(content_type) = spec["content_type"].as_str() {
+        attachment.content_type = content_type.to_string();
+    }
+    if let Some(id) = spec["inline_id"].as_str() {
+        attachment = attachment.inline(id);
+    }
+    Ok(attachment)
+}
+
 /// Email list tool
 pub struct EmailListTool {
     receiver: EmailReceiver,
@@ -103,6 +235,10 @@ impl Tool for EmailListTool {
         "email_list"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "List emails from an IMAP folder"
     }
@@ -175,6 +311,10 @@ impl Tool for EmailReadTool {
         "email_read"
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Read a specific email by UID"
     }
@@ -219,6 +359,415 @@ impl Tool for EmailReadTool {
     }
 }
 
+/// Email search tool
+pub struct EmailSearchTool {
+    receiver: EmailReceiver,
+}
+
+impl EmailSearchTool {
+    pub fn new(config: ImapConfig) -> Self {
+        Self {
+            receiver: EmailReceiver::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for EmailSearchTool {
+    fn name(&self) -> &str {
+        "email_search"
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Search emails in an IMAP folder by sender, recipient, subject, text, date range and read/flag state"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "folder": {
+                    "type": "string",
+                    "description": "IMAP folder name (default: INBOX)",
+                    "default": "INBOX"
+                },
+                "from": {
+                    "type": "string",
+                    "description": "Sender address or name contains this text"
+                },
+                "to": {
+                    "type": "string",
+                    "description": "Recipient address or name contains this text"
+                },
+                "subject": {
+                    "type": "string",
+                    "description": "Subject contains this text"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "Headers or body contain this text"
+                },
+                "since": {
+                    "type": "string",
+                    "description": "Received on or after this date (YYYY-MM-DD)"
+                },
+                "before": {
+                    "type": "string",
+                    "description": "Received before this date (YYYY-MM-DD)"
+                },
+                "unread": {
+                    "type": "boolean",
+                    "description": "true: unread only, false: read only"
+                },
+                "flagged": {
+                    "type": "boolean",
+                    "description": "true: flagged only, false: unflagged only"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of emails to return (default: 20)",
+                    "default": 20
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let folder = input["folder"].as_str().unwrap_or("INBOX");
+        let limit = input["limit"].as_u64().unwrap_or(20) as usize;
+        let text = |key: &str| input[key].as_str().map(str::to_string);
+        let criteria = SearchCriteria {
+            from: text("from"),
+            to: text("to"),
+            subject: text("subject"),
+            text: text("text"),
+            since: parse_date(&input["since"])?,
+            before: parse_date(&input["before"])?,
+            unseen: input["unread"].as_bool(),
+            flagged: input["flagged"].as_bool(),
+        };
+
+        let emails = self
+            .receiver
+            .search(folder, &criteria, limit)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        let summaries: Vec<Value> = emails
+            .iter()
+            .map(|e| {
+                json!({
+                    "uid": e.uid,
+                    "subject": e.subject,
+                    "from": e.from,
+                    "date": e.date,
+                    "flags": e.flags
+                })
+            })
+            .collect();
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "folder": folder,
+            "query": criteria.to_imap(),
+            "count": summaries.len(),
+            "emails": summaries
+        })).unwrap_or_default()))
+    }
+}
+
+/// Email folder listing tool
+pub struct EmailFoldersTool {
+    receiver: EmailReceiver,
+}
+
+impl EmailFoldersTool {
+    pub fn new(config: ImapConfig) -> Self {
+        Self {
+            receiver: EmailReceiver::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for EmailFoldersTool {
+    fn name(&self) -> &str {
+        "email_folders"
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "List the IMAP folders of the mailbox"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    async fn execute(&self, _input: Value) -> cc_core::Result<ToolResult> {
+        let folders = self
+            .receiver
+            .list_folders()
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        let folders: Vec<Value> = folders
+            .iter()
+            .filter(|f| f.is_selectable())
+            .map(|f| {
+                json!({
+                    "name": f.name,
+                    "attributes": f.attributes
+                })
+            })
+            .collect();
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "count": folders.len(),
+            "folders": folders
+        })).unwrap_or_default()))
+    }
+}
+
+/// Email move tool
+pub struct EmailMoveTool {
+    receiver: EmailReceiver,
+}
+
+impl EmailMoveTool {
+    pub fn new(config: ImapConfig) -> Self {
+        Self {
+            receiver: EmailReceiver::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for EmailMoveTool {
+    fn name(&self) -> &str {
+        "email_move"
+    }
+
+    fn description(&self) -> &str {
+        "Move emails to another IMAP folder (e.g. to archive or file them)"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "folder": {
+                    "type": "string",
+                    "description": "Folder the emails are in (default: INBOX)",
+                    "default": "INBOX"
+                },
+                "uids": {
+                    "type": "array",
+                    "items": { "type": "integer" },
+                    "description": "UIDs of the emails to move"
+                },
+                "destination": {
+                    "type": "string",
+                    "description": "Destination folder name (see email_folders)"
+                }
+            },
+            "required": ["uids", "destination"]
+        })
+    }
+
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let uids = parse_uids(&input["uids"]).ok()?;
+        Some(format!(
+            "Move {} from {} to {}",
+            describe_uids(&uids),
+            input["folder"].as_str().unwrap_or("INBOX"),
+            input["destination"].as_str()?
+        ))
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let folder = input["folder"].as_str().unwrap_or("INBOX");
+        let uids = parse_uids(&input["uids"])?;
+        let destination = input["destination"].as_str().ok_or_else(|| {
+            cc_core::Error::ToolExecution("Missing 'destination' parameter".to_string())
+        })?;
+
+        self.receiver
+            .move_emails(folder, &uids, destination)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "moved",
+            "from": folder,
+            "to": destination,
+            "uids": uids
+        })).unwrap_or_default()))
+    }
+}
+
+/// Email flag tool
+pub struct EmailFlagTool {
+    receiver: EmailReceiver,
+}
+
+impl EmailFlagTool {
+    pub fn new(config: ImapConfig) -> Self {
+        Self {
+            receiver: EmailReceiver::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for EmailFlagTool {
+    fn name(&self) -> &str {
+        "email_flag"
+    }
+
+    fn description(&self) -> &str {
+        "Mark emails as read or unread, or flag / unflag them"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "folder": {
+                    "type": "string",
+                    "description": "Folder the emails are in (default: INBOX)",
+                    "default": "INBOX"
+                },
+                "uids": {
+                    "type": "array",
+                    "items": { "type": "integer" },
+                    "description": "UIDs of the emails"
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["mark_read", "mark_unread", "flag", "unflag"],
+                    "description": "What to do with the emails"
+                }
+            },
+            "required": ["uids", "action"]
+        })
+    }
+
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let uids = parse_uids(&input["uids"]).ok()?;
+        let change = match FlagAction::parse(input["action"].as_str()?)? {
+            FlagAction::MarkRead => "Mark as read",
+            FlagAction::MarkUnread => "Mark as unread",
+            FlagAction::Flag => "Flag",
+            FlagAction::Unflag => "Unflag",
+        };
+        Some(format!(
+            "{} {} in {}",
+            change,
+            describe_uids(&uids),
+            input["folder"].as_str().unwrap_or("INBOX")
+        ))
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let folder = input["folder"].as_str().unwrap_or("INBOX");
+        let uids = parse_uids(&input["uids"])?;
+        let action_name = input["action"].as_str().unwrap_or_default();
+        let action = FlagAction::parse(action_name).ok_or_else(|| {
+            cc_core::Error::ToolExecution(format!(
+                "Invalid action '{}' (mark_read, mark_unread, flag, unflag)",
+                action_name
+            ))
+        })?;
+
+        self.receiver
+            .set_flags(folder, &uids, action)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "status": "updated",
+            "folder": folder,
+            "action": action_name,
+            "uids": uids
+        })).unwrap_or_default()))
+    }
+}
+
+/// A `YYYY-MM-DD` date parameter
+fn parse_date(value: &Value) -> cc_core::Result<Option<NaiveDate>> {
+    value
+        .as_str()
+        .map(|date| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                cc_core::Error::ToolExecution(format!("Invalid date '{}' (use YYYY-MM-DD)", date))
+            })
+        })
+        .transpose()
+}
+
+/// A non-empty list of UIDs
+fn parse_uids(value: &Value) -> cc_core::Result<Vec<u32>> {
+    let uids: Vec<u32> = value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_u64)
+                .filter_map(|uid| u32::try_from(uid).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    if uids.is_empty() {
+        return Err(cc_core::Error::ToolExecution(
+            "'uids' must be a non-empty array of email UIDs".to_string(),
+        ));
+    }
+    Ok(uids)
+}
+
+/// `3 emails (UIDs 3, 7, 9)` for a preview
+fn describe_uids(uids: &[u32]) -> String {
+    let list: Vec<String> = uids.iter().map(u32::to_string).collect();
+    if uids.len() == 1 {
+        format!("1 email (UID {})", list[0])
+    } else {
+        format!("{} emails (UIDs {})", uids.len(), list.join(", "))
+    }
+}
+
+/// Register the email tools for the configured servers
+///
+/// Sending needs SMTP settings; reading and triage need IMAP settings.
+pub fn register_email_tools(
+    manager: &mut cc_core::ToolManager,
+    smtp: Option<EmailConfig>,
+    imap: Option<ImapConfig>,
+) {
+    if let Some(smtp) = smtp {
+        if let Ok(tool) = EmailSendTool::new(smtp) {
+            manager.register(Arc::new(tool));
+        }
+    }
+    if let Some(imap) = imap {
+        manager.register(Arc::new(EmailListTool::new(imap.clone())));
+        manager.register(Arc::new(EmailReadTool::new(imap.clone())));
+        manager.register(Arc::new(EmailSearchTool::new(imap.clone())));
+        manager.register(Arc::new(EmailFoldersTool::new(imap.clone())));
+        manager.register(Arc::new(EmailMoveTool::new(imap.clone())));
+        manager.register(Arc::new(EmailFlagTool::new(imap)));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,6 +781,8 @@ mod tests {
             smtp_pass: "test".to_string(),
             from_address: "test@test.com".to_string(),
             from_name: None,
+            oauth: None,
+            allow_insecure_auth: false,
         };
         assert_eq!(config.smtp_host, "localhost");
     }
@@ -243,7 +794,122 @@ mod tests {
             imap_port: 993,
             imap_user: "test".to_string(),
             imap_pass: "test".to_string(),
+            oauth: None,
+            allow_insecure_auth: false,
         };
         assert_eq!(config.imap_host, "localhost");
     }
+
+    #[test]
+    fn test_addresses() {
+        assert_eq!(
+            addresses(&json!("a@example.com, b@example.com")),
+            vec!["a@example.com", "b@example.com"]
+        );
+        assert_eq!(addresses(&json!(["c@example.com", ""])), vec!["c@example.com"]);
+        assert!(addresses(&Value::Null).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_email_send_preview() {
+        let tool = EmailSendTool::new(EmailConfig {
+            smtp_host: "localhost".to_string(),
+            smtp_port: 25,
+            smtp_user: "test".to_string(),
+            smtp_pass: "test".to_string(),
+            from_address: "me@example.com".to_string(),
+            from_name: None,
+            oauth: None,
+            allow_insecure_auth: false,
+        })
+        .unwrap();
+        let preview = tool
+            .preview(&json!({
+                "to": "a@example.com, b@example.com",
+                "cc": ["c@example.com"],
+                "subject": "Report",
+                "body": "Attached.",
+                "attachments": [{"path": "/tmp/report.pdf"}]
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            preview,
+            "From: me@example.com\nTo: a@example.com, b@example.com\nCc: c@example.com\n\
+             Subject: Report\nAttachments: /tmp/report.pdf\n\nAttached."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_and_flag_previews() {
+        let imap = ImapConfig {
+            imap_host: "localhost".to_string(),
+            imap_port: 993,
+            imap_user: "test".to_string(),
+            imap_pass: "test".to_string(),
+            oauth: None,
+            allow_insecure_auth: false,
+        };
+
+        let preview = EmailMoveTool::new(imap.clone())
+            .preview(&json!({"uids": [3, 7], "destination": "Archive"}))
+            .await;
+        assert_eq!(preview.as_deref(), Some("Move 2 emails (UIDs 3, 7) from INBOX to Archive"));
+
+        let flag = EmailFlagTool::new(imap);
+        let preview = flag
+            .preview(&json!({"folder": "Work", "uids": [5], "action": "mark_read"}))
+            .await;
+        assert_eq!(preview.as_deref(), Some("Mark as read 1 email (UID 5) in Work"));
+        assert!(flag.preview(&json!({"uids": [5], "action": "delete"})).await.is_none());
+    }
+
+    #[test]
+    fn test_load_attachment() {
+        let path = std::env::temp_dir().join(format!("cc-email-test-{}.png", std::process::id()));
+        std::fs::write(&path, b"png").unwrap();
+
+        let attachment = load_attachment(&json!({
+            "path": path.to_string_lossy(),
+            "inline_id": "logo"
+        }))
+        .unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(attachment.content_type, "image/png");
+        assert_eq!(attachment.content_id.as_deref(), Some("logo"));
+        assert_eq!(attachment.data, b"png");
+
+        assert!(load_attachment(&json!({ "path": "/nonexistent/file.pdf" })).is_err());
+    }
+
+    #[test]
+    fn test_parse_params() {
+        assert_eq!(parse_uids(&json!([3, 7])).unwrap(), vec![3, 7]);
+        assert!(parse_uids(&json!([])).is_err());
+        assert!(parse_uids(&Value::Null).is_err());
+
+        assert_eq!(
+            parse_date(&json!("2024-02-01")).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 1)
+        );
+        assert_eq!(parse_date(&Value::Null).unwrap(), None);
+        assert!(parse_date(&json!("02/01/2024")).is_err());
+    }
+
+    #[test]
+    fn test_register_email_tools() {
+        let mut manager = cc_core::ToolManager::new();
+        let imap = ImapConfig {
+            imap_host: "localhost".to_string(),
+            imap_port: 993,
+            imap_user: "test".to_string(),
+            imap_pass: "test".to_string(),
+            oauth: None,
+            allow_insecure_auth: false,
+        };
+        register_email_tools(&mut manager, None, Some(imap));
+        assert_eq!(manager.len(), 6);
+        assert!(manager.tool_names().contains(&"email_search"));
+        assert!(!manager.tool_names().contains(&"email_send"));
+    }
 }
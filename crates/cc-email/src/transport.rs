@@ -0,0 +1,134 @@
+//! Connections to mail servers
+//!
+//! Plain TCP or TLS streams shared by the IMAP and SMTP clients. The
+//! standard TLS ports (993, 465) use implicit TLS; other ports start in
+//! plain text and are upgraded with STARTTLS when the server offers it.
+//! Without TLS the clients refuse to log in unless the configuration allows
+//! insecure authentication, since a stripped STARTTLS would otherwise leak
+//! the password.
+//!
+//! The IMAP and SMTP clients are our own rather than `lettre` (which the
+//! crate once listed as an optional, never-used feature): `lettre` only
+//! covers SMTP, while the channel also needs IMAP IDLE and UID commands, and
+//! both protocols share this rustls transport and the XOAUTH2 token refresh
+//! of [`crate::oauth`] instead of pulling in native-tls for SMTP alone.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
+
+/// Timeout for establishing a connection
+pub(crate) const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Ports that speak TLS from the first byte
+const IMPLICIT_TLS_PORTS: [u16; 2] = [993, 465];
+
+/// Whether `port` uses implicit TLS
+pub(crate) fn is_implicit_tls(port: u16) -> bool {
+    IMPLICIT_TLS_PORTS.contains(&port)
+}
+
+/// TCP stream to a mail server, optionally wrapped in TLS
+pub(crate) enum MailStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl MailStream {
+    /// Connect to `host:port`, using TLS right away if `tls` is set
+    pub(crate) async fn connect(host: &str, port: u16, tls: bool) -> io::Result<Self> {
+        let tcp = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "connection timed out"))??;
+        let stream = Self::Plain(tcp);
+        if tls {
+            stream.start_tls(host).await
+        } else {
+            Ok(stream)
+        }
+    }
+
+    /// Upgrade a plain connection to TLS (after STARTTLS was accepted)
+    pub(crate) async fn start_tls(self, host: &str) -> io::Result<Self> {
+        let tcp = match self {
+            Self::Plain(tcp) => tcp,
+            tls @ Self::Tls(_) => return Ok(tls),
+        };
+        let server_name = ServerName::try_from(host.to_string())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let stream = tls_connector().connect(server_name, tcp).await?;
+        Ok(Self::Tls(Box::new(stream)))
+    }
+
+    pub(crate) fn is_tls(&self) -> bool {
+        matches!(self, Self::Tls(_))
+    }
+}
+
+/// TLS connector trusting the Mozilla root certificates
+fn tls_connector() -> TlsConnector {
+    static CONNECTOR: OnceLock<TlsConnector> = OnceLock::new();
+    CONNECTOR
+        .get_or_init(|| {
+            let roots = RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            };
+            let config =
+                ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                    .with_safe_default_protocol_versions()
+                    .expect("ring supports the default TLS versions")
+                    .with_root_certificates(roots)
+                    .with_no_client_auth();
+            TlsConnector::from(Arc::new(config))
+        })
+        .clone()
+}
+
+impl AsyncRead for MailStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            Self::Tls(s) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for MailStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            Self::Tls(s) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_flush(cx),
+            Self::Tls(s) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            Self::Tls(s) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
@@ -1,21 +1,61 @@
 //! Facebook Messenger API client
 
+use cc_core::WebhookVerifier;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::error::{FacebookError, Result};
+use crate::window::Delivery;
 
 /// Facebook Messenger API base URL
 const FACEBOOK_API_URL: &str = "https://graph.facebook.com/v18.0";
 
+/// App ID of the Page Inbox, where people answer conversations handed over
+/// by the bot
+pub const PAGE_INBOX_APP_ID: &str = "263902037430900";
+
+/// Type of an attachment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentType {
+    Image,
+    Audio,
+    Video,
+    File,
+}
+
+impl AttachmentType {
+    /// Attachment type in the Send API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::Video => "video",
+            Self::File => "file",
+        }
+    }
+
+    /// Type for a MIME type (anything else is sent as a file)
+    pub fn from_mime(mime_type: &str) -> Self {
+        match mime_type.split('/').next().unwrap_or_default() {
+            "image" => Self::Image,
+            "audio" => Self::Audio,
+            "video" => Self::Video,
+            _ => Self::File,
+        }
+    }
+}
+
 /// Facebook API client
 #[derive(Clone)]
 pub struct FacebookApi {
     client: Client,
+    base_url: String,
     page_id: String,
     access_token: String,
     verify_token: String,
+    app_secret: Option<String>,
 }
 
 impl FacebookApi {
@@ -23,28 +63,71 @@ impl FacebookApi {
     pub fn new(page_id: &str, access_token: &str, verify_token: &str) -> Self {
         Self {
             client: Client::new(),
+            base_url: FACEBOOK_API_URL.to_string(),
             page_id: page_id.to_string(),
             access_token: access_token.to_string(),
             verify_token: verify_token.to_string(),
+            app_secret: None,
         }
     }
 
-    /// Send a message to a user via Facebook Messenger
+    /// Check webhook signatures with the app secret
+    pub fn with_app_secret(mut self, app_secret: &str) -> Self {
+        self.app_secret = Some(app_secret.to_string());
+        self
+    }
+
+    /// Use another Graph API base URL (for tests)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Send a reply to a user via Facebook Messenger
     pub async fn send_message(&self, recipient_id: &str, message: &str) -> Result<MessageResponse> {
-        let url = format!("{}/{}/messages", FACEBOOK_API_URL, self.page_id);
+        self.send_text(recipient_id, message, Delivery::Response).await
+    }
+
+    /// Send a text message
+    pub async fn send_text(&self, recipient_id: &str, text: &str, delivery: Delivery) -> Result<MessageResponse> {
+        debug!("Sending message to {}: {}", recipient_id, text);
+        self.send(recipient_id, OutgoingMessage::Text { text: text.to_string() }, delivery)
+            .await
+    }
+
+    /// Send an attachment from a public URL
+    pub async fn send_attachment(
+        &self,
+        recipient_id: &str,
+        attachment_type: AttachmentType,
+        url: &str,
+        delivery: Delivery,
+    ) -> Result<MessageResponse> {
+        debug!("Sending {} to {}: {}", attachment_type.as_str(), recipient_id, url);
+        let message = OutgoingMessage::Attachment {
+            attachment: OutgoingAttachment {
+                attachment_type: attachment_type.as_str().to_string(),
+                payload: OutgoingAttachmentPayload {
+                    url: url.to_string(),
+                    is_reusable: true,
+                },
+            },
+        };
+        self.send(recipient_id, message, delivery).await
+    }
+
+    async fn send(&self, recipient_id: &str, message: OutgoingMessage, delivery: Delivery) -> Result<MessageResponse> {
+        let url = format!("{}/{}/messages", self.base_url, self.page_id);
 
         let payload = SendMessagePayload {
-            messaging_type: "RESPONSE".to_string(),
+            messaging_type: delivery.messaging_type().to_string(),
             recipient: Recipient {
                 id: recipient_id.to_string(),
             },
-            message: MessageText {
-                text: message.to_string(),
-            },
+            message,
+            tag: delivery.tag().map(str::to_string),
         };
 
-        debug!("Sending message to {}: {}", recipient_id, message);
-
         let response = self
             .client
             .post(&url)
@@ -66,9 +149,85 @@ impl FacebookApi {
         Ok(message_response)
     }
 
+    /// Download a received attachment, returning its bytes and content type
+    pub async fn download_attachment(&self, url: &str) -> Result<(Vec<u8>, String)> {
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(FacebookError::Api(format!("attachment download failed: {}", status)));
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let bytes = response.bytes().await?;
+        Ok((bytes.to_vec(), content_type))
+    }
+
+    /// Hand the conversation over to another app (handover protocol)
+    ///
+    /// Pass [`PAGE_INBOX_APP_ID`] to escalate to a person in the Page Inbox.
+    pub async fn pass_thread_control(&self, psid: &str, target_app_id: &str, metadata: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "recipient": { "id": psid },
+            "target_app_id": target_app_id,
+            "metadata": metadata,
+        });
+        self.thread_control("pass_thread_control", &body).await?;
+        info!("Passed thread control for {} to app {}", psid, target_app_id);
+        Ok(())
+    }
+
+    /// Take the conversation back from the app that has it
+    /// (the bot must be the primary receiver)
+    pub async fn take_thread_control(&self, psid: &str, metadata: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "recipient": { "id": psid },
+            "metadata": metadata,
+        });
+        self.thread_control("take_thread_control", &body).await?;
+        info!("Took thread control for {}", psid);
+        Ok(())
+    }
+
+    /// Ask the primary receiver to hand the conversation over
+    pub async fn request_thread_control(&self, psid: &str, metadata: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "recipient": { "id": psid },
+            "metadata": metadata,
+        });
+        self.thread_control("request_thread_control", &body).await?;
+        Ok(())
+    }
+
+    async fn thread_control(&self, endpoint: &str, body: &serde_json::Value) -> Result<()> {
+        let url = format!("{}/{}/{}", self.base_url, self.page_id, endpoint);
+
+        let response = self
+            .client
+            .post(&url)
+            .query(&[("access_token", &self.access_token)])
+            .json(body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            error!("Facebook API error: {} - {}", status, body);
+            return Err(FacebookError::Api(format!("{} - {}", status, body)));
+        }
+
+        Ok(())
+    }
+
     /// Get user profile information
     pub async fn get_user_profile(&self, user_id: &str) -> Result<UserProfile> {
-        let url = format!("{}/{}", FACEBOOK_API_URL, user_id);
+        let url = format!("{}/{}", self.base_url, user_id);
 
         let response = self
             .client
@@ -105,6 +264,20 @@ impl FacebookApi {
         }
     }
 
+    /// Check the `X-Hub-Signature-256` header of a webhook request
+    pub fn verify_signature(&self, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        let Some(ref secret) = self.app_secret else {
+            return Err(FacebookError::AppSecretNotSet);
+        };
+        let verifier = WebhookVerifier::Meta {
+            app_secret: secret.clone(),
+        };
+        verifier.verify(headers, "", body).map_err(|e| {
+            warn!("Rejected webhook: {}", e);
+            FacebookError::WebhookVerificationFailed
+        })
+    }
+
     /// Handle incoming webhook payload
     pub fn handle_webhook(&self, payload: &str) -> Result<Vec<WebhookEntry>> {
         let webhook_payload: WebhookPayload = serde_json::from_str(payload)
@@ -126,7 +299,9 @@ impl FacebookApi {
 struct SendMessagePayload {
     messaging_type: String,
     recipient: Recipient,
-    message: MessageText,
+    message: OutgoingMessage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -135,8 +310,23 @@ struct Recipient {
 }
 
 #[derive(Debug, Serialize)]
-struct MessageText {
-    text: String,
+#[serde(untagged)]
+enum OutgoingMessage {
+    Text { text: String },
+    Attachment { attachment: OutgoingAttachment },
+}
+
+#[derive(Debug, Serialize)]
+struct OutgoingAttachment {
+    #[serde(rename = "type")]
+    attachment_type: String,
+    payload: OutgoingAttachmentPayload,
+}
+
+#[derive(Debug, Serialize)]
+struct OutgoingAttachmentPayload {
+    url: String,
+    is_reusable: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -163,6 +353,8 @@ pub struct WebhookEntry {
     pub id: Option<String>,
     pub time: Option<i64>,
     pub messaging: Option<Vec<WebhookMessaging>>,
+    /// Events of conversations another app has control of (handover protocol)
+    pub standby: Option<Vec<WebhookMessaging>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -171,6 +363,30 @@ pub struct WebhookMessaging {
     pub recipient: Option<WebhookRecipient>,
     pub timestamp: Option<i64>,
     pub message: Option<WebhookMessage>,
+    /// Control of the conversation was passed to this app
+    pub pass_thread_control: Option<PassThreadControl>,
+    /// Control of the conversation was taken from this app
+    pub take_thread_control: Option<TakeThreadControl>,
+    /// Another app asked for control of the conversation
+    pub request_thread_control: Option<RequestThreadControl>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PassThreadControl {
+    pub new_owner_app_id: Option<String>,
+    pub metadata: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TakeThreadControl {
+    pub previous_owner_app_id: Option<String>,
+    pub metadata: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequestThreadControl {
+    pub requested_owner_app_id: Option<String>,
+    pub metadata: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -188,6 +404,24 @@ pub struct WebhookMessage {
     pub mid: Option<String>,
     pub text: Option<String>,
     pub quick_reply: Option<WebhookQuickReply>,
+    pub attachments: Option<Vec<WebhookAttachment>>,
+    /// Set on copies of messages the page sent
+    pub is_echo: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookAttachment {
+    /// `image`, `audio`, `video`, `file`, `fallback`, ...
+    #[serde(rename = "type")]
+    pub attachment_type: String,
+    pub payload: Option<WebhookAttachmentPayload>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookAttachmentPayload {
+    pub url: Option<String>,
+    /// Set for stickers (sent as images)
+    pub sticker_id: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -198,6 +432,10 @@ pub struct WebhookQuickReply {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::window::MessageTag;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
     fn test_webhook_payload_parsing() {
@@ -220,6 +458,107 @@ mod tests {
         assert_eq!(entries.len(), 1);
     }
 
+    #[test]
+    fn test_webhook_attachments_and_handover() {
+        let payload = r#"{
+            "object": "page",
+            "entry": [{
+                "id": "page123",
+                "messaging": [{
+                    "sender": {"id": "user123"},
+                    "recipient": {"id": "page123"},
+                    "message": {"mid": "mid.1", "attachments": [
+                        {"type": "image", "payload": {"url": "https://cdn.example.com/a.jpg"}}
+                    ]}
+                }, {
+                    "sender": {"id": "user123"},
+                    "recipient": {"id": "page123"},
+                    "pass_thread_control": {"new_owner_app_id": "123", "metadata": "done"}
+                }],
+                "standby": [{
+                    "sender": {"id": "user456"},
+                    "recipient": {"id": "page123"},
+                    "message": {"mid": "mid.2", "text": "Hi"}
+                }]
+            }]
+        }"#;
+
+        let entries = FacebookApi::new("test", "test", "verify").handle_webhook(payload).unwrap();
+        let messaging = entries[0].messaging.as_ref().unwrap();
+        let attachments = messaging[0].message.as_ref().unwrap().attachments.as_ref().unwrap();
+        assert_eq!(attachments[0].attachment_type, "image");
+        assert!(messaging[1].pass_thread_control.is_some());
+        assert_eq!(entries[0].standby.as_ref().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_attachment_with_tag() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/page123/messages"))
+            .and(body_partial_json(json!({
+                "messaging_type": "MESSAGE_TAG",
+                "tag": "HUMAN_AGENT",
+                "recipient": { "id": "user123" },
+                "message": { "attachment": { "type": "image", "payload": { "url": "https://example.com/a.png" } } },
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "recipient_id": "user123", "message_id": "m_1",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let api = FacebookApi::new("page123", "token", "verify").with_base_url(&server.uri());
+        let response = api
+            .send_attachment(
+                "user123",
+                AttachmentType::Image,
+                "https://example.com/a.png",
+                Delivery::Tagged(MessageTag::HumanAgent),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.message_id.as_deref(), Some("m_1"));
+    }
+
+    #[tokio::test]
+    async fn test_pass_thread_control() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/page123/pass_thread_control"))
+            .and(body_partial_json(json!({
+                "recipient": { "id": "user123" },
+                "target_app_id": PAGE_INBOX_APP_ID,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let api = FacebookApi::new("page123", "token", "verify").with_base_url(&server.uri());
+        api.pass_thread_control("user123", PAGE_INBOX_APP_ID, "escalated")
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let body = br#"{"object":"page","entry":[]}"#;
+        let mut signed = HeaderMap::new();
+        signed.insert(
+            "x-hub-signature-256",
+            "sha256=550e646d4869f9eaf9e56ccc2b73b528c8d3aac4a24ff137e0a26e3e45bbd075".parse().unwrap(),
+        );
+        let api = FacebookApi::new("test", "test", "verify");
+        assert!(matches!(api.verify_signature(&signed, body), Err(FacebookError::AppSecretNotSet)));
+
+        let api = api.with_app_secret("secret");
+        assert!(api.verify_signature(&signed, body).is_ok());
+        assert!(api.verify_signature(&signed, b"{}").is_err());
+        assert!(api.verify_signature(&HeaderMap::new(), body).is_err());
+    }
+
     #[test]
     fn test_verify_webhook() {
         let api = FacebookApi::new("test", "test", "verify");
@@ -20,6 +20,9 @@ pub enum FacebookError {
     #[error("Facebook API request failed: {0}")]
     Request(String),
 
+    #[error("Messaging window with {0} is closed and no usable message tag was given")]
+    OutsideMessagingWindow(String),
+
     #[error("Facebook webhook verification failed")]
     WebhookVerificationFailed,
 
@@ -1,19 +1,36 @@
 //! Message handler for Facebook Messenger
 
+use std::collections::HashSet;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
-use cc_core::{ClaudeClient, Message as CoreMessage, MessageContent};
+use cc_core::{ClaudeClient, ImageSource, Message as CoreMessage, MessageContent};
+use cc_voice::WhisperClient;
 
-use crate::api::{FacebookApi, WebhookEntry, WebhookMessaging};
+use crate::api::{AttachmentType, FacebookApi, MessageResponse, WebhookEntry, WebhookMessaging, PAGE_INBOX_APP_ID};
 use crate::error::Result;
+use crate::media::MessageInput;
 use crate::session::InMemorySessionStore;
+use crate::window::{MessageTag, MessagingWindow};
+
+/// Quick reply payload that asks for a person
+pub const HUMAN_AGENT_PAYLOAD: &str = "HUMAN_AGENT";
+
+/// Command that asks for a person
+const HUMAN_COMMAND: &str = "/human";
 
 /// Facebook message handler
 pub struct FacebookHandler {
     api: FacebookApi,
     session_store: InMemorySessionStore,
     claude_client: Arc<ClaudeClient>,
+    window: MessagingWindow,
+    /// Conversations handed over to a person (another app has control)
+    human_threads: Arc<RwLock<HashSet<String>>>,
+    /// App that receives escalated conversations
+    handover_app_id: String,
+    transcriber: Option<Arc<WhisperClient>>,
 }
 
 impl FacebookHandler {
@@ -31,9 +48,32 @@ impl FacebookHandler {
             api,
             session_store,
             claude_client,
+            window: MessagingWindow::new(),
+            human_threads: Arc::new(RwLock::new(HashSet::new())),
+            handover_app_id: PAGE_INBOX_APP_ID.to_string(),
+            transcriber: None,
         }
     }
 
+    /// Transcribe voice messages with Whisper
+    pub fn with_transcriber(mut self, transcriber: Arc<WhisperClient>) -> Self {
+        self.transcriber = Some(transcriber);
+        self
+    }
+
+    /// Check webhook signatures with the app secret (see
+    /// [`FacebookHandler::process_signed_webhook`])
+    pub fn with_app_secret(mut self, app_secret: &str) -> Self {
+        self.api = self.api.with_app_secret(app_secret);
+        self
+    }
+
+    /// Escalate conversations to this app instead of the Page Inbox
+    pub fn with_handover_app_id(mut self, app_id: &str) -> Self {
+        self.handover_app_id = app_id.to_string();
+        self
+    }
+
     /// Handle incoming webhook entry
     pub async fn handle_webhook_entry(&self, entry: &WebhookEntry) -> Result<()> {
         if let Some(messages) = &entry.messaging {
@@ -43,20 +83,16 @@ impl FacebookHandler {
                 }
             }
         }
+        if let Some(standby) = &entry.standby {
+            for messaging in standby {
+                self.handle_standby(messaging).await;
+            }
+        }
         Ok(())
     }
 
     /// Handle a single messaging event
     async fn handle_messaging(&self, messaging: &WebhookMessaging) -> Result<()> {
-        // Only handle text messages
-        let message = match &messaging.message {
-            Some(msg) => msg,
-            None => {
-                debug!("Ignoring non-message event");
-                return Ok(());
-            }
-        };
-
         let sender_id = match &messaging.sender {
             Some(sender) => &sender.id,
             None => {
@@ -65,67 +101,70 @@ impl FacebookHandler {
             }
         };
 
-        // Check for quick replies
-        if let Some(quick_reply) = &message.quick_reply {
-            info!("Quick reply payload: {}", quick_reply.payload);
-            // Handle quick reply if needed
+        // Handover protocol events
+        if messaging.pass_thread_control.is_some() {
+            info!("Conversation with {} handed back to the bot", sender_id);
+            self.human_threads.write().await.remove(sender_id);
+            return Ok(());
+        }
+        if messaging.take_thread_control.is_some() {
+            info!("Conversation with {} taken over by another app", sender_id);
+            self.human_threads.write().await.insert(sender_id.clone());
+            return Ok(());
+        }
+        if let Some(request) = &messaging.request_thread_control {
+            info!(
+                "App {:?} requested control of the conversation with {}",
+                request.requested_owner_app_id, sender_id
+            );
+            return Ok(());
         }
 
-        // Get the message text
-        let text = match &message.text {
-            Some(text) => text.clone(),
+        let message = match &messaging.message {
+            Some(msg) => msg,
             None => {
-                debug!("Ignoring message without text");
+                debug!("Ignoring non-message event");
                 return Ok(());
             }
         };
+        if message.is_echo == Some(true) {
+            return Ok(());
+        }
 
-        info!("Received message from {}: {}", sender_id, text);
-
-        // Get or create session
-        let mut session = self.session_store.get_or_create(sender_id).await;
-
-        // Add user message to session
-        session.messages.push(CoreMessage::user(&text));
-        self.session_store
-            .add_message(sender_id, CoreMessage::user(&text))
-            .await;
+        self.window.record(sender_id).await;
 
-        // Build request
-        let mut request_builder = self
-            .claude_client
-            .request_builder()
-            .system("You are a helpful assistant. Respond in the same language as the user's question. Be concise and helpful.")
-            .max_tokens(2048);
+        if self.is_with_human(sender_id).await {
+            debug!("Conversation with {} is handled by a person", sender_id);
+            return Ok(());
+        }
 
-        // Add conversation history (limit to last 20 messages)
-        let history_start = session.messages.len().saturating_sub(20);
-        for message in session.messages.iter().skip(history_start) {
-            request_builder = request_builder.message(message.clone());
+        // Escalation to a person
+        let wants_human = message
+            .quick_reply
+            .as_ref()
+            .is_some_and(|q| q.payload == HUMAN_AGENT_PAYLOAD)
+            || message.text.as_deref().map(str::trim) == Some(HUMAN_COMMAND);
+        if wants_human {
+            return self.escalate_to_human(sender_id).await;
         }
 
-        let request = request_builder.build();
+        let input = MessageInput::collect(
+            &self.api,
+            message.text.as_deref(),
+            message.attachments.as_deref().unwrap_or_default(),
+            self.transcriber.as_deref(),
+        )
+        .await;
+        if input.is_empty() {
+            debug!("Ignoring message without content");
+            return Ok(());
+        }
 
-        // Send typing indicator (optional)
-        // Note: Facebook API doesn't have a simple typing indicator endpoint
+        info!("Received message from {}: {}", sender_id, input.text);
 
         // Get response from Claude
-        let response_text = match self.claude_client.messages(request).await {
-            Ok(response) => {
-                let text = response
-                    .content
-                    .iter()
-                    .filter_map(|c| {
-                        if let MessageContent::Text { text } = c {
-                            Some(text.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                text
-            }
+        let response_text = match self.ask_claude(sender_id, &input.text, input.images).await {
+            Ok(text) => text,
             Err(e) => {
                 error!("Claude API error: {}", e);
                 let error_msg = "Sorry, I encountered an error processing your request.";
@@ -134,11 +173,6 @@ impl FacebookHandler {
             }
         };
 
-        // Add assistant response to session
-        self.session_store
-            .add_message(sender_id, CoreMessage::assistant(&response_text))
-            .await;
-
         // Send response to user
         self.api.send_message(sender_id, &response_text).await?;
 
@@ -147,27 +181,81 @@ impl FacebookHandler {
         Ok(())
     }
 
-    /// Process a webhook payload
-    pub async fn process_webhook(&self, payload: &str) -> Result<()> {
-        let entries = self.api.handle_webhook(payload)?;
-
-        for entry in entries {
-            self.handle_webhook_entry(&entry).await?;
+    /// Handle an event of a conversation another app has control of
+    async fn handle_standby(&self, messaging: &WebhookMessaging) {
+        let (Some(sender), Some(message)) = (&messaging.sender, &messaging.message) else {
+            return;
+        };
+        if message.is_echo == Some(true) {
+            return;
         }
+        self.window.record(&sender.id).await;
+        self.human_threads.write().await.insert(sender.id.clone());
+    }
 
+    /// Hand the conversation over to a person
+    pub async fn escalate_to_human(&self, psid: &str) -> Result<()> {
+        self.api
+            .send_message(psid, "Connecting you with a person. Someone from our team will reply here soon.")
+            .await?;
+        self.api
+            .pass_thread_control(psid, &self.handover_app_id, "Escalated by the user")
+            .await?;
+        self.human_threads.write().await.insert(psid.to_string());
         Ok(())
     }
 
-    /// Handle incoming message and get response
-    pub async fn handle_message(&self, sender_id: &str, text: &str) -> Result<String> {
+    /// Whether the conversation is handled by a person
+    pub async fn is_with_human(&self, psid: &str) -> bool {
+        self.human_threads.read().await.contains(psid)
+    }
+
+    /// Send a message outside a reply (notifications, messages from a person)
+    ///
+    /// Outside the 24-hour window the message needs a `tag`.
+    pub async fn send_text(&self, psid: &str, text: &str, tag: Option<MessageTag>) -> Result<MessageResponse> {
+        let delivery = self.window.delivery(psid, tag).await?;
+        self.api.send_text(psid, text, delivery).await
+    }
+
+    /// Send an attachment from a public URL outside a reply
+    pub async fn send_attachment(
+        &self,
+        psid: &str,
+        attachment_type: AttachmentType,
+        url: &str,
+        tag: Option<MessageTag>,
+    ) -> Result<MessageResponse> {
+        let delivery = self.window.delivery(psid, tag).await?;
+        self.api.send_attachment(psid, attachment_type, url, delivery).await
+    }
+
+    /// The Messenger API client
+    pub fn api(&self) -> &FacebookApi {
+        &self.api
+    }
+
+    /// The messaging window tracker
+    pub fn window(&self) -> &MessagingWindow {
+        &self.window
+    }
+
+    /// Ask Claude with the conversation history and record the turn
+    async fn ask_claude(&self, sender_id: &str, text: &str, images: Vec<ImageSource>) -> cc_core::Result<String> {
         // Get or create session
-        let mut session = self.session_store.get_or_create(sender_id).await;
+        let session = self.session_store.get_or_create(sender_id).await;
 
-        // Add user message to session
-        session.messages.push(CoreMessage::user(text));
-        self.session_store
-            .add_message(sender_id, CoreMessage::user(text))
-            .await;
+        // Images are sent once and only noted in the history
+        let image_count = images.len();
+        let text = if text.trim().is_empty() { "Describe this image." } else { text };
+
+        // Build message history
+        let mut messages: Vec<CoreMessage> = session.messages;
+        messages.push(if images.is_empty() {
+            CoreMessage::user(text)
+        } else {
+            CoreMessage::user_with_images(text, images)
+        });
 
         // Build request
         let mut request_builder = self
@@ -177,37 +265,36 @@ impl FacebookHandler {
             .max_tokens(2048);
 
         // Add conversation history (limit to last 20 messages)
-        let history_start = session.messages.len().saturating_sub(20);
-        for message in session.messages.iter().skip(history_start) {
-            request_builder = request_builder.message(message.clone());
+        let history_start = messages.len().saturating_sub(20);
+        for message in messages.into_iter().skip(history_start) {
+            request_builder = request_builder.message(message);
         }
 
         let request = request_builder.build();
 
-        // Get response from Claude
-        let response_text = match self.claude_client.messages(request).await {
-            Ok(response) => {
-                let text = response
-                    .content
-                    .iter()
-                    .filter_map(|c| {
-                        if let MessageContent::Text { text } = c {
-                            Some(text.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                text
-            }
-            Err(e) => {
-                error!("Claude API error: {}", e);
-                return Err(crate::error::FacebookError::Api(e.to_string()).into());
-            }
+        let response = self.claude_client.messages(request).await?;
+        let response_text = response
+            .content
+            .iter()
+            .filter_map(|c| {
+                if let MessageContent::Text { text } = c {
+                    Some(text.clone())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Update session
+        let history_text = if image_count == 0 {
+            text.to_string()
+        } else {
+            format!("{}\n[{} image(s) attached]", text, image_count)
         };
-
-        // Add assistant response to session
+        self.session_store
+            .add_message(sender_id, CoreMessage::user(history_text))
+            .await;
         self.session_store
             .add_message(sender_id, CoreMessage::assistant(&response_text))
             .await;
@@ -215,6 +302,37 @@ impl FacebookHandler {
         Ok(response_text)
     }
 
+    /// Process a webhook payload
+    pub async fn process_webhook(&self, payload: &str) -> Result<()> {
+        let entries = self.api.handle_webhook(payload)?;
+
+        for entry in entries {
+            self.handle_webhook_entry(&entry).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Process a webhook request after checking its `X-Hub-Signature-256`
+    /// header
+    ///
+    /// Fails without processing anything when no app secret is configured
+    /// or the signature does not match.
+    pub async fn process_signed_webhook(&self, headers: &reqwest::header::HeaderMap, body: &[u8]) -> Result<()> {
+        self.api.verify_signature(headers, body)?;
+        let payload = std::str::from_utf8(body)
+            .map_err(|e| crate::error::FacebookError::InvalidPayload(e.to_string()))?;
+        self.process_webhook(payload).await
+    }
+
+    /// Handle incoming message and get response
+    pub async fn handle_message(&self, sender_id: &str, text: &str) -> Result<String> {
+        self.ask_claude(sender_id, text, Vec::new()).await.map_err(|e| {
+            error!("Claude API error: {}", e);
+            crate::error::FacebookError::Api(e.to_string())
+        })
+    }
+
     /// Clear conversation history for a user
     pub async fn clear_conversation(&self, sender_id: &str) -> Result<()> {
         self.session_store.clear(sender_id).await;
@@ -236,11 +354,61 @@ impl FacebookHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cc_core::{ApiConfig, Config, LlmConfig, LlmProvider, McpConfig, MemoryConfig, SchedulerConfig};
+
+    fn mock_config() -> Config {
+        Config {
+            llm: LlmConfig {
+                api_key: "test-key".to_string(),
+                model: "claude-sonnet-4-20250514".to_string(),
+                provider: LlmProvider::Claude,
+                base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
+            },
+            claude_api_key: "test-key".to_string(),
+            claude_model: "claude-sonnet-4-20250514".to_string(),
+            discord_token: None,
+            admin_user_ids: vec![],
+            api: ApiConfig::default(),
+            api_key: None,
+            memory: MemoryConfig::default(),
+            mcp: McpConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
+        }
+    }
 
     #[tokio::test]
-    async fn test_handler_creation() {
-        // This would require a real ClaudeClient to test properly
-        // For now, just verify the struct can be created
-        // let handler = FacebookHandler::new("page_id", "token", client);
+    async fn test_handover_events() {
+        let claude_client = Arc::new(ClaudeClient::new(&mock_config()).unwrap());
+        let handler = FacebookHandler::new("page123", "token", "verify", claude_client);
+
+        // A message on standby: another app (a person) has the conversation
+        let standby = r#"{"object": "page", "entry": [{"id": "page123", "standby": [
+            {"sender": {"id": "user123"}, "recipient": {"id": "page123"}, "message": {"mid": "m1", "text": "Hi"}}
+        ]}]}"#;
+        handler.process_webhook(standby).await.unwrap();
+        assert!(handler.is_with_human("user123").await);
+        assert!(handler.window().is_open("user123").await);
+
+        // Control passed back to the bot
+        let passed = r#"{"object": "page", "entry": [{"id": "page123", "messaging": [
+            {"sender": {"id": "user123"}, "recipient": {"id": "page123"}, "pass_thread_control": {"new_owner_app_id": "1"}}
+        ]}]}"#;
+        handler.process_webhook(passed).await.unwrap();
+        assert!(!handler.is_with_human("user123").await);
+
+        // Proactive messages need a tag outside the window
+        assert!(matches!(
+            handler.send_text("user456", "Hello", None).await,
+            Err(crate::error::FacebookError::OutsideMessagingWindow(_))
+        ));
     }
 }
@@ -6,9 +6,14 @@
 pub mod api;
 pub mod error;
 pub mod handler;
+pub mod media;
 pub mod session;
+pub mod webhook;
+pub mod window;
 
-pub use api::FacebookApi;
+pub use api::{AttachmentType, FacebookApi, PAGE_INBOX_APP_ID};
 pub use error::{FacebookError, Result};
 pub use handler::FacebookHandler;
 pub use session::InMemorySessionStore;
+pub use webhook::start_webhook_server;
+pub use window::{Delivery, MessageTag, MessagingWindow};
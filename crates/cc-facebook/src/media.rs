@@ -0,0 +1,168 @@
+//! Incoming attachments
+//!
+//! Images are passed to the model, voice messages are transcribed with
+//! Whisper and text documents are read into the prompt. Attachment URLs
+//! point to Facebook's CDN and need no access token.
+
+use cc_core::ImageSource;
+use cc_voice::WhisperClient;
+use tracing::{debug, error, warn};
+
+use crate::api::{FacebookApi, WebhookAttachment};
+
+/// Claude accepts images up to 5MB
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// The text and images of a received message
+#[derive(Debug, Default)]
+pub struct MessageInput {
+    pub text: String,
+    pub images: Vec<ImageSource>,
+}
+
+impl MessageInput {
+    /// Collect the text and attachments of a message
+    pub async fn collect(
+        api: &FacebookApi,
+        text: Option<&str>,
+        attachments: &[WebhookAttachment],
+        transcriber: Option<&WhisperClient>,
+    ) -> Self {
+        let mut input = Self {
+            text: text.unwrap_or_default().to_string(),
+            images: Vec::new(),
+        };
+
+        for attachment in attachments {
+            let Some(payload) = &attachment.payload else {
+                continue;
+            };
+            if payload.sticker_id.is_some() {
+                input.append("[Sticker]");
+                continue;
+            }
+            let Some(url) = payload.url.as_deref() else {
+                continue;
+            };
+
+            match attachment.attachment_type.as_str() {
+                "image" | "audio" | "file" => {
+                    let download = api.download_attachment(url).await;
+                    input
+                        .add_attachment(download, &attachment.attachment_type, url, transcriber)
+                        .await;
+                }
+                "video" => input.append("[Video messages are not supported]"),
+                other => debug!("Ignoring {} attachment", other),
+            }
+        }
+
+        input
+    }
+
+    /// Whether there is nothing to answer
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.images.is_empty()
+    }
+
+    /// Append a note (or document) to the text
+    fn append(&mut self, note: &str) {
+        if !self.text.trim().is_empty() {
+            self.text.push_str("\n\n");
+        }
+        self.text.push_str(note);
+    }
+
+    async fn add_attachment(
+        &mut self,
+        download: crate::error::Result<(Vec<u8>, String)>,
+        kind: &str,
+        url: &str,
+        transcriber: Option<&WhisperClient>,
+    ) {
+        let (bytes, content_type) = match download {
+            Ok(download) => download,
+            Err(e) => {
+                error!("Failed to download attachment: {}", e);
+                self.append("[The attached file could not be downloaded]");
+                return;
+            }
+        };
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+        let name = file_name(url, mime_type);
+
+        if mime_type.starts_with("image/") {
+            if bytes.len() > MAX_IMAGE_BYTES {
+                self.append("[The attached image is too large (5MB max)]");
+            } else {
+                self.images.push(ImageSource::from_bytes(mime_type, &bytes));
+            }
+        } else if kind == "audio" || mime_type.starts_with("audio/") {
+            // Voice clips are served as MP4, sometimes labelled video/mp4
+            let Some(transcriber) = transcriber else {
+                self.append("[Voice messages are not supported (transcription is not configured)]");
+                return;
+            };
+            match transcriber.transcribe_text(&bytes, &name).await {
+                Ok(transcript) if !transcript.trim().is_empty() => {
+                    // A voice message without text is the question itself
+                    if self.text.trim().is_empty() {
+                        self.text = transcript.trim().to_string();
+                    } else {
+                        self.append(&format!("[Voice message]\n{}", transcript.trim()));
+                    }
+                }
+                Ok(_) => self.append("[No speech was recognized in the voice message]"),
+                Err(e) => {
+                    warn!("Failed to transcribe {}: {}", name, e);
+                    self.append("[The voice message could not be transcribed]");
+                }
+            }
+        } else if let Some(document) = cc_core::extract_text(&name, Some(mime_type), &bytes) {
+            self.append(&document.to_prompt());
+        } else {
+            self.append(&format!("[Attached file {} is not a supported text format]", name));
+        }
+    }
+}
+
+/// File name from the last segment of the attachment URL, or one derived
+/// from the MIME type (Whisper and the document extractor use the extension)
+fn file_name(url: &str, mime_type: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let last = path.rsplit('/').next().unwrap_or_default();
+    if last.contains('.') {
+        return last.to_string();
+    }
+    let extension = match mime_type.split_once('/').map(|(_, subtype)| subtype) {
+        Some("plain") => "txt",
+        Some("mpeg") => "mp3",
+        Some(subtype) => subtype,
+        None => "bin",
+    };
+    format!("attachment.{}", extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name("https://cdn.fbsbx.com/v/t59/report.pdf?_nc_cat=1&oh=abc", "application/pdf"),
+            "report.pdf"
+        );
+        assert_eq!(file_name("https://cdn.fbsbx.com/v/t59/12345?x=1", "audio/mpeg"), "attachment.mp3");
+    }
+
+    #[tokio::test]
+    async fn test_missing_transcriber_is_noted() {
+        let mut input = MessageInput::default();
+        input
+            .add_attachment(Ok((vec![0u8; 4], "video/mp4".to_string())), "audio", "https://cdn/x/clip.mp4", None)
+            .await;
+        assert_eq!(input.text, "[Voice messages are not supported (transcription is not configured)]");
+        assert!(input.images.is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+//! Webhook server for receiving Messenger events
+//!
+//! Meta verifies the webhook with a GET request carrying the verify token
+//! and posts events signed with the app secret (`X-Hub-Signature-256`).
+//! Requests with an invalid signature are rejected.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use tracing::{error, info, warn};
+
+use crate::error::{FacebookError, Result};
+use crate::handler::FacebookHandler;
+
+/// Path of the webhook endpoint
+pub const WEBHOOK_PATH: &str = "/webhook/facebook";
+
+/// Router with the webhook endpoint
+pub fn create_webhook_router(handler: Arc<FacebookHandler>) -> Router {
+    Router::new()
+        .route(WEBHOOK_PATH, get(verify_webhook).post(handle_webhook))
+        .with_state(handler)
+}
+
+/// Serve the webhook on `port` until the server fails
+pub async fn start_webhook_server(handler: Arc<FacebookHandler>, port: u16) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| FacebookError::Config(e.to_string()))?;
+    info!("Facebook webhook server listening on {}", addr);
+
+    axum::serve(listener, create_webhook_router(handler))
+        .await
+        .map_err(|e| FacebookError::Config(e.to_string()))
+}
+
+/// Answer the verification request sent when the webhook is registered
+async fn verify_webhook(
+    State(handler): State<Arc<FacebookHandler>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let param = |name: &str| params.get(name).map(String::as_str).unwrap_or_default();
+    match handler
+        .api()
+        .verify_webhook(param("hub.mode"), param("hub.verify_token"), param("hub.challenge"))
+    {
+        Ok(challenge) => (StatusCode::OK, challenge),
+        Err(_) => (StatusCode::FORBIDDEN, String::new()),
+    }
+}
+
+/// Handle incoming events
+///
+/// Meta retries webhooks that are not answered quickly, so events are
+/// processed after responding.
+async fn handle_webhook(
+    State(handler): State<Arc<FacebookHandler>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    if handler.api().verify_signature(&headers, &body).is_err() {
+        warn!("Rejected Facebook webhook with invalid signature");
+        return StatusCode::UNAUTHORIZED;
+    }
+    let Ok(payload) = String::from_utf8(body.to_vec()) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    tokio::spawn(async move {
+        if let Err(e) = handler.process_webhook(&payload).await {
+            error!("Error processing Facebook webhook: {}", e);
+        }
+    });
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cc_core::{ApiConfig, Config, LlmConfig, McpConfig, MemoryConfig, SchedulerConfig};
+
+    fn handler() -> Arc<FacebookHandler> {
+        let config = Config {
+            llm: LlmConfig {
+                api_key: "test-key".to_string(),
+                ..Default::default()
+            },
+            claude_api_key: "test-key".to_string(),
+            claude_model: "claude-sonnet-4-20250514".to_string(),
+            discord_token: None,
+            admin_user_ids: vec![],
+            api: ApiConfig::default(),
+            api_key: None,
+            memory: MemoryConfig::default(),
+            mcp: McpConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
+        };
+        let client = Arc::new(cc_core::ClaudeClient::new(&config).unwrap());
+        Arc::new(FacebookHandler::new("page", "token", "verify", client).with_app_secret("secret"))
+    }
+
+    fn params(token: &str) -> Query<HashMap<String, String>> {
+        Query(HashMap::from([
+            ("hub.mode".to_string(), "subscribe".to_string()),
+            ("hub.verify_token".to_string(), token.to_string()),
+            ("hub.challenge".to_string(), "42".to_string()),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn test_verify_webhook() {
+        let response = verify_webhook(State(handler()), params("verify")).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+        assert_eq!(&body[..], b"42");
+
+        let response = verify_webhook(State(handler()), params("wrong")).await.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unsigned_events() {
+        let response = handle_webhook(State(handler()), HeaderMap::new(), Bytes::from_static(b"{}"))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,192 @@
+//! Messenger messaging window
+//!
+//! A page may message a user freely for 24 hours after the user's last
+//! message. After that a message needs a message tag and may only be sent
+//! for the tag's purpose; `HUMAN_AGENT` lets a person reply for 7 days.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::RwLock;
+
+use crate::error::{FacebookError, Result};
+
+/// Standard messaging window
+pub const STANDARD_WINDOW_HOURS: i64 = 24;
+
+/// Window of the `HUMAN_AGENT` tag
+pub const HUMAN_AGENT_WINDOW_DAYS: i64 = 7;
+
+/// Message tags for messages outside the standard window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageTag {
+    /// Reminders and updates for an event the user registered for
+    ConfirmedEventUpdate,
+    /// Updates about a purchase the user made
+    PostPurchaseUpdate,
+    /// Changes to the user's account or application
+    AccountUpdate,
+    /// Replies from a person, within 7 days of the user's last message
+    HumanAgent,
+}
+
+impl MessageTag {
+    /// Tag name in the Send API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ConfirmedEventUpdate => "CONFIRMED_EVENT_UPDATE",
+            Self::PostPurchaseUpdate => "POST_PURCHASE_UPDATE",
+            Self::AccountUpdate => "ACCOUNT_UPDATE",
+            Self::HumanAgent => "HUMAN_AGENT",
+        }
+    }
+
+    /// How long after the user's last message the tag may be used
+    /// (None = at any time)
+    pub fn window(&self) -> Option<Duration> {
+        match self {
+            Self::HumanAgent => Some(Duration::days(HUMAN_AGENT_WINDOW_DAYS)),
+            _ => None,
+        }
+    }
+}
+
+/// How a message is sent (`messaging_type` and `tag`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Reply to a message the user sent
+    Response,
+    /// Proactive message within the standard window
+    Update,
+    /// Message with a tag, outside the standard window
+    Tagged(MessageTag),
+}
+
+impl Delivery {
+    /// `messaging_type` of the Send API
+    pub fn messaging_type(&self) -> &'static str {
+        match self {
+            Self::Response => "RESPONSE",
+            Self::Update => "UPDATE",
+            Self::Tagged(_) => "MESSAGE_TAG",
+        }
+    }
+
+    /// `tag` of the Send API
+    pub fn tag(&self) -> Option<&'static str> {
+        match self {
+            Self::Tagged(tag) => Some(tag.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// Time of each user's last message
+#[derive(Debug, Clone, Default)]
+pub struct MessagingWindow {
+    last_message: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+}
+
+impl MessagingWindow {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a message from `psid` now
+    pub async fn record(&self, psid: &str) {
+        self.record_at(psid, Utc::now()).await;
+    }
+
+    /// Record a message from `psid` at `at`
+    pub async fn record_at(&self, psid: &str, at: DateTime<Utc>) {
+        let mut last_message = self.last_message.write().await;
+        let entry = last_message.entry(psid.to_string()).or_insert(at);
+        if at > *entry {
+            *entry = at;
+        }
+    }
+
+    /// Time of the last message from `psid`
+    pub async fn last_message_at(&self, psid: &str) -> Option<DateTime<Utc>> {
+        self.last_message.read().await.get(psid).copied()
+    }
+
+    /// Whether the standard window with `psid` is open
+    pub async fn is_open(&self, psid: &str) -> bool {
+        self.delivery(psid, None).await.is_ok()
+    }
+
+    /// How to send a proactive message to `psid` now
+    ///
+    /// Within the standard window the message is an update; outside it the
+    /// message needs `tag`, and the tag's own window must still be open.
+    pub async fn delivery(&self, psid: &str, tag: Option<MessageTag>) -> Result<Delivery> {
+        let last = self.last_message_at(psid).await;
+        resolve_delivery(psid, last, Utc::now(), tag)
+    }
+}
+
+fn resolve_delivery(
+    psid: &str,
+    last: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    tag: Option<MessageTag>,
+) -> Result<Delivery> {
+    let elapsed = last.map(|last| now - last);
+    if elapsed.is_some_and(|e| e <= Duration::hours(STANDARD_WINDOW_HOURS)) {
+        return Ok(Delivery::Update);
+    }
+
+    let Some(tag) = tag else {
+        return Err(FacebookError::OutsideMessagingWindow(psid.to_string()));
+    };
+    match tag.window() {
+        Some(window) if elapsed.is_none_or(|e| e > window) => {
+            Err(FacebookError::OutsideMessagingWindow(psid.to_string()))
+        }
+        _ => Ok(Delivery::Tagged(tag)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_delivery() {
+        let now = Utc::now();
+        let recent = Some(now - Duration::hours(2));
+        let days_ago = Some(now - Duration::days(3));
+        let weeks_ago = Some(now - Duration::days(10));
+
+        assert_eq!(resolve_delivery("u", recent, now, None).unwrap(), Delivery::Update);
+        assert!(resolve_delivery("u", days_ago, now, None).is_err());
+        assert!(resolve_delivery("u", None, now, None).is_err());
+
+        let human = Some(MessageTag::HumanAgent);
+        assert_eq!(
+            resolve_delivery("u", days_ago, now, human).unwrap(),
+            Delivery::Tagged(MessageTag::HumanAgent)
+        );
+        assert!(resolve_delivery("u", weeks_ago, now, human).is_err());
+
+        let account = Some(MessageTag::AccountUpdate);
+        let delivery = resolve_delivery("u", weeks_ago, now, account).unwrap();
+        assert_eq!(delivery.messaging_type(), "MESSAGE_TAG");
+        assert_eq!(delivery.tag(), Some("ACCOUNT_UPDATE"));
+    }
+
+    #[tokio::test]
+    async fn test_messaging_window() {
+        let window = MessagingWindow::new();
+        assert!(!window.is_open("u").await);
+
+        let earlier = Utc::now() - Duration::days(2);
+        window.record("u").await;
+        window.record_at("u", earlier).await;
+        assert!(window.is_open("u").await);
+        assert!(window.last_message_at("u").await.unwrap() > earlier);
+    }
+}
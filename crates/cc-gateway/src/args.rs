@@ -0,0 +1,425 @@
+//! Command line arguments
+//!
+//! `cc-gateway [OPTIONS] [COMMAND]`: without a command the gateway server
+//! starts. The flags of earlier versions (`--cli`, `--execute`, `--file`)
+//! are still accepted and mapped to the `cli` and `exec` commands.
+
+use std::path::PathBuf;
+
+use clap::{ArgAction, Parser, Subcommand};
+
+use cc_core::Config;
+
+use crate::cli::CliOptions;
+
+/// Shown after `--help`
+const AFTER_HELP: &str = "\
+Configuration:
+  設定は以下の優先順位で読み込まれます:
+    1. コマンドラインオプション (--model, --port)
+    2. 環境変数
+    3. 設定ファイル (--config, default: ./cc-gateway.toml)
+    4. デフォルト値
+
+Environment Variables (環境変数は TOML 設定を上書きします):
+  LLM_API_KEY             API key (required)
+  LLM_MODEL               Model name (default: claude-sonnet-4-20250514)
+  LLM_PROVIDER            Provider: claude or openai (default: claude)
+  LLM_BASE_URL            Custom API endpoint
+  DISCORD_BOT_TOKEN       Discord bot token (optional)
+  DISCORD_THREAD_AFTER    Exchanges before a channel chat moves to a thread (default: 3, 0 = never)
+  AUDIT_LOG_FILE          Audit log for Discord tool approvals ([tool_policy] in cc-gateway.toml) and API key usage
+  DISCORD_GUILD_DB        Per-server settings changed with /config (default: data/discord_guilds.db)
+  API_PORT                HTTP API port (default: 3000)
+  API_KEYS_DB_PATH        API keys issued through /api/keys (default: data/api_keys.db)
+  API_OIDC_ISSUER         Accept JWTs from this OIDC issuer (API_OIDC_AUDIENCE, API_OIDC_JWKS_URL, ...)
+  TASK_CALLBACK_SECRET    Signs /api/jobs completion callbacks (callback_url is rejected without it)
+  TASK_CALLBACK_ALLOWED_HOSTS  Internal callback hosts allowed besides public ones (comma separated)
+  MCP_ENABLED             Enable MCP integration (default: true)
+  MCP_CONFIG_PATH         Path to MCP config file
+  SCHEDULE_ENABLED        Enable scheduler (default: true)
+  SCHEDULE_CONFIG_PATH    Path to schedule.toml (default: schedule.toml)
+  PROMPTS_DIR             System prompt template directory (default: prompts)
+  WORKFLOWS_DIR           YAML workflow directory (default: workflows)
+  AGENTS_CONFIG_PATH      Sub-agent definitions (default: agents.toml / agents.yaml)
+  HOOKS_CONFIG_PATH       Inbound webhook definitions (default: hooks.toml)
+  EMAIL_CHANNEL_ENABLED   Answer incoming email (requires IMAP_* and SMTP_*)
+  IMAP_HOST / IMAP_PORT   IMAP server (default port: 993)
+  IMAP_USER / IMAP_PASS   IMAP credentials
+  IMAP_ALLOW_INSECURE_AUTH / SMTP_ALLOW_INSECURE_AUTH
+                          Log in to servers without TLS (default: false)
+  EMAIL_FOLDER            Folder to watch (default: INBOX)
+  EMAIL_ALLOWED_SENDERS   Comma-separated addresses or @domains (default: nobody)
+  EMAIL_REQUIRE_AUTH      Only answer mail that passed DMARC (default: true)
+  EMAIL_AUTHSERV_ID       authserv-id of your mail server; only its Authentication-Results are trusted
+  EMAIL_TOOLS             Tools the email agent may use (default: read-only tools)
+  EMAIL_OAUTH_PROVIDER    Use XOAUTH2 for SMTP/IMAP: google or microsoft
+  EMAIL_OAUTH_CLIENT_ID   OAuth2 client ID (plus EMAIL_OAUTH_CLIENT_SECRET)
+  EMAIL_OAUTH_TENANT      Microsoft tenant (default: common)
+  EMAIL_OAUTH_REFRESH_TOKEN
+                          Initial refresh token (required for Gmail)
+  EMAIL_OAUTH_TOKEN_PATH  Token cache (default: email_oauth_token.json)
+  CALENDAR_PROVIDER       Calendar tools backend: caldav, google or microsoft
+  CALDAV_URL / CALDAV_USERNAME / CALDAV_PASSWORD
+                          CalDAV server (CALENDAR_ID selects the calendar)
+  CALENDAR_OAUTH_CLIENT_ID / CALENDAR_OAUTH_REFRESH_TOKEN
+                          Google / Microsoft credentials (plus _CLIENT_SECRET, _TENANT)
+                          With SMTP_* set, meeting invitations are mailed (iMIP)
+  CARDDAV_URL / CARDDAV_USERNAME / CARDDAV_PASSWORD
+                          Contacts tools (CARDDAV_ADDRESSBOOK selects the address book)
+
+Examples:
+  cc-gateway exec \"今日の天気は？\"
+  cc-gateway exec --file prompt.txt
+  cc-gateway cli --tools read,glob,grep --verbose
+  cc-gateway --config cc-gateway-glm.toml --model glm-4.6 cli
+  cc-gateway serve --port 8080
+  cc-gateway serve --pid-file /run/cc-gateway/cc-gateway.pid
+  cc-gateway schedule add news \"0 9 * * *\" \"今日のニュースを要約して\"
+  cc-gateway mcp tools";
+
+/// Claude Code Gateway
+#[derive(Debug, Parser)]
+#[command(name = "cc-gateway", version, disable_version_flag = true, after_long_help = AFTER_HELP)]
+pub struct Args {
+    /// Configuration file (default: ./cc-gateway.toml)
+    #[arg(short, long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Model to use (overrides [llm] model and LLM_MODEL)
+    #[arg(long, global = true, value_name = "MODEL")]
+    pub model: Option<String>,
+
+    /// HTTP API port (overrides [api] port and API_PORT)
+    #[arg(long, global = true, value_name = "PORT")]
+    pub port: Option<u16>,
+
+    /// Print version
+    #[arg(short = 'v', long, action = ArgAction::Version)]
+    version: Option<bool>,
+
+    #[command(flatten)]
+    pub cli: CliArgs,
+
+    /// Same as `cli`
+    #[arg(long = "cli", hide = true)]
+    legacy_cli: bool,
+
+    /// Same as `exec PROMPT`
+    #[arg(short = 'e', long = "execute", hide = true, value_name = "PROMPT")]
+    legacy_execute: Option<String>,
+
+    /// Same as `exec --file PATH`
+    #[arg(short = 'f', long = "file", hide = true, value_name = "PATH")]
+    legacy_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Options of the `cli` and `exec` commands
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct CliArgs {
+    /// Show tool inputs and outputs in full
+    #[arg(long, global = true)]
+    pub verbose: bool,
+
+    /// Only register these tools (comma-separated)
+    #[arg(long, global = true, value_name = "LIST")]
+    pub tools: Option<String>,
+
+    /// Register no tools
+    #[arg(long, global = true, overrides_with = "tools")]
+    pub no_tools: bool,
+
+    /// Run tools that need approval (bash/write/edit) without asking y/n
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
+
+    /// Plan mode: show what every changing tool would do and ask y/n first
+    #[arg(long, global = true)]
+    pub plan: bool,
+
+    /// Continue the conversation saved as NAME (cli)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub resume: Option<String>,
+
+    /// Attach a file or image to the first prompt (repeatable)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub attach: Vec<PathBuf>,
+}
+
+impl CliArgs {
+    /// Options of the CLI modes
+    pub fn options(&self) -> CliOptions {
+        let tools = if self.no_tools {
+            Some(Vec::new())
+        } else {
+            self.tools.as_deref().map(|list| {
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+        };
+        CliOptions {
+            verbose: self.verbose,
+            tools,
+            yes: self.yes,
+            plan: self.plan,
+            resume: self.resume.clone(),
+            attach: self.attach.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Start the gateway: HTTP API, channels and scheduler (default)
+    ///
+    /// SIGTERM / Ctrl+C shut down gracefully; SIGHUP reloads the
+    /// configuration, MCP servers and schedules.
+    Serve {
+        /// Write the process ID to this file (removed on exit)
+        #[arg(long, value_name = "PATH")]
+        pid_file: Option<PathBuf>,
+    },
+    /// Start the interactive CLI
+    Cli,
+    /// Run a single prompt and exit
+    Exec(ExecArgs),
+    /// Manage schedules of a running gateway (see `schedule help`)
+    #[command(disable_help_flag = true)]
+    Schedule {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, value_name = "ARGS")]
+        args: Vec<String>,
+    },
+    /// Email account setup
+    #[command(subcommand)]
+    Email(EmailCommand),
+    /// Show the effective configuration (secrets are masked)
+    Config,
+    /// List the tools available to the CLI (built-in, skills and MCP)
+    Tools,
+    /// Saved CLI conversations
+    Sessions {
+        #[command(subcommand)]
+        command: Option<SessionsCommand>,
+    },
+    /// MCP servers
+    Mcp {
+        #[command(subcommand)]
+        command: Option<McpCommand>,
+    },
+    /// Delete all sessions, memories and audit log entries of a user
+    Purge {
+        /// Channel user (`discord:123`, `api:alice`) or linked identity ([memory.identities])
+        identity: String,
+        /// Do not ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+    /// Knowledge base of [knowledge] sources
+    Knowledge {
+        #[command(subcommand)]
+        command: Option<KnowledgeCommand>,
+    },
+}
+
+/// Prompt of the `exec` command
+#[derive(Debug, Clone, PartialEq, Eq, clap::Args)]
+#[group(required = true, multiple = false)]
+pub struct ExecArgs {
+    /// Prompt to run
+    pub prompt: Option<String>,
+
+    /// Read the prompt from a file
+    #[arg(long, short = 'f', value_name = "PATH")]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum EmailCommand {
+    /// Authorize email OAuth2 (device code flow)
+    Login,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum SessionsCommand {
+    /// List saved conversations (default)
+    List,
+    /// Print a saved conversation
+    Show { name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum KnowledgeCommand {
+    /// List the indexed documents (default)
+    List,
+    /// Index the sources now
+    Index,
+    /// Show the passages most relevant to a query
+    Search {
+        query: String,
+        /// Number of passages
+        #[arg(long, short = 'n', default_value_t = 5)]
+        limit: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum McpCommand {
+    /// List the configured MCP servers (default)
+    List,
+    /// Connect to the servers and list their tools
+    Tools,
+}
+
+impl Args {
+    /// The command to run, with the legacy flags mapped to commands
+    pub fn command(&self) -> Command {
+        if let Some(command) = &self.command {
+            return command.clone();
+        }
+        if let Some(prompt) = &self.legacy_execute {
+            return Command::Exec(ExecArgs {
+                prompt: Some(prompt.clone()),
+                file: None,
+            });
+        }
+        if let Some(file) = &self.legacy_file {
+            return Command::Exec(ExecArgs {
+                prompt: None,
+                file: Some(file.clone()),
+            });
+        }
+        if self.legacy_cli {
+            return Command::Cli;
+        }
+        Command::Serve { pid_file: None }
+    }
+
+    /// Load the configuration file and apply the command line overrides
+    pub fn load_config(&self) -> cc_core::Result<Config> {
+        let mut config = match &self.config {
+            Some(path) => Config::from_toml_file(path)?,
+            None => Config::load()?,
+        };
+        if let Some(model) = &self.model {
+            config.llm.model = model.clone();
+            config.claude_model = model.clone();
+        }
+        if let Some(port) = self.port {
+            config.api.port = port;
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::try_parse_from(std::iter::once("cc-gateway").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(parse(&[]).command(), Command::Serve { pid_file: None });
+        assert_eq!(
+            parse(&["serve", "--pid-file", "/run/cc-gateway.pid"]).command(),
+            Command::Serve {
+                pid_file: Some(PathBuf::from("/run/cc-gateway.pid"))
+            }
+        );
+        assert_eq!(parse(&["cli"]).command(), Command::Cli);
+        assert_eq!(parse(&["--cli"]).command(), Command::Cli);
+        assert_eq!(
+            parse(&["-e", "hello"]).command(),
+            Command::Exec(ExecArgs {
+                prompt: Some("hello".to_string()),
+                file: None
+            })
+        );
+        assert_eq!(
+            parse(&["exec", "-f", "prompt.txt"]).command(),
+            Command::Exec(ExecArgs {
+                prompt: None,
+                file: Some(PathBuf::from("prompt.txt"))
+            })
+        );
+        assert!(Args::try_parse_from(["cc-gateway", "exec"]).is_err());
+        assert_eq!(
+            parse(&["sessions", "show", "work"]).command(),
+            Command::Sessions {
+                command: Some(SessionsCommand::Show {
+                    name: "work".to_string()
+                })
+            }
+        );
+        assert_eq!(
+            parse(&["purge", "discord:123", "--yes"]).command(),
+            Command::Purge {
+                identity: "discord:123".to_string(),
+                yes: true
+            }
+        );
+        assert_eq!(
+            parse(&["knowledge", "search", "返品の期限", "-n", "3"]).command(),
+            Command::Knowledge {
+                command: Some(KnowledgeCommand::Search {
+                    query: "返品の期限".to_string(),
+                    limit: 3
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn test_schedule_args_pass_through() {
+        let args = parse(&["schedule", "add", "news", "0 9 * * *", "要約して", "--tools", "web_search"]);
+        let Command::Schedule { args: rest } = args.command() else {
+            panic!("expected schedule");
+        };
+        assert_eq!(rest, vec!["add", "news", "0 9 * * *", "要約して", "--tools", "web_search"]);
+        assert_eq!(args.cli.tools, None);
+
+        let Command::Schedule { args: rest } = parse(&["schedule", "--help"]).command() else {
+            panic!("expected schedule");
+        };
+        assert_eq!(rest, vec!["--help"]);
+    }
+
+    #[test]
+    fn test_cli_options() {
+        assert_eq!(parse(&["cli"]).cli.options(), CliOptions::default());
+
+        let options = parse(&["cli", "--verbose", "--tools", "bash, read"]).cli.options();
+        assert!(options.verbose);
+        assert_eq!(options.tools, Some(vec!["bash".to_string(), "read".to_string()]));
+
+        // Global options may come before the command
+        let options = parse(&["--no-tools", "-y", "exec", "hi"]).cli.options();
+        assert_eq!(options.tools, Some(Vec::new()));
+        assert!(options.yes);
+        assert!(!options.plan);
+        assert!(parse(&["exec", "--plan", "hi"]).cli.options().plan);
+
+        let options = parse(&["cli", "--resume", "work", "--attach", "a.png", "--attach", "b.md"])
+            .cli
+            .options();
+        assert_eq!(options.resume.as_deref(), Some("work"));
+        assert_eq!(options.attach, vec![PathBuf::from("a.png"), PathBuf::from("b.md")]);
+        assert!(Args::try_parse_from(["cc-gateway", "cli", "--resume"]).is_err());
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let args = parse(&["--model", "glm-4.6", "serve", "--port", "8080"]);
+        assert_eq!(args.model.as_deref(), Some("glm-4.6"));
+        assert_eq!(args.port, Some(8080));
+        assert!(Args::try_parse_from(["cc-gateway", "--port", "http"]).is_err());
+    }
+}
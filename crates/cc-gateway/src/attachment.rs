@@ -0,0 +1,236 @@
+//! File and image attachments of CLI prompts
+//!
+//! `/attach <path>`, `--attach <path>` and inline `@path` tokens add local
+//! files to the next user message: images and PDFs are sent to the model as
+//! they are, text files go through the document extractor.
+
+use std::path::{Path, PathBuf};
+
+use cc_core::{DocumentSource, ExtractedDocument, ImageSource, Message, MessageContent};
+
+/// Largest image the model accepts
+pub const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Largest PDF the model accepts
+pub const MAX_PDF_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Question used when only images or PDFs were attached
+const DEFAULT_QUESTION: &str = "この添付ファイルについて説明してください。";
+
+/// A file attached to a prompt
+#[derive(Debug, Clone)]
+pub enum Attachment {
+    Image { name: String, source: ImageSource },
+    Pdf { name: String, source: DocumentSource },
+    Text(ExtractedDocument),
+}
+
+impl Attachment {
+    /// Read the file at `path`
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| anyhow::anyhow!("{} を開けません: {}", path.display(), e))?;
+        if !metadata.is_file() {
+            anyhow::bail!("{} はファイルではありません", path.display());
+        }
+
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        if let Some(media_type) = image_media_type(&extension) {
+            if metadata.len() > MAX_IMAGE_BYTES {
+                anyhow::bail!("画像 {} は大きすぎます（5MB まで）", name);
+            }
+            let bytes = std::fs::read(path)?;
+            return Ok(Self::Image {
+                name,
+                source: ImageSource::from_bytes(media_type, &bytes),
+            });
+        }
+        if extension == "pdf" {
+            if metadata.len() > MAX_PDF_BYTES {
+                anyhow::bail!("PDF {} は大きすぎます（32MB まで）", name);
+            }
+            let bytes = std::fs::read(path)?;
+            return Ok(Self::Pdf {
+                name,
+                source: DocumentSource::pdf(&bytes),
+            });
+        }
+
+        let bytes = std::fs::read(path)?;
+        // Local files often have no known extension (Makefile, Dockerfile, ...)
+        let document = cc_core::extract_text(&name, None, &bytes).or_else(|| {
+            if bytes.contains(&0) {
+                None
+            } else {
+                cc_core::extract_text(&name, Some("text/plain"), &bytes)
+            }
+        });
+        document
+            .map(Self::Text)
+            .ok_or_else(|| anyhow::anyhow!("{} を読み込めません（テキスト・画像・PDF に対応）", name))
+    }
+
+    /// Short description for the terminal
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Image { name, source } => {
+                format!("🖼️  {}（画像, {} KB）", name, source.approximate_size() / 1024)
+            }
+            Self::Pdf { name, source } => {
+                format!("📄 {}（PDF, {} KB）", name, source.approximate_size() / 1024)
+            }
+            Self::Text(document) => {
+                let truncated = if document.truncated { ", 先頭のみ" } else { "" };
+                format!(
+                    "📝 {}（{} 文字{}）",
+                    document.name,
+                    document.text.chars().count(),
+                    truncated
+                )
+            }
+        }
+    }
+}
+
+/// The user message for `text` with `attachments`
+///
+/// Text files are appended to the prompt; images and PDFs follow it as
+/// separate content blocks.
+pub fn user_message(text: &str, attachments: &[Attachment]) -> Message {
+    let mut parts = Vec::new();
+    if !text.trim().is_empty() {
+        parts.push(text.trim().to_string());
+    }
+    let mut blocks = Vec::new();
+    for attachment in attachments {
+        match attachment {
+            Attachment::Text(document) => parts.push(document.to_prompt()),
+            Attachment::Image { source, .. } => blocks.push(MessageContent::Image {
+                source: source.clone(),
+            }),
+            Attachment::Pdf { source, .. } => blocks.push(MessageContent::Document {
+                source: source.clone(),
+            }),
+        }
+    }
+    if parts.is_empty() {
+        parts.push(DEFAULT_QUESTION.to_string());
+    }
+
+    let mut content = vec![MessageContent::Text {
+        text: parts.join("\n\n"),
+    }];
+    content.extend(blocks);
+    Message {
+        role: "user".to_string(),
+        content,
+    }
+}
+
+/// Split inline `@path` tokens naming existing files off `input`
+///
+/// Other `@` words (mentions, missing files) are left in the text.
+pub fn extract_inline(input: &str) -> (String, Vec<PathBuf>) {
+    let mut words = Vec::new();
+    let mut paths = Vec::new();
+    for word in input.split(' ') {
+        if let Some(path) = word.strip_prefix('@') {
+            let path = Path::new(path);
+            if !path.as_os_str().is_empty() && path.is_file() {
+                paths.push(path.to_path_buf());
+                continue;
+            }
+        }
+        words.push(word);
+    }
+    (words.join(" ").trim().to_string(), paths)
+}
+
+/// The media type of an image file the model accepts
+fn image_media_type(extension: &str) -> Option<&'static str> {
+    match extension {
+        "png" => Some(ImageSource::MEDIA_TYPE_PNG),
+        "jpg" | "jpeg" => Some(ImageSource::MEDIA_TYPE_JPEG),
+        "gif" => Some(ImageSource::MEDIA_TYPE_GIF),
+        "webp" => Some(ImageSource::MEDIA_TYPE_WEBP),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("Makefile");
+        std::fs::write(&text, "all:\n\tcargo build\n").unwrap();
+        let image = dir.path().join("shot.PNG");
+        std::fs::write(&image, b"\x89PNG\r\n").unwrap();
+        let pdf = dir.path().join("report.pdf");
+        std::fs::write(&pdf, b"%PDF-1.7").unwrap();
+        let binary = dir.path().join("data.bin");
+        std::fs::write(&binary, [0u8, 1, 2]).unwrap();
+
+        match Attachment::load(&text).unwrap() {
+            Attachment::Text(document) => assert_eq!(document.text, "all:\n\tcargo build\n"),
+            other => panic!("unexpected attachment: {:?}", other),
+        }
+        match Attachment::load(&image).unwrap() {
+            Attachment::Image { name, source } => {
+                assert_eq!(name, "shot.PNG");
+                assert_eq!(source.media_type, ImageSource::MEDIA_TYPE_PNG);
+            }
+            other => panic!("unexpected attachment: {:?}", other),
+        }
+        assert!(matches!(Attachment::load(&pdf).unwrap(), Attachment::Pdf { .. }));
+        assert!(Attachment::load(&binary).is_err());
+        assert!(Attachment::load(dir.path()).is_err());
+        assert!(Attachment::load(&dir.path().join("missing.txt")).is_err());
+    }
+
+    #[test]
+    fn test_user_message() {
+        let document = ExtractedDocument {
+            name: "notes.md".to_string(),
+            text: "memo".to_string(),
+            truncated: false,
+        };
+        let attachments = vec![
+            Attachment::Text(document),
+            Attachment::Image {
+                name: "a.png".to_string(),
+                source: ImageSource::png(b"png"),
+            },
+        ];
+        let message = user_message("要約して", &attachments);
+        assert_eq!(message.content.len(), 2);
+        assert!(message.text_content().starts_with("要約して\n\n[File: notes.md]"));
+        assert!(message.has_images());
+
+        let message = user_message("", &attachments[1..]);
+        assert_eq!(message.text_content(), DEFAULT_QUESTION);
+    }
+
+    #[test]
+    fn test_extract_inline() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("main.rs");
+        std::fs::write(&file, "fn main() {}").unwrap();
+
+        let input = format!("@{} を説明して @someone", file.display());
+        let (text, paths) = extract_inline(&input);
+        assert_eq!(text, "を説明して @someone");
+        assert_eq!(paths, vec![file]);
+
+        assert_eq!(extract_inline("mail me@example.com"), ("mail me@example.com".to_string(), vec![]));
+    }
+}
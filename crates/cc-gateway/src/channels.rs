@@ -0,0 +1,500 @@
+//! Channel services of server mode
+//!
+//! Every channel enabled in `[channels]` runs as its own supervised service
+//! (see [`crate::supervisor`]), so one disconnected or misconfigured channel
+//! does not take the others down. Channels whose credentials are missing are
+//! skipped with a warning.
+
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+
+use cc_core::{CancellationToken, ClaudeClient, Config, ContextProvider, Memorizer, SessionManager, ToolManager};
+use cc_email::send::EmailConfig;
+use cc_email::{EmailChannel, EmailChannelConfig, EmailSender, ImapConfig};
+use cc_voice::WhisperClient;
+
+use crate::supervisor::{ServiceFactory, Supervisor};
+
+/// What the channels share with the rest of the server
+pub struct ChannelContext {
+    pub config: Config,
+    pub claude_client: Arc<ClaudeClient>,
+    pub tools: Arc<ToolManager>,
+    /// Transcribes voice messages (None = they are not read)
+    pub transcriber: Option<Arc<WhisperClient>>,
+    /// Remembers facts about users (None = disabled)
+    pub memorizer: Option<Arc<Memorizer>>,
+    /// Finds documents relevant to a message (None = no knowledge base)
+    pub context: Option<Arc<dyn ContextProvider>>,
+    pub shutdown: CancellationToken,
+    pub supervisor: Arc<Supervisor>,
+}
+
+/// A started channel
+pub struct RunningChannel {
+    pub name: &'static str,
+    pub handle: JoinHandle<()>,
+}
+
+/// Start every enabled channel
+pub fn start_channels(context: &ChannelContext) -> Vec<RunningChannel> {
+    let mut running = Vec::new();
+    for name in context.config.channels.enabled() {
+        match service(name, context) {
+            Ok(factory) => {
+                let handle = context.supervisor.spawn(name, factory);
+                running.push(RunningChannel { name, handle });
+                tracing::info!("Channel {} started", name);
+            }
+            Err(e) => tracing::warn!("Channel {} disabled: {}", name, e),
+        }
+    }
+    if running.is_empty() {
+        tracing::info!("No channels started");
+    }
+    running
+}
+
+/// Build the service of the channel `name`, checking its settings
+fn service(name: &str, context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    match name {
+        "discord" => discord(context),
+        "email" => email(context),
+        "telegram" => telegram(context),
+        "slack" => slack(context),
+        "whatsapp" => whatsapp(context),
+        "line" => line(context),
+        "signal" => signal(context),
+        "imessage" => imessage(context),
+        "facebook" => facebook(context),
+        "instagram" => instagram(context),
+        "ws" => websocket(context),
+        "dashboard" => dashboard(context),
+        _ => anyhow::bail!("unknown channel"),
+    }
+}
+
+/// A non-empty environment variable
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn require(name: &str) -> anyhow::Result<String> {
+    env(name).ok_or_else(|| anyhow::anyhow!("{} is not set", name))
+}
+
+/// Comma-separated list from an environment variable
+fn env_list(name: &str) -> Vec<String> {
+    env(name)
+        .map(|value| {
+            value
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn discord(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    if context.config.discord_token.is_none() {
+        anyhow::bail!("DISCORD_BOT_TOKEN is not set");
+    }
+    let config = context.config.clone();
+    let client = Arc::clone(&context.claude_client);
+    let tools = Arc::clone(&context.tools);
+    let memorizer = context.memorizer.clone();
+    let documents = context.context.clone();
+    Ok(Box::new(move || {
+        Box::pin(start_discord_bot(
+            config.clone(),
+            Arc::clone(&client),
+            Arc::clone(&tools),
+            memorizer.clone(),
+            documents.clone(),
+        ))
+    }))
+}
+
+/// Start Discord bot
+async fn start_discord_bot(
+    config: Config,
+    claude_client: Arc<ClaudeClient>,
+    tool_manager: Arc<ToolManager>,
+    memorizer: Option<Arc<Memorizer>>,
+    context: Option<Arc<dyn ContextProvider>>,
+) -> anyhow::Result<()> {
+    use cc_discord::{DiscordBot, GuildConfigStore};
+
+    // DISCORD_THREAD_AFTER=0 keeps every conversation in its channel
+    let thread_after = match std::env::var("DISCORD_THREAD_AFTER").ok().and_then(|v| v.parse::<usize>().ok()) {
+        Some(0) => None,
+        Some(n) => Some(n),
+        None => Some(cc_discord::bot::DEFAULT_THREAD_AFTER),
+    };
+    let mut bot = DiscordBot::with_client(config, claude_client)
+        .with_thread_after(thread_after)
+        .with_tools(tool_manager);
+    if let Some(memorizer) = memorizer {
+        bot = bot.with_memorizer(memorizer);
+    }
+    if let Some(context) = context {
+        bot = bot.with_context_provider(context);
+    }
+
+    // Tool approvals are written to the audit log when one is configured
+    if let Ok(path) = std::env::var("AUDIT_LOG_FILE") {
+        let audit_config = cc_core::audit::AuditConfig {
+            log_file: Some(path),
+            ..Default::default()
+        };
+        match cc_core::audit::AuditLogger::new(audit_config) {
+            Ok(logger) => bot = bot.with_audit_logger(Arc::new(logger)),
+            Err(e) => tracing::warn!("Audit log disabled: {}", e),
+        }
+    }
+
+    // Per-server overrides from /config
+    let guild_db = std::env::var("DISCORD_GUILD_DB").unwrap_or_else(|_| GuildConfigStore::DEFAULT_PATH.to_string());
+    match GuildConfigStore::open(&guild_db) {
+        Ok(store) => bot = bot.with_guild_store(Arc::new(store)),
+        Err(e) => tracing::warn!("Per-server Discord settings disabled ({}): {}", guild_db, e),
+    }
+    bot.start().await
+        .map_err(|e| anyhow::anyhow!("Discord bot error: {}", e))
+}
+
+/// Email (IMAP IDLE + SMTP replies)
+fn email(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let (Some(imap), Some(smtp)) = (ImapConfig::from_env(), EmailConfig::from_env()) else {
+        anyhow::bail!("IMAP_HOST and SMTP_HOST are required");
+    };
+    let config = EmailChannelConfig {
+        tool_policy: context.config.tool_policy.clone(),
+        ..EmailChannelConfig::from_env()
+    };
+    let channel = Arc::new(
+        EmailChannel::new(
+            imap,
+            EmailSender::new(smtp)?,
+            Arc::clone(&context.claude_client),
+            Arc::clone(&context.tools),
+        )
+        .with_config(config),
+    );
+    let shutdown = context.shutdown.clone();
+    Ok(Box::new(move || {
+        let channel = Arc::clone(&channel);
+        let shutdown = shutdown.clone();
+        Box::pin(async move {
+            channel.run(shutdown).await;
+            Ok(())
+        })
+    }))
+}
+
+/// Telegram (TELEGRAM_BOT_TOKEN, TELEGRAM_ADMIN_IDS)
+fn telegram(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let token = require("TELEGRAM_BOT_TOKEN")?;
+    let admin_ids: Vec<i64> = env_list("TELEGRAM_ADMIN_IDS")
+        .iter()
+        .filter_map(|id| id.parse().ok())
+        .collect();
+    let client = Arc::clone(&context.claude_client);
+    let tools = Arc::clone(&context.tools);
+    let policy = context.config.tool_policy.clone();
+    let transcriber = context.transcriber.clone();
+    Ok(Box::new(move || {
+        let mut bot = cc_telegram::TelegramBot::new(&token, Arc::clone(&client), admin_ids.clone())
+            .with_tools(Arc::clone(&tools), policy.clone());
+        if let Some(transcriber) = &transcriber {
+            bot = bot.with_transcriber(Arc::clone(transcriber));
+        }
+        Box::pin(async move { Ok(bot.start().await?) })
+    }))
+}
+
+/// Slack in Socket Mode (SLACK_BOT_TOKEN, SLACK_APP_TOKEN)
+fn slack(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let bot_config = cc_slack::bot::SlackBotConfig {
+        bot_token: require("SLACK_BOT_TOKEN")?,
+        app_token: Some(require("SLACK_APP_TOKEN")?),
+        ..Default::default()
+    };
+    let bot = Arc::new(cc_slack::SlackBot::with_client(
+        bot_config,
+        context.config.clone(),
+        Arc::clone(&context.claude_client),
+    )?);
+    Ok(Box::new(move || {
+        let bot = Arc::clone(&bot);
+        Box::pin(async move { Ok(bot.start().await?) })
+    }))
+}
+
+/// WhatsApp through Twilio (TWILIO_*) or the Cloud API (WHATSAPP_*)
+fn whatsapp(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let twilio = match (
+        env("TWILIO_ACCOUNT_SID"),
+        env("TWILIO_AUTH_TOKEN"),
+        env("TWILIO_PHONE_NUMBER"),
+    ) {
+        (Some(sid), Some(token), Some(number)) => Some((sid, token, number)),
+        _ => None,
+    };
+    let cloud = match twilio {
+        Some(_) => None,
+        None => Some(cc_whatsapp::CloudApiConfig::from_env().map_err(|_| {
+            anyhow::anyhow!("TWILIO_* or WHATSAPP_ACCESS_TOKEN, WHATSAPP_PHONE_NUMBER_ID and WHATSAPP_VERIFY_TOKEN are required")
+        })?),
+    };
+    let allowed = env_list("WHATSAPP_ALLOWED_NUMBERS");
+    let public_url = env("WHATSAPP_PUBLIC_URL");
+    let port = context.config.channels.ports.whatsapp;
+    let client = Arc::clone(&context.claude_client);
+    let transcriber = context.transcriber.clone();
+    Ok(Box::new(move || {
+        let mut bot = match (&twilio, &cloud) {
+            (Some((sid, token, number)), _) => cc_whatsapp::WhatsAppBot::new(
+                sid,
+                token,
+                number,
+                Arc::clone(&client),
+                allowed.clone(),
+                port,
+            ),
+            (None, Some(cloud)) => {
+                cc_whatsapp::WhatsAppBot::cloud(cloud.clone(), Arc::clone(&client), allowed.clone(), port)
+            }
+            (None, None) => unreachable!("checked when the channel was built"),
+        };
+        if let Some(transcriber) = &transcriber {
+            bot = bot.with_transcriber(Arc::clone(transcriber));
+        }
+        if let Some(url) = &public_url {
+            bot = bot.with_public_url(url.clone());
+        }
+        Box::pin(async move { Ok(bot.start().await?) })
+    }))
+}
+
+/// LINE (LINE_CHANNEL_SECRET, LINE_CHANNEL_ACCESS_TOKEN)
+fn line(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let bot_config = cc_line::bot::LineBotConfig {
+        channel_secret: require("LINE_CHANNEL_SECRET")?,
+        channel_access_token: require("LINE_CHANNEL_ACCESS_TOKEN")?,
+        webhook_port: context.config.channels.ports.line,
+        ..Default::default()
+    };
+    let mut bot = cc_line::LineBot::with_client(
+        bot_config,
+        context.config.clone(),
+        Arc::clone(&context.claude_client),
+    )?;
+    if let Some(transcriber) = &context.transcriber {
+        bot = bot.with_transcriber(Arc::clone(transcriber));
+    }
+    let bot = Arc::new(bot);
+    Ok(Box::new(move || {
+        let bot = Arc::clone(&bot);
+        Box::pin(async move { Ok(bot.start().await?) })
+    }))
+}
+
+/// Signal through signal-cli's REST API (SIGNAL_PHONE_NUMBER, SIGNAL_API_URL)
+fn signal(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let defaults = cc_signal::bot::SignalBotConfig::default();
+    let bot_config = cc_signal::bot::SignalBotConfig {
+        phone_number: require("SIGNAL_PHONE_NUMBER")?,
+        api_url: env("SIGNAL_API_URL").unwrap_or(defaults.api_url.clone()),
+        ..defaults
+    };
+    let bot = Arc::new(cc_signal::SignalBot::with_client(
+        bot_config,
+        context.config.clone(),
+        Arc::clone(&context.claude_client),
+    )?);
+    Ok(Box::new(move || {
+        let bot = Arc::clone(&bot);
+        Box::pin(async move {
+            // A failed run leaves the bot marked as running
+            bot.stop();
+            Ok(bot.start().await?)
+        })
+    }))
+}
+
+/// iMessage through a BlueBubbles server (BLUEBUBBLES_URL) or Messages.app on macOS
+fn imessage(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let client = Arc::clone(&context.claude_client);
+    let bot = match env("BLUEBUBBLES_URL") {
+        Some(_) => cc_imessage::IMessageBot::bluebubbles(
+            context.config.clone(),
+            client,
+            cc_imessage::BlueBubblesConfig::from_env()?,
+        )?,
+        None => cc_imessage::IMessageBot::with_client(context.config.clone(), client)?,
+    };
+    let bot = Arc::new(bot);
+    Ok(Box::new(move || {
+        let bot = Arc::clone(&bot);
+        Box::pin(async move { Ok(bot.start().await?) })
+    }))
+}
+
+/// Facebook Messenger webhook (FACEBOOK_PAGE_ID, FACEBOOK_ACCESS_TOKEN,
+/// FACEBOOK_VERIFY_TOKEN, FACEBOOK_APP_SECRET)
+fn facebook(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let mut handler = cc_facebook::FacebookHandler::new(
+        &require("FACEBOOK_PAGE_ID")?,
+        &require("FACEBOOK_ACCESS_TOKEN")?,
+        &require("FACEBOOK_VERIFY_TOKEN")?,
+        Arc::clone(&context.claude_client),
+    )
+    .with_app_secret(&require("FACEBOOK_APP_SECRET")?);
+    if let Some(transcriber) = &context.transcriber {
+        handler = handler.with_transcriber(Arc::clone(transcriber));
+    }
+    let handler = Arc::new(handler);
+    let port = context.config.channels.ports.facebook;
+    Ok(Box::new(move || {
+        let handler = Arc::clone(&handler);
+        Box::pin(async move { Ok(cc_facebook::start_webhook_server(handler, port).await?) })
+    }))
+}
+
+/// Instagram DM webhook (INSTAGRAM_PAGE_ID, INSTAGRAM_ACCESS_TOKEN,
+/// INSTAGRAM_VERIFY_TOKEN, INSTAGRAM_APP_SECRET)
+fn instagram(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let api = cc_instagram::InstagramApi::new(
+        require("INSTAGRAM_ACCESS_TOKEN")?,
+        require("INSTAGRAM_PAGE_ID")?,
+        Some(require("INSTAGRAM_APP_SECRET")?),
+    );
+    let mut handler = cc_instagram::InstagramHandler::new(
+        api,
+        Arc::clone(&context.claude_client),
+        env_list("INSTAGRAM_ADMIN_PSIDS"),
+    );
+    if let Some(transcriber) = &context.transcriber {
+        handler = handler.with_transcriber(Arc::clone(transcriber));
+    }
+    let state = cc_instagram::WebhookState {
+        handler,
+        verify_token: require("INSTAGRAM_VERIFY_TOKEN")?,
+    };
+    let port = context.config.channels.ports.instagram;
+    Ok(Box::new(move || {
+        let state = state.clone();
+        Box::pin(async move { Ok(cc_instagram::start_webhook_server(state, port).await?) })
+    }))
+}
+
+/// WebSocket server (settings from WS_* and OPENAI_API_KEY, see cc-ws)
+///
+/// Clients log in with the shared token, or with the API keys and OIDC
+/// tokens the HTTP API accepts.
+fn websocket(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let config = context.config.clone();
+    let client = Arc::clone(&context.claude_client);
+    let tools = Arc::clone(&context.tools);
+    let authenticator: Arc<dyn cc_ws::WsAuthenticator> = Arc::new(ApiAuthenticator::from_env());
+    Ok(Box::new(move || {
+        let builder = cc_ws::WsServerBuilder::new(config.clone())
+            .port(config.channels.ports.ws)
+            .authenticator(Arc::clone(&authenticator));
+        let sessions = SessionManager::from_config(&config.memory);
+        let client = (*client).clone();
+        let tools = Arc::clone(&tools);
+        Box::pin(async move { Ok(builder.start(client, sessions?, tools).await?) })
+    }))
+}
+
+/// Checks WebSocket tokens against the issued API keys and the OIDC provider
+struct ApiAuthenticator {
+    keys: Option<cc_api::ApiKeyStore>,
+    oidc: Option<cc_api::OidcVerifier>,
+}
+
+impl ApiAuthenticator {
+    /// The key store at API_KEYS_DB_PATH and the provider of API_OIDC_ISSUER
+    fn from_env() -> Self {
+        let path = env("API_KEYS_DB_PATH").unwrap_or_else(|| cc_api::ApiKeyStore::DEFAULT_PATH.to_string());
+        let keys = match cc_api::ApiKeyStore::open(&path) {
+            Ok(store) => Some(store),
+            Err(e) => {
+                tracing::warn!("WebSocket clients cannot use API keys ({}): {}", path, e);
+                None
+            }
+        };
+        Self {
+            keys,
+            oidc: cc_api::OidcConfig::from_env().map(cc_api::OidcVerifier::new),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl cc_ws::WsAuthenticator for ApiAuthenticator {
+    fn is_enabled(&self) -> bool {
+        // A store that cannot be read counts as having keys, so nobody gets in unchecked
+        self.oidc.is_some()
+            || self.keys.as_ref().is_some_and(|keys| keys.has_active_keys().unwrap_or(true))
+    }
+
+    async fn authenticate(&self, token: &str) -> Option<String> {
+        if let Some(oidc) = self.oidc.as_ref().filter(|_| cc_api::OidcVerifier::is_jwt(token)) {
+            return match oidc.verify(token).await {
+                Ok(user) => Some(user.id),
+                Err(e) => {
+                    tracing::warn!("Rejected WebSocket JWT: {}", e);
+                    None
+                }
+            };
+        }
+        match self.keys.as_ref()?.authenticate(token) {
+            Ok(key) => key.map(|key| key.id),
+            Err(e) => {
+                tracing::error!("Failed to authenticate WebSocket API key: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Web dashboard of the stored sessions (DASHBOARD_HOST, default 127.0.0.1)
+fn dashboard(context: &ChannelContext) -> anyhow::Result<ServiceFactory> {
+    let host = env("DASHBOARD_HOST").unwrap_or_else(|| cc_dashboard::DashboardConfig::default().host);
+    let config = cc_dashboard::DashboardConfig::new(host, context.config.channels.ports.dashboard);
+    config.socket_addr()?;
+    let sessions = Arc::new(SessionManager::from_config(&context.config.memory)?);
+    let supervisor = Arc::clone(&context.supervisor);
+    Ok(Box::new(move || {
+        let server = cc_dashboard::DashboardServer::new(
+            config.clone(),
+            Arc::clone(&sessions) as Arc<dyn cc_dashboard::SessionProvider + Send + Sync>,
+            Arc::clone(&sessions) as Arc<dyn cc_dashboard::UsageProvider + Send + Sync>,
+        )
+        .with_services(Arc::clone(&supervisor) as Arc<dyn cc_dashboard::ServiceProvider + Send + Sync>);
+        Box::pin(async move { Ok(server.run().await?) })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_list() {
+        unsafe {
+            std::env::set_var("CC_GATEWAY_TEST_LIST", " 1, 2,,3 ");
+        }
+        assert_eq!(env_list("CC_GATEWAY_TEST_LIST"), vec!["1", "2", "3"]);
+        assert!(env_list("CC_GATEWAY_TEST_MISSING").is_empty());
+        assert!(require("CC_GATEWAY_TEST_MISSING").is_err());
+        unsafe {
+            std::env::remove_var("CC_GATEWAY_TEST_LIST");
+        }
+    }
+}
@@ -3,8 +3,12 @@
 //! Provides an interactive REPL for OpenClaw-like experience.
 //! Also supports non-interactive execute mode for one-shot execution.
 
-use cc_core::{ClaudeClient, Message, MessageContent, ToolManager, ToolResult};
-use cc_core::llm::{MessagesRequest, ToolDefinition};
+use async_trait::async_trait;
+use cc_core::{
+    AgentEngine, AgentHooks, ApprovalOutcome, ClaudeClient, Config, Message, PolicyDecision,
+    SubAgentTask, Tool, ToolApprover, ToolLimits, ToolManager, ToolPolicy, ToolResult,
+};
+use cc_core::llm::{MessagesRequest, ToolCall, ToolDefinition};
 use cc_tools::register_default_tools;
 use nu_ansi_term::{Color, Style};
 use reedline::{
@@ -12,9 +16,19 @@ use reedline::{
     Keybindings, MenuBuilder, Prompt, Reedline, ReedlineEvent, ReedlineMenu, Signal, Suggestion,
 };
 use serde_json::Value as JsonValue;
-use std::path::Path;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::info;
 
+use crate::attachment::{self, Attachment};
+use crate::conversations::{ConversationSummary, Conversations};
+use crate::extensions::CliExtensions;
+use crate::markdown::MarkdownRenderer;
+
+/// Longest tool input or output line shown when collapsed
+const PREVIEW_CHARS: usize = 80;
+
 /// Available commands for autocomplete display
 const COMMANDS: &[(&str, &str)] = &[
     ("/help", "ヘルプを表示"),
@@ -22,20 +36,36 @@ const COMMANDS: &[(&str, &str)] = &[
     ("/quit", "プログラムを終了"),
     ("/clear", "会話履歴をクリア"),
     ("/history", "会話履歴を表示"),
+    ("/save", "会話を名前を付けて保存（/save <名前>）"),
+    ("/load", "保存した会話を読み込む（/load <名前>）"),
+    ("/sessions", "保存した会話の一覧"),
+    ("/attach", "ファイルや画像を次のメッセージに添付（/attach <パス>）"),
+    ("/skill", "スキルを実行（/skill <名前> [JSON]）"),
+    ("/agent", "サブエージェントに依頼（/agent <名前> <依頼>）"),
+    ("/tool", "ツールを直接実行（/tool <名前> [JSON]）"),
 ];
 
 /// Command completer for reedline
 #[derive(Clone)]
 pub struct CommandCompleter {
-    commands: Vec<(&'static str, &'static str)>,
+    commands: Vec<(String, String)>,
 }
 
 impl CommandCompleter {
     pub fn new() -> Self {
         Self {
-            commands: COMMANDS.to_vec(),
+            commands: COMMANDS
+                .iter()
+                .map(|(cmd, desc)| (cmd.to_string(), desc.to_string()))
+                .collect(),
         }
     }
+
+    /// Also complete discovered commands such as `/skill weather`
+    pub fn with_commands(mut self, commands: Vec<(String, String)>) -> Self {
+        self.commands.extend(commands);
+        self
+    }
 }
 
 impl Default for CommandCompleter {
@@ -56,8 +86,8 @@ impl Completer for CommandCompleter {
             .iter()
             .filter(|(cmd, _)| cmd.starts_with(line))
             .map(|(cmd, desc)| Suggestion {
-                value: cmd.to_string(),
-                description: Some(desc.to_string()),
+                value: cmd.clone(),
+                description: Some(desc.clone()),
                 extra: None,
                 span: reedline::Span::new(0, pos),
                 append_whitespace: true,
@@ -105,10 +135,36 @@ impl Prompt for ColoredPrompt {
     }
 }
 
+/// Command line options of the CLI modes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CliOptions {
+    /// Print tool inputs and outputs in full (`--verbose`)
+    pub verbose: bool,
+    /// Tools to register (`--tools bash,read`; `--no-tools` = none; None = all)
+    pub tools: Option<Vec<String>>,
+    /// Run tools that need approval without asking (`--yes`)
+    pub yes: bool,
+    /// Ask before every tool that changes something (`--plan`)
+    pub plan: bool,
+    /// Saved conversation to continue (`--resume <name>`)
+    pub resume: Option<String>,
+    /// Files attached to the first prompt (`--attach <path>`, repeatable)
+    pub attach: Vec<PathBuf>,
+}
+
 /// CLI configuration
 pub struct CliConfig {
     pub system_prompt: String,
     pub max_iterations: usize,
+    pub options: CliOptions,
+    /// Decides which tools need a y/n confirmation
+    pub tool_policy: ToolPolicy,
+    /// Timeouts and concurrency of tool calls
+    pub tool_limits: ToolLimits,
+    /// Session database of saved conversations (None = /save is unavailable)
+    pub sessions_db: Option<String>,
+    /// Skills, sub-agents and MCP tools
+    pub extensions: CliExtensions,
 }
 
 impl Default for CliConfig {
@@ -119,23 +175,167 @@ impl Default for CliConfig {
                 必要に応じてツールを使用してユーザーを支援してください。"
                 .to_string(),
             max_iterations: 10,
+            options: CliOptions::default(),
+            tool_policy: ToolPolicy::default(),
+            tool_limits: ToolLimits::default(),
+            sessions_db: None,
+            extensions: CliExtensions::default(),
+        }
+    }
+}
+
+/// Tools of a CLI run and how their calls are shown and approved
+struct CliTools {
+    manager: ToolManager,
+    policy: ToolPolicy,
+    verbose: bool,
+    approver: TerminalApprover,
+}
+
+impl CliTools {
+    fn new(
+        options: &CliOptions,
+        mut policy: ToolPolicy,
+        limits: ToolLimits,
+        extra: &[Arc<dyn Tool>],
+    ) -> anyhow::Result<Self> {
+        let mut manager = build_tool_manager(extra, options.tools.as_deref())?;
+        manager.set_limits(limits);
+        policy.plan_mode |= options.plan;
+        manager.set_policy(policy.clone());
+        Ok(Self {
+            manager,
+            policy,
+            verbose: options.verbose,
+            approver: TerminalApprover {
+                auto_approve: options.yes,
+                verbose: options.verbose,
+                asking: tokio::sync::Mutex::new(()),
+            },
+        })
+    }
+}
+
+/// Register the default tools and `extra`, keeping only `selected` when given
+fn build_tool_manager(extra: &[Arc<dyn Tool>], selected: Option<&[String]>) -> anyhow::Result<ToolManager> {
+    let mut all = ToolManager::new();
+    register_default_tools(&mut all);
+    for tool in extra {
+        all.register(Arc::clone(tool));
+    }
+    let Some(selected) = selected else {
+        return Ok(all);
+    };
+
+    let mut manager = ToolManager::new();
+    for name in selected {
+        let tool = all.get(name).ok_or_else(|| {
+            let mut available = all.tool_names();
+            available.sort_unstable();
+            anyhow::anyhow!("不明なツール: {}（利用可能: {}）", name, available.join(", "))
+        })?;
+        manager.register(tool);
+    }
+    Ok(manager)
+}
+
+/// Asks on the terminal before a tool runs
+struct TerminalApprover {
+    /// Approve without asking (`--yes`)
+    auto_approve: bool,
+    verbose: bool,
+    /// Held while a question is on the terminal (tool calls may run concurrently)
+    asking: tokio::sync::Mutex<()>,
+}
+
+#[async_trait]
+impl ToolApprover for TerminalApprover {
+    async fn approve(&self, tool: &str, input: &JsonValue, preview: Option<&str>) -> ApprovalOutcome {
+        let by = std::env::var("USER").unwrap_or_else(|_| "cli".to_string());
+        if self.auto_approve {
+            return ApprovalOutcome::Approved { by };
+        }
+        // Nobody can answer when stdin is not a terminal
+        if !std::io::stdin().is_terminal() {
+            return ApprovalOutcome::TimedOut;
+        }
+
+        // The preview (diff, command, draft) says more than the input
+        let shown = match preview {
+            Some(preview) => format!("\n{}", indent(preview)),
+            None => format_input(input, self.verbose),
+        };
+        let question = format!(
+            "\n⚠️  {} を実行しますか？ {}\n   [y/N] ",
+            Color::Yellow.bold().paint(tool),
+            shown
+        );
+        let _asking = self.asking.lock().await;
+        let answer = tokio::task::spawn_blocking(move || {
+            print!("{}", question);
+            std::io::stdout().flush().ok();
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line).map(|_| line)
+        })
+        .await;
+
+        match answer {
+            Ok(Ok(line)) if matches!(line.trim().to_lowercase().as_str(), "y" | "yes") => {
+                ApprovalOutcome::Approved { by }
+            }
+            Ok(Ok(_)) => ApprovalOutcome::Denied { by },
+            _ => ApprovalOutcome::TimedOut,
+        }
+    }
+}
+
+/// Conversation of the REPL
+struct ReplState {
+    messages: Vec<Message>,
+    /// Name the conversation is saved under; it is saved after every turn
+    name: Option<String>,
+    conversations: Option<Conversations>,
+    /// Files to send with the next message
+    attachments: Vec<Attachment>,
+}
+
+impl ReplState {
+    /// Save the conversation when it has a name
+    fn autosave(&self) {
+        if let (Some(name), Some(conversations)) = (&self.name, &self.conversations) {
+            if let Err(e) = conversations.save(name, &self.messages) {
+                eprintln!("\n❌ 会話 {} を保存できませんでした: {}\n", name, e);
+            }
         }
     }
 }
 
 /// Run CLI interactive mode
-pub async fn run_cli(client: ClaudeClient) -> anyhow::Result<()> {
-    let config = CliConfig::default();
+pub async fn run_cli(client: ClaudeClient, options: CliOptions, config: &Config) -> anyhow::Result<()> {
+    let config = CliConfig {
+        options,
+        tool_policy: config.tool_policy.clone(),
+        tool_limits: config.tool_limits.clone(),
+        sessions_db: Some(config.memory.db_path.clone()),
+        extensions: CliExtensions::load(config).await,
+        ..Default::default()
+    };
     run_cli_with_config(client, config).await
 }
 
 /// Run CLI with custom configuration
 pub async fn run_cli_with_config(client: ClaudeClient, cli_config: CliConfig) -> anyhow::Result<()> {
     // Initialize tool manager
-    let mut tool_manager = ToolManager::new();
-    register_default_tools(&mut tool_manager);
+    let extensions = &cli_config.extensions;
+    let extra: Vec<_> = extensions.tools().cloned().collect();
+    let tools = CliTools::new(
+        &cli_config.options,
+        cli_config.tool_policy.clone(),
+        cli_config.tool_limits.clone(),
+        &extra,
+    )?;
 
-    info!("Starting CLI mode with {} tools", tool_manager.len());
+    info!("Starting CLI mode with {} tools", tools.manager.len());
 
     // Welcome message
     print_welcome();
@@ -164,7 +364,9 @@ pub async fn run_cli_with_config(client: ClaudeClient, cli_config: CliConfig) ->
 
     // Create line editor
     let mut line_editor = Reedline::create()
-        .with_completer(Box::new(CommandCompleter::new()))
+        .with_completer(Box::new(
+            CommandCompleter::new().with_commands(extensions.completions(&tools.manager)),
+        ))
         .with_menu(ReedlineMenu::EngineCompleter(menu))
         .with_hinter(Box::new(hinter))
         .with_edit_mode(Box::new(Emacs::new(keybindings)));
@@ -172,7 +374,39 @@ pub async fn run_cli_with_config(client: ClaudeClient, cli_config: CliConfig) ->
     let prompt = ColoredPrompt::new();
 
     // Conversation history
-    let mut messages: Vec<cc_core::Message> = Vec::new();
+    let conversations = match &cli_config.sessions_db {
+        Some(path) => match Conversations::open(path) {
+            Ok(conversations) => Some(conversations),
+            Err(e) => {
+                eprintln!("⚠️  会話の保存は使えません ({}): {}", path, e);
+                None
+            }
+        },
+        None => None,
+    };
+    let mut state = ReplState {
+        messages: Vec::new(),
+        name: None,
+        conversations,
+        attachments: Vec::new(),
+    };
+    for path in &cli_config.options.attach {
+        attach_file(&mut state, path);
+    }
+    if let Some(name) = &cli_config.options.resume {
+        let conversations = state
+            .conversations
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("--resume には会話の保存先が必要です"))?;
+        match conversations.load(name)? {
+            Some(messages) => {
+                println!("📂 会話 {} を再開します（{} 件のメッセージ）\n", name, messages.len());
+                state.messages = messages;
+            }
+            None => println!("📝 新しい会話 {} を開始します\n", name),
+        }
+        state.name = Some(name.clone());
+    }
 
     loop {
         let signal = line_editor.read_line(&prompt);
@@ -187,29 +421,48 @@ pub async fn run_cli_with_config(client: ClaudeClient, cli_config: CliConfig) ->
                 }
 
                 // Handle special commands
-                if handle_command(input, &mut messages) {
+                if handle_command(input, &mut state, &tools, extensions).await {
                     continue;
                 }
 
+                // Inline @path tokens are attached along with /attach files
+                let (text, paths) = attachment::extract_inline(input);
+                let mut attachments = std::mem::take(&mut state.attachments);
+                match load_attachments(&paths) {
+                    Ok(loaded) => attachments.extend(loaded),
+                    Err(e) => {
+                        state.attachments = attachments;
+                        eprintln!("\n❌ {}\n", e);
+                        continue;
+                    }
+                }
+                for attachment in &attachments {
+                    println!("📎 {}", attachment.describe());
+                }
+
                 // Add user message to history
-                messages.push(cc_core::Message::user(input));
+                state
+                    .messages
+                    .push(attachment::user_message(&text, &attachments));
+                println!();
 
                 // Run agent loop
                 match run_agent_turn(
                     &client,
-                    &mut messages,
+                    &mut state.messages,
                     &cli_config.system_prompt,
-                    &tool_manager,
+                    &tools,
                     cli_config.max_iterations,
                 )
                 .await
                 {
                     Ok(response) => {
-                        // Print response
-                        println!("\n{}\n", response);
+                        // The response was printed as it streamed
+                        println!();
 
                         // Add assistant response to history
-                        messages.push(cc_core::Message::assistant(&response));
+                        state.messages.push(cc_core::Message::assistant(&response));
+                        state.autosave();
                     }
                     Err(e) => {
                         eprintln!("\n❌ エラー: {}\n", e);
@@ -277,9 +530,19 @@ fn default_keybindings() -> Keybindings {
     keybindings
 }
 
-/// Handle special commands (/, /exit, /clear, /help)
-fn handle_command(input: &str, messages: &mut Vec<cc_core::Message>) -> bool {
-    let lower = input.to_lowercase();
+/// Handle special commands (/, /exit, /clear, /help, /save, /load, /sessions, /attach,
+/// /skill, /agent, /tool)
+async fn handle_command(
+    input: &str,
+    state: &mut ReplState,
+    tools: &CliTools,
+    extensions: &CliExtensions,
+) -> bool {
+    let (command, argument) = match input.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, Some(argument.trim()).filter(|a| !a.is_empty())),
+        None => (input, None),
+    };
+    let lower = command.to_lowercase();
 
     match lower.as_str() {
         "/exit" | "/quit" | "/q" => {
@@ -287,7 +550,10 @@ fn handle_command(input: &str, messages: &mut Vec<cc_core::Message>) -> bool {
             std::process::exit(0);
         }
         "/clear" => {
-            messages.clear();
+            // The saved conversation is kept; the next one starts unnamed
+            state.messages.clear();
+            state.name = None;
+            state.attachments.clear();
             println!("\n✅ 会話履歴をクリアしました。\n");
             true
         }
@@ -296,7 +562,47 @@ fn handle_command(input: &str, messages: &mut Vec<cc_core::Message>) -> bool {
             true
         }
         "/history" => {
-            print_history(messages);
+            print_history(&state.messages);
+            true
+        }
+        "/save" => {
+            save_conversation(state, argument);
+            true
+        }
+        "/load" => {
+            load_conversation(state, argument);
+            true
+        }
+        "/sessions" => {
+            print_conversations(state);
+            true
+        }
+        "/attach" => {
+            match argument {
+                Some(path) => attach_file(state, Path::new(path)),
+                None => print_attachments(state),
+            }
+            true
+        }
+        "/skill" => {
+            match argument {
+                Some(argument) => run_skill_command(tools, extensions, argument).await,
+                None => print_skills(extensions),
+            }
+            true
+        }
+        "/agent" => {
+            match argument {
+                Some(argument) => run_agent_command(state, extensions, argument).await,
+                None => print_agents(extensions),
+            }
+            true
+        }
+        "/tool" => {
+            match argument {
+                Some(argument) => run_tool_command(tools, argument).await,
+                None => print_tools(tools),
+            }
             true
         }
         _ if lower.starts_with('/') => {
@@ -307,117 +613,453 @@ fn handle_command(input: &str, messages: &mut Vec<cc_core::Message>) -> bool {
     }
 }
 
+/// `/save [name]`: save the conversation and keep saving it after every turn
+fn save_conversation(state: &mut ReplState, name: Option<&str>) {
+    let Some(conversations) = &state.conversations else {
+        eprintln!("\n❌ 会話の保存先がありません。\n");
+        return;
+    };
+    let Some(name) = name.or(state.name.as_deref()).map(str::to_string) else {
+        eprintln!("\n使い方: /save <名前>\n");
+        return;
+    };
+    match conversations.save(&name, &state.messages) {
+        Ok(()) => {
+            println!("\n💾 会話を {} として保存しました（以降も自動で保存します）\n", name);
+            state.name = Some(name);
+        }
+        Err(e) => eprintln!("\n❌ 保存できませんでした: {}\n", e),
+    }
+}
+
+/// `/load <name>`: replace the conversation with a saved one
+fn load_conversation(state: &mut ReplState, name: Option<&str>) {
+    let Some(conversations) = &state.conversations else {
+        eprintln!("\n❌ 会話の保存先がありません。\n");
+        return;
+    };
+    let Some(name) = name else {
+        eprintln!("\n使い方: /load <名前>（/sessions で一覧を表示）\n");
+        return;
+    };
+    match conversations.load(name) {
+        Ok(Some(messages)) => {
+            println!("\n📂 会話 {} を読み込みました（{} 件のメッセージ）\n", name, messages.len());
+            state.messages = messages;
+            state.name = Some(name.to_string());
+        }
+        Ok(None) => eprintln!("\n❓ 会話 {} は保存されていません。\n", name),
+        Err(e) => eprintln!("\n❌ 読み込めませんでした: {}\n", e),
+    }
+}
+
+/// `/attach <path>`: send a file with the next message
+fn attach_file(state: &mut ReplState, path: &Path) {
+    match Attachment::load(path) {
+        Ok(attachment) => {
+            println!("\n📎 {} を次のメッセージに添付します\n", attachment.describe());
+            state.attachments.push(attachment);
+        }
+        Err(e) => eprintln!("\n❌ {}\n", e),
+    }
+}
+
+/// `/attach`: list the files waiting for the next message
+fn print_attachments(state: &ReplState) {
+    println!();
+    if state.attachments.is_empty() {
+        println!("添付ファイルはありません。/attach <パス> または @パス で添付できます。");
+    } else {
+        println!("📎 次のメッセージに添付するファイル:");
+        for attachment in &state.attachments {
+            println!("  {}", attachment.describe());
+        }
+    }
+    println!();
+}
+
+/// Read the files at `paths`
+fn load_attachments(paths: &[PathBuf]) -> anyhow::Result<Vec<Attachment>> {
+    paths.iter().map(|path| Attachment::load(path)).collect()
+}
+
+/// `/tool <name> [json]`: run a tool directly (the tool policy still applies)
+async fn run_tool_command(tools: &CliTools, argument: &str) {
+    let (name, input) = match parse_invocation(argument) {
+        Ok(invocation) => invocation,
+        Err(e) => {
+            eprintln!("\n❌ {}\n", e);
+            return;
+        }
+    };
+    if !tools.manager.contains(name) {
+        eprintln!("\n❓ 不明なツール: {}。/tool で一覧を表示します。\n", name);
+        return;
+    }
+    eprintln!(
+        "\n⚙️  {} {}",
+        Color::Cyan.bold().paint(name),
+        format_input(&input, tools.verbose)
+    );
+    let result = run_tool(tools, name, input).await;
+    eprintln!("{}\n", format_output(&result, true));
+}
+
+/// `/skill <name> [json]`: run a skill
+async fn run_skill_command(tools: &CliTools, extensions: &CliExtensions, argument: &str) {
+    let name = argument.split_whitespace().next().unwrap_or_default();
+    if !extensions.is_skill(name) {
+        eprintln!("\n❓ 不明なスキル: {}。/skill で一覧を表示します。\n", name);
+        return;
+    }
+    run_tool_command(tools, argument).await;
+}
+
+/// `/agent <name> <task>`: hand a task to a sub-agent, with the conversation as context
+async fn run_agent_command(state: &ReplState, extensions: &CliExtensions, argument: &str) {
+    let Some(agents) = &extensions.agents else {
+        eprintln!("\n❌ サブエージェントは読み込まれていません。\n");
+        return;
+    };
+    let (name, instruction) = argument
+        .split_once(char::is_whitespace)
+        .map(|(name, instruction)| (name, instruction.trim()))
+        .unwrap_or((argument, ""));
+    let Some(agent) = agents.get_by_name(name) else {
+        eprintln!("\n❓ 不明なエージェント: {}。/agent で一覧を表示します。\n", name);
+        return;
+    };
+    if instruction.is_empty() {
+        eprintln!("\n使い方: /agent {} <依頼内容>\n", name);
+        return;
+    }
+
+    eprintln!("\n🤖 {} に依頼しています...", Color::Cyan.bold().paint(name));
+    let task = SubAgentTask::new(instruction).with_context(state.messages.clone());
+    match agents.execute_tracked(&agent, task).await {
+        Ok(result) if result.success => {
+            let mut renderer = MarkdownRenderer::for_stdout();
+            println!();
+            print!("{}", renderer.push(&result.output));
+            print!("{}", renderer.finish());
+            eprintln!(
+                "{}\n",
+                Style::new().dimmed().paint(format!(
+                    "   {} 回の反復、ツール呼び出し {} 回、{:.1} 秒",
+                    result.iterations,
+                    result.tool_calls.len(),
+                    result.execution_time_ms as f64 / 1000.0
+                ))
+            );
+        }
+        Ok(result) => eprintln!(
+            "\n❌ {} が失敗しました: {}\n",
+            name,
+            result.error.unwrap_or(result.output)
+        ),
+        Err(e) => eprintln!("\n❌ {} が失敗しました: {}\n", name, e),
+    }
+}
+
+/// Split `name {json}` into the name and its input (`{}` when omitted)
+fn parse_invocation(argument: &str) -> Result<(&str, JsonValue), String> {
+    let (name, json) = match argument.split_once(char::is_whitespace) {
+        Some((name, json)) => (name, json.trim()),
+        None => (argument, ""),
+    };
+    if json.is_empty() {
+        return Ok((name, serde_json::json!({})));
+    }
+    match serde_json::from_str::<JsonValue>(json) {
+        Ok(input) if input.is_object() => Ok((name, input)),
+        Ok(_) => Err("入力は JSON オブジェクトで指定してください（例: {\"path\": \"README.md\"}）".to_string()),
+        Err(e) => Err(format!("JSON を解析できません: {}", e)),
+    }
+}
+
+/// `/tool`: list the registered tools
+fn print_tools(tools: &CliTools) {
+    let mut names = tools.manager.tool_names();
+    names.sort_unstable();
+    println!();
+    println!("🔧 ツール ({} 件):", names.len());
+    for name in names {
+        if let Some(tool) = tools.manager.get(name) {
+            println!("  {}  {}", Color::Cyan.bold().paint(name), preview(tool.description()));
+        }
+    }
+    println!();
+}
+
+/// `/skill`: list the loaded skills
+fn print_skills(extensions: &CliExtensions) {
+    println!();
+    if extensions.skills.is_empty() {
+        println!("スキルはありません（skills/ や ~/.cc-gateway/skills/ に YAML・TOML で定義します）。");
+    } else {
+        println!("🧩 スキル ({} 件):", extensions.skills.len());
+        for skill in &extensions.skills {
+            println!(
+                "  {}  {}",
+                Color::Cyan.bold().paint(skill.name()),
+                preview(skill.description())
+            );
+        }
+    }
+    println!();
+}
+
+/// `/agent`: list the sub-agents
+fn print_agents(extensions: &CliExtensions) {
+    let agents = extensions
+        .agents
+        .as_ref()
+        .map(|agents| agents.all_agents())
+        .unwrap_or_default();
+    println!();
+    if agents.is_empty() {
+        println!("サブエージェントはありません。");
+    } else {
+        println!("🤖 サブエージェント ({} 件):", agents.len());
+        for agent in &agents {
+            println!(
+                "  {}  {}",
+                Color::Cyan.bold().paint(agent.name()),
+                preview(agent.description())
+            );
+        }
+    }
+    println!();
+}
+
+/// `/sessions`: list saved conversations
+fn print_conversations(state: &ReplState) {
+    let Some(conversations) = &state.conversations else {
+        eprintln!("\n❌ 会話の保存先がありません。\n");
+        return;
+    };
+    let list = match conversations.list() {
+        Ok(list) => list,
+        Err(e) => {
+            eprintln!("\n❌ 一覧を取得できませんでした: {}\n", e);
+            return;
+        }
+    };
+
+    print_conversation_list(&list, state.name.as_deref());
+}
+
+/// Saved conversations, marking `current`
+pub fn print_conversation_list(list: &[ConversationSummary], current: Option<&str>) {
+    println!();
+    if list.is_empty() {
+        println!("保存した会話はありません。/save <名前> で保存できます。");
+        println!();
+        return;
+    }
+    println!("💾 保存した会話 ({} 件):", list.len());
+    println!("{}", "─".repeat(50));
+    for conversation in list {
+        let current = if current == Some(conversation.name.as_str()) {
+            " *"
+        } else {
+            ""
+        };
+        println!(
+            "  {}{}  {}  {} 件  約 {} トークン",
+            Color::Cyan.bold().paint(&conversation.name),
+            current,
+            conversation
+                .updated_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            conversation.messages,
+            conversation.tokens
+        );
+    }
+    println!("{}", "─".repeat(50));
+    println!();
+}
+
 /// Run a single agent turn with tools
+///
+/// Answer text is printed as it streams; the final answer is returned.
 async fn run_agent_turn(
     client: &ClaudeClient,
     messages: &mut Vec<cc_core::Message>,
     system_prompt: &str,
-    tool_manager: &ToolManager,
+    tools: &CliTools,
     max_iterations: usize,
 ) -> anyhow::Result<String> {
-    let mut iterations = 0;
+    let definitions = get_tool_definitions(&tools.manager);
+    let mut request = MessagesRequest {
+        model: client.model().to_string(),
+        max_tokens: 4096,
+        system: Some(system_prompt.to_string()),
+        messages: std::mem::take(messages),
+        tools: if definitions.is_empty() { None } else { Some(definitions) },
+        thinking: None,
+        tool_choice: None,
+        temperature: None,
+    };
+    let hooks = CliHooks {
+        tools,
+        renderer: std::sync::Mutex::new(MarkdownRenderer::for_stdout()),
+    };
 
-    loop {
-        iterations += 1;
-        if iterations > max_iterations {
-            return Ok("最大反復回数に達しました。よりシンプルなリクエストで再試行してください。".to_string());
-        }
-
-        // Build request
-        let request = MessagesRequest {
-            model: client.model().to_string(),
-            max_tokens: 4096,
-            system: Some(system_prompt.to_string()),
-            messages: messages.clone(),
-            tools: Some(get_tool_definitions(tool_manager)),
-            thinking: None,
-        };
+    let run = AgentEngine::new(client)
+        .with_tools(&tools.manager)
+        .with_max_iterations(max_iterations)
+        .streaming(true)
+        .run(&mut request, &hooks)
+        .await;
+    hooks.finish_text();
+    *messages = request.messages;
+
+    match run {
+        Ok(run) => Ok(run.text),
+        Err(cc_core::Error::MaxIterations(_)) => {
+            let message = "最大反復回数に達しました。よりシンプルなリクエストで再試行してください。";
+            println!("{}", message);
+            Ok(message.to_string())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
 
-        let response = client.messages(request).await?;
-
-        match response.stop_reason.as_str() {
-            "end_turn" | "stop_sequence" | "stop" => {
-                // Extract text response
-                let text = response
-                    .content
-                    .iter()
-                    .filter_map(|c| {
-                        if let cc_core::MessageContent::Text { text } = c {
-                            Some(text.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
-
-                return Ok(text);
-            }
-            "tool_use" | "tool_calls" => {
-                // Process tool uses
-                let tool_uses: Vec<_> = response
-                    .content
-                    .iter()
-                    .filter_map(|c| {
-                        if let cc_core::MessageContent::ToolUse { id, name, input } = c {
-                            Some((id.clone(), name.clone(), input.clone()))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-
-                if tool_uses.is_empty() {
-                    continue;
-                }
+/// Streams answers to stdout and shows tool calls on stderr
+struct CliHooks<'a> {
+    tools: &'a CliTools,
+    renderer: std::sync::Mutex<MarkdownRenderer>,
+}
 
-                // Add assistant message with tool_use
-                messages.push(cc_core::Message {
-                    role: "assistant".to_string(),
-                    content: response.content.clone(),
-                });
-
-                // Execute tools and collect results
-                let mut tool_results = Vec::new();
-                for (id, name, input) in &tool_uses {
-                    info!("Executing tool: {} with input: {:?}", name, input);
-
-                    let result = execute_tool(tool_manager, name, input.clone()).await;
-                    tool_results.push(MessageContent::ToolResult {
-                        tool_use_id: id.clone(),
-                        content: result.output.clone(),
-                        is_error: result.is_error,
-                    });
-
-                    // Show tool execution to user
-                    if result.is_error {
-                        eprintln!("\n⚙️ ツール {} の実行に失敗: {}", name, result.output);
-                    } else {
-                        println!("\n⚙️ ツール {} を実行しました", name);
-                    }
-                }
+impl CliHooks<'_> {
+    /// Print what the renderer still holds of the streamed text
+    fn finish_text(&self) {
+        let mut renderer = self.renderer.lock().unwrap_or_else(|e| e.into_inner());
+        print!("{}", renderer.finish());
+        std::io::stdout().flush().ok();
+    }
+}
 
-                // Add user message with tool_results
-                messages.push(cc_core::Message {
-                    role: "user".to_string(),
-                    content: tool_results,
-                });
-            }
-            other => {
-                return Err(anyhow::anyhow!("Unknown stop_reason: {}", other));
+#[async_trait]
+impl AgentHooks for CliHooks<'_> {
+    fn on_text_delta(&self, delta: &str) {
+        let mut renderer = self.renderer.lock().unwrap_or_else(|e| e.into_inner());
+        print!("{}", renderer.push(delta));
+        std::io::stdout().flush().ok();
+    }
+
+    async fn on_text(&self, _text: &str) {
+        self.finish_text();
+    }
+
+    async fn on_tool_call(&self, call: &ToolCall, _tools: &ToolManager) -> ToolResult {
+        info!("Executing tool: {} with input: {:?}", call.name, call.input);
+        eprintln!(
+            "\n⚙️  {} {}",
+            Color::Cyan.bold().paint(&call.name),
+            format_input(&call.input, self.tools.verbose)
+        );
+        let result = run_tool(self.tools, &call.name, call.input.clone()).await;
+        eprintln!("{}", format_output(&result, self.tools.verbose));
+        result
+    }
+}
+
+/// Run one tool call as the tool policy allows
+async fn run_tool(tools: &CliTools, name: &str, input: JsonValue) -> ToolResult {
+    match tools.policy.decide_call(name, tools.manager.is_read_only(name)) {
+        PolicyDecision::Allow => {}
+        PolicyDecision::Deny => {
+            return ToolResult::error(format!("Tool {} is not allowed by the tool policy", name));
+        }
+        PolicyDecision::RequireApproval => {
+            let preview = tools.manager.preview(name, &input).await;
+            match tools.approver.approve(name, &input, preview.as_deref()).await {
+                ApprovalOutcome::Approved { .. } => {}
+                ApprovalOutcome::Denied { .. } => {
+                    return ToolResult::error(format!("The user denied running {}", name));
+                }
+                ApprovalOutcome::TimedOut => {
+                    return ToolResult::error(format!(
+                        "Running {} needs confirmation (run in a terminal or pass --yes)",
+                        name
+                    ));
+                }
             }
         }
     }
+    execute_tool(&tools.manager, name, input).await
 }
 
-/// Execute a tool by name
+/// Execute a tool call the policy allowed or the user approved
 async fn execute_tool(tool_manager: &ToolManager, name: &str, input: JsonValue) -> ToolResult {
-    match tool_manager.execute(name, input).await {
+    match tool_manager.execute_approved(name, input).await {
         Ok(result) => result,
         Err(e) => ToolResult::error(format!("Tool execution error: {}", e)),
     }
 }
 
+/// Tool input: the main argument on one line, or pretty JSON when verbose
+fn format_input(input: &JsonValue, verbose: bool) -> String {
+    if verbose {
+        let json = serde_json::to_string_pretty(input).unwrap_or_default();
+        return format!("\n{}", indent(&json));
+    }
+    let main = ["command", "file_path", "path", "pattern", "url", "query"]
+        .iter()
+        .find_map(|key| input.get(key).and_then(JsonValue::as_str));
+    let summary = match main {
+        Some(value) => value.to_string(),
+        None => input.to_string(),
+    };
+    Style::new().dimmed().paint(preview(&summary)).to_string()
+}
+
+/// Tool output: status and first line, or everything when verbose
+fn format_output(result: &ToolResult, verbose: bool) -> String {
+    let status = if result.is_error {
+        Color::Red.paint("✗ 失敗")
+    } else {
+        Color::Green.paint("✓ 完了")
+    };
+    if verbose {
+        return format!("   {}\n{}", status, indent(&result.output));
+    }
+    let lines = result.output.lines().count();
+    let first = result.output.lines().next().unwrap_or_default();
+    let more = if lines > 1 {
+        format!(" （他 {} 行、--verbose で全表示）", lines - 1)
+    } else {
+        String::new()
+    };
+    format!(
+        "   {} {}{}",
+        status,
+        Style::new().dimmed().paint(preview(first)),
+        more
+    )
+}
+
+/// First line, shortened to PREVIEW_CHARS
+fn preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > PREVIEW_CHARS || text.contains('\n') {
+        let short: String = line.chars().take(PREVIEW_CHARS).collect();
+        format!("{}…", short)
+    } else {
+        line.to_string()
+    }
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("   │ {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Get tool definitions for the request
 fn get_tool_definitions(tool_manager: &ToolManager) -> Vec<ToolDefinition> {
     tool_manager.definitions()
@@ -492,39 +1134,57 @@ const MAX_ITERATIONS: usize = 10;
 /// ```bash
 /// cc-gateway --execute "今日の天気は？"
 /// cc-gateway -e "2 + 2 を計算して"
+/// cc-gateway -e "README を要約して" --tools read,glob
 /// ```
-pub async fn run_execute(client: ClaudeClient, prompt: &str) -> anyhow::Result<()> {
-    // プロンプトが空の場合はエラー
-    let prompt = prompt.trim();
-    if prompt.is_empty() {
+pub async fn run_execute(
+    client: ClaudeClient,
+    prompt: &str,
+    options: &CliOptions,
+    tool_policy: ToolPolicy,
+    tool_limits: ToolLimits,
+) -> anyhow::Result<()> {
+    // 添付ファイル（--attach と @パス）を読み込む
+    let (prompt, paths) = attachment::extract_inline(prompt.trim());
+    let attachments = match load_attachments(&[options.attach.clone(), paths].concat()) {
+        Ok(attachments) => attachments,
+        Err(e) => {
+            eprintln!("エラー: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // プロンプトも添付ファイルもない場合はエラー
+    if prompt.is_empty() && attachments.is_empty() {
         eprintln!("エラー: プロンプトが空です");
         std::process::exit(1);
     }
 
     // ツールマネージャーを初期化
-    let mut tool_manager = ToolManager::new();
-    register_default_tools(&mut tool_manager);
+    let tools = match CliTools::new(options, tool_policy, tool_limits, &[]) {
+        Ok(tools) => tools,
+        Err(e) => {
+            eprintln!("エラー: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-    info!("Starting execute mode with {} tools", tool_manager.len());
+    info!("Starting execute mode with {} tools", tools.manager.len());
 
     // メッセージを構築
-    let mut messages: Vec<Message> = vec![Message::user(prompt)];
+    let mut messages: Vec<Message> = vec![attachment::user_message(&prompt, &attachments)];
 
     // Agent turn を実行
     match run_agent_turn(
         &client,
         &mut messages,
         SYSTEM_PROMPT,
-        &tool_manager,
+        &tools,
         MAX_ITERATIONS,
     )
     .await
     {
-        Ok(response) => {
-            // レスポンスを出力
-            println!("{}", response);
-            Ok(())
-        }
+        // レスポンスはストリーミングで出力済み
+        Ok(_) => Ok(()),
         Err(e) => {
             eprintln!("エラー: {}", e);
             std::process::exit(1);
@@ -539,7 +1199,13 @@ pub async fn run_execute(client: ClaudeClient, prompt: &str) -> anyhow::Result<(
 /// cc-gateway --file prompt.txt
 /// cc-gateway -f ./queries/hello.txt
 /// ```
-pub async fn run_file(client: ClaudeClient, path: &Path) -> anyhow::Result<()> {
+pub async fn run_file(
+    client: ClaudeClient,
+    path: &Path,
+    options: &CliOptions,
+    tool_policy: ToolPolicy,
+    tool_limits: ToolLimits,
+) -> anyhow::Result<()> {
     // ファイルの存在チェック
     if !path.exists() {
         eprintln!("エラー: ファイルが存在しません: {}", path.display());
@@ -565,5 +1231,54 @@ pub async fn run_file(client: ClaudeClient, path: &Path) -> anyhow::Result<()> {
     info!("Executing prompt from file: {}", path.display());
 
     // execute モードと同じ処理を実行
-    run_execute(client, prompt).await
+    run_execute(client, prompt, options, tool_policy, tool_limits).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_build_tool_manager() {
+        assert!(build_tool_manager(&[], None).unwrap().contains("bash"));
+        assert!(build_tool_manager(&[], Some(&[])).unwrap().is_empty());
+
+        let manager = build_tool_manager(&[], Some(&args(&["read", "grep"]))).unwrap();
+        let mut names = manager.tool_names();
+        names.sort_unstable();
+        assert_eq!(names, vec!["grep", "read"]);
+
+        assert!(build_tool_manager(&[], Some(&args(&["rm"]))).is_err());
+    }
+
+    #[test]
+    fn test_parse_invocation() {
+        let (name, input) = parse_invocation("read {\"path\": \"README.md\"}").unwrap();
+        assert_eq!(name, "read");
+        assert_eq!(input["path"], "README.md");
+
+        assert_eq!(parse_invocation("glob").unwrap(), ("glob", serde_json::json!({})));
+        assert!(parse_invocation("read [1]").is_err());
+        assert!(parse_invocation("read {oops").is_err());
+    }
+
+    #[test]
+    fn test_format_tool_call() {
+        let input = serde_json::json!({ "command": "ls -la", "timeout": 10 });
+        assert!(format_input(&input, false).contains("ls -la"));
+        assert!(!format_input(&input, false).contains("timeout"));
+        assert!(format_input(&input, true).contains("\"timeout\": 10"));
+
+        let result = ToolResult::success("line 1\nline 2\nline 3");
+        let collapsed = format_output(&result, false);
+        assert!(collapsed.contains("line 1") && !collapsed.contains("line 3"));
+        assert!(collapsed.contains("他 2 行"));
+        assert!(format_output(&result, true).contains("line 3"));
+
+        assert_eq!(preview(&"x".repeat(100)).chars().count(), PREVIEW_CHARS + 1);
+    }
 }
@@ -0,0 +1,113 @@
+//! Named CLI conversations
+//!
+//! `/save`, `/load`, `/sessions` and `--resume` keep CLI conversations in the
+//! session database, one session per name (channel `cli:<name>`).
+
+use chrono::{DateTime, Utc};
+use cc_core::llm::estimate_text_tokens;
+use cc_core::{Message, Session, SessionStore};
+
+/// Channel ID prefix of saved CLI conversations
+const CHANNEL_PREFIX: &str = "cli:";
+
+/// A saved conversation as listed by `/sessions`
+#[derive(Debug, Clone)]
+pub struct ConversationSummary {
+    pub name: String,
+    pub messages: usize,
+    /// Rough token count of the history
+    pub tokens: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Saved CLI conversations
+pub struct Conversations {
+    store: SessionStore,
+}
+
+impl Conversations {
+    /// Open the session database
+    pub fn open(db_path: &str) -> cc_core::Result<Self> {
+        Ok(Self {
+            store: SessionStore::new(db_path)?,
+        })
+    }
+
+    #[cfg(test)]
+    fn in_memory() -> Self {
+        Self {
+            store: SessionStore::in_memory().unwrap(),
+        }
+    }
+
+    /// Save the history under `name`, replacing what was saved before
+    pub fn save(&self, name: &str, messages: &[Message]) -> cc_core::Result<()> {
+        let channel_id = channel_id(name);
+        let mut session = self
+            .store
+            .get_latest_by_channel(&channel_id)?
+            .unwrap_or_else(|| Session::new(channel_id));
+        session.messages = messages.to_vec();
+        session.set_title(Some(name.to_string()));
+        self.store.save(&session)
+    }
+
+    /// History saved under `name`
+    pub fn load(&self, name: &str) -> cc_core::Result<Option<Vec<Message>>> {
+        Ok(self
+            .store
+            .get_latest_by_channel(&channel_id(name))?
+            .map(|session| session.messages))
+    }
+
+    /// Saved conversations, most recently updated first
+    pub fn list(&self) -> cc_core::Result<Vec<ConversationSummary>> {
+        Ok(self
+            .store
+            .list_by_channel_prefix(CHANNEL_PREFIX)?
+            .into_iter()
+            .map(|session| ConversationSummary {
+                name: session.channel_id[CHANNEL_PREFIX.len()..].to_string(),
+                messages: session.messages.len(),
+                tokens: session
+                    .messages
+                    .iter()
+                    .map(|m| estimate_text_tokens(&m.text_content()))
+                    .sum(),
+                updated_at: session.updated_at,
+            })
+            .collect())
+    }
+}
+
+fn channel_id(name: &str) -> String {
+    format!("{}{}", CHANNEL_PREFIX, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_and_list() {
+        let conversations = Conversations::in_memory();
+        assert!(conversations.load("work").unwrap().is_none());
+
+        conversations
+            .save("work", &[Message::user("hello"), Message::assistant("hi there")])
+            .unwrap();
+        conversations.save("notes", &[Message::user("memo")]).unwrap();
+
+        // Saving again replaces the history
+        let mut history = conversations.load("work").unwrap().unwrap();
+        history.push(Message::user("more"));
+        conversations.save("work", &history).unwrap();
+        assert_eq!(conversations.load("work").unwrap().unwrap().len(), 3);
+
+        let list = conversations.list().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "work");
+        assert_eq!(list[0].messages, 3);
+        assert!(list[0].tokens > 0);
+    }
+}
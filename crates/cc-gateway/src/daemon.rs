@@ -0,0 +1,144 @@
+//! Process control of server mode
+//!
+//! SIGTERM and Ctrl+C shut the gateway down gracefully. SIGHUP shuts it down
+//! the same way and starts it again with the configuration, MCP servers and
+//! schedules read anew, so `systemctl reload` picks up edited files. The PID
+//! file (`serve --pid-file`) is removed on exit.
+
+use std::path::{Path, PathBuf};
+
+/// Why the server stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerExit {
+    Shutdown,
+    Reload,
+}
+
+/// Signals the server reacts to
+///
+/// Created before the server starts, so a SIGHUP during startup is not lost
+/// (or handled by the default action, which ends the process).
+pub struct Signals {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    pub fn new() -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Ok(Self {
+                terminate: signal(SignalKind::terminate())?,
+                hangup: signal(SignalKind::hangup())?,
+            })
+        }
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    /// Wait for the next signal
+    pub async fn recv(&mut self) -> std::io::Result<ServerExit> {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                result = tokio::signal::ctrl_c() => result.map(|_| ServerExit::Shutdown),
+                _ = self.terminate.recv() => Ok(ServerExit::Shutdown),
+                _ = self.hangup.recv() => Ok(ServerExit::Reload),
+            }
+        }
+        #[cfg(not(unix))]
+        tokio::signal::ctrl_c().await.map(|_| ServerExit::Shutdown)
+    }
+}
+
+/// PID file of a running server, removed when dropped
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write the current process ID to `path`
+    ///
+    /// Fails when the file names another process that is still running
+    /// (checked through /proc, so elsewhere a leftover file is overwritten).
+    pub fn create(path: &Path) -> anyhow::Result<Self> {
+        if let Some(pid) = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| content.trim().parse::<u32>().ok())
+        {
+            if pid != std::process::id() && Path::new("/proc").join(pid.to_string()).exists() {
+                anyhow::bail!(
+                    "cc-gateway is already running (PID {} in {})",
+                    pid,
+                    path.display()
+                );
+            }
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, format!("{}\n", std::process::id()))
+            .map_err(|e| anyhow::anyhow!("Failed to write PID file {}: {}", path.display(), e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to remove PID file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run/cc-gateway.pid");
+
+        let pid_file = PidFile::create(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.trim(), std::process::id().to_string());
+        drop(pid_file);
+        assert!(!path.exists());
+
+        // A leftover file of a process that no longer exists is replaced
+        std::fs::write(&path, "4294967295\n").unwrap();
+        drop(PidFile::create(&path).unwrap());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pid_file_of_running_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cc-gateway.pid");
+        std::fs::write(&path, "1\n").unwrap();
+        assert!(PidFile::create(&path).is_err());
+        assert!(path.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hangup_reloads() {
+        let mut signals = Signals::new().unwrap();
+        let status = std::process::Command::new("sh")
+            .args(["-c", &format!("kill -HUP {}", std::process::id())])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let exit = tokio::time::timeout(std::time::Duration::from_secs(5), signals.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exit, ServerExit::Reload);
+    }
+}
@@ -0,0 +1,148 @@
+//! Skills, sub-agents and MCP tools of the REPL
+//!
+//! Discovered when the REPL starts so `/skill`, `/agent` and `/tool` can
+//! run them directly and the command completer can offer their names.
+
+use std::sync::Arc;
+
+use cc_core::{Config, SkillLoader, SubAgentManager, Tool, ToolManager};
+use cc_mcp::McpRegistry;
+use cc_tools::register_default_tools;
+
+/// Longest description shown next to a completion
+const DESCRIPTION_CHARS: usize = 50;
+
+/// Capabilities loaded on top of the built-in tools
+#[derive(Default)]
+pub struct CliExtensions {
+    /// Tools defined in skill files
+    pub skills: Vec<Arc<dyn Tool>>,
+    /// Tools of the connected MCP servers
+    pub mcp_tools: Vec<Arc<dyn Tool>>,
+    /// Sub-agents for `/agent` (None = not loaded)
+    pub agents: Option<Arc<SubAgentManager>>,
+    /// Keeps the MCP server connections open
+    _mcp: Option<McpRegistry>,
+}
+
+impl CliExtensions {
+    /// Load skills, MCP tools and sub-agents like server mode does
+    ///
+    /// Failures are logged and leave the capability out.
+    pub async fn load(config: &Config) -> Self {
+        let mut extensions = Self::load_tools(config).await;
+
+        // Sub-agents work with every tool, as in server mode
+        let mut all = ToolManager::new();
+        all.set_policy(config.tool_policy.clone());
+        register_default_tools(&mut all);
+        for tool in extensions.tools() {
+            all.register(Arc::clone(tool));
+        }
+        extensions.agents = Some(Arc::new(crate::load_sub_agents(config, &Arc::new(all))));
+        extensions
+    }
+
+    /// Load skills and MCP tools only
+    pub async fn load_tools(config: &Config) -> Self {
+        let mut skill_tools = ToolManager::new();
+        let skills = match SkillLoader::new().load_and_register(&mut skill_tools).await {
+            Ok(names) => tools_named(&skill_tools, &names),
+            Err(e) => {
+                tracing::warn!("Failed to load skills: {}", e);
+                Vec::new()
+            }
+        };
+
+        let mut mcp_tools = ToolManager::new();
+        let mcp = if config.mcp.enabled {
+            crate::initialize_mcp(config, &mut mcp_tools)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("MCP initialization failed: {}", e);
+                    None
+                })
+        } else {
+            None
+        };
+        let names: Vec<String> = mcp_tools.tool_names().into_iter().map(str::to_string).collect();
+        let mcp_tools = tools_named(&mcp_tools, &names);
+
+        Self {
+            skills,
+            mcp_tools,
+            agents: None,
+            _mcp: mcp,
+        }
+    }
+
+    /// Tools to register besides the built-in ones
+    pub fn tools(&self) -> impl Iterator<Item = &Arc<dyn Tool>> {
+        self.skills.iter().chain(&self.mcp_tools)
+    }
+
+    /// Whether `name` is a skill
+    pub fn is_skill(&self, name: &str) -> bool {
+        self.skills.iter().any(|skill| skill.name() == name)
+    }
+
+    /// Completions for `/skill <name>`, `/agent <name>` and `/tool <name>`
+    pub fn completions(&self, tools: &ToolManager) -> Vec<(String, String)> {
+        let mut commands: Vec<(String, String)> = self
+            .skills
+            .iter()
+            .map(|skill| (format!("/skill {}", skill.name()), short(skill.description())))
+            .collect();
+        if let Some(agents) = &self.agents {
+            for agent in agents.all_agents() {
+                commands.push((format!("/agent {}", agent.name()), short(agent.description())));
+            }
+        }
+        let mut names = tools.tool_names();
+        names.sort_unstable();
+        for name in names {
+            if let Some(tool) = tools.get(name) {
+                commands.push((format!("/tool {}", name), short(tool.description())));
+            }
+        }
+        commands
+    }
+}
+
+fn tools_named(manager: &ToolManager, names: &[String]) -> Vec<Arc<dyn Tool>> {
+    names.iter().filter_map(|name| manager.get(name)).collect()
+}
+
+/// First line of a description, cut to fit the completion menu
+fn short(description: &str) -> String {
+    let line = description.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > DESCRIPTION_CHARS {
+        format!("{}…", line.chars().take(DESCRIPTION_CHARS).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions() {
+        let mut tools = ToolManager::new();
+        register_default_tools(&mut tools);
+        let extensions = CliExtensions {
+            skills: vec![tools.get("glob").unwrap()],
+            ..Default::default()
+        };
+        assert!(extensions.is_skill("glob"));
+        assert!(!extensions.is_skill("bash"));
+
+        let commands = extensions.completions(&tools);
+        assert_eq!(commands[0].0, "/skill glob");
+        assert!(commands.iter().any(|(command, _)| command == "/tool bash"));
+        assert!(commands
+            .iter()
+            .all(|(_, description)| description.chars().count() <= DESCRIPTION_CHARS + 1));
+    }
+}
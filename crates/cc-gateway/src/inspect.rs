@@ -0,0 +1,183 @@
+//! Inspection subcommands
+//!
+//! `config`, `tools`, `sessions` and `mcp` show how the gateway is set up
+//! without starting it.
+
+use nu_ansi_term::Color;
+use serde_json::Value;
+
+use cc_core::{Config, ToolManager};
+use cc_tools::register_default_tools;
+
+use crate::args::{McpCommand, SessionsCommand};
+use crate::conversations::Conversations;
+use crate::extensions::CliExtensions;
+
+/// Shown instead of secrets
+const MASK: &str = "********";
+
+/// Parts of a key name that mark a secret value
+const SECRET_KEYS: &[&str] = &["key", "token", "secret", "password", "pass"];
+
+/// `config`: print the effective configuration as TOML
+pub fn show_config(config: &Config) -> anyhow::Result<()> {
+    let mut value = serde_json::to_value(config)?;
+    mask_secrets(&mut value);
+    print!("{}", toml::to_string_pretty(&value)?);
+    Ok(())
+}
+
+/// Mask secret strings and drop nulls (TOML has no null)
+fn mask_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            for (key, v) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                let secret = SECRET_KEYS.iter().any(|part| key.contains(part));
+                match v {
+                    Value::String(s) if secret && !s.is_empty() => *s = MASK.to_string(),
+                    _ => mask_secrets(v),
+                }
+            }
+        }
+        Value::Array(items) => {
+            items.retain(|v| !v.is_null());
+            items.iter_mut().for_each(mask_secrets);
+        }
+        _ => {}
+    }
+}
+
+/// `tools`: list the built-in, skill and MCP tools
+pub async fn list_tools(config: &Config) -> anyhow::Result<()> {
+    let mut builtin = ToolManager::new();
+    register_default_tools(&mut builtin);
+    let extensions = CliExtensions::load_tools(config).await;
+
+    let mut names = builtin.tool_names();
+    names.sort_unstable();
+    print_group(
+        "組み込みツール",
+        names.into_iter().filter_map(|name| builtin.get(name)),
+    );
+    print_group("スキル", extensions.skills.iter().cloned());
+    print_group("MCP ツール", extensions.mcp_tools.iter().cloned());
+    Ok(())
+}
+
+fn print_group(title: &str, tools: impl Iterator<Item = std::sync::Arc<dyn cc_core::Tool>>) {
+    let tools: Vec<_> = tools.collect();
+    println!("{} ({} 件):", title, tools.len());
+    for tool in &tools {
+        let description = tool.description().lines().next().unwrap_or_default();
+        println!("  {} {}", Color::Cyan.bold().paint(format!("{:<24}", tool.name())), description);
+    }
+    println!();
+}
+
+/// `sessions`: list or print saved CLI conversations
+pub fn run_sessions(config: &Config, command: Option<SessionsCommand>) -> anyhow::Result<()> {
+    let conversations = Conversations::open(&config.memory.db_path)?;
+    match command.unwrap_or(SessionsCommand::List) {
+        SessionsCommand::List => {
+            crate::cli::print_conversation_list(&conversations.list()?, None);
+        }
+        SessionsCommand::Show { name } => {
+            let messages = conversations
+                .load(&name)?
+                .ok_or_else(|| anyhow::anyhow!("会話 {} は保存されていません", name))?;
+            for message in &messages {
+                let text = message.text_content();
+                if text.is_empty() {
+                    continue;
+                }
+                let role = match message.role.as_str() {
+                    "user" => Color::Green.bold().paint("👤 あなた"),
+                    "assistant" => Color::Cyan.bold().paint("🤖 AI"),
+                    other => Color::Default.bold().paint(other),
+                };
+                println!("{}\n{}\n", role, text);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `mcp`: list the configured servers, or connect and list their tools
+pub async fn run_mcp(config: &Config, command: Option<McpCommand>) -> anyhow::Result<()> {
+    if !config.mcp.enabled {
+        println!("MCP は無効です（MCP_ENABLED=false）。");
+        return Ok(());
+    }
+    let Some(mcp_config) = crate::load_mcp_config(config)? else {
+        println!("MCP の設定がありません（mcp.json または MCP_CONFIG_PATH）。");
+        return Ok(());
+    };
+
+    match command.unwrap_or(McpCommand::List) {
+        McpCommand::List => {
+            println!("MCP サーバー ({} 件):", mcp_config.servers.len());
+            for server in &mcp_config.servers {
+                let status = if server.enabled {
+                    Color::Green.paint("有効")
+                } else {
+                    Color::DarkGray.paint("無効")
+                };
+                println!(
+                    "  {} {}  {}",
+                    Color::Cyan.bold().paint(format!("{:<20}", server.name)),
+                    status,
+                    server.command
+                );
+            }
+        }
+        McpCommand::Tools => {
+            let mut tools = ToolManager::new();
+            let _registry = cc_mcp::McpRegistry::initialize(&mcp_config, &mut tools)
+                .await
+                .map_err(|e| anyhow::anyhow!("MCP registry initialization failed: {}", e))?;
+            let mut names = tools.tool_names();
+            names.sort_unstable();
+            print_group("MCP ツール", names.into_iter().filter_map(|name| tools.get(name)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_secrets() {
+        let mut value = serde_json::json!({
+            "discord_token": "abc",
+            "llm": { "api_key": "sk-123", "model": "claude", "base_url": null },
+            "api": { "key": "", "port": 3000 },
+            "notify": [{ "password": "p", "to": "me@example.com" }]
+        });
+        mask_secrets(&mut value);
+        assert_eq!(value["discord_token"], MASK);
+        assert_eq!(value["llm"]["api_key"], MASK);
+        assert_eq!(value["llm"]["model"], "claude");
+        assert!(value["llm"].get("base_url").is_none());
+        // Empty secrets stay empty so a missing key is visible
+        assert_eq!(value["api"]["key"], "");
+        assert_eq!(value["notify"][0]["password"], MASK);
+        assert_eq!(value["notify"][0]["to"], "me@example.com");
+    }
+
+    #[test]
+    fn test_show_config_is_valid_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cc-gateway.toml");
+        std::fs::write(&path, "[llm]\napi_key = \"sk-test\"\nmodel = \"claude\"\n").unwrap();
+        let config = Config::from_toml_file(&path).unwrap();
+        let mut value = serde_json::to_value(&config).unwrap();
+        mask_secrets(&mut value);
+        let text = toml::to_string_pretty(&value).unwrap();
+        assert!(text.contains("[llm]"));
+        assert!(!text.contains("sk-test"));
+    }
+}
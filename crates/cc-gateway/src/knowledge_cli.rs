@@ -0,0 +1,65 @@
+//! Knowledge subcommand
+//!
+//! `[knowledge]` のソースを索引し、検索結果を確認します。
+//! サーバーモードでは起動時と `reindex_interval_secs` ごとに自動で索引されます。
+//!
+//! ```bash
+//! cc-gateway knowledge index
+//! cc-gateway knowledge search "返品の期限" -n 3
+//! cc-gateway knowledge list
+//! ```
+
+use cc_core::Config;
+use cc_knowledge::KnowledgeBase;
+
+use crate::args::KnowledgeCommand;
+
+/// Characters of a passage shown by `search`
+const PREVIEW_CHARS: usize = 300;
+
+/// `knowledge [list|index|search]`
+pub async fn run_knowledge(config: &Config, command: Option<KnowledgeCommand>) -> anyhow::Result<()> {
+    if !config.knowledge.enabled {
+        println!("The knowledge base is disabled (set [knowledge] enabled = true or KNOWLEDGE_ENABLED=true)");
+        return Ok(());
+    }
+    let knowledge = KnowledgeBase::open(&config.knowledge)
+        .map_err(|e| anyhow::anyhow!("Failed to open the knowledge base: {}", e))?;
+
+    match command.unwrap_or(KnowledgeCommand::List) {
+        KnowledgeCommand::List => {
+            let documents = knowledge.documents()?;
+            println!("{} documents in {}", documents.len(), config.knowledge.db_path);
+            for document in documents {
+                println!(
+                    "  {}  {} chunks  {}  {}",
+                    document.indexed_at.format("%Y-%m-%d %H:%M"),
+                    document.chunks,
+                    document.title,
+                    document.source
+                );
+            }
+        }
+        KnowledgeCommand::Index => {
+            let report = knowledge.index_sources().await?;
+            println!(
+                "{} indexed, {} unchanged, {} removed, {} failed",
+                report.indexed, report.unchanged, report.removed, report.failed
+            );
+        }
+        KnowledgeCommand::Search { query, limit } => {
+            let hits = knowledge.search(&query, limit).await?;
+            if hits.is_empty() {
+                println!("No documents indexed (run `cc-gateway knowledge index`)");
+            }
+            for hit in hits {
+                let mut preview: String = hit.content.chars().take(PREVIEW_CHARS).collect();
+                if preview.len() < hit.content.len() {
+                    preview.push('…');
+                }
+                println!("[{:.3}] {} ({})\n{}\n", hit.score, hit.title, hit.source, preview);
+            }
+        }
+    }
+    Ok(())
+}
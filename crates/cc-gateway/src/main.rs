@@ -3,51 +3,59 @@
 //! Main entry point for the Claude Code Gateway application.
 //!
 //! Usage:
-//!   cc-gateway           - Start server mode (HTTP API + Discord Bot + Scheduler)
-//!   cc-gateway --cli     - Start interactive CLI mode
+//!   cc-gateway [serve]   - Start server mode (HTTP API + channels + Scheduler)
+//!   cc-gateway cli       - Start interactive CLI mode
+//!   cc-gateway exec      - Run a single prompt and exit
+//!   cc-gateway schedule  - Manage schedules of a running gateway
+//!   cc-gateway config / tools / sessions / mcp - Inspect the setup
+//!   cc-gateway purge ID  - Delete all data of a user
+//!   cc-gateway knowledge - Index and search the knowledge base
+//!   cc-gateway email login - Authorize email OAuth2 (device code flow)
 //!   cc-gateway --help    - Show help
 
+mod args;
+mod attachment;
+mod channels;
 mod cli;
-
-use cc_core::{ClaudeClient, Config, SessionManager, ToolManager};
+mod conversations;
+mod daemon;
+mod extensions;
+mod inspect;
+mod knowledge_cli;
+mod markdown;
+mod purge_cli;
+mod schedule_cli;
+mod supervisor;
+
+use args::{Command, EmailCommand};
+use clap::Parser;
+
+use cc_core::notify::NotifierCredentials;
+use cc_core::{
+    AgentsConfig, CancellationToken, ClaudeClient, Config, DefaultSubAgent, DelegateTaskTool,
+    DelegationConfig, DelegationContext, Notifier, PromptLibrary, SessionManager, SubAgentManager,
+    TaskQueue, ToolManager,
+};
+use cc_email::send::EmailConfig;
+use cc_email::{EmailSender, ImapConfig};
 use cc_mcp::McpRegistry;
-use cc_schedule::{Scheduler, ScheduleConfig};
+use cc_schedule::{
+    OutputDispatcher, ReminderManager, ReminderSetTool, RunHistory, ScheduleConfig,
+    ScheduleCreateTool, Scheduler,
+};
+use cc_api::{HooksConfig, WebhookHandler};
+use cc_voice::{WhisperClient, WhisperConfig};
 use cc_tools::register_default_tools;
-use std::sync::Arc;
+use cc_workflow::{WorkflowEngine, WorkflowRegistry};
+use std::sync::{Arc, OnceLock};
 use tracing_subscriber::EnvFilter;
 
-/// Run mode
-enum RunMode {
-    /// Server mode (HTTP API + Discord Bot)
-    Server,
-    /// Interactive CLI mode
-    Cli,
-    /// Execute single prompt and exit (非対話モード: ワンショット実行)
-    Execute(String),
-    /// Execute from file and exit (非対話モード: ファイルから実行)
-    File(std::path::PathBuf),
-    /// Show help
-    Help,
-    /// Show version
-    Version,
-}
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Parse command line arguments
-    let mode = parse_args();
-
-    match mode {
-        RunMode::Help => {
-            print_help();
-            return Ok(());
-        }
-        RunMode::Version => {
-            println!("cc-gateway {}", env!("CARGO_PKG_VERSION"));
-            return Ok(());
-        }
-        _ => {}
-    }
+    // Parse command line arguments (--help and --version exit here)
+    let args = args::Args::parse();
+    let command = args.command();
+    let cli_options = args.cli.options();
 
     // Initialize logging
     tracing_subscriber::fmt()
@@ -61,11 +69,26 @@ async fn main() -> anyhow::Result<()> {
     // This allows .env to provide API keys that TOML config references
     dotenvy::dotenv().ok();
 
-    // Load configuration (TOML file + environment variables)
-    // 環境変数は TOML 設定を上書きします
-    let config = Config::load()
+    // Load configuration (TOML file + environment variables + command line)
+    // 環境変数は TOML 設定を、コマンドラインオプションは環境変数を上書きします
+    let config = args
+        .load_config()
         .map_err(|e| anyhow::anyhow!("Config error: {}", e))?;
 
+    // LLM クライアントが不要なコマンド
+    match &command {
+        // スケジュール管理は稼働中のサーバーに委譲する
+        Command::Schedule { args } => return schedule_cli::run_schedule(&config, args).await,
+        Command::Email(EmailCommand::Login) => return run_email_login().await,
+        Command::Config => return inspect::show_config(&config),
+        Command::Tools => return inspect::list_tools(&config).await,
+        Command::Sessions { command } => return inspect::run_sessions(&config, command.clone()),
+        Command::Mcp { command } => return inspect::run_mcp(&config, command.clone()).await,
+        Command::Purge { identity, yes } => return purge_cli::run_purge(&config, identity, *yes).await,
+        Command::Knowledge { command } => return knowledge_cli::run_knowledge(&config, command.clone()).await,
+        _ => {}
+    }
+
     tracing::info!("Starting cc-gateway...");
     tracing::info!("Model: {}", config.llm.model);
 
@@ -73,111 +96,136 @@ async fn main() -> anyhow::Result<()> {
     let claude_client = ClaudeClient::new(&config)
         .map_err(|e| anyhow::anyhow!("Failed to create LLM client: {}", e))?;
 
-    match mode {
-        RunMode::Cli => {
+    match command {
+        Command::Cli => {
             // CLI mode
             tracing::info!("Running in CLI mode");
-            cli::run_cli(claude_client).await
+            cli::run_cli(claude_client, cli_options, &config).await
         }
-        RunMode::Execute(prompt) => {
-            // 非対話モード: ワンショット実行
-            tracing::info!("Running in execute mode");
-            cli::run_execute(claude_client, &prompt).await
-        }
-        RunMode::File(path) => {
+        Command::Exec(args::ExecArgs { file: Some(path), .. }) => {
             // 非対話モード: ファイルから実行
             tracing::info!("Running in file mode: {:?}", path);
-            cli::run_file(claude_client, &path).await
+            cli::run_file(
+                claude_client,
+                &path,
+                &cli_options,
+                config.tool_policy.clone(),
+                config.tool_limits.clone(),
+            )
+            .await
         }
-        RunMode::Server => {
-            // Server mode
-            run_server(config, claude_client).await
+        Command::Exec(args::ExecArgs { prompt, .. }) => {
+            // 非対話モード: ワンショット実行
+            tracing::info!("Running in execute mode");
+            let prompt = prompt.unwrap_or_default();
+            cli::run_execute(
+                claude_client,
+                &prompt,
+                &cli_options,
+                config.tool_policy.clone(),
+                config.tool_limits.clone(),
+            )
+            .await
         }
-        _ => Ok(()),
-    }
-}
-
-/// Parse command line arguments
-fn parse_args() -> RunMode {
-    let args: Vec<String> = std::env::args().collect();
-    let mut i = 1;
-
-    while i < args.len() {
-        match args[i].as_str() {
-            "--cli" | "-c" => return RunMode::Cli,
-            "--help" | "-h" => return RunMode::Help,
-            "--version" | "-v" => return RunMode::Version,
-            "--execute" | "-e" => {
-                if i + 1 < args.len() {
-                    let prompt = args[i + 1].clone();
-                    return RunMode::Execute(prompt);
-                } else {
-                    eprintln!("エラー: --execute には引数が必要です");
-                    std::process::exit(1);
-                }
-            }
-            "--file" | "-f" => {
-                if i + 1 < args.len() {
-                    let path = std::path::PathBuf::from(&args[i + 1]);
-                    return RunMode::File(path);
-                } else {
-                    eprintln!("エラー: --file には引数が必要です");
-                    std::process::exit(1);
+        Command::Serve { pid_file } => {
+            // Server mode
+            let _pid_file = pid_file.as_deref().map(daemon::PidFile::create).transpose()?;
+            let mut signals = daemon::Signals::new()?;
+            let mut config = config;
+            let mut claude_client = claude_client;
+            while run_server(config.clone(), claude_client, &mut signals).await? == daemon::ServerExit::Reload {
+                // A broken file keeps the running configuration
+                match args.load_config() {
+                    Ok(reloaded) => config = reloaded,
+                    Err(e) => tracing::error!("Config error, keeping the previous configuration: {}", e),
                 }
+                claude_client = ClaudeClient::new(&config)
+                    .map_err(|e| anyhow::anyhow!("Failed to create LLM client: {}", e))?;
+                tracing::info!("Restarting cc-gateway (model: {})", config.llm.model);
             }
-            _ => {}
+            Ok(())
         }
-        i += 1;
+        _ => unreachable!("handled before the LLM client is created"),
     }
-
-    RunMode::Server
 }
 
-/// Print help message
-fn print_help() {
-    println!("cc-gateway - Claude Code Gateway");
-    println!();
-    println!("Usage:");
-    println!("  cc-gateway              Start server mode (HTTP API + Discord Bot + Scheduler)");
-    println!("  cc-gateway --cli        Start interactive CLI mode");
-    println!("  cc-gateway --execute PROMPT");
-    println!("                          Execute single prompt and exit (非対話モード)");
-    println!("  cc-gateway --file PATH  Execute prompt from file and exit (非対話モード)");
-    println!("  cc-gateway --help       Show this help message");
-    println!("  cc-gateway --version    Show version");
-    println!();
-    println!("Configuration:");
-    println!("  設定は以下の優先順位で読み込まれます:");
-    println!("    1. 環境変数");
-    println!("    2. cc-gateway.toml 設定ファイル");
-    println!("    3. デフォルト値");
-    println!();
-    println!("Environment Variables (環境変数は TOML 設定を上書きします):");
-    println!("  LLM_API_KEY             API key (required)");
-    println!("  LLM_MODEL               Model name (default: claude-sonnet-4-20250514)");
-    println!("  LLM_PROVIDER            Provider: claude or openai (default: claude)");
-    println!("  LLM_BASE_URL            Custom API endpoint");
-    println!("  DISCORD_BOT_TOKEN       Discord bot token (optional)");
-    println!("  API_PORT                HTTP API port (default: 3000)");
-    println!("  MCP_ENABLED             Enable MCP integration (default: true)");
-    println!("  MCP_CONFIG_PATH         Path to MCP config file");
-    println!("  SCHEDULE_ENABLED        Enable scheduler (default: true)");
-    println!("  SCHEDULE_CONFIG_PATH    Path to schedule.toml (default: schedule.toml)");
-    println!();
-    println!("Examples:");
-    println!("  cc-gateway --execute \"今日の天気は？\"");
-    println!("  cc-gateway -e \"2 + 2 を計算して\"");
-    println!("  cc-gateway --file prompt.txt");
-    println!("  cc-gateway -f ./queries/hello.txt");
+/// Authorize the email OAuth2 client with the device code flow
+///
+/// The token is cached in EMAIL_OAUTH_TOKEN_PATH and refreshed by the server.
+async fn run_email_login() -> anyhow::Result<()> {
+    let oauth = cc_email::OAuth2::from_env().ok_or_else(|| {
+        anyhow::anyhow!("EMAIL_OAUTH_PROVIDER and EMAIL_OAUTH_CLIENT_ID are required")
+    })?;
+    let authorization = oauth.start_device_flow().await?;
+    match &authorization.message {
+        Some(message) => println!("{}", message),
+        None => println!(
+            "Open {} and enter the code {}",
+            authorization.verification_uri, authorization.user_code
+        ),
+    }
+    oauth.complete_device_flow(&authorization).await?;
+    println!("Email OAuth2 authorization complete.");
+    Ok(())
 }
 
-/// Run server mode (HTTP API + Discord Bot + Scheduler)
-async fn run_server(config: Config, claude_client: ClaudeClient) -> anyhow::Result<()> {
-    let claude_client = Arc::new(claude_client);
+/// Run server mode (HTTP API + channels + Scheduler) until a signal arrives
+async fn run_server(
+    config: Config,
+    claude_client: ClaudeClient,
+    signals: &mut daemon::Signals,
+) -> anyhow::Result<daemon::ServerExit> {
+    // Outbound notifications (schedule results, quota warnings, API error alerts)
+    let notifier = load_notifier(&config);
+    let claude_client = Arc::new(claude_client.with_notifier(Arc::clone(&notifier)));
 
     // Initialize tool manager
     let mut tool_manager = ToolManager::new();
+    tool_manager.set_limits(config.tool_limits.clone());
+    tool_manager.set_policy(config.tool_policy.clone());
     register_default_tools(&mut tool_manager);
+    register_pim_tools(&mut tool_manager).await;
+
+    // Long-term memory the agent manages with memory_save/search/delete
+    let memory_store = match cc_core::memory::open_backend(&config.memory) {
+        Ok(store) => {
+            cc_core::memory::register_memory_tools(&mut tool_manager, Arc::clone(&store), &config.memory);
+            Some(store)
+        }
+        Err(e) => {
+            tracing::warn!("Memory tools disabled: {}", e);
+            None
+        }
+    };
+
+    // Documents from [knowledge] sources, searched with knowledge_search
+    let knowledge = if config.knowledge.enabled {
+        match cc_knowledge::KnowledgeBase::open(&config.knowledge) {
+            Ok(knowledge) => {
+                let knowledge = Arc::new(knowledge);
+                cc_knowledge::register_knowledge_tools(&mut tool_manager, Arc::clone(&knowledge));
+                Some(knowledge)
+            }
+            Err(e) => {
+                tracing::warn!("Knowledge base disabled: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // The scheduler needs the finished tool manager, so the handle is filled in after start
+    let scheduler_slot = Arc::new(OnceLock::new());
+    let reminder_slot = Arc::new(OnceLock::new());
+    if config.scheduler.enabled {
+        tool_manager.register(Arc::new(ScheduleCreateTool::new(Arc::clone(&scheduler_slot))));
+        tool_manager.register(Arc::new(ReminderSetTool::new(Arc::clone(&reminder_slot))));
+    }
+
+    // Sub-agents are built from the finished tool manager, so delegation is wired up later
+    let delegation_slot = Arc::new(OnceLock::new());
+    tool_manager.register(Arc::new(DelegateTaskTool::new(Arc::clone(&delegation_slot))));
 
     let builtin_tool_count = tool_manager.len();
     tracing::info!(
@@ -209,50 +257,193 @@ async fn run_server(config: Config, claude_client: ClaudeClient) -> anyhow::Resu
     let tool_manager = Arc::new(tool_manager);
 
     // Create session manager
-    let session_manager = SessionManager::new(&config.memory.db_path)
+    let session_manager = SessionManager::from_config(&config.memory)
         .map_err(|e| anyhow::anyhow!("Failed to create session manager: {}", e))?;
 
     // Track running services for graceful shutdown
     let mut service_handles = Vec::new();
+    // Dependencies reported by the HTTP API's /readyz
+    let mut health_checks: Vec<Arc<dyn cc_api::HealthCheck>> = Vec::new();
+    let mcp_registry = mcp_registry.map(Arc::new);
+    if let Some(registry) = &mcp_registry {
+        health_checks.push(Arc::new(McpCheck(Arc::clone(registry))));
+    }
     let mut scheduler_handle = None;
 
+    // Load system prompt templates (hot reloaded)
+    let prompts = load_prompt_library();
+    if let Some(prompts) = &prompts {
+        service_handles.push(prompts.spawn_hot_reload(std::time::Duration::from_secs(5)));
+    }
+
+    // Expired memories are hidden right away and deleted hourly
+    if let Some(store) = &memory_store {
+        service_handles.push(cc_core::memory::spawn_expiry_sweep(
+            Arc::clone(store),
+            std::time::Duration::from_secs(3600),
+        ));
+    }
+
+    // Sources are indexed in the background, then every reindex_interval_secs
+    if let Some(knowledge) = &knowledge {
+        service_handles.push(Arc::clone(knowledge).spawn_indexer());
+    }
+    // Relevant passages are added to each turn unless top_k is 0
+    let context: Option<Arc<dyn cc_core::ContextProvider>> = knowledge
+        .filter(|_| config.knowledge.top_k > 0)
+        .map(|knowledge| knowledge as Arc<dyn cc_core::ContextProvider>);
+
+    // Sub-agents (workflow agent steps and the delegate_task tool)
+    // Cancelled on shutdown so in-flight delegated tasks stop
+    let shutdown = CancellationToken::new();
+    // Channels, the HTTP API and the scheduler are restarted when they stop
+    let supervisor = supervisor::Supervisor::new(
+        supervisor::Backoff::from_config(&config.channels),
+        shutdown.clone(),
+    );
+    let sub_agents = Arc::new(load_sub_agents(&config, &tool_manager));
+    delegation_slot
+        .set(DelegationContext {
+            agents: Arc::clone(&sub_agents),
+            tools: Arc::clone(&tool_manager),
+            cancel: shutdown.clone(),
+            events: None,
+        })
+        .ok();
+
+    // Delegated tasks accepted via the API run in the background and survive restarts
+    let queue_path = std::env::var("TASK_QUEUE_DB_PATH")
+        .unwrap_or_else(|_| TaskQueue::DEFAULT_PATH.to_string());
+    let task_queue = match TaskQueue::open(&queue_path) {
+        Ok(mut queue) => {
+            // Completion callbacks are only sent when they can be signed
+            if let Ok(secret) = std::env::var("TASK_CALLBACK_SECRET") {
+                if !secret.is_empty() {
+                    queue = queue.with_callback_secret(secret);
+                }
+            }
+            if let Ok(hosts) = std::env::var("TASK_CALLBACK_ALLOWED_HOSTS") {
+                queue = queue.with_callback_hosts(
+                    hosts.split(',').map(str::trim).filter(|h| !h.is_empty()),
+                );
+            }
+            service_handles.push(queue.start(
+                Arc::clone(&sub_agents),
+                DelegationConfig::default().max_concurrency,
+                shutdown.clone(),
+            ));
+            Some(queue)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to open task queue ({}): {}", queue_path, e);
+            None
+        }
+    };
+
+    // Load YAML workflows (run by the scheduler, the API or webhooks)
+    let workflows = load_workflow_engine(&claude_client, &tool_manager, Arc::clone(&sub_agents));
+
+    // Deliver results using the same bot credentials as the gateways
+    let mut output = OutputDispatcher::from_env().with_notifier(Arc::clone(&notifier));
+    if let Some(token) = &config.discord_token {
+        output = output.with_discord_token(token.clone());
+    }
+
+    // Inbound webhooks trigger workflows / agent tasks
+    let hooks = load_webhook_handler(&workflows, &output);
+
     // Start Scheduler if enabled
     let schedule_enabled = config.scheduler.enabled;
 
     if schedule_enabled {
-        let schedule_config = load_schedule_config();
+        let schedule_path = schedule_config_path(&config);
+        let loaded = load_schedule_config(&schedule_path);
+        let persist = loaded.is_some();
+        let schedule_config = loaded.unwrap_or_default();
         let enabled_count = schedule_config.enabled_tasks().len();
+        let history_settings = schedule_config.history.clone();
+        let reminder_settings = schedule_config.reminders.clone();
+
+        // One-shot reminders share the delivery credentials
+        if reminder_settings.enabled {
+            match ReminderManager::open(&reminder_settings.db_path, output.clone()) {
+                Ok(reminders) => {
+                    service_handles.push(reminders.start());
+                    reminder_slot.set(reminders).ok();
+                }
+                Err(e) => tracing::warn!(
+                    "リマインダーを開けません ({}): {}",
+                    reminder_settings.db_path,
+                    e
+                ),
+            }
+        }
 
-        if enabled_count > 0 {
-            let scheduler = Scheduler::new(
-                schedule_config,
-                (*claude_client).clone(),
-                Arc::clone(&tool_manager),
-            );
-            let handle = scheduler.start();
-            scheduler_handle = Some(handle);
-            tracing::info!("スケジューラーを開始しました ({} タスク)", enabled_count);
-        } else {
-            tracing::info!("スケジュールタスクがありません");
+        let mut scheduler = Scheduler::new(
+            schedule_config,
+            (*claude_client).clone(),
+            Arc::clone(&tool_manager),
+        )
+        .with_output_dispatcher(output)
+        .with_workflow_engine(Arc::clone(&workflows));
+        if let Some(prompts) = &prompts {
+            scheduler = scheduler.with_prompt_library(Arc::clone(prompts));
+        }
+        if history_settings.enabled {
+            match RunHistory::open(&history_settings.db_path) {
+                Ok(history) => scheduler = scheduler.with_run_history(history),
+                Err(e) => tracing::warn!("実行履歴を開けません ({}): {}", history_settings.db_path, e),
+            }
         }
+        // Runtime changes (API / CLI) are written back unless the file failed to parse
+        if persist {
+            scheduler = scheduler.with_config_path(&schedule_path);
+        }
+        let handle = scheduler.start();
+        scheduler_slot.set(handle.clone()).ok();
+        service_handles.push(supervisor.spawn("scheduler", watch_scheduler(handle.clone())));
+        scheduler_handle = Some(handle);
+        tracing::info!("スケジューラーを開始しました ({} タスク有効)", enabled_count);
     } else {
         tracing::info!("スケジューラーは無効です");
     }
 
-    // Start Discord bot if token is configured
-    if let Some(_token) = &config.discord_token {
-        let discord_config = config.clone();
-        let discord_client = Arc::clone(&claude_client);
+    // Voice messages and uploaded audio are transcribed with OpenAI Whisper
+    let transcriber = std::env::var("OPENAI_API_KEY")
+        .ok()
+        .and_then(|key| WhisperClient::new(WhisperConfig::openai(key)).ok())
+        .map(Arc::new);
+
+    // Facts about users remembered across conversations ([memory.memorizer])
+    let memorizer = match &memory_store {
+        Some(store) if config.memory.memorizer.enabled => {
+            tracing::info!("Memorizer enabled");
+            Some(Arc::new(cc_core::Memorizer::new(
+                Arc::clone(&claude_client),
+                Arc::clone(store),
+                &config.memory,
+            )))
+        }
+        _ => None,
+    };
 
-        let handle = tokio::spawn(async move {
-            if let Err(e) = start_discord_bot(discord_config, discord_client).await {
-                tracing::error!("Discord bot error: {}", e);
-            }
-        });
-        service_handles.push(handle);
-        tracing::info!("Discord bot started");
-    } else {
-        tracing::info!("Discord bot disabled (no token configured)");
+    // Start the channels enabled in [channels]
+    let channel_context = channels::ChannelContext {
+        config: config.clone(),
+        claude_client: Arc::clone(&claude_client),
+        tools: Arc::clone(&tool_manager),
+        transcriber: transcriber.clone(),
+        memorizer: memorizer.clone(),
+        context: context.clone(),
+        shutdown: shutdown.clone(),
+        supervisor: Arc::clone(&supervisor),
+    };
+    for channel in channels::start_channels(&channel_context) {
+        health_checks.push(Arc::new(cc_api::TaskCheck::new(
+            format!("channel:{}", channel.name),
+            channel.handle.abort_handle(),
+        )));
+        service_handles.push(channel.handle);
     }
 
     // Start HTTP API server
@@ -260,27 +451,97 @@ async fn run_server(config: Config, claude_client: ClaudeClient) -> anyhow::Resu
     let api_config = config.clone();
     let api_client = Arc::clone(&claude_client);
     let api_tool_manager = Arc::clone(&tool_manager);
-
-    let handle = tokio::spawn(async move {
-        if let Err(e) = cc_api::start_server(
-            api_port,
-            api_config,
-            (*api_client).clone(),
-            session_manager,
-            api_tool_manager,
-        ).await {
-            tracing::error!("HTTP API error: {}", e);
+    let files_dir = std::env::var("API_FILES_DIR")
+        .unwrap_or_else(|_| cc_api::FileStore::DEFAULT_DIR.to_string());
+    let files = match cc_api::FileStore::open(&files_dir) {
+        Ok(store) => Some(Arc::new(store)),
+        Err(e) => {
+            tracing::warn!("File uploads disabled ({}): {}", files_dir, e);
+            None
+        }
+    };
+    // Issued API keys (managed through /api/keys) alongside API_KEY
+    let keys_path = std::env::var("API_KEYS_DB_PATH")
+        .unwrap_or_else(|_| cc_api::ApiKeyStore::DEFAULT_PATH.to_string());
+    let api_keys = match cc_api::ApiKeyStore::open(&keys_path) {
+        Ok(store) => Some(Arc::new(store)),
+        Err(e) => {
+            tracing::warn!("API key store disabled ({}): {}", keys_path, e);
+            None
+        }
+    };
+    // Key usage and authentication failures go to the audit log when one is configured
+    let api_audit = std::env::var("AUDIT_LOG_FILE").ok().and_then(|path| {
+        let audit_config = cc_core::audit::AuditConfig {
+            log_file: Some(path),
+            ..Default::default()
+        };
+        match cc_core::audit::AuditLogger::new(audit_config) {
+            Ok(logger) => Some(Arc::new(logger)),
+            Err(e) => {
+                tracing::warn!("API audit log disabled: {}", e);
+                None
+            }
         }
     });
-    service_handles.push(handle);
+    let api_services = cc_api::ApiServices {
+        prompts: prompts.clone(),
+        scheduler: scheduler_handle.clone(),
+        workflows: Some(workflows),
+        hooks,
+        sub_agents: Some(Arc::clone(&sub_agents)),
+        task_queue,
+        files,
+        transcriber,
+        api_keys,
+        // JWTs from an OIDC provider (API_OIDC_ISSUER) as an alternative to API keys
+        oidc: cc_api::OidcConfig::from_env().map(|config| Arc::new(cc_api::OidcVerifier::new(config))),
+        audit: api_audit,
+        memorizer,
+        memory: memory_store,
+        context,
+        health_checks,
+    };
+
+    // The first run uses the session manager opened above; restarts open a new one
+    let first_sessions = std::sync::Mutex::new(Some(session_manager));
+    let api_memory_config = config.memory.clone();
+    let api_shutdown = shutdown.clone();
+    let api_handle = supervisor.spawn("api", Box::new(move || {
+        let sessions = first_sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .map(Ok)
+            .unwrap_or_else(|| SessionManager::from_config(&api_memory_config));
+        let config = api_config.clone();
+        let client = (*api_client).clone();
+        let tools = Arc::clone(&api_tool_manager);
+        let services = api_services.clone();
+        let shutdown = api_shutdown.clone();
+        Box::pin(async move {
+            cc_api::start_server(api_port, config, client, sessions?, tools, services, shutdown)
+                .await
+                .map_err(|e| anyhow::anyhow!("HTTP API error: {}", e))
+        })
+    }));
     tracing::info!("HTTP API server started on port {}", api_port);
 
     tracing::info!("cc-gateway initialized successfully");
-    tracing::info!("Press Ctrl+C to exit");
+    tracing::info!("Press Ctrl+C to exit (SIGHUP reloads the configuration)");
 
-    // Wait for shutdown signal
-    tokio::signal::ctrl_c().await?;
-    tracing::info!("Shutting down...");
+    // Wait for shutdown (SIGTERM / Ctrl+C) or reload (SIGHUP)
+    let exit = signals.recv().await?;
+    match exit {
+        daemon::ServerExit::Shutdown => tracing::info!("Shutting down..."),
+        daemon::ServerExit::Reload => tracing::info!("Reloading: stopping services..."),
+    }
+    shutdown.cancel();
+
+    // Let the HTTP API finish in-flight requests (bounded by api.shutdown_timeout_secs)
+    if let Err(e) = api_handle.await {
+        tracing::warn!("HTTP API task failed: {}", e);
+    }
 
     // Stop scheduler
     if let Some(handle) = scheduler_handle {
@@ -292,46 +553,253 @@ async fn run_server(config: Config, claude_client: ClaudeClient) -> anyhow::Resu
         handle.abort();
     }
 
-    // Gracefully shutdown MCP clients
-    if let Some(registry) = mcp_registry {
+    // Gracefully shutdown MCP clients (the HTTP API has released its reference)
+    if let Some(registry) = mcp_registry.and_then(Arc::into_inner) {
         if let Err(e) = registry.shutdown().await {
             tracing::warn!("Error during MCP shutdown: {}", e);
         }
     }
 
     tracing::info!("Shutdown complete");
-    Ok(())
+    Ok(exit)
+}
+
+/// Watch the scheduler's task loops
+///
+/// A loop that stopped (panicked) fails the service; the supervisor restarts
+/// it, which starts the stopped loops again.
+fn watch_scheduler(handle: cc_schedule::SchedulerHandle) -> supervisor::ServiceFactory {
+    Box::new(move || {
+        let handle = handle.clone();
+        Box::pin(async move {
+            handle.restart_stopped_tasks();
+            loop {
+                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                let stopped = handle.stopped_tasks();
+                if !stopped.is_empty() {
+                    anyhow::bail!("scheduled tasks stopped: {}", stopped.join(", "));
+                }
+            }
+        })
+    })
+}
+
+/// Resolve the schedule file path
+///
+/// SCHEDULE_CONFIG_PATH / [scheduler] config_path > existing default location > schedule.toml
+fn schedule_config_path(config: &Config) -> std::path::PathBuf {
+    if let Some(path) = &config.scheduler.config_path {
+        return path.into();
+    }
+
+    ScheduleConfig::DEFAULT_PATHS
+        .iter()
+        .find(|path| std::path::Path::new(path).exists())
+        .unwrap_or(&ScheduleConfig::DEFAULT_PATHS[0])
+        .into()
 }
 
 /// Load schedule configuration
-fn load_schedule_config() -> ScheduleConfig {
-    // Check for custom config path
-    if let Ok(path) = std::env::var("SCHEDULE_CONFIG_PATH") {
-        tracing::info!("Loading schedule config from: {}", path);
-        match ScheduleConfig::from_file(&path) {
-            Ok(config) => return config,
-            Err(e) => tracing::warn!("Failed to load schedule config from {}: {}", path, e),
+///
+/// Returns None if the file exists but could not be parsed.
+fn load_schedule_config(path: &std::path::Path) -> Option<ScheduleConfig> {
+    if !path.exists() {
+        return Some(ScheduleConfig::default());
+    }
+
+    tracing::info!("Loading schedule config from: {}", path.display());
+    match ScheduleConfig::from_file(path) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            tracing::warn!("Failed to load schedule config from {}: {}", path.display(), e);
+            None
         }
     }
+}
+
+/// Create the notifier from `[notify]`, using the gateways' bot tokens and SMTP settings
+fn load_notifier(config: &Config) -> Arc<Notifier> {
+    let mut credentials = NotifierCredentials::from_env();
+    if let Some(token) = &config.discord_token {
+        credentials = credentials.with_discord_token(token.clone());
+    }
+    if let Some(sender) = EmailConfig::from_env().and_then(|c| EmailSender::new(c).ok()) {
+        credentials = credentials.with_email(Arc::new(sender));
+    }
+
+    let notifier = Notifier::from_config(&config.notify, credentials);
+    if !notifier.targets().is_empty() {
+        tracing::info!("Notification targets: {:?}", notifier.targets());
+    }
+    Arc::new(notifier)
+}
+
+/// Load prompt templates from PROMPTS_DIR (default: prompts/)
+fn load_prompt_library() -> Option<Arc<PromptLibrary>> {
+    let dir = std::env::var("PROMPTS_DIR").unwrap_or_else(|_| "prompts".to_string());
+    if !std::path::Path::new(&dir).exists() {
+        tracing::info!("プロンプトテンプレートディレクトリがありません: {}", dir);
+        return None;
+    }
+
+    match PromptLibrary::load(&dir) {
+        Ok(library) => Some(Arc::new(library)),
+        Err(e) => {
+            tracing::warn!("Failed to load prompt templates from {}: {}", dir, e);
+            None
+        }
+    }
+}
+
+/// Load workflows from WORKFLOWS_DIR (default: workflows)
+///
+/// `agent` steps run on the sub-agent they name, or the best match among the
+/// general-purpose agent and the declared sub-agents.
+/// The engine is created even without workflow files so webhooks can run agent tasks.
+fn load_workflow_engine(
+    claude_client: &ClaudeClient,
+    tool_manager: &Arc<ToolManager>,
+    sub_agents: Arc<SubAgentManager>,
+) -> Arc<WorkflowEngine> {
+    let dir = std::env::var("WORKFLOWS_DIR").unwrap_or_else(|_| "workflows".to_string());
+    let registry = WorkflowRegistry::load_dir(&dir).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load workflows from {}: {}", dir, e);
+        WorkflowRegistry::new()
+    });
+
+    let engine = WorkflowEngine::new(claude_client.clone(), Arc::clone(tool_manager))
+        .with_registry(registry)
+        .with_agents(sub_agents);
+    Arc::new(engine)
+}
+
+/// Create the sub-agents: a general-purpose agent plus those declared in
+/// AGENTS_CONFIG_PATH (default: agents.toml, agents.yaml or agents.yml)
+fn load_sub_agents(config: &Config, tool_manager: &Arc<ToolManager>) -> SubAgentManager {
+    let mut agents = SubAgentManager::new();
+    match DefaultSubAgent::new(
+        "general",
+        "General-purpose agent for workflow steps",
+        config,
+        Arc::clone(tool_manager),
+    ) {
+        Ok(agent) => agents.register(Arc::new(agent)),
+        Err(e) => tracing::warn!("Failed to create workflow sub-agent: {}", e),
+    }
+
+    let path = match std::env::var("AGENTS_CONFIG_PATH") {
+        Ok(path) => Some(path),
+        Err(_) => AgentsConfig::DEFAULT_PATHS
+            .iter()
+            .find(|p| std::path::Path::new(p).exists())
+            .map(|p| p.to_string()),
+    };
+    let Some(path) = path else {
+        return agents;
+    };
 
-    // Try default paths
-    ScheduleConfig::load_default().unwrap_or_default()
+    match AgentsConfig::from_file(&path)
+        .and_then(|declared| declared.register_all(&mut agents, config, tool_manager))
+    {
+        Ok(count) => tracing::info!("Loaded {} sub-agent(s) from {}", count, path),
+        Err(e) => tracing::warn!("Failed to load sub-agents from {}: {}", path, e),
+    }
+    agents
 }
 
-/// Start Discord bot
-async fn start_discord_bot(config: Config, claude_client: Arc<ClaudeClient>) -> anyhow::Result<()> {
-    use cc_discord::DiscordBot;
+/// Load inbound webhooks from HOOKS_CONFIG_PATH (default: hooks.toml)
+fn load_webhook_handler(
+    workflows: &Arc<WorkflowEngine>,
+    output: &OutputDispatcher,
+) -> Option<Arc<WebhookHandler>> {
+    let path = std::env::var("HOOKS_CONFIG_PATH")
+        .unwrap_or_else(|_| HooksConfig::DEFAULT_PATH.to_string());
+    if !std::path::Path::new(&path).exists() {
+        return None;
+    }
 
-    let bot = DiscordBot::with_client(config, claude_client);
-    bot.start().await
-        .map_err(|e| anyhow::anyhow!("Discord bot error: {}", e))
+    match HooksConfig::from_file(&path) {
+        Ok(hooks) => {
+            let handler = WebhookHandler::new(hooks, Arc::clone(workflows))
+                .with_output_dispatcher(output.clone());
+            tracing::info!("Loaded {} webhook(s) from {}", handler.len(), path);
+            Some(Arc::new(handler))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load webhooks from {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Register calendar and contacts tools for the backends configured in the environment
+async fn register_pim_tools(tool_manager: &mut ToolManager) {
+    match cc_calendar::provider_from_env().await {
+        Ok(Some(provider)) => {
+            tracing::info!("Calendar tools enabled ({})", provider.name());
+            cc_calendar::register_calendar_tools(tool_manager, Arc::clone(&provider));
+
+            // Invitations go out through the SMTP account; replies are read over IMAP
+            if let Some(sender) = EmailConfig::from_env().and_then(|c| EmailSender::new(c).ok()) {
+                tracing::info!("Meeting invitations enabled (organizer: {})", sender.from_address());
+                let mailer = Arc::new(cc_calendar::InviteMailer::new(provider, sender));
+                cc_calendar::register_invite_tools(tool_manager, mailer, ImapConfig::from_env());
+            }
+        }
+        Ok(None) => tracing::debug!("No calendar configured"),
+        Err(e) => tracing::warn!("Calendar tools disabled: {}", e),
+    }
+
+    if let Some(config) = cc_contacts::ContactsConfig::from_env() {
+        match cc_contacts::ContactsClient::new(config).await {
+            Ok(client) => {
+                tracing::info!("Contacts tools enabled");
+                cc_contacts::register_contacts_tools(tool_manager, Arc::new(client));
+            }
+            Err(e) => tracing::warn!("Contacts tools disabled: {}", e),
+        }
+    }
 }
 
 /// Initialize MCP tools from configuration
+/// `/readyz` check of the connected MCP servers
+struct McpCheck(Arc<McpRegistry>);
+
+#[async_trait::async_trait]
+impl cc_api::HealthCheck for McpCheck {
+    fn name(&self) -> &str {
+        "mcp"
+    }
+
+    async fn check(&self) -> Result<Option<String>, String> {
+        let servers = self.0.check_servers().await;
+        let failed: Vec<String> = servers
+            .iter()
+            .filter_map(|(name, error)| error.as_ref().map(|e| format!("{}: {}", name, e)))
+            .collect();
+        if failed.is_empty() {
+            Ok(Some(format!("{} servers", servers.len())))
+        } else {
+            Err(failed.join("; "))
+        }
+    }
+}
+
 async fn initialize_mcp(
     config: &Config,
     tool_manager: &mut ToolManager,
 ) -> anyhow::Result<Option<McpRegistry>> {
+    let Some(mcp_config) = load_mcp_config(config)? else {
+        return Ok(None);
+    };
+
+    McpRegistry::initialize(&mcp_config, tool_manager)
+        .await
+        .map_err(|e| anyhow::anyhow!("MCP registry initialization failed: {}", e))
+}
+
+/// Load the MCP configuration ([mcp] config_path, or mcp.json when it exists)
+fn load_mcp_config(config: &Config) -> anyhow::Result<Option<cc_mcp::McpConfig>> {
     use cc_mcp::McpConfig;
 
     let mcp_config = match &config.mcp.config_path {
@@ -353,8 +821,5 @@ async fn initialize_mcp(
             }
         }
     };
-
-    McpRegistry::initialize(&mcp_config, tool_manager)
-        .await
-        .map_err(|e| anyhow::anyhow!("MCP registry initialization failed: {}", e))
+    Ok(Some(mcp_config))
 }
@@ -0,0 +1,263 @@
+//! Streaming markdown rendering for the CLI
+//!
+//! Text is rendered line by line as it streams in: headings, lists, quotes,
+//! bold/italic and inline code are styled with ANSI escapes, and fenced code
+//! blocks are syntax-highlighted with syntect. When stdout is not a terminal
+//! the text is passed through unchanged.
+
+use nu_ansi_term::{Color, Style};
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::as_24_bit_terminal_escaped;
+
+/// Theme of highlighted code blocks
+const THEME: &str = "base16-ocean.dark";
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme() -> &'static Theme {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    &THEMES.get_or_init(ThemeSet::load_defaults).themes[THEME]
+}
+
+/// Renders markdown as it arrives in chunks
+pub struct MarkdownRenderer {
+    /// Pass text through without formatting
+    plain: bool,
+    /// Text of the line not yet complete
+    pending: String,
+    /// Highlighter of the open code block
+    code: Option<HighlightLines<'static>>,
+    /// Output so far ends mid-line (plain mode)
+    mid_line: bool,
+}
+
+impl MarkdownRenderer {
+    /// Format only when stdout is a terminal
+    pub fn for_stdout() -> Self {
+        use std::io::IsTerminal;
+        Self::new(!std::io::stdout().is_terminal())
+    }
+
+    pub fn new(plain: bool) -> Self {
+        Self {
+            plain,
+            pending: String::new(),
+            code: None,
+            mid_line: false,
+        }
+    }
+
+    /// Add a chunk, returning the output for the lines it completes
+    pub fn push(&mut self, chunk: &str) -> String {
+        if self.plain {
+            if !chunk.is_empty() {
+                self.mid_line = !chunk.ends_with('\n');
+            }
+            return chunk.to_string();
+        }
+        self.pending.push_str(chunk);
+        let mut output = String::new();
+        while let Some(end) = self.pending.find('\n') {
+            let line: String = self.pending.drain(..=end).collect();
+            output.push_str(&self.render_line(line.trim_end_matches('\n')));
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Render whatever is left, ending the last line, and reset for the next response
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        let mut output = if rest.is_empty() {
+            rest
+        } else {
+            self.render_line(&rest)
+        };
+        if !output.is_empty() || std::mem::take(&mut self.mid_line) {
+            output.push('\n');
+        }
+        self.code = None;
+        output
+    }
+
+    fn render_line(&mut self, line: &str) -> String {
+        let trimmed = line.trim_start();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            let fence = Style::new().dimmed().paint(line).to_string();
+            if self.code.take().is_none() {
+                let syntax = syntaxes()
+                    .find_syntax_by_token(info.split_whitespace().next().unwrap_or_default())
+                    .unwrap_or_else(|| syntaxes().find_syntax_plain_text());
+                self.code = Some(HighlightLines::new(syntax, theme()));
+            }
+            return fence;
+        }
+        if let Some(highlighter) = &mut self.code {
+            let source = format!("{}\n", line);
+            return match highlighter.highlight_line(&source, syntaxes()) {
+                Ok(ranges) => {
+                    let escaped = as_24_bit_terminal_escaped(&ranges, false);
+                    format!("{}\x1b[0m", escaped.trim_end_matches('\n'))
+                }
+                Err(_) => line.to_string(),
+            };
+        }
+        render_block(line)
+    }
+}
+
+/// A line outside code blocks
+fn render_block(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        let style = Color::Cyan.bold();
+        let style = if hashes == 1 { style.underline() } else { style };
+        return style.paint(trimmed[hashes + 1..].trim()).to_string();
+    }
+    if trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-' || c == '*' || c == '_') {
+        return Style::new().dimmed().paint("─".repeat(40)).to_string();
+    }
+    if let Some(quote) = trimmed.strip_prefix('>') {
+        let bar = Style::new().dimmed().paint("│");
+        return format!("{}{} {}", indent, bar, render_inline(quote.trim_start()));
+    }
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = trimmed.strip_prefix(bullet) {
+            return format!("{}• {}", indent, render_inline(item));
+        }
+    }
+    format!("{}{}", indent, render_inline(trimmed))
+}
+
+/// Bold, italic and inline code
+fn render_inline(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let styled = [
+            ("`", Color::Yellow.normal()),
+            ("**", Style::new().bold()),
+            ("__", Style::new().bold()),
+            ("*", Style::new().italic()),
+            ("_", Style::new().italic()),
+        ]
+        .into_iter()
+        .find_map(|(marker, style)| {
+            let inner = rest.strip_prefix(marker)?;
+            // `snake_case` and `2 * 3` are not emphasis
+            if marker == "_" && output.chars().last().is_some_and(char::is_alphanumeric) {
+                return None;
+            }
+            if inner.starts_with(' ') {
+                return None;
+            }
+            let end = inner.find(marker).filter(|&end| end > 0)?;
+            let content = &inner[..end];
+            let rendered = if marker == "`" {
+                content.to_string()
+            } else {
+                render_inline(content)
+            };
+            Some((style.paint(rendered).to_string(), &inner[end + marker.len()..]))
+        });
+
+        match styled {
+            Some((rendered, remaining)) => {
+                output.push_str(&rendered);
+                rest = remaining;
+            }
+            None => {
+                let mut chars = rest.chars();
+                if let Some(c) = chars.next() {
+                    output.push(c);
+                }
+                rest = chars.as_str();
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(chunks: &[&str]) -> String {
+        let mut renderer = MarkdownRenderer::new(false);
+        let mut output: String = chunks.iter().map(|chunk| renderer.push(chunk)).collect();
+        output.push_str(&renderer.finish());
+        output
+    }
+
+    #[test]
+    fn test_streams_complete_lines() {
+        let mut renderer = MarkdownRenderer::new(false);
+        assert_eq!(renderer.push("Hello, wo"), "");
+        assert_eq!(renderer.push("rld\nNext"), "Hello, world\n");
+        assert_eq!(renderer.finish(), "Next\n");
+        assert_eq!(renderer.finish(), "");
+
+        let mut renderer = MarkdownRenderer::new(true);
+        assert_eq!(renderer.push("**raw**"), "**raw**");
+        assert_eq!(renderer.finish(), "\n");
+        assert_eq!(renderer.push("done\n"), "done\n");
+        assert_eq!(renderer.finish(), "");
+    }
+
+    #[test]
+    fn test_chunk_boundaries_do_not_matter() {
+        let text = "# Title\nSome **bold** and `code`\n- item\n";
+        let whole = render(&[text]);
+        let pieces: Vec<String> = text.chars().map(String::from).collect();
+        let split = render(&pieces.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(whole, split);
+    }
+
+    #[test]
+    fn test_inline_formatting() {
+        assert_eq!(
+            render_inline("a **b** c"),
+            format!("a {} c", Style::new().bold().paint("b"))
+        );
+        assert_eq!(
+            render_inline("an *it* word"),
+            format!("an {} word", Style::new().italic().paint("it"))
+        );
+        assert_eq!(
+            render_inline("`x*y*`"),
+            Color::Yellow.normal().paint("x*y*").to_string()
+        );
+        assert_eq!(render_inline("snake_case_name"), "snake_case_name");
+        assert_eq!(render_inline("2 * 3 * 4"), "2 * 3 * 4");
+        assert_eq!(render_inline("**unclosed"), "**unclosed");
+    }
+
+    #[test]
+    fn test_code_block_is_highlighted() {
+        let output = render(&["```rust\nfn main() {}\n```\nafter **it**\n"]);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].contains("\x1b[38;2;"));
+        assert!(lines[1].contains("main"));
+        // The block is closed, so markdown applies again
+        assert!(lines[3].contains(&Style::new().bold().paint("it").to_string()));
+    }
+
+    #[test]
+    fn test_block_elements() {
+        assert!(render_block("- item").starts_with("• "));
+        assert!(render_block("  * nested").starts_with("  • "));
+        assert!(render_block("> quoted").contains("quoted"));
+        assert_eq!(render_block("## Heading"), Color::Cyan.bold().paint("Heading").to_string());
+        assert_eq!(render_block("#hashtag"), "#hashtag");
+    }
+}
@@ -0,0 +1,69 @@
+//! Purge subcommand
+//!
+//! ある利用者（identity）のセッション・メモリ・監査ログを削除します。
+//! ストアを直接操作するため、稼働中のゲートウェイに対しては
+//! `DELETE /api/identities/{identity}` を使ってください。
+//!
+//! ```bash
+//! cc-gateway purge discord:123456789
+//! cc-gateway purge alice --yes
+//! ```
+
+use std::io::{IsTerminal, Write};
+
+use cc_core::audit::{AuditConfig, AuditLogger};
+use cc_core::{Config, SessionManager};
+
+/// Recorded as the requester of the purge in the audit log
+const REQUESTED_BY: &str = "cli";
+
+/// `purge IDENTITY`: delete all data of an identity
+pub async fn run_purge(config: &Config, identity: &str, yes: bool) -> anyhow::Result<()> {
+    if !yes && !confirm(identity)? {
+        println!("Cancelled");
+        return Ok(());
+    }
+
+    let sessions = SessionManager::from_config(&config.memory)
+        .map_err(|e| anyhow::anyhow!("Failed to open the session store: {}", e))?;
+    let memories = cc_core::memory::open_backend(&config.memory)
+        .map_err(|e| anyhow::anyhow!("Failed to open the memory store: {}", e))?;
+    let audit = match std::env::var("AUDIT_LOG_FILE") {
+        Ok(path) => Some(AuditLogger::new(AuditConfig {
+            log_file: Some(path),
+            log_to_console: false,
+            ..Default::default()
+        })?),
+        Err(_) => None,
+    };
+
+    let report = cc_core::purge_identity(
+        identity,
+        &config.memory,
+        &sessions,
+        Some(memories.as_ref()),
+        audit.as_ref(),
+        REQUESTED_BY,
+    )
+    .await?;
+    println!(
+        "Purged {}: {} sessions, {} memories, {} audit log entries",
+        report.identity, report.sessions, report.memories, report.audit_entries
+    );
+    if audit.is_none() {
+        println!("AUDIT_LOG_FILE is not set, so the audit log was left untouched");
+    }
+    Ok(())
+}
+
+/// Ask before deleting (fails without a terminal to answer)
+fn confirm(identity: &str) -> anyhow::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Purging needs confirmation (run in a terminal or pass --yes)");
+    }
+    print!("Delete all sessions, memories and audit log entries of {}? [y/N] ", identity);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
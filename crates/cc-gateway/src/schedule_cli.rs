@@ -0,0 +1,253 @@
+//! Schedule management subcommand
+//!
+//! 稼働中の cc-gateway の HTTP API 経由でスケジュールを操作します。
+//! schedule.toml を手で編集して再起動する必要はありません。
+//!
+//! ```bash
+//! cc-gateway schedule list
+//! cc-gateway schedule add daily-news "0 9 * * *" "今日のニュースを要約して" --tools web_search
+//! cc-gateway schedule pause daily-news
+//! cc-gateway schedule run daily-news
+//! ```
+
+use cc_core::Config;
+use cc_schedule::ScheduleTask;
+use serde_json::Value;
+
+/// Usage text for the `schedule` subcommand
+pub const USAGE: &str = "\
+Usage: cc-gateway schedule <command> [args]
+
+Commands:
+  list                          List schedules with their next run time
+  add NAME CRON PROMPT [--tools a,b] [--discord CHANNEL_ID] [--workflow NAME] [--disabled]
+                                Add a schedule (with --workflow, PROMPT is passed
+                                as the workflow's `prompt` input)
+  remove NAME                   Remove a schedule
+  pause NAME                    Pause a schedule
+  resume NAME                   Resume a paused schedule
+  run NAME                      Run a schedule immediately
+
+Environment Variables:
+  CC_GATEWAY_URL                Gateway base URL (default: http://127.0.0.1:<API_PORT>)
+  API_KEY                       API key for the HTTP API";
+
+/// Run the `schedule` subcommand
+pub async fn run_schedule(config: &Config, args: &[String]) -> anyhow::Result<()> {
+    let Some(command) = args.first() else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+
+    let client = ScheduleApiClient::new(config);
+    match (command.as_str(), &args[1..]) {
+        ("list" | "ls", []) => client.list().await,
+        ("add", rest) => client.add(parse_add_args(rest)?).await,
+        ("remove" | "rm", [name]) => client.action(reqwest::Method::DELETE, name, None).await,
+        ("pause", [name]) => client.action(reqwest::Method::POST, name, Some("pause")).await,
+        ("resume", [name]) => client.action(reqwest::Method::POST, name, Some("resume")).await,
+        ("run", [name]) => client.action(reqwest::Method::POST, name, Some("run")).await,
+        ("help" | "--help" | "-h", _) => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => anyhow::bail!("Invalid schedule command\n\n{}", USAGE),
+    }
+}
+
+/// Parse `add NAME CRON PROMPT [options]`
+fn parse_add_args(args: &[String]) -> anyhow::Result<ScheduleTask> {
+    let mut positional = Vec::new();
+    let mut tools = Vec::new();
+    let mut discord_channel = None;
+    let mut workflow = None;
+    let mut enabled = true;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--tools" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("--tools requires a value"))?;
+                tools.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(String::from),
+                );
+            }
+            "--discord" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("--discord requires a channel ID"))?;
+                discord_channel = Some(value.clone());
+            }
+            "--workflow" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("--workflow requires a workflow name"))?;
+                workflow = Some(value.clone());
+            }
+            "--disabled" => enabled = false,
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let [name, cron, prompt]: [String; 3] = positional
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("add requires NAME CRON PROMPT\n\n{}", USAGE))?;
+
+    Ok(ScheduleTask {
+        name,
+        cron,
+        prompt,
+        workflow,
+        tools,
+        discord_channel,
+        outputs: Vec::new(),
+        enabled,
+        batch: false,
+    })
+}
+
+/// Minimal client for the `/api/schedules` endpoints
+struct ScheduleApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl ScheduleApiClient {
+    fn new(config: &Config) -> Self {
+        let base_url = std::env::var("CC_GATEWAY_URL")
+            .unwrap_or_else(|_| format!("http://127.0.0.1:{}", config.api.port));
+        let api_key = std::env::var("API_KEY")
+            .ok()
+            .or_else(|| config.api_key.clone())
+            .or_else(|| config.api.key.clone());
+
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self
+            .http
+            .request(method, format!("{}/api/schedules{}", self.base_url, path));
+        match &self.api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    async fn list(&self) -> anyhow::Result<()> {
+        let body = send(self.request(reqwest::Method::GET, "")).await?;
+
+        if !body["running"].as_bool().unwrap_or(false) {
+            println!("Scheduler is not running");
+        }
+
+        let schedules = body["schedules"].as_array().cloned().unwrap_or_default();
+        if schedules.is_empty() {
+            println!("No schedules");
+            return Ok(());
+        }
+
+        for schedule in schedules {
+            let state = if schedule["enabled"].as_bool().unwrap_or(true) {
+                "enabled"
+            } else {
+                "paused"
+            };
+            println!(
+                "{:<20} {:<16} {:<8} next: {}",
+                schedule["name"].as_str().unwrap_or("-"),
+                schedule["cron"].as_str().unwrap_or("-"),
+                state,
+                schedule["next_run"].as_str().unwrap_or("-"),
+            );
+        }
+        Ok(())
+    }
+
+    async fn add(&self, task: ScheduleTask) -> anyhow::Result<()> {
+        let body = send(self.request(reqwest::Method::POST, "").json(&task)).await?;
+        print_message(&body);
+        Ok(())
+    }
+
+    async fn action(
+        &self,
+        method: reqwest::Method,
+        name: &str,
+        action: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let path = match action {
+            Some(action) => format!("/{}/{}", name, action),
+            None => format!("/{}", name),
+        };
+        let body = send(self.request(method, &path)).await?;
+        print_message(&body);
+        Ok(())
+    }
+}
+
+/// Send a request and return the JSON body, turning API errors into `anyhow` errors
+async fn send(request: reqwest::RequestBuilder) -> anyhow::Result<Value> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to cc-gateway: {}", e))?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+
+    if !status.is_success() {
+        let message = body["error"].as_str().unwrap_or("request failed");
+        anyhow::bail!("{} ({})", message, status);
+    }
+    Ok(body)
+}
+
+fn print_message(body: &Value) {
+    if let Some(message) = body["message"].as_str() {
+        println!("{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_add_args() {
+        let task = parse_add_args(&args(&[
+            "news",
+            "0 9 * * *",
+            "summarize",
+            "--tools",
+            "web_search, read",
+            "--disabled",
+        ]))
+        .unwrap();
+
+        assert_eq!(task.name, "news");
+        assert_eq!(task.cron, "0 9 * * *");
+        assert_eq!(task.tools, vec!["web_search", "read"]);
+        assert!(!task.enabled);
+    }
+
+    #[test]
+    fn test_parse_add_args_missing_prompt() {
+        assert!(parse_add_args(&args(&["news", "0 9 * * *"])).is_err());
+        assert!(parse_add_args(&args(&["news", "0 9 * * *", "p", "--tools"])).is_err());
+    }
+}
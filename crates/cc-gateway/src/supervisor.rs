@@ -0,0 +1,309 @@
+//! Supervision of the long-running services of server mode
+//!
+//! Channels, the HTTP API server and the scheduler are started through a
+//! [`Supervisor`] instead of a bare `tokio::spawn`. Every run executes in its
+//! own task, so a run that returns, fails or panics is noticed and started
+//! again after an exponential backoff. The status of each service is shown on
+//! the dashboard (`/api/services`).
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::task::{JoinError, JoinHandle};
+
+use cc_core::{CancellationToken, ChannelsConfig};
+use cc_dashboard::{ServiceInfo, ServiceProvider};
+
+/// One run of a service
+pub type ServiceFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+
+/// Starts a run of a service (called again on every restart)
+pub type ServiceFactory = Box<dyn Fn() -> ServiceFuture + Send + Sync>;
+
+/// Delay between restarts, doubled after every consecutive failure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Backoff {
+    /// `restart_delay_secs` / `max_restart_delay_secs` of `[channels]`
+    pub fn from_config(config: &ChannelsConfig) -> Self {
+        Self {
+            initial: Duration::from_secs(config.restart_delay_secs),
+            max: Duration::from_secs(config.max_restart_delay_secs.max(config.restart_delay_secs)),
+        }
+    }
+
+    /// Delay after `failures` consecutive failed runs (1 = the first failure)
+    pub fn delay(&self, failures: u32) -> Duration {
+        let factor = 1u32 << failures.saturating_sub(1).min(16);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ServiceState {
+    Running,
+    Restarting,
+    Stopped,
+}
+
+impl ServiceState {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Restarting => "restarting",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ServiceStatus {
+    state: ServiceState,
+    restarts: u32,
+    last_error: Option<String>,
+    started_at: Option<DateTime<Utc>>,
+    restart_at: Option<DateTime<Utc>>,
+}
+
+/// Runs services and restarts them when they stop
+pub struct Supervisor {
+    backoff: Backoff,
+    shutdown: CancellationToken,
+    services: Mutex<BTreeMap<String, ServiceStatus>>,
+}
+
+impl Supervisor {
+    /// Services stop being restarted once `shutdown` is cancelled
+    pub fn new(backoff: Backoff, shutdown: CancellationToken) -> Arc<Self> {
+        Arc::new(Self {
+            backoff,
+            shutdown,
+            services: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Start `factory` as the service `name`, restarting it until shutdown
+    ///
+    /// On shutdown the current run is awaited so services watching the
+    /// cancellation token can finish; aborting the returned handle also
+    /// aborts the run.
+    pub fn spawn(self: &Arc<Self>, name: impl Into<String>, factory: ServiceFactory) -> JoinHandle<()> {
+        let name = name.into();
+        let supervisor = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut failures = 0;
+            loop {
+                let started = Instant::now();
+                supervisor.update(&name, |status| {
+                    status.state = ServiceState::Running;
+                    status.started_at = Some(Utc::now());
+                    status.restart_at = None;
+                });
+
+                let mut run = AbortOnDrop(tokio::spawn(factory()));
+                let result = tokio::select! {
+                    result = &mut run.0 => result,
+                    _ = supervisor.shutdown.cancelled() => {
+                        let _ = (&mut run.0).await;
+                        break;
+                    }
+                };
+                let error = describe_failure(result);
+                tracing::error!("Service {} stopped: {}", name, error);
+
+                // A run that stayed up longer than the longest delay starts the backoff over
+                if started.elapsed() >= supervisor.backoff.max {
+                    failures = 0;
+                }
+                failures += 1;
+                let delay = supervisor.backoff.delay(failures);
+                supervisor.update(&name, |status| {
+                    status.state = ServiceState::Restarting;
+                    status.last_error = Some(error);
+                    status.started_at = None;
+                    status.restart_at = chrono::Duration::from_std(delay).ok().map(|d| Utc::now() + d);
+                });
+
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = supervisor.shutdown.cancelled() => break,
+                }
+                tracing::info!("Restarting service {} (after {:?})", name, delay);
+                supervisor.update(&name, |status| status.restarts += 1);
+            }
+            supervisor.update(&name, |status| {
+                status.state = ServiceState::Stopped;
+                status.started_at = None;
+                status.restart_at = None;
+            });
+        })
+    }
+
+    /// Status of every service started so far
+    pub fn statuses(&self) -> Vec<ServiceInfo> {
+        self.lock()
+            .iter()
+            .map(|(name, status)| ServiceInfo {
+                name: name.clone(),
+                status: status.state.as_str().to_string(),
+                restarts: status.restarts,
+                last_error: status.last_error.clone(),
+                started_at: status.started_at.map(|t| t.timestamp()),
+                restart_at: status.restart_at.map(|t| t.timestamp()),
+            })
+            .collect()
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut ServiceStatus)) {
+        let mut services = self.lock();
+        let status = services.entry(name.to_string()).or_insert(ServiceStatus {
+            state: ServiceState::Running,
+            restarts: 0,
+            last_error: None,
+            started_at: None,
+            restart_at: None,
+        });
+        f(status);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, ServiceStatus>> {
+        self.services.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl ServiceProvider for Supervisor {
+    async fn get_services(&self) -> Vec<ServiceInfo> {
+        self.statuses()
+    }
+}
+
+/// Aborts the run when the supervising task is aborted
+struct AbortOnDrop(JoinHandle<anyhow::Result<()>>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn describe_failure(result: Result<anyhow::Result<()>, JoinError>) -> String {
+    match result {
+        Ok(Ok(())) => "exited".to_string(),
+        Ok(Err(e)) => e.to_string(),
+        Err(e) if e.is_panic() => {
+            let payload = e.into_panic();
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown".to_string());
+            format!("panicked: {}", message)
+        }
+        Err(e) => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_backoff_delay() {
+        let backoff = Backoff {
+            initial: Duration::from_secs(5),
+            max: Duration::from_secs(60),
+        };
+        assert_eq!(backoff.delay(1), Duration::from_secs(5));
+        assert_eq!(backoff.delay(2), Duration::from_secs(10));
+        assert_eq!(backoff.delay(4), Duration::from_secs(40));
+        assert_eq!(backoff.delay(5), Duration::from_secs(60));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(60));
+
+        let backoff = Backoff::from_config(&ChannelsConfig::default());
+        assert_eq!(backoff.initial, Duration::from_secs(5));
+        assert_eq!(backoff.max, Duration::from_secs(300));
+    }
+
+    #[tokio::test]
+    async fn test_restarts_failed_and_panicked_runs() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&runs);
+        let factory: ServiceFactory = Box::new(move || {
+            let run = counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if run % 2 == 0 {
+                    anyhow::bail!("connection lost");
+                }
+                panic!("bad state");
+            })
+        });
+        let shutdown = CancellationToken::new();
+        let backoff = Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(2),
+        };
+        let supervisor = Supervisor::new(backoff, shutdown.clone());
+        let handle = supervisor.spawn("test", factory);
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while runs.load(Ordering::SeqCst) < 4 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let status = &supervisor.statuses()[0];
+        assert_eq!(status.name, "test");
+        assert!(status.restarts >= 2);
+        assert!(status.last_error.is_some());
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap();
+        assert_eq!(supervisor.statuses()[0].status, "stopped");
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_run() {
+        let shutdown = CancellationToken::new();
+        let token = shutdown.clone();
+        let finished = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&finished);
+        let factory: ServiceFactory = Box::new(move || {
+            let token = token.clone();
+            let counter = Arc::clone(&counter);
+            Box::pin(async move {
+                token.cancelled().await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+        });
+        let supervisor = Supervisor::new(Backoff::from_config(&ChannelsConfig::default()), shutdown.clone());
+        let handle = supervisor.spawn("api", factory);
+        while supervisor.statuses().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(supervisor.statuses()[0].status, "running");
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap();
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+        assert_eq!(supervisor.statuses()[0].restarts, 0);
+    }
+
+    #[test]
+    fn test_describe_failure() {
+        assert_eq!(describe_failure(Ok(Ok(()))), "exited");
+        assert_eq!(describe_failure(Ok(Err(anyhow::anyhow!("boom")))), "boom");
+    }
+}
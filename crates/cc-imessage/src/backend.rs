@@ -0,0 +1,101 @@
+//! iMessage backends
+//!
+//! Messages go through Messages.app on this Mac (AppleScript, with the
+//! Messages database for incoming messages) or through a BlueBubbles
+//! server, which lets the gateway run on hosts other than macOS.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use tracing::debug;
+
+use crate::bluebubbles::{direct_chat_guid, BlueBubblesClient};
+use crate::error::{IMessageError, Result};
+use crate::script::{AppleScript, IncomingAttachment, ReceivedMessage};
+use crate::tapback::Tapback;
+
+/// iMessage backend
+#[derive(Debug, Clone, Default)]
+pub enum Backend {
+    /// Messages.app on this Mac
+    #[default]
+    AppleScript,
+    /// A BlueBubbles server
+    BlueBubbles(Arc<BlueBubblesClient>),
+}
+
+impl Backend {
+    /// Backend name for logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AppleScript => "AppleScript",
+            Self::BlueBubbles(_) => "BlueBubbles",
+        }
+    }
+
+    /// Send a text message to `recipient`, or to the chat when its GUID is
+    /// known (group chats)
+    pub async fn send_text(&self, recipient: &str, chat_guid: Option<&str>, text: &str) -> Result<()> {
+        match self {
+            Self::AppleScript => match chat_guid {
+                Some(chat_guid) => AppleScript::send_to_chat_id(chat_guid, text),
+                None => AppleScript::send_message(recipient, text),
+            },
+            Self::BlueBubbles(client) => {
+                let chat_guid = chat_guid
+                    .map(str::to_string)
+                    .unwrap_or_else(|| direct_chat_guid(recipient));
+                client.send_text(&chat_guid, text).await
+            }
+        }
+    }
+
+    /// Send a file to `recipient`, or to the chat when its GUID is known
+    pub async fn send_file(&self, recipient: &str, chat_guid: Option<&str>, path: &Path) -> Result<()> {
+        match self {
+            Self::AppleScript => match chat_guid {
+                Some(chat_guid) => AppleScript::send_file_to_chat_id(chat_guid, path),
+                None => AppleScript::send_file(recipient, path),
+            },
+            Self::BlueBubbles(client) => {
+                let chat_guid = chat_guid
+                    .map(str::to_string)
+                    .unwrap_or_else(|| direct_chat_guid(recipient));
+                client.send_file(&chat_guid, path).await
+            }
+        }
+    }
+
+    /// React to `msg` with a tapback
+    ///
+    /// AppleScript cannot send tapbacks; only BlueBubbles (with the
+    /// Private API) can.
+    pub async fn send_tapback(&self, msg: &ReceivedMessage, tapback: Tapback) -> Result<()> {
+        let Self::BlueBubbles(client) = self else {
+            return Err(IMessageError::Unsupported("tapbacks need the BlueBubbles backend".to_string()));
+        };
+        let Some(ref guid) = msg.guid else {
+            return Err(IMessageError::Unsupported("message without GUID".to_string()));
+        };
+        let chat_guid = msg
+            .chat_guid
+            .clone()
+            .unwrap_or_else(|| direct_chat_guid(&msg.sender));
+        client.send_tapback(&chat_guid, guid, tapback).await
+    }
+
+    /// Read the contents of a received attachment
+    pub async fn read_attachment(&self, attachment: &IncomingAttachment) -> Result<Vec<u8>> {
+        if let Some(ref path) = attachment.path {
+            debug!("Reading attachment {}", path.display());
+            return Ok(tokio::fs::read(path).await?);
+        }
+        match (self, attachment.guid.as_deref()) {
+            (Self::BlueBubbles(client), Some(guid)) => client.download_attachment(guid).await,
+            _ => Err(IMessageError::Unsupported(format!(
+                "attachment {} has no file",
+                attachment.name
+            ))),
+        }
+    }
+}
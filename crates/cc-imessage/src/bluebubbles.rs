@@ -0,0 +1,348 @@
+//! BlueBubbles server backend
+//!
+//! [BlueBubbles](https://bluebubbles.app) runs on a Mac signed in to
+//! iMessage and exposes it over a REST API, so the gateway itself can run
+//! on any host. Messages are polled with `/api/v1/message/query`; every
+//! request is authenticated with the server password. Tapbacks need the
+//! server's Private API.
+
+use std::path::Path;
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+use crate::error::{IMessageError, Result};
+use crate::script::{IncomingAttachment, ReceivedMessage};
+use crate::tapback::Tapback;
+
+/// BlueBubbles server settings
+#[derive(Debug, Clone)]
+pub struct BlueBubblesConfig {
+    /// Server URL, e.g. `http://192.168.1.10:1234`
+    pub url: String,
+    /// Server password
+    pub password: String,
+}
+
+impl BlueBubblesConfig {
+    /// Read `BLUEBUBBLES_URL` and `BLUEBUBBLES_PASSWORD`
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| IMessageError::Config(format!("{} is not set", name)))
+        };
+        Ok(Self {
+            url: var("BLUEBUBBLES_URL")?,
+            password: var("BLUEBUBBLES_PASSWORD")?,
+        })
+    }
+}
+
+/// A message polled from the server
+#[derive(Debug, Clone)]
+pub struct PolledMessage {
+    /// Creation time (Unix milliseconds), the polling cursor
+    pub date_created: i64,
+    pub message: ReceivedMessage,
+}
+
+/// BlueBubbles REST API client
+#[derive(Debug, Clone)]
+pub struct BlueBubblesClient {
+    client: Client,
+    base_url: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiMessage {
+    guid: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    is_from_me: bool,
+    #[serde(default)]
+    date_created: i64,
+    #[serde(default)]
+    handle: Option<ApiHandle>,
+    #[serde(default)]
+    chats: Vec<ApiChat>,
+    #[serde(default)]
+    attachments: Vec<ApiAttachment>,
+    /// Set for tapbacks
+    #[serde(default)]
+    associated_message_type: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct ApiHandle {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct ApiChat {
+    guid: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiAttachment {
+    guid: String,
+    #[serde(default)]
+    mime_type: Option<String>,
+    #[serde(default)]
+    transfer_name: Option<String>,
+    #[serde(default)]
+    total_bytes: Option<u64>,
+}
+
+impl ApiMessage {
+    fn is_tapback(&self) -> bool {
+        match self.associated_message_type {
+            None | Some(Value::Null) => false,
+            Some(Value::Number(ref n)) => n.as_i64() != Some(0),
+            Some(Value::String(ref s)) => !s.is_empty() && s != "0",
+            Some(_) => true,
+        }
+    }
+
+    fn into_polled(self) -> PolledMessage {
+        PolledMessage {
+            date_created: self.date_created,
+            message: ReceivedMessage {
+                sender: self.handle.map(|h| h.address).unwrap_or_default(),
+                content: self
+                    .text
+                    .unwrap_or_default()
+                    .replace('\u{FFFC}', "")
+                    .trim()
+                    .to_string(),
+                timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp_millis(self.date_created)
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default(),
+                guid: Some(self.guid),
+                chat_guid: self.chats.into_iter().next().map(|c| c.guid),
+                attachments: self
+                    .attachments
+                    .into_iter()
+                    .map(|a| IncomingAttachment {
+                        name: a.transfer_name.unwrap_or_else(|| a.guid.clone()),
+                        mime_type: a.mime_type,
+                        size: a.total_bytes,
+                        path: None,
+                        guid: Some(a.guid),
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Chat GUID of a direct conversation with `address`
+pub fn direct_chat_guid(address: &str) -> String {
+    format!("iMessage;-;{}", address)
+}
+
+impl BlueBubblesClient {
+    /// Create a client for the server in `config`
+    pub fn new(config: BlueBubblesConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()?;
+        Ok(Self {
+            client,
+            base_url: config.url.trim_end_matches('/').to_string(),
+            password: config.password,
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/v1{}", self.base_url, path)
+    }
+
+    /// Check the response status and return its body
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let text = response.text().await.unwrap_or_default();
+        Err(IMessageError::Api(format!("{}: {}", status, text)))
+    }
+
+    async fn post_json(&self, path: &str, body: &Value) -> Result<Value> {
+        let response = self
+            .client
+            .post(self.url(path))
+            .query(&[("password", &self.password)])
+            .json(body)
+            .send()
+            .await?;
+        Ok(Self::check(response).await?.json().await?)
+    }
+
+    /// Check that the server is reachable and the password is accepted
+    pub async fn ping(&self) -> Result<()> {
+        let response = self
+            .client
+            .get(self.url("/ping"))
+            .query(&[("password", &self.password)])
+            .send()
+            .await?;
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    /// Incoming messages created after `after` (Unix milliseconds), oldest first
+    ///
+    /// Messages sent from this account and tapbacks are skipped.
+    pub async fn messages_after(&self, after: i64, limit: usize) -> Result<Vec<PolledMessage>> {
+        let body = json!({
+            "after": after,
+            "limit": limit,
+            "sort": "ASC",
+            "with": ["chat", "handle", "attachment"],
+        });
+        let value = self.post_json("/message/query", &body).await?;
+        let response: ApiResponse<Vec<ApiMessage>> = serde_json::from_value(value)?;
+
+        let messages: Vec<PolledMessage> = response
+            .data
+            .into_iter()
+            .filter(|m| !m.is_from_me && !m.is_tapback())
+            .map(ApiMessage::into_polled)
+            .collect();
+        debug!("Polled {} BlueBubbles messages", messages.len());
+        Ok(messages)
+    }
+
+    /// Send a text message to a chat
+    pub async fn send_text(&self, chat_guid: &str, message: &str) -> Result<()> {
+        let body = json!({
+            "chatGuid": chat_guid,
+            "tempGuid": uuid::Uuid::new_v4().to_string(),
+            "message": message,
+        });
+        self.post_json("/message/text", &body).await?;
+        info!("Message sent to {} via BlueBubbles", chat_guid);
+        Ok(())
+    }
+
+    /// Send a file to a chat
+    pub async fn send_file(&self, chat_guid: &str, path: &Path) -> Result<()> {
+        let bytes = tokio::fs::read(path).await?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "attachment".to_string());
+        let form = reqwest::multipart::Form::new()
+            .text("chatGuid", chat_guid.to_string())
+            .text("tempGuid", uuid::Uuid::new_v4().to_string())
+            .text("name", name.clone())
+            .part("attachment", reqwest::multipart::Part::bytes(bytes).file_name(name));
+
+        let response = self
+            .client
+            .post(self.url("/message/attachment"))
+            .query(&[("password", &self.password)])
+            .multipart(form)
+            .send()
+            .await?;
+        Self::check(response).await?;
+        info!("File {} sent to {} via BlueBubbles", path.display(), chat_guid);
+        Ok(())
+    }
+
+    /// React to a message with a tapback (requires the Private API)
+    pub async fn send_tapback(&self, chat_guid: &str, message_guid: &str, tapback: Tapback) -> Result<()> {
+        let body = json!({
+            "chatGuid": chat_guid,
+            "selectedMessageGuid": message_guid,
+            "reaction": tapback.name(),
+        });
+        self.post_json("/message/react", &body).await?;
+        Ok(())
+    }
+
+    /// Download an attachment
+    pub async fn download_attachment(&self, guid: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(self.url(&format!("/attachment/{}/download", guid)))
+            .query(&[("password", &self.password)])
+            .send()
+            .await?;
+        Ok(Self::check(response).await?.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(url: &str) -> BlueBubblesClient {
+        BlueBubblesClient::new(BlueBubblesConfig {
+            url: url.to_string(),
+            password: "secret".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_messages_after() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/message/query"))
+            .and(query_param("password", "secret"))
+            .and(body_partial_json(json!({ "after": 1000, "sort": "ASC" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "status": 200,
+                "data": [
+                    { "guid": "M1", "text": "\u{FFFC}What is this?", "isFromMe": false, "dateCreated": 1700000000000i64,
+                      "handle": { "address": "+819012345678" },
+                      "chats": [{ "guid": "iMessage;-;+819012345678" }],
+                      "attachments": [{ "guid": "A1", "mimeType": "image/jpeg", "transferName": "photo.jpg", "totalBytes": 10 }] },
+                    { "guid": "M2", "text": "Loved “What is this?”", "isFromMe": false, "dateCreated": 1700000000001i64,
+                      "associatedMessageType": "love" },
+                    { "guid": "M3", "text": "answer", "isFromMe": true, "dateCreated": 1700000000002i64 },
+                ],
+            })))
+            .mount(&server)
+            .await;
+
+        let messages = client(&server.uri()).messages_after(1000, 50).await.unwrap();
+        assert_eq!(messages.len(), 1);
+        let polled = &messages[0];
+        assert_eq!(polled.date_created, 1700000000000);
+        assert_eq!(polled.message.sender, "+819012345678");
+        assert_eq!(polled.message.content, "What is this?");
+        assert_eq!(polled.message.chat_guid.as_deref(), Some("iMessage;-;+819012345678"));
+        assert_eq!(polled.message.attachments[0].guid.as_deref(), Some("A1"));
+    }
+
+    #[tokio::test]
+    async fn test_send_tapback() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/message/react"))
+            .and(body_partial_json(json!({ "selectedMessageGuid": "M1", "reaction": "like" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": 200, "data": {} })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        client(&server.uri())
+            .send_tapback(&direct_chat_guid("+819012345678"), "M1", Tapback::Like)
+            .await
+            .unwrap();
+    }
+}
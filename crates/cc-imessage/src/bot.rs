@@ -2,12 +2,15 @@
 //!
 //! Main entry point for the iMessage Gateway
 
+use std::path::Path;
 use std::sync::Arc;
 
 use tracing::{error, info};
 
 use cc_core::{ClaudeClient, Config};
 
+use crate::backend::Backend;
+use crate::bluebubbles::{BlueBubblesClient, BlueBubblesConfig};
 use crate::error::{IMessageError, Result};
 use crate::handler::{HandlerConfig, MessageHandler};
 use crate::script::AppleScript;
@@ -22,6 +25,7 @@ pub struct IMessageBot {
     session_store: Arc<InMemorySessionStore>,
     handler_config: HandlerConfig,
     watcher_config: WatcherConfig,
+    backend: Backend,
 }
 
 impl IMessageBot {
@@ -53,6 +57,7 @@ impl IMessageBot {
             session_store,
             handler_config: HandlerConfig::default(),
             watcher_config: WatcherConfig::default(),
+            backend: Backend::AppleScript,
         })
     }
 
@@ -79,9 +84,42 @@ impl IMessageBot {
             session_store,
             handler_config: HandlerConfig::default(),
             watcher_config: WatcherConfig::default(),
+            backend: Backend::AppleScript,
         })
     }
 
+    /// Create a bot using a BlueBubbles server (works on any OS)
+    pub fn bluebubbles(
+        config: Config,
+        claude_client: Arc<ClaudeClient>,
+        bluebubbles: BlueBubblesConfig,
+    ) -> Result<Self> {
+        let client = BlueBubblesClient::new(bluebubbles)?;
+        let session_store = Arc::new(InMemorySessionStore::new());
+
+        // Start session cleanup task
+        let store_clone = session_store.clone();
+        tokio::spawn(async move {
+            if let Err(e) = store_clone.start_cleanup_task().await {
+                error!("Session cleanup task failed: {}", e);
+            }
+        });
+
+        Ok(Self {
+            config,
+            claude_client,
+            session_store,
+            handler_config: HandlerConfig::default(),
+            watcher_config: WatcherConfig::default(),
+            backend: Backend::BlueBubbles(Arc::new(client)),
+        })
+    }
+
+    /// Get the backend
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
     /// Get the session store
     pub fn session_store(&self) -> Arc<InMemorySessionStore> {
         self.session_store.clone()
@@ -125,7 +163,12 @@ impl IMessageBot {
 
     /// Send a message to a recipient (one-way, no session)
     pub async fn send_message(&self, recipient: &str, message: &str) -> Result<()> {
-        AppleScript::send_message(recipient, message)
+        self.backend.send_text(recipient, None, message).await
+    }
+
+    /// Send a file to a recipient
+    pub async fn send_file(&self, recipient: &str, path: &Path) -> Result<()> {
+        self.backend.send_file(recipient, None, path).await
     }
 
     /// Send a message to a chat by name
@@ -148,16 +191,20 @@ impl IMessageBot {
         info!("Starting iMessage bot...");
 
         // Create message handler
-        let handler = Arc::new(MessageHandler::new(
-            self.claude_client.clone(),
-            self.session_store.clone(),
-            self.handler_config.clone(),
-        ));
+        let handler = Arc::new(
+            MessageHandler::new(
+                self.claude_client.clone(),
+                self.session_store.clone(),
+                self.handler_config.clone(),
+            )
+            .with_backend(self.backend.clone()),
+        );
 
         // Build and start watcher
         let watcher = WatcherBuilder::new()
             .poll_interval(self.watcher_config.poll_interval_secs)
             .watch_chats(self.watcher_config.watch_chats.clone())
+            .database_path(self.watcher_config.database_path.clone())
             .handler(handler)
             .build()?;
 
@@ -169,15 +216,19 @@ impl IMessageBot {
 
     /// Run the bot with shutdown signal
     pub async fn run(&self, mut shutdown: tokio::sync::broadcast::Receiver<()>) -> Result<()> {
-        let handler = Arc::new(MessageHandler::new(
-            self.claude_client.clone(),
-            self.session_store.clone(),
-            self.handler_config.clone(),
-        ));
+        let handler = Arc::new(
+            MessageHandler::new(
+                self.claude_client.clone(),
+                self.session_store.clone(),
+                self.handler_config.clone(),
+            )
+            .with_backend(self.backend.clone()),
+        );
 
         let watcher = WatcherBuilder::new()
             .poll_interval(self.watcher_config.poll_interval_secs)
             .watch_chats(self.watcher_config.watch_chats.clone())
+            .database_path(self.watcher_config.database_path.clone())
             .handler(handler)
             .build()?;
 
@@ -205,7 +256,7 @@ impl IMessageBot {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, target_os = "macos"))]
 mod tests {
     use super::*;
     use cc_core::{Config, LlmConfig, LlmProvider, ApiConfig, MemoryConfig, McpConfig, SchedulerConfig};
@@ -218,6 +269,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -228,6 +282,12 @@ mod tests {
             memory: MemoryConfig::default(),
             mcp: McpConfig::default(),
             scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -0,0 +1,229 @@
+//! Messages database reader
+//!
+//! Messages.app stores messages in `~/Library/Messages/chat.db`. Reading it
+//! (which needs Full Disk Access for the gateway process) gives message
+//! GUIDs, chats and attachments that AppleScript does not expose. The
+//! database is opened read-only and polled by ROWID.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OpenFlags};
+
+use crate::error::Result;
+use crate::script::{IncomingAttachment, ReceivedMessage};
+
+/// Seconds between the Unix epoch and the Apple epoch (2001-01-01)
+const APPLE_EPOCH_OFFSET: i64 = 978_307_200;
+
+/// Placeholder Messages puts in the text for each attachment
+const ATTACHMENT_PLACEHOLDER: char = '\u{FFFC}';
+
+/// A message read from the database
+#[derive(Debug, Clone)]
+pub struct DbMessage {
+    pub rowid: i64,
+    pub message: ReceivedMessage,
+}
+
+/// Read-only access to the Messages database
+#[derive(Debug, Clone)]
+pub struct MessagesDb {
+    path: PathBuf,
+}
+
+impl MessagesDb {
+    /// `~/Library/Messages/chat.db`
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Messages/chat.db"))
+    }
+
+    /// Open the database, checking that it can be read
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let db = Self { path: path.into() };
+        db.connection()?;
+        Ok(db)
+    }
+
+    /// Database path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn connection(&self) -> Result<Connection> {
+        let conn = Connection::open_with_flags(
+            &self.path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        // Messages.app keeps writing; wait instead of failing on its locks
+        conn.busy_timeout(std::time::Duration::from_secs(2))?;
+        Ok(conn)
+    }
+
+    /// ROWID of the newest message (0 when empty)
+    pub fn max_rowid(&self) -> Result<i64> {
+        let conn = self.connection()?;
+        Ok(conn.query_row("SELECT COALESCE(MAX(ROWID), 0) FROM message", [], |row| row.get(0))?)
+    }
+
+    /// Incoming messages after `rowid`, oldest first
+    pub fn messages_after(&self, rowid: i64, limit: usize) -> Result<Vec<DbMessage>> {
+        let conn = self.connection()?;
+        query_messages(&conn, rowid, limit)
+    }
+}
+
+/// Incoming messages after `rowid`, with their attachments
+///
+/// Messages sent from this account and tapbacks are skipped.
+fn query_messages(conn: &Connection, rowid: i64, limit: usize) -> Result<Vec<DbMessage>> {
+    let mut stmt = conn.prepare(
+        "SELECT m.ROWID, m.guid, m.text, m.date, h.id, c.guid
+         FROM message m
+         LEFT JOIN handle h ON h.ROWID = m.handle_id
+         LEFT JOIN chat_message_join cmj ON cmj.message_id = m.ROWID
+         LEFT JOIN chat c ON c.ROWID = cmj.chat_id
+         WHERE m.ROWID > ?1 AND m.is_from_me = 0
+           AND COALESCE(m.associated_message_type, 0) = 0
+         ORDER BY m.ROWID
+         LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![rowid, limit as i64], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<i64>>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, Option<String>>(5)?,
+        ))
+    })?;
+
+    let mut messages = Vec::new();
+    for row in rows {
+        let (rowid, guid, text, date, sender, chat_guid) = row?;
+        let content: String = text
+            .unwrap_or_default()
+            .chars()
+            .filter(|&c| c != ATTACHMENT_PLACEHOLDER)
+            .collect();
+        messages.push(DbMessage {
+            rowid,
+            message: ReceivedMessage {
+                sender: sender.unwrap_or_default(),
+                content: content.trim().to_string(),
+                timestamp: date.map(apple_time_to_rfc3339).unwrap_or_default(),
+                guid,
+                chat_guid,
+                attachments: query_attachments(conn, rowid)?,
+            },
+        });
+    }
+    Ok(messages)
+}
+
+fn query_attachments(conn: &Connection, message_rowid: i64) -> Result<Vec<IncomingAttachment>> {
+    let mut stmt = conn.prepare(
+        "SELECT a.filename, a.mime_type, a.transfer_name, a.total_bytes
+         FROM attachment a
+         JOIN message_attachment_join maj ON maj.attachment_id = a.ROWID
+         WHERE maj.message_id = ?1",
+    )?;
+    let rows = stmt.query_map(params![message_rowid], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<i64>>(3)?,
+        ))
+    })?;
+
+    let mut attachments = Vec::new();
+    for row in rows {
+        let (filename, mime_type, transfer_name, total_bytes) = row?;
+        let Some(filename) = filename else {
+            // Not downloaded yet
+            continue;
+        };
+        let path = expand_home(&filename);
+        let name = transfer_name.unwrap_or_else(|| {
+            path.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        attachments.push(IncomingAttachment {
+            name,
+            mime_type,
+            size: total_bytes.map(|b| b as u64),
+            path: Some(path),
+            guid: None,
+        });
+    }
+    Ok(attachments)
+}
+
+/// Attachment paths are stored as `~/Library/Messages/Attachments/...`
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Message date (nanoseconds since 2001-01-01; seconds before macOS 10.13)
+fn apple_time_to_rfc3339(date: i64) -> String {
+    let seconds = if date > 1_000_000_000_000 { date / 1_000_000_000 } else { date };
+    DateTime::<Utc>::from_timestamp(seconds + APPLE_EPOCH_OFFSET, 0)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE message (ROWID INTEGER PRIMARY KEY, guid TEXT, text TEXT, date INTEGER,
+                 handle_id INTEGER, is_from_me INTEGER, associated_message_type INTEGER);
+             CREATE TABLE handle (ROWID INTEGER PRIMARY KEY, id TEXT);
+             CREATE TABLE chat (ROWID INTEGER PRIMARY KEY, guid TEXT);
+             CREATE TABLE chat_message_join (chat_id INTEGER, message_id INTEGER);
+             CREATE TABLE attachment (ROWID INTEGER PRIMARY KEY, filename TEXT, mime_type TEXT,
+                 transfer_name TEXT, total_bytes INTEGER);
+             CREATE TABLE message_attachment_join (message_id INTEGER, attachment_id INTEGER);
+
+             INSERT INTO handle VALUES (1, '+819012345678');
+             INSERT INTO chat VALUES (1, 'iMessage;-;+819012345678');
+             INSERT INTO message VALUES (1, 'G1', 'old', 0, 1, 0, 0);
+             INSERT INTO message VALUES (2, 'G2', '￼これは何？', 700000000000000000, 1, 0, 0);
+             INSERT INTO message VALUES (3, 'G3', 'reply', 700000000000000000, 0, 1, 0);
+             INSERT INTO message VALUES (4, 'G4', 'Liked “これは何？”', 700000000000000000, 1, 0, 2001);
+             INSERT INTO chat_message_join VALUES (1, 2);
+             INSERT INTO attachment VALUES (1, '/tmp/IMG_0001.heic', 'image/heic', 'IMG_0001.heic', 1234);
+             INSERT INTO message_attachment_join VALUES (2, 1);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_query_messages() {
+        let conn = test_db();
+        let messages = query_messages(&conn, 1, 10).unwrap();
+
+        // Own messages and tapbacks are skipped
+        assert_eq!(messages.len(), 1);
+        let DbMessage { rowid, message } = &messages[0];
+        assert_eq!(*rowid, 2);
+        assert_eq!(message.sender, "+819012345678");
+        assert_eq!(message.content, "これは何？");
+        assert_eq!(message.guid.as_deref(), Some("G2"));
+        assert_eq!(message.chat_guid.as_deref(), Some("iMessage;-;+819012345678"));
+        assert!(message.timestamp.starts_with("2023-"));
+        assert_eq!(message.attachments.len(), 1);
+        assert_eq!(message.attachments[0].name, "IMG_0001.heic");
+        assert_eq!(message.attachments[0].path.as_deref(), Some(Path::new("/tmp/IMG_0001.heic")));
+    }
+}
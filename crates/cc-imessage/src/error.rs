@@ -26,6 +26,18 @@ pub enum IMessageError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("Messages database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("BlueBubbles API error: {0}")]
+    Api(String),
+
+    #[error("Not supported by this backend: {0}")]
+    Unsupported(String),
+
     #[error("Timeout waiting for response")]
     Timeout,
 }
@@ -1,13 +1,21 @@
 //! iMessage message handler implementation
+//!
+//! Image attachments go to the model and text documents are read into the
+//! prompt. Replies go to the chat the message came from.
 
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
-use cc_core::{ClaudeClient, Message};
+use cc_core::{ClaudeClient, ImageSource, Message};
 
+use crate::backend::Backend;
 use crate::error::Result;
-use crate::script::{AppleScript, ReceivedMessage};
+use crate::script::ReceivedMessage;
 use crate::session::InMemorySessionStore;
+use crate::tapback::Tapback;
+
+/// Largest image passed to the model
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
 
 /// Configuration for the message handler
 #[derive(Clone, Debug)]
@@ -18,6 +26,8 @@ pub struct HandlerConfig {
     pub max_message_length: usize,
     /// System prompt for Claude
     pub system_prompt: String,
+    /// Tapback acknowledging a received message (BlueBubbles only)
+    pub ack_tapback: Option<Tapback>,
 }
 
 impl Default for HandlerConfig {
@@ -26,6 +36,7 @@ impl Default for HandlerConfig {
             allowed_senders: Vec::new(),
             max_message_length: 1000, // iMessage has no strict limit, but we split for readability
             system_prompt: "You are a helpful assistant. Respond concisely as this is a messaging app. Respond in the same language as the user's question.".to_string(),
+            ack_tapback: None,
         }
     }
 }
@@ -35,6 +46,7 @@ pub struct MessageHandler {
     claude_client: Arc<ClaudeClient>,
     session_store: Arc<InMemorySessionStore>,
     config: HandlerConfig,
+    backend: Backend,
 }
 
 impl MessageHandler {
@@ -48,9 +60,21 @@ impl MessageHandler {
             claude_client,
             session_store,
             config,
+            backend: Backend::AppleScript,
         }
     }
 
+    /// Send replies through `backend`
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// The backend replies are sent through
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
     /// Process an incoming message
     pub async fn process_message(&self, msg: &ReceivedMessage) -> Result<()> {
         // Check sender authorization
@@ -60,17 +84,79 @@ impl MessageHandler {
         }
 
         let content = msg.content.trim();
-        if content.is_empty() {
+        if content.is_empty() && msg.attachments.is_empty() {
+            return Ok(());
+        }
+        if Tapback::is_legacy_text(content) {
+            debug!("Ignoring tapback from {}", msg.sender);
             return Ok(());
         }
 
         // Handle commands
         if content.starts_with('/') {
-            return self.handle_command(&msg.sender, content).await;
+            return self.handle_command(msg, content).await;
+        }
+
+        if let Some(tapback) = self.config.ack_tapback {
+            if let Err(e) = self.backend.send_tapback(msg, tapback).await {
+                debug!("Failed to send tapback: {}", e);
+            }
         }
 
         // Regular message processing
-        self.process_with_claude(&msg.sender, content).await
+        let (images, notes) = self.read_attachments(msg).await;
+        let mut content = content.to_string();
+        for note in notes {
+            if !content.is_empty() {
+                content.push_str("\n\n");
+            }
+            content.push_str(&note);
+        }
+        self.process_with_claude(msg, &content, images).await
+    }
+
+    /// Read the attachments of `msg`
+    ///
+    /// Returns the images for the model and notes for the prompt: the text
+    /// of documents, or why an attachment was skipped.
+    async fn read_attachments(&self, msg: &ReceivedMessage) -> (Vec<ImageSource>, Vec<String>) {
+        let mut images = Vec::new();
+        let mut notes = Vec::new();
+
+        for attachment in &msg.attachments {
+            let name = attachment.name.as_str();
+            let mime = attachment.mime_type.as_deref().unwrap_or_default();
+            // HEIC photos are not accepted by the model
+            let is_image = matches!(mime, "image/jpeg" | "image/png" | "image/gif" | "image/webp");
+
+            if is_image && attachment.size.is_some_and(|size| size > MAX_IMAGE_BYTES) {
+                notes.push(format!("[画像 {} は大きすぎるため読み込めませんでした（5MB まで）]", name));
+                continue;
+            }
+            if !is_image && !cc_core::document::is_text_document(name, Some(mime)) {
+                notes.push(format!("[添付ファイル {} の形式には対応していません]", name));
+                continue;
+            }
+
+            let bytes = match self.backend.read_attachment(attachment).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    warn!("Failed to read attachment {}: {}", name, e);
+                    notes.push(format!("[添付ファイル {} を読み込めませんでした]", name));
+                    continue;
+                }
+            };
+            if is_image {
+                images.push(ImageSource::from_bytes(mime, &bytes));
+            } else {
+                match cc_core::extract_text(name, Some(mime), &bytes) {
+                    Some(document) => notes.push(document.to_prompt()),
+                    None => notes.push(format!("[添付ファイル {} をテキストとして読み込めませんでした]", name)),
+                }
+            }
+        }
+
+        (images, notes)
     }
 
     /// Check if sender is allowed
@@ -84,7 +170,8 @@ impl MessageHandler {
     }
 
     /// Handle slash commands
-    async fn handle_command(&self, sender: &str, content: &str) -> Result<()> {
+    async fn handle_command(&self, msg: &ReceivedMessage, content: &str) -> Result<()> {
+        let sender = msg.sender.as_str();
         let parts: Vec<&str> = content.splitn(2, ' ').collect();
         let command = parts[0];
         let _args = parts.get(1).copied().unwrap_or("");
@@ -92,9 +179,9 @@ impl MessageHandler {
         match command {
             "/clear" | "/reset" => {
                 if self.session_store.clear(sender) {
-                    self.send_reply(sender, "セッションをリセットしました。").await?;
+                    self.send_reply(msg, "セッションをリセットしました。").await?;
                 } else {
-                    self.send_reply(sender, "リセットするセッションがありません。").await?;
+                    self.send_reply(msg, "リセットするセッションがありません。").await?;
                 }
             }
             "/help" => {
@@ -106,7 +193,7 @@ impl MessageHandler {
                     "\n",
                     "それ以外は通常のチャットとして動作します。"
                 );
-                self.send_reply(sender, help_text).await?;
+                self.send_reply(msg, help_text).await?;
             }
             "/status" => {
                 let session = self.session_store.get(sender);
@@ -114,12 +201,12 @@ impl MessageHandler {
                     Some(s) => format!("メッセージ数: {}", s.message_count()),
                     None => "新しいセッション".to_string(),
                 };
-                self.send_reply(sender, &status).await?;
+                self.send_reply(msg, &status).await?;
             }
             _ => {
                 // Unknown command, treat as regular message
                 let clean_content = content.trim_start_matches('/');
-                self.process_with_claude(sender, clean_content).await?;
+                self.process_with_claude(msg, clean_content, Vec::new()).await?;
             }
         }
 
@@ -127,15 +214,29 @@ impl MessageHandler {
     }
 
     /// Process message with Claude API
-    async fn process_with_claude(&self, sender: &str, content: &str) -> Result<()> {
+    async fn process_with_claude(
+        &self,
+        msg: &ReceivedMessage,
+        content: &str,
+        images: Vec<ImageSource>,
+    ) -> Result<()> {
+        let sender = msg.sender.as_str();
         info!("Processing message from {}: {}", sender, content);
 
         // Get or create session
         let session = self.session_store.get_or_create(sender);
 
+        // Images are sent once and only noted in the history
+        let image_count = images.len();
+        let content = if content.trim().is_empty() { "この画像について説明してください。" } else { content };
+
         // Build message history
         let mut messages: Vec<Message> = session.messages.clone();
-        messages.push(Message::user(content));
+        messages.push(if images.is_empty() {
+            Message::user(content)
+        } else {
+            Message::user_with_images(content, images)
+        });
 
         // Build request with conversation history
         let mut request_builder = self
@@ -169,17 +270,22 @@ impl MessageHandler {
                     .join("\n");
 
                 // Update session
+                let history_text = if image_count == 0 {
+                    content.to_string()
+                } else {
+                    format!("{}\n[画像 {} 枚を添付]", content, image_count)
+                };
                 self.session_store
-                    .add_message(sender, Message::user(content));
+                    .add_message(sender, Message::user(history_text));
                 self.session_store
                     .add_message(sender, Message::assistant(&text));
 
                 // Send response
-                self.send_reply(sender, &text).await?;
+                self.send_reply(msg, &text).await?;
             }
             Err(e) => {
                 error!("Claude API error: {:?}", e);
-                self.send_reply(sender, &format!("エラーが発生しました: {}", e))
+                self.send_reply(msg, &format!("エラーが発生しました: {}", e))
                     .await?;
             }
         }
@@ -188,10 +294,12 @@ impl MessageHandler {
     }
 
     /// Send a reply via iMessage
-    async fn send_reply(&self, sender: &str, text: &str) -> Result<()> {
+    async fn send_reply(&self, msg: &ReceivedMessage, text: &str) -> Result<()> {
+        let chat_guid = msg.chat_guid.as_deref();
+
         // Split message if necessary
         if text.len() <= self.config.max_message_length {
-            AppleScript::send_message(sender, text)?;
+            self.backend.send_text(&msg.sender, chat_guid, text).await?;
         } else {
             let chunks = self.split_message(text, self.config.max_message_length);
             for (i, chunk) in chunks.iter().enumerate() {
@@ -206,7 +314,7 @@ impl MessageHandler {
                     tokio::time::sleep(std::time::Duration::from_millis(500)).await;
                 }
 
-                if let Err(e) = AppleScript::send_message(sender, &content) {
+                if let Err(e) = self.backend.send_text(&msg.sender, chat_guid, &content).await {
                     error!("Failed to send message chunk {}: {:?}", i, e);
                     break;
                 }
@@ -266,6 +374,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -276,6 +387,12 @@ mod tests {
             memory: MemoryConfig::default(),
             mcp: McpConfig::default(),
             scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -287,6 +404,7 @@ mod tests {
             claude_client,
             session_store: Arc::new(InMemorySessionStore::new()),
             config: HandlerConfig::default(),
+            backend: Backend::AppleScript,
         };
 
         let short = "Short message";
@@ -308,6 +426,7 @@ mod tests {
             claude_client: claude_client.clone(),
             session_store: Arc::new(store.clone()),
             config: handler_config.clone(),
+            backend: Backend::AppleScript,
         };
 
         // Empty allowed_senders = allow all
@@ -319,6 +438,7 @@ mod tests {
             claude_client,
             session_store: Arc::new(store),
             config: handler_config,
+            backend: Backend::AppleScript,
         };
         assert!(handler.is_sender_allowed("+819012345678"));
         assert!(!handler.is_sender_allowed("+81998765432"));
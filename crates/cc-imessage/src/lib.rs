@@ -2,14 +2,22 @@
 //!
 //! macOS の Apple Script を使用して iMessage 経由で Claude API へのアクセスを提供します。
 //! メッセージの送受信とセッション管理を行います。
+//! BlueBubbles サーバーを使うと macOS 以外のホストでも動作します。
 
+pub mod backend;
+pub mod bluebubbles;
 pub mod bot;
+pub mod chatdb;
 pub mod error;
 pub mod handler;
 pub mod script;
 pub mod session;
+pub mod tapback;
 pub mod watcher;
 
+pub use backend::Backend;
+pub use bluebubbles::{BlueBubblesClient, BlueBubblesConfig};
 pub use bot::IMessageBot;
 pub use error::{IMessageError, Result};
 pub use session::InMemorySessionStore;
+pub use tapback::Tapback;
@@ -2,6 +2,7 @@
 //!
 //! macOS の Apple Script を使用して Messages.app と対話します。
 
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tracing::{debug, error};
 
@@ -105,6 +106,59 @@ impl AppleScript {
         Ok(())
     }
 
+    /// Send a message to a chat by its ID (e.g. `iMessage;+;chat123456`)
+    pub fn send_to_chat_id(chat_id: &str, message: &str) -> Result<()> {
+        let script = format!(
+            r#"
+            tell application "Messages"
+                send "{}" to chat id "{}"
+            end tell
+            "#,
+            Self::escape_applescript(message),
+            Self::escape_applescript(chat_id)
+        );
+
+        Self::execute(&script)?;
+        debug!("Message sent to chat id: {}", chat_id);
+        Ok(())
+    }
+
+    /// Send a file to a specific contact (by phone number or email)
+    pub fn send_file(recipient: &str, path: &Path) -> Result<()> {
+        let script = format!(
+            r#"
+            tell application "Messages"
+                set targetService to 1st service whose service type = iMessage
+                set targetBuddy to buddy "{}" of targetService
+                send (POSIX file "{}") to targetBuddy
+            end tell
+            "#,
+            Self::escape_applescript(recipient),
+            Self::escape_applescript(&path.to_string_lossy())
+        );
+
+        Self::execute(&script)?;
+        debug!("File {} sent to {}", path.display(), recipient);
+        Ok(())
+    }
+
+    /// Send a file to a chat by its ID
+    pub fn send_file_to_chat_id(chat_id: &str, path: &Path) -> Result<()> {
+        let script = format!(
+            r#"
+            tell application "Messages"
+                send (POSIX file "{}") to chat id "{}"
+            end tell
+            "#,
+            Self::escape_applescript(&path.to_string_lossy()),
+            Self::escape_applescript(chat_id)
+        );
+
+        Self::execute(&script)?;
+        debug!("File {} sent to chat id: {}", path.display(), chat_id);
+        Ok(())
+    }
+
     /// Get unread messages count
     pub fn get_unread_count() -> Result<i32> {
         let script = r#"
@@ -166,6 +220,7 @@ impl AppleScript {
                         sender: Self::clean_sender(parts[0]),
                         content: parts[1].to_string(),
                         timestamp: parts[2].to_string(),
+                        ..Default::default()
                     })
                 } else {
                     None
@@ -236,11 +291,29 @@ impl AppleScript {
 }
 
 /// Received message information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ReceivedMessage {
     pub sender: String,
     pub content: String,
     pub timestamp: String,
+    /// Message GUID (Messages database and BlueBubbles only)
+    pub guid: Option<String>,
+    /// Chat GUID, e.g. `iMessage;+;chat123456` for a group
+    pub chat_guid: Option<String>,
+    pub attachments: Vec<IncomingAttachment>,
+}
+
+/// Attachment of a received message
+#[derive(Debug, Clone, Default)]
+pub struct IncomingAttachment {
+    /// File name
+    pub name: String,
+    pub mime_type: Option<String>,
+    pub size: Option<u64>,
+    /// Local file (Messages database)
+    pub path: Option<PathBuf>,
+    /// Attachment GUID (BlueBubbles)
+    pub guid: Option<String>,
 }
 
 /// Chat information
@@ -0,0 +1,85 @@
+//! Tapbacks (iMessage reactions)
+//!
+//! Tapbacks are stored as messages with an `associated_message_type` of
+//! 2000-2005 (3000-3005 when removed). AppleScript shows them as text such
+//! as `Liked “...”`; they are reactions, not questions for the bot.
+
+/// Tapback kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tapback {
+    Love,
+    Like,
+    Dislike,
+    Laugh,
+    Emphasize,
+    Question,
+}
+
+impl Tapback {
+    const ALL: [Tapback; 6] = [
+        Self::Love,
+        Self::Like,
+        Self::Dislike,
+        Self::Laugh,
+        Self::Emphasize,
+        Self::Question,
+    ];
+
+    /// Reaction name used by the BlueBubbles API
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Love => "love",
+            Self::Like => "like",
+            Self::Dislike => "dislike",
+            Self::Laugh => "laugh",
+            Self::Emphasize => "emphasize",
+            Self::Question => "question",
+        }
+    }
+
+    /// Parse a reaction name (`like`, `love`, ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// Tapback of a Messages database `associated_message_type`
+    ///
+    /// Returns the kind and whether the tapback was removed.
+    pub fn from_associated_type(value: i64) -> Option<(Self, bool)> {
+        let (base, removed) = match value {
+            2000..=2005 => (2000, false),
+            3000..=3005 => (3000, true),
+            _ => return None,
+        };
+        Some((Self::ALL[(value - base) as usize], removed))
+    }
+
+    /// Whether AppleScript message text is a tapback (`Liked “...”`)
+    pub fn is_legacy_text(text: &str) -> bool {
+        const PREFIXES: [&str; 6] = [
+            "Loved “",
+            "Liked “",
+            "Disliked “",
+            "Laughed at “",
+            "Emphasized “",
+            "Questioned “",
+        ];
+        let text = text.trim();
+        text.ends_with('”') && PREFIXES.iter().any(|p| text.starts_with(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tapback() {
+        assert_eq!(Tapback::from_associated_type(2001), Some((Tapback::Like, false)));
+        assert_eq!(Tapback::from_associated_type(3000), Some((Tapback::Love, true)));
+        assert_eq!(Tapback::from_associated_type(0), None);
+        assert_eq!(Tapback::from_name("laugh"), Some(Tapback::Laugh));
+        assert!(Tapback::is_legacy_text("Liked “See you at 3”"));
+        assert!(!Tapback::is_legacy_text("Liked the movie"));
+    }
+}
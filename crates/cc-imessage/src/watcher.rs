@@ -1,16 +1,22 @@
 //! iMessage watcher for polling new messages
 //!
-//! Periodically checks for new messages via Apple Script
+//! Periodically checks for new messages: with the BlueBubbles backend on
+//! the server, otherwise in the Messages database, falling back to Apple
+//! Script (text only) when the database cannot be read.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::time::interval;
 use tracing::{debug, error, info, warn};
 
+use crate::backend::Backend;
+use crate::bluebubbles::BlueBubblesClient;
+use crate::chatdb::MessagesDb;
 use crate::error::Result;
 use crate::handler::MessageHandler;
-use crate::script::{AppleScript, ChatInfo};
+use crate::script::{AppleScript, ChatInfo, ReceivedMessage};
 
 /// Configuration for the message watcher
 #[derive(Clone, Debug)]
@@ -21,6 +27,8 @@ pub struct WatcherConfig {
     pub watch_chats: Vec<String>,
     /// Maximum messages to process per poll
     pub max_messages_per_poll: usize,
+    /// Messages database (None = poll with Apple Script)
+    pub database_path: Option<PathBuf>,
 }
 
 impl Default for WatcherConfig {
@@ -29,6 +37,7 @@ impl Default for WatcherConfig {
             poll_interval_secs: 5,
             watch_chats: Vec::new(),
             max_messages_per_poll: 10,
+            database_path: MessagesDb::default_path(),
         }
     }
 }
@@ -64,6 +73,15 @@ impl MessageWatcher {
             self.config.poll_interval_secs
         );
 
+        if let Backend::BlueBubbles(client) = self.handler.backend() {
+            let client = Arc::clone(client);
+            return self.watch_bluebubbles(&client).await;
+        }
+
+        if let Some(db) = self.open_database() {
+            return self.watch_database(&db).await;
+        }
+
         // Check Messages.app is available
         if !AppleScript::is_messages_running()? {
             info!("Activating Messages.app...");
@@ -93,6 +111,92 @@ impl MessageWatcher {
         info!("iMessage watcher stopped");
     }
 
+    /// Open the Messages database, if configured and readable
+    fn open_database(&self) -> Option<MessagesDb> {
+        let path = self.config.database_path.as_ref()?;
+        match MessagesDb::open(path) {
+            Ok(db) => Some(db),
+            Err(e) => {
+                warn!(
+                    "Cannot read {} (grant Full Disk Access to read attachments), polling with Apple Script: {}",
+                    path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Poll the Messages database for messages after the newest one
+    async fn watch_database(&self, db: &MessagesDb) -> Result<()> {
+        let mut last_rowid = db.max_rowid()?;
+        info!("Watching the Messages database {}", db.path().display());
+
+        let mut poll_interval = interval(Duration::from_secs(self.config.poll_interval_secs));
+        while self.running.load(std::sync::atomic::Ordering::SeqCst) {
+            poll_interval.tick().await;
+
+            match db.messages_after(last_rowid, self.config.max_messages_per_poll) {
+                Ok(messages) => {
+                    for db_message in messages {
+                        last_rowid = db_message.rowid;
+                        self.dispatch(&db_message.message).await;
+                    }
+                }
+                Err(e) => error!("Error reading the Messages database: {:?}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Poll a BlueBubbles server for messages created after start
+    async fn watch_bluebubbles(&self, client: &BlueBubblesClient) -> Result<()> {
+        client.ping().await?;
+        let mut cursor = chrono::Utc::now().timestamp_millis();
+        info!("Watching BlueBubbles server for new messages");
+
+        let mut poll_interval = interval(Duration::from_secs(self.config.poll_interval_secs));
+        while self.running.load(std::sync::atomic::Ordering::SeqCst) {
+            poll_interval.tick().await;
+
+            match client
+                .messages_after(cursor, self.config.max_messages_per_poll)
+                .await
+            {
+                Ok(messages) => {
+                    for polled in messages {
+                        cursor = cursor.max(polled.date_created);
+                        self.dispatch(&polled.message).await;
+                    }
+                }
+                Err(e) => error!("Error polling BlueBubbles: {:?}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Process `msg` if it is in a watched chat
+    async fn dispatch(&self, msg: &ReceivedMessage) {
+        if !self.is_watched(msg) {
+            return;
+        }
+        debug!("New message from {}", msg.sender);
+        if let Err(e) = self.handler.process_message(msg).await {
+            error!("Error processing message: {:?}", e);
+        }
+    }
+
+    /// Whether `msg` is in a watched chat (by sender or chat GUID)
+    fn is_watched(&self, msg: &ReceivedMessage) -> bool {
+        self.config.watch_chats.is_empty()
+            || self.config.watch_chats.iter().any(|watch| {
+                msg.sender.contains(watch.as_str())
+                    || msg.chat_guid.as_deref().is_some_and(|chat| chat.contains(watch.as_str()))
+            })
+    }
+
     /// Initialize with current messages to avoid processing old ones
     async fn initialize_seen_messages(&self) -> Result<()> {
         let chats = self.get_watched_chats()?;
@@ -212,6 +316,12 @@ impl WatcherBuilder {
         self
     }
 
+    /// Set the Messages database (None = poll with Apple Script)
+    pub fn database_path(mut self, path: Option<PathBuf>) -> Self {
+        self.config.database_path = path;
+        self
+    }
+
     /// Set message handler
     pub fn handler(mut self, handler: Arc<MessageHandler>) -> Self {
         self.handler = Some(handler);
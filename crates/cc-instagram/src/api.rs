@@ -1,20 +1,59 @@
 //! Instagram Graph API client implementation
 
+use cc_core::WebhookVerifier;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::error::{InstagramError, Result};
+use crate::window::Delivery;
 
 /// Instagram API base URL
 const INSTAGRAM_GRAPH_API_URL: &str = "https://graph.instagram.com";
 
+/// App ID of the Page Inbox, where people answer conversations handed over
+/// by the bot
+pub const PAGE_INBOX_APP_ID: &str = "263902037430900";
+
+/// Type of an attachment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentType {
+    Image,
+    Audio,
+    Video,
+    File,
+}
+
+impl AttachmentType {
+    /// Attachment type in the Send API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::Video => "video",
+            Self::File => "file",
+        }
+    }
+
+    /// Type for a MIME type (anything else is sent as a file)
+    pub fn from_mime(mime_type: &str) -> Self {
+        match mime_type.split('/').next().unwrap_or_default() {
+            "image" => Self::Image,
+            "audio" => Self::Audio,
+            "video" => Self::Video,
+            _ => Self::File,
+        }
+    }
+}
+
 /// Instagram Graph API client
 #[derive(Clone)]
 pub struct InstagramApi {
     client: Client,
+    base_url: String,
     access_token: String,
-    #[allow(dead_code)]
+    /// Checks webhook signatures
     app_secret: Option<String>,
     page_id: String,
 }
@@ -23,6 +62,10 @@ pub struct InstagramApi {
 pub struct SendMessageRequest {
     pub recipient: Recipient,
     pub message: MessagePayload,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messaging_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,7 +76,22 @@ pub struct Recipient {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MessagePayload {
-    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<OutgoingAttachment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutgoingAttachment {
+    #[serde(rename = "type")]
+    pub attachment_type: String,
+    pub payload: OutgoingAttachmentPayload,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutgoingAttachmentPayload {
+    pub url: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -56,6 +114,8 @@ pub struct UserProfileResponse {
 pub struct WebhookEntry {
     pub id: String,
     pub messaging: Option<Vec<WebhookMessagingEvent>>,
+    /// Events of conversations another app has control of (handover protocol)
+    pub standby: Option<Vec<WebhookMessagingEvent>>,
     pub changes: Option<Vec<WebhookChange>>,
 }
 
@@ -65,6 +125,24 @@ pub struct WebhookMessagingEvent {
     pub recipient: WebhookRecipient,
     pub timestamp: String,
     pub message: Option<WebhookMessage>,
+    /// Control of the conversation was passed to this app
+    pub pass_thread_control: Option<ThreadControl>,
+    /// Control of the conversation was taken from this app
+    pub take_thread_control: Option<ThreadControl>,
+    /// Another app asked for control of the conversation
+    pub request_thread_control: Option<ThreadControl>,
+    /// Received on standby: another app has control of the conversation
+    #[serde(skip)]
+    pub standby: bool,
+}
+
+/// Handover protocol event
+#[derive(Debug, Deserialize)]
+pub struct ThreadControl {
+    pub new_owner_app_id: Option<String>,
+    pub previous_owner_app_id: Option<String>,
+    pub requested_owner_app_id: Option<String>,
+    pub metadata: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -82,6 +160,14 @@ pub struct WebhookMessage {
     pub mid: Option<String>,
     pub text: Option<String>,
     pub attachments: Option<Vec<WebhookAttachment>>,
+    pub quick_reply: Option<WebhookQuickReply>,
+    /// Set on copies of messages the account sent
+    pub is_echo: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookQuickReply {
+    pub payload: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -114,25 +200,63 @@ impl InstagramApi {
         let client = Client::new();
         Self {
             client,
+            base_url: INSTAGRAM_GRAPH_API_URL.to_string(),
             access_token,
             app_secret,
             page_id,
         }
     }
 
-    /// Send a message to a user via Instagram Direct Message
+    /// Use another Graph API base URL (for tests)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Send a reply to a user via Instagram Direct Message
     pub async fn send_message(&self, psid: &str, text: &str) -> Result<SendMessageResponse> {
+        self.send_text(psid, text, Delivery::Response).await
+    }
+
+    /// Send a text message
+    pub async fn send_text(&self, psid: &str, text: &str, delivery: Delivery) -> Result<SendMessageResponse> {
+        let message = MessagePayload {
+            text: Some(text.to_string()),
+            attachment: None,
+        };
+        self.send(psid, message, delivery).await
+    }
+
+    /// Send an attachment from a public URL
+    pub async fn send_attachment(
+        &self,
+        psid: &str,
+        attachment_type: AttachmentType,
+        url: &str,
+        delivery: Delivery,
+    ) -> Result<SendMessageResponse> {
+        let message = MessagePayload {
+            text: None,
+            attachment: Some(OutgoingAttachment {
+                attachment_type: attachment_type.as_str().to_string(),
+                payload: OutgoingAttachmentPayload { url: url.to_string() },
+            }),
+        };
+        self.send(psid, message, delivery).await
+    }
+
+    async fn send(&self, psid: &str, message: MessagePayload, delivery: Delivery) -> Result<SendMessageResponse> {
         info!("Sending message to PSID: {}", psid);
 
-        let url = format!("{}/v21.0/me/messages", INSTAGRAM_GRAPH_API_URL);
+        let url = format!("{}/v21.0/me/messages", self.base_url);
 
         let request_body = SendMessageRequest {
             recipient: Recipient {
                 psid: psid.to_string(),
             },
-            message: MessagePayload {
-                text: text.to_string(),
-            },
+            message,
+            messaging_type: Some(delivery.messaging_type().to_string()),
+            tag: delivery.tag().map(str::to_string),
         };
 
         let response = self
@@ -175,7 +299,7 @@ impl InstagramApi {
     pub async fn get_user_profile(&self, psid: &str) -> Result<UserProfileResponse> {
         info!("Getting user profile for PSID: {}", psid);
 
-        let url = format!("{}/{}", INSTAGRAM_GRAPH_API_URL, psid);
+        let url = format!("{}/{}", self.base_url, psid);
 
         let response = self
             .client
@@ -204,19 +328,102 @@ impl InstagramApi {
         Ok(profile)
     }
 
+    /// Download a received attachment, returning its bytes and content type
+    pub async fn download_attachment(&self, url: &str) -> Result<(Vec<u8>, String)> {
+        let response = self.client.get(url).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(InstagramError::GraphApi(format!("attachment download failed: {}", status)));
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let bytes = response.bytes().await?;
+        Ok((bytes.to_vec(), content_type))
+    }
+
+    /// Hand the conversation over to another app (handover protocol)
+    ///
+    /// Pass [`PAGE_INBOX_APP_ID`] to escalate to a person in the inbox.
+    pub async fn pass_thread_control(&self, psid: &str, target_app_id: &str, metadata: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "recipient": { "id": psid },
+            "target_app_id": target_app_id,
+            "metadata": metadata,
+        });
+        self.thread_control("pass_thread_control", &body).await?;
+        info!("Passed thread control for {} to app {}", psid, target_app_id);
+        Ok(())
+    }
+
+    /// Take the conversation back from the app that has it
+    /// (the bot must be the primary receiver)
+    pub async fn take_thread_control(&self, psid: &str, metadata: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "recipient": { "id": psid },
+            "metadata": metadata,
+        });
+        self.thread_control("take_thread_control", &body).await?;
+        info!("Took thread control for {}", psid);
+        Ok(())
+    }
+
+    /// Ask the primary receiver to hand the conversation over
+    pub async fn request_thread_control(&self, psid: &str, metadata: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "recipient": { "id": psid },
+            "metadata": metadata,
+        });
+        self.thread_control("request_thread_control", &body).await
+    }
+
+    async fn thread_control(&self, endpoint: &str, body: &serde_json::Value) -> Result<()> {
+        let url = format!("{}/v21.0/me/{}", self.base_url, endpoint);
+
+        let response = self
+            .client
+            .post(&url)
+            .query(&[("access_token", &self.access_token)])
+            .json(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            error!("Instagram API error: {} - {}", status, body);
+            return Err(InstagramError::GraphApi(format!(
+                "Status: {}, Body: {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Handle incoming webhook
+    ///
+    /// Events received on standby (another app has control of the
+    /// conversation) are included with `standby` set.
     pub fn handle_webhook(&self, payload: &str) -> Result<Vec<WebhookMessagingEvent>> {
         let webhook: WebhookPayloadInstagram = serde_json::from_str(payload)?;
 
         let mut events = Vec::new();
 
         for entry in webhook.entry {
-            if let Some(messaging) = entry.messaging {
-                for event in messaging {
-                    // Only handle messages sent to our page
-                    if event.recipient.id == self.page_id {
-                        events.push(event);
-                    }
+            let standby = entry.standby.unwrap_or_default().into_iter().map(|mut event| {
+                event.standby = true;
+                event
+            });
+            for event in entry.messaging.unwrap_or_default().into_iter().chain(standby) {
+                // Only handle messages sent to our page
+                if event.recipient.id == self.page_id {
+                    events.push(event);
                 }
             }
         }
@@ -224,6 +431,20 @@ impl InstagramApi {
         Ok(events)
     }
 
+    /// Check the `X-Hub-Signature-256` header of a webhook request
+    pub fn verify_signature(&self, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        let Some(ref secret) = self.app_secret else {
+            return Err(InstagramError::AppSecretNotSet);
+        };
+        let verifier = WebhookVerifier::Meta {
+            app_secret: secret.clone(),
+        };
+        verifier.verify(headers, "", body).map_err(|e| {
+            warn!("Rejected webhook: {}", e);
+            InstagramError::WebhookVerificationFailed
+        })
+    }
+
     /// Verify webhook challenge (for initial setup)
     pub fn verify_webhook_challenge(
         &self,
@@ -256,9 +477,118 @@ impl Default for InstagramApi {
     fn default() -> Self {
         Self {
             client: Client::new(),
+            base_url: INSTAGRAM_GRAPH_API_URL.to_string(),
             access_token: String::new(),
             app_secret: None,
             page_id: String::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::MessageTag;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn api() -> InstagramApi {
+        InstagramApi::new("token".to_string(), "ig123".to_string(), None)
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let body = br#"{"object":"instagram","entry":[]}"#;
+        let mut signed = HeaderMap::new();
+        signed.insert(
+            "x-hub-signature-256",
+            "sha256=2a44ea874f92f773c67858c85ecd6e97bed41f00f466ba204b6ae1a03cf68d83".parse().unwrap(),
+        );
+        assert!(matches!(api().verify_signature(&signed, body), Err(InstagramError::AppSecretNotSet)));
+
+        let api = InstagramApi::new("token".to_string(), "ig123".to_string(), Some("secret".to_string()));
+        assert!(api.verify_signature(&signed, body).is_ok());
+        assert!(api.verify_signature(&signed, b"{}").is_err());
+        assert!(api.verify_signature(&HeaderMap::new(), body).is_err());
+    }
+
+    #[test]
+    fn test_webhook_standby_and_attachments() {
+        let payload = r#"{
+            "object": "instagram",
+            "entry": [{
+                "id": "ig123",
+                "messaging": [{
+                    "sender": {"id": "user1"},
+                    "recipient": {"id": "ig123"},
+                    "timestamp": "1700000000000",
+                    "message": {"mid": "m1", "attachments": [
+                        {"type": "image", "payload": {"url": "https://lookaside.fbsbx.com/a.jpg"}}
+                    ]}
+                }],
+                "standby": [{
+                    "sender": {"id": "user2"},
+                    "recipient": {"id": "ig123"},
+                    "timestamp": "1700000000001",
+                    "message": {"mid": "m2", "text": "Hi"}
+                }]
+            }]
+        }"#;
+
+        let events = api().handle_webhook(payload).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(!events[0].standby);
+        let attachments = events[0].message.as_ref().unwrap().attachments.as_ref().unwrap();
+        assert_eq!(attachments[0].attachment_type, "image");
+        assert!(events[1].standby);
+        assert_eq!(events[1].sender.id, "user2");
+    }
+
+    #[tokio::test]
+    async fn test_send_text_with_human_agent_tag() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v21.0/me/messages"))
+            .and(body_partial_json(json!({
+                "recipient": { "id": "user1" },
+                "message": { "text": "Following up" },
+                "messaging_type": "MESSAGE_TAG",
+                "tag": "HUMAN_AGENT",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "recipient_id": "user1", "message_id": "m_1",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = api()
+            .with_base_url(&server.uri())
+            .send_text("user1", "Following up", Delivery::Tagged(MessageTag::HumanAgent))
+            .await
+            .unwrap();
+        assert_eq!(response.message_id.as_deref(), Some("m_1"));
+    }
+
+    #[tokio::test]
+    async fn test_pass_thread_control() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v21.0/me/pass_thread_control"))
+            .and(body_partial_json(json!({
+                "recipient": { "id": "user1" },
+                "target_app_id": PAGE_INBOX_APP_ID,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "success": true })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        api()
+            .with_base_url(&server.uri())
+            .pass_thread_control("user1", PAGE_INBOX_APP_ID, "escalated")
+            .await
+            .unwrap();
+    }
+}
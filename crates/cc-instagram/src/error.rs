@@ -17,6 +17,9 @@ pub enum InstagramError {
     #[error("Instagram Graph API error: {0}")]
     GraphApi(String),
 
+    #[error("Messaging window with {0} is closed and no usable message tag was given")]
+    OutsideMessagingWindow(String),
+
     #[error("Webhook verification failed")]
     WebhookVerificationFailed,
 
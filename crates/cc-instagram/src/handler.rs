@@ -1,14 +1,25 @@
 //! Message handler for Instagram bot
 
+use std::collections::HashSet;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 use cc_core::ClaudeClient;
 use cc_core::Message;
+use cc_voice::WhisperClient;
 
-use crate::api::{InstagramApi, WebhookMessagingEvent};
+use crate::api::{AttachmentType, InstagramApi, SendMessageResponse, WebhookMessagingEvent, PAGE_INBOX_APP_ID};
 use crate::error::Result;
+use crate::media::MessageInput;
 use crate::session::InMemorySessionStore;
+use crate::window::{MessageTag, MessagingWindow};
+
+/// Quick reply payload that asks for a person
+pub const HUMAN_AGENT_PAYLOAD: &str = "HUMAN_AGENT";
+
+/// Command that asks for a person
+const HUMAN_COMMAND: &str = "/human";
 
 /// Split a message into chunks at sentence boundaries
 fn split_message(text: &str, max_size: usize) -> Vec<String> {
@@ -53,6 +64,10 @@ pub struct HandlerState {
     pub claude_client: Arc<ClaudeClient>,
     pub session_store: InMemorySessionStore,
     pub admin_psids: Vec<String>,
+    /// Time of each user's last message
+    pub window: MessagingWindow,
+    /// Conversations handed over to a person (another app has control)
+    pub human_threads: Arc<RwLock<HashSet<String>>>,
 }
 
 /// Instagram message handler
@@ -60,6 +75,9 @@ pub struct HandlerState {
 pub struct InstagramHandler {
     api: InstagramApi,
     state: Arc<HandlerState>,
+    /// App that receives escalated conversations
+    handover_app_id: String,
+    transcriber: Option<Arc<WhisperClient>>,
 }
 
 impl InstagramHandler {
@@ -73,12 +91,49 @@ impl InstagramHandler {
             claude_client,
             session_store: InMemorySessionStore::new(),
             admin_psids,
+            window: MessagingWindow::new(),
+            human_threads: Arc::new(RwLock::new(HashSet::new())),
         });
 
         Self {
             api,
             state,
+            handover_app_id: PAGE_INBOX_APP_ID.to_string(),
+            transcriber: None,
+        }
+    }
+
+    /// Transcribe voice messages with Whisper
+    pub fn with_transcriber(mut self, transcriber: Arc<WhisperClient>) -> Self {
+        self.transcriber = Some(transcriber);
+        self
+    }
+
+    /// Escalate conversations to this app instead of the inbox
+    pub fn with_handover_app_id(mut self, app_id: &str) -> Self {
+        self.handover_app_id = app_id.to_string();
+        self
+    }
+
+    /// Process a webhook request after checking its `X-Hub-Signature-256`
+    /// header, returning the replies sent
+    ///
+    /// Fails without processing anything when no app secret is configured
+    /// or the signature does not match.
+    pub async fn process_signed_webhook(
+        &self,
+        headers: &reqwest::header::HeaderMap,
+        body: &[u8],
+    ) -> Result<Vec<String>> {
+        self.api.verify_signature(headers, body)?;
+        let payload = String::from_utf8_lossy(body);
+        let mut replies = Vec::new();
+        for event in self.api.handle_webhook(&payload)? {
+            if let Some(reply) = self.handle_event(event).await? {
+                replies.push(reply);
+            }
         }
+        Ok(replies)
     }
 
     /// Handle incoming webhook event
@@ -93,27 +148,87 @@ impl InstagramHandler {
             return Ok(None);
         }
 
-        // Get message text
-        let message_text = match &event.message {
-            Some(msg) => msg.text.clone().unwrap_or_default(),
-            None => return Ok(None),
+        // Handover protocol events
+        if event.pass_thread_control.is_some() {
+            info!("Conversation with {} handed back to the bot", sender_psid);
+            self.state.human_threads.write().await.remove(&sender_psid);
+            return Ok(None);
+        }
+        if event.take_thread_control.is_some() {
+            info!("Conversation with {} taken over by another app", sender_psid);
+            self.state.human_threads.write().await.insert(sender_psid);
+            return Ok(None);
+        }
+        if let Some(request) = &event.request_thread_control {
+            info!(
+                "App {:?} requested control of the conversation with {}",
+                request.requested_owner_app_id, sender_psid
+            );
+            return Ok(None);
+        }
+
+        let message = match &event.message {
+            Some(msg) if msg.is_echo != Some(true) => msg,
+            _ => return Ok(None),
         };
 
-        if message_text.is_empty() {
+        self.state.window.record(&sender_psid).await;
+
+        // Another app (a person) has the conversation
+        if event.standby {
+            self.state.human_threads.write().await.insert(sender_psid);
+            return Ok(None);
+        }
+        if self.is_with_human(&sender_psid).await {
+            debug!("Conversation with {} is handled by a person", sender_psid);
+            return Ok(None);
+        }
+
+        // Escalation to a person
+        let wants_human = message
+            .quick_reply
+            .as_ref()
+            .is_some_and(|q| q.payload == HUMAN_AGENT_PAYLOAD)
+            || message.text.as_deref().map(str::trim) == Some(HUMAN_COMMAND);
+        if wants_human {
+            self.escalate_to_human(&sender_psid).await?;
+            return Ok(None);
+        }
+
+        let input = MessageInput::collect(
+            &self.api,
+            message.text.as_deref(),
+            message.attachments.as_deref().unwrap_or_default(),
+            self.transcriber.as_deref(),
+        )
+        .await;
+        if input.is_empty() {
             return Ok(None);
         }
 
         info!(
             "Processing message from PSID {}: {}",
-            sender_psid, message_text
+            sender_psid, input.text
         );
 
         // Get or create session
         let session = self.state.session_store.get_or_create(&sender_psid).await;
 
+        // Images are sent once and only noted in the history
+        let image_count = input.images.len();
+        let message_text = if input.text.trim().is_empty() {
+            "Describe this image.".to_string()
+        } else {
+            input.text
+        };
+
         // Build message history
         let mut messages: Vec<Message> = session.messages.clone();
-        messages.push(Message::user(&message_text));
+        messages.push(if input.images.is_empty() {
+            Message::user(&message_text)
+        } else {
+            Message::user_with_images(&message_text, input.images)
+        });
 
         // Build request with conversation history
         let mut request_builder = self
@@ -148,9 +263,14 @@ impl InstagramHandler {
                     .join("\n");
 
                 // Update session
+                let history_text = if image_count == 0 {
+                    message_text
+                } else {
+                    format!("{}\n[{} image(s) attached]", message_text, image_count)
+                };
                 self.state
                     .session_store
-                    .add_message(&sender_psid, Message::user(&message_text))
+                    .add_message(&sender_psid, Message::user(history_text))
                     .await;
                 self.state
                     .session_store
@@ -218,12 +338,55 @@ impl InstagramHandler {
                 mid: Some(uuid::Uuid::new_v4().to_string()),
                 text: Some(text.to_string()),
                 attachments: None,
+                quick_reply: None,
+                is_echo: None,
             }),
+            pass_thread_control: None,
+            take_thread_control: None,
+            request_thread_control: None,
+            standby: false,
         };
 
         self.handle_event(event).await
     }
 
+    /// Hand the conversation over to a person
+    pub async fn escalate_to_human(&self, psid: &str) -> Result<()> {
+        self.api
+            .send_message(psid, "Connecting you with a person. Someone from our team will reply here soon.")
+            .await?;
+        self.api
+            .pass_thread_control(psid, &self.handover_app_id, "Escalated by the user")
+            .await?;
+        self.state.human_threads.write().await.insert(psid.to_string());
+        Ok(())
+    }
+
+    /// Whether the conversation is handled by a person
+    pub async fn is_with_human(&self, psid: &str) -> bool {
+        self.state.human_threads.read().await.contains(psid)
+    }
+
+    /// Send a message outside a reply (notifications, messages from a person)
+    ///
+    /// Outside the 24-hour window the message needs the `HUMAN_AGENT` tag.
+    pub async fn send_text(&self, psid: &str, text: &str, tag: Option<MessageTag>) -> Result<SendMessageResponse> {
+        let delivery = self.state.window.delivery(psid, tag).await?;
+        self.api.send_text(psid, text, delivery).await
+    }
+
+    /// Send an attachment from a public URL outside a reply
+    pub async fn send_attachment(
+        &self,
+        psid: &str,
+        attachment_type: AttachmentType,
+        url: &str,
+        tag: Option<MessageTag>,
+    ) -> Result<SendMessageResponse> {
+        let delivery = self.state.window.delivery(psid, tag).await?;
+        self.api.send_attachment(psid, attachment_type, url, delivery).await
+    }
+
     /// Clear conversation history for a user
     pub async fn clear_session(&self, psid: &str) {
         self.state.session_store.clear(psid).await;
@@ -244,3 +407,70 @@ impl InstagramHandler {
         &self.state
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cc_core::{ApiConfig, Config, LlmConfig, LlmProvider, McpConfig, MemoryConfig, SchedulerConfig};
+
+    fn mock_config() -> Config {
+        Config {
+            llm: LlmConfig {
+                api_key: "test-key".to_string(),
+                model: "claude-sonnet-4-20250514".to_string(),
+                provider: LlmProvider::Claude,
+                base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
+            },
+            claude_api_key: "test-key".to_string(),
+            claude_model: "claude-sonnet-4-20250514".to_string(),
+            discord_token: None,
+            admin_user_ids: vec![],
+            api: ApiConfig::default(),
+            api_key: None,
+            memory: MemoryConfig::default(),
+            mcp: McpConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handover_events() {
+        let claude_client = Arc::new(ClaudeClient::new(&mock_config()).unwrap());
+        let api = InstagramApi::new("token".to_string(), "ig123".to_string(), None);
+        let handler = InstagramHandler::new(api, claude_client, Vec::new());
+
+        // A message on standby: another app (a person) has the conversation
+        let standby = r#"{"object": "instagram", "entry": [{"id": "ig123", "standby": [
+            {"sender": {"id": "user1"}, "recipient": {"id": "ig123"}, "timestamp": "1", "message": {"mid": "m1", "text": "Hi"}}
+        ]}]}"#;
+        for event in handler.api().handle_webhook(standby).unwrap() {
+            assert!(handler.handle_event(event).await.unwrap().is_none());
+        }
+        assert!(handler.is_with_human("user1").await);
+        assert!(handler.state().window.is_open("user1").await);
+
+        // Control passed back to the bot
+        let passed = r#"{"object": "instagram", "entry": [{"id": "ig123", "messaging": [
+            {"sender": {"id": "user1"}, "recipient": {"id": "ig123"}, "timestamp": "2", "pass_thread_control": {"new_owner_app_id": "1"}}
+        ]}]}"#;
+        for event in handler.api().handle_webhook(passed).unwrap() {
+            handler.handle_event(event).await.unwrap();
+        }
+        assert!(!handler.is_with_human("user1").await);
+
+        // Proactive messages need the HUMAN_AGENT tag outside the window
+        assert!(matches!(
+            handler.send_text("user2", "Hello", None).await,
+            Err(crate::error::InstagramError::OutsideMessagingWindow(_))
+        ));
+    }
+}
@@ -7,9 +7,14 @@
 pub mod api;
 pub mod error;
 pub mod handler;
+pub mod media;
 pub mod session;
+pub mod webhook;
+pub mod window;
 
-pub use api::InstagramApi;
+pub use api::{AttachmentType, InstagramApi, PAGE_INBOX_APP_ID};
 pub use error::{InstagramError, Result};
 pub use handler::InstagramHandler;
 pub use session::InMemorySessionStore;
+pub use webhook::{start_webhook_server, WebhookState};
+pub use window::{Delivery, MessageTag, MessagingWindow};
@@ -0,0 +1,169 @@
+//! Webhook server for receiving Instagram messages
+//!
+//! Meta verifies the webhook with a GET request carrying the verify token
+//! and posts events signed with the app secret (`X-Hub-Signature-256`).
+//! Requests with an invalid signature are rejected.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use tracing::{error, info, warn};
+
+use crate::error::{InstagramError, Result};
+use crate::handler::InstagramHandler;
+
+/// Path of the webhook endpoint
+pub const WEBHOOK_PATH: &str = "/webhook/instagram";
+
+/// Webhook server state
+#[derive(Clone)]
+pub struct WebhookState {
+    pub handler: InstagramHandler,
+    /// Token entered when registering the webhook
+    pub verify_token: String,
+}
+
+/// Router with the webhook endpoint
+pub fn create_webhook_router(state: WebhookState) -> Router {
+    Router::new()
+        .route(WEBHOOK_PATH, get(verify_webhook).post(handle_webhook))
+        .with_state(Arc::new(state))
+}
+
+/// Serve the webhook on `port` until the server fails
+pub async fn start_webhook_server(state: WebhookState, port: u16) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| InstagramError::Config(e.to_string()))?;
+    info!("Instagram webhook server listening on {}", addr);
+
+    axum::serve(listener, create_webhook_router(state))
+        .await
+        .map_err(|e| InstagramError::Config(e.to_string()))
+}
+
+/// Answer the verification request sent when the webhook is registered
+async fn verify_webhook(
+    State(state): State<Arc<WebhookState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let param = |name: &str| params.get(name).map(String::as_str).unwrap_or_default();
+    if param("hub.verify_token") != state.verify_token {
+        warn!("Rejected Instagram webhook verification with a wrong token");
+        return (StatusCode::FORBIDDEN, String::new());
+    }
+    match state.handler.api().verify_webhook_challenge(
+        param("hub.mode"),
+        param("hub.verify_token"),
+        param("hub.challenge"),
+    ) {
+        Ok(challenge) => (StatusCode::OK, challenge),
+        Err(_) => (StatusCode::FORBIDDEN, String::new()),
+    }
+}
+
+/// Handle incoming events
+///
+/// Meta retries webhooks that are not answered quickly, so events are
+/// processed after responding.
+async fn handle_webhook(
+    State(state): State<Arc<WebhookState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    if state.handler.api().verify_signature(&headers, &body).is_err() {
+        warn!("Rejected Instagram webhook with invalid signature");
+        return StatusCode::UNAUTHORIZED;
+    }
+    let events = match state.handler.api().handle_webhook(&String::from_utf8_lossy(&body)) {
+        Ok(events) => events,
+        Err(e) => {
+            warn!("Invalid Instagram webhook payload: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    for event in events {
+        let handler = state.handler.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handler.handle_event(event).await {
+                error!("Error handling Instagram event: {}", e);
+            }
+        });
+    }
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::InstagramApi;
+    use cc_core::{ApiConfig, Config, LlmConfig, McpConfig, MemoryConfig, SchedulerConfig};
+
+    fn state() -> Arc<WebhookState> {
+        let config = Config {
+            llm: LlmConfig {
+                api_key: "test-key".to_string(),
+                ..Default::default()
+            },
+            claude_api_key: "test-key".to_string(),
+            claude_model: "claude-sonnet-4-20250514".to_string(),
+            discord_token: None,
+            admin_user_ids: vec![],
+            api: ApiConfig::default(),
+            api_key: None,
+            memory: MemoryConfig::default(),
+            mcp: McpConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
+        };
+        let client = Arc::new(cc_core::ClaudeClient::new(&config).unwrap());
+        let api = InstagramApi::new("token".to_string(), "page".to_string(), Some("secret".to_string()));
+        Arc::new(WebhookState {
+            handler: InstagramHandler::new(api, client, vec![]),
+            verify_token: "verify".to_string(),
+        })
+    }
+
+    fn params(token: &str) -> Query<HashMap<String, String>> {
+        Query(HashMap::from([
+            ("hub.mode".to_string(), "subscribe".to_string()),
+            ("hub.verify_token".to_string(), token.to_string()),
+            ("hub.challenge".to_string(), "42".to_string()),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn test_verify_webhook() {
+        let response = verify_webhook(State(state()), params("verify")).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+        assert_eq!(&body[..], b"42");
+
+        let response = verify_webhook(State(state()), params("wrong")).await.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unsigned_events() {
+        let response = handle_webhook(State(state()), HeaderMap::new(), Bytes::from_static(b"{}"))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,347 @@
+//! Knowledge base
+//!
+//! Ties sources, chunking, embeddings and the index together. Sources are
+//! files, folders (read recursively) and URLs. Indexing skips documents whose
+//! content and embedding model have not changed, and drops documents whose
+//! source disappeared.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use cc_core::{ContextProvider, KnowledgeConfig};
+
+use crate::chunk::chunk_text;
+use crate::embed::{Embedder, embedder_from_config};
+use crate::error::{KnowledgeError, Result};
+use crate::extract::{SourceDocument, document_from_bytes};
+use crate::store::{DocumentInfo, KnowledgeStore, NewChunk, SearchHit};
+
+/// Files larger than this are not indexed
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Timeout for fetching a URL source
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Result of indexing the sources
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IndexReport {
+    /// Documents (re-)indexed
+    pub indexed: usize,
+    /// Documents already up to date
+    pub unchanged: usize,
+    /// Documents removed because their source is gone
+    pub removed: usize,
+    /// Documents that could not be read or embedded
+    pub failed: usize,
+}
+
+/// Outcome of ingesting one document
+enum Ingested {
+    Indexed,
+    Unchanged,
+    Unsupported,
+}
+
+/// Searchable index of user-provided documents
+pub struct KnowledgeBase {
+    store: KnowledgeStore,
+    embedder: Arc<dyn Embedder>,
+    http: reqwest::Client,
+    config: KnowledgeConfig,
+}
+
+impl KnowledgeBase {
+    /// Open the index and embedder configured in `[knowledge]`
+    pub fn open(config: &KnowledgeConfig) -> Result<Self> {
+        let store = KnowledgeStore::new(&config.db_path)?;
+        let embedder = embedder_from_config(&config.embedding)?;
+        Ok(Self::new(store, embedder, config.clone()))
+    }
+
+    pub fn new(store: KnowledgeStore, embedder: Arc<dyn Embedder>, config: KnowledgeConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            store,
+            embedder,
+            http,
+            config,
+        }
+    }
+
+    /// Index all configured sources
+    pub async fn index_sources(&self) -> Result<IndexReport> {
+        let mut report = IndexReport::default();
+        let mut seen = HashSet::new();
+        // Documents are only removed when every source could be listed
+        let mut listed_all = true;
+
+        for source in &self.config.sources {
+            if is_url(source) {
+                seen.insert(source.clone());
+                self.record(&mut report, source, self.ingest_url(source).await);
+                continue;
+            }
+            let files = match collect_files(Path::new(source)) {
+                Ok(files) => files,
+                Err(e) => {
+                    warn!("Failed to read knowledge source {}: {}", source, e);
+                    report.failed += 1;
+                    listed_all = false;
+                    continue;
+                }
+            };
+            for file in files {
+                let path = file.to_string_lossy().to_string();
+                seen.insert(path.clone());
+                let result = self.ingest_path(&file).await;
+                self.record(&mut report, &path, result);
+            }
+        }
+
+        if listed_all {
+            for source in self.store.sources()? {
+                if !seen.contains(&source) {
+                    self.store.remove_document(&source)?;
+                    report.removed += 1;
+                }
+            }
+        }
+        info!(
+            "Knowledge base indexed: {} indexed, {} unchanged, {} removed, {} failed",
+            report.indexed, report.unchanged, report.removed, report.failed
+        );
+        Ok(report)
+    }
+
+    fn record(&self, report: &mut IndexReport, source: &str, result: Result<Ingested>) {
+        match result {
+            Ok(Ingested::Indexed) => report.indexed += 1,
+            Ok(Ingested::Unchanged) => report.unchanged += 1,
+            Ok(Ingested::Unsupported) => debug!("Skipping unsupported document {}", source),
+            Err(e) => {
+                warn!("Failed to index {}: {}", source, e);
+                report.failed += 1;
+            }
+        }
+    }
+
+    async fn ingest_path(&self, path: &Path) -> Result<Ingested> {
+        let bytes = tokio::fs::read(path).await?;
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        match document_from_bytes(&path.to_string_lossy(), &name, None, &bytes) {
+            Some(document) => self.ingest(document).await,
+            None => Ok(Ingested::Unsupported),
+        }
+    }
+
+    async fn ingest_url(&self, url: &str) -> Result<Ingested> {
+        let response = self.http.get(url).send().await?.error_for_status()?;
+        let media_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = response.bytes().await?;
+        let name = url.trim_end_matches('/').rsplit('/').next().unwrap_or(url);
+        match document_from_bytes(url, name, media_type.as_deref(), &bytes) {
+            Some(document) => self.ingest(document).await,
+            None => Ok(Ingested::Unsupported),
+        }
+    }
+
+    async fn ingest(&self, document: SourceDocument) -> Result<Ingested> {
+        let hash = hex::encode(Sha256::digest(document.text.as_bytes()));
+        let state = self.store.document_state(&document.source)?;
+        if state.as_ref().is_some_and(|(h, e)| *h == hash && e == self.embedder.id()) {
+            return Ok(Ingested::Unchanged);
+        }
+
+        let texts = chunk_text(&document.text, self.config.chunk_size, self.config.chunk_overlap);
+        let embeddings = self.embedder.embed(&texts).await?;
+        if embeddings.len() != texts.len() {
+            return Err(KnowledgeError::Embedding(format!(
+                "expected {} embeddings, got {}",
+                texts.len(),
+                embeddings.len()
+            )));
+        }
+        let chunks: Vec<NewChunk> = texts
+            .into_iter()
+            .zip(embeddings)
+            .map(|(content, embedding)| NewChunk { content, embedding })
+            .collect();
+        self.store
+            .replace_document(&document.source, &document.title, &hash, self.embedder.id(), &chunks)?;
+        debug!("Indexed {} ({} chunks)", document.source, chunks.len());
+        Ok(Ingested::Indexed)
+    }
+
+    /// The `limit` chunks most relevant to `query`
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let mut embeddings = self.embedder.embed(&[query.to_string()]).await?;
+        let Some(embedding) = embeddings.pop() else {
+            return Ok(Vec::new());
+        };
+        self.store.search(&embedding, limit)
+    }
+
+    /// All indexed documents
+    pub fn documents(&self) -> Result<Vec<DocumentInfo>> {
+        self.store.list_documents()
+    }
+
+    /// Index the sources now and then every `reindex_interval_secs`
+    /// (only once when it is 0)
+    pub fn spawn_indexer(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let interval = self.config.reindex_interval_secs;
+            loop {
+                if let Err(e) = self.index_sources().await {
+                    warn!("Failed to index the knowledge base: {}", e);
+                }
+                if interval == 0 {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl ContextProvider for KnowledgeBase {
+    async fn context(&self, query: &str) -> cc_core::Result<Option<String>> {
+        if self.config.top_k == 0 || query.trim().is_empty() {
+            return Ok(None);
+        }
+        let hits: Vec<SearchHit> = self
+            .search(query, self.config.top_k)
+            .await?
+            .into_iter()
+            .filter(|hit| hit.score >= self.config.min_score)
+            .collect();
+        if hits.is_empty() {
+            return Ok(None);
+        }
+
+        let mut section = String::from(
+            "## Relevant documents\n\nPassages from the knowledge base that may help answer the message. \
+             Cite the source when you use them.",
+        );
+        for hit in hits {
+            section.push_str(&format!("\n\n### {} ({})\n{}", hit.title, hit.source, hit.content));
+        }
+        Ok(Some(section))
+    }
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// Files under `path` (itself if it is a file), skipping hidden entries and
+/// files over [`MAX_FILE_BYTES`]
+fn collect_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let metadata = std::fs::metadata(path)?;
+    if metadata.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+            } else if metadata.is_file() && metadata.len() <= MAX_FILE_BYTES {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embed::HashEmbedder;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn knowledge_base(sources: Vec<String>) -> KnowledgeBase {
+        let config = KnowledgeConfig {
+            sources,
+            chunk_size: 200,
+            chunk_overlap: 40,
+            ..Default::default()
+        };
+        KnowledgeBase::new(KnowledgeStore::in_memory().unwrap(), Arc::new(HashEmbedder::new()), config)
+    }
+
+    #[tokio::test]
+    async fn test_index_and_search() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("policies")).unwrap();
+        std::fs::write(
+            dir.path().join("policies/returns.md"),
+            "Returns are accepted within 30 days with the receipt.",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("shipping.txt"), "Shipping takes three business days.").unwrap();
+        std::fs::write(dir.path().join(".hidden.md"), "Secret returns note").unwrap();
+        std::fs::write(dir.path().join("logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        let kb = knowledge_base(vec![dir.path().to_string_lossy().to_string()]);
+        let report = kb.index_sources().await.unwrap();
+        assert_eq!(report, IndexReport { indexed: 2, ..Default::default() });
+
+        let hits = kb.search("how many days for returns", 1).await.unwrap();
+        assert!(hits[0].source.ends_with("returns.md"));
+
+        // Unchanged files are skipped, deleted ones removed
+        std::fs::remove_file(dir.path().join("shipping.txt")).unwrap();
+        let report = kb.index_sources().await.unwrap();
+        assert_eq!(report, IndexReport { unchanged: 1, removed: 1, ..Default::default() });
+        assert_eq!(kb.documents().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_url_source_and_context() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/faq"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "<html><head><title>FAQ</title></head><body><p>The office opens at nine.</p></body></html>",
+                "text/html; charset=utf-8",
+            ))
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/faq", server.uri());
+        let kb = knowledge_base(vec![url.clone()]);
+        assert_eq!(kb.index_sources().await.unwrap().indexed, 1);
+
+        let context = kb.context("When does the office open?").await.unwrap().unwrap();
+        assert!(context.contains(&format!("### FAQ ({})", url)));
+        assert!(context.contains("The office opens at nine."));
+        assert!(kb.context("zebra giraffe").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,114 @@
+//! Splitting documents into chunks
+//!
+//! Paragraphs are kept together while they fit in a chunk. Longer paragraphs
+//! are cut at the end of a sentence or at whitespace. Each chunk starts with
+//! the end of the previous one, so a passage cut in two is still found whole.
+
+/// Characters after which a paragraph may be cut
+const SENTENCE_ENDS: &[char] = &['.', '!', '?', '。', '！', '？', '\n'];
+
+/// Split `text` into chunks of at most about `size` characters, consecutive
+/// chunks sharing up to `overlap` characters
+pub fn chunk_text(text: &str, size: usize, overlap: usize) -> Vec<String> {
+    let size = size.max(1);
+    let overlap = overlap.min(size / 2);
+    let text = text.replace("\r\n", "\n");
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let paragraphs = text.split("\n\n").map(str::trim).filter(|p| !p.is_empty());
+    for piece in paragraphs.flat_map(|p| split_long(p, size - overlap)) {
+        if !current.is_empty() && char_len(&current) + 2 + char_len(piece) > size {
+            let carried = tail(&current, overlap).to_string();
+            chunks.push(std::mem::replace(&mut current, carried));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(piece);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Cut a paragraph into pieces of at most `max` characters
+fn split_long(paragraph: &str, max: usize) -> Vec<&str> {
+    let max = max.max(1);
+    let mut pieces = Vec::new();
+    let mut rest = paragraph;
+    while char_len(rest) > max {
+        let limit = rest.char_indices().nth(max).map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..limit];
+        // Prefer a sentence end, then whitespace, in the second half of the window
+        let half = window.char_indices().nth(max / 2).map_or(0, |(i, _)| i);
+        let cut = window[half..]
+            .rfind(SENTENCE_ENDS)
+            .map(|i| half + i + window[half + i..].chars().next().map_or(1, char::len_utf8))
+            .or_else(|| window[half..].rfind(char::is_whitespace).map(|i| half + i))
+            .unwrap_or(limit);
+        let (piece, next) = rest.split_at(cut);
+        let piece = piece.trim();
+        if !piece.is_empty() {
+            pieces.push(piece);
+        }
+        rest = next.trim_start();
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+/// The last `n` characters of `text`, starting at a word boundary when there is one
+fn tail(text: &str, n: usize) -> &str {
+    if n == 0 {
+        return "";
+    }
+    let start = text.char_indices().rev().nth(n - 1).map_or(0, |(i, _)| i);
+    let tail = &text[start..];
+    if start == 0 {
+        return tail;
+    }
+    match tail.find(char::is_whitespace) {
+        Some(i) if !tail[i..].trim().is_empty() => tail[i..].trim_start(),
+        _ => tail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_one_chunk() {
+        assert_eq!(chunk_text("Hello.\n\nWorld.", 100, 20), vec!["Hello.\n\nWorld."]);
+        assert!(chunk_text(" \n\n ", 100, 20).is_empty());
+    }
+
+    #[test]
+    fn test_paragraphs_and_overlap() {
+        let text = "First paragraph about cats.\n\nSecond paragraph about dogs.\n\nThird paragraph about birds.";
+        let chunks = chunk_text(text, 60, 20);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].starts_with("First") && chunks[0].contains("dogs"));
+        // The second chunk repeats the end of the first
+        assert!(chunks[1].starts_with("about dogs."));
+        assert!(chunks[1].ends_with("birds."));
+        assert!(chunks.iter().all(|c| char_len(c) <= 62));
+    }
+
+    #[test]
+    fn test_long_paragraph_is_cut_at_sentences() {
+        let text = "これは最初の文です。".repeat(30);
+        let chunks = chunk_text(&text, 100, 0);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| char_len(c) <= 100 && c.ends_with('。')));
+        assert_eq!(chunks.concat(), text);
+    }
+}
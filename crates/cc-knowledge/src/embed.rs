@@ -0,0 +1,280 @@
+//! Embedding models
+//!
+//! [`HashEmbedder`] works offline by hashing the words of a text into a
+//! fixed-size vector; it matches passages that share words with the query.
+//! [`OpenAiEmbedder`] calls an OpenAI-compatible `/embeddings` endpoint for
+//! semantic matching.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use cc_core::{EmbeddingConfig, EmbeddingProvider};
+
+use crate::error::{KnowledgeError, Result};
+
+/// Dimensions of [`HashEmbedder`] vectors
+const HASH_DIMENSIONS: usize = 1024;
+
+/// Default model of [`OpenAiEmbedder`]
+const DEFAULT_OPENAI_MODEL: &str = "text-embedding-3-small";
+
+/// Default endpoint of [`OpenAiEmbedder`]
+const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Texts sent in one embeddings request
+const BATCH_SIZE: usize = 64;
+
+/// Turns texts into vectors whose cosine similarity reflects relatedness
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Identifies the model; documents embedded by another model are re-indexed
+    fn id(&self) -> &str;
+
+    /// One vector per text, in order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Build the embedder selected by `[knowledge.embedding]`
+pub fn embedder_from_config(config: &EmbeddingConfig) -> Result<Arc<dyn Embedder>> {
+    match config.provider {
+        EmbeddingProvider::Local => Ok(Arc::new(HashEmbedder::new())),
+        EmbeddingProvider::OpenAi => {
+            let api_key = config
+                .api_key
+                .clone()
+                .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+                .unwrap_or_default();
+            let base_url = config.base_url.as_deref().unwrap_or(DEFAULT_OPENAI_BASE_URL);
+            if api_key.is_empty() && base_url == DEFAULT_OPENAI_BASE_URL {
+                return Err(KnowledgeError::Configuration(
+                    "OpenAI embeddings need knowledge.embedding.api_key or OPENAI_API_KEY".to_string(),
+                ));
+            }
+            let model = config.model.as_deref().unwrap_or(DEFAULT_OPENAI_MODEL);
+            Ok(Arc::new(OpenAiEmbedder::new(base_url, api_key, model)))
+        }
+    }
+}
+
+/// Cosine similarity of two vectors (0 for empty or mismatched ones)
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+/// Offline embedder hashing words (and pairs of CJK characters) into a vector
+pub struct HashEmbedder {
+    id: String,
+}
+
+impl HashEmbedder {
+    pub fn new() -> Self {
+        Self {
+            id: format!("local-hash-{}", HASH_DIMENSIONS),
+        }
+    }
+
+    fn embed_one(text: &str) -> Vec<f32> {
+        let mut counts: HashMap<String, f32> = HashMap::new();
+        for term in terms(text) {
+            *counts.entry(term).or_default() += 1.0;
+        }
+        let mut vector = vec![0.0; HASH_DIMENSIONS];
+        for (term, count) in counts {
+            let hash = fnv1a(&term);
+            let sign = if hash & (1 << 63) == 0 { 1.0 } else { -1.0 };
+            // Dampen repeated words so long passages are not dominated by them
+            vector[(hash % HASH_DIMENSIONS as u64) as usize] += sign * (1.0 + count.ln());
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| Self::embed_one(text)).collect())
+    }
+}
+
+/// Lowercase words of two or more characters, and pairs of adjacent CJK
+/// characters (which are not separated by spaces)
+fn terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.iter().any(|c| is_cjk(*c)) {
+            if chars.len() == 1 {
+                terms.push(word.to_string());
+            }
+            terms.extend(chars.windows(2).map(|pair| pair.iter().collect::<String>()));
+        } else if chars.len() >= 2 {
+            terms.push(word.to_lowercase());
+        }
+    }
+    terms
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}' | '\u{ac00}'..='\u{d7af}')
+}
+
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Embedder using an OpenAI-compatible `/embeddings` endpoint
+pub struct OpenAiEmbedder {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(base_url: &str, api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        let model = model.into();
+        Self {
+            client: reqwest::Client::new(),
+            url: format!("{}/embeddings", base_url.trim_end_matches('/')),
+            api_key: api_key.into(),
+            id: format!("openai:{}", model),
+            model,
+        }
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .json(&json!({ "model": self.model, "input": texts }));
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(KnowledgeError::Embedding(format!("{}: {}", status, body)));
+        }
+
+        let mut data = response.json::<EmbeddingsResponse>().await?.data;
+        if data.len() != texts.len() {
+            return Err(KnowledgeError::Embedding(format!(
+                "expected {} embeddings, got {}",
+                texts.len(),
+                data.len()
+            )));
+        }
+        data.sort_by_key(|d| d.index);
+        Ok(data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAiEmbedder {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(BATCH_SIZE) {
+            vectors.extend(self.embed_batch(batch).await?);
+        }
+        Ok(vectors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn embed(texts: &[&str]) -> Vec<Vec<f32>> {
+        let texts: Vec<String> = texts.iter().map(|t| t.to_string()).collect();
+        HashEmbedder::new().embed(&texts).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hash_embedder_similarity() {
+        let v = embed(&[
+            "How do I reset my password?",
+            "To reset your password, open the account settings.",
+            "The office is closed on public holidays.",
+            "パスワードの再設定方法",
+            "パスワードを再設定するには設定画面を開きます",
+        ])
+        .await;
+        assert!(cosine(&v[0], &v[1]) > cosine(&v[0], &v[2]));
+        assert!(cosine(&v[3], &v[4]) > cosine(&v[3], &v[2]));
+        assert!((cosine(&v[0], &v[0]) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_terms() {
+        assert_eq!(terms("Hello, a World!"), vec!["hello", "world"]);
+        assert_eq!(terms("東京タワー"), vec!["東京", "京タ", "タワ", "ワー"]);
+    }
+
+    #[tokio::test]
+    async fn test_openai_embedder() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .and(header("authorization", "Bearer key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": [
+                    { "index": 1, "embedding": [0.0, 1.0] },
+                    { "index": 0, "embedding": [1.0, 0.0] }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let embedder = OpenAiEmbedder::new(&format!("{}/v1/", server.uri()), "key", "test-model");
+        assert_eq!(embedder.id(), "openai:test-model");
+        let vectors = embedder.embed(&["a".to_string(), "b".to_string()]).await.unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+    }
+}
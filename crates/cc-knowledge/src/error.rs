@@ -0,0 +1,31 @@
+//! Error types for cc-knowledge
+
+use thiserror::Error;
+
+/// cc-knowledge error type
+#[derive(Error, Debug)]
+pub enum KnowledgeError {
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Embedding error: {0}")]
+    Embedding(String),
+
+    #[error("Configuration error: {0}")]
+    Configuration(String),
+}
+
+/// Result type alias
+pub type Result<T> = std::result::Result<T, KnowledgeError>;
+
+impl From<KnowledgeError> for cc_core::Error {
+    fn from(e: KnowledgeError) -> Self {
+        cc_core::Error::Storage(e.to_string())
+    }
+}
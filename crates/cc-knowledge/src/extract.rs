@@ -0,0 +1,126 @@
+//! Text extraction from source documents
+//!
+//! Text formats are read as they are. HTML pages keep the text of their
+//! headings, paragraphs, list items, code blocks and table cells; scripts,
+//! styles and navigation are dropped.
+
+use scraper::{ElementRef, Html, Selector};
+
+/// Elements whose text is kept from HTML pages
+const HTML_BLOCKS: &str = "h1, h2, h3, h4, h5, h6, p, li, pre, blockquote, dt, dd, td, th";
+
+/// A document ready to be chunked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDocument {
+    /// File path or URL
+    pub source: String,
+    /// Page title or file name
+    pub title: String,
+    /// Plain text
+    pub text: String,
+}
+
+/// Extract the text of a document (None if the format is not supported)
+pub fn document_from_bytes(
+    source: &str,
+    name: &str,
+    media_type: Option<&str>,
+    bytes: &[u8],
+) -> Option<SourceDocument> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let is_html = media_type.is_some_and(|t| t.to_ascii_lowercase().starts_with("text/html"))
+        || name.rsplit_once('.').is_some_and(|(_, ext)| {
+            ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm")
+        });
+
+    if is_html {
+        let html = String::from_utf8_lossy(bytes);
+        let (title, text) = html_text(&html);
+        return Some(SourceDocument {
+            source: source.to_string(),
+            title: title.unwrap_or_else(|| name.to_string()),
+            text,
+        });
+    }
+
+    if !cc_core::document::is_text_document(name, media_type) {
+        return None;
+    }
+    let text = std::str::from_utf8(bytes).ok()?;
+    Some(SourceDocument {
+        source: source.to_string(),
+        title: name.to_string(),
+        text: text.to_string(),
+    })
+}
+
+/// Title and block text of an HTML page, one paragraph per block
+fn html_text(html: &str) -> (Option<String>, String) {
+    let document = Html::parse_document(html);
+    let title_selector = Selector::parse("title").expect("valid selector");
+    let block_selector = Selector::parse(HTML_BLOCKS).expect("valid selector");
+
+    let title = document
+        .select(&title_selector)
+        .next()
+        .map(|title| collapse_whitespace(&title.text().collect::<String>()))
+        .filter(|title| !title.is_empty());
+
+    let blocks: Vec<String> = document
+        .select(&block_selector)
+        // A block inside another one (<p> in <li>) is part of the outer text
+        .filter(|element| !has_block_ancestor(element, &block_selector))
+        .map(|element| {
+            if element.value().name() == "pre" {
+                element.text().collect::<String>().trim_matches('\n').to_string()
+            } else {
+                collapse_whitespace(&element.text().collect::<String>())
+            }
+        })
+        .filter(|text| !text.is_empty())
+        .collect();
+    (title, blocks.join("\n\n"))
+}
+
+fn has_block_ancestor(element: &ElementRef, blocks: &Selector) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| blocks.matches(&ancestor))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_document() {
+        let html = r#"<html><head><title> Returns  policy </title><script>var x = 1;</script></head>
+            <body><nav><a href="/">Home</a></nav>
+            <h1>Returns</h1>
+            <p>Items can be returned
+               within 30 days.</p>
+            <ul><li><p>Keep the receipt.</p></li></ul>
+            <pre>code
+  block</pre></body></html>"#;
+        let doc = document_from_bytes("https://example.com/returns", "returns", Some("text/html"), html.as_bytes())
+            .unwrap();
+        assert_eq!(doc.title, "Returns policy");
+        assert_eq!(
+            doc.text,
+            "Returns\n\nItems can be returned within 30 days.\n\nKeep the receipt.\n\ncode\n  block"
+        );
+    }
+
+    #[test]
+    fn test_text_and_unsupported_documents() {
+        let doc = document_from_bytes("docs/faq.md", "faq.md", None, b"\xEF\xBB\xBF# FAQ").unwrap();
+        assert_eq!(doc.title, "faq.md");
+        assert_eq!(doc.text, "# FAQ");
+        assert!(document_from_bytes("report.pdf", "report.pdf", Some("application/pdf"), b"%PDF").is_none());
+    }
+}
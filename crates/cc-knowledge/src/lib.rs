@@ -0,0 +1,44 @@
+//! cc-knowledge: Knowledge base for cc-gateway
+//!
+//! This crate lets the gateway answer questions grounded in documents
+//! users provide.
+//!
+//! ## Features
+//!
+//! - Sources: local files, folders (recursive) and web pages
+//! - Text extraction from text formats and HTML
+//! - Chunking by paragraph with overlap
+//! - Embeddings computed locally (word hashing) or through an
+//!   OpenAI-compatible API
+//! - SQLite index, re-indexed periodically (unchanged documents are skipped)
+//! - Agent tool (`knowledge_search`)
+//! - Context injection: relevant passages are added to the system prompt
+//!   through [`cc_core::ContextProvider`]
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use cc_knowledge::KnowledgeBase;
+//!
+//! let knowledge = Arc::new(KnowledgeBase::open(&config.knowledge)?);
+//! knowledge.index_sources().await?;
+//!
+//! for hit in knowledge.search("How do I return an item?", 3).await? {
+//!     println!("{} ({:.2}): {}", hit.source, hit.score, hit.content);
+//! }
+//! ```
+
+pub mod base;
+pub mod chunk;
+pub mod embed;
+pub mod error;
+pub mod extract;
+pub mod store;
+pub mod tools;
+
+pub use base::{IndexReport, KnowledgeBase};
+pub use embed::{Embedder, HashEmbedder, OpenAiEmbedder};
+pub use error::{KnowledgeError, Result};
+pub use store::{DocumentInfo, KnowledgeStore, SearchHit};
+pub use tools::{register_knowledge_tools, KnowledgeSearchTool};
@@ -0,0 +1,261 @@
+//! SQLite index of documents and chunk embeddings
+//!
+//! Embeddings are stored as little-endian `f32` blobs and searched by brute
+//! force, which is fast enough for the tens of thousands of chunks a
+//! gateway's document folders produce.
+
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use tracing::debug;
+
+use cc_core::migrate::{self, Migration};
+
+use crate::embed::cosine;
+use crate::error::Result;
+
+/// Schema versions of the knowledge index
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create documents and chunks",
+    "CREATE TABLE IF NOT EXISTS documents (
+        id INTEGER PRIMARY KEY,
+        source TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        content_hash TEXT NOT NULL,
+        embedder TEXT NOT NULL,
+        indexed_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS chunks (
+        id INTEGER PRIMARY KEY,
+        document_id INTEGER NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+        ordinal INTEGER NOT NULL,
+        content TEXT NOT NULL,
+        embedding BLOB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_chunks_document ON chunks(document_id)",
+)];
+
+/// An indexed document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentInfo {
+    pub source: String,
+    pub title: String,
+    pub chunks: usize,
+    pub indexed_at: DateTime<Utc>,
+}
+
+/// A chunk matching a search
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    /// File path or URL of the document
+    pub source: String,
+    pub title: String,
+    pub content: String,
+    /// Position of the chunk in the document
+    pub ordinal: usize,
+    /// Cosine similarity to the query
+    pub score: f32,
+}
+
+/// A chunk to store with its embedding
+pub struct NewChunk {
+    pub content: String,
+    pub embedding: Vec<f32>,
+}
+
+/// SQLite-based knowledge index
+pub struct KnowledgeStore {
+    conn: Mutex<Connection>,
+}
+
+impl KnowledgeStore {
+    /// Open (or create) the index at `db_path`
+    pub fn new(db_path: &str) -> Result<Self> {
+        debug!("Opening knowledge database at: {}", db_path);
+        if let Some(parent) = Path::new(db_path).parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        Self::init(Connection::open(db_path)?)
+    }
+
+    /// Create an in-memory index (useful for testing)
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON")?;
+        migrate::migrate(&conn, "knowledge", MIGRATIONS)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Content hash and embedder of an indexed document
+    pub fn document_state(&self, source: &str) -> Result<Option<(String, String)>> {
+        Ok(self
+            .conn()
+            .query_row(
+                "SELECT content_hash, embedder FROM documents WHERE source = ?1",
+                params![source],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?)
+    }
+
+    /// Store a document, replacing its previous chunks
+    pub fn replace_document(
+        &self,
+        source: &str,
+        title: &str,
+        content_hash: &str,
+        embedder: &str,
+        chunks: &[NewChunk],
+    ) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM documents WHERE source = ?1", params![source])?;
+        tx.execute(
+            "INSERT INTO documents (source, title, content_hash, embedder, indexed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![source, title, content_hash, embedder, Utc::now().to_rfc3339()],
+        )?;
+        let document_id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO chunks (document_id, ordinal, content, embedding) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (ordinal, chunk) in chunks.iter().enumerate() {
+                insert.execute(params![document_id, ordinal as i64, chunk.content, to_blob(&chunk.embedding)])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove a document and its chunks
+    pub fn remove_document(&self, source: &str) -> Result<bool> {
+        let deleted = self
+            .conn()
+            .execute("DELETE FROM documents WHERE source = ?1", params![source])?;
+        Ok(deleted > 0)
+    }
+
+    /// Sources of all indexed documents
+    pub fn sources(&self) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT source FROM documents ORDER BY source")?;
+        let sources = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(sources)
+    }
+
+    /// All indexed documents with their chunk counts
+    pub fn list_documents(&self) -> Result<Vec<DocumentInfo>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT d.source, d.title, COUNT(c.id), d.indexed_at
+             FROM documents d LEFT JOIN chunks c ON c.document_id = d.id
+             GROUP BY d.id ORDER BY d.source",
+        )?;
+        let documents = stmt
+            .query_map([], |row| {
+                let indexed_at: String = row.get(3)?;
+                Ok(DocumentInfo {
+                    source: row.get(0)?,
+                    title: row.get(1)?,
+                    chunks: row.get::<_, i64>(2)? as usize,
+                    indexed_at: DateTime::parse_from_rfc3339(&indexed_at)
+                        .map(|at| at.with_timezone(&Utc))
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(documents)
+    }
+
+    /// The `limit` chunks most similar to `query`, best first
+    pub fn search(&self, query: &[f32], limit: usize) -> Result<Vec<SearchHit>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT d.source, d.title, c.content, c.ordinal, c.embedding
+             FROM chunks c JOIN documents d ON d.id = c.document_id",
+        )?;
+        let mut hits = stmt
+            .query_map([], |row| {
+                let embedding: Vec<u8> = row.get(4)?;
+                Ok(SearchHit {
+                    source: row.get(0)?,
+                    title: row.get(1)?,
+                    content: row.get(2)?,
+                    ordinal: row.get::<_, i64>(3)? as usize,
+                    score: cosine(query, &from_blob(&embedding)),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+}
+
+fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(content: &str, embedding: Vec<f32>) -> NewChunk {
+        NewChunk {
+            content: content.to_string(),
+            embedding,
+        }
+    }
+
+    #[test]
+    fn test_replace_and_search() -> Result<()> {
+        let store = KnowledgeStore::in_memory()?;
+        store.replace_document(
+            "a.md",
+            "A",
+            "h1",
+            "test",
+            &[chunk("north", vec![1.0, 0.0]), chunk("east", vec![0.0, 1.0])],
+        )?;
+        store.replace_document("b.md", "B", "h2", "test", &[chunk("north-east", vec![1.0, 1.0])])?;
+
+        let hits = store.search(&[1.0, 0.1], 2)?;
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].content, "north");
+        assert_eq!(hits[1].content, "north-east");
+        assert_eq!(store.document_state("a.md")?, Some(("h1".to_string(), "test".to_string())));
+
+        // Replacing drops the old chunks
+        store.replace_document("a.md", "A", "h3", "test", &[chunk("west", vec![-1.0, 0.0])])?;
+        let documents = store.list_documents()?;
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].chunks, 1);
+
+        assert!(store.remove_document("a.md")?);
+        assert_eq!(store.sources()?, vec!["b.md"]);
+        assert_eq!(store.search(&[1.0, 0.0], 10)?.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Knowledge base tools for cc-gateway
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use cc_core::{Tool, ToolResult};
+
+use crate::base::KnowledgeBase;
+
+/// Passages returned by a search unless a limit is given
+const DEFAULT_SEARCH_LIMIT: usize = 5;
+
+/// Knowledge base search tool
+pub struct KnowledgeSearchTool {
+    knowledge: Arc<KnowledgeBase>,
+}
+
+impl KnowledgeSearchTool {
+    pub fn new(knowledge: Arc<KnowledgeBase>) -> Self {
+        Self { knowledge }
+    }
+}
+
+#[async_trait]
+impl Tool for KnowledgeSearchTool {
+    fn name(&self) -> &str {
+        "knowledge_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the knowledge base of user-provided documents and web pages for passages relevant to a question"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Question or topic to look for"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of passages to return (default: 5)",
+                    "default": 5
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+        let query = input["query"]
+            .as_str()
+            .ok_or_else(|| cc_core::Error::ToolExecution("Missing 'query' parameter".to_string()))?;
+        let limit = input["limit"]
+            .as_u64()
+            .map_or(DEFAULT_SEARCH_LIMIT, |l| l as usize);
+
+        let hits = self
+            .knowledge
+            .search(query, limit)
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(e.to_string()))?;
+
+        Ok(ToolResult::success(serde_json::to_string(&json!({
+            "query": query,
+            "count": hits.len(),
+            "results": hits.iter().map(|hit| json!({
+                "source": hit.source,
+                "title": hit.title,
+                "content": hit.content,
+                "score": hit.score,
+            })).collect::<Vec<_>>()
+        })).unwrap_or_default()))
+    }
+}
+
+/// Register the knowledge base tools
+pub fn register_knowledge_tools(manager: &mut cc_core::ToolManager, knowledge: Arc<KnowledgeBase>) {
+    manager.register(Arc::new(KnowledgeSearchTool::new(knowledge)));
+}
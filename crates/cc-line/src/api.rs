@@ -8,12 +8,17 @@ use tracing::{debug, error, info};
 use crate::error::{LineError, Result};
 use crate::types::*;
 
+/// Messages LINE accepts in one reply or push request
+pub const MAX_MESSAGES_PER_REQUEST: usize = 5;
+
 /// LINE Messaging API client
 #[derive(Clone)]
 pub struct LineApiClient {
     client: Client,
     channel_access_token: String,
     base_url: String,
+    /// Host for message content (`api-data.line.me`)
+    data_base_url: String,
 }
 
 impl LineApiClient {
@@ -28,6 +33,7 @@ impl LineApiClient {
             client,
             channel_access_token: channel_access_token.to_string(),
             base_url: "https://api.line.me/v2".to_string(),
+            data_base_url: "https://api-data.line.me/v2".to_string(),
         })
     }
 
@@ -66,11 +72,16 @@ impl LineApiClient {
 
     /// Reply to a message
     pub async fn reply_message(&self, reply_token: &str, text: &str) -> Result<()> {
+        self.reply(reply_token, vec![MessageContent::text(text)]).await
+    }
+
+    /// Reply with up to 5 messages (text, Flex, stickers)
+    pub async fn reply(&self, reply_token: &str, messages: Vec<MessageContent>) -> Result<()> {
         let url = format!("{}/bot/message/reply", self.base_url);
 
         let body = ReplyMessage {
             reply_token: reply_token.to_string(),
-            messages: vec![MessageContent::Text { text: text.to_string() }],
+            messages,
         };
 
         debug!("Replying to message");
@@ -93,11 +104,16 @@ impl LineApiClient {
 
     /// Push a message to a user/group/room
     pub async fn push_message(&self, to: &str, text: &str) -> Result<()> {
+        self.push(to, vec![MessageContent::text(text)]).await
+    }
+
+    /// Push up to 5 messages (text, Flex, stickers) to a user/group/room
+    pub async fn push(&self, to: &str, messages: Vec<MessageContent>) -> Result<()> {
         let url = format!("{}/bot/message/push", self.base_url);
 
         let body = PushMessage {
             to: to.to_string(),
-            messages: vec![MessageContent::Text { text: text.to_string() }],
+            messages,
         };
 
         debug!("Pushing message to: {}", to);
@@ -120,42 +136,48 @@ impl LineApiClient {
 
     /// Send multiple messages (for long responses)
     pub async fn push_messages(&self, to: &str, texts: &[String]) -> Result<()> {
-        // LINE allows up to 5 messages per API call
-        for chunk in texts.chunks(5) {
-            let url = format!("{}/bot/message/push", self.base_url);
-
-            let messages: Vec<MessageContent> = chunk
-                .iter()
-                .map(|text| MessageContent::Text { text: text.clone() })
-                .collect();
-
-            let body = PushMessage {
-                to: to.to_string(),
-                messages,
-            };
-
-            let response = self
-                .add_auth(self.client.post(&url).json(&body))
-                .send()
-                .await
-                .map_err(LineError::HttpError)?;
-
-            let status = response.status();
-            if !status.is_success() {
-                let error_text = response.text().await.unwrap_or_default();
-                error!("Push messages failed: {} - {}", status, error_text);
-                return Err(LineError::ApiError(format!("{}: {}", status, error_text)));
-            }
+        let messages: Vec<MessageContent> = texts.iter().map(MessageContent::text).collect();
 
+        // LINE allows up to 5 messages per API call
+        for (i, chunk) in messages.chunks(MAX_MESSAGES_PER_REQUEST).enumerate() {
             // Small delay between chunks
-            if chunk.len() == 5 && texts.len() > 5 {
+            if i > 0 {
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
             }
+            self.push(to, chunk.to_vec()).await?;
         }
 
         Ok(())
     }
 
+    /// Download the content of an image, audio, video or file message
+    ///
+    /// Returns the bytes and the content type.
+    pub async fn get_content(&self, message_id: &str) -> Result<(Vec<u8>, String)> {
+        let url = format!("{}/bot/message/{}/content", self.data_base_url, message_id);
+
+        let response = self
+            .add_auth(self.client.get(&url))
+            .send()
+            .await
+            .map_err(LineError::HttpError)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LineError::ApiError(format!("{}: {}", status, error_text)));
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let bytes = response.bytes().await.map_err(LineError::HttpError)?;
+        Ok((bytes.to_vec(), content_type))
+    }
+
     /// Get group member profile
     pub async fn get_group_member_profile(&self, group_id: &str, user_id: &str) -> Result<LineProfile> {
         let url = format!("{}/bot/group/{}/member/{}", self.base_url, group_id, user_id);
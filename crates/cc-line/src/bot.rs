@@ -7,15 +7,17 @@ use std::sync::Arc;
 use tracing::{error, info};
 
 use cc_core::{ClaudeClient, Config};
+use cc_voice::WhisperClient;
 
 use crate::api::LineApiClient;
 use crate::error::{LineError, Result};
 use crate::handler::{HandlerConfig, MessageHandler};
 use crate::session::InMemorySessionStore;
+use crate::types::MessageContent;
 use crate::webhook::{start_webhook_server, WebhookState};
 
 /// LINE Bot configuration
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct LineBotConfig {
     /// Channel secret
     pub channel_secret: String,
@@ -27,6 +29,21 @@ pub struct LineBotConfig {
     pub allowed_users: Vec<String>,
     /// System prompt for Claude
     pub system_prompt: Option<String>,
+    /// Offer follow-up questions as quick reply buttons
+    pub suggest_follow_ups: bool,
+}
+
+impl Default for LineBotConfig {
+    fn default() -> Self {
+        Self {
+            channel_secret: String::new(),
+            channel_access_token: String::new(),
+            webhook_port: 0,
+            allowed_users: Vec::new(),
+            system_prompt: None,
+            suggest_follow_ups: true,
+        }
+    }
 }
 
 /// LINE Bot for Claude Code Gateway
@@ -36,6 +53,7 @@ pub struct LineBot {
     claude_client: Arc<ClaudeClient>,
     session_store: Arc<InMemorySessionStore>,
     handler_config: HandlerConfig,
+    transcriber: Option<Arc<WhisperClient>>,
 }
 
 impl LineBot {
@@ -61,6 +79,7 @@ impl LineBot {
                 "You are a helpful assistant. Respond concisely as this is a messaging app. Respond in the same language as the user's question.".to_string()
             }),
             max_message_length: 5000,
+            suggest_follow_ups: bot_config.suggest_follow_ups,
         };
 
         // Start session cleanup task
@@ -77,6 +96,7 @@ impl LineBot {
             claude_client: Arc::new(claude_client),
             session_store,
             handler_config,
+            transcriber: None,
         })
     }
 
@@ -102,6 +122,7 @@ impl LineBot {
                 "You are a helpful assistant. Respond concisely as this is a messaging app. Respond in the same language as the user's question.".to_string()
             }),
             max_message_length: 5000,
+            suggest_follow_ups: bot_config.suggest_follow_ups,
         };
 
         // Start session cleanup task
@@ -118,9 +139,16 @@ impl LineBot {
             claude_client,
             session_store,
             handler_config,
+            transcriber: None,
         })
     }
 
+    /// Transcribe audio messages with Whisper
+    pub fn with_transcriber(mut self, transcriber: Arc<WhisperClient>) -> Self {
+        self.transcriber = Some(transcriber);
+        self
+    }
+
     /// Get the session store
     pub fn session_store(&self) -> Arc<InMemorySessionStore> {
         self.session_store.clone()
@@ -131,21 +159,36 @@ impl LineBot {
         self.api_client.push_message(user_id, text).await
     }
 
+    /// Send a sticker to a user
+    pub async fn send_sticker(&self, user_id: &str, package_id: &str, sticker_id: &str) -> Result<()> {
+        self.api_client
+            .push(user_id, vec![MessageContent::sticker(package_id, sticker_id)])
+            .await
+    }
+
     /// Get user profile
     pub async fn get_profile(&self, user_id: &str) -> Result<crate::types::LineProfile> {
         self.api_client.get_profile(user_id).await
     }
 
-    /// Start the webhook server (blocking)
-    pub async fn start(&self) -> Result<()> {
-        info!("Starting LINE bot webhook server on port {}", self.bot_config.webhook_port);
-
-        let handler = Arc::new(MessageHandler::new(
+    fn handler(&self) -> MessageHandler {
+        let handler = MessageHandler::new(
             self.api_client.clone(),
             self.claude_client.clone(),
             self.session_store.clone(),
             self.handler_config.clone(),
-        ));
+        );
+        match self.transcriber {
+            Some(ref transcriber) => handler.with_transcriber(transcriber.clone()),
+            None => handler,
+        }
+    }
+
+    /// Start the webhook server (blocking)
+    pub async fn start(&self) -> Result<()> {
+        info!("Starting LINE bot webhook server on port {}", self.bot_config.webhook_port);
+
+        let handler = Arc::new(self.handler());
 
         let state = WebhookState {
             channel_secret: self.bot_config.channel_secret.clone(),
@@ -159,12 +202,7 @@ impl LineBot {
     pub async fn run(&self, mut shutdown: tokio::sync::broadcast::Receiver<()>) -> Result<()> {
         info!("Starting LINE bot webhook server on port {}", self.bot_config.webhook_port);
 
-        let handler = Arc::new(MessageHandler::new(
-            self.api_client.clone(),
-            self.claude_client.clone(),
-            self.session_store.clone(),
-            self.handler_config.clone(),
-        ));
+        let handler = Arc::new(self.handler());
 
         let state = WebhookState {
             channel_secret: self.bot_config.channel_secret.clone(),
@@ -203,6 +241,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: cc_core::LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -213,6 +254,12 @@ mod tests {
             memory: cc_core::MemoryConfig::default(),
             mcp: cc_core::McpConfig::default(),
             scheduler: cc_core::SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -1,14 +1,19 @@
 //! LINE message handler implementation
 
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
-use cc_core::{ClaudeClient, Message};
+use cc_core::{ClaudeClient, ImageSource, Message};
+use cc_voice::WhisperClient;
 
-use crate::api::LineApiClient;
+use crate::api::{LineApiClient, MAX_MESSAGES_PER_REQUEST};
 use crate::error::Result;
+use crate::rich;
 use crate::session::InMemorySessionStore;
-use crate::types::LineEvent;
+use crate::types::{LineEvent, LineMessage, MessageContent};
+
+/// Claude accepts images up to 5MB
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
 
 /// Configuration for the message handler
 #[derive(Clone, Debug)]
@@ -19,6 +24,8 @@ pub struct HandlerConfig {
     pub system_prompt: String,
     /// Maximum message length before splitting
     pub max_message_length: usize,
+    /// Ask Claude for follow-up questions and offer them as quick replies
+    pub suggest_follow_ups: bool,
 }
 
 impl Default for HandlerConfig {
@@ -27,6 +34,7 @@ impl Default for HandlerConfig {
             allowed_users: Vec::new(),
             system_prompt: "You are a helpful assistant. Respond concisely as this is a messaging app. Respond in the same language as the user's question.".to_string(),
             max_message_length: 5000, // LINE has ~5000 char limit per message
+            suggest_follow_ups: true,
         }
     }
 }
@@ -37,6 +45,7 @@ pub struct MessageHandler {
     claude_client: Arc<ClaudeClient>,
     session_store: Arc<InMemorySessionStore>,
     config: HandlerConfig,
+    transcriber: Option<Arc<WhisperClient>>,
 }
 
 impl MessageHandler {
@@ -52,9 +61,16 @@ impl MessageHandler {
             claude_client,
             session_store,
             config,
+            transcriber: None,
         }
     }
 
+    /// Transcribe audio messages with Whisper
+    pub fn with_transcriber(mut self, transcriber: Arc<WhisperClient>) -> Self {
+        self.transcriber = Some(transcriber);
+        self
+    }
+
     /// Process an incoming event
     pub async fn process_event(&self, event: &LineEvent) -> Result<()> {
         // Only handle message events
@@ -62,10 +78,8 @@ impl MessageHandler {
             return Ok(());
         }
 
-        // Get the message text
-        let text = match &event.message {
-            Some(msg) => msg.text.as_deref().unwrap_or(""),
-            None => return Ok(()),
+        let Some(message) = &event.message else {
+            return Ok(());
         };
 
         // Get the sender ID
@@ -83,18 +97,94 @@ impl MessageHandler {
         // Get reply token if available
         let reply_token = event.reply_token.as_deref();
 
-        let content = text.trim();
-        if content.is_empty() {
-            return Ok(());
+        match message.message_type.as_str() {
+            "text" => {
+                let content = message.text.as_deref().unwrap_or("").trim();
+                if content.is_empty() {
+                    return Ok(());
+                }
+
+                // Handle commands
+                if content.starts_with('/') {
+                    return self.handle_command(sender_id, content, reply_token).await;
+                }
+
+                // Regular message processing
+                self.process_with_claude(sender_id, content, Vec::new(), reply_token)
+                    .await
+            }
+            "image" => self.process_image(sender_id, message, reply_token).await,
+            "audio" => self.process_audio(sender_id, message, reply_token).await,
+            "sticker" => {
+                let content = sticker_prompt(message);
+                self.process_with_claude(sender_id, &content, Vec::new(), reply_token)
+                    .await
+            }
+            other => {
+                debug!("Ignoring {} message from {}", other, sender_id);
+                Ok(())
+            }
         }
+    }
 
-        // Handle commands
-        if content.starts_with('/') {
-            return self.handle_command(sender_id, content, reply_token).await;
+    /// Send an image message to Claude
+    async fn process_image(&self, sender_id: &str, message: &LineMessage, reply_token: Option<&str>) -> Result<()> {
+        let (bytes, content_type) = match self.api_client.get_content(&message.id).await {
+            Ok(content) => content,
+            Err(e) => {
+                error!("Failed to download image {}: {}", message.id, e);
+                return self
+                    .send_reply(sender_id, "The image could not be downloaded.", reply_token)
+                    .await;
+            }
+        };
+        if bytes.len() > MAX_IMAGE_BYTES {
+            return self
+                .send_reply(sender_id, "The image is too large (5MB max).", reply_token)
+                .await;
         }
 
-        // Regular message processing
-        self.process_with_claude(sender_id, content, reply_token).await
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+        let images = vec![ImageSource::from_bytes(mime_type, &bytes)];
+        self.process_with_claude(sender_id, "", images, reply_token).await
+    }
+
+    /// Transcribe an audio message and send the transcript to Claude
+    async fn process_audio(&self, sender_id: &str, message: &LineMessage, reply_token: Option<&str>) -> Result<()> {
+        let Some(ref transcriber) = self.transcriber else {
+            return self
+                .send_reply(
+                    sender_id,
+                    "Voice messages are not supported (transcription is not configured).",
+                    reply_token,
+                )
+                .await;
+        };
+
+        // LINE delivers voice messages as M4A
+        let result = match self.api_client.get_content(&message.id).await {
+            Ok((bytes, _)) => transcriber
+                .transcribe_text(&bytes, &format!("{}.m4a", message.id))
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        match result {
+            Ok(transcript) if !transcript.trim().is_empty() => {
+                self.process_with_claude(sender_id, transcript.trim(), Vec::new(), reply_token)
+                    .await
+            }
+            Ok(_) => {
+                self.send_reply(sender_id, "No speech was recognized in the voice message.", reply_token)
+                    .await
+            }
+            Err(e) => {
+                warn!("Failed to transcribe audio {}: {}", message.id, e);
+                self.send_reply(sender_id, "The voice message could not be transcribed.", reply_token)
+                    .await
+            }
+        }
     }
 
     /// Check if user is allowed
@@ -139,7 +229,8 @@ impl MessageHandler {
             _ => {
                 // Unknown command, treat as regular message
                 let clean_content = content.trim_start_matches('/');
-                self.process_with_claude(sender_id, clean_content, reply_token).await?;
+                self.process_with_claude(sender_id, clean_content, Vec::new(), reply_token)
+                    .await?;
             }
         }
 
@@ -147,21 +238,41 @@ impl MessageHandler {
     }
 
     /// Process message with Claude API
-    async fn process_with_claude(&self, sender_id: &str, content: &str, reply_token: Option<&str>) -> Result<()> {
+    async fn process_with_claude(
+        &self,
+        sender_id: &str,
+        content: &str,
+        images: Vec<ImageSource>,
+        reply_token: Option<&str>,
+    ) -> Result<()> {
         info!("Processing message from {}: {}", sender_id, content);
 
         // Get or create session
         let session = self.session_store.get_or_create(sender_id);
 
+        // Images are sent once and only noted in the history
+        let image_count = images.len();
+        let content = if content.trim().is_empty() { "Describe this image." } else { content };
+
         // Build message history
         let mut messages: Vec<Message> = session.messages.clone();
-        messages.push(Message::user(content));
+        messages.push(if images.is_empty() {
+            Message::user(content)
+        } else {
+            Message::user_with_images(content, images)
+        });
+
+        let system_prompt = if self.config.suggest_follow_ups {
+            format!("{}\n\n{}", self.config.system_prompt, rich::FOLLOW_UP_INSTRUCTION)
+        } else {
+            self.config.system_prompt.clone()
+        };
 
         // Build request with conversation history
         let mut request_builder = self
             .claude_client
             .request_builder()
-            .system(&self.config.system_prompt)
+            .system(&system_prompt)
             .max_tokens(2048);
 
         // Add conversation history (limit to last 20 messages)
@@ -188,14 +299,22 @@ impl MessageHandler {
                     .collect::<Vec<_>>()
                     .join("\n");
 
+                let (answer, follow_ups) = rich::extract_follow_ups(&text);
+
                 // Update session
+                let history_text = if image_count == 0 {
+                    content.to_string()
+                } else {
+                    format!("{}\n[{} image(s) attached]", content, image_count)
+                };
                 self.session_store
-                    .add_message(sender_id, Message::user(content));
+                    .add_message(sender_id, Message::user(history_text));
                 self.session_store
-                    .add_message(sender_id, Message::assistant(&text));
+                    .add_message(sender_id, Message::assistant(&answer));
 
                 // Send response
-                self.send_reply(sender_id, &text, reply_token).await?;
+                let messages = self.answer_messages(&answer, &follow_ups);
+                self.send_messages(sender_id, messages, reply_token).await?;
             }
             Err(e) => {
                 error!("Claude API error: {:?}", e);
@@ -207,35 +326,66 @@ impl MessageHandler {
         Ok(())
     }
 
-    /// Send a reply
+    /// Reply messages for an answer: a Flex card for structured answers,
+    /// text chunks otherwise, with the follow-ups as quick replies on the
+    /// last message
+    fn answer_messages(&self, answer: &str, follow_ups: &[String]) -> Vec<MessageContent> {
+        let mut messages: Vec<MessageContent> = match rich::answer_bubble(answer) {
+            Some(bubble) => vec![MessageContent::flex(rich::alt_text(answer), bubble)],
+            None => self
+                .split_message(answer, self.config.max_message_length)
+                .into_iter()
+                .map(MessageContent::text)
+                .collect(),
+        };
+
+        if let Some(quick_reply) = rich::quick_reply(follow_ups) {
+            if let Some(last) = messages.pop() {
+                messages.push(last.with_quick_reply(quick_reply));
+            }
+        }
+        messages
+    }
+
+    /// Send a text reply
     async fn send_reply(&self, sender_id: &str, text: &str, reply_token: Option<&str>) -> Result<()> {
+        let messages = self
+            .split_message(text, self.config.max_message_length)
+            .into_iter()
+            .map(MessageContent::text)
+            .collect();
+        self.send_messages(sender_id, messages, reply_token).await
+    }
+
+    /// Send messages, the first batch via the reply API when we have a token
+    /// and the rest via push
+    async fn send_messages(
+        &self,
+        sender_id: &str,
+        messages: Vec<MessageContent>,
+        reply_token: Option<&str>,
+    ) -> Result<()> {
+        let mut batches = messages.chunks(MAX_MESSAGES_PER_REQUEST);
+
         // Prefer reply API if we have a token, otherwise use push
         if let Some(token) = reply_token {
-            if text.len() <= self.config.max_message_length {
-                self.api_client.reply_message(token, text).await?;
-            } else {
-                // Split and use push for multiple messages
-                let chunks = self.split_message(text, self.config.max_message_length);
-                // First message via reply
-                if let Some(first) = chunks.first() {
-                    self.api_client.reply_message(token, first).await?;
-                }
-                // Rest via push
-                for chunk in chunks.iter().skip(1) {
-                    if let Err(e) = self.api_client.push_message(sender_id, chunk).await {
-                        error!("Failed to push message: {:?}", e);
-                        break;
-                    }
-                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-                }
+            if let Some(first) = batches.next() {
+                self.api_client.reply(token, first.to_vec()).await?;
             }
-        } else {
-            // Split message if necessary
-            if text.len() <= self.config.max_message_length {
-                self.api_client.push_message(sender_id, text).await?;
-            } else {
-                let chunks = self.split_message(text, self.config.max_message_length);
-                self.api_client.push_messages(sender_id, &chunks).await?;
+        }
+
+        for (i, batch) in batches.enumerate() {
+            let delivered = i > 0 || reply_token.is_some();
+            if delivered {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+            if let Err(e) = self.api_client.push(sender_id, batch.to_vec()).await {
+                // Nothing was sent yet: report the failure
+                if !delivered {
+                    return Err(e);
+                }
+                error!("Failed to push message: {:?}", e);
+                break;
             }
         }
 
@@ -278,6 +428,15 @@ impl MessageHandler {
     }
 }
 
+/// Prompt for a sticker message, from the words LINE attaches to it
+fn sticker_prompt(message: &LineMessage) -> String {
+    if message.keywords.is_empty() {
+        return "[Sticker]".to_string();
+    }
+    let keywords: Vec<&str> = message.keywords.iter().take(5).map(String::as_str).collect();
+    format!("[Sticker: {}]", keywords.join(", "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,6 +449,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -300,6 +462,12 @@ mod tests {
             memory: MemoryConfig::default(),
             mcp: McpConfig::default(),
             scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -321,6 +489,7 @@ mod tests {
             claude_client,
             session_store: Arc::new(InMemorySessionStore::new()),
             config: config.clone(),
+            transcriber: None,
         };
 
         let short = "Short message";
@@ -331,4 +500,43 @@ mod tests {
         let result = handler.split_message(long, 30);
         assert!(result.len() > 1);
     }
+
+    #[test]
+    fn test_answer_messages() {
+        let handler = MessageHandler::new(
+            LineApiClient::new("test-token").unwrap(),
+            Arc::new(ClaudeClient::new(&mock_config()).unwrap()),
+            Arc::new(InMemorySessionStore::new()),
+            HandlerConfig::default(),
+        );
+        let follow_ups = vec!["And tomorrow?".to_string()];
+
+        let messages = handler.answer_messages("It is sunny.", &follow_ups);
+        assert_eq!(messages.len(), 1);
+        match &messages[0] {
+            MessageContent::Text { text, quick_reply } => {
+                assert_eq!(text, "It is sunny.");
+                assert_eq!(quick_reply.as_ref().unwrap().items[0].action.text, "And tomorrow?");
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        let messages = handler.answer_messages("- Sunny\n- 25°C", &[]);
+        assert!(matches!(&messages[0], MessageContent::Flex { quick_reply: None, .. }));
+    }
+
+    #[test]
+    fn test_sticker_prompt() {
+        let mut message = LineMessage {
+            message_type: "sticker".to_string(),
+            id: "1".to_string(),
+            text: None,
+            package_id: Some("446".to_string()),
+            sticker_id: Some("1988".to_string()),
+            keywords: Vec::new(),
+        };
+        assert_eq!(sticker_prompt(&message), "[Sticker]");
+        message.keywords = vec!["Happy".to_string(), "OK".to_string()];
+        assert_eq!(sticker_prompt(&message), "[Sticker: Happy, OK]");
+    }
 }
@@ -7,6 +7,7 @@ pub mod api;
 pub mod bot;
 pub mod error;
 pub mod handler;
+pub mod rich;
 pub mod session;
 pub mod types;
 pub mod webhook;
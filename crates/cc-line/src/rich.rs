@@ -0,0 +1,332 @@
+//! Rich LINE replies
+//!
+//! Answers with structure (headings, lists, links) are sent as Flex Message
+//! cards instead of raw Markdown, which LINE does not render. Follow-up
+//! questions suggested by the model become quick reply buttons.
+
+use serde_json::{json, Value};
+
+use crate::types::{QuickReply, QuickReplyItem};
+
+/// Prefix of the lines the model uses to suggest follow-up questions
+pub const FOLLOW_UP_PREFIX: &str = "FOLLOWUP:";
+
+/// Appended to the system prompt when follow-ups are enabled
+pub const FOLLOW_UP_INSTRUCTION: &str = "After your answer, you may suggest up to 3 short follow-up questions the user might ask next, each on its own line starting with \"FOLLOWUP: \". Write them in the user's language.";
+
+/// LINE allows 13 quick reply buttons
+const MAX_QUICK_REPLY_ITEMS: usize = 13;
+/// Quick reply and button labels are limited to 20 characters
+const MAX_LABEL_CHARS: usize = 20;
+/// Longer answers are sent as plain text
+const MAX_FLEX_CHARS: usize = 2000;
+/// Link buttons shown in the card footer
+const MAX_LINK_BUTTONS: usize = 4;
+/// Flex Message alt text (notifications, chat list) is limited to 400 characters
+const MAX_ALT_TEXT_CHARS: usize = 400;
+
+/// A block of a Markdown answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Heading(String),
+    Item { marker: String, text: String },
+    Paragraph(String),
+}
+
+/// A `[label](url)` link
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Link {
+    label: String,
+    url: String,
+}
+
+/// Split the answer from the `FOLLOWUP:` lines
+///
+/// Returns the answer without those lines and the suggested questions.
+pub fn extract_follow_ups(text: &str) -> (String, Vec<String>) {
+    let mut answer = Vec::new();
+    let mut follow_ups: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match line.trim().strip_prefix(FOLLOW_UP_PREFIX) {
+            Some(question) => {
+                let question = question.trim();
+                if !question.is_empty() && !follow_ups.iter().any(|q| q == question) {
+                    follow_ups.push(question.to_string());
+                }
+            }
+            None => answer.push(line),
+        }
+    }
+    (answer.join("\n").trim_end().to_string(), follow_ups)
+}
+
+/// Quick reply buttons sending each question (None when there are none)
+pub fn quick_reply(questions: &[String]) -> Option<QuickReply> {
+    let items: Vec<QuickReplyItem> = questions
+        .iter()
+        .filter(|q| !q.trim().is_empty())
+        .take(MAX_QUICK_REPLY_ITEMS)
+        .map(|q| QuickReplyItem::message(truncate(q, MAX_LABEL_CHARS), q.clone()))
+        .collect();
+    if items.is_empty() {
+        return None;
+    }
+    Some(QuickReply { items })
+}
+
+/// Whether the answer has structure worth a card: a heading, a list of at
+/// least two items or a link (code blocks are left as text)
+pub fn is_structured(markdown: &str) -> bool {
+    if markdown.contains("```") {
+        return false;
+    }
+    let (blocks, links) = parse(markdown);
+    let items = blocks.iter().filter(|b| matches!(b, Block::Item { .. })).count();
+    let has_heading = blocks.iter().any(|b| matches!(b, Block::Heading(_)));
+    has_heading || items >= 2 || !links.is_empty()
+}
+
+/// Flex bubble for a structured answer
+///
+/// Returns None for unstructured or long answers, which read better as text.
+pub fn answer_bubble(markdown: &str) -> Option<Value> {
+    if markdown.chars().count() > MAX_FLEX_CHARS || !is_structured(markdown) {
+        return None;
+    }
+    let (blocks, links) = parse(markdown);
+    if blocks.is_empty() {
+        return None;
+    }
+
+    let contents: Vec<Value> = blocks
+        .iter()
+        .map(|block| match block {
+            Block::Heading(text) => json!({
+                "type": "text", "text": text, "weight": "bold", "size": "md", "wrap": true,
+            }),
+            Block::Item { marker, text } => json!({
+                "type": "box",
+                "layout": "baseline",
+                "spacing": "sm",
+                "contents": [
+                    { "type": "text", "text": marker, "size": "sm", "color": "#888888", "flex": 0 },
+                    { "type": "text", "text": text, "size": "sm", "wrap": true, "flex": 1 },
+                ],
+            }),
+            Block::Paragraph(text) => json!({
+                "type": "text", "text": text, "size": "sm", "wrap": true,
+            }),
+        })
+        .collect();
+
+    let mut bubble = json!({
+        "type": "bubble",
+        "body": { "type": "box", "layout": "vertical", "spacing": "md", "contents": contents },
+    });
+
+    let buttons: Vec<Value> = links
+        .iter()
+        .take(MAX_LINK_BUTTONS)
+        .map(|link| {
+            json!({
+                "type": "button",
+                "style": "link",
+                "height": "sm",
+                "action": { "type": "uri", "label": truncate(&link.label, MAX_LABEL_CHARS), "uri": link.url },
+            })
+        })
+        .collect();
+    if !buttons.is_empty() {
+        bubble["footer"] = json!({ "type": "box", "layout": "vertical", "spacing": "sm", "contents": buttons });
+    }
+
+    Some(bubble)
+}
+
+/// Plain text alt text of a card
+pub fn alt_text(markdown: &str) -> String {
+    let (blocks, _) = parse(markdown);
+    let text = blocks
+        .iter()
+        .map(|block| match block {
+            Block::Heading(text) | Block::Paragraph(text) => text.clone(),
+            Block::Item { marker, text } => format!("{} {}", marker, text),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    truncate(&text, MAX_ALT_TEXT_CHARS)
+}
+
+/// Parse the answer into blocks, collecting its links
+fn parse(markdown: &str) -> (Vec<Block>, Vec<Link>) {
+    let mut blocks = Vec::new();
+    let mut links = Vec::new();
+    let mut paragraph: Vec<String> = Vec::new();
+
+    let flush = |paragraph: &mut Vec<String>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(paragraph.join("\n")));
+            paragraph.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+            continue;
+        }
+        let (text, line_links) = strip_links(line);
+        links.extend(line_links);
+
+        if let Some(heading) = heading(&text) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(strip_inline(heading)));
+        } else if let Some((marker, item)) = list_item(&text) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Item {
+                marker,
+                text: strip_inline(item),
+            });
+        } else {
+            let text = strip_inline(&text);
+            if !text.is_empty() {
+                paragraph.push(text);
+            }
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+
+    (blocks, links)
+}
+
+/// `# Title` or a line that is entirely bold
+fn heading(line: &str) -> Option<&str> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+        return Some(line[hashes..].trim());
+    }
+    line.strip_prefix("**")
+        .and_then(|rest| rest.strip_suffix("**"))
+        .filter(|inner| !inner.is_empty() && !inner.contains("**"))
+}
+
+/// `- item`, `* item`, `• item`, `1. item` or `1) item`, as (marker, text)
+fn list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "• ", "・"] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            return Some(("•".to_string(), rest.trim()));
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 || digits > 3 {
+        return None;
+    }
+    let rest = &line[digits..];
+    let rest = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "))?;
+    Some((format!("{}.", &line[..digits]), rest.trim()))
+}
+
+/// Replace `[label](url)` with `label`, returning the http(s) links
+fn strip_links(line: &str) -> (String, Vec<Link>) {
+    let mut text = String::new();
+    let mut links = Vec::new();
+    let mut rest = line;
+
+    while let Some(open) = rest.find('[') {
+        let after_open = &rest[open + 1..];
+        let parsed = after_open.find("](").and_then(|close| {
+            let label = &after_open[..close];
+            let after_label = &after_open[close + 2..];
+            after_label.find(')').map(|end| (label, &after_label[..end], &after_label[end + 1..]))
+        });
+        match parsed {
+            Some((label, url, tail))
+                if !label.contains(['[', ']']) && (url.starts_with("http://") || url.starts_with("https://")) =>
+            {
+                text.push_str(&rest[..open]);
+                text.push_str(label);
+                links.push(Link {
+                    label: strip_inline(label),
+                    url: url.to_string(),
+                });
+                rest = tail;
+            }
+            _ => {
+                text.push_str(&rest[..open + 1]);
+                rest = after_open;
+            }
+        }
+    }
+    text.push_str(rest);
+
+    (text, links)
+}
+
+/// Remove bold, italic-underscore and code markers
+fn strip_inline(text: &str) -> String {
+    text.replace("**", "").replace("__", "").replace('`', "").trim().to_string()
+}
+
+/// At most `max` characters, ending with `…` when cut
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_follow_ups() {
+        let text = "Tokyo is the capital.\n\nFOLLOWUP: What is the population of Tokyo?\nFOLLOWUP: Best time to visit?\nFOLLOWUP: Best time to visit?";
+        let (answer, follow_ups) = extract_follow_ups(text);
+        assert_eq!(answer, "Tokyo is the capital.");
+        assert_eq!(follow_ups, vec!["What is the population of Tokyo?", "Best time to visit?"]);
+
+        let quick_reply = quick_reply(&follow_ups).unwrap();
+        assert_eq!(quick_reply.items.len(), 2);
+        assert_eq!(quick_reply.items[0].action.label, "What is the populat…");
+        assert_eq!(quick_reply.items[0].action.text, "What is the population of Tokyo?");
+        assert!(super::quick_reply(&[]).is_none());
+    }
+
+    #[test]
+    fn test_answer_bubble() {
+        assert!(answer_bubble("Just a sentence.").is_none());
+        assert!(answer_bubble("```\n- a\n- b\n```").is_none());
+
+        let markdown = "## Options\n\n1. **Train** is fastest\n2. Bus is cheaper\n\nSee [the timetable](https://example.com/timetable) for details.";
+        let bubble = answer_bubble(markdown).unwrap();
+        let contents = bubble["body"]["contents"].as_array().unwrap();
+        assert_eq!(contents[0]["text"], "Options");
+        assert_eq!(contents[0]["weight"], "bold");
+        assert_eq!(contents[1]["contents"][0]["text"], "1.");
+        assert_eq!(contents[1]["contents"][1]["text"], "Train is fastest");
+        assert_eq!(contents[3]["text"], "See the timetable for details.");
+
+        let button = &bubble["footer"]["contents"][0];
+        assert_eq!(button["action"]["uri"], "https://example.com/timetable");
+        assert_eq!(button["action"]["label"], "the timetable");
+
+        assert_eq!(
+            alt_text(markdown),
+            "Options\n1. Train is fastest\n2. Bus is cheaper\nSee the timetable for details."
+        );
+    }
+
+    #[test]
+    fn test_strip_links_keeps_other_brackets() {
+        let (text, links) = strip_links("[note] and [doc](ftp://x) and [ok](https://a.b)");
+        assert_eq!(text, "[note] and [doc](ftp://x) and ok");
+        let (text, _) = strip_links("[note] [ok](https://a.b)");
+        assert_eq!(text, "[note] ok");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].url, "https://a.b");
+    }
+}
@@ -18,11 +18,21 @@ pub struct LineProfile {
 /// LINE message event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LineMessage {
+    /// `text`, `image`, `audio`, `sticker`, ...
     #[serde(rename = "type")]
     pub message_type: String,
     pub id: String,
     #[serde(default)]
     pub text: Option<String>,
+    /// Sticker package (sticker messages)
+    #[serde(rename = "packageId", default)]
+    pub package_id: Option<String>,
+    /// Sticker ID (sticker messages)
+    #[serde(rename = "stickerId", default)]
+    pub sticker_id: Option<String>,
+    /// Words describing the sticker (sticker messages)
+    #[serde(default)]
+    pub keywords: Vec<String>,
 }
 
 /// LINE source (user, group, or room)
@@ -77,7 +87,104 @@ pub struct PushMessage {
 #[serde(tag = "type")]
 #[serde(rename_all = "lowercase")]
 pub enum MessageContent {
-    Text { text: String },
+    #[serde(rename_all = "camelCase")]
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        quick_reply: Option<QuickReply>,
+    },
+    /// Flex Message; `contents` is a bubble or carousel container
+    #[serde(rename_all = "camelCase")]
+    Flex {
+        alt_text: String,
+        contents: serde_json::Value,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        quick_reply: Option<QuickReply>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Sticker {
+        package_id: String,
+        sticker_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        quick_reply: Option<QuickReply>,
+    },
+}
+
+impl MessageContent {
+    /// Plain text message
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text {
+            text: text.into(),
+            quick_reply: None,
+        }
+    }
+
+    /// Flex Message with a bubble or carousel container
+    pub fn flex(alt_text: impl Into<String>, contents: serde_json::Value) -> Self {
+        Self::Flex {
+            alt_text: alt_text.into(),
+            contents,
+            quick_reply: None,
+        }
+    }
+
+    /// Sticker message
+    pub fn sticker(package_id: impl Into<String>, sticker_id: impl Into<String>) -> Self {
+        Self::Sticker {
+            package_id: package_id.into(),
+            sticker_id: sticker_id.into(),
+            quick_reply: None,
+        }
+    }
+
+    /// Attach quick reply buttons (shown for the last message sent)
+    pub fn with_quick_reply(mut self, quick_reply: QuickReply) -> Self {
+        match self {
+            Self::Text { quick_reply: ref mut q, .. }
+            | Self::Flex { quick_reply: ref mut q, .. }
+            | Self::Sticker { quick_reply: ref mut q, .. } => *q = Some(quick_reply),
+        }
+        self
+    }
+}
+
+/// Quick reply buttons
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickReply {
+    pub items: Vec<QuickReplyItem>,
+}
+
+/// A quick reply button
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickReplyItem {
+    /// Always `action`
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub action: QuickReplyAction,
+}
+
+/// Action of a quick reply button: sends `text` as the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickReplyAction {
+    /// Always `message`
+    #[serde(rename = "type")]
+    pub action_type: String,
+    pub label: String,
+    pub text: String,
+}
+
+impl QuickReplyItem {
+    /// Button labelled `label` that sends `text`
+    pub fn message(label: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            item_type: "action".to_string(),
+            action: QuickReplyAction {
+                action_type: "message".to_string(),
+                label: label.into(),
+                text: text.into(),
+            },
+        }
+    }
 }
 
 /// API response
@@ -96,3 +203,31 @@ pub struct ErrorDetail {
     pub message: String,
     pub property: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_message_content_json() {
+        let quick_reply = QuickReply {
+            items: vec![QuickReplyItem::message("More", "Tell me more")],
+        };
+        let message = MessageContent::text("Hi").with_quick_reply(quick_reply);
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "type": "text",
+                "text": "Hi",
+                "quickReply": { "items": [
+                    { "type": "action", "action": { "type": "message", "label": "More", "text": "Tell me more" } }
+                ] },
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(MessageContent::sticker("446", "1988")).unwrap(),
+            json!({ "type": "sticker", "packageId": "446", "stickerId": "1988" })
+        );
+    }
+}
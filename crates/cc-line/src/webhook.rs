@@ -11,16 +11,12 @@ use axum::{
     routing::post,
     Router,
 };
-use base64::{engine::general_purpose::STANDARD, Engine};
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use cc_core::{SignatureError, WebhookVerifier};
 use tracing::{debug, error, info, warn};
 
 use crate::handler::MessageHandler;
 use crate::types::WebhookBody;
 
-type HmacSha256 = Hmac<Sha256>;
-
 /// Webhook server state
 #[derive(Clone)]
 pub struct WebhookState {
@@ -45,19 +41,19 @@ async fn handle_webhook(
     let body = String::from_utf8(body.to_vec()).map_err(|_| StatusCode::BAD_REQUEST)?;
 
     // Verify signature
-    let signature = headers
-        .get("x-line-signature")
-        .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| {
-            warn!("Missing x-line-signature header");
-            StatusCode::BAD_REQUEST
+    let verifier = WebhookVerifier::Line {
+        channel_secret: state.channel_secret.clone(),
+    };
+    verifier
+        .verify(&headers, "", body.as_bytes())
+        .map_err(|e| {
+            warn!("Rejected LINE webhook: {}", e);
+            match e {
+                SignatureError::MissingHeader(_) => StatusCode::BAD_REQUEST,
+                SignatureError::Mismatch | SignatureError::StaleTimestamp => StatusCode::UNAUTHORIZED,
+            }
         })?;
 
-    if !verify_signature(&state.channel_secret, &body, signature) {
-        warn!("Invalid signature");
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
     // Parse webhook body
     let webhook: WebhookBody = serde_json::from_str(&body).map_err(|e| {
         error!("Failed to parse webhook body: {:?}", e);
@@ -77,20 +73,6 @@ async fn handle_webhook(
     Ok(StatusCode::OK)
 }
 
-/// Verify LINE signature
-fn verify_signature(channel_secret: &str, body: &str, signature: &str) -> bool {
-    let mut mac = match HmacSha256::new_from_slice(channel_secret.as_bytes()) {
-        Ok(m) => m,
-        Err(_) => return false,
-    };
-
-    mac.update(body.as_bytes());
-    let result = mac.finalize();
-    let computed = STANDARD.encode(result.into_bytes());
-
-    computed == signature
-}
-
 /// Start webhook server
 pub async fn start_webhook_server(
     state: WebhookState,
@@ -114,7 +96,12 @@ pub async fn start_webhook_server(
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use cc_core::webhook::verify_line;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    type HmacSha256 = Hmac<Sha256>;
 
     #[test]
     fn test_verify_signature() {
@@ -127,7 +114,7 @@ mod tests {
         let result = mac.finalize();
         let valid_signature = STANDARD.encode(result.into_bytes());
 
-        assert!(verify_signature(secret, body, &valid_signature));
-        assert!(!verify_signature(secret, body, "invalid_signature"));
+        assert!(verify_line(secret, body.as_bytes(), &valid_signature));
+        assert!(!verify_line(secret, body.as_bytes(), "invalid_signature"));
     }
 }
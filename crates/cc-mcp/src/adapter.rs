@@ -23,6 +23,8 @@ pub struct McpToolAdapter {
     description: String,
     /// JSON schema for input parameters
     input_schema: JsonValue,
+    /// Whether the server marks the tool as read-only
+    read_only: bool,
 }
 
 impl McpToolAdapter {
@@ -37,6 +39,7 @@ impl McpToolAdapter {
             name: tool.name,
             description: tool.description,
             input_schema: tool.input_schema,
+            read_only: tool.read_only,
         }
     }
 
@@ -63,6 +66,11 @@ impl Tool for McpToolAdapter {
         self.input_schema.clone()
     }
 
+    /// Whether the server marks the tool as read-only (`readOnlyHint`)
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Execute the tool with the given input
     ///
     /// # Arguments
@@ -72,7 +80,7 @@ impl Tool for McpToolAdapter {
     /// A ToolResult containing the output or error message
     async fn execute(&self, input: JsonValue) -> Result<ToolResult> {
         match self.client.call_tool(&self.name, input).await {
-            Ok(output) => Ok(ToolResult::success(output)),
+            Ok(output) => Ok(output),
             Err(e) => Ok(ToolResult::error(e.to_string())),
         }
     }
@@ -93,6 +101,7 @@ mod tests {
             name: "test_tool".to_string(),
             description: "A test tool".to_string(),
             input_schema: schema.clone(),
+            read_only: false,
         };
 
         // We can't create a full McpClient without a real connection,
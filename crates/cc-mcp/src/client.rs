@@ -10,7 +10,8 @@ use rmcp::{
 use serde_json::Value as JsonValue;
 use tokio::process::Command;
 
-use cc_core::Result;
+use cc_core::llm::ImageSource;
+use cc_core::{Result, ToolResult};
 
 /// MCP Tool information
 #[derive(Debug, Clone)]
@@ -21,6 +22,8 @@ pub struct McpTool {
     pub description: String,
     /// JSON schema for input parameters
     pub input_schema: JsonValue,
+    /// The server marks the tool as not modifying anything (`readOnlyHint`)
+    pub read_only: bool,
 }
 
 impl From<Tool> for McpTool {
@@ -33,6 +36,11 @@ impl From<Tool> for McpTool {
                 .unwrap_or_default()
                 .to_string(),
             input_schema: serde_json::to_value(&tool.input_schema).unwrap_or(JsonValue::Null),
+            read_only: tool
+                .annotations
+                .as_ref()
+                .and_then(|annotations| annotations.read_only_hint)
+                .unwrap_or(false),
         }
     }
 }
@@ -119,8 +127,8 @@ impl McpClient {
     /// * `args` - The arguments to pass to the tool
     ///
     /// # Returns
-    /// The tool execution result as a string
-    pub async fn call_tool(&self, name: &str, args: JsonValue) -> Result<String> {
+    /// The text of the result, with any images attached
+    pub async fn call_tool(&self, name: &str, args: JsonValue) -> Result<ToolResult> {
         let arguments = args.as_object().cloned();
         let name_str = name.to_string();
 
@@ -135,20 +143,21 @@ impl McpClient {
             .await
             .map_err(|e| cc_core::Error::Mcp(format!("Tool call failed: {}", e)))?;
 
-        // Extract text content from the result
-        let output = result
-            .content
-            .into_iter()
-            .filter_map(|c| {
-                if let rmcp::model::RawContent::Text(text) = c.raw {
-                    Some(text.text)
-                } else {
-                    None
+        // Text blocks become the output; images are passed on as image blocks
+        let mut texts = Vec::new();
+        let mut images = Vec::new();
+        for content in result.content {
+            match content.raw {
+                rmcp::model::RawContent::Text(text) => texts.push(text.text),
+                rmcp::model::RawContent::Image(image) => {
+                    images.push(ImageSource::base64(image.mime_type, image.data))
                 }
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+                _ => {}
+            }
+        }
 
+        let mut output = ToolResult::success(texts.join("\n"));
+        output.images = images;
         Ok(output)
     }
 
@@ -157,6 +157,19 @@ impl McpRegistry {
     pub fn client_count(&self) -> usize {
         self.clients.len()
     }
+
+    /// Check that every connected server still answers (lists its tools)
+    ///
+    /// # Returns
+    /// The server names with the error of each server that failed
+    pub async fn check_servers(&self) -> Vec<(String, Option<String>)> {
+        let mut results = Vec::with_capacity(self.clients.len());
+        for client in &self.clients {
+            let error = client.list_tools().await.err().map(|e| e.to_string());
+            results.push((client.server_name().to_string(), error));
+        }
+        results
+    }
 }
 
 impl Default for McpRegistry {
@@ -3,6 +3,7 @@
 //! TOML 形式の設定ファイルからスケジュールを読み込みます。
 
 use crate::error::{Result, ScheduleError};
+use crate::output::OutputTarget;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -10,7 +11,108 @@ use std::path::Path;
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ScheduleConfig {
     /// スケジュールタスクのリスト
+    #[serde(default)]
     pub schedules: Vec<ScheduleTask>,
+
+    /// バッチモードの設定
+    #[serde(default)]
+    pub batch: BatchSettings,
+
+    /// 実行履歴の設定
+    #[serde(default)]
+    pub history: HistorySettings,
+
+    /// 失敗通知の設定
+    #[serde(default)]
+    pub alerts: AlertSettings,
+
+    /// 単発リマインダーの設定
+    #[serde(default)]
+    pub reminders: ReminderSettings,
+}
+
+/// バッチモード (Message Batches API) の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchSettings {
+    /// バッチ結果 (JSONL) の保存先ディレクトリ
+    pub results_dir: String,
+
+    /// ステータス確認の間隔（秒）
+    pub poll_interval_secs: u64,
+
+    /// 結果を待つ最大時間（秒）
+    pub max_wait_secs: u64,
+}
+
+impl Default for BatchSettings {
+    fn default() -> Self {
+        Self {
+            results_dir: "data/batch_results".to_string(),
+            poll_interval_secs: 60,
+            max_wait_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// 実行履歴 (SQLite) の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// 実行履歴を記録する
+    pub enabled: bool,
+
+    /// データベースファイルのパス
+    pub db_path: String,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            db_path: "data/schedule_history.db".to_string(),
+        }
+    }
+}
+
+/// 連続失敗時の通知設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    /// 通知するまでの連続失敗回数（0 で無効）
+    pub consecutive_failures: u32,
+
+    /// 通知先（空の場合は通知しない）
+    pub outputs: Vec<OutputTarget>,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            consecutive_failures: 3,
+            outputs: Vec::new(),
+        }
+    }
+}
+
+/// 単発リマインダーの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReminderSettings {
+    /// リマインダーを有効にする
+    pub enabled: bool,
+
+    /// データベースファイルのパス
+    pub db_path: String,
+}
+
+impl Default for ReminderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            db_path: "data/reminders.db".to_string(),
+        }
+    }
 }
 
 /// 個別のスケジュールタスク
@@ -23,26 +125,61 @@ pub struct ScheduleTask {
     pub cron: String,
 
     /// AI に送信するプロンプト
+    #[serde(default)]
     pub prompt: String,
 
+    /// 実行するワークフロー名（指定時はプロンプトの代わりにワークフローを実行）
+    ///
+    /// `prompt` はワークフローの `prompt` 入力として渡されます。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<String>,
+
     /// 使用するツール（省略時は全ツール使用可）
     #[serde(default)]
     pub tools: Vec<String>,
 
-    /// 結果を送信する Discord チャンネル名（省略可）
+    /// 結果を送信する Discord チャンネル ID（省略可、`outputs` の短縮形）
     #[serde(default)]
     pub discord_channel: Option<String>,
 
+    /// 結果の送信先（省略時は送信しない）
+    #[serde(default)]
+    pub outputs: Vec<OutputTarget>,
+
     /// 有効/無効
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+
+    /// Message Batches API 経由で実行する（低コスト・非同期、結果は遅延）
+    #[serde(default)]
+    pub batch: bool,
 }
 
 fn default_enabled() -> bool {
     true
 }
 
+impl ScheduleTask {
+    /// `discord_channel` を含む全ての送信先
+    pub fn output_targets(&self) -> Vec<OutputTarget> {
+        let mut targets = self.outputs.clone();
+        if let Some(channel_id) = &self.discord_channel {
+            let legacy = OutputTarget::Discord {
+                channel_id: channel_id.clone(),
+            };
+            if !targets.contains(&legacy) {
+                targets.push(legacy);
+            }
+        }
+        targets
+    }
+}
+
 impl ScheduleConfig {
+    /// 設定ファイルを探すデフォルトパス（優先順）
+    pub const DEFAULT_PATHS: [&'static str; 3] =
+        ["schedule.toml", "config/schedule.toml", ".cc-gateway/schedule.toml"];
+
     /// TOML ファイルから設定を読み込む
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = std::fs::read_to_string(path.as_ref())
@@ -51,11 +188,17 @@ impl ScheduleConfig {
         Ok(config)
     }
 
+    /// TOML ファイルに保存する
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| ScheduleError::ConfigLoad(format!("TOML 変換エラー: {}", e)))?;
+        std::fs::write(path.as_ref(), content)?;
+        Ok(())
+    }
+
     /// デフォルトパスから設定を読み込む
     pub fn load_default() -> Result<Self> {
-        let paths = ["schedule.toml", "config/schedule.toml", ".cc-gateway/schedule.toml"];
-
-        for path in &paths {
+        for path in &Self::DEFAULT_PATHS {
             if Path::new(path).exists() {
                 return Self::from_file(path);
             }
@@ -87,5 +230,85 @@ prompt = "おはようございます"
         assert_eq!(config.schedules.len(), 1);
         assert_eq!(config.schedules[0].name, "毎朝の挨拶");
         assert!(config.schedules[0].enabled); // デフォルトで有効
+        assert!(!config.schedules[0].batch);
+        assert_eq!(config.batch.poll_interval_secs, 60);
+        assert!(config.history.enabled);
+        assert_eq!(config.alerts.consecutive_failures, 3);
+        assert!(config.alerts.outputs.is_empty());
+        assert!(config.reminders.enabled);
+    }
+
+    #[test]
+    fn test_parse_alerts() {
+        let toml = r#"
+[history]
+db_path = "/tmp/history.db"
+
+[alerts]
+consecutive_failures = 2
+
+[[alerts.outputs]]
+type = "discord"
+channel_id = "999"
+"#;
+        let config: ScheduleConfig = toml::from_str(toml).unwrap();
+        assert!(config.schedules.is_empty());
+        assert_eq!(config.history.db_path, "/tmp/history.db");
+        assert_eq!(config.alerts.consecutive_failures, 2);
+        assert_eq!(
+            config.alerts.outputs,
+            vec![OutputTarget::Discord {
+                channel_id: "999".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_batch_settings() {
+        let toml = r#"
+[batch]
+results_dir = "/tmp/results"
+poll_interval_secs = 30
+
+[[schedules]]
+name = "夜間レポート"
+cron = "0 2 * * *"
+prompt = "レポートを作成"
+batch = true
+"#;
+        let config: ScheduleConfig = toml::from_str(toml).unwrap();
+        assert!(config.schedules[0].batch);
+        assert_eq!(config.batch.results_dir, "/tmp/results");
+        assert_eq!(config.batch.poll_interval_secs, 30);
+        assert_eq!(config.batch.max_wait_secs, 24 * 60 * 60);
+    }
+
+    #[test]
+    fn test_parse_outputs() {
+        let toml = r#"
+[[schedules]]
+name = "日次サマリー"
+cron = "0 9 * * *"
+prompt = "サマリーを作成"
+discord_channel = "111"
+
+[[schedules.outputs]]
+type = "telegram"
+chat_id = "-100123"
+
+[[schedules.outputs]]
+type = "webhook"
+url = "https://example.com/hook"
+"#;
+        let config: ScheduleConfig = toml::from_str(toml).unwrap();
+        let targets = config.schedules[0].output_targets();
+        assert_eq!(targets.len(), 3);
+        assert_eq!(
+            targets[2],
+            OutputTarget::Discord {
+                channel_id: "111".to_string()
+            }
+        );
+        assert!(config.schedules[0].outputs.len() == 2);
     }
 }
@@ -20,6 +20,33 @@ pub enum ScheduleError {
     #[error("LLM error: {0}")]
     Llm(String),
 
+    #[error("タスクが見つかりません: {0}")]
+    TaskNotFound(String),
+
+    #[error("タスクは既に存在します: {0}")]
+    TaskExists(String),
+
+    #[error("リマインダーが見つかりません: {0}")]
+    ReminderNotFound(i64),
+
+    #[error("配信エラー: {0}")]
+    Delivery(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Workflow error: {0}")]
+    Workflow(#[from] cc_workflow::WorkflowError),
+
+    #[error("ワークフロー実行失敗: {0}")]
+    WorkflowFailed(String),
+
     #[error("Core error: {0}")]
     Core(#[from] cc_core::Error),
 
@@ -0,0 +1,203 @@
+//! 実行履歴
+//!
+//! スケジュールタスクの実行結果（ステータス・所要時間・トークン数・出力の抜粋）を
+//! SQLite に保存します。
+
+use crate::error::Result;
+use crate::scheduler::ScheduleResult;
+use cc_core::migrate::{migrate, Migration};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// 出力の抜粋として保存する最大文字数
+const EXCERPT_CHARS: usize = 500;
+
+/// `schedule_runs` テーブルのスキーマ
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create schedule_runs",
+    "CREATE TABLE IF NOT EXISTS schedule_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        task_name TEXT NOT NULL,
+        started_at TEXT NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        success INTEGER NOT NULL,
+        input_tokens INTEGER NOT NULL,
+        output_tokens INTEGER NOT NULL,
+        output_excerpt TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_schedule_runs_task ON schedule_runs(task_name, id);",
+)];
+
+/// 実行履歴の 1 レコード
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: i64,
+    /// タスク名
+    pub task_name: String,
+    /// 実行開始時刻
+    pub started_at: DateTime<Utc>,
+    /// 所要時間（ミリ秒）
+    pub duration_ms: u64,
+    /// 成功/失敗
+    pub success: bool,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// 応答（失敗時はエラーメッセージ）の抜粋
+    pub output_excerpt: String,
+}
+
+/// SQLite ベースの実行履歴ストア
+///
+/// クローンしてスケジューラーと HTTP API で共有できます。
+#[derive(Clone)]
+pub struct RunHistory {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl RunHistory {
+    /// データベースファイルを開く（親ディレクトリがなければ作成）
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// インメモリのストアを作成（テスト用）
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        migrate(&conn, "schedule_runs", MIGRATIONS)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 実行結果を記録
+    pub fn record(&self, result: &ScheduleResult) -> Result<()> {
+        let excerpt: String = result.response.chars().take(EXCERPT_CHARS).collect();
+        self.lock().execute(
+            "INSERT INTO schedule_runs
+                (task_name, started_at, duration_ms, success, input_tokens, output_tokens, output_excerpt)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                result.task_name,
+                result.executed_at.to_rfc3339(),
+                result.duration_ms as i64,
+                result.success,
+                result.input_tokens as i64,
+                result.output_tokens as i64,
+                excerpt,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 直近の実行履歴（新しい順）
+    ///
+    /// `task_name` を指定するとそのタスクのみに絞り込みます。
+    pub fn recent(&self, task_name: Option<&str>, limit: usize) -> Result<Vec<RunRecord>> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, task_name, started_at, duration_ms, success, input_tokens, output_tokens, output_excerpt
+             FROM schedule_runs
+             WHERE ?1 IS NULL OR task_name = ?1
+             ORDER BY id DESC
+             LIMIT ?2",
+        )?;
+
+        let records = stmt
+            .query_map(params![task_name, limit as i64], |row| {
+                let started_at: String = row.get(2)?;
+                Ok(RunRecord {
+                    id: row.get(0)?,
+                    task_name: row.get(1)?,
+                    started_at: DateTime::parse_from_rfc3339(&started_at)
+                        .map_err(|_| rusqlite::Error::InvalidQuery)?
+                        .with_timezone(&Utc),
+                    duration_ms: row.get::<_, i64>(3)? as u64,
+                    success: row.get(4)?,
+                    input_tokens: row.get::<_, i64>(5)? as u64,
+                    output_tokens: row.get::<_, i64>(6)? as u64,
+                    output_excerpt: row.get(7)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+
+    /// 直近の連続失敗回数
+    pub fn consecutive_failures(&self, task_name: &str) -> Result<u32> {
+        let count: i64 = self.lock().query_row(
+            "SELECT COUNT(*) FROM schedule_runs
+             WHERE task_name = ?1
+               AND success = 0
+               AND id > COALESCE(
+                   (SELECT MAX(id) FROM schedule_runs WHERE task_name = ?1 AND success = 1), 0)",
+            params![task_name],
+            |row| row.get(0),
+        )?;
+        Ok(count as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(task: &str, success: bool) -> ScheduleResult {
+        ScheduleResult {
+            task_name: task.to_string(),
+            executed_at: Utc::now(),
+            response: "x".repeat(EXCERPT_CHARS + 10),
+            success,
+            duration_ms: 1200,
+            input_tokens: 10,
+            output_tokens: 20,
+        }
+    }
+
+    #[test]
+    fn test_record_and_recent() {
+        let history = RunHistory::in_memory().unwrap();
+        history.record(&result("a", true)).unwrap();
+        history.record(&result("b", false)).unwrap();
+
+        let all = history.recent(None, 10).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].task_name, "b");
+        assert!(!all[0].success);
+        assert_eq!(all[1].output_excerpt.chars().count(), EXCERPT_CHARS);
+        assert_eq!(all[1].duration_ms, 1200);
+
+        let only_a = history.recent(Some("a"), 10).unwrap();
+        assert_eq!(only_a.len(), 1);
+        assert_eq!(only_a[0].output_tokens, 20);
+    }
+
+    #[test]
+    fn test_consecutive_failures() {
+        let history = RunHistory::in_memory().unwrap();
+        assert_eq!(history.consecutive_failures("a").unwrap(), 0);
+
+        history.record(&result("a", false)).unwrap();
+        history.record(&result("a", true)).unwrap();
+        history.record(&result("a", false)).unwrap();
+        history.record(&result("b", true)).unwrap();
+        history.record(&result("a", false)).unwrap();
+        assert_eq!(history.consecutive_failures("a").unwrap(), 2);
+
+        history.record(&result("a", true)).unwrap();
+        assert_eq!(history.consecutive_failures("a").unwrap(), 0);
+    }
+}
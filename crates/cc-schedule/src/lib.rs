@@ -4,8 +4,20 @@
 
 mod config;
 mod error;
+mod history;
+mod natural;
+mod output;
+mod reminder;
 mod scheduler;
+mod tool;
 
-pub use config::{ScheduleConfig, ScheduleTask};
+pub use config::{
+    AlertSettings, BatchSettings, HistorySettings, ReminderSettings, ScheduleConfig, ScheduleTask,
+};
 pub use error::{Result, ScheduleError};
-pub use scheduler::{Scheduler, SchedulerHandle};
+pub use history::{RunHistory, RunRecord};
+pub use natural::{parse_natural_schedule, parse_natural_time};
+pub use output::{OutputDispatcher, OutputTarget};
+pub use reminder::{Reminder, ReminderManager};
+pub use scheduler::{ScheduleResult, ScheduledTaskInfo, Scheduler, SchedulerHandle};
+pub use tool::{ReminderSetTool, ScheduleCreateTool};
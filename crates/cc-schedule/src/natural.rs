@@ -0,0 +1,331 @@
+//! 自然言語の日時指定の解釈
+//!
+//! - 繰り返し: "every weekday at 8am" や "毎週月曜 10時半" を 5 フィールドの cron 式に変換
+//! - 単発: "in 20 minutes" や "明日 15時" を日時に変換
+//!
+//! 時刻は UTC として扱います。
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// 時刻の指定がない場合の実行時刻
+const DEFAULT_TIME: (u32, u32) = (9, 0);
+
+static EVERY_MINUTES: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"every\s+(\d+)\s*(?:minutes?|mins?)\b|(\d+)\s*分(?:ごと|毎|おき)").unwrap()
+});
+static EVERY_HOURS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"every\s+(\d+)\s*(?:hours?|hrs?)\b|(\d+)\s*時間(?:ごと|毎|おき)").unwrap()
+});
+static HOURLY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"every\s+hour\b|\bhourly\b|毎時(?:\s*(\d{1,2})\s*分)?").unwrap()
+});
+static TIME_12H: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(\d{1,2})(?::(\d{2}))?\s*(am|pm)\b").unwrap()
+});
+static TIME_24H: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(\d{1,2}):(\d{2})\b").unwrap());
+static TIME_JA: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(午前|午後)?\s*(\d{1,2})\s*時(?:\s*(\d{1,2})\s*分|(半))?").unwrap()
+});
+static MONTH_DAY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(\d{1,2})(?:st|nd|rd|th)\b|毎月\s*(\d{1,2})\s*日").unwrap()
+});
+static WEEKDAY_JA: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([月火水木金土日])曜").unwrap());
+static RELATIVE_EN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bin\s+(\d+|an?|one)\s*(seconds?|secs?|minutes?|mins?|hours?|hrs?|days?|weeks?)\b")
+        .unwrap()
+});
+static RELATIVE_JA: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+)\s*(秒|分|時間|日|週間)後").unwrap());
+
+/// cron の曜日名（日曜始まり）
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+const DAYS_EN: [&str; 7] = [
+    "sunday",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+];
+const DAYS_JA: [&str; 7] = ["日", "月", "火", "水", "木", "金", "土"];
+
+/// 自然言語のスケジュール指定を 5 フィールドの cron 式に変換
+///
+/// 変換できない場合は `None` を返します。
+pub fn parse_natural_schedule(text: &str) -> Option<String> {
+    let text = text.trim().to_lowercase();
+
+    if let Some(n) = capture_number(&EVERY_MINUTES, &text).filter(|n| (1..60).contains(n)) {
+        return Some(format!("*/{} * * * *", n));
+    }
+    if let Some(n) = capture_number(&EVERY_HOURS, &text).filter(|n| (1..24).contains(n)) {
+        return Some(format!("0 */{} * * *", n));
+    }
+    if let Some(caps) = HOURLY.captures(&text) {
+        let minute = caps
+            .get(1)
+            .and_then(|m| m.as_str().parse::<u32>().ok())
+            .filter(|m| *m < 60)
+            .unwrap_or(0);
+        return Some(format!("{} * * * *", minute));
+    }
+
+    let (day_of_month, day_of_week) = parse_days(&text)?;
+    let (hour, minute) = parse_time(&text)?.unwrap_or(DEFAULT_TIME);
+    Some(format!(
+        "{} {} {} * {}",
+        minute, hour, day_of_month, day_of_week
+    ))
+}
+
+/// 単発の日時指定を解釈
+///
+/// 相対指定 ("in 20 minutes", "2時間後")、日付 + 時刻 ("tomorrow at 3pm", "明日 15時",
+/// "next friday 10:00")、時刻のみ ("at 5pm"、過ぎていれば翌日)、RFC 3339 に対応します。
+pub fn parse_natural_time(text: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let text = text.trim().to_lowercase();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(&text.to_uppercase()) {
+        return Some(time.with_timezone(&Utc));
+    }
+    if let Ok(time) = NaiveDateTime::parse_from_str(&text, "%Y-%m-%d %H:%M") {
+        return Some(Utc.from_utc_datetime(&time));
+    }
+
+    if let Some(caps) = RELATIVE_EN.captures(&text) {
+        let amount = match &caps[1] {
+            "a" | "an" | "one" => 1,
+            n => n.parse().ok()?,
+        };
+        return now.checked_add_signed(relative_duration(&caps[2], amount)?);
+    }
+    if let Some(caps) = RELATIVE_JA.captures(&text) {
+        return now.checked_add_signed(relative_duration(&caps[2], caps[1].parse().ok()?)?);
+    }
+
+    let time = parse_time(&text)?;
+    let date = if text.contains("day after tomorrow") || text.contains("明後日") {
+        now.date_naive() + Duration::days(2)
+    } else if text.contains("tomorrow") || text.contains("明日") {
+        now.date_naive() + Duration::days(1)
+    } else if let Some(weekday) = find_weekday(&text) {
+        // 次のその曜日（今日は含めない）
+        let days_ahead = (weekday + 7 - now.weekday().num_days_from_sunday()) % 7;
+        now.date_naive() + Duration::days(if days_ahead == 0 { 7 } else { days_ahead } as i64)
+    } else if time.is_some() {
+        // 時刻のみ: 過ぎていれば翌日
+        let (hour, minute) = time?;
+        let today = at(now.date_naive(), hour, minute)?;
+        return Some(if today > now { today } else { today + Duration::days(1) });
+    } else {
+        return None;
+    };
+
+    let (hour, minute) = time.unwrap_or(DEFAULT_TIME);
+    at(date, hour, minute)
+}
+
+/// 相対指定の長さ（範囲外の量は None）
+fn relative_duration(unit: &str, amount: i64) -> Option<Duration> {
+    if amount <= 0 {
+        return None;
+    }
+    match unit {
+        u if u.starts_with("sec") || u == "秒" => Duration::try_seconds(amount),
+        u if u.starts_with("min") || u == "分" => Duration::try_minutes(amount),
+        u if u.starts_with("h") || u == "時間" => Duration::try_hours(amount),
+        u if u.starts_with("day") || u == "日" => Duration::try_days(amount),
+        u if u.starts_with("week") || u == "週間" => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+/// 曜日の指定（日曜 = 0）
+fn find_weekday(text: &str) -> Option<u32> {
+    (0..7)
+        .find(|&i| text.contains(DAYS_EN[i]))
+        .or_else(|| {
+            let caps = WEEKDAY_JA.captures(text)?;
+            DAYS_JA.iter().position(|d| *d == &caps[1])
+        })
+        .map(|i| i as u32)
+}
+
+fn at(date: NaiveDate, hour: u32, minute: u32) -> Option<DateTime<Utc>> {
+    Some(Utc.from_utc_datetime(&date.and_hms_opt(hour, minute, 0)?))
+}
+
+/// 繰り返しの日付指定を (日, 曜日) の cron フィールドとして取得
+fn parse_days(text: &str) -> Option<(String, String)> {
+    let any = || "*".to_string();
+
+    if text.contains("weekday") || text.contains("平日") {
+        return Some((any(), "MON-FRI".to_string()));
+    }
+    if text.contains("weekend") || text.contains("週末") || text.contains("土日") {
+        return Some((any(), "SAT,SUN".to_string()));
+    }
+
+    let mut days: Vec<usize> = (0..7).filter(|&i| text.contains(DAYS_EN[i])).collect();
+    for caps in WEEKDAY_JA.captures_iter(text) {
+        if let Some(i) = DAYS_JA.iter().position(|d| *d == &caps[1]) {
+            days.push(i);
+        }
+    }
+    if !days.is_empty() {
+        days.sort_unstable();
+        days.dedup();
+        let names: Vec<&str> = days.iter().map(|&i| DAY_NAMES[i]).collect();
+        return Some((any(), names.join(",")));
+    }
+
+    if text.contains("month") || text.contains("毎月") {
+        let day = capture_number(&MONTH_DAY, text)
+            .filter(|d| (1..=31).contains(d))
+            .unwrap_or(1);
+        return Some((day.to_string(), any()));
+    }
+
+    let daily = ["every day", "everyday", "daily", "each day", "毎日", "毎朝", "毎晩"];
+    if daily.iter().any(|w| text.contains(w)) {
+        return Some((any(), any()));
+    }
+
+    None
+}
+
+/// 時刻を (時, 分) として取得
+///
+/// 時刻の指定がなければ `Some(None)`、不正な時刻なら `None` を返します。
+fn parse_time(text: &str) -> Option<Option<(u32, u32)>> {
+    if text.contains("noon") || text.contains("正午") {
+        return Some(Some((12, 0)));
+    }
+    if text.contains("midnight") {
+        return Some(Some((0, 0)));
+    }
+
+    if let Some(caps) = TIME_12H.captures(text) {
+        let hour: u32 = caps[1].parse().ok().filter(|h| (1..=12).contains(h))?;
+        let minute = caps.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+        let hour = match (&caps[3], hour) {
+            ("am", 12) => 0,
+            ("pm", h) if h < 12 => h + 12,
+            (_, h) => h,
+        };
+        return valid_time(hour, minute).map(Some);
+    }
+
+    if let Some(caps) = TIME_24H.captures(text) {
+        return valid_time(caps[1].parse().ok()?, caps[2].parse().ok()?).map(Some);
+    }
+
+    if let Some(caps) = TIME_JA.captures(text) {
+        let mut hour: u32 = caps[2].parse().ok()?;
+        let minute = match (caps.get(3), caps.get(4)) {
+            (Some(m), _) => m.as_str().parse().ok()?,
+            (None, Some(_)) => 30,
+            _ => 0,
+        };
+        if caps.get(1).map(|m| m.as_str()) == Some("午後") && hour < 12 {
+            hour += 12;
+        }
+        return valid_time(hour, minute).map(Some);
+    }
+
+    Some(None)
+}
+
+fn valid_time(hour: u32, minute: u32) -> Option<(u32, u32)> {
+    (hour < 24 && minute < 60).then_some((hour, minute))
+}
+
+/// 最初にマッチしたグループの数値を取得
+fn capture_number(re: &Regex, text: &str) -> Option<u32> {
+    re.captures(text)?
+        .iter()
+        .skip(1)
+        .flatten()
+        .next()?
+        .as_str()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cron(text: &str) -> Option<String> {
+        parse_natural_schedule(text)
+    }
+
+    #[test]
+    fn test_english() {
+        assert_eq!(
+            cron("every weekday at 8am").as_deref(),
+            Some("0 8 * * MON-FRI")
+        );
+        assert_eq!(cron("daily at 6:30 pm").as_deref(), Some("30 18 * * *"));
+        assert_eq!(
+            cron("every Monday and Thursday at 09:15").as_deref(),
+            Some("15 9 * * MON,THU")
+        );
+        assert_eq!(cron("every weekend at noon").as_deref(), Some("0 12 * * SAT,SUN"));
+        assert_eq!(cron("on the 15th of every month").as_deref(), Some("0 9 15 * *"));
+        assert_eq!(cron("every 15 minutes").as_deref(), Some("*/15 * * * *"));
+        assert_eq!(cron("every 2 hours").as_deref(), Some("0 */2 * * *"));
+        assert_eq!(cron("hourly").as_deref(), Some("0 * * * *"));
+        assert_eq!(cron("every day at 12am").as_deref(), Some("0 0 * * *"));
+    }
+
+    #[test]
+    fn test_japanese() {
+        assert_eq!(cron("平日の朝8時").as_deref(), Some("0 8 * * MON-FRI"));
+        assert_eq!(cron("毎日 午後3時半").as_deref(), Some("30 15 * * *"));
+        assert_eq!(cron("毎週月曜と水曜の10時").as_deref(), Some("0 10 * * MON,WED"));
+        assert_eq!(cron("毎月1日 9時").as_deref(), Some("0 9 1 * *"));
+        assert_eq!(cron("30分ごと").as_deref(), Some("*/30 * * * *"));
+        assert_eq!(cron("毎時15分").as_deref(), Some("15 * * * *"));
+    }
+
+    #[test]
+    fn test_natural_time() {
+        // 2026-10-16 (金) 10:00 UTC
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 10, 0, 0).unwrap();
+        let time = |text: &str| parse_natural_time(text, now).map(|t| t.to_rfc3339());
+
+        assert_eq!(time("in 20 minutes").as_deref(), Some("2026-10-16T10:20:00+00:00"));
+        assert_eq!(time("in an hour").as_deref(), Some("2026-10-16T11:00:00+00:00"));
+        assert_eq!(time("3日後").as_deref(), Some("2026-10-19T10:00:00+00:00"));
+        assert_eq!(time("tomorrow at 3pm").as_deref(), Some("2026-10-17T15:00:00+00:00"));
+        assert_eq!(time("明日 午前8時半").as_deref(), Some("2026-10-17T08:30:00+00:00"));
+        assert_eq!(time("at 9am").as_deref(), Some("2026-10-17T09:00:00+00:00"));
+        assert_eq!(time("at 5pm").as_deref(), Some("2026-10-16T17:00:00+00:00"));
+        assert_eq!(time("next monday").as_deref(), Some("2026-10-19T09:00:00+00:00"));
+        assert_eq!(time("金曜 12:00").as_deref(), Some("2026-10-23T12:00:00+00:00"));
+        assert_eq!(
+            time("2026-12-24T18:00:00Z").as_deref(),
+            Some("2026-12-24T18:00:00+00:00")
+        );
+        assert!(time("someday").is_none());
+        assert!(time("tomorrow at 25:00").is_none());
+
+        // 範囲外の量はパニックせずに None
+        assert!(time("in 200000000000 days").is_none());
+        assert!(time("in 9000000000000000000 weeks").is_none());
+        assert!(time("200000000000日後").is_none());
+    }
+
+    #[test]
+    fn test_unparseable() {
+        assert!(cron("tomorrow at 3pm").is_none());
+        assert!(cron("sometime").is_none());
+        assert!(cron("every day at 25:00").is_none());
+        assert!(cron("every 90 minutes").is_none());
+    }
+}
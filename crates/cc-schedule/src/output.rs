@@ -0,0 +1,447 @@
+//! スケジュール結果の配信
+//!
+//! タスクの実行結果を Discord / Telegram / Slack / メール / Webhook、
+//! または `[notify]` の論理ターゲットに送信します。
+//! 各サービスの認証情報は環境変数から読み込みます。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use cc_core::notify::{Notification, NotificationLevel, Notifier};
+use cc_core::ToolOrigin;
+use cc_email::EmailSender;
+use cc_email::send::EmailConfig;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::{Result, ScheduleError};
+use crate::scheduler::ScheduleResult;
+
+/// Discord のメッセージ上限
+const DISCORD_MAX_CHARS: usize = 2000;
+/// Telegram のメッセージ上限
+const TELEGRAM_MAX_CHARS: usize = 4096;
+/// Slack のメッセージ上限（推奨値）
+const SLACK_MAX_CHARS: usize = 40000;
+
+/// 結果の送信先
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum OutputTarget {
+    /// Discord チャンネル (チャンネル ID)
+    Discord { channel_id: String },
+    /// Telegram チャット (チャット ID)
+    Telegram { chat_id: String },
+    /// Slack チャンネル (チャンネル ID または名前)
+    Slack { channel: String },
+    /// メールアドレス
+    Email {
+        to: String,
+        /// 件名（省略時は "[cc-gateway] <タスク名>"）
+        #[serde(default)]
+        subject: Option<String>,
+    },
+    /// Webhook URL (JSON を POST)
+    Webhook {
+        url: String,
+        /// 追加の HTTP ヘッダー
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// `[notify]` の論理ターゲット名（例: "ops"）
+    Notify { target: String },
+}
+
+impl OutputTarget {
+    /// ログ用の短い説明
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Discord { channel_id } => format!("discord:{}", channel_id),
+            Self::Telegram { chat_id } => format!("telegram:{}", chat_id),
+            Self::Slack { channel } => format!("slack:{}", channel),
+            Self::Email { to, .. } => format!("email:{}", to),
+            Self::Webhook { url, .. } => format!("webhook:{}", url),
+            Self::Notify { target } => format!("notify:{}", target),
+        }
+    }
+
+    /// ツール呼び出し元のチャンネルに対応する送信先（未対応のプラットフォームは None）
+    pub fn from_origin(origin: &ToolOrigin) -> Option<Self> {
+        let id = origin.channel_id.clone();
+        match origin.platform.as_str() {
+            "discord" => Some(Self::Discord { channel_id: id }),
+            "telegram" => Some(Self::Telegram { chat_id: id }),
+            "slack" => Some(Self::Slack { channel: id }),
+            "email" => Some(Self::Email { to: id, subject: None }),
+            _ => None,
+        }
+    }
+}
+
+/// Webhook に送信する JSON
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    task: &'a str,
+    executed_at: String,
+    success: bool,
+    response: &'a str,
+}
+
+/// 結果を送信先に配信する
+#[derive(Clone, Default)]
+pub struct OutputDispatcher {
+    http: reqwest::Client,
+    discord_token: Option<String>,
+    telegram_token: Option<String>,
+    slack_token: Option<String>,
+    email: Option<EmailConfig>,
+    notifier: Option<Arc<Notifier>>,
+}
+
+impl OutputDispatcher {
+    /// 認証情報なしで作成（Webhook のみ利用可能）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 環境変数から認証情報を読み込む
+    ///
+    /// - `DISCORD_BOT_TOKEN`
+    /// - `TELEGRAM_BOT_TOKEN`
+    /// - `SLACK_BOT_TOKEN`
+    /// - `SMTP_HOST`, `SMTP_PORT`, `SMTP_USER`, `SMTP_PASS`, `SMTP_FROM`
+    pub fn from_env() -> Self {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+        Self {
+            http: reqwest::Client::new(),
+            discord_token: env("DISCORD_BOT_TOKEN"),
+            telegram_token: env("TELEGRAM_BOT_TOKEN"),
+            slack_token: env("SLACK_BOT_TOKEN"),
+            email: EmailConfig::from_env(),
+            notifier: None,
+        }
+    }
+
+    /// Discord Bot トークンを設定
+    pub fn with_discord_token(mut self, token: impl Into<String>) -> Self {
+        self.discord_token = Some(token.into());
+        self
+    }
+
+    /// Telegram Bot トークンを設定
+    pub fn with_telegram_token(mut self, token: impl Into<String>) -> Self {
+        self.telegram_token = Some(token.into());
+        self
+    }
+
+    /// Slack Bot トークンを設定
+    pub fn with_slack_token(mut self, token: impl Into<String>) -> Self {
+        self.slack_token = Some(token.into());
+        self
+    }
+
+    /// メール送信設定を設定
+    pub fn with_email(mut self, config: EmailConfig) -> Self {
+        self.email = Some(config);
+        self
+    }
+
+    /// `notify` 送信先で使う Notifier を設定
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// 全ての送信先に配信する
+    ///
+    /// 個別の送信失敗はログに記録し、残りの送信先への配信を続けます。
+    pub async fn dispatch(&self, targets: &[OutputTarget], result: &ScheduleResult) {
+        for target in targets {
+            match self.send(target, result).await {
+                Ok(()) => info!(
+                    task = %result.task_name,
+                    target = %target.describe(),
+                    "結果を送信しました"
+                ),
+                Err(e) => warn!(
+                    task = %result.task_name,
+                    target = %target.describe(),
+                    "結果の送信に失敗: {}",
+                    e
+                ),
+            }
+        }
+    }
+
+    /// 単一の送信先に配信する
+    pub async fn send(&self, target: &OutputTarget, result: &ScheduleResult) -> Result<()> {
+        match target {
+            OutputTarget::Discord { channel_id } => self.send_discord(channel_id, result).await,
+            OutputTarget::Telegram { chat_id } => self.send_telegram(chat_id, result).await,
+            OutputTarget::Slack { channel } => self.send_slack(channel, result).await,
+            OutputTarget::Email { to, subject } => {
+                self.send_email(to, subject.as_deref(), result).await
+            }
+            OutputTarget::Webhook { url, headers } => {
+                self.send_webhook(url, headers, result).await
+            }
+            OutputTarget::Notify { target } => self.send_notify(target, result).await,
+        }
+    }
+
+    async fn send_discord(&self, channel_id: &str, result: &ScheduleResult) -> Result<()> {
+        let token = require(&self.discord_token, "DISCORD_BOT_TOKEN")?;
+        let url = format!("https://discord.com/api/v10/channels/{}/messages", channel_id);
+
+        for chunk in split_message(&format_message(result), DISCORD_MAX_CHARS) {
+            let response = self
+                .http
+                .post(&url)
+                .header("Authorization", format!("Bot {}", token))
+                .json(&serde_json::json!({ "content": chunk }))
+                .send()
+                .await?;
+            check_status(response, "Discord").await?;
+        }
+        Ok(())
+    }
+
+    async fn send_telegram(&self, chat_id: &str, result: &ScheduleResult) -> Result<()> {
+        let token = require(&self.telegram_token, "TELEGRAM_BOT_TOKEN")?;
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", token);
+
+        for chunk in split_message(&format_message(result), TELEGRAM_MAX_CHARS) {
+            let response = self
+                .http
+                .post(&url)
+                .json(&serde_json::json!({ "chat_id": chat_id, "text": chunk }))
+                .send()
+                .await?;
+            check_status(response, "Telegram").await?;
+        }
+        Ok(())
+    }
+
+    async fn send_slack(&self, channel: &str, result: &ScheduleResult) -> Result<()> {
+        let token = require(&self.slack_token, "SLACK_BOT_TOKEN")?;
+
+        for chunk in split_message(&format_message(result), SLACK_MAX_CHARS) {
+            let response = self
+                .http
+                .post("https://slack.com/api/chat.postMessage")
+                .bearer_auth(token)
+                .json(&serde_json::json!({ "channel": channel, "text": chunk }))
+                .send()
+                .await?;
+            let body: serde_json::Value = check_status(response, "Slack").await?.json().await?;
+            // Slack は HTTP 200 でも ok: false を返す
+            if body.get("ok").and_then(|v| v.as_bool()) != Some(true) {
+                return Err(ScheduleError::Delivery(format!(
+                    "Slack API error: {}",
+                    body.get("error").and_then(|v| v.as_str()).unwrap_or("unknown")
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_email(
+        &self,
+        to: &str,
+        subject: Option<&str>,
+        result: &ScheduleResult,
+    ) -> Result<()> {
+        let config = self.email.clone().ok_or_else(|| {
+            ScheduleError::Delivery("SMTP_HOST が設定されていません".to_string())
+        })?;
+        let subject = subject
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("[cc-gateway] {}", result.task_name));
+
+        let sender = EmailSender::new(config).map_err(|e| ScheduleError::Delivery(e.to_string()))?;
+        sender
+            .send(to, &subject, &format_message(result), false)
+            .await
+            .map_err(|e| ScheduleError::Delivery(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn send_webhook(
+        &self,
+        url: &str,
+        headers: &HashMap<String, String>,
+        result: &ScheduleResult,
+    ) -> Result<()> {
+        let mut request = self.http.post(url).json(&webhook_payload(result));
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        check_status(request.send().await?, "Webhook").await?;
+        Ok(())
+    }
+
+    async fn send_notify(&self, target: &str, result: &ScheduleResult) -> Result<()> {
+        let notifier = self.notifier.as_ref().ok_or_else(|| {
+            ScheduleError::Delivery("[notify] が設定されていません".to_string())
+        })?;
+        notifier.notify(target, &notification(result)).await?;
+        Ok(())
+    }
+}
+
+/// 実行結果を通知に変換
+fn notification(result: &ScheduleResult) -> Notification {
+    let level = if result.success {
+        NotificationLevel::Info
+    } else {
+        NotificationLevel::Error
+    };
+    Notification::new(level, &result.task_name, &result.response)
+}
+
+fn require<'a>(token: &'a Option<String>, name: &str) -> Result<&'a str> {
+    token
+        .as_deref()
+        .ok_or_else(|| ScheduleError::Delivery(format!("{} が設定されていません", name)))
+}
+
+async fn check_status(response: reqwest::Response, service: &str) -> Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(ScheduleError::Delivery(format!(
+        "{} returned {}: {}",
+        service, status, body
+    )))
+}
+
+fn webhook_payload(result: &ScheduleResult) -> WebhookPayload<'_> {
+    WebhookPayload {
+        task: &result.task_name,
+        executed_at: result.executed_at.to_rfc3339(),
+        success: result.success,
+        response: &result.response,
+    }
+}
+
+/// チャット向けのメッセージ本文を作成
+fn format_message(result: &ScheduleResult) -> String {
+    if result.success {
+        format!("📅 {}\n\n{}", result.task_name, result.response)
+    } else {
+        format!("⚠️ {} の実行に失敗しました\n\n{}", result.task_name, result.response)
+    }
+}
+
+/// 文字数上限に合わせてメッセージを分割（可能なら改行で区切る）
+fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+
+    while rest.chars().count() > max_chars {
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let split_at = rest[..limit]
+            .rfind('\n')
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+
+        chunks.push(rest[..split_at].to_string());
+        rest = rest[split_at..].trim_start_matches('\n');
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest.to_string());
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn result(success: bool) -> ScheduleResult {
+        ScheduleResult {
+            task_name: "日次レポート".to_string(),
+            executed_at: Utc::now(),
+            response: "完了しました".to_string(),
+            success,
+            duration_ms: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_targets() {
+        let targets: Vec<OutputTarget> = serde_json::from_value(serde_json::json!([
+            {"type": "discord", "channel_id": "123"},
+            {"type": "telegram", "chat_id": "-100"},
+            {"type": "slack", "channel": "C01"},
+            {"type": "email", "to": "me@example.com"},
+            {"type": "webhook", "url": "https://example.com/hook", "headers": {"X-Token": "t"}},
+            {"type": "notify", "target": "ops"}
+        ]))
+        .unwrap();
+
+        assert_eq!(targets.len(), 6);
+        assert_eq!(targets[0].describe(), "discord:123");
+        assert!(matches!(&targets[3], OutputTarget::Email { subject: None, .. }));
+        assert!(matches!(&targets[4], OutputTarget::Webhook { headers, .. } if headers.len() == 1));
+        assert_eq!(targets[5].describe(), "notify:ops");
+    }
+
+    #[test]
+    fn test_format_message() {
+        assert!(format_message(&result(true)).starts_with("📅 日次レポート"));
+        assert!(format_message(&result(false)).starts_with("⚠️ 日次レポート"));
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let result = result(true);
+        let json = serde_json::to_value(webhook_payload(&result)).unwrap();
+        assert_eq!(json["task"], "日次レポート");
+        assert_eq!(json["success"], true);
+        assert_eq!(json["response"], "完了しました");
+    }
+
+    #[test]
+    fn test_split_message() {
+        assert_eq!(split_message("short", 10), vec!["short"]);
+        assert_eq!(split_message("", 10), vec![""]);
+
+        let chunks = split_message("line one\nline two\nline three", 12);
+        assert_eq!(chunks, vec!["line one", "line two", "line three"]);
+
+        // マルチバイト文字でも文字境界で分割する
+        let chunks = split_message(&"あ".repeat(25), 10);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 10));
+    }
+
+    #[tokio::test]
+    async fn test_missing_token_is_error() {
+        let dispatcher = OutputDispatcher::new();
+        let target = OutputTarget::Discord {
+            channel_id: "123".to_string(),
+        };
+        let err = dispatcher.send(&target, &result(true)).await.unwrap_err();
+        assert!(err.to_string().contains("DISCORD_BOT_TOKEN"));
+    }
+
+    #[test]
+    fn test_notification_level() {
+        assert_eq!(notification(&result(true)).level, NotificationLevel::Info);
+        let failed = notification(&result(false));
+        assert_eq!(failed.level, NotificationLevel::Error);
+        assert_eq!(failed.title, "日次レポート");
+    }
+}
@@ -0,0 +1,315 @@
+//! 単発リマインダー
+//!
+//! cron タスクとは別に、指定日時に 1 回だけメッセージを配信します。
+//! リマインダーは SQLite に保存され、再起動後も配信されます
+//! （停止中に期限を過ぎたものは起動直後に配信）。
+
+use crate::error::{Result, ScheduleError};
+use crate::output::{OutputDispatcher, OutputTarget};
+use crate::scheduler::ScheduleResult;
+use cc_core::migrate::{migrate, Migration};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// `reminders` テーブルのスキーマ
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create reminders",
+    "CREATE TABLE IF NOT EXISTS reminders (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        message TEXT NOT NULL,
+        due_at TEXT NOT NULL,
+        targets TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    )",
+)];
+
+/// 配信待ちがない場合の再確認間隔
+const IDLE_POLL: Duration = Duration::from_secs(3600);
+
+/// リマインダー
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: i64,
+    /// 配信するメッセージ
+    pub message: String,
+    /// 配信日時
+    pub due_at: DateTime<Utc>,
+    /// 送信先
+    pub targets: Vec<OutputTarget>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// リマインダーの管理と配信
+///
+/// クローンしてツールや HTTP API から共有できます。
+#[derive(Clone)]
+pub struct ReminderManager {
+    inner: Arc<ReminderInner>,
+}
+
+struct ReminderInner {
+    conn: Mutex<Connection>,
+    output: OutputDispatcher,
+    /// 追加・削除時に配信ループを起こす
+    wake: Notify,
+}
+
+impl ReminderManager {
+    /// データベースファイルを開く（親ディレクトリがなければ作成）
+    pub fn open<P: AsRef<Path>>(path: P, output: OutputDispatcher) -> Result<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?, output)
+    }
+
+    /// インメモリのストアを作成（テスト用）
+    pub fn in_memory(output: OutputDispatcher) -> Result<Self> {
+        Self::init(Connection::open_in_memory()?, output)
+    }
+
+    fn init(conn: Connection, output: OutputDispatcher) -> Result<Self> {
+        migrate(&conn, "reminders", MIGRATIONS)?;
+        Ok(Self {
+            inner: Arc::new(ReminderInner {
+                conn: Mutex::new(conn),
+                output,
+                wake: Notify::new(),
+            }),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.inner.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// リマインダーを追加
+    pub fn add(
+        &self,
+        message: impl Into<String>,
+        due_at: DateTime<Utc>,
+        targets: Vec<OutputTarget>,
+    ) -> Result<Reminder> {
+        let message = message.into();
+        let created_at = Utc::now();
+        let targets_json = serde_json::to_string(&targets)?;
+
+        let id = {
+            let conn = self.lock();
+            conn.execute(
+                "INSERT INTO reminders (message, due_at, targets, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![message, timestamp(due_at), targets_json, timestamp(created_at)],
+            )?;
+            conn.last_insert_rowid()
+        };
+        info!(id, due_at = %due_at, "リマインダーを追加しました");
+        self.inner.wake.notify_one();
+
+        Ok(Reminder {
+            id,
+            message,
+            due_at,
+            targets,
+            created_at,
+        })
+    }
+
+    /// リマインダーを取り消す
+    pub fn cancel(&self, id: i64) -> Result<()> {
+        let deleted = self
+            .lock()
+            .execute("DELETE FROM reminders WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(ScheduleError::ReminderNotFound(id));
+        }
+        self.inner.wake.notify_one();
+        Ok(())
+    }
+
+    /// 配信待ちのリマインダー（配信日時順）
+    pub fn pending(&self) -> Result<Vec<Reminder>> {
+        self.query("SELECT id, message, due_at, targets, created_at FROM reminders ORDER BY due_at, id", [])
+    }
+
+    /// 配信ループを開始
+    pub fn start(&self) -> JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            info!("リマインダーの配信を開始しました");
+            loop {
+                manager.deliver_due(Utc::now()).await;
+
+                let delay = match manager.next_due() {
+                    Ok(Some(due_at)) => (due_at - Utc::now()).to_std().unwrap_or(Duration::ZERO),
+                    Ok(None) => IDLE_POLL,
+                    Err(e) => {
+                        warn!("リマインダーの取得に失敗: {}", e);
+                        IDLE_POLL
+                    }
+                };
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = manager.inner.wake.notified() => {}
+                }
+            }
+        })
+    }
+
+    /// 期限を過ぎたリマインダーを配信して削除し、配信した件数を返す
+    async fn deliver_due(&self, now: DateTime<Utc>) -> usize {
+        let due = match self.query(
+            "SELECT id, message, due_at, targets, created_at FROM reminders
+             WHERE due_at <= ?1 ORDER BY due_at, id",
+            params![timestamp(now)],
+        ) {
+            Ok(due) => due,
+            Err(e) => {
+                warn!("リマインダーの取得に失敗: {}", e);
+                return 0;
+            }
+        };
+
+        for reminder in &due {
+            // 配信失敗で同じリマインダーを繰り返し送らないよう先に削除
+            if let Err(e) = self
+                .lock()
+                .execute("DELETE FROM reminders WHERE id = ?1", params![reminder.id])
+            {
+                warn!(id = reminder.id, "リマインダーの削除に失敗: {}", e);
+                continue;
+            }
+            let result = ScheduleResult {
+                task_name: "リマインダー".to_string(),
+                executed_at: now,
+                response: reminder.message.clone(),
+                success: true,
+                duration_ms: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+            };
+            self.inner.output.dispatch(&reminder.targets, &result).await;
+        }
+        due.len()
+    }
+
+    fn next_due(&self) -> Result<Option<DateTime<Utc>>> {
+        let due_at: Option<String> =
+            self.lock()
+                .query_row("SELECT MIN(due_at) FROM reminders", [], |row| row.get(0))?;
+        Ok(due_at
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|t| t.with_timezone(&Utc)))
+    }
+
+    fn query(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Reminder>> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare(sql)?;
+        let reminders = stmt
+            .query_map(params, |row| {
+                let due_at: String = row.get(2)?;
+                let targets: String = row.get(3)?;
+                let created_at: String = row.get(4)?;
+                Ok(Reminder {
+                    id: row.get(0)?,
+                    message: row.get(1)?,
+                    due_at: parse_timestamp(&due_at)?,
+                    targets: serde_json::from_str(&targets)
+                        .map_err(|_| rusqlite::Error::InvalidQuery)?,
+                    created_at: parse_timestamp(&created_at)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(reminders)
+    }
+}
+
+/// 文字列比較で順序が保たれる固定幅の RFC 3339 形式
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(value: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| rusqlite::Error::InvalidQuery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    #[test]
+    fn test_add_cancel_pending() {
+        let manager = ReminderManager::in_memory(OutputDispatcher::new()).unwrap();
+        let now = Utc::now();
+        let later = manager
+            .add("later", now + ChronoDuration::hours(2), Vec::new())
+            .unwrap();
+        let sooner = manager
+            .add(
+                "sooner",
+                now + ChronoDuration::minutes(5),
+                vec![OutputTarget::Discord {
+                    channel_id: "1".to_string(),
+                }],
+            )
+            .unwrap();
+
+        let pending = manager.pending().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].id, sooner.id);
+        assert_eq!(pending[0].targets.len(), 1);
+
+        manager.cancel(later.id).unwrap();
+        assert!(matches!(
+            manager.cancel(later.id),
+            Err(ScheduleError::ReminderNotFound(_))
+        ));
+        assert_eq!(manager.pending().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_due_removes_reminders() {
+        let manager = ReminderManager::in_memory(OutputDispatcher::new()).unwrap();
+        let now = Utc::now();
+        manager
+            .add("overdue", now - ChronoDuration::minutes(1), Vec::new())
+            .unwrap();
+        manager
+            .add("future", now + ChronoDuration::hours(1), Vec::new())
+            .unwrap();
+
+        assert_eq!(manager.deliver_due(now).await, 1);
+        assert_eq!(manager.deliver_due(now).await, 0);
+
+        let pending = manager.pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].message, "future");
+        assert_eq!(manager.next_due().unwrap(), Some(pending[0].due_at));
+    }
+
+    #[test]
+    fn test_persists_across_reopen() {
+        let path = std::env::temp_dir().join(format!("cc-reminders-{}.db", std::process::id()));
+        let due_at = Utc::now() + ChronoDuration::days(1);
+        {
+            let manager = ReminderManager::open(&path, OutputDispatcher::new()).unwrap();
+            manager.add("persisted", due_at, Vec::new()).unwrap();
+        }
+        let manager = ReminderManager::open(&path, OutputDispatcher::new()).unwrap();
+        let pending = manager.pending().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].message, "persisted");
+    }
+}
@@ -1,37 +1,225 @@
 //! スケジューラー
 //!
 //! cron スケジュールに基づいてタスクを実行します。
+//! 実行中のタスクは [`SchedulerHandle`] から追加・削除・一時停止・即時実行できます。
 
-use crate::config::{ScheduleConfig, ScheduleTask};
+use crate::config::{AlertSettings, BatchSettings, ScheduleConfig, ScheduleTask};
 use crate::error::{Result, ScheduleError};
-use cc_core::{ClaudeClient, ToolManager};
+use crate::history::RunHistory;
+use crate::output::OutputDispatcher;
+use cc_core::{ClaudeClient, PromptContext, PromptLibrary, ToolManager};
+use cc_workflow::WorkflowEngine;
 use chrono::{DateTime, Utc};
 use cron::Schedule as CronSchedule;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::broadcast;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Notify};
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
 /// スケジューラーのハンドル
+///
+/// クローンして HTTP API などから共有できます。
+#[derive(Clone)]
 pub struct SchedulerHandle {
+    inner: Arc<SchedulerInner>,
+}
+
+struct SchedulerInner {
+    ctx: TaskContext,
+    /// 登録済みタスク（設定ファイルの順序を保持）
+    slots: Mutex<Vec<TaskSlot>>,
     /// スケジューラータスクの終了送信
     shutdown_tx: broadcast::Sender<()>,
-    /// 実行中のタスクハンドル
+    /// 変更を書き戻す設定ファイル
+    config_path: Option<PathBuf>,
+    /// 書き戻し時に保持するタスク以外の設定
+    settings: ScheduleConfig,
+}
+
+/// 実行中のタスク
+struct TaskSlot {
+    task: ScheduleTask,
+    schedule: CronSchedule,
+    /// false の間は cron による実行をスキップ
+    enabled: Arc<AtomicBool>,
+    /// 即時実行の通知
+    trigger: Arc<Notify>,
     handle: JoinHandle<()>,
 }
 
+/// タスクの状態（一覧表示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledTaskInfo {
+    #[serde(flatten)]
+    pub task: ScheduleTask,
+    /// 次回実行時刻（一時停止中は None）
+    pub next_run: Option<DateTime<Utc>>,
+}
+
 impl SchedulerHandle {
+    /// タスクを追加して開始
+    pub fn add_task(&self, task: ScheduleTask) -> Result<()> {
+        {
+            let mut slots = self.lock_slots();
+            if slots.iter().any(|s| s.task.name == task.name) {
+                return Err(ScheduleError::TaskExists(task.name));
+            }
+            let slot = spawn_task(task, &self.inner.ctx, &self.inner.shutdown_tx)?;
+            info!(task = %slot.task.name, "タスクを追加しました");
+            slots.push(slot);
+        }
+        self.persist();
+        Ok(())
+    }
+
+    /// タスクを停止して削除
+    pub fn remove_task(&self, name: &str) -> Result<ScheduleTask> {
+        let slot = {
+            let mut slots = self.lock_slots();
+            let index = slots
+                .iter()
+                .position(|s| s.task.name == name)
+                .ok_or_else(|| ScheduleError::TaskNotFound(name.to_string()))?;
+            slots.remove(index)
+        };
+        slot.handle.abort();
+        info!(task = %name, "タスクを削除しました");
+        self.persist();
+        Ok(slot.task)
+    }
+
+    /// タスクを一時停止（cron による実行をスキップ）
+    pub fn pause(&self, name: &str) -> Result<()> {
+        self.set_enabled(name, false)
+    }
+
+    /// 一時停止したタスクを再開
+    pub fn resume(&self, name: &str) -> Result<()> {
+        self.set_enabled(name, true)
+    }
+
+    /// タスクを即時実行（一時停止中でも実行）
+    pub fn trigger_now(&self, name: &str) -> Result<()> {
+        let slots = self.lock_slots();
+        let slot = slots
+            .iter()
+            .find(|s| s.task.name == name)
+            .ok_or_else(|| ScheduleError::TaskNotFound(name.to_string()))?;
+        slot.trigger.notify_one();
+        info!(task = %name, "タスクの即時実行を要求しました");
+        Ok(())
+    }
+
+    /// 全タスクと次回実行時刻を取得
+    pub fn list_with_next_run(&self) -> Vec<ScheduledTaskInfo> {
+        self.lock_slots()
+            .iter()
+            .map(|slot| {
+                let enabled = slot.enabled.load(Ordering::SeqCst);
+                let mut task = slot.task.clone();
+                task.enabled = enabled;
+                ScheduledTaskInfo {
+                    next_run: enabled
+                        .then(|| slot.schedule.upcoming(Utc).next())
+                        .flatten(),
+                    task,
+                }
+            })
+            .collect()
+    }
+
+    /// 実行ループが終了したタスク（パニックなど）の名前
+    pub fn stopped_tasks(&self) -> Vec<String> {
+        self.lock_slots()
+            .iter()
+            .filter(|s| s.handle.is_finished())
+            .map(|s| s.task.name.clone())
+            .collect()
+    }
+
+    /// 実行ループが終了したタスクを起動し直す
+    ///
+    /// 一時停止の状態はそのまま引き継ぎます。起動し直したタスクの名前を返します。
+    pub fn restart_stopped_tasks(&self) -> Vec<String> {
+        let mut restarted = Vec::new();
+        for slot in self.lock_slots().iter_mut().filter(|s| s.handle.is_finished()) {
+            slot.handle = tokio::spawn(run_schedule_task(
+                slot.task.clone(),
+                slot.schedule.clone(),
+                self.inner.ctx.clone(),
+                Arc::clone(&slot.enabled),
+                Arc::clone(&slot.trigger),
+                self.inner.shutdown_tx.subscribe(),
+            ));
+            warn!(task = %slot.task.name, "停止したタスクを再起動しました");
+            restarted.push(slot.task.name.clone());
+        }
+        restarted
+    }
+
+    /// 実行履歴ストア（設定されている場合）
+    pub fn history(&self) -> Option<&RunHistory> {
+        self.inner.ctx.history.as_ref()
+    }
+
     /// スケジューラーを停止
-    pub async fn stop(self) {
-        let _ = self.shutdown_tx.send(());
-        let _ = self.handle.await;
+    pub async fn stop(&self) {
+        let _ = self.inner.shutdown_tx.send(());
+        let handles: Vec<JoinHandle<()>> = {
+            let mut slots = self.lock_slots();
+            slots.drain(..).map(|s| s.handle).collect()
+        };
+        for handle in handles {
+            let _ = handle.await;
+        }
+        info!("スケジューラーを停止しました");
+    }
+
+    fn set_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        {
+            let mut slots = self.lock_slots();
+            let slot = slots
+                .iter_mut()
+                .find(|s| s.task.name == name)
+                .ok_or_else(|| ScheduleError::TaskNotFound(name.to_string()))?;
+            slot.enabled.store(enabled, Ordering::SeqCst);
+            slot.task.enabled = enabled;
+        }
+        info!(
+            task = %name,
+            "タスクを{}しました",
+            if enabled { "再開" } else { "一時停止" }
+        );
+        self.persist();
+        Ok(())
+    }
+
+    fn lock_slots(&self) -> std::sync::MutexGuard<'_, Vec<TaskSlot>> {
+        self.inner.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 現在のタスク一覧を設定ファイルに書き戻す
+    fn persist(&self) {
+        let Some(path) = &self.inner.config_path else {
+            return;
+        };
+        let config = ScheduleConfig {
+            schedules: self.lock_slots().iter().map(|s| s.task.clone()).collect(),
+            ..self.inner.settings.clone()
+        };
+        match config.save(path) {
+            Ok(()) => info!(path = %path.display(), "スケジュール設定を保存しました"),
+            Err(e) => warn!(path = %path.display(), "スケジュール設定の保存に失敗: {}", e),
+        }
     }
 }
 
 /// スケジュール実行結果
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct ScheduleResult {
     /// タスク名
     pub task_name: String,
@@ -41,6 +229,24 @@ pub struct ScheduleResult {
     pub response: String,
     /// 成功/失敗
     pub success: bool,
+    /// 所要時間（ミリ秒）
+    pub duration_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// タスク実行に必要な共有リソース
+#[derive(Clone)]
+struct TaskContext {
+    client: ClaudeClient,
+    tool_manager: Arc<ToolManager>,
+    system_prompt: String,
+    prompts: Option<Arc<PromptLibrary>>,
+    batch: BatchSettings,
+    output: OutputDispatcher,
+    history: Option<RunHistory>,
+    alerts: AlertSettings,
+    workflows: Option<Arc<WorkflowEngine>>,
 }
 
 /// スケジューラー
@@ -49,6 +255,11 @@ pub struct Scheduler {
     client: ClaudeClient,
     tool_manager: Arc<ToolManager>,
     system_prompt: String,
+    prompts: Option<Arc<PromptLibrary>>,
+    output: OutputDispatcher,
+    config_path: Option<PathBuf>,
+    history: Option<RunHistory>,
+    workflows: Option<Arc<WorkflowEngine>>,
 }
 
 impl Scheduler {
@@ -65,6 +276,11 @@ impl Scheduler {
             system_prompt: "あなたはスケジュールされたタスクを実行する AI アシスタントです。\
                 指示に従って作業を行い、結果を報告してください。"
                 .to_string(),
+            prompts: None,
+            output: OutputDispatcher::new(),
+            config_path: None,
+            history: None,
+            workflows: None,
         }
     }
 
@@ -74,64 +290,127 @@ impl Scheduler {
         self
     }
 
-    /// スケジューラーを開始
-    pub fn start(self) -> SchedulerHandle {
-        let (shutdown_tx, shutdown_rx) = broadcast::channel::<()>(1);
-        let shutdown_tx_clone = shutdown_tx.clone();
+    /// プロンプトテンプレートを設定
+    ///
+    /// `schedules/<タスク名>` または `scheduler` テンプレートがあれば、
+    /// 実行ごとにレンダリングしてシステムプロンプトとして使用します。
+    pub fn with_prompt_library(mut self, prompts: Arc<PromptLibrary>) -> Self {
+        self.prompts = Some(prompts);
+        self
+    }
 
-        let handle = tokio::spawn(async move {
-            info!("スケジューラーを開始しました ({} タスク)", self.config.schedules.len());
+    /// 結果の配信設定を設定
+    ///
+    /// タスクの `outputs` / `discord_channel` に実行結果を送信します。
+    pub fn with_output_dispatcher(mut self, output: OutputDispatcher) -> Self {
+        self.output = output;
+        self
+    }
 
-            // 各タスクを別々のタスクで実行
-            let mut task_handles = Vec::new();
+    /// 実行時の変更（追加・削除・一時停止）を書き戻す設定ファイルを設定
+    ///
+    /// 書き戻し時にファイル内のコメントは失われます。
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
 
-            for task in self.config.enabled_tasks() {
-                let task = task.clone();
-                let client = self.client.clone();
-                let tool_manager = Arc::clone(&self.tool_manager);
-                let system_prompt = self.system_prompt.clone();
-                let mut rx = shutdown_rx.resubscribe();
+    /// 実行履歴ストアを設定
+    ///
+    /// 各実行を記録し、`[alerts]` の回数だけ連続で失敗したら通知します。
+    pub fn with_run_history(mut self, history: RunHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
 
-                let handle = tokio::spawn(async move {
-                    run_schedule_task(task, client, tool_manager, system_prompt, &mut rx).await;
-                });
+    /// ワークフローエンジンを設定
+    ///
+    /// `workflow` を指定したタスクはプロンプトの代わりにワークフローを実行します。
+    pub fn with_workflow_engine(mut self, workflows: Arc<WorkflowEngine>) -> Self {
+        self.workflows = Some(workflows);
+        self
+    }
 
-                task_handles.push(handle);
-            }
+    /// スケジューラーを開始
+    ///
+    /// 無効 (`enabled = false`) なタスクも一時停止状態で登録されます。
+    pub fn start(self) -> SchedulerHandle {
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let mut settings = self.config;
+        let schedules = std::mem::take(&mut settings.schedules);
+        let ctx = TaskContext {
+            client: self.client,
+            tool_manager: self.tool_manager,
+            system_prompt: self.system_prompt,
+            prompts: self.prompts,
+            batch: settings.batch.clone(),
+            output: self.output,
+            history: self.history,
+            alerts: settings.alerts.clone(),
+            workflows: self.workflows,
+        };
 
-            // 全タスクが終了するまで待機
-            for handle in task_handles {
-                let _ = handle.await;
+        let mut slots = Vec::new();
+        for task in schedules {
+            let name = task.name.clone();
+            match spawn_task(task, &ctx, &shutdown_tx) {
+                Ok(slot) => slots.push(slot),
+                Err(e) => error!(task = %name, "タスクを開始できません: {}", e),
             }
-
-            info!("スケジューラーを停止しました");
-        });
+        }
+        info!("スケジューラーを開始しました ({} タスク)", slots.len());
 
         SchedulerHandle {
-            shutdown_tx: shutdown_tx_clone,
-            handle,
+            inner: Arc::new(SchedulerInner {
+                ctx,
+                slots: Mutex::new(slots),
+                shutdown_tx,
+                config_path: self.config_path,
+                settings,
+            }),
         }
     }
 }
 
+/// タスクの実行ループを起動
+fn spawn_task(
+    task: ScheduleTask,
+    ctx: &TaskContext,
+    shutdown_tx: &broadcast::Sender<()>,
+) -> Result<TaskSlot> {
+    let schedule = parse_cron(&task.cron)?;
+    let enabled = Arc::new(AtomicBool::new(task.enabled));
+    let trigger = Arc::new(Notify::new());
+
+    let handle = tokio::spawn(run_schedule_task(
+        task.clone(),
+        schedule.clone(),
+        ctx.clone(),
+        Arc::clone(&enabled),
+        Arc::clone(&trigger),
+        shutdown_tx.subscribe(),
+    ));
+
+    Ok(TaskSlot {
+        task,
+        schedule,
+        enabled,
+        trigger,
+        handle,
+    })
+}
+
 /// 個別のスケジュールタスクを実行
 async fn run_schedule_task(
     task: ScheduleTask,
-    client: ClaudeClient,
-    tool_manager: Arc<ToolManager>,
-    system_prompt: String,
-    shutdown_rx: &mut broadcast::Receiver<()>,
+    schedule: CronSchedule,
+    ctx: TaskContext,
+    enabled: Arc<AtomicBool>,
+    trigger: Arc<Notify>,
+    mut shutdown_rx: broadcast::Receiver<()>,
 ) {
-    // cron スケジュールをパース
-    let schedule = match parse_cron(&task.cron) {
-        Ok(s) => s,
-        Err(e) => {
-            error!(task = %task.name, "cron パースエラー: {}", e);
-            return;
-        }
-    };
-
     info!(task = %task.name, cron = %task.cron, "スケジュールタスクを開始");
+    let targets = task.output_targets();
 
     loop {
         // 次の実行時刻を取得
@@ -145,42 +424,164 @@ async fn run_schedule_task(
         };
 
         let delay = (next - now).to_std().unwrap_or(Duration::ZERO);
-        info!(
-            task = %task.name,
-            next = %next.format("%Y-%m-%d %H:%M:%S"),
-            "次回実行まで待機中"
-        );
+        if enabled.load(Ordering::SeqCst) {
+            info!(
+                task = %task.name,
+                next = %next.format("%Y-%m-%d %H:%M:%S"),
+                "次回実行まで待機中"
+            );
+        }
 
-        // 実行時刻まで待機（シャットダウン確認付き）
+        // 実行時刻・即時実行・シャットダウンのいずれかを待つ
         tokio::select! {
             _ = tokio::time::sleep(delay) => {
-                // 実行時刻になった
-                info!(task = %task.name, "スケジュールタスクを実行");
-
-                match execute_task(&task, &client, &tool_manager, &system_prompt).await {
-                    Ok(response) => {
-                        info!(task = %task.name, "タスク完了: {}", truncate(&response, 100));
-                    }
-                    Err(e) => {
-                        error!(task = %task.name, "タスク失敗: {}", e);
-                    }
+                if !enabled.load(Ordering::SeqCst) {
+                    continue;
                 }
+                info!(task = %task.name, "スケジュールタスクを実行");
+            }
+            _ = trigger.notified() => {
+                info!(task = %task.name, "スケジュールタスクを即時実行");
             }
             _ = shutdown_rx.recv() => {
                 info!(task = %task.name, "シャットダウン要求を受信");
                 break;
             }
         }
+
+        let result = run_once(&task, &ctx).await;
+        if !targets.is_empty() {
+            ctx.output.dispatch(&targets, &result).await;
+        }
+        record_run(&result, &ctx).await;
+    }
+}
+
+/// 実行結果を履歴に記録し、連続失敗が閾値に達したら通知
+async fn record_run(result: &ScheduleResult, ctx: &TaskContext) {
+    let Some(history) = &ctx.history else {
+        return;
+    };
+    if let Err(e) = history.record(result) {
+        warn!(task = %result.task_name, "実行履歴の記録に失敗: {}", e);
+        return;
+    }
+
+    let threshold = ctx.alerts.consecutive_failures;
+    if result.success || threshold == 0 || ctx.alerts.outputs.is_empty() {
+        return;
+    }
+    match history.consecutive_failures(&result.task_name) {
+        // 閾値に達した時点で 1 回だけ通知（成功するまで再通知しない）
+        Ok(failures) if failures == threshold => {
+            warn!(task = %result.task_name, failures, "連続失敗の通知を送信");
+            let alert = ScheduleResult {
+                response: format!(
+                    "{} 回連続で失敗しています。\n\n最後のエラー: {}",
+                    failures, result.response
+                ),
+                ..result.clone()
+            };
+            ctx.output.dispatch(&ctx.alerts.outputs, &alert).await;
+        }
+        Ok(_) => {}
+        Err(e) => warn!(task = %result.task_name, "連続失敗回数の取得に失敗: {}", e),
     }
 }
 
-/// タスクを実行して AI の応答を取得
+/// タスクを 1 回実行して結果を返す
+async fn run_once(task: &ScheduleTask, ctx: &TaskContext) -> ScheduleResult {
+    let system_prompt = render_system_prompt(task, ctx.prompts.as_deref())
+        .unwrap_or_else(|| ctx.system_prompt.clone());
+
+    let executed_at = Utc::now();
+    let started = Instant::now();
+    let outcome = match &task.workflow {
+        Some(workflow) => execute_workflow(task, workflow, ctx.workflows.as_deref()).await,
+        None => {
+            execute_task(
+                task,
+                &ctx.client,
+                &ctx.tool_manager,
+                &system_prompt,
+                &ctx.batch,
+            )
+            .await
+        }
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    match outcome {
+        Ok((response, usage)) => {
+            info!(task = %task.name, "タスク完了: {}", truncate(&response, 100));
+            ScheduleResult {
+                task_name: task.name.clone(),
+                executed_at,
+                response,
+                success: true,
+                duration_ms,
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+            }
+        }
+        Err(e) => {
+            error!(task = %task.name, "タスク失敗: {}", e);
+            ScheduleResult {
+                task_name: task.name.clone(),
+                executed_at,
+                response: e.to_string(),
+                success: false,
+                duration_ms,
+                input_tokens: 0,
+                output_tokens: 0,
+            }
+        }
+    }
+}
+
+/// テンプレートからタスク用のシステムプロンプトを生成
+fn render_system_prompt(task: &ScheduleTask, prompts: Option<&PromptLibrary>) -> Option<String> {
+    let context = PromptContext::new()
+        .agent("scheduler")
+        .var("task", &task.name);
+    let task_template = format!("schedules/{}", task.name);
+    prompts?.render_first(&[task_template.as_str(), "scheduler"], &context)
+}
+
+/// ワークフローを実行して出力とトークン使用量を取得
+async fn execute_workflow(
+    task: &ScheduleTask,
+    workflow: &str,
+    engine: Option<&WorkflowEngine>,
+) -> Result<(String, cc_core::Usage)> {
+    let engine = engine.ok_or_else(|| {
+        ScheduleError::WorkflowFailed(format!("ワークフローエンジンが設定されていません: {}", workflow))
+    })?;
+
+    let mut inputs = serde_json::Map::new();
+    if !task.prompt.is_empty() {
+        inputs.insert("prompt".to_string(), task.prompt.clone().into());
+    }
+    let run = engine.run_named(workflow, inputs).await?;
+    let usage = cc_core::Usage {
+        input_tokens: run.input_tokens,
+        output_tokens: run.output_tokens,
+        ..Default::default()
+    };
+    match run.error {
+        None => Ok((run.output, usage)),
+        Some(e) => Err(ScheduleError::WorkflowFailed(e)),
+    }
+}
+
+/// タスクを実行して AI の応答とトークン使用量を取得
 async fn execute_task(
     task: &ScheduleTask,
     client: &ClaudeClient,
     tool_manager: &ToolManager,
     system_prompt: &str,
-) -> Result<String> {
+    batch: &BatchSettings,
+) -> Result<(String, cc_core::Usage)> {
     use cc_core::llm::MessagesRequest;
 
     // ユーザーメッセージを作成
@@ -205,9 +606,15 @@ async fn execute_task(
         messages,
         tools: Some(tools),
         thinking: None,
+        tool_choice: None,
+        temperature: None,
     };
 
-    let response = client.messages(request).await?;
+    let response = if task.batch {
+        execute_batch(task, client, request, batch).await?
+    } else {
+        client.messages(request).await?
+    };
 
     // テキスト応答を抽出
     let text = response
@@ -223,7 +630,67 @@ async fn execute_task(
         .collect::<Vec<_>>()
         .join("\n");
 
-    Ok(text)
+    Ok((text, response.usage.unwrap_or_default()))
+}
+
+/// Message Batches API 経由でリクエストを実行し、結果を保存する
+async fn execute_batch(
+    task: &ScheduleTask,
+    client: &ClaudeClient,
+    request: cc_core::llm::MessagesRequest,
+    settings: &BatchSettings,
+) -> Result<cc_core::MessagesResponse> {
+    use cc_core::llm::{BatchRequest, BatchResultType};
+
+    let custom_id = format!("{}-{}", sanitize_id(&task.name), Utc::now().timestamp());
+    let batch = client
+        .create_batch(vec![BatchRequest::new(&custom_id, request)])
+        .await?;
+    info!(task = %task.name, batch_id = %batch.id, "バッチを投入しました");
+
+    let batch = client
+        .wait_for_batch(
+            &batch.id,
+            Duration::from_secs(settings.poll_interval_secs.max(1)),
+            Duration::from_secs(settings.max_wait_secs),
+        )
+        .await?;
+    let results = client.batch_results(&batch).await?;
+
+    // 結果を JSONL として保存
+    let dir = std::path::Path::new(&settings.results_dir);
+    tokio::fs::create_dir_all(dir).await?;
+    let path = dir.join(format!("{}.jsonl", batch.id));
+    let mut lines = String::new();
+    for result in &results {
+        lines.push_str(&serde_json::to_string(result).map_err(cc_core::Error::from)?);
+        lines.push('\n');
+    }
+    tokio::fs::write(&path, lines).await?;
+    info!(task = %task.name, path = %path.display(), "バッチ結果を保存しました");
+
+    let result = results
+        .into_iter()
+        .find(|r| r.custom_id == custom_id)
+        .ok_or_else(|| ScheduleError::Llm(format!("バッチ {} に結果がありません", batch.id)))?;
+
+    match result.result {
+        BatchResultType::Succeeded { message } => Ok(message),
+        BatchResultType::Errored { error } => {
+            Err(ScheduleError::Llm(format!("バッチリクエスト失敗: {}", error)))
+        }
+        BatchResultType::Canceled => Err(ScheduleError::Llm("バッチがキャンセルされました".to_string())),
+        BatchResultType::Expired => Err(ScheduleError::Llm("バッチの有効期限が切れました".to_string())),
+    }
+}
+
+/// タスク名を custom_id に使える文字列に変換
+fn sanitize_id(name: &str) -> String {
+    let id: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    id.chars().take(48).collect()
 }
 
 /// cron 文字列をパース
@@ -232,7 +699,7 @@ async fn execute_task(
 /// - 5フィールド: "0 9 * * *" → "0 0 9 * * * *" (毎日 9:00)
 /// - 6フィールド: "0 0 9 * * *" → "0 0 9 * * * *" (秒付き、年に *)
 /// - 7フィールド: そのまま
-fn parse_cron(cron_expr: &str) -> Result<CronSchedule> {
+pub(crate) fn parse_cron(cron_expr: &str) -> Result<CronSchedule> {
     let fields: Vec<&str> = cron_expr.split_whitespace().collect();
 
     let normalized = match fields.len() {
@@ -274,9 +741,267 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_sanitize_id() {
+        assert_eq!(sanitize_id("daily-report"), "daily-report");
+        assert_eq!(sanitize_id("日次 report"), "___report");
+        assert_eq!(sanitize_id(&"a".repeat(100)).len(), 48);
+    }
+
     #[test]
     fn test_parse_cron_invalid() {
         let result = parse_cron("invalid");
         assert!(result.is_err());
     }
+
+    fn test_client() -> ClaudeClient {
+        let path = std::env::temp_dir().join(format!("cc-schedule-test-{}.toml", std::process::id()));
+        std::fs::write(&path, "[llm]\napi_key = \"test-key\"\n").unwrap();
+        let config = cc_core::Config::from_toml_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        ClaudeClient::new(&config).unwrap()
+    }
+
+    fn yearly_task(name: &str) -> ScheduleTask {
+        ScheduleTask {
+            name: name.to_string(),
+            cron: "0 0 1 1 *".to_string(),
+            prompt: "test".to_string(),
+            workflow: None,
+            tools: Vec::new(),
+            discord_channel: None,
+            outputs: Vec::new(),
+            enabled: true,
+            batch: false,
+        }
+    }
+
+    fn start_scheduler(tasks: Vec<ScheduleTask>) -> SchedulerHandle {
+        let config = ScheduleConfig {
+            schedules: tasks,
+            ..Default::default()
+        };
+        Scheduler::new(config, test_client(), Arc::new(ToolManager::new())).start()
+    }
+
+    #[tokio::test]
+    async fn test_handle_add_and_remove() {
+        let handle = start_scheduler(vec![yearly_task("a")]);
+
+        handle.add_task(yearly_task("b")).unwrap();
+        assert!(matches!(
+            handle.add_task(yearly_task("b")),
+            Err(ScheduleError::TaskExists(_))
+        ));
+        assert!(matches!(
+            handle.add_task(ScheduleTask {
+                cron: "bogus".to_string(),
+                ..yearly_task("c")
+            }),
+            Err(ScheduleError::CronParse(_))
+        ));
+
+        let names: Vec<String> = handle
+            .list_with_next_run()
+            .into_iter()
+            .map(|t| t.task.name)
+            .collect();
+        assert_eq!(names, vec!["a", "b"]);
+
+        assert_eq!(handle.remove_task("a").unwrap().name, "a");
+        assert!(matches!(handle.remove_task("a"), Err(ScheduleError::TaskNotFound(_))));
+        assert!(matches!(handle.trigger_now("a"), Err(ScheduleError::TaskNotFound(_))));
+
+        handle.stop().await;
+        assert!(handle.list_with_next_run().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handle_pause_resume() {
+        let handle = start_scheduler(vec![yearly_task("a")]);
+        assert!(handle.list_with_next_run()[0].next_run.is_some());
+
+        handle.pause("a").unwrap();
+        let info = &handle.list_with_next_run()[0];
+        assert!(!info.task.enabled);
+        assert!(info.next_run.is_none());
+
+        handle.resume("a").unwrap();
+        assert!(handle.list_with_next_run()[0].next_run.is_some());
+        assert!(handle.pause("missing").is_err());
+
+        handle.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_restart_stopped_tasks() {
+        let handle = start_scheduler(vec![yearly_task("a"), yearly_task("b")]);
+        assert!(handle.stopped_tasks().is_empty());
+        handle.pause("a").unwrap();
+
+        handle.lock_slots()[0].handle.abort();
+        while !handle.lock_slots()[0].handle.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(handle.stopped_tasks(), vec!["a"]);
+
+        assert_eq!(handle.restart_stopped_tasks(), vec!["a"]);
+        assert!(handle.stopped_tasks().is_empty());
+        assert!(!handle.list_with_next_run()[0].task.enabled);
+
+        handle.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_handle_persists_changes() {
+        let path = std::env::temp_dir().join(format!("cc-schedule-persist-{}.toml", std::process::id()));
+        let config = ScheduleConfig {
+            schedules: vec![yearly_task("a")],
+            alerts: AlertSettings {
+                consecutive_failures: 5,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let handle = Scheduler::new(config, test_client(), Arc::new(ToolManager::new()))
+            .with_config_path(&path)
+            .start();
+
+        handle
+            .add_task(ScheduleTask {
+                outputs: vec![crate::output::OutputTarget::Webhook {
+                    url: "https://example.com/hook".to_string(),
+                    headers: [("X-Token".to_string(), "t".to_string())].into(),
+                }],
+                ..yearly_task("b")
+            })
+            .unwrap();
+        handle.pause("a").unwrap();
+
+        let saved = ScheduleConfig::from_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(saved.schedules.len(), 2);
+        assert!(!saved.schedules[0].enabled);
+        assert_eq!(saved.schedules[1].name, "b");
+        assert_eq!(saved.schedules[1].outputs.len(), 1);
+        assert_eq!(saved.alerts.consecutive_failures, 5);
+
+        handle.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_schedule_create_tool_delivers_to_origin() {
+        use cc_core::{Tool, ToolOrigin};
+        use chrono::Datelike;
+        use std::sync::OnceLock;
+
+        let slot = Arc::new(OnceLock::new());
+        let tool = crate::tool::ScheduleCreateTool::new(Arc::clone(&slot));
+        let handle = start_scheduler(Vec::new());
+        slot.set(handle.clone()).ok();
+
+        let input = serde_json::json!({
+            "schedule": "every weekend at noon",
+            "prompt": "Remind the user to water the plants",
+            "name": "plants"
+        });
+        let result = ToolOrigin::new("telegram", "-100")
+            .scope(tool.execute(input))
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.output);
+
+        let info = &handle.list_with_next_run()[0];
+        assert_eq!(info.task.cron, "0 12 * * SAT,SUN");
+        assert_eq!(
+            info.task.outputs,
+            vec![crate::output::OutputTarget::Telegram {
+                chat_id: "-100".to_string()
+            }]
+        );
+        let next = info.next_run.unwrap();
+        assert!(matches!(next.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun));
+
+        handle.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_record_run_writes_history() {
+        let history = RunHistory::in_memory().unwrap();
+        let ctx = TaskContext {
+            client: test_client(),
+            tool_manager: Arc::new(ToolManager::new()),
+            system_prompt: String::new(),
+            prompts: None,
+            batch: BatchSettings::default(),
+            output: OutputDispatcher::new(),
+            history: Some(history.clone()),
+            alerts: AlertSettings::default(),
+            workflows: None,
+        };
+        let result = ScheduleResult {
+            task_name: "a".to_string(),
+            executed_at: Utc::now(),
+            response: "timeout".to_string(),
+            success: false,
+            duration_ms: 30,
+            input_tokens: 0,
+            output_tokens: 0,
+        };
+
+        record_run(&result, &ctx).await;
+        record_run(&result, &ctx).await;
+
+        assert_eq!(history.recent(Some("a"), 10).unwrap().len(), 2);
+        assert_eq!(history.consecutive_failures("a").unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_once_workflow() {
+        let workflow = cc_workflow::Workflow::from_yaml(
+            r#"
+name: check
+steps:
+  - id: has_prompt
+    type: condition
+    if: "{{prompt}}"
+"#,
+        )
+        .unwrap();
+        let mut registry = cc_workflow::WorkflowRegistry::new();
+        registry.insert(workflow);
+        let tool_manager = Arc::new(ToolManager::new());
+        let engine = WorkflowEngine::new(test_client(), Arc::clone(&tool_manager))
+            .with_registry(registry);
+
+        let mut ctx = TaskContext {
+            client: test_client(),
+            tool_manager,
+            system_prompt: String::new(),
+            prompts: None,
+            batch: BatchSettings::default(),
+            output: OutputDispatcher::new(),
+            history: None,
+            alerts: AlertSettings::default(),
+            workflows: None,
+        };
+        let task = ScheduleTask {
+            workflow: Some("check".to_string()),
+            ..yearly_task("wf")
+        };
+
+        // エンジン未設定なら失敗として記録される
+        assert!(!run_once(&task, &ctx).await.success);
+
+        ctx.workflows = Some(Arc::new(engine));
+        let result = run_once(&task, &ctx).await;
+        assert!(result.success, "{}", result.response);
+        assert_eq!(result.response, "true");
+
+        let missing = ScheduleTask {
+            workflow: Some("missing".to_string()),
+            ..yearly_task("wf")
+        };
+        assert!(!run_once(&missing, &ctx).await.success);
+    }
 }
@@ -0,0 +1,305 @@
+//! スケジュール・リマインダー作成ツール
+//!
+//! 会話の中から "remind me every weekday at 8am to check the deploy queue" や
+//! "remind me in 20 minutes" のような依頼を受けて登録します。
+//! 結果は依頼元のチャンネルに配信されます。
+
+use crate::config::ScheduleTask;
+use crate::natural::{parse_natural_schedule, parse_natural_time};
+use crate::output::OutputTarget;
+use crate::reminder::ReminderManager;
+use crate::scheduler::{parse_cron, SchedulerHandle};
+use async_trait::async_trait;
+use cc_core::{Result, Tool, ToolOrigin, ToolResult};
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::sync::{Arc, OnceLock};
+
+/// `schedule_create` ツール
+///
+/// スケジューラーはツール登録後に起動するため、ハンドルは後から設定します。
+pub struct ScheduleCreateTool {
+    scheduler: Arc<OnceLock<SchedulerHandle>>,
+}
+
+impl ScheduleCreateTool {
+    /// 新しいツールを作成
+    ///
+    /// スケジューラー起動後に `scheduler` にハンドルを設定してください。
+    pub fn new(scheduler: Arc<OnceLock<SchedulerHandle>>) -> Self {
+        Self { scheduler }
+    }
+}
+
+#[async_trait]
+impl Tool for ScheduleCreateTool {
+    fn name(&self) -> &str {
+        "schedule_create"
+    }
+
+    fn description(&self) -> &str {
+        "Create a recurring scheduled task. The prompt is run on the given schedule and \
+         the result is delivered to the channel this request came from. Use it for \
+         recurring reminders and reports, e.g. 'every weekday at 8am'. Times are in UTC."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "schedule": {
+                    "type": "string",
+                    "description": "When to run: a cron expression (e.g. '0 8 * * MON-FRI') or natural language (e.g. 'every weekday at 8am', '毎日 9時', 'every 30 minutes')"
+                },
+                "prompt": {
+                    "type": "string",
+                    "description": "Instruction executed on each run, e.g. 'Remind the user to check the deploy queue'"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Unique task name (generated if omitted)"
+                },
+                "tools": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Tools the task may use (all tools if omitted)"
+                }
+            },
+            "required": ["schedule", "prompt"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let schedule = input["schedule"].as_str().ok_or_else(|| {
+            cc_core::Error::ToolExecution("Missing 'schedule' parameter".to_string())
+        })?;
+        let prompt = input["prompt"].as_str().ok_or_else(|| {
+            cc_core::Error::ToolExecution("Missing 'prompt' parameter".to_string())
+        })?;
+
+        let Some(scheduler) = self.scheduler.get() else {
+            return Ok(ToolResult::error("Scheduler is not running"));
+        };
+
+        let Some(cron) = resolve_cron(schedule) else {
+            return Ok(ToolResult::error(format!(
+                "Could not understand schedule '{}'. Provide a cron expression such as '0 8 * * MON-FRI'.",
+                schedule
+            )));
+        };
+
+        let name = match input["name"].as_str().map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => format!("schedule-{}", Utc::now().format("%Y%m%d%H%M%S")),
+        };
+        let tools = input["tools"]
+            .as_array()
+            .map(|tools| {
+                tools
+                    .iter()
+                    .filter_map(|t| t.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let outputs: Vec<OutputTarget> = ToolOrigin::current()
+            .as_ref()
+            .and_then(OutputTarget::from_origin)
+            .into_iter()
+            .collect();
+
+        let task = ScheduleTask {
+            name: name.clone(),
+            cron: cron.clone(),
+            prompt: prompt.to_string(),
+            workflow: None,
+            tools,
+            discord_channel: None,
+            outputs: outputs.clone(),
+            enabled: true,
+            batch: false,
+        };
+        if let Err(e) = scheduler.add_task(task) {
+            return Ok(ToolResult::error(format!("Failed to create schedule: {}", e)));
+        }
+
+        let next_run = scheduler
+            .list_with_next_run()
+            .into_iter()
+            .find(|info| info.task.name == name)
+            .and_then(|info| info.next_run)
+            .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let delivery = if outputs.is_empty() {
+            "none (results are only recorded)".to_string()
+        } else {
+            outputs
+                .iter()
+                .map(OutputTarget::describe)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        Ok(ToolResult::success(format!(
+            "Created schedule '{}'\ncron: {}\nnext run: {}\ndelivery: {}",
+            name, cron, next_run, delivery
+        )))
+    }
+}
+
+/// `reminder_set` ツール
+///
+/// 単発のリマインダーを登録します。
+pub struct ReminderSetTool {
+    reminders: Arc<OnceLock<ReminderManager>>,
+}
+
+impl ReminderSetTool {
+    /// 新しいツールを作成
+    ///
+    /// リマインダー起動後に `reminders` にマネージャーを設定してください。
+    pub fn new(reminders: Arc<OnceLock<ReminderManager>>) -> Self {
+        Self { reminders }
+    }
+}
+
+#[async_trait]
+impl Tool for ReminderSetTool {
+    fn name(&self) -> &str {
+        "reminder_set"
+    }
+
+    fn description(&self) -> &str {
+        "Set a one-time reminder. The message is sent once at the given time to the \
+         channel this request came from. For recurring reminders use schedule_create. \
+         Times are in UTC."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "when": {
+                    "type": "string",
+                    "description": "When to remind: relative ('in 20 minutes', '2時間後'), day and time ('tomorrow at 3pm', '明日 15時', 'next friday 10:00') or RFC 3339"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "Reminder message sent to the user"
+                }
+            },
+            "required": ["when", "message"]
+        })
+    }
+
+    async fn execute(&self, input: Value) -> Result<ToolResult> {
+        let when = input["when"].as_str().ok_or_else(|| {
+            cc_core::Error::ToolExecution("Missing 'when' parameter".to_string())
+        })?;
+        let message = input["message"].as_str().ok_or_else(|| {
+            cc_core::Error::ToolExecution("Missing 'message' parameter".to_string())
+        })?;
+
+        let Some(reminders) = self.reminders.get() else {
+            return Ok(ToolResult::error("Reminders are not enabled"));
+        };
+
+        let now = Utc::now();
+        let due_at = match parse_natural_time(when, now) {
+            Some(due_at) if due_at > now => due_at,
+            Some(_) => return Ok(ToolResult::error(format!("'{}' is in the past", when))),
+            None => {
+                return Ok(ToolResult::error(format!(
+                    "Could not understand time '{}'. Use e.g. 'in 20 minutes' or '2026-01-31T15:00:00Z'.",
+                    when
+                )));
+            }
+        };
+
+        let Some(target) = ToolOrigin::current().as_ref().and_then(OutputTarget::from_origin) else {
+            return Ok(ToolResult::error(
+                "Reminders can only be set from a chat channel",
+            ));
+        };
+
+        match reminders.add(message, due_at, vec![target.clone()]) {
+            Ok(reminder) => Ok(ToolResult::success(format!(
+                "Reminder #{} set for {} ({})",
+                reminder.id,
+                due_at.format("%Y-%m-%d %H:%M UTC"),
+                target.describe()
+            ))),
+            Err(e) => Ok(ToolResult::error(format!("Failed to set reminder: {}", e))),
+        }
+    }
+}
+
+/// cron 式ならそのまま、そうでなければ自然言語として解釈
+fn resolve_cron(schedule: &str) -> Option<String> {
+    let schedule = schedule.trim();
+    if parse_cron(schedule).is_ok() {
+        return Some(schedule.to_string());
+    }
+    parse_natural_schedule(schedule).filter(|cron| parse_cron(cron).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_cron() {
+        assert_eq!(resolve_cron("0 9 * * *").as_deref(), Some("0 9 * * *"));
+        assert_eq!(
+            resolve_cron("every weekday at 8am").as_deref(),
+            Some("0 8 * * MON-FRI")
+        );
+        assert!(resolve_cron("whenever").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_without_scheduler() {
+        let tool = ScheduleCreateTool::new(Arc::new(OnceLock::new()));
+        let result = tool
+            .execute(json!({"schedule": "daily", "prompt": "hi"}))
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(tool.execute(json!({"prompt": "hi"})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reminder_set() {
+        use crate::output::OutputDispatcher;
+
+        let slot = Arc::new(OnceLock::new());
+        let manager = ReminderManager::in_memory(OutputDispatcher::new()).unwrap();
+        slot.set(manager.clone()).ok();
+        let tool = ReminderSetTool::new(slot);
+        let input = json!({"when": "in 20 minutes", "message": "check the oven"});
+
+        // 呼び出し元のチャンネルがなければ配信先を決められない
+        assert!(tool.execute(input.clone()).await.unwrap().is_error);
+        assert!(
+            tool.execute(json!({"when": "someday", "message": "x"}))
+                .await
+                .unwrap()
+                .is_error
+        );
+
+        let result = ToolOrigin::new("discord", "42")
+            .scope(tool.execute(input))
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.output);
+
+        let pending = manager.pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].message, "check the oven");
+        assert_eq!(
+            pending[0].targets,
+            vec![OutputTarget::Discord {
+                channel_id: "42".to_string()
+            }]
+        );
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Communicates with signal-cli-rest-api server
 
+use base64::Engine;
 use reqwest::Client;
 use tracing::{debug, error, info};
 
@@ -31,6 +32,11 @@ impl SignalApiClient {
         })
     }
 
+    /// The bot's phone number
+    pub fn phone_number(&self) -> &str {
+        &self.phone_number
+    }
+
     /// Check if the API is available
     pub async fn health_check(&self) -> Result<bool> {
         let url = format!("{}/v1/about", self.base_url);
@@ -122,6 +128,45 @@ impl SignalApiClient {
         Ok(send_response)
     }
 
+    /// Send a file, with `message` as caption
+    pub async fn send_attachment(
+        &self,
+        recipient: &str,
+        message: &str,
+        data: &[u8],
+        content_type: &str,
+        filename: &str,
+    ) -> Result<SendResponse> {
+        self.send_message_with_attachments(
+            recipient,
+            message,
+            vec![attachment_data_uri(data, content_type, filename)],
+        )
+        .await
+    }
+
+    /// Download a received attachment
+    pub async fn download_attachment(&self, attachment_id: &str) -> Result<Vec<u8>> {
+        let url = format!("{}/v1/attachments/{}", self.base_url, attachment_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(SignalError::HttpError)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(SignalError::Attachment(format!("{}: {}", status, error_text)));
+        }
+
+        let bytes = response.bytes().await.map_err(SignalError::HttpError)?;
+        debug!("Downloaded attachment {} ({} bytes)", attachment_id, bytes.len());
+        Ok(bytes.to_vec())
+    }
+
     /// Receive messages
     pub async fn receive_messages(&self) -> Result<Vec<SignalMessage>> {
         let url = format!("{}/v1/receive/{}", self.base_url, self.phone_number);
@@ -142,11 +187,15 @@ impl SignalApiClient {
             return Err(SignalError::ApiError(format!("{}: {}", status, error_text)));
         }
 
-        // The API returns messages directly or an empty array
-        let messages: Vec<SignalMessage> = response
+        // Envelopes without a data message (receipts, typing) are skipped
+        let envelopes: Vec<ReceivedEnvelope> = response
             .json()
             .await
             .map_err(|e| SignalError::ParseError(e.to_string()))?;
+        let messages: Vec<SignalMessage> = envelopes
+            .into_iter()
+            .filter_map(SignalMessage::from_envelope)
+            .collect();
 
         debug!("Received {} messages", messages.len());
         Ok(messages)
@@ -202,10 +251,38 @@ impl SignalApiClient {
         Ok(groups)
     }
 
-    /// Send a reaction to a message
+    /// React to a message
+    ///
+    /// `target_author` and `target_timestamp` identify the message;
+    /// `recipient` is the sender or the group recipient of the chat.
     pub async fn send_reaction(
         &self,
         recipient: &str,
+        target_author: &str,
+        target_timestamp: u64,
+        emoji: &str,
+    ) -> Result<()> {
+        self.reaction(reqwest::Method::POST, recipient, target_author, target_timestamp, emoji)
+            .await
+    }
+
+    /// Remove a reaction sent with [`send_reaction`](Self::send_reaction)
+    pub async fn remove_reaction(
+        &self,
+        recipient: &str,
+        target_author: &str,
+        target_timestamp: u64,
+        emoji: &str,
+    ) -> Result<()> {
+        self.reaction(reqwest::Method::DELETE, recipient, target_author, target_timestamp, emoji)
+            .await
+    }
+
+    async fn reaction(
+        &self,
+        method: reqwest::Method,
+        recipient: &str,
+        target_author: &str,
         target_timestamp: u64,
         emoji: &str,
     ) -> Result<()> {
@@ -213,13 +290,14 @@ impl SignalApiClient {
 
         let body = serde_json::json!({
             "recipient": recipient,
+            "reaction": emoji,
+            "target_author": target_author,
             "timestamp": target_timestamp,
-            "emoji": emoji,
         });
 
         let response = self
             .client
-            .post(&url)
+            .request(method, &url)
             .json(&body)
             .send()
             .await
@@ -242,13 +320,80 @@ impl SignalApiClient {
     }
 }
 
+/// Recipient for sending to a group with internal ID `group_id`
+///
+/// Received messages carry the internal ID; `/v2/send` takes `group.`
+/// followed by the base64 of that ID.
+pub fn group_recipient(group_id: &str) -> String {
+    format!(
+        "group.{}",
+        base64::engine::general_purpose::STANDARD.encode(group_id)
+    )
+}
+
+/// An attachment for `base64_attachments`
+fn attachment_data_uri(data: &[u8], content_type: &str, filename: &str) -> String {
+    format!(
+        "data:{};filename={};base64,{}",
+        content_type,
+        filename,
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
     fn test_api_client_creation() {
         let client = SignalApiClient::new("http://localhost:8080", "+1234567890");
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn test_group_recipient() {
+        assert_eq!(group_recipient("abc="), "group.YWJjPQ==");
+        assert_eq!(
+            attachment_data_uri(b"hi", "text/plain", "a.txt"),
+            "data:text/plain;filename=a.txt;base64,aGk="
+        );
+    }
+
+    #[tokio::test]
+    async fn test_receive_and_react() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/receive/+15559990000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "envelope": { "sourceNumber": "+15550001111", "timestamp": 1, "receiptMessage": {} } },
+                { "envelope": { "sourceNumber": "+15550001111", "timestamp": 2,
+                    "dataMessage": { "timestamp": 2, "message": "hello" } } },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/reactions/+15559990000"))
+            .and(body_partial_json(serde_json::json!({
+                "reaction": "👀",
+                "target_author": "+15550001111",
+                "timestamp": 2,
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = SignalApiClient::new(&server.uri(), "+15559990000").unwrap();
+        let messages = client.receive_messages().await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, "hello");
+
+        client
+            .send_reaction("+15550001111", "+15550001111", 2, "👀")
+            .await
+            .unwrap();
+    }
 }
@@ -27,6 +27,12 @@ pub struct SignalBotConfig {
     pub poll_interval_secs: u64,
     /// Allowed senders
     pub allowed_senders: Vec<String>,
+    /// Allowed groups (internal group IDs, empty = all)
+    pub allowed_groups: Vec<String>,
+    /// In groups, answer only when mentioned or replied to
+    pub require_mention_in_groups: bool,
+    /// Reaction acknowledging a received message (None = no reaction)
+    pub ack_reaction: Option<String>,
     /// System prompt for Claude
     pub system_prompt: Option<String>,
 }
@@ -38,6 +44,9 @@ impl Default for SignalBotConfig {
             phone_number: String::new(),
             poll_interval_secs: 5,
             allowed_senders: Vec::new(),
+            allowed_groups: Vec::new(),
+            require_mention_in_groups: true,
+            ack_reaction: Some("👀".to_string()),
             system_prompt: None,
         }
     }
@@ -65,6 +74,9 @@ impl SignalBot {
 
         let handler_config = HandlerConfig {
             allowed_senders: bot_config.allowed_senders.clone(),
+            allowed_groups: bot_config.allowed_groups.clone(),
+            require_mention_in_groups: bot_config.require_mention_in_groups,
+            ack_reaction: bot_config.ack_reaction.clone(),
             max_message_length: 2000,
             system_prompt: bot_config.system_prompt.clone().unwrap_or_else(|| {
                 "You are a helpful assistant. Respond concisely as this is a messaging app. Respond in the same language as the user's question.".to_string()
@@ -100,6 +112,9 @@ impl SignalBot {
 
         let handler_config = HandlerConfig {
             allowed_senders: bot_config.allowed_senders.clone(),
+            allowed_groups: bot_config.allowed_groups.clone(),
+            require_mention_in_groups: bot_config.require_mention_in_groups,
+            ack_reaction: bot_config.ack_reaction.clone(),
             max_message_length: 2000,
             system_prompt: bot_config.system_prompt.clone().unwrap_or_else(|| {
                 "You are a helpful assistant. Respond concisely as this is a messaging app. Respond in the same language as the user's question.".to_string()
@@ -140,6 +155,21 @@ impl SignalBot {
         Ok(())
     }
 
+    /// Send a file to a recipient (phone number or `group.<id>`)
+    pub async fn send_attachment(
+        &self,
+        recipient: &str,
+        message: &str,
+        data: &[u8],
+        content_type: &str,
+        filename: &str,
+    ) -> Result<()> {
+        self.api_client
+            .send_attachment(recipient, message, data, content_type, filename)
+            .await?;
+        Ok(())
+    }
+
     /// Get list of groups
     pub async fn get_groups(&self) -> Result<Vec<GroupInfo>> {
         self.api_client.get_groups().await
@@ -266,6 +296,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: cc_core::LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -276,6 +309,12 @@ mod tests {
             memory: cc_core::MemoryConfig::default(),
             mcp: cc_core::McpConfig::default(),
             scheduler: cc_core::SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -285,6 +324,7 @@ mod tests {
         assert_eq!(config.api_url, "http://localhost:8080");
         assert!(config.phone_number.is_empty());
         assert_eq!(config.poll_interval_secs, 5);
+        assert!(config.require_mention_in_groups);
     }
 
     #[tokio::test]
@@ -1,20 +1,37 @@
 //! Signal message handler implementation
+//!
+//! Direct messages share a session per sender and group messages a session
+//! per group. In groups the bot only answers when mentioned or replied to
+//! (unless `require_mention_in_groups` is off). Image attachments go to the
+//! model and text documents are read into the prompt.
 
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
-use cc_core::{ClaudeClient, Message};
+use cc_core::{ClaudeClient, ImageSource, Message};
 
-use crate::api::SignalApiClient;
+use crate::api::{group_recipient, SignalApiClient};
 use crate::error::Result;
 use crate::session::InMemorySessionStore;
 use crate::types::SignalMessage;
 
+/// Largest image passed to the model
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Placeholder Signal puts in the text for each mention
+const MENTION_PLACEHOLDER: char = '\u{FFFC}';
+
 /// Configuration for the message handler
 #[derive(Clone, Debug)]
 pub struct HandlerConfig {
     /// Allowed senders (phone numbers). Empty = allow all.
     pub allowed_senders: Vec<String>,
+    /// Allowed groups (internal group IDs). Empty = allow all.
+    pub allowed_groups: Vec<String>,
+    /// In groups, answer only messages that mention or reply to the bot
+    pub require_mention_in_groups: bool,
+    /// Reaction sent when a message is picked up (None = no reaction)
+    pub ack_reaction: Option<String>,
     /// Maximum message length before splitting
     pub max_message_length: usize,
     /// System prompt for Claude
@@ -25,12 +42,38 @@ impl Default for HandlerConfig {
     fn default() -> Self {
         Self {
             allowed_senders: Vec::new(),
+            allowed_groups: Vec::new(),
+            require_mention_in_groups: true,
+            ack_reaction: Some("👀".to_string()),
             max_message_length: 2000, // Signal has ~2000 char limit
             system_prompt: "You are a helpful assistant. Respond concisely as this is a messaging app. Respond in the same language as the user's question.".to_string(),
         }
     }
 }
 
+/// The conversation a message belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Chat {
+    /// Where replies go (sender, or `group.<id>`); also the session key
+    recipient: String,
+    is_group: bool,
+}
+
+impl Chat {
+    fn of(msg: &SignalMessage) -> Self {
+        match msg.group {
+            Some(ref group) => Self {
+                recipient: group_recipient(group),
+                is_group: true,
+            },
+            None => Self {
+                recipient: msg.sender.clone(),
+                is_group: false,
+            },
+        }
+    }
+}
+
 /// Message handler for Signal
 pub struct MessageHandler {
     api_client: SignalApiClient,
@@ -63,18 +106,45 @@ impl MessageHandler {
             return Ok(());
         }
 
-        let content = msg.content.trim();
-        if content.is_empty() {
+        if let Some(ref group) = msg.group {
+            if !self.is_group_allowed(group) {
+                debug!("Ignoring message from group {}", group);
+                return Ok(());
+            }
+            if self.config.require_mention_in_groups && !self.is_addressed(msg) {
+                return Ok(());
+            }
+        }
+
+        let chat = Chat::of(msg);
+        let content = resolve_mentions(msg, self.api_client.phone_number());
+        let content = content.trim();
+        if content.is_empty() && msg.attachments.is_empty() {
             return Ok(());
         }
 
         // Handle commands
         if content.starts_with('/') {
-            return self.handle_command(&msg.sender, content).await;
+            return self.handle_command(&chat, content).await;
         }
 
+        self.acknowledge(&chat, msg).await;
+
         // Regular message processing
-        self.process_with_claude(&msg.sender, content).await
+        let (images, notes) = self.read_attachments(msg).await;
+        let mut content = content.to_string();
+        for note in notes {
+            if !content.is_empty() {
+                content.push_str("\n\n");
+            }
+            content.push_str(&note);
+        }
+        if chat.is_group {
+            // Several people talk in a group session
+            let name = msg.sender_name.as_deref().unwrap_or(&msg.sender);
+            content = format!("{}: {}", name, content);
+        }
+        self.process_with_claude(&chat, &content, images).await
     }
 
     /// Check if sender is allowed
@@ -87,17 +157,93 @@ impl MessageHandler {
         })
     }
 
+    /// Check if the group is allowed
+    fn is_group_allowed(&self, group: &str) -> bool {
+        self.config.allowed_groups.is_empty()
+            || self.config.allowed_groups.iter().any(|allowed| allowed == group)
+    }
+
+    /// Whether the message mentions or replies to the bot
+    fn is_addressed(&self, msg: &SignalMessage) -> bool {
+        let bot = self.api_client.phone_number();
+        msg.mentions
+            .iter()
+            .any(|m| m.number.as_deref() == Some(bot))
+            || msg.quote_author.as_deref() == Some(bot)
+    }
+
+    /// React to `msg` to show it is being answered
+    async fn acknowledge(&self, chat: &Chat, msg: &SignalMessage) {
+        let Some(ref emoji) = self.config.ack_reaction else {
+            return;
+        };
+        if let Err(e) = self
+            .api_client
+            .send_reaction(&chat.recipient, &msg.sender, msg.timestamp, emoji)
+            .await
+        {
+            warn!("Failed to send reaction: {}", e);
+        }
+    }
+
+    /// Download the attachments of `msg`
+    ///
+    /// Returns the images for the model and notes for the prompt: the text
+    /// of documents, or why an attachment was skipped.
+    async fn read_attachments(&self, msg: &SignalMessage) -> (Vec<ImageSource>, Vec<String>) {
+        let mut images = Vec::new();
+        let mut notes = Vec::new();
+
+        for attachment in &msg.attachments {
+            let Some(ref id) = attachment.id else {
+                continue;
+            };
+            let name = attachment.filename.as_deref().unwrap_or(id);
+            let mime = attachment.content_type.as_str();
+            let is_image = mime.starts_with("image/");
+
+            if is_image && attachment.size.is_some_and(|size| size > MAX_IMAGE_BYTES) {
+                notes.push(format!("[Image {} is too large (5MB max)]", name));
+                continue;
+            }
+            if !is_image && !cc_core::document::is_text_document(name, Some(mime)) {
+                notes.push(format!("[Attachment {} ({}) is not supported]", name, mime));
+                continue;
+            }
+
+            let bytes = match self.api_client.download_attachment(id).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    warn!("Failed to download attachment {}: {}", id, e);
+                    notes.push(format!("[Attachment {} could not be downloaded]", name));
+                    continue;
+                }
+            };
+            if is_image {
+                images.push(ImageSource::from_bytes(mime, &bytes));
+            } else {
+                match cc_core::extract_text(name, Some(mime), &bytes) {
+                    Some(document) => notes.push(document.to_prompt()),
+                    None => notes.push(format!("[Attachment {} could not be read as text]", name)),
+                }
+            }
+        }
+
+        (images, notes)
+    }
+
     /// Handle slash commands
-    async fn handle_command(&self, sender: &str, content: &str) -> Result<()> {
+    async fn handle_command(&self, chat: &Chat, content: &str) -> Result<()> {
         let parts: Vec<&str> = content.splitn(2, ' ').collect();
         let command = parts[0];
+        let recipient = chat.recipient.as_str();
 
         match command {
             "/clear" | "/reset" => {
-                if self.session_store.clear(sender) {
-                    self.send_reply(sender, "Session reset.").await?;
+                if self.session_store.clear(recipient) {
+                    self.send_reply(recipient, "Session reset.").await?;
                 } else {
-                    self.send_reply(sender, "No session to reset.").await?;
+                    self.send_reply(recipient, "No session to reset.").await?;
                 }
             }
             "/help" => {
@@ -107,22 +253,22 @@ impl MessageHandler {
                     "/help - Show this help\n",
                     "/status - Show session status\n",
                     "\n",
-                    "Otherwise, just chat normally."
+                    "Otherwise, just chat normally. In groups, mention the bot or reply to it."
                 );
-                self.send_reply(sender, help_text).await?;
+                self.send_reply(recipient, help_text).await?;
             }
             "/status" => {
-                let session = self.session_store.get(sender);
+                let session = self.session_store.get(recipient);
                 let status = match session {
                     Some(s) => format!("Message count: {}", s.message_count()),
                     None => "New session".to_string(),
                 };
-                self.send_reply(sender, &status).await?;
+                self.send_reply(recipient, &status).await?;
             }
             _ => {
                 // Unknown command, treat as regular message
                 let clean_content = content.trim_start_matches('/');
-                self.process_with_claude(sender, clean_content).await?;
+                self.process_with_claude(chat, clean_content, Vec::new()).await?;
             }
         }
 
@@ -130,15 +276,24 @@ impl MessageHandler {
     }
 
     /// Process message with Claude API
-    async fn process_with_claude(&self, sender: &str, content: &str) -> Result<()> {
-        info!("Processing message from {}: {}", sender, content);
+    async fn process_with_claude(&self, chat: &Chat, content: &str, images: Vec<ImageSource>) -> Result<()> {
+        let recipient = chat.recipient.as_str();
+        info!("Processing message in {}: {}", recipient, content);
 
         // Get or create session
-        let session = self.session_store.get_or_create(sender);
+        let session = self.session_store.get_or_create(recipient);
+
+        // Images are sent once and only noted in the history
+        let image_count = images.len();
+        let content = if content.trim().is_empty() { "Describe this image." } else { content };
 
         // Build message history
         let mut messages: Vec<Message> = session.messages.clone();
-        messages.push(Message::user(content));
+        messages.push(if images.is_empty() {
+            Message::user(content)
+        } else {
+            Message::user_with_images(content, images)
+        });
 
         // Build request with conversation history
         let mut request_builder = self
@@ -172,17 +327,22 @@ impl MessageHandler {
                     .join("\n");
 
                 // Update session
+                let history_text = if image_count == 0 {
+                    content.to_string()
+                } else {
+                    format!("{}\n[{} image(s) attached]", content, image_count)
+                };
                 self.session_store
-                    .add_message(sender, Message::user(content));
+                    .add_message(recipient, Message::user(history_text));
                 self.session_store
-                    .add_message(sender, Message::assistant(&text));
+                    .add_message(recipient, Message::assistant(&text));
 
                 // Send response
-                self.send_reply(sender, &text).await?;
+                self.send_reply(recipient, &text).await?;
             }
             Err(e) => {
                 error!("Claude API error: {:?}", e);
-                self.send_reply(sender, &format!("Error: {}", e))
+                self.send_reply(recipient, &format!("Error: {}", e))
                     .await?;
             }
         }
@@ -191,10 +351,10 @@ impl MessageHandler {
     }
 
     /// Send a reply via Signal
-    async fn send_reply(&self, sender: &str, text: &str) -> Result<()> {
+    async fn send_reply(&self, recipient: &str, text: &str) -> Result<()> {
         // Split message if necessary
         if text.len() <= self.config.max_message_length {
-            self.api_client.send_message(sender, text).await?;
+            self.api_client.send_message(recipient, text).await?;
         } else {
             let chunks = self.split_message(text, self.config.max_message_length);
             for (i, chunk) in chunks.iter().enumerate() {
@@ -209,7 +369,7 @@ impl MessageHandler {
                     tokio::time::sleep(std::time::Duration::from_millis(500)).await;
                 }
 
-                if let Err(e) = self.api_client.send_message(sender, &content).await {
+                if let Err(e) = self.api_client.send_message(recipient, &content).await {
                     error!("Failed to send message chunk {}: {:?}", i, e);
                     break;
                 }
@@ -256,9 +416,38 @@ impl MessageHandler {
     }
 }
 
+/// The message text with the bot's mentions removed and other mentions
+/// written as `@name`
+fn resolve_mentions(msg: &SignalMessage, bot_number: &str) -> String {
+    let mut mentions = msg.mentions.iter();
+    let mut text = String::with_capacity(msg.content.len());
+    for c in msg.content.chars() {
+        if c != MENTION_PLACEHOLDER {
+            text.push(c);
+            continue;
+        }
+        let Some(mention) = mentions.next() else {
+            continue;
+        };
+        if mention.number.as_deref() == Some(bot_number) {
+            continue;
+        }
+        let name = mention
+            .name
+            .as_deref()
+            .or(mention.number.as_deref())
+            .or(mention.uuid.as_deref())
+            .unwrap_or("someone");
+        text.push('@');
+        text.push_str(name);
+    }
+    text
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::Mention;
     use cc_core::{Config, LlmConfig, LlmProvider, ApiConfig, MemoryConfig, McpConfig, SchedulerConfig};
 
     fn mock_config() -> Config {
@@ -268,6 +457,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -278,6 +470,12 @@ mod tests {
             memory: MemoryConfig::default(),
             mcp: McpConfig::default(),
             scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -311,6 +509,55 @@ mod tests {
         assert!(result.len() > 1);
     }
 
+    fn group_message(content: &str, mentions: Vec<Mention>) -> SignalMessage {
+        SignalMessage {
+            sender: "+15550001111".to_string(),
+            sender_name: Some("Alice".to_string()),
+            content: content.to_string(),
+            timestamp: 1,
+            group: Some("abc=".to_string()),
+            attachments: Vec::new(),
+            mentions,
+            quote_author: None,
+        }
+    }
+
+    fn mention(name: &str, number: &str) -> Mention {
+        Mention {
+            name: Some(name.to_string()),
+            number: Some(number.to_string()),
+            uuid: None,
+            start: 0,
+            length: 1,
+        }
+    }
+
+    #[test]
+    fn test_group_mentions() {
+        let handler = MessageHandler::new(
+            SignalApiClient::new("http://localhost:8080", "+15559990000").unwrap(),
+            Arc::new(ClaudeClient::new(&mock_config()).unwrap()),
+            Arc::new(InMemorySessionStore::new()),
+            HandlerConfig::default(),
+        );
+
+        let msg = group_message(
+            "\u{FFFC} ask \u{FFFC} about it",
+            vec![mention("Bot", "+15559990000"), mention("Bob", "+15550002222")],
+        );
+        assert!(handler.is_addressed(&msg));
+        assert_eq!(resolve_mentions(&msg, "+15559990000"), " ask @Bob about it");
+        assert_eq!(Chat::of(&msg).recipient, "group.YWJjPQ==");
+
+        let mut msg = group_message("hello all", Vec::new());
+        assert!(!handler.is_addressed(&msg));
+        msg.quote_author = Some("+15559990000".to_string());
+        assert!(handler.is_addressed(&msg));
+
+        msg.group = None;
+        assert_eq!(Chat::of(&msg).recipient, "+15550001111");
+    }
+
     #[test]
     fn test_is_sender_allowed() {
         let config = HandlerConfig::default();
@@ -5,8 +5,11 @@ use serde::{Deserialize, Serialize};
 /// Received Signal message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignalMessage {
-    /// Sender phone number
+    /// Sender phone number (or UUID when the number is hidden)
     pub sender: String,
+    /// Sender profile name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_name: Option<String>,
     /// Message content
     pub content: String,
     /// Timestamp
@@ -17,20 +20,138 @@ pub struct SignalMessage {
     /// Attachments
     #[serde(default)]
     pub attachments: Vec<Attachment>,
+    /// Mentions; each one is a U+FFFC placeholder in `content`
+    #[serde(default)]
+    pub mentions: Vec<Mention>,
+    /// Author of the quoted message, if this is a reply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_author: Option<String>,
+}
+
+impl SignalMessage {
+    /// The data message of a received envelope, if it has one
+    ///
+    /// Receipts, typing indicators and sync messages are skipped.
+    pub fn from_envelope(received: ReceivedEnvelope) -> Option<Self> {
+        let envelope = received.envelope;
+        let data = envelope.data_message?;
+        let sender = envelope
+            .source_number
+            .or(envelope.source)
+            .or(envelope.source_uuid)?;
+        let mut mentions = data.mentions;
+        mentions.sort_by_key(|m| m.start);
+
+        Some(Self {
+            sender,
+            sender_name: envelope.source_name.filter(|n| !n.is_empty()),
+            content: data.message.unwrap_or_default(),
+            timestamp: data.timestamp.unwrap_or(envelope.timestamp),
+            group: data.group_info.map(|g| g.group_id),
+            attachments: data.attachments,
+            mentions,
+            quote_author: data
+                .quote
+                .and_then(|q| q.author_number.or(q.author)),
+        })
+    }
 }
 
 /// Attachment information
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Attachment {
     /// Content type
     pub content_type: String,
     /// File name
     pub filename: Option<String>,
+    /// Attachment ID for `GET /v1/attachments/{id}` (received attachments)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Size in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
     /// Base64 encoded data (for received attachments)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<String>,
 }
 
+/// A mention of an account in a message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mention {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub number: Option<String>,
+    #[serde(default)]
+    pub uuid: Option<String>,
+    /// Position in the message (UTF-16 code units)
+    #[serde(default)]
+    pub start: usize,
+    #[serde(default)]
+    pub length: usize,
+}
+
+/// Entry returned by `GET /v1/receive/{number}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceivedEnvelope {
+    pub envelope: Envelope,
+}
+
+/// Signal envelope (only the fields the bot uses)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub source_number: Option<String>,
+    #[serde(default)]
+    pub source_uuid: Option<String>,
+    #[serde(default)]
+    pub source_name: Option<String>,
+    #[serde(default)]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub data_message: Option<DataMessage>,
+}
+
+/// Content of a received message
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataMessage {
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub group_info: Option<GroupRef>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    #[serde(default)]
+    pub mentions: Vec<Mention>,
+    #[serde(default)]
+    pub quote: Option<Quote>,
+}
+
+/// Group a message was sent in
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupRef {
+    /// Internal group ID (base64)
+    pub group_id: String,
+}
+
+/// Quoted message of a reply
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub author_number: Option<String>,
+}
+
 /// Message to send via Signal
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SendMessage {
@@ -71,12 +192,49 @@ pub struct AccountInfo {
 /// Group information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupInfo {
-    /// Group ID (base64 encoded)
+    /// Group ID for sending (`group.<base64>`)
     pub id: String,
+    /// Internal group ID, as in received messages and `allowed_groups`
+    #[serde(default)]
+    pub internal_id: String,
     /// Group name
     pub name: String,
     /// Group members
     pub members: Vec<String>,
     /// Whether the bot is in the group
+    #[serde(default)]
     pub is_member: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_envelope() {
+        let json = r#"[
+            {"envelope": {"source": "+15550001111", "sourceNumber": "+15550001111", "sourceName": "Alice",
+              "timestamp": 1700000000000, "typingMessage": {"action": "STARTED"}}},
+            {"envelope": {"source": "+15550001111", "sourceNumber": "+15550001111", "sourceName": "Alice",
+              "timestamp": 1700000000001,
+              "dataMessage": {"timestamp": 1700000000001, "message": "\uFFFC what is this?",
+                "groupInfo": {"groupId": "abc=", "type": "DELIVER"},
+                "attachments": [{"contentType": "image/jpeg", "filename": null, "id": "x1.jpg", "size": 10}],
+                "mentions": [{"name": "+15559990000", "number": "+15559990000", "start": 0, "length": 1}]}},
+             "account": "+15559990000"}
+        ]"#;
+        let envelopes: Vec<ReceivedEnvelope> = serde_json::from_str(json).unwrap();
+        let messages: Vec<SignalMessage> = envelopes
+            .into_iter()
+            .filter_map(SignalMessage::from_envelope)
+            .collect();
+
+        assert_eq!(messages.len(), 1);
+        let msg = &messages[0];
+        assert_eq!(msg.sender, "+15550001111");
+        assert_eq!(msg.sender_name.as_deref(), Some("Alice"));
+        assert_eq!(msg.group.as_deref(), Some("abc="));
+        assert_eq!(msg.attachments[0].id.as_deref(), Some("x1.jpg"));
+        assert_eq!(msg.mentions[0].number.as_deref(), Some("+15559990000"));
+    }
+}
@@ -105,6 +105,165 @@ impl SlackApiClient {
         self.post_message(&message).await
     }
 
+    /// Post a message with blocks (`text` is the notification fallback)
+    pub async fn send_blocks(
+        &self,
+        channel: &str,
+        text: &str,
+        blocks: Vec<serde_json::Value>,
+        thread_ts: Option<&str>,
+    ) -> Result<PostMessageResponse> {
+        let message = PostMessage {
+            channel: channel.to_string(),
+            text: text.to_string(),
+            thread_ts: thread_ts.map(|s| s.to_string()),
+            blocks: Some(blocks),
+        };
+
+        self.post_message(&message).await
+    }
+
+    /// Update a message the bot posted
+    pub async fn update_message(&self, update: &UpdateMessage) -> Result<UpdateMessageResponse> {
+        let url = format!("{}/chat.update", self.base_url);
+
+        debug!("Updating message {} in channel: {}", update.ts, update.channel);
+
+        let response = self
+            .add_auth(self.client.post(&url).json(update))
+            .send()
+            .await
+            .map_err(SlackError::HttpError)?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(SlackError::RateLimited);
+        }
+
+        let result: SlackResponse<UpdateMessageResponse> = response
+            .json()
+            .await
+            .map_err(|e| SlackError::ParseError(e.to_string()))?;
+
+        if !result.ok {
+            return match result.error.as_deref() {
+                Some("ratelimited") => Err(SlackError::RateLimited),
+                _ => Err(SlackError::ApiError(result.error.unwrap_or("Unknown error".to_string()))),
+            };
+        }
+
+        Ok(result.data.unwrap())
+    }
+
+    /// Get the messages of a thread (the parent first)
+    pub async fn conversations_replies(&self, channel: &str, ts: &str) -> Result<Vec<SlackMessage>> {
+        let url = format!("{}/conversations.replies", self.base_url);
+
+        debug!("Getting replies of {} in channel: {}", ts, channel);
+
+        let response = self
+            .add_auth(
+                self.client
+                    .get(&url)
+                    .query(&[("channel", channel), ("ts", ts), ("limit", "200")]),
+            )
+            .send()
+            .await
+            .map_err(SlackError::HttpError)?;
+
+        let result: SlackResponse<ConversationsRepliesResponse> = response
+            .json()
+            .await
+            .map_err(|e| SlackError::ParseError(e.to_string()))?;
+
+        if !result.ok {
+            return Err(SlackError::ApiError(result.error.unwrap_or("Unknown error".to_string())));
+        }
+
+        Ok(result.data.map(|d| d.messages).unwrap_or_default())
+    }
+
+    /// Get the latest messages of a channel (newest first)
+    pub async fn conversations_history(&self, channel: &str, limit: usize) -> Result<Vec<SlackMessage>> {
+        let url = format!("{}/conversations.history", self.base_url);
+
+        debug!("Getting {} messages of channel: {}", limit, channel);
+
+        let limit = limit.to_string();
+        let response = self
+            .add_auth(
+                self.client
+                    .get(&url)
+                    .query(&[("channel", channel), ("limit", limit.as_str())]),
+            )
+            .send()
+            .await
+            .map_err(SlackError::HttpError)?;
+
+        let result: SlackResponse<ConversationsRepliesResponse> = response
+            .json()
+            .await
+            .map_err(|e| SlackError::ParseError(e.to_string()))?;
+
+        if !result.ok {
+            return Err(SlackError::ApiError(result.error.unwrap_or("Unknown error".to_string())));
+        }
+
+        Ok(result.data.map(|d| d.messages).unwrap_or_default())
+    }
+
+    /// Open a modal for an interaction's `trigger_id`
+    pub async fn views_open(&self, trigger_id: &str, view: &serde_json::Value) -> Result<()> {
+        let url = format!("{}/views.open", self.base_url);
+
+        let body = serde_json::json!({
+            "trigger_id": trigger_id,
+            "view": view,
+        });
+
+        let response = self
+            .add_auth(self.client.post(&url).json(&body))
+            .send()
+            .await
+            .map_err(SlackError::HttpError)?;
+
+        let result: SlackResponse<serde_json::Value> = response
+            .json()
+            .await
+            .map_err(|e| SlackError::ParseError(e.to_string()))?;
+
+        if !result.ok {
+            return Err(SlackError::ApiError(result.error.unwrap_or("Unknown error".to_string())));
+        }
+
+        Ok(())
+    }
+
+    /// Reply to a slash command or shortcut through its `response_url`
+    ///
+    /// Ephemeral replies are only shown to the user who invoked it.
+    pub async fn respond(&self, response_url: &str, text: &str, ephemeral: bool) -> Result<()> {
+        let body = serde_json::json!({
+            "response_type": if ephemeral { "ephemeral" } else { "in_channel" },
+            "text": text,
+        });
+
+        let response = self
+            .client
+            .post(response_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(SlackError::HttpError)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(SlackError::ApiError(format!("{}: {}", status, error_text)));
+        }
+
+        Ok(())
+    }
+
     /// Get list of conversations (channels)
     pub async fn conversations_list(&self, types: Option<&str>) -> Result<Vec<SlackChannel>> {
         let url = format!("{}/conversations.list", self.base_url);
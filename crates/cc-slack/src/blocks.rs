@@ -0,0 +1,309 @@
+//! Block Kit rendering of agent replies
+//!
+//! Replies are rendered as section blocks (Markdown converted to Slack
+//! mrkdwn), code fences as preformatted rich text, and a context block with
+//! the model and token usage. Slack limits section text to 3000 characters
+//! and messages to 50 blocks, so long replies span several messages.
+
+use serde_json::{json, Value};
+
+use cc_core::Usage;
+
+/// Characters allowed in a section block
+pub const MAX_SECTION_CHARS: usize = 3000;
+
+/// Blocks allowed in a message
+pub const MAX_BLOCKS: usize = 50;
+
+/// Fallback (notification) text length
+const MAX_FALLBACK_CHARS: usize = 3000;
+
+/// Shown while waiting for the first tokens
+pub const THINKING_TEXT: &str = "Thinking…";
+
+/// A piece of a reply
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    Prose(String),
+    Code(String),
+}
+
+/// Split Markdown into prose and fenced code
+fn segments(markdown: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            let text = std::mem::take(&mut current);
+            let text = text.trim_matches('\n');
+            if in_code {
+                segments.push(Segment::Code(text.to_string()));
+            } else if !text.trim().is_empty() {
+                segments.push(Segment::Prose(text.to_string()));
+            }
+            in_code = !in_code;
+            continue;
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+
+    // An unclosed fence (e.g. while streaming) still renders as code
+    let text = current.trim_matches('\n');
+    if in_code {
+        segments.push(Segment::Code(text.to_string()));
+    } else if !text.trim().is_empty() {
+        segments.push(Segment::Prose(text.to_string()));
+    }
+    segments
+}
+
+/// Convert Markdown prose to Slack mrkdwn
+pub fn to_mrkdwn(markdown: &str) -> String {
+    markdown
+        .lines()
+        .map(|line| {
+            let (quote, line) = match line.strip_prefix("> ") {
+                Some(rest) => ("> ", rest),
+                None => ("", line),
+            };
+            let line = escape(line);
+            let trimmed = line.trim_start_matches('#');
+            let line = if trimmed.len() < line.len() && trimmed.starts_with(' ') {
+                format!("*{}*", trimmed.trim())
+            } else {
+                convert_inline(&line)
+            };
+            format!("{}{}", quote, line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Escape the characters Slack treats as control sequences
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Bold, strikethrough and links; inline code is left as is
+fn convert_inline(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for (index, part) in line.split('`').enumerate() {
+        if index > 0 {
+            out.push('`');
+        }
+        if index % 2 == 1 {
+            out.push_str(part);
+            continue;
+        }
+        let part = part.replace("**", "*").replace("__", "*").replace("~~", "~");
+        out.push_str(&convert_links(&part));
+    }
+    out
+}
+
+/// `[text](url)` to `<url|text>`
+fn convert_links(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        let link = rest[start..].find("](").and_then(|mid| {
+            let url_start = start + mid + 2;
+            rest[url_start..]
+                .find(')')
+                .map(|end| (&rest[start + 1..start + mid], &rest[url_start..url_start + end], url_start + end + 1))
+        });
+        match link {
+            Some((label, url, end)) if !url.contains(char::is_whitespace) => {
+                out.push_str(&rest[..start]);
+                out.push_str(&format!("<{}|{}>", url, label));
+                rest = &rest[end..];
+            }
+            _ => {
+                out.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Split `text` into chunks of at most `max` characters, preferring
+/// paragraph and line breaks
+fn chunk(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for line in text.split('\n') {
+        let len = line.chars().count();
+        if current_len > 0 && current_len + 1 + len > max {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if len > max {
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(max) {
+                chunks.push(piece.iter().collect());
+            }
+            continue;
+        }
+        if current_len > 0 {
+            current.push('\n');
+            current_len += 1;
+        }
+        current.push_str(line);
+        current_len += len;
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Blocks for reply `text`: sections and code blocks
+pub fn answer_blocks(text: &str) -> Vec<Value> {
+    let mut blocks = Vec::new();
+    for segment in segments(text) {
+        match segment {
+            Segment::Prose(prose) => {
+                for part in chunk(&to_mrkdwn(&prose), MAX_SECTION_CHARS) {
+                    blocks.push(json!({
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": part },
+                    }));
+                }
+            }
+            Segment::Code(code) => {
+                for part in chunk(&code, MAX_SECTION_CHARS) {
+                    blocks.push(json!({
+                        "type": "rich_text",
+                        "elements": [{
+                            "type": "rich_text_preformatted",
+                            "elements": [{ "type": "text", "text": part }],
+                        }],
+                    }));
+                }
+            }
+        }
+    }
+    blocks
+}
+
+/// Context block with the model and token usage
+pub fn usage_context(model: &str, usage: &Usage) -> Value {
+    json!({
+        "type": "context",
+        "elements": [{
+            "type": "mrkdwn",
+            "text": format!(
+                "{} · {} in / {} out tokens",
+                model, usage.input_tokens, usage.output_tokens
+            ),
+        }],
+    })
+}
+
+/// Context block with a status line
+fn status_context(text: &str) -> Value {
+    json!({
+        "type": "context",
+        "elements": [{ "type": "mrkdwn", "text": format!("_{}_", text) }],
+    })
+}
+
+/// Blocks shown while the reply is streaming
+///
+/// Only the start of a reply too long for one message is shown; the final
+/// update sends the rest.
+pub fn streaming_blocks(text: &str) -> Vec<Value> {
+    let mut blocks = answer_blocks(text);
+    blocks.truncate(MAX_BLOCKS - 1);
+    blocks.push(status_context(THINKING_TEXT));
+    blocks
+}
+
+/// The final reply as one or more messages of blocks, with the usage
+/// context at the end
+pub fn reply_messages(text: &str, model: &str, usage: Option<&Usage>) -> Vec<Vec<Value>> {
+    let mut blocks = answer_blocks(text);
+    if blocks.is_empty() {
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": "_(empty response)_" },
+        }));
+    }
+    if let Some(usage) = usage {
+        blocks.push(usage_context(model, usage));
+    }
+    blocks.chunks(MAX_BLOCKS).map(<[Value]>::to_vec).collect()
+}
+
+/// Plain text sent with blocks, used for notifications
+pub fn fallback_text(text: &str) -> String {
+    let text = text.trim();
+    if text.is_empty() {
+        return THINKING_TEXT.to_string();
+    }
+    let mut fallback: String = text.chars().take(MAX_FALLBACK_CHARS).collect();
+    if fallback.len() < text.len() {
+        fallback.push('…');
+    }
+    fallback
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_mrkdwn() {
+        assert_eq!(to_mrkdwn("## Summary"), "*Summary*");
+        assert_eq!(
+            to_mrkdwn("**bold** and ~~gone~~, see [docs](https://example.com)"),
+            "*bold* and ~gone~, see <https://example.com|docs>"
+        );
+        assert_eq!(to_mrkdwn("keep `**code**` & <tags>"), "keep `**code**` &amp; &lt;tags&gt;");
+        assert_eq!(to_mrkdwn("> quoted **text**"), "> quoted *text*");
+        assert_eq!(to_mrkdwn("#hashtag [not a link]"), "#hashtag [not a link]");
+    }
+
+    #[test]
+    fn test_answer_blocks() {
+        let blocks = answer_blocks("Run this:\n\n```sh\ncargo test\n```\n\nDone.");
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0]["text"]["text"], "Run this:");
+        assert_eq!(blocks[1]["type"], "rich_text");
+        assert_eq!(blocks[1]["elements"][0]["elements"][0]["text"], "cargo test");
+        assert_eq!(blocks[2]["text"]["text"], "Done.");
+
+        // Unclosed fence while streaming
+        let blocks = answer_blocks("```\nfn main");
+        assert_eq!(blocks[0]["type"], "rich_text");
+    }
+
+    #[test]
+    fn test_long_replies() {
+        let long = "あ".repeat(MAX_SECTION_CHARS + 10);
+        let blocks = answer_blocks(&long);
+        assert_eq!(blocks.len(), 2);
+
+        let many = vec!["paragraph"; 60].join("\n\n```\ncode\n```\n\n");
+        let usage = Usage {
+            input_tokens: 10,
+            output_tokens: 20,
+            ..Default::default()
+        };
+        let messages = reply_messages(&many, "claude", Some(&usage));
+        assert!(messages.len() > 1);
+        assert!(messages.iter().all(|m| m.len() <= MAX_BLOCKS));
+        let last = messages.last().unwrap().last().unwrap();
+        assert_eq!(last["elements"][0]["text"], "claude · 10 in / 20 out tokens");
+
+        assert!(streaming_blocks(&many).len() <= MAX_BLOCKS);
+        assert_eq!(fallback_text(&long).chars().count(), MAX_FALLBACK_CHARS + 1);
+    }
+}
@@ -10,10 +10,11 @@ use cc_core::{ClaudeClient, Config};
 
 use crate::api::SlackApiClient;
 use crate::error::{Result, SlackError};
-use crate::handler::{HandlerConfig, MessageHandler};
+use crate::handler::{HandlerConfig, MessageHandler, DEFAULT_STREAM_UPDATE_INTERVAL};
 use crate::session::InMemorySessionStore;
-use crate::socket::SocketModeClient;
-use crate::types::{SlackEvent, SlackMessage};
+use crate::settings::ChannelSettingsStore;
+use crate::socket::{SocketEvent, SocketModeClient};
+use crate::types::SlackMessage;
 
 /// Slack Bot configuration
 #[derive(Clone, Debug, Default)]
@@ -36,6 +37,7 @@ pub struct SlackBot {
     api_client: SlackApiClient,
     claude_client: Arc<ClaudeClient>,
     session_store: Arc<InMemorySessionStore>,
+    settings_store: Arc<ChannelSettingsStore>,
     handler_config: HandlerConfig,
 }
 
@@ -61,6 +63,7 @@ impl SlackBot {
                 "You are a helpful assistant. Respond concisely. Use Slack markdown formatting when appropriate.".to_string()
             }),
             max_message_length: 3500,
+            stream_update_interval: DEFAULT_STREAM_UPDATE_INTERVAL,
         };
 
         // Start session cleanup task
@@ -76,6 +79,7 @@ impl SlackBot {
             api_client,
             claude_client: Arc::new(claude_client),
             session_store,
+            settings_store: Arc::new(ChannelSettingsStore::new()),
             handler_config,
         })
     }
@@ -101,6 +105,7 @@ impl SlackBot {
                 "You are a helpful assistant. Respond concisely. Use Slack markdown formatting when appropriate.".to_string()
             }),
             max_message_length: 3500,
+            stream_update_interval: DEFAULT_STREAM_UPDATE_INTERVAL,
         };
 
         // Start session cleanup task
@@ -116,6 +121,7 @@ impl SlackBot {
             api_client,
             claude_client,
             session_store,
+            settings_store: Arc::new(ChannelSettingsStore::new()),
             handler_config,
         })
     }
@@ -125,6 +131,11 @@ impl SlackBot {
         self.session_store.clone()
     }
 
+    /// Get the channel settings store
+    pub fn settings_store(&self) -> Arc<ChannelSettingsStore> {
+        self.settings_store.clone()
+    }
+
     /// Test the Slack API connection
     pub async fn test_connection(&self) -> Result<String> {
         let auth = self.api_client.auth_test().await?;
@@ -163,33 +174,14 @@ impl SlackBot {
             self.claude_client.clone(),
             self.session_store.clone(),
             handler_config,
-        ));
+        )
+        .with_settings_store(self.settings_store.clone()));
 
         // Start Socket Mode
         let socket_client = SocketModeClient::new(app_token, self.api_client.clone());
 
-        socket_client.start(move |event: SlackEvent| {
-            // Only handle message events
-            if event.event_type == "message" {
-                let msg = SlackMessage {
-                    channel: event.channel.unwrap_or_default(),
-                    user: event.user,
-                    text: event.text.unwrap_or_default(),
-                    ts: event.ts.unwrap_or_default(),
-                    thread_ts: event.thread_ts,
-                    bot_id: event.bot_id,
-                    subtype: event.subtype,
-                };
-
-                // We need to handle this in an async context
-                // For simplicity, we'll spawn a task
-                let handler_clone = handler.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = handler_clone.process_message(&msg).await {
-                        error!("Error processing message: {:?}", e);
-                    }
-                });
-            }
+        socket_client.start(move |event: SocketEvent| {
+            dispatch(&handler, event);
             Ok(())
         }).await
     }
@@ -214,7 +206,8 @@ impl SlackBot {
             self.claude_client.clone(),
             self.session_store.clone(),
             handler_config,
-        ));
+        )
+        .with_settings_store(self.settings_store.clone()));
 
         // Start Socket Mode in a task
         let socket_client = SocketModeClient::new(app_token, self.api_client.clone());
@@ -223,25 +216,8 @@ impl SlackBot {
 
         let socket_task = tokio::spawn(async move {
             if running_clone.load(std::sync::atomic::Ordering::SeqCst) {
-                let _ = socket_client.start(move |event: SlackEvent| {
-                    if event.event_type == "message" {
-                        let msg = SlackMessage {
-                            channel: event.channel.unwrap_or_default(),
-                            user: event.user,
-                            text: event.text.unwrap_or_default(),
-                            ts: event.ts.unwrap_or_default(),
-                            thread_ts: event.thread_ts,
-                            bot_id: event.bot_id,
-                            subtype: event.subtype,
-                        };
-
-                        let handler_clone = handler.clone();
-                        tokio::spawn(async move {
-                            if let Err(e) = handler_clone.process_message(&msg).await {
-                                error!("Error processing message: {:?}", e);
-                            }
-                        });
-                    }
+                let _ = socket_client.start(move |event: SocketEvent| {
+                    dispatch(&handler, event);
                     Ok(())
                 }).await;
             }
@@ -257,6 +233,45 @@ impl SlackBot {
     }
 }
 
+/// Handle a Socket Mode event in its own task
+fn dispatch(handler: &Arc<MessageHandler>, event: SocketEvent) {
+    let handler = handler.clone();
+    match event {
+        // Only handle message events
+        SocketEvent::Event(event) if event.event_type == "message" => {
+            let msg = SlackMessage {
+                channel: event.channel.unwrap_or_default(),
+                user: event.user,
+                text: event.text.unwrap_or_default(),
+                ts: event.ts.unwrap_or_default(),
+                thread_ts: event.thread_ts,
+                bot_id: event.bot_id,
+                subtype: event.subtype,
+            };
+            tokio::spawn(async move {
+                if let Err(e) = handler.process_message(&msg).await {
+                    error!("Error processing message: {:?}", e);
+                }
+            });
+        }
+        SocketEvent::Event(_) => {}
+        SocketEvent::SlashCommand(command) => {
+            tokio::spawn(async move {
+                if let Err(e) = handler.handle_slash_command(&command).await {
+                    error!("Error handling slash command: {:?}", e);
+                }
+            });
+        }
+        SocketEvent::Interaction(payload) => {
+            tokio::spawn(async move {
+                if let Err(e) = handler.handle_interaction(&payload).await {
+                    error!("Error handling interaction: {:?}", e);
+                }
+            });
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,6 +301,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: cc_core::LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -296,6 +314,12 @@ mod tests {
             memory: cc_core::MemoryConfig::default(),
             mcp: cc_core::McpConfig::default(),
             scheduler: cc_core::SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 }
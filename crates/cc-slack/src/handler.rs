@@ -1,14 +1,24 @@
 //! Slack message handler implementation
 
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
 use tracing::{debug, error, info};
 
-use cc_core::{ClaudeClient, Message};
+use cc_core::{ClaudeClient, Message, MessagesRequest};
 
 use crate::api::SlackApiClient;
+use crate::blocks;
 use crate::error::Result;
 use crate::session::InMemorySessionStore;
-use crate::types::SlackMessage;
+use crate::settings::ChannelSettingsStore;
+use crate::summary::SummaryScope;
+use crate::types::{SlackMessage, UpdateMessage};
+
+/// Default time between message updates while a reply streams
+///
+/// chat.update allows about one call per second per channel.
+pub const DEFAULT_STREAM_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Configuration for the message handler
 #[derive(Clone, Debug)]
@@ -23,6 +33,8 @@ pub struct HandlerConfig {
     pub system_prompt: String,
     /// Maximum message length before splitting
     pub max_message_length: usize,
+    /// Time between message updates while a reply streams
+    pub stream_update_interval: Duration,
 }
 
 impl Default for HandlerConfig {
@@ -33,16 +45,18 @@ impl Default for HandlerConfig {
             bot_user_id: None,
             system_prompt: "You are a helpful assistant. Respond concisely. Use Slack markdown formatting when appropriate.".to_string(),
             max_message_length: 3500, // Slack has ~4000 char limit, leave some buffer
+            stream_update_interval: DEFAULT_STREAM_UPDATE_INTERVAL,
         }
     }
 }
 
 /// Message handler for Slack
 pub struct MessageHandler {
-    api_client: SlackApiClient,
-    claude_client: Arc<ClaudeClient>,
-    session_store: Arc<InMemorySessionStore>,
-    config: HandlerConfig,
+    pub(crate) api_client: SlackApiClient,
+    pub(crate) claude_client: Arc<ClaudeClient>,
+    pub(crate) session_store: Arc<InMemorySessionStore>,
+    pub(crate) settings_store: Arc<ChannelSettingsStore>,
+    pub(crate) config: HandlerConfig,
 }
 
 impl MessageHandler {
@@ -57,10 +71,17 @@ impl MessageHandler {
             api_client,
             claude_client,
             session_store,
+            settings_store: Arc::new(ChannelSettingsStore::new()),
             config,
         }
     }
 
+    /// Use a shared channel settings store
+    pub fn with_settings_store(mut self, settings_store: Arc<ChannelSettingsStore>) -> Self {
+        self.settings_store = settings_store;
+        self
+    }
+
     /// Process an incoming message
     pub async fn process_message(&self, msg: &SlackMessage) -> Result<()> {
         // Skip bot messages (including our own)
@@ -104,7 +125,7 @@ impl MessageHandler {
     }
 
     /// Check if channel is allowed
-    fn is_channel_allowed(&self, channel_id: &str) -> bool {
+    pub(crate) fn is_channel_allowed(&self, channel_id: &str) -> bool {
         if self.config.allowed_channels.is_empty() {
             return true;
         }
@@ -112,7 +133,7 @@ impl MessageHandler {
     }
 
     /// Check if user is allowed
-    fn is_user_allowed(&self, user_id: &str) -> bool {
+    pub(crate) fn is_user_allowed(&self, user_id: &str) -> bool {
         if self.config.allowed_users.is_empty() {
             return true;
         }
@@ -139,6 +160,7 @@ impl MessageHandler {
                     "!clear - Reset conversation session\n",
                     "!help - Show this help\n",
                     "!status - Show session status\n",
+                    "!summarize [count] - Summarize this thread, or the latest channel messages\n",
                     "\n",
                     "Otherwise, just chat normally!"
                 );
@@ -153,6 +175,18 @@ impl MessageHandler {
                 };
                 self.send_reply(msg, &status).await?;
             }
+            "!summarize" | "/summarize" => {
+                let arg = content[command.len()..].trim();
+                match SummaryScope::parse(arg, msg.thread_ts.as_deref()) {
+                    Ok(scope) => {
+                        let reply_ts = msg.thread_ts.as_deref().unwrap_or(&msg.ts);
+                        self.summarize(&msg.channel, &scope, reply_ts).await?;
+                    }
+                    Err(e) => {
+                        self.send_reply(msg, &e).await?;
+                    }
+                }
+            }
             _ => {
                 // Unknown command, treat as regular message
                 self.process_with_claude(msg).await?;
@@ -181,18 +215,55 @@ impl MessageHandler {
         // Add reaction to show we're processing
         let _ = self.api_client.reactions_add(&msg.channel, &msg.ts, "thinking_face").await;
 
+        self.ask_in_thread(&msg.channel, &msg.ts, &session_key, content).await
+    }
+
+    /// Ask Claude `content` in session `session_key` and stream the reply
+    /// into the thread of `thread_ts`
+    pub(crate) async fn ask_in_thread(
+        &self,
+        channel: &str,
+        thread_ts: &str,
+        session_key: &str,
+        content: &str,
+    ) -> Result<()> {
         // Get or create session
-        let session = self.session_store.get_or_create(&session_key);
+        let session = self.session_store.get_or_create(session_key);
 
         // Build message history
         let mut messages: Vec<Message> = session.messages.clone();
         messages.push(Message::user(content));
 
+        let request = self.build_request(channel, None, messages);
+        if let Some(text) = self.reply_in_thread(channel, thread_ts, request).await? {
+            // Update session
+            self.session_store
+                .add_message(session_key, Message::user(content));
+            self.session_store
+                .add_message(session_key, Message::assistant(&text));
+        }
+
+        Ok(())
+    }
+
+    /// Build a request for `channel` with its settings (model and system
+    /// prompt); `system` overrides the system prompt
+    pub(crate) fn build_request(
+        &self,
+        channel: &str,
+        system: Option<&str>,
+        messages: Vec<Message>,
+    ) -> MessagesRequest {
+        let settings = self.settings_store.get(channel);
+        let system = system
+            .or(settings.system_prompt.as_deref())
+            .unwrap_or(&self.config.system_prompt);
+
         // Build request with conversation history
         let mut request_builder = self
             .claude_client
             .request_builder()
-            .system(&self.config.system_prompt)
+            .system(system)
             .max_tokens(2048);
 
         // Add conversation history (limit to last 20 messages)
@@ -201,11 +272,35 @@ impl MessageHandler {
             request_builder = request_builder.message(message);
         }
 
-        let request = request_builder.build();
+        let mut request = request_builder.build();
+        if let Some(model) = settings.model {
+            request.model = model;
+        }
+        request
+    }
 
-        match self.claude_client.messages(request).await {
+    /// Post a placeholder in the thread of `thread_ts`, fill it in as the
+    /// reply to `request` streams, and return the reply text
+    ///
+    /// API errors are shown in the placeholder and return None.
+    pub(crate) async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_ts: &str,
+        request: MessagesRequest,
+    ) -> Result<Option<String>> {
+        let placeholder = self
+            .api_client
+            .send_blocks(
+                channel,
+                blocks::THINKING_TEXT,
+                blocks::streaming_blocks(""),
+                Some(thread_ts),
+            )
+            .await?;
+
+        match self.stream_reply(request, channel, &placeholder.ts).await {
             Ok(response) => {
-                // Extract text from response
                 let text = response
                     .content
                     .iter()
@@ -219,23 +314,81 @@ impl MessageHandler {
                     .collect::<Vec<_>>()
                     .join("\n");
 
-                // Update session
-                self.session_store
-                    .add_message(&session_key, Message::user(content));
-                self.session_store
-                    .add_message(&session_key, Message::assistant(&text));
-
-                // Send response
-                self.send_reply(msg, &text).await?;
+                // Replace the placeholder with the final blocks; replies
+                // longer than one message continue in the thread
+                let messages = blocks::reply_messages(&text, &response.model, response.usage.as_ref());
+                let fallback = blocks::fallback_text(&text);
+                for (i, message_blocks) in messages.into_iter().enumerate() {
+                    if i == 0 {
+                        self.api_client
+                            .update_message(&UpdateMessage {
+                                channel: channel.to_string(),
+                                ts: placeholder.ts.clone(),
+                                text: fallback.clone(),
+                                blocks: Some(message_blocks),
+                            })
+                            .await?;
+                    } else {
+                        self.api_client
+                            .send_blocks(channel, &fallback, message_blocks, Some(thread_ts))
+                            .await?;
+                    }
+                }
+                Ok(Some(text))
             }
             Err(e) => {
                 error!("Claude API error: {:?}", e);
-                self.send_reply(msg, &format!("Error: {}", e))
+                self.api_client
+                    .update_message(&UpdateMessage {
+                        channel: channel.to_string(),
+                        ts: placeholder.ts,
+                        text: format!("Error: {}", e),
+                        blocks: Some(Vec::new()),
+                    })
                     .await?;
+                Ok(None)
             }
         }
+    }
 
-        Ok(())
+    /// Stream the reply to `request`, updating message `ts` as text arrives
+    ///
+    /// Updates are sent at most once per `stream_update_interval`; failed
+    /// updates (e.g. rate limits) are skipped, as the next one catches up.
+    async fn stream_reply(
+        &self,
+        request: MessagesRequest,
+        channel: &str,
+        ts: &str,
+    ) -> cc_core::Result<cc_core::MessagesResponse> {
+        let (text_tx, mut text_rx) = watch::channel(String::new());
+        let stream = self
+            .claude_client
+            .messages_stream(request, |piece| text_tx.send_modify(|text| text.push_str(piece)));
+        tokio::pin!(stream);
+
+        let mut ticker = tokio::time::interval(self.config.stream_update_interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                result = &mut stream => return result,
+                _ = ticker.tick() => {
+                    if !text_rx.has_changed().unwrap_or(false) {
+                        continue;
+                    }
+                    let text = text_rx.borrow_and_update().clone();
+                    let update = UpdateMessage {
+                        channel: channel.to_string(),
+                        ts: ts.to_string(),
+                        text: blocks::fallback_text(&text),
+                        blocks: Some(blocks::streaming_blocks(&text)),
+                    };
+                    if let Err(e) = self.api_client.update_message(&update).await {
+                        debug!("Skipped streaming update: {}", e);
+                    }
+                }
+            }
+        }
     }
 
     /// Send a reply
@@ -322,6 +475,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test-key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -332,6 +488,12 @@ mod tests {
             memory: MemoryConfig::default(),
             mcp: McpConfig::default(),
             scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -353,6 +515,7 @@ mod tests {
             api_client,
             claude_client,
             session_store: Arc::new(InMemorySessionStore::new()),
+            settings_store: Arc::new(ChannelSettingsStore::new()),
             config: config.clone(),
         };
 
@@ -376,6 +539,7 @@ mod tests {
             api_client,
             claude_client: claude_client.clone(),
             session_store: Arc::new(store.clone()),
+            settings_store: Arc::new(ChannelSettingsStore::new()),
             config: config.clone(),
         };
 
@@ -392,6 +556,7 @@ mod tests {
             api_client: SlackApiClient::new("xoxb-test").unwrap(),
             claude_client,
             session_store: Arc::new(store),
+            settings_store: Arc::new(ChannelSettingsStore::new()),
             config: config_with_allow,
         };
         assert!(handler_with_allow.is_channel_allowed("C12345678"));
@@ -0,0 +1,259 @@
+//! Slash commands, shortcuts and modals
+//!
+//! - `/claude <question>`: posts the question to the channel and streams
+//!   the answer in its thread (replies in the thread continue the session)
+//! - `/claude config`: opens the channel settings modal (model, system prompt)
+//! - `/claude summarize [count]`: summarizes the latest channel messages
+//! - `/claude clear`, `/claude help`
+//! - "Summarize this thread" message shortcut (callback ID `summarize_thread`)
+
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+use crate::error::Result;
+use crate::handler::MessageHandler;
+use crate::settings::ChannelSettings;
+use crate::summary::{SummaryScope, DEFAULT_CHANNEL_MESSAGES};
+use crate::types::{InteractionPayload, SlashCommand};
+
+/// The slash command handled by the bot
+pub const SLASH_COMMAND: &str = "/claude";
+
+/// Callback ID of the "Summarize this thread" message shortcut
+pub const SUMMARIZE_CALLBACK_ID: &str = "summarize_thread";
+
+/// Callback ID of the channel settings modal
+pub const CONFIG_CALLBACK_ID: &str = "claude_config";
+
+const SLASH_HELP: &str = concat!(
+    "*/claude* commands:\n",
+    "`/claude <question>` - Ask Claude (the answer is posted in a thread)\n",
+    "`/claude config` - Configure the model and system prompt for this channel\n",
+    "`/claude summarize [count]` - Summarize the latest messages of this channel (default 100)\n",
+    "`/claude clear` - Reset the channel session\n",
+    "`/claude help` - Show this help\n",
+    "\n",
+    "Use the *Summarize this thread* message shortcut to summarize a thread."
+);
+
+const NOT_ALLOWED: &str = "You are not allowed to use Claude in this channel.";
+
+impl MessageHandler {
+    /// Handle a slash command
+    pub async fn handle_slash_command(&self, command: &SlashCommand) -> Result<()> {
+        if command.command != SLASH_COMMAND {
+            debug!("Ignoring slash command: {}", command.command);
+            return Ok(());
+        }
+        if !self.is_user_allowed(&command.user_id) || !self.is_channel_allowed(&command.channel_id) {
+            return self.api_client.respond(&command.response_url, NOT_ALLOWED, true).await;
+        }
+
+        let text = command.text.trim();
+        let subcommand = text.split_whitespace().next().unwrap_or("");
+        match subcommand {
+            "" | "config" => {
+                let settings = self.settings_store.get(&command.channel_id);
+                self.api_client
+                    .views_open(&command.trigger_id, &config_modal(&command.channel_id, &settings))
+                    .await
+            }
+            "help" => self.api_client.respond(&command.response_url, SLASH_HELP, true).await,
+            "clear" | "reset" => {
+                let reply = if self.session_store.clear(&command.channel_id) {
+                    "Session reset."
+                } else {
+                    "No session to reset."
+                };
+                self.api_client.respond(&command.response_url, reply, true).await
+            }
+            "summarize" => {
+                let arg = text["summarize".len()..].trim();
+                let count = match SummaryScope::parse(arg, None) {
+                    Ok(SummaryScope::Channel(count)) => count,
+                    Ok(SummaryScope::Thread(_)) => DEFAULT_CHANNEL_MESSAGES,
+                    Err(e) => return self.api_client.respond(&command.response_url, &e, true).await,
+                };
+                let request = format!(
+                    "<@{}> requested a summary of the last {} messages.",
+                    command.user_id, count
+                );
+                let posted = match self.api_client.send_message(&command.channel_id, &request, None).await {
+                    Ok(posted) => posted,
+                    Err(e) => {
+                        let reply = format!("Could not post in this channel (is the bot a member?): {}", e);
+                        return self.api_client.respond(&command.response_url, &reply, true).await;
+                    }
+                };
+                self.summarize(&command.channel_id, &SummaryScope::Channel(count), &posted.ts)
+                    .await
+            }
+            _ => self.ask_from_command(command, text).await,
+        }
+    }
+
+    /// Post `question` and answer it in the thread of the posted message
+    async fn ask_from_command(&self, command: &SlashCommand, question: &str) -> Result<()> {
+        info!("Slash command from {} in {}: {}", command.user_id, command.channel_id, question);
+
+        let quoted = question
+            .lines()
+            .map(|line| format!("> {}", line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let posted = match self
+            .api_client
+            .send_message(&command.channel_id, &format!("<@{}> asked:\n{}", command.user_id, quoted), None)
+            .await
+        {
+            Ok(posted) => posted,
+            Err(e) => {
+                warn!("Failed to post question in {}: {}", command.channel_id, e);
+                let reply = format!("Could not post in this channel (is the bot a member?): {}", e);
+                return self.api_client.respond(&command.response_url, &reply, true).await;
+            }
+        };
+
+        // Same key as replies in that thread, so the conversation continues there
+        let session_key = format!("{}-{}", command.channel_id, posted.ts);
+        self.ask_in_thread(&command.channel_id, &posted.ts, &session_key, question)
+            .await
+    }
+
+    /// Handle an interactive payload (shortcuts and modal submissions)
+    pub async fn handle_interaction(&self, payload: &InteractionPayload) -> Result<()> {
+        let view_callback_id = payload
+            .view
+            .as_ref()
+            .and_then(|view| view["callback_id"].as_str());
+
+        match (payload.interaction_type.as_str(), payload.callback_id.as_deref(), view_callback_id) {
+            ("message_action", Some(SUMMARIZE_CALLBACK_ID), _) => self.summarize_thread(payload).await,
+            ("view_submission", _, Some(CONFIG_CALLBACK_ID)) => {
+                let Some((channel, settings)) = payload.view.as_ref().and_then(modal_settings) else {
+                    warn!("Settings modal without channel");
+                    return Ok(());
+                };
+                if !self.is_user_allowed(&payload.user.id) || !self.is_channel_allowed(&channel) {
+                    return Ok(());
+                }
+                info!("{} updated Claude settings for {}: {:?}", payload.user.id, channel, settings);
+                self.settings_store.set(&channel, settings);
+                Ok(())
+            }
+            (interaction_type, callback_id, _) => {
+                debug!("Ignoring interaction {} ({:?})", interaction_type, callback_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Summarize the thread of the shortcut's message into that thread
+    async fn summarize_thread(&self, payload: &InteractionPayload) -> Result<()> {
+        let (Some(channel), Some(message)) = (&payload.channel, &payload.message) else {
+            return Ok(());
+        };
+        if !self.is_user_allowed(&payload.user.id) || !self.is_channel_allowed(&channel.id) {
+            return match payload.response_url.as_deref() {
+                Some(url) => self.api_client.respond(url, NOT_ALLOWED, true).await,
+                None => Ok(()),
+            };
+        }
+
+        let thread_ts = message.thread_ts.as_deref().unwrap_or(&message.ts);
+        self.summarize(&channel.id, &SummaryScope::Thread(thread_ts.to_string()), thread_ts)
+            .await
+    }
+}
+
+/// The channel settings modal
+fn config_modal(channel: &str, settings: &ChannelSettings) -> Value {
+    let input = |block_id: &str, label: &str, hint: &str, value: Option<&String>, multiline: bool| {
+        let mut element = json!({
+            "type": "plain_text_input",
+            "action_id": "value",
+            "multiline": multiline,
+        });
+        if let Some(value) = value {
+            element["initial_value"] = json!(value);
+        }
+        json!({
+            "type": "input",
+            "block_id": block_id,
+            "optional": true,
+            "label": { "type": "plain_text", "text": label },
+            "hint": { "type": "plain_text", "text": hint },
+            "element": element,
+        })
+    };
+
+    json!({
+        "type": "modal",
+        "callback_id": CONFIG_CALLBACK_ID,
+        "private_metadata": channel,
+        "title": { "type": "plain_text", "text": "Claude settings" },
+        "submit": { "type": "plain_text", "text": "Save" },
+        "close": { "type": "plain_text", "text": "Cancel" },
+        "blocks": [
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!("Settings for <#{}>", channel) },
+            },
+            input("model", "Model", "Leave empty to use the default model", settings.model.as_ref(), false),
+            input(
+                "system_prompt",
+                "System prompt",
+                "Leave empty to use the default system prompt",
+                settings.system_prompt.as_ref(),
+                true,
+            ),
+        ],
+    })
+}
+
+/// The channel and settings of a submitted settings modal
+fn modal_settings(view: &Value) -> Option<(String, ChannelSettings)> {
+    let channel = view["private_metadata"].as_str().filter(|c| !c.is_empty())?;
+    let value = |block_id: &str| {
+        view["state"]["values"][block_id]["value"]["value"]
+            .as_str()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    Some((
+        channel.to_string(),
+        ChannelSettings {
+            model: value("model"),
+            system_prompt: value("system_prompt"),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_modal_round_trip() {
+        let settings = ChannelSettings {
+            model: Some("claude-haiku".to_string()),
+            system_prompt: None,
+        };
+        let mut view = config_modal("C1", &settings);
+        assert_eq!(view["blocks"][1]["element"]["initial_value"], "claude-haiku");
+        assert!(view["blocks"][2]["element"].get("initial_value").is_none());
+
+        // Slack returns the entered values in view.state
+        view["state"] = json!({
+            "values": {
+                "model": { "value": { "type": "plain_text_input", "value": " " } },
+                "system_prompt": { "value": { "type": "plain_text_input", "value": "Be brief." } },
+            }
+        });
+        let (channel, submitted) = modal_settings(&view).unwrap();
+        assert_eq!(channel, "C1");
+        assert_eq!(submitted.model, None);
+        assert_eq!(submitted.system_prompt.as_deref(), Some("Be brief."));
+    }
+}
@@ -4,13 +4,18 @@
 //! Socket Mode と Events API の両方をサポートします。
 
 pub mod api;
+pub mod blocks;
 pub mod bot;
 pub mod error;
 pub mod handler;
+pub mod interaction;
 pub mod session;
+pub mod settings;
 pub mod socket;
+pub mod summary;
 pub mod types;
 
 pub use bot::SlackBot;
 pub use error::{Result, SlackError};
 pub use session::InMemorySessionStore;
+pub use settings::{ChannelSettings, ChannelSettingsStore};
@@ -0,0 +1,68 @@
+//! Per-channel settings
+//!
+//! Set from the `/claude config` modal and kept in memory like sessions.
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+/// Overrides for one channel
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelSettings {
+    /// Model (None = the gateway's model)
+    pub model: Option<String>,
+    /// System prompt (None = the handler's system prompt)
+    pub system_prompt: Option<String>,
+}
+
+/// In-memory channel settings store
+#[derive(Clone, Default)]
+pub struct ChannelSettingsStore {
+    settings: Arc<DashMap<String, ChannelSettings>>,
+}
+
+impl ChannelSettingsStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Settings of a channel (defaults when none are set)
+    pub fn get(&self, channel_id: &str) -> ChannelSettings {
+        self.settings
+            .get(channel_id)
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replace the settings of a channel
+    pub fn set(&self, channel_id: &str, settings: ChannelSettings) {
+        if settings == ChannelSettings::default() {
+            self.settings.remove(channel_id);
+        } else {
+            self.settings.insert(channel_id.to_string(), settings);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_reset() {
+        let store = ChannelSettingsStore::new();
+        assert_eq!(store.get("C1"), ChannelSettings::default());
+
+        let settings = ChannelSettings {
+            model: Some("claude-haiku".to_string()),
+            system_prompt: None,
+        };
+        store.set("C1", settings.clone());
+        assert_eq!(store.get("C1"), settings);
+        assert_eq!(store.get("C2"), ChannelSettings::default());
+
+        store.set("C1", ChannelSettings::default());
+        assert!(store.settings.is_empty());
+    }
+}
@@ -8,7 +8,40 @@ use tracing::{debug, error, info};
 
 use crate::api::SlackApiClient;
 use crate::error::{Result, SlackError};
-use crate::types::SlackEvent;
+use crate::types::{InteractionPayload, SlackEvent, SlashCommand};
+
+/// Event received over Socket Mode
+#[derive(Debug, Clone)]
+pub enum SocketEvent {
+    /// Events API event (messages, mentions, ...)
+    Event(SlackEvent),
+    /// Slash command invocation
+    SlashCommand(SlashCommand),
+    /// Shortcut, modal submission or other interactive payload
+    Interaction(Box<InteractionPayload>),
+}
+
+impl SocketEvent {
+    /// Parse the payload of an envelope (None for envelopes without one)
+    pub fn from_envelope(envelope_type: &str, payload: serde_json::Value) -> Result<Option<Self>> {
+        let parse_error = |e: serde_json::Error| SlackError::ParseError(e.to_string());
+        match envelope_type {
+            "events_api" => match payload.get("event") {
+                Some(event) => Ok(Some(Self::Event(
+                    serde_json::from_value(event.clone()).map_err(parse_error)?,
+                ))),
+                None => Ok(None),
+            },
+            "slash_commands" => Ok(Some(Self::SlashCommand(
+                serde_json::from_value(payload).map_err(parse_error)?,
+            ))),
+            "interactive" => Ok(Some(Self::Interaction(Box::new(
+                serde_json::from_value(payload).map_err(parse_error)?,
+            )))),
+            _ => Ok(None),
+        }
+    }
+}
 
 /// Socket Mode client
 pub struct SocketModeClient {
@@ -59,7 +92,7 @@ impl SocketModeClient {
     /// Start the Socket Mode connection
     pub async fn start<F>(&self, mut event_handler: F) -> Result<()>
     where
-        F: FnMut(SlackEvent) -> Result<()> + Send,
+        F: FnMut(SocketEvent) -> Result<()> + Send,
     {
         let ws_url = self.get_websocket_url().await?;
         info!("Connecting to Slack Socket Mode: {}", ws_url.split('?').next().unwrap_or(&ws_url));
@@ -101,32 +134,28 @@ impl SocketModeClient {
                     }
 
                     // Handle different message types
-                    match envelope.envelope_type.as_deref() {
-                        Some("hello") => {
+                    match (envelope.envelope_type.as_deref(), envelope.payload) {
+                        (Some("hello"), _) => {
                             info!("Received hello from Slack Socket Mode");
                         }
-                        Some("events_api") => {
-                            if let Some(payload) = envelope.payload {
-                                if let Some(event) = payload.get("event") {
-                                    let slack_event: SlackEvent = serde_json::from_value(event.clone())
-                                        .map_err(|e| SlackError::ParseError(e.to_string()))?;
-
-                                    if let Err(e) = event_handler(slack_event) {
+                        (Some(envelope_type), Some(payload)) => {
+                            debug!("Received {} payload", envelope_type);
+                            match SocketEvent::from_envelope(envelope_type, payload) {
+                                Ok(Some(event)) => {
+                                    if let Err(e) = event_handler(event) {
                                         error!("Error handling event: {:?}", e);
                                     }
                                 }
+                                Ok(None) => {
+                                    debug!("Ignoring envelope type: {}", envelope_type);
+                                }
+                                Err(e) => {
+                                    error!("Failed to parse {} payload: {}", envelope_type, e);
+                                }
                             }
                         }
-                        Some("interactive") => {
-                            // Handle interactive components (buttons, etc.)
-                            debug!("Received interactive event");
-                        }
-                        Some("slash_commands") => {
-                            // Handle slash commands
-                            debug!("Received slash command");
-                        }
-                        _ => {
-                            debug!("Unknown envelope type: {:?}", envelope.envelope_type);
+                        (envelope_type, _) => {
+                            debug!("Unknown envelope type: {:?}", envelope_type);
                         }
                     }
                 }
@@ -152,3 +181,43 @@ impl SocketModeClient {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_event_from_envelope() {
+        let event = SocketEvent::from_envelope(
+            "events_api",
+            serde_json::json!({"event": {"type": "message", "channel": "C1", "text": "hi", "ts": "1.0"}}),
+        )
+        .unwrap();
+        assert!(matches!(event, Some(SocketEvent::Event(e)) if e.text.as_deref() == Some("hi")));
+
+        let command = SocketEvent::from_envelope(
+            "slash_commands",
+            serde_json::json!({
+                "token": "t", "team_id": "T1", "team_domain": "example", "channel_id": "C1",
+                "channel_name": "general", "user_id": "U1", "user_name": "alice", "command": "/claude",
+                "text": "hello", "response_url": "https://hooks.slack.com/x", "trigger_id": "1.2"
+            }),
+        )
+        .unwrap();
+        assert!(matches!(command, Some(SocketEvent::SlashCommand(c)) if c.text == "hello"));
+
+        let shortcut = SocketEvent::from_envelope(
+            "interactive",
+            serde_json::json!({
+                "type": "message_action", "callback_id": "summarize_thread", "trigger_id": "1.2",
+                "user": {"id": "U1"}, "channel": {"id": "C1"},
+                "message": {"user": "U2", "text": "topic", "ts": "1.0"}
+            }),
+        )
+        .unwrap();
+        assert!(matches!(shortcut, Some(SocketEvent::Interaction(p)) if p.interaction_type == "message_action"));
+
+        assert!(SocketEvent::from_envelope("disconnect", serde_json::json!({})).unwrap().is_none());
+        assert!(SocketEvent::from_envelope("slash_commands", serde_json::json!({})).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Thread and channel summaries
+//!
+//! History is read with conversations.replies (a thread) or
+//! conversations.history (recent channel messages). A history too long for
+//! one request is split by estimated tokens; each part is summarized first
+//! and the part summaries are combined into the posted summary.
+
+use tracing::{info, warn};
+
+use cc_core::llm::estimate_text_tokens;
+use cc_core::{Message, MessageContent};
+
+use crate::error::Result;
+use crate::handler::MessageHandler;
+use crate::types::SlackMessage;
+
+/// Channel messages summarized when no count is given
+pub const DEFAULT_CHANNEL_MESSAGES: usize = 100;
+
+/// Most channel messages summarized at once
+pub const MAX_CHANNEL_MESSAGES: usize = 500;
+
+/// Estimated tokens of history sent in one request
+const MAX_PART_TOKENS: u64 = 30_000;
+
+/// Most parts summarized separately (older history is dropped)
+const MAX_PARTS: usize = 8;
+
+const SUMMARY_SYSTEM_PROMPT: &str = "Summarize the following Slack conversation. List the main points, decisions and open questions concisely. Keep the <@USER> mentions as they are. Answer in the language of the conversation.";
+
+const PART_SYSTEM_PROMPT: &str = "Summarize this part of a longer Slack conversation. Keep who said what (<@USER> mentions as they are), decisions, numbers and open questions. Answer in the language of the conversation.";
+
+const COMBINE_SYSTEM_PROMPT: &str = "The following are summaries of consecutive parts of one Slack conversation, oldest first. Combine them into one concise summary of the main points, decisions and open questions. Keep the <@USER> mentions as they are. Answer in the language of the conversation.";
+
+/// What to summarize
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SummaryScope {
+    /// The thread with this parent timestamp
+    Thread(String),
+    /// The latest messages of the channel
+    Channel(usize),
+}
+
+impl SummaryScope {
+    /// Parse the argument of a summarize command
+    ///
+    /// In a thread the command summarizes the thread; elsewhere, or with a
+    /// message count, the latest channel messages.
+    pub fn parse(arg: &str, thread_ts: Option<&str>) -> std::result::Result<Self, String> {
+        let arg = arg.trim();
+        if arg.is_empty() {
+            return Ok(match thread_ts {
+                Some(ts) => Self::Thread(ts.to_string()),
+                None => Self::Channel(DEFAULT_CHANNEL_MESSAGES),
+            });
+        }
+        match arg.parse::<usize>() {
+            Ok(count) if (1..=MAX_CHANNEL_MESSAGES).contains(&count) => Ok(Self::Channel(count)),
+            _ => Err(format!(
+                "Give the number of messages to summarize (1-{}).",
+                MAX_CHANNEL_MESSAGES
+            )),
+        }
+    }
+}
+
+/// Messages as `<@user>: text` lines, oldest first
+pub fn transcript_lines(messages: &[SlackMessage]) -> Vec<String> {
+    messages
+        .iter()
+        .filter_map(|message| {
+            let text = message.text.trim();
+            if text.is_empty() {
+                return None;
+            }
+            let author = match (&message.user, &message.bot_id) {
+                (Some(user), _) => format!("<@{}>", user),
+                (None, Some(_)) => "bot".to_string(),
+                (None, None) => "unknown".to_string(),
+            };
+            Some(format!("{}: {}", author, text))
+        })
+        .collect()
+}
+
+/// Split transcript lines into parts of at most `max_tokens` (estimated)
+///
+/// Only the newest `max_parts` parts are kept. A single line longer than
+/// the budget forms its own part.
+fn split_parts(lines: &[String], max_tokens: u64, max_parts: usize) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut tokens = 0;
+
+    for line in lines {
+        let line_tokens = estimate_text_tokens(line) + 1;
+        if !current.is_empty() && tokens + line_tokens > max_tokens {
+            parts.push(current.join("\n"));
+            current.clear();
+            tokens = 0;
+        }
+        current.push(line);
+        tokens += line_tokens;
+    }
+    if !current.is_empty() {
+        parts.push(current.join("\n"));
+    }
+
+    let dropped = parts.len().saturating_sub(max_parts);
+    parts.split_off(dropped)
+}
+
+impl MessageHandler {
+    /// Summarize `scope` of `channel` and post the summary in the thread
+    /// of `reply_ts`
+    pub(crate) async fn summarize(&self, channel: &str, scope: &SummaryScope, reply_ts: &str) -> Result<()> {
+        let history = match scope {
+            SummaryScope::Thread(ts) => self.api_client.conversations_replies(channel, ts).await,
+            SummaryScope::Channel(count) => self
+                .api_client
+                .conversations_history(channel, *count)
+                .await
+                // History comes newest first
+                .map(|messages| messages.into_iter().rev().collect()),
+        };
+        let messages = match history {
+            Ok(messages) => messages,
+            Err(e) => {
+                warn!("Failed to read history of {}: {}", channel, e);
+                let reply = format!("Could not read the conversation (is the bot a member?): {}", e);
+                self.api_client.send_message(channel, &reply, Some(reply_ts)).await?;
+                return Ok(());
+            }
+        };
+
+        let lines = transcript_lines(&messages);
+        if lines.is_empty() {
+            self.api_client
+                .send_message(channel, "There is nothing to summarize.", Some(reply_ts))
+                .await?;
+            return Ok(());
+        }
+
+        let parts = split_parts(&lines, MAX_PART_TOKENS, MAX_PARTS);
+        info!(
+            "Summarizing {:?} in {} ({} messages, {} parts)",
+            scope,
+            channel,
+            lines.len(),
+            parts.len()
+        );
+
+        let request = if parts.len() == 1 {
+            self.build_request(channel, Some(SUMMARY_SYSTEM_PROMPT), vec![Message::user(&parts[0])])
+        } else {
+            let mut summaries = Vec::new();
+            for (i, part) in parts.iter().enumerate() {
+                let request = self.build_request(channel, Some(PART_SYSTEM_PROMPT), vec![Message::user(part)]);
+                match self.claude_client.messages(request).await {
+                    Ok(response) => {
+                        let text = response
+                            .content
+                            .iter()
+                            .filter_map(|c| match c {
+                                MessageContent::Text { text } => Some(text.as_str()),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        summaries.push(format!("Part {}:\n{}", i + 1, text));
+                    }
+                    Err(e) => {
+                        warn!("Failed to summarize part {} of {}: {}", i + 1, channel, e);
+                        self.api_client
+                            .send_message(channel, &format!("Error: {}", e), Some(reply_ts))
+                            .await?;
+                        return Ok(());
+                    }
+                }
+            }
+            self.build_request(
+                channel,
+                Some(COMBINE_SYSTEM_PROMPT),
+                vec![Message::user(summaries.join("\n\n"))],
+            )
+        };
+
+        self.reply_in_thread(channel, reply_ts, request).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(user: Option<&str>, text: &str) -> SlackMessage {
+        SlackMessage {
+            channel: String::new(),
+            user: user.map(str::to_string),
+            text: text.to_string(),
+            ts: "1.0".to_string(),
+            thread_ts: None,
+            bot_id: user.is_none().then(|| "B1".to_string()),
+            subtype: None,
+        }
+    }
+
+    #[test]
+    fn test_scope_parse() {
+        assert_eq!(SummaryScope::parse("", Some("1.0")), Ok(SummaryScope::Thread("1.0".to_string())));
+        assert_eq!(SummaryScope::parse("", None), Ok(SummaryScope::Channel(DEFAULT_CHANNEL_MESSAGES)));
+        assert_eq!(SummaryScope::parse(" 50 ", Some("1.0")), Ok(SummaryScope::Channel(50)));
+        assert!(SummaryScope::parse("0", None).is_err());
+        assert!(SummaryScope::parse("lots", None).is_err());
+    }
+
+    #[test]
+    fn test_transcript_lines() {
+        let messages = vec![
+            message(Some("U1"), "Shall we ship on Friday?"),
+            message(None, "Thinking…"),
+            message(Some("U2"), " "),
+            message(Some("U2"), "Yes, after the review."),
+        ];
+        assert_eq!(
+            transcript_lines(&messages),
+            vec!["<@U1>: Shall we ship on Friday?", "bot: Thinking…", "<@U2>: Yes, after the review."]
+        );
+    }
+
+    #[test]
+    fn test_split_parts() {
+        let lines: Vec<String> = (0..10).map(|i| format!("<@U1>: message {}", i)).collect();
+        assert_eq!(split_parts(&lines, 10_000, 8).len(), 1);
+
+        // ~6 tokens per line: two lines per part
+        let parts = split_parts(&lines, 12, 8);
+        assert_eq!(parts.len(), 5);
+        assert_eq!(parts[0], "<@U1>: message 0\n<@U1>: message 1");
+
+        // Only the newest parts are kept
+        let parts = split_parts(&lines, 12, 2);
+        assert_eq!(parts, vec!["<@U1>: message 6\n<@U1>: message 7", "<@U1>: message 8\n<@U1>: message 9"]);
+    }
+}
@@ -40,8 +40,11 @@ pub struct SlackChannel {
 /// Slack message event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackMessage {
+    /// Not included in conversations.replies results
+    #[serde(default)]
     pub channel: String,
     pub user: Option<String>,
+    #[serde(default)]
     pub text: String,
     pub ts: String,
     #[serde(default)]
@@ -107,6 +110,42 @@ pub struct SlashCommand {
     pub trigger_id: String,
 }
 
+/// Interactive payload (shortcuts, modal submissions, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionPayload {
+    /// `message_action`, `view_submission`, `block_actions`, ...
+    #[serde(rename = "type")]
+    pub interaction_type: String,
+    /// Shortcut callback ID (for modals it is in `view`)
+    #[serde(default)]
+    pub callback_id: Option<String>,
+    #[serde(default)]
+    pub trigger_id: Option<String>,
+    pub user: InteractionUser,
+    #[serde(default)]
+    pub channel: Option<InteractionChannel>,
+    /// The message a message shortcut was used on
+    #[serde(default)]
+    pub message: Option<SlackMessage>,
+    #[serde(default)]
+    pub response_url: Option<String>,
+    /// The submitted modal
+    #[serde(default)]
+    pub view: Option<serde_json::Value>,
+}
+
+/// User of an interaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionUser {
+    pub id: String,
+}
+
+/// Channel of an interaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionChannel {
+    pub id: String,
+}
+
 /// Message to send
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostMessage {
@@ -118,6 +157,18 @@ pub struct PostMessage {
     pub blocks: Option<Vec<serde_json::Value>>,
 }
 
+/// Message update (chat.update)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateMessage {
+    pub channel: String,
+    /// Timestamp of the message to update
+    pub ts: String,
+    pub text: String,
+    /// Replaces the message's blocks (an empty list removes them)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<serde_json::Value>>,
+}
+
 /// API response wrapper
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackResponse<T> {
@@ -163,3 +214,18 @@ pub struct PostMessageResponse {
     pub channel: String,
     pub message: Option<SlackMessage>,
 }
+
+/// Update message response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateMessageResponse {
+    pub ts: String,
+    pub channel: String,
+}
+
+/// Conversations replies / history response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationsRepliesResponse {
+    pub messages: Vec<SlackMessage>,
+    #[serde(default)]
+    pub has_more: bool,
+}
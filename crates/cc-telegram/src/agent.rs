@@ -0,0 +1,85 @@
+//! Agent loop for Telegram turns
+//!
+//! Runs the model with the gateway's tools (when configured) and the
+//! `telegram_send_file` tool for sending generated files back. Calls are
+//! checked against the tool policy; those needing approval wait for the
+//! [`ToolApprover`] (the chat's inline keyboard).
+
+use async_trait::async_trait;
+
+use cc_core::llm::ToolCall;
+use cc_core::{
+    AgentEngine, AgentHooks, ApprovalOutcome, MessagesRequest, PolicyDecision, ToolApprover, ToolManager, ToolResult,
+};
+
+use crate::commands::BotState;
+use crate::media::FileSender;
+
+/// Model calls per turn before giving up
+const MAX_ITERATIONS: usize = 10;
+
+/// Run `request` until the model answers without calling tools
+pub(crate) async fn run_agent(
+    state: &BotState,
+    mut request: MessagesRequest,
+    approver: &dyn ToolApprover,
+    files: &FileSender,
+) -> cc_core::Result<String> {
+    let mut engine = AgentEngine::new(&state.claude_client).with_max_iterations(MAX_ITERATIONS);
+    if let Some(tools) = &state.tools {
+        engine = engine.with_tools(tools);
+    }
+    let hooks = TelegramHooks { state, approver, files };
+    Ok(engine.run(&mut request, &hooks).await?.text)
+}
+
+/// Applies the tool policy and handles `telegram_send_file`
+struct TelegramHooks<'a> {
+    state: &'a BotState,
+    approver: &'a dyn ToolApprover,
+    files: &'a FileSender,
+}
+
+#[async_trait]
+impl AgentHooks for TelegramHooks<'_> {
+    async fn on_tool_call(&self, call: &ToolCall, tools: &ToolManager) -> ToolResult {
+        run_tool(self.state, tools, &call.name, call.input.clone(), self.approver, self.files).await
+    }
+}
+
+/// Run one tool call as the tool policy allows
+async fn run_tool(
+    state: &BotState,
+    tools: &ToolManager,
+    name: &str,
+    input: serde_json::Value,
+    approver: &dyn ToolApprover,
+    files: &FileSender,
+) -> ToolResult {
+    match state.tool_policy.decide_call(name, tools.is_read_only(name)) {
+        PolicyDecision::Allow => {}
+        PolicyDecision::Deny => {
+            return ToolResult::error(format!("Tool {} is not allowed by the tool policy", name));
+        }
+        PolicyDecision::RequireApproval => {
+            let preview = tools.preview(name, &input).await;
+            match approver.approve(name, &input, preview.as_deref()).await {
+                ApprovalOutcome::Approved { .. } => {}
+                ApprovalOutcome::Denied { .. } => {
+                    return ToolResult::error(format!("The user denied running {}", name));
+                }
+                ApprovalOutcome::TimedOut => {
+                    return ToolResult::error(format!("Running {} was not approved in time", name));
+                }
+            }
+        }
+    }
+
+    if name == FileSender::TOOL_NAME {
+        return files.send(&input).await;
+    }
+    tools
+        .execute_approved(name, input)
+        .await
+        .unwrap_or_else(|e| ToolResult::error(e.to_string()))
+}
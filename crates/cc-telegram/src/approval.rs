@@ -0,0 +1,133 @@
+//! Tool approval with inline keyboards
+//!
+//! Calls the [`ToolPolicy`](cc_core::ToolPolicy) marks for approval are posted
+//! in the chat (as their preview when the tool has one) with Approve / Deny
+//! buttons. The press arrives as a callback
+//! query (see [`handle_callback`](crate::commands::handle_callback)), which
+//! resolves the waiting call through [`PendingActions`].
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use teloxide::prelude::*;
+use tracing::{info, warn};
+
+use cc_core::{ApprovalOutcome, ToolApprover};
+
+use crate::conversation::Conversation;
+use crate::keyboard::{approval_keyboard, new_id, PendingActions};
+
+/// Longest tool input shown in the prompt
+const MAX_INPUT_PREVIEW: usize = 1000;
+
+/// Asks in a chat with Approve / Deny buttons
+pub struct KeyboardApprover {
+    bot: Bot,
+    conversation: Conversation,
+    pending: Arc<PendingActions>,
+    timeout: Duration,
+}
+
+impl KeyboardApprover {
+    pub fn new(bot: Bot, conversation: Conversation, pending: Arc<PendingActions>, timeout: Duration) -> Self {
+        Self {
+            bot,
+            conversation,
+            pending,
+            timeout,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolApprover for KeyboardApprover {
+    async fn approve(&self, tool: &str, input: &JsonValue, preview: Option<&str>) -> ApprovalOutcome {
+        let id = new_id();
+        let decision = self.pending.register_approval(&id, tool);
+
+        let prompt = self
+            .conversation
+            .send(&self.bot, approval_prompt(tool, input, preview))
+            .reply_markup(approval_keyboard(&id))
+            .await;
+        let prompt = match prompt {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Failed to ask for approval of {}: {}", tool, e);
+                self.pending.cancel_approval(&id);
+                return ApprovalOutcome::TimedOut;
+            }
+        };
+
+        let outcome = match tokio::time::timeout(self.timeout, decision).await {
+            Ok(Ok(outcome)) => outcome,
+            _ => {
+                self.pending.cancel_approval(&id);
+                // The callback handler edits the prompt on a decision; a
+                // timeout closes it here
+                if let Err(e) = self
+                    .bot
+                    .edit_message_text(self.conversation.chat_id, prompt.id, decided_prompt(tool, &ApprovalOutcome::TimedOut))
+                    .await
+                {
+                    warn!("Failed to close approval prompt: {}", e);
+                }
+                ApprovalOutcome::TimedOut
+            }
+        };
+        info!(
+            "Approval of {} in {}: {:?}",
+            tool,
+            self.conversation.session_key(),
+            outcome
+        );
+        outcome
+    }
+}
+
+/// The prompt asking to approve a call to `tool`, showing `preview` or else the input
+fn approval_prompt(tool: &str, input: &JsonValue, preview: Option<&str>) -> String {
+    let mut input = match preview {
+        Some(preview) => preview.to_string(),
+        None => serde_json::to_string_pretty(input).unwrap_or_default(),
+    };
+    if input.len() > MAX_INPUT_PREVIEW {
+        let mut end = MAX_INPUT_PREVIEW;
+        while !input.is_char_boundary(end) {
+            end -= 1;
+        }
+        input.truncate(end);
+        input.push_str("\n…");
+    }
+    format!("⚠️ ツール {} を実行しようとしています。実行を承認しますか？\n\n{}", tool, input)
+}
+
+/// The prompt after a decision
+pub(crate) fn decided_prompt(tool: &str, outcome: &ApprovalOutcome) -> String {
+    match outcome {
+        ApprovalOutcome::Approved { .. } => format!("✅ ツール {} の実行を承認しました。", tool),
+        ApprovalOutcome::Denied { .. } => format!("🚫 ツール {} の実行を拒否しました。", tool),
+        ApprovalOutcome::TimedOut => format!("⌛ ツール {} の実行は承認されませんでした（タイムアウト）。", tool),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approval_prompt() {
+        let prompt = approval_prompt("bash", &serde_json::json!({ "command": "ls" }), None);
+        assert!(prompt.starts_with("⚠️ ツール bash を実行しようとしています。"));
+        assert!(prompt.contains("\"command\": \"ls\""));
+
+        let long = approval_prompt("write", &serde_json::json!({ "content": "あ".repeat(1000) }), None);
+        assert!(long.len() < MAX_INPUT_PREVIEW + 200);
+        assert!(long.ends_with('…'));
+
+        let preview = approval_prompt("bash", &serde_json::json!({ "command": "ls" }), Some("$ ls"));
+        assert!(preview.ends_with("\n\n$ ls"));
+    }
+}
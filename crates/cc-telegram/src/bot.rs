@@ -2,13 +2,20 @@
 
 use std::sync::Arc;
 
-use teloxide::{dispatching::UpdateFilterExt, prelude::*, utils::command::BotCommands};
+use teloxide::{
+    dispatching::UpdateFilterExt, prelude::*, types::UpdateKind, utils::command::BotCommands,
+};
 use tracing::info;
 
-use cc_core::ClaudeClient;
+use cc_core::{ClaudeClient, PersonaRegistry, ToolManager, ToolPolicy};
+use cc_voice::WhisperClient;
 
-use crate::commands::{handle_ask, handle_clear, handle_help, BotState};
+use crate::commands::{
+    handle_ask, handle_callback, handle_clear, handle_help, handle_message, handle_persona, BotState,
+};
+use crate::conversation::Conversation;
 use crate::error::Result;
+use crate::keyboard::PendingActions;
 use crate::session::InMemorySessionStore;
 
 /// Telegram bot commands
@@ -24,12 +31,14 @@ enum Command {
     Clear,
     #[command(description = "Show help message")]
     Help,
+    #[command(description = "Switch or list personas")]
+    Persona(String),
 }
 
 /// Telegram bot wrapper
 pub struct TelegramBot {
     bot: Bot,
-    state: Arc<BotState>,
+    state: BotState,
 }
 
 impl TelegramBot {
@@ -38,15 +47,40 @@ impl TelegramBot {
         let bot = Bot::new(token);
         let session_store = Arc::new(InMemorySessionStore::new());
 
-        let state = Arc::new(BotState {
+        let state = BotState {
             claude_client,
             session_store,
             admin_user_ids,
-        });
+            personas: Arc::new(PersonaRegistry::new()),
+            tools: None,
+            tool_policy: ToolPolicy::default(),
+            pending: Arc::new(PendingActions::new()),
+            transcriber: None,
+        };
 
         Self { bot, state }
     }
 
+    /// Use a persona registry
+    pub fn with_personas(mut self, personas: Arc<PersonaRegistry>) -> Self {
+        self.state.personas = personas;
+        self
+    }
+
+    /// Let the agent call these tools; calls `policy` marks for approval are
+    /// confirmed with an inline keyboard
+    pub fn with_tools(mut self, tools: Arc<ToolManager>, policy: ToolPolicy) -> Self {
+        self.state.tools = Some(tools);
+        self.state.tool_policy = policy;
+        self
+    }
+
+    /// Transcribe voice and audio messages with Whisper
+    pub fn with_transcriber(mut self, transcriber: Arc<WhisperClient>) -> Self {
+        self.state.transcriber = Some(transcriber);
+        self
+    }
+
     /// Start the bot
     pub async fn start(self) -> Result<()> {
         info!("Starting Telegram bot...");
@@ -58,11 +92,19 @@ impl TelegramBot {
                     Command::Ask(question) => handle_ask(bot, msg, state, question).await,
                     Command::Clear => handle_clear(bot, msg, state).await,
                     Command::Help => handle_help(bot, msg).await,
+                    Command::Persona(name) => handle_persona(bot, msg, state, name).await,
                 }
             });
-
-        Dispatcher::builder(self.bot, command_handler)
-            .dependencies(dptree::deps![self.state])
+        let message_handler = Update::filter_message().endpoint(handle_message);
+        let callback_handler = Update::filter_callback_query().endpoint(handle_callback);
+        let handler = dptree::entry()
+            .branch(command_handler)
+            .branch(message_handler)
+            .branch(callback_handler);
+
+        Dispatcher::builder(self.bot, handler)
+            .dependencies(dptree::deps![Arc::new(self.state)])
+            .distribution_function(distribution)
             .enable_ctrlc_handler()
             .build()
             .dispatch()
@@ -72,6 +114,17 @@ impl TelegramBot {
     }
 }
 
+/// Updates of a conversation (chat or forum topic) are handled in order,
+/// except button presses: a turn waiting for an approval must not block the
+/// press that approves it
+fn distribution(update: &Update) -> Option<String> {
+    match update.kind {
+        UpdateKind::CallbackQuery(_) => None,
+        UpdateKind::Message(ref msg) => Some(Conversation::of(msg).session_key()),
+        _ => update.chat().map(|chat| chat.id.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,5 +140,8 @@ mod tests {
 
         let cmd = Command::Help;
         assert!(matches!(cmd, Command::Help));
+
+        let cmd = Command::parse("/persona coder", "bot").unwrap();
+        assert!(matches!(cmd, Command::Persona(name) if name == "coder"));
     }
 }
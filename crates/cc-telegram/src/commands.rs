@@ -1,12 +1,23 @@
 //! Telegram bot commands
 
 use std::sync::Arc;
+use std::time::Duration;
 use teloxide::prelude::*;
-use tracing::info;
+use teloxide::types::{InlineKeyboardMarkup, Me, ParseMode};
+use tracing::{debug, info, warn};
 
-use cc_core::ClaudeClient;
+use cc_core::{ApprovalOutcome, ClaudeClient, PersonaRegistry, ToolManager, ToolOrigin, ToolPolicy};
+use cc_voice::WhisperClient;
 
+use crate::agent::run_agent;
+use crate::approval::{decided_prompt, KeyboardApprover};
+use crate::conversation::{addressed_text, Conversation};
 use crate::error::Result;
+use crate::format::format_answer;
+use crate::keyboard::{
+    followup_keyboard, new_id, split_suggestions, CallbackAction, PendingActions, SUGGESTION_INSTRUCTION,
+};
+use crate::media::{FileSender, Media};
 use crate::session::InMemorySessionStore;
 
 /// Alias for cc_core::Message to avoid conflict with teloxide::types::Message
@@ -17,8 +28,49 @@ pub struct BotState {
     pub claude_client: Arc<ClaudeClient>,
     pub session_store: Arc<InMemorySessionStore>,
     pub admin_user_ids: Vec<i64>,
+    pub personas: Arc<PersonaRegistry>,
+    /// Tools the agent may call (None = answer without tools)
+    pub tools: Option<Arc<ToolManager>>,
+    /// Which tools need confirmation with the inline keyboard or are refused
+    pub tool_policy: ToolPolicy,
+    /// Tool calls and follow-up suggestions waiting for a button press
+    pub pending: Arc<PendingActions>,
+    /// Transcribes voice messages (None = voice messages are not read)
+    pub transcriber: Option<Arc<WhisperClient>>,
 }
 
+impl BotState {
+    fn is_admin(&self, user_id: i64) -> bool {
+        self.admin_user_ids.is_empty() || self.admin_user_ids.contains(&user_id)
+    }
+
+    /// Whether the sender of `msg` may change the conversation's session
+    /// (clear it, switch its persona): anyone allowed in a private chat, and
+    /// configured admins or the group's admins in groups
+    async fn can_manage(&self, bot: &Bot, msg: &Message) -> Result<bool> {
+        let Some(user) = msg.from.as_ref() else {
+            return Ok(false);
+        };
+        let user_id = user.id.0 as i64;
+        if !self.is_admin(user_id) {
+            return Ok(false);
+        }
+        if msg.chat.is_private() || self.admin_user_ids.contains(&user_id) {
+            return Ok(true);
+        }
+        let member = bot.get_chat_member(msg.chat.id, user.id).await?;
+        Ok(member.is_privileged())
+    }
+}
+
+/// The sender of `msg` (the chat itself for channel posts)
+fn sender_id(msg: &Message) -> i64 {
+    msg.from.as_ref().map_or(msg.chat.id.0, |user| user.id.0 as i64)
+}
+
+const UNAUTHORIZED: &str = "⚠️ 認証エラー: このボットを使用する権限がありません。";
+const NOT_MANAGER: &str = "⚠️ このコマンドはグループの管理者のみ使用できます。";
+
 /// Handle /ask command
 pub async fn handle_ask(
     bot: Bot,
@@ -26,31 +78,98 @@ pub async fn handle_ask(
     state: Arc<BotState>,
     question: String,
 ) -> Result<()> {
-    let user_id = msg.chat.id.0;
-    let chat_id = msg.chat.id;
+    let user_id = sender_id(&msg);
+    let conversation = Conversation::of(&msg);
 
     info!("Processing /ask from user {}: {}", user_id, question);
 
     // Check admin permission
-    if !state.admin_user_ids.is_empty() && !state.admin_user_ids.contains(&user_id) {
-        bot.send_message(chat_id, "⚠️ 認証エラー: このボットを使用する権限がありません。")
-            .await?;
+    if !state.is_admin(user_id) {
+        conversation.send(&bot, UNAUTHORIZED).await?;
         return Ok(());
     }
 
     if question.trim().is_empty() {
-        bot.send_message(chat_id, "質問を入力してください。使い方: /ask <質問>")
+        conversation
+            .send(&bot, "質問を入力してください。使い方: /ask <質問>")
             .await?;
         return Ok(());
     }
 
+    let media = Media::default();
+    run_turn(&bot, &state, conversation, &user_id.to_string(), &question, &media).await
+}
+
+/// Handle a message that is not a command
+///
+/// Private chats are answered as a whole; in groups only messages that
+/// mention the bot or reply to it are. Photos, voice messages and documents
+/// are read along with the caption.
+pub async fn handle_message(bot: Bot, msg: Message, me: Me, state: Arc<BotState>) -> Result<()> {
+    let Some(text) = addressed_text(&msg, &me) else {
+        return Ok(());
+    };
+    let user_id = sender_id(&msg);
+    let conversation = Conversation::of(&msg);
+
+    if !state.is_admin(user_id) {
+        debug!("Ignoring message from unauthorized user {}", user_id);
+        if msg.chat.is_private() {
+            conversation.send(&bot, UNAUTHORIZED).await?;
+        }
+        return Ok(());
+    }
+
+    let media = if Media::has_media(&msg) {
+        conversation.typing(&bot).await?;
+        let media = Media::collect(&bot, &msg, state.transcriber.as_deref()).await;
+        if !media.notes.is_empty() {
+            conversation
+                .send(&bot, format!("⚠️ {}", media.notes.join("\n")))
+                .await?;
+        }
+        media
+    } else {
+        Media::default()
+    };
+
+    let prompt = media.prompt(&text);
+    if prompt.is_empty() {
+        if !Media::has_media(&msg) {
+            conversation
+                .send(&bot, "はい、何かお手伝いしましょうか？")
+                .await?;
+        }
+        return Ok(());
+    }
+
+    info!(
+        "Processing message from user {} in {}: {} (images: {})",
+        user_id,
+        conversation.session_key(),
+        text,
+        media.images.len()
+    );
+    run_turn(&bot, &state, conversation, &user_id.to_string(), &prompt, &media).await
+}
+
+/// Answer `question` (with the images of `media`) in `conversation`, with
+/// suggested follow-ups as buttons
+async fn run_turn(
+    bot: &Bot,
+    state: &BotState,
+    conversation: Conversation,
+    sender_id: &str,
+    question: &str,
+    media: &Media,
+) -> Result<()> {
     // Get or create session
-    let session_key = chat_id.to_string();
+    let session_key = conversation.session_key();
     let session = state.session_store.get_or_create(&session_key).await;
 
     // Build message history
     let mut messages: Vec<CoreMessage> = session.messages.clone();
-    messages.push(CoreMessage::user(&question));
+    messages.push(media.message(question));
 
     // Build request
     let mut request_builder = state
@@ -64,81 +183,270 @@ pub async fn handle_ask(
     for message in messages.into_iter().skip(history_start) {
         request_builder = request_builder.message(message);
     }
+    if let Some(ref tools) = state.tools {
+        for tool in tools.definitions() {
+            request_builder = request_builder.tool(tool);
+        }
+        request_builder = request_builder.tool(FileSender::definition());
+    }
+
+    let mut request = request_builder.build();
 
-    let request = request_builder.build();
+    // Apply the selected persona (session > user > chat > default)
+    if let Some(persona) = state.personas.resolve(
+        session.persona.as_deref(),
+        Some(sender_id),
+        Some(&session_key),
+    ) {
+        persona.apply(&mut request);
+    }
+    let system = request.system.take().unwrap_or_default();
+    request.system = Some(format!("{}\n\n{}", system, SUGGESTION_INSTRUCTION));
 
     // Send "typing" action
-    bot.send_chat_action(chat_id, teloxide::types::ChatAction::Typing)
-        .await?;
+    conversation.typing(bot).await?;
 
-    // Call Claude API
-    let response_text = match state.claude_client.messages(request).await {
-        Ok(response) => {
-            let text = response
-                .content
-                .iter()
-                .filter_map(|c| {
-                    if let cc_core::MessageContent::Text { text } = c {
-                        Some(text.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            // Update session
-            state
-                .session_store
-                .add_message(&session_key, CoreMessage::user(&question))
-                .await;
-            state
-                .session_store
-                .add_message(&session_key, CoreMessage::assistant(&text))
-                .await;
-
-            // Truncate if too long for Telegram (4096 char limit)
-            if text.len() > 4000 {
-                format!("{}\n\n...(truncated)", &text[..4000])
-            } else {
-                text
-            }
+    // Run the agent; tool calls needing approval are asked with a keyboard
+    let approver = KeyboardApprover::new(
+        bot.clone(),
+        conversation,
+        Arc::clone(&state.pending),
+        Duration::from_secs(state.tool_policy.approval_timeout_secs),
+    );
+    let origin = ToolOrigin::new("telegram", session_key.clone()).with_user(sender_id);
+    let files = FileSender::new(bot.clone(), conversation);
+    let text = match origin
+        .scope(run_agent(state, request, &approver, &files))
+        .await
+    {
+        Ok(text) => text,
+        Err(e) => {
+            conversation
+                .send(bot, format!("エラーが発生しました: {}", e))
+                .await?;
+            return Ok(());
         }
-        Err(e) => format!("エラーが発生しました: {}", e),
     };
 
-    // Send response (split if needed)
-    if response_text.len() > 4096 {
-        for chunk in response_text.as_bytes().chunks(4000) {
-            let chunk_text = String::from_utf8_lossy(chunk).to_string();
-            bot.send_message(chat_id, &chunk_text).await?;
-        }
+    // Suggestions become buttons and are not kept in the history
+    let (text, suggestions) = split_suggestions(&text);
+    state
+        .session_store
+        .add_message(&session_key, CoreMessage::user(media.history_text(question)))
+        .await;
+    state
+        .session_store
+        .add_message(&session_key, CoreMessage::assistant(&text))
+        .await;
+
+    let keyboard = if suggestions.is_empty() {
+        None
     } else {
-        bot.send_message(chat_id, &response_text).await?;
+        let id = new_id();
+        let keyboard = followup_keyboard(&id, &suggestions);
+        state.pending.set_followups(&session_key, &id, suggestions);
+        Some(keyboard)
+    };
+    send_answer(bot, conversation, &text, keyboard).await
+}
+
+/// Send `text` as MarkdownV2, split for Telegram's 4096 character limit,
+/// with `keyboard` under the last part
+///
+/// A part Telegram refuses to parse is sent again as plain text.
+async fn send_answer(
+    bot: &Bot,
+    conversation: Conversation,
+    text: &str,
+    keyboard: Option<InlineKeyboardMarkup>,
+) -> Result<()> {
+    let parts = format_answer(text);
+    let last = parts.len() - 1;
+    for (index, part) in parts.into_iter().enumerate() {
+        let keyboard = keyboard.as_ref().filter(|_| index == last);
+        if let Some(markdown) = part.markdown_v2 {
+            let mut request = conversation.send(bot, markdown).parse_mode(ParseMode::MarkdownV2);
+            if let Some(keyboard) = keyboard {
+                request = request.reply_markup(keyboard.clone());
+            }
+            match request.await {
+                Ok(_) => continue,
+                Err(e) => warn!("Failed to send MarkdownV2, sending plain text: {}", e),
+            }
+        }
+        let mut request = conversation.send(bot, part.plain);
+        if let Some(keyboard) = keyboard {
+            request = request.reply_markup(keyboard.clone());
+        }
+        request.await?;
+    }
+    Ok(())
+}
+
+/// Handle an inline keyboard press
+pub async fn handle_callback(bot: Bot, query: CallbackQuery, state: Arc<BotState>) -> Result<()> {
+    let action = query.data.as_deref().and_then(CallbackAction::parse);
+    let (Some(action), Some(message)) = (action, query.message.as_ref()) else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+    let chat_id = message.chat().id;
+    let conversation = message.regular_message().map_or(
+        Conversation {
+            chat_id,
+            thread_id: None,
+        },
+        Conversation::of,
+    );
+    let user_id = query.from.id.0 as i64;
+
+    if !state.is_admin(user_id) {
+        bot.answer_callback_query(query.id)
+            .text("⚠️ このボットを使用する権限がありません。")
+            .show_alert(true)
+            .await?;
+        return Ok(());
     }
 
+    match action {
+        CallbackAction::Approve(ref id) | CallbackAction::Deny(ref id) => {
+            let by = user_id.to_string();
+            let outcome = match action {
+                CallbackAction::Approve(_) => ApprovalOutcome::Approved { by },
+                _ => ApprovalOutcome::Denied { by },
+            };
+            let Some(tool) = state.pending.resolve_approval(id, outcome.clone()) else {
+                bot.answer_callback_query(query.id)
+                    .text("この承認リクエストは期限切れです。")
+                    .await?;
+                return Ok(());
+            };
+            info!("Tool {} in chat {}: {:?}", tool, chat_id, outcome);
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(chat_id, message.id(), decided_prompt(&tool, &outcome))
+                .await?;
+        }
+        CallbackAction::FollowUp(id, index) => {
+            let session_key = conversation.session_key();
+            let Some(question) = state.pending.take_followup(&session_key, &id, index) else {
+                bot.answer_callback_query(query.id)
+                    .text("この候補は期限切れです。")
+                    .await?;
+                return Ok(());
+            };
+            bot.answer_callback_query(query.id).await?;
+            // The pressed suggestion is asked like a typed question
+            bot.edit_message_reply_markup(chat_id, message.id()).await?;
+            conversation.send(&bot, format!("❓ {}", question)).await?;
+            let media = Media::default();
+            run_turn(&bot, &state, conversation, &user_id.to_string(), &question, &media).await?;
+        }
+    }
     Ok(())
 }
 
 /// Handle /clear command
 pub async fn handle_clear(bot: Bot, msg: Message, state: Arc<BotState>) -> Result<()> {
-    let user_id = msg.chat.id.0;
-    let chat_id = msg.chat.id;
+    let conversation = Conversation::of(&msg);
 
-    // Check admin permission
-    if !state.admin_user_ids.is_empty() && !state.admin_user_ids.contains(&user_id) {
-        bot.send_message(chat_id, "⚠️ 認証エラー: このボットを使用する権限がありません。")
-            .await?;
+    // Clearing a group's history is up to its admins
+    if !state.can_manage(&bot, &msg).await? {
+        conversation.send(&bot, NOT_MANAGER).await?;
         return Ok(());
     }
 
-    let session_key = chat_id.to_string();
+    let session_key = conversation.session_key();
     state.session_store.clear(&session_key).await;
 
-    bot.send_message(chat_id, "✅ 会話履歴をクリアしました。")
+    conversation
+        .send(&bot, "✅ 会話履歴をクリアしました。")
         .await?;
 
-    info!("Cleared session for chat {}", chat_id);
+    info!("Cleared session for {}", session_key);
+    Ok(())
+}
+
+/// Handle /persona command
+pub async fn handle_persona(
+    bot: Bot,
+    msg: Message,
+    state: Arc<BotState>,
+    name: String,
+) -> Result<()> {
+    let user_id = sender_id(&msg);
+    let conversation = Conversation::of(&msg);
+
+    // Check admin permission
+    if !state.is_admin(user_id) {
+        conversation.send(&bot, UNAUTHORIZED).await?;
+        return Ok(());
+    }
+
+    if state.personas.is_empty() {
+        conversation
+            .send(&bot, "ペルソナが設定されていません。")
+            .await?;
+        return Ok(());
+    }
+
+    // Anyone may list personas; switching them in a group is up to its admins
+    if !name.trim().is_empty() && !state.can_manage(&bot, &msg).await? {
+        conversation.send(&bot, NOT_MANAGER).await?;
+        return Ok(());
+    }
+
+    let session_key = conversation.session_key();
+    let response = match name.trim() {
+        "" => {
+            let session = state.session_store.get_or_create(&session_key).await;
+            let sender_id = user_id.to_string();
+            let current = state
+                .personas
+                .resolve(
+                    session.persona.as_deref(),
+                    Some(&sender_id),
+                    Some(&session_key),
+                )
+                .map(|p| p.name.clone());
+
+            let mut text = String::from("🎭 ペルソナ一覧:\n");
+            for persona in state.personas.list() {
+                let marker = if current.as_deref() == Some(persona.name.as_str()) {
+                    " ← 現在"
+                } else {
+                    ""
+                };
+                match &persona.description {
+                    Some(description) => {
+                        text.push_str(&format!("- {} - {}{}\n", persona.name, description, marker))
+                    }
+                    None => text.push_str(&format!("- {}{}\n", persona.name, marker)),
+                }
+            }
+            text
+        }
+        "default" | "reset" => {
+            state.session_store.set_persona(&session_key, None).await;
+            info!("Reset persona for {}", session_key);
+            "✅ ペルソナをデフォルトに戻しました。".to_string()
+        }
+        name => match state.personas.get(name) {
+            Some(persona) => {
+                state
+                    .session_store
+                    .set_persona(&session_key, Some(persona.name.clone()))
+                    .await;
+                info!("Switched persona for {} to {}", session_key, persona.name);
+                format!("✅ ペルソナを {} に切り替えました。", persona.name)
+            }
+            None => format!(
+                "ペルソナ {} は見つかりません。/persona で一覧を表示できます。",
+                name
+            ),
+        },
+    };
+
+    conversation.send(&bot, response).await?;
     Ok(())
 }
 
@@ -149,14 +457,25 @@ pub async fn handle_help(bot: Bot, msg: Message) -> Result<()> {
 使い方:
 /ask <質問> - Claude に質問する
 /clear - 会話履歴をクリア
+/persona [名前] - ペルソナを切り替え (省略で一覧、default でリセット)
 /help - このヘルプを表示
 
 例:
 /ask 今日の天気は？
 /ask 前の会話を覚えてる？
 
+プライベートチャットではそのまま話しかけられます。
+グループではボットをメンションするか、ボットの返信に返信してください
+(フォーラムのトピックごとに会話が分かれます)。
+/clear と /persona の切り替えはグループの管理者のみ使用できます。
+
+写真・音声メッセージ・テキストファイルを送ると、その内容について答えます。
+
+回答の下のボタンで続きの質問を送れます。
+危険なツールを実行する前には承認 / 拒否ボタンで確認します。
+
 powered by cc-gateway"#;
 
-    bot.send_message(msg.chat.id, help_text).await?;
+    Conversation::of(&msg).send(&bot, help_text).await?;
     Ok(())
 }
@@ -0,0 +1,122 @@
+//! Where a conversation takes place
+//!
+//! A private chat, a group, or one topic of a forum supergroup. Each has its
+//! own session, and the bot's messages go back to the same topic.
+
+use teloxide::prelude::*;
+use teloxide::types::{Me, ThreadId};
+
+use crate::media::Media;
+
+/// A chat, or a topic in a forum supergroup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conversation {
+    pub chat_id: ChatId,
+    /// Forum topic (None outside forums)
+    pub thread_id: Option<ThreadId>,
+}
+
+impl Conversation {
+    /// The conversation `msg` belongs to
+    pub fn of(msg: &Message) -> Self {
+        Self {
+            chat_id: msg.chat.id,
+            // Reply threads in ordinary groups also carry a thread id; only
+            // forum topics get their own session
+            thread_id: if msg.is_topic_message { msg.thread_id } else { None },
+        }
+    }
+
+    /// Session key: the chat, or chat and topic
+    pub fn session_key(&self) -> String {
+        session_key(self.chat_id, self.thread_id)
+    }
+
+    /// Send `text` to the conversation
+    pub fn send(&self, bot: &Bot, text: impl Into<String>) -> <Bot as Requester>::SendMessage {
+        let request = bot.send_message(self.chat_id, text);
+        match self.thread_id {
+            Some(thread_id) => request.message_thread_id(thread_id),
+            None => request,
+        }
+    }
+
+    /// Show "typing" in the conversation
+    pub async fn typing(&self, bot: &Bot) -> crate::error::Result<()> {
+        let request = bot.send_chat_action(self.chat_id, teloxide::types::ChatAction::Typing);
+        match self.thread_id {
+            Some(thread_id) => request.message_thread_id(thread_id).await?,
+            None => request.await?,
+        };
+        Ok(())
+    }
+}
+
+fn session_key(chat_id: ChatId, thread_id: Option<ThreadId>) -> String {
+    match thread_id {
+        Some(thread_id) => format!("{}:{}", chat_id, thread_id.0 .0),
+        None => chat_id.to_string(),
+    }
+}
+
+/// The text (or media caption) of `msg` addressed to the bot, without the
+/// mention
+///
+/// Private chats are always addressed to the bot. In groups the bot only
+/// answers when mentioned by @username or when a message replies to it.
+pub fn addressed_text(msg: &Message, me: &Me) -> Option<String> {
+    let text = match msg.text().or(msg.caption()) {
+        Some(text) => text,
+        None if Media::has_media(msg) => "",
+        None => return None,
+    };
+    // Unknown commands, or commands for other bots
+    if text.starts_with('/') {
+        return None;
+    }
+    if msg.chat.is_private() {
+        return Some(text.trim().to_string());
+    }
+
+    let is_reply_to_bot = msg
+        .reply_to_message()
+        .and_then(|m| m.from.as_ref())
+        .is_some_and(|user| user.id == me.id);
+    let mention = format!("@{}", me.username());
+    let (is_mention, text) = strip_mention(text, &mention);
+    (is_mention || is_reply_to_bot).then_some(text)
+}
+
+/// Remove `mention` (ASCII case-insensitive, as usernames are) from `text`
+fn strip_mention(text: &str, mention: &str) -> (bool, String) {
+    let start = text.char_indices().map(|(i, _)| i).find(|&i| {
+        text.get(i..i + mention.len())
+            .is_some_and(|s| s.eq_ignore_ascii_case(mention))
+    });
+    match start {
+        Some(start) => {
+            let stripped = format!("{}{}", &text[..start], &text[start + mention.len()..]);
+            (true, stripped.trim().to_string())
+        }
+        None => (false, text.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use teloxide::types::MessageId;
+
+    #[test]
+    fn test_session_key() {
+        assert_eq!(session_key(ChatId(42), None), "42");
+        assert_eq!(session_key(ChatId(-100123), Some(ThreadId(MessageId(7)))), "-100123:7");
+    }
+
+    #[test]
+    fn test_strip_mention() {
+        assert_eq!(strip_mention("@CcBot hello", "@ccbot"), (true, "hello".to_string()));
+        assert_eq!(strip_mention("what is this @ccbot?", "@ccbot"), (true, "what is this ?".to_string()));
+        assert_eq!(strip_mention("hello everyone", "@ccbot"), (false, "hello everyone".to_string()));
+    }
+}
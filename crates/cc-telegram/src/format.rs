@@ -0,0 +1,389 @@
+//! Telegram message formatting
+//!
+//! The model answers in Markdown, which Telegram rejects unless it is
+//! converted to MarkdownV2 with every reserved character escaped. Long
+//! answers are split into messages under Telegram's 4096 character limit at
+//! paragraph or line boundaries, closing and reopening code blocks that
+//! span two messages.
+
+/// Telegram's message length limit (in UTF-16 code units)
+pub const MAX_MESSAGE_LEN: usize = 4096;
+
+/// Length of the Markdown parts before conversion, leaving room for escapes
+const MAX_SOURCE_LEN: usize = 3000;
+
+/// Characters MarkdownV2 reserves outside code
+const RESERVED: &[char] = &[
+    '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!', '\\',
+];
+
+/// Length as Telegram counts it
+pub fn telegram_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Escape `text` for MarkdownV2 outside code
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if RESERVED.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape `text` inside `code` and ```pre``` entities
+fn escape_code(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('`', "\\`")
+}
+
+/// Escape a link target
+fn escape_url(url: &str) -> String {
+    url.replace('\\', "\\\\").replace(')', "\\)")
+}
+
+/// Convert the model's Markdown to MarkdownV2
+///
+/// Supported: code blocks, inline code, bold, italic, strikethrough, links,
+/// headings (shown bold), bullets and quotes. Everything else is escaped and
+/// shown as written.
+pub fn to_markdown_v2(markdown: &str) -> String {
+    let mut out = Vec::new();
+    let mut in_code = false;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(language) = trimmed.strip_prefix("```") {
+            if in_code {
+                out.push("```".to_string());
+            } else {
+                out.push(format!("```{}", escape_code(language.trim())));
+            }
+            in_code = !in_code;
+        } else if in_code {
+            out.push(escape_code(line));
+        } else {
+            out.push(convert_line(line));
+        }
+    }
+    if in_code {
+        out.push("```".to_string());
+    }
+    out.join("\n")
+}
+
+fn convert_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        let heading = trimmed[hashes..].trim().replace("**", "");
+        return format!("*{}*", inline(&heading));
+    }
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = trimmed.strip_prefix(bullet) {
+            return format!("{}• {}", indent, inline(item));
+        }
+    }
+    if let Some(quote) = trimmed.strip_prefix('>') {
+        return format!(">{}", inline(quote.strip_prefix(' ').unwrap_or(quote)));
+    }
+    format!("{}{}", indent, inline(trimmed))
+}
+
+/// Convert inline Markdown
+fn inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let rest = &chars[i..];
+
+        // `code`
+        if c == '`' {
+            if let Some(end) = find(&chars, i + 1, &['`']) {
+                let code: String = chars[i + 1..end].iter().collect();
+                out.push('`');
+                out.push_str(&escape_code(&code));
+                out.push('`');
+                i = end + 1;
+                continue;
+            }
+        }
+
+        // **bold**, __bold__, ~~strike~~
+        let styled = [("**", '*'), ("__", '*'), ("~~", '~')]
+            .into_iter()
+            .find_map(|(marker, entity)| {
+                let marker: Vec<char> = marker.chars().collect();
+                if !rest.starts_with(&marker) {
+                    return None;
+                }
+                let end = find(&chars, i + 2, &marker).filter(|&end| end > i + 2)?;
+                Some((end, entity))
+            });
+        if let Some((end, entity)) = styled {
+            let inner: String = chars[i + 2..end].iter().collect();
+            out.push(entity);
+            out.push_str(&inline(&inner));
+            out.push(entity);
+            i = end + 2;
+            continue;
+        }
+
+        // *italic* / _italic_ (not inside snake_case words or before a space)
+        if (c == '*' || c == '_')
+            && chars.get(i + 1).is_some_and(|n| !n.is_whitespace() && *n != c)
+            && (c == '*' || i == 0 || !chars[i - 1].is_alphanumeric())
+        {
+            if let Some(end) = find(&chars, i + 1, &[c]) {
+                let closes_word = c == '*' || chars.get(end + 1).is_none_or(|n| !n.is_alphanumeric());
+                if end > i + 1 && !chars[end - 1].is_whitespace() && closes_word {
+                    let inner: String = chars[i + 1..end].iter().collect();
+                    out.push('_');
+                    out.push_str(&inline(&inner));
+                    out.push('_');
+                    i = end + 1;
+                    continue;
+                }
+            }
+        }
+
+        // [text](url)
+        if c == '[' {
+            if let Some(close) = find(&chars, i + 1, &[']']) {
+                if chars.get(close + 1) == Some(&'(') {
+                    if let Some(end) = find(&chars, close + 2, &[')']) {
+                        let label: String = chars[i + 1..close].iter().collect();
+                        let url: String = chars[close + 2..end].iter().collect();
+                        out.push('[');
+                        out.push_str(&inline(&label));
+                        out.push_str("](");
+                        out.push_str(&escape_url(&url));
+                        out.push(')');
+                        i = end + 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        if RESERVED.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+/// Index of the next `marker` at or after `from`
+fn find(chars: &[char], from: usize, marker: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&j| chars[j..].starts_with(marker))
+}
+
+/// One message of an answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedPart {
+    /// MarkdownV2 text (None when the part goes out as plain text)
+    pub markdown_v2: Option<String>,
+    /// The part as written, sent when Telegram rejects the formatting
+    pub plain: String,
+}
+
+/// Convert and split an answer into messages
+pub fn format_answer(markdown: &str) -> Vec<FormattedPart> {
+    let mut parts = Vec::new();
+    for part in split(markdown, MAX_SOURCE_LEN) {
+        let converted = to_markdown_v2(&part);
+        if telegram_len(&converted) <= MAX_MESSAGE_LEN {
+            parts.push(FormattedPart {
+                markdown_v2: Some(converted),
+                plain: part,
+            });
+        } else {
+            // Escaping made it too long; send it as written
+            parts.extend(split(&part, MAX_MESSAGE_LEN).into_iter().map(|plain| FormattedPart {
+                markdown_v2: None,
+                plain,
+            }));
+        }
+    }
+    parts
+}
+
+/// Split `text` into parts of at most `max` UTF-16 units, preferring
+/// paragraph and then line boundaries and keeping code blocks balanced
+pub fn split(text: &str, max: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut current_len = 0;
+    // The opening fence of the code block we're in
+    let mut fence: Option<String> = None;
+    // Fence open at the start of `current`
+    let mut opened_with: Option<String> = None;
+
+    for line in text.lines() {
+        let is_fence = line.trim_start().starts_with("```");
+        // Room for closing an open code block at the end of the part
+        let reserve = if fence.is_some() { 4 } else { 0 };
+        for piece in split_line(line, max.saturating_sub(reserve + 8).max(1)) {
+            let len = telegram_len(&piece) + 1;
+            if !current.is_empty() && current_len + len + reserve > max {
+                // Break at the last blank line if it keeps most of the part
+                let cut = current
+                    .iter()
+                    .rposition(|l| l.trim().is_empty())
+                    .filter(|&p| p > current.len() / 2 && fence.is_none() && opened_with.is_none())
+                    .filter(|&p| {
+                        let carried: usize = current[p..].iter().map(|l| telegram_len(l) + 1).sum();
+                        carried + len <= max
+                    })
+                    .unwrap_or(current.len());
+                let carried: Vec<String> = current.split_off(cut);
+                let mut part = current.join("\n");
+                if let Some(ref fence) = opened_with {
+                    part = format!("{}\n{}", fence, part);
+                }
+                if fence.is_some() {
+                    part.push_str("\n```");
+                }
+                if !part.trim().is_empty() {
+                    parts.push(part.trim_end().to_string());
+                }
+                opened_with = fence.clone();
+                current = carried.into_iter().skip_while(|l| l.trim().is_empty()).collect();
+                current_len = current.iter().map(|l| telegram_len(l) + 1).sum::<usize>()
+                    + opened_with.as_deref().map_or(0, |f| telegram_len(f) + 1);
+            }
+            current.push(piece);
+            current_len += len;
+        }
+        if is_fence {
+            fence = match fence {
+                Some(_) => None,
+                None => Some(line.trim().to_string()),
+            };
+        }
+    }
+
+    let mut part = current.join("\n");
+    if let Some(ref fence) = opened_with {
+        part = format!("{}\n{}", fence, part);
+    }
+    if !part.trim().is_empty() || parts.is_empty() {
+        parts.push(part.trim_end().to_string());
+    }
+    parts
+}
+
+/// Split one line into pieces of at most `max` UTF-16 units, at spaces when
+/// possible
+fn split_line(line: &str, max: usize) -> Vec<String> {
+    if telegram_len(line) <= max {
+        return vec![line.to_string()];
+    }
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for word in line.split_inclusive(' ') {
+        let word_len = telegram_len(word);
+        if current_len + word_len > max && !current.is_empty() {
+            pieces.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if word_len > max {
+            for c in word.chars() {
+                if current_len + c.len_utf16() > max {
+                    pieces.push(std::mem::take(&mut current));
+                    current_len = 0;
+                }
+                current.push(c);
+                current_len += c.len_utf16();
+            }
+        } else {
+            current.push_str(word);
+            current_len += word_len;
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("1.5 + (2) = 3.5!"), "1\\.5 \\+ \\(2\\) \\= 3\\.5\\!");
+    }
+
+    #[test]
+    fn test_to_markdown_v2() {
+        assert_eq!(to_markdown_v2("**Note:** see `a_b.rs`."), "*Note:* see `a_b.rs`\\.");
+        assert_eq!(to_markdown_v2("use *this* or _that_, not snake_case_name"), "use _this_ or _that_, not snake\\_case\\_name");
+        assert_eq!(to_markdown_v2("~~old~~ [the docs](https://e.x/a-b)"), "~old~ [the docs](https://e.x/a-b)");
+        assert_eq!(to_markdown_v2("## Steps\n- one\n- two.\n> quote"), "*Steps*\n• one\n• two\\.\n>quote");
+        assert_eq!(
+            to_markdown_v2("```rust\nlet a = `x` * 2;\n```\n2 * 3"),
+            "```rust\nlet a = \\`x\\` * 2;\n```\n2 \\* 3"
+        );
+        // An unclosed code block is closed
+        assert_eq!(to_markdown_v2("```\ncode"), "```\ncode\n```");
+    }
+
+    #[test]
+    fn test_format_answer() {
+        let parts = format_answer("Hello **world**.");
+        assert_eq!(
+            parts,
+            vec![FormattedPart {
+                markdown_v2: Some("Hello *world*\\.".to_string()),
+                plain: "Hello **world**.".to_string(),
+            }]
+        );
+
+        // Text made only of reserved characters doubles when escaped
+        let dots = ".".repeat(2900);
+        let parts = format_answer(&dots);
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].markdown_v2.is_none());
+    }
+
+    #[test]
+    fn test_split_paragraphs() {
+        let paragraph = "word ".repeat(100);
+        let text = format!("{p}\n\n{p}\n\n{p}", p = paragraph.trim());
+        let parts = split(&text, 1100);
+        assert_eq!(parts.len(), 2);
+        assert!(parts.iter().all(|p| telegram_len(p) <= 1100));
+        assert!(parts[1].starts_with("word"));
+    }
+
+    #[test]
+    fn test_split_keeps_code_blocks_balanced() {
+        let code: Vec<String> = (0..200).map(|i| format!("let x{} = {};", i, i)).collect();
+        let text = format!("Here:\n```rust\n{}\n```\nDone.", code.join("\n"));
+        let parts = split(&text, 1000);
+        assert!(parts.len() > 1);
+        for part in &parts {
+            assert!(telegram_len(part) <= 1000, "{}", telegram_len(part));
+            assert_eq!(part.matches("```").count() % 2, 0, "{}", part);
+        }
+        assert!(parts[1].starts_with("```rust\n"));
+    }
+
+    #[test]
+    fn test_split_long_line() {
+        let text = "あ".repeat(5000);
+        let parts = split(&text, 3000);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts.concat(), text);
+    }
+}
@@ -0,0 +1,236 @@
+//! Inline keyboards
+//!
+//! Two keyboards are attached to bot messages: Approve / Deny buttons for
+//! tool calls that need confirmation, and quick-reply buttons with follow-up
+//! questions the model suggested. Presses arrive as callback queries whose
+//! data is parsed into a [`CallbackAction`]; the state they refer to lives in
+//! [`PendingActions`].
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use tokio::sync::oneshot;
+
+use cc_core::ApprovalOutcome;
+
+/// Follow-up suggestions kept per answer
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Prefix of suggestion lines at the end of an answer
+const SUGGESTION_PREFIX: &str = ">> ";
+
+/// Longest suggestion shown on a button
+const MAX_SUGGESTION_CHARS: usize = 60;
+
+/// Instruction added to the system prompt so the model suggests follow-ups
+pub const SUGGESTION_INSTRUCTION: &str = "After your answer, you may suggest up to 3 short follow-up questions the user might ask next, each on its own line starting with \">> \".";
+
+/// What a button press asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallbackAction {
+    /// Run the tool call waiting under this id
+    Approve(String),
+    /// Refuse the tool call waiting under this id
+    Deny(String),
+    /// Ask the suggestion with this index of the answer with this id
+    FollowUp(String, usize),
+}
+
+impl CallbackAction {
+    /// Parse callback data
+    pub fn parse(data: &str) -> Option<Self> {
+        let (kind, rest) = data.split_once(':')?;
+        match kind {
+            "approve" => Some(Self::Approve(rest.to_string())),
+            "deny" => Some(Self::Deny(rest.to_string())),
+            "followup" => {
+                let (id, index) = rest.rsplit_once(':')?;
+                Some(Self::FollowUp(id.to_string(), index.parse().ok()?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Callback data (Telegram allows up to 64 bytes)
+    pub fn data(&self) -> String {
+        match self {
+            Self::Approve(id) => format!("approve:{}", id),
+            Self::Deny(id) => format!("deny:{}", id),
+            Self::FollowUp(id, index) => format!("followup:{}:{}", id, index),
+        }
+    }
+}
+
+/// A short id for callback data
+pub fn new_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..12].to_string()
+}
+
+/// Approve / Deny buttons for the tool call waiting under `id`
+pub fn approval_keyboard(id: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("✅ 承認", CallbackAction::Approve(id.to_string()).data()),
+        InlineKeyboardButton::callback("🚫 拒否", CallbackAction::Deny(id.to_string()).data()),
+    ]])
+}
+
+/// One button per follow-up suggestion of the answer with `id`
+pub fn followup_keyboard(id: &str, suggestions: &[String]) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(suggestions.iter().enumerate().map(|(index, suggestion)| {
+        let label = if suggestion.chars().count() > MAX_SUGGESTION_CHARS {
+            let mut label: String = suggestion.chars().take(MAX_SUGGESTION_CHARS - 1).collect();
+            label.push('…');
+            label
+        } else {
+            suggestion.clone()
+        };
+        vec![InlineKeyboardButton::callback(
+            label,
+            CallbackAction::FollowUp(id.to_string(), index).data(),
+        )]
+    }))
+}
+
+/// Split the trailing suggestion lines off an answer
+pub fn split_suggestions(text: &str) -> (String, Vec<String>) {
+    let mut lines: Vec<&str> = text.trim_end().lines().collect();
+    let mut suggestions = Vec::new();
+    while let Some(line) = lines.last() {
+        let line = line.trim();
+        if let Some(suggestion) = line.strip_prefix(SUGGESTION_PREFIX.trim_end()) {
+            let suggestion = suggestion.trim();
+            if !suggestion.is_empty() {
+                suggestions.push(suggestion.to_string());
+            }
+            lines.pop();
+        } else if line.is_empty() && !suggestions.is_empty() {
+            lines.pop();
+        } else {
+            break;
+        }
+    }
+    suggestions.reverse();
+    suggestions.truncate(MAX_SUGGESTIONS);
+    (lines.join("\n").trim_end().to_string(), suggestions)
+}
+
+/// A tool call waiting for a press
+#[derive(Debug)]
+struct PendingApproval {
+    tool: String,
+    decision: oneshot::Sender<ApprovalOutcome>,
+}
+
+/// Tool calls waiting for a press, and the latest suggestions per chat
+#[derive(Debug, Default)]
+pub struct PendingActions {
+    approvals: Mutex<HashMap<String, PendingApproval>>,
+    followups: Mutex<HashMap<String, (String, Vec<String>)>>,
+}
+
+impl PendingActions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for a decision on the call to `tool` registered under `id`
+    pub fn register_approval(&self, id: &str, tool: &str) -> oneshot::Receiver<ApprovalOutcome> {
+        let (decision, rx) = oneshot::channel();
+        self.lock_approvals().insert(
+            id.to_string(),
+            PendingApproval {
+                tool: tool.to_string(),
+                decision,
+            },
+        );
+        rx
+    }
+
+    /// Deliver a decision, returning the tool (None if nothing waits under
+    /// `id` anymore)
+    pub fn resolve_approval(&self, id: &str, outcome: ApprovalOutcome) -> Option<String> {
+        let pending = self.lock_approvals().remove(id)?;
+        pending.decision.send(outcome).ok()?;
+        Some(pending.tool)
+    }
+
+    /// Stop waiting under `id`
+    pub fn cancel_approval(&self, id: &str) {
+        self.lock_approvals().remove(id);
+    }
+
+    /// Remember the suggestions of the latest answer in a session (older
+    /// keyboards of the session stop working)
+    pub fn set_followups(&self, session_key: &str, id: &str, suggestions: Vec<String>) {
+        self.lock_followups()
+            .insert(session_key.to_string(), (id.to_string(), suggestions));
+    }
+
+    /// The suggestion pressed, if it belongs to the session's latest answer
+    pub fn take_followup(&self, session_key: &str, id: &str, index: usize) -> Option<String> {
+        let mut followups = self.lock_followups();
+        let (latest, suggestions) = followups.get(session_key)?;
+        if latest != id {
+            return None;
+        }
+        let suggestion = suggestions.get(index).cloned()?;
+        followups.remove(session_key);
+        Some(suggestion)
+    }
+
+    fn lock_approvals(&self) -> std::sync::MutexGuard<'_, HashMap<String, PendingApproval>> {
+        self.approvals.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_followups(&self) -> std::sync::MutexGuard<'_, HashMap<String, (String, Vec<String>)>> {
+        self.followups.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_data_round_trip() {
+        for action in [
+            CallbackAction::Approve("abc".to_string()),
+            CallbackAction::Deny("abc".to_string()),
+            CallbackAction::FollowUp("abc".to_string(), 2),
+        ] {
+            assert!(action.data().len() <= 64);
+            assert_eq!(CallbackAction::parse(&action.data()), Some(action));
+        }
+        assert_eq!(CallbackAction::parse("followup:abc:x"), None);
+        assert_eq!(CallbackAction::parse("unknown:abc"), None);
+    }
+
+    #[test]
+    fn test_split_suggestions() {
+        let (text, suggestions) =
+            split_suggestions("Rust is a language.\n\n>> What is ownership?\n>> Show an example\n");
+        assert_eq!(text, "Rust is a language.");
+        assert_eq!(suggestions, vec!["What is ownership?", "Show an example"]);
+
+        let (text, suggestions) = split_suggestions("No suggestions here.\n> quoted");
+        assert_eq!(text, "No suggestions here.\n> quoted");
+        assert!(suggestions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pending_actions() {
+        let pending = PendingActions::new();
+        let rx = pending.register_approval("a1", "bash");
+        let tool = pending.resolve_approval("a1", ApprovalOutcome::Approved { by: "1".to_string() });
+        assert_eq!(tool.as_deref(), Some("bash"));
+        assert_eq!(rx.await.unwrap(), ApprovalOutcome::Approved { by: "1".to_string() });
+        assert_eq!(pending.resolve_approval("a1", ApprovalOutcome::TimedOut), None);
+
+        pending.set_followups("7", "old", vec!["q".to_string()]);
+        pending.set_followups("7", "new", vec!["q1".to_string(), "q2".to_string()]);
+        assert_eq!(pending.take_followup("7", "old", 0), None);
+        assert_eq!(pending.take_followup("7", "new", 1).as_deref(), Some("q2"));
+        assert_eq!(pending.take_followup("7", "new", 0), None);
+    }
+}
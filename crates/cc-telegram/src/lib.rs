@@ -3,9 +3,15 @@
 //! This crate provides Telegram bot integration for cc-gateway,
 //! allowing users to interact with Claude through Telegram.
 
+pub mod agent;
+pub mod approval;
 pub mod bot;
 pub mod commands;
+pub mod conversation;
 pub mod error;
+pub mod format;
+pub mod keyboard;
+pub mod media;
 pub mod session;
 
 pub use bot::TelegramBot;
@@ -0,0 +1,341 @@
+//! Photos, voice messages and documents
+//!
+//! Media is downloaded with getFile: photos and image documents go to the
+//! model as images, voice and audio messages are transcribed with Whisper
+//! (cc-voice), and text documents are read with the document extractor.
+//! In the other direction, the agent can send files it generated back to
+//! the conversation with the `telegram_send_file` tool.
+
+use std::path::Path;
+
+use serde_json::json;
+use teloxide::net::Download;
+use teloxide::prelude::*;
+use teloxide::types::{FileMeta, InputFile};
+use tracing::{debug, info, warn};
+
+use cc_core::{ExtractedDocument, ImageSource, ToolDefinition, ToolResult};
+use cc_voice::WhisperClient;
+
+use crate::conversation::Conversation;
+use crate::error::{Result, TelegramError};
+
+/// Largest file the Bot API lets bots download
+pub const MAX_DOWNLOAD_BYTES: u32 = 20 * 1024 * 1024;
+
+/// Largest image the model accepts
+pub const MAX_IMAGE_BYTES: u32 = 5 * 1024 * 1024;
+
+/// Largest file bots may upload
+pub const MAX_UPLOAD_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Question used when only images were sent
+const DEFAULT_IMAGE_QUESTION: &str = "この画像について説明してください。";
+
+/// Media of one message, ready for the model
+#[derive(Debug, Default)]
+pub struct Media {
+    pub images: Vec<ImageSource>,
+    /// Transcript of a voice or audio message
+    pub transcript: Option<String>,
+    pub documents: Vec<ExtractedDocument>,
+    /// Problems to tell the user about (skipped or unreadable media)
+    pub notes: Vec<String>,
+}
+
+impl Media {
+    /// Whether `msg` carries media this module handles
+    pub fn has_media(msg: &Message) -> bool {
+        msg.photo().is_some() || msg.voice().is_some() || msg.audio().is_some() || msg.document().is_some()
+    }
+
+    /// Download and convert the media of `msg`
+    pub async fn collect(bot: &Bot, msg: &Message, transcriber: Option<&WhisperClient>) -> Self {
+        let mut media = Self::default();
+
+        // Telegram sends several sizes of a photo; the last is the largest
+        if let Some(photo) = msg.photo().and_then(|sizes| sizes.last()) {
+            media.add_image(bot, &photo.file, ImageSource::MEDIA_TYPE_JPEG, "photo").await;
+        }
+
+        if let Some(document) = msg.document() {
+            let name = document.file_name.clone().unwrap_or_else(|| "document".to_string());
+            let mime = document.mime_type.as_ref().map(|m| m.essence_str().to_string());
+            match image_media_type(mime.as_deref()) {
+                Some(media_type) => media.add_image(bot, &document.file, media_type, &name).await,
+                None => media.add_document(bot, &document.file, &name, mime.as_deref()).await,
+            }
+        }
+
+        let audio = msg
+            .voice()
+            .map(|voice| (&voice.file, "voice.ogg".to_string()))
+            .or_else(|| {
+                msg.audio().map(|audio| {
+                    let name = audio.file_name.clone().unwrap_or_else(|| "audio.mp3".to_string());
+                    (&audio.file, name)
+                })
+            });
+        if let Some((file, name)) = audio {
+            media.add_transcript(bot, file, &name, transcriber).await;
+        }
+
+        media
+    }
+
+    async fn add_image(&mut self, bot: &Bot, file: &FileMeta, media_type: &str, name: &str) {
+        if file.size > MAX_IMAGE_BYTES {
+            self.notes
+                .push(format!("画像 {} は大きすぎるため送信できませんでした（5MB まで）。", name));
+            return;
+        }
+        match download(bot, file).await {
+            Ok(bytes) => self.images.push(ImageSource::from_bytes(media_type, &bytes)),
+            Err(e) => {
+                warn!("Failed to download image {}: {}", name, e);
+                self.notes.push(format!("画像 {} をダウンロードできませんでした。", name));
+            }
+        }
+    }
+
+    async fn add_document(&mut self, bot: &Bot, file: &FileMeta, name: &str, mime: Option<&str>) {
+        if !cc_core::document::is_text_document(name, mime) {
+            self.notes.push(format!(
+                "ファイル {} の形式には対応していません（テキスト形式のファイルと画像に対応しています）。",
+                name
+            ));
+            return;
+        }
+        let bytes = match download(bot, file).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Failed to download document {}: {}", name, e);
+                self.notes.push(format!("ファイル {} をダウンロードできませんでした。", name));
+                return;
+            }
+        };
+        match cc_core::extract_text(name, mime, &bytes) {
+            Some(document) => {
+                if document.truncated {
+                    self.notes
+                        .push(format!("ファイル {} は長いため、先頭の部分だけを読み込みました。", name));
+                }
+                self.documents.push(document);
+            }
+            None => self
+                .notes
+                .push(format!("ファイル {} を読み込めませんでした（UTF-8 のテキストのみ対応）。", name)),
+        }
+    }
+
+    async fn add_transcript(
+        &mut self,
+        bot: &Bot,
+        file: &FileMeta,
+        name: &str,
+        transcriber: Option<&WhisperClient>,
+    ) {
+        let Some(transcriber) = transcriber else {
+            self.notes
+                .push("音声の文字起こしが設定されていないため、音声メッセージは読み取れません。".to_string());
+            return;
+        };
+        let result = match download(bot, file).await {
+            Ok(bytes) => transcriber.transcribe_text(&bytes, name).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match result {
+            Ok(text) if !text.trim().is_empty() => {
+                debug!("Transcribed {} ({} chars)", name, text.len());
+                self.transcript = Some(text.trim().to_string());
+            }
+            Ok(_) => self.notes.push("音声から文字を読み取れませんでした。".to_string()),
+            Err(e) => {
+                warn!("Failed to transcribe {}: {}", name, e);
+                self.notes.push("音声の文字起こしに失敗しました。".to_string());
+            }
+        }
+    }
+
+    /// The user's turn: `text` (the caption) with the transcript and
+    /// documents. A voice message without caption is the question itself.
+    pub fn prompt(&self, text: &str) -> String {
+        let mut parts = Vec::new();
+        let text = text.trim();
+        if !text.is_empty() {
+            parts.push(text.to_string());
+        }
+        if let Some(ref transcript) = self.transcript {
+            if text.is_empty() {
+                parts.push(transcript.clone());
+            } else {
+                parts.push(format!("[Voice message]\n{}", transcript));
+            }
+        }
+        for document in &self.documents {
+            parts.push(document.to_prompt());
+        }
+        if parts.is_empty() && !self.images.is_empty() {
+            parts.push(DEFAULT_IMAGE_QUESTION.to_string());
+        }
+        parts.join("\n\n")
+    }
+
+    /// The user message for `prompt` with the images
+    pub fn message(&self, prompt: &str) -> cc_core::Message {
+        if self.images.is_empty() {
+            cc_core::Message::user(prompt)
+        } else {
+            cc_core::Message::user_with_images(prompt, self.images.clone())
+        }
+    }
+
+    /// `prompt` as kept in the history (images are not kept, only noted)
+    pub fn history_text(&self, prompt: &str) -> String {
+        if self.images.is_empty() {
+            prompt.to_string()
+        } else {
+            format!("{}\n[画像 {} 枚を添付]", prompt, self.images.len())
+        }
+    }
+}
+
+/// The media type of an image document the model accepts
+fn image_media_type(mime: Option<&str>) -> Option<&'static str> {
+    [
+        ImageSource::MEDIA_TYPE_PNG,
+        ImageSource::MEDIA_TYPE_JPEG,
+        ImageSource::MEDIA_TYPE_GIF,
+        ImageSource::MEDIA_TYPE_WEBP,
+    ]
+    .into_iter()
+    .find(|t| mime.is_some_and(|m| m.eq_ignore_ascii_case(t)))
+}
+
+/// Download a file with getFile
+async fn download(bot: &Bot, file: &FileMeta) -> Result<Vec<u8>> {
+    if file.size > MAX_DOWNLOAD_BYTES {
+        return Err(TelegramError::Download(format!(
+            "file is larger than {} bytes",
+            MAX_DOWNLOAD_BYTES
+        )));
+    }
+    let file = bot.get_file(file.id.clone()).await?;
+    let mut bytes = Vec::with_capacity(file.meta.size as usize);
+    bot.download_file(&file.path, &mut bytes)
+        .await
+        .map_err(|e| TelegramError::Download(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Sends files the agent generated to the conversation
+pub struct FileSender {
+    bot: Bot,
+    conversation: Conversation,
+}
+
+impl FileSender {
+    /// Name of the tool the agent calls
+    pub const TOOL_NAME: &'static str = "telegram_send_file";
+
+    pub fn new(bot: Bot, conversation: Conversation) -> Self {
+        Self { bot, conversation }
+    }
+
+    /// The tool offered to the model
+    pub fn definition() -> ToolDefinition {
+        ToolDefinition::new(
+            Self::TOOL_NAME,
+            "Send a local file (e.g. one a tool generated: a screenshot, PDF or report) to the user in this Telegram chat",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Path of the file to send"
+                    },
+                    "caption": {
+                        "type": "string",
+                        "description": "Short caption shown with the file (optional)"
+                    }
+                },
+                "required": ["path"]
+            }),
+        )
+    }
+
+    /// Send the file named in the tool `input`
+    pub async fn send(&self, input: &serde_json::Value) -> ToolResult {
+        let Some(path) = input["path"].as_str() else {
+            return ToolResult::error("Missing path parameter");
+        };
+        let path = Path::new(path);
+        let size = match tokio::fs::metadata(path).await {
+            Ok(meta) if meta.is_file() => meta.len(),
+            Ok(_) => return ToolResult::error(format!("{} is not a file", path.display())),
+            Err(e) => return ToolResult::error(format!("Cannot read {}: {}", path.display(), e)),
+        };
+        if size > MAX_UPLOAD_BYTES {
+            return ToolResult::error(format!(
+                "{} is too large to send ({} bytes, limit {})",
+                path.display(),
+                size,
+                MAX_UPLOAD_BYTES
+            ));
+        }
+
+        let mut request = self
+            .bot
+            .send_document(self.conversation.chat_id, InputFile::file(path));
+        if let Some(thread_id) = self.conversation.thread_id {
+            request = request.message_thread_id(thread_id);
+        }
+        if let Some(caption) = input["caption"].as_str().filter(|c| !c.is_empty()) {
+            request = request.caption(caption);
+        }
+        match request.await {
+            Ok(_) => {
+                info!("Sent {} to {}", path.display(), self.conversation.session_key());
+                ToolResult::success(format!("Sent {} to the user", path.display()))
+            }
+            Err(e) => ToolResult::error(format!("Failed to send {}: {}", path.display(), e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_media_type() {
+        assert_eq!(image_media_type(Some("image/png")), Some("image/png"));
+        assert_eq!(image_media_type(Some("image/svg+xml")), None);
+        assert_eq!(image_media_type(None), None);
+    }
+
+    #[test]
+    fn test_prompt() {
+        let voice = Media {
+            transcript: Some("明日の予定は？".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(voice.prompt(""), "明日の予定は？");
+        assert_eq!(voice.prompt("要約して"), "要約して\n\n[Voice message]\n明日の予定は？");
+
+        let photo = Media {
+            images: vec![ImageSource::jpeg(b"jpeg")],
+            ..Default::default()
+        };
+        assert_eq!(photo.prompt(" "), DEFAULT_IMAGE_QUESTION);
+        assert_eq!(photo.message("q").content.len(), 2);
+        assert_eq!(photo.history_text("q"), "q\n[画像 1 枚を添付]");
+
+        let document = Media {
+            documents: vec![cc_core::extract_text("a.txt", None, b"hello").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(document.prompt("これは何？"), "これは何？\n\n[File: a.txt]\n```\nhello\n```");
+        assert_eq!(Media::default().prompt(""), "");
+    }
+}
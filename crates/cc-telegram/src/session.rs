@@ -17,6 +17,8 @@ pub struct InMemorySessionStore {
 #[derive(Debug, Clone)]
 pub struct Session {
     pub messages: Vec<cc_core::Message>,
+    /// Selected persona name (None = default)
+    pub persona: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -26,6 +28,7 @@ impl Default for Session {
         let now = chrono::Utc::now();
         Self {
             messages: Vec::new(),
+            persona: None,
             created_at: now,
             updated_at: now,
         }
@@ -62,10 +65,21 @@ impl InMemorySessionStore {
         session.updated_at = chrono::Utc::now();
     }
 
-    /// Clear a session
+    /// Set the persona for a session (None = reset to default)
+    pub async fn set_persona(&self, key: &str, persona: Option<String>) {
+        let mut sessions = self.sessions.write().await;
+        let session = sessions.entry(key.to_string()).or_default();
+        session.persona = persona;
+        session.updated_at = chrono::Utc::now();
+    }
+
+    /// Clear a session's messages (the selected persona is kept)
     pub async fn clear(&self, key: &str) {
         let mut sessions = self.sessions.write().await;
-        sessions.remove(key);
+        if let Some(session) = sessions.get_mut(key) {
+            session.messages.clear();
+            session.updated_at = chrono::Utc::now();
+        }
     }
 
     /// Get all session keys (for debugging/admin)
@@ -118,6 +132,21 @@ mod tests {
         assert!(session.messages.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_persona_survives_clear() {
+        let store = InMemorySessionStore::new();
+        store.set_persona("test-chat", Some("coder".to_string())).await;
+        store
+            .add_message("test-chat", cc_core::Message::user("Hello"))
+            .await;
+
+        store.clear("test-chat").await;
+
+        let session = store.get_or_create("test-chat").await;
+        assert!(session.messages.is_empty());
+        assert_eq!(session.persona.as_deref(), Some("coder"));
+    }
+
     #[tokio::test]
     async fn test_session_count() {
         let store = InMemorySessionStore::new();
@@ -1,26 +1,128 @@
 //! Bash command execution tool
 //!
-//! Executes shell commands with optional timeout.
+//! Executes shell commands with optional timeout. Calls with a `session_id`
+//! share a long-lived shell, so the working directory, exported variables
+//! and activated virtualenvs carry over from one call to the next. Commands
+//! can also run in the background, writing their output to a log file, and
+//! be stopped with the `kill` action. `kill` only stops processes this tool
+//! started in the background.
+//!
+//! Sessions and background processes belong to the conversation (the
+//! [`ToolOrigin`] of the call), so the same `session_id` in another
+//! conversation gets its own shell. Sessions unused for 30 minutes are closed,
+//! and the least recently used idle one makes room when all are open.
 
 use async_trait::async_trait;
-use cc_core::{Result, Tool, ToolResult};
+use cc_core::{Result, Tool, ToolOrigin, ToolResult};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::time::Duration;
-use tokio::process::Command;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStderr, ChildStdout, Command};
 use tokio::time::timeout;
 
+/// Shell sessions kept open at once
+const MAX_SESSIONS: usize = 16;
+
+/// Sessions unused for this long are closed
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Conversation a shell session belongs to, and its `session_id`
+type SessionKey = (String, String);
+
 /// Bash tool for executing shell commands
-pub struct BashTool;
+pub struct BashTool {
+    /// Open shell sessions by conversation and id
+    sessions: Mutex<HashMap<SessionKey, OpenSession>>,
+    /// Background processes started by this tool, with their conversation
+    jobs: Mutex<HashMap<u32, String>>,
+    /// Most sessions kept open at once
+    max_sessions: usize,
+    /// How long a session may go unused before it is closed
+    idle_timeout: Duration,
+}
+
+impl BashTool {
+    /// Create a new BashTool instance
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            jobs: Mutex::new(HashMap::new()),
+            max_sessions: MAX_SESSIONS,
+            idle_timeout: SESSION_IDLE_TIMEOUT,
+        }
+    }
+}
+
+/// An open shell session with the time it was last used
+struct OpenSession {
+    shell: Arc<tokio::sync::Mutex<ShellSession>>,
+    last_used: Instant,
+}
+
+impl OpenSession {
+    /// Whether no call is running a command in the shell
+    fn is_idle(&self) -> bool {
+        self.shell.try_lock().is_ok()
+    }
+}
+
+/// Conversation of the current tool call (`platform:channel:user`)
+fn conversation() -> String {
+    ToolOrigin::current()
+        .map(|origin| {
+            format!(
+                "{}:{}:{}",
+                origin.platform,
+                origin.channel_id,
+                origin.user_id.unwrap_or_default()
+            )
+        })
+        .unwrap_or_default()
+}
+
+impl Default for BashTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What to do with the call
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Action {
+    /// Run `command`
+    #[default]
+    Run,
+    /// Stop a background process (`pid`) or close a session (`session_id`)
+    Kill,
+}
 
 /// Input parameters for the bash tool
 #[derive(Debug, Deserialize)]
 struct BashInput {
     /// The command to execute
-    command: String,
+    #[serde(default)]
+    command: Option<String>,
     /// Timeout in milliseconds (default: 120000)
     #[serde(default = "default_timeout")]
     timeout_ms: u64,
+    /// Shell session to run in (created on first use)
+    #[serde(default)]
+    session_id: Option<String>,
+    /// Start the command and return its pid without waiting
+    #[serde(default)]
+    background: bool,
+    #[serde(default)]
+    action: Action,
+    /// Background process to kill
+    #[serde(default)]
+    pid: Option<u32>,
 }
 
 fn default_timeout() -> u64 {
@@ -38,6 +140,12 @@ struct BashOutput {
     exit_code: Option<i32>,
     /// Whether the command timed out
     timed_out: bool,
+    /// Session the command ran in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    /// Working directory of the session after the command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
 }
 
 #[async_trait]
@@ -47,7 +155,9 @@ impl Tool for BashTool {
     }
 
     fn description(&self) -> &str {
-        "Execute a bash command with optional timeout. Use this for terminal operations like git, npm, docker, etc."
+        "Execute a bash command with optional timeout. Use this for terminal operations like git, npm, docker, etc. \
+         Pass a session_id to keep the working directory and environment between calls, \
+         background: true for long-running processes (servers, watchers), and action: kill to stop them."
     }
 
     fn input_schema(&self) -> Value {
@@ -56,39 +166,99 @@ impl Tool for BashTool {
             "properties": {
                 "command": {
                     "type": "string",
-                    "description": "The command to execute"
+                    "description": "The command to execute (required unless action is kill)"
                 },
                 "timeout_ms": {
                     "type": "integer",
                     "description": "Timeout in milliseconds (default: 120000, max: 600000)",
                     "default": 120000,
                     "maximum": 600000
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Run in this persistent shell session (created on first use); cd, exported variables and activated virtualenvs carry over"
+                },
+                "background": {
+                    "type": "boolean",
+                    "description": "Start the command in the background and return its pid and log file instead of waiting",
+                    "default": false
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["run", "kill"],
+                    "description": "run (default) or kill: stop the background process pid, or close session_id with its background processes",
+                    "default": "run"
+                },
+                "pid": {
+                    "type": "integer",
+                    "description": "Background process to stop with action kill"
                 }
-            },
-            "required": ["command"]
+            }
         })
     }
 
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let bash_input: BashInput = serde_json::from_value(input.clone()).ok()?;
+        if bash_input.action == Action::Kill {
+            return Some(match (bash_input.pid, &bash_input.session_id) {
+                (Some(pid), _) => format!("Kill background process {}", pid),
+                (None, Some(session)) => format!("Close shell session '{}' and its background processes", session),
+                (None, None) => "Nothing to kill (no pid or session_id)".to_string(),
+            });
+        }
+
+        let command = bash_input.command?;
+        let place = match &bash_input.session_id {
+            Some(session) => format!("session '{}'", session),
+            None => std::env::current_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default(),
+        };
+        let mode = if bash_input.background {
+            "in the background".to_string()
+        } else {
+            format!("timeout {}ms", bash_input.timeout_ms.min(600_000))
+        };
+        Some(format!("$ {}\n(in {}, {})", command, place, mode))
+    }
+
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let bash_input: BashInput = serde_json::from_value(input)
             .map_err(|e| cc_core::Error::ToolExecution(format!("Invalid input: {}", e)))?;
+        let owner = conversation();
+
+        if bash_input.action == Action::Kill {
+            return Ok(self.kill(&owner, bash_input.pid, bash_input.session_id).await);
+        }
+        let Some(command) = bash_input.command.as_deref() else {
+            return Err(cc_core::Error::ToolExecution("Missing 'command' parameter".to_string()));
+        };
 
         // Limit timeout to 10 minutes max
         let timeout_ms = bash_input.timeout_ms.min(600_000);
         let duration = Duration::from_millis(timeout_ms);
 
         tracing::debug!(
-            command = %bash_input.command,
+            command = %command,
             timeout_ms = timeout_ms,
+            session_id = ?bash_input.session_id,
+            background = bash_input.background,
             "Executing bash command"
         );
 
+        if bash_input.background {
+            return Ok(self.run_in_background(&owner, command, bash_input.session_id).await);
+        }
+        if let Some(session_id) = bash_input.session_id {
+            return Ok(self.run_in_session(&(owner, session_id), command, duration).await);
+        }
+
         // Execute the command with timeout
         let result = timeout(
             duration,
             Command::new("bash")
                 .arg("-c")
-                .arg(&bash_input.command)
+                .arg(command)
                 .output(),
         )
         .await;
@@ -103,17 +273,11 @@ impl Tool for BashTool {
                     stderr,
                     exit_code: output.status.code(),
                     timed_out: false,
+                    session_id: None,
+                    cwd: None,
                 };
 
-                let output_str = serde_json::to_string_pretty(&bash_output)
-                    .unwrap_or_else(|_| format!("{:?}", bash_output));
-
-                // Return as error if exit code is non-zero
-                if output.status.success() {
-                    Ok(ToolResult::success(output_str))
-                } else {
-                    Ok(ToolResult::error(output_str))
-                }
+                Ok(to_result(&bash_output))
             }
             Ok(Err(e)) => {
                 Ok(ToolResult::error(format!("Failed to execute command: {}", e)))
@@ -128,13 +292,449 @@ impl Tool for BashTool {
     }
 }
 
+impl BashTool {
+    /// The session `key`, started when it is not open yet
+    ///
+    /// Closes sessions that were idle past the timeout, and the least recently
+    /// used idle session when all are open.
+    fn session(&self, key: &SessionKey) -> std::io::Result<Arc<tokio::sync::Mutex<ShellSession>>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        sessions.retain(|(_, id), open| {
+            let expired = now.duration_since(open.last_used) >= self.idle_timeout && open.is_idle();
+            if expired {
+                tracing::info!("Closing idle shell session '{}'", id);
+            }
+            !expired
+        });
+        if let Some(open) = sessions.get_mut(key) {
+            open.last_used = now;
+            return Ok(Arc::clone(&open.shell));
+        }
+
+        if sessions.len() >= self.max_sessions {
+            let victim = sessions
+                .iter()
+                .filter(|(_, open)| open.is_idle())
+                .min_by_key(|(_, open)| open.last_used)
+                .map(|(key, _)| key.clone());
+            match victim {
+                Some(victim) => {
+                    sessions.remove(&victim);
+                    tracing::info!("Closed shell session '{}' to make room for '{}'", victim.1, key.1);
+                }
+                None => {
+                    return Err(std::io::Error::other(format!(
+                        "All {} shell sessions are busy",
+                        self.max_sessions
+                    )));
+                }
+            }
+        }
+        let shell = Arc::new(tokio::sync::Mutex::new(ShellSession::spawn()?));
+        sessions.insert(
+            key.clone(),
+            OpenSession {
+                shell: Arc::clone(&shell),
+                last_used: now,
+            },
+        );
+        Ok(shell)
+    }
+
+    /// Forget the session `key`, returning it
+    fn remove_session(&self, key: &SessionKey) -> Option<Arc<tokio::sync::Mutex<ShellSession>>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key)
+            .map(|open| open.shell)
+    }
+
+    /// Whether `pid` is a background process this tool started for `owner`;
+    /// forgets it when `forget`
+    fn owns_job(&self, pid: u32, owner: &str, forget: bool) -> bool {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if jobs.get(&pid).map(String::as_str) != Some(owner) {
+            return false;
+        }
+        if forget {
+            jobs.remove(&pid);
+        }
+        true
+    }
+
+    async fn run_in_session(&self, key: &SessionKey, command: &str, duration: Duration) -> ToolResult {
+        let id = &key.1;
+        let session = match self.session(key) {
+            Ok(session) => session,
+            Err(e) => return ToolResult::error(format!("Failed to start shell session '{}': {}", id, e)),
+        };
+        let mut shell = session.lock().await;
+        let guard = AbandonGuard::new(self, key, &session, &shell);
+        let result = timeout(duration, shell.run(command)).await;
+        guard.disarm();
+        match result {
+            Ok(Ok(run)) => {
+                if run.cwd.is_none() {
+                    // The shell exited (e.g. `exit`); the next call starts a new one
+                    self.remove_session(key);
+                }
+                to_result(&BashOutput {
+                    stdout: run.stdout,
+                    stderr: run.stderr,
+                    exit_code: run.exit_code,
+                    timed_out: false,
+                    session_id: Some(id.to_string()),
+                    cwd: run.cwd,
+                })
+            }
+            Ok(Err(e)) => {
+                shell.close().await;
+                self.remove_session(key);
+                ToolResult::error(format!("Shell session '{}' failed and was closed: {}", id, e))
+            }
+            Err(_) => {
+                // The shell is still busy with the command, so it cannot be reused
+                shell.close().await;
+                self.remove_session(key);
+                ToolResult::error(format!(
+                    "Command timed out after {}ms; shell session '{}' was closed and its state is lost",
+                    duration.as_millis(),
+                    id
+                ))
+            }
+        }
+    }
+
+    /// Start `command` in the background (in the session `id` when given)
+    ///
+    /// The command gets its own process group (`set -m`), so killing it
+    /// also stops the processes it started.
+    async fn run_in_background(&self, owner: &str, command: &str, session_id: Option<String>) -> ToolResult {
+        let log = log_path();
+        let script = format!(
+            "set -m\n( {}\n) > {} 2>&1 < /dev/null &\necho $!\nset +m",
+            command,
+            shell_quote(&log.display().to_string())
+        );
+
+        let pid = match &session_id {
+            Some(id) => {
+                let key = (owner.to_string(), id.clone());
+                let session = match self.session(&key) {
+                    Ok(session) => session,
+                    Err(e) => return ToolResult::error(format!("Failed to start shell session '{}': {}", id, e)),
+                };
+                let mut shell = session.lock().await;
+                let guard = AbandonGuard::new(self, &key, &session, &shell);
+                let result = timeout(Duration::from_secs(10), shell.run(&script)).await;
+                guard.disarm();
+                let stdout = match result {
+                    Ok(Ok(run)) => run.stdout,
+                    _ => {
+                        shell.close().await;
+                        self.remove_session(&key);
+                        return ToolResult::error(format!("Shell session '{}' failed and was closed", id));
+                    }
+                };
+                match parse_pid(&stdout) {
+                    Ok(pid) => {
+                        shell.jobs.push(pid);
+                        pid
+                    }
+                    Err(error) => return error,
+                }
+            }
+            None => match Command::new("bash").arg("-c").arg(&script).output().await {
+                Ok(output) => match parse_pid(&String::from_utf8_lossy(&output.stdout)) {
+                    Ok(pid) => pid,
+                    Err(error) => return error,
+                },
+                Err(e) => return ToolResult::error(format!("Failed to execute command: {}", e)),
+            },
+        };
+        self.jobs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(pid, owner.to_string());
+
+        ToolResult::success(
+            serde_json::to_string_pretty(&json!({
+                "status": "started",
+                "pid": pid,
+                "log": log,
+                "session_id": session_id,
+            }))
+            .unwrap_or_default(),
+        )
+    }
+
+    /// Stop the background process `pid`, or close the session `session_id`
+    async fn kill(&self, owner: &str, pid: Option<u32>, session_id: Option<String>) -> ToolResult {
+        if let Some(pid) = pid {
+            if !self.owns_job(pid, owner, true) {
+                return ToolResult::error(format!(
+                    "Process {} was not started in the background by this tool",
+                    pid
+                ));
+            }
+            return match kill_process(pid).await {
+                Ok(()) => ToolResult::success(json!({"status": "killed", "pid": pid}).to_string()),
+                Err(e) => ToolResult::error(format!("Failed to kill process {}: {}", pid, e)),
+            };
+        }
+        let Some(id) = session_id else {
+            return ToolResult::error("Pass the pid of a background process or a session_id to kill");
+        };
+        let key = (owner.to_string(), id);
+        let id = &key.1;
+        let Some(session) = self.remove_session(&key) else {
+            return ToolResult::error(format!("No shell session '{}' is open", id));
+        };
+        let mut shell = session.lock().await;
+        let jobs = std::mem::take(&mut shell.jobs);
+        for pid in &jobs {
+            // Jobs that already finished cannot be killed; that is fine
+            self.owns_job(*pid, owner, true);
+            kill_process(*pid).await.ok();
+        }
+        shell.close().await;
+        ToolResult::success(json!({"status": "closed", "session_id": id, "killed": jobs}).to_string())
+    }
+}
+
+/// Closes a session whose call was dropped while its command ran
+///
+/// The [`ToolManager`](cc_core::ToolManager) drops a call that runs past its
+/// timeout. The shell is then still busy with the command and can never be
+/// used again, so the session is forgotten and its process group stopped.
+struct AbandonGuard<'a> {
+    tool: &'a BashTool,
+    key: &'a SessionKey,
+    session: &'a Arc<tokio::sync::Mutex<ShellSession>>,
+    pid: Option<u32>,
+    armed: bool,
+}
+
+impl<'a> AbandonGuard<'a> {
+    fn new(
+        tool: &'a BashTool,
+        key: &'a SessionKey,
+        session: &'a Arc<tokio::sync::Mutex<ShellSession>>,
+        shell: &ShellSession,
+    ) -> Self {
+        Self {
+            tool,
+            key,
+            session,
+            pid: shell.child.id(),
+            armed: true,
+        }
+    }
+
+    /// The call finished; leave the session to the caller
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for AbandonGuard<'_> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let mut sessions = self.tool.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        // A kill action may have replaced the session in the meantime
+        if sessions.get(self.key).is_some_and(|open| Arc::ptr_eq(&open.shell, self.session)) {
+            sessions.remove(self.key);
+        }
+        drop(sessions);
+        // The shell itself is killed when the session is dropped; stop the command too
+        if let (Some(pid), Ok(runtime)) = (self.pid, tokio::runtime::Handle::try_current()) {
+            runtime.spawn(async move {
+                kill_process(pid).await.ok();
+            });
+        }
+    }
+}
+
+/// Pid printed by a background start, or the error result
+fn parse_pid(stdout: &str) -> std::result::Result<u32, ToolResult> {
+    stdout.trim().parse().map_err(|_| {
+        ToolResult::error(format!(
+            "Failed to start the command in the background: {}",
+            stdout.trim()
+        ))
+    })
+}
+
+/// Pretty JSON of `output`, an error result when the command failed
+fn to_result(output: &BashOutput) -> ToolResult {
+    let output_str = serde_json::to_string_pretty(output).unwrap_or_else(|_| format!("{:?}", output));
+
+    // Return as error if exit code is non-zero
+    if output.exit_code == Some(0) {
+        ToolResult::success(output_str)
+    } else {
+        ToolResult::error(output_str)
+    }
+}
+
+/// Stop the process group of `pid` (background commands lead their own),
+/// or else the process alone
+async fn kill_process(pid: u32) -> std::io::Result<()> {
+    let group = Command::new("kill").arg("--").arg(format!("-{}", pid)).output().await?;
+    if group.status.success() {
+        return Ok(());
+    }
+    let output = Command::new("kill").arg(pid.to_string()).output().await?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
+
+/// A fresh log file path for a background command
+fn log_path() -> PathBuf {
+    std::env::temp_dir().join(format!("cc-bash-{}-{}.log", std::process::id(), next_id()))
+}
+
+fn next_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// `text` quoted for the shell
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// A long-lived `bash` reading commands from its stdin
+struct ShellSession {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    stderr: BufReader<ChildStderr>,
+    /// Background processes started in the session
+    jobs: Vec<u32>,
+}
+
+/// Result of a command run in a [`ShellSession`]
+struct SessionRun {
+    stdout: String,
+    stderr: String,
+    exit_code: Option<i32>,
+    /// Working directory afterwards (`None` when the shell exited)
+    cwd: Option<String>,
+}
+
+impl ShellSession {
+    fn spawn() -> std::io::Result<Self> {
+        let mut command = Command::new("bash");
+        command
+            .arg("--noprofile")
+            .arg("--norc")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        // Its own process group, so closing the session stops a running command too
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command.spawn()?;
+        let (Some(stdin), Some(stdout), Some(stderr)) = (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(std::io::Error::other("bash has no stdio pipes"));
+        };
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            stderr: BufReader::new(stderr),
+            jobs: Vec::new(),
+        })
+    }
+
+    /// Run `command` in the shell and wait for it to finish
+    ///
+    /// The command reads from /dev/null so it cannot swallow the lines that
+    /// follow. A marker line on stdout and stderr (carrying the exit code and
+    /// working directory) tells where its output ends.
+    async fn run(&mut self, command: &str) -> std::io::Result<SessionRun> {
+        let marker = format!("__cc_bash_done_{}_{}", std::process::id(), next_id());
+        let script = format!(
+            "{{\n{command}\n}} < /dev/null\n__cc_status=$?\n\
+             printf '\\n{marker} %d %s\\n' \"$__cc_status\" \"$PWD\"\n\
+             printf '\\n{marker}\\n' >&2\n"
+        );
+        self.stdin.write_all(script.as_bytes()).await?;
+        self.stdin.flush().await?;
+
+        let ((stdout, status), (stderr, _)) = tokio::try_join!(
+            read_until_marker(&mut self.stdout, &marker),
+            read_until_marker(&mut self.stderr, &marker),
+        )?;
+        let Some(status) = status else {
+            // EOF before the marker: the command ended the shell
+            let exit_code = self.child.wait().await?.code();
+            return Ok(SessionRun {
+                stdout,
+                stderr,
+                exit_code,
+                cwd: None,
+            });
+        };
+        let (code, cwd) = status.split_once(' ').unwrap_or((status.as_str(), ""));
+        Ok(SessionRun {
+            stdout,
+            stderr,
+            exit_code: code.parse().ok(),
+            cwd: Some(cwd.to_string()),
+        })
+    }
+
+    /// Stop the shell and the command it is running
+    async fn close(&mut self) {
+        if let Some(pid) = self.child.id() {
+            kill_process(pid).await.ok();
+        }
+        self.child.kill().await.ok();
+    }
+}
+
+/// Lines of `reader` up to the `marker` line, and the rest of that line
+///
+/// The marker is printed after a newline, which is taken off the output
+/// again. At EOF the output read so far is returned without a marker.
+async fn read_until_marker<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    marker: &str,
+) -> std::io::Result<(String, Option<String>)> {
+    let mut output = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).await? == 0 {
+            return Ok((String::from_utf8_lossy(&output).to_string(), None));
+        }
+        let text = String::from_utf8_lossy(&line);
+        if let Some(rest) = text.trim_end_matches('\n').strip_prefix(marker) {
+            output.pop();
+            return Ok((String::from_utf8_lossy(&output).to_string(), Some(rest.trim_start().to_string())));
+        }
+        output.extend_from_slice(&line);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[tokio::test]
     async fn test_bash_echo() {
-        let tool = BashTool;
+        let tool = BashTool::new();
         let input = json!({"command": "echo hello"});
         let result = tool.execute(input).await.unwrap();
 
@@ -144,16 +744,27 @@ mod tests {
 
     #[tokio::test]
     async fn test_bash_failure() {
-        let tool = BashTool;
+        let tool = BashTool::new();
         let input = json!({"command": "exit 1"});
         let result = tool.execute(input).await.unwrap();
 
         assert!(result.is_error);
     }
 
+    #[tokio::test]
+    async fn test_bash_preview() {
+        let tool = BashTool::new();
+        let preview = tool.preview(&json!({"command": "rm -rf build"})).await.unwrap();
+        assert!(preview.starts_with("$ rm -rf build\n(in "));
+        assert!(preview.ends_with("timeout 120000ms)"));
+
+        let preview = tool.preview(&json!({"action": "kill", "session_id": "dev"})).await.unwrap();
+        assert_eq!(preview, "Close shell session 'dev' and its background processes");
+    }
+
     #[tokio::test]
     async fn test_bash_timeout() {
-        let tool = BashTool;
+        let tool = BashTool::new();
         let input = json!({
             "command": "sleep 10",
             "timeout_ms": 100
@@ -163,4 +774,194 @@ mod tests {
         assert!(result.is_error);
         assert!(result.output.contains("timed out"));
     }
+
+    fn output(result: &ToolResult) -> Value {
+        serde_json::from_str(&result.output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bash_session_keeps_state() {
+        let tool = BashTool::new();
+        let dir = tempfile::TempDir::new().unwrap();
+        let dir = dir.path().canonicalize().unwrap();
+
+        let first = tool
+            .execute(json!({
+                "command": format!("cd {} && export GREETING=hi && printf partial", dir.display()),
+                "session_id": "s1"
+            }))
+            .await
+            .unwrap();
+        assert!(!first.is_error, "{}", first.output);
+        assert_eq!(output(&first)["stdout"], "partial");
+        assert_eq!(output(&first)["cwd"], dir.display().to_string());
+
+        let second = tool
+            .execute(json!({"command": "echo $GREETING; pwd; echo oops >&2; false", "session_id": "s1"}))
+            .await
+            .unwrap();
+        assert!(second.is_error);
+        let second = output(&second);
+        assert_eq!(second["stdout"], format!("hi\n{}\n", dir.display()));
+        assert_eq!(second["stderr"], "oops\n");
+        assert_eq!(second["exit_code"], 1);
+
+        // Other sessions and one-off calls start fresh
+        let other = tool
+            .execute(json!({"command": "echo \"[$GREETING]\"", "session_id": "s2"}))
+            .await
+            .unwrap();
+        assert_eq!(output(&other)["stdout"], "[]\n");
+
+        let closed = tool.execute(json!({"action": "kill", "session_id": "s1"})).await.unwrap();
+        assert!(!closed.is_error, "{}", closed.output);
+        let again = tool
+            .execute(json!({"command": "echo \"[$GREETING]\"", "session_id": "s1"}))
+            .await
+            .unwrap();
+        assert_eq!(output(&again)["stdout"], "[]\n");
+    }
+
+    #[tokio::test]
+    async fn test_bash_session_exit_and_timeout() {
+        let tool = BashTool::new();
+        let exited = tool.execute(json!({"command": "exit 3", "session_id": "s"})).await.unwrap();
+        assert!(exited.is_error);
+        assert_eq!(output(&exited)["exit_code"], 3);
+
+        let timed_out = tool
+            .execute(json!({"command": "export X=1; sleep 10", "session_id": "s", "timeout_ms": 100}))
+            .await
+            .unwrap();
+        assert!(timed_out.output.contains("was closed"));
+        let fresh = tool
+            .execute(json!({"command": "echo \"[$X]\"", "session_id": "s"}))
+            .await
+            .unwrap();
+        assert_eq!(output(&fresh)["stdout"], "[]\n");
+    }
+
+    #[tokio::test]
+    async fn test_bash_background_and_kill() {
+        let tool = BashTool::new();
+        let started = tool
+            .execute(json!({"command": "echo started; sleep 30", "background": true}))
+            .await
+            .unwrap();
+        assert!(!started.is_error, "{}", started.output);
+        let started = output(&started);
+        let pid = started["pid"].as_u64().unwrap();
+
+        let log = PathBuf::from(started["log"].as_str().unwrap());
+        for _ in 0..50 {
+            if std::fs::read_to_string(&log).unwrap_or_default() == "started\n" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "started\n");
+
+        let killed = tool.execute(json!({"action": "kill", "pid": pid})).await.unwrap();
+        assert!(!killed.is_error, "{}", killed.output);
+        std::fs::remove_file(&log).ok();
+
+        let missing = tool.execute(json!({"action": "kill", "session_id": "none"})).await.unwrap();
+        assert!(missing.is_error);
+
+        // Only processes started by the tool can be killed
+        let again = tool.execute(json!({"action": "kill", "pid": pid})).await.unwrap();
+        assert!(again.is_error);
+        let foreign = tool
+            .execute(json!({"action": "kill", "pid": std::process::id()}))
+            .await
+            .unwrap();
+        assert!(foreign.output.contains("not started in the background by this tool"));
+    }
+
+    #[tokio::test]
+    async fn test_bash_session_closed_when_call_dropped() {
+        let tool = BashTool::new();
+        let call = tool.execute(json!({"command": "sleep 10", "session_id": "s"}));
+        assert!(timeout(Duration::from_millis(200), call).await.is_err());
+        assert!(tool.sessions.lock().unwrap().is_empty());
+
+        let next = tool
+            .execute(json!({"command": "echo ok", "session_id": "s", "timeout_ms": 5000}))
+            .await
+            .unwrap();
+        assert_eq!(output(&next)["stdout"], "ok\n");
+    }
+
+    #[tokio::test]
+    async fn test_bash_sessions_belong_to_the_conversation() {
+        let tool = BashTool::new();
+        let alice = ToolOrigin::new("discord", "1").with_user("alice");
+        let bob = ToolOrigin::new("discord", "2").with_user("bob");
+
+        alice
+            .clone()
+            .scope(tool.execute(json!({"command": "export SECRET=42", "session_id": "s"})))
+            .await
+            .unwrap();
+        let other = bob
+            .clone()
+            .scope(tool.execute(json!({"command": "echo \"[$SECRET]\"", "session_id": "s"})))
+            .await
+            .unwrap();
+        assert_eq!(output(&other)["stdout"], "[]\n");
+        let own = alice
+            .clone()
+            .scope(tool.execute(json!({"command": "echo \"[$SECRET]\"", "session_id": "s"})))
+            .await
+            .unwrap();
+        assert_eq!(output(&own)["stdout"], "[42]\n");
+
+        // Background processes can only be killed from their conversation
+        let started = alice
+            .clone()
+            .scope(tool.execute(json!({"command": "sleep 30", "background": true})))
+            .await
+            .unwrap();
+        let pid = output(&started)["pid"].as_u64().unwrap();
+        let foreign = bob
+            .scope(tool.execute(json!({"action": "kill", "pid": pid})))
+            .await
+            .unwrap();
+        assert!(foreign.is_error);
+        let killed = alice
+            .scope(tool.execute(json!({"action": "kill", "pid": pid})))
+            .await
+            .unwrap();
+        assert!(!killed.is_error, "{}", killed.output);
+        std::fs::remove_file(output(&started)["log"].as_str().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn test_bash_sessions_evicted() {
+        let open = |tool: &BashTool| {
+            let mut ids: Vec<String> = tool.sessions.lock().unwrap().keys().map(|(_, id)| id.clone()).collect();
+            ids.sort();
+            ids
+        };
+
+        // The least recently used session makes room when all are open
+        let tool = BashTool {
+            max_sessions: 2,
+            ..BashTool::new()
+        };
+        for id in ["a", "b", "a", "c"] {
+            let result = tool.execute(json!({"command": "true", "session_id": id})).await.unwrap();
+            assert!(!result.is_error, "{}", result.output);
+        }
+        assert_eq!(open(&tool), vec!["a", "c"]);
+
+        // Sessions idle past the timeout are closed
+        let tool = BashTool {
+            idle_timeout: Duration::ZERO,
+            ..BashTool::new()
+        };
+        tool.execute(json!({"command": "true", "session_id": "a"})).await.unwrap();
+        tool.execute(json!({"command": "true", "session_id": "b"})).await.unwrap();
+        assert_eq!(open(&tool), vec!["b"]);
+    }
 }
@@ -0,0 +1,196 @@
+//! Line diffs for previews of file changes
+//!
+//! [`unified_diff`] renders the change from one text to another in unified
+//! diff format, as shown when a write or edit waits for confirmation.
+
+/// Unchanged lines shown around each change
+const CONTEXT: usize = 3;
+
+/// Longest diff shown, in lines
+const MAX_LINES: usize = 200;
+
+/// Largest `old x new` line product compared line by line; bigger changes
+/// are shown as the old lines replaced by the new ones
+const MAX_TABLE: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Unified diff from `old` to `new` of the file at `path`
+///
+/// Empty when the texts are the same.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&a, &b);
+    if ops.iter().all(|op| matches!(op, Op::Equal(..))) {
+        return String::new();
+    }
+
+    let mut lines = vec![format!("--- {}", path), format!("+++ {}", path)];
+    for hunk in hunks(&ops) {
+        let ops = &ops[hunk.0..hunk.1];
+        let (old_start, new_start) = start_of(ops);
+        let old_len = ops.iter().filter(|op| !matches!(op, Op::Insert(_))).count();
+        let new_len = ops.iter().filter(|op| !matches!(op, Op::Delete(_))).count();
+        lines.push(format!(
+            "@@ -{},{} +{},{} @@",
+            old_start + 1,
+            old_len,
+            new_start + 1,
+            new_len
+        ));
+        for op in ops {
+            lines.push(match *op {
+                Op::Equal(i, _) => format!(" {}", a[i]),
+                Op::Delete(i) => format!("-{}", a[i]),
+                Op::Insert(j) => format!("+{}", b[j]),
+            });
+        }
+    }
+
+    if lines.len() > MAX_LINES {
+        let more = lines.len() - MAX_LINES;
+        lines.truncate(MAX_LINES);
+        lines.push(format!("… ({} more lines)", more));
+    }
+    lines.join("\n")
+}
+
+/// Edit script from `a` to `b`
+fn diff_ops(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    if a_mid.len().saturating_mul(b_mid.len()) <= MAX_TABLE {
+        ops.extend(lcs_ops(a_mid, b_mid, prefix));
+    } else {
+        ops.extend((0..a_mid.len()).map(|i| Op::Delete(prefix + i)));
+        ops.extend((0..b_mid.len()).map(|j| Op::Insert(prefix + j)));
+    }
+    ops.extend((0..suffix).map(|k| Op::Equal(a.len() - suffix + k, b.len() - suffix + k)));
+    ops
+}
+
+/// Edit script of the longest common subsequence (indices offset by `offset`)
+fn lcs_ops(a: &[&str], b: &[&str], offset: usize) -> Vec<Op> {
+    let width = b.len() + 1;
+    // table[i * width + j]: LCS length of a[i..] and b[j..]
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i * width + j] = if a[i] == b[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            ops.push(Op::Equal(offset + i, offset + j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(Op::Delete(offset + i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(offset + j));
+            j += 1;
+        }
+    }
+    ops.extend((i..a.len()).map(|i| Op::Delete(offset + i)));
+    ops.extend((j..b.len()).map(|j| Op::Insert(offset + j)));
+    ops
+}
+
+/// Ranges of `ops` forming hunks: changes with up to [`CONTEXT`] lines around them
+fn hunks(ops: &[Op]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (k, op) in ops.iter().enumerate() {
+        if matches!(op, Op::Equal(..)) {
+            continue;
+        }
+        let start = k.saturating_sub(CONTEXT);
+        let end = (k + 1 + CONTEXT).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    hunks
+}
+
+/// Old and new line index where a hunk starts
+fn start_of(ops: &[Op]) -> (usize, usize) {
+    let old = ops.iter().find_map(|op| match *op {
+        Op::Equal(i, _) | Op::Delete(i) => Some(i),
+        Op::Insert(_) => None,
+    });
+    let new = ops.iter().find_map(|op| match *op {
+        Op::Equal(_, j) | Op::Insert(j) => Some(j),
+        Op::Delete(_) => None,
+    });
+    // A hunk that only inserts (or only deletes) starts where the other side is
+    match (old, new) {
+        (Some(old), Some(new)) => (old, new),
+        (Some(old), None) => (old, old),
+        (None, Some(new)) => (new, new),
+        (None, None) => (0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\nj\nk\n";
+        let diff = unified_diff("x.txt", old, new);
+        assert_eq!(
+            diff,
+            "--- x.txt\n+++ x.txt\n\
+             @@ -2,9 +2,10 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n i\n j\n+k"
+        );
+
+        assert_eq!(unified_diff("x.txt", old, old), "");
+    }
+
+    #[test]
+    fn test_unified_diff_separate_hunks() {
+        let old: String = (1..=20).map(|n| format!("{}\n", n)).collect();
+        let new: String = (1..=20)
+            .map(|n| match n {
+                2 => "two\n".to_string(),
+                19 => "nineteen\n".to_string(),
+                n => format!("{}\n", n),
+            })
+            .collect();
+        let diff = unified_diff("n.txt", &old, &new);
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@\n 16\n 17\n 18\n-19\n+nineteen\n 20"));
+    }
+
+    #[test]
+    fn test_unified_diff_new_file() {
+        let diff = unified_diff("new.txt", "", "one\ntwo\n");
+        assert_eq!(diff, "--- new.txt\n+++ new.txt\n@@ -1,0 +1,2 @@\n+one\n+two");
+    }
+}
@@ -5,6 +5,8 @@ use cc_core::{Result, Tool, ToolResult};
 use serde_json::{json, Value};
 use tokio::fs;
 
+use crate::diff::unified_diff;
+
 /// Edit tool for making string replacements in files
 pub struct EditTool;
 
@@ -51,6 +53,22 @@ impl Tool for EditTool {
         })
     }
 
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let path = input["path"].as_str()?;
+        let old_string = input["old_string"].as_str()?;
+        let new_string = input["new_string"].as_str()?;
+        let replace_all = input["replace_all"].as_bool().unwrap_or(false);
+
+        let content = match fs::read_to_string(path).await {
+            Ok(c) => c,
+            Err(e) => return Some(format!("Cannot read '{}': {}", path, e)),
+        };
+        match replace(&content, old_string, new_string, replace_all) {
+            Ok((new_content, _)) => Some(unified_diff(path, &content, &new_content)),
+            Err(e) => Some(format!("The edit would fail: {}", e)),
+        }
+    }
+
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let path = input["path"].as_str().ok_or_else(|| {
             cc_core::Error::ToolExecution("Missing 'path' parameter".to_string())
@@ -76,34 +94,14 @@ impl Tool for EditTool {
             }
         };
 
-        // Count occurrences
-        let count = content.matches(old_string).count();
-
-        if count == 0 {
-            return Ok(ToolResult::error(format!(
-                "String not found in file: '{}'",
-                old_string
-            )));
-        }
-
-        if !replace_all && count > 1 {
-            return Ok(ToolResult::error(format!(
-                "Found {} occurrences of the string. Use 'replace_all: true' to replace all, or make the string more specific.",
-                count
-            )));
-        }
-
-        // Perform replacement
-        let new_content = if replace_all {
-            content.replace(old_string, new_string)
-        } else {
-            content.replacen(old_string, new_string, 1)
+        let (new_content, replaced_count) = match replace(&content, old_string, new_string, replace_all) {
+            Ok(replaced) => replaced,
+            Err(e) => return Ok(ToolResult::error(e)),
         };
 
         // Write back
         match fs::write(path, new_content).await {
             Ok(()) => {
-                let replaced_count = if replace_all { count } else { 1 };
                 Ok(ToolResult::success(format!(
                     "Successfully replaced {} occurrence(s) in '{}'",
                     replaced_count, path
@@ -116,6 +114,33 @@ impl Tool for EditTool {
     }
 }
 
+/// `content` with `old_string` replaced, and the number of replacements
+fn replace(
+    content: &str,
+    old_string: &str,
+    new_string: &str,
+    replace_all: bool,
+) -> std::result::Result<(String, usize), String> {
+    let count = content.matches(old_string).count();
+
+    if count == 0 {
+        return Err(format!("String not found in file: '{}'", old_string));
+    }
+
+    if !replace_all && count > 1 {
+        return Err(format!(
+            "Found {} occurrences of the string. Use 'replace_all: true' to replace all, or make the string more specific.",
+            count
+        ));
+    }
+
+    if replace_all {
+        Ok((content.replace(old_string, new_string), count))
+    } else {
+        Ok((content.replacen(old_string, new_string, 1), 1))
+    }
+}
+
 impl Default for EditTool {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,171 @@
+//! `.gitignore` rules for walking a directory tree
+//!
+//! Covers the common part of the gitignore format: comments, `!` negation,
+//! trailing `/` for directories, patterns anchored by a `/`, and `**`.
+//! Deeper `.gitignore` files win over the ones above them.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// One pattern line of a `.gitignore`
+struct Rule {
+    matcher: GlobMatcher,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// The rules of one `.gitignore` file
+pub(crate) struct Gitignore {
+    /// Directory holding the file; patterns are relative to it
+    dir: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl Gitignore {
+    /// Rules of the `.gitignore` in `dir`, if there is one
+    pub(crate) fn load(dir: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(dir.join(".gitignore")).ok()?;
+        Some(Self::parse(dir, &text))
+    }
+
+    /// Parse the text of a `.gitignore` in `dir`; invalid patterns are skipped
+    pub(crate) fn parse(dir: &Path, text: &str) -> Self {
+        let rules = text.lines().filter_map(parse_rule).collect();
+        Self {
+            dir: dir.to_path_buf(),
+            rules,
+        }
+    }
+
+    /// Whether `path` is ignored (`Some(true)`), re-included by a `!` rule
+    /// (`Some(false)`), or not matched by any rule (`None`)
+    fn decide(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.dir).ok()?;
+        // The last matching rule wins
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.matcher.is_match(relative))
+            .map(|rule| !rule.negated)
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    if pattern.is_empty() {
+        return None;
+    }
+    // A slash anywhere but at the end anchors the pattern to the file's directory
+    let glob = if pattern.contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+    let matcher = GlobBuilder::new(&glob)
+        .literal_separator(true)
+        .build()
+        .ok()?
+        .compile_matcher();
+    Some(Rule {
+        matcher,
+        negated,
+        dir_only,
+    })
+}
+
+/// The `.gitignore` files in effect at some point of a walk, outermost first
+#[derive(Clone, Default)]
+pub(crate) struct IgnoreStack(Vec<Arc<Gitignore>>);
+
+impl IgnoreStack {
+    /// Rules for a walk starting at `root`: the `.gitignore` files from the
+    /// enclosing git repository's top level down to `root`
+    pub(crate) fn for_root(root: &Path) -> Self {
+        let mut dirs = Vec::new();
+        let mut in_repo = false;
+        for dir in root.ancestors().skip(1) {
+            dirs.push(dir);
+            if dir.join(".git").exists() {
+                in_repo = true;
+                break;
+            }
+        }
+        let mut stack = Self::default();
+        if in_repo {
+            for dir in dirs.into_iter().rev() {
+                stack = stack.enter(dir);
+            }
+        }
+        stack.enter(root)
+    }
+
+    /// Rules inside `dir`: these plus the `.gitignore` of `dir`
+    pub(crate) fn enter(&self, dir: &Path) -> Self {
+        match Gitignore::load(dir) {
+            Some(gitignore) => {
+                let mut stack = self.clone();
+                stack.0.push(Arc::new(gitignore));
+                stack
+            }
+            None => self.clone(),
+        }
+    }
+
+    /// Whether `path` is ignored
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.0
+            .iter()
+            .rev()
+            .find_map(|gitignore| gitignore.decide(path, is_dir))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_rules() {
+        let dir = Path::new("/repo");
+        let gitignore = Gitignore::parse(
+            dir,
+            "# build output\ntarget/\n*.log\n!keep.log\n/secret.txt\ndocs/*.tmp\n",
+        );
+        let ignored = |path: &str, is_dir| gitignore.decide(&dir.join(path), is_dir) == Some(true);
+
+        assert!(ignored("target", true));
+        assert!(ignored("crates/a/target", true));
+        assert!(!ignored("target", false));
+        assert!(ignored("x/debug.log", false));
+        assert_eq!(gitignore.decide(&dir.join("keep.log"), false), Some(false));
+        assert!(ignored("secret.txt", false));
+        assert!(!ignored("sub/secret.txt", false));
+        assert!(ignored("docs/a.tmp", false));
+        assert!(!ignored("docs/x/a.tmp", false));
+        assert_eq!(gitignore.decide(Path::new("/elsewhere/a.log"), false), None);
+    }
+
+    #[test]
+    fn test_deeper_gitignore_wins() {
+        let stack = IgnoreStack(vec![
+            Arc::new(Gitignore::parse(Path::new("/repo"), "*.gen.rs\n")),
+            Arc::new(Gitignore::parse(Path::new("/repo/keep"), "!*.gen.rs\n")),
+        ]);
+        assert!(stack.is_ignored(Path::new("/repo/src/a.gen.rs"), false));
+        assert!(!stack.is_ignored(Path::new("/repo/keep/a.gen.rs"), false));
+        assert!(!stack.is_ignored(Path::new("/repo/src/a.rs"), false));
+    }
+}
@@ -41,6 +41,10 @@ impl Tool for GlobTool {
         })
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let pattern = input["pattern"].as_str().ok_or_else(|| {
             cc_core::Error::ToolExecution("Missing 'pattern' parameter".to_string())
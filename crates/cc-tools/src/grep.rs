@@ -1,8 +1,26 @@
 //! Grep tool for content search in files
+//!
+//! Searches with ripgrep's engine (the `grep` crate): the walk skips hidden
+//! files and whatever `.gitignore` excludes, binary files are passed over,
+//! and matches come back as JSON with their context lines.
 
+use crate::gitignore::IgnoreStack;
 use async_trait::async_trait;
 use cc_core::{Result, Tool, ToolResult};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use grep::regex::{RegexMatcher, RegexMatcherBuilder};
+use grep::searcher::{
+    BinaryDetection, Searcher, SearcherBuilder, Sink, SinkContext, SinkContextKind, SinkMatch,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Matches returned when `max_results` is not given
+const DEFAULT_MAX_RESULTS: usize = 100;
+
+/// Longest line shown, in characters
+const MAX_LINE_CHARS: usize = 500;
 
 /// Grep tool for content search
 pub struct GrepTool;
@@ -14,6 +32,70 @@ impl GrepTool {
     }
 }
 
+/// Input parameters for the grep tool
+#[derive(Debug, Deserialize)]
+struct GrepInput {
+    pattern: String,
+    #[serde(default = "default_path")]
+    path: String,
+    /// Single include glob (kept alongside `include`)
+    #[serde(default)]
+    glob: Option<String>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    ignore_case: bool,
+    #[serde(default)]
+    fixed_strings: bool,
+    #[serde(default)]
+    word: bool,
+    #[serde(default)]
+    multiline: bool,
+    #[serde(default)]
+    context: Option<usize>,
+    #[serde(default)]
+    before_context: Option<usize>,
+    #[serde(default)]
+    after_context: Option<usize>,
+    #[serde(default)]
+    max_results: Option<usize>,
+    /// Search hidden files and directories too
+    #[serde(default)]
+    hidden: bool,
+    /// Search files that `.gitignore` excludes too
+    #[serde(default)]
+    no_ignore: bool,
+}
+
+fn default_path() -> String {
+    ".".to_string()
+}
+
+/// One matching line (or lines, in multiline mode)
+#[derive(Debug, Serialize)]
+struct GrepMatch {
+    path: String,
+    line: u64,
+    text: String,
+    /// Lines directly before the match
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    before: Vec<String>,
+    /// Lines directly after the match
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    after: Vec<String>,
+}
+
+/// Result of a search
+#[derive(Debug, Serialize)]
+struct GrepOutput {
+    matches: Vec<GrepMatch>,
+    files_searched: usize,
+    /// More matches exist beyond `max_results`
+    truncated: bool,
+}
+
 #[async_trait]
 impl Tool for GrepTool {
     fn name(&self) -> &str {
@@ -21,10 +103,16 @@ impl Tool for GrepTool {
     }
 
     fn description(&self) -> &str {
-        "Search for patterns in file contents using regular expressions"
+        "Search for patterns in file contents using regular expressions (ripgrep engine). \
+         Skips hidden files, binary files and anything .gitignore excludes. \
+         Returns JSON: matches with path, line number, text and optional context lines."
     }
 
     fn input_schema(&self) -> Value {
+        let globs = |description: &str| {
+            json!({"type": "array", "items": {"type": "string"}, "description": description})
+        };
+        let lines = |description: &str| json!({"type": "integer", "minimum": 0, "description": description});
         json!({
             "type": "object",
             "properties": {
@@ -34,82 +122,410 @@ impl Tool for GrepTool {
                 },
                 "path": {
                     "type": "string",
-                    "description": "The file or directory to search in"
+                    "description": "The file or directory to search in (default: current directory)"
                 },
                 "glob": {
                     "type": "string",
                     "description": "File pattern to limit search (e.g., '*.rs')"
                 },
+                "include": globs("Only search files matching one of these globs (e.g., ['*.rs', 'src/**/*.toml'])"),
+                "exclude": globs("Skip files and directories matching one of these globs (e.g., ['target', '*.min.js'])"),
                 "ignore_case": {
                     "type": "boolean",
                     "description": "Case insensitive search (default: false)"
+                },
+                "fixed_strings": {
+                    "type": "boolean",
+                    "description": "Treat the pattern as a literal string, not a regex (default: false)"
+                },
+                "word": {
+                    "type": "boolean",
+                    "description": "Only match whole words (default: false)"
+                },
+                "multiline": {
+                    "type": "boolean",
+                    "description": "Let matches span lines; '.' also matches newlines (default: false)"
+                },
+                "context": lines("Lines of context before and after each match (like grep -C)"),
+                "before_context": lines("Lines of context before each match (like grep -B)"),
+                "after_context": lines("Lines of context after each match (like grep -A)"),
+                "max_results": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Maximum number of matches to return (default: 100)"
+                },
+                "hidden": {
+                    "type": "boolean",
+                    "description": "Also search hidden files and directories (default: false)"
+                },
+                "no_ignore": {
+                    "type": "boolean",
+                    "description": "Also search files excluded by .gitignore (default: false)"
                 }
             },
             "required": ["pattern"]
         })
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, input: Value) -> Result<ToolResult> {
-        let pattern = input["pattern"].as_str().ok_or_else(|| {
-            cc_core::Error::ToolExecution("Missing 'pattern' parameter".to_string())
-        })?;
+        let input: GrepInput = serde_json::from_value(input)
+            .map_err(|e| cc_core::Error::ToolExecution(format!("Invalid input: {}", e)))?;
 
-        let path = input["path"].as_str().unwrap_or(".");
-        let glob_pattern = input["glob"].as_str();
-        let ignore_case = input["ignore_case"].as_bool().unwrap_or(false);
+        tracing::debug!(pattern = %input.pattern, path = %input.path, glob = ?input.glob, ignore_case = input.ignore_case, "Grepping files");
 
-        tracing::debug!(pattern = %pattern, path = %path, glob = ?glob_pattern, ignore_case = ignore_case, "Grepping files");
+        let search = match Search::new(&input) {
+            Ok(search) => search,
+            Err(e) => return Ok(ToolResult::error(e)),
+        };
+        let root = PathBuf::from(&input.path);
+        let output = tokio::task::spawn_blocking(move || search.run(&root))
+            .await
+            .map_err(|e| cc_core::Error::ToolExecution(format!("Search failed: {}", e)))?;
 
-        // Build ripgrep command
-        let mut args = vec!["--no-heading", "--with-filename", "--line-number"];
+        match output {
+            Ok(output) => Ok(ToolResult::success(serde_json::to_string_pretty(&output)?)),
+            Err(e) => Ok(ToolResult::error(e)),
+        }
+    }
+}
+
+impl Default for GrepTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        if ignore_case {
-            args.push("--ignore-case");
+/// A configured search, run on a blocking thread
+struct Search {
+    matcher: RegexMatcher,
+    searcher: Searcher,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    hidden: bool,
+    no_ignore: bool,
+    max_results: usize,
+}
+
+/// Where a search is while walking
+struct Progress {
+    matches: Vec<GrepMatch>,
+    files_searched: usize,
+    truncated: bool,
+}
+
+impl Search {
+    fn new(input: &GrepInput) -> std::result::Result<Self, String> {
+        let mut builder = RegexMatcherBuilder::new();
+        builder
+            .case_insensitive(input.ignore_case)
+            .fixed_strings(input.fixed_strings)
+            .word(input.word);
+        if input.multiline {
+            builder.multi_line(true).dot_matches_new_line(true);
+        } else {
+            builder.line_terminator(Some(b'\n'));
         }
+        let matcher = builder
+            .build(&input.pattern)
+            .map_err(|e| format!("Invalid pattern '{}': {}", input.pattern, e))?;
+
+        let searcher = SearcherBuilder::new()
+            .line_number(true)
+            .multi_line(input.multiline)
+            .before_context(input.before_context.or(input.context).unwrap_or(0))
+            .after_context(input.after_context.or(input.context).unwrap_or(0))
+            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .build();
+
+        let include: Vec<&str> = input.glob.iter().chain(&input.include).map(String::as_str).collect();
+        let exclude: Vec<&str> = input.exclude.iter().map(String::as_str).collect();
 
-        if let Some(g) = glob_pattern {
-            args.push("--glob");
-            args.push(g);
+        Ok(Self {
+            matcher,
+            searcher,
+            include: glob_set(&include)?,
+            exclude: glob_set(&exclude)?,
+            hidden: input.hidden,
+            no_ignore: input.no_ignore,
+            max_results: input.max_results.unwrap_or(DEFAULT_MAX_RESULTS).max(1),
+        })
+    }
+
+    fn run(mut self, root: &Path) -> std::result::Result<GrepOutput, String> {
+        let mut progress = Progress {
+            matches: Vec::new(),
+            files_searched: 0,
+            truncated: false,
+        };
+
+        let metadata = std::fs::metadata(root).map_err(|e| format!("Cannot search '{}': {}", root.display(), e))?;
+        if metadata.is_file() {
+            self.search_file(root, &root.display().to_string(), &mut progress);
+        } else {
+            // Walk the canonical path so .gitignore files above `root` line up,
+            // but report paths the way the caller wrote `root`
+            let canonical = root
+                .canonicalize()
+                .map_err(|e| format!("Cannot search '{}': {}", root.display(), e))?;
+            let ignores = if self.no_ignore {
+                IgnoreStack::default()
+            } else {
+                IgnoreStack::for_root(&canonical)
+            };
+            self.walk(&canonical, root, &canonical, &ignores, &mut progress);
         }
 
-        args.push("--");
-        args.push(pattern);
-        args.push(path);
+        Ok(GrepOutput {
+            matches: progress.matches,
+            files_searched: progress.files_searched,
+            truncated: progress.truncated,
+        })
+    }
 
-        let output = match tokio::process::Command::new("rg")
-            .args(&args)
-            .output()
-            .await
-        {
-            Ok(o) => o,
-            Err(e) => {
-                // rg not found, try fallback
-                return Ok(ToolResult::error(format!(
-                    "ripgrep (rg) not found. Please install ripgrep: {}",
-                    e
-                )));
-            }
+    /// Search the files under `dir`, in name order
+    fn walk(&mut self, dir: &Path, root: &Path, canonical: &Path, ignores: &IgnoreStack, progress: &mut Progress) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
         };
+        let mut entries: Vec<_> = entries.filter_map(|entry| entry.ok()).collect();
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            if progress.truncated {
+                return;
+            }
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name == ".git" || (!self.hidden && name.starts_with('.')) {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            let relative = path.strip_prefix(canonical).unwrap_or(&path);
+            let is_dir = file_type.is_dir();
+            if ignores.is_ignored(&path, is_dir) || self.exclude.as_ref().is_some_and(|set| set.is_match(relative)) {
+                continue;
+            }
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            if is_dir {
+                let ignores = if self.no_ignore { ignores.clone() } else { ignores.enter(&path) };
+                self.walk(&path, root, canonical, &ignores, progress);
+            } else if file_type.is_file() && self.include.as_ref().is_none_or(|set| set.is_match(relative)) {
+                let shown = root.join(relative).display().to_string();
+                self.search_file(&path, &shown, progress);
+            }
+        }
+    }
+
+    fn search_file(&mut self, path: &Path, shown: &str, progress: &mut Progress) {
+        progress.files_searched += 1;
+        let mut sink = Collector {
+            path: shown,
+            first: progress.matches.len(),
+            progress,
+            max_results: self.max_results,
+            before: Vec::new(),
+        };
+        if let Err(e) = self.searcher.search_path(&self.matcher, path, &mut sink) {
+            tracing::debug!(path = %path.display(), error = %e, "Skipping unreadable file");
+        }
+    }
+}
 
-        if output.status.success() && !stdout.is_empty() {
-            Ok(ToolResult::success(stdout))
-        } else if stdout.is_empty() {
-            Ok(ToolResult::success("No matches found".to_string()))
+/// Glob set for include/exclude patterns; a pattern without `/` matches at any depth
+fn glob_set(patterns: &[&str]) -> std::result::Result<Option<GlobSet>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./");
+        let glob = if pattern.contains('/') {
+            pattern.to_string()
         } else {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            Ok(ToolResult::error(if !stderr.is_empty() {
-                stderr
-            } else {
-                "No matches found".to_string()
-            }))
+            format!("**/{}", pattern)
+        };
+        let glob = GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| format!("Invalid glob: {}", e))
+}
+
+/// Collects the matches of one file
+struct Collector<'a> {
+    path: &'a str,
+    /// Index of this file's first match in `progress.matches`
+    first: usize,
+    progress: &'a mut Progress,
+    max_results: usize,
+    /// Context lines waiting for the next match
+    before: Vec<String>,
+}
+
+impl Sink for Collector<'_> {
+    type Error = std::io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> std::result::Result<bool, Self::Error> {
+        if self.progress.matches.len() >= self.max_results {
+            self.progress.truncated = true;
+            return Ok(false);
+        }
+        self.progress.matches.push(GrepMatch {
+            path: self.path.to_string(),
+            line: mat.line_number().unwrap_or(0),
+            text: line_text(mat.bytes()),
+            before: std::mem::take(&mut self.before),
+            after: Vec::new(),
+        });
+        Ok(true)
+    }
+
+    fn context(&mut self, _searcher: &Searcher, context: &SinkContext<'_>) -> std::result::Result<bool, Self::Error> {
+        let text = line_text(context.bytes());
+        match context.kind() {
+            SinkContextKind::Before => self.before.push(text),
+            SinkContextKind::After => {
+                if self.progress.matches.len() > self.first {
+                    if let Some(last) = self.progress.matches.last_mut() {
+                        last.after.push(text);
+                    }
+                }
+            }
+            SinkContextKind::Other => {}
         }
+        Ok(true)
+    }
+
+    fn context_break(&mut self, _searcher: &Searcher) -> std::result::Result<bool, Self::Error> {
+        self.before.clear();
+        Ok(true)
     }
 }
 
-impl Default for GrepTool {
-    fn default() -> Self {
-        Self::new()
+/// Matched or context text without its line ending, cut to [`MAX_LINE_CHARS`]
+fn line_text(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_end_matches(['\n', '\r']);
+    match text.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn tree() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {\n    let todo = 1;\n    // TODO: more\n}\n").unwrap();
+        fs::write(root.join("src/nested/lib.rs"), "// todo later\npub fn x() {}\n").unwrap();
+        fs::write(root.join("notes.md"), "TODO list\n").unwrap();
+        fs::write(root.join("run.log"), "TODO in a log\n").unwrap();
+        fs::write(root.join("target/out.rs"), "TODO generated\n").unwrap();
+        fs::write(root.join(".hidden/h.txt"), "TODO hidden\n").unwrap();
+        fs::write(root.join("blob.bin"), b"TODO\0\x01\x02").unwrap();
+        dir
+    }
+
+    async fn grep(input: Value) -> Value {
+        let result = GrepTool::new().execute(input).await.unwrap();
+        assert!(!result.is_error, "{}", result.output);
+        serde_json::from_str(&result.output).unwrap()
+    }
+
+    fn paths(output: &Value) -> Vec<String> {
+        output["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| {
+                let path = m["path"].as_str().unwrap();
+                path.rsplit_once(std::path::MAIN_SEPARATOR).map_or(path, |(_, name)| name).to_string()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_grep_respects_gitignore_hidden_and_binary() {
+        let dir = tree();
+        let root = dir.path().to_str().unwrap();
+
+        let output = grep(json!({"pattern": "TODO", "path": root})).await;
+        assert_eq!(paths(&output), vec!["notes.md", "main.rs"]);
+        assert_eq!(output["matches"][1]["line"], 3);
+        assert_eq!(output["matches"][1]["text"], "    // TODO: more");
+        assert_eq!(output["truncated"], false);
+
+        let output = grep(json!({"pattern": "TODO", "path": root, "hidden": true, "no_ignore": true})).await;
+        assert_eq!(paths(&output), vec!["h.txt", "notes.md", "run.log", "main.rs", "out.rs"]);
+    }
+
+    #[tokio::test]
+    async fn test_grep_flags_and_globs() {
+        let dir = tree();
+        let root = dir.path().to_str().unwrap();
+
+        let output = grep(json!({"pattern": "todo", "path": root, "ignore_case": true, "include": ["*.rs"]})).await;
+        assert_eq!(paths(&output), vec!["main.rs", "main.rs", "lib.rs"]);
+
+        let output = grep(json!({"pattern": "todo", "path": root, "ignore_case": true, "word": true, "exclude": ["nested"]})).await;
+        assert_eq!(paths(&output), vec!["notes.md", "main.rs", "main.rs"]);
+
+        let output = grep(json!({"pattern": "x()", "path": root, "fixed_strings": true, "glob": "src/**/*.rs"})).await;
+        assert_eq!(paths(&output), vec!["lib.rs"]);
+
+        let output = grep(json!({"pattern": "main\\(\\) \\{\\n\\s+let", "path": root, "multiline": true})).await;
+        assert_eq!(output["matches"][0]["text"], "fn main() {\n    let todo = 1;");
+    }
+
+    #[tokio::test]
+    async fn test_grep_context_and_max_results() {
+        let dir = tree();
+        let file = dir.path().join("src/main.rs");
+        let file = file.to_str().unwrap();
+
+        let output = grep(json!({"pattern": "todo", "path": file, "before_context": 1, "after_context": 1})).await;
+        let m = &output["matches"][0];
+        assert_eq!(m["path"], file);
+        assert_eq!(m["line"], 2);
+        assert_eq!(m["before"], json!(["fn main() {"]));
+        assert_eq!(m["after"], json!(["    // TODO: more"]));
+
+        let output = grep(json!({"pattern": "TODO|todo", "path": dir.path(), "max_results": 2})).await;
+        assert_eq!(output["matches"].as_array().unwrap().len(), 2);
+        assert_eq!(output["truncated"], true);
+    }
+
+    #[tokio::test]
+    async fn test_grep_errors() {
+        let result = GrepTool::new().execute(json!({"pattern": "(unclosed"})).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("Invalid pattern"));
+
+        let result = GrepTool::new()
+            .execute(json!({"pattern": "x", "path": "/nonexistent/dir"}))
+            .await
+            .unwrap();
+        assert!(result.is_error);
     }
 }
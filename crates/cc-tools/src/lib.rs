@@ -13,6 +13,9 @@ pub mod grep;
 pub mod web_search;
 pub mod web_fetch;
 
+mod diff;
+mod gitignore;
+
 pub use bash::BashTool;
 pub use read::ReadTool;
 pub use write::WriteTool;
@@ -26,7 +29,7 @@ use std::sync::Arc;
 
 /// Register all default built-in tools with the tool manager
 pub fn register_default_tools(manager: &mut ToolManager) {
-    manager.register(Arc::new(BashTool));
+    manager.register(Arc::new(BashTool::new()));
     manager.register(Arc::new(ReadTool));
     manager.register(Arc::new(WriteTool));
     manager.register(Arc::new(EditTool));
@@ -1,10 +1,20 @@
 //! Read tool for reading file contents
+//!
+//! Text files are returned as lines, images as image blocks the model can
+//! look at, and other binary files are refused.
 
 use async_trait::async_trait;
+use cc_core::llm::ImageSource;
 use cc_core::{Result, Tool, ToolResult};
 use serde_json::{json, Value};
 use tokio::fs;
 
+/// Largest image returned as an image block, in bytes
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Leading bytes checked for NUL when telling text from binary
+const SNIFF_BYTES: usize = 8000;
+
 /// Read tool for reading file contents
 pub struct ReadTool;
 
@@ -22,7 +32,9 @@ impl Tool for ReadTool {
     }
 
     fn description(&self) -> &str {
-        "Read a file from the filesystem. Supports line ranges for partial reading."
+        "Read a file from the filesystem. Supports line ranges for partial reading; \
+         lines are prefixed with their line number (\"12: ...\"), which is not part of the file. \
+         Images (PNG, JPEG, GIF, WebP) are returned as images; other binary files are refused."
     }
 
     fn input_schema(&self) -> Value {
@@ -35,10 +47,12 @@ impl Tool for ReadTool {
                 },
                 "offset": {
                     "type": "integer",
+                    "minimum": 1,
                     "description": "Line number to start reading from (1-indexed)"
                 },
                 "limit": {
                     "type": "integer",
+                    "minimum": 1,
                     "description": "Maximum number of lines to read"
                 }
             },
@@ -46,6 +60,10 @@ impl Tool for ReadTool {
         })
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let path = input["path"].as_str().ok_or_else(|| {
             cc_core::Error::ToolExecution("Missing 'path' parameter".to_string())
@@ -56,30 +74,79 @@ impl Tool for ReadTool {
 
         tracing::debug!(path = %path, offset = offset, limit = ?limit, "Reading file");
 
-        match fs::read_to_string(path).await {
-            Ok(content) => {
-                let lines: Vec<&str> = content.lines().collect();
-
-                // Apply offset (1-indexed to 0-indexed)
-                let start = if offset > 0 { offset - 1 } else { 0 };
-                if start >= lines.len() {
-                    return Ok(ToolResult::success("(empty or beyond file end)".to_string()));
-                }
+        let bytes = match fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return Ok(ToolResult::error(format!("Failed to read file '{}': {}", path, e)));
+            }
+        };
+
+        if let Some(media_type) = image_media_type(&bytes) {
+            if bytes.len() > MAX_IMAGE_BYTES {
+                return Ok(ToolResult::error(format!(
+                    "Image '{}' is too large ({} bytes, max {})",
+                    path,
+                    bytes.len(),
+                    MAX_IMAGE_BYTES
+                )));
+            }
+            return Ok(ToolResult::success(format!("Image '{}' ({}, {} bytes)", path, media_type, bytes.len()))
+                .with_image(ImageSource::from_bytes(media_type, &bytes)));
+        }
 
-                let end = match limit {
-                    Some(l) => (start + l as usize).min(lines.len()),
-                    None => lines.len(),
-                };
+        let len = bytes.len();
+        let has_nul = bytes[..len.min(SNIFF_BYTES)].contains(&0);
+        let content = match String::from_utf8(bytes) {
+            Ok(content) if !has_nul => content,
+            _ => {
+                return Ok(ToolResult::error(format!(
+                    "'{}' is a binary file ({} bytes) and cannot be shown as text",
+                    path, len
+                )));
+            }
+        };
+        let lines: Vec<&str> = content.lines().collect();
 
-                let selected_lines: Vec<&str> = lines[start..end].to_vec();
-                let result = selected_lines.join("\n");
+        // Apply offset (1-indexed to 0-indexed)
+        let start = if offset > 0 { offset - 1 } else { 0 };
+        if start >= lines.len() {
+            return Ok(ToolResult::success("(empty or beyond file end)".to_string()));
+        }
 
-                Ok(ToolResult::success(result))
-            }
-            Err(e) => {
-                Ok(ToolResult::error(format!("Failed to read file '{}': {}", path, e)))
-            }
+        let end = match limit {
+            Some(l) => (start + l as usize).min(lines.len()),
+            None => lines.len(),
+        };
+
+        let mut result: Vec<String> = lines[start..end]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| format!("{}: {}", start + i + 1, line))
+            .collect();
+        if end < lines.len() {
+            result.push(format!(
+                "… ({} more lines; continue with offset={})",
+                lines.len() - end,
+                end + 1
+            ));
         }
+
+        Ok(ToolResult::success(result.join("\n")))
+    }
+}
+
+/// Media type of an image the model can view, by its leading bytes
+fn image_media_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(ImageSource::MEDIA_TYPE_PNG)
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageSource::MEDIA_TYPE_JPEG)
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(ImageSource::MEDIA_TYPE_GIF)
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some(ImageSource::MEDIA_TYPE_WEBP)
+    } else {
+        None
     }
 }
 
@@ -153,9 +220,10 @@ mod tests {
         let result = tool.execute(input).await.unwrap();
 
         assert!(!result.is_error);
-        assert!(result.output.contains("Line 1"));
-        assert!(result.output.contains("Line 2"));
+        assert!(result.output.contains("1: Line 1"));
+        assert!(result.output.contains("2: Line 2"));
         assert!(!result.output.contains("Line 3"));
+        assert!(result.output.ends_with("… (1 more lines; continue with offset=3)"));
     }
 
     #[tokio::test]
@@ -169,6 +237,41 @@ mod tests {
         assert!(result.output.contains("Failed to read file"));
     }
 
+    #[tokio::test]
+    async fn test_read_image() {
+        let tool = ReadTool::new();
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+
+        let input = json!({"path": temp_file.path().to_str().unwrap()});
+        let result = tool.execute(input).await.unwrap();
+
+        assert!(!result.is_error);
+        assert!(result.output.contains("image/png"));
+        assert_eq!(result.images.len(), 1);
+        assert_eq!(result.images[0].media_type, "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_read_binary_file() {
+        let tool = ReadTool::new();
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"ELF\0\x01\x02").unwrap();
+        let input = json!({"path": temp_file.path().to_str().unwrap()});
+        let result = tool.execute(input).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("binary file (6 bytes)"));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&[0x66, 0x6f, 0xff, 0xfe]).unwrap();
+        let input = json!({"path": temp_file.path().to_str().unwrap()});
+        let result = tool.execute(input).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.images.is_empty());
+    }
+
     #[tokio::test]
     async fn test_read_missing_path() {
         let tool = ReadTool::new();
@@ -323,6 +323,10 @@ impl Tool for WebFetchTool {
         })
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let fetch_input: FetchInput = serde_json::from_value(input).map_err(|e| {
             cc_core::Error::ToolExecution(format!("Invalid input parameters: {}", e))
@@ -280,6 +280,10 @@ impl Tool for WebSearchTool {
         })
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let search_input: SearchInput =
             serde_json::from_value(input).map_err(|e| {
@@ -5,6 +5,8 @@ use cc_core::{Result, Tool, ToolResult};
 use serde_json::{json, Value};
 use tokio::fs;
 
+use crate::diff::unified_diff;
+
 /// Write tool for creating/overwriting files
 pub struct WriteTool;
 
@@ -42,6 +44,21 @@ impl Tool for WriteTool {
         })
     }
 
+    async fn preview(&self, input: &Value) -> Option<String> {
+        let path = input["path"].as_str()?;
+        let content = input["content"].as_str()?;
+        match fs::read_to_string(path).await {
+            Ok(old) if old == content => Some(format!("'{}' already has this content (no change)", path)),
+            Ok(old) => Some(unified_diff(path, &old, content)),
+            Err(_) => Some(format!(
+                "Create '{}' ({} bytes)\n{}",
+                path,
+                content.len(),
+                unified_diff(path, "", content)
+            )),
+        }
+    }
+
     async fn execute(&self, input: Value) -> Result<ToolResult> {
         let path = input["path"].as_str().ok_or_else(|| {
             cc_core::Error::ToolExecution("Missing 'path' parameter".to_string())
@@ -158,6 +175,24 @@ mod tests {
         assert_eq!(content, "New content");
     }
 
+    #[tokio::test]
+    async fn test_write_preview() {
+        let tool = WriteTool::new();
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        let path = file_path.to_str().unwrap();
+
+        let preview = tool.preview(&json!({"path": path, "content": "one\n"})).await.unwrap();
+        assert!(preview.starts_with("Create '"));
+        assert!(preview.ends_with("+one"));
+        assert!(!file_path.exists());
+
+        fs::write(&file_path, "one\ntwo\n").unwrap();
+        let preview = tool.preview(&json!({"path": path, "content": "one\n2\n"})).await.unwrap();
+        assert!(preview.ends_with("@@ -1,2 +1,2 @@\n one\n-two\n+2"));
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "one\ntwo\n");
+    }
+
     #[tokio::test]
     async fn test_write_missing_path() {
         let tool = WriteTool::new();
@@ -3,20 +3,26 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use cc_voice::WhisperClient;
+
+use crate::cloud::{CloudApiClient, CloudApiConfig};
 use crate::error::Result;
+use crate::provider::{MediaKind, TemplateMessage, WhatsAppProvider};
 use crate::twilio::TwilioClient;
 use crate::webhook::WebhookServer;
 
 /// WhatsApp bot wrapper
 pub struct WhatsAppBot {
-    twilio_client: Arc<TwilioClient>,
+    provider: WhatsAppProvider,
     claude_client: Arc<cc_core::ClaudeClient>,
     admin_numbers: Vec<String>,
     port: u16,
+    transcriber: Option<Arc<WhisperClient>>,
+    public_url: Option<String>,
 }
 
 impl WhatsAppBot {
-    /// Create a new WhatsApp bot
+    /// Create a new WhatsApp bot using Twilio
     pub fn new(
         account_sid: &str,
         auth_token: &str,
@@ -32,28 +38,104 @@ impl WhatsAppBot {
         ));
 
         Self {
-            twilio_client,
+            provider: WhatsAppProvider::Twilio(twilio_client),
             claude_client,
             admin_numbers,
             port,
+            transcriber: None,
+            public_url: None,
         }
     }
 
+    /// Create a new WhatsApp bot using the WhatsApp Business Cloud API
+    pub fn cloud(
+        config: CloudApiConfig,
+        claude_client: Arc<cc_core::ClaudeClient>,
+        admin_numbers: Vec<String>,
+        port: u16,
+    ) -> Self {
+        Self {
+            provider: WhatsAppProvider::Cloud(Arc::new(CloudApiClient::new(config))),
+            claude_client,
+            admin_numbers,
+            port,
+            transcriber: None,
+            public_url: None,
+        }
+    }
+
+    /// Transcribe voice and audio messages with `transcriber`
+    pub fn with_transcriber(mut self, transcriber: Arc<WhisperClient>) -> Self {
+        self.transcriber = Some(transcriber);
+        self
+    }
+
+    /// Set the public webhook URL Twilio signs (see
+    /// [`WebhookServer::with_public_url`])
+    pub fn with_public_url(mut self, url: impl Into<String>) -> Self {
+        self.public_url = Some(url.into());
+        self
+    }
+
     /// Start the bot (webhook server)
     pub async fn start(self) -> Result<()> {
         let addr: SocketAddr = ([0, 0, 0, 0], self.port).into();
-        let server = WebhookServer::new(
+        let mut server = WebhookServer::new(
             addr,
-            self.twilio_client,
+            self.provider,
             self.claude_client,
             self.admin_numbers,
         );
+        if let Some(transcriber) = self.transcriber {
+            server = server.with_transcriber(transcriber);
+        }
+        if let Some(url) = self.public_url {
+            server = server.with_public_url(url);
+        }
 
         server.start().await
     }
 
-    /// Get the Twilio client for direct use
-    pub fn twilio_client(&self) -> Arc<TwilioClient> {
-        Arc::clone(&self.twilio_client)
+    /// Send a text message (within the 24 hour session window)
+    pub async fn send_text(&self, to: &str, body: &str) -> Result<String> {
+        self.provider.send_text(to, body).await
+    }
+
+    /// Send an image, audio, video or document from a public URL
+    pub async fn send_media(
+        &self,
+        to: &str,
+        kind: MediaKind,
+        url: &str,
+        caption: Option<&str>,
+    ) -> Result<String> {
+        self.provider.send_media(to, kind, url, caption, None).await
+    }
+
+    /// Send a pre-approved template, e.g. a notification outside the
+    /// 24 hour session window
+    pub async fn send_template(&self, to: &str, template: &TemplateMessage) -> Result<String> {
+        self.provider.send_template(to, template).await
+    }
+
+    /// Get the backend for direct use
+    pub fn provider(&self) -> &WhatsAppProvider {
+        &self.provider
+    }
+
+    /// Get the Twilio client for direct use (None with the Cloud API)
+    pub fn twilio_client(&self) -> Option<Arc<TwilioClient>> {
+        match self.provider {
+            WhatsAppProvider::Twilio(ref client) => Some(Arc::clone(client)),
+            WhatsAppProvider::Cloud(_) => None,
+        }
+    }
+
+    /// Get the Cloud API client for direct use (None with Twilio)
+    pub fn cloud_client(&self) -> Option<Arc<CloudApiClient>> {
+        match self.provider {
+            WhatsAppProvider::Cloud(ref client) => Some(Arc::clone(client)),
+            WhatsAppProvider::Twilio(_) => None,
+        }
     }
 }
@@ -0,0 +1,504 @@
+//! WhatsApp Business Cloud API client (Meta)
+//!
+//! Sends messages through the Graph API, verifies webhooks (the
+//! `hub.challenge` handshake and the `X-Hub-Signature-256` header), and
+//! uploads and downloads media.
+
+use cc_core::WebhookVerifier;
+use reqwest::header::HeaderMap;
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{debug, error, info, warn};
+
+use crate::error::{Result, WhatsAppError};
+
+/// Graph API base URL
+const GRAPH_API_URL: &str = "https://graph.facebook.com/v21.0";
+
+/// Cloud API settings
+#[derive(Debug, Clone, Default)]
+pub struct CloudApiConfig {
+    /// System user or app access token
+    pub access_token: String,
+    /// Phone number ID of the business number
+    pub phone_number_id: String,
+    /// Token entered when registering the webhook
+    pub verify_token: String,
+    /// App secret for webhook signatures (None = signatures not checked)
+    pub app_secret: Option<String>,
+}
+
+impl CloudApiConfig {
+    /// Read the settings from `WHATSAPP_ACCESS_TOKEN`,
+    /// `WHATSAPP_PHONE_NUMBER_ID`, `WHATSAPP_VERIFY_TOKEN` and
+    /// `WHATSAPP_APP_SECRET` (optional)
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        match (
+            var("WHATSAPP_ACCESS_TOKEN"),
+            var("WHATSAPP_PHONE_NUMBER_ID"),
+            var("WHATSAPP_VERIFY_TOKEN"),
+        ) {
+            (Some(access_token), Some(phone_number_id), Some(verify_token)) => Ok(Self {
+                access_token,
+                phone_number_id,
+                verify_token,
+                app_secret: var("WHATSAPP_APP_SECRET"),
+            }),
+            _ => Err(WhatsAppError::CredentialsNotSet),
+        }
+    }
+}
+
+/// WhatsApp Business Cloud API client
+#[derive(Debug, Clone)]
+pub struct CloudApiClient {
+    client: Client,
+    config: CloudApiConfig,
+    base_url: String,
+}
+
+/// Media attached to an incoming message
+#[derive(Debug, Clone, Deserialize)]
+pub struct CloudMedia {
+    pub id: String,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    /// File name (documents only)
+    #[serde(default)]
+    pub filename: Option<String>,
+    #[serde(default)]
+    pub caption: Option<String>,
+}
+
+/// Text of an incoming text message
+#[derive(Debug, Clone, Deserialize)]
+pub struct CloudText {
+    pub body: String,
+}
+
+/// Incoming message from the Cloud API webhook
+#[derive(Debug, Clone, Deserialize)]
+pub struct CloudMessage {
+    /// Sender's WhatsApp ID (phone number without `+`)
+    pub from: String,
+    pub id: String,
+    /// `text`, `image`, `audio`, `document`, ...
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub text: Option<CloudText>,
+    #[serde(default)]
+    pub image: Option<CloudMedia>,
+    #[serde(default)]
+    pub audio: Option<CloudMedia>,
+    #[serde(default)]
+    pub document: Option<CloudMedia>,
+    #[serde(default)]
+    pub video: Option<CloudMedia>,
+}
+
+impl CloudMessage {
+    /// The attached media, if any
+    pub fn media(&self) -> Option<&CloudMedia> {
+        self.image
+            .as_ref()
+            .or(self.document.as_ref())
+            .or(self.audio.as_ref())
+            .or(self.video.as_ref())
+    }
+
+    /// The message text, or the media caption
+    pub fn text(&self) -> &str {
+        self.text
+            .as_ref()
+            .map(|t| t.body.as_str())
+            .or_else(|| self.media().and_then(|m| m.caption.as_deref()))
+            .unwrap_or("")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookPayload {
+    #[serde(default)]
+    entry: Vec<WebhookEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookEntry {
+    #[serde(default)]
+    changes: Vec<WebhookChange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookChange {
+    #[serde(default)]
+    field: String,
+    value: WebhookValue,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookValue {
+    /// Delivery statuses arrive without messages
+    #[serde(default)]
+    messages: Vec<CloudMessage>,
+}
+
+/// Message template component parameter set (see the Cloud API docs)
+pub type TemplateComponent = Value;
+
+impl CloudApiClient {
+    /// Create a new Cloud API client
+    pub fn new(config: CloudApiConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            base_url: GRAPH_API_URL.to_string(),
+        }
+    }
+
+    /// Use another Graph API URL (for testing)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Answer the webhook verification request
+    ///
+    /// Returns the challenge to echo back when `mode` is `subscribe` and
+    /// the token matches.
+    pub fn verify_webhook(&self, mode: &str, token: &str, challenge: &str) -> Result<String> {
+        if mode == "subscribe" && token == self.config.verify_token {
+            info!("WhatsApp webhook verified");
+            Ok(challenge.to_string())
+        } else {
+            error!("WhatsApp webhook verification failed: invalid mode or token");
+            Err(WhatsAppError::SignatureVerificationFailed)
+        }
+    }
+
+    /// Check the `X-Hub-Signature-256` header of a webhook request
+    ///
+    /// Fails when no app secret is configured, so unsigned requests are
+    /// never accepted.
+    pub fn verify_signature(&self, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        let Some(ref secret) = self.config.app_secret else {
+            return Err(WhatsAppError::AppSecretNotSet);
+        };
+        let verifier = WebhookVerifier::Meta {
+            app_secret: secret.clone(),
+        };
+        verifier.verify(headers, "", body).map_err(|e| {
+            warn!("Rejected WhatsApp webhook: {}", e);
+            WhatsAppError::SignatureVerificationFailed
+        })
+    }
+
+    /// Messages in a webhook body (delivery statuses are skipped)
+    pub fn parse_webhook(&self, body: &[u8]) -> Result<Vec<CloudMessage>> {
+        let payload: WebhookPayload =
+            serde_json::from_slice(body).map_err(|e| WhatsAppError::InvalidPayload(e.to_string()))?;
+        Ok(payload
+            .entry
+            .into_iter()
+            .flat_map(|entry| entry.changes)
+            .filter(|change| change.field == "messages")
+            .flat_map(|change| change.value.messages)
+            .collect())
+    }
+
+    /// Send a message object, returning the message ID
+    async fn send(&self, to: &str, message: Value) -> Result<String> {
+        let url = format!("{}/{}/messages", self.base_url, self.config.phone_number_id);
+
+        let mut payload = json!({
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": to,
+        });
+        if let (Some(payload), Some(message)) = (payload.as_object_mut(), message.as_object()) {
+            payload.extend(message.clone());
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.config.access_token)
+            .json(&payload)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            error!("WhatsApp Cloud API error: {} - {}", status, text);
+            return Err(WhatsAppError::Api(format!(
+                "Failed to send message: {} - {}",
+                status, text
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct SentMessage {
+            id: String,
+        }
+        #[derive(Deserialize)]
+        struct SendResponse {
+            messages: Vec<SentMessage>,
+        }
+
+        let result: SendResponse = response.json().await?;
+        result
+            .messages
+            .into_iter()
+            .next()
+            .map(|m| m.id)
+            .ok_or_else(|| WhatsAppError::Api("No message ID in response".to_string()))
+    }
+
+    /// Send a text message
+    pub async fn send_text(&self, to: &str, body: &str) -> Result<String> {
+        info!("Sending WhatsApp Cloud message to {}", to);
+        self.send(to, json!({ "type": "text", "text": { "body": body } }))
+            .await
+    }
+
+    /// Send a template message
+    ///
+    /// Outside the 24 hour customer service window only approved templates
+    /// can be sent. `components` fill the template's parameters.
+    pub async fn send_template(
+        &self,
+        to: &str,
+        name: &str,
+        language: &str,
+        components: Vec<TemplateComponent>,
+    ) -> Result<String> {
+        info!("Sending WhatsApp template {} to {}", name, to);
+        let mut template = json!({
+            "name": name,
+            "language": { "code": language },
+        });
+        if !components.is_empty() {
+            template["components"] = Value::Array(components);
+        }
+        self.send(to, json!({ "type": "template", "template": template }))
+            .await
+    }
+
+    /// Send uploaded media (`kind` is `image`, `document`, `audio` or `video`)
+    pub async fn send_media(
+        &self,
+        to: &str,
+        kind: &str,
+        media_id: &str,
+        caption: Option<&str>,
+        filename: Option<&str>,
+    ) -> Result<String> {
+        self.send_media_object(to, kind, json!({ "id": media_id }), caption, filename)
+            .await
+    }
+
+    /// Send media by public URL (WhatsApp fetches it)
+    pub async fn send_media_link(
+        &self,
+        to: &str,
+        kind: &str,
+        link: &str,
+        caption: Option<&str>,
+        filename: Option<&str>,
+    ) -> Result<String> {
+        self.send_media_object(to, kind, json!({ "link": link }), caption, filename)
+            .await
+    }
+
+    async fn send_media_object(
+        &self,
+        to: &str,
+        kind: &str,
+        mut media: Value,
+        caption: Option<&str>,
+        filename: Option<&str>,
+    ) -> Result<String> {
+        // Audio messages take neither caption nor file name
+        if kind != "audio" {
+            if let Some(caption) = caption {
+                media["caption"] = json!(caption);
+            }
+        }
+        if kind == "document" {
+            if let Some(filename) = filename {
+                media["filename"] = json!(filename);
+            }
+        }
+        self.send(to, json!({ "type": kind, kind: media })).await
+    }
+
+    /// Upload media, returning its media ID
+    pub async fn upload_media(&self, bytes: Vec<u8>, mime_type: &str, filename: &str) -> Result<String> {
+        let url = format!("{}/{}/media", self.base_url, self.config.phone_number_id);
+
+        debug!("Uploading {} ({}, {} bytes)", filename, mime_type, bytes.len());
+
+        let part = Part::bytes(bytes)
+            .file_name(filename.to_string())
+            .mime_str(mime_type)?;
+        let form = Form::new()
+            .text("messaging_product", "whatsapp")
+            .text("type", mime_type.to_string())
+            .part("file", part);
+
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.config.access_token)
+            .multipart(form)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(WhatsAppError::Api(format!(
+                "Failed to upload media: {} - {}",
+                status, text
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct UploadResponse {
+            id: String,
+        }
+
+        let result: UploadResponse = response.json().await?;
+        Ok(result.id)
+    }
+
+    /// Download media by ID, returning the bytes and MIME type
+    pub async fn download_media(&self, media_id: &str) -> Result<(Vec<u8>, String)> {
+        let url = format!("{}/{}", self.base_url, media_id);
+
+        #[derive(Deserialize)]
+        struct MediaInfo {
+            url: String,
+            #[serde(default)]
+            mime_type: String,
+        }
+
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(&self.config.access_token)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(WhatsAppError::Api(format!(
+                "Failed to get media URL: {} - {}",
+                status, text
+            )));
+        }
+        let info: MediaInfo = response.json().await?;
+
+        // The media URL also requires the access token
+        let response = self
+            .client
+            .get(&info.url)
+            .bearer_auth(&self.config.access_token)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(WhatsAppError::Api(format!(
+                "Failed to download media: {}",
+                response.status()
+            )));
+        }
+        let bytes = response.bytes().await?;
+        Ok((bytes.to_vec(), info.mime_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(app_secret: Option<&str>) -> CloudApiClient {
+        CloudApiClient::new(CloudApiConfig {
+            access_token: "token".to_string(),
+            phone_number_id: "1234".to_string(),
+            verify_token: "verify".to_string(),
+            app_secret: app_secret.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn test_verify_webhook() {
+        let client = client(None);
+        assert_eq!(client.verify_webhook("subscribe", "verify", "42").unwrap(), "42");
+        assert!(client.verify_webhook("subscribe", "wrong", "42").is_err());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+
+        let body = br#"{"object":"whatsapp_business_account"}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(body);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        let mut signed = HeaderMap::new();
+        signed.insert("x-hub-signature-256", signature.parse().unwrap());
+
+        let client = client(Some("secret"));
+        assert!(client.verify_signature(&signed, body).is_ok());
+        assert!(client.verify_signature(&signed, b"tampered").is_err());
+        assert!(client.verify_signature(&HeaderMap::new(), body).is_err());
+
+        // Without an app secret nothing is accepted
+        assert!(matches!(
+            self::client(None).verify_signature(&signed, body),
+            Err(WhatsAppError::AppSecretNotSet)
+        ));
+    }
+
+    #[test]
+    fn test_parse_webhook() {
+        let body = br#"{
+            "object": "whatsapp_business_account",
+            "entry": [{
+                "id": "WABA_ID",
+                "changes": [{
+                    "field": "messages",
+                    "value": {
+                        "messaging_product": "whatsapp",
+                        "metadata": {"display_phone_number": "15550001111", "phone_number_id": "1234"},
+                        "contacts": [{"profile": {"name": "Alice"}, "wa_id": "15551234567"}],
+                        "messages": [
+                            {"from": "15551234567", "id": "wamid.1", "timestamp": "1700000000", "type": "text", "text": {"body": "Hello"}},
+                            {"from": "15551234567", "id": "wamid.2", "timestamp": "1700000001", "type": "image",
+                             "image": {"id": "media1", "mime_type": "image/jpeg", "sha256": "x", "caption": "What is this?"}}
+                        ]
+                    }
+                }, {
+                    "field": "messages",
+                    "value": {"messaging_product": "whatsapp", "statuses": [{"id": "wamid.0", "status": "delivered"}]}
+                }]
+            }]
+        }"#;
+
+        let messages = client(None).parse_webhook(body).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].text(), "Hello");
+        assert!(messages[0].media().is_none());
+        assert_eq!(messages[1].kind, "image");
+        assert_eq!(messages[1].text(), "What is this?");
+        assert_eq!(messages[1].media().unwrap().id, "media1");
+
+        assert!(client(None).parse_webhook(b"not json").is_err());
+    }
+}
@@ -5,16 +5,19 @@ use thiserror::Error;
 /// cc-whatsapp error type
 #[derive(Error, Debug)]
 pub enum WhatsAppError {
-    #[error("Twilio credentials not set")]
+    #[error("WhatsApp credentials not set")]
     CredentialsNotSet,
 
     #[error("Webhook signature verification failed")]
     SignatureVerificationFailed,
 
+    #[error("WhatsApp app secret not set")]
+    AppSecretNotSet,
+
     #[error("Invalid webhook payload: {0}")]
     InvalidPayload(String),
 
-    #[error("Twilio API error: {0}")]
+    #[error("WhatsApp API error: {0}")]
     Api(String),
 
     #[error("HTTP error: {0}")]
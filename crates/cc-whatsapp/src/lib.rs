@@ -1,16 +1,20 @@
-//! cc-whatsapp: WhatsApp Bot for cc-gateway via Twilio API
+//! cc-whatsapp: WhatsApp Bot for cc-gateway
 //!
-//! This crate provides WhatsApp bot integration for cc-gateway,
-//! using Twilio's WhatsApp Business API.
+//! This crate provides WhatsApp bot integration for cc-gateway, using
+//! either Twilio's WhatsApp API or Meta's WhatsApp Business Cloud API.
 
 pub mod bot;
+pub mod cloud;
 pub mod error;
+pub mod provider;
 pub mod session;
 pub mod twilio;
 pub mod webhook;
 
 pub use bot::WhatsAppBot;
+pub use cloud::{CloudApiClient, CloudApiConfig};
 pub use error::{Result, WhatsAppError};
+pub use provider::{MediaKind, TemplateMessage, WhatsAppProvider};
 pub use session::InMemorySessionStore;
-pub use twilio::TwilioClient;
+pub use twilio::{TwilioClient, TwilioMedia};
 pub use webhook::WebhookServer;
@@ -0,0 +1,207 @@
+//! WhatsApp backends
+//!
+//! Messages go through either Twilio or Meta's WhatsApp Business Cloud API.
+//! Both receive messages on `/webhook/whatsapp`; Twilio posts forms, the
+//! Cloud API posts JSON after a GET verification handshake.
+
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::cloud::CloudApiClient;
+use crate::error::Result;
+use crate::twilio::TwilioClient;
+
+/// Kind of outgoing media
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    Audio,
+    Video,
+    Document,
+}
+
+impl MediaKind {
+    /// Cloud API message type
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::Video => "video",
+            Self::Document => "document",
+        }
+    }
+
+    /// Kind for a MIME type (anything else is sent as a document)
+    pub fn from_mime(mime_type: &str) -> Self {
+        match mime_type.split('/').next().unwrap_or_default() {
+            "image" => Self::Image,
+            "audio" => Self::Audio,
+            "video" => Self::Video,
+            _ => Self::Document,
+        }
+    }
+}
+
+/// A pre-approved template message
+///
+/// WhatsApp only delivers free-form messages within 24 hours of the user's
+/// last message; notifications outside that window must use a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateMessage {
+    /// Template name (Cloud API) or Content SID `HX...` (Twilio)
+    pub name: String,
+    /// Template language code, e.g. `en_US` (Cloud API only)
+    pub language: String,
+    /// Values of the body parameters `{{1}}`, `{{2}}`, ...
+    pub variables: Vec<String>,
+}
+
+impl TemplateMessage {
+    /// Create a template message without parameters
+    pub fn new(name: impl Into<String>, language: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            language: language.into(),
+            variables: Vec::new(),
+        }
+    }
+
+    /// Set the body parameters
+    pub fn with_variables(mut self, variables: Vec<String>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Cloud API components filling the body parameters
+    fn cloud_components(&self) -> Vec<serde_json::Value> {
+        if self.variables.is_empty() {
+            return Vec::new();
+        }
+        let parameters: Vec<_> = self
+            .variables
+            .iter()
+            .map(|v| json!({ "type": "text", "text": v }))
+            .collect();
+        vec![json!({ "type": "body", "parameters": parameters })]
+    }
+}
+
+/// WhatsApp backend
+#[derive(Debug, Clone)]
+pub enum WhatsAppProvider {
+    /// Twilio's WhatsApp API
+    Twilio(Arc<TwilioClient>),
+    /// Meta's WhatsApp Business Cloud API
+    Cloud(Arc<CloudApiClient>),
+}
+
+impl WhatsAppProvider {
+    /// Provider name for logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Twilio(_) => "Twilio",
+            Self::Cloud(_) => "Cloud API",
+        }
+    }
+
+    /// Send a text message to `to` (the sender as the provider gave it),
+    /// returning the message ID
+    pub async fn send_text(&self, to: &str, body: &str) -> Result<String> {
+        match self {
+            Self::Twilio(client) => client.send_message(to, body).await,
+            Self::Cloud(client) => client.send_text(to, body).await,
+        }
+    }
+
+    /// Send media from a public URL, returning the message ID
+    ///
+    /// The caption is ignored for audio. `filename` is shown for documents
+    /// (Cloud API only).
+    pub async fn send_media(
+        &self,
+        to: &str,
+        kind: MediaKind,
+        url: &str,
+        caption: Option<&str>,
+        filename: Option<&str>,
+    ) -> Result<String> {
+        let caption = caption.filter(|_| kind != MediaKind::Audio);
+        match self {
+            Self::Twilio(client) => client.send_media(to, url, caption).await,
+            Self::Cloud(client) => {
+                client
+                    .send_media_link(to, kind.as_str(), url, caption, filename)
+                    .await
+            }
+        }
+    }
+
+    /// Send a template message, returning the message ID
+    pub async fn send_template(&self, to: &str, template: &TemplateMessage) -> Result<String> {
+        match self {
+            Self::Twilio(client) => {
+                client
+                    .send_template(to, &template.name, &template.variables)
+                    .await
+            }
+            Self::Cloud(client) => {
+                client
+                    .send_template(to, &template.name, &template.language, template.cloud_components())
+                    .await
+            }
+        }
+    }
+}
+
+/// Phone number digits, for comparing numbers across providers
+///
+/// Twilio senders look like `whatsapp:+15551234567`, Cloud API senders like
+/// `15551234567`.
+pub fn normalize_number(number: &str) -> String {
+    number.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// Whether `from` may use the bot (an empty list allows everyone)
+pub fn is_allowed(admin_numbers: &[String], from: &str) -> bool {
+    if admin_numbers.is_empty() {
+        return true;
+    }
+    let from = normalize_number(from);
+    admin_numbers.iter().any(|n| normalize_number(n) == from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_allowed() {
+        let admins = vec!["+1 555 123 4567".to_string()];
+        assert!(is_allowed(&admins, "whatsapp:+15551234567"));
+        assert!(is_allowed(&admins, "15551234567"));
+        assert!(!is_allowed(&admins, "15557654321"));
+        assert!(is_allowed(&[], "15557654321"));
+    }
+
+    #[test]
+    fn test_media_kind_and_template() {
+        assert_eq!(MediaKind::from_mime("image/png"), MediaKind::Image);
+        assert_eq!(MediaKind::from_mime("audio/ogg"), MediaKind::Audio);
+        assert_eq!(MediaKind::from_mime("application/pdf"), MediaKind::Document);
+
+        let template = TemplateMessage::new("reminder", "en_US");
+        assert!(template.cloud_components().is_empty());
+        let template = template.with_variables(vec!["Alice".to_string(), "3pm".to_string()]);
+        assert_eq!(
+            template.cloud_components(),
+            vec![json!({
+                "type": "body",
+                "parameters": [
+                    { "type": "text", "text": "Alice" },
+                    { "type": "text", "text": "3pm" },
+                ],
+            })]
+        );
+    }
+}
@@ -1,7 +1,11 @@
 //! Twilio API client for WhatsApp
 
+use std::collections::HashMap;
+
+use cc_core::WebhookVerifier;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use tracing::info;
 
 use crate::error::{Result, WhatsAppError};
@@ -19,21 +23,59 @@ pub struct TwilioClient {
 /// Incoming WhatsApp message from Twilio webhook
 #[derive(Debug, Deserialize)]
 pub struct IncomingMessage {
+    #[serde(rename = "From")]
     pub from: String,
+    #[serde(rename = "To")]
     pub to: String,
+    #[serde(rename = "Body", default)]
     pub body: String,
     #[serde(rename = "MessageSid")]
     pub message_sid: String,
     #[serde(rename = "AccountSid")]
     pub account_sid: String,
+    /// Other parameters (`NumMedia`, `MediaUrl0`, `MediaContentType0`, ...)
+    #[serde(flatten)]
+    pub params: HashMap<String, String>,
+}
+
+/// Media attached to an incoming message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TwilioMedia {
+    pub url: String,
+    pub content_type: String,
 }
 
-/// Outgoing message payload
-#[derive(Debug, Serialize)]
-struct SendMessagePayload {
-    from: String,
-    to: String,
-    body: String,
+impl IncomingMessage {
+    /// The attached media
+    pub fn media(&self) -> Vec<TwilioMedia> {
+        let count: usize = self
+            .params
+            .get("NumMedia")
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0);
+        (0..count)
+            .filter_map(|i| {
+                let url = self.params.get(&format!("MediaUrl{}", i))?;
+                Some(TwilioMedia {
+                    url: url.clone(),
+                    content_type: self
+                        .params
+                        .get(&format!("MediaContentType{}", i))
+                        .cloned()
+                        .unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `whatsapp:`-prefixed address Twilio expects
+fn whatsapp_address(number: &str) -> String {
+    if number.starts_with("whatsapp:") {
+        number.to_string()
+    } else {
+        format!("whatsapp:{}", number)
+    }
 }
 
 impl TwilioClient {
@@ -51,23 +93,47 @@ impl TwilioClient {
     /// Send a WhatsApp message
     pub async fn send_message(&self, to: &str, body: &str) -> Result<String> {
         info!("Sending WhatsApp message to {}", to);
+        self.send(to, vec![("Body", body.to_string())]).await
+    }
+
+    /// Send media by public URL (Twilio fetches it), with an optional caption
+    pub async fn send_media(&self, to: &str, media_url: &str, caption: Option<&str>) -> Result<String> {
+        info!("Sending WhatsApp media to {}", to);
+        let mut params = vec![("MediaUrl", media_url.to_string())];
+        if let Some(caption) = caption {
+            params.push(("Body", caption.to_string()));
+        }
+        self.send(to, params).await
+    }
 
+    /// Send a pre-approved template (Content API)
+    ///
+    /// Outside the 24 hour session window WhatsApp only delivers approved
+    /// templates. `variables` fill the template's `{{1}}`, `{{2}}`, ...
+    pub async fn send_template(&self, to: &str, content_sid: &str, variables: &[String]) -> Result<String> {
+        info!("Sending WhatsApp template {} to {}", content_sid, to);
+        let mut params = vec![("ContentSid", content_sid.to_string())];
+        if !variables.is_empty() {
+            params.push(("ContentVariables", content_variables(variables)));
+        }
+        self.send(to, params).await
+    }
+
+    /// Create a message with `params`, returning its SID
+    async fn send(&self, to: &str, mut params: Vec<(&str, String)>) -> Result<String> {
         let url = format!(
             "{}/2010-04-01/Accounts/{}/Messages.json",
             self.base_url, self.account_sid
         );
 
-        let payload = SendMessagePayload {
-            from: format!("whatsapp:{}", self.phone_number),
-            to: to.to_string(),
-            body: body.to_string(),
-        };
+        params.push(("From", whatsapp_address(&self.phone_number)));
+        params.push(("To", whatsapp_address(to)));
 
         let response = self
             .client
             .post(&url)
             .basic_auth(&self.account_sid, Some(&self.auth_token))
-            .form(&payload)
+            .form(&params)
             .send()
             .await?;
 
@@ -89,28 +155,47 @@ impl TwilioClient {
         Ok(result.sid)
     }
 
-    /// Verify webhook signature
-    pub fn verify_signature(&self, url: &str, params: &str, signature: &str) -> bool {
-        use hmac::{Hmac, Mac};
-        use sha2::Sha256;
-
-        type HmacSha256 = Hmac<Sha256>;
-
-        let mut mac = match HmacSha256::new_from_slice(self.auth_token.as_bytes()) {
-            Ok(m) => m,
-            Err(_) => return false,
-        };
+    /// Download incoming media (media URLs require the account credentials)
+    pub async fn download_media(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(url)
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .send()
+            .await?;
 
-        let data = format!("{}{}", url, params);
-        mac.update(data.as_bytes());
+        if !response.status().is_success() {
+            return Err(WhatsAppError::Api(format!(
+                "Failed to download media: {}",
+                response.status()
+            )));
+        }
 
-        let expected = mac.finalize().into_bytes();
-        let expected_hex = hex::encode(expected);
+        Ok(response.bytes().await?.to_vec())
+    }
 
-        expected_hex == signature
+    /// Check the `X-Twilio-Signature` header of a webhook
+    ///
+    /// `url` is the webhook URL exactly as configured in the Twilio
+    /// console and `body` the form-encoded request body.
+    pub fn verify_signature(&self, headers: &HeaderMap, url: &str, body: &[u8]) -> bool {
+        let verifier = WebhookVerifier::Twilio {
+            auth_token: self.auth_token.clone(),
+        };
+        verifier.verify(headers, url, body).is_ok()
     }
 }
 
+/// `ContentVariables` JSON: `{"1": "...", "2": "..."}`
+fn content_variables(variables: &[String]) -> String {
+    let map: serde_json::Map<String, serde_json::Value> = variables
+        .iter()
+        .enumerate()
+        .map(|(i, v)| ((i + 1).to_string(), serde_json::Value::String(v.clone())))
+        .collect();
+    serde_json::Value::Object(map).to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +209,50 @@ mod tests {
         );
         assert_eq!(client.account_sid, "AC123");
     }
+
+    #[test]
+    fn test_verify_signature() {
+        // Example from Twilio's webhook security documentation
+        let client = TwilioClient::new("AC123".to_string(), "12345".to_string(), "+1234567890".to_string());
+        let url = "https://mycompany.com/myapp.php?foo=1&bar=2";
+        let body = b"CallSid=CA1234567890ABCDE&Caller=%2B12349013030&Digits=1234&From=%2B12349013030&To=%2B18005551212";
+        let mut signed = HeaderMap::new();
+        signed.insert("x-twilio-signature", "0/KCTR6DLpKmkAf8muzZqo1nDgQ=".parse().unwrap());
+        assert!(client.verify_signature(&signed, url, body));
+        assert!(!client.verify_signature(&signed, url, b"Digits=0000"));
+        assert!(!client.verify_signature(&HeaderMap::new(), url, body));
+    }
+
+    #[test]
+    fn test_incoming_media() {
+        let form = "From=whatsapp%3A%2B15551234567&To=whatsapp%3A%2B15550001111&Body=&MessageSid=MM1&AccountSid=AC1\
+                    &NumMedia=2&MediaUrl0=https%3A%2F%2Fapi.twilio.com%2Fm0&MediaContentType0=image%2Fjpeg\
+                    &MediaUrl1=https%3A%2F%2Fapi.twilio.com%2Fm1&MediaContentType1=audio%2Fogg";
+        let form: String = form.split_whitespace().collect();
+        let msg: IncomingMessage = serde_urlencoded::from_str(&form).unwrap();
+        assert_eq!(msg.from, "whatsapp:+15551234567");
+        assert_eq!(
+            msg.media(),
+            vec![
+                TwilioMedia {
+                    url: "https://api.twilio.com/m0".to_string(),
+                    content_type: "image/jpeg".to_string(),
+                },
+                TwilioMedia {
+                    url: "https://api.twilio.com/m1".to_string(),
+                    content_type: "audio/ogg".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_addresses_and_variables() {
+        assert_eq!(whatsapp_address("+15551234567"), "whatsapp:+15551234567");
+        assert_eq!(whatsapp_address("whatsapp:+15551234567"), "whatsapp:+15551234567");
+        assert_eq!(
+            content_variables(&["Alice".to_string(), "3pm".to_string()]),
+            r#"{"1":"Alice","2":"3pm"}"#
+        );
+    }
 }
@@ -1,28 +1,55 @@
-//! Webhook server for receiving WhatsApp messages from Twilio
-
+//! Webhook server for receiving WhatsApp messages
+//!
+//! Twilio posts form-encoded messages signed with the auth token
+//! (`X-Twilio-Signature`); the Cloud API verifies the webhook with a GET
+//! request and posts JSON signed with the app secret (`X-Hub-Signature-256`).
+//! Requests with an invalid signature are rejected.
+//!
+//! Attached images go to the model, text documents are read into the
+//! prompt, and voice and audio messages are transcribed with Whisper
+//! (cc-voice) when a transcriber is configured.
+
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Form, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
-    routing::post,
+    routing::{get, post},
     Router,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+use cc_core::ImageSource;
+use cc_voice::WhisperClient;
 
+use crate::cloud::{CloudApiClient, CloudMessage};
 use crate::error::{Result, WhatsAppError};
+use crate::provider::{is_allowed, WhatsAppProvider};
 use crate::session::InMemorySessionStore;
 use crate::twilio::{IncomingMessage, TwilioClient};
 
+/// Largest image passed to the model
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Path of the webhook endpoint
+const WEBHOOK_PATH: &str = "/webhook/whatsapp";
+
 /// Webhook server state
 #[derive(Clone)]
 pub struct WebhookState {
-    pub twilio_client: Arc<TwilioClient>,
+    pub provider: WhatsAppProvider,
     pub session_store: Arc<InMemorySessionStore>,
     pub claude_client: Arc<cc_core::ClaudeClient>,
     pub admin_numbers: Vec<String>,
+    /// Transcribes voice and audio messages (unset: they are not read)
+    pub transcriber: Option<Arc<WhisperClient>>,
+    /// Public webhook URL Twilio signs (unset: derived from the request's
+    /// `Host` and `X-Forwarded-Proto` headers)
+    pub public_url: Option<String>,
 }
 
 /// Webhook server
@@ -35,28 +62,53 @@ impl WebhookServer {
     /// Create a new webhook server
     pub fn new(
         addr: SocketAddr,
-        twilio_client: Arc<TwilioClient>,
+        provider: WhatsAppProvider,
         claude_client: Arc<cc_core::ClaudeClient>,
         admin_numbers: Vec<String>,
     ) -> Self {
         let session_store = Arc::new(InMemorySessionStore::new());
 
         let state = WebhookState {
-            twilio_client,
+            provider,
             session_store,
             claude_client,
             admin_numbers,
+            transcriber: None,
+            public_url: None,
         };
 
         Self { addr, state }
     }
 
+    /// Transcribe voice and audio messages with `transcriber`
+    pub fn with_transcriber(mut self, transcriber: Arc<WhisperClient>) -> Self {
+        self.state.transcriber = Some(transcriber);
+        self
+    }
+
+    /// Set the public webhook URL as configured in the Twilio console
+    ///
+    /// Twilio signs the URL it posts to; behind a proxy that rewrites the
+    /// host or path it cannot be derived from the request.
+    pub fn with_public_url(mut self, url: impl Into<String>) -> Self {
+        self.state.public_url = Some(url.into());
+        self
+    }
+
     /// Start the webhook server
     pub async fn start(self) -> Result<()> {
-        info!("Starting WhatsApp webhook server on {}", self.addr);
-
+        info!(
+            "Starting WhatsApp webhook server ({}) on {}",
+            self.state.provider.name(),
+            self.addr
+        );
+
+        let route = match self.state.provider {
+            WhatsAppProvider::Twilio(_) => post(handle_webhook),
+            WhatsAppProvider::Cloud(_) => get(verify_cloud_webhook).post(handle_cloud_webhook),
+        };
         let app = Router::new()
-            .route("/webhook/whatsapp", post(handle_webhook))
+            .route(WEBHOOK_PATH, route)
             .with_state(Arc::new(self.state));
 
         let listener = tokio::net::TcpListener::bind(self.addr)
@@ -71,28 +123,289 @@ impl WebhookServer {
     }
 }
 
-/// Handle incoming WhatsApp webhook
+/// Handle incoming WhatsApp webhook (Twilio)
 async fn handle_webhook(
     State(state): State<Arc<WebhookState>>,
-    Form(msg): Form<IncomingMessage>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> impl IntoResponse {
-    info!("Received WhatsApp message from {}: {}", msg.from, msg.body);
+    let WhatsAppProvider::Twilio(ref client) = state.provider else {
+        return (StatusCode::NOT_FOUND, "");
+    };
+
+    let url = state
+        .public_url
+        .clone()
+        .unwrap_or_else(|| request_url(&headers));
+    if !client.verify_signature(&headers, &url, &body) {
+        warn!("Rejected WhatsApp webhook with invalid signature (URL {})", url);
+        return (StatusCode::FORBIDDEN, "Invalid signature");
+    }
+
+    let msg: IncomingMessage = match serde_urlencoded::from_bytes(&body) {
+        Ok(msg) => msg,
+        Err(e) => {
+            warn!("Invalid WhatsApp webhook payload: {}", e);
+            return (StatusCode::BAD_REQUEST, "");
+        }
+    };
+
+    let media = msg.media();
+    info!(
+        "Received WhatsApp message from {} ({} media): {}",
+        msg.from,
+        media.len(),
+        msg.body
+    );
 
     // Check admin permission
-    if !state.admin_numbers.is_empty() && !state.admin_numbers.contains(&msg.from) {
+    if !is_allowed(&state.admin_numbers, &msg.from) {
         return (StatusCode::FORBIDDEN, "Unauthorized");
     }
 
-    let body = msg.body.trim();
-    if body.is_empty() {
+    if media.is_empty() {
+        handle_message(&state, &msg.from, msg.body.trim(), Vec::new()).await;
         return (StatusCode::OK, "");
     }
 
+    // Downloading and transcribing can outlast Twilio's webhook timeout
+    let client = Arc::clone(client);
+    let state = Arc::clone(&state);
+    tokio::spawn(async move {
+        let input = twilio_message_input(&client, &msg, state.transcriber.as_deref()).await;
+        handle_message(&state, &msg.from, input.text.trim(), input.images).await;
+    });
+    (StatusCode::OK, "")
+}
+
+/// Webhook URL as seen by the client, from the `Host` and
+/// `X-Forwarded-Proto` headers
+fn request_url(headers: &HeaderMap) -> String {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let scheme = header("x-forwarded-proto").unwrap_or("https");
+    let host = header("x-forwarded-host").or_else(|| header("host")).unwrap_or_default();
+    format!("{}://{}{}", scheme, host, WEBHOOK_PATH)
+}
+
+/// Answer the Cloud API verification request
+async fn verify_cloud_webhook(
+    State(state): State<Arc<WebhookState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let WhatsAppProvider::Cloud(ref client) = state.provider else {
+        return (StatusCode::NOT_FOUND, String::new());
+    };
+    let param = |name: &str| params.get(name).map(String::as_str).unwrap_or_default();
+    match client.verify_webhook(param("hub.mode"), param("hub.verify_token"), param("hub.challenge")) {
+        Ok(challenge) => (StatusCode::OK, challenge),
+        Err(_) => (StatusCode::FORBIDDEN, String::new()),
+    }
+}
+
+/// Handle incoming Cloud API webhook
+///
+/// Meta retries webhooks that are not answered quickly, so messages are
+/// processed after responding.
+async fn handle_cloud_webhook(
+    State(state): State<Arc<WebhookState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let WhatsAppProvider::Cloud(ref client) = state.provider else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    if let Err(e) = client.verify_signature(&headers, &body) {
+        warn!("Rejected WhatsApp webhook: {}", e);
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let messages = match client.parse_webhook(&body) {
+        Ok(messages) => messages,
+        Err(e) => {
+            warn!("Invalid WhatsApp webhook payload: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    for message in messages {
+        info!("Received WhatsApp {} message from {}", message.kind, message.from);
+        if !is_allowed(&state.admin_numbers, &message.from) {
+            warn!("Ignoring message from unauthorized number {}", message.from);
+            continue;
+        }
+
+        let state = Arc::clone(&state);
+        let client = Arc::clone(client);
+        tokio::spawn(async move {
+            let input = cloud_message_input(&client, &message, state.transcriber.as_deref()).await;
+            handle_message(&state, &message.from, input.text.trim(), input.images).await;
+        });
+    }
+
+    StatusCode::OK
+}
+
+/// Text and images of an incoming message
+#[derive(Debug, Default)]
+struct MessageInput {
+    text: String,
+    images: Vec<ImageSource>,
+}
+
+impl MessageInput {
+    fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Append a note (or document) to the text
+    fn append(&mut self, note: &str) {
+        if !self.text.trim().is_empty() {
+            self.text.push_str("\n\n");
+        }
+        self.text.push_str(note);
+    }
+
+    /// Add a downloaded attachment
+    ///
+    /// Images go to the model, audio is transcribed and other files are
+    /// read as text documents.
+    async fn add_attachment(
+        &mut self,
+        download: Result<Vec<u8>>,
+        mime_type: &str,
+        name: &str,
+        transcriber: Option<&WhisperClient>,
+    ) {
+        let bytes = match download {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!("Failed to download media {}: {}", name, e);
+                self.append("[The attached file could not be downloaded]");
+                return;
+            }
+        };
+
+        if mime_type.starts_with("image/") {
+            if bytes.len() > MAX_IMAGE_BYTES {
+                self.append("[The attached image is too large (5MB max)]");
+            } else {
+                self.images.push(ImageSource::from_bytes(mime_type, &bytes));
+            }
+        } else if mime_type.starts_with("audio/") {
+            let Some(transcriber) = transcriber else {
+                self.append("[Voice messages are not supported (transcription is not configured)]");
+                return;
+            };
+            match transcriber.transcribe_text(&bytes, name).await {
+                Ok(transcript) if !transcript.trim().is_empty() => {
+                    // A voice message without text is the question itself
+                    if self.text.trim().is_empty() {
+                        self.text = transcript.trim().to_string();
+                    } else {
+                        self.append(&format!("[Voice message]\n{}", transcript.trim()));
+                    }
+                }
+                Ok(_) => self.append("[No speech was recognized in the voice message]"),
+                Err(e) => {
+                    warn!("Failed to transcribe {}: {}", name, e);
+                    self.append("[The voice message could not be transcribed]");
+                }
+            }
+        } else if let Some(document) = cc_core::extract_text(name, Some(mime_type), &bytes) {
+            self.append(&document.to_prompt());
+        } else {
+            self.append(&format!("[Attached file {} is not a supported text format]", name));
+        }
+    }
+}
+
+/// MIME type without parameters (`audio/ogg; codecs=opus` -> `audio/ogg`)
+fn essence(mime_type: &str) -> &str {
+    mime_type.split(';').next().unwrap_or_default().trim()
+}
+
+/// File name for an attachment sent without one, e.g. `audio.ogg`
+///
+/// Whisper and the document extractor use the extension.
+fn attachment_name(mime_type: &str) -> String {
+    let (kind, subtype) = essence(mime_type).split_once('/').unwrap_or(("file", "bin"));
+    let extension = match subtype {
+        "plain" => "txt",
+        "mpeg" => "mp3",
+        "markdown" => "md",
+        other => other,
+    };
+    let stem = match kind {
+        "image" | "audio" => kind,
+        _ => "document",
+    };
+    format!("{}.{}", stem, extension)
+}
+
+/// The text and images of a Twilio message with media
+async fn twilio_message_input(
+    client: &TwilioClient,
+    msg: &IncomingMessage,
+    transcriber: Option<&WhisperClient>,
+) -> MessageInput {
+    let mut input = MessageInput::new(&msg.body);
+    for media in msg.media() {
+        let mime_type = essence(&media.content_type);
+        if mime_type.starts_with("video/") {
+            input.append("[Video messages are not supported]");
+            continue;
+        }
+        let download = client.download_media(&media.url).await;
+        input
+            .add_attachment(download, mime_type, &attachment_name(mime_type), transcriber)
+            .await;
+    }
+    input
+}
+
+/// The text and images of a Cloud API message
+async fn cloud_message_input(
+    client: &CloudApiClient,
+    message: &CloudMessage,
+    transcriber: Option<&WhisperClient>,
+) -> MessageInput {
+    let mut input = MessageInput::new(message.text());
+
+    let Some(media) = message.media() else {
+        return input;
+    };
+    let mime_type = essence(media.mime_type.as_deref().unwrap_or_default());
+
+    match message.kind.as_str() {
+        "image" | "document" | "audio" => {
+            let name = media
+                .filename
+                .clone()
+                .unwrap_or_else(|| attachment_name(mime_type));
+            let download = client.download_media(&media.id).await.map(|(bytes, _)| bytes);
+            input.add_attachment(download, mime_type, &name, transcriber).await;
+        }
+        kind => input.append(&format!("[{} messages are not supported]", kind)),
+    }
+
+    input
+}
+
+/// Answer a message (command or question) from `from`
+async fn handle_message(state: &WebhookState, from: &str, body: &str, images: Vec<ImageSource>) {
+    if body.is_empty() && images.is_empty() {
+        return;
+    }
+
     // Handle commands
     if body.starts_with('/') {
-        match handle_command(&state, &msg.from, body).await {
+        match handle_command(state, from, body).await {
             Ok(response) => {
-                if let Err(e) = state.twilio_client.send_message(&msg.from, &response).await {
+                if let Err(e) = state.provider.send_text(from, &response).await {
                     error!("Failed to send response: {}", e);
                 }
             }
@@ -100,26 +413,24 @@ async fn handle_webhook(
                 error!("Error handling command: {}", e);
             }
         }
-        return (StatusCode::OK, "");
+        return;
     }
 
     // Regular message - process with Claude
-    match process_with_claude(&state, &msg.from, body).await {
+    match process_with_claude(state, from, body, images).await {
         Ok(response) => {
-            if let Err(e) = state.twilio_client.send_message(&msg.from, &response).await {
+            if let Err(e) = state.provider.send_text(from, &response).await {
                 error!("Failed to send response: {}", e);
             }
         }
         Err(e) => {
             error!("Error processing message: {}", e);
             let _ = state
-                .twilio_client
-                .send_message(&msg.from, &format!("Error: {}", e))
+                .provider
+                .send_text(from, &format!("Error: {}", e))
                 .await;
         }
     }
-
-    (StatusCode::OK, "")
 }
 
 /// Handle slash commands
@@ -148,12 +459,25 @@ powered by cc-gateway"#
 }
 
 /// Process message with Claude
-async fn process_with_claude(state: &WebhookState, from: &str, body: &str) -> Result<String> {
+async fn process_with_claude(
+    state: &WebhookState,
+    from: &str,
+    body: &str,
+    images: Vec<ImageSource>,
+) -> Result<String> {
     let session = state.session_store.get_or_create(from).await;
 
+    // Images are sent once and only noted in the history
+    let image_count = images.len();
+    let body = if body.is_empty() { "Describe this image." } else { body };
+
     // Build message history
     let mut messages = session.messages.clone();
-    messages.push(cc_core::Message::user(body));
+    messages.push(if images.is_empty() {
+        cc_core::Message::user(body)
+    } else {
+        cc_core::Message::user_with_images(body, images)
+    });
 
     // Build request
     let mut request_builder = state
@@ -189,9 +513,14 @@ async fn process_with_claude(state: &WebhookState, from: &str, body: &str) -> Re
         .join("\n");
 
     // Update session
+    let history_text = if image_count == 0 {
+        body.to_string()
+    } else {
+        format!("{}\n[{} image(s) attached]", body, image_count)
+    };
     state
         .session_store
-        .add_message(from, cc_core::Message::user(body))
+        .add_message(from, cc_core::Message::user(history_text))
         .await;
     state
         .session_store
@@ -199,9 +528,58 @@ async fn process_with_claude(state: &WebhookState, from: &str, body: &str) -> Re
         .await;
 
     // Truncate for WhatsApp (character limit)
-    if text.len() > 4000 {
-        Ok(format!("{}...(truncated)", &text[..4000]))
+    if text.chars().count() > 4000 {
+        Ok(format!("{}...(truncated)", text.chars().take(4000).collect::<String>()))
     } else {
         Ok(text)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_request_url() {
+        let mut headers = HeaderMap::new();
+        headers.insert("host", HeaderValue::from_static("bot.example.com"));
+        assert_eq!(request_url(&headers), "https://bot.example.com/webhook/whatsapp");
+
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("http"));
+        headers.insert("x-forwarded-host", HeaderValue::from_static("public.example.com"));
+        assert_eq!(request_url(&headers), "http://public.example.com/webhook/whatsapp");
+    }
+
+    #[test]
+    fn test_attachment_name() {
+        assert_eq!(attachment_name("audio/ogg; codecs=opus"), "audio.ogg");
+        assert_eq!(attachment_name("audio/mpeg"), "audio.mp3");
+        assert_eq!(attachment_name("image/jpeg"), "image.jpeg");
+        assert_eq!(attachment_name("text/plain"), "document.txt");
+        assert_eq!(attachment_name("application/pdf"), "document.pdf");
+    }
+
+    #[tokio::test]
+    async fn test_add_attachment() {
+        let mut input = MessageInput::new("");
+        input
+            .add_attachment(Ok(b"hello".to_vec()), "audio/ogg", "audio.ogg", None)
+            .await;
+        assert!(input.text.contains("transcription is not configured"));
+
+        let mut input = MessageInput::new("Summarize this");
+        input
+            .add_attachment(Ok(b"meeting notes".to_vec()), "text/plain", "document.txt", None)
+            .await;
+        assert!(input.text.starts_with("Summarize this\n\n"));
+        assert!(input.text.contains("meeting notes"));
+
+        let mut input = MessageInput::new("");
+        input
+            .add_attachment(Ok(vec![0xff, 0xd8]), "image/jpeg", "image.jpeg", None)
+            .await;
+        assert_eq!(input.images.len(), 1);
+        assert!(input.text.is_empty());
+    }
+}
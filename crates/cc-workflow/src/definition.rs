@@ -0,0 +1,332 @@
+//! Workflow definitions (YAML)
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{Result, WorkflowError};
+
+/// Default iteration limit for `loop` steps
+const DEFAULT_MAX_ITERATIONS: usize = 10;
+
+/// A multi-step workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workflow {
+    /// Unique workflow name
+    pub name: String,
+
+    /// Human readable description
+    #[serde(default)]
+    pub description: String,
+
+    /// Input variables with their default values
+    #[serde(default)]
+    pub inputs: Map<String, Value>,
+
+    /// Steps executed in order
+    pub steps: Vec<Step>,
+
+    /// Final output template (defaults to the output of the last step)
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+/// A single workflow step
+///
+/// The step output is stored as a variable named after `id`,
+/// so later steps can reference it as `{{id}}` (or `{{id.field}}` for JSON output).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    /// Step ID (unique within the workflow)
+    pub id: String,
+
+    /// Keep running the workflow if this step fails
+    #[serde(default)]
+    pub continue_on_error: bool,
+
+    #[serde(flatten)]
+    pub kind: StepKind,
+}
+
+/// Step types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StepKind {
+    /// Single LLM call (no tools)
+    Prompt {
+        prompt: String,
+        #[serde(default)]
+        system: Option<String>,
+        #[serde(default)]
+        model: Option<String>,
+        #[serde(default)]
+        max_tokens: Option<u64>,
+        #[serde(default)]
+        temperature: Option<f32>,
+    },
+
+    /// Direct tool call; string values in `input` are rendered as templates
+    Tool {
+        tool: String,
+        #[serde(default)]
+        input: Value,
+    },
+
+    /// Sub-agent task with its own tool loop
+    Agent {
+        instruction: String,
+        /// Sub-agent name (best matching agent if omitted)
+        #[serde(default)]
+        agent: Option<String>,
+        /// Tools available to the agent (all tools if empty)
+        #[serde(default)]
+        tools: Vec<String>,
+        #[serde(default)]
+        max_iterations: Option<usize>,
+    },
+
+    /// Branch on a condition
+    Condition {
+        #[serde(rename = "if")]
+        condition: Condition,
+        #[serde(default)]
+        then: Vec<Step>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<Step>,
+    },
+
+    /// Repeat steps for each item of a list, or while a condition holds
+    Loop {
+        /// List to iterate: a variable holding a JSON array, or newline separated text
+        #[serde(default)]
+        over: Option<String>,
+        /// Repeat while this condition is true (checked before each iteration)
+        #[serde(default, rename = "while")]
+        while_condition: Option<Condition>,
+        /// Variable name for the current item (default: `item`)
+        #[serde(default = "default_item_var", rename = "as")]
+        item_var: String,
+        #[serde(default = "default_max_iterations")]
+        max_iterations: usize,
+        steps: Vec<Step>,
+    },
+}
+
+impl StepKind {
+    /// Step type name
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Prompt { .. } => "prompt",
+            Self::Tool { .. } => "tool",
+            Self::Agent { .. } => "agent",
+            Self::Condition { .. } => "condition",
+            Self::Loop { .. } => "loop",
+        }
+    }
+
+    /// Nested steps (for conditions and loops)
+    fn children(&self) -> Vec<&Step> {
+        match self {
+            Self::Condition { then, otherwise, .. } => then.iter().chain(otherwise).collect(),
+            Self::Loop { steps, .. } => steps.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn default_item_var() -> String {
+    "item".to_string()
+}
+
+fn default_max_iterations() -> usize {
+    DEFAULT_MAX_ITERATIONS
+}
+
+/// Condition for `condition` and `loop` steps
+///
+/// `value` is rendered as a template. Without `equals` / `contains` the
+/// condition is true when the value is non-empty and not `false`, `0`, `no` or `null`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Condition {
+    /// Truthiness of a template
+    Value(String),
+    /// Comparison
+    Compare {
+        value: String,
+        #[serde(default)]
+        equals: Option<String>,
+        #[serde(default)]
+        contains: Option<String>,
+        /// Negate the result
+        #[serde(default)]
+        not: bool,
+    },
+}
+
+impl Workflow {
+    /// Parse a workflow from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let workflow: Workflow = serde_yaml::from_str(yaml)?;
+        workflow.validate()?;
+        Ok(workflow)
+    }
+
+    /// Load a workflow from a YAML file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_yaml(&content)
+    }
+
+    /// Check that step IDs are unique and do not shadow inputs
+    pub fn validate(&self) -> Result<()> {
+        if self.steps.is_empty() {
+            return Err(self.invalid("no steps defined"));
+        }
+
+        let mut seen = HashSet::new();
+        let mut stack: Vec<&Step> = self.steps.iter().collect();
+        while let Some(step) = stack.pop() {
+            if step.id.is_empty() || step.id.contains('.') {
+                return Err(self.invalid(format!("invalid step id '{}'", step.id)));
+            }
+            if self.inputs.contains_key(&step.id) {
+                return Err(self.invalid(format!("step id '{}' shadows an input", step.id)));
+            }
+            if !seen.insert(step.id.as_str()) {
+                return Err(self.invalid(format!("duplicate step id '{}'", step.id)));
+            }
+            if let StepKind::Loop {
+                over: None,
+                while_condition: None,
+                ..
+            } = &step.kind
+            {
+                return Err(self.invalid(format!(
+                    "loop '{}' needs either 'over' or 'while'",
+                    step.id
+                )));
+            }
+            stack.extend(step.kind.children());
+        }
+        Ok(())
+    }
+
+    fn invalid(&self, message: impl Into<String>) -> WorkflowError {
+        WorkflowError::Invalid {
+            workflow: self.name.clone(),
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+name: digest
+description: Daily digest
+inputs:
+  topic: rust
+steps:
+  - id: search
+    type: tool
+    tool: web_search
+    input:
+      query: "{{topic}} news"
+  - id: summary
+    type: prompt
+    prompt: "Summarize: {{search}}"
+  - id: check
+    type: condition
+    if:
+      value: "{{summary}}"
+      contains: security
+    then:
+      - id: alert
+        type: agent
+        instruction: "Investigate {{summary}}"
+  - id: each
+    type: loop
+    over: "{{search.items}}"
+    as: entry
+    steps:
+      - id: note
+        type: prompt
+        prompt: "{{entry}}"
+output: "{{summary}}"
+"#;
+
+    #[test]
+    fn test_parse_workflow() {
+        let workflow = Workflow::from_yaml(YAML).unwrap();
+        assert_eq!(workflow.name, "digest");
+        assert_eq!(workflow.inputs["topic"], "rust");
+        assert_eq!(workflow.steps.len(), 4);
+        assert_eq!(workflow.steps[0].kind.type_name(), "tool");
+
+        match &workflow.steps[2].kind {
+            StepKind::Condition {
+                condition: Condition::Compare { contains, .. },
+                then,
+                otherwise,
+            } => {
+                assert_eq!(contains.as_deref(), Some("security"));
+                assert_eq!(then.len(), 1);
+                assert!(otherwise.is_empty());
+            }
+            other => panic!("unexpected step: {:?}", other),
+        }
+        match &workflow.steps[3].kind {
+            StepKind::Loop {
+                item_var,
+                max_iterations,
+                ..
+            } => {
+                assert_eq!(item_var, "entry");
+                assert_eq!(*max_iterations, DEFAULT_MAX_ITERATIONS);
+            }
+            other => panic!("unexpected step: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicates() {
+        let yaml = r#"
+name: dup
+steps:
+  - id: a
+    type: prompt
+    prompt: x
+  - id: check
+    type: condition
+    if: "{{a}}"
+    then:
+      - id: a
+        type: prompt
+        prompt: y
+"#;
+        assert!(matches!(
+            Workflow::from_yaml(yaml),
+            Err(WorkflowError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_loop_needs_source() {
+        let yaml = r#"
+name: bad-loop
+steps:
+  - id: l
+    type: loop
+    steps:
+      - id: x
+        type: prompt
+        prompt: x
+"#;
+        assert!(Workflow::from_yaml(yaml).is_err());
+    }
+}
@@ -0,0 +1,561 @@
+//! Workflow execution
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+
+use cc_core::llm::MessagesRequest;
+use cc_core::{ClaudeClient, Message, MessageContent, SubAgentManager, SubAgentTask, ToolManager};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::{debug, info, warn};
+
+use crate::definition::{Step, StepKind, Workflow};
+use crate::error::{Result, WorkflowError};
+use crate::registry::WorkflowRegistry;
+use crate::template;
+
+/// Upper bound on executed steps per run (guards against runaway loops)
+const MAX_STEPS: usize = 500;
+
+/// Upper bound on iterations of a single loop step
+const MAX_LOOP_ITERATIONS: usize = 100;
+
+/// Default max_tokens for prompt steps
+const DEFAULT_MAX_TOKENS: u64 = 4096;
+
+/// Result of a workflow run
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowRun {
+    pub workflow: String,
+    pub success: bool,
+    /// Final output
+    pub output: String,
+    /// Error message if the run failed
+    pub error: Option<String>,
+    /// Executed steps in start order (steps inside loops appear once per iteration)
+    pub steps: Vec<StepRecord>,
+    /// Variables at the end of the run (inputs and step outputs)
+    pub variables: Value,
+    pub duration_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Result of a single step
+#[derive(Debug, Clone, Serialize)]
+pub struct StepRecord {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub success: bool,
+    pub output: String,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Mutable state of a running workflow
+struct RunState {
+    vars: Map<String, Value>,
+    records: Vec<StepRecord>,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+type StepFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Executes workflows
+pub struct WorkflowEngine {
+    client: ClaudeClient,
+    tool_manager: Arc<ToolManager>,
+    agents: Option<Arc<SubAgentManager>>,
+    registry: WorkflowRegistry,
+}
+
+impl WorkflowEngine {
+    /// Create a new engine
+    pub fn new(client: ClaudeClient, tool_manager: Arc<ToolManager>) -> Self {
+        Self {
+            client,
+            tool_manager,
+            agents: None,
+            registry: WorkflowRegistry::new(),
+        }
+    }
+
+    /// Tools available to `tool` and `agent` steps
+    pub fn tool_manager(&self) -> &ToolManager {
+        &self.tool_manager
+    }
+
+    /// Set the sub-agents used by `agent` steps
+    pub fn with_agents(mut self, agents: Arc<SubAgentManager>) -> Self {
+        self.agents = Some(agents);
+        self
+    }
+
+    /// Set the named workflows
+    pub fn with_registry(mut self, registry: WorkflowRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Named workflows
+    pub fn registry(&self) -> &WorkflowRegistry {
+        &self.registry
+    }
+
+    /// Run a registered workflow by name
+    pub async fn run_named(&self, name: &str, inputs: Map<String, Value>) -> Result<WorkflowRun> {
+        let workflow = self
+            .registry
+            .get(name)
+            .ok_or_else(|| WorkflowError::NotFound(name.to_string()))?;
+        Ok(self.run(workflow, inputs).await)
+    }
+
+    /// Run a workflow
+    ///
+    /// `inputs` override the workflow's default input values.
+    /// Step failures are reported in the returned [`WorkflowRun`].
+    pub async fn run(&self, workflow: &Workflow, inputs: Map<String, Value>) -> WorkflowRun {
+        let started = Instant::now();
+        info!(workflow = %workflow.name, "Running workflow");
+
+        let mut vars = workflow.inputs.clone();
+        vars.extend(inputs);
+        let mut state = RunState {
+            vars,
+            records: Vec::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+        };
+
+        let result = self.execute_steps(&workflow.steps, &mut state).await;
+        let result = result.and_then(|last| match &workflow.output {
+            Some(output) => template::render("output", output, &Value::Object(state.vars.clone())),
+            None => Ok(last.as_ref().map(value_to_string).unwrap_or_default()),
+        });
+
+        let (success, output, error) = match result {
+            Ok(output) => (true, output, None),
+            Err(e) => {
+                warn!(workflow = %workflow.name, "Workflow failed: {}", e);
+                (false, String::new(), Some(e.to_string()))
+            }
+        };
+
+        WorkflowRun {
+            workflow: workflow.name.clone(),
+            success,
+            output,
+            error,
+            steps: state.records,
+            variables: Value::Object(state.vars),
+            duration_ms: started.elapsed().as_millis() as u64,
+            input_tokens: state.input_tokens,
+            output_tokens: state.output_tokens,
+        }
+    }
+
+    /// Run steps in order and return the output of the last one
+    fn execute_steps<'a>(
+        &'a self,
+        steps: &'a [Step],
+        state: &'a mut RunState,
+    ) -> StepFuture<'a, Option<Value>> {
+        Box::pin(async move {
+            let mut last = None;
+            for step in steps {
+                last = Some(self.execute_step(step, state).await?);
+            }
+            Ok(last)
+        })
+    }
+
+    async fn execute_step(&self, step: &Step, state: &mut RunState) -> Result<Value> {
+        if state.records.len() >= MAX_STEPS {
+            return Err(WorkflowError::StepFailed {
+                step: step.id.clone(),
+                message: format!("step limit ({}) exceeded", MAX_STEPS),
+            });
+        }
+
+        debug!(step = %step.id, kind = step.kind.type_name(), "Executing step");
+        let started = Instant::now();
+        let index = state.records.len();
+        state.records.push(StepRecord {
+            id: step.id.clone(),
+            kind: step.kind.type_name().to_string(),
+            success: false,
+            output: String::new(),
+            error: None,
+            duration_ms: 0,
+        });
+
+        let result = self.execute_kind(step, state).await;
+        let record = &mut state.records[index];
+        record.duration_ms = started.elapsed().as_millis() as u64;
+
+        let value = match result {
+            Ok(value) => {
+                record.success = true;
+                record.output = value_to_string(&value);
+                value
+            }
+            Err(e) => {
+                let message = match e {
+                    WorkflowError::StepFailed { step: ref id, ref message } if *id == step.id => {
+                        message.clone()
+                    }
+                    ref other => other.to_string(),
+                };
+                record.error = Some(message.clone());
+                if !step.continue_on_error {
+                    return Err(WorkflowError::StepFailed {
+                        step: step.id.clone(),
+                        message,
+                    });
+                }
+                Value::Null
+            }
+        };
+
+        state.vars.insert(step.id.clone(), value.clone());
+        Ok(value)
+    }
+
+    async fn execute_kind(&self, step: &Step, state: &mut RunState) -> Result<Value> {
+        let vars = Value::Object(state.vars.clone());
+        let failed = |message: String| WorkflowError::StepFailed {
+            step: step.id.clone(),
+            message,
+        };
+
+        match &step.kind {
+            StepKind::Prompt {
+                prompt,
+                system,
+                model,
+                max_tokens,
+                temperature,
+            } => {
+                let prompt = template::render(&step.id, prompt, &vars)?;
+                let system = system
+                    .as_deref()
+                    .map(|s| template::render(&step.id, s, &vars))
+                    .transpose()?;
+                let request = MessagesRequest {
+                    model: model.clone().unwrap_or_else(|| self.client.model().to_string()),
+                    max_tokens: max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+                    system,
+                    messages: vec![Message::user(&prompt)],
+                    tools: None,
+                    thinking: None,
+                    tool_choice: None,
+                    temperature: *temperature,
+                };
+                let response = self.client.messages(request).await?;
+                let usage = response.usage.unwrap_or_default();
+                state.input_tokens += usage.input_tokens as u64;
+                state.output_tokens += usage.output_tokens as u64;
+
+                let text = response
+                    .content
+                    .iter()
+                    .filter_map(|c| match c {
+                        MessageContent::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(template::output_value(&text))
+            }
+
+            StepKind::Tool { tool, input } => {
+                let input = template::render_value(&step.id, input, &vars)?;
+                let result = self.tool_manager.execute(tool, input).await?;
+                if result.is_error {
+                    return Err(failed(result.output));
+                }
+                Ok(template::output_value(&result.output))
+            }
+
+            StepKind::Agent {
+                instruction,
+                agent,
+                tools,
+                max_iterations,
+            } => {
+                let agents = self
+                    .agents
+                    .as_ref()
+                    .ok_or_else(|| failed("no sub-agents configured".to_string()))?;
+                let instruction = template::render(&step.id, instruction, &vars)?;
+                let mut task = SubAgentTask::new(instruction);
+                if !tools.is_empty() {
+                    task = task.with_tools(
+                        self.tool_manager
+                            .definitions()
+                            .into_iter()
+                            .filter(|t| tools.contains(&t.name))
+                            .collect(),
+                    );
+                }
+                if let Some(max_iterations) = max_iterations {
+                    task = task.with_max_iterations(*max_iterations);
+                }
+
+                let result = match agent {
+                    Some(name) => {
+                        let agent = agents
+                            .get_by_name(name)
+                            .ok_or_else(|| failed(format!("sub-agent '{}' not found", name)))?;
+                        agents.execute_tracked(&agent, task).await?
+                    }
+                    None => agents.execute_with_best_agent(task).await?,
+                };
+                state.input_tokens += result.input_tokens;
+                state.output_tokens += result.output_tokens;
+                if !result.success {
+                    return Err(failed(
+                        result.error.unwrap_or_else(|| "sub-agent failed".to_string()),
+                    ));
+                }
+                Ok(template::output_value(&result.output))
+            }
+
+            StepKind::Condition {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let matched = template::evaluate(&step.id, condition, &vars)?;
+                let branch = if matched { then } else { otherwise };
+                let last = self.execute_steps(branch, state).await?;
+                Ok(last.unwrap_or(Value::Bool(matched)))
+            }
+
+            StepKind::Loop {
+                over,
+                while_condition,
+                item_var,
+                max_iterations,
+                steps,
+            } => {
+                let limit = (*max_iterations).min(MAX_LOOP_ITERATIONS);
+                let mut outputs = Vec::new();
+
+                if let Some(over) = over {
+                    let items = template::items(&step.id, over, &vars)?;
+                    if items.len() > limit {
+                        warn!(step = %step.id, "Loop truncated to {} of {} items", limit, items.len());
+                    }
+                    for item in items.into_iter().take(limit) {
+                        state.vars.insert(item_var.clone(), item);
+                        let last = self.execute_steps(steps, state).await?;
+                        outputs.push(last.unwrap_or(Value::Null));
+                    }
+                } else if let Some(condition) = while_condition {
+                    for index in 0..limit {
+                        let vars = Value::Object(state.vars.clone());
+                        if !template::evaluate(&step.id, condition, &vars)? {
+                            break;
+                        }
+                        state.vars.insert(item_var.clone(), Value::from(index));
+                        let last = self.execute_steps(steps, state).await?;
+                        outputs.push(last.unwrap_or(Value::Null));
+                    }
+                }
+                state.vars.remove(item_var);
+                Ok(Value::Array(outputs))
+            }
+        }
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use cc_core::{Tool, ToolResult};
+    use serde_json::json;
+
+    /// Returns `text` as-is, or fails when `fail` is set
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+
+        async fn execute(&self, input: Value) -> cc_core::Result<ToolResult> {
+            if input["fail"].as_bool().unwrap_or(false) {
+                return Ok(ToolResult::error("boom"));
+            }
+            Ok(ToolResult::success(match &input["text"] {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            }))
+        }
+    }
+
+    fn test_engine() -> WorkflowEngine {
+        let path = std::env::temp_dir().join(format!("cc-workflow-test-{}.toml", std::process::id()));
+        std::fs::write(&path, "[llm]\napi_key = \"test-key\"\n").unwrap();
+        let config = cc_core::Config::from_toml_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let mut tools = ToolManager::new();
+        tools.register(Arc::new(EchoTool));
+        WorkflowEngine::new(ClaudeClient::new(&config).unwrap(), Arc::new(tools))
+    }
+
+    #[tokio::test]
+    async fn test_variables_condition_and_loop() {
+        let workflow = Workflow::from_yaml(
+            r#"
+name: pipeline
+inputs:
+  city: Tokyo
+steps:
+  - id: fetch
+    type: tool
+    tool: echo
+    input:
+      text: '{"status": "ok", "items": ["a", "b", "c"]}'
+  - id: branch
+    type: condition
+    if:
+      value: "{{fetch.status}}"
+      equals: ok
+    then:
+      - id: greet
+        type: tool
+        tool: echo
+        input:
+          text: "hello {{city}}"
+    else:
+      - id: never
+        type: tool
+        tool: echo
+        input:
+          fail: true
+  - id: each
+    type: loop
+    over: "{{fetch.items}}"
+    steps:
+      - id: shout
+        type: tool
+        tool: echo
+        input:
+          text: "{{item}}!"
+output: "{{greet}} / {{#each each}}{{this}}{{/each}}"
+"#,
+        )
+        .unwrap();
+
+        let engine = test_engine();
+        let run = engine
+            .run(&workflow, Map::from_iter([("city".to_string(), json!("Osaka"))]))
+            .await;
+
+        assert!(run.success, "{:?}", run.error);
+        assert_eq!(run.output, "hello Osaka / a!b!c!");
+        assert_eq!(run.variables["each"], json!(["a!", "b!", "c!"]));
+        assert!(run.steps.iter().all(|s| s.id != "never"));
+        // fetch, branch, greet, each, shout x3
+        assert_eq!(run.steps.len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_step_failure() {
+        let workflow = Workflow::from_yaml(
+            r#"
+name: failing
+steps:
+  - id: optional
+    type: tool
+    tool: echo
+    continue_on_error: true
+    input:
+      fail: true
+  - id: required
+    type: tool
+    tool: echo
+    input:
+      fail: true
+  - id: unreachable
+    type: tool
+    tool: echo
+    input:
+      text: x
+"#,
+        )
+        .unwrap();
+
+        let run = test_engine().run(&workflow, Map::new()).await;
+        assert!(!run.success);
+        assert!(run.error.unwrap().contains("required"));
+        assert_eq!(run.steps.len(), 2);
+        assert!(!run.steps[0].success);
+        assert_eq!(run.steps[0].error.as_deref(), Some("boom"));
+    }
+
+    #[tokio::test]
+    async fn test_while_loop_and_missing_agent() {
+        let workflow = Workflow::from_yaml(
+            r#"
+name: counting
+inputs:
+  go: "yes"
+steps:
+  - id: repeat
+    type: loop
+    while: "{{go}}"
+    max_iterations: 3
+    as: i
+    steps:
+      - id: tick
+        type: tool
+        tool: echo
+        input:
+          text: "{{i}}"
+  - id: delegate
+    type: agent
+    continue_on_error: true
+    instruction: "do something"
+"#,
+        )
+        .unwrap();
+
+        let engine = test_engine();
+        let run = engine.run(&workflow, Map::new()).await;
+        assert!(run.success);
+        assert_eq!(run.variables["repeat"], json!(["0", "1", "2"]));
+        assert!(!run.steps.last().unwrap().success);
+
+        assert!(matches!(
+            engine.run_named("missing", Map::new()).await,
+            Err(WorkflowError::NotFound(_))
+        ));
+    }
+}
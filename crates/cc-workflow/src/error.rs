@@ -0,0 +1,28 @@
+//! Error types for cc-workflow
+
+use thiserror::Error;
+
+/// cc-workflow error type
+#[derive(Error, Debug)]
+pub enum WorkflowError {
+    #[error("YAML parsing error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid workflow '{workflow}': {message}")]
+    Invalid { workflow: String, message: String },
+
+    #[error("Workflow not found: {0}")]
+    NotFound(String),
+
+    #[error("Step '{step}' failed: {message}")]
+    StepFailed { step: String, message: String },
+
+    #[error("Core error: {0}")]
+    Core(#[from] cc_core::Error),
+}
+
+/// Result type alias
+pub type Result<T> = std::result::Result<T, WorkflowError>;
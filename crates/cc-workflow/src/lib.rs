@@ -0,0 +1,49 @@
+//! ワークフローモジュール
+//!
+//! YAML で定義した複数ステップのパイプラインを実行します。
+//! ステップの出力はステップ ID の変数として後続ステップから参照できます。
+//!
+//! ```yaml
+//! name: daily-digest
+//! description: ニュースを検索して要約する
+//! inputs:
+//!   topic: rust
+//! steps:
+//!   - id: search
+//!     type: tool            # ツールを直接呼び出す
+//!     tool: web_search
+//!     input:
+//!       query: "{{topic}} news"
+//!   - id: summary
+//!     type: prompt          # LLM に 1 回問い合わせる
+//!     prompt: "次の検索結果を 3 行で要約してください:\n{{search}}"
+//!   - id: check
+//!     type: condition       # 条件分岐
+//!     if:
+//!       value: "{{summary}}"
+//!       contains: security
+//!     then:
+//!       - id: investigate
+//!         type: agent       # サブエージェントに委譲（ツールループあり）
+//!         instruction: "セキュリティ関連の話題を詳しく調査: {{summary}}"
+//!   - id: translate
+//!     type: loop            # 配列・改行区切りテキストの各要素、または while 条件で繰り返し
+//!     over: "{{search.results}}"
+//!     as: result
+//!     steps:
+//!       - id: ja
+//!         type: prompt
+//!         prompt: "日本語に翻訳: {{result.title}}"
+//! output: "{{summary}}"
+//! ```
+
+mod definition;
+mod engine;
+mod error;
+mod registry;
+mod template;
+
+pub use definition::{Condition, Step, StepKind, Workflow};
+pub use engine::{StepRecord, WorkflowEngine, WorkflowRun};
+pub use error::{Result, WorkflowError};
+pub use registry::WorkflowRegistry;
@@ -0,0 +1,101 @@
+//! Workflow registry
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use tracing::{info, warn};
+
+use crate::definition::Workflow;
+use crate::error::Result;
+
+/// Named workflows loaded from YAML files
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowRegistry {
+    workflows: BTreeMap<String, Workflow>,
+}
+
+impl WorkflowRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load every `*.yaml` / `*.yml` file in a directory
+    ///
+    /// A missing directory yields an empty registry; invalid files are skipped with a warning.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut registry = Self::new();
+        let dir = dir.as_ref();
+        if !dir.is_dir() {
+            return Ok(registry);
+        }
+
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(|e| e.to_str()),
+                    Some("yaml" | "yml")
+                )
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match Workflow::from_file(&path) {
+                Ok(workflow) => registry.insert(workflow),
+                Err(e) => warn!("Skipping workflow {}: {}", path.display(), e),
+            }
+        }
+        info!("Loaded {} workflow(s) from {}", registry.len(), dir.display());
+        Ok(registry)
+    }
+
+    /// Add or replace a workflow
+    pub fn insert(&mut self, workflow: Workflow) {
+        self.workflows.insert(workflow.name.clone(), workflow);
+    }
+
+    /// Get a workflow by name
+    pub fn get(&self, name: &str) -> Option<&Workflow> {
+        self.workflows.get(name)
+    }
+
+    /// All workflows (sorted by name)
+    pub fn list(&self) -> Vec<&Workflow> {
+        self.workflows.values().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.workflows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workflows.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("cc-workflows-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("hello.yaml"),
+            "name: hello\nsteps:\n  - id: greet\n    type: prompt\n    prompt: hi\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.yml"), "name: broken\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let registry = WorkflowRegistry::load_dir(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(registry.len(), 1);
+        assert!(registry.get("hello").is_some());
+        assert!(WorkflowRegistry::load_dir(dir.join("missing")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+//! Variable expansion for workflow steps
+
+use cc_core::PromptTemplate;
+use serde_json::Value;
+
+use crate::definition::Condition;
+use crate::error::{Result, WorkflowError};
+
+/// Render a template string against the workflow variables
+pub fn render(step: &str, source: &str, vars: &Value) -> Result<String> {
+    if !source.contains("{{") {
+        return Ok(source.to_string());
+    }
+    let template = PromptTemplate::parse(step, source).map_err(|e| WorkflowError::StepFailed {
+        step: step.to_string(),
+        message: e.to_string(),
+    })?;
+    Ok(template.render(vars))
+}
+
+/// Render every string inside a JSON value (used for tool inputs)
+///
+/// A string that consists of a single `{{path}}` is replaced by the variable
+/// itself, so arrays and objects keep their type.
+pub fn render_value(step: &str, value: &Value, vars: &Value) -> Result<Value> {
+    Ok(match value {
+        Value::String(s) => match single_var(s).and_then(|path| lookup(vars, path)) {
+            Some(v) => v.clone(),
+            None => Value::String(render(step, s, vars)?),
+        },
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|v| render_value(step, v, vars))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), render_value(step, v, vars)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Items for a `loop` step's `over`
+///
+/// Uses a JSON array variable directly; otherwise the rendered text is parsed
+/// as a JSON array or split into non-empty lines.
+pub fn items(step: &str, over: &str, vars: &Value) -> Result<Vec<Value>> {
+    if let Some(Value::Array(items)) = single_var(over).and_then(|path| lookup(vars, path)) {
+        return Ok(items.clone());
+    }
+    let text = render(step, over, vars)?;
+    if let Ok(Value::Array(items)) = serde_json::from_str(text.trim()) {
+        return Ok(items);
+    }
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| Value::String(line.to_string()))
+        .collect())
+}
+
+/// Evaluate a condition
+pub fn evaluate(step: &str, condition: &Condition, vars: &Value) -> Result<bool> {
+    match condition {
+        Condition::Value(value) => Ok(is_truthy(&render(step, value, vars)?)),
+        Condition::Compare {
+            value,
+            equals,
+            contains,
+            not,
+        } => {
+            let value = render(step, value, vars)?;
+            let value = value.trim();
+            let result = match (equals, contains) {
+                (Some(expected), _) => value == render(step, expected, vars)?.trim(),
+                (None, Some(needle)) => value.contains(render(step, needle, vars)?.trim()),
+                (None, None) => is_truthy(value),
+            };
+            Ok(result != *not)
+        }
+    }
+}
+
+/// Convert step output to a variable (JSON if it parses as an object or array)
+pub fn output_value(output: &str) -> Value {
+    match serde_json::from_str::<Value>(output.trim()) {
+        Ok(value @ (Value::Object(_) | Value::Array(_))) => value,
+        _ => Value::String(output.to_string()),
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    !matches!(
+        value.trim().to_lowercase().as_str(),
+        "" | "false" | "0" | "no" | "null" | "[]" | "{}"
+    )
+}
+
+/// `{{path}}` only (no other text or block helpers)
+fn single_var(source: &str) -> Option<&str> {
+    let inner = source.trim().strip_prefix("{{")?.strip_suffix("}}")?.trim();
+    if inner.is_empty() || inner.contains("{{") || inner.starts_with(['#', '/', '!']) {
+        return None;
+    }
+    Some(inner)
+}
+
+fn lookup<'a>(vars: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(vars, |value, key| match value {
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => value.get(key),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_value_keeps_types() {
+        let vars = json!({"list": [1, 2], "user": {"name": "taro"}});
+        let input = json!({"items": "{{list}}", "greeting": "hi {{user.name}}", "n": 3});
+        assert_eq!(
+            render_value("s", &input, &vars).unwrap(),
+            json!({"items": [1, 2], "greeting": "hi taro", "n": 3})
+        );
+    }
+
+    #[test]
+    fn test_items() {
+        let vars = json!({"list": ["a", "b"], "text": "x\n\ny\n", "raw": "[1, 2, 3]"});
+        assert_eq!(items("s", "{{list}}", &vars).unwrap().len(), 2);
+        assert_eq!(items("s", "{{text}}", &vars).unwrap(), vec![json!("x"), json!("y")]);
+        assert_eq!(items("s", "{{raw}}", &vars).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let vars = json!({"status": "OK", "empty": "", "text": "found a security issue"});
+        let cond = |c: Value| serde_json::from_value::<Condition>(c).unwrap();
+
+        assert!(evaluate("s", &cond(json!("{{status}}")), &vars).unwrap());
+        assert!(!evaluate("s", &cond(json!("{{empty}}")), &vars).unwrap());
+        assert!(evaluate("s", &cond(json!({"value": "{{status}}", "equals": "OK"})), &vars).unwrap());
+        assert!(
+            evaluate("s", &cond(json!({"value": "{{text}}", "contains": "security"})), &vars)
+                .unwrap()
+        );
+        assert!(
+            !evaluate("s", &cond(json!({"value": "{{status}}", "equals": "OK", "not": true})), &vars)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_output_value() {
+        assert_eq!(output_value("{\"a\": 1}"), json!({"a": 1}));
+        assert_eq!(output_value("42"), json!("42"));
+    }
+}
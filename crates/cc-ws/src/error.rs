@@ -17,6 +17,9 @@ pub enum WsError {
     #[error("Core error: {0}")]
     Core(#[from] cc_core::Error),
 
+    #[error("Voice error: {0}")]
+    Voice(#[from] cc_voice::VoiceError),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -3,75 +3,283 @@
 //! Handles WebSocket connections and message routing.
 
 use axum::{
+    body::Bytes,
     extract::{
-        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
-        State,
+        ws::{close_code, CloseFrame, Message as WsMessage, WebSocket, WebSocketUpgrade},
+        Query, State,
     },
-    response::Response,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use cc_core::llm::{Message, MessageContent, MessagesRequest, ToolDefinition};
+use cc_core::webhook::tokens_match;
+use cc_core::Session;
 
 use crate::message::{ClientMessage, ImageData, ServerMessage, TokenUsage};
-use crate::session::WsSession;
-use crate::server::WsState;
-use crate::Result;
+use crate::room::{ClientRole, Member};
+use crate::session::{Outgoing, WsSession};
+use crate::server::{WsAuthenticator, WsState};
+use crate::voice::audio_frames;
+use crate::{Result, WsError};
+
+/// Query parameters of the upgrade request
+#[derive(Debug, Default, Deserialize)]
+pub struct ConnectParams {
+    /// Auth token (browsers cannot set headers on WebSocket requests)
+    pub token: Option<String>,
+    /// Persisted session to resume (or join, when other clients use it)
+    pub session_id: Option<String>,
+    /// `observer` to follow the session without sending messages
+    #[serde(default)]
+    pub role: ClientRole,
+}
 
 /// Handle WebSocket upgrade request
+///
+/// The token is read from `Authorization: Bearer` or the `token` query
+/// parameter; it is the shared token, or an API key or OIDC token when an
+/// [`WsAuthenticator`] is set. Connections resume the session given as
+/// `session_id` when they own it, or get a new one. Every client connected
+/// to a session receives its messages.
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<WsState>>,
+    Query(params): Query<ConnectParams>,
+    headers: HeaderMap,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    let caller = authenticate(
+        state.settings.auth_token.as_deref(),
+        state.authenticator.as_deref(),
+        &headers,
+        params.token.as_deref(),
+    )
+    .await;
+    let Some(caller) = caller else {
+        warn!("Rejected WebSocket connection: missing or invalid token");
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    let session = match bind_session(&state, &caller, params.session_id.as_deref()).await {
+        Ok(session) => session,
+        Err(status) => return status.into_response(),
+    };
+    let role = params.role;
+    ws.on_upgrade(move |socket| handle_socket(socket, state, caller, session, role))
+}
+
+/// Who opened a connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Caller {
+    /// The shared token, or anyone when no authentication is configured
+    Trusted,
+    /// An API key or OIDC user
+    User(String),
+}
+
+impl Caller {
+    /// Prefix of the channel ids of the caller's sessions
+    fn channel_prefix(&self) -> String {
+        match self {
+            Self::Trusted => "ws:".to_string(),
+            // Escaped so one user's prefix never starts another's
+            Self::User(id) => format!("ws:{}:", id.replace('%', "%25").replace(':', "%3A")),
+        }
+    }
+
+    /// Whether a session of `channel_id` belongs to the caller
+    fn owns(&self, channel_id: &str) -> bool {
+        channel_id.starts_with(&self.channel_prefix())
+    }
+
+    /// Whether the caller may watch sub-agent progress
+    ///
+    /// Progress events are not tied to a session and carry task
+    /// instructions, so only trusted callers receive them.
+    fn sees_agent_events(&self) -> bool {
+        matches!(self, Self::Trusted)
+    }
+}
+
+/// Who the request's token identifies (None when it is missing or invalid)
+///
+/// Anyone is trusted when neither a shared token nor an enabled
+/// authenticator is configured.
+async fn authenticate(
+    expected: Option<&str>,
+    authenticator: Option<&dyn WsAuthenticator>,
+    headers: &HeaderMap,
+    query_token: Option<&str>,
+) -> Option<Caller> {
+    let authenticator = authenticator.filter(|a| a.is_enabled());
+    if expected.is_none() && authenticator.is_none() {
+        return Some(Caller::Trusted);
+    }
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or(query_token)?;
+    if expected.is_some_and(|expected| tokens_match(expected, provided)) {
+        return Some(Caller::Trusted);
+    }
+    authenticator?.authenticate(provided).await.map(Caller::User)
+}
+
+/// The persisted session a connection is bound to
+///
+/// Only WebSocket sessions of the caller can be resumed, so a token cannot
+/// open the conversations of other channels or users.
+async fn bind_session(
+    state: &WsState,
+    caller: &Caller,
+    session_id: Option<&str>,
+) -> std::result::Result<Session, StatusCode> {
+    let result = match session_id {
+        Some(id) => match state.session_manager.get_session(id).await {
+            Ok(Some(session)) if caller.owns(&session.channel_id) => Ok(session),
+            Ok(Some(_)) => {
+                warn!("WebSocket connection for a session it does not own: {}", id);
+                return Err(StatusCode::NOT_FOUND);
+            }
+            Ok(None) => {
+                warn!("WebSocket connection for unknown session: {}", id);
+                return Err(StatusCode::NOT_FOUND);
+            }
+            Err(e) => Err(e),
+        },
+        None => {
+            let channel_id = format!("{}{}", caller.channel_prefix(), uuid::Uuid::new_v4());
+            state.session_manager.get_or_create(&channel_id).await
+        }
+    };
+    result.map_err(|e| {
+        error!("Failed to bind WebSocket session: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
 }
 
 /// Handle established WebSocket connection
-async fn handle_socket(socket: WebSocket, state: Arc<WsState>) {
-    let session_id = uuid::Uuid::new_v4().to_string();
-    info!("New WebSocket connection: {}", session_id);
+async fn handle_socket(
+    socket: WebSocket,
+    state: Arc<WsState>,
+    caller: Caller,
+    bound: Session,
+    role: ClientRole,
+) {
+    let session_id = bound.id.clone();
+    let member = Member {
+        client_id: uuid::Uuid::new_v4().to_string(),
+        role,
+    };
+    info!(
+        "New WebSocket connection: {} (channel {}, client {}, {:?})",
+        session_id, bound.channel_id, member.client_id, role
+    );
 
     // Split socket into sender and receiver
     let (ws_tx, mut ws_rx) = socket.split();
     let ws_tx = Arc::new(tokio::sync::Mutex::new(ws_tx));
 
     // Create channel for outgoing messages
-    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    let (tx, mut rx) = mpsc::unbounded_channel::<Outgoing>();
+
+    // Send initial session info
+    let mut members = state.rooms.members(&session_id);
+    members.push(member.clone());
+    let init_msg = ServerMessage::SessionInfo {
+        session_id: session_id.clone(),
+        client_id: member.client_id.clone(),
+        message_count: bound.message_count(),
+        members,
+    };
+    if let Err(e) = tx.send(serde_json::to_string(&init_msg).unwrap().into()) {
+        error!("Failed to send initial message: {}", e);
+        return;
+    }
+
+    // Join the session's room and forward its messages to this client
+    let (room_tx, mut room_rx) = state.rooms.join(&session_id, member.clone());
+    let room_task = {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            loop {
+                let frame = match room_rx.recv().await {
+                    Ok(frame) => frame,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Client fell behind; skipped {} session messages", skipped);
+                        continue;
+                    }
+                    Err(_) => break,
+                };
+                if tx.send(frame).is_err() {
+                    break;
+                }
+            }
+        })
+    };
 
     // Create WebSocket session
     let session = Arc::new(tokio::sync::Mutex::new(WsSession::new(
         session_id.clone(),
+        bound.channel_id.clone(),
+        member.clone(),
         tx.clone(),
+        room_tx,
         state.broadcast_tx.clone(),
         state.claude_client.clone(),
         state.session_manager.clone(),
         state.tool_manager.clone(),
     )));
 
-    // Send initial session info
-    let init_msg = ServerMessage::SessionInfo {
-        session_id: session_id.clone(),
-        message_count: 0,
-    };
-    if let Err(e) = tx.send(serde_json::to_string(&init_msg).unwrap()) {
-        error!("Failed to send initial message: {}", e);
-        return;
-    }
+    // Forward sub-agent progress to trusted clients
+    let hub = state.agent_events.as_ref().filter(|_| caller.sees_agent_events());
+    let progress_task = hub.map(|hub| {
+        let mut events = hub.subscribe();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("Skipped {} agent progress events", skipped);
+                        continue;
+                    }
+                    Err(_) => break,
+                };
+                let msg = ServerMessage::AgentProgress { event };
+                if tx.send(serde_json::to_string(&msg).unwrap().into()).is_err() {
+                    break;
+                }
+            }
+        })
+    });
 
     // Clone for tasks
     let session_id_send = session_id.clone();
     let session_id_recv = session_id.clone();
     let ws_tx_send = ws_tx.clone();
     let ws_tx_recv = ws_tx.clone();
+    let ws_tx_keepalive = ws_tx.clone();
+    // Any frame from the client (including pongs) counts as activity
+    let last_seen = Arc::new(std::sync::Mutex::new(Instant::now()));
+    let last_seen_recv = last_seen.clone();
 
     // Task to send messages to client
     let send_task = async move {
         while let Some(msg) = rx.recv().await {
+            let frame = match msg {
+                Outgoing::Text(text) => WsMessage::Text(text.into()),
+                Outgoing::Binary(data) => WsMessage::Binary(data.into()),
+            };
             let mut tx = ws_tx_send.lock().await;
-            if tx.send(WsMessage::Text(msg.into())).await.is_err() {
+            if tx.send(frame).await.is_err() {
                 break;
             }
         }
@@ -83,23 +291,19 @@ async fn handle_socket(socket: WebSocket, state: Arc<WsState>) {
     let state_clone = state.clone();
     let recv_task = async move {
         while let Some(msg) = ws_rx.next().await {
-            match msg {
+            *last_seen_recv.lock().unwrap() = Instant::now();
+            let result = match msg {
                 Ok(WsMessage::Text(text)) => {
-                    if let Err(e) = handle_client_message(&text, &session_clone, &state_clone).await
-                    {
-                        error!("Error handling message: {}", e);
-                        let error_msg = ServerMessage::Error {
-                            message: e.to_string(),
-                        };
-                        let _ = session_clone.lock().await.tx.send(
-                            serde_json::to_string(&error_msg).unwrap(),
-                        );
-                    }
+                    handle_client_message(&text, &session_clone, &state_clone).await
+                }
+                Ok(WsMessage::Binary(data)) => {
+                    handle_audio_chunk(&data, &session_clone, &state_clone).await
                 }
                 Ok(WsMessage::Ping(data)) => {
                     debug!("Received ping from session: {}", session_id_recv);
                     let mut tx = ws_tx_recv.lock().await;
                     let _ = tx.send(WsMessage::Pong(data)).await;
+                    Ok(())
                 }
                 Ok(WsMessage::Close(_)) => {
                     info!("Client closed connection: {}", session_id_recv);
@@ -109,17 +313,60 @@ async fn handle_socket(socket: WebSocket, state: Arc<WsState>) {
                     warn!("WebSocket error: {}", e);
                     break;
                 }
-                _ => {}
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                error!("Error handling message: {}", e);
+                let error_msg = ServerMessage::Error {
+                    message: e.to_string(),
+                };
+                let _ = session_clone
+                    .lock()
+                    .await
+                    .tx
+                    .send(serde_json::to_string(&error_msg).unwrap().into());
             }
         }
         debug!("Receive task ended for session: {}", session_id_recv);
     };
 
-    // Run both tasks
+    // Ping the client and close the connection once it goes silent
+    let ping_interval = state.settings.ping_interval;
+    let idle_timeout = state.settings.idle_timeout;
+    let session_id_keepalive = session_id.clone();
+    let keepalive_task = async move {
+        let mut interval = tokio::time::interval(ping_interval);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let mut tx = ws_tx_keepalive.lock().await;
+            if last_seen.lock().unwrap().elapsed() >= idle_timeout {
+                info!("Closing idle WebSocket connection: {}", session_id_keepalive);
+                let _ = tx
+                    .send(WsMessage::Close(Some(CloseFrame {
+                        code: close_code::AWAY,
+                        reason: "idle timeout".into(),
+                    })))
+                    .await;
+                break;
+            }
+            if tx.send(WsMessage::Ping(Bytes::new())).await.is_err() {
+                break;
+            }
+        }
+    };
+
+    // Run until any task ends
     tokio::select! {
         _ = send_task => {},
         _ = recv_task => {},
+        _ = keepalive_task => {},
+    }
+    if let Some(task) = progress_task {
+        task.abort();
     }
+    room_task.abort();
+    state.rooms.leave(&session_id, &member.client_id);
 
     info!("WebSocket connection closed: {}", session_id);
 }
@@ -134,6 +381,11 @@ async fn handle_client_message(
 
     debug!("Received message: {:?}", msg);
 
+    let observer = session.lock().await.member.role == ClientRole::Observer;
+    if observer && !matches!(msg, ClientMessage::SessionInfo | ClientMessage::Ping) {
+        return Err(WsError::Other("Observers cannot send messages".to_string()));
+    }
+
     match msg {
         ClientMessage::Chat { message, image } => {
             handle_chat(session, state, message, image).await?;
@@ -142,28 +394,40 @@ async fn handle_client_message(
             handle_clear(session).await?;
         }
         ClientMessage::SessionInfo => {
-            handle_session_info(session).await?;
+            handle_session_info(session, state).await?;
         }
         ClientMessage::Ping => {
             let session = session.lock().await;
             let pong = ServerMessage::Pong;
-            session.tx.send(serde_json::to_string(&pong).unwrap()).ok();
+            session.tx.send(serde_json::to_string(&pong).unwrap().into()).ok();
+        }
+        ClientMessage::AudioStart { format } => {
+            voice_enabled(state)?;
+            session.lock().await.audio.start(format);
+        }
+        ClientMessage::AudioEnd => {
+            handle_audio_end(session, state).await?;
         }
     }
 
     Ok(())
 }
 
-/// Handle chat message
+/// Handle chat message, returning the reply (None when the API call failed)
 async fn handle_chat(
     session: &Arc<tokio::sync::Mutex<WsSession>>,
     state: &Arc<WsState>,
     text: String,
     image: Option<ImageData>,
-) -> Result<()> {
-    let (_session_id, system_prompt, tx) = {
+) -> Result<Option<String>> {
+    let (system_prompt, tx, room_tx) = {
         let s = session.lock().await;
-        (s.session_id.clone(), s.system_prompt.clone(), s.tx.clone())
+        // Let the other clients in the session see what was asked
+        s.broadcast(&ServerMessage::UserMessage {
+            client_id: s.member.client_id.clone(),
+            message: text.clone(),
+        });
+        (s.system_prompt.clone(), s.tx.clone(), s.room_tx.clone())
     };
 
     // Build message content
@@ -215,6 +479,8 @@ async fn handle_chat(
         messages,
         tools: if tools.is_empty() { None } else { Some(tools) },
         thinking: None,
+        tool_choice: None,
+        temperature: None,
     };
 
     // Send to Claude API
@@ -244,44 +510,110 @@ async fn handle_chat(
 
             // Send response
             let server_msg = ServerMessage::ChatResponse {
-                response: response_text,
+                response: response_text.clone(),
                 tokens_used,
             };
-            tx.send(serde_json::to_string(&server_msg)?).ok();
+            room_tx.send(serde_json::to_string(&server_msg)?.into()).ok();
+            Ok(Some(response_text))
         }
         Err(e) => {
             error!("Claude API error: {}", e);
             let error_msg = ServerMessage::Error {
                 message: format!("Claude API error: {}", e),
             };
-            tx.send(serde_json::to_string(&error_msg)?).ok();
+            tx.send(serde_json::to_string(&error_msg)?.into()).ok();
+            Ok(None)
         }
     }
+}
+
+/// The voice clients, or an error when voice chat is disabled
+fn voice_enabled(state: &WsState) -> Result<&crate::voice::WsVoice> {
+    state
+        .voice
+        .as_ref()
+        .ok_or_else(|| WsError::Other("Voice chat is not enabled".to_string()))
+}
+
+/// Buffer an audio chunk of the current utterance
+async fn handle_audio_chunk(
+    data: &[u8],
+    session: &Arc<tokio::sync::Mutex<WsSession>>,
+    state: &Arc<WsState>,
+) -> Result<()> {
+    voice_enabled(state)?;
+    let mut session = session.lock().await;
+    if session.member.role == ClientRole::Observer {
+        return Err(WsError::Other("Observers cannot send messages".to_string()));
+    }
+    session.audio.push(data)
+}
+
+/// Transcribe the buffered utterance, answer it and speak the reply
+async fn handle_audio_end(
+    session: &Arc<tokio::sync::Mutex<WsSession>>,
+    state: &Arc<WsState>,
+) -> Result<()> {
+    let voice = voice_enabled(state)?;
+    let (utterance, tx, room_tx) = {
+        let mut s = session.lock().await;
+        (s.audio.take(), s.tx.clone(), s.room_tx.clone())
+    };
+    let (filename, audio) =
+        utterance.ok_or_else(|| WsError::Other("No audio received".to_string()))?;
+
+    let text = voice.whisper.transcribe_text(&audio, &filename).await?;
+    let text = text.trim().to_string();
+    let msg = ServerMessage::Transcription { text: text.clone() };
+    tx.send(serde_json::to_string(&msg)?.into()).ok();
+    if text.is_empty() {
+        return Ok(());
+    }
+
+    let Some(response) = handle_chat(session, state, text, None).await? else {
+        return Ok(());
+    };
+    let Some(tts) = &voice.tts else {
+        return Ok(());
+    };
+
+    let speech = tts.synthesize(&response).await?;
+    let msg = ServerMessage::AudioStart {
+        content_type: speech.content_type.clone(),
+        format: speech.format.to_string(),
+        bytes: speech.audio_data.len(),
+    };
+    room_tx.send(serde_json::to_string(&msg)?.into()).ok();
+    for frame in audio_frames(&speech.audio_data) {
+        room_tx.send(Outgoing::Binary(frame)).ok();
+    }
+    room_tx
+        .send(serde_json::to_string(&ServerMessage::AudioEnd)?.into())
+        .ok();
 
     Ok(())
 }
 
 /// Handle clear session
 async fn handle_clear(session: &Arc<tokio::sync::Mutex<WsSession>>) -> Result<()> {
-    let (tx, session_id) = {
-        let s = session.lock().await;
-        s.clear_session().await?;
-        (s.tx.clone(), s.session_id.clone())
-    };
+    let s = session.lock().await;
+    s.clear_session().await?;
 
-    info!("Session cleared: {}", session_id);
+    info!("Session cleared: {}", s.session_id);
 
-    let msg = ServerMessage::SessionCleared;
-    tx.send(serde_json::to_string(&msg)?).ok();
+    s.broadcast(&ServerMessage::SessionCleared);
 
     Ok(())
 }
 
 /// Handle session info request
-async fn handle_session_info(session: &Arc<tokio::sync::Mutex<WsSession>>) -> Result<()> {
-    let (tx, session_id) = {
+async fn handle_session_info(
+    session: &Arc<tokio::sync::Mutex<WsSession>>,
+    state: &Arc<WsState>,
+) -> Result<()> {
+    let (tx, session_id, client_id) = {
         let s = session.lock().await;
-        (s.tx.clone(), s.session_id.clone())
+        (s.tx.clone(), s.session_id.clone(), s.member.client_id.clone())
     };
 
     let messages = {
@@ -290,10 +622,12 @@ async fn handle_session_info(session: &Arc<tokio::sync::Mutex<WsSession>>) -> Re
     };
 
     let msg = ServerMessage::SessionInfo {
+        members: state.rooms.members(&session_id),
         session_id,
+        client_id,
         message_count: messages.len(),
     };
-    tx.send(serde_json::to_string(&msg)?).ok();
+    tx.send(serde_json::to_string(&msg)?.into()).ok();
 
     Ok(())
 }
@@ -315,6 +649,75 @@ mod tests {
         assert!(json.contains("chat_response"));
     }
 
+    /// Knows the API key `key-1` of user `alice`
+    struct Keys;
+
+    #[async_trait::async_trait]
+    impl WsAuthenticator for Keys {
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        async fn authenticate(&self, token: &str) -> Option<String> {
+            (token == "key-1").then(|| "alice".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticate() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(authenticate(None, None, &headers, None).await, Some(Caller::Trusted));
+        assert_eq!(authenticate(Some("secret"), None, &headers, None).await, None);
+        assert_eq!(authenticate(Some("secret"), None, &headers, Some("wrong")).await, None);
+        assert_eq!(
+            authenticate(Some("secret"), None, &headers, Some("secret")).await,
+            Some(Caller::Trusted)
+        );
+        assert_eq!(
+            authenticate(Some("secret"), Some(&Keys), &headers, Some("key-1")).await,
+            Some(Caller::User("alice".to_string()))
+        );
+        assert_eq!(authenticate(None, Some(&Keys), &headers, Some("key-2")).await, None);
+
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert_eq!(
+            authenticate(Some("secret"), None, &headers, None).await,
+            Some(Caller::Trusted)
+        );
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+    }
+
+    #[test]
+    fn test_session_ownership() {
+        let alice = Caller::User("alice".to_string());
+        assert!(alice.owns("ws:alice:0b5c"));
+        assert!(!alice.owns("ws:bob:0b5c"));
+        let other = Caller::User("a:b".to_string()).channel_prefix();
+        assert!(!Caller::User("a".to_string()).owns(&format!("{}0b5c", other)));
+        assert!(!alice.owns("discord:123"));
+
+        assert!(Caller::Trusted.owns("ws:0b5c"));
+        assert!(Caller::Trusted.owns("ws:alice:0b5c"));
+        assert!(!Caller::Trusted.owns("telegram:42"));
+        assert!(!Caller::Trusted.owns("api-session"));
+
+        assert!(Caller::Trusted.sees_agent_events());
+        assert!(!alice.sees_agent_events());
+    }
+
+    #[test]
+    fn test_audio_messages() {
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type":"audio_start","format":"ogg"}"#).unwrap();
+        assert!(matches!(msg, ClientMessage::AudioStart { format: Some(f) } if f == "ogg"));
+        let msg: ClientMessage = serde_json::from_str(r#"{"type":"audio_start"}"#).unwrap();
+        assert!(matches!(msg, ClientMessage::AudioStart { format: None }));
+
+        let json = serde_json::to_string(&ServerMessage::AudioEnd).unwrap();
+        assert_eq!(json, r#"{"type":"audio_end"}"#);
+    }
+
     #[test]
     fn test_client_message_deserialization() {
         let json = r#"{"type":"chat","message":"Hello"}"#;
@@ -6,11 +6,15 @@
 pub mod error;
 pub mod handler;
 pub mod message;
+pub mod room;
 pub mod server;
 pub mod session;
+pub mod voice;
 
 pub use error::{Result, WsError};
 pub use handler::websocket_handler;
-pub use message::{ClientMessage, ServerMessage};
-pub use server::{start_ws_server, WsState};
-pub use session::WsSession;
+pub use message::{ClientMessage, PresenceEvent, ServerMessage};
+pub use room::{ClientRole, Member, Rooms};
+pub use server::{start_ws_server, WsAuthenticator, WsServerBuilder, WsSettings, WsState};
+pub use session::{Outgoing, WsSession};
+pub use voice::WsVoice;
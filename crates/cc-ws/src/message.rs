@@ -2,8 +2,11 @@
 //!
 //! Defines the JSON message format for WebSocket communication.
 
+use cc_core::agents::SubAgentEvent;
 use serde::{Deserialize, Serialize};
 
+use crate::room::Member;
+
 /// Message from client to server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -23,6 +26,16 @@ pub enum ClientMessage {
 
     /// Ping for keepalive
     Ping,
+
+    /// Start a spoken message; audio follows as binary frames
+    AudioStart {
+        /// Container format used as the file extension for Whisper (default: webm)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+    },
+
+    /// End of the spoken message: transcribe and answer it
+    AudioEnd,
 }
 
 /// Message from server to client
@@ -50,7 +63,25 @@ pub enum ServerMessage {
     /// Session information
     SessionInfo {
         session_id: String,
+        /// ID of this connection within the session
+        client_id: String,
         message_count: usize,
+        /// Clients connected to the session
+        members: Vec<Member>,
+    },
+
+    /// Message a client sent to the session (echoed to every member)
+    UserMessage {
+        client_id: String,
+        message: String,
+    },
+
+    /// A client joined or left the session
+    Presence {
+        event: PresenceEvent,
+        member: Member,
+        /// Clients connected after the change
+        members: Vec<Member>,
     },
 
     /// Session cleared notification
@@ -71,6 +102,36 @@ pub enum ServerMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         output: Option<String>,
     },
+
+    /// Sub-agent progress (iteration, tool call, token usage); only sent to
+    /// connections made with the shared token or without authentication
+    AgentProgress {
+        #[serde(flatten)]
+        event: SubAgentEvent,
+    },
+
+    /// Transcription of a spoken message
+    Transcription {
+        text: String,
+    },
+
+    /// Spoken reply; audio follows as binary frames
+    AudioStart {
+        content_type: String,
+        format: String,
+        bytes: usize,
+    },
+
+    /// End of the spoken reply
+    AudioEnd,
+}
+
+/// Kind of presence change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceEvent {
+    Joined,
+    Left,
 }
 
 /// Image data for multimodal input
@@ -143,6 +204,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_agent_progress_message() {
+        use cc_core::agents::{SubAgentEventKind, SubAgentId, TaskId};
+
+        let msg = ServerMessage::AgentProgress {
+            event: SubAgentEvent::new(
+                TaskId::new("t1"),
+                SubAgentId::new("a1"),
+                "coder",
+                SubAgentEventKind::IterationStarted { iteration: 2 },
+            ),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains(r#""type":"agent_progress"#));
+        assert!(json.contains(r#""event":"iteration_started"#));
+
+        let parsed: ServerMessage = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed, ServerMessage::AgentProgress { .. }));
+    }
+
     #[test]
     fn test_image_data_base64() {
         let bytes = b"test image data";
@@ -0,0 +1,161 @@
+//! Shared sessions
+//!
+//! Every connection bound to the same session joins its room. Messages
+//! about the conversation are broadcast to all members, and members are
+//! told when others join or leave.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use crate::message::{PresenceEvent, ServerMessage};
+use crate::session::Outgoing;
+
+/// Capacity of a room's broadcast channel
+const ROOM_CAPACITY: usize = 256;
+
+/// What a client may do in a session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientRole {
+    /// Talks to the assistant
+    #[default]
+    User,
+    /// Only follows the conversation (e.g. a dashboard)
+    Observer,
+}
+
+/// A client connected to a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Member {
+    pub client_id: String,
+    pub role: ClientRole,
+}
+
+struct Room {
+    tx: broadcast::Sender<Outgoing>,
+    members: Vec<Member>,
+}
+
+/// Rooms of the sessions with at least one connection
+#[derive(Default)]
+pub struct Rooms {
+    rooms: Mutex<HashMap<String, Room>>,
+}
+
+impl Rooms {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Join a session's room and announce the new member to everyone in it
+    ///
+    /// Returns the room's sender and a receiver that already sees the announcement.
+    pub fn join(
+        &self,
+        session_id: &str,
+        member: Member,
+    ) -> (broadcast::Sender<Outgoing>, broadcast::Receiver<Outgoing>) {
+        let mut rooms = self.rooms.lock().unwrap();
+        let room = rooms.entry(session_id.to_string()).or_insert_with(|| Room {
+            tx: broadcast::channel(ROOM_CAPACITY).0,
+            members: Vec::new(),
+        });
+        let rx = room.tx.subscribe();
+        room.members.push(member.clone());
+        announce(room, PresenceEvent::Joined, member);
+        (room.tx.clone(), rx)
+    }
+
+    /// Leave a session's room, announcing it to the remaining members
+    pub fn leave(&self, session_id: &str, client_id: &str) {
+        let mut rooms = self.rooms.lock().unwrap();
+        let Some(room) = rooms.get_mut(session_id) else {
+            return;
+        };
+        let Some(index) = room.members.iter().position(|m| m.client_id == client_id) else {
+            return;
+        };
+        let member = room.members.remove(index);
+        if room.members.is_empty() {
+            rooms.remove(session_id);
+        } else {
+            announce(room, PresenceEvent::Left, member);
+        }
+    }
+
+    /// Clients connected to a session
+    pub fn members(&self, session_id: &str) -> Vec<Member> {
+        self.rooms
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map(|room| room.members.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn announce(room: &Room, event: PresenceEvent, member: Member) {
+    let msg = ServerMessage::Presence {
+        event,
+        member,
+        members: room.members.clone(),
+    };
+    if let Ok(json) = serde_json::to_string(&msg) {
+        let _ = room.tx.send(json.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: &str, role: ClientRole) -> Member {
+        Member {
+            client_id: id.to_string(),
+            role,
+        }
+    }
+
+    fn presence(rx: &mut broadcast::Receiver<Outgoing>) -> (PresenceEvent, String, usize) {
+        let Ok(Outgoing::Text(json)) = rx.try_recv() else {
+            panic!("expected a presence message");
+        };
+        match serde_json::from_str(&json).unwrap() {
+            ServerMessage::Presence {
+                event,
+                member,
+                members,
+            } => (event, member.client_id, members.len()),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_join_and_leave() {
+        let rooms = Rooms::new();
+        let (_, mut user_rx) = rooms.join("s1", member("user", ClientRole::User));
+        assert_eq!(presence(&mut user_rx), (PresenceEvent::Joined, "user".to_string(), 1));
+
+        let (tx, mut observer_rx) = rooms.join("s1", member("dash", ClientRole::Observer));
+        assert_eq!(presence(&mut user_rx), (PresenceEvent::Joined, "dash".to_string(), 2));
+        assert_eq!(presence(&mut observer_rx), (PresenceEvent::Joined, "dash".to_string(), 2));
+
+        // Other sessions are separate rooms
+        rooms.join("s2", member("other", ClientRole::User));
+        assert!(user_rx.try_recv().is_err());
+
+        tx.send(Outgoing::Text("hello".to_string())).unwrap();
+        assert_eq!(user_rx.try_recv().unwrap(), Outgoing::Text("hello".to_string()));
+        assert_eq!(observer_rx.try_recv().unwrap(), Outgoing::Text("hello".to_string()));
+
+        rooms.leave("s1", "user");
+        assert_eq!(presence(&mut observer_rx), (PresenceEvent::Left, "user".to_string(), 1));
+        assert_eq!(rooms.members("s1"), vec![member("dash", ClientRole::Observer)]);
+
+        rooms.leave("s1", "dash");
+        assert!(rooms.members("s1").is_empty());
+    }
+}
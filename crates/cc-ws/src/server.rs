@@ -2,12 +2,14 @@
 //!
 //! Starts and manages the axum-based WebSocket server.
 
+use async_trait::async_trait;
 use axum::{
     routing::{get, get_service},
     Router,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
 use tower_http::{
     cors::{Any, CorsLayer},
@@ -15,9 +17,12 @@ use tower_http::{
 };
 use tracing::info;
 
+use cc_core::agents::SubAgentEventHub;
 use cc_core::{ClaudeClient, Config, SessionManager, ToolManager};
 
 use crate::handler::websocket_handler;
+use crate::room::Rooms;
+use crate::voice::WsVoice;
 use crate::Result;
 
 /// Shared WebSocket server state
@@ -35,9 +40,74 @@ pub struct WsState {
     pub default_system_prompt: Option<String>,
     /// Server configuration
     pub config: Config,
+    /// Sub-agent progress events pushed to every connection (optional)
+    pub agent_events: Option<SubAgentEventHub>,
+    /// Authentication and keepalive settings
+    pub settings: WsSettings,
+    /// Speech recognition and synthesis for voice chat (optional)
+    pub voice: Option<WsVoice>,
+    /// Connections of each session
+    pub rooms: Arc<Rooms>,
+    /// Checks issued API keys and OIDC tokens (optional)
+    pub authenticator: Option<Arc<dyn WsAuthenticator>>,
+}
+
+/// Accepts tokens besides [`WsSettings::auth_token`], such as the API keys
+/// and OIDC tokens of the HTTP API
+#[async_trait]
+pub trait WsAuthenticator: Send + Sync {
+    /// Whether clients must present a token (e.g. some keys are issued)
+    fn is_enabled(&self) -> bool;
+
+    /// Who `token` belongs to (an API key id, an OIDC user), None when it is invalid
+    async fn authenticate(&self, token: &str) -> Option<String>;
+}
+
+/// Connection settings of the WebSocket server
+#[derive(Debug, Clone)]
+pub struct WsSettings {
+    /// Token clients must present on upgrade (None = no authentication)
+    pub auth_token: Option<String>,
+    /// Interval of the server's pings
+    pub ping_interval: Duration,
+    /// Connections that send nothing (not even a pong) for this long are closed
+    pub idle_timeout: Duration,
+}
+
+impl Default for WsSettings {
+    fn default() -> Self {
+        Self {
+            auth_token: None,
+            ping_interval: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+impl WsSettings {
+    /// Load from WS_AUTH_TOKEN (falls back to API_KEY), WS_PING_INTERVAL_SECS
+    /// and WS_IDLE_TIMEOUT_SECS
+    pub fn from_env() -> Self {
+        let secs = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+        };
+        let defaults = Self::default();
+        Self {
+            auth_token: std::env::var("WS_AUTH_TOKEN")
+                .or_else(|_| std::env::var("API_KEY"))
+                .ok()
+                .filter(|token| !token.is_empty()),
+            ping_interval: secs("WS_PING_INTERVAL_SECS").unwrap_or(defaults.ping_interval),
+            idle_timeout: secs("WS_IDLE_TIMEOUT_SECS").unwrap_or(defaults.idle_timeout),
+        }
+    }
 }
 
 /// Start the WebSocket server
+#[allow(clippy::too_many_arguments)]
 pub async fn start_ws_server(
     port: u16,
     config: Config,
@@ -45,6 +115,10 @@ pub async fn start_ws_server(
     session_manager: SessionManager,
     tool_manager: Arc<ToolManager>,
     static_dir: Option<&str>,
+    agent_events: Option<SubAgentEventHub>,
+    settings: WsSettings,
+    voice: Option<WsVoice>,
+    authenticator: Option<Arc<dyn WsAuthenticator>>,
 ) -> Result<()> {
     // Create broadcast channel
     let (broadcast_tx, _) = broadcast::channel(256);
@@ -57,8 +131,20 @@ pub async fn start_ws_server(
         broadcast_tx,
         default_system_prompt: None, // Can be set via environment or config
         config: config.clone(),
+        agent_events,
+        settings,
+        voice,
+        rooms: Arc::new(Rooms::new()),
+        authenticator,
     });
 
+    if state.settings.auth_token.is_some() || state.authenticator.is_some() {
+        info!("WebSocket authentication enabled");
+    }
+    if state.voice.is_some() {
+        info!("WebSocket voice chat enabled");
+    }
+
     // Build CORS layer
     let cors_layer = CorsLayer::new()
         .allow_origin(Any)
@@ -101,6 +187,10 @@ pub struct WsServerBuilder {
     port: u16,
     config: Config,
     static_dir: Option<String>,
+    agent_events: Option<SubAgentEventHub>,
+    settings: WsSettings,
+    voice: Option<WsVoice>,
+    authenticator: Option<Arc<dyn WsAuthenticator>>,
 }
 
 impl WsServerBuilder {
@@ -110,6 +200,10 @@ impl WsServerBuilder {
             port: 3001,
             config,
             static_dir: None,
+            agent_events: None,
+            settings: WsSettings::from_env(),
+            voice: WsVoice::from_env(),
+            authenticator: None,
         }
     }
 
@@ -125,6 +219,36 @@ impl WsServerBuilder {
         self
     }
 
+    /// Push sub-agent progress events to the clients
+    pub fn agent_events(mut self, hub: SubAgentEventHub) -> Self {
+        self.agent_events = Some(hub);
+        self
+    }
+
+    /// Set the authentication and keepalive settings
+    pub fn settings(mut self, settings: WsSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Require this token on upgrade
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.settings.auth_token = Some(token.into());
+        self
+    }
+
+    /// Also accept the tokens `authenticator` knows
+    pub fn authenticator(mut self, authenticator: Arc<dyn WsAuthenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Accept spoken messages (and speak replies when TTS is set)
+    pub fn voice(mut self, voice: WsVoice) -> Self {
+        self.voice = Some(voice);
+        self
+    }
+
     /// Build and start the server
     pub async fn start(
         self,
@@ -139,6 +263,10 @@ impl WsServerBuilder {
             session_manager,
             tool_manager,
             self.static_dir.as_deref(),
+            self.agent_events,
+            self.settings,
+            self.voice,
+            self.authenticator,
         )
         .await
     }
@@ -156,6 +284,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test_key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -166,6 +297,12 @@ mod tests {
             memory: MemoryConfig::default(),
             mcp: McpConfig::default(),
             scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
@@ -174,9 +311,11 @@ mod tests {
         let config = create_test_config();
         let builder = WsServerBuilder::new(config)
             .port(8080)
-            .static_dir("./static");
+            .static_dir("./static")
+            .auth_token("secret");
 
         assert_eq!(builder.port, 8080);
+        assert_eq!(builder.settings.auth_token.as_deref(), Some("secret"));
         assert_eq!(builder.static_dir, Some("./static".to_string()));
     }
 }
@@ -8,12 +8,37 @@ use tracing::{debug, info};
 
 use cc_core::{ClaudeClient, SessionManager, ToolManager};
 
+use crate::message::ServerMessage;
+use crate::room::Member;
+use crate::voice::AudioBuffer;
+
+/// Frame queued for a WebSocket client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outgoing {
+    /// JSON message
+    Text(String),
+    /// Audio data
+    Binary(Vec<u8>),
+}
+
+impl From<String> for Outgoing {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
 /// WebSocket session state
 pub struct WsSession {
-    /// Unique session ID (maps to channel_id in SessionManager)
+    /// Persisted session ID (pass as `session_id` to resume the session)
     pub session_id: String,
+    /// Channel ID of the session in SessionManager
+    pub channel_id: String,
+    /// This connection within the session
+    pub member: Member,
     /// Channel to send messages to this WebSocket client
-    pub tx: mpsc::UnboundedSender<String>,
+    pub tx: mpsc::UnboundedSender<Outgoing>,
+    /// Channel to send messages to every client in the session
+    pub room_tx: broadcast::Sender<Outgoing>,
     /// Broadcast channel for server-wide events
     pub broadcast_tx: broadcast::Sender<String>,
     /// Reference to Claude client
@@ -24,13 +49,19 @@ pub struct WsSession {
     pub tool_manager: Arc<ToolManager>,
     /// System prompt for this session
     pub system_prompt: Option<String>,
+    /// Audio of the utterance being received
+    pub audio: AudioBuffer,
 }
 
 impl WsSession {
     /// Create a new WebSocket session
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         session_id: String,
-        tx: mpsc::UnboundedSender<String>,
+        channel_id: String,
+        member: Member,
+        tx: mpsc::UnboundedSender<Outgoing>,
+        room_tx: broadcast::Sender<Outgoing>,
         broadcast_tx: broadcast::Sender<String>,
         claude_client: Arc<ClaudeClient>,
         session_manager: Arc<SessionManager>,
@@ -38,22 +69,36 @@ impl WsSession {
     ) -> Self {
         Self {
             session_id,
+            channel_id,
+            member,
             tx,
+            room_tx,
             broadcast_tx,
             claude_client,
             session_manager,
             tool_manager,
             system_prompt: None,
+            audio: AudioBuffer::default(),
         }
     }
 
     /// Send a message to this client
     pub fn send(&self, message: &str) {
-        if let Err(e) = self.tx.send(message.to_string()) {
+        if let Err(e) = self.tx.send(message.to_string().into()) {
             debug!("Failed to send message to client: {}", e);
         }
     }
 
+    /// Send a message to every client in the session
+    pub fn broadcast(&self, message: &ServerMessage) {
+        match serde_json::to_string(message) {
+            Ok(json) => {
+                let _ = self.room_tx.send(json.into());
+            }
+            Err(e) => debug!("Failed to serialize message: {}", e),
+        }
+    }
+
     /// Set system prompt for this session
     pub fn set_system_prompt(&mut self, prompt: impl Into<String>) {
         self.system_prompt = Some(prompt.into());
@@ -61,22 +106,22 @@ impl WsSession {
 
     /// Get or create the underlying session
     pub async fn get_or_create_session(&self) -> cc_core::Result<cc_core::Session> {
-        self.session_manager.get_or_create(&self.session_id).await
+        self.session_manager.get_or_create(&self.channel_id).await
     }
 
     /// Add a message to session history
     pub async fn add_message(&self, message: cc_core::Message) -> cc_core::Result<()> {
-        self.session_manager.add_message(&self.session_id, message).await
+        self.session_manager.add_message(&self.channel_id, message).await
     }
 
     /// Get session messages
     pub async fn get_messages(&self) -> cc_core::Result<Vec<cc_core::Message>> {
-        self.session_manager.get_messages(&self.session_id).await
+        self.session_manager.get_messages(&self.channel_id).await
     }
 
     /// Clear session history
     pub async fn clear_session(&self) -> cc_core::Result<()> {
-        self.session_manager.clear_messages(&self.session_id).await
+        self.session_manager.clear_messages(&self.channel_id).await
     }
 
     /// Log session event
@@ -98,6 +143,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 provider: LlmProvider::Claude,
                 base_url: None,
+                region: None,
+                project_id: None,
+                retry: Default::default(),
             },
             claude_api_key: "test_key".to_string(),
             claude_model: "claude-sonnet-4-20250514".to_string(),
@@ -108,13 +156,20 @@ mod tests {
             memory: MemoryConfig::default(),
             mcp: McpConfig::default(),
             scheduler: SchedulerConfig::default(),
+            personas: Default::default(),
+            notify: Default::default(),
+            tool_policy: Default::default(),
+            channels: Default::default(),
+            knowledge: Default::default(),
+            tool_limits: Default::default(),
         }
     }
 
     #[tokio::test]
     async fn test_session_send() {
-        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Outgoing>();
         let (broadcast_tx, _) = broadcast::channel(16);
+        let (room_tx, mut room_rx) = broadcast::channel(16);
 
         // Create minimal mock dependencies
         let config = create_test_config();
@@ -124,7 +179,13 @@ mod tests {
 
         let session = WsSession::new(
             "test-session".to_string(),
+            "ws:test".to_string(),
+            Member {
+                client_id: "client-1".to_string(),
+                role: Default::default(),
+            },
             tx,
+            room_tx,
             broadcast_tx,
             claude_client,
             session_manager,
@@ -134,6 +195,10 @@ mod tests {
         session.send("test message");
 
         let received = rx.recv().await.unwrap();
-        assert_eq!(received, "test message");
+        assert_eq!(received, Outgoing::Text("test message".to_string()));
+
+        session.broadcast(&ServerMessage::SessionCleared);
+        let received = room_rx.recv().await.unwrap();
+        assert_eq!(received, Outgoing::Text(r#"{"type":"session_cleared"}"#.to_string()));
     }
 }
@@ -0,0 +1,139 @@
+//! Voice chat over WebSocket
+//!
+//! Clients stream audio as binary frames and finish an utterance with an
+//! `audio_end` message. The buffered audio is transcribed with Whisper,
+//! answered like a chat message, and the reply is synthesized with TTS and
+//! sent back as binary frames.
+
+use std::sync::Arc;
+
+use cc_voice::{TtsClient, TtsConfig, WhisperClient, WhisperConfig};
+
+use crate::{Result, WsError};
+
+/// Largest utterance accepted (the Whisper API upload limit)
+pub const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+
+/// Audio format assumed when the client does not name one (MediaRecorder's default)
+pub const DEFAULT_AUDIO_FORMAT: &str = "webm";
+
+/// Size of the binary frames synthesized audio is sent in
+const AUDIO_FRAME_BYTES: usize = 32 * 1024;
+
+/// Speech recognition and synthesis clients
+#[derive(Clone)]
+pub struct WsVoice {
+    /// Transcribes incoming audio
+    pub whisper: Arc<WhisperClient>,
+    /// Synthesizes replies (None = text replies only)
+    pub tts: Option<Arc<TtsClient>>,
+}
+
+impl WsVoice {
+    /// Voice input only
+    pub fn new(whisper: WhisperClient) -> Self {
+        Self {
+            whisper: Arc::new(whisper),
+            tts: None,
+        }
+    }
+
+    /// Also speak the replies
+    pub fn with_tts(mut self, tts: TtsClient) -> Self {
+        self.tts = Some(Arc::new(tts));
+        self
+    }
+
+    /// OpenAI Whisper and TTS from OPENAI_API_KEY (None when unset)
+    pub fn from_env() -> Option<Self> {
+        let key = std::env::var("OPENAI_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty())?;
+        let whisper = WhisperClient::new(WhisperConfig::openai(&key)).ok()?;
+        let voice = Self::new(whisper);
+        Some(match TtsClient::new(TtsConfig::openai(key)) {
+            Ok(tts) => voice.with_tts(tts),
+            Err(_) => voice,
+        })
+    }
+}
+
+/// Audio received for the current utterance
+#[derive(Debug, Default)]
+pub struct AudioBuffer {
+    format: Option<String>,
+    data: Vec<u8>,
+}
+
+impl AudioBuffer {
+    /// Start a new utterance, discarding anything buffered
+    pub fn start(&mut self, format: Option<String>) {
+        self.format = format;
+        self.data.clear();
+    }
+
+    /// Append a chunk (the utterance is dropped once it exceeds the limit)
+    pub fn push(&mut self, chunk: &[u8]) -> Result<()> {
+        if self.data.len() + chunk.len() > MAX_AUDIO_BYTES {
+            self.data.clear();
+            return Err(WsError::Other(format!(
+                "Audio exceeds {} bytes",
+                MAX_AUDIO_BYTES
+            )));
+        }
+        self.data.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    /// Take the utterance as a file name (for Whisper's format detection) and its bytes
+    pub fn take(&mut self) -> Option<(String, Vec<u8>)> {
+        let format = self.format.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        let filename = format!(
+            "utterance.{}",
+            format.as_deref().unwrap_or(DEFAULT_AUDIO_FORMAT)
+        );
+        Some((filename, std::mem::take(&mut self.data)))
+    }
+}
+
+/// Split synthesized audio into binary frames
+pub fn audio_frames(audio: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    audio.chunks(AUDIO_FRAME_BYTES).map(<[u8]>::to_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_buffer() {
+        let mut buffer = AudioBuffer::default();
+        assert!(buffer.take().is_none());
+
+        buffer.push(b"abc").unwrap();
+        buffer.push(b"def").unwrap();
+        let (filename, data) = buffer.take().unwrap();
+        assert_eq!(filename, "utterance.webm");
+        assert_eq!(data, b"abcdef");
+        assert!(buffer.take().is_none());
+
+        buffer.start(Some("ogg".to_string()));
+        buffer.push(b"x").unwrap();
+        assert_eq!(buffer.take().unwrap().0, "utterance.ogg");
+
+        buffer.push(&vec![0; MAX_AUDIO_BYTES]).unwrap();
+        assert!(buffer.push(b"overflow").is_err());
+        assert!(buffer.take().is_none());
+    }
+
+    #[test]
+    fn test_audio_frames() {
+        let audio = vec![1u8; AUDIO_FRAME_BYTES * 2 + 10];
+        let frames: Vec<_> = audio_frames(&audio).collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].len(), 10);
+    }
+}